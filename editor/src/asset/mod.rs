@@ -234,7 +234,22 @@ fn is_engine_resource(ext: &OsStr) -> bool {
     let ext = ext.to_string_lossy().to_lowercase();
     matches!(
         ext.as_str(),
-        "rgs" | "fbx" | "jpg" | "tga" | "png" | "bmp" | "ogg" | "wav" | "shader"
+        "rgs"
+            | "fbx"
+            | "gltf"
+            | "glb"
+            | "obj"
+            | "stl"
+            | "tmx"
+            | "tmj"
+            | "jpg"
+            | "tga"
+            | "png"
+            | "bmp"
+            | "ogg"
+            | "wav"
+            | "shader"
+            | "spritesheet"
     )
 }
 
@@ -47,7 +47,7 @@ macro_rules! define_command_stack {
                     if top < self.commands.len() {
                         for mut dropped_command in self.commands.drain(top..) {
                             if self.debug {
-                                println!("Finalizing command {:?}", dropped_command);
+                                fyrox::core::log_info!("Finalizing command {:?}", dropped_command);
                             }
                             dropped_command.finalize(&mut context);
                         }
@@ -55,7 +55,7 @@ macro_rules! define_command_stack {
                 }
 
                 if self.debug {
-                    println!("Executing command {:?}", command);
+                    fyrox::core::log_info!("Executing command {:?}", command);
                 }
 
                 command.execute(&mut context);
@@ -68,7 +68,7 @@ macro_rules! define_command_stack {
                     if let Some(top) = self.top.as_mut() {
                         if let Some(command) = self.commands.get_mut(*top) {
                             if self.debug {
-                                println!("Undo command {:?}", command);
+                                fyrox::core::log_info!("Undo command {:?}", command);
                             }
                             command.revert(&mut context)
                         }
@@ -101,7 +101,7 @@ macro_rules! define_command_stack {
 
                     if let Some(command) = command {
                         if self.debug {
-                            println!("Redo command {:?}", command);
+                            fyrox::core::log_info!("Redo command {:?}", command);
                         }
                         command.execute(&mut context)
                     }
@@ -111,7 +111,7 @@ macro_rules! define_command_stack {
             pub fn clear(&mut self, mut context: $context) {
                 for mut dropped_command in self.commands.drain(..) {
                     if self.debug {
-                        println!("Finalizing command {:?}", dropped_command);
+                        fyrox::core::log_info!("Finalizing command {:?}", dropped_command);
                     }
                     dropped_command.finalize(&mut context);
                 }
@@ -30,6 +30,7 @@ use fyrox::{
         Animation, AnimationContainer,
     },
     core::{
+        algebra::{Vector2, Vector3},
         futures::executor::block_on,
         parking_lot::Mutex,
         pool::{ErasedHandle, Handle},
@@ -46,30 +47,37 @@ use fyrox::{
     renderer::framework::state::PolygonFillMode,
     resource::{
         curve::{CurveResource, CurveResourceState},
-        model::{MaterialSearchOptions, Model, ModelResource},
+        model::{
+            MaterialSearchOptions, MeshOptimizationOptions, Model, ModelResource, ModelUpAxis,
+        },
+        spritesheet::{SpriteSheet, SpriteSheetResource},
         texture::{
-            CompressionOptions, MipFilter, TextureMagnificationFilter, TextureMinificationFilter,
-            TextureResource, TextureWrapMode,
+            CompressionOptions, MipFilter, TargetPlatform, TextureMagnificationFilter,
+            TextureMinificationFilter, TextureResource, TextureWrapMode,
         },
     },
     scene::{
         base::{Base, LevelOfDetail, LodGroup, Mobility, Property, PropertyValue},
         camera::{
-            ColorGradingLut, Exposure, OrthographicProjection, PerspectiveProjection, Projection,
-            SkyBox,
+            AsymmetricProjection, ColorGradingLut, Exposure, OrthographicProjection,
+            PerspectiveProjection, Projection, SkyBox,
         },
+        cloth::{ClothCollider, ClothColliderKind},
         collider::{
             BallShape, BitMask, CapsuleShape, ColliderShape, ConeShape, ConvexPolyhedronShape,
             CuboidShape, CylinderShape, GeometrySource, HeightfieldShape, InteractionGroups,
             SegmentShape, TriangleShape, TrimeshShape,
         },
         dim2,
+        foliage::FoliageLayer,
         graph::physics::CoefficientCombineRule,
         joint::*,
         light::{
+            animation::LightAnimation,
             directional::{CsmOptions, FrustumSplitOptions},
             BaseLight,
         },
+        lines::{LineQuad, Polyline},
         mesh::{
             surface::{BlendShape, Surface, SurfaceSharedData},
             RenderPath,
@@ -94,10 +102,12 @@ use fyrox::{
             Status,
         },
         terrain::{Chunk, Layer},
+        trail::{TrailAlignment, TrailTextureMode},
         transform::Transform,
+        water::WaterWave,
     },
 };
-use std::rc::Rc;
+use std::{ops::Range, rc::Rc};
 
 pub mod animation;
 pub mod handle;
@@ -150,6 +160,22 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_vec_collection::<Layer>();
     container.register_inheritable_inspectable::<Layer>();
 
+    container.register_inheritable_vec_collection::<FoliageLayer>();
+    container.register_inheritable_inspectable::<FoliageLayer>();
+
+    container.register_inheritable_vec_collection::<WaterWave>();
+    container.register_inheritable_inspectable::<WaterWave>();
+
+    container.insert(VecCollectionPropertyEditorDefinition::<Vector3<f32>>::new());
+    container.register_inheritable_vec_collection::<Polyline>();
+    container.register_inheritable_inspectable::<Polyline>();
+    container.register_inheritable_vec_collection::<LineQuad>();
+    container.register_inheritable_inspectable::<LineQuad>();
+
+    container.register_inheritable_vec_collection::<u32>();
+    container.register_inheritable_vec_collection::<ClothCollider>();
+    container.register_inheritable_inspectable::<ClothCollider>();
+
     container.register_inheritable_vec_collection::<Emitter>();
 
     container.register_inheritable_vec_collection::<LevelOfDetail>();
@@ -164,6 +190,17 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_vec_collection::<GeometrySource>();
     container.register_inheritable_inspectable::<GeometrySource>();
 
+    container.register_inheritable_inspectable::<dim2::tilemap::TileSet>();
+    container.register_inheritable_vec_collection::<dim2::tilemap::TileDefinition>();
+    container.register_inheritable_inspectable::<dim2::tilemap::TileDefinition>();
+    container.register_inheritable_vec_collection::<dim2::tilemap::TileCollisionShape>();
+    container.register_inheritable_enum::<dim2::tilemap::TileCollisionShape, _>();
+    container.register_inheritable_vec_collection::<dim2::tilemap::TileAnimationFrame>();
+    container.register_inheritable_inspectable::<dim2::tilemap::TileAnimationFrame>();
+    container.register_inheritable_vec_collection::<dim2::tilemap::TileMapLayer>();
+    container.register_inheritable_inspectable::<dim2::tilemap::TileMapLayer>();
+    container.register_inheritable_vec_collection::<Vector2<f32>>();
+
     container.insert(make_status_enum_editor_definition());
 
     container.insert(EnumPropertyEditorDefinition::<LodGroup>::new_optional());
@@ -212,6 +249,17 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(InheritablePropertyEditorDefinition::<Option<CurveResource>>::new());
     container.register_inheritable_vec_collection::<Option<CurveResource>>();
 
+    container.insert(ResourceFieldPropertyEditorDefinition::<SpriteSheet>::new(
+        Rc::new(|resource_manager, path| {
+            block_on(resource_manager.request::<SpriteSheet, _>(path))
+        }),
+        sender.clone(),
+    ));
+    container.insert(InheritablePropertyEditorDefinition::<
+        Option<SpriteSheetResource>,
+    >::new());
+    container.register_inheritable_vec_collection::<Option<SpriteSheetResource>>();
+
     container.insert(ResourceFieldPropertyEditorDefinition::<Shader>::new(
         Rc::new(|resource_manager, path| block_on(resource_manager.request::<Shader, _>(path))),
         sender,
@@ -258,6 +306,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_inspectable::<CuboidEmitter>();
     container.register_inheritable_inspectable::<PerspectiveProjection>();
     container.register_inheritable_inspectable::<OrthographicProjection>();
+    container.register_inheritable_inspectable::<AsymmetricProjection>();
     container.register_inheritable_inspectable::<Transform>();
     container.register_inheritable_inspectable::<CsmOptions>();
 
@@ -286,10 +335,14 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_enum::<RigidBodyType, _>();
     container.register_inheritable_enum::<Exposure, _>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
+    container.register_inheritable_enum::<LightAnimation, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
     container.register_inheritable_enum::<DistanceModel, _>();
     container.register_inheritable_enum::<sound::Renderer, _>();
     container.register_inheritable_enum::<RenderPath, _>();
+    container.register_inheritable_enum::<TrailAlignment, _>();
+    container.register_inheritable_enum::<TrailTextureMode, _>();
+    container.register_inheritable_enum::<ClothColliderKind, _>();
 
     container.insert(ScriptPropertyEditorDefinition {});
     container.insert(BitFieldPropertyEditorDefinition::<BitMask>::new());
@@ -355,6 +408,11 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(EnumPropertyEditorDefinition::<PolygonFillMode>::new());
 
     container.insert(EnumPropertyEditorDefinition::<MipFilter>::new());
+    container.insert(EnumPropertyEditorDefinition::<TargetPlatform>::new());
+    container.insert(EnumPropertyEditorDefinition::<TargetPlatform>::new_optional());
+    container.insert(EnumPropertyEditorDefinition::<ModelUpAxis>::new());
+    container.insert(InspectablePropertyEditorDefinition::<MeshOptimizationOptions>::new());
+    container.insert(EnumPropertyEditorDefinition::<Range<f32>>::new_optional());
 
     container.insert(InspectablePropertyEditorDefinition::<Limb>::new());
     container.register_inheritable_vec_collection::<Limb>();
@@ -123,6 +123,12 @@ pub fn calculate_gizmo_distance_scaling(
                     .global_position()
                     .metric_distance(&graph[camera].global_position())
         }
+        Projection::Asymmetric(proj) => {
+            distance_scale_factor(proj.vertical_fov())
+                * graph[gizmo_origin]
+                    .global_position()
+                    .metric_distance(&graph[camera].global_position())
+        }
         Projection::Orthographic(ortho) => 0.4 * ortho.vertical_size,
     };
 
@@ -12,7 +12,7 @@ use crate::{
         commands::{
             navmesh::{
                 AddNavmeshEdgeCommand, ConnectNavmeshEdgesCommand, DeleteNavmeshVertexCommand,
-                MoveNavmeshVertexCommand,
+                MoveNavmeshVertexCommand, SetNavmeshCommand,
             },
             ChangeSelectionCommand, CommandGroup, SceneCommand,
         },
@@ -36,12 +36,16 @@ use fyrox::{
         grid::{Column, GridBuilder, Row},
         message::{KeyCode, MessageDirection, UiMessage},
         stack_panel::StackPanelBuilder,
+        utils::make_simple_tooltip,
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowMessage, WindowTitle},
         BuildContext, Orientation, Thickness, UiNode, UserInterface,
     },
-    scene::{camera::Camera, navmesh::NavigationalMesh, node::Node},
-    utils::astar::PathVertex,
+    scene::{camera::Camera, mesh::Mesh, navmesh::NavigationalMesh, node::Node, terrain::Terrain},
+    utils::{
+        astar::PathVertex,
+        navmesh::generator::{NavmeshGenerationSettings, NavmeshGenerator},
+    },
 };
 use std::collections::HashMap;
 
@@ -50,6 +54,7 @@ pub mod selection;
 pub struct NavmeshPanel {
     pub window: Handle<UiNode>,
     connect_edges: Handle<UiNode>,
+    generate: Handle<UiNode>,
     sender: MessageSender,
 }
 
@@ -69,20 +74,39 @@ fn fetch_selection(editor_selection: &Selection) -> Option<NavmeshSelection> {
 impl NavmeshPanel {
     pub fn new(ctx: &mut BuildContext, sender: MessageSender) -> Self {
         let connect_edges;
+        let generate;
         let window = WindowBuilder::new(WidgetBuilder::new().with_name("NavmeshPanel"))
             .open(false)
             .with_title(WindowTitle::text("Navmesh"))
             .with_content(
                 GridBuilder::new(
                     WidgetBuilder::new().with_child(
-                        StackPanelBuilder::new(WidgetBuilder::new().with_child({
-                            connect_edges = ButtonBuilder::new(
-                                WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
-                            )
-                            .with_text("Connect Edges")
-                            .build(ctx);
-                            connect_edges
-                        }))
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child({
+                                    connect_edges = ButtonBuilder::new(
+                                        WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("Connect Edges")
+                                    .build(ctx);
+                                    connect_edges
+                                })
+                                .with_child({
+                                    generate = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_margin(Thickness::uniform(1.0))
+                                            .with_tooltip(make_simple_tooltip(
+                                                ctx,
+                                                "Generates navmesh from every visible mesh \
+                                                and terrain of the scene. Current navmesh \
+                                                will be replaced.",
+                                            )),
+                                    )
+                                    .with_text("Generate")
+                                    .build(ctx);
+                                    generate
+                                }),
+                        )
                         .with_orientation(Orientation::Horizontal)
                         .build(ctx),
                     ),
@@ -97,10 +121,16 @@ impl NavmeshPanel {
             window,
             sender,
             connect_edges,
+            generate,
         }
     }
 
-    pub fn handle_message(&mut self, message: &UiMessage, editor_scene: &EditorScene) {
+    pub fn handle_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &EditorScene,
+        engine: &Engine,
+    ) {
         scope_profile!();
 
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
@@ -124,6 +154,33 @@ impl NavmeshPanel {
                             [vertices[0], vertices[1]],
                         ));
                 }
+            } else if message.destination() == self.generate {
+                if let Some(selection) = fetch_selection(&editor_scene.selection) {
+                    let graph = &engine.scenes[editor_scene.scene].graph;
+
+                    let mut generator = NavmeshGenerator::new(NavmeshGenerationSettings::default());
+                    for (handle, node) in graph.pair_iter() {
+                        if node.global_visibility()
+                            && (node.cast::<Mesh>().is_some() || node.cast::<Terrain>().is_some())
+                        {
+                            generator.add_node(graph, handle);
+                        }
+                    }
+
+                    self.sender.do_scene_command(CommandGroup::from(vec![
+                        SceneCommand::new(ChangeSelectionCommand::new(
+                            Selection::Navmesh(NavmeshSelection::new(
+                                selection.navmesh_node(),
+                                vec![],
+                            )),
+                            editor_scene.selection.clone(),
+                        )),
+                        SceneCommand::new(SetNavmeshCommand::new(
+                            selection.navmesh_node(),
+                            generator.generate(),
+                        )),
+                    ]));
+                }
             }
         }
     }
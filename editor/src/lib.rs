@@ -1380,7 +1380,8 @@ impl Editor {
             self.scene_settings
                 .handle_ui_message(message, &self.message_sender);
 
-            self.navmesh_panel.handle_message(message, editor_scene);
+            self.navmesh_panel
+                .handle_message(message, editor_scene, engine);
 
             self.inspector
                 .handle_ui_message(message, editor_scene, engine, &self.message_sender);
@@ -218,7 +218,7 @@ impl LogPanel {
         let mut item_to_bring_into_view = Handle::NONE;
 
         while let Ok(msg) = self.receiver.try_recv() {
-            if msg.kind < self.severity {
+            if msg.kind.severity() < self.severity.severity() {
                 continue;
             }
 
@@ -238,6 +238,7 @@ impl LogPanel {
                                 .with_context_menu(self.context_menu.menu.clone())
                                 .with_margin(Thickness::uniform(1.0))
                                 .with_foreground(Brush::Solid(match msg.kind {
+                                    MessageKind::Debug => Color::opaque(150, 150, 150),
                                     MessageKind::Information => Color::opaque(210, 210, 210),
                                     MessageKind::Warning => Color::ORANGE,
                                     MessageKind::Error => Color::RED,
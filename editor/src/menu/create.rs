@@ -21,11 +21,14 @@ use fyrox::{
     scene::{
         base::BaseBuilder,
         camera::CameraBuilder,
+        cloth::ClothBuilder,
         decal::DecalBuilder,
+        foliage::{FoliageBuilder, FoliageLayer},
         light::{
             directional::DirectionalLightBuilder, point::PointLightBuilder, spot::SpotLightBuilder,
             BaseLightBuilder,
         },
+        lines::LinesBuilder,
         mesh::{
             surface::{Surface, SurfaceData, SurfaceSharedData},
             MeshBuilder,
@@ -37,9 +40,12 @@ use fyrox::{
             ParticleSystemBuilder,
         },
         pivot::PivotBuilder,
+        sky::SkyBuilder,
         sound::{listener::ListenerBuilder, SoundBuilder},
         sprite::SpriteBuilder,
         terrain::{Layer, TerrainBuilder},
+        trail::TrailBuilder,
+        water::WaterBuilder,
     },
     utils::navmesh::Navmesh,
 };
@@ -86,6 +92,12 @@ pub struct CreateEntityMenu {
     create_cylinder: Handle<UiNode>,
     create_quad: Handle<UiNode>,
     create_decal: Handle<UiNode>,
+    create_foliage: Handle<UiNode>,
+    create_water: Handle<UiNode>,
+    create_sky: Handle<UiNode>,
+    create_trail: Handle<UiNode>,
+    create_lines: Handle<UiNode>,
+    create_cloth: Handle<UiNode>,
     create_point_light: Handle<UiNode>,
     create_spot_light: Handle<UiNode>,
     create_directional_light: Handle<UiNode>,
@@ -115,6 +127,12 @@ impl CreateEntityMenu {
         let create_camera;
         let create_sprite;
         let create_decal;
+        let create_foliage;
+        let create_water;
+        let create_sky;
+        let create_trail;
+        let create_lines;
+        let create_cloth;
         let create_navmesh;
         let create_particle_system;
         let create_terrain;
@@ -214,6 +232,30 @@ impl CreateEntityMenu {
                 create_decal = create_menu_item("Decal", vec![], ctx);
                 create_decal
             },
+            {
+                create_foliage = create_menu_item("Foliage", vec![], ctx);
+                create_foliage
+            },
+            {
+                create_water = create_menu_item("Water", vec![], ctx);
+                create_water
+            },
+            {
+                create_sky = create_menu_item("Sky", vec![], ctx);
+                create_sky
+            },
+            {
+                create_trail = create_menu_item("Trail", vec![], ctx);
+                create_trail
+            },
+            {
+                create_lines = create_menu_item("Lines", vec![], ctx);
+                create_lines
+            },
+            {
+                create_cloth = create_menu_item("Cloth", vec![], ctx);
+                create_cloth
+            },
             {
                 create_navmesh = create_menu_item("Navmesh", vec![], ctx);
                 create_navmesh
@@ -239,6 +281,12 @@ impl CreateEntityMenu {
                 create_listener,
                 create_navmesh,
                 create_decal,
+                create_foliage,
+                create_water,
+                create_sky,
+                create_trail,
+                create_lines,
+                create_cloth,
                 physics_menu,
                 physics2d_menu,
                 dim2_menu,
@@ -379,6 +427,22 @@ impl CreateEntityMenu {
                         )
                     } else if message.destination() == self.create_decal {
                         Some(DecalBuilder::new(BaseBuilder::new().with_name("Decal")).build_node())
+                    } else if message.destination() == self.create_foliage {
+                        Some(
+                            FoliageBuilder::new(BaseBuilder::new().with_name("Foliage"))
+                                .with_layers(vec![FoliageLayer::default()])
+                                .build_node(),
+                        )
+                    } else if message.destination() == self.create_water {
+                        Some(WaterBuilder::new(BaseBuilder::new().with_name("Water")).build_node())
+                    } else if message.destination() == self.create_sky {
+                        Some(SkyBuilder::new(BaseBuilder::new().with_name("Sky")).build_node())
+                    } else if message.destination() == self.create_trail {
+                        Some(TrailBuilder::new(BaseBuilder::new().with_name("Trail")).build_node())
+                    } else if message.destination() == self.create_lines {
+                        Some(LinesBuilder::new(BaseBuilder::new().with_name("Lines")).build_node())
+                    } else if message.destination() == self.create_cloth {
+                        Some(ClothBuilder::new(BaseBuilder::new().with_name("Cloth")).build_node())
                     } else if message.destination() == self.create_listener {
                         Some(
                             ListenerBuilder::new(BaseBuilder::new().with_name("Listener"))
@@ -2,24 +2,36 @@ use crate::menu::create_menu_item;
 use fyrox::{
     core::pool::Handle,
     gui::{menu::MenuItemMessage, message::UiMessage, BuildContext, UiNode},
-    scene::{base::BaseBuilder, dim2::rectangle::RectangleBuilder, node::Node},
+    scene::{
+        base::BaseBuilder,
+        dim2::{rectangle::RectangleBuilder, tilemap::TileMapBuilder},
+        node::Node,
+    },
 };
 
 pub struct Dim2Menu {
     pub menu: Handle<UiNode>,
     create_sprite: Handle<UiNode>,
+    create_tile_map: Handle<UiNode>,
 }
 
 impl Dim2Menu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let create_sprite;
+        let create_tile_map;
 
         let menu = create_menu_item(
             "2D",
-            vec![{
-                create_sprite = create_menu_item("Rectangle (2D Sprite)", vec![], ctx);
-                create_sprite
-            }],
+            vec![
+                {
+                    create_sprite = create_menu_item("Rectangle (2D Sprite)", vec![], ctx);
+                    create_sprite
+                },
+                {
+                    create_tile_map = create_menu_item("Tile Map", vec![], ctx);
+                    create_tile_map
+                },
+            ],
             ctx,
         );
 
@@ -27,6 +39,7 @@ impl Dim2Menu {
             menu,
 
             create_sprite,
+            create_tile_map,
         }
     }
 
@@ -36,6 +49,10 @@ impl Dim2Menu {
                 let node =
                     RectangleBuilder::new(BaseBuilder::new().with_name("Sprite (2D)")).build_node();
                 Some(node)
+            } else if message.destination() == self.create_tile_map {
+                let node =
+                    TileMapBuilder::new(BaseBuilder::new().with_name("Tile Map")).build_node();
+                Some(node)
             } else {
                 None
             }
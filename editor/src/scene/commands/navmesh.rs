@@ -306,3 +306,36 @@ impl Command for MoveNavmeshVertexCommand {
         self.set_position(fetch_navmesh(context, self.navmesh_node), position);
     }
 }
+
+#[derive(Debug)]
+pub struct SetNavmeshCommand {
+    navmesh_node: Handle<Node>,
+    navmesh: Navmesh,
+}
+
+impl SetNavmeshCommand {
+    pub fn new(navmesh_node: Handle<Node>, navmesh: Navmesh) -> Self {
+        Self {
+            navmesh_node,
+            navmesh,
+        }
+    }
+
+    fn swap(&mut self, context: &mut SceneContext) {
+        std::mem::swap(fetch_navmesh(context, self.navmesh_node), &mut self.navmesh);
+    }
+}
+
+impl Command for SetNavmeshCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Set Navmesh".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+}
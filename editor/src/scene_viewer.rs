@@ -742,7 +742,8 @@ impl SceneViewer {
                                             .unwrap();
 
                                         let normal = match camera.projection() {
-                                            Projection::Perspective(_) => {
+                                            Projection::Perspective(_)
+                                            | Projection::Asymmetric(_) => {
                                                 Vector3::new(0.0, 1.0, 0.0)
                                             }
                                             Projection::Orthographic(_) => {
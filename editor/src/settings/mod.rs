@@ -31,7 +31,10 @@ use fyrox::{
         window::{WindowBuilder, WindowMessage, WindowTitle},
         HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
     },
-    renderer::{CsmSettings, QualitySettings, ShadowMapPrecision},
+    renderer::{
+        CsmSettings, LightLodSettings, QualitySettings, ShadowFilter, ShadowFilterSettings,
+        ShadowMapPrecision,
+    },
 };
 use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
@@ -132,6 +135,9 @@ impl Settings {
         container.insert(EnumPropertyEditorDefinition::<ShadowMapPrecision>::new());
         container.insert(InspectablePropertyEditorDefinition::<DebuggingSettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<CsmSettings>::new());
+        container.insert(InspectablePropertyEditorDefinition::<LightLodSettings>::new());
+        container.insert(EnumPropertyEditorDefinition::<ShadowFilter>::new());
+        container.insert(InspectablePropertyEditorDefinition::<ShadowFilterSettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<QualitySettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<CameraSettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<
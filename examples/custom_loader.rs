@@ -230,6 +230,7 @@ fn main() {
             .loaders
             .try_replace::<TextureLoader, _>(CustomTextureLoader(Arc::new(TextureLoader {
                 default_import_options: Default::default(),
+                cache: None,
             })))
             .is_some());
     }
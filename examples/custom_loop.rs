@@ -11,7 +11,8 @@ use fyrox::{
         log::{Log, MessageKind},
     },
     engine::{
-        Engine, EngineInitParams, GraphicsContext, GraphicsContextParams, SerializationContext,
+        timestep::FixedTimestep, Engine, EngineInitParams, GraphicsContext, GraphicsContextParams,
+        SerializationContext,
     },
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
@@ -42,8 +43,7 @@ fn main() {
 
     // Define game loop variables.
     let mut previous = Instant::now();
-    let fixed_timestep = 1.0 / 60.0;
-    let mut lag = 0.0;
+    let mut timestep = FixedTimestep::new(1.0 / 60.0);
 
     // Finally run our event loop which will respond to OS and window events and update
     // engine state accordingly. Engine lets you to decide which event should be handled,
@@ -56,8 +56,9 @@ fn main() {
                 // 60 fps.
                 let elapsed = previous.elapsed();
                 previous = Instant::now();
-                lag += elapsed.as_secs_f32();
-                while lag >= fixed_timestep {
+                timestep.set_time_scale(engine.time_scale());
+                timestep.begin_frame(elapsed.as_secs_f32());
+                while timestep.next_step() {
                     // ************************
                     // ************************
                     // Put your game logic here.
@@ -65,11 +66,22 @@ fn main() {
                     // ************************
 
                     // It is very important to update the engine every frame!
-                    engine.update(fixed_timestep, control_flow, &mut lag, Default::default());
-
-                    lag -= fixed_timestep;
+                    let fixed_dt = timestep.fixed_dt();
+                    engine.update(
+                        fixed_dt,
+                        control_flow,
+                        timestep.accumulator_mut(),
+                        Default::default(),
+                    );
                 }
 
+                // ************************
+                // ************************
+                // Put your per-frame code here, `timestep.interpolation_factor()` could be used
+                // to interpolate between previous and current state of the game for smooth motion.
+                // ************************
+                // ************************
+
                 // It is very important to "pump" messages from UI. Even if don't need to
                 // respond to such message, you should call this method, otherwise UI
                 // might behave very weird.
@@ -0,0 +1,151 @@
+//! Frame arena is a fast bump allocator for temporary data, that lives no longer than a single frame.
+//!
+//! Allocation in the arena is just a pointer bump, and every allocation is freed at once when the
+//! arena is [reset](FrameArena::reset). The memory of the arena is retained after reset, so after a few
+//! frames the arena will have enough memory for every temporary allocation of a frame and no heap
+//! allocations will be made at all.
+//!
+//! ```rust
+//! use fyrox_core::arena::FrameArena;
+//!
+//! let mut arena = FrameArena::new();
+//!
+//! for frame in 0..3 {
+//!     // Allocations cannot outlive the arena borrow, so it is impossible to reset the arena while
+//!     // there are references to the allocated data.
+//!     {
+//!         let visibility = arena.alloc_slice_fill_copy(100, false);
+//!         visibility[frame] = true;
+//!
+//!         let mut visible = arena.vec();
+//!         visible.extend(visibility.iter().enumerate().filter(|(_, v)| **v).map(|(i, _)| i));
+//!         assert_eq!(visible.as_slice(), &[frame]);
+//!     }
+//!
+//!     arena.reset();
+//! }
+//! ```
+//!
+//! Keep in mind, that destructors of the values allocated directly in the arena (for example by
+//! [`FrameArena::alloc`]) are **not** called. Collections allocated in the arena (see [`ArenaVec`]) do
+//! drop their items.
+
+use bumpalo::Bump;
+use std::fmt::{Debug, Formatter};
+
+/// A vector that stores its items in a [`FrameArena`].
+pub type ArenaVec<'a, T> = bumpalo::collections::Vec<'a, T>;
+
+/// See module docs.
+#[derive(Default)]
+pub struct FrameArena {
+    bump: Bump,
+}
+
+impl Debug for FrameArena {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FrameArena {{ {} bytes }}", self.allocated_bytes())
+    }
+}
+
+impl FrameArena {
+    /// Creates new empty arena. It does not allocate until the first allocation in it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates new arena with the given amount of pre-allocated memory (in bytes).
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bump: Bump::with_capacity(capacity),
+        }
+    }
+
+    /// Puts the given value in the arena and returns a reference to it. Destructor of the value will
+    /// not be called.
+    #[inline]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        self.bump.alloc(value)
+    }
+
+    /// Copies the given slice in the arena.
+    #[inline]
+    pub fn alloc_slice_copy<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        self.bump.alloc_slice_copy(slice)
+    }
+
+    /// Allocates a slice of the given length and fills it with the given value.
+    #[inline]
+    pub fn alloc_slice_fill_copy<T: Copy>(&self, len: usize, value: T) -> &mut [T] {
+        self.bump.alloc_slice_fill_copy(len, value)
+    }
+
+    /// Allocates a slice of the given length and fills it with the values produced by the given
+    /// function. The function is called with the index of an item.
+    #[inline]
+    pub fn alloc_slice_fill_with<T, F>(&self, len: usize, func: F) -> &mut [T]
+    where
+        F: FnMut(usize) -> T,
+    {
+        self.bump.alloc_slice_fill_with(len, func)
+    }
+
+    /// Creates new empty vector, that stores its items in the arena.
+    #[inline]
+    pub fn vec<T>(&self) -> ArenaVec<'_, T> {
+        ArenaVec::new_in(&self.bump)
+    }
+
+    /// Creates new empty vector with the given capacity, that stores its items in the arena.
+    #[inline]
+    pub fn vec_with_capacity<T>(&self, capacity: usize) -> ArenaVec<'_, T> {
+        ArenaVec::with_capacity_in(capacity, &self.bump)
+    }
+
+    /// Returns total amount of memory (in bytes) that is owned by the arena.
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+
+    /// Frees every allocation made in the arena at once. The memory is retained, so it will be
+    /// reused by subsequent allocations. This method should be called once per frame.
+    pub fn reset(&mut self) {
+        self.bump.reset()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arena::FrameArena;
+
+    #[test]
+    fn test_frame_arena() {
+        let mut arena = FrameArena::new();
+
+        let value = arena.alloc(123u32);
+        *value += 1;
+        assert_eq!(*value, 124);
+
+        let slice = arena.alloc_slice_copy(&[1, 2, 3]);
+        assert_eq!(slice, &[1, 2, 3]);
+        assert_eq!(arena.alloc_slice_fill_copy(3, 1.0), &[1.0, 1.0, 1.0]);
+        assert_eq!(arena.alloc_slice_fill_with(3, |i| i * 2), &[0, 2, 4]);
+
+        let mut vec = arena.vec_with_capacity(2);
+        for i in 0..100 {
+            vec.push(i);
+        }
+        assert_eq!(vec.len(), 100);
+        drop(vec);
+
+        // Memory must be retained after reset and the arena should not grow when the same amount of
+        // data is allocated every frame.
+        arena.reset();
+        let allocated = arena.allocated_bytes();
+        for _ in 0..10 {
+            arena.alloc_slice_fill_copy(100, 0u8);
+            arena.reset();
+        }
+        assert_eq!(arena.allocated_bytes(), allocated);
+    }
+}
@@ -0,0 +1,299 @@
+//! Virtual file system. Every file read via [`load_file`] is resolved using a set of mounted
+//! [IO providers](provider::IoProvider). By default, there is only one provider mounted at the root:
+//! the native file system on PC, application assets on Android and HTTP on WebAssembly.
+//!
+//! ## Mount points and overlays
+//!
+//! Every provider is mounted at some mount point (a relative path, empty path is the root) and
+//! receives paths relative to it. Providers that were mounted later have higher priority, so they
+//! could be used to override (overlay) files of previously mounted providers. For example, a patch
+//! pack mounted at the root will override files of the base game.
+
+use crate::{lazy_static::lazy_static, pack::PackError, parking_lot::RwLock};
+use std::{
+    io::Error,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+pub mod provider;
+
+use provider::IoProvider;
+
+#[derive(Debug)]
+pub enum FileLoadError {
+    Io(std::io::Error),
+    Custom(String),
+    Pack(PackError),
+}
+
+impl From<std::io::Error> for FileLoadError {
+    fn from(e: Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<PackError> for FileLoadError {
+    fn from(e: PackError) -> Self {
+        Self::Pack(e)
+    }
+}
+
+#[cfg(target_os = "android")]
+pub static ANDROID_APP: once_cell::sync::OnceCell<android_activity::AndroidApp> =
+    once_cell::sync::OnceCell::new();
+
+#[cfg(target_arch = "wasm32")]
+impl From<wasm_bindgen::JsValue> for FileLoadError {
+    fn from(value: wasm_bindgen::JsValue) -> Self {
+        let string = match js_sys::JSON::stringify(&value) {
+            Ok(string) => String::from(string),
+            Err(_) => format!("{:?}", value),
+        };
+        Self::Custom(string)
+    }
+}
+
+/// Converts a path to the canonical form that is used by the virtual file system: components are
+/// separated by `/`, current directory components are removed and parent directory components are
+/// resolved. Returns `None` for absolute paths and for paths that go outside of the working directory.
+pub(crate) fn normalize_path(path: &Path) -> Option<String> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_str()?),
+            Component::CurDir => (),
+            Component::ParentDir => {
+                components.pop()?;
+            }
+            Component::Prefix(_) | Component::RootDir => return None,
+        }
+    }
+    Some(components.join("/"))
+}
+
+struct MountPoint {
+    // Normalized path, empty string is the root.
+    path: String,
+    provider: Arc<dyn IoProvider>,
+}
+
+lazy_static! {
+    static ref MOUNT_POINTS: RwLock<Vec<MountPoint>> = RwLock::new(vec![MountPoint {
+        path: Default::default(),
+        provider: default_provider(),
+    }]);
+}
+
+/// Creates the default IO provider of the current platform: [`provider::FsIoProvider`] on PC,
+/// [`provider::AndroidAssetIoProvider`] on Android and [`provider::HttpIoProvider`] on WebAssembly.
+/// On iOS, [`provider::FsIoProvider`] reads files from the application bundle (see
+/// [`bundle_directory`]).
+pub fn default_provider() -> Arc<dyn IoProvider> {
+    #[cfg(all(
+        not(target_os = "android"),
+        not(target_os = "ios"),
+        not(target_arch = "wasm32")
+    ))]
+    {
+        Arc::new(provider::FsIoProvider::default())
+    }
+
+    #[cfg(target_os = "ios")]
+    {
+        Arc::new(provider::FsIoProvider::new(
+            bundle_directory().unwrap_or_default(),
+        ))
+    }
+
+    #[cfg(target_os = "android")]
+    {
+        Arc::new(provider::AndroidAssetIoProvider)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        Arc::new(provider::HttpIoProvider::default())
+    }
+}
+
+/// Returns a directory of the application bundle, where the assets of the application are stored.
+/// The working directory of iOS applications is not the bundle directory, so relative paths have
+/// to be resolved against it. On other platforms, it is the directory of the executable.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn bundle_directory() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| path.parent().map(|path| path.to_path_buf()))
+}
+
+/// Mounts the given IO provider at the given mount point. The mount point must be a relative path,
+/// empty path means the root. The provider will have the highest priority among all mounted providers.
+pub fn mount<P: AsRef<Path>>(mount_point: P, provider: Arc<dyn IoProvider>) {
+    let path = normalize_path(mount_point.as_ref()).unwrap_or_else(|| {
+        crate::log::Log::warn(format!(
+            "{} is not a valid mount point, the provider will be mounted at the root!",
+            mount_point.as_ref().display()
+        ));
+        Default::default()
+    });
+
+    MOUNT_POINTS.write().push(MountPoint { path, provider });
+}
+
+/// Unmounts the given IO provider from every mount point. Returns `true` if the provider was mounted,
+/// `false` - otherwise.
+pub fn unmount(provider: &dyn IoProvider) -> bool {
+    let address = provider as *const dyn IoProvider as *const u8;
+    let mut mount_points = MOUNT_POINTS.write();
+    let count = mount_points.len();
+    mount_points.retain(|mount_point| Arc::as_ptr(&mount_point.provider) as *const u8 != address);
+    mount_points.len() != count
+}
+
+/// Unmounts every provider and mounts the [default one](default_provider) at the root.
+pub fn reset_mounts() {
+    *MOUNT_POINTS.write() = vec![MountPoint {
+        path: Default::default(),
+        provider: default_provider(),
+    }];
+}
+
+/// Returns every mount point with its provider, in the order of mounting (the last one has the highest
+/// priority).
+pub fn mount_points() -> Vec<(PathBuf, Arc<dyn IoProvider>)> {
+    MOUNT_POINTS
+        .read()
+        .iter()
+        .map(|mount_point| {
+            (
+                PathBuf::from(&mount_point.path),
+                mount_point.provider.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Returns a list of providers, that could contain a file at the given path, together with the path
+/// relative to their mount points. The list is sorted by priority (the highest first).
+fn resolve(path: &Path) -> Vec<(Arc<dyn IoProvider>, PathBuf)> {
+    let normalized = normalize_path(path);
+
+    MOUNT_POINTS
+        .read()
+        .iter()
+        .rev()
+        .filter_map(|mount_point| match normalized.as_ref() {
+            Some(normalized) => {
+                let relative = if mount_point.path.is_empty() {
+                    Some(normalized.as_str())
+                } else {
+                    normalized
+                        .strip_prefix(mount_point.path.as_str())
+                        .and_then(|rest| rest.strip_prefix('/'))
+                };
+                relative.map(|relative| (mount_point.provider.clone(), PathBuf::from(relative)))
+            }
+            // Absolute paths (or paths outside of the working directory) could be handled only by
+            // the providers mounted at the root.
+            None if mount_point.path.is_empty() => {
+                Some((mount_point.provider.clone(), path.to_path_buf()))
+            }
+            None => None,
+        })
+        .collect()
+}
+
+/// Loads content of a file at the given path using mounted IO providers. See [module docs](self) for
+/// more info.
+pub async fn load_file<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, FileLoadError> {
+    let path = path.as_ref();
+
+    let mut candidates = resolve(path);
+    if let Some((last_provider, last_path)) = candidates.pop() {
+        for (provider, relative_path) in candidates {
+            if provider.exists(&relative_path).await {
+                return provider.load_file(&relative_path).await;
+            }
+        }
+
+        // There is no need to check existence for the last candidate, loading will fail anyway with
+        // the appropriate error.
+        last_provider.load_file(&last_path).await
+    } else {
+        Err(FileLoadError::Io(Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("There is no mount point for {}!", path.display()),
+        )))
+    }
+}
+
+/// Checks whether a file at the given path exists in any of the mounted IO providers.
+pub async fn exists<P: AsRef<Path>>(path: P) -> bool {
+    for (provider, relative_path) in resolve(path.as_ref()) {
+        if provider.exists(&relative_path).await {
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns a path in the native file system for a file at the given path, if the IO provider that
+/// has the file is backed by the native file system. See [`IoProvider::native_path`] for more info.
+pub async fn native_path<P: AsRef<Path>>(path: P) -> Option<PathBuf> {
+    for (provider, relative_path) in resolve(path.as_ref()) {
+        if provider.exists(&relative_path).await {
+            return provider.native_path(&relative_path);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{futures::executor::block_on, io::provider::EmbeddedIoProvider};
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path(Path::new("./data/../data/textures/a.png")).as_deref(),
+            Some("data/textures/a.png")
+        );
+        assert_eq!(normalize_path(Path::new("../a.png")), None);
+        assert_eq!(normalize_path(Path::new("/a.png")), None);
+    }
+
+    #[test]
+    fn test_mount_points() {
+        let base = Arc::new(
+            EmbeddedIoProvider::new()
+                .with_file("a.txt", b"base a".as_slice())
+                .with_file("b.txt", b"base b".as_slice()),
+        );
+        let overlay =
+            Arc::new(EmbeddedIoProvider::new().with_file("a.txt", b"overlay a".as_slice()));
+
+        mount("__vfs_test", base.clone());
+        mount("__vfs_test", overlay.clone());
+
+        assert_eq!(
+            block_on(load_file("__vfs_test/a.txt")).unwrap(),
+            b"overlay a"
+        );
+        assert_eq!(
+            block_on(load_file("./__vfs_test/b.txt")).unwrap(),
+            b"base b"
+        );
+        assert!(block_on(exists("__vfs_test/b.txt")));
+        assert!(!block_on(exists("__vfs_test/c.txt")));
+        assert_eq!(block_on(native_path("__vfs_test/a.txt")), None);
+
+        assert!(unmount(&*overlay));
+        assert!(!unmount(&*overlay));
+        assert_eq!(block_on(load_file("__vfs_test/a.txt")).unwrap(), b"base a");
+
+        assert!(unmount(&*base));
+        assert!(block_on(load_file("__vfs_test/a.txt")).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! IO providers are sources of files for the virtual file system. See [`IoProvider`] docs for more info.
+
+use crate::io::{normalize_path, FileLoadError};
+use fxhash::FxHashMap;
+use std::{
+    borrow::Cow,
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+};
+
+/// Future type for IO providers. See [`IoProvider`].
+#[cfg(target_arch = "wasm32")]
+pub type IoFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Future type for IO providers. See [`IoProvider`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type IoFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// IO provider is a source of files for the virtual file system. Every provider is mounted at some
+/// mount point (see [`super::mount`]) and receives paths relative to it. The engine has a number of
+/// built-in providers:
+///
+/// - [`FsIoProvider`] - reads files from the native file system (PC only).
+/// - [`AndroidAssetIoProvider`] - reads files from the assets of an Android application.
+/// - [`HttpIoProvider`] - fetches files over HTTP (WebAssembly only).
+/// - [`EmbeddedIoProvider`] - serves files embedded in the executable.
+/// - [`crate::pack::Pack`] - reads files from an asset pack.
+///
+/// You can implement this trait for your own source of files, for example to load files from a
+/// remote server on PC.
+pub trait IoProvider: Send + Sync + 'static {
+    /// Loads content of a file at the given path.
+    fn load_file<'a>(&'a self, path: &'a Path) -> IoFuture<'a, Result<Vec<u8>, FileLoadError>>;
+
+    /// Checks whether a file at the given path exists or not.
+    fn exists<'a>(&'a self, path: &'a Path) -> IoFuture<'a, bool>;
+
+    /// Returns a path of the file in the native file system, if the provider is backed by one. It is
+    /// used by the code that needs to read files partially (for example, streaming sound buffers).
+    /// Default implementation returns `None`, which means that the file must be loaded entirely
+    /// using [`IoProvider::load_file`].
+    fn native_path(&self, path: &Path) -> Option<PathBuf> {
+        let _ = path;
+        None
+    }
+}
+
+/// Reads files from the native file system relative to the given root directory. Empty root means
+/// that paths are relative to the working directory of the application.
+#[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+#[derive(Default, Debug)]
+pub struct FsIoProvider {
+    root: PathBuf,
+}
+
+#[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+impl FsIoProvider {
+    /// Creates a new provider with the given root directory.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+impl IoProvider for FsIoProvider {
+    fn load_file<'a>(&'a self, path: &'a Path) -> IoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move { Ok(std::fs::read(self.root.join(path))?) })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> IoFuture<'a, bool> {
+        Box::pin(async move { self.root.join(path).exists() })
+    }
+
+    fn native_path(&self, path: &Path) -> Option<PathBuf> {
+        Some(self.root.join(path))
+    }
+}
+
+/// Reads files from the assets of an Android application. Requires [`super::ANDROID_APP`] to be set.
+#[cfg(target_os = "android")]
+#[derive(Default, Debug)]
+pub struct AndroidAssetIoProvider;
+
+#[cfg(target_os = "android")]
+impl IoProvider for AndroidAssetIoProvider {
+    fn load_file<'a>(&'a self, path: &'a Path) -> IoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            let asset_manager = super::ANDROID_APP
+                .get()
+                .ok_or_else(|| FileLoadError::Custom("ANDROID_APP is not set".to_string()))?
+                .asset_manager();
+            let mut opened_asset = asset_manager
+                .open(&std::ffi::CString::new(path.to_str().unwrap()).unwrap())
+                .ok_or_else(|| FileLoadError::Custom(format!("File {:?} not found!", path)))?;
+            let bytes = opened_asset.get_buffer()?;
+            Ok(bytes.to_vec())
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> IoFuture<'a, bool> {
+        Box::pin(async move {
+            super::ANDROID_APP
+                .get()
+                .map(|v| {
+                    v.asset_manager()
+                        .open(&std::ffi::CString::new(path.to_str().unwrap()).unwrap())
+                        .is_some()
+                })
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// Fetches files over HTTP. Paths are appended to the base URL, empty base URL means that paths
+/// are relative to the location of the page.
+#[cfg(target_arch = "wasm32")]
+#[derive(Default, Debug)]
+pub struct HttpIoProvider {
+    base_url: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl HttpIoProvider {
+    /// Creates a new provider with the given base URL.
+    pub fn new<S: Into<String>>(base_url: S) -> Self {
+        Self {
+            base_url: base_url.into(),
+        }
+    }
+
+    fn url(&self, path: &Path) -> String {
+        format!("{}{}", self.base_url, path.to_string_lossy())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl IoProvider for HttpIoProvider {
+    fn load_file<'a>(&'a self, path: &'a Path) -> IoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            use js_sys::Uint8Array;
+            use wasm_bindgen::JsCast;
+            use wasm_bindgen_futures::JsFuture;
+
+            match web_sys::window() {
+                Some(window) => {
+                    let resp_value = JsFuture::from(window.fetch_with_str(&self.url(path))).await?;
+
+                    let resp: web_sys::Response = resp_value.dyn_into().unwrap();
+                    let data = JsFuture::from(resp.array_buffer().unwrap()).await?;
+                    let bytes = Uint8Array::new(&data).to_vec();
+                    Ok(bytes)
+                }
+                None => Err(FileLoadError::Custom("Window not found!".to_owned())),
+            }
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> IoFuture<'a, bool> {
+        Box::pin(async move {
+            use wasm_bindgen::JsCast;
+            use wasm_bindgen_futures::JsFuture;
+
+            match web_sys::window() {
+                Some(window) => {
+                    if let Ok(resp_value) =
+                        JsFuture::from(window.fetch_with_str(&self.url(path))).await
+                    {
+                        let resp: web_sys::Response = resp_value.dyn_into().unwrap();
+
+                        resp.status() == 200
+                    } else {
+                        false
+                    }
+                }
+                None => false,
+            }
+        })
+    }
+}
+
+/// Serves files embedded in the executable, usually with `include_bytes!` macro. It could also be
+/// used to serve files generated at runtime.
+///
+/// ```rust
+/// use fyrox_core::io::{self, provider::EmbeddedIoProvider};
+/// use std::sync::Arc;
+///
+/// let provider = EmbeddedIoProvider::new()
+///     .with_file("shaders/custom.shader", b"(name: \"Custom\")".as_slice());
+///
+/// io::mount("data", Arc::new(provider));
+/// ```
+#[derive(Default, Debug)]
+pub struct EmbeddedIoProvider {
+    files: FxHashMap<PathBuf, Cow<'static, [u8]>>,
+}
+
+impl EmbeddedIoProvider {
+    /// Creates a new provider without files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file with the given path (relative to the mount point) and content.
+    pub fn add_file<P, D>(&mut self, path: P, data: D)
+    where
+        P: AsRef<Path>,
+        D: Into<Cow<'static, [u8]>>,
+    {
+        // Paths are stored in the same form as the virtual file system passes them to providers.
+        let path = normalize_path(path.as_ref())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| path.as_ref().to_path_buf());
+        self.files.insert(path, data.into());
+    }
+
+    /// Same as [`Self::add_file`], but could be used to build the provider in a single chain of calls.
+    pub fn with_file<P, D>(mut self, path: P, data: D) -> Self
+    where
+        P: AsRef<Path>,
+        D: Into<Cow<'static, [u8]>>,
+    {
+        self.add_file(path, data);
+        self
+    }
+}
+
+impl IoProvider for EmbeddedIoProvider {
+    fn load_file<'a>(&'a self, path: &'a Path) -> IoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            self.files
+                .get(path)
+                .map(|data| data.to_vec())
+                .ok_or_else(|| FileLoadError::Custom(format!("File {:?} not found!", path)))
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> IoFuture<'a, bool> {
+        Box::pin(async move { self.files.contains_key(path) })
+    }
+}
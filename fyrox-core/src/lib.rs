@@ -12,10 +12,12 @@ extern crate lazy_static;
 
 pub use arrayvec;
 pub use byteorder;
+pub use crypto_secretbox;
 pub use nalgebra as algebra;
 pub use num_traits;
 pub use parking_lot;
 pub use rand;
+pub use salsa20;
 pub use uuid;
 
 use crate::visitor::{Visit, VisitResult, Visitor};
@@ -27,6 +29,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
+pub mod arena;
 pub mod color;
 pub mod color_gradient;
 pub mod curve;
@@ -35,6 +38,7 @@ pub mod log;
 pub mod math;
 pub mod numeric_range;
 pub mod octree;
+pub mod pack;
 pub mod pool;
 pub mod profiler;
 pub mod quadtree;
@@ -1,8 +1,18 @@
-//! Simple logger, it writes in file and in console at the same time.
+//! Structured logger. Every message has a level (see [`MessageKind`]), a timestamp and an optional
+//! module path, messages could be filtered globally and per-module, and passed to any number of
+//! sinks (see [`LogSink`]). By default, the log writes to the standard output and to `fyrox.log`
+//! file (on platforms with a file system).
+//!
+//! Use [`Log::info`] and similar methods to write messages, or [`crate::log_info`] and similar
+//! macros to write formatted messages with the module path of the caller, so they could be filtered
+//! using [`Log::set_module_verbosity`].
 
 use crate::lazy_static::lazy_static;
 use crate::parking_lot::Mutex;
-use std::fmt::{Debug, Display};
+use std::collections::VecDeque;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use crate::instant::Instant;
 #[cfg(not(target_arch = "wasm32"))]
@@ -23,6 +33,7 @@ extern "C" {
 }
 
 /// A message that could be sent by the logger to all listeners.
+#[derive(Clone, Debug)]
 pub struct LogMessage {
     /// Kind of the message: information, warning or error.
     pub kind: MessageKind,
@@ -31,20 +42,39 @@ pub struct LogMessage {
     /// Time point at which the message was recorded. It is relative to the moment when the
     /// logger was initialized.
     pub time: Duration,
+    /// Path of the module, that has written the message. It is set only for messages written
+    /// with logging macros (see [`crate::log_info`]) or [`Log::write_module`].
+    pub module: Option<&'static str>,
+}
+
+impl Display for LogMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{:.3}s] {}",
+            self.time.as_secs_f32(),
+            self.kind.as_str()
+        )?;
+        if let Some(module) = self.module {
+            write!(f, "{}: ", module)?;
+        }
+        write!(f, "{}", self.content)
+    }
 }
 
 lazy_static! {
     static ref LOG: Mutex<Log> = Mutex::new(Log {
-        #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
-        file: std::fs::File::create("fyrox.log").unwrap(),
         verbosity: MessageKind::Information,
+        module_verbosity: Default::default(),
         listeners: Default::default(),
+        sinks: Log::default_sinks(),
         time_origin: Instant::now()
     });
 }
 
-/// A kind of message.
-#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash)]
+/// A kind of message. Kinds are ordered by their severity (see [`MessageKind::severity`]), not by
+/// their numeric values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(u32)]
 pub enum MessageKind {
     /// Some useful information.
@@ -53,11 +83,38 @@ pub enum MessageKind {
     Warning = 1,
     /// An error of some kind.
     Error = 2,
+    /// Detailed information, that is useful for debugging only. Such messages are filtered out by
+    /// default.
+    Debug = 3,
+}
+
+impl PartialOrd for MessageKind {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MessageKind {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.severity().cmp(&other.severity())
+    }
 }
 
 impl MessageKind {
+    /// Returns severity of the message kind, it is used to filter messages by verbosity level.
+    /// The least severe kind is [`MessageKind::Debug`], the most severe is [`MessageKind::Error`].
+    pub fn severity(self) -> u32 {
+        match self {
+            MessageKind::Debug => 0,
+            MessageKind::Information => 1,
+            MessageKind::Warning => 2,
+            MessageKind::Error => 3,
+        }
+    }
+
     fn as_str(self) -> &'static str {
         match self {
+            MessageKind::Debug => "[DEBUG]: ",
             MessageKind::Information => "[INFO]: ",
             MessageKind::Warning => "[WARNING]: ",
             MessageKind::Error => "[ERROR]: ",
@@ -65,73 +122,282 @@ impl MessageKind {
     }
 }
 
+/// A destination of log messages. Sinks receive only messages, that passed verbosity filters.
+pub trait LogSink: Send {
+    /// Writes the message.
+    fn write(&mut self, message: &LogMessage);
+
+    /// Writes all buffered messages, if any. It is called when [`Log::flush`] is called, and
+    /// when a panic happens (see [`Log::install_panic_hook`]).
+    fn flush(&mut self) {}
+}
+
+/// Writes messages to the standard output, or to the browser console on WebAssembly.
+#[derive(Default)]
+pub struct StdoutSink;
+
+impl LogSink for StdoutSink {
+    fn write(&mut self, message: &LogMessage) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            log(&message.to_string());
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = io::stdout().write_all(message.to_string().as_bytes());
+        }
+    }
+
+    fn flush(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = io::stdout().flush();
+        }
+    }
+}
+
+/// Writes messages to a file and rotates it when it becomes too large: `fyrox.log` is renamed to
+/// `fyrox.log.1`, `fyrox.log.1` to `fyrox.log.2` and so on, the oldest file is removed. The file
+/// is also rotated when the sink is created, so the log of the previous run is kept.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileSink {
+    path: std::path::PathBuf,
+    file: Option<std::fs::File>,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileSink {
+    /// Default maximum size of a log file (in bytes).
+    pub const DEFAULT_MAX_SIZE: u64 = 16 * 1024 * 1024;
+
+    /// Default amount of old log files, that are kept.
+    pub const DEFAULT_MAX_FILES: usize = 3;
+
+    /// Creates new sink, that writes to the file at the given path. Errors are ignored, the sink
+    /// writes nothing if the file could not be created.
+    pub fn new<P: AsRef<std::path::Path>>(path: P) -> Self {
+        let mut sink = Self {
+            path: path.as_ref().to_owned(),
+            file: None,
+            size: 0,
+            max_size: Self::DEFAULT_MAX_SIZE,
+            max_files: Self::DEFAULT_MAX_FILES,
+        };
+        sink.rotate();
+        sink
+    }
+
+    /// Sets maximum size of the file (in bytes), the file is rotated when it is reached.
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets amount of old log files, that are kept. Zero means that the file is truncated when it
+    /// is rotated.
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    fn rotated_path(&self, index: usize) -> std::path::PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn rotate(&mut self) {
+        self.file = None;
+        if self.max_files > 0 {
+            let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let _ = std::fs::rename(self.rotated_path(index), self.rotated_path(index + 1));
+            }
+            let _ = std::fs::rename(&self.path, self.rotated_path(1));
+        }
+        self.file = std::fs::File::create(&self.path).ok();
+        self.size = 0;
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LogSink for FileSink {
+    fn write(&mut self, message: &LogMessage) {
+        let text = message.to_string();
+        if self.size > 0 && self.size + text.len() as u64 > self.max_size {
+            self.rotate();
+        }
+        if let Some(file) = self.file.as_mut() {
+            if file.write_all(text.as_bytes()).is_ok() {
+                self.size += text.len() as u64;
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = self.file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Keeps a number of the most recent messages in memory, for example to show them in a console or
+/// to attach them to a crash report. The sink could be cloned, all clones share the same buffer.
+///
+/// ```rust
+/// use fyrox_core::log::{Log, RingBufferSink};
+///
+/// let ring = RingBufferSink::new(100);
+/// Log::add_sink(ring.clone());
+///
+/// Log::info("Hello!");
+/// assert!(ring.messages().iter().any(|m| m.content.starts_with("Hello!")));
+/// ```
+#[derive(Clone)]
+pub struct RingBufferSink {
+    messages: Arc<Mutex<VecDeque<LogMessage>>>,
+    capacity: usize,
+}
+
+impl RingBufferSink {
+    /// Creates new sink, that keeps at most `capacity` messages.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            messages: Default::default(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Returns a copy of all messages in the buffer, oldest first.
+    pub fn messages(&self) -> Vec<LogMessage> {
+        self.messages.lock().iter().cloned().collect()
+    }
+
+    /// Same as [`Self::messages`], but returns `None` instead of waiting if the buffer is locked
+    /// at the moment. Useful in places, where waiting could cause a deadlock (for example, in a
+    /// panic hook).
+    pub fn try_messages(&self) -> Option<Vec<LogMessage>> {
+        self.messages
+            .try_lock()
+            .map(|messages| messages.iter().cloned().collect())
+    }
+
+    /// Removes all messages from the buffer.
+    pub fn clear(&self) {
+        self.messages.lock().clear();
+    }
+}
+
+impl LogSink for RingBufferSink {
+    fn write(&mut self, message: &LogMessage) {
+        let mut messages = self.messages.lock();
+        if messages.len() >= self.capacity {
+            messages.pop_front();
+        }
+        messages.push_back(message.clone());
+    }
+}
+
 /// See module docs.
 pub struct Log {
-    #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
-    file: std::fs::File,
     verbosity: MessageKind,
+    module_verbosity: Vec<(String, MessageKind)>,
     listeners: Vec<Sender<LogMessage>>,
+    sinks: Vec<Box<dyn LogSink>>,
     time_origin: Instant,
 }
 
 impl Log {
-    fn write_internal<S>(&mut self, kind: MessageKind, message: S)
+    fn default_sinks() -> Vec<Box<dyn LogSink>> {
+        #[allow(unused_mut)]
+        let mut sinks: Vec<Box<dyn LogSink>> = vec![Box::new(StdoutSink)];
+        // Tests must not litter the working directory with log files.
+        #[cfg(all(not(test), not(target_arch = "wasm32"), not(target_os = "android")))]
+        sinks.push(Box::new(FileSink::new("fyrox.log")));
+        sinks
+    }
+
+    fn min_level(&self, module: Option<&str>) -> MessageKind {
+        // The most specific filter wins.
+        module
+            .and_then(|module| {
+                self.module_verbosity
+                    .iter()
+                    .filter(|(prefix, _)| {
+                        module == prefix
+                            || (module.starts_with(prefix.as_str())
+                                && module[prefix.len()..].starts_with("::"))
+                    })
+                    .max_by_key(|(prefix, _)| prefix.len())
+                    .map(|(_, kind)| *kind)
+            })
+            .unwrap_or(self.verbosity)
+    }
+
+    fn write_internal<S>(&mut self, kind: MessageKind, module: Option<&'static str>, message: S)
     where
         S: AsRef<str>,
     {
-        let mut msg = message.as_ref().to_owned();
-        if kind as u32 >= self.verbosity as u32 {
-            for listener in self.listeners.iter() {
-                let _ = listener.send(LogMessage {
-                    kind,
-                    content: msg.clone(),
-                    time: Instant::now() - self.time_origin,
-                });
-            }
-
-            msg.insert_str(0, kind.as_str());
+        if kind.severity() < self.min_level(module).severity() {
+            return;
+        }
 
-            #[cfg(target_arch = "wasm32")]
-            {
-                log(&msg);
-            }
+        let message = LogMessage {
+            kind,
+            content: message.as_ref().to_owned(),
+            time: Instant::now() - self.time_origin,
+            module,
+        };
 
-            #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
-            {
-                let _ = io::stdout().write_all(msg.as_bytes());
-                let _ = self.file.write_all(msg.as_bytes());
-            }
+        // Listeners, whose receivers were dropped, are removed.
+        self.listeners
+            .retain(|listener| listener.send(message.clone()).is_ok());
 
-            #[cfg(target_os = "android")]
-            {
-                let _ = io::stdout().write_all(msg.as_bytes());
-            }
+        for sink in self.sinks.iter_mut() {
+            sink.write(&message);
         }
     }
 
-    fn writeln_internal<S>(&mut self, kind: MessageKind, message: S)
+    /// Writes string into console and into file.
+    pub fn write<S>(kind: MessageKind, msg: S)
+    where
+        S: AsRef<str>,
+    {
+        LOG.lock().write_internal(kind, None, msg);
+    }
+
+    /// Writes line into console and into file.
+    pub fn writeln<S>(kind: MessageKind, msg: S)
     where
         S: AsRef<str>,
     {
-        let mut msg = message.as_ref().to_owned();
+        let mut msg = msg.as_ref().to_owned();
         msg.push('\n');
-        self.write_internal(kind, msg)
+        Self::write(kind, msg)
     }
 
-    /// Writes string into console and into file.
-    pub fn write<S>(kind: MessageKind, msg: S)
+    /// Writes line with the given module path. The path is used for per-module filtering, see
+    /// [`Self::set_module_verbosity`]. Usually there's no need to call this method directly, use
+    /// [`crate::log_info`] and similar macros instead.
+    pub fn write_module<S>(kind: MessageKind, module: &'static str, msg: S)
     where
         S: AsRef<str>,
     {
-        LOG.lock().write_internal(kind, msg);
+        let mut msg = msg.as_ref().to_owned();
+        msg.push('\n');
+        LOG.lock().write_internal(kind, Some(module), msg);
     }
 
-    /// Writes line into console and into file.
-    pub fn writeln<S>(kind: MessageKind, msg: S)
+    /// Writes debug message.
+    pub fn debug<S>(msg: S)
     where
         S: AsRef<str>,
     {
-        LOG.lock().writeln_internal(kind, msg);
+        Self::writeln(MessageKind::Debug, msg)
     }
 
     /// Writes information message.
@@ -163,11 +429,77 @@ impl Log {
         LOG.lock().verbosity = kind;
     }
 
-    /// Adds a listener that will receive a copy of every message passed into the log.
+    /// Sets verbosity level of the module and all its submodules, it overrides global verbosity
+    /// level. For example, `Log::set_module_verbosity("fyrox::renderer", MessageKind::Debug)`
+    /// enables debug messages of the renderer only.
+    pub fn set_module_verbosity(module: &str, kind: MessageKind) {
+        let mut log = LOG.lock();
+        log.module_verbosity.retain(|(prefix, _)| prefix != module);
+        log.module_verbosity.push((module.to_owned(), kind));
+    }
+
+    /// Removes verbosity level of the module, set by [`Self::set_module_verbosity`].
+    pub fn reset_module_verbosity(module: &str) {
+        LOG.lock()
+            .module_verbosity
+            .retain(|(prefix, _)| prefix != module);
+    }
+
+    /// Adds a listener that will receive a copy of every message passed into the log. The listener is
+    /// removed once its receiver is dropped.
     pub fn add_listener(listener: Sender<LogMessage>) {
         LOG.lock().listeners.push(listener)
     }
 
+    /// Adds new sink, that will receive every message passed into the log.
+    pub fn add_sink<S: LogSink + 'static>(sink: S) {
+        LOG.lock().sinks.push(Box::new(sink))
+    }
+
+    /// Removes all sinks, including the default ones (standard output and `fyrox.log` file).
+    pub fn clear_sinks() {
+        let mut log = LOG.lock();
+        for sink in log.sinks.iter_mut() {
+            sink.flush();
+        }
+        log.sinks.clear();
+    }
+
+    /// Flushes all sinks.
+    pub fn flush() {
+        for sink in LOG.lock().sinks.iter_mut() {
+            sink.flush();
+        }
+    }
+
+    /// Installs a panic hook, that writes panic message with a backtrace to the log and flushes
+    /// all sinks. Previous panic hook is called after that. It is safe to call the method multiple
+    /// times, the hook is installed only once.
+    pub fn install_panic_hook() {
+        static INSTALLED: AtomicBool = AtomicBool::new(false);
+        if INSTALLED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let backtrace = std::backtrace::Backtrace::force_capture();
+            // The panic could happen while the log is locked (for example, in a sink), waiting
+            // for the lock in such case will never end.
+            if let Some(mut log) = LOG.try_lock() {
+                log.write_internal(
+                    MessageKind::Error,
+                    None,
+                    format!("{}\nBacktrace:\n{}\n", info, backtrace),
+                );
+                for sink in log.sinks.iter_mut() {
+                    sink.flush();
+                }
+            }
+            previous(info);
+        }));
+    }
+
     /// Allows you to verify that the result of operation is Ok, or print the error in the log.
     ///
     /// # Use cases
@@ -202,3 +534,133 @@ impl Log {
         }
     }
 }
+
+/// Writes formatted debug message with the module path of the caller.
+///
+/// ```rust
+/// use fyrox_core::log_debug;
+///
+/// let frame = 42;
+/// log_debug!("Frame {} has started", frame);
+/// ```
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        $crate::log::Log::write_module($crate::log::MessageKind::Debug, module_path!(), format!($($arg)*))
+    };
+}
+
+/// Writes formatted information message with the module path of the caller.
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::log::Log::write_module($crate::log::MessageKind::Information, module_path!(), format!($($arg)*))
+    };
+}
+
+/// Writes formatted warning message with the module path of the caller.
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::log::Log::write_module($crate::log::MessageKind::Warning, module_path!(), format!($($arg)*))
+    };
+}
+
+/// Writes formatted error message with the module path of the caller.
+#[macro_export]
+macro_rules! log_err {
+    ($($arg:tt)*) => {
+        $crate::log::Log::write_module($crate::log::MessageKind::Error, module_path!(), format!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::log::{FileSink, Log, LogSink, MessageKind, RingBufferSink, LOG};
+
+    #[test]
+    fn test_listener_removal() {
+        let listener_count = || LOG.lock().listeners.len();
+        let count = listener_count();
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        Log::add_listener(sender);
+        Log::info("listened");
+        assert!(receiver
+            .try_iter()
+            .any(|message| message.content == "listened\n"));
+        assert_eq!(listener_count(), count + 1);
+
+        drop(receiver);
+        Log::info("not listened");
+        assert_eq!(listener_count(), count);
+    }
+
+    #[test]
+    fn test_module_filtering() {
+        let ring = RingBufferSink::new(64);
+        Log::add_sink(ring.clone());
+        Log::set_module_verbosity("test_log::renderer", MessageKind::Debug);
+        Log::set_module_verbosity("test_log::renderer::shader", MessageKind::Error);
+
+        let contains = |text: &str| ring.messages().iter().any(|m| m.content.contains(text));
+
+        Log::write_module(MessageKind::Debug, "test_log::renderer", "renderer debug");
+        Log::write_module(MessageKind::Debug, "test_log::renderer_ext", "ext debug");
+        Log::write_module(
+            MessageKind::Warning,
+            "test_log::renderer::shader",
+            "shader warn",
+        );
+        Log::write_module(
+            MessageKind::Error,
+            "test_log::renderer::shader",
+            "shader err",
+        );
+        assert!(contains("renderer debug"));
+        assert!(!contains("ext debug"));
+        assert!(!contains("shader warn"));
+        assert!(contains("shader err"));
+
+        Log::reset_module_verbosity("test_log::renderer");
+        Log::write_module(MessageKind::Debug, "test_log::renderer", "second debug");
+        assert!(!contains("second debug"));
+
+        crate::log_err!("{} {}", "formatted", 3);
+        let message = ring
+            .messages()
+            .into_iter()
+            .find(|m| m.content.contains("formatted 3"))
+            .unwrap();
+        assert_eq!(message.module, Some(module_path!()));
+        assert!(message
+            .to_string()
+            .ends_with("[ERROR]: fyrox_core::log::test: formatted 3\n"));
+
+        // Oldest messages are dropped.
+        let mut ring = RingBufferSink::new(2);
+        for content in ["a", "b", "c"] {
+            ring.write(&crate::log::LogMessage {
+                kind: MessageKind::Information,
+                content: content.to_owned(),
+                time: Default::default(),
+                module: None,
+            });
+        }
+        let messages = ring.messages();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "b");
+
+        let path = std::env::temp_dir().join("fyrox_log_rotation_test.log");
+        let mut file = FileSink::new(&path).with_max_size(40).with_max_files(1);
+        for message in messages.iter().cycle().take(4) {
+            file.write(message);
+        }
+        let rotated = file.rotated_path(1);
+        assert!(std::fs::metadata(&path).unwrap().len() <= 40);
+        assert!(std::fs::metadata(&rotated).unwrap().len() > 0);
+        drop(file);
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(rotated);
+    }
+}
@@ -0,0 +1,585 @@
+//! Asset packs are archives with game assets (textures, models, sounds, etc.), that could be mounted
+//! to the [virtual file system](crate::io), so every file read via [`crate::io::load_file`] will be
+//! looked up in mounted packs first and only then in the file system. This way a shipped game does not need to expose its raw asset folders.
+//!
+//! Packs are content-addressed - files with the same content are stored only once. Every file could
+//! optionally be compressed (DEFLATE) and encrypted (XSalsa20). Use [`PackBuilder`] to create a pack
+//! and [`mount_pack`] to make its content available to the engine.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use fyrox_core::pack::{mount_pack, Pack, PackBuilder};
+//!
+//! const KEY: [u8; 32] = *b"an example key, use your own one";
+//!
+//! // At build time.
+//! let mut builder = PackBuilder::new().with_compression(true).with_encryption(KEY);
+//! builder.add_directory("data").unwrap();
+//! builder.save("data.pak").unwrap();
+//!
+//! // At runtime.
+//! mount_pack(Pack::open("data.pak", Some(KEY)).unwrap());
+//! ```
+//!
+//! ## Security
+//!
+//! Encryption protects assets from casual extraction only: the key must be shipped with the game, so
+//! a determined person will always be able to extract it. Paths of the files are not encrypted.
+
+use crate::{
+    byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt},
+    io::{
+        self, normalize_path,
+        provider::{IoFuture, IoProvider},
+        FileLoadError,
+    },
+};
+use fxhash::FxHashMap;
+use salsa20::{
+    cipher::{KeyIvInit, StreamCipher},
+    XSalsa20,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    io::{Cursor, Read, Seek, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+const MAGIC: [u8; 4] = *b"FPAK";
+const VERSION: u32 = 1;
+const FLAG_ENCRYPTED: u32 = 1;
+const BLOB_FLAG_COMPRESSED: u8 = 1;
+const SALT_SIZE: usize = 16;
+
+/// A key that is used to encrypt and decrypt content of a pack.
+pub type PackKey = [u8; 32];
+
+/// An error that may occur during pack creation or reading.
+#[derive(Debug)]
+pub enum PackError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// The data is not a pack.
+    NotAPack,
+    /// The pack was created with unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The pack is encrypted, but no key was provided.
+    KeyRequired,
+    /// Index of the pack is malformed.
+    InvalidIndex,
+    /// A path cannot be stored in a pack. Only relative paths, that do not go outside of the
+    /// working directory, are supported.
+    InvalidPath(PathBuf),
+    /// There is no file at the given path in the pack.
+    NotFound(PathBuf),
+    /// Content of the file is corrupted. It could also mean that the pack was decrypted using
+    /// wrong key.
+    Corrupted(PathBuf),
+}
+
+impl Display for PackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackError::Io(v) => {
+                write!(f, "An i/o error has occurred: {v}")
+            }
+            PackError::NotAPack => {
+                write!(f, "The data is not a pack!")
+            }
+            PackError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported pack version {v}!")
+            }
+            PackError::KeyRequired => {
+                write!(f, "The pack is encrypted, but no key was provided!")
+            }
+            PackError::InvalidIndex => {
+                write!(f, "Index of the pack is malformed!")
+            }
+            PackError::InvalidPath(v) => {
+                write!(f, "Path {} cannot be stored in a pack!", v.display())
+            }
+            PackError::NotFound(v) => {
+                write!(f, "There is no file {} in the pack!", v.display())
+            }
+            PackError::Corrupted(v) => {
+                write!(
+                    f,
+                    "Content of {} is corrupted or the key is wrong!",
+                    v.display()
+                )
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for PackError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+fn apply_keystream(key: &PackKey, salt: &[u8; SALT_SIZE], blob_index: u64, data: &mut [u8]) {
+    // Every blob uses its own nonce, so the same keystream is never reused.
+    let mut nonce = [0u8; 24];
+    nonce[..SALT_SIZE].copy_from_slice(salt);
+    nonce[SALT_SIZE..].copy_from_slice(&blob_index.to_le_bytes());
+
+    let mut cipher = XSalsa20::new(key.into(), &nonce.into());
+    cipher.apply_keystream(data);
+}
+
+struct BlobInfo {
+    // Hash of the original (uncompressed and unencrypted) content.
+    hash: u64,
+    // Offset relative to the beginning of the data section.
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+    flags: u8,
+}
+
+enum PackSource {
+    Memory(Vec<u8>),
+    #[cfg(not(target_arch = "wasm32"))]
+    File(crate::parking_lot::Mutex<std::fs::File>),
+}
+
+struct PackIndex {
+    data_offset: u64,
+    key: Option<PackKey>,
+    salt: [u8; SALT_SIZE],
+    blobs: Vec<BlobInfo>,
+    entries: FxHashMap<String, u32>,
+}
+
+/// Pack is a read-only archive with files. See [module docs](self) for more info.
+pub struct Pack {
+    source: PackSource,
+    index: PackIndex,
+}
+
+impl Pack {
+    fn read_index<R: Read + Seek>(
+        reader: &mut R,
+        key: Option<PackKey>,
+    ) -> Result<PackIndex, PackError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(PackError::NotAPack);
+        }
+
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != VERSION {
+            return Err(PackError::UnsupportedVersion(version));
+        }
+
+        let flags = reader.read_u32::<LittleEndian>()?;
+        let key = if flags & FLAG_ENCRYPTED != 0 {
+            Some(key.ok_or(PackError::KeyRequired)?)
+        } else {
+            None
+        };
+
+        let mut salt = [0u8; SALT_SIZE];
+        reader.read_exact(&mut salt)?;
+
+        let blob_count = reader.read_u32::<LittleEndian>()?;
+        let entry_count = reader.read_u32::<LittleEndian>()?;
+
+        let mut blobs = Vec::new();
+        for _ in 0..blob_count {
+            blobs.push(BlobInfo {
+                hash: reader.read_u64::<LittleEndian>()?,
+                offset: reader.read_u64::<LittleEndian>()?,
+                stored_size: reader.read_u64::<LittleEndian>()?,
+                size: reader.read_u64::<LittleEndian>()?,
+                flags: reader.read_u8()?,
+            });
+        }
+
+        let mut entries = FxHashMap::default();
+        for _ in 0..entry_count {
+            let path_len = reader.read_u32::<LittleEndian>()?;
+            let mut path = Vec::new();
+            reader
+                .by_ref()
+                .take(path_len as u64)
+                .read_to_end(&mut path)?;
+            let path = String::from_utf8(path).map_err(|_| PackError::InvalidIndex)?;
+            let blob = reader.read_u32::<LittleEndian>()?;
+            if blob >= blob_count {
+                return Err(PackError::InvalidIndex);
+            }
+            entries.insert(path, blob);
+        }
+
+        Ok(PackIndex {
+            data_offset: reader.stream_position()?,
+            key,
+            salt,
+            blobs,
+            entries,
+        })
+    }
+
+    /// Creates a pack from a memory block. The key must be provided if the pack is encrypted.
+    pub fn from_bytes(bytes: Vec<u8>, key: Option<PackKey>) -> Result<Self, PackError> {
+        let index = Self::read_index(&mut Cursor::new(bytes.as_slice()), key)?;
+        Ok(Self {
+            source: PackSource::Memory(bytes),
+            index,
+        })
+    }
+
+    /// Opens a pack from a file. Only the index of the pack is read, content of files will be read
+    /// on demand. The key must be provided if the pack is encrypted.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open<P: AsRef<Path>>(path: P, key: Option<PackKey>) -> Result<Self, PackError> {
+        let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+        let index = Self::read_index(&mut reader, key)?;
+        let source = PackSource::File(crate::parking_lot::Mutex::new(reader.into_inner()));
+        Ok(Self { source, index })
+    }
+
+    /// Returns `true` if the pack contains a file at the given path.
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        normalize_path(path.as_ref()).map_or(false, |path| self.index.entries.contains_key(&path))
+    }
+
+    /// Returns an iterator over paths of every file in the pack.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.index.entries.keys().map(|path| path.as_str())
+    }
+
+    /// Returns total amount of files in the pack.
+    pub fn len(&self) -> usize {
+        self.index.entries.len()
+    }
+
+    /// Returns `true` if the pack has no files.
+    pub fn is_empty(&self) -> bool {
+        self.index.entries.is_empty()
+    }
+
+    /// Reads content of a file at the given path.
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>, PackError> {
+        let path = path.as_ref();
+
+        let blob_index = normalize_path(path)
+            .and_then(|normalized| self.index.entries.get(&normalized).cloned())
+            .ok_or_else(|| PackError::NotFound(path.to_path_buf()))?;
+        let blob = &self.index.blobs[blob_index as usize];
+
+        // The index could be corrupted (or crafted), so the blob must be checked against the actual
+        // size of the pack before allocating memory for it.
+        let length = match &self.source {
+            PackSource::Memory(bytes) => bytes.len() as u64,
+            #[cfg(not(target_arch = "wasm32"))]
+            PackSource::File(file) => file.lock().metadata()?.len(),
+        };
+        let offset = self
+            .index
+            .data_offset
+            .checked_add(blob.offset)
+            .filter(|offset| {
+                offset
+                    .checked_add(blob.stored_size)
+                    .map_or(false, |end| end <= length)
+            })
+            .ok_or_else(|| PackError::Corrupted(path.to_path_buf()))?;
+
+        let mut data = vec![0; blob.stored_size as usize];
+        match &self.source {
+            PackSource::Memory(bytes) => {
+                let begin = offset as usize;
+                let end = begin + data.len();
+                data.copy_from_slice(&bytes[begin..end]);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            PackSource::File(file) => {
+                let mut file = file.lock();
+                file.seek(std::io::SeekFrom::Start(offset))?;
+                file.read_exact(&mut data)?;
+            }
+        }
+
+        if let Some(key) = self.index.key.as_ref() {
+            apply_keystream(key, &self.index.salt, blob_index as u64, &mut data);
+        }
+
+        if blob.flags & BLOB_FLAG_COMPRESSED != 0 {
+            // Decompressed data can't be larger than the size in the index.
+            let size =
+                usize::try_from(blob.size).map_err(|_| PackError::Corrupted(path.to_path_buf()))?;
+            data = miniz_oxide::inflate::decompress_to_vec_with_limit(&data, size)
+                .map_err(|_| PackError::Corrupted(path.to_path_buf()))?;
+        }
+
+        if data.len() as u64 != blob.size || fxhash::hash64(&data) != blob.hash {
+            return Err(PackError::Corrupted(path.to_path_buf()));
+        }
+
+        Ok(data)
+    }
+}
+
+/// Pack builder allows you to create packs. See [module docs](self) for more info.
+#[derive(Default)]
+pub struct PackBuilder {
+    compression: bool,
+    key: Option<PackKey>,
+    blobs: Vec<Vec<u8>>,
+    // Content hash -> indices of blobs with such hash.
+    blob_lookup: FxHashMap<u64, Vec<u32>>,
+    entries: BTreeMap<String, u32>,
+}
+
+impl PackBuilder {
+    /// Creates a new pack builder without compression and encryption.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines whether the content of files should be compressed or not. Files, that cannot be
+    /// compressed efficiently (for example, already compressed images or sounds), will be stored
+    /// as is anyway.
+    pub fn with_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets a key that will be used to encrypt the content of files.
+    pub fn with_encryption(mut self, key: PackKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Adds a file with the given content to the pack. The path must be relative to the working
+    /// directory of the game, because it will be used to find the file at runtime. If there is a file
+    /// with the same path already, it will be replaced.
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P, content: Vec<u8>) -> Result<(), PackError> {
+        let path = path.as_ref();
+        let normalized =
+            normalize_path(path).ok_or_else(|| PackError::InvalidPath(path.to_path_buf()))?;
+
+        // Store each unique content only once. Hashes are compared first, and the content itself is
+        // compared only in case of equal hashes to handle possible collisions.
+        let hash = fxhash::hash64(&content);
+        let candidates = self.blob_lookup.entry(hash).or_default();
+        let blob_index = match candidates
+            .iter()
+            .find(|index| self.blobs[**index as usize] == content)
+        {
+            Some(index) => *index,
+            None => {
+                let index = self.blobs.len() as u32;
+                self.blobs.push(content);
+                candidates.push(index);
+                index
+            }
+        };
+
+        self.entries.insert(normalized, blob_index);
+
+        Ok(())
+    }
+
+    /// Adds every file from the given directory and its sub-directories to the pack. Paths of the
+    /// files will be stored as is (`root` joined with the relative path of a file in it), so `root`
+    /// must be relative to the working directory of the game.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_directory<P: AsRef<Path>>(&mut self, root: P) -> Result<(), PackError> {
+        for entry in std::fs::read_dir(root.as_ref())? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.add_directory(&path)?;
+            } else {
+                let content = std::fs::read(&path)?;
+                self.add_file(&path, content)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the pack to the given writer.
+    pub fn write<W: Write>(self, writer: &mut W) -> Result<(), PackError> {
+        let salt = crate::rand::random::<[u8; SALT_SIZE]>();
+
+        let mut blob_infos = Vec::new();
+        let mut data = Vec::new();
+        for (index, content) in self.blobs.into_iter().enumerate() {
+            let hash = fxhash::hash64(&content);
+            let size = content.len() as u64;
+
+            let mut flags = 0;
+            let mut stored = content;
+            if self.compression {
+                let compressed = miniz_oxide::deflate::compress_to_vec(&stored, 6);
+                if compressed.len() < stored.len() {
+                    stored = compressed;
+                    flags |= BLOB_FLAG_COMPRESSED;
+                }
+            }
+
+            if let Some(key) = self.key.as_ref() {
+                apply_keystream(key, &salt, index as u64, &mut stored);
+            }
+
+            blob_infos.push(BlobInfo {
+                hash,
+                offset: data.len() as u64,
+                stored_size: stored.len() as u64,
+                size,
+                flags,
+            });
+
+            data.extend_from_slice(&stored);
+        }
+
+        writer.write_all(&MAGIC)?;
+        writer.write_u32::<LittleEndian>(VERSION)?;
+        writer.write_u32::<LittleEndian>(if self.key.is_some() {
+            FLAG_ENCRYPTED
+        } else {
+            0
+        })?;
+        writer.write_all(&salt)?;
+        writer.write_u32::<LittleEndian>(blob_infos.len() as u32)?;
+        writer.write_u32::<LittleEndian>(self.entries.len() as u32)?;
+
+        for blob in blob_infos.iter() {
+            writer.write_u64::<LittleEndian>(blob.hash)?;
+            writer.write_u64::<LittleEndian>(blob.offset)?;
+            writer.write_u64::<LittleEndian>(blob.stored_size)?;
+            writer.write_u64::<LittleEndian>(blob.size)?;
+            writer.write_u8(blob.flags)?;
+        }
+
+        for (path, blob) in self.entries.iter() {
+            writer.write_u32::<LittleEndian>(path.len() as u32)?;
+            writer.write_all(path.as_bytes())?;
+            writer.write_u32::<LittleEndian>(*blob)?;
+        }
+
+        writer.write_all(&data)?;
+
+        Ok(())
+    }
+
+    /// Writes the pack to a memory block.
+    pub fn build(self) -> Result<Vec<u8>, PackError> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Writes the pack to a file at the given path.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save<P: AsRef<Path>>(self, path: P) -> Result<(), PackError> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+impl IoProvider for Pack {
+    fn load_file<'a>(&'a self, path: &'a Path) -> IoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move { Ok(self.read(path)?) })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> IoFuture<'a, bool> {
+        Box::pin(async move { self.contains(path) })
+    }
+}
+
+/// Mounts the given pack at the root of the [virtual file system](crate::io), so its files could be
+/// read using [`crate::io::load_file`]. Packs that were mounted later have higher priority, this could
+/// be used to "patch" files of previously mounted packs. Returns a shared reference to the pack, that
+/// could be used to unmount it later.
+pub fn mount_pack(pack: Pack) -> Arc<Pack> {
+    let pack = Arc::new(pack);
+    io::mount("", pack.clone());
+    pack
+}
+
+/// Unmounts previously mounted pack. Returns `true` if the pack was mounted, `false` - otherwise.
+pub fn unmount_pack(pack: &Arc<Pack>) -> bool {
+    io::unmount(&**pack)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pack_round_trip() {
+        let key = [42u8; 32];
+
+        for (compression, key) in [
+            (false, None),
+            (true, None),
+            (false, Some(key)),
+            (true, Some(key)),
+        ] {
+            let mut builder = PackBuilder::new().with_compression(compression);
+            if let Some(key) = key {
+                builder = builder.with_encryption(key);
+            }
+
+            let compressible = vec![1u8; 1024];
+            builder
+                .add_file("data/a.bin", compressible.clone())
+                .unwrap();
+            builder.add_file("data/b.bin", b"foobar".to_vec()).unwrap();
+            // Duplicated content must be stored once.
+            builder
+                .add_file("data/c.bin", compressible.clone())
+                .unwrap();
+
+            let pack = Pack::from_bytes(builder.build().unwrap(), key).unwrap();
+
+            assert_eq!(pack.len(), 3);
+            assert_eq!(pack.index.blobs.len(), 2);
+            assert!(pack.contains("./data/a.bin"));
+            assert!(!pack.contains("data/d.bin"));
+            assert_eq!(pack.read("data/a.bin").unwrap(), compressible);
+            assert_eq!(pack.read("data/b.bin").unwrap(), b"foobar");
+            assert_eq!(pack.read("data/c.bin").unwrap(), compressible);
+            assert!(matches!(
+                pack.read("data/d.bin"),
+                Err(PackError::NotFound(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_encrypted_pack_requires_key() {
+        let mut builder = PackBuilder::new().with_encryption([1u8; 32]);
+        builder.add_file("a.bin", b"foobar".to_vec()).unwrap();
+        let bytes = builder.build().unwrap();
+
+        assert!(matches!(
+            Pack::from_bytes(bytes.clone(), None),
+            Err(PackError::KeyRequired)
+        ));
+
+        let pack = Pack::from_bytes(bytes, Some([2u8; 32])).unwrap();
+        assert!(matches!(pack.read("a.bin"), Err(PackError::Corrupted(_))));
+    }
+
+    #[test]
+    fn test_truncated_pack() {
+        let mut builder = PackBuilder::new();
+        builder.add_file("a.bin", b"foobar".to_vec()).unwrap();
+        let mut bytes = builder.build().unwrap();
+        bytes.truncate(bytes.len() - 1);
+
+        let pack = Pack::from_bytes(bytes, None).unwrap();
+        assert!(matches!(pack.read("a.bin"), Err(PackError::Corrupted(_))));
+    }
+}
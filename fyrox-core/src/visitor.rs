@@ -10,6 +10,9 @@
 
 pub use fyrox_core_derive::Visit;
 
+pub mod diff;
+mod text;
+
 pub mod prelude {
     //! Types to use `#[derive(Visit)]`
     pub use super::{Visit, VisitError, VisitResult, Visitor};
@@ -344,29 +347,11 @@ impl FieldKind {
             Self::UnitQuaternion(data) => {
                 format!("<quat = {}; {}; {}; {}>, ", data.i, data.j, data.k, data.w)
             }
-            Self::Matrix4(data) => {
-                let mut out = String::from("<mat4 = ");
-                for f in data.iter() {
-                    out += format!("{}; ", f).as_str();
-                }
-                out
-            }
-            Self::Data(data) => {
-                let out = match String::from_utf8(data.clone()) {
-                    Ok(s) => s,
-                    Err(_) => base64::engine::general_purpose::STANDARD.encode(data),
-                };
-                format!("<data = {}>, ", out)
-            }
-            Self::Matrix3(data) => {
-                let mut out = String::from("<mat3 = ");
-                for f in data.iter() {
-                    out += format!("{}; ", f).as_str();
-                }
-                out
-            }
+            Self::Matrix4(data) => matrix_to_string("mat4", data.iter()),
+            Self::Data(data) => text::data_to_string(data),
+            Self::Matrix3(data) => matrix_to_string("mat3", data.iter()),
 
-            Self::Uuid(uuid) => uuid.to_string(),
+            Self::Uuid(uuid) => format!("<uuid = {}>, ", uuid),
             Self::UnitComplex(data) => {
                 format!("<complex = {}; {}>, ", data.re, data.im)
             }
@@ -377,21 +362,20 @@ impl FieldKind {
             } => {
                 let base64_encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
                 format!(
-                    "<podarray = {}; {}; [{}]>",
+                    "<podarray = {}; {}; [{}]>, ",
                     type_id, element_size, base64_encoded
                 )
             }
-            Self::Matrix2(data) => {
-                let mut out = String::from("<mat2 = ");
-                for f in data.iter() {
-                    out += format!("{}; ", f).as_str();
-                }
-                out
-            }
+            Self::Matrix2(data) => matrix_to_string("mat2", data.iter()),
         }
     }
 }
 
+fn matrix_to_string<'a>(kind: &str, values: impl Iterator<Item = &'a f32>) -> String {
+    let values = values.map(|f| f.to_string()).collect::<Vec<_>>();
+    format!("<{} = {}>, ", kind, values.join("; "))
+}
+
 macro_rules! impl_field_data {
     ($type_name:ty, $($kind:tt)*) => {
         impl Visit for $type_name {
@@ -533,6 +517,7 @@ pub enum VisitError {
     UnexpectedRcNullIndex,
     PoisonedMutex,
     FileLoadError(FileLoadError),
+    InvalidText(String),
 }
 
 impl Display for VisitError {
@@ -555,6 +540,7 @@ impl Display for VisitError {
             Self::UnexpectedRcNullIndex => write!(f, "unexpected rc null index"),
             Self::PoisonedMutex => write!(f, "attempt to lock poisoned mutex"),
             Self::FileLoadError(e) => write!(f, "file load error: {:?}", e),
+            Self::InvalidText(msg) => write!(f, "invalid text format: {}", msg),
         }
     }
 }
@@ -1228,12 +1214,19 @@ impl Visitor {
         }
     }
 
+    /// Returns the text representation of the visitor, it could be loaded back using [`Self::load_text`].
+    /// The text form is useful for human-edited files and meaningful diffs in version control systems.
     pub fn save_text(&self) -> String {
         let mut out_string = String::new();
         self.print_node(self.root, 0, &mut out_string);
         out_string
     }
 
+    /// Creates a visitor for reading from the text representation produced by [`Self::save_text`].
+    pub fn load_text(text: &str) -> Result<Self, VisitError> {
+        text::parse(text)
+    }
+
     pub fn save_binary_to_memory<W: Write>(&self, mut writer: W) -> VisitResult {
         writer.write_all(Self::MAGIC.as_bytes())?;
         let mut stack = vec![self.root];
@@ -1919,7 +1912,11 @@ mod test {
 
     #[test]
     fn visitor_test() {
-        let path = Path::new("test.bin");
+        let directory = Path::new("test_output");
+        if !directory.exists() {
+            std::fs::create_dir_all(directory).unwrap();
+        }
+        let path = directory.join("test.bin");
 
         // Save
         {
@@ -1931,15 +1928,15 @@ mod test {
 
             objects.visit("Objects", &mut visitor).unwrap();
 
-            visitor.save_binary(path).unwrap();
-            if let Ok(mut file) = File::create(Path::new("test.txt")) {
+            visitor.save_binary(&path).unwrap();
+            if let Ok(mut file) = File::create(directory.join("test.txt")) {
                 file.write_all(visitor.save_text().as_bytes()).unwrap();
             }
         }
 
         // Load
         {
-            let mut visitor = futures::executor::block_on(Visitor::load_binary(path)).unwrap();
+            let mut visitor = futures::executor::block_on(Visitor::load_binary(&path)).unwrap();
             let mut resource: Rc<Resource> = Rc::new(Default::default());
             resource.visit("SharedResource", &mut visitor).unwrap();
 
@@ -1948,6 +1945,196 @@ mod test {
         }
     }
 
+    #[test]
+    fn visitor_text_round_trip() {
+        #[derive(Default, Visit, PartialEq, Debug)]
+        struct Values {
+            flag: bool,
+            int: i32,
+            float: f32,
+            vector: Vector3<f32>,
+            matrix: Matrix4<f32>,
+            rotation: UnitQuaternion<f32>,
+            id: Uuid,
+            name: String,
+            items: Vec<Option<u16>>,
+        }
+
+        let mut values = Values {
+            flag: true,
+            int: -42,
+            float: 0.1,
+            vector: Vector3::new(1.5, -2.25, f32::MAX),
+            matrix: Matrix4::new_translation(&Vector3::new(1.0, 2.0, 3.0)),
+            rotation: Default::default(),
+            id: Uuid::from_u128(0x1234_5678_9abc_def0),
+            name: "Tricky \"name\" <with>, [special]\n\tcharacters; \u{1F600}".to_owned(),
+            items: vec![Some(1), None, Some(3)],
+        };
+        let mut pod = vec![1.0f32, 2.0, 3.0];
+        // Text data is quoted and escaped, binary data is written as base64.
+        let mut raw = b"raw <data>".to_vec();
+        let mut binary = vec![0xff, 0x00, 0x7f];
+        let mut multiline = b"a>, b\nc".to_vec();
+
+        let mut visitor = Visitor::new();
+        values.visit("Values", &mut visitor).unwrap();
+        PodVecView::from_pod_vec(&mut pod)
+            .visit("Pod", &mut visitor)
+            .unwrap();
+        Data { vec: &mut raw }.visit("Raw", &mut visitor).unwrap();
+        Data { vec: &mut binary }
+            .visit("Binary", &mut visitor)
+            .unwrap();
+        Data {
+            vec: &mut multiline,
+        }
+        .visit("Multiline", &mut visitor)
+        .unwrap();
+
+        let text = visitor.save_text();
+        let mut loaded = Visitor::load_text(&text).unwrap();
+        assert_eq!(loaded.save_text(), text);
+        assert_eq!(
+            loaded.save_binary_to_vec().unwrap(),
+            visitor.save_binary_to_vec().unwrap()
+        );
+
+        let mut loaded_values = Values::default();
+        loaded_values.visit("Values", &mut loaded).unwrap();
+        assert_eq!(loaded_values, values);
+        let mut loaded_pod = Vec::<f32>::new();
+        PodVecView::from_pod_vec(&mut loaded_pod)
+            .visit("Pod", &mut loaded)
+            .unwrap();
+        assert_eq!(loaded_pod, pod);
+        let mut loaded_raw = Vec::new();
+        Data {
+            vec: &mut loaded_raw,
+        }
+        .visit("Raw", &mut loaded)
+        .unwrap();
+        assert_eq!(loaded_raw, raw);
+        for (name, expected) in [("Binary", &binary), ("Multiline", &multiline)] {
+            let mut loaded_data = Vec::new();
+            Data {
+                vec: &mut loaded_data,
+            }
+            .visit(name, &mut loaded)
+            .unwrap();
+            assert_eq!(&loaded_data, expected);
+        }
+
+        // Malformed text.
+        assert!(Visitor::load_text("").is_err());
+        assert!(Visitor::load_text("__ROOT__[Fields=1, Children=0]: Foo<u32 = abc>, ").is_err());
+        assert!(Visitor::load_text("__ROOT__[Fields=0, Children=1]: ").is_err());
+        assert!(Visitor::load_text("__ROOT__[Fields=1, Children=0]: Foo<data = \"abc>, ").is_err());
+        assert!(
+            Visitor::load_text("__ROOT__[Fields=1, Children=0]: Foo<mat2 = 1; 0; 0; ").is_err()
+        );
+        assert!(Visitor::load_text("__ROOT__[Fields=0, Children=4294967295]: ").is_err());
+        let deep = (0..300)
+            .map(|depth| format!("{}Node[Fields=0, Children=1]: \n", "\t".repeat(depth)))
+            .collect::<String>();
+        assert!(
+            matches!(Visitor::load_text(&deep), Err(VisitError::InvalidText(msg)) if msg.contains("limit"))
+        );
+    }
+
+    #[test]
+    fn every_field_kind_text_round_trip() {
+        let kinds = || {
+            vec![
+                FieldKind::Bool(true),
+                FieldKind::U8(u8::MAX),
+                FieldKind::I8(i8::MIN),
+                FieldKind::U16(u16::MAX),
+                FieldKind::I16(i16::MIN),
+                FieldKind::U32(u32::MAX),
+                FieldKind::I32(i32::MIN),
+                FieldKind::U64(u64::MAX),
+                FieldKind::I64(i64::MIN),
+                FieldKind::F32(-0.1),
+                FieldKind::F64(std::f64::consts::PI),
+                FieldKind::UnitQuaternion(UnitQuaternion::new_unchecked(Quaternion::new(
+                    0.0, 0.0, 1.0, 0.0,
+                ))),
+                FieldKind::Matrix4(Matrix4::new_translation(&Vector3::new(1.0, -2.5, 3.0))),
+                FieldKind::Data(b"text with \"quotes\", \\ and >, \r\n\t".to_vec()),
+                FieldKind::Data(b"\xff>, \x00\n>".to_vec()),
+                FieldKind::Data(Vec::new()),
+                FieldKind::Matrix3(Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0)),
+                FieldKind::Uuid(Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef)),
+                FieldKind::UnitComplex(UnitComplex::new_unchecked(Complex::new(0.0, 1.0))),
+                FieldKind::PodArray {
+                    type_id: 8,
+                    element_size: 4,
+                    bytes: vec![0x3e, 0x2c, 0x20, 0x3e],
+                },
+                FieldKind::Matrix2(Matrix2::new(1.0, 0.5, -0.5, 1.0)),
+                FieldKind::Vector2F32(Vector2::new(1.0, -1.0)),
+                FieldKind::Vector3F32(Vector3::new(1.0, -1.0, 0.5)),
+                FieldKind::Vector4F32(Vector4::new(1.0, -1.0, 0.5, f32::MAX)),
+                FieldKind::Vector2F64(Vector2::new(1.0, -1.0)),
+                FieldKind::Vector3F64(Vector3::new(1.0, -1.0, 0.5)),
+                FieldKind::Vector4F64(Vector4::new(1.0, -1.0, 0.5, f64::MIN)),
+                FieldKind::Vector2U8(Vector2::new(1, u8::MAX)),
+                FieldKind::Vector3U8(Vector3::new(1, 2, u8::MAX)),
+                FieldKind::Vector4U8(Vector4::new(1, 2, 3, u8::MAX)),
+                FieldKind::Vector2I8(Vector2::new(-1, i8::MIN)),
+                FieldKind::Vector3I8(Vector3::new(-1, 2, i8::MIN)),
+                FieldKind::Vector4I8(Vector4::new(-1, 2, 3, i8::MIN)),
+                FieldKind::Vector2U16(Vector2::new(1, u16::MAX)),
+                FieldKind::Vector3U16(Vector3::new(1, 2, u16::MAX)),
+                FieldKind::Vector4U16(Vector4::new(1, 2, 3, u16::MAX)),
+                FieldKind::Vector2I16(Vector2::new(-1, i16::MIN)),
+                FieldKind::Vector3I16(Vector3::new(-1, 2, i16::MIN)),
+                FieldKind::Vector4I16(Vector4::new(-1, 2, 3, i16::MIN)),
+                FieldKind::Vector2U32(Vector2::new(1, u32::MAX)),
+                FieldKind::Vector3U32(Vector3::new(1, 2, u32::MAX)),
+                FieldKind::Vector4U32(Vector4::new(1, 2, 3, u32::MAX)),
+                FieldKind::Vector2I32(Vector2::new(-1, i32::MIN)),
+                FieldKind::Vector3I32(Vector3::new(-1, 2, i32::MIN)),
+                FieldKind::Vector4I32(Vector4::new(-1, 2, 3, i32::MIN)),
+                FieldKind::Vector2U64(Vector2::new(1, u64::MAX)),
+                FieldKind::Vector3U64(Vector3::new(1, 2, u64::MAX)),
+                FieldKind::Vector4U64(Vector4::new(1, 2, 3, u64::MAX)),
+                FieldKind::Vector2I64(Vector2::new(-1, i64::MIN)),
+                FieldKind::Vector3I64(Vector3::new(-1, 2, i64::MIN)),
+                FieldKind::Vector4I64(Vector4::new(-1, 2, 3, i64::MIN)),
+            ]
+        };
+
+        // Every kind is placed in the root and in a nested region, after a matrix and after data.
+        let mut visitor = Visitor::new();
+        let root = visitor.current_node;
+        let nested = visitor.nodes.spawn(VisitorNode::new("Nested", root));
+        visitor.nodes[root].children.push(nested);
+        for node in [root, nested] {
+            let fields = &mut visitor.nodes[node].fields;
+            for (i, kind) in kinds().into_iter().enumerate() {
+                let matrix = Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, 0.5, -2.0));
+                fields.push(Field::new(
+                    &format!("Matrix{}", i),
+                    FieldKind::Matrix4(matrix),
+                ));
+                let data = b"data>, with separators; ]".to_vec();
+                fields.push(Field::new(&format!("Data{}", i), FieldKind::Data(data)));
+                fields.push(Field::new(&format!("Field{}", i), kind));
+            }
+        }
+
+        let text = visitor.save_text();
+        assert_eq!(text.lines().count(), 2);
+        let loaded = Visitor::load_text(&text).unwrap();
+        assert_eq!(loaded.save_text(), text);
+        assert_eq!(
+            loaded.save_binary_to_vec().unwrap(),
+            visitor.save_binary_to_vec().unwrap()
+        );
+    }
+
     #[test]
     fn pod_vec_view_from_pod_vec() {
         // Pod for u8
@@ -2011,7 +2198,7 @@ mod test {
         );
         assert_eq!(
             FieldKind::Data(Vec::<u8>::new()).as_string(),
-            "<data = >, ".to_string()
+            "<data = \"\">, ".to_string()
         );
 
         assert_eq!(FieldKind::F32(0.0).as_string(), "<f32 = 0>, ".to_string());
@@ -2024,15 +2211,15 @@ mod test {
 
         assert_eq!(
             FieldKind::Matrix2(Matrix2::default()).as_string(),
-            "<mat2 = 0; 0; 0; 0; ".to_string()
+            "<mat2 = 0; 0; 0; 0>, ".to_string()
         );
         assert_eq!(
             FieldKind::Matrix3(Matrix3::default()).as_string(),
-            "<mat3 = 0; 0; 0; 0; 0; 0; 0; 0; 0; ".to_string()
+            "<mat3 = 0; 0; 0; 0; 0; 0; 0; 0; 0>, ".to_string()
         );
         assert_eq!(
             FieldKind::Matrix4(Matrix4::default()).as_string(),
-            "<mat4 = 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; ".to_string()
+            "<mat4 = 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0; 0>, ".to_string()
         );
         assert_eq!(
             FieldKind::PodArray {
@@ -2041,7 +2228,7 @@ mod test {
                 bytes: Vec::new()
             }
             .as_string(),
-            "<podarray = 0; 0; []>".to_string()
+            "<podarray = 0; 0; []>, ".to_string()
         );
 
         assert_eq!(FieldKind::U8(0).as_string(), "<u8 = 0>, ".to_string());
@@ -2059,7 +2246,7 @@ mod test {
         );
         assert_eq!(
             FieldKind::Uuid(Uuid::default()).as_string(),
-            "00000000-0000-0000-0000-000000000000".to_string()
+            "<uuid = 00000000-0000-0000-0000-000000000000>, ".to_string()
         );
 
         assert_eq!(
@@ -0,0 +1,356 @@
+//! Structured comparison of two visitor documents.
+//!
+//! # Overview
+//!
+//! [`VisitorDiff`] walks two visitor trees side by side and collects every region and field that
+//! was added, removed or changed. Regions are matched by their names (which are unique within the
+//! parent region), fields are matched by their names within a region. Each entry carries a path
+//! made of region names separated by `/`, for example `Scene/Graph/Pool/Item3/Name`. The root
+//! region is not included in paths.
+//!
+//! The diff is useful to debug save games (to see what exactly has changed between two saves) and
+//! as a base for scene merging tools.
+
+use crate::{
+    pool::Handle,
+    visitor::{Field, FieldKind, VisitError, Visitor, VisitorNode},
+};
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+/// Kind of a single difference between two documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffKind {
+    /// A region exists only in the new document. Its content is not listed separately.
+    RegionAdded,
+    /// A region exists only in the old document. Its content is not listed separately.
+    RegionRemoved,
+    /// A field exists only in the new document.
+    FieldAdded {
+        /// Textual representation of the new value.
+        value: String,
+    },
+    /// A field exists only in the old document.
+    FieldRemoved {
+        /// Textual representation of the old value.
+        value: String,
+    },
+    /// A field exists in both documents, but its value (or type) differs.
+    FieldChanged {
+        /// Textual representation of the old value.
+        old: String,
+        /// Textual representation of the new value.
+        new: String,
+    },
+}
+
+/// A single difference between two documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffEntry {
+    /// Path to the region or field, region names are separated by `/`.
+    pub path: String,
+    /// Kind of the difference.
+    pub kind: DiffKind,
+}
+
+impl Display for DiffEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            DiffKind::RegionAdded => write!(f, "+ {}/", self.path),
+            DiffKind::RegionRemoved => write!(f, "- {}/", self.path),
+            DiffKind::FieldAdded { value } => write!(f, "+ {} = {}", self.path, value),
+            DiffKind::FieldRemoved { value } => write!(f, "- {} = {}", self.path, value),
+            DiffKind::FieldChanged { old, new } => {
+                write!(f, "~ {}: {} -> {}", self.path, old, new)
+            }
+        }
+    }
+}
+
+/// A set of differences between two visitor documents, in the order of their appearance in the
+/// documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VisitorDiff {
+    entries: Vec<DiffEntry>,
+}
+
+impl Display for VisitorDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for entry in self.entries.iter() {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl VisitorDiff {
+    /// Compares two documents and returns every difference between them.
+    pub fn new(old: &Visitor, new: &Visitor) -> Self {
+        let mut diff = Self::default();
+        diff.diff_nodes(old, old.root, new, new.root, "");
+        diff
+    }
+
+    /// Loads two documents from binary data and compares them.
+    pub fn from_memory(old: Vec<u8>, new: Vec<u8>) -> Result<Self, VisitError> {
+        let old = Visitor::load_from_memory(old)?;
+        let new = Visitor::load_from_memory(new)?;
+        Ok(Self::new(&old, &new))
+    }
+
+    /// Loads two documents from binary files and compares them.
+    pub async fn from_files<P: AsRef<Path>>(old: P, new: P) -> Result<Self, VisitError> {
+        let old = Visitor::load_binary(old).await?;
+        let new = Visitor::load_binary(new).await?;
+        Ok(Self::new(&old, &new))
+    }
+
+    /// Returns all found differences.
+    pub fn entries(&self) -> &[DiffEntry] {
+        &self.entries
+    }
+
+    /// Returns `true` if the documents are equal.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the amount of found differences.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns an iterator over the differences whose paths start with the given prefix. Could be
+    /// used to inspect a particular part of a document, for example a single scene node.
+    pub fn entries_under<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a DiffEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.path.starts_with(prefix))
+    }
+
+    fn push(&mut self, path: String, kind: DiffKind) {
+        self.entries.push(DiffEntry { path, kind });
+    }
+
+    fn diff_nodes(
+        &mut self,
+        old: &Visitor,
+        old_handle: Handle<VisitorNode>,
+        new: &Visitor,
+        new_handle: Handle<VisitorNode>,
+        path: &str,
+    ) {
+        let old_node = old.nodes.borrow(old_handle);
+        let new_node = new.nodes.borrow(new_handle);
+
+        for old_field in old_node.fields.iter() {
+            let field_path = join_path(path, &old_field.name);
+            match new_node.fields.iter().find(|f| f.name == old_field.name) {
+                Some(new_field) => {
+                    if !kinds_equal(old_field, new_field) {
+                        self.push(
+                            field_path,
+                            DiffKind::FieldChanged {
+                                old: value_string(&old_field.kind),
+                                new: value_string(&new_field.kind),
+                            },
+                        );
+                    }
+                }
+                None => self.push(
+                    field_path,
+                    DiffKind::FieldRemoved {
+                        value: value_string(&old_field.kind),
+                    },
+                ),
+            }
+        }
+
+        for new_field in new_node.fields.iter() {
+            if !old_node.fields.iter().any(|f| f.name == new_field.name) {
+                self.push(
+                    join_path(path, &new_field.name),
+                    DiffKind::FieldAdded {
+                        value: value_string(&new_field.kind),
+                    },
+                );
+            }
+        }
+
+        for &old_child_handle in old_node.children.iter() {
+            let old_child = old.nodes.borrow(old_child_handle);
+            let child_path = join_path(path, &old_child.name);
+            match new_node
+                .children
+                .iter()
+                .find(|&&h| new.nodes.borrow(h).name == old_child.name)
+            {
+                Some(&new_child_handle) => {
+                    self.diff_nodes(old, old_child_handle, new, new_child_handle, &child_path)
+                }
+                None => self.push(child_path, DiffKind::RegionRemoved),
+            }
+        }
+
+        for &new_child_handle in new_node.children.iter() {
+            let new_child = new.nodes.borrow(new_child_handle);
+            if !old_node
+                .children
+                .iter()
+                .any(|&h| old.nodes.borrow(h).name == new_child.name)
+            {
+                self.push(join_path(path, &new_child.name), DiffKind::RegionAdded);
+            }
+        }
+    }
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}/{}", path, name)
+    }
+}
+
+// Fields are compared by their binary representation, it is exact and takes type into account.
+fn kinds_equal(a: &Field, b: &Field) -> bool {
+    let mut a_bytes = Vec::new();
+    let mut b_bytes = Vec::new();
+    match (Field::save(a, &mut a_bytes), Field::save(b, &mut b_bytes)) {
+        (Ok(_), Ok(_)) => a_bytes == b_bytes,
+        _ => false,
+    }
+}
+
+fn value_string(kind: &FieldKind) -> String {
+    kind.as_string()
+        .trim_start_matches('<')
+        .trim_end_matches(|c| matches!(c, '>' | ',' | ';' | ' '))
+        .to_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::visitor::{Visit, VisitResult};
+
+    #[derive(Default, Visit)]
+    struct Item {
+        name: String,
+        health: f32,
+    }
+
+    #[derive(Default)]
+    struct Save {
+        level: u32,
+        score: Option<u64>,
+        items: Vec<Item>,
+    }
+
+    impl Visit for Save {
+        fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+            let mut region = visitor.enter_region(name)?;
+            self.level.visit("Level", &mut region)?;
+            if let Some(score) = self.score.as_mut() {
+                score.visit("Score", &mut region)?;
+            }
+            self.items.visit("Items", &mut region)?;
+            Ok(())
+        }
+    }
+
+    fn save(mut save: Save) -> Visitor {
+        let mut visitor = Visitor::new();
+        save.visit("Save", &mut visitor).unwrap();
+        visitor
+    }
+
+    #[test]
+    fn test_equal_documents() {
+        let make = || Save {
+            level: 2,
+            score: Some(10),
+            items: vec![Item {
+                name: "Sword".to_string(),
+                health: 1.0,
+            }],
+        };
+        let diff = VisitorDiff::new(&save(make()), &save(make()));
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = save(Save {
+            level: 1,
+            score: Some(10),
+            items: vec![Item {
+                name: "Sword".to_string(),
+                health: 1.0,
+            }],
+        });
+        let new = save(Save {
+            level: 2,
+            score: None,
+            items: vec![
+                Item {
+                    name: "Sword".to_string(),
+                    health: 0.5,
+                },
+                Item {
+                    name: "Shield".to_string(),
+                    health: 1.0,
+                },
+            ],
+        });
+
+        // Round-trip through binary representation to check loading as well.
+        let diff = VisitorDiff::from_memory(
+            old.save_binary_to_vec().unwrap(),
+            new.save_binary_to_vec().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            diff.entries(),
+            &[
+                DiffEntry {
+                    path: "Save/Level".to_string(),
+                    kind: DiffKind::FieldChanged {
+                        old: "u32 = 1".to_string(),
+                        new: "u32 = 2".to_string()
+                    }
+                },
+                DiffEntry {
+                    path: "Save/Score".to_string(),
+                    kind: DiffKind::FieldRemoved {
+                        value: "u64 = 10".to_string()
+                    }
+                },
+                DiffEntry {
+                    path: "Save/Items/Length".to_string(),
+                    kind: DiffKind::FieldChanged {
+                        old: "u32 = 1".to_string(),
+                        new: "u32 = 2".to_string()
+                    }
+                },
+                DiffEntry {
+                    path: "Save/Items/Item0/ItemData/Health".to_string(),
+                    kind: DiffKind::FieldChanged {
+                        old: "f32 = 1".to_string(),
+                        new: "f32 = 0.5".to_string()
+                    }
+                },
+                DiffEntry {
+                    path: "Save/Items/Item1".to_string(),
+                    kind: DiffKind::RegionAdded
+                },
+            ]
+        );
+        assert_eq!(diff.entries_under("Save/Items/Item0").count(), 1);
+        assert_eq!(diff.to_string().lines().count(), 5);
+    }
+}
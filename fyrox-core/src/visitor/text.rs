@@ -0,0 +1,393 @@
+//! Parser of the text representation of a visitor, produced by [`Visitor::save_text`].
+//!
+//! Every node starts on a new line: its nesting level is defined by the amount of leading tabs, followed by
+//! `Name[Fields=N, Children=M]: ` header and `N` fields. Children of the node are placed right after it
+//! (recursively). Every field is written in `FieldName<type = value>, ` form, where the value is:
+//!
+//! - A single number or boolean for scalars - `FieldName<f32 = 1.5>, `.
+//! - Components separated by `; ` for vectors, quaternions, complex numbers and matrices (in column-major
+//!   order) - `FieldName<mat2 = 1; 0; 0; 1>, `.
+//! - A hyphenated uuid - `FieldName<uuid = 01234567-89ab-cdef-0123-456789abcdef>, `.
+//! - Type id, element size and base64 encoded bytes for pod arrays - `FieldName<podarray = 8; 4; [AACAPw==]>, `.
+//! - A quoted string for data, that is a valid UTF-8 text - `FieldName<data = "text">, `, and base64 encoded
+//!   bytes otherwise - `FieldName<data64 = [base64]>, `. Backslashes, quotes and line breaks of the text are
+//!   escaped (`\\`, `\"`, `\n`, `\r`, `\t`), so every node always takes exactly one line.
+
+use crate::{
+    algebra::{
+        Complex, Matrix2, Matrix3, Matrix4, Quaternion, SVector, Scalar, UnitComplex,
+        UnitQuaternion,
+    },
+    pool::{Handle, Pool},
+    visitor::{Blackboard, Field, FieldKind, VisitError, Visitor, VisitorNode},
+};
+use base64::Engine;
+use std::{fmt::Display, str::FromStr};
+use uuid::Uuid;
+
+// Maximum nesting level of nodes, the parser is recursive, so deeper text could overflow the stack.
+const MAX_DEPTH: usize = 256;
+
+// Writes data in the form described in the module docs.
+pub(super) fn data_to_string(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) => {
+            let mut out = String::with_capacity(text.len() + 12);
+            out += "<data = \"";
+            for c in text.chars() {
+                match c {
+                    '\\' => out += "\\\\",
+                    '"' => out += "\\\"",
+                    '\n' => out += "\\n",
+                    '\r' => out += "\\r",
+                    '\t' => out += "\\t",
+                    _ => out.push(c),
+                }
+            }
+            out += "\">, ";
+            out
+        }
+        Err(_) => format!(
+            "<data64 = [{}]>, ",
+            base64::engine::general_purpose::STANDARD.encode(data)
+        ),
+    }
+}
+
+pub(super) fn parse(text: &str) -> Result<Visitor, VisitError> {
+    let mut visitor = Visitor {
+        nodes: Pool::new(),
+        rc_map: Default::default(),
+        arc_map: Default::default(),
+        reading: true,
+        current_node: Handle::NONE,
+        root: Handle::NONE,
+        blackboard: Blackboard::new(),
+    };
+
+    let mut cursor = Cursor { text, rest: text };
+    visitor.root = load_node(&mut visitor, &mut cursor, 0)?;
+    visitor.current_node = visitor.root;
+
+    cursor.skip_blank_lines();
+    if !cursor.rest.is_empty() {
+        return Err(cursor.error("unexpected node outside of the root node"));
+    }
+
+    Ok(visitor)
+}
+
+fn load_node(
+    visitor: &mut Visitor,
+    cursor: &mut Cursor,
+    depth: usize,
+) -> Result<Handle<VisitorNode>, VisitError> {
+    if depth > MAX_DEPTH {
+        return Err(cursor.error(format!("nesting level exceeds the limit of {}", MAX_DEPTH)));
+    }
+
+    cursor.skip_blank_lines();
+    if cursor.rest.is_empty() {
+        return Err(cursor.error("unexpected end of text"));
+    }
+
+    let content = cursor.rest.trim_start_matches('\t');
+    let tabs = cursor.rest.len() - content.len();
+    if tabs != depth {
+        return Err(cursor.error(format!("expected nesting level {}, got {}", depth, tabs)));
+    }
+    cursor.rest = content;
+
+    let name = cursor.take_until("[Fields=")?;
+    let field_count = cursor.take_until(", Children=")?;
+    let field_count: usize = cursor.parse(field_count)?;
+    let child_count = cursor.take_until("]:")?;
+    let child_count: usize = cursor.parse(child_count)?;
+    cursor.skip_spaces();
+
+    let mut node = VisitorNode {
+        name: name.to_owned(),
+        ..VisitorNode::default()
+    };
+    for _ in 0..field_count {
+        let field = cursor.field()?;
+        node.fields.push(field);
+    }
+    if !cursor.end_line() {
+        return Err(cursor.error(format!("unexpected `{}`", cursor.current_line())));
+    }
+
+    // Every child takes at least one byte, so a huge count in malformed text can't allocate much.
+    let mut children = Vec::with_capacity(child_count.min(cursor.rest.len()));
+    for _ in 0..child_count {
+        children.push(load_node(visitor, cursor, depth + 1)?);
+    }
+
+    node.children = children.clone();
+
+    let handle = visitor.nodes.spawn(node);
+    for child_handle in children.iter() {
+        visitor.nodes.borrow_mut(*child_handle).parent = handle;
+    }
+
+    Ok(handle)
+}
+
+struct Cursor<'a> {
+    text: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn error<M: Display>(&self, message: M) -> VisitError {
+        let offset = self.text.len() - self.rest.len();
+        let line = self.text[..offset].matches('\n').count() + 1;
+        VisitError::InvalidText(format!("line {}: {}", line, message))
+    }
+
+    fn current_line(&self) -> &'a str {
+        match self.rest.find('\n') {
+            Some(index) => &self.rest[..index],
+            None => self.rest,
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        self.rest = self.rest.trim_start_matches(' ');
+    }
+
+    fn skip_blank_lines(&mut self) {
+        loop {
+            let line = self.current_line();
+            if !line.trim().is_empty() {
+                break;
+            }
+            match self.rest.get((line.len() + 1)..) {
+                Some(rest) => self.rest = rest,
+                None => {
+                    self.rest = "";
+                    break;
+                }
+            }
+        }
+    }
+
+    // Moves the cursor to the next line, returns `false` if the current line is not finished yet.
+    fn end_line(&mut self) -> bool {
+        self.skip_spaces();
+        self.eat("\r");
+        self.rest.is_empty() || self.eat("\n")
+    }
+
+    fn eat(&mut self, pattern: &str) -> bool {
+        match self.rest.strip_prefix(pattern) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, pattern: &str) -> Result<(), VisitError> {
+        if self.eat(pattern) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", pattern)))
+        }
+    }
+
+    // Returns everything up to the pattern on the current line and moves the cursor past the pattern.
+    fn take_until(&mut self, pattern: &str) -> Result<&'a str, VisitError> {
+        match self.current_line().find(pattern) {
+            Some(index) => {
+                let text = &self.rest[..index];
+                self.rest = &self.rest[(index + pattern.len())..];
+                Ok(text)
+            }
+            None => Err(self.error(format!("expected `{}`", pattern))),
+        }
+    }
+
+    fn parse<T: FromStr>(&self, text: &str) -> Result<T, VisitError> {
+        text.trim()
+            .parse()
+            .map_err(|_| self.error(format!("invalid value `{}`", text.trim())))
+    }
+
+    fn value<T: FromStr>(&mut self) -> Result<T, VisitError> {
+        let text = self.take_until(">")?;
+        self.parse(text)
+    }
+
+    fn values<T: FromStr + Default + Copy, const N: usize>(
+        &mut self,
+    ) -> Result<[T; N], VisitError> {
+        let text = self.take_until(">")?;
+        let mut parts = text.split(';').filter(|part| !part.trim().is_empty());
+        let mut values = [T::default(); N];
+        for value in values.iter_mut() {
+            match parts.next() {
+                Some(part) => *value = self.parse(part)?,
+                None => return Err(self.error(format!("expected {} values", N))),
+            }
+        }
+        if parts.next().is_some() {
+            return Err(self.error(format!("expected {} values", N)));
+        }
+        Ok(values)
+    }
+
+    fn vector<T, const N: usize>(&mut self) -> Result<SVector<T, N>, VisitError>
+    where
+        T: FromStr + Default + Copy + Scalar,
+    {
+        Ok(SVector::from_column_slice(&self.values::<T, N>()?))
+    }
+
+    fn base64(&mut self) -> Result<Vec<u8>, VisitError> {
+        self.expect("[")?;
+        let text = self.take_until("]")?;
+        base64::engine::general_purpose::STANDARD
+            .decode(text.trim())
+            .map_err(|e| self.error(e))
+    }
+
+    // Reads a quoted string with escaped characters, the opening quote must be the next character.
+    fn quoted(&mut self) -> Result<Vec<u8>, VisitError> {
+        self.expect("\"")?;
+        let mut out = String::new();
+        let mut chars = self.rest.char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[(index + 1)..];
+                    return Ok(out.into_bytes());
+                }
+                '\\' => match chars.next() {
+                    Some((_, '\\')) => out.push('\\'),
+                    Some((_, '"')) => out.push('"'),
+                    Some((_, 'n')) => out.push('\n'),
+                    Some((_, 'r')) => out.push('\r'),
+                    Some((_, 't')) => out.push('\t'),
+                    Some((next, other)) => {
+                        self.rest = &self.rest[next..];
+                        return Err(self.error(format!("invalid escape sequence `\\{}`", other)));
+                    }
+                    None => break,
+                },
+                '\n' => {
+                    self.rest = &self.rest[index..];
+                    return Err(self.error("unterminated string"));
+                }
+                _ => out.push(c),
+            }
+        }
+        self.rest = "";
+        Err(self.error("unterminated string"))
+    }
+
+    fn field(&mut self) -> Result<Field, VisitError> {
+        let name = self.take_until("<")?;
+        let kind = self.take_until("=")?.trim();
+        self.skip_spaces();
+
+        let kind = match kind {
+            "bool" => FieldKind::Bool(self.value()?),
+            "u8" => FieldKind::U8(self.value()?),
+            "i8" => FieldKind::I8(self.value()?),
+            "u16" => FieldKind::U16(self.value()?),
+            "i16" => FieldKind::I16(self.value()?),
+            "u32" => FieldKind::U32(self.value()?),
+            "i32" => FieldKind::I32(self.value()?),
+            "u64" => FieldKind::U64(self.value()?),
+            "i64" => FieldKind::I64(self.value()?),
+            "f32" => FieldKind::F32(self.value()?),
+            "f64" => FieldKind::F64(self.value()?),
+            "vec2f32" => FieldKind::Vector2F32(self.vector()?),
+            "vec3f32" => FieldKind::Vector3F32(self.vector()?),
+            "vec4f32" => FieldKind::Vector4F32(self.vector()?),
+            "vec2f64" => FieldKind::Vector2F64(self.vector()?),
+            "vec3f64" => FieldKind::Vector3F64(self.vector()?),
+            "vec4f64" => FieldKind::Vector4F64(self.vector()?),
+            "vec2i8" => FieldKind::Vector2I8(self.vector()?),
+            "vec3i8" => FieldKind::Vector3I8(self.vector()?),
+            "vec4i8" => FieldKind::Vector4I8(self.vector()?),
+            "vec2u8" => FieldKind::Vector2U8(self.vector()?),
+            "vec3u8" => FieldKind::Vector3U8(self.vector()?),
+            "vec4u8" => FieldKind::Vector4U8(self.vector()?),
+            "vec2i16" => FieldKind::Vector2I16(self.vector()?),
+            "vec3i16" => FieldKind::Vector3I16(self.vector()?),
+            "vec4i16" => FieldKind::Vector4I16(self.vector()?),
+            "vec2u16" => FieldKind::Vector2U16(self.vector()?),
+            "vec3u16" => FieldKind::Vector3U16(self.vector()?),
+            "vec4u16" => FieldKind::Vector4U16(self.vector()?),
+            "vec2i32" => FieldKind::Vector2I32(self.vector()?),
+            "vec3i32" => FieldKind::Vector3I32(self.vector()?),
+            "vec4i32" => FieldKind::Vector4I32(self.vector()?),
+            "vec2u32" => FieldKind::Vector2U32(self.vector()?),
+            "vec3u32" => FieldKind::Vector3U32(self.vector()?),
+            "vec4u32" => FieldKind::Vector4U32(self.vector()?),
+            "vec2i64" => FieldKind::Vector2I64(self.vector()?),
+            "vec3i64" => FieldKind::Vector3I64(self.vector()?),
+            "vec4i64" => FieldKind::Vector4I64(self.vector()?),
+            "vec2u64" => FieldKind::Vector2U64(self.vector()?),
+            "vec3u64" => FieldKind::Vector3U64(self.vector()?),
+            "vec4u64" => FieldKind::Vector4U64(self.vector()?),
+            "quat" => {
+                let [i, j, k, w] = self.values::<f32, 4>()?;
+                FieldKind::UnitQuaternion(UnitQuaternion::new_normalize(Quaternion::new(
+                    w, i, j, k,
+                )))
+            }
+            "complex" => {
+                let [re, im] = self.values::<f32, 2>()?;
+                FieldKind::UnitComplex(UnitComplex::from_complex(Complex::new(re, im)))
+            }
+            // Matrices are written in column-major order.
+            "mat2" => FieldKind::Matrix2(Matrix2::from_column_slice(&self.values::<f32, 4>()?)),
+            "mat3" => FieldKind::Matrix3(Matrix3::from_column_slice(&self.values::<f32, 9>()?)),
+            "mat4" => FieldKind::Matrix4(Matrix4::from_column_slice(&self.values::<f32, 16>()?)),
+            "uuid" => {
+                let text = self.take_until(">")?;
+                FieldKind::Uuid(Uuid::parse_str(text.trim()).map_err(|e| self.error(e))?)
+            }
+            "data" => {
+                self.skip_spaces();
+                let bytes = self.quoted()?;
+                self.skip_spaces();
+                self.expect(">")?;
+                FieldKind::Data(bytes)
+            }
+            "data64" => {
+                self.skip_spaces();
+                let bytes = self.base64()?;
+                self.skip_spaces();
+                self.expect(">")?;
+                FieldKind::Data(bytes)
+            }
+            "podarray" => {
+                let type_id = self.take_until(";")?;
+                let type_id = self.parse(type_id)?;
+                let element_size = self.take_until(";")?;
+                let element_size = self.parse(element_size)?;
+                self.skip_spaces();
+                let bytes = self.base64()?;
+                self.skip_spaces();
+                self.expect(">")?;
+                FieldKind::PodArray {
+                    type_id,
+                    element_size,
+                    bytes,
+                }
+            }
+            _ => return Err(self.error(format!("unknown field type `{}`", kind))),
+        };
+
+        // Trailing separator is optional, so the text could be edited by hand more freely.
+        self.eat(",");
+        self.skip_spaces();
+
+        Ok(Field::new(name, kind))
+    }
+}
@@ -31,9 +31,13 @@ impl FileSystemWatcher {
         })
     }
 
+    /// Tries to fetch next pending file system event. Errors reported by the underlying watcher are
+    /// skipped. Returns `None` if there are no more events in the queue.
     pub fn try_get_event(&self) -> Option<Event> {
-        if let Ok(Ok(evt)) = self.receiver.try_recv() {
-            return Some(evt);
+        while let Ok(result) = self.receiver.try_recv() {
+            if let Ok(evt) = result {
+                return Some(evt);
+            }
         }
         None
     }
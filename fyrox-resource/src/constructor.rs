@@ -42,6 +42,11 @@ impl ResourceConstructorContainer {
         self.map.lock().insert(type_uuid, constructor);
     }
 
+    /// Returns `true` if there's a constructor for the given type UUID.
+    pub fn contains(&self, type_uuid: &Uuid) -> bool {
+        self.map.lock().contains_key(type_uuid)
+    }
+
     /// Unregisters type constructor.
     pub fn remove(&self, type_uuid: Uuid) {
         self.map.lock().remove(&type_uuid);
@@ -0,0 +1,301 @@
+//! Custom resource types support. See [`CustomResourceLoader`] docs for more info.
+
+use crate::{
+    core::{io, log::Log, TypeUuidProvider},
+    event::ResourceEventBroadcaster,
+    loader::{BoxedLoaderFuture, ResourceLoader},
+    ResourceData, ResourceLoadError, UntypedResource,
+};
+use std::{any::Any, path::Path, sync::Arc};
+
+/// A loader of a custom resource type. It is the simplest way of adding your own resource types (for
+/// example dialogue graphs, level metadata, etc.) to the resource manager. Custom resources are
+/// loaded the same way as built-in ones: asynchronously, using the virtual file system (see
+/// [`crate::core::io`]), they're shared, could be referenced from scenes and other resources and
+/// they're reloaded automatically when their files are changed (if a file system watcher is set).
+///
+/// The loader receives the content of a file and must convert it to resource data, all the rest is
+/// done by the resource manager. Use [`crate::manager::ResourceManagerState::register_resource_type`]
+/// to register the loader.
+///
+/// ```rust
+/// use fyrox_resource::{
+///     core::{reflect::prelude::*, uuid::Uuid, visitor::prelude::*, TypeUuidProvider},
+///     custom::CustomResourceLoader,
+///     manager::ResourceManager,
+///     ResourceData,
+/// };
+/// use std::{any::Any, borrow::Cow, path::{Path, PathBuf}, string::FromUtf8Error};
+///
+/// #[derive(Debug, Default, Visit, Reflect)]
+/// struct Dialogue {
+///     path: PathBuf,
+///     lines: Vec<String>,
+/// }
+///
+/// impl TypeUuidProvider for Dialogue {
+///     fn type_uuid() -> Uuid {
+///         fyrox_resource::core::uuid::uuid!("2f8b6ee7-10f8-4ac2-9b6a-4c2b61a9e1d4")
+///     }
+/// }
+///
+/// impl ResourceData for Dialogue {
+///     fn path(&self) -> Cow<Path> {
+///         Cow::Borrowed(&self.path)
+///     }
+///
+///     fn set_path(&mut self, path: PathBuf) {
+///         self.path = path;
+///     }
+///
+///     fn as_any(&self) -> &dyn Any {
+///         self
+///     }
+///
+///     fn as_any_mut(&mut self) -> &mut dyn Any {
+///         self
+///     }
+///
+///     fn type_uuid(&self) -> Uuid {
+///         <Self as TypeUuidProvider>::type_uuid()
+///     }
+/// }
+///
+/// struct DialogueLoader;
+///
+/// impl CustomResourceLoader for DialogueLoader {
+///     type Data = Dialogue;
+///     type Error = FromUtf8Error;
+///
+///     fn extensions(&self) -> &[&str] {
+///         &["dialogue"]
+///     }
+///
+///     fn load_from_bytes(&self, path: &Path, bytes: Vec<u8>) -> Result<Dialogue, FromUtf8Error> {
+///         Ok(Dialogue {
+///             path: path.to_path_buf(),
+///             lines: String::from_utf8(bytes)?.lines().map(|l| l.to_string()).collect(),
+///         })
+///     }
+/// }
+///
+/// let resource_manager = ResourceManager::new();
+/// resource_manager.state().register_resource_type(DialogueLoader);
+/// let dialogue = resource_manager.request::<Dialogue, _>("data/intro.dialogue");
+/// ```
+pub trait CustomResourceLoader: Send + Sync + 'static {
+    /// Type of the resource data produced by the loader.
+    type Data: ResourceData + Default + TypeUuidProvider;
+
+    /// Type of the error that may occur during loading.
+    type Error: ResourceLoadError;
+
+    /// Returns a list of file extensions supported by the loader.
+    fn extensions(&self) -> &[&str];
+
+    /// Converts the content of a file at the given path to resource data. This method is called on
+    /// a loading thread (or in a JS micro-task on WebAssembly), so it could do heavy computations.
+    fn load_from_bytes(&self, path: &Path, bytes: Vec<u8>) -> Result<Self::Data, Self::Error>;
+}
+
+/// Adapts [`CustomResourceLoader`] to the [`ResourceLoader`] trait, so it could be used by the
+/// resource manager.
+pub struct CustomResourceLoaderAdapter<L> {
+    loader: Arc<L>,
+}
+
+impl<L> CustomResourceLoaderAdapter<L>
+where
+    L: CustomResourceLoader,
+{
+    /// Wraps the given custom resource loader.
+    pub fn new(loader: L) -> Self {
+        Self {
+            loader: Arc::new(loader),
+        }
+    }
+
+    /// Returns a reference to the wrapped custom resource loader.
+    pub fn loader(&self) -> &L {
+        &self.loader
+    }
+}
+
+impl<L> ResourceLoader for CustomResourceLoaderAdapter<L>
+where
+    L: CustomResourceLoader,
+{
+    fn extensions(&self) -> &[&str] {
+        self.loader.extensions()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn load(
+        &self,
+        resource: UntypedResource,
+        event_broadcaster: ResourceEventBroadcaster,
+        reload: bool,
+    ) -> BoxedLoaderFuture {
+        let loader = self.loader.clone();
+
+        Box::pin(async move {
+            let path = resource.path();
+
+            let bytes = match io::load_file(&path).await {
+                Ok(bytes) => bytes,
+                Err(error) => {
+                    Log::err(format!(
+                        "Unable to load resource from {:?}! Reason {:?}",
+                        path, error
+                    ));
+
+                    resource.commit_error(path, error);
+
+                    return;
+                }
+            };
+
+            match loader.load_from_bytes(&path, bytes) {
+                Ok(mut data) => {
+                    Log::info(format!("Resource {:?} is loaded!", path));
+
+                    data.set_path(path);
+                    resource.commit_ok(data);
+
+                    event_broadcaster.broadcast_loaded_or_reloaded(resource, reload);
+                }
+                Err(error) => {
+                    Log::err(format!(
+                        "Unable to load resource from {:?}! Reason {:?}",
+                        path, error
+                    ));
+
+                    resource.commit_error(path, error);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            futures::executor::block_on,
+            io::{self, provider::EmbeddedIoProvider},
+            reflect::prelude::*,
+            uuid::{uuid, Uuid},
+            visitor::prelude::*,
+            TypeUuidProvider,
+        },
+        custom::CustomResourceLoader,
+        manager::ResourceManager,
+        ResourceData,
+    };
+    use std::{
+        any::Any,
+        borrow::Cow,
+        path::{Path, PathBuf},
+        string::FromUtf8Error,
+        sync::Arc,
+    };
+
+    #[derive(Debug, Default, Visit, Reflect)]
+    struct Text {
+        path: PathBuf,
+        text: String,
+    }
+
+    impl TypeUuidProvider for Text {
+        fn type_uuid() -> Uuid {
+            uuid!("0d2e5f34-5f5c-4f55-9e56-5d3f3e3fb0a1")
+        }
+    }
+
+    impl ResourceData for Text {
+        fn path(&self) -> Cow<Path> {
+            Cow::Borrowed(&self.path)
+        }
+
+        fn set_path(&mut self, path: PathBuf) {
+            self.path = path;
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn type_uuid(&self) -> Uuid {
+            <Self as TypeUuidProvider>::type_uuid()
+        }
+    }
+
+    struct TextLoader;
+
+    impl CustomResourceLoader for TextLoader {
+        type Data = Text;
+        type Error = FromUtf8Error;
+
+        fn extensions(&self) -> &[&str] {
+            &["txt"]
+        }
+
+        fn load_from_bytes(&self, _path: &Path, bytes: Vec<u8>) -> Result<Text, FromUtf8Error> {
+            Ok(Text {
+                path: Default::default(),
+                text: String::from_utf8(bytes)?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_custom_resource_type() {
+        let provider = Arc::new(
+            EmbeddedIoProvider::new()
+                .with_file("hello.txt", b"Hello".as_slice())
+                .with_file("invalid.txt", [0xFFu8, 0xFE].as_slice()),
+        );
+        io::mount("custom_resource_test", provider.clone());
+
+        let resource_manager = ResourceManager::new();
+        resource_manager.state().register_resource_type(TextLoader);
+        // Second registration must replace the loader, not add a new one.
+        resource_manager.state().register_resource_type(TextLoader);
+        assert_eq!(resource_manager.state().loaders.len(), 1);
+        assert!(resource_manager
+            .state()
+            .constructors_container
+            .try_create(&<Text as TypeUuidProvider>::type_uuid())
+            .is_some());
+
+        let text = block_on(resource_manager.request::<Text, _>("custom_resource_test/hello.txt"))
+            .unwrap();
+        assert_eq!(text.data_ref().text, "Hello");
+        assert_eq!(
+            text.data_ref().path,
+            Path::new("custom_resource_test/hello.txt")
+        );
+
+        assert!(
+            block_on(resource_manager.request::<Text, _>("custom_resource_test/invalid.txt"))
+                .is_err()
+        );
+
+        io::unmount(&*provider);
+    }
+}
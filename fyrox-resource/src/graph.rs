@@ -3,6 +3,21 @@
 use crate::{collect_used_resources, state::ResourceState, untyped::UntypedResource};
 use fxhash::FxHashSet;
 
+/// Collects all resources that are directly referenced by the given resource. Only loaded resources
+/// could have dependencies, resources in any other state will have an empty set of dependencies.
+pub fn collect_direct_dependencies(resource: &UntypedResource) -> Vec<UntypedResource> {
+    let mut dependent_resources = FxHashSet::default();
+
+    let resource_state = resource.0.lock();
+    if let ResourceState::Ok(resource_data) = &*resource_state {
+        (**resource_data).as_reflect(&mut |entity| {
+            collect_used_resources(entity, &mut dependent_resources);
+        });
+    }
+
+    dependent_resources.into_iter().collect()
+}
+
 /// A node of [`ResourceDependencyGraph`].
 pub struct ResourceGraphNode {
     /// A resource associated with the graph node.
@@ -17,18 +32,8 @@ impl ResourceGraphNode {
     pub fn new(resource: &UntypedResource) -> Self {
         let mut children = Vec::new();
 
-        // Look for dependent resources.
-        let mut dependent_resources = FxHashSet::default();
-
-        let resource_state = resource.0.lock();
-        if let ResourceState::Ok(resource_data) = &*resource_state {
-            (**resource_data).as_reflect(&mut |entity| {
-                collect_used_resources(entity, &mut dependent_resources);
-            });
-        }
-
         children.extend(
-            dependent_resources
+            collect_direct_dependencies(resource)
                 .into_iter()
                 .map(|r| ResourceGraphNode::new(&r)),
         );
@@ -12,6 +12,7 @@ use crate::{
         TypeUuidProvider,
     },
     manager::ResourceManager,
+    memory::ResourceMemoryUsage,
     state::ResourceState,
     untyped::UntypedResource,
 };
@@ -33,12 +34,15 @@ use std::{
 pub use fyrox_core as core;
 
 pub mod constructor;
+pub mod custom;
 pub mod entry;
 pub mod event;
 pub mod graph;
 pub mod loader;
 pub mod manager;
+pub mod memory;
 pub mod options;
+pub mod set;
 pub mod state;
 mod task;
 pub mod untyped;
@@ -70,6 +74,12 @@ pub trait ResourceData: 'static + Debug + Visit + Send + Reflect {
 
     /// Returns unique data type id.
     fn type_uuid(&self) -> Uuid;
+
+    /// Returns an estimation of memory occupied by the resource data. It is used for memory
+    /// diagnostics and budgeting (see [`crate::memory`]), default implementation reports no memory.
+    fn memory_usage(&self) -> ResourceMemoryUsage {
+        ResourceMemoryUsage::default()
+    }
 }
 
 /// A trait for resource load error.
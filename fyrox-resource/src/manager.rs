@@ -2,14 +2,17 @@
 
 use crate::{
     constructor::ResourceConstructorContainer,
+    custom::{CustomResourceLoader, CustomResourceLoaderAdapter},
     entry::{TimedEntry, DEFAULT_RESOURCE_LIFETIME},
     event::{ResourceEvent, ResourceEventBroadcaster},
+    graph::collect_direct_dependencies,
     loader::ResourceLoadersContainer,
+    memory::{ResourceMemoryInfo, ResourceMemoryReport},
     state::ResourceState,
     task::TaskPool,
     Resource, ResourceData, UntypedResource,
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_core::{
     futures::future::join_all,
     log::Log,
@@ -21,6 +24,7 @@ use fyrox_core::{
 };
 use std::path::PathBuf;
 use std::{
+    cmp::Ordering,
     ffi::OsStr,
     fmt::{Debug, Display, Formatter},
     marker::PhantomData,
@@ -49,6 +53,69 @@ impl ResourceWaitContext {
     }
 }
 
+/// Priority of resource loading. Resources with higher priority are loaded first, resources with the
+/// same priority are loaded in the order of their requests. Priority affects only the resources that
+/// are waiting in the loading queue, already started loading cannot be interrupted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourceLoadPriority {
+    /// Lowest priority, it is useful for background streaming of resources that will be needed later.
+    Low,
+    /// Default priority of every request.
+    Normal,
+    /// Highest priority, it is useful for resources that are needed right now.
+    High,
+}
+
+impl Default for ResourceLoadPriority {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// An error, that is used as a load error of resources which loading was cancelled. See
+/// [`ResourceManager::cancel_loading`] for more info.
+#[derive(Debug)]
+pub struct LoadingCancelledError;
+
+impl Display for LoadingCancelledError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Loading was cancelled!")
+    }
+}
+
+/// Reference information of a resource in the resource manager. See
+/// [`ResourceManagerState::reference_diagnostics`] for more info.
+#[derive(Debug, Clone)]
+pub struct ResourceReferenceInfo {
+    /// Path of the resource.
+    pub path: PathBuf,
+    /// Amount of references to the resource, excluding the one held by the resource manager. It
+    /// includes references from other resources. Zero means that the resource is not used anymore
+    /// and will be unloaded soon.
+    pub use_count: usize,
+    /// Paths of the resources that are directly referenced by the resource.
+    pub dependencies: Vec<PathBuf>,
+    /// Paths of the resources in the manager that directly reference the resource.
+    pub dependents: Vec<PathBuf>,
+}
+
+// Memory budget is checked periodically, because collecting memory usage of all resources is not
+// free.
+const MEMORY_BUDGET_CHECK_INTERVAL: f32 = 1.0;
+
+impl Display for ResourceReferenceInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} (used {} times)", self.path.display(), self.use_count)?;
+        for dependency in self.dependencies.iter() {
+            writeln!(f, "\tdepends on {}", dependency.display())?;
+        }
+        for dependent in self.dependents.iter() {
+            writeln!(f, "\tused by {}", dependent.display())?;
+        }
+        Ok(())
+    }
+}
+
 /// See module docs.
 pub struct ResourceManagerState {
     /// A set of resource loaders. Use this field to register your own resource loader.
@@ -62,6 +129,8 @@ pub struct ResourceManagerState {
     resources: Vec<TimedEntry<UntypedResource>>,
     task_pool: Arc<TaskPool>,
     watcher: Option<FileSystemWatcher>,
+    memory_budget: Option<usize>,
+    memory_budget_timer: f32,
 }
 
 /// See module docs.
@@ -158,6 +227,31 @@ impl ResourceManager {
         }
     }
 
+    /// Same as [`Self::request`], but allows you to specify a priority of loading. If the resource was
+    /// already requested, but still waits in the loading queue, its priority will be raised to the given
+    /// one (it is never lowered). See [`ResourceLoadPriority`] docs for more info.
+    pub fn request_with_priority<T, P>(
+        &self,
+        path: P,
+        priority: ResourceLoadPriority,
+    ) -> Resource<T>
+    where
+        P: AsRef<Path>,
+        T: ResourceData + TypeUuidProvider,
+    {
+        let untyped = self.state().request_with_priority(
+            path,
+            <T as TypeUuidProvider>::type_uuid(),
+            priority,
+        );
+        let actual_type_uuid = untyped.type_uuid();
+        assert_eq!(actual_type_uuid, <T as TypeUuidProvider>::type_uuid());
+        Resource {
+            state: Some(untyped),
+            phantom: PhantomData::<T>,
+        }
+    }
+
     /// Same as [`Self::request`], but returns untyped resource.
     pub fn request_untyped<P>(&self, path: P, type_uuid: Uuid) -> UntypedResource
     where
@@ -166,6 +260,28 @@ impl ResourceManager {
         self.state().request(path, type_uuid)
     }
 
+    /// Same as [`Self::request_with_priority`], but returns untyped resource.
+    pub fn request_untyped_with_priority<P>(
+        &self,
+        path: P,
+        type_uuid: Uuid,
+        priority: ResourceLoadPriority,
+    ) -> UntypedResource
+    where
+        P: AsRef<Path>,
+    {
+        self.state()
+            .request_with_priority(path, type_uuid, priority)
+    }
+
+    /// Cancels loading of the given resource, if it still waits in the loading queue. The resource will
+    /// be switched to [`ResourceState::LoadError`] state with [`LoadingCancelledError`] and removed
+    /// from the resource manager, so it could be requested again later. Returns `true` if the loading
+    /// was cancelled, `false` - if the loading has already started or finished.
+    pub fn cancel_loading(&self, resource: &UntypedResource) -> bool {
+        self.state().cancel_loading(resource)
+    }
+
     /// Saves given resources in the specified path and registers it in resource manager, so
     /// it will be accessible through it later.
     pub fn register<P, F>(
@@ -218,6 +334,8 @@ impl ResourceManagerState {
             constructors_container: Default::default(),
             watcher: None,
             built_in_resources: Default::default(),
+            memory_budget: None,
+            memory_budget_timer: MEMORY_BUDGET_CHECK_INTERVAL,
         }
     }
 
@@ -229,6 +347,39 @@ impl ResourceManagerState {
         self.watcher = watcher;
     }
 
+    /// Registers a custom resource type, that will be loaded by the given loader. It adds the loader
+    /// (replacing previous loader of the same type, if any) and a constructor for the resource type,
+    /// so the resources of this type could be requested, saved in scenes and hot-reloaded the same
+    /// way as built-in ones. See [`CustomResourceLoader`] docs for more info.
+    pub fn register_resource_type<L>(&mut self, loader: L)
+    where
+        L: CustomResourceLoader,
+    {
+        let type_uuid = <L::Data as TypeUuidProvider>::type_uuid();
+        if !self.constructors_container.contains(&type_uuid) {
+            self.constructors_container.add::<L::Data>();
+        }
+
+        for extension in loader.extensions() {
+            if let Some(existing) = self.loaders.iter().find(|existing| {
+                !existing.as_any().is::<CustomResourceLoaderAdapter<L>>()
+                    && existing
+                        .extensions()
+                        .iter()
+                        .any(|ext| ext.eq_ignore_ascii_case(extension))
+            }) {
+                Log::warn(format!(
+                    "Extension {} is already handled by another resource loader ({:?}), \
+                    the first registered loader will be used!",
+                    extension,
+                    existing.extensions()
+                ));
+            }
+        }
+
+        self.loaders.set(CustomResourceLoaderAdapter::new(loader));
+    }
+
     /// Returns total amount of registered resources.
     pub fn count_registered_resources(&self) -> usize {
         self.resources.len()
@@ -286,20 +437,38 @@ impl ResourceManagerState {
             }
         });
 
+        if self.memory_budget.is_some() {
+            self.memory_budget_timer -= dt;
+            if self.memory_budget_timer <= 0.0 {
+                self.memory_budget_timer = MEMORY_BUDGET_CHECK_INTERVAL;
+                self.enforce_memory_budget();
+            }
+        }
+
         if let Some(watcher) = self.watcher.as_ref() {
-            if let Some(evt) = watcher.try_get_event() {
-                if let notify::EventKind::Modify(_) = evt.kind {
-                    for path in evt.paths {
-                        if let Ok(relative_path) = make_relative_path(path) {
-                            if self.try_reload_resource_from_path(&relative_path) {
-                                Log::info(format!(
-                                        "File {} was changed, trying to reload a respective resource...",
-                                        relative_path.display()
-                                    ));
-
-                                break;
-                            }
-                        }
+            // Collect all pending events at once, a single change of a file could produce multiple
+            // events (for example, when an editor truncates a file first and then writes new content)
+            // and there could be multiple changed files since the last update.
+            let mut changed_paths = FxHashSet::default();
+            while let Some(evt) = watcher.try_get_event() {
+                if let notify::EventKind::Modify(_) | notify::EventKind::Create(_) = evt.kind {
+                    changed_paths.extend(evt.paths);
+                }
+            }
+
+            for path in changed_paths {
+                if let Ok(mut relative_path) = make_relative_path(path) {
+                    // Import options are stored in sidecar files (see `options` module), a change of
+                    // such file must reload the resource it belongs to.
+                    if relative_path.extension() == Some(OsStr::new("options")) {
+                        relative_path.set_extension("");
+                    }
+
+                    if self.try_reload_resource_from_path(&relative_path) {
+                        Log::info(format!(
+                            "File {} was changed, trying to reload a respective resource...",
+                            relative_path.display()
+                        ));
                     }
                 }
             }
@@ -352,6 +521,178 @@ impl ResourceManagerState {
             .retain(|resource| resource.value.use_count() > 1);
     }
 
+    /// Immediately unloads every resource that is not used anywhere except the resource manager itself
+    /// and returns the amount of unloaded resources. Unlike [`Self::destroy_unused_resources`], this
+    /// method also unloads resources that become unused only after their dependents were unloaded (for
+    /// example, textures of a model that is not used anymore) and broadcasts [`ResourceEvent::Removed`]
+    /// for every unloaded resource. Resources that are still loading are kept.
+    ///
+    /// Unused resources are unloaded automatically after [`DEFAULT_RESOURCE_LIFETIME`] seconds, this
+    /// method could be used to free memory right away, for example after switching to another level.
+    /// Keep in mind, that resources that reference each other (directly or indirectly) will never be
+    /// unloaded, use [`Self::reference_diagnostics`] to find such resources.
+    pub fn unload_unused(&mut self) -> usize {
+        let mut unloaded_count = 0;
+
+        loop {
+            let mut unloaded_paths = Vec::new();
+
+            // Unloading a resource may release the last references to its dependencies, so repeat
+            // until there is nothing left to unload.
+            self.resources.retain(|resource| {
+                let state = resource.0.lock();
+                if resource.value.use_count() > 1 || matches!(*state, ResourceState::Pending { .. })
+                {
+                    true
+                } else {
+                    unloaded_paths.push(state.path().to_path_buf());
+                    false
+                }
+            });
+
+            if unloaded_paths.is_empty() {
+                break;
+            }
+
+            unloaded_count += unloaded_paths.len();
+
+            for path in unloaded_paths {
+                Log::info(format!(
+                    "Resource {} was unloaded because it is not used anymore!",
+                    path.display()
+                ));
+
+                self.event_broadcaster
+                    .broadcast(ResourceEvent::Removed(path));
+            }
+        }
+
+        unloaded_count
+    }
+
+    /// Collects reference information for every resource in the manager: how many times it is used,
+    /// which resources it depends on and which resources depend on it. This method is intended for
+    /// diagnostics only, since it is quite heavy - it uses reflection to look into the content of every
+    /// loaded resource.
+    pub fn reference_diagnostics(&self) -> Vec<ResourceReferenceInfo> {
+        // Use counts must be fetched first, dependency collection creates temporary references.
+        let mut infos = self
+            .resources
+            .iter()
+            .map(|resource| ResourceReferenceInfo {
+                path: resource.path(),
+                use_count: resource.use_count().saturating_sub(1),
+                dependencies: Default::default(),
+                dependents: Default::default(),
+            })
+            .collect::<Vec<_>>();
+
+        for (index, resource) in self.resources.iter().enumerate() {
+            let path = infos[index].path.clone();
+
+            for dependency in collect_direct_dependencies(resource) {
+                if let Some(dependency_index) = self
+                    .resources
+                    .iter()
+                    .position(|entry| entry.value == dependency)
+                {
+                    infos[dependency_index].dependents.push(path.clone());
+                }
+
+                infos[index].dependencies.push(dependency.path());
+            }
+        }
+
+        for info in infos.iter_mut() {
+            info.dependencies.sort();
+            info.dependents.sort();
+        }
+
+        infos
+    }
+
+    /// Collects memory usage of every resource in the manager. The report could be used to find
+    /// resources that consume the most memory and to get total memory usage per resource type. See
+    /// [`ResourceData::memory_usage`] for more info.
+    pub fn memory_report(&self) -> ResourceMemoryReport {
+        ResourceMemoryReport::new(
+            self.resources
+                .iter()
+                .map(|resource| ResourceMemoryInfo {
+                    path: resource.path(),
+                    type_uuid: resource.type_uuid(),
+                    usage: resource.memory_usage(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Sets memory budget (in bytes, CPU and GPU memory combined) for the resources in the manager.
+    /// When the total memory usage of resources exceeds the budget, the manager unloads resources
+    /// that are not used anywhere else, starting from the least recently used ones, without waiting
+    /// until their lifetime runs out. It is useful for streamed content, that should be kept in
+    /// memory while there's enough room for it. Resources that are still in use are never unloaded,
+    /// so the budget could be exceeded anyway. `None` disables the budget (default).
+    pub fn set_memory_budget(&mut self, budget: Option<usize>) {
+        self.memory_budget = budget;
+    }
+
+    /// Returns current memory budget, see [`Self::set_memory_budget`] for more info.
+    pub fn memory_budget(&self) -> Option<usize> {
+        self.memory_budget
+    }
+
+    /// Immediately unloads least recently used resources, that are not used anywhere else, until
+    /// total memory usage fits the memory budget. Returns the amount of unloaded resources. This
+    /// method is called automatically by [`Self::update`] once per second.
+    pub fn enforce_memory_budget(&mut self) -> usize {
+        let budget = match self.memory_budget {
+            Some(budget) => budget,
+            None => return 0,
+        };
+
+        let mut total = self.memory_report().total().total();
+        if total <= budget {
+            return 0;
+        }
+
+        // Time to live of unused resources decreases since the moment they became unused, so the
+        // least recently used resources have the smallest time to live.
+        let mut candidates = self
+            .resources
+            .iter()
+            .filter(|resource| resource.value.use_count() <= 1 && !resource.value.is_loading())
+            .map(|resource| (resource.time_to_live, resource.value.clone()))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let mut evicted = Vec::new();
+        for (_, candidate) in candidates {
+            if total <= budget {
+                break;
+            }
+            total = total.saturating_sub(candidate.memory_usage().total());
+            evicted.push(candidate);
+        }
+
+        self.resources
+            .retain(|resource| !evicted.contains(&resource.value));
+
+        for resource in evicted.iter() {
+            let path = resource.path();
+
+            Log::info(format!(
+                "Resource {} was unloaded to fit the memory budget!",
+                path.display()
+            ));
+
+            self.event_broadcaster
+                .broadcast(ResourceEvent::Removed(path));
+        }
+
+        evicted.len()
+    }
+
     /// Returns total amount of resources that still loading.
     pub fn count_pending_resources(&self) -> usize {
         self.resources.iter().fold(0, |counter, resource| {
@@ -381,24 +722,78 @@ impl ResourceManagerState {
 
     /// Tries to load a resources at a given path.
     pub fn request<P>(&mut self, path: P, type_uuid: Uuid) -> UntypedResource
+    where
+        P: AsRef<Path>,
+    {
+        self.request_with_priority(path, type_uuid, ResourceLoadPriority::Normal)
+    }
+
+    /// Tries to load a resources at a given path with the given priority. If the resource was already
+    /// requested and still waits in the loading queue, its priority will be raised to the given one.
+    pub fn request_with_priority<P>(
+        &mut self,
+        path: P,
+        type_uuid: Uuid,
+        priority: ResourceLoadPriority,
+    ) -> UntypedResource
     where
         P: AsRef<Path>,
     {
         match self.find(path.as_ref()) {
-            Some(existing) => existing.clone(),
+            Some(existing) => {
+                self.task_pool.raise_priority(existing, priority);
+                existing.clone()
+            }
             None => {
                 let resource = UntypedResource::new_pending(path.as_ref().to_owned(), type_uuid);
 
                 self.push(resource.clone());
 
-                self.try_spawn_loading_task(path.as_ref(), resource.clone(), false);
+                self.try_spawn_loading_task(path.as_ref(), resource.clone(), false, priority);
 
                 resource
             }
         }
     }
 
-    fn try_spawn_loading_task(&mut self, path: &Path, resource: UntypedResource, reload: bool) {
+    /// Cancels loading of the given resource, if it still waits in the loading queue. See
+    /// [`ResourceManager::cancel_loading`] for more info.
+    pub fn cancel_loading(&mut self, resource: &UntypedResource) -> bool {
+        if !self.task_pool.cancel(resource) {
+            return false;
+        }
+
+        let path = resource.path();
+
+        if let Some(index) = self
+            .resources
+            .iter()
+            .position(|entry| entry.value == *resource)
+        {
+            self.resources.remove(index);
+
+            self.event_broadcaster
+                .broadcast(ResourceEvent::Removed(path.clone()));
+        }
+
+        resource.commit_error(path, LoadingCancelledError);
+
+        true
+    }
+
+    /// Returns total amount of resources that are waiting in the loading queue. Unlike
+    /// [`Self::count_pending_resources`], it does not include resources which loading has already started.
+    pub fn count_queued_resources(&self) -> usize {
+        self.task_pool.queued_task_count()
+    }
+
+    fn try_spawn_loading_task(
+        &mut self,
+        path: &Path,
+        resource: UntypedResource,
+        reload: bool,
+        priority: ResourceLoadPriority,
+    ) {
         if let Some(loader) = path.extension() {
             let ext_lowercase = loader.to_ascii_lowercase();
             if let Some(loader) = self.loaders.iter().find(|loader| {
@@ -407,11 +802,9 @@ impl ResourceManagerState {
                     .iter()
                     .any(|ext| OsStr::new(ext) == ext_lowercase.as_os_str())
             }) {
-                self.task_pool.spawn_task(loader.load(
-                    resource,
-                    self.event_broadcaster.clone(),
-                    reload,
-                ));
+                let future = loader.load(resource.clone(), self.event_broadcaster.clone(), reload);
+                self.task_pool
+                    .spawn_loading_task(priority, resource, future);
 
                 return;
             }
@@ -429,7 +822,7 @@ impl ResourceManagerState {
             state.switch_to_pending_state();
             drop(state);
 
-            self.try_spawn_loading_task(&path, resource, true);
+            self.try_spawn_loading_task(&path, resource, true, ResourceLoadPriority::Normal);
         }
     }
 
@@ -470,3 +863,134 @@ impl ResourceManagerState {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
+        manager::ResourceManagerState,
+        memory::ResourceMemoryUsage,
+        ResourceData, UntypedResource,
+    };
+    use std::{
+        any::Any,
+        borrow::Cow,
+        path::{Path, PathBuf},
+    };
+
+    #[derive(Debug, Default, Visit, Reflect)]
+    struct Stub {
+        path: PathBuf,
+        dependencies: Vec<UntypedResource>,
+        #[visit(skip)]
+        size: usize,
+    }
+
+    impl ResourceData for Stub {
+        fn path(&self) -> Cow<Path> {
+            Cow::Borrowed(&self.path)
+        }
+
+        fn set_path(&mut self, path: PathBuf) {
+            self.path = path;
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn type_uuid(&self) -> Uuid {
+            Uuid::default()
+        }
+
+        fn memory_usage(&self) -> ResourceMemoryUsage {
+            ResourceMemoryUsage::new(self.size, self.size)
+        }
+    }
+
+    fn stub(path: &str, dependencies: Vec<UntypedResource>) -> UntypedResource {
+        sized_stub(path, dependencies, 0)
+    }
+
+    fn sized_stub(path: &str, dependencies: Vec<UntypedResource>, size: usize) -> UntypedResource {
+        UntypedResource::new_ok(Stub {
+            path: path.into(),
+            dependencies,
+            size,
+        })
+    }
+
+    #[test]
+    fn test_reference_diagnostics_and_unload_unused() {
+        let mut state = ResourceManagerState::new();
+
+        let texture = stub("texture.png", vec![]);
+        let model = stub("model.fbx", vec![texture.clone()]);
+        let unused = stub("unused.png", vec![]);
+
+        state.push(texture.clone());
+        state.push(model.clone());
+        state.push(unused);
+
+        drop(texture);
+
+        let diagnostics = state.reference_diagnostics();
+        assert_eq!(diagnostics.len(), 3);
+
+        let texture_info = &diagnostics[0];
+        assert_eq!(texture_info.path, Path::new("texture.png"));
+        assert_eq!(texture_info.use_count, 1);
+        assert!(texture_info.dependencies.is_empty());
+        assert_eq!(texture_info.dependents, vec![PathBuf::from("model.fbx")]);
+
+        let model_info = &diagnostics[1];
+        assert_eq!(model_info.use_count, 1);
+        assert_eq!(model_info.dependencies, vec![PathBuf::from("texture.png")]);
+        assert!(model_info.dependents.is_empty());
+
+        assert_eq!(diagnostics[2].use_count, 0);
+
+        // The model is still used, so only the unused texture must be unloaded.
+        assert_eq!(state.unload_unused(), 1);
+        assert_eq!(state.len(), 2);
+
+        // Once the model is not used anymore, its texture must be unloaded too.
+        drop(model);
+        assert_eq!(state.unload_unused(), 2);
+        assert!(state.is_empty());
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let mut state = ResourceManagerState::new();
+
+        let used = sized_stub("used.png", vec![], 100);
+        state.push(used.clone());
+        state.push(sized_stub("old.png", vec![], 100));
+        state.update(1.0);
+        state.push(sized_stub("recent.png", vec![], 100));
+
+        let report = state.memory_report();
+        assert_eq!(report.total(), ResourceMemoryUsage::new(300, 300));
+        assert_eq!(report.top_consumers(1).len(), 1);
+
+        // No budget - nothing to unload.
+        assert_eq!(state.enforce_memory_budget(), 0);
+
+        // The least recently used resource must be unloaded first.
+        state.set_memory_budget(Some(400));
+        assert_eq!(state.enforce_memory_budget(), 1);
+        assert!(state.find("old.png").is_none());
+        assert!(state.find("recent.png").is_some());
+
+        // Used resources must never be unloaded.
+        state.set_memory_budget(Some(0));
+        assert_eq!(state.enforce_memory_budget(), 1);
+        assert_eq!(state.len(), 1);
+        assert!(state.find("used.png").is_some());
+    }
+}
@@ -0,0 +1,177 @@
+//! Memory diagnostics of resources. Every resource type reports an estimation of memory that its
+//! data occupies (see [`crate::ResourceData::memory_usage`]), the resource manager collects these
+//! estimations in a [`ResourceMemoryReport`] (see
+//! [`crate::manager::ResourceManagerState::memory_report`]) and can use them to keep unused
+//! resources within a memory budget (see
+//! [`crate::manager::ResourceManagerState::set_memory_budget`]).
+
+use crate::core::uuid::Uuid;
+use fxhash::FxHashMap;
+use std::{
+    cmp::Reverse,
+    fmt::{Display, Formatter},
+    ops::{Add, AddAssign},
+    path::PathBuf,
+};
+
+/// Amount of memory (in bytes) occupied by a resource.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ResourceMemoryUsage {
+    /// Amount of memory in the RAM.
+    pub cpu: usize,
+    /// Amount of memory on the GPU. It is an estimation of memory that the renderer needs to upload
+    /// the resource to the GPU, the actual amount depends on the driver.
+    pub gpu: usize,
+}
+
+impl ResourceMemoryUsage {
+    /// Creates new memory usage.
+    pub fn new(cpu: usize, gpu: usize) -> Self {
+        Self { cpu, gpu }
+    }
+
+    /// Returns total amount of memory.
+    pub fn total(&self) -> usize {
+        self.cpu + self.gpu
+    }
+}
+
+impl Add for ResourceMemoryUsage {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            cpu: self.cpu + rhs.cpu,
+            gpu: self.gpu + rhs.gpu,
+        }
+    }
+}
+
+impl AddAssign for ResourceMemoryUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// Memory information of a single resource.
+#[derive(Clone, Debug)]
+pub struct ResourceMemoryInfo {
+    /// Path of the resource.
+    pub path: PathBuf,
+    /// Type UUID of the resource, it could be used as a category of the resource.
+    pub type_uuid: Uuid,
+    /// Amount of memory occupied by the resource.
+    pub usage: ResourceMemoryUsage,
+}
+
+/// Memory usage of every resource in the resource manager. See
+/// [`crate::manager::ResourceManagerState::memory_report`] for more info.
+#[derive(Clone, Debug, Default)]
+pub struct ResourceMemoryReport {
+    /// Memory information of every resource, sorted by total memory usage in descending order.
+    pub resources: Vec<ResourceMemoryInfo>,
+}
+
+impl ResourceMemoryReport {
+    /// Creates new report from the given set of entries.
+    pub fn new(mut resources: Vec<ResourceMemoryInfo>) -> Self {
+        resources.sort_by_key(|info| Reverse(info.usage.total()));
+        Self { resources }
+    }
+
+    /// Returns total memory usage of all resources.
+    pub fn total(&self) -> ResourceMemoryUsage {
+        self.resources
+            .iter()
+            .fold(Default::default(), |total, info| total + info.usage)
+    }
+
+    /// Returns total memory usage of resources of the given type.
+    pub fn total_of_type(&self, type_uuid: Uuid) -> ResourceMemoryUsage {
+        self.resources
+            .iter()
+            .filter(|info| info.type_uuid == type_uuid)
+            .fold(Default::default(), |total, info| total + info.usage)
+    }
+
+    /// Returns total memory usage per every resource type.
+    pub fn totals_by_type(&self) -> FxHashMap<Uuid, ResourceMemoryUsage> {
+        let mut totals = FxHashMap::<Uuid, ResourceMemoryUsage>::default();
+        for info in self.resources.iter() {
+            *totals.entry(info.type_uuid).or_default() += info.usage;
+        }
+        totals
+    }
+
+    /// Returns the given amount of resources that consume the most memory.
+    pub fn top_consumers(&self, count: usize) -> &[ResourceMemoryInfo] {
+        &self.resources[..count.min(self.resources.len())]
+    }
+}
+
+impl Display for ResourceMemoryReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let total = self.total();
+        writeln!(
+            f,
+            "Total: {} KiB (CPU), {} KiB (GPU)",
+            total.cpu / 1024,
+            total.gpu / 1024
+        )?;
+        for info in self.resources.iter() {
+            writeln!(
+                f,
+                "\t{} - {} KiB (CPU), {} KiB (GPU)",
+                info.path.display(),
+                info.usage.cpu / 1024,
+                info.usage.gpu / 1024
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_memory_report() {
+        let type_a = Uuid::from_u128(1);
+        let type_b = Uuid::from_u128(2);
+
+        let report = ResourceMemoryReport::new(vec![
+            ResourceMemoryInfo {
+                path: "a.png".into(),
+                type_uuid: type_a,
+                usage: ResourceMemoryUsage::new(10, 10),
+            },
+            ResourceMemoryInfo {
+                path: "b.png".into(),
+                type_uuid: type_a,
+                usage: ResourceMemoryUsage::new(100, 100),
+            },
+            ResourceMemoryInfo {
+                path: "c.ogg".into(),
+                type_uuid: type_b,
+                usage: ResourceMemoryUsage::new(50, 0),
+            },
+        ]);
+
+        assert_eq!(report.total(), ResourceMemoryUsage::new(160, 110));
+        assert_eq!(
+            report.total_of_type(type_a),
+            ResourceMemoryUsage::new(110, 110)
+        );
+        assert_eq!(
+            report.totals_by_type()[&type_b],
+            ResourceMemoryUsage::new(50, 0)
+        );
+
+        let top = report.top_consumers(2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].path, PathBuf::from("b.png"));
+        assert_eq!(top[1].path, PathBuf::from("c.ogg"));
+        assert_eq!(report.top_consumers(10).len(), 3);
+    }
+}
@@ -20,13 +20,19 @@ pub trait ImportOptions: Serialize + DeserializeOwned + Default + Clone {
 }
 
 /// Tries to load import settings for a resource. It is not part of ImportOptions trait because
-/// `async fn` is not yet supported for traits.
+/// `async fn` is not yet supported for traits. Import settings are stored in a "sidecar" file next to
+/// the resource, with additional `.options` extension (for example `texture.png.options`). Such files
+/// are optional, `None` is returned silently if there's no file.
 pub async fn try_get_import_settings<T>(resource_path: &Path) -> Option<T>
 where
     T: ImportOptions,
 {
     let settings_path = append_extension(resource_path, "options");
 
+    if !io::exists(&settings_path).await {
+        return None;
+    }
+
     match io::load_file(&settings_path).await {
         Ok(bytes) => match ron::de::from_bytes::<T>(&bytes) {
             Ok(options) => Some(options),
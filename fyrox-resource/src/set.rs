@@ -0,0 +1,151 @@
+//! Resource load set allows you to track loading of a group of resources as a whole. See
+//! [`ResourceLoadSet`] docs for more info.
+
+use crate::{manager::ResourceManager, state::ResourceState, UntypedResource};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Resource load set is a group of resources (possibly of different types), which loading is tracked
+/// as a whole. It could be used to build loading screens or to stream parts of a large world: the set
+/// provides aggregate loading progress, could be awaited as a single future and could be cancelled.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox_resource::{
+///     manager::{ResourceLoadPriority, ResourceManager},
+///     set::ResourceLoadSet,
+///     core::uuid::Uuid,
+/// };
+///
+/// fn begin_loading(resource_manager: &ResourceManager, type_uuid: Uuid) -> ResourceLoadSet {
+///     let mut set = ResourceLoadSet::new();
+///     for path in ["data/level.rgs", "data/music.ogg"] {
+///         set.add(resource_manager.request_untyped_with_priority(
+///             path,
+///             type_uuid,
+///             ResourceLoadPriority::High,
+///         ));
+///     }
+///     set
+/// }
+///
+/// fn update_loading_screen(set: &ResourceLoadSet) {
+///     println!("Loading... {}%", (set.progress() * 100.0) as u32);
+/// }
+/// ```
+#[must_use]
+#[derive(Default, Clone)]
+pub struct ResourceLoadSet {
+    resources: Vec<UntypedResource>,
+}
+
+impl ResourceLoadSet {
+    /// Creates a new empty load set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new resource to the set.
+    pub fn add<R>(&mut self, resource: R)
+    where
+        R: Into<UntypedResource>,
+    {
+        self.resources.push(resource.into());
+    }
+
+    /// Adds a new resource to the set and returns the set back. This method could be used to build
+    /// a set in a single chain of calls.
+    pub fn with<R>(mut self, resource: R) -> Self
+    where
+        R: Into<UntypedResource>,
+    {
+        self.add(resource);
+        self
+    }
+
+    /// Returns a slice with all resources of the set.
+    pub fn resources(&self) -> &[UntypedResource] {
+        &self.resources
+    }
+
+    /// Returns total amount of resources in the set.
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Returns `true` if the set has no resources.
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    fn count<F>(&self, func: F) -> usize
+    where
+        F: Fn(&ResourceState) -> bool,
+    {
+        self.resources
+            .iter()
+            .filter(|resource| func(&resource.0.lock()))
+            .count()
+    }
+
+    /// Returns total amount of resources that are still loading (or waiting in the loading queue).
+    pub fn count_pending(&self) -> usize {
+        self.count(|state| matches!(state, ResourceState::Pending { .. }))
+    }
+
+    /// Returns total amount of successfully loaded resources.
+    pub fn count_loaded(&self) -> usize {
+        self.count(|state| matches!(state, ResourceState::Ok(_)))
+    }
+
+    /// Returns total amount of resources that have failed to load (including the ones which loading
+    /// was cancelled).
+    pub fn count_failed(&self) -> usize {
+        self.count(|state| matches!(state, ResourceState::LoadError { .. }))
+    }
+
+    /// Returns `true` if every resource in the set is either loaded or failed to load.
+    pub fn is_finished(&self) -> bool {
+        self.count_pending() == 0
+    }
+
+    /// Returns loading progress of the set in `[0; 1]` range. Failed resources are counted as
+    /// finished, so the progress always reaches `1.0` eventually. Empty set is always fully loaded.
+    pub fn progress(&self) -> f32 {
+        if self.resources.is_empty() {
+            1.0
+        } else {
+            (self.len() - self.count_pending()) as f32 / self.len() as f32
+        }
+    }
+
+    /// Cancels loading of every resource of the set that still waits in the loading queue. Returns
+    /// the amount of cancelled resources. See [`ResourceManager::cancel_loading`] for more info.
+    pub fn cancel(&self, resource_manager: &ResourceManager) -> usize {
+        self.resources
+            .iter()
+            .filter(|resource| resource_manager.cancel_loading(resource))
+            .count()
+    }
+}
+
+impl Future for ResourceLoadSet {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        for resource in self.resources.iter() {
+            let mut resource = resource.clone();
+            if Pin::new(&mut resource).poll(cx).is_pending() {
+                // The task will be woken up once this resource is loaded, remaining resources will
+                // be checked on the next poll.
+                return Poll::Pending;
+            }
+        }
+
+        Poll::Ready(())
+    }
+}
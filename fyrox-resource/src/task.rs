@@ -1,10 +1,54 @@
 #[cfg(not(target_arch = "wasm32"))]
 use crate::core::futures::executor::ThreadPool;
-use std::future::Future;
+use crate::{
+    core::parking_lot::Mutex, loader::BoxedLoaderFuture, manager::ResourceLoadPriority,
+    UntypedResource,
+};
+use std::{cmp::Ordering, collections::BinaryHeap, sync::Arc};
 
+struct QueuedTask {
+    priority: ResourceLoadPriority,
+    // Monotonically increasing number, used to preserve FIFO order of tasks with the same priority.
+    sequence: u64,
+    resource: UntypedResource,
+    future: BoxedLoaderFuture,
+}
+
+impl PartialEq for QueuedTask {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueuedTask {}
+
+impl PartialOrd for QueuedTask {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedTask {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+#[derive(Default)]
+struct TaskQueue {
+    tasks: BinaryHeap<QueuedTask>,
+    next_sequence: u64,
+}
+
+/// Task pool executes loading tasks in the order of their priorities. Every spawned task is put in
+/// a queue first and the actual future that is spawned on the executor is a small "trampoline" that
+/// takes the most prioritized task from the queue at the moment when the executor runs it.
 pub struct TaskPool {
     #[cfg(not(target_arch = "wasm32"))]
     thread_pool: ThreadPool,
+    queue: Arc<Mutex<TaskQueue>>,
 }
 
 impl Default for TaskPool {
@@ -18,22 +62,107 @@ impl TaskPool {
         Self {
             #[cfg(not(target_arch = "wasm32"))]
             thread_pool: ThreadPool::new().unwrap(),
+            queue: Default::default(),
+        }
+    }
+
+    pub fn spawn_loading_task(
+        &self,
+        priority: ResourceLoadPriority,
+        resource: UntypedResource,
+        future: BoxedLoaderFuture,
+    ) {
+        let mut queue = self.queue.lock();
+        let sequence = queue.next_sequence;
+        queue.next_sequence += 1;
+        queue.tasks.push(QueuedTask {
+            priority,
+            sequence,
+            resource,
+            future,
+        });
+        drop(queue);
+
+        let queue = self.queue.clone();
+        let trampoline = async move {
+            // Queue lock must be released before awaiting the task.
+            let task = queue.lock().tasks.pop();
+            if let Some(task) = task {
+                task.future.await;
+            }
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        crate::core::wasm_bindgen_futures::spawn_local(trampoline);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.thread_pool.spawn_ok(trampoline);
+    }
+
+    /// Raises priority of a queued loading task of the given resource. Does nothing if the task is
+    /// not in the queue (it is already running or finished) or its priority is already higher.
+    pub fn raise_priority(&self, resource: &UntypedResource, priority: ResourceLoadPriority) {
+        let mut queue = self.queue.lock();
+        if queue
+            .tasks
+            .iter()
+            .any(|task| task.resource == *resource && task.priority < priority)
+        {
+            let mut tasks = std::mem::take(&mut queue.tasks).into_vec();
+            for task in tasks.iter_mut() {
+                if task.resource == *resource && task.priority < priority {
+                    task.priority = priority;
+                }
+            }
+            queue.tasks = BinaryHeap::from(tasks);
         }
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub fn spawn_task<F>(&self, future: F)
-    where
-        F: Future<Output = ()> + 'static,
-    {
-        crate::core::wasm_bindgen_futures::spawn_local(future);
+    /// Removes a queued loading task of the given resource. Returns `true` if the task was removed,
+    /// `false` - if the task is not in the queue (it is already running or finished).
+    pub fn cancel(&self, resource: &UntypedResource) -> bool {
+        let mut queue = self.queue.lock();
+        let count = queue.tasks.len();
+        let mut tasks = std::mem::take(&mut queue.tasks).into_vec();
+        tasks.retain(|task| task.resource != *resource);
+        queue.tasks = BinaryHeap::from(tasks);
+        queue.tasks.len() != count
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn spawn_task<F>(&self, future: F)
-    where
-        F: Future<Output = ()> + Send + 'static,
-    {
-        self.thread_pool.spawn_ok(future);
+    /// Returns total amount of tasks that are waiting in the queue.
+    pub fn queued_task_count(&self) -> usize {
+        self.queue.lock().tasks.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::uuid::Uuid;
+
+    fn task(priority: ResourceLoadPriority, sequence: u64) -> QueuedTask {
+        QueuedTask {
+            priority,
+            sequence,
+            resource: UntypedResource::new_pending(Default::default(), Uuid::default()),
+            future: Box::pin(async {}),
+        }
+    }
+
+    #[test]
+    fn test_queue_order() {
+        let mut queue = BinaryHeap::new();
+        queue.push(task(ResourceLoadPriority::Normal, 0));
+        queue.push(task(ResourceLoadPriority::Low, 1));
+        queue.push(task(ResourceLoadPriority::High, 2));
+        queue.push(task(ResourceLoadPriority::Normal, 3));
+        queue.push(task(ResourceLoadPriority::High, 4));
+
+        let order = std::iter::from_fn(|| queue.pop())
+            .map(|task| task.sequence)
+            .collect::<Vec<_>>();
+
+        // Higher priority first, FIFO within the same priority.
+        assert_eq!(order, vec![2, 4, 0, 3, 1]);
     }
 }
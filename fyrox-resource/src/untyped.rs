@@ -4,6 +4,7 @@ use crate::{
     core::{
         parking_lot::Mutex, reflect::prelude::*, uuid::Uuid, visitor::prelude::*, TypeUuidProvider,
     },
+    memory::ResourceMemoryUsage,
     state::ResourceState,
     Resource, ResourceData, ResourceLoadError,
 };
@@ -118,6 +119,15 @@ impl UntypedResource {
         }
     }
 
+    /// Returns an estimation of memory occupied by the resource data. Resources that are not loaded
+    /// do not occupy any memory.
+    pub fn memory_usage(&self) -> ResourceMemoryUsage {
+        match &*self.0.lock() {
+            ResourceState::Ok(data) => data.memory_usage(),
+            _ => ResourceMemoryUsage::default(),
+        }
+    }
+
     /// Tries to cast untyped resource to a particular type.
     pub fn try_cast<T>(&self) -> Option<Resource<T>>
     where
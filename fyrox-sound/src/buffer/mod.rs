@@ -15,7 +15,9 @@ use crate::{
 use fyrox_core::{
     io::FileLoadError, reflect::prelude::*, uuid::Uuid, visitor::prelude::*, TypeUuidProvider,
 };
-use fyrox_resource::{Resource, ResourceData, SOUND_BUFFER_RESOURCE_UUID};
+use fyrox_resource::{
+    memory::ResourceMemoryUsage, Resource, ResourceData, SOUND_BUFFER_RESOURCE_UUID,
+};
 use std::{
     any::Any,
     borrow::Cow,
@@ -104,23 +106,37 @@ pub trait RawStreamingDataSource: Iterator<Item = f32> + Send + Sync + Debug {
 }
 
 impl DataSource {
-    /// Tries to create new `File` data source from given path. May fail if file does not exists.
+    /// Tries to create new `File` data source from given path. May fail if file does not exists. If the
+    /// file is provided by an [IO provider](fyrox_core::io::provider::IoProvider) that is not backed by
+    /// the native file system (for example, a mounted pack), it will be loaded in memory entirely and
+    /// `Memory` data source will be created instead.
     pub async fn from_file<P>(path: P) -> Result<Self, FileLoadError>
     where
         P: AsRef<Path>,
     {
-        Ok(DataSource::File {
-            path: path.as_ref().to_path_buf(),
-
-            #[cfg(not(target_arch = "wasm32"))]
-            data: std::io::BufReader::new(match std::fs::File::open(path) {
-                Ok(file) => file,
-                Err(e) => return Err(FileLoadError::Io(e)),
-            }),
-
-            #[cfg(target_arch = "wasm32")]
-            data: Cursor::new(fyrox_core::io::load_file(path).await?),
-        })
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            match fyrox_core::io::native_path(path.as_ref()).await {
+                Some(native_path) => Ok(DataSource::File {
+                    path: path.as_ref().to_path_buf(),
+                    data: std::io::BufReader::new(match std::fs::File::open(native_path) {
+                        Ok(file) => file,
+                        Err(e) => return Err(FileLoadError::Io(e)),
+                    }),
+                }),
+                None => Ok(DataSource::Memory(Cursor::new(
+                    fyrox_core::io::load_file(path).await?,
+                ))),
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            Ok(DataSource::File {
+                path: path.as_ref().to_path_buf(),
+                data: Cursor::new(fyrox_core::io::load_file(path).await?),
+            })
+        }
     }
 
     /// Creates new data source from given memory block. This function does not checks if this is valid source or
@@ -275,4 +291,9 @@ impl ResourceData for SoundBuffer {
     fn type_uuid(&self) -> Uuid {
         SOUND_BUFFER_RESOURCE_UUID
     }
+
+    fn memory_usage(&self) -> ResourceMemoryUsage {
+        // Streaming buffers keep only a small portion of samples in memory.
+        ResourceMemoryUsage::new(std::mem::size_of_val(self.samples()), 0)
+    }
 }
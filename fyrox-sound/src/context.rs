@@ -32,6 +32,10 @@ use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 /// TODO: Make this configurable, for now its set to most commonly used sample rate of 44100 Hz.
 pub const SAMPLE_RATE: u32 = 44100;
 
+/// Speed of sound (in units per second), it is used to simulate the Doppler effect. It matches the speed
+/// of sound in the air, if one unit is one meter.
+pub const SPEED_OF_SOUND: f32 = 343.3;
+
 /// Distance model defines how volume of sound will decay when distance to listener changes.
 #[derive(
     Copy, Clone, Debug, Eq, PartialEq, Reflect, Visit, AsRefStr, EnumString, EnumVariantNames,
@@ -258,6 +262,7 @@ impl State {
             {
                 if let Some(bus_input_buffer) = self.bus_graph.try_get_bus_input_buffer(&source.bus)
                 {
+                    source.doppler_shift = doppler_shift(source, &self.listener);
                     source.render(output_device_buffer.len());
 
                     match self.renderer {
@@ -289,6 +294,29 @@ impl State {
     }
 }
 
+// Calculates pitch multiplier, that simulates the Doppler effect for the given source. Relative speeds
+// are clamped to the half of the speed of sound to keep the shift in reasonable range. The shift is
+// scaled by spatial blend factor, so 2D sources are not affected.
+fn doppler_shift(source: &SoundSource, listener: &Listener) -> f64 {
+    let to_listener = listener.position() - source.position();
+    let distance = to_listener.norm();
+    if distance <= f32::EPSILON {
+        return 1.0;
+    }
+    let direction = to_listener.scale(1.0 / distance);
+
+    // Positive speeds mean that the source and the listener are approaching each other.
+    let max_speed = SPEED_OF_SOUND * 0.5;
+    let source_speed = source
+        .velocity()
+        .dot(&direction)
+        .clamp(-max_speed, max_speed);
+    let listener_speed = (-listener.velocity().dot(&direction)).clamp(-max_speed, max_speed);
+    let shift = (SPEED_OF_SOUND + listener_speed) / (SPEED_OF_SOUND - source_speed);
+
+    (1.0 + (shift - 1.0) * source.spatial_blend()) as f64
+}
+
 impl SoundContext {
     /// TODO: This is magic constant that gives 1024 + 1 number when summed with
     ///       HRTF length for faster FFT calculations. Find a better way of selecting this.
@@ -134,7 +134,9 @@ impl OggDecoder {
             .seek_absgp_pg(sample_index as u64)
             .is_err()
         {
-            println!("Failed to seek vorbis/ogg, see https://github.com/RustAudio/lewton/issues/73")
+            fyrox_core::log_warn!(
+                "Failed to seek vorbis/ogg, see https://github.com/RustAudio/lewton/issues/73"
+            )
         }
     }
 
@@ -119,6 +119,8 @@ pub struct SoundSource {
     #[reflect(min_value = 0.0, step = 0.05)]
     radius: f32,
     position: Vector3<f32>,
+    #[visit(optional)]
+    velocity: Vector3<f32>,
     #[reflect(min_value = 0.0, step = 0.05)]
     max_distance: f32,
     #[reflect(min_value = 0.0, step = 0.05)]
@@ -136,6 +138,11 @@ pub struct SoundSource {
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) prev_distance_gain: Option<f32>,
+    // Pitch multiplier, that simulates the Doppler effect. It is calculated by the context on
+    // every render.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) doppler_shift: f64,
 }
 
 impl Default for SoundSource {
@@ -160,12 +167,14 @@ impl Default for SoundSource {
             prev_buffer_sample: (0.0, 0.0),
             radius: 1.0,
             position: Vector3::new(0.0, 0.0, 0.0),
+            velocity: Vector3::new(0.0, 0.0, 0.0),
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
             prev_distance_gain: None,
+            doppler_shift: 1.0,
         }
     }
 }
@@ -358,6 +367,19 @@ impl SoundSource {
         self.position
     }
 
+    /// Sets velocity of source in world space (in units per second). Velocities of the source and the
+    /// listener are used to simulate the Doppler effect, it changes pitch of the source when it is
+    /// moving relative to the listener.
+    pub fn set_velocity(&mut self, velocity: Vector3<f32>) -> &mut Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Returns velocity of source.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
     /// Sets radius of imaginable sphere around source in which no distance attenuation is applied.
     pub fn set_radius(&mut self, radius: f32) -> &mut Self {
         self.radius = radius;
@@ -544,7 +566,7 @@ impl SoundSource {
     // Renders until the end of the block or until amount samples is written and returns
     // the number of written samples.
     fn render_until_block_end(&mut self, buffer: &mut SoundBuffer, mut amount: usize) -> usize {
-        let step = self.pitch * self.resampling_multiplier;
+        let step = self.pitch * self.doppler_shift * self.resampling_multiplier;
         if step == 1.0 {
             if self.buf_read_pos < 0.0 {
                 // This can theoretically happen if we change pitch on the fly.
@@ -712,6 +734,7 @@ pub struct SoundSourceBuilder {
     playback_time: Duration,
     radius: f32,
     position: Vector3<f32>,
+    velocity: Vector3<f32>,
     max_distance: f32,
     rolloff_factor: f32,
     spatial_blend: f32,
@@ -739,6 +762,7 @@ impl SoundSourceBuilder {
             playback_time: Default::default(),
             radius: 1.0,
             position: Vector3::new(0.0, 0.0, 0.0),
+            velocity: Vector3::new(0.0, 0.0, 0.0),
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
             spatial_blend: 1.0,
@@ -818,6 +842,12 @@ impl SoundSourceBuilder {
         self
     }
 
+    /// See `set_velocity` of SoundSource.
+    pub fn with_velocity(mut self, velocity: Vector3<f32>) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
     /// See `set_radius` of SpatialSource.
     pub fn with_radius(mut self, radius: f32) -> Self {
         self.radius = radius;
@@ -856,6 +886,7 @@ impl SoundSourceBuilder {
             frame_samples: Default::default(),
             radius: self.radius,
             position: self.position,
+            velocity: self.velocity,
             max_distance: self.max_distance,
             rolloff_factor: self.rolloff_factor,
             spatial_blend: self.spatial_blend,
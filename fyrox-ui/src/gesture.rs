@@ -0,0 +1,566 @@
+//! Gesture recognition for touch screens. See [`GestureRecognizer`] docs for more info.
+
+#![warn(missing_docs)]
+
+use crate::{core::algebra::Vector2, message::TouchPhase};
+use serde::{Deserialize, Serialize};
+
+/// Phase of a continuous gesture (pan or pinch).
+#[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum GesturePhase {
+    /// The gesture was recognized.
+    Started,
+    /// Parameters of the gesture were changed.
+    Changed,
+    /// The gesture has ended, fingers were lifted or the gesture was interrupted by another one.
+    Ended,
+}
+
+/// Direction of a swipe. Screen coordinates are used, so `Up` means towards the top of the screen.
+#[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// A recognized gesture. Every position is in screen coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// A short touch without movement. Taps that follow each other quickly at the same place are
+    /// counted, so double tap is a tap with `count` equal to 2.
+    Tap {
+        /// Position of the tap.
+        position: Vector2<f32>,
+        /// Number of the taps in a series.
+        count: u32,
+    },
+    /// A finger stays on the screen without movement for a while. The finger does not produce a
+    /// tap after a long press.
+    LongPress {
+        /// Position of the finger.
+        position: Vector2<f32>,
+    },
+    /// A single finger moves on the screen.
+    Pan {
+        /// Phase of the gesture.
+        phase: GesturePhase,
+        /// Current position of the finger.
+        position: Vector2<f32>,
+        /// Movement of the finger since the previous pan event.
+        delta: Vector2<f32>,
+        /// Total movement of the finger since the beginning of the gesture.
+        translation: Vector2<f32>,
+    },
+    /// Two fingers move towards or away from each other.
+    Pinch {
+        /// Phase of the gesture.
+        phase: GesturePhase,
+        /// The middle point between the fingers.
+        center: Vector2<f32>,
+        /// Ratio between current distance between the fingers and the initial one.
+        scale: f32,
+        /// Ratio between current distance between the fingers and the distance on the previous
+        /// pinch event. It could be used to zoom a camera incrementally.
+        delta_scale: f32,
+        /// Movement of the center since the previous pinch event.
+        center_delta: Vector2<f32>,
+    },
+    /// A quick movement of a single finger in some direction. The pan gesture, that precedes every
+    /// swipe, is reported too.
+    Swipe {
+        /// Position where the finger touched the screen.
+        start: Vector2<f32>,
+        /// Dominant direction of the movement.
+        direction: SwipeDirection,
+        /// Average velocity of the finger in pixels per second.
+        velocity: Vector2<f32>,
+    },
+}
+
+/// Thresholds of the gesture recognition. Distances are in pixels, durations are in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GestureSettings {
+    /// Maximum duration of a touch, that is treated as a tap.
+    pub tap_max_duration: f32,
+    /// Maximum time between taps of a series.
+    pub multi_tap_interval: f32,
+    /// Maximum distance between taps of a series.
+    pub multi_tap_distance: f32,
+    /// A touch without movement becomes a long press after this time.
+    pub long_press_duration: f32,
+    /// A finger must move farther than this distance to start a pan. Smaller movements are
+    /// treated as a jitter of a finger, that stays at the same place.
+    pub pan_threshold: f32,
+    /// Minimum average velocity of a swipe in pixels per second.
+    pub swipe_min_velocity: f32,
+    /// Maximum duration of a swipe.
+    pub swipe_max_duration: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            tap_max_duration: 0.3,
+            multi_tap_interval: 0.3,
+            multi_tap_distance: 20.0,
+            long_press_duration: 0.5,
+            pan_threshold: 10.0,
+            swipe_min_velocity: 600.0,
+            swipe_max_duration: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Finger {
+    id: u64,
+    start_position: Vector2<f32>,
+    position: Vector2<f32>,
+    start_time: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Idle,
+    // A single finger touched the screen, it could become any single-finger gesture.
+    Pending,
+    LongPressed,
+    Panning,
+    Pinching {
+        initial_distance: f32,
+        distance: f32,
+    },
+    // Gesture was interrupted or finished, but some fingers are still on the screen.
+    Consumed,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LastTap {
+    position: Vector2<f32>,
+    time: f32,
+    count: u32,
+}
+
+/// Gesture recognizer turns raw touch events into high-level gestures: taps, long presses, pans,
+/// pinches and swipes. Only one gesture is recognized at a time: if a second finger touches the
+/// screen, current single-finger gesture ends and a pinch begins.
+///
+/// The recognizer does not measure time on its own, instead every event must be accompanied with
+/// the time (in seconds) of some monotonic clock. [`Self::update`] must be called periodically,
+/// so long presses could be recognized while a finger does not move.
+///
+/// ```rust
+/// use fyrox_ui::{
+///     core::algebra::Vector2,
+///     gesture::{Gesture, GestureRecognizer},
+///     message::TouchPhase,
+/// };
+///
+/// let mut recognizer = GestureRecognizer::default();
+/// recognizer.process_touch(0, TouchPhase::Started, Vector2::new(10.0, 10.0), 0.0);
+/// recognizer.process_touch(0, TouchPhase::Ended, Vector2::new(10.0, 10.0), 0.1);
+/// assert!(matches!(
+///     recognizer.pop_gesture(),
+///     Some(Gesture::Tap { count: 1, .. })
+/// ));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    /// Thresholds of the recognition.
+    pub settings: GestureSettings,
+    fingers: Vec<Finger>,
+    state: State,
+    last_tap: Option<LastTap>,
+    gestures: Vec<Gesture>,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl GestureRecognizer {
+    /// Creates a new recognizer with the given thresholds.
+    pub fn new(settings: GestureSettings) -> Self {
+        Self {
+            settings,
+            fingers: Default::default(),
+            state: State::Idle,
+            last_tap: None,
+            gestures: Default::default(),
+        }
+    }
+
+    /// Returns `true` if there is at least one finger on the screen.
+    pub fn is_touching(&self) -> bool {
+        !self.fingers.is_empty()
+    }
+
+    /// Returns the oldest recognized gesture, that was not taken yet.
+    pub fn pop_gesture(&mut self) -> Option<Gesture> {
+        if self.gestures.is_empty() {
+            None
+        } else {
+            Some(self.gestures.remove(0))
+        }
+    }
+
+    /// Takes every recognized gesture, that was not taken yet.
+    pub fn take_gestures(&mut self) -> Vec<Gesture> {
+        std::mem::take(&mut self.gestures)
+    }
+
+    /// Checks whether a finger stays on the screen long enough to become a long press.
+    pub fn update(&mut self, time: f32) {
+        if self.state == State::Pending {
+            if let Some(finger) = self.fingers.first() {
+                if time - finger.start_time >= self.settings.long_press_duration {
+                    self.state = State::LongPressed;
+                    self.gestures.push(Gesture::LongPress {
+                        position: finger.position,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Handles a touch event. `time` is the time of the event in seconds.
+    pub fn process_touch(&mut self, id: u64, phase: TouchPhase, position: Vector2<f32>, time: f32) {
+        self.update(time);
+
+        match phase {
+            TouchPhase::Started => self.on_started(id, position, time),
+            TouchPhase::Moved => self.on_moved(id, position),
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.on_ended(id, position, time, phase == TouchPhase::Cancelled)
+            }
+        }
+    }
+
+    fn pinch_params(&self) -> Option<(Vector2<f32>, f32)> {
+        match self.fingers.as_slice() {
+            [a, b, ..] => Some((
+                (a.position + b.position).scale(0.5),
+                (a.position - b.position).norm(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn end_current(&mut self) {
+        match self.state {
+            State::Panning => {
+                if let Some(finger) = self.fingers.first() {
+                    self.gestures.push(Gesture::Pan {
+                        phase: GesturePhase::Ended,
+                        position: finger.position,
+                        delta: Vector2::default(),
+                        translation: finger.position - finger.start_position,
+                    });
+                }
+            }
+            State::Pinching {
+                initial_distance,
+                distance,
+            } => {
+                if let Some((center, _)) = self.pinch_params() {
+                    self.gestures.push(Gesture::Pinch {
+                        phase: GesturePhase::Ended,
+                        center,
+                        scale: distance / initial_distance,
+                        delta_scale: 1.0,
+                        center_delta: Vector2::default(),
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn on_started(&mut self, id: u64, position: Vector2<f32>, time: f32) {
+        if self.fingers.iter().any(|finger| finger.id == id) {
+            return;
+        }
+
+        self.fingers.push(Finger {
+            id,
+            start_position: position,
+            position,
+            start_time: time,
+        });
+
+        match self.fingers.len() {
+            1 => self.state = State::Pending,
+            2 if self.state != State::Consumed => {
+                self.end_current();
+                if let Some((center, distance)) = self.pinch_params() {
+                    let distance = distance.max(f32::EPSILON);
+                    self.state = State::Pinching {
+                        initial_distance: distance,
+                        distance,
+                    };
+                    self.gestures.push(Gesture::Pinch {
+                        phase: GesturePhase::Started,
+                        center,
+                        scale: 1.0,
+                        delta_scale: 1.0,
+                        center_delta: Vector2::default(),
+                    });
+                }
+            }
+            // Three and more fingers are not supported.
+            _ => {
+                self.end_current();
+                self.state = State::Consumed;
+            }
+        }
+    }
+
+    fn on_moved(&mut self, id: u64, position: Vector2<f32>) {
+        let index = match self.fingers.iter().position(|finger| finger.id == id) {
+            Some(index) => index,
+            None => return,
+        };
+        let previous_center = self.pinch_params().map(|(center, _)| center);
+        let finger = &mut self.fingers[index];
+        let delta = position - finger.position;
+        finger.position = position;
+        let finger = *finger;
+
+        match self.state {
+            State::Pending
+                if (position - finger.start_position).norm() > self.settings.pan_threshold =>
+            {
+                self.state = State::Panning;
+                self.gestures.push(Gesture::Pan {
+                    phase: GesturePhase::Started,
+                    position,
+                    delta: position - finger.start_position,
+                    translation: position - finger.start_position,
+                });
+            }
+            State::Panning => {
+                self.gestures.push(Gesture::Pan {
+                    phase: GesturePhase::Changed,
+                    position,
+                    delta,
+                    translation: position - finger.start_position,
+                });
+            }
+            State::Pinching {
+                initial_distance,
+                distance: previous_distance,
+            } if index < 2 => {
+                if let (Some((center, distance)), Some(previous_center)) =
+                    (self.pinch_params(), previous_center)
+                {
+                    let distance = distance.max(f32::EPSILON);
+                    self.state = State::Pinching {
+                        initial_distance,
+                        distance,
+                    };
+                    self.gestures.push(Gesture::Pinch {
+                        phase: GesturePhase::Changed,
+                        center,
+                        scale: distance / initial_distance,
+                        delta_scale: distance / previous_distance,
+                        center_delta: center - previous_center,
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn on_ended(&mut self, id: u64, position: Vector2<f32>, time: f32, cancelled: bool) {
+        let index = match self.fingers.iter().position(|finger| finger.id == id) {
+            Some(index) => index,
+            None => return,
+        };
+        self.fingers[index].position = position;
+        let finger = self.fingers[index];
+
+        match self.state {
+            State::Pending if !cancelled => {
+                if time - finger.start_time <= self.settings.tap_max_duration {
+                    self.tap(position, time);
+                }
+            }
+            State::Panning if !cancelled => {
+                self.end_current();
+
+                let duration = time - finger.start_time;
+                let translation = position - finger.start_position;
+                if duration > 0.0 && duration <= self.settings.swipe_max_duration {
+                    let velocity = translation.scale(1.0 / duration);
+                    if velocity.norm() >= self.settings.swipe_min_velocity {
+                        let direction = if translation.x.abs() >= translation.y.abs() {
+                            if translation.x > 0.0 {
+                                SwipeDirection::Right
+                            } else {
+                                SwipeDirection::Left
+                            }
+                        } else if translation.y > 0.0 {
+                            SwipeDirection::Down
+                        } else {
+                            SwipeDirection::Up
+                        };
+
+                        self.gestures.push(Gesture::Swipe {
+                            start: finger.start_position,
+                            direction,
+                            velocity,
+                        });
+                    }
+                }
+            }
+            _ => self.end_current(),
+        }
+
+        self.fingers.remove(index);
+        self.state = if self.fingers.is_empty() {
+            State::Idle
+        } else {
+            State::Consumed
+        };
+    }
+
+    fn tap(&mut self, position: Vector2<f32>, time: f32) {
+        let count = match self.last_tap {
+            Some(last_tap)
+                if time - last_tap.time <= self.settings.multi_tap_interval
+                    && (position - last_tap.position).norm()
+                        <= self.settings.multi_tap_distance =>
+            {
+                last_tap.count + 1
+            }
+            _ => 1,
+        };
+
+        self.last_tap = Some(LastTap {
+            position,
+            time,
+            count,
+        });
+        self.gestures.push(Gesture::Tap { position, count });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        gesture::{Gesture, GesturePhase, GestureRecognizer, SwipeDirection},
+        message::TouchPhase,
+    };
+
+    #[test]
+    fn test_gesture_recognition() {
+        let mut recognizer = GestureRecognizer::default();
+        let p = Vector2::new;
+
+        // Double tap.
+        recognizer.process_touch(0, TouchPhase::Started, p(100.0, 100.0), 0.0);
+        recognizer.process_touch(0, TouchPhase::Ended, p(102.0, 100.0), 0.1);
+        recognizer.process_touch(1, TouchPhase::Started, p(105.0, 100.0), 0.2);
+        recognizer.process_touch(1, TouchPhase::Ended, p(105.0, 100.0), 0.25);
+        assert_eq!(
+            recognizer.take_gestures(),
+            vec![
+                Gesture::Tap {
+                    position: p(102.0, 100.0),
+                    count: 1
+                },
+                Gesture::Tap {
+                    position: p(105.0, 100.0),
+                    count: 2
+                }
+            ]
+        );
+
+        // Long press does not produce a tap.
+        recognizer.process_touch(2, TouchPhase::Started, p(10.0, 10.0), 1.0);
+        recognizer.update(1.2);
+        assert!(recognizer.pop_gesture().is_none());
+        recognizer.update(1.6);
+        assert_eq!(
+            recognizer.pop_gesture(),
+            Some(Gesture::LongPress {
+                position: p(10.0, 10.0)
+            })
+        );
+        recognizer.process_touch(2, TouchPhase::Ended, p(10.0, 10.0), 1.7);
+        assert!(recognizer.pop_gesture().is_none());
+
+        // Fast pan becomes a swipe.
+        recognizer.process_touch(3, TouchPhase::Started, p(0.0, 0.0), 2.0);
+        recognizer.process_touch(3, TouchPhase::Moved, p(5.0, 0.0), 2.05);
+        recognizer.process_touch(3, TouchPhase::Moved, p(50.0, 0.0), 2.1);
+        recognizer.process_touch(3, TouchPhase::Moved, p(150.0, -20.0), 2.15);
+        recognizer.process_touch(3, TouchPhase::Ended, p(150.0, -20.0), 2.2);
+        let gestures = recognizer.take_gestures();
+        assert_eq!(gestures.len(), 4);
+        assert!(matches!(
+            gestures[0],
+            Gesture::Pan {
+                phase: GesturePhase::Started,
+                ..
+            }
+        ));
+        assert_eq!(
+            gestures[1],
+            Gesture::Pan {
+                phase: GesturePhase::Changed,
+                position: p(150.0, -20.0),
+                delta: p(100.0, -20.0),
+                translation: p(150.0, -20.0),
+            }
+        );
+        assert!(matches!(
+            gestures[2],
+            Gesture::Pan {
+                phase: GesturePhase::Ended,
+                ..
+            }
+        ));
+        assert!(matches!(
+            gestures[3],
+            Gesture::Swipe {
+                direction: SwipeDirection::Right,
+                ..
+            }
+        ));
+
+        // Second finger turns the touch into a pinch.
+        recognizer.process_touch(4, TouchPhase::Started, p(100.0, 100.0), 3.0);
+        recognizer.process_touch(5, TouchPhase::Started, p(200.0, 100.0), 3.05);
+        recognizer.process_touch(5, TouchPhase::Moved, p(300.0, 100.0), 3.1);
+        recognizer.process_touch(4, TouchPhase::Ended, p(100.0, 100.0), 3.2);
+        recognizer.process_touch(5, TouchPhase::Ended, p(300.0, 100.0), 3.25);
+        let gestures = recognizer.take_gestures();
+        assert_eq!(gestures.len(), 3);
+        assert_eq!(
+            gestures[1],
+            Gesture::Pinch {
+                phase: GesturePhase::Changed,
+                center: p(200.0, 100.0),
+                scale: 2.0,
+                delta_scale: 2.0,
+                center_delta: p(50.0, 0.0),
+            }
+        );
+        assert!(matches!(
+            gestures[2],
+            Gesture::Pinch {
+                phase: GesturePhase::Ended,
+                scale,
+                ..
+            } if scale == 2.0
+        ));
+        assert!(!recognizer.is_touching());
+    }
+}
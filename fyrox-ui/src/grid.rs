@@ -164,8 +164,6 @@ crate::define_widget_deref!(Grid);
 /// particular use outside of grid's internals.
 #[derive(Clone)]
 pub struct Cell {
-    /// A set of nodes of the cell.
-    pub nodes: Vec<Handle<UiNode>>,
     /// Current width constraint of the cell.
     pub width_constraint: Option<f32>,
     /// Current height constraint of the cell.
@@ -348,18 +346,6 @@ impl Control for Grid {
                 groups[group_index(row.size_mode, column.size_mode)].push(cells.len());
 
                 cells.push(Cell {
-                    nodes: self
-                        .children()
-                        .iter()
-                        .filter_map(|&c| {
-                            let child_ref = ui.node(c);
-                            if child_ref.row() == row_index && child_ref.column() == column_index {
-                                Some(c)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect(),
                     width_constraint: choose_constraint(column, available_size.x),
                     height_constraint: choose_constraint(row, available_size.y),
                     row_index,
@@ -368,6 +354,19 @@ impl Control for Grid {
             }
         }
 
+        // Nodes of each cell are temporary, so they're stored in the frame arena to prevent heap
+        // allocations on each measurement.
+        let arena = ui.frame_arena();
+        let mut cell_nodes = arena.vec_with_capacity(cells.len());
+        for cell in cells.iter() {
+            let mut nodes = arena.vec();
+            nodes.extend(self.children().iter().cloned().filter(|&c| {
+                let child_ref = ui.node(c);
+                child_ref.row() == cell.row_index && child_ref.column() == cell.column_index
+            }));
+            cell_nodes.push(nodes.into_bump_slice());
+        }
+
         for group in groups.iter() {
             for &cell_index in group.iter() {
                 let cell = &cells[cell_index];
@@ -394,7 +393,7 @@ impl Control for Grid {
                 );
 
                 let mut cell_size = Vector2::<f32>::default();
-                for &node in cell.nodes.iter() {
+                for &node in cell_nodes[cell_index].iter() {
                     ui.measure_node(node, child_constraint);
                     let node_ref = ui.node(node);
                     let desired_size = node_ref.desired_size();
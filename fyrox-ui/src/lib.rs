@@ -197,6 +197,7 @@ pub mod dropdown_list;
 pub mod expander;
 pub mod file_browser;
 pub mod formatted_text;
+pub mod gesture;
 pub mod grid;
 pub mod image;
 pub mod inspector;
@@ -236,16 +237,19 @@ use crate::{
     canvas::Canvas,
     core::{
         algebra::{Matrix3, Vector2},
+        arena::FrameArena,
         color::Color,
+        log::Log,
         math::Rect,
         pool::{Handle, Pool},
         scope_profile,
         visitor::prelude::*,
     },
     draw::{CommandTexture, Draw, DrawingContext},
+    gesture::{GestureRecognizer, GestureSettings},
     message::{
         ButtonState, CursorIcon, KeyboardModifiers, MessageDirection, MouseButton, OsEvent,
-        UiMessage,
+        TouchPhase, UiMessage,
     },
     popup::{Placement, PopupMessage},
     ttf::{Font, FontBuilder, SharedFont},
@@ -427,24 +431,24 @@ impl NodeStatistics {
     }
 
     pub fn print_diff(&self, prev_stats: &NodeStatistics, show_unchanged: bool) {
-        println!("**** Diff UI Node Statistics ****");
+        Log::info("**** Diff UI Node Statistics ****");
         for type_name in self.unite_type_names(prev_stats) {
             let count = self.count_of(type_name);
             let prev_count = prev_stats.count_of(type_name);
             let delta = count - prev_count;
             if delta != 0 || show_unchanged {
-                println!("{}: \x1b[93m{}\x1b[0m", type_name, delta);
+                Log::info(format!("{}: {}", type_name, delta));
             }
         }
     }
 
     pub fn print_changed(&self, prev_stats: &NodeStatistics) {
-        println!("**** Changed UI Node Statistics ****");
+        Log::info("**** Changed UI Node Statistics ****");
         for type_name in self.unite_type_names(prev_stats) {
             let count = self.count_of(type_name);
             let prev_count = prev_stats.count_of(type_name);
             if count - prev_count != 0 {
-                println!("{}: \x1b[93m{}\x1b[0m", type_name, count);
+                Log::info(format!("{}: {}", type_name, count));
             }
         }
     }
@@ -571,6 +575,15 @@ pub struct UserInterface {
     pub default_font: SharedFont,
     double_click_entries: FxHashMap<MouseButton, DoubleClickEntry>,
     pub double_click_time_slice: f32,
+    // A finger, that emulates the mouse.
+    primary_touch: Option<u64>,
+    // Widgets, that were touched by fingers, that are still on the screen.
+    touch_targets: FxHashMap<u64, Handle<UiNode>>,
+    gesture_recognizer: GestureRecognizer,
+    gesture_target: Handle<UiNode>,
+    // Time since the creation of the UI, used for gesture recognition.
+    time: f32,
+    frame_arena: FrameArena,
 }
 
 fn is_on_screen(node: &UiNode, nodes: &Pool<UiNode>) -> bool {
@@ -691,6 +704,12 @@ impl UserInterface {
             default_font,
             double_click_entries: Default::default(),
             double_click_time_slice: 0.5, // 500 ms is standard in most operating systems.
+            primary_touch: None,
+            touch_targets: Default::default(),
+            gesture_recognizer: Default::default(),
+            gesture_target: Handle::NONE,
+            time: 0.0,
+            frame_arena: Default::default(),
         };
         ui.root_canvas = ui.add_node(UiNode::new(Canvas {
             widget: WidgetBuilder::new().build(),
@@ -804,6 +823,13 @@ impl UserInterface {
         self.screen_size
     }
 
+    /// Returns a reference to the frame arena of the user interface. It could be used by widgets to
+    /// allocate temporary data on layout step, see [`FrameArena`] docs for more info. The arena is
+    /// reset at the beginning of each [`Self::update`] call.
+    pub fn frame_arena(&self) -> &FrameArena {
+        &self.frame_arena
+    }
+
     pub fn set_screen_size(&mut self, screen_size: Vector2<f32>) {
         self.screen_size = screen_size;
     }
@@ -845,12 +871,18 @@ impl UserInterface {
     pub fn update(&mut self, screen_size: Vector2<f32>, dt: f32) {
         scope_profile!();
 
+        self.frame_arena.reset();
+
         self.screen_size = screen_size;
+        self.time += dt;
 
         for entry in self.double_click_entries.values_mut() {
             entry.timer -= dt;
         }
 
+        self.gesture_recognizer.update(self.time);
+        self.send_gestures();
+
         self.handle_layout_events();
 
         self.measure_node(self.root_canvas, screen_size);
@@ -1779,6 +1811,28 @@ impl UserInterface {
         }
     }
 
+    fn send_gestures(&mut self) {
+        for gesture in self.gesture_recognizer.take_gestures() {
+            if self.nodes.is_valid_handle(self.gesture_target) {
+                self.send_message(WidgetMessage::gesture(
+                    self.gesture_target,
+                    MessageDirection::FromWidget,
+                    gesture,
+                ));
+            }
+        }
+    }
+
+    /// Returns current thresholds of the gesture recognition.
+    pub fn gesture_settings(&self) -> &GestureSettings {
+        &self.gesture_recognizer.settings
+    }
+
+    /// Sets new thresholds of the gesture recognition.
+    pub fn set_gesture_settings(&mut self, settings: GestureSettings) {
+        self.gesture_recognizer.settings = settings;
+    }
+
     fn reset_double_click_entries(&mut self) {
         for entry in self.double_click_entries.values_mut() {
             entry.timer = self.double_click_time_slice;
@@ -2066,6 +2120,72 @@ impl UserInterface {
                 // TODO: Is message needed for focused node?
                 self.keyboard_modifiers = modifiers;
             }
+            &OsEvent::Touch {
+                id,
+                phase,
+                location,
+            } => {
+                let target = if phase == TouchPhase::Started {
+                    let target = self.hit_test(location);
+                    // Gestures are sent to a widget under the first finger.
+                    if !self.gesture_recognizer.is_touching() {
+                        self.gesture_target = target;
+                    }
+                    self.touch_targets.insert(id, target);
+                    target
+                } else if matches!(phase, TouchPhase::Ended | TouchPhase::Cancelled) {
+                    self.touch_targets.remove(&id).unwrap_or_default()
+                } else {
+                    self.touch_targets.get(&id).cloned().unwrap_or_default()
+                };
+
+                if target.is_some() {
+                    let constructor = match phase {
+                        TouchPhase::Started => WidgetMessage::touch_started,
+                        TouchPhase::Moved => WidgetMessage::touch_moved,
+                        TouchPhase::Ended => WidgetMessage::touch_ended,
+                        TouchPhase::Cancelled => WidgetMessage::touch_cancelled,
+                    };
+                    self.send_message(constructor(
+                        target,
+                        MessageDirection::FromWidget,
+                        location,
+                        id,
+                    ));
+                    event_processed = true;
+                }
+
+                self.gesture_recognizer
+                    .process_touch(id, phase, location, self.time);
+                self.send_gestures();
+
+                // The first finger emulates the left mouse button, so every widget could be used
+                // on touch screens. Other fingers are ignored until the first one is lifted.
+                if self.primary_touch.is_none() && phase == TouchPhase::Started {
+                    self.primary_touch = Some(id);
+                }
+
+                if self.primary_touch == Some(id) {
+                    event_processed |=
+                        self.process_os_event(&OsEvent::CursorMoved { position: location });
+
+                    let state = match phase {
+                        TouchPhase::Started => Some(ButtonState::Pressed),
+                        TouchPhase::Moved => None,
+                        TouchPhase::Ended | TouchPhase::Cancelled => {
+                            self.primary_touch = None;
+                            Some(ButtonState::Released)
+                        }
+                    };
+
+                    if let Some(state) = state {
+                        event_processed |= self.process_os_event(&OsEvent::MouseInput {
+                            button: MouseButton::Left,
+                            state,
+                        });
+                    }
+                }
+            }
         }
 
         self.prev_picked_node = self.picked_node;
@@ -2499,15 +2619,21 @@ fn transform_size(transform_space_bounds: Vector2<f32>, matrix: &Matrix3<f32>) -
 
 #[cfg(test)]
 mod test {
-    use crate::message::{ButtonState, KeyCode};
     use crate::{
         border::BorderBuilder,
-        core::algebra::{Rotation2, UnitComplex, Vector2},
+        core::{
+            algebra::{Rotation2, UnitComplex, Vector2},
+            pool::Handle,
+        },
         message::MessageDirection,
         text_box::TextBoxBuilder,
         transform_size,
         widget::{WidgetBuilder, WidgetMessage},
-        OsEvent, UserInterface,
+        OsEvent, UiNode, UserInterface,
+    };
+    use crate::{
+        gesture::{Gesture, GesturePhase},
+        message::{ButtonState, KeyCode, MouseButton, TouchPhase},
     };
 
     #[test]
@@ -2594,4 +2720,122 @@ mod test {
 
         assert!(ui.poll_message().is_none());
     }
+
+    #[test]
+    fn test_touch_emulates_mouse() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+        let border = BorderBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        // Hit test uses drawing commands of the widgets.
+        ui.draw();
+        while ui.poll_message().is_some() {}
+
+        fn mouse_buttons(
+            ui: &mut UserInterface,
+            border: Handle<UiNode>,
+        ) -> Vec<(bool, MouseButton)> {
+            let mut events = Vec::new();
+            while let Some(message) = ui.poll_message() {
+                if message.destination() != border {
+                    continue;
+                }
+                match message.data() {
+                    Some(&WidgetMessage::MouseDown { button, .. }) => events.push((true, button)),
+                    Some(&WidgetMessage::MouseUp { button, .. }) => events.push((false, button)),
+                    _ => (),
+                }
+            }
+            events
+        }
+
+        let touch = |id, phase, location| OsEvent::Touch {
+            id,
+            phase,
+            location,
+        };
+
+        ui.process_os_event(&touch(1, TouchPhase::Started, Vector2::new(50.0, 50.0)));
+        assert_eq!(
+            mouse_buttons(&mut ui, border),
+            vec![(true, MouseButton::Left)]
+        );
+
+        // The second finger does not interfere with the first one.
+        ui.process_os_event(&touch(2, TouchPhase::Started, Vector2::new(500.0, 500.0)));
+        ui.process_os_event(&touch(2, TouchPhase::Ended, Vector2::new(500.0, 500.0)));
+        assert!(mouse_buttons(&mut ui, border).is_empty());
+
+        ui.process_os_event(&touch(1, TouchPhase::Moved, Vector2::new(60.0, 60.0)));
+        ui.process_os_event(&touch(1, TouchPhase::Ended, Vector2::new(60.0, 60.0)));
+        assert_eq!(
+            mouse_buttons(&mut ui, border),
+            vec![(false, MouseButton::Left)]
+        );
+    }
+
+    #[test]
+    fn test_touch_routing_and_gestures() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+        let border = BorderBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        while ui.poll_message().is_some() {}
+
+        let touch = |id, phase, location| OsEvent::Touch {
+            id,
+            phase,
+            location,
+        };
+
+        // A finger that leaves the bounds of the widget, is still routed to it.
+        ui.process_os_event(&touch(1, TouchPhase::Started, Vector2::new(50.0, 50.0)));
+        ui.process_os_event(&touch(1, TouchPhase::Moved, Vector2::new(300.0, 50.0)));
+        ui.process_os_event(&touch(1, TouchPhase::Ended, Vector2::new(300.0, 50.0)));
+
+        let mut touches = Vec::new();
+        let mut gestures = Vec::new();
+        while let Some(message) = ui.poll_message() {
+            if message.destination() != border {
+                continue;
+            }
+            match message.data() {
+                Some(WidgetMessage::TouchStarted { pos, id })
+                | Some(WidgetMessage::TouchMoved { pos, id })
+                | Some(WidgetMessage::TouchEnded { pos, id }) => touches.push((*id, pos.x)),
+                Some(WidgetMessage::Gesture(gesture)) => gestures.push(*gesture),
+                _ => (),
+            }
+        }
+        assert_eq!(touches, vec![(1, 50.0), (1, 300.0), (1, 300.0)]);
+        assert!(matches!(
+            gestures.as_slice(),
+            [
+                Gesture::Pan {
+                    phase: GesturePhase::Started,
+                    ..
+                },
+                Gesture::Pan {
+                    phase: GesturePhase::Ended,
+                    ..
+                }
+            ]
+        ));
+
+        // Long press is recognized by the clock of the UI.
+        ui.process_os_event(&touch(2, TouchPhase::Started, Vector2::new(50.0, 50.0)));
+        ui.update(screen_size, 1.0);
+        let mut long_press = false;
+        while let Some(message) = ui.poll_message() {
+            if message.destination() == border {
+                if let Some(WidgetMessage::Gesture(Gesture::LongPress { .. })) = message.data() {
+                    long_press = true;
+                }
+            }
+        }
+        assert!(long_press);
+    }
 }
@@ -397,7 +397,7 @@ pub enum ButtonState {
 }
 
 /// A set of possible mouse buttons.
-#[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum MouseButton {
     /// Left mouse button.
     Left,
@@ -440,6 +440,28 @@ pub enum OsEvent {
     KeyboardModifiers(KeyboardModifiers),
     /// Mouse wheel event, with a tuple that stores the (x, y) offsets.
     MouseWheel(f32, f32),
+    /// Touch event of a touch screen.
+    Touch {
+        /// Unique identifier of a finger, it stays the same until the finger is lifted.
+        id: u64,
+        /// Phase of the touch.
+        phase: TouchPhase,
+        /// Position of the finger in screen coordinates.
+        location: Vector2<f32>,
+    },
+}
+
+/// Phase of a touch.
+#[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum TouchPhase {
+    /// A finger touched the screen.
+    Started,
+    /// A finger moved on the screen.
+    Moved,
+    /// A finger was lifted from the screen.
+    Ended,
+    /// The system cancelled tracking of the finger (for example, because of an incoming call).
+    Cancelled,
 }
 
 /// A set of possible keyboard modifiers.
@@ -7,6 +7,7 @@ use crate::{
     brush::Brush,
     core::{algebra::Vector2, math::Rect, pool::Handle},
     define_constructor,
+    gesture::Gesture,
     message::{CursorIcon, KeyCode, MessageDirection, UiMessage},
     HorizontalAlignment, LayoutEvent, MouseButton, MouseState, RcUiNodeHandle, Thickness, UiNode,
     UserInterface, VerticalAlignment, BRUSH_FOREGROUND, BRUSH_PRIMARY,
@@ -299,6 +300,53 @@ pub enum WidgetMessage {
     /// A request to set new tooltip for a widget. Old tooltip will be removed only if its reference
     /// counter was 1.
     Tooltip(Option<RcUiNodeHandle>),
+
+    /// Initiated when a finger touches widget's geometry.
+    ///
+    /// Direction: **From UI**.
+    TouchStarted {
+        /// Position of the finger in screen coordinates.
+        pos: Vector2<f32>,
+        /// Unique identifier of the finger.
+        id: u64,
+    },
+
+    /// Initiated when a finger, that touched widget's geometry, moves. The message is sent to the
+    /// same widget until the finger is lifted, even if the finger is outside of widget's bounds.
+    ///
+    /// Direction: **From UI**.
+    TouchMoved {
+        /// Position of the finger in screen coordinates.
+        pos: Vector2<f32>,
+        /// Unique identifier of the finger.
+        id: u64,
+    },
+
+    /// Initiated when a finger, that touched widget's geometry, is lifted.
+    ///
+    /// Direction: **From UI**.
+    TouchEnded {
+        /// Position of the finger in screen coordinates.
+        pos: Vector2<f32>,
+        /// Unique identifier of the finger.
+        id: u64,
+    },
+
+    /// Initiated when a touch of widget's geometry was interrupted by the OS.
+    ///
+    /// Direction: **From UI**.
+    TouchCancelled {
+        /// Position of the finger in screen coordinates.
+        pos: Vector2<f32>,
+        /// Unique identifier of the finger.
+        id: u64,
+    },
+
+    /// Initiated when a gesture was recognized on a widget. Gestures are sent to a widget under the
+    /// first finger of a gesture. See [`crate::gesture`] module docs for more info.
+    ///
+    /// Direction: **From UI**.
+    Gesture(Gesture),
 }
 
 impl WidgetMessage {
@@ -545,6 +593,36 @@ impl WidgetMessage {
         /// be used anywhere else.
         WidgetMessage:DoubleClick => fn double_click(button: MouseButton), layout: false
     );
+
+    define_constructor!(
+        /// Creates [`WidgetMessage::TouchStarted`] message. This method is for internal use only, and should not
+        /// be used anywhere else.
+        WidgetMessage:TouchStarted => fn touch_started(pos: Vector2<f32>, id: u64), layout: false
+    );
+
+    define_constructor!(
+        /// Creates [`WidgetMessage::TouchMoved`] message. This method is for internal use only, and should not
+        /// be used anywhere else.
+        WidgetMessage:TouchMoved => fn touch_moved(pos: Vector2<f32>, id: u64), layout: false
+    );
+
+    define_constructor!(
+        /// Creates [`WidgetMessage::TouchEnded`] message. This method is for internal use only, and should not
+        /// be used anywhere else.
+        WidgetMessage:TouchEnded => fn touch_ended(pos: Vector2<f32>, id: u64), layout: false
+    );
+
+    define_constructor!(
+        /// Creates [`WidgetMessage::TouchCancelled`] message. This method is for internal use only, and should not
+        /// be used anywhere else.
+        WidgetMessage:TouchCancelled => fn touch_cancelled(pos: Vector2<f32>, id: u64), layout: false
+    );
+
+    define_constructor!(
+        /// Creates [`WidgetMessage::Gesture`] message. This method is for internal use only, and should not
+        /// be used anywhere else.
+        WidgetMessage:Gesture => fn gesture(Gesture), layout: false
+    );
 }
 
 /// Widget is a base UI element, that is always used to build derived, more complex, widgets. In general, it is a container
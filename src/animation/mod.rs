@@ -950,6 +950,22 @@ impl AnimationContainer {
         }
     }
 
+    /// Advances all enabled animations in the container by the given time. Unlike [`Self::update_animations`],
+    /// this method does not touch the scene graph, so multiple containers could be updated in parallel. This
+    /// method is intended to be used only by the internals of the engine!
+    pub fn tick_animations(&mut self, dt: f32) {
+        for animation in self.pool.iter_mut().filter(|anim| anim.enabled) {
+            animation.tick(dt);
+        }
+    }
+
+    /// Applies current poses of all enabled animations in the container to respective nodes.
+    pub(crate) fn apply_poses(&self, nodes: &mut NodePool) {
+        for animation in self.pool.iter().filter(|anim| anim.enabled) {
+            animation.pose.apply_internal(nodes);
+        }
+    }
+
     /// Removes queued animation events from every animation in the container.
     ///
     /// # Potential use cases
@@ -5,8 +5,12 @@
 
 use crate::{
     animation::spritesheet::signal::Signal,
+    asset::ResourceStateRef,
     core::{algebra::Vector2, math::Rect, reflect::prelude::*, visitor::prelude::*},
-    resource::texture::TextureResource,
+    resource::{
+        spritesheet::{SpriteSheet, SpriteSheetAnimationDefinition, SpriteSheetResource},
+        texture::TextureResource,
+    },
 };
 use std::collections::vec_deque::VecDeque;
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
@@ -129,6 +133,10 @@ impl Default for SpriteSheetFramesContainer {
 
 /// Sprite sheet animation is an animation based on key frames, where each key frame is packed into single image. Usually, all key
 /// frames have the same size, but this is not mandatory.
+///
+/// The animation could either use its own grid of frames (see [`Self::frames`]), or play an animation of a
+/// [`SpriteSheet`] resource (see [`Self::new_from_sprite_sheet`]). In the latter case frames could have arbitrary
+/// sizes and pivots.
 #[derive(Visit, Reflect, Clone, Debug)]
 pub struct SpriteSheetAnimation {
     #[visit(rename = "Frames")]
@@ -144,6 +152,10 @@ pub struct SpriteSheetAnimation {
     #[reflect(hidden)]
     #[visit(skip)]
     events: VecDeque<Event>,
+    #[visit(optional)]
+    sprite_sheet: Option<SpriteSheetResource>,
+    #[visit(optional)]
+    sprite_sheet_animation: String,
 }
 
 impl Default for SpriteSheetAnimation {
@@ -157,6 +169,8 @@ impl Default for SpriteSheetAnimation {
             signals: Default::default(),
             texture: None,
             events: Default::default(),
+            sprite_sheet: None,
+            sprite_sheet_animation: Default::default(),
         }
     }
 }
@@ -296,6 +310,67 @@ impl SpriteSheetAnimation {
         }
     }
 
+    /// Creates new animation that plays an animation with the given name from the sprite sheet. Playback speed and
+    /// looping are taken from the animation definition, if the sprite sheet is already loaded.
+    pub fn new_from_sprite_sheet(sprite_sheet: SpriteSheetResource, animation: &str) -> Self {
+        let mut result = Self::default();
+        result.set_sprite_sheet(Some(sprite_sheet), animation);
+        result
+    }
+
+    /// Sets a sprite sheet and the name of its animation to play. When the sprite sheet is set, the frames container of the
+    /// animation is ignored. Playback speed and looping are taken from the animation definition, if the sprite sheet is
+    /// already loaded.
+    pub fn set_sprite_sheet(&mut self, sprite_sheet: Option<SpriteSheetResource>, animation: &str) {
+        self.sprite_sheet = sprite_sheet;
+        self.sprite_sheet_animation = animation.to_owned();
+        let playback = self
+            .with_sprite_sheet_animation(|_, definition| (definition.speed, definition.looping));
+        if let Some((speed, looping)) = playback {
+            self.speed = speed;
+            self.looping = looping;
+        }
+        self.current_frame = self
+            .current_frame
+            .min(self.frame_count().saturating_sub(1) as f32);
+    }
+
+    /// Returns current sprite sheet of the animation.
+    pub fn sprite_sheet(&self) -> Option<SpriteSheetResource> {
+        self.sprite_sheet.clone()
+    }
+
+    /// Returns the name of the sprite sheet animation that is played.
+    pub fn sprite_sheet_animation(&self) -> &str {
+        &self.sprite_sheet_animation
+    }
+
+    fn with_sprite_sheet_animation<F, R>(&self, func: F) -> Option<R>
+    where
+        F: FnOnce(&SpriteSheet, &SpriteSheetAnimationDefinition) -> R,
+    {
+        let sprite_sheet = self.sprite_sheet.as_ref()?;
+        let state = sprite_sheet.state();
+        if let ResourceStateRef::Ok(sprite_sheet) = state.get() {
+            sprite_sheet
+                .animation(&self.sprite_sheet_animation)
+                .map(|definition| func(sprite_sheet, definition))
+        } else {
+            None
+        }
+    }
+
+    /// Returns total amount of frames in the animation. If the animation uses a sprite sheet, that is not loaded yet,
+    /// the animation is considered empty.
+    pub fn frame_count(&self) -> usize {
+        if self.sprite_sheet.is_some() {
+            self.with_sprite_sheet_animation(|_, definition| definition.frames.len())
+                .unwrap_or_default()
+        } else {
+            self.frames_container.len()
+        }
+    }
+
     /// Creates new animation with given frames container.
     pub fn with_container(container: SpriteSheetFramesContainer) -> Self {
         Self {
@@ -310,9 +385,18 @@ impl SpriteSheetAnimation {
         std::mem::replace(&mut self.texture, texture)
     }
 
-    /// Returns current texture of the animation.
+    /// Returns current texture of the animation. If the animation has no texture, the texture of its sprite sheet is
+    /// returned.
     pub fn texture(&self) -> Option<TextureResource> {
-        self.texture.clone()
+        self.texture.clone().or_else(|| {
+            let sprite_sheet = self.sprite_sheet.as_ref()?;
+            let state = sprite_sheet.state();
+            if let ResourceStateRef::Ok(sprite_sheet) = state.get() {
+                sprite_sheet.texture()
+            } else {
+                None
+            }
+        })
     }
 
     /// Returns a shared reference to inner frames container.
@@ -346,7 +430,8 @@ impl SpriteSheetAnimation {
             return;
         }
 
-        if self.frames_container.is_empty() {
+        let frame_count = self.frame_count();
+        if frame_count == 0 {
             self.status = Status::Stopped;
             return;
         }
@@ -367,19 +452,19 @@ impl SpriteSheetAnimation {
         }
 
         self.current_frame = next_frame;
-        if self.current_frame >= self.frames_container.len() as f32 {
+        if self.current_frame >= frame_count as f32 {
             if self.looping {
                 // Continue playing from beginning.
                 self.current_frame = 0.0;
             } else {
                 // Keep on last frame and stop.
-                self.current_frame = self.frames_container.len().saturating_sub(1) as f32;
+                self.current_frame = frame_count.saturating_sub(1) as f32;
                 self.status = Status::Stopped;
             }
         } else if self.current_frame <= 0.0 {
             if self.looping {
                 // Continue playing from end.
-                self.current_frame = self.frames_container.len().saturating_sub(1) as f32;
+                self.current_frame = frame_count.saturating_sub(1) as f32;
             } else {
                 // Keep on first frame and stop.
                 self.current_frame = 0.0;
@@ -395,6 +480,17 @@ impl SpriteSheetAnimation {
 
     /// Tries to fetch UV rectangle at given frame. Returns `None` if animation is empty.
     pub fn frame_uv_rect(&self, i: usize) -> Option<Rect<f32>> {
+        if self.sprite_sheet.is_some() {
+            return self
+                .with_sprite_sheet_animation(|sprite_sheet, definition| {
+                    let index = *definition.frames.get(i)?;
+                    sprite_sheet
+                        .frame(index as usize)
+                        .map(|frame| frame.uv_rect)
+                })
+                .flatten();
+        }
+
         assert_ne!(self.frames_container.size.x, 0);
         assert_ne!(self.frames_container.size.y, 0);
 
@@ -415,9 +511,28 @@ impl SpriteSheetAnimation {
         self.frame_uv_rect(self.current_frame())
     }
 
+    /// Tries to fetch a pivot of a frame (see [`crate::resource::spritesheet::SpriteSheetFrame::pivot`]). Frames of
+    /// the own grid of the animation always have their pivot at the center. Returns `None` if animation is empty.
+    pub fn frame_pivot(&self, i: usize) -> Option<Vector2<f32>> {
+        if self.sprite_sheet.is_some() {
+            self.with_sprite_sheet_animation(|sprite_sheet, definition| {
+                let index = *definition.frames.get(i)?;
+                sprite_sheet.frame(index as usize).map(|frame| frame.pivot)
+            })
+            .flatten()
+        } else {
+            self.frames_container.get(i).map(|_| Vector2::new(0.5, 0.5))
+        }
+    }
+
+    /// Tries to fetch a pivot of current frame. Returns `None` if animation is empty.
+    pub fn current_frame_pivot(&self) -> Option<Vector2<f32>> {
+        self.frame_pivot(self.current_frame())
+    }
+
     /// Sets current frame of the animation. Input value will be clamped to [0; frame_count] range.
     pub fn set_current_frame(&mut self, current_frame: usize) {
-        self.current_frame = current_frame.min(self.frames_container.len()) as f32;
+        self.current_frame = current_frame.min(self.frame_count()) as f32;
     }
 
     /// Returns true if the animation is looping, false - otherwise.
@@ -448,7 +563,7 @@ impl SpriteSheetAnimation {
 
     /// Sets current frame index to the last frame in the animation.
     pub fn rewind_to_end(&mut self) {
-        self.current_frame = self.frames_container.len().saturating_sub(1) as f32;
+        self.current_frame = self.frame_count().saturating_sub(1) as f32;
     }
 
     /// Returns current status of the animation.
@@ -505,8 +620,12 @@ impl SpriteSheetAnimation {
 
 #[cfg(test)]
 mod test {
-    use crate::animation::spritesheet::{
-        signal::Signal, Event, ImageParameters, SpriteSheetAnimation, Status,
+    use crate::{
+        animation::spritesheet::{
+            signal::Signal, Event, ImageParameters, SpriteSheetAnimation, Status,
+        },
+        asset::Resource,
+        resource::spritesheet::{SpriteSheet, SpriteSheetAnimationDefinition, SpriteSheetFrame},
     };
     use fyrox_core::algebra::Vector2;
     use fyrox_core::math::Rect;
@@ -712,4 +831,52 @@ mod test {
         // Only two should appear.
         assert_eq!(animation.pop_event(), None);
     }
+
+    #[test]
+    fn test_sprite_sheet_resource_playback() {
+        let frames = vec![
+            SpriteSheetFrame {
+                name: "a".to_string(),
+                uv_rect: Rect::new(0.0, 0.0, 0.5, 0.25),
+                pivot: Vector2::new(0.5, 1.0),
+            },
+            SpriteSheetFrame {
+                name: "b".to_string(),
+                uv_rect: Rect::new(0.5, 0.0, 0.25, 0.5),
+                pivot: Vector2::new(0.0, 0.0),
+            },
+        ];
+        let animations = vec![SpriteSheetAnimationDefinition {
+            name: "blink".to_string(),
+            frames: vec![1, 0, 1],
+            speed: 1.0,
+            looping: false,
+        }];
+        let sprite_sheet = Resource::new_ok(SpriteSheet::new(None, frames, animations).unwrap());
+
+        let mut animation = SpriteSheetAnimation::new_from_sprite_sheet(sprite_sheet, "blink");
+        assert_eq!(animation.frame_count(), 3);
+        assert_eq!(animation.speed(), 1.0);
+        assert!(!animation.is_looping());
+
+        animation.play();
+
+        let expected_output = [
+            (Rect::new(0.5, 0.0, 0.25, 0.5), Vector2::new(0.0, 0.0)),
+            (Rect::new(0.0, 0.0, 0.5, 0.25), Vector2::new(0.5, 1.0)),
+            (Rect::new(0.5, 0.0, 0.25, 0.5), Vector2::new(0.0, 0.0)),
+        ];
+
+        for &(uv_rect, pivot) in &expected_output {
+            assert_eq!(animation.current_frame_uv_rect(), Some(uv_rect));
+            assert_eq!(animation.current_frame_pivot(), Some(pivot));
+            animation.update(1.0);
+        }
+
+        assert_eq!(animation.status(), Status::Stopped);
+
+        animation.set_sprite_sheet(animation.sprite_sheet(), "unknown");
+        assert_eq!(animation.frame_count(), 0);
+        assert_eq!(animation.current_frame_uv_rect(), None);
+    }
 }
@@ -0,0 +1,416 @@
+//! Console commands with typed arguments. See [`Command`] docs for more info.
+
+use crate::{console::ConsoleError, plugin::PluginContext};
+use std::fmt::{Display, Formatter};
+
+/// Type of a command argument or a console variable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgumentKind {
+    /// `true`/`false`, `1`/`0` or `on`/`off`.
+    Bool,
+    /// Signed integer number.
+    Integer,
+    /// Floating-point number.
+    Float,
+    /// Any string, use quotes to pass a string with spaces.
+    String,
+    /// One of the given names (case-insensitive).
+    Enum(Vec<String>),
+}
+
+impl ArgumentKind {
+    /// Converts the token to a value of this kind. Returns `None` if the token is not valid.
+    pub fn parse(&self, token: &str) -> Option<Value> {
+        match self {
+            ArgumentKind::Bool => match token.to_lowercase().as_str() {
+                "true" | "1" | "on" => Some(Value::Bool(true)),
+                "false" | "0" | "off" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ArgumentKind::Integer => token.parse().ok().map(Value::Integer),
+            ArgumentKind::Float => token.parse().ok().map(Value::Float),
+            ArgumentKind::String => Some(Value::String(token.to_owned())),
+            ArgumentKind::Enum(variants) => variants
+                .iter()
+                .find(|variant| variant.eq_ignore_ascii_case(token))
+                .map(|variant| Value::String(variant.clone())),
+        }
+    }
+
+    /// Returns all values of this kind that start with the given prefix. Only booleans and
+    /// enumerations have a known set of values.
+    pub fn completions(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        let candidates = match self {
+            ArgumentKind::Bool => vec!["true".to_owned(), "false".to_owned()],
+            ArgumentKind::Enum(variants) => variants.clone(),
+            _ => return Vec::new(),
+        };
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate.to_lowercase().starts_with(&prefix))
+            .collect()
+    }
+}
+
+impl Display for ArgumentKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgumentKind::Bool => write!(f, "bool"),
+            ArgumentKind::Integer => write!(f, "int"),
+            ArgumentKind::Float => write!(f, "float"),
+            ArgumentKind::String => write!(f, "string"),
+            ArgumentKind::Enum(variants) => write!(f, "{}", variants.join("|")),
+        }
+    }
+}
+
+/// Parsed value of a command argument or a console variable.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Boolean value.
+    Bool(bool),
+    /// Integer value.
+    Integer(i64),
+    /// Floating-point value.
+    Float(f32),
+    /// String value, also used for enumerations.
+    String(String),
+}
+
+impl Value {
+    /// Returns the boolean value, or `None` if the value is not a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer value, or `None` if the value is not an integer.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the floating-point value, integers are converted. Returns `None` for other values.
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            Value::Float(value) => Some(*value),
+            Value::Integer(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+
+    /// Returns the string value, or `None` if the value is not a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value.as_str()),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Description of a command argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Argument {
+    /// Name of the argument, it is shown in the usage hint of the command.
+    pub name: String,
+    /// Type of the argument.
+    pub kind: ArgumentKind,
+    /// Optional arguments could be omitted, they must go after required arguments.
+    pub optional: bool,
+}
+
+/// A function that is called when a command is executed. It receives parsed arguments (omitted
+/// optional arguments are not included) and returns an error message if the command has failed.
+pub type CommandHandler = dyn FnMut(&[Value], &mut PluginContext) -> Result<(), String>;
+
+/// A named command, that could be executed from the console. Arguments are checked and converted
+/// before the handler is called, so the handler could safely unwrap the values of required
+/// arguments. Commands should print their output to the log (see [`crate::core::log::Log`]), the
+/// log is mirrored to the console.
+///
+/// If the last argument is a string, it takes the rest of the line, so `echo Hello world` passes
+/// `Hello world` as a single argument.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::{
+///     console::command::{ArgumentKind, Command},
+///     core::log::Log,
+/// };
+///
+/// let command = Command::new("spawn", "Spawns a number of enemies.", |args, _ctx| {
+///     let count = args[0].as_integer().unwrap();
+///     let kind = args.get(1).and_then(|v| v.as_str()).unwrap_or("zombie");
+///     Log::info(format!("Spawning {} enemies of {} kind", count, kind));
+///     Ok(())
+/// })
+/// .with_argument("count", ArgumentKind::Integer)
+/// .with_optional_argument(
+///     "kind",
+///     ArgumentKind::Enum(vec!["zombie".to_owned(), "skeleton".to_owned()]),
+/// );
+///
+/// assert_eq!(command.usage(), "spawn <count: int> [kind: zombie|skeleton]");
+/// ```
+pub struct Command {
+    name: String,
+    description: String,
+    arguments: Vec<Argument>,
+    handler: Box<CommandHandler>,
+}
+
+impl Command {
+    /// Creates new command without arguments.
+    pub fn new<F>(name: &str, description: &str, handler: F) -> Self
+    where
+        F: FnMut(&[Value], &mut PluginContext) -> Result<(), String> + 'static,
+    {
+        Self {
+            name: name.to_owned(),
+            description: description.to_owned(),
+            arguments: Default::default(),
+            handler: Box::new(handler),
+        }
+    }
+
+    /// Adds a required argument to the command.
+    pub fn with_argument(mut self, name: &str, kind: ArgumentKind) -> Self {
+        self.arguments.push(Argument {
+            name: name.to_owned(),
+            kind,
+            optional: false,
+        });
+        self
+    }
+
+    /// Adds an optional argument to the command.
+    pub fn with_optional_argument(mut self, name: &str, kind: ArgumentKind) -> Self {
+        self.arguments.push(Argument {
+            name: name.to_owned(),
+            kind,
+            optional: true,
+        });
+        self
+    }
+
+    /// Returns name of the command.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns description of the command.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns arguments of the command.
+    pub fn arguments(&self) -> &[Argument] {
+        &self.arguments
+    }
+
+    /// Returns usage hint of the command, for example `give <item: string> [count: int]`.
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.clone();
+        for argument in self.arguments.iter() {
+            let (open, close) = if argument.optional {
+                ('[', ']')
+            } else {
+                ('<', '>')
+            };
+            usage += &format!(" {}{}: {}{}", open, argument.name, argument.kind, close);
+        }
+        usage
+    }
+
+    /// Checks and converts the tokens (without the name of the command) to argument values.
+    pub fn parse_arguments(&self, tokens: &[String]) -> Result<Vec<Value>, ConsoleError> {
+        let mut values = Vec::new();
+        for (index, argument) in self.arguments.iter().enumerate() {
+            let token = if index + 1 == self.arguments.len()
+                && argument.kind == ArgumentKind::String
+                && tokens.len() > index
+            {
+                tokens[index..].join(" ")
+            } else if let Some(token) = tokens.get(index) {
+                token.clone()
+            } else if argument.optional {
+                break;
+            } else {
+                return Err(ConsoleError::MissingArgument {
+                    command: self.name.clone(),
+                    argument: argument.name.clone(),
+                });
+            };
+
+            match argument.kind.parse(&token) {
+                Some(value) => values.push(value),
+                None => {
+                    return Err(ConsoleError::InvalidArgument {
+                        command: self.name.clone(),
+                        argument: argument.name.clone(),
+                        expected: argument.kind.clone(),
+                        value: token,
+                    })
+                }
+            }
+        }
+
+        if tokens.len() > self.arguments.len()
+            && self
+                .arguments
+                .last()
+                .map_or(true, |last| last.kind != ArgumentKind::String)
+        {
+            return Err(ConsoleError::TooManyArguments {
+                command: self.name.clone(),
+                usage: self.usage(),
+            });
+        }
+
+        Ok(values)
+    }
+
+    /// Parses the arguments and calls the handler of the command.
+    pub fn execute(
+        &mut self,
+        tokens: &[String],
+        context: &mut PluginContext,
+    ) -> Result<(), ConsoleError> {
+        let values = self.parse_arguments(tokens)?;
+        (self.handler)(&values, context).map_err(|message| ConsoleError::Failed {
+            command: self.name.clone(),
+            message,
+        })
+    }
+}
+
+/// Splits a command line into tokens. Tokens are separated by whitespace, double quotes could be
+/// used to pass tokens with spaces, `\"` and `\\` are escaped quote and backslash respectively.
+pub fn tokenize(line: &str) -> Result<Vec<String>, ConsoleError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut in_quotes = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_token = true;
+            }
+            '\\' if in_quotes => match chars.next() {
+                Some(escaped @ ('"' | '\\')) => current.push(escaped),
+                Some(other) => {
+                    current.push('\\');
+                    current.push(other);
+                }
+                None => current.push('\\'),
+            },
+            c if c.is_whitespace() && !in_quotes => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err(ConsoleError::UnterminatedQuote);
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::console::{
+        command::{tokenize, ArgumentKind, Command, Value},
+        ConsoleError,
+    };
+
+    fn tokens(line: &str) -> Vec<String> {
+        tokenize(line).unwrap()
+    }
+
+    #[test]
+    fn test_command_arguments() {
+        assert_eq!(
+            tokens(r#"  give "health potion"  5 "" "say \"hi\"" "#),
+            ["give", "health potion", "5", "", r#"say "hi""#]
+        );
+        assert!(matches!(
+            tokenize(r#"echo "oops"#),
+            Err(ConsoleError::UnterminatedQuote)
+        ));
+
+        let command = Command::new("give", "", |_, _| Ok(()))
+            .with_argument("item", ArgumentKind::Enum(vec!["Sword".to_owned()]))
+            .with_argument("count", ArgumentKind::Integer)
+            .with_optional_argument("scale", ArgumentKind::Float)
+            .with_optional_argument("message", ArgumentKind::String);
+        assert_eq!(
+            command.usage(),
+            "give <item: Sword> <count: int> [scale: float] [message: string]"
+        );
+
+        assert_eq!(
+            command.parse_arguments(&tokens("sword 2")).unwrap(),
+            [Value::String("Sword".to_owned()), Value::Integer(2)]
+        );
+        assert_eq!(
+            command
+                .parse_arguments(&tokens("Sword 2 1.5 for  the \"hero\""))
+                .unwrap(),
+            [
+                Value::String("Sword".to_owned()),
+                Value::Integer(2),
+                Value::Float(1.5),
+                Value::String("for the hero".to_owned())
+            ]
+        );
+        assert!(matches!(
+            command.parse_arguments(&tokens("Sword")),
+            Err(ConsoleError::MissingArgument { argument, .. }) if argument == "count"
+        ));
+        assert!(matches!(
+            command.parse_arguments(&tokens("Axe 2")),
+            Err(ConsoleError::InvalidArgument { argument, .. }) if argument == "item"
+        ));
+
+        let toggle =
+            Command::new("toggle", "", |_, _| Ok(())).with_argument("enabled", ArgumentKind::Bool);
+        assert_eq!(
+            toggle.parse_arguments(&tokens("OFF")).unwrap(),
+            [Value::Bool(false)]
+        );
+        assert!(matches!(
+            toggle.parse_arguments(&tokens("on off")),
+            Err(ConsoleError::TooManyArguments { .. })
+        ));
+        assert_eq!(ArgumentKind::Bool.completions("T"), ["true"]);
+    }
+}
@@ -0,0 +1,768 @@
+//! Drop-down developer console. See [`Console`] docs for more info.
+
+pub mod command;
+mod panel;
+pub mod variable;
+
+use crate::{
+    console::{
+        command::{tokenize, ArgumentKind, Command},
+        panel::ConsolePanel,
+        variable::{engine_variables, ConsoleVariable},
+    },
+    core::{
+        futures::executor::block_on,
+        io::{self, FileLoadError},
+        log::{Log, LogMessage, MessageKind},
+    },
+    event::{ElementState, Event, WindowEvent},
+    gui::{
+        message::{KeyCode, MessageDirection, UiMessage},
+        widget::WidgetMessage,
+        UserInterface,
+    },
+    plugin::PluginContext,
+    utils::translate_key,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+};
+
+/// An error, that may occur during execution of a console command.
+#[derive(Debug)]
+pub enum ConsoleError {
+    /// There is no command or variable with the given name.
+    UnknownCommand(String),
+    /// A command or a variable with the given name is already registered.
+    NameCollision(String),
+    /// A required argument is not specified.
+    MissingArgument {
+        /// Name of the command.
+        command: String,
+        /// Name of the argument.
+        argument: String,
+    },
+    /// An argument could not be converted to its type.
+    InvalidArgument {
+        /// Name of the command.
+        command: String,
+        /// Name of the argument.
+        argument: String,
+        /// Expected type of the argument.
+        expected: ArgumentKind,
+        /// Actual value of the argument.
+        value: String,
+    },
+    /// The command received more arguments than it takes.
+    TooManyArguments {
+        /// Name of the command.
+        command: String,
+        /// Usage hint of the command.
+        usage: String,
+    },
+    /// A quoted string is not closed.
+    UnterminatedQuote,
+    /// The command has failed.
+    Failed {
+        /// Name of the command.
+        command: String,
+        /// Error message of the command.
+        message: String,
+    },
+    /// A script file could not be loaded.
+    Io {
+        /// Path of the script.
+        path: PathBuf,
+        /// Actual error.
+        error: FileLoadError,
+    },
+}
+
+impl Display for ConsoleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsoleError::UnknownCommand(name) => {
+                write!(f, "Unknown command or variable {}", name)
+            }
+            ConsoleError::NameCollision(name) => {
+                write!(f, "Command or variable {} is already registered", name)
+            }
+            ConsoleError::MissingArgument { command, argument } => {
+                write!(f, "{}: missing argument {}", command, argument)
+            }
+            ConsoleError::InvalidArgument {
+                command,
+                argument,
+                expected,
+                value,
+            } => write!(
+                f,
+                "{}: argument {} must be {}, got {}",
+                command, argument, expected, value
+            ),
+            ConsoleError::TooManyArguments { command, usage } => {
+                write!(f, "{}: too many arguments, usage: {}", command, usage)
+            }
+            ConsoleError::UnterminatedQuote => write!(f, "Unterminated quote"),
+            ConsoleError::Failed { command, message } => write!(f, "{}: {}", command, message),
+            ConsoleError::Io { path, error } => {
+                write!(f, "Unable to load script {}: {:?}", path.display(), error)
+            }
+        }
+    }
+}
+
+// Built-in commands need access to the console itself, so they're handled separately.
+const BUILTIN_COMMANDS: [(&str, &str, &str); 5] = [
+    (
+        "help",
+        "help [name: string]",
+        "Prints all commands or usage of the given command.",
+    ),
+    ("clear", "clear", "Clears the console."),
+    (
+        "cvars",
+        "cvars",
+        "Prints all variables with their current values.",
+    ),
+    (
+        "exec",
+        "exec <path: string>",
+        "Executes a script file with console commands.",
+    ),
+    ("echo", "echo [text: string]", "Prints the given text."),
+];
+
+// Prevents infinite recursion of scripts, that execute each other.
+const MAX_SCRIPT_DEPTH: usize = 16;
+
+/// Drop-down developer console. It is a panel at the top of the screen, that shows the log and
+/// allows to execute commands (see [`Command`]) and to read or change console variables (see
+/// [`ConsoleVariable`]).
+///
+/// - Toggle key (`` ` `` by default) opens and closes the console.
+/// - `Enter` executes the command, `Up`/`Down` walk through the history of commands.
+/// - `Tab` completes names of commands and variables, and values of their arguments.
+/// - `help`, `clear`, `cvars`, `exec <path>` and `echo <text>` commands are always available.
+///
+/// Scripts are plain text files with one command per line, lines starting with `#` or `//` are
+/// comments. For example, a game could execute `autoexec.cfg` with [`Self::execute_file`] at
+/// startup, to apply user settings.
+///
+/// The console is disabled in release builds by default, use [`Self::set_enabled`] to make it
+/// available in a shipped game (for example, when the game is launched with some command-line
+/// flag). Disabled console ignores the toggle key, but commands could still be executed from code.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     console::{
+///         command::{ArgumentKind, Command, Value},
+///         variable::ConsoleVariable,
+///         Console,
+///     },
+///     core::log::Log,
+///     event::Event,
+///     event_loop::ControlFlow,
+///     gui::message::UiMessage,
+///     plugin::{Plugin, PluginContext},
+/// };
+///
+/// struct Game {
+///     console: Console,
+/// }
+///
+/// impl Game {
+///     fn new(mut context: PluginContext) -> Self {
+///         let mut console = Console::new(context.user_interface);
+///         console.set_enabled(
+///             context.user_interface,
+///             std::env::args().any(|arg| arg == "--console"),
+///         );
+///         console
+///             .register_command(
+///                 Command::new("greet", "Prints a greeting.", |args, _ctx| {
+///                     Log::info(format!("Hello, {}!", args[0]));
+///                     Ok(())
+///                 })
+///                 .with_argument("name", ArgumentKind::String),
+///             )
+///             .unwrap();
+///         console
+///             .register_variable(ConsoleVariable::new(
+///                 "god_mode",
+///                 "Makes the player invulnerable.",
+///                 Value::Bool(false),
+///             ))
+///             .unwrap();
+///         Self { console }
+///     }
+///
+///     fn is_god_mode(&self) -> bool {
+///         self.console
+///             .variable("god_mode")
+///             .and_then(|v| v.stored_value())
+///             .and_then(|v| v.as_bool())
+///             .unwrap_or_default()
+///     }
+/// }
+///
+/// impl Plugin for Game {
+///     fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
+///         self.console.update(context);
+///     }
+///
+///     fn on_os_event(
+///         &mut self,
+///         event: &Event<()>,
+///         mut context: PluginContext,
+///         _control_flow: &mut ControlFlow,
+///     ) {
+///         self.console.on_os_event(event, &mut context);
+///     }
+///
+///     fn on_ui_message(
+///         &mut self,
+///         context: &mut PluginContext,
+///         message: &UiMessage,
+///         _control_flow: &mut ControlFlow,
+///     ) {
+///         self.console.on_ui_message(message, context);
+///     }
+/// }
+/// ```
+pub struct Console {
+    commands: BTreeMap<String, Command>,
+    variables: BTreeMap<String, ConsoleVariable>,
+    history: Vec<String>,
+    history_position: Option<usize>,
+    enabled: bool,
+    toggle_key: KeyCode,
+    script_depth: usize,
+    receiver: Receiver<LogMessage>,
+    panel: ConsolePanel,
+}
+
+impl Console {
+    /// Default height of the console panel.
+    pub const DEFAULT_HEIGHT: f32 = 300.0;
+
+    /// Creates new console and its panel in the given user interface. The console mirrors all log
+    /// messages written after its creation.
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Log::add_listener(sender);
+
+        Self {
+            commands: Default::default(),
+            variables: engine_variables()
+                .into_iter()
+                .map(|variable| (variable.name().to_owned(), variable))
+                .collect(),
+            history: Default::default(),
+            history_position: None,
+            enabled: cfg!(debug_assertions),
+            toggle_key: KeyCode::Backquote,
+            script_depth: 0,
+            receiver,
+            panel: ConsolePanel::new(ui, Self::DEFAULT_HEIGHT),
+        }
+    }
+
+    /// Enables or disables the console. Disabled console is closed and cannot be opened by the
+    /// toggle key.
+    pub fn set_enabled(&mut self, ui: &UserInterface, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.panel.set_visible(ui, false);
+        }
+    }
+
+    /// Returns `true` if the console is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Sets the key, that opens and closes the console.
+    pub fn set_toggle_key(&mut self, key: KeyCode) {
+        self.toggle_key = key;
+    }
+
+    /// Returns the key, that opens and closes the console.
+    pub fn toggle_key(&self) -> KeyCode {
+        self.toggle_key
+    }
+
+    /// Opens or closes the console (if it is enabled).
+    pub fn set_open(&mut self, ui: &UserInterface, open: bool) {
+        self.panel.set_visible(ui, open && self.enabled);
+    }
+
+    /// Returns `true` if the console is open. A game should usually ignore its own controls while
+    /// the console is open.
+    pub fn is_open(&self) -> bool {
+        self.panel.is_visible()
+    }
+
+    /// Registers new command. Fails if a command or a variable with the same name exists.
+    pub fn register_command(&mut self, command: Command) -> Result<(), ConsoleError> {
+        self.check_name(command.name())?;
+        self.commands.insert(command.name().to_owned(), command);
+        Ok(())
+    }
+
+    /// Removes a command with the given name.
+    pub fn unregister_command(&mut self, name: &str) -> Option<Command> {
+        self.commands.remove(name)
+    }
+
+    /// Registers new variable. Fails if a command or a variable with the same name exists.
+    pub fn register_variable(&mut self, variable: ConsoleVariable) -> Result<(), ConsoleError> {
+        self.check_name(variable.name())?;
+        self.variables.insert(variable.name().to_owned(), variable);
+        Ok(())
+    }
+
+    /// Removes a variable with the given name.
+    pub fn unregister_variable(&mut self, name: &str) -> Option<ConsoleVariable> {
+        self.variables.remove(name)
+    }
+
+    /// Returns a reference to a variable with the given name.
+    pub fn variable(&self, name: &str) -> Option<&ConsoleVariable> {
+        self.variables.get(name)
+    }
+
+    /// Returns an iterator over all registered commands.
+    pub fn commands(&self) -> impl Iterator<Item = &Command> {
+        self.commands.values()
+    }
+
+    /// Returns an iterator over all registered variables.
+    pub fn variables(&self) -> impl Iterator<Item = &ConsoleVariable> {
+        self.variables.values()
+    }
+
+    fn check_name(&self, name: &str) -> Result<(), ConsoleError> {
+        if self.commands.contains_key(name)
+            || self.variables.contains_key(name)
+            || BUILTIN_COMMANDS
+                .iter()
+                .any(|(builtin, ..)| *builtin == name)
+        {
+            Err(ConsoleError::NameCollision(name.to_owned()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Prints a line to the console (but not to the log).
+    pub fn print(&mut self, ui: &mut UserInterface, text: &str, kind: MessageKind) {
+        self.panel.add_line(ui, text, kind);
+    }
+
+    /// Executes a single command line.
+    pub fn execute(&mut self, line: &str, context: &mut PluginContext) -> Result<(), ConsoleError> {
+        let tokens = tokenize(line)?;
+        let (name, args) = match tokens.split_first() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+
+        match name.as_str() {
+            "help" => self.help(args.first().map(|s| s.as_str()), context.user_interface),
+            "clear" => {
+                self.panel.clear(context.user_interface);
+                Ok(())
+            }
+            "cvars" => {
+                let lines = self
+                    .variables
+                    .values()
+                    .map(|variable| match variable.value(context) {
+                        Some(value) => format!("{} = {}", variable.name(), value),
+                        None => format!("{} is not available", variable.name()),
+                    })
+                    .collect::<Vec<_>>();
+                for line in lines {
+                    self.print(context.user_interface, &line, MessageKind::Information);
+                }
+                Ok(())
+            }
+            "exec" => match args {
+                [path] => self.execute_file(path, context),
+                _ => Err(ConsoleError::MissingArgument {
+                    command: name.clone(),
+                    argument: "path".to_owned(),
+                }),
+            },
+            "echo" => {
+                self.print(
+                    context.user_interface,
+                    &args.join(" "),
+                    MessageKind::Information,
+                );
+                Ok(())
+            }
+            _ => {
+                if let Some(command) = self.commands.get_mut(name) {
+                    command.execute(args, context)
+                } else if let Some(variable) = self.variables.get_mut(name) {
+                    match args {
+                        [] => {
+                            let text = match variable.value(context) {
+                                Some(value) => format!("{} = {}", name, value),
+                                None => format!("{} is not available", name),
+                            };
+                            self.print(context.user_interface, &text, MessageKind::Information);
+                            Ok(())
+                        }
+                        [value] => {
+                            variable
+                                .set(value, context)
+                                .map_err(|message| ConsoleError::Failed {
+                                    command: name.clone(),
+                                    message,
+                                })
+                        }
+                        _ => Err(ConsoleError::TooManyArguments {
+                            command: name.clone(),
+                            usage: format!("{} [value: {}]", name, variable.kind()),
+                        }),
+                    }
+                } else {
+                    Err(ConsoleError::UnknownCommand(name.clone()))
+                }
+            }
+        }
+    }
+
+    fn help(&mut self, name: Option<&str>, ui: &mut UserInterface) -> Result<(), ConsoleError> {
+        let lines = match name {
+            None => BUILTIN_COMMANDS
+                .iter()
+                .map(|(_, usage, description)| format!("{} - {}", usage, description))
+                .chain(
+                    self.commands
+                        .values()
+                        .map(|command| format!("{} - {}", command.usage(), command.description())),
+                )
+                .collect::<Vec<_>>(),
+            Some(name) => {
+                if let Some((_, usage, description)) = BUILTIN_COMMANDS
+                    .iter()
+                    .find(|(builtin, ..)| *builtin == name)
+                {
+                    vec![format!("{} - {}", usage, description)]
+                } else if let Some(command) = self.commands.get(name) {
+                    vec![format!("{} - {}", command.usage(), command.description())]
+                } else if let Some(variable) = self.variables.get(name) {
+                    vec![format!(
+                        "{} [value: {}] - {}",
+                        name,
+                        variable.kind(),
+                        variable.description()
+                    )]
+                } else {
+                    return Err(ConsoleError::UnknownCommand(name.to_owned()));
+                }
+            }
+        };
+        for line in lines {
+            self.print(ui, &line, MessageKind::Information);
+        }
+        Ok(())
+    }
+
+    /// Executes a script - one command per line, empty lines and lines starting with `#` or `//`
+    /// are skipped. Execution stops at the first failed command.
+    pub fn execute_script(
+        &mut self,
+        script: &str,
+        context: &mut PluginContext,
+    ) -> Result<(), ConsoleError> {
+        if self.script_depth >= MAX_SCRIPT_DEPTH {
+            return Err(ConsoleError::Failed {
+                command: "exec".to_owned(),
+                message: "Too many nested scripts".to_owned(),
+            });
+        }
+
+        self.script_depth += 1;
+        let result = script
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"))
+            .try_for_each(|line| self.execute(line, context));
+        self.script_depth -= 1;
+        result
+    }
+
+    /// Loads a script file and executes it, see [`Self::execute_script`] for more info.
+    pub fn execute_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        context: &mut PluginContext,
+    ) -> Result<(), ConsoleError> {
+        let path = path.as_ref();
+        let data = block_on(io::load_file(path)).map_err(|error| ConsoleError::Io {
+            path: path.to_owned(),
+            error,
+        })?;
+        self.execute_script(&String::from_utf8_lossy(&data), context)
+    }
+
+    /// Returns possible completions of the last word of the command line: names of commands and
+    /// variables for the first word, or values of the argument for the next words.
+    pub fn complete(&self, line: &str) -> Vec<String> {
+        let mut tokens = match tokenize(line) {
+            Ok(tokens) => tokens,
+            Err(_) => return Vec::new(),
+        };
+        if tokens.is_empty() || line.ends_with(char::is_whitespace) {
+            tokens.push(String::new());
+        }
+        let prefix = tokens.last().map(|s| s.as_str()).unwrap_or_default();
+
+        if tokens.len() == 1 {
+            return self.names(prefix);
+        }
+
+        let argument = tokens.len() - 2;
+        let name = tokens[0].as_str();
+        if name == "help" && argument == 0 {
+            self.names(prefix)
+        } else if let Some(command) = self.commands.get(name) {
+            command
+                .arguments()
+                .get(argument)
+                .map(|argument| argument.kind.completions(prefix))
+                .unwrap_or_default()
+        } else if let Some(variable) = self.variables.get(name).filter(|_| argument == 0) {
+            variable.kind().completions(prefix)
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn names(&self, prefix: &str) -> Vec<String> {
+        let mut names = BUILTIN_COMMANDS
+            .iter()
+            .map(|(name, ..)| *name)
+            .chain(self.commands.keys().map(|name| name.as_str()))
+            .chain(self.variables.keys().map(|name| name.as_str()))
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| name.to_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// Returns the command line with the last word completed, or `None` if there is nothing to
+    /// complete. If there are multiple completions, the word is completed up to their common prefix.
+    pub fn complete_line(&self, line: &str) -> Option<String> {
+        let completions = self.complete(line);
+        let first = completions.first()?;
+
+        let mut common = first.len();
+        for completion in completions.iter().skip(1) {
+            common = first
+                .char_indices()
+                .zip(completion.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.min(completion.len()), |((i, _), _)| i.min(common));
+        }
+
+        let start = if line.ends_with(char::is_whitespace) {
+            line.len()
+        } else {
+            line.rfind(char::is_whitespace).map_or(0, |i| i + 1)
+        };
+        let mut completed = format!("{}{}", &line[..start], &first[..common]);
+        if completions.len() == 1 {
+            completed.push(' ');
+        }
+        if completed != line {
+            Some(completed)
+        } else {
+            None
+        }
+    }
+
+    fn submit(&mut self, context: &mut PluginContext) {
+        let line = self.panel.input_text(context.user_interface);
+        self.panel.set_input_text(context.user_interface, "");
+        self.history_position = None;
+
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        if self.history.last().map(|s| s.as_str()) != Some(line) {
+            self.history.push(line.to_owned());
+        }
+
+        self.print(
+            context.user_interface,
+            &format!("> {}", line),
+            MessageKind::Information,
+        );
+        if let Err(err) = self.execute(line, context) {
+            self.print(context.user_interface, &err.to_string(), MessageKind::Error);
+        }
+    }
+
+    fn navigate_history(&mut self, ui: &UserInterface, up: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        self.history_position = match (self.history_position, up) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(position), true) => Some(position.saturating_sub(1)),
+            (Some(position), false) => {
+                Some(position + 1).filter(|&position| position < self.history.len())
+            }
+        };
+
+        let text = self
+            .history_position
+            .map(|position| self.history[position].as_str())
+            .unwrap_or_default();
+        self.panel.set_input_text(ui, text);
+    }
+
+    /// Handles the toggle key. Must be called from [`crate::plugin::Plugin::on_os_event`].
+    pub fn on_os_event(&mut self, event: &Event<()>, context: &mut PluginContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event, .. },
+            ..
+        } = event
+        {
+            if self.enabled
+                && event.state == ElementState::Pressed
+                && !event.repeat
+                && translate_key(event.physical_key) == self.toggle_key
+            {
+                let open = !self.is_open();
+                self.set_open(context.user_interface, open);
+            }
+        }
+    }
+
+    /// Handles input of the console. Must be called from [`crate::plugin::Plugin::on_ui_message`].
+    pub fn on_ui_message(&mut self, message: &UiMessage, context: &mut PluginContext) {
+        if message.destination() != self.panel.input
+            || message.direction() != MessageDirection::FromWidget
+        {
+            return;
+        }
+
+        if let Some(WidgetMessage::KeyDown(key)) = message.data() {
+            match key {
+                KeyCode::Enter | KeyCode::NumpadEnter => self.submit(context),
+                KeyCode::ArrowUp => self.navigate_history(context.user_interface, true),
+                KeyCode::ArrowDown => self.navigate_history(context.user_interface, false),
+                KeyCode::Tab => {
+                    let line = self.panel.input_text(context.user_interface);
+                    if let Some(completed) = self.complete_line(&line) {
+                        self.panel
+                            .set_input_text(context.user_interface, &completed);
+                    } else {
+                        let completions = self.complete(&line);
+                        if completions.len() > 1 {
+                            self.print(
+                                context.user_interface,
+                                &completions.join("  "),
+                                MessageKind::Information,
+                            );
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Mirrors new log messages to the console. Must be called every frame or every fixed update.
+    pub fn update(&mut self, context: &mut PluginContext) {
+        while let Ok(message) = self.receiver.try_recv() {
+            self.panel.add_line(
+                context.user_interface,
+                message.content.trim_end(),
+                message.kind,
+            );
+        }
+    }
+
+    /// Removes the panel of the console from the user interface.
+    pub fn destroy(self, ui: &UserInterface) {
+        self.panel.destroy(ui);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        console::{
+            command::{ArgumentKind, Command, Value},
+            variable::ConsoleVariable,
+            Console, ConsoleError,
+        },
+        core::algebra::Vector2,
+        gui::UserInterface,
+    };
+
+    #[test]
+    fn test_console_completion() {
+        let mut ui = UserInterface::new(Vector2::new(100.0, 100.0));
+        let mut console = Console::new(&mut ui);
+        console
+            .register_command(
+                Command::new("spawn", "", |_, _| Ok(()))
+                    .with_argument(
+                        "kind",
+                        ArgumentKind::Enum(vec!["zombie".to_owned(), "zeppelin".to_owned()]),
+                    )
+                    .with_argument("count", ArgumentKind::Integer),
+            )
+            .unwrap();
+        console
+            .register_variable(ConsoleVariable::new("sv_cheats", "", Value::Bool(false)))
+            .unwrap();
+        assert!(matches!(
+            console.register_variable(ConsoleVariable::new("help", "", Value::Integer(0))),
+            Err(ConsoleError::NameCollision(_))
+        ));
+
+        assert_eq!(console.complete("sp"), ["spawn"]);
+        assert_eq!(console.complete("s"), ["spawn", "sv_cheats"]);
+        assert_eq!(console.complete("spawn z"), ["zombie", "zeppelin"]);
+        assert!(console.complete("spawn zombie ").is_empty());
+        assert_eq!(console.complete("sv_cheats "), ["true", "false"]);
+        assert_eq!(console.complete("help cl"), ["clear"]);
+
+        assert_eq!(console.complete_line("sp").as_deref(), Some("spawn "));
+        assert_eq!(console.complete_line("spawn z"), None);
+        assert_eq!(
+            console.complete_line("r_point_sh").as_deref(),
+            Some("r_point_shadow")
+        );
+        assert_eq!(
+            console.complete_line("spawn zo").as_deref(),
+            Some("spawn zombie ")
+        );
+        assert_eq!(
+            console
+                .variable("sv_cheats")
+                .and_then(|v| v.stored_value())
+                .and_then(|v| v.as_bool()),
+            Some(false)
+        );
+    }
+}
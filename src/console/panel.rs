@@ -0,0 +1,200 @@
+//! User interface of the console.
+
+use crate::{
+    core::{color::Color, log::MessageKind, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{KeyCode, MessageDirection},
+        scroll_viewer::ScrollViewerBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBox, TextBoxBuilder, TextCommitMode},
+        widget::{WidgetBuilder, WidgetMessage},
+        Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+// Oldest lines are removed from the panel when the limit is reached.
+const MAX_LINES: usize = 512;
+
+pub(super) struct ConsolePanel {
+    root: Handle<UiNode>,
+    messages: Handle<UiNode>,
+    pub(super) input: Handle<UiNode>,
+    lines: VecDeque<Handle<UiNode>>,
+    visible: bool,
+}
+
+impl ConsolePanel {
+    pub(super) fn new(ui: &mut UserInterface, height: f32) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let messages;
+        let input;
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_name("Console")
+                .with_visibility(false)
+                .with_height(height)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_background(Brush::Solid(Color::from_rgba(20, 20, 20, 220)))
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child({
+                                messages = ListViewBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_margin(Thickness::uniform(1.0))
+                                        .on_row(0),
+                                )
+                                .with_scroll_viewer(
+                                    ScrollViewerBuilder::new(
+                                        WidgetBuilder::new().with_margin(Thickness::uniform(3.0)),
+                                    )
+                                    .with_horizontal_scroll_allowed(false)
+                                    .with_vertical_scroll_allowed(true)
+                                    .build(ctx),
+                                )
+                                .build(ctx);
+                                messages
+                            })
+                            .with_child({
+                                input = TextBoxBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_margin(Thickness::uniform(2.0))
+                                        .with_background(Brush::Solid(Color::opaque(40, 40, 40)))
+                                        .on_row(1),
+                                )
+                                // Commands are executed on Enter, so the text box must not lose
+                                // focus when it is pressed.
+                                .with_text_commit_mode(TextCommitMode::Immediate)
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                // The key, that toggles the console, must not be typed in.
+                                .with_filter(Rc::new(RefCell::new(|c: char| c != '`')))
+                                .build(ctx);
+                                input
+                            }),
+                    )
+                    .add_row(Row::stretch())
+                    .add_row(Row::strict(24.0))
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            messages,
+            input,
+            lines: Default::default(),
+            visible: false,
+        }
+    }
+
+    pub(super) fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub(super) fn set_visible(&mut self, ui: &UserInterface, visible: bool) {
+        if self.visible == visible {
+            return;
+        }
+        self.visible = visible;
+
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+        if visible {
+            // The panel must be drawn on top of the game UI.
+            ui.send_message(WidgetMessage::topmost(
+                self.root,
+                MessageDirection::ToWidget,
+            ));
+            ui.send_message(WidgetMessage::focus(self.input, MessageDirection::ToWidget));
+        } else {
+            ui.send_message(WidgetMessage::unfocus(
+                self.input,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    pub(super) fn add_line(&mut self, ui: &mut UserInterface, text: &str, kind: MessageKind) {
+        let item = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(1.0))
+                .with_foreground(Brush::Solid(match kind {
+                    MessageKind::Debug => Color::opaque(150, 150, 150),
+                    MessageKind::Information => Color::opaque(210, 210, 210),
+                    MessageKind::Warning => Color::ORANGE,
+                    MessageKind::Error => Color::RED,
+                })),
+        )
+        .with_text(text)
+        .with_wrap(WrapMode::Word)
+        .build(&mut ui.build_ctx());
+
+        ui.send_message(ListViewMessage::add_item(
+            self.messages,
+            MessageDirection::ToWidget,
+            item,
+        ));
+        ui.send_message(ListViewMessage::bring_item_into_view(
+            self.messages,
+            MessageDirection::ToWidget,
+            item,
+        ));
+
+        self.lines.push_back(item);
+        while self.lines.len() > MAX_LINES {
+            if let Some(line) = self.lines.pop_front() {
+                ui.send_message(ListViewMessage::remove_item(
+                    self.messages,
+                    MessageDirection::ToWidget,
+                    line,
+                ));
+            }
+        }
+    }
+
+    pub(super) fn clear(&mut self, ui: &UserInterface) {
+        self.lines.clear();
+        ui.send_message(ListViewMessage::items(
+            self.messages,
+            MessageDirection::ToWidget,
+            vec![],
+        ));
+    }
+
+    pub(super) fn input_text(&self, ui: &UserInterface) -> String {
+        ui.try_get_node(self.input)
+            .and_then(|node| node.cast::<TextBox>())
+            .map(|text_box| text_box.text())
+            .unwrap_or_default()
+    }
+
+    pub(super) fn set_input_text(&self, ui: &UserInterface, text: &str) {
+        ui.send_message(TextMessage::text(
+            self.input,
+            MessageDirection::ToWidget,
+            text.to_owned(),
+        ));
+        // Move the caret to the end of the new text.
+        ui.send_message(WidgetMessage::key_down(
+            self.input,
+            MessageDirection::ToWidget,
+            KeyCode::End,
+        ));
+    }
+
+    pub(super) fn destroy(&self, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::remove(self.root, MessageDirection::ToWidget));
+    }
+}
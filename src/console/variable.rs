@@ -0,0 +1,306 @@
+//! Console variables. See [`ConsoleVariable`] docs for more info.
+
+use crate::{
+    console::command::{ArgumentKind, Value},
+    engine::GraphicsContext,
+    plugin::PluginContext,
+    renderer::QualitySettings,
+};
+
+/// A function that reads the current value of a bound variable. It returns `None` if the value is
+/// not available at the moment (for example, renderer settings while graphics context is not
+/// initialized).
+pub type VariableGetter = dyn Fn(&PluginContext) -> Option<Value>;
+
+/// A function that applies a new value of a bound variable.
+pub type VariableSetter = dyn FnMut(Value, &mut PluginContext) -> Result<(), String>;
+
+enum Storage {
+    Value(Value),
+    Bound {
+        getter: Box<VariableGetter>,
+        setter: Box<VariableSetter>,
+    },
+}
+
+/// Console variable (cvar) is a named value, that could be read and changed from the console:
+/// `name` prints the value, `name value` sets it. A variable either stores its value in the
+/// console (game code reads it using [`super::Console::variable`]), or it is bound to some
+/// engine state with a pair of getter and setter.
+///
+/// The console has a number of built-in variables: `time_scale` and a set of `r_*` variables bound
+/// to quality settings of the renderer. Type `cvars` in the console to see all of them.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::console::{
+///     command::{ArgumentKind, Value},
+///     variable::ConsoleVariable,
+/// };
+///
+/// // A variable, that is stored in the console.
+/// let god_mode = ConsoleVariable::new("god_mode", "Makes the player invulnerable.", Value::Bool(false));
+///
+/// // A variable, that is bound to the engine state.
+/// let lag = ConsoleVariable::bound(
+///     "lag",
+///     "Remaining time of the game loop.",
+///     ArgumentKind::Float,
+///     |ctx| Some(Value::Float(*ctx.lag)),
+///     |value, ctx| {
+///         *ctx.lag = value.as_float().unwrap();
+///         Ok(())
+///     },
+/// );
+/// ```
+pub struct ConsoleVariable {
+    name: String,
+    description: String,
+    kind: ArgumentKind,
+    storage: Storage,
+}
+
+impl ConsoleVariable {
+    /// Creates new variable, that stores its value in the console. Type of the variable is defined
+    /// by the initial value, use [`Self::with_kind`] to restrict a string variable to a set of
+    /// values.
+    pub fn new(name: &str, description: &str, value: Value) -> Self {
+        let kind = match value {
+            Value::Bool(_) => ArgumentKind::Bool,
+            Value::Integer(_) => ArgumentKind::Integer,
+            Value::Float(_) => ArgumentKind::Float,
+            Value::String(_) => ArgumentKind::String,
+        };
+        Self {
+            name: name.to_owned(),
+            description: description.to_owned(),
+            kind,
+            storage: Storage::Value(value),
+        }
+    }
+
+    /// Creates new variable, that is bound to some external state. The setter receives values of
+    /// the given kind only.
+    pub fn bound<G, S>(
+        name: &str,
+        description: &str,
+        kind: ArgumentKind,
+        getter: G,
+        setter: S,
+    ) -> Self
+    where
+        G: Fn(&PluginContext) -> Option<Value> + 'static,
+        S: FnMut(Value, &mut PluginContext) -> Result<(), String> + 'static,
+    {
+        Self {
+            name: name.to_owned(),
+            description: description.to_owned(),
+            kind,
+            storage: Storage::Bound {
+                getter: Box::new(getter),
+                setter: Box::new(setter),
+            },
+        }
+    }
+
+    /// Sets the kind of the variable.
+    pub fn with_kind(mut self, kind: ArgumentKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Returns name of the variable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns description of the variable.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns kind of the variable.
+    pub fn kind(&self) -> &ArgumentKind {
+        &self.kind
+    }
+
+    /// Returns the value of a variable, that stores its value in the console. Returns `None` for
+    /// bound variables.
+    pub fn stored_value(&self) -> Option<&Value> {
+        match self.storage {
+            Storage::Value(ref value) => Some(value),
+            Storage::Bound { .. } => None,
+        }
+    }
+
+    /// Returns current value of the variable.
+    pub fn value(&self, context: &PluginContext) -> Option<Value> {
+        match self.storage {
+            Storage::Value(ref value) => Some(value.clone()),
+            Storage::Bound { ref getter, .. } => getter(context),
+        }
+    }
+
+    /// Converts the token to the value of the variable kind and applies it.
+    pub fn set(&mut self, token: &str, context: &mut PluginContext) -> Result<(), String> {
+        let value = self
+            .kind
+            .parse(token)
+            .ok_or_else(|| format!("Expected a value of {} type, got {}", self.kind, token))?;
+        match self.storage {
+            Storage::Value(ref mut stored) => {
+                *stored = value;
+                Ok(())
+            }
+            Storage::Bound { ref mut setter, .. } => setter(value, context),
+        }
+    }
+}
+
+fn quality_variable(
+    name: &str,
+    description: &str,
+    kind: ArgumentKind,
+    get: fn(&QualitySettings) -> Value,
+    set: fn(&mut QualitySettings, Value),
+) -> ConsoleVariable {
+    ConsoleVariable::bound(
+        name,
+        description,
+        kind,
+        move |ctx| match &*ctx.graphics_context {
+            GraphicsContext::Initialized(graphics) => {
+                Some(get(&graphics.renderer.get_quality_settings()))
+            }
+            GraphicsContext::Uninitialized(_) => None,
+        },
+        move |value, ctx| match ctx.graphics_context {
+            GraphicsContext::Initialized(graphics) => {
+                let mut settings = graphics.renderer.get_quality_settings();
+                set(&mut settings, value);
+                graphics
+                    .renderer
+                    .set_quality_settings(&settings)
+                    .map_err(|err| err.to_string())
+            }
+            GraphicsContext::Uninitialized(_) => {
+                Err("Graphics context is not initialized.".to_owned())
+            }
+        },
+    )
+}
+
+// All values passed to setters are already checked, so unwraps below never panic.
+pub(crate) fn engine_variables() -> Vec<ConsoleVariable> {
+    vec![
+        ConsoleVariable::bound(
+            "time_scale",
+            "Global time scale of the game, 0 pauses the game.",
+            ArgumentKind::Float,
+            |ctx| Some(Value::Float(*ctx.time_scale)),
+            |value, ctx| {
+                *ctx.time_scale = value.as_float().unwrap().max(0.0);
+                Ok(())
+            },
+        ),
+        quality_variable(
+            "r_ssao",
+            "Screen-space ambient occlusion.",
+            ArgumentKind::Bool,
+            |s| Value::Bool(s.use_ssao),
+            |s, v| s.use_ssao = v.as_bool().unwrap(),
+        ),
+        quality_variable(
+            "r_ssao_radius",
+            "Radius of screen-space ambient occlusion.",
+            ArgumentKind::Float,
+            |s| Value::Float(s.ssao_radius),
+            |s, v| s.ssao_radius = v.as_float().unwrap().max(0.0),
+        ),
+        quality_variable(
+            "r_fxaa",
+            "Fast approximate anti-aliasing.",
+            ArgumentKind::Bool,
+            |s| Value::Bool(s.fxaa),
+            |s, v| s.fxaa = v.as_bool().unwrap(),
+        ),
+        quality_variable(
+            "r_bloom",
+            "Bloom effect.",
+            ArgumentKind::Bool,
+            |s| Value::Bool(s.use_bloom),
+            |s, v| s.use_bloom = v.as_bool().unwrap(),
+        ),
+        quality_variable(
+            "r_light_scatter",
+            "Light scattering in volumetric lights.",
+            ArgumentKind::Bool,
+            |s| Value::Bool(s.light_scatter_enabled),
+            |s, v| s.light_scatter_enabled = v.as_bool().unwrap(),
+        ),
+        quality_variable(
+            "r_parallax",
+            "Parallax mapping.",
+            ArgumentKind::Bool,
+            |s| Value::Bool(s.use_parallax_mapping),
+            |s, v| s.use_parallax_mapping = v.as_bool().unwrap(),
+        ),
+        quality_variable(
+            "r_point_shadows",
+            "Shadows from point lights.",
+            ArgumentKind::Bool,
+            |s| Value::Bool(s.point_shadows_enabled),
+            |s, v| s.point_shadows_enabled = v.as_bool().unwrap(),
+        ),
+        quality_variable(
+            "r_point_soft_shadows",
+            "Soft shadows from point lights.",
+            ArgumentKind::Bool,
+            |s| Value::Bool(s.point_soft_shadows),
+            |s, v| s.point_soft_shadows = v.as_bool().unwrap(),
+        ),
+        quality_variable(
+            "r_point_shadows_distance",
+            "Maximum distance from the camera to point lights, that cast shadows.",
+            ArgumentKind::Float,
+            |s| Value::Float(s.point_shadows_distance),
+            |s, v| s.point_shadows_distance = v.as_float().unwrap().max(0.0),
+        ),
+        quality_variable(
+            "r_point_shadow_map_size",
+            "Size of point light shadow maps (in pixels).",
+            ArgumentKind::Integer,
+            |s| Value::Integer(s.point_shadow_map_size as i64),
+            |s, v| s.point_shadow_map_size = v.as_integer().unwrap().clamp(1, 8192) as usize,
+        ),
+        quality_variable(
+            "r_spot_shadows",
+            "Shadows from spot lights.",
+            ArgumentKind::Bool,
+            |s| Value::Bool(s.spot_shadows_enabled),
+            |s, v| s.spot_shadows_enabled = v.as_bool().unwrap(),
+        ),
+        quality_variable(
+            "r_spot_soft_shadows",
+            "Soft shadows from spot lights.",
+            ArgumentKind::Bool,
+            |s| Value::Bool(s.spot_soft_shadows),
+            |s, v| s.spot_soft_shadows = v.as_bool().unwrap(),
+        ),
+        quality_variable(
+            "r_spot_shadows_distance",
+            "Maximum distance from the camera to spot lights, that cast shadows.",
+            ArgumentKind::Float,
+            |s| Value::Float(s.spot_shadows_distance),
+            |s, v| s.spot_shadows_distance = v.as_float().unwrap().max(0.0),
+        ),
+        quality_variable(
+            "r_spot_shadow_map_size",
+            "Size of spot light shadow maps (in pixels).",
+            ArgumentKind::Integer,
+            |s| Value::Integer(s.spot_shadow_map_size as i64),
+            |s, v| s.spot_shadow_map_size = v.as_integer().unwrap().clamp(1, 8192) as usize,
+        ),
+    ]
+}
@@ -0,0 +1,630 @@
+//! Crash reporting. See [`CrashHandler`] docs for more info.
+
+use crate::core::{
+    log::{Log, LogMessage, RingBufferSink},
+    parking_lot::Mutex,
+};
+use lazy_static::lazy_static;
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// A function, that is called when a crash report is created. The second argument is the path of
+/// the report file, it is `None` if the report could not be written to disk (or there is no file
+/// system, like on WebAssembly).
+pub type CrashCallback = dyn Fn(&CrashReport, Option<&Path>) + Send + Sync;
+
+/// Information about a crash.
+#[derive(Clone, Debug)]
+pub struct CrashReport {
+    /// Version of the engine.
+    pub engine_version: &'static str,
+    /// Target operating system.
+    pub os: &'static str,
+    /// Target architecture.
+    pub arch: &'static str,
+    /// Panic message or the name of the signal, that killed the process.
+    pub reason: String,
+    /// Source code location of the panic, if known.
+    pub location: Option<String>,
+    /// Name of the crashed thread, if known.
+    pub thread: Option<String>,
+    /// Backtrace of the crashed thread. It could be empty, for example if debug info is stripped.
+    pub backtrace: String,
+    /// Vendor, model and driver version of the GPU. It is `None` if the renderer was not created.
+    pub gpu_info: Option<String>,
+    /// Name (usually a path) of the current scene, see [`CrashHandler::set_scene_name`].
+    pub scene_name: Option<String>,
+    /// Most recent log messages, oldest first.
+    pub log_tail: Vec<LogMessage>,
+}
+
+impl CrashReport {
+    fn new(reason: String, location: Option<String>) -> Self {
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+
+        // The crash could happen while some of the locks are held by the crashed thread, so
+        // nothing here waits for them.
+        let (gpu_info, scene_name, log_tail) = match STATE.try_lock() {
+            Some(state) => (
+                state.gpu_info.clone(),
+                state.scene_name.clone(),
+                state
+                    .log_tail
+                    .as_ref()
+                    .and_then(|ring| ring.try_messages())
+                    .unwrap_or_default(),
+            ),
+            None => Default::default(),
+        };
+
+        Self {
+            engine_version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            reason,
+            location,
+            thread: std::thread::current().name().map(|name| name.to_owned()),
+            backtrace,
+            gpu_info,
+            scene_name,
+            log_tail,
+        }
+    }
+
+    /// Writes the report to a new file in the given directory and returns the path of the file.
+    pub fn write_to_directory<P: AsRef<Path>>(&self, directory: P) -> std::io::Result<PathBuf> {
+        let directory = directory.as_ref();
+        std::fs::create_dir_all(directory)?;
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut path = directory.join(format!("crash_{}.txt", timestamp.as_secs()));
+        let mut index = 1;
+        while path.exists() {
+            path = directory.join(format!("crash_{}_{}.txt", timestamp.as_secs(), index));
+            index += 1;
+        }
+
+        std::fs::write(&path, self.to_string())?;
+        Ok(path)
+    }
+}
+
+impl Display for CrashReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let unknown = "unknown";
+        writeln!(f, "Fyrox {} crash report", self.engine_version)?;
+        writeln!(f, "Platform: {} {}", self.os, self.arch)?;
+        writeln!(f, "Reason: {}", self.reason)?;
+        writeln!(
+            f,
+            "Location: {}",
+            self.location.as_deref().unwrap_or(unknown)
+        )?;
+        writeln!(f, "Thread: {}", self.thread.as_deref().unwrap_or(unknown))?;
+        writeln!(f, "GPU: {}", self.gpu_info.as_deref().unwrap_or(unknown))?;
+        writeln!(
+            f,
+            "Scene: {}",
+            self.scene_name.as_deref().unwrap_or(unknown)
+        )?;
+        writeln!(f, "\nBacktrace:\n{}", self.backtrace)?;
+        writeln!(f, "\nLog:")?;
+        for message in self.log_tail.iter() {
+            write!(f, "{}", message)?;
+        }
+        Ok(())
+    }
+}
+
+struct CrashState {
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    directory: Option<PathBuf>,
+    // Pre-opened file for a report of a native crash, see `native` module.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    native_report_path: Option<PathBuf>,
+    // Reports of native crashes of previous runs, see `CrashHandler::pending_reports`.
+    native_reports: Vec<PathBuf>,
+    callback: Option<Box<CrashCallback>>,
+    gpu_info: Option<String>,
+    scene_name: Option<String>,
+    log_tail: Option<RingBufferSink>,
+}
+
+lazy_static! {
+    static ref STATE: Mutex<CrashState> = Mutex::new(CrashState {
+        directory: None,
+        native_report_path: None,
+        native_reports: Vec::new(),
+        callback: None,
+        gpu_info: None,
+        scene_name: None,
+        log_tail: None,
+    });
+}
+
+// Only the first crash is reported, for example a panic with `panic = "abort"` is followed by
+// SIGABRT.
+static REPORTED: AtomicBool = AtomicBool::new(false);
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+fn report(reason: String, location: Option<String>) {
+    if REPORTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let report = CrashReport::new(reason, location);
+    Log::flush();
+
+    if let Some(state) = STATE.try_lock() {
+        #[cfg(not(target_arch = "wasm32"))]
+        let path = state
+            .directory
+            .as_ref()
+            .and_then(|directory| report.write_to_directory(directory).ok());
+        #[cfg(target_arch = "wasm32")]
+        let path: Option<PathBuf> = None;
+
+        if let Some(callback) = state.callback.as_ref() {
+            callback(&report, path.as_deref());
+        }
+    }
+}
+
+// Formats the part of a report, that is known before a crash. Native crash handler can't allocate
+// memory, so it writes the header formatted in advance.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn format_header(state: &CrashState) -> String {
+    let unknown = "unknown";
+    format!(
+        "Fyrox {} crash report\nPlatform: {} {}\nGPU: {}\nScene: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        state.gpu_info.as_deref().unwrap_or(unknown),
+        state.scene_name.as_deref().unwrap_or(unknown)
+    )
+}
+
+/// Crash handler captures panics and native crashes (segmentation faults, illegal instructions,
+/// aborts, etc. - on Unix-like platforms only), writes a report (see [`CrashReport`]) to disk and
+/// calls a user-defined callback, that could upload the report to a server or show a message box.
+///
+/// The process is in an undefined state after a native crash, so the signal handler only writes a
+/// short report (without a backtrace and a log tail) to a file, that is opened when the handler is
+/// installed (the file is removed on normal exit if there was no crash), and the callback is not
+/// called for such crashes. Instead, the reports are found when the handler is installed on the next
+/// run of the game, see [`CrashHandler::pending_reports`].
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use fyrox::engine::crash::CrashHandler;
+///
+/// CrashHandler::new()
+///     .with_directory("crash_reports")
+///     .with_callback(|report, path| {
+///         // Upload the report to a server, ask the user to send the file, etc.
+///         println!("{} crashed: {}, see {:?}", report.engine_version, report.reason, path);
+///     })
+///     .install();
+///
+/// for path in CrashHandler::pending_reports() {
+///     // Upload the report of a native crash of a previous run and remove it.
+///     let _ = std::fs::remove_file(path);
+/// }
+/// ```
+pub struct CrashHandler {
+    directory: Option<PathBuf>,
+    log_tail: usize,
+    native_crashes: bool,
+    callback: Option<Box<CrashCallback>>,
+}
+
+impl Default for CrashHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrashHandler {
+    /// Default amount of log messages in a report.
+    pub const DEFAULT_LOG_TAIL: usize = 100;
+
+    /// Creates new crash handler, that writes reports to `crash_reports` directory.
+    pub fn new() -> Self {
+        Self {
+            directory: Some("crash_reports".into()),
+            log_tail: Self::DEFAULT_LOG_TAIL,
+            native_crashes: true,
+            callback: None,
+        }
+    }
+
+    /// Sets the directory for crash reports.
+    pub fn with_directory<P: AsRef<Path>>(mut self, directory: P) -> Self {
+        self.directory = Some(directory.as_ref().to_owned());
+        self
+    }
+
+    /// Disables writing of reports to disk, the reports are passed to the callback only.
+    pub fn without_directory(mut self) -> Self {
+        self.directory = None;
+        self
+    }
+
+    /// Sets the amount of most recent log messages, that will be added to a report.
+    pub fn with_log_tail(mut self, messages: usize) -> Self {
+        self.log_tail = messages;
+        self
+    }
+
+    /// Enables or disables handling of native crashes. It could be disabled if the game uses
+    /// some other crash handler (for example, one of a game distribution platform).
+    pub fn with_native_crashes(mut self, enabled: bool) -> Self {
+        self.native_crashes = enabled;
+        self
+    }
+
+    /// Sets a function, that will be called when a report is created.
+    pub fn with_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&CrashReport, Option<&Path>) + Send + Sync + 'static,
+    {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Installs the crash handler. The panic hook of the log (see [`Log::install_panic_hook`]) is
+    /// installed too (if it is not installed yet), it is called before a report is created, so the
+    /// panic message is included in the log tail of the report. The handler could be installed
+    /// only once, next calls change the settings of the handler, but do not install it again.
+    pub fn install(self) {
+        {
+            let mut state = STATE.lock();
+            state.directory = self.directory;
+            state.callback = self.callback;
+            if state.log_tail.is_none() && self.log_tail > 0 {
+                let ring = RingBufferSink::new(self.log_tail);
+                Log::add_sink(ring.clone());
+                state.log_tail = Some(ring);
+            }
+        }
+
+        if INSTALLED.swap(true, Ordering::SeqCst) {
+            #[cfg(unix)]
+            native::update_header();
+            return;
+        }
+
+        // Chain to the panic hook of the log, it writes a backtrace to the log.
+        Log::install_panic_hook();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let reason = match info.payload().downcast_ref::<&str>() {
+                Some(message) => (*message).to_owned(),
+                None => match info.payload().downcast_ref::<String>() {
+                    Some(message) => message.clone(),
+                    None => "panic".to_owned(),
+                },
+            };
+            let location = info
+                .location()
+                .map(|location| format!("{}:{}", location.file(), location.line()));
+            previous(info);
+            report(reason, location);
+        }));
+
+        #[cfg(unix)]
+        if self.native_crashes {
+            native::install();
+        }
+    }
+
+    /// Returns paths of the reports of native crashes, that happened during previous runs of the game
+    /// (Unix-like platforms only). The reports are collected when the handler is installed, they're
+    /// not removed automatically, so the game should remove them once they're handled (uploaded to a
+    /// server, for example), otherwise they will be returned again on the next run.
+    pub fn pending_reports() -> Vec<PathBuf> {
+        STATE.lock().native_reports.clone()
+    }
+
+    /// Sets the description of the GPU, that will be added to reports. The renderer sets it
+    /// automatically when it is created.
+    pub fn set_gpu_info<S: AsRef<str>>(gpu_info: S) {
+        STATE.lock().gpu_info = Some(gpu_info.as_ref().to_owned());
+        #[cfg(unix)]
+        native::update_header();
+    }
+
+    /// Sets the name of the current scene, that will be added to reports. The executor sets it
+    /// automatically for the scene passed with `--override-scene` argument, games should set it
+    /// when they load their scenes.
+    pub fn set_scene_name<S: AsRef<str>>(name: S) {
+        STATE.lock().scene_name = Some(name.as_ref().to_owned());
+        #[cfg(unix)]
+        native::update_header();
+    }
+}
+
+#[cfg(unix)]
+mod native {
+    use super::{format_header, Log, Ordering, REPORTED, STATE};
+    use std::{
+        os::{raw::c_int, unix::io::IntoRawFd},
+        path::{Path, PathBuf},
+        sync::atomic::{AtomicBool, AtomicI32, AtomicPtr},
+    };
+
+    const SIGNALS: [(c_int, &str); 5] = [
+        (libc::SIGSEGV, "SIGSEGV (segmentation fault)"),
+        (libc::SIGBUS, "SIGBUS (bus error)"),
+        (libc::SIGILL, "SIGILL (illegal instruction)"),
+        (libc::SIGFPE, "SIGFPE (arithmetic exception)"),
+        (libc::SIGABRT, "SIGABRT (abort)"),
+    ];
+
+    const ALT_STACK_SIZE: usize = 64 * 1024;
+
+    // Everything the signal handler needs is prepared in advance, because the handler can only
+    // call async-signal-safe functions: it can't allocate memory, take locks or open files.
+    static REPORT_FD: AtomicI32 = AtomicI32::new(-1);
+    static HEADER: AtomicPtr<Vec<u8>> = AtomicPtr::new(std::ptr::null_mut());
+    static WRITTEN: AtomicBool = AtomicBool::new(false);
+
+    fn write_all(fd: c_int, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            // SAFETY: The pointer and the length are taken from a valid slice.
+            let written = unsafe { libc::write(fd, bytes.as_ptr() as *const _, bytes.len()) };
+            if written <= 0 {
+                return;
+            }
+            bytes = &bytes[written as usize..];
+        }
+    }
+
+    extern "C" fn handle_signal(signal: c_int) {
+        // A panic with `panic = "abort"` is already reported, it is followed by SIGABRT.
+        if !REPORTED.swap(true, Ordering::SeqCst) {
+            let name = SIGNALS
+                .iter()
+                .find(|(other, _)| *other == signal)
+                .map_or("unknown signal", |(_, name)| *name);
+
+            // Standard error output is used if there is no report file.
+            let mut fd = REPORT_FD.load(Ordering::SeqCst);
+            if fd < 0 {
+                fd = libc::STDERR_FILENO;
+            }
+
+            let header = HEADER.load(Ordering::SeqCst);
+            if !header.is_null() {
+                // SAFETY: Headers are never freed, see `update_header`.
+                write_all(fd, unsafe { &*header });
+            }
+            write_all(fd, b"Reason: Fatal signal ");
+            write_all(fd, name.as_bytes());
+            write_all(
+                fd,
+                b"\n\nBacktrace and log are not available for native crashes.\n",
+            );
+            WRITTEN.store(true, Ordering::SeqCst);
+        }
+
+        // The handler is reset to the default one (see SA_RESETHAND below), so the signal
+        // terminates the process as usual when the handler returns.
+        // SAFETY: `raise` is async-signal-safe.
+        unsafe {
+            libc::raise(signal);
+        }
+    }
+
+    // Removes the report file on normal exit, if there was no crash.
+    extern "C" fn remove_unused_report() {
+        if !WRITTEN.load(Ordering::SeqCst) {
+            if let Some(state) = STATE.try_lock() {
+                if let Some(path) = state.native_report_path.as_ref() {
+                    let _ = std::fs::remove_file(path);
+                }
+            }
+        }
+    }
+
+    pub(super) fn update_header() {
+        let header = match STATE.try_lock() {
+            Some(state) => format_header(&state),
+            None => return,
+        };
+        // The previous header is leaked, because the signal handler could still be reading it.
+        // The header changes only a few times (for example, when a scene is loaded).
+        HEADER.store(
+            Box::into_raw(Box::new(header.into_bytes())),
+            Ordering::SeqCst,
+        );
+    }
+
+    // Returns id of the process, that created the report file with the given name.
+    fn report_pid(file_name: &str) -> Option<libc::pid_t> {
+        let name = file_name.strip_prefix("crash_")?.strip_suffix(".txt")?;
+        let (_, pid) = name.split_once("_native_")?;
+        pid.parse().ok()
+    }
+
+    fn is_alive(pid: libc::pid_t) -> bool {
+        // SAFETY: Zero signal only checks whether the process exists.
+        let result = unsafe { libc::kill(pid, 0) };
+        result == 0 || std::io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+    }
+
+    // Collects reports of native crashes of previous runs. Empty report files are left by processes,
+    // that were terminated without running `atexit` handlers (by SIGKILL or `_exit`, for example),
+    // such files are removed, unless the process is still running (another instance of the game).
+    pub(super) fn collect_reports(directory: &Path) -> Vec<PathBuf> {
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut reports = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let pid = match path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(report_pid)
+            {
+                Some(pid) => pid,
+                None => continue,
+            };
+
+            if entry.metadata().map_or(true, |metadata| metadata.len() > 0) {
+                reports.push(path);
+            } else if !is_alive(pid) {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+        reports.sort();
+        reports
+    }
+
+    fn open_report_file() {
+        let mut state = STATE.lock();
+        let directory = match state.directory.as_ref() {
+            Some(directory) => directory.clone(),
+            None => return,
+        };
+
+        state.native_reports = collect_reports(&directory);
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let path = directory.join(format!(
+            "crash_{}_native_{}.txt",
+            timestamp.as_secs(),
+            std::process::id()
+        ));
+        let file = std::fs::create_dir_all(&directory).and_then(|_| {
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+        });
+        match file {
+            Ok(file) => {
+                REPORT_FD.store(file.into_raw_fd(), Ordering::SeqCst);
+                state.native_report_path = Some(path);
+                // SAFETY: The function does not unwind.
+                unsafe {
+                    libc::atexit(remove_unused_report);
+                }
+            }
+            Err(err) => Log::err(format!(
+                "Unable to create native crash report file {}: {:?}",
+                path.display(),
+                err
+            )),
+        }
+    }
+
+    // The signal handler runs on an alternate stack, so stack overflows are reported too. Threads,
+    // spawned by the standard library, have their own alternate stacks.
+    fn install_alt_stack() {
+        // SAFETY: The stack is leaked, so it lives as long as the thread.
+        unsafe {
+            let mut current: libc::stack_t = std::mem::zeroed();
+            if libc::sigaltstack(std::ptr::null(), &mut current) == 0
+                && current.ss_flags & libc::SS_DISABLE == 0
+            {
+                return;
+            }
+
+            let stack = Box::leak(vec![0u8; ALT_STACK_SIZE].into_boxed_slice());
+            let new = libc::stack_t {
+                ss_sp: stack.as_mut_ptr() as *mut _,
+                ss_flags: 0,
+                ss_size: ALT_STACK_SIZE,
+            };
+            libc::sigaltstack(&new, std::ptr::null_mut());
+        }
+    }
+
+    pub(super) fn install() {
+        update_header();
+        open_report_file();
+        install_alt_stack();
+
+        for (signal, _) in SIGNALS.iter() {
+            // SAFETY: The action is fully initialized and the handler is async-signal-safe.
+            unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = handle_signal as extern "C" fn(c_int) as libc::sighandler_t;
+                action.sa_flags = libc::SA_RESETHAND | libc::SA_ONSTACK;
+                libc::sigemptyset(&mut action.sa_mask);
+                libc::sigaction(*signal, &action, std::ptr::null_mut());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(unix)]
+    use crate::engine::crash::native;
+    use crate::engine::crash::{format_header, CrashHandler, CrashReport, STATE};
+
+    #[test]
+    fn test_crash_report() {
+        CrashHandler::set_gpu_info("Test GPU");
+        CrashHandler::set_scene_name("data/level.rgs");
+
+        let report = CrashReport::new("Test panic".to_owned(), Some("main.rs:1".to_owned()));
+        assert_eq!(report.gpu_info.as_deref(), Some("Test GPU"));
+
+        let directory = std::env::temp_dir().join("fyrox_crash_report_test");
+        let path = report.write_to_directory(&directory).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("Reason: Test panic"));
+        assert!(text.contains("Location: main.rs:1"));
+        assert!(text.contains("Scene: data/level.rgs"));
+        let _ = std::fs::remove_dir_all(directory);
+
+        // Native crash reports use the header, that is formatted in advance.
+        let header = format_header(&STATE.lock());
+        assert!(header.contains("GPU: Test GPU"));
+        assert!(header.contains("Scene: data/level.rgs"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_native_reports_of_previous_runs() {
+        let directory = std::env::temp_dir().join("fyrox_native_crash_report_test");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+
+        // Id of a finished process.
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let finished = child.id();
+        child.wait().unwrap();
+
+        let crashed = directory.join(format!("crash_1_native_{}.txt", finished));
+        std::fs::write(&crashed, "Reason: Fatal signal SIGSEGV").unwrap();
+        let killed = directory.join(format!("crash_2_native_{}.txt", finished));
+        std::fs::write(&killed, "").unwrap();
+        let running = directory.join(format!("crash_3_native_{}.txt", std::process::id()));
+        std::fs::write(&running, "").unwrap();
+        let panic = directory.join("crash_4.txt");
+        std::fs::write(&panic, "Reason: Test panic").unwrap();
+
+        assert_eq!(native::collect_reports(&directory), vec![crashed]);
+        assert!(!killed.exists());
+        assert!(running.exists());
+        assert!(panic.exists());
+
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
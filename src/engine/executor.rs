@@ -5,9 +5,11 @@ use crate::{
     core::{
         instant::Instant,
         log::{Log, MessageKind},
+        watcher::FileSystemWatcher,
     },
     engine::{
-        Engine, EngineInitParams, GraphicsContext, GraphicsContextParams, SerializationContext,
+        crash::CrashHandler, timestep::FixedTimestep, Engine, EngineInitParams, GraphicsContext,
+        GraphicsContextParams, SerializationContext,
     },
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
@@ -19,6 +21,7 @@ use clap::Parser;
 use std::{
     ops::{Deref, DerefMut},
     sync::Arc,
+    time::Duration,
 };
 use winit::window::WindowAttributes;
 
@@ -31,11 +34,13 @@ struct Args {
 
 /// Executor is a small wrapper that manages plugins and scripts for your game.
 pub struct Executor {
-    event_loop: EventLoop<()>,
+    // Headless executor does not need an event loop, it runs its own loop.
+    event_loop: Option<EventLoop<()>>,
     engine: Engine,
-    desired_update_rate: f32,
+    timestep: FixedTimestep,
     loader: Option<AsyncSceneLoader>,
     headless: bool,
+    hot_reloading: bool,
 }
 
 impl Deref for Executor {
@@ -68,6 +73,8 @@ impl Executor {
         event_loop: EventLoop<()>,
         graphics_context_params: GraphicsContextParams,
     ) -> Self {
+        Log::install_panic_hook();
+
         let serialization_context = Arc::new(SerializationContext::new());
         let engine = Engine::new(EngineInitParams {
             graphics_context_params,
@@ -77,11 +84,12 @@ impl Executor {
         .unwrap();
 
         Self {
-            event_loop,
+            event_loop: Some(event_loop),
             engine,
-            desired_update_rate: Self::DEFAULT_UPDATE_RATE,
+            timestep: FixedTimestep::new(1.0 / Self::DEFAULT_UPDATE_RATE),
             loader: None,
             headless: false,
+            hot_reloading: false,
         }
     }
 
@@ -101,11 +109,35 @@ impl Executor {
         )
     }
 
+    /// Creates new game executor, that runs without a window and without an event loop. Such
+    /// executor could be used for dedicated game servers or to run simulations on machines without
+    /// a display. See [`Engine::new_headless`] for more info.
+    pub fn new_headless() -> Self {
+        Log::install_panic_hook();
+
+        let engine = Engine::new_headless(
+            Arc::new(SerializationContext::new()),
+            ResourceManager::new(),
+        )
+        .unwrap();
+
+        Self {
+            event_loop: None,
+            engine,
+            timestep: FixedTimestep::new(1.0 / Self::DEFAULT_UPDATE_RATE),
+            loader: None,
+            headless: true,
+            hot_reloading: false,
+        }
+    }
+
     /// Defines whether the executor should initialize graphics context or not. Headless mode could
     /// be useful for game servers, where you don't need to have a window, renderer, sound, etc.
-    /// By default, headless mode is off.
+    /// By default, headless mode is off. Keep in mind, that the executor still needs an event loop
+    /// (which requires a display on some platforms), use [`Executor::new_headless`] to avoid it.
     pub fn set_headless(&mut self, headless: bool) {
         self.headless = headless;
+        self.engine.headless = headless;
     }
 
     /// Returns `true` if the headless mode is turned on, `false` - otherwise.
@@ -113,14 +145,39 @@ impl Executor {
         self.headless
     }
 
+    /// Defines whether the executor should watch the working directory of the game and reload
+    /// changed resources (textures, models, shaders, sounds, etc.) automatically. Reloaded resources
+    /// are propagated to every scene, so the changes will be visible immediately. This is useful
+    /// during development, but should be turned off in production builds. By default, hot reloading
+    /// is off.
+    pub fn set_hot_reloading(&mut self, hot_reloading: bool) {
+        self.hot_reloading = hot_reloading;
+    }
+
+    /// Returns `true` if the hot reloading of resources is turned on, `false` - otherwise.
+    pub fn is_hot_reloading(&self) -> bool {
+        self.hot_reloading
+    }
+
     /// Sets the desired update rate in frames per second.
     pub fn set_desired_update_rate(&mut self, update_rate: f32) {
-        self.desired_update_rate = update_rate.abs();
+        self.timestep.set_fixed_dt(1.0 / update_rate.abs());
     }
 
     /// Returns desired update rate in frames per second.
     pub fn desired_update_rate(&self) -> f32 {
-        self.desired_update_rate
+        1.0 / self.timestep.fixed_dt()
+    }
+
+    /// Sets maximum amount of fixed updates, that could be performed in a single frame. See
+    /// [`FixedTimestep::set_max_catch_up_steps`] for more info.
+    pub fn set_max_catch_up_steps(&mut self, steps: u32) {
+        self.timestep.set_max_catch_up_steps(steps);
+    }
+
+    /// Returns maximum amount of fixed updates per frame.
+    pub fn max_catch_up_steps(&self) -> u32 {
+        self.timestep.max_catch_up_steps()
     }
 
     /// Adds new plugin constructor to the executor, the plugin will be enabled only on [`Executor::run`].
@@ -139,7 +196,22 @@ impl Executor {
 
         let args = Args::parse();
 
+        if self.hot_reloading {
+            match FileSystemWatcher::new(".", Duration::from_secs(1)) {
+                Ok(watcher) => {
+                    engine.resource_manager.state().set_watcher(Some(watcher));
+                }
+                Err(e) => {
+                    Log::err(format!(
+                        "Unable to enable hot reloading of resources. Reason: {e:?}"
+                    ));
+                }
+            }
+        }
+
         if !args.override_scene.is_empty() {
+            CrashHandler::set_scene_name(&args.override_scene);
+
             // Try to load specified scene in a separate thread.
             self.loader = Some(AsyncSceneLoader::begin_loading(
                 args.override_scene.into(),
@@ -152,11 +224,57 @@ impl Executor {
         }
 
         let mut previous = Instant::now();
-        let fixed_time_step = 1.0 / self.desired_update_rate;
-        let mut lag = 0.0;
+        // Mobile applications are suspended when they go to background, the game must not be
+        // updated until it is resumed.
+        let mut suspended = false;
+        let mut timestep = self.timestep;
+        let fixed_time_step = timestep.fixed_dt();
+
+        let event_loop = match event_loop {
+            Some(event_loop) => event_loop,
+            None => {
+                let mut control_flow = ControlFlow::Poll;
+                loop {
+                    poll_scene_loader(&mut self.loader, &mut engine);
+
+                    let scenes = engine
+                        .scenes
+                        .pair_iter()
+                        .map(|(s, _)| s)
+                        .collect::<Vec<_>>();
+
+                    for scene_handle in scenes {
+                        if !engine.has_scripted_scene(scene_handle) {
+                            engine.register_scripted_scene(scene_handle);
+                        }
+                    }
+
+                    let elapsed = previous.elapsed();
+                    previous = Instant::now();
+                    update_frame(
+                        &mut engine,
+                        &mut timestep,
+                        elapsed.as_secs_f32(),
+                        &mut control_flow,
+                    );
+
+                    if let ControlFlow::ExitWithCode(code) = control_flow {
+                        drop(engine);
+                        std::process::exit(code);
+                    }
+
+                    std::thread::sleep(Duration::from_secs_f32(timestep.time_to_next_step()));
+                }
+            }
+        };
 
         event_loop.run(move |event, window_target, control_flow| {
-            engine.handle_os_event_by_plugins(&event, fixed_time_step, control_flow, &mut lag);
+            engine.handle_os_event_by_plugins(
+                &event,
+                fixed_time_step,
+                control_flow,
+                timestep.accumulator_mut(),
+            );
 
             let scenes = engine
                 .scenes
@@ -178,48 +296,48 @@ impl Executor {
                         .initialize_graphics_context(window_target)
                         .expect("Unable to initialize graphics context!");
 
+                    // Time in background must not be simulated.
+                    suspended = false;
+                    previous = Instant::now();
+                    *control_flow = ControlFlow::Poll;
+
                     engine.handle_graphics_context_created_by_plugins(
                         fixed_time_step,
                         control_flow,
-                        &mut lag,
+                        timestep.accumulator_mut(),
                     );
                 }
                 Event::Suspended if !headless => {
+                    // On mobile platforms the surface is destroyed when the application goes to
+                    // background, so the graphics context must be re-created on resume.
                     engine
                         .destroy_graphics_context()
                         .expect("Unable to destroy graphics context!");
 
+                    suspended = true;
+                    engine.input.release_all();
+                    engine.input.cancel_touches();
+
                     engine.handle_graphics_context_destroyed_by_plugins(
                         fixed_time_step,
                         control_flow,
-                        &mut lag,
+                        timestep.accumulator_mut(),
                     );
                 }
+                Event::MainEventsCleared if suspended => {
+                    *control_flow = ControlFlow::Wait;
+                }
                 Event::MainEventsCleared => {
-                    if let Some(loader) = self.loader.as_ref() {
-                        if let Some(result) = loader.fetch_result() {
-                            let override_scene = match result {
-                                Ok(scene) => engine.scenes.add(scene),
-                                Err(e) => {
-                                    Log::err(e);
-                                    Default::default()
-                                }
-                            };
-
-                            engine.enable_plugins(override_scene, true);
-
-                            self.loader = None;
-                        }
-                    }
+                    poll_scene_loader(&mut self.loader, &mut engine);
 
                     let elapsed = previous.elapsed();
                     previous = Instant::now();
-                    lag += elapsed.as_secs_f32();
-
-                    while lag >= fixed_time_step {
-                        engine.update(fixed_time_step, control_flow, &mut lag, Default::default());
-                        lag -= fixed_time_step;
-                    }
+                    update_frame(
+                        &mut engine,
+                        &mut timestep,
+                        elapsed.as_secs_f32(),
+                        control_flow,
+                    );
 
                     if let GraphicsContext::Initialized(ref ctx) = engine.graphics_context {
                         ctx.window.request_redraw();
@@ -229,12 +347,16 @@ impl Executor {
                     engine.handle_before_rendering_by_plugins(
                         fixed_time_step,
                         control_flow,
-                        &mut lag,
+                        timestep.accumulator_mut(),
                     );
 
                     engine.render().unwrap();
                 }
-                Event::WindowEvent { event, .. } => {
+                Event::WindowEvent { window_id, event } => {
+                    if engine.handle_secondary_window_event(window_id, &event) {
+                        return;
+                    }
+
                     match event {
                         WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                         WindowEvent::Resized(size) => {
@@ -257,3 +379,60 @@ impl Executor {
         })
     }
 }
+
+// Enables plugins with the override scene once it is loaded.
+fn poll_scene_loader(loader: &mut Option<AsyncSceneLoader>, engine: &mut Engine) {
+    let result = loader.as_ref().and_then(|loader| {
+        // Upload the scene to GPU over multiple frames when possible, to prevent hitches on the
+        // first frames of the scene.
+        if let GraphicsContext::Initialized(ref mut ctx) = engine.graphics_context {
+            loader.poll(&mut ctx.renderer, Duration::from_millis(8))
+        } else {
+            loader.fetch_result()
+        }
+    });
+
+    if let Some(result) = result {
+        let override_scene = match result {
+            Ok(scene) => engine.scenes.add(scene),
+            Err(e) => {
+                Log::err(e);
+                Default::default()
+            }
+        };
+
+        engine.enable_plugins(override_scene, true);
+
+        *loader = None;
+    }
+}
+
+// Performs fixed updates for the time, that passed since the previous frame, and then the variable
+// update of the frame.
+fn update_frame(
+    engine: &mut Engine,
+    timestep: &mut FixedTimestep,
+    frame_time: f32,
+    control_flow: &mut ControlFlow,
+) {
+    timestep.set_time_scale(engine.time_scale());
+    let frame_dt = timestep.begin_frame(frame_time);
+
+    let fixed_dt = timestep.fixed_dt();
+    while timestep.next_step() {
+        engine.update(
+            fixed_dt,
+            control_flow,
+            timestep.accumulator_mut(),
+            Default::default(),
+        );
+    }
+
+    let interpolation_factor = timestep.interpolation_factor();
+    engine.handle_frame_update_by_plugins(
+        frame_dt,
+        interpolation_factor,
+        control_flow,
+        timestep.accumulator_mut(),
+    );
+}
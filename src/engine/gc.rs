@@ -0,0 +1,66 @@
+//! Amortized cleanup of unused entities - nodes removed from scene graphs, native sound sources of
+//! removed sound nodes and GPU resources that were not used for a while. Instead of destroying all
+//! of them at once, which could cause a noticeable frame time spike (for example, when a large part
+//! of a level is removed), the engine finalizes them over multiple frames within a time budget. See
+//! [`GcSettings`] docs for more info.
+
+use crate::core::instant::Instant;
+use std::time::Duration;
+
+/// Settings of the amortized cleanup pass, that is performed by the engine at the end of every update
+/// tick.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GcSettings {
+    /// Maximum amount of time per frame that could be spent on the cleanup. The budget is checked
+    /// before an entity is finalized, so zero budget effectively disables the cleanup. Default is
+    /// 1 ms.
+    pub time_budget: Duration,
+}
+
+impl Default for GcSettings {
+    fn default() -> Self {
+        Self {
+            time_budget: Duration::from_millis(1),
+        }
+    }
+}
+
+/// Time budget of a single cleanup pass.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeBudget {
+    deadline: Option<Instant>,
+}
+
+impl TimeBudget {
+    /// Creates a new time budget, that starts now and ends after the given amount of time.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            deadline: Some(Instant::now() + duration),
+        }
+    }
+
+    /// Creates a new time budget, that is never exhausted. It could be used to finalize everything
+    /// at once, for example when a scene is unloaded.
+    pub fn unlimited() -> Self {
+        Self { deadline: None }
+    }
+
+    /// Returns `true` if there is no more time left in the budget.
+    pub fn is_exhausted(&self) -> bool {
+        self.deadline
+            .map_or(false, |deadline| Instant::now() >= deadline)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::gc::TimeBudget;
+    use std::time::Duration;
+
+    #[test]
+    fn test_time_budget() {
+        assert!(TimeBudget::new(Duration::ZERO).is_exhausted());
+        assert!(!TimeBudget::new(Duration::from_secs(3600)).is_exhausted());
+        assert!(!TimeBudget::unlimited().is_exhausted());
+    }
+}
@@ -3,9 +3,19 @@
 
 #![warn(missing_docs)]
 
+pub mod crash;
 pub mod error;
 pub mod executor;
+pub mod gc;
+pub mod secondary_window;
+pub mod settings;
+pub mod timestep;
+pub mod window;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::plugin::dynamic::{self, DynamicPlugin, DynamicPluginError};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::renderer::framework::program_cache::ProgramBinaryFunctions;
 use crate::scene::camera::SkyBoxKind;
 use crate::{
     asset::{
@@ -13,24 +23,46 @@ use crate::{
         manager::{ResourceManager, ResourceWaitContext},
         ResourceStateRef,
     },
-    core::{algebra::Vector2, futures::executor::block_on, instant, log::Log, pool::Handle},
-    engine::error::EngineError,
-    event::Event,
+    core::{
+        algebra::Vector2, arena::FrameArena, futures::executor::block_on, instant, log::Log,
+        pool::Handle,
+    },
+    engine::{
+        error::EngineError,
+        gc::{GcSettings, TimeBudget},
+        secondary_window::SecondaryWindow,
+        window::{CursorMode, CustomCursorState, FileDragEvent},
+    },
+    event::{Event, WindowEvent},
     event_loop::ControlFlow,
     gui::UserInterface,
-    material::shader::{loader::ShaderLoader, Shader, ShaderResource, ShaderResourceExtension},
+    input::{gamepad::GamepadBackend, Input},
+    material::{
+        shader::{loader::ShaderLoader, Shader, ShaderResource, ShaderResourceExtension},
+        SharedMaterial,
+    },
     plugin::{Plugin, PluginConstructor, PluginContext, PluginRegistrationContext},
-    renderer::{framework::error::FrameworkError, framework::state::GlKind, Renderer},
+    renderer::{
+        framework::{error::FrameworkError, state::GlKind},
+        snapshot::RenderSnapshot,
+        Renderer,
+    },
     resource::{
         curve::{loader::CurveLoader, CurveResourceState},
+        dialogue::loader::DialogueGraphLoader,
+        item::loader::ItemLoader,
         model::{loader::ModelLoader, Model, ModelResource},
+        quest::loader::QuestLoader,
+        spritesheet::loader::SpriteSheetLoader,
         texture::{loader::TextureLoader, Texture, TextureKind},
     },
     scene::{
         base::NodeScriptMessage,
         graph::GraphUpdateSwitches,
+        mesh::Mesh,
         node::{constructor::NodeConstructorContainer, Node},
         sound::SoundEngine,
+        terrain::Terrain,
         Scene, SceneContainer,
     },
     script::{
@@ -38,24 +70,27 @@ use crate::{
         ScriptDeinitContext, ScriptMessage, ScriptMessageContext, ScriptMessageKind,
         ScriptMessageSender,
     },
-    window::{Window, WindowBuilder},
+    utils::{session::GameSession, translate_event},
+    window::{Window, WindowBuilder, WindowId},
+    xr::{XrError, XrSession},
 };
 use fxhash::{FxHashMap, FxHashSet};
 use fyrox_sound::buffer::{loader::SoundBufferLoader, SoundBuffer};
 #[cfg(not(target_arch = "wasm32"))]
 use glutin::{
-    config::ConfigTemplateBuilder,
+    config::{Config, ConfigTemplateBuilder},
     context::{
         ContextApi, ContextAttributesBuilder, GlProfile, NotCurrentGlContextSurfaceAccessor,
-        PossiblyCurrentContext, Version,
+        PossiblyCurrentContext, PossiblyCurrentContextGlSurfaceAccessor, Version,
     },
     display::{GetGlDisplay, GlDisplay},
     surface::{GlSurface, Surface, SwapInterval, WindowSurface},
 };
 #[cfg(not(target_arch = "wasm32"))]
-use glutin_winit::{DisplayBuilder, GlWindow};
+use glutin_winit::{finalize_window, DisplayBuilder, GlWindow};
 #[cfg(not(target_arch = "wasm32"))]
 use raw_window_handle::HasRawWindowHandle;
+use rayon::prelude::*;
 use std::{
     any::TypeId,
     collections::{HashSet, VecDeque},
@@ -68,7 +103,7 @@ use std::{
     time::Duration,
 };
 #[cfg(not(target_arch = "wasm32"))]
-use std::{ffi::CString, num::NonZeroU32};
+use std::{ffi::CString, num::NonZeroU32, path::Path};
 use winit::{
     dpi::{Position, Size},
     event_loop::EventLoopWindowTarget,
@@ -93,9 +128,14 @@ impl Default for SerializationContext {
 impl SerializationContext {
     /// Creates default serialization context.
     pub fn new() -> Self {
+        let script_constructors = ScriptConstructorContainer::new();
+
+        #[cfg(feature = "lua")]
+        script_constructors.add::<crate::script::lua::LuaScript>("Lua Script");
+
         Self {
             node_constructors: NodeConstructorContainer::new(),
-            script_constructors: ScriptConstructorContainer::new(),
+            script_constructors,
         }
     }
 }
@@ -131,13 +171,27 @@ pub struct InitializedGraphicsContext {
     /// Current renderer.
     pub renderer: Renderer,
 
+    /// A set of additional OS windows, each with its own user interface. See [`SecondaryWindow`] docs
+    /// for more info.
+    pub secondary_windows: FxHashMap<WindowId, SecondaryWindow>,
+
     params: GraphicsContextParams,
     #[cfg(not(target_arch = "wasm32"))]
+    gl_config: Config,
+    #[cfg(not(target_arch = "wasm32"))]
     gl_context: PossiblyCurrentContext,
     #[cfg(not(target_arch = "wasm32"))]
     gl_surface: Surface<WindowSurface>,
 }
 
+#[cfg(all(feature = "openxr", not(target_arch = "wasm32")))]
+impl InitializedGraphicsContext {
+    // Native OpenGL objects of the renderer, they're shared with OpenXR runtime.
+    pub(crate) fn gl_objects(&self) -> (&Config, &PossiblyCurrentContext, &Surface<WindowSurface>) {
+        (&self.gl_config, &self.gl_context, &self.gl_surface)
+    }
+}
+
 /// Graphics context of the engine, it could be in two main states:
 ///
 /// - [`GraphicsContext::Initialized`] - active graphics context, that is fully initialized and ready for use.
@@ -212,20 +266,67 @@ pub struct Engine {
     // A set of plugin constructors.
     plugin_constructors: Vec<Box<dyn PluginConstructor>>,
 
+    // A set of plugins loaded from dynamic libraries.
+    #[cfg(not(target_arch = "wasm32"))]
+    dynamic_plugins: Vec<DynamicPlugin>,
+
     // A set of plugins used by the engine.
     plugins: Vec<Box<dyn Plugin>>,
 
     plugins_enabled: bool,
 
+    plugins_override_scene: Handle<Scene>,
+
+    // Headless engine never creates a graphics context, but still updates everything else.
+    pub(crate) headless: bool,
+
+    // Size of the virtual frame, that is used to update scenes and ui in headless mode.
+    headless_frame_size: Vector2<f32>,
+
     // Amount of time (in seconds) that passed from creation of the engine.
     elapsed_time: f32,
 
+    time_scale: f32,
+
+    /// Action-based input of the game, it is updated automatically at the beginning of every update
+    /// tick. See [`Input`] docs for more info.
+    pub input: Input,
+
+    /// Persistent data of the current game session, it is not affected by loading or unloading scenes.
+    /// See [`GameSession`] docs for more info.
+    pub session: GameSession,
+
+    gamepads: GamepadBackend,
+
+    xr_session: Option<XrSession>,
+
+    cursor_mode: CursorMode,
+
+    custom_cursor: Option<CustomCursorState>,
+
+    file_drag_events: Vec<FileDragEvent>,
+
     /// A special container that is able to create nodes by their type UUID. Use a copy of this
     /// value whenever you need it as a parameter in other parts of the engine.
     pub serialization_context: Arc<SerializationContext>,
 
     /// Script processor is used to run script methods in a strict order.
     pub script_processor: ScriptProcessor,
+
+    // Temporary allocations of plugins, reset at the beginning of each update tick.
+    frame_arena: FrameArena,
+
+    gc_settings: GcSettings,
+
+    // Immutable copy of the scenes of the last rendered frame, it is rendered during the next update
+    // when pipelined rendering is enabled.
+    render_snapshot: RenderSnapshot,
+
+    pipelined_rendering: bool,
+
+    // An error that happened while rendering a snapshot during an update, it is returned by the next
+    // call of `render`.
+    deferred_render_error: Option<FrameworkError>,
 }
 
 /// Performs dispatch of script messages.
@@ -423,6 +524,8 @@ impl ScriptProcessor {
         scenes: &mut SceneContainer,
         plugins: &mut Vec<Box<dyn Plugin>>,
         resource_manager: &ResourceManager,
+        input: &mut Input,
+        session: &mut GameSession,
         dt: f32,
         elapsed_time: f32,
     ) {
@@ -470,6 +573,8 @@ impl ScriptProcessor {
                     resource_manager,
                     message_sender: &scripted_scene.message_sender,
                     message_dispatcher: &mut scripted_scene.message_dispatcher,
+                    input,
+                    session,
                 };
 
                 'init_loop: for init_loop_iteration in 0..max_iterations {
@@ -480,6 +585,8 @@ impl ScriptProcessor {
                     while let Ok(event) = context.scene.graph.script_message_receiver.try_recv() {
                         match event {
                             NodeScriptMessage::InitializeScript { handle } => {
+                                // The node could get a new script, typed queries must see it.
+                                context.scene.graph.query_cache.invalidate();
                                 context.handle = handle;
 
                                 process_node(&mut context, &mut |script, context| {
@@ -735,7 +842,8 @@ macro_rules! define_process_node {
                         return;
                     }
 
-                    if let Some(script) = node.script.take() {
+                    // Scripts are not used for rendering, so the node is not marked as modified.
+                    if let Some(script) = node.untracked_mut().script.take() {
                         script
                     } else {
                         // No script.
@@ -753,7 +861,7 @@ macro_rules! define_process_node {
             // Put the script back to the node. We must do a checked borrow, because it is possible
             // that the node is already destroyed by script logic.
             if let Some(node) = context.scene.graph.try_get_mut(context.handle) {
-                node.script = Some(script);
+                node.untracked_mut().script = Some(script);
             }
         }
     };
@@ -768,6 +876,8 @@ pub(crate) fn process_scripts<T>(
     resource_manager: &ResourceManager,
     message_sender: &ScriptMessageSender,
     message_dispatcher: &mut ScriptMessageDispatcher,
+    input: &mut Input,
+    session: &mut GameSession,
     dt: f32,
     elapsed_time: f32,
     mut func: T,
@@ -783,6 +893,8 @@ pub(crate) fn process_scripts<T>(
         resource_manager,
         message_sender,
         message_dispatcher,
+        input,
+        session,
     };
 
     for node_index in 0..context.scene.graph.capacity() {
@@ -792,6 +904,65 @@ pub(crate) fn process_scripts<T>(
     }
 }
 
+// Renders the given scenes with the main user interface into the main window, and the user interfaces
+// of the secondary windows into their own windows. User interfaces must be drawn before the call.
+fn present_frame(
+    ctx: &mut InitializedGraphicsContext,
+    scenes: &[(Handle<Scene>, Option<&Scene>)],
+    user_interface: &UserInterface,
+) -> Result<(), FrameworkError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        ctx.renderer.render_and_swap_buffers(
+            scenes,
+            user_interface.get_drawing_context(),
+            &ctx.gl_surface,
+            &ctx.gl_context,
+        )?;
+
+        if !ctx.secondary_windows.is_empty() {
+            for secondary_window in ctx.secondary_windows.values() {
+                ctx.gl_context.make_current(&secondary_window.gl_surface)?;
+
+                let inner_size = secondary_window.window.inner_size();
+                ctx.renderer.render_ui_and_swap_buffers(
+                    secondary_window.user_interface.get_drawing_context(),
+                    (inner_size.width, inner_size.height),
+                    &secondary_window.gl_surface,
+                    &ctx.gl_context,
+                )?;
+            }
+
+            // Restore the main window surface.
+            ctx.gl_context.make_current(&ctx.gl_surface)?;
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        ctx.renderer
+            .render_and_swap_buffers(scenes, &user_interface.get_drawing_context())?;
+    }
+
+    Ok(())
+}
+
+fn sync_material_to_shader(
+    material: &SharedMaterial,
+    shader: &ShaderResource,
+    resource_manager: &ResourceManager,
+    synced: &mut FxHashSet<u64>,
+) {
+    if synced.contains(&material.key()) {
+        return;
+    }
+
+    let mut material_ref = material.lock();
+    if material_ref.shader() == shader {
+        material_ref.sync_to_shader(Some(resource_manager));
+        synced.insert(material.key());
+    }
+}
+
 pub(crate) fn initialize_resource_manager_loaders(
     resource_manager: &ResourceManager,
     serialization_context: Arc<SerializationContext>,
@@ -826,12 +997,25 @@ pub(crate) fn initialize_resource_manager_loaders(
     loaders.set(model_loader);
     loaders.set(TextureLoader {
         default_import_options: Default::default(),
+        cache: None,
     });
     loaders.set(SoundBufferLoader {
         default_import_options: Default::default(),
     });
     loaders.set(ShaderLoader);
     loaders.set(CurveLoader);
+
+    // Sprite sheets use the same mechanism as custom resource types of games.
+    state.register_resource_type(SpriteSheetLoader {
+        resource_manager: resource_manager.clone(),
+    });
+    state.register_resource_type(DialogueGraphLoader);
+    state.register_resource_type(ItemLoader {
+        resource_manager: resource_manager.clone(),
+    });
+    state.register_resource_type(QuestLoader);
+    #[cfg(feature = "lua")]
+    state.register_resource_type(crate::script::lua::LuaSourceLoader);
 }
 
 impl Engine {
@@ -898,11 +1082,79 @@ impl Engine {
             serialization_context,
             script_processor: Default::default(),
             plugins_enabled: false,
+            plugins_override_scene: Default::default(),
             plugin_constructors: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            dynamic_plugins: Default::default(),
+            headless: false,
+            headless_frame_size: Vector2::new(1920.0, 1080.0),
             elapsed_time: 0.0,
+            time_scale: 1.0,
+            input: Default::default(),
+            session: Default::default(),
+            gamepads: Default::default(),
+            xr_session: None,
+            cursor_mode: Default::default(),
+            custom_cursor: None,
+            file_drag_events: Default::default(),
+            frame_arena: Default::default(),
+            gc_settings: Default::default(),
+            render_snapshot: Default::default(),
+            pipelined_rendering: false,
+            deferred_render_error: None,
         })
     }
 
+    /// Creates new instance of engine, that runs without a window, graphics context and renderer. Headless
+    /// engine still updates scenes (including physics, animations, scripts, etc.), user interface and
+    /// plugins, but it never renders anything. Such engine could be used for dedicated game servers or to
+    /// run simulation tests on CI machines without GPU. Scenes are updated as if they were rendered in a
+    /// frame of fixed size, it could be changed by [`Engine::set_frame_size`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use fyrox::{
+    ///     asset::manager::ResourceManager,
+    ///     engine::{Engine, SerializationContext},
+    ///     event_loop::ControlFlow,
+    ///     scene::Scene,
+    /// };
+    /// use std::sync::Arc;
+    ///
+    /// let mut engine = Engine::new_headless(
+    ///     Arc::new(SerializationContext::new()),
+    ///     ResourceManager::new(),
+    /// )
+    /// .unwrap();
+    ///
+    /// engine.scenes.add(Scene::new());
+    ///
+    /// // Simulate one second of the game.
+    /// let dt = 1.0 / 60.0;
+    /// for _ in 0..60 {
+    ///     engine.update(dt, &mut ControlFlow::Poll, &mut 0.0, Default::default());
+    /// }
+    /// ```
+    pub fn new_headless(
+        serialization_context: Arc<SerializationContext>,
+        resource_manager: ResourceManager,
+    ) -> Result<Self, EngineError> {
+        let mut engine = Self::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context,
+            resource_manager,
+        })?;
+        engine.headless = true;
+        Ok(engine)
+    }
+
+    /// Returns `true` if the engine runs without graphics context, see [`Engine::new_headless`] for more
+    /// info.
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
     /// Tries to initialize the graphics context. The method will attempt to use the info stored in `graphics_context`
     /// variable of the engine to attempt to initialize the graphics context. It will fail if the graphics context is
     /// already initialized as well as if there any platform-dependent error (for example your hardware does not support
@@ -914,6 +1166,12 @@ impl Engine {
         &mut self,
         window_target: &EventLoopWindowTarget<()>,
     ) -> Result<(), EngineError> {
+        if self.headless {
+            return Err(EngineError::Custom(
+                "Graphics context can't be initialized in headless mode!".to_string(),
+            ));
+        }
+
         if let GraphicsContext::Uninitialized(params) = &self.graphics_context {
             let mut window_builder = WindowBuilder::new();
             if let Some(inner_size) = params.window_attributes.inner_size {
@@ -951,7 +1209,7 @@ impl Engine {
                 .with_active(params.window_attributes.active);
 
             #[cfg(not(target_arch = "wasm32"))]
-            let (window, gl_context, gl_surface, glow_context, gl_kind) = {
+            let (window, gl_config, gl_context, gl_surface, glow_context, gl_kind) = {
                 let template = ConfigTemplateBuilder::new()
                     .prefer_hardware_accelerated(Some(true))
                     .with_stencil_size(8)
@@ -1008,6 +1266,7 @@ impl Engine {
 
                     (
                         window,
+                        gl_config,
                         gl_context,
                         gl_surface,
                         glow::Context::from_loader_function(|s| {
@@ -1060,23 +1319,39 @@ impl Engine {
                     .unwrap_or_else(|| NonZeroU32::new(1).unwrap()),
             );
 
+            #[allow(unused_mut)]
+            let mut renderer = Renderer::new(
+                glow_context,
+                (window.inner_size().width, window.inner_size().height),
+                &self.resource_manager,
+                gl_kind,
+            )?;
+
+            // glow does not wrap program binary functions, they're used by the pipeline cache.
+            #[cfg(not(target_arch = "wasm32"))]
+            renderer
+                .pipeline_state()
+                .set_program_binary_functions(unsafe {
+                    ProgramBinaryFunctions::load(|name| gl_config.display().get_proc_address(name))
+                });
+
             self.graphics_context = GraphicsContext::Initialized(InitializedGraphicsContext {
+                #[cfg(not(target_arch = "wasm32"))]
+                gl_config,
                 #[cfg(not(target_arch = "wasm32"))]
                 gl_context,
                 #[cfg(not(target_arch = "wasm32"))]
                 gl_surface,
-                renderer: Renderer::new(
-                    glow_context,
-                    (window.inner_size().width, window.inner_size().height),
-                    &self.resource_manager,
-                    gl_kind,
-                )?,
+                renderer,
                 window,
+                secondary_windows: Default::default(),
                 params: params.clone(),
             });
 
             self.sound_engine.initialize_audio_output_device()?;
 
+            Log::verify(self.apply_cursor());
+
             Ok(())
         } else {
             Err(EngineError::Custom(
@@ -1121,6 +1396,7 @@ impl Engine {
             });
 
             self.sound_engine.destroy_audio_output_device();
+            self.render_snapshot.clear();
 
             Ok(())
         } else {
@@ -1131,8 +1407,13 @@ impl Engine {
     }
 
     /// Adjust size of the frame to be rendered. Must be called after the window size changes.
-    /// Will update the renderer and GL context frame size.
+    /// Will update the renderer and GL context frame size. In headless mode it sets the size of the
+    /// virtual frame.
     pub fn set_frame_size(&mut self, new_size: (u32, u32)) -> Result<(), FrameworkError> {
+        if self.headless {
+            self.headless_frame_size = Vector2::new(new_size.0 as f32, new_size.1 as f32);
+        }
+
         if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
             ctx.renderer.set_frame_size(new_size)?;
 
@@ -1147,6 +1428,102 @@ impl Engine {
         Ok(())
     }
 
+    /// Creates a new OS window with its own user interface. The window shares the graphics context with the
+    /// main window, which means that it can be created only when the graphics context is initialized. See
+    /// [`SecondaryWindow`] docs for more info. Returns an id of the new window, that can be used to access
+    /// the window using [`InitializedGraphicsContext::secondary_windows`].
+    #[allow(unused_variables)]
+    pub fn create_secondary_window(
+        &mut self,
+        window_target: &EventLoopWindowTarget<()>,
+        window_builder: WindowBuilder,
+    ) -> Result<WindowId, EngineError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+                let window = finalize_window(window_target, window_builder, &ctx.gl_config)
+                    .map_err(|e| EngineError::Custom(format!("{:?}", e)))?;
+
+                let gl_surface = unsafe {
+                    let attrs = window.build_surface_attributes(Default::default());
+                    ctx.gl_config
+                        .display()
+                        .create_window_surface(&ctx.gl_config, &attrs)?
+                };
+
+                let inner_size = window.inner_size();
+                let frame_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
+
+                let id = window.id();
+                ctx.secondary_windows.insert(
+                    id,
+                    SecondaryWindow {
+                        window,
+                        user_interface: UserInterface::new(frame_size),
+                        gl_surface,
+                    },
+                );
+
+                Ok(id)
+            } else {
+                Err(EngineError::Custom(
+                    "Graphics context must be initialized to create secondary windows!".to_string(),
+                ))
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            Err(EngineError::Custom(
+                "Secondary windows are not supported on WebAssembly!".to_string(),
+            ))
+        }
+    }
+
+    /// Destroys a secondary window with the given id. Returns `true` if the window existed, `false` - otherwise.
+    pub fn destroy_secondary_window(&mut self, id: WindowId) -> bool {
+        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            ctx.secondary_windows.remove(&id).is_some()
+        } else {
+            false
+        }
+    }
+
+    /// Passes an OS event to a secondary window with the given id. The method handles resizing of the window
+    /// and routes the input to the user interface of the window. Secondary windows are destroyed automatically
+    /// on close request. Returns `true` if the event was addressed to a secondary window, `false` - otherwise
+    /// (which means that the event should be handled by the main window).
+    pub fn handle_secondary_window_event(&mut self, id: WindowId, event: &WindowEvent) -> bool {
+        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            if let WindowEvent::CloseRequested = event {
+                return ctx.secondary_windows.remove(&id).is_some();
+            }
+
+            if let Some(secondary_window) = ctx.secondary_windows.get_mut(&id) {
+                if let WindowEvent::Resized(size) = event {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    secondary_window.gl_surface.resize(
+                        &ctx.gl_context,
+                        NonZeroU32::new(size.width).unwrap_or_else(|| NonZeroU32::new(1).unwrap()),
+                        NonZeroU32::new(size.height).unwrap_or_else(|| NonZeroU32::new(1).unwrap()),
+                    );
+
+                    secondary_window
+                        .user_interface
+                        .set_screen_size(Vector2::new(size.width as f32, size.height as f32));
+                }
+
+                if let Some(os_event) = translate_event(event) {
+                    secondary_window.user_interface.process_os_event(&os_event);
+                }
+
+                return true;
+            }
+        }
+
+        false
+    }
+
     /// Amount of time (in seconds) that passed from creation of the engine. Keep in mind, that
     /// this value is **not** guaranteed to match real time. A user can change delta time with
     /// which the engine "ticks" and this delta time affects elapsed time.
@@ -1154,6 +1531,47 @@ impl Engine {
         self.elapsed_time
     }
 
+    /// Sets global time scale of the game, that is applied to real time of every frame by the game
+    /// loop (see [`timestep::FixedTimestep`]). It could be used for slow motion (values less than
+    /// 1.0), fast-forwarding (values greater than 1.0) or pausing (0.0) of the game. Plugins could
+    /// change it using [`PluginContext::time_scale`]. Keep in mind, that the engine itself does not
+    /// scale time delta passed to [`Self::update`], it is done by the game loop.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    /// Returns global time scale of the game.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Sets new settings of the amortized cleanup of removed nodes, sound sources and unused GPU resources,
+    /// that is performed at the end of every update tick. See [`GcSettings`] docs for more info.
+    pub fn set_gc_settings(&mut self, settings: GcSettings) {
+        self.gc_settings = settings;
+    }
+
+    /// Returns current settings of the amortized cleanup.
+    pub fn gc_settings(&self) -> &GcSettings {
+        &self.gc_settings
+    }
+
+    /// Enables or disables pipelined rendering. When enabled, [`Self::render`] only copies the state
+    /// of the scenes into an immutable snapshot, and the snapshot is rendered on the main thread during
+    /// the next [`Self::pre_update`], while the scenes are updated in parallel on the task system. This
+    /// could significantly reduce frame time of heavy scenes, but adds one frame of latency. Scripts,
+    /// plugins and the user interface are still updated on the main thread after scene update is
+    /// finished. Pipelined rendering is not used while a VR session is active, because the headset
+    /// requires the poses of the current frame. It is disabled by default.
+    pub fn set_pipelined_rendering(&mut self, enabled: bool) {
+        self.pipelined_rendering = enabled;
+    }
+
+    /// Returns `true` if pipelined rendering is enabled, `false` - otherwise.
+    pub fn is_pipelined_rendering(&self) -> bool {
+        self.pipelined_rendering
+    }
+
     /// Performs single update tick with given time delta. Engine internally will perform update
     /// of all scenes, sub-systems, user interface, etc. Must be called in order to get engine
     /// functioning.
@@ -1199,15 +1617,35 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
-        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
-            let inner_size = ctx.window.inner_size();
-            let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.reload_changed_dynamic_plugins();
+
+        self.frame_arena.reset();
+
+        let window_size = match self.frame_size() {
+            Some(window_size) => window_size,
+            None => return,
+        };
 
-            self.resource_manager.state().update(dt);
+        if !self.headless {
+            self.gamepads.poll(&mut self.input);
+        }
+        if let Some(xr_session) = self.xr_session.as_mut() {
+            let result = xr_session.poll(&mut self.input);
+            self.handle_xr_result(result);
+        }
+        self.input.update();
+        self.resource_manager.state().update(dt);
+        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
             ctx.renderer.update_caches(dt);
-            self.handle_model_events();
+        }
+        self.handle_model_events();
 
-            for (handle, scene) in self.scenes.pair_iter_mut().filter(|(_, s)| s.enabled) {
+        let jobs = self
+            .scenes
+            .pair_iter_mut()
+            .filter(|(_, s)| s.enabled)
+            .map(|(handle, scene)| {
                 let frame_size = scene.render_target.as_ref().map_or(window_size, |rt| {
                     if let TextureKind::Rectangle { width, height } = rt.data_ref().kind() {
                         Vector2::new(width as f32, height as f32)
@@ -1215,17 +1653,38 @@ impl Engine {
                         panic!("only rectangle textures can be used as render target!");
                     }
                 });
+                let switches = switches.get(&handle).cloned().unwrap_or_default();
+                (scene, frame_size, switches)
+            })
+            .collect::<Vec<_>>();
+
+        match self.graphics_context {
+            GraphicsContext::Initialized(ref mut ctx) if self.render_snapshot.take_pending() => {
+                // Render the snapshot of the previous frame on the main thread (it owns the graphics
+                // context), while the scenes are updated on the task system.
+                let result = rayon::in_place_scope(|scope| {
+                    scope.spawn(move |_| {
+                        jobs.into_par_iter()
+                            .for_each(|(scene, frame_size, switches)| {
+                                scene.update(frame_size, dt, switches)
+                            });
+                    });
 
-                scene.update(
-                    frame_size,
-                    dt,
-                    switches.get(&handle).cloned().unwrap_or_default(),
-                );
+                    present_frame(ctx, &self.render_snapshot.scenes(), &self.user_interface)
+                });
+                if let Err(err) = result {
+                    self.deferred_render_error = Some(err);
+                }
+            }
+            _ => {
+                for (scene, frame_size, switches) in jobs {
+                    scene.update(frame_size, dt, switches);
+                }
             }
-
-            self.update_plugins(dt, control_flow, lag);
-            self.handle_scripts(dt);
         }
+
+        self.update_plugins(dt, control_flow, lag);
+        self.handle_scripts(dt);
     }
 
     /// Performs post update for the engine.
@@ -1233,14 +1692,51 @@ impl Engine {
     /// Normally, this is called from `Engine::update()`.
     /// You should only call this manually if you don't use that method.
     pub fn post_update(&mut self, dt: f32) {
+        let window_size = match self.frame_size() {
+            Some(window_size) => window_size,
+            None => return,
+        };
+
+        let time = instant::Instant::now();
+        self.update_custom_cursor();
+        self.user_interface.update(window_size, dt);
+        if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
+            for secondary_window in ctx.secondary_windows.values_mut() {
+                let frame_size = secondary_window.frame_size();
+                secondary_window.user_interface.update(frame_size, dt);
+            }
+        }
+        self.performance_statistics.ui_time = instant::Instant::now() - time;
+        self.elapsed_time += dt;
+        self.file_drag_events.clear();
+
+        self.collect_garbage();
+    }
+
+    // Finalizes removed nodes, sound sources and unused GPU resources within the time budget.
+    fn collect_garbage(&mut self) {
+        let budget = TimeBudget::new(self.gc_settings.time_budget);
+        for scene in self.scenes.iter_mut() {
+            scene.graph.collect_garbage(&budget);
+        }
+        if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
+            ctx.renderer.collect_garbage(&budget);
+        }
+    }
+
+    // Returns size of the main window, or size of the virtual frame in headless mode. `None` means
+    // that the engine can't be updated yet, because its graphics context is not initialized.
+    fn frame_size(&self) -> Option<Vector2<f32>> {
         if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
             let inner_size = ctx.window.inner_size();
-            let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
-
-            let time = instant::Instant::now();
-            self.user_interface.update(window_size, dt);
-            self.performance_statistics.ui_time = instant::Instant::now() - time;
-            self.elapsed_time += dt;
+            Some(Vector2::new(
+                inner_size.width as f32,
+                inner_size.height as f32,
+            ))
+        } else if self.headless {
+            Some(self.headless_frame_size)
+        } else {
+            None
         }
     }
 
@@ -1264,6 +1760,8 @@ impl Engine {
             &mut self.scenes,
             &mut self.plugins,
             &self.resource_manager,
+            &mut self.input,
+            &mut self.session,
             dt,
             self.elapsed_time,
         );
@@ -1285,6 +1783,10 @@ impl Engine {
                 performance_statistics: &self.performance_statistics,
                 elapsed_time: self.elapsed_time,
                 script_processor: &self.script_processor,
+                time_scale: &mut self.time_scale,
+                input: &mut self.input,
+                session: &mut self.session,
+                frame_arena: &self.frame_arena,
             };
 
             for plugin in self.plugins.iter_mut() {
@@ -1303,6 +1805,10 @@ impl Engine {
                     performance_statistics: &self.performance_statistics,
                     elapsed_time: self.elapsed_time,
                     script_processor: &self.script_processor,
+                    time_scale: &mut self.time_scale,
+                    input: &mut self.input,
+                    session: &mut self.session,
+                    frame_arena: &self.frame_arena,
                 };
 
                 for plugin in self.plugins.iter_mut() {
@@ -1321,6 +1827,18 @@ impl Engine {
         control_flow: &mut ControlFlow,
         lag: &mut f32,
     ) {
+        self.input.process_event(event);
+
+        if let (Event::WindowEvent { window_id, event }, GraphicsContext::Initialized(ctx)) =
+            (event, &self.graphics_context)
+        {
+            if *window_id == ctx.window.id() {
+                if let Some(file_drag_event) = FileDragEvent::from_window_event(event) {
+                    self.file_drag_events.push(file_drag_event);
+                }
+            }
+        }
+
         if self.plugins_enabled {
             for plugin in self.plugins.iter_mut() {
                 plugin.on_os_event(
@@ -1336,7 +1854,44 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        time_scale: &mut self.time_scale,
+                        input: &mut self.input,
+                        session: &mut self.session,
+                        frame_arena: &self.frame_arena,
+                    },
+                    control_flow,
+                );
+            }
+        }
+    }
+
+    pub(crate) fn handle_frame_update_by_plugins(
+        &mut self,
+        dt: f32,
+        interpolation_factor: f32,
+        control_flow: &mut ControlFlow,
+        lag: &mut f32,
+    ) {
+        if self.plugins_enabled {
+            for plugin in self.plugins.iter_mut() {
+                plugin.frame_update(
+                    &mut PluginContext {
+                        scenes: &mut self.scenes,
+                        resource_manager: &self.resource_manager,
+                        graphics_context: &mut self.graphics_context,
+                        dt,
+                        lag,
+                        user_interface: &mut self.user_interface,
+                        serialization_context: &self.serialization_context,
+                        performance_statistics: &self.performance_statistics,
+                        elapsed_time: self.elapsed_time,
+                        script_processor: &self.script_processor,
+                        time_scale: &mut self.time_scale,
+                        input: &mut self.input,
+                        session: &mut self.session,
+                        frame_arena: &self.frame_arena,
                     },
+                    interpolation_factor,
                     control_flow,
                 );
             }
@@ -1363,6 +1918,10 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        time_scale: &mut self.time_scale,
+                        input: &mut self.input,
+                        session: &mut self.session,
+                        frame_arena: &self.frame_arena,
                     },
                     control_flow,
                 );
@@ -1390,6 +1949,10 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        time_scale: &mut self.time_scale,
+                        input: &mut self.input,
+                        session: &mut self.session,
+                        frame_arena: &self.frame_arena,
                     },
                     control_flow,
                 );
@@ -1417,6 +1980,10 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        time_scale: &mut self.time_scale,
+                        input: &mut self.input,
+                        session: &mut self.session,
+                        frame_arena: &self.frame_arena,
                     },
                     control_flow,
                 );
@@ -1451,6 +2018,8 @@ impl Engine {
                     &self.resource_manager,
                     &scripted_scene.message_sender,
                     &mut scripted_scene.message_dispatcher,
+                    &mut self.input,
+                    &mut self.session,
                     dt,
                     self.elapsed_time,
                     |script, context| {
@@ -1463,7 +2032,8 @@ impl Engine {
         }
     }
 
-    /// Handle hot-reloading of resources.
+    /// Handle hot-reloading of resources. Reloaded models are propagated to every instance in every
+    /// scene, reloaded shaders cause synchronization of properties of every material that uses them.
     ///
     /// Normally, this is called from `Engine::update()`.
     /// You should only call this manually if you don't use that method.
@@ -1487,6 +2057,42 @@ impl Engine {
                     for scene in self.scenes.iter_mut() {
                         scene.resolve();
                     }
+                } else if let Some(shader) = resource.try_cast::<Shader>() {
+                    Log::info(format!(
+                        "A shader resource {} was reloaded, synchronizing materials...",
+                        shader.path().display()
+                    ));
+
+                    // Textures and sound buffers do not need any special handling, the renderer
+                    // re-uploads changed textures and sound sources read samples directly from
+                    // their buffers. Materials, however, store their own set of properties which
+                    // must match the properties of the shader.
+                    let mut synced = FxHashSet::default();
+                    for scene in self.scenes.iter() {
+                        for node in scene.graph.linear_iter() {
+                            if let Some(mesh) = node.cast::<Mesh>() {
+                                for surface in mesh.surfaces() {
+                                    sync_material_to_shader(
+                                        surface.material(),
+                                        &shader,
+                                        &self.resource_manager,
+                                        &mut synced,
+                                    );
+                                }
+                            } else if let Some(terrain) = node.cast::<Terrain>() {
+                                for layer in terrain.layers() {
+                                    sync_material_to_shader(
+                                        &layer.material,
+                                        &shader,
+                                        &self.resource_manager,
+                                        &mut synced,
+                                    );
+                                }
+                            }
+                        }
+                    }
+
+                    Log::info(format!("{} material(s) were synchronized.", synced.len()));
                 }
             }
         }
@@ -1496,38 +2102,105 @@ impl Engine {
     /// see anything.
     #[inline]
     pub fn render(&mut self) -> Result<(), FrameworkError> {
+        if let Some(err) = self.deferred_render_error.take() {
+            return Err(err);
+        }
+
+        // A snapshot that wasn't rendered by an update (for example, when there was no update tick
+        // in this frame) must be presented with the user interface it was extracted with.
+        if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
+            if self.render_snapshot.take_pending() {
+                present_frame(ctx, &self.render_snapshot.scenes(), &self.user_interface)?;
+            }
+        }
+
         self.user_interface.draw();
+        if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
+            for secondary_window in ctx.secondary_windows.values_mut() {
+                secondary_window.user_interface.draw();
+            }
+        }
+
+        if let Some(xr_session) = self.xr_session.as_mut() {
+            let result = xr_session.begin_frame(&mut self.scenes);
+            self.handle_xr_result(result);
+        }
 
         if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                ctx.renderer.render_and_swap_buffers(
-                    &self.scenes,
-                    self.user_interface.get_drawing_context(),
-                    &ctx.gl_surface,
-                    &ctx.gl_context,
-                )?;
+            if self.pipelined_rendering && self.xr_session.is_none() {
+                self.render_snapshot.extract(&mut self.scenes);
+                return Ok(());
             }
-            #[cfg(target_arch = "wasm32")]
-            {
-                ctx.renderer.render_and_swap_buffers(
-                    &self.scenes,
-                    &self.user_interface.get_drawing_context(),
-                )?;
+
+            let scenes = self
+                .scenes
+                .pair_iter()
+                .map(|(handle, scene)| (handle, Some(scene)))
+                .collect::<Vec<_>>();
+            present_frame(ctx, &scenes, &self.user_interface)?;
+
+            if let Some(xr_session) = self.xr_session.as_mut() {
+                if let Err(err) = xr_session.end_frame(&mut ctx.renderer) {
+                    Log::err(format!("Unable to submit a frame to VR runtime: {err}"));
+                    if let XrError::InstanceLost = err {
+                        self.xr_session = None;
+                    }
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Sets a new VR session, that will be driven by the engine: the headset and the controllers
+    /// will be polled on every update tick and every rendered frame of the session's scene will be
+    /// submitted to the headset. Pass `None` to stop using VR, the rig of the previous session
+    /// stays in its scene. See [`XrSession`] docs for more info.
+    pub fn set_xr_session(&mut self, xr_session: Option<XrSession>) -> Option<XrSession> {
+        if xr_session.is_none() {
+            self.input.reset_xr_controllers();
+        }
+        std::mem::replace(&mut self.xr_session, xr_session)
+    }
+
+    /// Returns a reference to current VR session (if any).
+    pub fn xr_session(&self) -> Option<&XrSession> {
+        self.xr_session.as_ref()
+    }
+
+    /// Returns a mutable reference to current VR session (if any).
+    pub fn xr_session_mut(&mut self) -> Option<&mut XrSession> {
+        self.xr_session.as_mut()
+    }
+
+    fn handle_xr_result(&mut self, result: Result<(), XrError>) {
+        if let Err(err) = result {
+            Log::err(format!("VR session failed: {err}"));
+            // The runtime is gone, there is nothing to talk to anymore.
+            if let XrError::InstanceLost = err {
+                self.xr_session = None;
+                self.input.reset_xr_controllers();
+            }
+        }
+    }
+
     /// Enables or disables registered plugins.
     pub(crate) fn enable_plugins(&mut self, override_scene: Handle<Scene>, enabled: bool) {
         if self.plugins_enabled != enabled {
             self.plugins_enabled = enabled;
+            self.plugins_override_scene = override_scene;
 
             if self.plugins_enabled {
+                let constructors = self.plugin_constructors.iter();
+                #[cfg(not(target_arch = "wasm32"))]
+                let constructors = constructors.chain(
+                    self.dynamic_plugins
+                        .iter()
+                        .filter_map(|plugin| plugin.constructor.as_ref()),
+                );
+
                 // Create and initialize instances.
-                for constructor in self.plugin_constructors.iter() {
+                for constructor in constructors {
                     self.plugins.push(constructor.create_instance(
                         override_scene,
                         PluginContext {
@@ -1541,6 +2214,10 @@ impl Engine {
                             performance_statistics: &self.performance_statistics,
                             elapsed_time: self.elapsed_time,
                             script_processor: &self.script_processor,
+                            time_scale: &mut self.time_scale,
+                            input: &mut self.input,
+                            session: &mut self.session,
+                            frame_arena: &self.frame_arena,
                         },
                     ));
                 }
@@ -1560,6 +2237,10 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        time_scale: &mut self.time_scale,
+                        input: &mut self.input,
+                        session: &mut self.session,
+                        frame_arena: &self.frame_arena,
                     });
                 }
             }
@@ -1577,6 +2258,243 @@ impl Engine {
 
         self.plugin_constructors.push(Box::new(constructor));
     }
+
+    /// Adds a plugin, that is loaded from the given dynamic library. If `reload_when_changed` is
+    /// set, the plugin will be reloaded every time when the library file changes. See
+    /// [`DynamicPlugin`] docs for more info and safety notes.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_dynamic_plugin<P>(
+        &mut self,
+        path: P,
+        reload_when_changed: bool,
+    ) -> Result<(), DynamicPluginError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut plugin = DynamicPlugin::load(path, reload_when_changed)?;
+
+        self.register_dynamic_plugin(&mut plugin);
+
+        if plugin.has_custom_nodes && reload_when_changed {
+            Log::warn(format!(
+                "Dynamic plugin {} registers custom scene nodes, it won't be reloaded!",
+                plugin.path().display()
+            ));
+        }
+
+        if self.plugins_enabled {
+            if let Some(constructor) = plugin.constructor.as_ref() {
+                self.plugins.push(constructor.create_instance(
+                    self.plugins_override_scene,
+                    PluginContext {
+                        scenes: &mut self.scenes,
+                        resource_manager: &self.resource_manager,
+                        graphics_context: &mut self.graphics_context,
+                        dt: 0.0,
+                        lag: &mut 0.0,
+                        user_interface: &mut self.user_interface,
+                        serialization_context: &self.serialization_context,
+                        performance_statistics: &self.performance_statistics,
+                        elapsed_time: self.elapsed_time,
+                        script_processor: &self.script_processor,
+                        time_scale: &mut self.time_scale,
+                        input: &mut self.input,
+                        session: &mut self.session,
+                        frame_arena: &self.frame_arena,
+                    },
+                ));
+            }
+        }
+
+        self.dynamic_plugins.push(plugin);
+
+        Ok(())
+    }
+
+    /// Returns a list of plugins loaded from dynamic libraries.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn dynamic_plugins(&self) -> &[DynamicPlugin] {
+        &self.dynamic_plugins
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn register_dynamic_plugin(&self, plugin: &mut DynamicPlugin) {
+        if let Some(constructor) = plugin.constructor.as_ref() {
+            let context = &self.serialization_context;
+
+            // Remember which types were registered by the plugin, so they could be re-registered on
+            // reloading.
+            let existing_scripts = context
+                .script_constructors
+                .map()
+                .keys()
+                .cloned()
+                .collect::<FxHashSet<_>>();
+            let node_count = context.node_constructors.len();
+
+            constructor.register(PluginRegistrationContext {
+                serialization_context: context,
+            });
+
+            plugin.script_types = context
+                .script_constructors
+                .map()
+                .keys()
+                .filter(|type_uuid| !existing_scripts.contains(type_uuid))
+                .cloned()
+                .collect();
+            plugin.has_custom_nodes = context.node_constructors.len() != node_count;
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_changed_dynamic_plugins(&mut self) {
+        for index in 0..self.dynamic_plugins.len() {
+            let plugin = &self.dynamic_plugins[index];
+            if plugin.is_reload_when_changed() && !plugin.has_custom_nodes && plugin.is_changed() {
+                Log::info(format!(
+                    "Dynamic plugin {} was changed, reloading...",
+                    plugin.path().display()
+                ));
+
+                match self.reload_dynamic_plugin(index) {
+                    Ok(_) => Log::info("Dynamic plugin was successfully reloaded!"),
+                    Err(err) => Log::err(format!("Unable to reload dynamic plugin: {err}")),
+                }
+            }
+        }
+    }
+
+    /// Reloads a dynamic plugin at the given index. Every script instance of the types registered
+    /// by the plugin is serialized before unloading the plugin and restored after loading its new
+    /// version. See [`DynamicPlugin`] docs for more info.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the index is out of bounds.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload_dynamic_plugin(&mut self, index: usize) -> Result<(), DynamicPluginError> {
+        if self.dynamic_plugins[index].has_custom_nodes {
+            return Err(DynamicPluginError::NotReloadable);
+        }
+
+        // Flush pending script messages, their payloads could be of the plugin types.
+        if self.plugins_enabled {
+            self.handle_scripts(0.0);
+        }
+
+        let script_types = self.dynamic_plugins[index].script_types.clone();
+
+        // Scripts could exist in scenes and in scenes of model resources (prefabs).
+        let mut saved_scene_scripts = Vec::new();
+        for (handle, scene) in self.scenes.pair_iter_mut() {
+            saved_scene_scripts.push((
+                handle,
+                dynamic::take_scripts(&mut scene.graph, &script_types),
+            ));
+        }
+        let mut saved_model_scripts = Vec::new();
+        let resources = self.resource_manager.state().resources();
+        for resource in resources {
+            if let Some(model) = resource.try_cast::<Model>() {
+                if model.is_ok() {
+                    let scripts = dynamic::take_scripts(
+                        &mut model.data_ref().get_scene_mut().graph,
+                        &script_types,
+                    );
+                    saved_model_scripts.push((model, scripts));
+                }
+            }
+        }
+
+        // Destroy the plugin instance.
+        let instance_index = self.plugin_constructors.len()
+            + self.dynamic_plugins[..index]
+                .iter()
+                .filter(|plugin| plugin.constructor.is_some())
+                .count();
+        let has_instance =
+            self.plugins_enabled && self.dynamic_plugins[index].constructor.is_some();
+        if has_instance {
+            let mut instance = self.plugins.remove(instance_index);
+            instance.on_deinit(PluginContext {
+                scenes: &mut self.scenes,
+                resource_manager: &self.resource_manager,
+                graphics_context: &mut self.graphics_context,
+                dt: 0.0,
+                lag: &mut 0.0,
+                user_interface: &mut self.user_interface,
+                serialization_context: &self.serialization_context,
+                performance_statistics: &self.performance_statistics,
+                elapsed_time: self.elapsed_time,
+                script_processor: &self.script_processor,
+                time_scale: &mut self.time_scale,
+                input: &mut self.input,
+                session: &mut self.session,
+                frame_arena: &self.frame_arena,
+            });
+        }
+
+        for type_uuid in script_types {
+            self.serialization_context
+                .script_constructors
+                .remove(type_uuid);
+        }
+
+        // Everything that belongs to the plugin is destroyed at this point, so it is safe to unload
+        // the library.
+        let mut plugin = self.dynamic_plugins.remove(index);
+        plugin.unload();
+        let result = plugin.load_library();
+        self.register_dynamic_plugin(&mut plugin);
+        self.dynamic_plugins.insert(index, plugin);
+
+        for (handle, scripts) in saved_scene_scripts {
+            if let Some(scene) = self.scenes.try_get_mut(handle) {
+                dynamic::restore_scripts(
+                    &mut scene.graph,
+                    scripts,
+                    &self.serialization_context,
+                    &self.resource_manager,
+                );
+            }
+        }
+        for (model, scripts) in saved_model_scripts {
+            dynamic::restore_scripts(
+                &mut model.data_ref().get_scene_mut().graph,
+                scripts,
+                &self.serialization_context,
+                &self.resource_manager,
+            );
+        }
+
+        if self.plugins_enabled {
+            if let Some(constructor) = self.dynamic_plugins[index].constructor.as_ref() {
+                let instance = constructor.create_instance(
+                    self.plugins_override_scene,
+                    PluginContext {
+                        scenes: &mut self.scenes,
+                        resource_manager: &self.resource_manager,
+                        graphics_context: &mut self.graphics_context,
+                        dt: 0.0,
+                        lag: &mut 0.0,
+                        user_interface: &mut self.user_interface,
+                        serialization_context: &self.serialization_context,
+                        performance_statistics: &self.performance_statistics,
+                        elapsed_time: self.elapsed_time,
+                        script_processor: &self.script_processor,
+                        time_scale: &mut self.time_scale,
+                        input: &mut self.input,
+                        session: &mut self.session,
+                        frame_arena: &self.frame_arena,
+                    },
+                );
+                self.plugins.insert(instance_index, instance);
+            }
+        }
+
+        result
+    }
 }
 
 impl Drop for Engine {
@@ -1604,16 +2522,27 @@ mod test {
     use crate::{
         asset::manager::ResourceManager,
         core::{pool::Handle, reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
-        engine::ScriptProcessor,
+        engine::{Engine, ScriptProcessor, SerializationContext},
+        event_loop::ControlFlow,
         impl_component_provider,
-        scene::{base::BaseBuilder, node::Node, pivot::PivotBuilder, Scene, SceneContainer},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            node::Node,
+            pivot::PivotBuilder,
+            rigidbody::RigidBodyBuilder,
+            Scene, SceneContainer,
+        },
         script::{
             Script, ScriptContext, ScriptDeinitContext, ScriptMessageContext, ScriptMessagePayload,
             ScriptTrait,
         },
     };
 
-    use std::sync::mpsc::{self, Sender, TryRecvError};
+    use std::sync::{
+        mpsc::{self, Sender, TryRecvError},
+        Arc,
+    };
 
     #[derive(PartialEq, Eq, Clone, Debug)]
     enum Event {
@@ -1749,6 +2678,8 @@ mod test {
                 &mut scene_container,
                 &mut Default::default(),
                 &resource_manager,
+                &mut Default::default(),
+                &mut Default::default(),
                 0.0,
                 0.0,
             );
@@ -1916,6 +2847,8 @@ mod test {
                 &mut scene_container,
                 &mut Default::default(),
                 &resource_manager,
+                &mut Default::default(),
+                &mut Default::default(),
                 0.0,
                 0.0,
             );
@@ -1933,4 +2866,35 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_headless_update() {
+        let mut engine = Engine::new_headless(
+            Arc::new(SerializationContext::new()),
+            ResourceManager::new(),
+        )
+        .unwrap();
+        assert!(engine.is_headless());
+
+        let mut scene = Scene::new();
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.5))
+            .build(&mut scene.graph);
+        let body = RigidBodyBuilder::new(BaseBuilder::new().with_children(&[collider]))
+            .build(&mut scene.graph);
+        let scene_handle = engine.scenes.add(scene);
+
+        for _ in 0..10 {
+            engine.update(
+                1.0 / 60.0,
+                &mut ControlFlow::Poll,
+                &mut 0.0,
+                Default::default(),
+            );
+        }
+
+        // Physics must be simulated even without a graphics context.
+        assert!(engine.scenes[scene_handle].graph[body].global_position().y < 0.0);
+        assert!(engine.elapsed_time() > 0.0);
+    }
 }
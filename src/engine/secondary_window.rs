@@ -0,0 +1,44 @@
+//! Secondary windows are additional OS windows, each with its own user interface. See [`SecondaryWindow`]
+//! docs for more info.
+
+use crate::{core::algebra::Vector2, gui::UserInterface, window::Window};
+#[cfg(not(target_arch = "wasm32"))]
+use glutin::surface::{Surface, WindowSurface};
+
+/// Secondary window is an additional OS window with its own user interface instance. It shares the graphics
+/// context (and thus all GPU resources) with the main window of the engine, so any texture could be shown
+/// in it.
+///
+/// # Input routing
+///
+/// Every OS event addressed to a secondary window is passed only to the user interface of this window,
+/// see [`super::Engine::handle_secondary_window_event`] for more info.
+///
+/// # Scenes
+///
+/// Secondary window does not render scenes directly. To show a scene in a secondary window, set a
+/// [render target](crate::scene::Scene::render_target) for the scene and put an image widget with the
+/// render target texture in the user interface of the window. This way the same scene (with different
+/// cameras) could be shown in multiple windows at once.
+///
+/// # Lifetime
+///
+/// Secondary windows are bound to the graphics context, they're destroyed together with it.
+pub struct SecondaryWindow {
+    /// OS window.
+    pub window: Window,
+
+    /// User interface of the window.
+    pub user_interface: UserInterface,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) gl_surface: Surface<WindowSurface>,
+}
+
+impl SecondaryWindow {
+    /// Returns current size of the client area of the window.
+    pub fn frame_size(&self) -> Vector2<f32> {
+        let inner_size = self.window.inner_size();
+        Vector2::new(inner_size.width as f32, inner_size.height as f32)
+    }
+}
@@ -0,0 +1,452 @@
+//! User-facing settings of a game (graphics quality, audio volumes, key bindings, window mode) with
+//! persistence. See [`SettingsService`] docs for more info.
+
+use crate::{
+    core::{log::Log, reflect::prelude::*},
+    engine::{window::FullscreenMode, Engine, GraphicsContext},
+    gui::key::KeyBinding,
+    renderer::QualitySettings,
+    scene::sound::context::SoundContext,
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Mode of the main window of a game.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum WindowMode {
+    /// Ordinary window with decorations.
+    #[default]
+    Windowed,
+    /// Borderless window, that covers the entire screen.
+    Fullscreen,
+    /// Exclusive fullscreen with the best video mode of current monitor. See
+    /// [`FullscreenMode::Exclusive`] for more info.
+    ExclusiveFullscreen,
+}
+
+/// Audio volumes. Volumes are applied as gains of audio buses of every scene, so sounds should be
+/// routed to buses (for example `Music`, `Effects`, `Voice`) to be controlled separately.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct AudioSettings {
+    /// Gain of the primary bus, that affects all sounds.
+    pub master_volume: f32,
+    /// Gains of audio buses by their names.
+    #[serde(default)]
+    pub bus_volumes: HashMap<String, f32>,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            bus_volumes: Default::default(),
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Applies the volumes to audio buses of the given sound context.
+    pub fn apply(&self, sound_context: &SoundContext) {
+        let mut state = sound_context.state();
+        let bus_graph = state.bus_graph_mut();
+        bus_graph.primary_bus_mut().set_gain(self.master_volume);
+        for bus in bus_graph.buses_iter_mut() {
+            if let Some(volume) = self.bus_volumes.get(bus.name()) {
+                bus.set_gain(*volume);
+            }
+        }
+    }
+}
+
+/// Settings of a game, that are usually exposed in options menus.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct Settings {
+    /// Quality settings of the renderer.
+    #[serde(default)]
+    pub graphics: QualitySettings,
+    /// Audio volumes.
+    #[serde(default)]
+    pub audio: AudioSettings,
+    /// Key bindings by names of actions.
+    #[serde(default)]
+    pub key_bindings: HashMap<String, KeyBinding>,
+    /// Mode of the main window.
+    #[serde(default)]
+    pub window_mode: WindowMode,
+}
+
+/// An event, that is sent to subscribers of a [`SettingsService`] when a part of settings changes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SettingsEvent {
+    /// Graphics quality settings have changed.
+    GraphicsChanged,
+    /// Audio volumes have changed.
+    AudioChanged,
+    /// Key bindings have changed.
+    KeyBindingsChanged,
+    /// Window mode has changed.
+    WindowModeChanged,
+}
+
+/// All possible errors that may occur during settings loading or saving.
+#[derive(Debug)]
+pub enum SettingsError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Settings file has invalid content.
+    RonSpanned(ron::error::SpannedError),
+    /// Settings could not be serialized.
+    Ron(ron::Error),
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            SettingsError::RonSpanned(v) => write!(f, "Invalid settings file: {v}"),
+            SettingsError::Ron(v) => write!(f, "Unable to serialize settings: {v}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for SettingsError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for SettingsError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::RonSpanned(e)
+    }
+}
+
+impl From<ron::Error> for SettingsError {
+    fn from(e: ron::Error) -> Self {
+        Self::Ron(e)
+    }
+}
+
+/// Returns a platform-specific directory for configuration files of the given application:
+///
+/// - Windows - `%APPDATA%\<app_name>`
+/// - macOS - `~/Library/Application Support/<app_name>`
+/// - Linux and other Unix systems - `$XDG_CONFIG_HOME/<app_name>` or `~/.config/<app_name>`
+/// - iOS - `~/Library/Application Support/<app_name>` inside the sandbox of the application
+/// - Android - `<internal data path>/<app_name>`, requires [`crate::core::io::ANDROID_APP`] to be
+///   set
+///
+/// If there's no such directory (for example, on WebAssembly), a relative path `<app_name>` is
+/// returned.
+pub fn config_directory(app_name: &str) -> PathBuf {
+    let env = |name: &str| {
+        std::env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+
+    let base = if cfg!(target_os = "windows") {
+        env("APPDATA")
+    } else if cfg!(any(target_os = "macos", target_os = "ios")) {
+        env("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else if cfg!(target_os = "android") {
+        android_data_directory()
+    } else if cfg!(target_arch = "wasm32") {
+        None
+    } else {
+        env("XDG_CONFIG_HOME").or_else(|| env("HOME").map(|home| home.join(".config")))
+    };
+
+    base.map_or_else(|| PathBuf::from(app_name), |base| base.join(app_name))
+}
+
+#[cfg(target_os = "android")]
+fn android_data_directory() -> Option<PathBuf> {
+    crate::core::io::ANDROID_APP
+        .get()
+        .and_then(|app| app.internal_data_path())
+}
+
+#[cfg(not(target_os = "android"))]
+fn android_data_directory() -> Option<PathBuf> {
+    None
+}
+
+/// Settings service keeps settings of a game, applies them to the engine and saves them to a file
+/// in a platform-specific configuration directory (see [`config_directory`]).
+///
+/// Options menus should modify a copy of the settings and pass it to [`SettingsService::set`],
+/// which applies changed parts to the engine and notifies subscribers. Audio volumes are applied
+/// to every scene that exists at the moment, call [`SettingsService::apply`] (or
+/// [`AudioSettings::apply`]) after loading a new scene.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use fyrox::engine::{
+///     settings::{SettingsService, WindowMode},
+///     Engine,
+/// };
+///
+/// fn toggle_fullscreen(service: &mut SettingsService, engine: &mut Engine) {
+///     let mut settings = service.settings().clone();
+///     settings.window_mode = match settings.window_mode {
+///         WindowMode::Windowed => WindowMode::Fullscreen,
+///         WindowMode::Fullscreen | WindowMode::ExclusiveFullscreen => WindowMode::Windowed,
+///     };
+///     service.set(settings, engine);
+///     service.save().unwrap();
+/// }
+/// ```
+pub struct SettingsService {
+    settings: Settings,
+    path: PathBuf,
+    subscribers: Vec<Sender<SettingsEvent>>,
+}
+
+impl SettingsService {
+    /// Name of the settings file in the configuration directory.
+    pub const FILE_NAME: &'static str = "settings.ron";
+
+    /// Creates new service, that stores settings in the configuration directory of the given
+    /// application. Settings are loaded from the file if it exists, defaults are used otherwise.
+    pub fn new(app_name: &str) -> Self {
+        Self::from_file(config_directory(app_name).join(Self::FILE_NAME))
+    }
+
+    /// Creates new service, that stores settings in the given file. Settings are loaded from the
+    /// file if it exists, defaults are used otherwise.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_owned();
+
+        let settings = if path.exists() {
+            match Self::load(&path) {
+                Ok(settings) => settings,
+                Err(err) => {
+                    Log::err(format!(
+                        "Unable to load settings from {}: {}. Defaults will be used.",
+                        path.display(),
+                        err
+                    ));
+                    Default::default()
+                }
+            }
+        } else {
+            Default::default()
+        };
+
+        Self {
+            settings,
+            path,
+            subscribers: Default::default(),
+        }
+    }
+
+    fn load(path: &Path) -> Result<Settings, SettingsError> {
+        let file = File::open(path)?;
+        Ok(ron::de::from_reader(file)?)
+    }
+
+    /// Saves current settings to the file.
+    pub fn save(&self) -> Result<(), SettingsError> {
+        if let Some(directory) = self.path.parent() {
+            if !directory.as_os_str().is_empty() {
+                fs::create_dir_all(directory)?;
+            }
+        }
+        let file = File::create(&self.path)?;
+        ron::ser::to_writer_pretty(file, &self.settings, PrettyConfig::default())?;
+        Ok(())
+    }
+
+    /// Returns a path to the settings file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns current settings.
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Returns a key binding of the given action.
+    pub fn key_binding(&self, action: &str) -> KeyBinding {
+        self.settings
+            .key_bindings
+            .get(action)
+            .cloned()
+            .unwrap_or(KeyBinding::NotSet)
+    }
+
+    /// Adds a subscriber, that will receive [`SettingsEvent`]s when settings change.
+    pub fn subscribe(&mut self, sender: Sender<SettingsEvent>) {
+        self.subscribers.push(sender);
+    }
+
+    /// Replaces current settings with the given ones, applies changed parts to the engine and
+    /// notifies subscribers. Settings are not saved automatically, use [`Self::save`] for that.
+    pub fn set(&mut self, settings: Settings, engine: &mut Engine) {
+        for event in self.replace(settings) {
+            self.apply_part(event, engine);
+        }
+    }
+
+    // Replaces settings and notifies subscribers, returns a list of changed parts.
+    fn replace(&mut self, settings: Settings) -> Vec<SettingsEvent> {
+        let mut events = Vec::new();
+        if settings.graphics != self.settings.graphics {
+            events.push(SettingsEvent::GraphicsChanged);
+        }
+        if settings.audio != self.settings.audio {
+            events.push(SettingsEvent::AudioChanged);
+        }
+        if settings.key_bindings != self.settings.key_bindings {
+            events.push(SettingsEvent::KeyBindingsChanged);
+        }
+        if settings.window_mode != self.settings.window_mode {
+            events.push(SettingsEvent::WindowModeChanged);
+        }
+
+        self.settings = settings;
+
+        // Remove subscribers that were dropped.
+        self.subscribers
+            .retain(|subscriber| events.iter().all(|event| subscriber.send(*event).is_ok()));
+
+        events
+    }
+
+    /// Applies every part of the settings to the engine. Call this method once the graphics
+    /// context is initialized and after loading new scenes.
+    pub fn apply(&self, engine: &mut Engine) {
+        for event in [
+            SettingsEvent::GraphicsChanged,
+            SettingsEvent::AudioChanged,
+            SettingsEvent::WindowModeChanged,
+        ] {
+            self.apply_part(event, engine);
+        }
+    }
+
+    fn apply_part(&self, part: SettingsEvent, engine: &mut Engine) {
+        match part {
+            SettingsEvent::GraphicsChanged => {
+                if let GraphicsContext::Initialized(ref mut graphics_context) =
+                    engine.graphics_context
+                {
+                    if graphics_context.renderer.get_quality_settings() != self.settings.graphics {
+                        Log::verify(
+                            graphics_context
+                                .renderer
+                                .set_quality_settings(&self.settings.graphics),
+                        );
+                    }
+                }
+            }
+            SettingsEvent::AudioChanged => {
+                for scene in engine.scenes.iter() {
+                    self.settings.audio.apply(&scene.graph.sound_context);
+                }
+            }
+            SettingsEvent::WindowModeChanged => {
+                if let GraphicsContext::Initialized(ref mut graphics_context) =
+                    engine.graphics_context
+                {
+                    Log::verify(graphics_context.set_fullscreen_mode(
+                        match self.settings.window_mode {
+                            WindowMode::Windowed => FullscreenMode::Windowed,
+                            WindowMode::Fullscreen => FullscreenMode::Borderless { monitor: None },
+                            WindowMode::ExclusiveFullscreen => FullscreenMode::Exclusive {
+                                monitor: None,
+                                video_mode: None,
+                            },
+                        },
+                    ));
+                }
+            }
+            // Key bindings are read by the game itself.
+            SettingsEvent::KeyBindingsChanged => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gui::message::KeyCode;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_settings_persistence() {
+        let path = std::env::temp_dir()
+            .join("fyrox_test_settings")
+            .join(SettingsService::FILE_NAME);
+        let _ = fs::remove_file(&path);
+
+        let mut service = SettingsService::from_file(&path);
+        assert_eq!(service.settings(), &Settings::default());
+
+        let mut settings = Settings::default();
+        settings.audio.master_volume = 0.5;
+        settings.audio.bus_volumes.insert("Music".to_string(), 0.25);
+        settings
+            .key_bindings
+            .insert("Jump".to_string(), KeyBinding::Some(KeyCode::Space));
+        settings.window_mode = WindowMode::Fullscreen;
+        service.replace(settings.clone());
+        service.save().unwrap();
+
+        let service = SettingsService::from_file(&path);
+        assert_eq!(service.settings(), &settings);
+        assert_eq!(
+            service.key_binding("Jump"),
+            KeyBinding::Some(KeyCode::Space)
+        );
+        assert_eq!(service.key_binding("Fire"), KeyBinding::NotSet);
+    }
+
+    #[test]
+    fn test_settings_events() {
+        let mut service = SettingsService::from_file("fyrox_test_settings_events.ron");
+        let (sender, receiver) = channel();
+        service.subscribe(sender);
+
+        let mut settings = service.settings().clone();
+        settings.audio.master_volume = 0.1;
+        settings.window_mode = WindowMode::Fullscreen;
+        service.replace(settings.clone());
+
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                SettingsEvent::AudioChanged,
+                SettingsEvent::WindowModeChanged
+            ]
+        );
+
+        // Nothing has changed.
+        service.replace(settings);
+        assert_eq!(receiver.try_recv().ok(), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Fixed time step with accumulator, that splits real time of the game loop into fixed simulation
+//! steps. See [`FixedTimestep`] docs for more info.
+
+/// Fixed time step splits real time, that passed between frames, into a number of simulation steps
+/// of the same length. It makes the simulation (physics, scripts, game logic) deterministic and
+/// independent of frame rate. The game loop consists of two phases:
+///
+/// 1) Fixed update (simulation) - executed zero or more times per frame with exactly
+///    [`Self::fixed_dt`] delta time.
+/// 2) Variable update (rendering) - executed once per frame. Since the simulation is usually a bit
+///    "behind" the real time, renderer could use [`Self::interpolation_factor`] to interpolate
+///    between previous and current simulation states to get smooth motion.
+///
+/// Unprocessed time is stored in an accumulator. If the game can't keep up with the simulation
+/// (for example the frame took too long), then the accumulator is limited by
+/// [`Self::max_catch_up_steps`] simulation steps, and the rest of the time is dropped. This prevents
+/// the "spiral of death", when every next frame needs more and more simulation steps.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::engine::timestep::FixedTimestep;
+///
+/// let mut timestep = FixedTimestep::new(1.0 / 60.0);
+///
+/// // Real time, that passed since the last frame.
+/// let frame_time = 1.0 / 30.0;
+/// timestep.begin_frame(frame_time);
+/// while timestep.next_step() {
+///     // Simulate the game with `timestep.fixed_dt()` delta time.
+/// }
+/// // Render the frame, interpolating between simulation states using `timestep.interpolation_factor()`.
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedTimestep {
+    fixed_dt: f32,
+    max_catch_up_steps: u32,
+    time_scale: f32,
+    accumulator: f32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(1.0 / 60.0)
+    }
+}
+
+impl FixedTimestep {
+    /// Default maximum amount of simulation steps per frame.
+    pub const DEFAULT_MAX_CATCH_UP_STEPS: u32 = 8;
+
+    /// Creates new fixed time step with the given duration of a simulation step (in seconds).
+    pub fn new(fixed_dt: f32) -> Self {
+        Self {
+            fixed_dt: fixed_dt.max(f32::EPSILON),
+            max_catch_up_steps: Self::DEFAULT_MAX_CATCH_UP_STEPS,
+            time_scale: 1.0,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Sets new duration of a simulation step (in seconds).
+    pub fn set_fixed_dt(&mut self, fixed_dt: f32) {
+        self.fixed_dt = fixed_dt.max(f32::EPSILON);
+    }
+
+    /// Returns duration of a simulation step (in seconds).
+    pub fn fixed_dt(&self) -> f32 {
+        self.fixed_dt
+    }
+
+    /// Sets maximum amount of simulation steps, that could be performed in a single frame. Time
+    /// that exceeds this limit is dropped, which means that the game will slow down instead of
+    /// freezing when it can't keep up with the simulation. Minimal value is 1.
+    pub fn set_max_catch_up_steps(&mut self, steps: u32) {
+        self.max_catch_up_steps = steps.max(1);
+    }
+
+    /// Returns maximum amount of simulation steps per frame.
+    pub fn max_catch_up_steps(&self) -> u32 {
+        self.max_catch_up_steps
+    }
+
+    /// Sets time scale, that is applied to real time of every frame. It could be used for slow
+    /// motion (values less than 1.0), fast-forwarding (values greater than 1.0) or pausing (0.0)
+    /// of the game. Duration of a simulation step remains the same, only amount of steps per frame
+    /// changes, so the simulation remains deterministic. Negative values are clamped to zero.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    /// Returns current time scale.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Returns a reference to the accumulator, that holds remaining (scaled) time that should be
+    /// simulated. It could be reset to zero after some heavy calculations (for example level
+    /// loading), so the game won't try to catch up with the time spent in them.
+    pub fn accumulator_mut(&mut self) -> &mut f32 {
+        &mut self.accumulator
+    }
+
+    /// Returns amount of remaining (scaled) time, that should be simulated.
+    pub fn accumulator(&self) -> f32 {
+        self.accumulator
+    }
+
+    /// Drops all accumulated time.
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+
+    /// Starts a new frame, `frame_time` is real time (in seconds) that passed since the previous
+    /// frame. Returns scaled frame time, that should be used for variable update phase.
+    pub fn begin_frame(&mut self, frame_time: f32) -> f32 {
+        let scaled = frame_time.max(0.0) * self.time_scale;
+        self.accumulator = (self.accumulator + scaled)
+            .min(self.max_catch_up_steps as f32 * self.fixed_dt)
+            .max(0.0);
+        scaled
+    }
+
+    /// Consumes a simulation step from the accumulator. Returns `false` if there is not enough
+    /// time for a simulation step, which means that the fixed update phase of the current frame
+    /// is finished.
+    pub fn next_step(&mut self) -> bool {
+        if self.accumulator >= self.fixed_dt {
+            self.accumulator -= self.fixed_dt;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns a value in `[0; 1]` range, that defines how far the real time is from the last
+    /// simulation step, relative to the duration of a simulation step. It should be used to
+    /// interpolate between previous and current states of the simulation when rendering.
+    pub fn interpolation_factor(&self) -> f32 {
+        (self.accumulator / self.fixed_dt).clamp(0.0, 1.0)
+    }
+
+    /// Returns real time (in seconds) that should pass before the next simulation step.
+    pub fn time_to_next_step(&self) -> f32 {
+        if self.time_scale > 0.0 {
+            (self.fixed_dt - self.accumulator).max(0.0) / self.time_scale
+        } else {
+            self.fixed_dt
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::timestep::FixedTimestep;
+
+    fn count_steps(timestep: &mut FixedTimestep) -> usize {
+        let mut steps = 0;
+        while timestep.next_step() {
+            steps += 1;
+        }
+        steps
+    }
+
+    #[test]
+    fn test_fixed_timestep() {
+        let mut timestep = FixedTimestep::new(0.25);
+
+        timestep.begin_frame(0.6);
+        assert_eq!(count_steps(&mut timestep), 2);
+        assert!((timestep.interpolation_factor() - 0.4).abs() < 0.001);
+
+        timestep.begin_frame(0.2);
+        assert_eq!(count_steps(&mut timestep), 1);
+
+        // Too long frame must not produce more than the maximum amount of steps.
+        timestep.set_max_catch_up_steps(3);
+        timestep.begin_frame(100.0);
+        assert_eq!(count_steps(&mut timestep), 3);
+        assert_eq!(timestep.accumulator(), 0.0);
+
+        // Time scale changes amount of steps, but not their duration.
+        timestep.set_time_scale(0.5);
+        assert_eq!(timestep.begin_frame(1.0), 0.5);
+        assert_eq!(count_steps(&mut timestep), 2);
+
+        timestep.set_time_scale(0.0);
+        timestep.begin_frame(1.0);
+        assert_eq!(count_steps(&mut timestep), 0);
+    }
+}
@@ -0,0 +1,525 @@
+//! Management of the main window: monitors and video modes, fullscreen modes, vertical
+//! synchronization, cursor modes, custom cursors and file drag-and-drop. Most of the methods are
+//! available on [`InitializedGraphicsContext`], cursor-related methods are available on [`Engine`],
+//! because they're preserved when the graphics context is re-created.
+
+use crate::{
+    core::{algebra::Vector2, pool::Handle},
+    engine::{Engine, GraphicsContext, InitializedGraphicsContext},
+    error::ExternalError,
+    event::WindowEvent,
+    gui::{
+        image::ImageBuilder,
+        message::MessageDirection,
+        widget::{WidgetBuilder, WidgetMessage},
+        UiNode,
+    },
+    monitor::{MonitorHandle, VideoMode},
+    resource::texture::TextureResource,
+    utils::into_gui_texture,
+    window::{CursorGrabMode, Fullscreen},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use glutin::surface::{GlSurface, SwapInterval};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::num::NonZeroU32;
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+/// All possible errors that may occur during window management.
+#[derive(Debug)]
+pub enum WindowError {
+    /// There is no monitor with the given index.
+    InvalidMonitor(usize),
+    /// The monitor does not support the given video mode.
+    UnsupportedVideoMode(VideoModeInfo),
+    /// The operation is not supported on current platform.
+    NotSupported(String),
+}
+
+impl Display for WindowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowError::InvalidMonitor(index) => write!(f, "There is no monitor {index}"),
+            WindowError::UnsupportedVideoMode(mode) => write!(
+                f,
+                "Video mode {}x{}@{}mHz ({} bits) is not supported",
+                mode.size.x, mode.size.y, mode.refresh_rate_millihertz, mode.bit_depth
+            ),
+            WindowError::NotSupported(v) => write!(f, "Operation is not supported: {v}"),
+        }
+    }
+}
+
+impl From<ExternalError> for WindowError {
+    fn from(e: ExternalError) -> Self {
+        Self::NotSupported(e.to_string())
+    }
+}
+
+/// A video mode of a monitor, that could be used for exclusive fullscreen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VideoModeInfo {
+    /// Resolution in pixels.
+    pub size: Vector2<u32>,
+    /// Color depth in bits per pixel.
+    pub bit_depth: u16,
+    /// Refresh rate in millihertz (for example 59940 for 59.94 Hz).
+    pub refresh_rate_millihertz: u32,
+}
+
+impl VideoModeInfo {
+    fn from_video_mode(mode: &VideoMode) -> Self {
+        Self {
+            size: Vector2::new(mode.size().width, mode.size().height),
+            bit_depth: mode.bit_depth(),
+            refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+        }
+    }
+}
+
+/// Description of a monitor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorInfo {
+    /// Index of the monitor, that could be used to place the window on it.
+    pub index: usize,
+    /// Human-readable name of the monitor, if available.
+    pub name: Option<String>,
+    /// Position of the top-left corner of the monitor on the virtual desktop, in pixels.
+    pub position: Vector2<i32>,
+    /// Current resolution of the monitor in pixels.
+    pub size: Vector2<u32>,
+    /// DPI scale factor of the monitor.
+    pub scale_factor: f64,
+    /// Current refresh rate in millihertz, if available.
+    pub refresh_rate_millihertz: Option<u32>,
+    /// Video modes, that could be used for exclusive fullscreen on this monitor.
+    pub video_modes: Vec<VideoModeInfo>,
+    /// `true` if this is the primary monitor of the system.
+    pub is_primary: bool,
+}
+
+/// Fullscreen mode of the main window.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FullscreenMode {
+    /// Ordinary window.
+    #[default]
+    Windowed,
+    /// Borderless window, that covers an entire monitor. It does not change the video mode of the
+    /// monitor, so switching to it and back is fast.
+    Borderless {
+        /// Index of the monitor, `None` - current monitor of the window.
+        monitor: Option<usize>,
+    },
+    /// Exclusive fullscreen, that changes the video mode of a monitor. It may give slightly better
+    /// performance and lower latency, but switching is slower and other applications can't be
+    /// shown on top of the window.
+    Exclusive {
+        /// Index of the monitor, `None` - current monitor of the window.
+        monitor: Option<usize>,
+        /// Video mode to use, it must be one of [`MonitorInfo::video_modes`] of the monitor.
+        /// `None` - the mode with the largest resolution and the highest refresh rate.
+        video_mode: Option<VideoModeInfo>,
+    },
+}
+
+/// Mode of the mouse cursor.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CursorMode {
+    /// Visible cursor, that moves freely.
+    #[default]
+    Normal,
+    /// Hidden cursor, that moves freely.
+    Hidden,
+    /// Visible cursor, that can't leave the window.
+    Confined,
+    /// Hidden cursor, that is locked in place. Use mouse motion (for example
+    /// [`crate::input::InputSource::MouseAxis`]) to control a camera. This is the usual mode for
+    /// first-person games.
+    Locked,
+}
+
+impl CursorMode {
+    fn is_visible(self) -> bool {
+        matches!(self, CursorMode::Normal | CursorMode::Confined)
+    }
+}
+
+/// An image, that is used as the mouse cursor instead of the OS cursor. The image is drawn by the
+/// user interface of the main window on top of every other widget, the OS cursor is hidden.
+#[derive(Clone, Debug)]
+pub struct CustomCursor {
+    /// Image of the cursor.
+    pub texture: TextureResource,
+    /// Size of the cursor in pixels.
+    pub size: Vector2<f32>,
+    /// A point of the image (in pixels from its top-left corner), that is placed at the position of
+    /// the mouse.
+    pub hotspot: Vector2<f32>,
+}
+
+pub(crate) struct CustomCursorState {
+    cursor: CustomCursor,
+    image: Handle<UiNode>,
+    position: Vector2<f32>,
+}
+
+/// File drag-and-drop event of the main window. Every file is reported separately, if multiple
+/// files are dragged at once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileDragEvent {
+    /// A file is dragged over the window.
+    Hovered(PathBuf),
+    /// A file was dropped on the window.
+    Dropped(PathBuf),
+    /// Dragged files have left the window or the drag was cancelled.
+    Cancelled,
+}
+
+impl FileDragEvent {
+    /// Converts a window event to a drag-and-drop event, if possible.
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        match event {
+            WindowEvent::HoveredFile(path) => Some(Self::Hovered(path.clone())),
+            WindowEvent::DroppedFile(path) => Some(Self::Dropped(path.clone())),
+            WindowEvent::HoveredFileCancelled => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+// Returns index of the mode, that matches the requested one, or the best mode if nothing was
+// requested.
+fn select_video_mode(modes: &[VideoModeInfo], requested: Option<VideoModeInfo>) -> Option<usize> {
+    match requested {
+        Some(requested) => modes.iter().position(|mode| *mode == requested),
+        None => modes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, mode)| {
+                (
+                    mode.size.x as u64 * mode.size.y as u64,
+                    mode.refresh_rate_millihertz,
+                    mode.bit_depth,
+                )
+            })
+            .map(|(index, _)| index),
+    }
+}
+
+impl InitializedGraphicsContext {
+    /// Returns a list of every monitor available in the system.
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        let primary = self.window.primary_monitor();
+        self.window
+            .available_monitors()
+            .enumerate()
+            .map(|(index, monitor)| MonitorInfo {
+                index,
+                name: monitor.name(),
+                position: Vector2::new(monitor.position().x, monitor.position().y),
+                size: Vector2::new(monitor.size().width, monitor.size().height),
+                scale_factor: monitor.scale_factor(),
+                refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+                video_modes: monitor
+                    .video_modes()
+                    .map(|mode| VideoModeInfo::from_video_mode(&mode))
+                    .collect(),
+                is_primary: primary.as_ref() == Some(&monitor),
+            })
+            .collect()
+    }
+
+    /// Returns index of the monitor, that contains the window.
+    pub fn current_monitor(&self) -> Option<usize> {
+        let current = self.window.current_monitor()?;
+        self.window
+            .available_monitors()
+            .position(|monitor| monitor == current)
+    }
+
+    fn monitor(&self, index: Option<usize>) -> Result<Option<MonitorHandle>, WindowError> {
+        match index {
+            Some(index) => self
+                .window
+                .available_monitors()
+                .nth(index)
+                .map(Some)
+                .ok_or(WindowError::InvalidMonitor(index)),
+            None => Ok(self.window.current_monitor()),
+        }
+    }
+
+    /// Returns current fullscreen mode of the window.
+    pub fn fullscreen_mode(&self) -> FullscreenMode {
+        let index_of = |monitor: Option<MonitorHandle>| {
+            monitor.and_then(|monitor| {
+                self.window
+                    .available_monitors()
+                    .position(|other| other == monitor)
+            })
+        };
+
+        match self.window.fullscreen() {
+            None => FullscreenMode::Windowed,
+            Some(Fullscreen::Borderless(monitor)) => FullscreenMode::Borderless {
+                monitor: index_of(monitor),
+            },
+            Some(Fullscreen::Exclusive(mode)) => FullscreenMode::Exclusive {
+                monitor: index_of(Some(mode.monitor())),
+                video_mode: Some(VideoModeInfo::from_video_mode(&mode)),
+            },
+        }
+    }
+
+    /// Switches the window to the given fullscreen mode.
+    pub fn set_fullscreen_mode(&mut self, mode: FullscreenMode) -> Result<(), WindowError> {
+        let fullscreen = match mode {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless { monitor } => {
+                Some(Fullscreen::Borderless(self.monitor(monitor)?))
+            }
+            FullscreenMode::Exclusive {
+                monitor,
+                video_mode,
+            } => {
+                let monitor = self
+                    .monitor(monitor)?
+                    .or_else(|| self.window.primary_monitor())
+                    .ok_or_else(|| WindowError::NotSupported("No monitors found".to_string()))?;
+                let modes = monitor.video_modes().collect::<Vec<_>>();
+                let infos = modes
+                    .iter()
+                    .map(VideoModeInfo::from_video_mode)
+                    .collect::<Vec<_>>();
+                let index =
+                    select_video_mode(&infos, video_mode).ok_or_else(|| match video_mode {
+                        Some(video_mode) => WindowError::UnsupportedVideoMode(video_mode),
+                        None => WindowError::NotSupported(
+                            "The monitor does not have any video modes".to_string(),
+                        ),
+                    })?;
+                Some(Fullscreen::Exclusive(modes[index].clone()))
+            }
+        };
+
+        self.window.set_fullscreen(fullscreen);
+
+        Ok(())
+    }
+
+    /// Moves the window to the center of the given monitor. A window in borderless fullscreen
+    /// mode is moved to the monitor and stays fullscreen.
+    pub fn move_to_monitor(&mut self, index: usize) -> Result<(), WindowError> {
+        let monitor = self
+            .window
+            .available_monitors()
+            .nth(index)
+            .ok_or(WindowError::InvalidMonitor(index))?;
+
+        match self.window.fullscreen() {
+            Some(Fullscreen::Borderless(_)) => {
+                self.window
+                    .set_fullscreen(Some(Fullscreen::Borderless(Some(monitor))));
+            }
+            Some(Fullscreen::Exclusive(_)) => {
+                self.set_fullscreen_mode(FullscreenMode::Exclusive {
+                    monitor: Some(index),
+                    video_mode: None,
+                })?;
+            }
+            None => {
+                let window_size = self.window.outer_size();
+                let x = monitor.position().x
+                    + (monitor.size().width as i32 - window_size.width as i32).max(0) / 2;
+                let y = monitor.position().y
+                    + (monitor.size().height as i32 - window_size.height as i32).max(0) / 2;
+                self.window
+                    .set_outer_position(crate::dpi::PhysicalPosition::new(x, y));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if vertical synchronization is enabled.
+    pub fn vsync(&self) -> bool {
+        self.params.vsync
+    }
+
+    /// Enables or disables vertical synchronization. The setting is preserved when the graphics
+    /// context is re-created. Browsers always synchronize rendering with the display, so v-sync
+    /// can't be disabled on WebAssembly.
+    pub fn set_vsync(&mut self, vsync: bool) -> Result<(), WindowError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let interval = if vsync {
+                SwapInterval::Wait(NonZeroU32::new(1).unwrap())
+            } else {
+                SwapInterval::DontWait
+            };
+            self.gl_surface
+                .set_swap_interval(&self.gl_context, interval)
+                .map_err(|e| WindowError::NotSupported(e.to_string()))?;
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        if !vsync {
+            return Err(WindowError::NotSupported(
+                "V-sync can't be disabled in a browser".to_string(),
+            ));
+        }
+
+        self.params.vsync = vsync;
+
+        Ok(())
+    }
+}
+
+impl Engine {
+    /// Returns current mode of the mouse cursor.
+    pub fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode
+    }
+
+    /// Sets new mode of the mouse cursor. Some platforms support only one of confined or locked
+    /// modes, the other one is used as a fallback in this case. The mode is preserved when the
+    /// graphics context is re-created.
+    pub fn set_cursor_mode(&mut self, mode: CursorMode) -> Result<(), WindowError> {
+        self.cursor_mode = mode;
+        self.apply_cursor()
+    }
+
+    /// Returns current custom cursor, if any.
+    pub fn custom_cursor(&self) -> Option<&CustomCursor> {
+        self.custom_cursor.as_ref().map(|state| &state.cursor)
+    }
+
+    /// Replaces the OS cursor with the given image, `None` restores the OS cursor. See
+    /// [`CustomCursor`] docs for more info.
+    pub fn set_custom_cursor(&mut self, cursor: Option<CustomCursor>) -> Result<(), WindowError> {
+        if let Some(state) = self.custom_cursor.take() {
+            self.user_interface.send_message(WidgetMessage::remove(
+                state.image,
+                MessageDirection::ToWidget,
+            ));
+        }
+
+        if let Some(cursor) = cursor {
+            let position = self.user_interface.cursor_position() - cursor.hotspot;
+            let image = ImageBuilder::new(
+                WidgetBuilder::new()
+                    .with_width(cursor.size.x)
+                    .with_height(cursor.size.y)
+                    .with_desired_position(position)
+                    .with_visibility(self.cursor_mode.is_visible())
+                    .with_hit_test_visibility(false)
+                    .with_draw_on_top(true),
+            )
+            .with_texture(into_gui_texture(cursor.texture.clone()))
+            .build(&mut self.user_interface.build_ctx());
+
+            self.custom_cursor = Some(CustomCursorState {
+                cursor,
+                image,
+                position,
+            });
+        }
+
+        self.apply_cursor()
+    }
+
+    /// Returns drag-and-drop events of the main window, that have happened since the previous
+    /// update. The events are cleared at the end of every update.
+    pub fn file_drag_events(&self) -> &[FileDragEvent] {
+        &self.file_drag_events
+    }
+
+    pub(crate) fn apply_cursor(&self) -> Result<(), WindowError> {
+        if let Some(state) = self.custom_cursor.as_ref() {
+            self.user_interface.send_message(WidgetMessage::visibility(
+                state.image,
+                MessageDirection::ToWidget,
+                self.cursor_mode.is_visible(),
+            ));
+        }
+
+        if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
+            let window = &ctx.window;
+
+            window
+                .set_cursor_visible(self.cursor_mode.is_visible() && self.custom_cursor.is_none());
+
+            let (preferred, fallback) = match self.cursor_mode {
+                CursorMode::Normal | CursorMode::Hidden => (CursorGrabMode::None, None),
+                CursorMode::Confined => (CursorGrabMode::Confined, Some(CursorGrabMode::Locked)),
+                CursorMode::Locked => (CursorGrabMode::Locked, Some(CursorGrabMode::Confined)),
+            };
+
+            if let Err(err) = window.set_cursor_grab(preferred) {
+                match fallback {
+                    Some(fallback) => window.set_cursor_grab(fallback)?,
+                    None => return Err(err.into()),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Moves the image of the custom cursor to the mouse position.
+    pub(crate) fn update_custom_cursor(&mut self) {
+        if let Some(state) = self.custom_cursor.as_mut() {
+            let position = self.user_interface.cursor_position() - state.cursor.hotspot;
+            if position != state.position {
+                state.position = position;
+                self.user_interface
+                    .send_message(WidgetMessage::desired_position(
+                        state.image,
+                        MessageDirection::ToWidget,
+                        position,
+                    ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_video_mode_selection() {
+        let mode = |w, h, hz| VideoModeInfo {
+            size: Vector2::new(w, h),
+            bit_depth: 32,
+            refresh_rate_millihertz: hz,
+        };
+        let modes = [
+            mode(1280, 720, 60000),
+            mode(1920, 1080, 60000),
+            mode(1920, 1080, 144000),
+            mode(1600, 900, 240000),
+        ];
+
+        assert_eq!(select_video_mode(&modes, None), Some(2));
+        assert_eq!(
+            select_video_mode(&modes, Some(mode(1280, 720, 60000))),
+            Some(0)
+        );
+        assert_eq!(select_video_mode(&modes, Some(mode(800, 600, 60000))), None);
+        assert_eq!(select_video_mode(&[], None), None);
+
+        assert_eq!(
+            FileDragEvent::from_window_event(&WindowEvent::DroppedFile("a.png".into())),
+            Some(FileDragEvent::Dropped("a.png".into()))
+        );
+        assert_eq!(
+            FileDragEvent::from_window_event(&WindowEvent::Focused(true)),
+            None
+        );
+    }
+}
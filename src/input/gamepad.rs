@@ -0,0 +1,301 @@
+//! Gamepad support. Gamepads are handled by [`GamepadBackend`], that feeds states of their buttons
+//! and axes to [`Input`], so gamepads could be bound to actions like any other input. See
+//! [`Input::gamepads`] and [`Input::rumble`] for more info.
+
+use crate::{
+    core::log::Log,
+    input::{GamepadAxis, GamepadButton, Input},
+};
+use fxhash::FxHashMap;
+use gilrs::{
+    ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Replay, Ticks},
+    EventType, Gilrs,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+/// Unique identifier of a connected gamepad. Identifiers are assigned in the order of connection,
+/// starting from zero, and are reused when a gamepad is reconnected. It means that `GamepadId(0)`
+/// could be used as the gamepad of the first player, `GamepadId(1)` - of the second, etc.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct GamepadId(pub usize);
+
+impl From<gilrs::GamepadId> for GamepadId {
+    fn from(id: gilrs::GamepadId) -> Self {
+        Self(id.into())
+    }
+}
+
+/// An event of a gamepad.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GamepadEvent {
+    /// A gamepad was connected.
+    Connected(GamepadId),
+    /// A gamepad was disconnected. Every button of the gamepad is released and every axis is reset.
+    Disconnected(GamepadId),
+}
+
+/// Parameters of a force-feedback effect. Most gamepads have two motors: a strong (low-frequency)
+/// one and a weak (high-frequency) one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rumble {
+    /// Magnitude of the strong motor in `[0; 1]` range.
+    pub strong: f32,
+    /// Magnitude of the weak motor in `[0; 1]` range.
+    pub weak: f32,
+    /// Duration of the effect.
+    pub duration: Duration,
+}
+
+impl Rumble {
+    /// Creates new rumble effect.
+    pub fn new(strong: f32, weak: f32, duration: Duration) -> Self {
+        Self {
+            strong,
+            weak,
+            duration,
+        }
+    }
+}
+
+/// All possible errors that may occur during force-feedback effect playback.
+#[derive(Debug)]
+pub enum GamepadError {
+    /// There is no gamepad backend on the current platform.
+    Unsupported,
+    /// The gamepad is not connected.
+    NotConnected(GamepadId),
+    /// The gamepad does not support force-feedback.
+    ForceFeedbackUnsupported(GamepadId),
+    /// Backend error.
+    ForceFeedback(gilrs::ff::Error),
+}
+
+impl Display for GamepadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GamepadError::Unsupported => {
+                write!(f, "Gamepads are not supported on the current platform.")
+            }
+            GamepadError::NotConnected(id) => write!(f, "Gamepad {} is not connected.", id.0),
+            GamepadError::ForceFeedbackUnsupported(id) => {
+                write!(f, "Gamepad {} does not support force-feedback.", id.0)
+            }
+            GamepadError::ForceFeedback(v) => write!(f, "Force-feedback error: {v}"),
+        }
+    }
+}
+
+impl From<gilrs::ff::Error> for GamepadError {
+    fn from(e: gilrs::ff::Error) -> Self {
+        Self::ForceFeedback(e)
+    }
+}
+
+fn translate_button(button: gilrs::Button) -> Option<GamepadButton> {
+    Some(match button {
+        gilrs::Button::South => GamepadButton::South,
+        gilrs::Button::East => GamepadButton::East,
+        gilrs::Button::North => GamepadButton::North,
+        gilrs::Button::West => GamepadButton::West,
+        gilrs::Button::LeftTrigger => GamepadButton::LeftBumper,
+        gilrs::Button::RightTrigger => GamepadButton::RightBumper,
+        gilrs::Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+        gilrs::Button::RightTrigger2 => GamepadButton::RightTrigger,
+        gilrs::Button::Select => GamepadButton::Select,
+        gilrs::Button::Start => GamepadButton::Start,
+        gilrs::Button::Mode => GamepadButton::Mode,
+        gilrs::Button::LeftThumb => GamepadButton::LeftThumb,
+        gilrs::Button::RightThumb => GamepadButton::RightThumb,
+        gilrs::Button::DPadUp => GamepadButton::DPadUp,
+        gilrs::Button::DPadDown => GamepadButton::DPadDown,
+        gilrs::Button::DPadLeft => GamepadButton::DPadLeft,
+        gilrs::Button::DPadRight => GamepadButton::DPadRight,
+        _ => return None,
+    })
+}
+
+fn translate_axis(axis: gilrs::Axis) -> Option<GamepadAxis> {
+    Some(match axis {
+        gilrs::Axis::LeftStickX => GamepadAxis::LeftStickX,
+        gilrs::Axis::LeftStickY => GamepadAxis::LeftStickY,
+        gilrs::Axis::RightStickX => GamepadAxis::RightStickX,
+        gilrs::Axis::RightStickY => GamepadAxis::RightStickY,
+        gilrs::Axis::LeftZ => GamepadAxis::LeftTrigger,
+        gilrs::Axis::RightZ => GamepadAxis::RightTrigger,
+        _ => return None,
+    })
+}
+
+// Gilrs reports analog triggers as buttons with values on most platforms.
+fn trigger_axis(button: gilrs::Button) -> Option<GamepadAxis> {
+    match button {
+        gilrs::Button::LeftTrigger2 => Some(GamepadAxis::LeftTrigger),
+        gilrs::Button::RightTrigger2 => Some(GamepadAxis::RightTrigger),
+        _ => None,
+    }
+}
+
+fn magnitude(value: f32) -> u16 {
+    (value.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+}
+
+/// Gamepad backend polls events of connected gamepads and passes them to [`Input`]. It also plays
+/// rumble effects, requested via [`Input::rumble`]. The engine has its own instance, that is
+/// updated automatically (except in headless mode), so there's no need to use the backend directly
+/// unless you're writing your own game loop.
+#[derive(Default)]
+pub struct GamepadBackend {
+    // Created on first poll, `None` if there's no backend on the current platform.
+    gilrs: Option<Gilrs>,
+    initialized: bool,
+    effects: FxHashMap<GamepadId, Effect>,
+}
+
+impl GamepadBackend {
+    /// Creates new gamepad backend. Actual initialization is deferred until the first
+    /// [`Self::poll`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if gamepads are supported on the current platform. Always returns `false`
+    /// before the first [`Self::poll`].
+    pub fn is_supported(&self) -> bool {
+        self.gilrs.is_some()
+    }
+
+    fn initialize(&mut self, input: &mut Input) {
+        self.initialized = true;
+
+        match Gilrs::new() {
+            Ok(gilrs) => {
+                for (id, gamepad) in gilrs.gamepads() {
+                    input.connect_gamepad(id.into(), gamepad.name());
+                }
+                self.gilrs = Some(gilrs);
+            }
+            Err(e) => Log::warn(format!("Gamepads are unavailable. Reason: {e}")),
+        }
+    }
+
+    /// Passes every pending gamepad event to the given input and plays requested rumble effects.
+    pub fn poll(&mut self, input: &mut Input) {
+        if !self.initialized {
+            self.initialize(input);
+        }
+
+        let gilrs = match self.gilrs.as_mut() {
+            Some(gilrs) => gilrs,
+            None => {
+                input.drain_rumble_requests().for_each(drop);
+                return;
+            }
+        };
+
+        while let Some(gilrs::Event { id, event, .. }) = gilrs.next_event() {
+            let gamepad = GamepadId::from(id);
+            match event {
+                EventType::Connected => {
+                    input.connect_gamepad(gamepad, gilrs.gamepad(id).name());
+                }
+                EventType::Disconnected => {
+                    input.disconnect_gamepad(gamepad);
+                    self.effects.remove(&gamepad);
+                }
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(button) = translate_button(button) {
+                        input.set_gamepad_button(gamepad, button, true);
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(button) = translate_button(button) {
+                        input.set_gamepad_button(gamepad, button, false);
+                    }
+                }
+                EventType::ButtonChanged(button, value, _) => {
+                    if let Some(axis) = trigger_axis(button) {
+                        input.set_gamepad_axis(gamepad, axis, value);
+                    }
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    if let Some(axis) = translate_axis(axis) {
+                        input.set_gamepad_axis(gamepad, axis, value);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let requests = input.drain_rumble_requests().collect::<Vec<_>>();
+        for (gamepad, rumble) in requests {
+            let result = match rumble {
+                Some(rumble) => self.play_rumble(gamepad, rumble),
+                None => self.stop_rumble(gamepad),
+            };
+
+            if let Err(e) = result {
+                Log::warn(format!("Unable to play rumble effect. Reason: {e}"));
+            }
+        }
+    }
+
+    /// Immediately plays a rumble effect on the given gamepad, replacing the previous effect of
+    /// the gamepad.
+    pub fn play_rumble(&mut self, gamepad: GamepadId, rumble: Rumble) -> Result<(), GamepadError> {
+        let gilrs = self.gilrs.as_mut().ok_or(GamepadError::Unsupported)?;
+
+        let (id, ff_supported) = gilrs
+            .gamepads()
+            .find(|(id, _)| GamepadId::from(*id) == gamepad)
+            .map(|(id, pad)| (id, pad.is_ff_supported()))
+            .ok_or(GamepadError::NotConnected(gamepad))?;
+
+        if !ff_supported {
+            return Err(GamepadError::ForceFeedbackUnsupported(gamepad));
+        }
+
+        let scheduling = Replay {
+            play_for: Ticks::from_ms(rumble.duration.as_millis() as u32),
+            ..Default::default()
+        };
+
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong {
+                    magnitude: magnitude(rumble.strong),
+                },
+                scheduling,
+                envelope: Default::default(),
+            })
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Weak {
+                    magnitude: magnitude(rumble.weak),
+                },
+                scheduling,
+                envelope: Default::default(),
+            })
+            .gamepads(&[id])
+            .finish(gilrs)?;
+
+        effect.play()?;
+
+        // Dropping the previous effect stops it.
+        self.effects.insert(gamepad, effect);
+
+        Ok(())
+    }
+
+    /// Stops current rumble effect of the given gamepad.
+    pub fn stop_rumble(&mut self, gamepad: GamepadId) -> Result<(), GamepadError> {
+        if let Some(effect) = self.effects.remove(&gamepad) {
+            effect.stop()?;
+        }
+        Ok(())
+    }
+}
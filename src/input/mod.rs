@@ -0,0 +1,1075 @@
+//! Action-based input. Instead of matching raw OS events, a game defines a set of named actions
+//! (for example `Jump` or `MoveForward`) and binds them to keys, mouse buttons, mouse axes and gamepad
+//! inputs. Game logic then queries actions by their names. See [`Input`] docs for more info.
+
+#![warn(missing_docs)]
+
+pub mod gamepad;
+pub mod touch;
+
+use crate::{
+    core::{algebra::Vector2, instant::Instant},
+    event::{DeviceEvent, ElementState, Event, MouseScrollDelta, WindowEvent},
+    gui::{
+        gesture::{Gesture, GestureRecognizer},
+        message::{KeyCode, MouseButton, TouchPhase},
+    },
+    input::{
+        gamepad::{GamepadEvent, GamepadId, Rumble},
+        touch::{Touch, TouchEvent},
+    },
+    utils::{translate_button, translate_key, translate_touch_phase},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    fs::{self, File},
+    path::Path,
+};
+
+/// Buttons of a gamepad. Names of the face buttons are layout-independent: `South` is `A` on Xbox
+/// controllers and `Cross` on PlayStation controllers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Analog axes of a gamepad. Stick axes have `[-1; 1]` range (up and right are positive), trigger
+/// axes have `[0; 1]` range.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+/// Relative mouse axes. Their values are equal to the mouse movement (in device units) or the wheel
+/// rotation (in lines) since the previous update.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MouseAxis {
+    /// Horizontal movement of the mouse.
+    X,
+    /// Vertical movement of the mouse.
+    Y,
+    /// Rotation of the mouse wheel.
+    Wheel,
+}
+
+/// A hand, that holds a motion controller of a VR headset.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum XrHand {
+    Left,
+    Right,
+}
+
+/// Buttons of a VR motion controller. Names are layout-independent: `Primary` is `A`/`X` button of
+/// Oculus Touch controllers, `Secondary` is `B`/`Y` button.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum XrButton {
+    Primary,
+    Secondary,
+    Thumbstick,
+    Menu,
+}
+
+/// Analog axes of a VR motion controller. Thumbstick axes have `[-1; 1]` range (up and right are
+/// positive), trigger and grip axes have `[0; 1]` range.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum XrAxis {
+    Trigger,
+    Grip,
+    ThumbstickX,
+    ThumbstickY,
+}
+
+/// A physical input, that could be bound to an action.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputSource {
+    /// A key on a keyboard.
+    Key(KeyCode),
+    /// A button of a mouse.
+    MouseButton(MouseButton),
+    /// Movement of a mouse or rotation of its wheel.
+    MouseAxis(MouseAxis),
+    /// A button of a gamepad.
+    GamepadButton(GamepadButton),
+    /// An analog axis of a gamepad.
+    GamepadAxis(GamepadAxis),
+    /// A button of a VR motion controller in the given hand.
+    XrButton(XrHand, XrButton),
+    /// An analog axis of a VR motion controller in the given hand.
+    XrAxis(XrHand, XrAxis),
+    /// Any finger on a touch screen. See [`Input::touches`] for more info about individual
+    /// touches.
+    Touch,
+}
+
+impl InputSource {
+    /// Returns `true` if the source has digital (pressed/released) nature.
+    pub fn is_digital(&self) -> bool {
+        matches!(
+            self,
+            InputSource::Key(_)
+                | InputSource::MouseButton(_)
+                | InputSource::GamepadButton(_)
+                | InputSource::XrButton(..)
+                | InputSource::Touch
+        )
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// A binding of an action to a physical input.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputBinding {
+    /// Physical input of the binding.
+    pub source: InputSource,
+    /// Sensitivity of the binding, the value of the source is multiplied by it. Negative values
+    /// could be used to invert an axis or to bind a key to the negative direction of an axis
+    /// action (for example `A` key of `MoveRight` action).
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Values of the source in `[-dead_zone; dead_zone]` range are treated as zero, values
+    /// outside of the dead zone are remapped to start from zero. Applied to analog sources only.
+    #[serde(default)]
+    pub dead_zone: f32,
+    /// A gamepad, which the binding is restricted to. `None` means any connected gamepad. It
+    /// could be used for local multiplayer, where each player has its own gamepad. Ignored by
+    /// keyboard and mouse sources.
+    #[serde(default)]
+    pub gamepad: Option<GamepadId>,
+}
+
+impl InputBinding {
+    /// Creates new binding with the scale of 1.0 and no dead zone.
+    pub fn new(source: InputSource) -> Self {
+        Self {
+            source,
+            scale: 1.0,
+            dead_zone: 0.0,
+            gamepad: None,
+        }
+    }
+
+    /// Sets sensitivity of the binding.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets dead zone of the binding.
+    pub fn with_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone.clamp(0.0, 0.99);
+        self
+    }
+
+    /// Restricts the binding to the given gamepad.
+    pub fn with_gamepad(mut self, gamepad: GamepadId) -> Self {
+        self.gamepad = Some(gamepad);
+        self
+    }
+
+    fn apply(&self, value: f32) -> f32 {
+        let dead_zone = self.dead_zone.clamp(0.0, 0.99);
+        let value = if self.source.is_digital() || dead_zone == 0.0 {
+            value
+        } else if value.abs() <= dead_zone {
+            0.0
+        } else {
+            value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+        };
+        value * self.scale
+    }
+}
+
+impl From<InputSource> for InputBinding {
+    fn from(source: InputSource) -> Self {
+        Self::new(source)
+    }
+}
+
+/// Defines how values of bindings of an action are combined.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ActionKind {
+    /// The value of the action is the maximum absolute value of its bindings in `[0; 1]` range.
+    /// Analog inputs (for example gamepad triggers) could be bound to such actions too.
+    #[default]
+    Button,
+    /// The value of the action is the sum of the values of its bindings. Values of keys, buttons
+    /// and gamepad axes are clamped to `[-1; 1]` range, values of mouse axes are added to them
+    /// unclamped.
+    Axis,
+}
+
+/// A named action of a game with a set of bindings.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Action {
+    /// Kind of the action.
+    #[serde(default)]
+    pub kind: ActionKind,
+    /// Physical inputs of the action.
+    #[serde(default)]
+    pub bindings: Vec<InputBinding>,
+}
+
+impl Action {
+    /// Creates new button action with the given bindings.
+    pub fn button<I: IntoIterator<Item = InputBinding>>(bindings: I) -> Self {
+        Self {
+            kind: ActionKind::Button,
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+
+    /// Creates new axis action with the given bindings.
+    pub fn axis<I: IntoIterator<Item = InputBinding>>(bindings: I) -> Self {
+        Self {
+            kind: ActionKind::Axis,
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+
+    /// Adds a binding to the action.
+    pub fn with_binding<B: Into<InputBinding>>(mut self, binding: B) -> Self {
+        self.bindings.push(binding.into());
+        self
+    }
+}
+
+/// All possible errors that may occur during input map loading or saving.
+#[derive(Debug)]
+pub enum InputMapError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Input map file has invalid content.
+    RonSpanned(ron::error::SpannedError),
+    /// Input map could not be serialized.
+    Ron(ron::Error),
+}
+
+impl Display for InputMapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputMapError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            InputMapError::RonSpanned(v) => write!(f, "Invalid input map file: {v}"),
+            InputMapError::Ron(v) => write!(f, "Unable to serialize input map: {v}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for InputMapError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for InputMapError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::RonSpanned(e)
+    }
+}
+
+impl From<ron::Error> for InputMapError {
+    fn from(e: ron::Error) -> Self {
+        Self::Ron(e)
+    }
+}
+
+/// A set of named actions. Input map could be saved to a file and loaded back, so bindings changed
+/// by a player (see [`InputMap::rebind`]) will persist.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    /// Actions by their names.
+    pub actions: BTreeMap<String, Action>,
+}
+
+impl InputMap {
+    /// Adds an action to the input map.
+    pub fn with_action<N: Into<String>>(mut self, name: N, action: Action) -> Self {
+        self.actions.insert(name.into(), action);
+        self
+    }
+
+    /// Returns a reference to an action with the given name.
+    pub fn action(&self, name: &str) -> Option<&Action> {
+        self.actions.get(name)
+    }
+
+    /// Replaces a binding of an action. Returns `false` if there's no such action or binding.
+    pub fn rebind(&mut self, action: &str, binding_index: usize, source: InputSource) -> bool {
+        match self
+            .actions
+            .get_mut(action)
+            .and_then(|action| action.bindings.get_mut(binding_index))
+        {
+            Some(binding) => {
+                binding.source = source;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds bindings of every action of the given map, that does not exist in this map. It could be
+    /// used to add new actions of a game to an input map, that was saved by an older version.
+    pub fn merge_missing(&mut self, defaults: &InputMap) {
+        for (name, action) in defaults.actions.iter() {
+            if !self.actions.contains_key(name) {
+                self.actions.insert(name.clone(), action.clone());
+            }
+        }
+    }
+
+    /// Loads an input map from the given file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, InputMapError> {
+        let file = File::open(path)?;
+        Ok(ron::de::from_reader(file)?)
+    }
+
+    /// Saves the input map to the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), InputMapError> {
+        if let Some(directory) = path.as_ref().parent() {
+            if !directory.as_os_str().is_empty() {
+                fs::create_dir_all(directory)?;
+            }
+        }
+        let file = File::create(path)?;
+        ron::ser::to_writer_pretty(file, self, PrettyConfig::default())?;
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct ActionState {
+    value: f32,
+    previous: f32,
+}
+
+/// Input handles raw input events and provides states of actions defined in an [`InputMap`]. The
+/// engine has its own instance (see [`crate::engine::Engine::input`]), that is updated
+/// automatically and is available to plugins and scripts.
+///
+/// States of actions change only on [`Input::update`], that is called by the engine once per
+/// update tick, so the state is consistent during the tick. A press, that was released before the
+/// next update, is not lost: the action is reported as pressed for one tick.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     gui::message::{KeyCode, MouseButton},
+///     input::{Action, GamepadAxis, GamepadButton, Input, InputBinding, InputMap, InputSource},
+/// };
+///
+/// let map = InputMap::default()
+///     .with_action(
+///         "Jump",
+///         Action::button([
+///             InputSource::Key(KeyCode::Space).into(),
+///             InputSource::GamepadButton(GamepadButton::South).into(),
+///         ]),
+///     )
+///     .with_action(
+///         "MoveRight",
+///         Action::axis([
+///             InputSource::Key(KeyCode::KeyD).into(),
+///             InputBinding::new(InputSource::Key(KeyCode::KeyA)).with_scale(-1.0),
+///             InputBinding::new(InputSource::GamepadAxis(GamepadAxis::LeftStickX))
+///                 .with_dead_zone(0.15),
+///         ]),
+///     )
+///     .with_action(
+///         "Fire",
+///         Action::button([InputSource::MouseButton(MouseButton::Left).into()]),
+///     );
+///
+/// let mut input = Input::new(map);
+///
+/// fn update_player(input: &Input) {
+///     if input.is_just_pressed("Jump") {
+///         // Jump.
+///     }
+///     let velocity = input.value("MoveRight") * 5.0;
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Input {
+    map: InputMap,
+    states: FxHashMap<String, ActionState>,
+    pressed: FxHashSet<InputSource>,
+    // Sources that were pressed since the last update, it keeps short presses from being lost.
+    pressed_since_update: FxHashSet<InputSource>,
+    mouse_motion: Vector2<f32>,
+    mouse_wheel: f32,
+    gamepads: BTreeMap<GamepadId, GamepadState>,
+    pending_gamepad_events: Vec<GamepadEvent>,
+    gamepad_events: Vec<GamepadEvent>,
+    rumble_requests: Vec<(GamepadId, Option<Rumble>)>,
+    xr_pressed: FxHashSet<(XrHand, XrButton)>,
+    xr_pressed_since_update: FxHashSet<(XrHand, XrButton)>,
+    xr_axes: FxHashMap<(XrHand, XrAxis), f32>,
+    touches: Vec<Touch>,
+    pending_touch_events: Vec<TouchEvent>,
+    gesture_recognizer: GestureRecognizer,
+    gesture_clock: Option<Instant>,
+    gestures: Vec<Gesture>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct GamepadState {
+    name: String,
+    pressed: FxHashSet<GamepadButton>,
+    pressed_since_update: FxHashSet<GamepadButton>,
+    axes: FxHashMap<GamepadAxis, f32>,
+}
+
+impl Input {
+    /// Value of a button action, at which the action is considered pressed.
+    pub const PRESS_THRESHOLD: f32 = 0.5;
+
+    /// Creates new input with the given input map.
+    pub fn new(map: InputMap) -> Self {
+        Self {
+            map,
+            ..Default::default()
+        }
+    }
+
+    /// Returns a reference to the current input map.
+    pub fn map(&self) -> &InputMap {
+        &self.map
+    }
+
+    /// Returns a reference to the current input map. It could be used to change bindings at
+    /// runtime.
+    pub fn map_mut(&mut self) -> &mut InputMap {
+        &mut self.map
+    }
+
+    /// Replaces the current input map.
+    pub fn set_map(&mut self, map: InputMap) {
+        self.map = map;
+        self.states
+            .retain(|name, _| self.map.actions.contains_key(name));
+    }
+
+    /// Handles an OS event. The engine calls this method automatically for its own instance.
+    pub fn process_event<T>(&mut self, event: &Event<T>) {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput { event, .. } if !event.repeat => {
+                    self.set_pressed(
+                        InputSource::Key(translate_key(event.physical_key)),
+                        event.state == ElementState::Pressed,
+                    );
+                }
+                WindowEvent::MouseInput { state, button, .. } => {
+                    self.set_pressed(
+                        InputSource::MouseButton(translate_button(*button)),
+                        *state == ElementState::Pressed,
+                    );
+                }
+                WindowEvent::MouseWheel { delta, .. } => {
+                    self.add_mouse_wheel(match delta {
+                        MouseScrollDelta::LineDelta(_, y) => *y,
+                        // Roughly convert pixels to lines.
+                        MouseScrollDelta::PixelDelta(position) => position.y as f32 / 16.0,
+                    });
+                }
+                WindowEvent::Touch(touch) => self.add_touch_event(
+                    touch.id,
+                    translate_touch_phase(touch.phase),
+                    Vector2::new(touch.location.x as f32, touch.location.y as f32),
+                ),
+                WindowEvent::Focused(false) => {
+                    self.release_all();
+                    self.cancel_touches();
+                }
+                _ => (),
+            },
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                self.add_mouse_motion(Vector2::new(delta.0 as f32, delta.1 as f32));
+            }
+            _ => (),
+        }
+    }
+
+    /// Sets state of a key or a mouse button. Other sources are ignored, gamepad buttons must be
+    /// set using [`Self::set_gamepad_button`].
+    pub fn set_pressed(&mut self, source: InputSource, pressed: bool) {
+        if !matches!(source, InputSource::Key(_) | InputSource::MouseButton(_)) {
+            return;
+        }
+
+        if pressed {
+            self.pressed.insert(source);
+            self.pressed_since_update.insert(source);
+        } else {
+            self.pressed.remove(&source);
+        }
+    }
+
+    /// Registers a connected gamepad. [`GamepadEvent::Connected`] will be reported on the next
+    /// update.
+    pub fn connect_gamepad(&mut self, gamepad: GamepadId, name: &str) {
+        self.gamepads.entry(gamepad).or_default().name = name.to_owned();
+        self.pending_gamepad_events
+            .push(GamepadEvent::Connected(gamepad));
+    }
+
+    /// Removes a disconnected gamepad. [`GamepadEvent::Disconnected`] will be reported on the next
+    /// update.
+    pub fn disconnect_gamepad(&mut self, gamepad: GamepadId) {
+        if self.gamepads.remove(&gamepad).is_some() {
+            self.pending_gamepad_events
+                .push(GamepadEvent::Disconnected(gamepad));
+        }
+    }
+
+    /// Sets state of a gamepad button.
+    pub fn set_gamepad_button(&mut self, gamepad: GamepadId, button: GamepadButton, pressed: bool) {
+        let state = self.gamepads.entry(gamepad).or_default();
+        if pressed {
+            state.pressed.insert(button);
+            state.pressed_since_update.insert(button);
+        } else {
+            state.pressed.remove(&button);
+        }
+    }
+
+    /// Sets value of a gamepad axis.
+    pub fn set_gamepad_axis(&mut self, gamepad: GamepadId, axis: GamepadAxis, value: f32) {
+        self.gamepads
+            .entry(gamepad)
+            .or_default()
+            .axes
+            .insert(axis, value);
+    }
+
+    /// Sets state of a button of a VR motion controller. The engine calls this method automatically
+    /// when there is an active [`crate::xr::XrSession`].
+    pub fn set_xr_button(&mut self, hand: XrHand, button: XrButton, pressed: bool) {
+        if pressed {
+            self.xr_pressed.insert((hand, button));
+            self.xr_pressed_since_update.insert((hand, button));
+        } else {
+            self.xr_pressed.remove(&(hand, button));
+        }
+    }
+
+    /// Sets value of an axis of a VR motion controller. The engine calls this method automatically
+    /// when there is an active [`crate::xr::XrSession`].
+    pub fn set_xr_axis(&mut self, hand: XrHand, axis: XrAxis, value: f32) {
+        self.xr_axes.insert((hand, axis), value);
+    }
+
+    /// Releases every button and resets every axis of VR motion controllers. It is called when the
+    /// XR session loses input focus (for example, when the system menu of the headset is opened).
+    pub fn reset_xr_controllers(&mut self) {
+        self.xr_pressed.clear();
+        self.xr_axes.clear();
+    }
+
+    /// Releases every button and resets every axis of a VR motion controller in the given hand. It
+    /// is called when the controller is disconnected.
+    pub fn reset_xr_controller(&mut self, hand: XrHand) {
+        self.xr_pressed.retain(|(h, _)| *h != hand);
+        self.xr_axes.retain(|(h, _), _| *h != hand);
+    }
+
+    /// Returns an iterator over connected gamepads, that yields identifiers and names of the
+    /// gamepads.
+    pub fn gamepads(&self) -> impl Iterator<Item = (GamepadId, &str)> {
+        self.gamepads
+            .iter()
+            .map(|(id, state)| (*id, state.name.as_str()))
+    }
+
+    /// Returns `true` if the given gamepad is connected.
+    pub fn is_gamepad_connected(&self, gamepad: GamepadId) -> bool {
+        self.gamepads.contains_key(&gamepad)
+    }
+
+    /// Returns gamepad events, that have occurred since the previous update tick.
+    pub fn gamepad_events(&self) -> &[GamepadEvent] {
+        &self.gamepad_events
+    }
+
+    /// Requests a rumble (force-feedback) effect on the given gamepad. The effect replaces the
+    /// current effect of the gamepad and starts on the next update. Gamepads without force-feedback
+    /// support ignore the request.
+    pub fn rumble(&mut self, gamepad: GamepadId, rumble: Rumble) {
+        self.rumble_requests.push((gamepad, Some(rumble)));
+    }
+
+    /// Requests to stop current rumble effect of the given gamepad.
+    pub fn stop_rumble(&mut self, gamepad: GamepadId) {
+        self.rumble_requests.push((gamepad, None));
+    }
+
+    /// Returns pending rumble requests, `None` means that the effect must be stopped. The engine
+    /// passes the requests to its [`gamepad::GamepadBackend`] automatically.
+    pub fn drain_rumble_requests(
+        &mut self,
+    ) -> impl Iterator<Item = (GamepadId, Option<Rumble>)> + '_ {
+        self.rumble_requests.drain(..)
+    }
+
+    /// Adds relative mouse movement.
+    pub fn add_mouse_motion(&mut self, delta: Vector2<f32>) {
+        self.mouse_motion += delta;
+    }
+
+    /// Adds mouse wheel rotation (in lines).
+    pub fn add_mouse_wheel(&mut self, delta: f32) {
+        self.mouse_wheel += delta;
+    }
+
+    /// Releases every key and mouse button. It is called when the main window loses focus, so no
+    /// key will "stuck" in pressed state. Gamepads are not affected, since their state is tracked
+    /// regardless of the focus.
+    pub fn release_all(&mut self) {
+        self.pressed.clear();
+    }
+
+    /// Returns `true` if the given digital source is pressed right now. Gamepad buttons are checked
+    /// on every connected gamepad.
+    pub fn is_source_pressed(&self, source: InputSource) -> bool {
+        match source {
+            InputSource::GamepadButton(button) => self
+                .gamepads
+                .values()
+                .any(|state| state.pressed.contains(&button)),
+            InputSource::XrButton(hand, button) => self.xr_pressed.contains(&(hand, button)),
+            InputSource::Touch => self.touches.iter().any(Touch::is_active),
+            _ => self.pressed.contains(&source),
+        }
+    }
+
+    /// Returns current raw value of the given source. Gamepad sources are checked on every
+    /// connected gamepad, the value with the largest magnitude is returned.
+    pub fn source_value(&self, source: InputSource) -> f32 {
+        self.raw_value(source, None)
+    }
+
+    /// Returns current raw value of the given source of a particular gamepad. Keyboard and mouse
+    /// sources do not depend on the gamepad.
+    pub fn gamepad_source_value(&self, gamepad: GamepadId, source: InputSource) -> f32 {
+        self.raw_value(source, Some(gamepad))
+    }
+
+    fn raw_value(&self, source: InputSource, gamepad: Option<GamepadId>) -> f32 {
+        let gamepads = self
+            .gamepads
+            .iter()
+            .filter(move |(id, _)| gamepad.is_none() || gamepad == Some(**id))
+            .map(|(_, state)| state);
+
+        match source {
+            InputSource::Key(_) | InputSource::MouseButton(_) => {
+                if self.pressed.contains(&source) || self.pressed_since_update.contains(&source) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            InputSource::MouseAxis(MouseAxis::X) => self.mouse_motion.x,
+            InputSource::MouseAxis(MouseAxis::Y) => self.mouse_motion.y,
+            InputSource::MouseAxis(MouseAxis::Wheel) => self.mouse_wheel,
+            InputSource::GamepadButton(button) => {
+                let mut gamepads = gamepads;
+                if gamepads.any(|state| {
+                    state.pressed.contains(&button) || state.pressed_since_update.contains(&button)
+                }) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            InputSource::GamepadAxis(axis) => gamepads
+                .filter_map(|state| state.axes.get(&axis).cloned())
+                .fold(
+                    0.0f32,
+                    |max, value| {
+                        if value.abs() > max.abs() {
+                            value
+                        } else {
+                            max
+                        }
+                    },
+                ),
+            InputSource::XrButton(hand, button) => {
+                if self.xr_pressed.contains(&(hand, button))
+                    || self.xr_pressed_since_update.contains(&(hand, button))
+                {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            InputSource::XrAxis(hand, axis) => {
+                self.xr_axes.get(&(hand, axis)).cloned().unwrap_or_default()
+            }
+            // Touches, that were lifted on the current tick, are still reported, so short taps
+            // are not lost.
+            InputSource::Touch => {
+                if self
+                    .touches
+                    .iter()
+                    .all(|touch| touch.phase == TouchPhase::Cancelled)
+                {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+
+    fn action_value(&self, action: &Action) -> f32 {
+        match action.kind {
+            ActionKind::Button => action
+                .bindings
+                .iter()
+                .map(|binding| {
+                    binding
+                        .apply(self.raw_value(binding.source, binding.gamepad))
+                        .abs()
+                })
+                .fold(0.0, f32::max)
+                .min(1.0),
+            ActionKind::Axis => {
+                let mut clamped = 0.0;
+                let mut relative = 0.0;
+                for binding in action.bindings.iter() {
+                    let value = binding.apply(self.raw_value(binding.source, binding.gamepad));
+                    if let InputSource::MouseAxis(_) = binding.source {
+                        relative += value;
+                    } else {
+                        clamped += value;
+                    }
+                }
+                f32::clamp(clamped, -1.0, 1.0) + relative
+            }
+        }
+    }
+
+    /// Updates states of every action and resets relative axes. The engine calls this method
+    /// automatically for its own instance at the beginning of every update tick.
+    pub fn update(&mut self) {
+        self.update_touches();
+
+        for (name, action) in self.map.actions.iter() {
+            let value = self.action_value(action);
+            let state = self.states.entry(name.clone()).or_default();
+            state.previous = state.value;
+            state.value = value;
+        }
+
+        self.pressed_since_update.clear();
+        self.xr_pressed_since_update.clear();
+        for state in self.gamepads.values_mut() {
+            state.pressed_since_update.clear();
+        }
+        self.gamepad_events = std::mem::take(&mut self.pending_gamepad_events);
+        self.mouse_motion = Vector2::default();
+        self.mouse_wheel = 0.0;
+    }
+
+    fn state(&self, action: &str) -> ActionState {
+        self.states.get(action).cloned().unwrap_or_default()
+    }
+
+    /// Returns current value of the given action. Button actions have values in `[0; 1]` range,
+    /// axis actions - in `[-1; 1]` range (plus movement of the mouse, if it is bound to the
+    /// action). Returns zero if there's no such action.
+    pub fn value(&self, action: &str) -> f32 {
+        self.state(action).value
+    }
+
+    /// Returns `true` if the given action is pressed (its absolute value is greater or equal to
+    /// [`Self::PRESS_THRESHOLD`]).
+    pub fn is_pressed(&self, action: &str) -> bool {
+        self.state(action).value.abs() >= Self::PRESS_THRESHOLD
+    }
+
+    /// Returns `true` if the given action was pressed on the current update tick.
+    pub fn is_just_pressed(&self, action: &str) -> bool {
+        let state = self.state(action);
+        state.value.abs() >= Self::PRESS_THRESHOLD && state.previous.abs() < Self::PRESS_THRESHOLD
+    }
+
+    /// Returns `true` if the given action was released on the current update tick.
+    pub fn is_just_released(&self, action: &str) -> bool {
+        let state = self.state(action);
+        state.value.abs() < Self::PRESS_THRESHOLD && state.previous.abs() >= Self::PRESS_THRESHOLD
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn map() -> InputMap {
+        InputMap::default()
+            .with_action(
+                "Jump",
+                Action::button([
+                    InputSource::Key(KeyCode::Space).into(),
+                    InputSource::GamepadButton(GamepadButton::South).into(),
+                ]),
+            )
+            .with_action(
+                "MoveRight",
+                Action::axis([
+                    InputSource::Key(KeyCode::KeyD).into(),
+                    InputBinding::new(InputSource::Key(KeyCode::KeyA)).with_scale(-1.0),
+                    InputBinding::new(InputSource::GamepadAxis(GamepadAxis::LeftStickX))
+                        .with_dead_zone(0.2),
+                ]),
+            )
+            .with_action(
+                "LookX",
+                Action::axis([
+                    InputBinding::new(InputSource::MouseAxis(MouseAxis::X)).with_scale(0.5)
+                ]),
+            )
+    }
+
+    #[test]
+    fn test_button_action() {
+        let mut input = Input::new(map());
+        let space = InputSource::Key(KeyCode::Space);
+
+        input.set_pressed(space, true);
+        input.update();
+        assert!(input.is_pressed("Jump"));
+        assert!(input.is_just_pressed("Jump"));
+
+        input.update();
+        assert!(input.is_pressed("Jump"));
+        assert!(!input.is_just_pressed("Jump"));
+
+        input.set_pressed(space, false);
+        input.update();
+        assert!(input.is_just_released("Jump"));
+
+        // Short press between updates must not be lost.
+        input.set_pressed(space, true);
+        input.set_pressed(space, false);
+        input.update();
+        assert!(input.is_just_pressed("Jump"));
+        input.update();
+        assert!(!input.is_pressed("Jump"));
+
+        assert!(!input.is_pressed("Unknown"));
+    }
+
+    #[test]
+    fn test_axis_action() {
+        let mut input = Input::new(map());
+
+        input.set_pressed(InputSource::Key(KeyCode::KeyA), true);
+        input.update();
+        assert_eq!(input.value("MoveRight"), -1.0);
+
+        // Opposite keys cancel each other.
+        input.set_pressed(InputSource::Key(KeyCode::KeyD), true);
+        input.update();
+        assert_eq!(input.value("MoveRight"), 0.0);
+
+        input.release_all();
+        input.set_gamepad_axis(GamepadId(0), GamepadAxis::LeftStickX, 0.1);
+        input.update();
+        assert_eq!(input.value("MoveRight"), 0.0);
+
+        input.set_gamepad_axis(GamepadId(0), GamepadAxis::LeftStickX, 0.6);
+        input.update();
+        assert!((input.value("MoveRight") - 0.5).abs() < 0.001);
+
+        // Mouse movement is relative and is reset on every update.
+        input.add_mouse_motion(Vector2::new(10.0, 0.0));
+        input.update();
+        assert_eq!(input.value("LookX"), 5.0);
+        input.update();
+        assert_eq!(input.value("LookX"), 0.0);
+    }
+
+    #[test]
+    fn test_multiple_gamepads() {
+        let mut map = map();
+        map.actions.insert(
+            "Player2Jump".to_string(),
+            Action::button([
+                InputBinding::new(InputSource::GamepadButton(GamepadButton::South))
+                    .with_gamepad(GamepadId(1)),
+            ]),
+        );
+        let mut input = Input::new(map);
+
+        input.connect_gamepad(GamepadId(0), "First");
+        input.connect_gamepad(GamepadId(1), "Second");
+        input.update();
+        assert_eq!(input.gamepad_events().len(), 2);
+        assert_eq!(input.gamepads().count(), 2);
+
+        // Any gamepad triggers unrestricted bindings.
+        input.set_gamepad_button(GamepadId(0), GamepadButton::South, true);
+        input.update();
+        assert!(input.gamepad_events().is_empty());
+        assert!(input.is_pressed("Jump"));
+        assert!(!input.is_pressed("Player2Jump"));
+
+        input.set_gamepad_button(GamepadId(0), GamepadButton::South, false);
+        input.set_gamepad_button(GamepadId(1), GamepadButton::South, true);
+        input.update();
+        assert!(input.is_pressed("Jump"));
+        assert!(input.is_just_pressed("Player2Jump"));
+
+        // Disconnection releases everything.
+        input.disconnect_gamepad(GamepadId(1));
+        input.update();
+        assert_eq!(
+            input.gamepad_events(),
+            &[GamepadEvent::Disconnected(GamepadId(1))]
+        );
+        assert!(input.is_just_released("Player2Jump"));
+        assert!(!input.is_gamepad_connected(GamepadId(1)));
+
+        let rumble = Rumble::new(1.0, 0.5, std::time::Duration::from_millis(200));
+        input.rumble(GamepadId(0), rumble);
+        input.stop_rumble(GamepadId(0));
+        assert_eq!(
+            input.drain_rumble_requests().collect::<Vec<_>>(),
+            vec![(GamepadId(0), Some(rumble)), (GamepadId(0), None)]
+        );
+    }
+
+    #[test]
+    fn test_xr_controllers() {
+        let mut map = map();
+        map.actions.insert(
+            "Grab".to_string(),
+            Action::button([InputSource::XrButton(XrHand::Right, XrButton::Primary).into()]),
+        );
+        map.actions.insert(
+            "Fire".to_string(),
+            Action::axis([
+                InputBinding::new(InputSource::XrAxis(XrHand::Left, XrAxis::Trigger))
+                    .with_dead_zone(0.1),
+            ]),
+        );
+        let mut input = Input::new(map);
+
+        input.set_xr_button(XrHand::Left, XrButton::Primary, true);
+        input.set_xr_axis(XrHand::Left, XrAxis::Trigger, 0.05);
+        input.update();
+        assert!(!input.is_pressed("Grab"));
+        assert_eq!(input.value("Fire"), 0.0);
+
+        input.set_xr_button(XrHand::Right, XrButton::Primary, true);
+        input.set_xr_axis(XrHand::Left, XrAxis::Trigger, 1.0);
+        input.update();
+        assert!(input.is_just_pressed("Grab"));
+        assert_eq!(input.value("Fire"), 1.0);
+
+        input.reset_xr_controller(XrHand::Left);
+        input.update();
+        assert!(input.is_pressed("Grab"));
+        assert_eq!(input.value("Fire"), 0.0);
+
+        input.reset_xr_controllers();
+        input.update();
+        assert!(input.is_just_released("Grab"));
+    }
+
+    #[test]
+    fn test_touches() {
+        let mut map = map();
+        map.actions.insert(
+            "Tap".to_string(),
+            Action::button([InputSource::Touch.into()]),
+        );
+        let mut input = Input::new(map);
+
+        // A short tap between updates is reported as ended, but the action is still pressed.
+        input.add_touch_event(0, TouchPhase::Started, Vector2::new(10.0, 10.0));
+        input.add_touch_event(0, TouchPhase::Ended, Vector2::new(12.0, 10.0));
+        input.update();
+        assert!(input.is_just_pressed("Tap"));
+        assert_eq!(input.touches().len(), 1);
+        assert_eq!(input.touch(0).unwrap().phase, TouchPhase::Ended);
+        assert!(matches!(input.gestures(), [Gesture::Tap { count: 1, .. }]));
+        input.update();
+        assert!(input.touches().is_empty());
+        assert!(input.gestures().is_empty());
+        assert!(input.is_just_released("Tap"));
+
+        input.add_touch_event(1, TouchPhase::Started, Vector2::new(10.0, 10.0));
+        input.update();
+        assert_eq!(input.touch(1).unwrap().phase, TouchPhase::Started);
+        input.add_touch_event(1, TouchPhase::Moved, Vector2::new(15.0, 10.0));
+        input.add_touch_event(1, TouchPhase::Moved, Vector2::new(20.0, 5.0));
+        input.update();
+        let touch = *input.touch(1).unwrap();
+        assert_eq!(touch.phase, TouchPhase::Moved);
+        assert_eq!(touch.delta, Vector2::new(10.0, -5.0));
+        assert_eq!(touch.start_position, Vector2::new(10.0, 10.0));
+        input.update();
+        assert_eq!(input.touch(1).unwrap().delta, Vector2::default());
+        assert!(input.is_pressed("Tap"));
+
+        input.cancel_touches();
+        input.update();
+        assert_eq!(input.touch(1).unwrap().phase, TouchPhase::Cancelled);
+        assert!(input.is_just_released("Tap"));
+    }
+
+    #[test]
+    fn test_input_map_persistence() {
+        let path = std::env::temp_dir()
+            .join("fyrox_test_input")
+            .join("input.ron");
+
+        let mut map = map();
+        assert!(map.rebind("Jump", 0, InputSource::Key(KeyCode::KeyJ)));
+        assert!(!map.rebind("Jump", 5, InputSource::Key(KeyCode::KeyJ)));
+        map.save(&path).unwrap();
+
+        let loaded = InputMap::load(&path).unwrap();
+        assert_eq!(loaded, map);
+    }
+}
@@ -0,0 +1,151 @@
+//! Touch screen support. Touches are fed to [`Input`] by the engine and could be read using
+//! [`Input::touches`], any touch could also be bound to an action using [`InputSource::Touch`].
+//! High-level gestures (taps, pans, pinches, etc.) are available via [`Input::gestures`].
+//!
+//! [`InputSource::Touch`]: crate::input::InputSource::Touch
+
+use crate::{
+    core::{algebra::Vector2, instant::Instant},
+    gui::{
+        gesture::{Gesture, GestureSettings},
+        message::TouchPhase,
+    },
+    input::Input,
+};
+
+/// A finger on a touch screen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Touch {
+    /// Unique identifier of the finger, it stays the same until the finger is lifted.
+    pub id: u64,
+    /// Phase of the touch. [`TouchPhase::Started`], [`TouchPhase::Ended`] and
+    /// [`TouchPhase::Cancelled`] phases are reported only on a single update tick, fingers that stay
+    /// on the screen have [`TouchPhase::Moved`] phase (even if they do not move).
+    pub phase: TouchPhase,
+    /// Position of the finger in window coordinates (in pixels).
+    pub position: Vector2<f32>,
+    /// Position where the finger touched the screen.
+    pub start_position: Vector2<f32>,
+    /// Movement of the finger since the previous update tick.
+    pub delta: Vector2<f32>,
+}
+
+impl Touch {
+    /// Returns `true` if the finger is still on the screen.
+    pub fn is_active(&self) -> bool {
+        matches!(self.phase, TouchPhase::Started | TouchPhase::Moved)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(super) struct TouchEvent {
+    pub id: u64,
+    pub phase: TouchPhase,
+    pub position: Vector2<f32>,
+    pub time: f32,
+}
+
+impl Input {
+    /// Registers an event of a touch screen, it will be applied on the next update. The engine
+    /// calls this method automatically for its own instance.
+    pub fn add_touch_event(&mut self, id: u64, phase: TouchPhase, position: Vector2<f32>) {
+        let time = self.gesture_time();
+        self.pending_touch_events.push(TouchEvent {
+            id,
+            phase,
+            position,
+            time,
+        });
+    }
+
+    /// Returns every touch of the current update tick: fingers, that are on the screen, and
+    /// fingers, that were lifted since the previous update tick.
+    pub fn touches(&self) -> &[Touch] {
+        &self.touches
+    }
+
+    /// Returns a touch with the given identifier.
+    pub fn touch(&self, id: u64) -> Option<&Touch> {
+        self.touches.iter().find(|touch| touch.id == id)
+    }
+
+    /// Returns gestures, that were recognized on the current update tick. It could be used to
+    /// control a camera on touch screens, for example pinch to zoom and pan to rotate.
+    pub fn gestures(&self) -> &[Gesture] {
+        &self.gestures
+    }
+
+    /// Returns current thresholds of the gesture recognition.
+    pub fn gesture_settings(&self) -> &GestureSettings {
+        &self.gesture_recognizer.settings
+    }
+
+    /// Sets new thresholds of the gesture recognition.
+    pub fn set_gesture_settings(&mut self, settings: GestureSettings) {
+        self.gesture_recognizer.settings = settings;
+    }
+
+    /// Cancels every active touch, it is called when the window loses focus.
+    pub fn cancel_touches(&mut self) {
+        let time = self.gesture_time();
+        for touch in self.touches.iter().filter(|touch| touch.is_active()) {
+            self.pending_touch_events.push(TouchEvent {
+                id: touch.id,
+                phase: TouchPhase::Cancelled,
+                position: touch.position,
+                time,
+            });
+        }
+    }
+
+    // Events are applied on updates only, so every event remembers the time when it happened to
+    // keep gesture recognition precise.
+    fn gesture_time(&mut self) -> f32 {
+        self.gesture_clock
+            .get_or_insert_with(Instant::now)
+            .elapsed()
+            .as_secs_f32()
+    }
+
+    pub(super) fn update_touches(&mut self) {
+        self.touches.retain(Touch::is_active);
+        for touch in self.touches.iter_mut() {
+            // Started phase is reported only on the tick when a finger touched the screen.
+            touch.phase = TouchPhase::Moved;
+            touch.delta = Vector2::default();
+        }
+
+        for event in std::mem::take(&mut self.pending_touch_events) {
+            self.gesture_recognizer.process_touch(
+                event.id,
+                event.phase,
+                event.position,
+                event.time,
+            );
+
+            match self.touches.iter_mut().find(|touch| touch.id == event.id) {
+                Some(touch) => {
+                    touch.delta += event.position - touch.position;
+                    touch.position = event.position;
+                    // Movement of a finger, that touched the screen on this tick, keeps it started.
+                    if event.phase != TouchPhase::Moved {
+                        touch.phase = event.phase;
+                    }
+                }
+                None if event.phase == TouchPhase::Started => self.touches.push(Touch {
+                    id: event.id,
+                    phase: TouchPhase::Started,
+                    position: event.position,
+                    start_position: event.position,
+                    delta: Vector2::default(),
+                }),
+                // Events of unknown fingers are ignored.
+                None => (),
+            }
+        }
+
+        let time = self.gesture_time();
+        self.gesture_recognizer.update(time);
+        self.gestures = self.gesture_recognizer.take_gestures();
+    }
+}
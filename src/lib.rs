@@ -10,14 +10,19 @@
 #![allow(clippy::approx_constant)]
 
 pub mod animation;
+pub mod console;
 pub mod engine;
+pub mod input;
 pub mod material;
+pub mod network;
 pub mod plugin;
 pub mod renderer;
 pub mod resource;
 pub mod scene;
 pub mod script;
 pub mod utils;
+pub mod video;
+pub mod xr;
 
 pub use crate::core::rand;
 pub use fxhash;
@@ -394,6 +394,45 @@ impl Display for MaterialError {
     }
 }
 
+fn default_property_value(
+    kind: &PropertyKind,
+    resource_manager: Option<&ResourceManager>,
+) -> PropertyValue {
+    match kind {
+        PropertyKind::Float(value) => PropertyValue::Float(*value),
+        PropertyKind::Int(value) => PropertyValue::Int(*value),
+        PropertyKind::UInt(value) => PropertyValue::UInt(*value),
+        PropertyKind::Vector2(value) => PropertyValue::Vector2(*value),
+        PropertyKind::Vector3(value) => PropertyValue::Vector3(*value),
+        PropertyKind::Vector4(value) => PropertyValue::Vector4(*value),
+        PropertyKind::Color { r, g, b, a } => {
+            PropertyValue::Color(Color::from_rgba(*r, *g, *b, *a))
+        }
+        PropertyKind::Matrix2(value) => PropertyValue::Matrix2(*value),
+        PropertyKind::Matrix3(value) => PropertyValue::Matrix3(*value),
+        PropertyKind::Matrix4(value) => PropertyValue::Matrix4(*value),
+        PropertyKind::Bool(value) => PropertyValue::Bool(*value),
+        PropertyKind::Sampler {
+            default,
+            fallback: usage,
+        } => PropertyValue::Sampler {
+            value: default
+                .as_ref()
+                .and_then(|path| resource_manager.map(|rm| rm.request::<Texture, _>(path))),
+            fallback: *usage,
+        },
+        PropertyKind::FloatArray(value) => PropertyValue::FloatArray(value.clone()),
+        PropertyKind::IntArray(value) => PropertyValue::IntArray(value.clone()),
+        PropertyKind::UIntArray(value) => PropertyValue::UIntArray(value.clone()),
+        PropertyKind::Vector2Array(value) => PropertyValue::Vector2Array(value.clone()),
+        PropertyKind::Vector3Array(value) => PropertyValue::Vector3Array(value.clone()),
+        PropertyKind::Vector4Array(value) => PropertyValue::Vector4Array(value.clone()),
+        PropertyKind::Matrix2Array(value) => PropertyValue::Matrix2Array(value.clone()),
+        PropertyKind::Matrix3Array(value) => PropertyValue::Matrix3Array(value.clone()),
+        PropertyKind::Matrix4Array(value) => PropertyValue::Matrix4Array(value.clone()),
+    }
+}
+
 impl Material {
     /// Creates a new instance of material with the standard shader. For the full list
     /// of properties of the standard material see [shader module docs](self::shader).
@@ -475,43 +514,10 @@ impl Material {
 
         let mut property_values = FxHashMap::default();
         for property_definition in data.definition.properties.iter() {
-            let value = match &property_definition.kind {
-                PropertyKind::Float(value) => PropertyValue::Float(*value),
-                PropertyKind::Int(value) => PropertyValue::Int(*value),
-                PropertyKind::UInt(value) => PropertyValue::UInt(*value),
-                PropertyKind::Vector2(value) => PropertyValue::Vector2(*value),
-                PropertyKind::Vector3(value) => PropertyValue::Vector3(*value),
-                PropertyKind::Vector4(value) => PropertyValue::Vector4(*value),
-                PropertyKind::Color { r, g, b, a } => {
-                    PropertyValue::Color(Color::from_rgba(*r, *g, *b, *a))
-                }
-                PropertyKind::Matrix2(value) => PropertyValue::Matrix2(*value),
-                PropertyKind::Matrix3(value) => PropertyValue::Matrix3(*value),
-                PropertyKind::Matrix4(value) => PropertyValue::Matrix4(*value),
-                PropertyKind::Bool(value) => PropertyValue::Bool(*value),
-                PropertyKind::Sampler {
-                    default,
-                    fallback: usage,
-                } => PropertyValue::Sampler {
-                    value: default.as_ref().and_then(|path| {
-                        resource_manager
-                            .clone()
-                            .map(|rm| rm.request::<Texture, _>(path))
-                    }),
-                    fallback: *usage,
-                },
-                PropertyKind::FloatArray(value) => PropertyValue::FloatArray(value.clone()),
-                PropertyKind::IntArray(value) => PropertyValue::IntArray(value.clone()),
-                PropertyKind::UIntArray(value) => PropertyValue::UIntArray(value.clone()),
-                PropertyKind::Vector2Array(value) => PropertyValue::Vector2Array(value.clone()),
-                PropertyKind::Vector3Array(value) => PropertyValue::Vector3Array(value.clone()),
-                PropertyKind::Vector4Array(value) => PropertyValue::Vector4Array(value.clone()),
-                PropertyKind::Matrix2Array(value) => PropertyValue::Matrix2Array(value.clone()),
-                PropertyKind::Matrix3Array(value) => PropertyValue::Matrix3Array(value.clone()),
-                PropertyKind::Matrix4Array(value) => PropertyValue::Matrix4Array(value.clone()),
-            };
-
-            property_values.insert(ImmutableString::new(&property_definition.name), value);
+            property_values.insert(
+                ImmutableString::new(&property_definition.name),
+                default_property_value(&property_definition.kind, resource_manager.as_ref()),
+            );
         }
 
         drop(data);
@@ -522,6 +528,37 @@ impl Material {
         }
     }
 
+    /// Synchronizes the set of properties of the material with the current state of its shader. It adds
+    /// properties, that were added to the shader, removes properties, that no longer exist in the shader,
+    /// and resets properties, that have changed their type, to their default values. Values of the rest
+    /// of the properties are kept as is.
+    ///
+    /// The engine calls this method automatically for every material in every scene when a shader is
+    /// reloaded (for example, when it was changed on disk and hot reloading is enabled). You need to call
+    /// it manually only for materials that are not used in scenes.
+    pub fn sync_to_shader(&mut self, resource_manager: Option<&ResourceManager>) {
+        let data = self.shader.data_ref();
+
+        let mut property_values = FxHashMap::default();
+        for property_definition in data.definition.properties.iter() {
+            let name = ImmutableString::new(&property_definition.name);
+            let default_value = default_property_value(&property_definition.kind, resource_manager);
+            let value = match self.properties.remove(&name) {
+                Some(value)
+                    if std::mem::discriminant(&value) == std::mem::discriminant(&default_value) =>
+                {
+                    value
+                }
+                _ => default_value,
+            };
+            property_values.insert(name, value);
+        }
+
+        drop(data);
+
+        self.properties = property_values;
+    }
+
     /// Searches for a property with given name.
     ///
     /// # Complexity
@@ -722,3 +759,85 @@ impl SharedMaterial {
         Self::new(self.0.lock().clone())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::sstorage::ImmutableString,
+        material::{
+            shader::{PropertyDefinition, PropertyKind, ShaderResource, ShaderResourceExtension},
+            Material, PropertyValue,
+        },
+    };
+
+    #[test]
+    fn test_sync_to_shader() {
+        let code = r#"
+            (
+                name: "TestShader",
+                properties: [
+                    (
+                        name: "foo",
+                        kind: Float(1.0),
+                    ),
+                    (
+                        name: "bar",
+                        kind: Int(2),
+                    ),
+                    (
+                        name: "baz",
+                        kind: Bool(false),
+                    ),
+                ],
+                passes: [],
+            )
+            "#;
+
+        let shader = ShaderResource::from_str(code, "test").unwrap();
+        let mut material = Material::from_shader(shader.clone(), None);
+
+        let foo = ImmutableString::new("foo");
+        let bar = ImmutableString::new("bar");
+        let baz = ImmutableString::new("baz");
+        let qux = ImmutableString::new("qux");
+
+        material
+            .set_property(&foo, PropertyValue::Float(3.0))
+            .unwrap();
+        material.set_property(&bar, PropertyValue::Int(4)).unwrap();
+
+        // Simulate changes in the shader after reloading: "bar" changes its type, "baz" is removed
+        // and "qux" is added.
+        shader.data_ref().definition.properties = vec![
+            PropertyDefinition {
+                name: "foo".to_string(),
+                kind: PropertyKind::Float(1.0),
+            },
+            PropertyDefinition {
+                name: "bar".to_string(),
+                kind: PropertyKind::UInt(5),
+            },
+            PropertyDefinition {
+                name: "qux".to_string(),
+                kind: PropertyKind::Int(6),
+            },
+        ];
+
+        material.sync_to_shader(None);
+
+        assert_eq!(material.properties().len(), 3);
+        assert!(matches!(
+            material.property_ref(&foo),
+            Some(PropertyValue::Float(value)) if *value == 3.0
+        ));
+        assert!(matches!(
+            material.property_ref(&bar),
+            Some(PropertyValue::UInt(5))
+        ));
+        assert!(material.property_ref(&baz).is_none());
+        assert!(matches!(
+            material.property_ref(&qux),
+            Some(PropertyValue::Int(6))
+        ));
+    }
+}
@@ -159,6 +159,7 @@
 //! | fyrox_cameraPosition       | `Vector3`       | Position of the camera.
 //! | fyrox_usePOM               | `bool`          | Whether to use parallax mapping or not.
 //! | fyrox_lightPosition        | `Vector3`       | Light position.
+//! | fyrox_lightProbe           | `[Vector3; 9]`  | Spherical harmonics of ambient light from the scene light probes.
 //!
 //! To use any of the variables, just define a uniform with appropriate name:
 //!
@@ -269,11 +270,25 @@ pub const STANDARD_TERRAIN_SHADER_NAME: &str = "StandardTerrain";
 /// A source code of the standard terrain shader.
 pub const STANDARD_TERRAIN_SHADER_SRC: &str = include_str!("standard/terrain.shader");
 
+/// A name of the standard impostor shader.
+pub const STANDARD_IMPOSTOR_SHADER_NAME: &str = "StandardImpostor";
+
+/// A source code of the standard impostor shader.
+pub const STANDARD_IMPOSTOR_SHADER_SRC: &str = include_str!("standard/impostor.shader");
+
+/// A name of the standard trail shader.
+pub const STANDARD_TRAIL_SHADER_NAME: &str = "StandardTrail";
+
+/// A source code of the standard trail shader.
+pub const STANDARD_TRAIL_SHADER_SRC: &str = include_str!("standard/trail.shader");
+
 /// A list of names of standard shaders.
-pub const STANDARD_SHADER_NAMES: [&str; 3] = [
+pub const STANDARD_SHADER_NAMES: [&str; 5] = [
     STANDARD_SHADER_NAME,
     STANDARD_TWOSIDES_SHADER_NAME,
     STANDARD_TERRAIN_SHADER_NAME,
+    STANDARD_IMPOSTOR_SHADER_NAME,
+    STANDARD_TRAIL_SHADER_NAME,
 ];
 
 /// Internal state of the shader.
@@ -314,6 +329,10 @@ impl Visit for Shader {
                 self.definition = ShaderDefinition::from_str(STANDARD_TERRAIN_SHADER_SRC).unwrap();
             } else if self.path == Path::new("StandardTwoSides") {
                 self.definition = ShaderDefinition::from_str(STANDARD_TWOSIDES_SHADER_SRC).unwrap();
+            } else if self.path == Path::new("StandardImpostor") {
+                self.definition = ShaderDefinition::from_str(STANDARD_IMPOSTOR_SHADER_SRC).unwrap();
+            } else if self.path == Path::new("StandardTrail") {
+                self.definition = ShaderDefinition::from_str(STANDARD_TRAIL_SHADER_SRC).unwrap();
             }
         }
 
@@ -587,6 +606,14 @@ pub trait ShaderResourceExtension: Sized {
     /// Returns an instance of standard two-sides terrain shader.
     fn standard_twosides() -> Self;
 
+    /// Returns an instance of standard impostor shader. See [`crate::utils::impostor`] docs for
+    /// more info.
+    fn standard_impostor() -> Self;
+
+    /// Returns an instance of standard trail shader. See [`crate::scene::trail`] docs for more
+    /// info.
+    fn standard_trail() -> Self;
+
     /// Returns a list of standard shader.
     fn standard_shaders() -> Vec<ShaderResource>;
 }
@@ -613,12 +640,24 @@ impl ShaderResourceExtension for ShaderResource {
         STANDARD_TWOSIDES.clone()
     }
 
+    /// Returns an instance of standard impostor shader.
+    fn standard_impostor() -> Self {
+        STANDARD_IMPOSTOR.clone()
+    }
+
+    /// Returns an instance of standard trail shader.
+    fn standard_trail() -> Self {
+        STANDARD_TRAIL.clone()
+    }
+
     /// Returns a list of standard shader.
     fn standard_shaders() -> Vec<ShaderResource> {
         vec![
             Self::standard(),
             Self::standard_terrain(),
             Self::standard_twosides(),
+            Self::standard_impostor(),
+            Self::standard_trail(),
         ]
     }
 }
@@ -647,6 +686,18 @@ lazy_static! {
     );
 }
 
+lazy_static! {
+    static ref STANDARD_IMPOSTOR: ShaderResource = ShaderResource::new_ok(
+        Shader::from_str(STANDARD_IMPOSTOR_SHADER_SRC, STANDARD_IMPOSTOR_SHADER_NAME).unwrap(),
+    );
+}
+
+lazy_static! {
+    static ref STANDARD_TRAIL: ShaderResource = ShaderResource::new_ok(
+        Shader::from_str(STANDARD_TRAIL_SHADER_SRC, STANDARD_TRAIL_SHADER_NAME).unwrap(),
+    );
+}
+
 #[cfg(test)]
 mod test {
     use crate::material::shader::{
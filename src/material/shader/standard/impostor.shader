@@ -0,0 +1,134 @@
+(
+    name: "StandardImpostorShader",
+
+    // Each property's name must match respective uniform name.
+    properties: [
+        (
+            name: "diffuseTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "normalTexture",
+            kind: Sampler(default: None, fallback: Normal),
+        ),
+        (
+            name: "impostorCenter",
+            kind: Vector3((0.0, 0.0, 0.0)),
+        ),
+        (
+            name: "viewCount",
+            kind: UInt(1),
+        ),
+        (
+            name: "atlasColumns",
+            kind: UInt(1),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+
+                // Properties.
+                uniform vec3 impostorCenter;
+                uniform uint viewCount;
+                uniform uint atlasColumns;
+
+                // Define uniforms with reserved names. Fyrox will automatically provide
+                // required data to these uniforms.
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+                uniform vec3 fyrox_cameraPosition;
+
+                out vec2 texCoord;
+
+                void main()
+                {
+                    // Everything is done in local space of the impostor, the quad rotates around
+                    // vertical axis to face the camera.
+                    vec3 localCameraPosition = (inverse(fyrox_worldMatrix) * vec4(fyrox_cameraPosition, 1.0)).xyz;
+                    vec2 toCamera = localCameraPosition.xz - impostorCenter.xz;
+                    vec2 direction = length(toCamera) > 0.0001 ? normalize(toCamera) : vec2(0.0, 1.0);
+
+                    // Select the view, that was baked from the closest angle.
+                    float angle = atan(direction.x, direction.y);
+                    float views = float(viewCount);
+                    uint view = uint(mod(floor(angle / (2.0 * PI) * views + 0.5), views));
+
+                    vec3 right = vec3(direction.y, 0.0, -direction.x);
+                    vec3 offset = vertexPosition - impostorCenter;
+                    vec3 localPosition = impostorCenter + right * offset.x + vec3(0.0, offset.y, 0.0);
+
+                    uint rows = (viewCount + atlasColumns - 1u) / atlasColumns;
+                    vec2 cell = vec2(float(view % atlasColumns), float(view / atlasColumns));
+                    texCoord = (cell + vertexTexCoord) / vec2(float(atlasColumns), float(rows));
+
+                    gl_Position = fyrox_worldViewProjection * vec4(localPosition, 1.0);
+                }
+                "#,
+            fragment_shader:
+                r#"
+                layout(location = 0) out vec4 outColor;
+                layout(location = 1) out vec4 outNormal;
+                layout(location = 2) out vec4 outAmbient;
+                layout(location = 3) out vec4 outMaterial;
+                layout(location = 4) out uint outDecalMask;
+
+                // Properties.
+                uniform sampler2D diffuseTexture;
+                uniform sampler2D normalTexture;
+
+                // Define uniforms with reserved names. Fyrox will automatically provide
+                // required data to these uniforms.
+                uniform mat4 fyrox_worldMatrix;
+
+                in vec2 texCoord;
+
+                void main()
+                {
+                    outColor = texture(diffuseTexture, texCoord);
+
+                    // Alpha test.
+                    if (outColor.a < 0.5) {
+                        discard;
+                    }
+                    outColor.a = 1.0;
+
+                    // Normals are baked in local space of the impostor.
+                    vec3 n = texture(normalTexture, texCoord).xyz * 2.0 - 1.0;
+                    outNormal = vec4(normalize(mat3(fyrox_worldMatrix) * n) * 0.5 + 0.5, 1.0);
+
+                    // Non-metallic, rough surface without ambient occlusion.
+                    outMaterial = vec4(0.0, 1.0, 1.0, 1.0);
+
+                    outAmbient = vec4(0.0, 0.0, 0.0, 1.0);
+
+                    outDecalMask = 0u;
+                }
+                "#,
+        ),
+    ],
+)
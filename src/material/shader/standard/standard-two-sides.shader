@@ -178,6 +178,7 @@
                 // required data to these uniforms.
                 uniform vec3 fyrox_cameraPosition;
                 uniform bool fyrox_usePOM;
+                uniform vec3 fyrox_lightProbe[9];
 
                 in vec3 position;
                 in vec3 normal;
@@ -208,14 +209,16 @@
                     outColor.a = 1.0;
 
                     vec4 n = normalize(texture(normalTexture, tc) * 2.0 - 1.0);
-                    outNormal = vec4(normalize(tangentSpace * n.xyz) * 0.5 + 0.5, 1.0);
+                    vec3 worldNormal = normalize(tangentSpace * n.xyz);
+                    outNormal = vec4(worldNormal * 0.5 + 0.5, 1.0);
 
                     outMaterial.x = texture(metallicTexture, tc).r;
                     outMaterial.y = texture(roughnessTexture, tc).r;
                     outMaterial.z = texture(aoTexture, tc).r;
                     outMaterial.a = 1.0;
 
-                    outAmbient.xyz = emissionStrength * texture(emissionTexture, tc).rgb + texture(lightmapTexture, secondTexCoord).rgb;
+                    outAmbient.xyz = emissionStrength * texture(emissionTexture, tc).rgb + texture(lightmapTexture, secondTexCoord).rgb
+                        + S_LightProbeIrradiance(fyrox_lightProbe, worldNormal) / PI;
                     outAmbient.a = 1.0;
 
                     outDecalMask = layerIndex;
@@ -0,0 +1,102 @@
+(
+    name: "StandardTrailShader",
+
+    // Each property's name must match respective uniform name.
+    properties: [
+        (
+            name: "diffuseTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "diffuseColor",
+            kind: Color(r: 255, g: 255, b: 255, a: 255),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: true,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: SrcAlpha,
+                        dfactor: OneMinusSrcAlpha,
+                        alpha_sfactor: SrcAlpha,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec4 vertexColor;
+                layout(location = 3) in vec4 vertexDirection;
+
+                // Define uniforms with reserved names. Fyrox will automatically provide
+                // required data to these uniforms.
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+                uniform vec3 fyrox_cameraPosition;
+
+                out vec2 texCoord;
+                out vec4 color;
+
+                void main()
+                {
+                    // View-aligned vertices are stored on the center line of the strip, their `xyz` direction
+                    // is the tangent of the strip and `w` is a signed half-width. Such vertices are pushed
+                    // sideways so the strip faces the camera. Everything is done in local space of the node.
+                    vec3 position = vertexPosition;
+                    if (vertexDirection.w != 0.0) {
+                        vec3 localCameraPosition = (inverse(fyrox_worldMatrix) * vec4(fyrox_cameraPosition, 1.0)).xyz;
+                        vec3 side = cross(vertexDirection.xyz, localCameraPosition - vertexPosition);
+                        float sideLength = length(side);
+                        if (sideLength > 0.00001) {
+                            position += side / sideLength * vertexDirection.w;
+                        }
+                    }
+
+                    texCoord = vertexTexCoord;
+                    color = vertexColor;
+                    gl_Position = fyrox_worldViewProjection * vec4(position, 1.0);
+                }
+                "#,
+            fragment_shader:
+                r#"
+                uniform sampler2D diffuseTexture;
+                uniform vec4 diffuseColor;
+
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+                in vec4 color;
+
+                void main()
+                {
+                    FragColor = diffuseColor * color * texture(diffuseTexture, texCoord);
+                }
+                "#,
+        ),
+    ],
+)
@@ -0,0 +1,284 @@
+//! Snapshot interpolation for remote entities. See [`SnapshotBuffer`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        math::lerpf,
+    },
+    network::Tick,
+};
+use std::collections::VecDeque;
+
+/// A state, that could be interpolated between two snapshots.
+pub trait Interpolate: Clone {
+    /// Returns a state between `self` and `other`, `t` is in `[0; 1]` range.
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        lerpf(*self, *other, t)
+    }
+}
+
+impl Interpolate for Vector2<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for Vector3<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for UnitQuaternion<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        // Slerp is undefined for opposite rotations.
+        self.try_slerp(other, t, f32::EPSILON)
+            .unwrap_or_else(|| self.nlerp(other, t))
+    }
+}
+
+impl<A: Interpolate, B: Interpolate> Interpolate for (A, B) {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        (
+            self.0.interpolate(&other.0, t),
+            self.1.interpolate(&other.1, t),
+        )
+    }
+}
+
+impl<A: Interpolate, B: Interpolate, C: Interpolate> Interpolate for (A, B, C) {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        (
+            self.0.interpolate(&other.0, t),
+            self.1.interpolate(&other.1, t),
+            self.2.interpolate(&other.2, t),
+        )
+    }
+}
+
+/// Snapshot buffer smooths motion of remote entities. States of an entity arrive from the server
+/// irregularly (because of network jitter and packet loss), so instead of showing the most recent
+/// state the buffer renders the entity slightly in the past (see [`Self::set_delay`]) and
+/// interpolates between two snapshots around the render time. The delay should be a bit more
+/// than the interval between server updates, so there is almost always a newer snapshot to
+/// interpolate to. If there are no newer snapshots, the last state is held.
+///
+/// Render time is measured in ticks, it advances with [`Self::update`] and smoothly adjusts to the
+/// arrival rate of snapshots, so the buffer does not drift away from the server over time.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::{core::algebra::Vector3, network::interpolation::SnapshotBuffer};
+///
+/// let fixed_dt = 1.0 / 60.0;
+/// let mut buffer = SnapshotBuffer::new(fixed_dt);
+///
+/// // Snapshot of the remote entity from the server.
+/// buffer.push(0, Vector3::new(1.0f32, 2.0, 3.0));
+///
+/// // Every frame (or fixed step).
+/// buffer.update(fixed_dt);
+/// if let Some(position) = buffer.sample() {
+///     // Set the position of the entity.
+/// }
+/// ```
+pub struct SnapshotBuffer<S> {
+    snapshots: VecDeque<(Tick, S)>,
+    fixed_dt: f32,
+    delay: f32,
+    capacity: usize,
+    render_tick: Option<f64>,
+}
+
+impl<S: Interpolate> SnapshotBuffer<S> {
+    /// Default interpolation delay (in seconds).
+    pub const DEFAULT_DELAY: f32 = 0.1;
+
+    /// Default maximum amount of snapshots.
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    // Maximum relative change of the speed of render time, when it catches up with the snapshots.
+    const MAX_TIME_CORRECTION: f64 = 0.1;
+
+    /// Creates new buffer, `fixed_dt` is the duration of a tick (in seconds).
+    pub fn new(fixed_dt: f32) -> Self {
+        Self {
+            snapshots: Default::default(),
+            fixed_dt: fixed_dt.max(f32::EPSILON),
+            delay: Self::DEFAULT_DELAY,
+            capacity: Self::DEFAULT_CAPACITY,
+            render_tick: None,
+        }
+    }
+
+    /// Sets interpolation delay (in seconds). Bigger delay is more tolerant to jitter and packet
+    /// loss, but remote entities are shown further in the past.
+    pub fn set_delay(&mut self, delay: f32) {
+        self.delay = delay.max(0.0);
+    }
+
+    /// Returns interpolation delay (in seconds).
+    pub fn delay(&self) -> f32 {
+        self.delay
+    }
+
+    /// Sets maximum amount of snapshots, oldest snapshots are dropped when the limit is reached.
+    /// Minimal value is 2.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(2);
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Returns maximum amount of snapshots.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns amount of snapshots in the buffer.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns `true` if the buffer has no snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Returns current render time (in ticks), or `None` if there were no snapshots yet.
+    pub fn render_tick(&self) -> Option<f64> {
+        self.render_tick
+    }
+
+    /// Removes all snapshots and resets render time.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.render_tick = None;
+    }
+
+    /// Adds a snapshot of the server state at the given tick. Snapshots could arrive out of order,
+    /// duplicated snapshots and snapshots older than render time are ignored.
+    pub fn push(&mut self, tick: Tick, state: S) {
+        if self
+            .render_tick
+            .map_or(false, |render_tick| (tick as f64) < render_tick)
+        {
+            return;
+        }
+
+        let position = self.snapshots.partition_point(|(other, _)| *other < tick);
+        if self
+            .snapshots
+            .get(position)
+            .map_or(false, |(other, _)| *other == tick)
+        {
+            return;
+        }
+        self.snapshots.insert(position, (tick, state));
+
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Advances render time by the given time (in seconds). It should be called every frame with
+    /// frame time, or in every fixed step with fixed time step.
+    pub fn update(&mut self, dt: f32) {
+        let latest = match self.snapshots.back() {
+            Some((tick, _)) => *tick as f64,
+            None => return,
+        };
+
+        let delay = (self.delay / self.fixed_dt) as f64;
+        let target = latest - delay;
+        let render_tick = match self.render_tick {
+            Some(render_tick) => {
+                let error = target - render_tick;
+                if error > 2.0 * delay.max(1.0) {
+                    // Too far behind, for example after a lag spike.
+                    target
+                } else {
+                    let correction = (error / delay.max(1.0))
+                        .clamp(-Self::MAX_TIME_CORRECTION, Self::MAX_TIME_CORRECTION);
+                    // Render time never goes past the latest snapshot, there is nothing to show.
+                    (render_tick + (dt / self.fixed_dt) as f64 * (1.0 + correction)).min(latest)
+                }
+            }
+            None => target,
+        };
+        self.render_tick = Some(render_tick);
+
+        // Keep one snapshot before the render time to interpolate from.
+        while self
+            .snapshots
+            .get(1)
+            .map_or(false, |(tick, _)| (*tick as f64) <= render_tick)
+        {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Returns interpolated state at current render time.
+    pub fn sample(&self) -> Option<S> {
+        let render_tick = self.render_tick?;
+        let next = self
+            .snapshots
+            .partition_point(|(tick, _)| (*tick as f64) <= render_tick);
+        match (
+            next.checked_sub(1).and_then(|i| self.snapshots.get(i)),
+            self.snapshots.get(next),
+        ) {
+            (Some((from_tick, from)), Some((to_tick, to))) => {
+                let t = (render_tick - *from_tick as f64) / (*to_tick - *from_tick) as f64;
+                Some(from.interpolate(to, t as f32))
+            }
+            (Some((_, state)), None) | (None, Some((_, state))) => Some(state.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::network::interpolation::SnapshotBuffer;
+
+    #[test]
+    fn test_snapshot_buffer() {
+        let mut buffer = SnapshotBuffer::new(0.1);
+        buffer.set_delay(0.2);
+        assert_eq!(buffer.sample(), None);
+
+        for tick in [0, 2, 1, 4, 3, 3] {
+            buffer.push(tick, tick as f32);
+        }
+        assert_eq!(buffer.len(), 5);
+
+        buffer.update(0.0);
+        assert_eq!(buffer.sample(), Some(2.0));
+        buffer.update(0.05);
+        assert!((buffer.sample().unwrap() - 2.5).abs() < 0.001);
+        // Old snapshots are no longer needed.
+        assert_eq!(buffer.len(), 3);
+
+        // Render time is behind the target, so it advances a bit faster.
+        buffer.push(5, 5.0);
+        buffer.update(0.1);
+        assert!((buffer.sample().unwrap() - 3.6).abs() < 0.001);
+
+        // Snapshots are late, the last state is held.
+        for _ in 0..5 {
+            buffer.update(0.1);
+        }
+        assert_eq!(buffer.sample(), Some(5.0));
+
+        // Snapshots older than render time are ignored.
+        buffer.push(1, 1.0);
+        assert_eq!(buffer.len(), 1);
+    }
+}
@@ -0,0 +1,511 @@
+//! Deterministic lockstep multiplayer. Instead of replicating states, peers exchange inputs and
+//! run the same deterministic simulation, so only inputs travel over the network. See
+//! [`LockstepSession`] docs for more info.
+//!
+//! Lockstep requires bit-exact simulation on every peer: use [`FixedTimestep`] for updates, seeded
+//! random number generators and avoid iteration over collections with unspecified order. Floating
+//! point math could differ between platforms and compilers, so peers should run the same build.
+//! Desyncs could be detected by exchanging hashes of the simulation state (see [`hash_state`]).
+//!
+//! [`FixedTimestep`]: crate::engine::timestep::FixedTimestep
+
+use crate::{
+    core::visitor::{Visit, VisitError, Visitor},
+    network::Tick,
+};
+use std::collections::{BTreeMap, VecDeque};
+
+// Amount of ticks, which hashes are kept to compare with late hashes of other peers.
+const HASH_HISTORY: Tick = 256;
+
+/// Serializes the state using [`Visit`] implementation. The result could be used as a rollback
+/// snapshot (see [`load_state`]).
+pub fn save_state<T: Visit>(state: &mut T) -> Result<Vec<u8>, VisitError> {
+    let mut visitor = Visitor::new();
+    state.visit("State", &mut visitor)?;
+    visitor.save_binary_to_vec()
+}
+
+/// Restores the state, that was previously saved by [`save_state`].
+pub fn load_state<T: Visit>(state: &mut T, data: Vec<u8>) -> Result<(), VisitError> {
+    let mut visitor = Visitor::load_from_memory(data)?;
+    state.visit("State", &mut visitor)
+}
+
+/// Calculates a hash of the state, that is stable across platforms and runs, so peers could
+/// compare hashes of their states to detect desyncs. The state is serialized using [`Visit`],
+/// so only the visited fields affect the hash. Shared pointers (`Rc`/`Arc`) are serialized by
+/// their addresses, so states with shared pointers can't be compared between peers.
+pub fn hash_state<T: Visit>(state: &mut T) -> Result<u64, VisitError> {
+    Ok(hash_bytes(&save_state(state)?))
+}
+
+// FNV-1a, unlike std hashers its output is specified and never changes.
+fn hash_bytes(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Information about a desync - a peer has different state at the same tick.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Desync {
+    /// The tick, where the states differ.
+    pub tick: Tick,
+    /// The player, whose state differs from the local one.
+    pub player: usize,
+    /// Hash of the local state.
+    pub local_hash: u64,
+    /// Hash of the remote state.
+    pub remote_hash: u64,
+}
+
+/// Lockstep session collects inputs of all players for every tick and tells when a tick could be
+/// simulated. Inputs of the local player are scheduled few ticks ahead (see
+/// [`Self::set_input_delay`]), so they have time to reach other peers before the tick is
+/// simulated.
+///
+/// In pure lockstep mode (default) the simulation waits until inputs of all players arrive. When
+/// prediction is enabled (see [`Self::set_max_prediction`]), missing inputs are predicted by
+/// repeating the last known input of a player. If the actual input differs, the session requests
+/// a rollback (see [`Self::take_rollback`]) - the game must restore its state saved before the
+/// tick (see [`RollbackBuffer`]) and simulate the ticks again.
+///
+/// Exchange of inputs is left to the game, the session only tells what to send and what was
+/// received. Inputs are usually sent with [`crate::network::transport::DeliveryMode::ReliableOrdered`].
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::network::lockstep::LockstepSession;
+///
+/// // Two players, the local one is the first.
+/// let mut session = LockstepSession::<u8>::new(2, 0);
+///
+/// // Fixed update.
+/// let tick = session.add_local_input(1);
+/// // Send (tick, input) to the other peer...
+///
+/// // Input of the other peer has arrived.
+/// session.add_remote_input(1, tick, 0);
+///
+/// while let Some(inputs) = session.advance() {
+///     // Simulate the tick with inputs of all players.
+/// }
+/// ```
+pub struct LockstepSession<I> {
+    player_count: usize,
+    local_player: usize,
+    input_delay: u32,
+    max_prediction: u32,
+    // Tick of the next local input, it is unknown until the first local input.
+    local_tick: Option<Tick>,
+    // Next tick to simulate.
+    current_tick: Tick,
+    // All ticks before this one have inputs of all players.
+    confirmed_tick: Tick,
+    inputs: BTreeMap<Tick, Vec<Option<I>>>,
+    // Inputs, that were used to simulate ticks, that are not confirmed yet.
+    predicted: BTreeMap<Tick, Vec<I>>,
+    rollback: Option<Tick>,
+    hashes: BTreeMap<Tick, Vec<Option<u64>>>,
+}
+
+impl<I: Clone + PartialEq + Default> LockstepSession<I> {
+    /// Default input delay (in ticks).
+    pub const DEFAULT_INPUT_DELAY: u32 = 2;
+
+    /// Creates new session for the given amount of players, `local_player` is an index of the local
+    /// player in `[0; player_count)` range.
+    pub fn new(player_count: usize, local_player: usize) -> Self {
+        assert!(local_player < player_count);
+
+        Self {
+            player_count,
+            local_player,
+            input_delay: Self::DEFAULT_INPUT_DELAY,
+            max_prediction: 0,
+            local_tick: None,
+            current_tick: 0,
+            confirmed_tick: 0,
+            inputs: Default::default(),
+            predicted: Default::default(),
+            rollback: None,
+            hashes: Default::default(),
+        }
+    }
+
+    /// Sets input delay (in ticks). Bigger delay hides more latency, but the game feels less
+    /// responsive. All peers must use the same delay, because ticks before the first input have
+    /// default inputs of all players. The delay must be set before the first local input, it is
+    /// ignored after.
+    pub fn set_input_delay(&mut self, delay: u32) {
+        self.input_delay = delay;
+    }
+
+    /// Returns input delay (in ticks).
+    pub fn input_delay(&self) -> u32 {
+        self.input_delay
+    }
+
+    /// Sets maximum amount of ticks, that could be simulated with predicted inputs. Zero means
+    /// pure lockstep without rollbacks.
+    pub fn set_max_prediction(&mut self, ticks: u32) {
+        self.max_prediction = ticks;
+    }
+
+    /// Returns maximum amount of ticks, that could be simulated with predicted inputs.
+    pub fn max_prediction(&self) -> u32 {
+        self.max_prediction
+    }
+
+    /// Returns amount of players.
+    pub fn player_count(&self) -> usize {
+        self.player_count
+    }
+
+    /// Returns index of the local player.
+    pub fn local_player(&self) -> usize {
+        self.local_player
+    }
+
+    /// Returns the tick, that will be simulated next.
+    pub fn current_tick(&self) -> Tick {
+        self.current_tick
+    }
+
+    /// Returns the first tick, that does not have inputs of all players yet. States of previous
+    /// ticks are final and will never be rolled back.
+    pub fn confirmed_tick(&self) -> Tick {
+        self.confirmed_tick
+    }
+
+    /// Schedules the input of the local player. Returns the tick of the input, it should be sent
+    /// to other peers along with the input.
+    pub fn add_local_input(&mut self, input: I) -> Tick {
+        let tick = match self.local_tick {
+            Some(tick) => tick,
+            None => {
+                // Ticks, that are skipped by the delay, have default inputs.
+                let first = self.current_tick + self.input_delay;
+                for tick in self.current_tick..first {
+                    for player in 0..self.player_count {
+                        self.set_input(player, tick, I::default());
+                    }
+                }
+                first
+            }
+        };
+        self.local_tick = Some(tick + 1);
+        self.set_input(self.local_player, tick, input);
+        tick
+    }
+
+    /// Adds an input of a remote player. Duplicated inputs are ignored.
+    pub fn add_remote_input(&mut self, player: usize, tick: Tick, input: I) {
+        if player < self.player_count && player != self.local_player {
+            self.set_input(player, tick, input);
+        }
+    }
+
+    fn set_input(&mut self, player: usize, tick: Tick, input: I) {
+        if tick < self.confirmed_tick {
+            return;
+        }
+
+        let player_count = self.player_count;
+        let inputs = self
+            .inputs
+            .entry(tick)
+            .or_insert_with(|| vec![None; player_count]);
+        if inputs[player].is_some() {
+            return;
+        }
+
+        if let Some(predicted) = self.predicted.get(&tick) {
+            if predicted[player] != input {
+                self.rollback = Some(self.rollback.map_or(tick, |rollback| rollback.min(tick)));
+            }
+        }
+        inputs[player] = Some(input);
+
+        while self
+            .inputs
+            .get(&self.confirmed_tick)
+            .map_or(false, |inputs| inputs.iter().all(|input| input.is_some()))
+        {
+            self.predicted.remove(&self.confirmed_tick);
+            self.confirmed_tick += 1;
+        }
+
+        self.discard_inputs();
+    }
+
+    fn discard_inputs(&mut self) {
+        // Inputs of ticks, that are not simulated yet, are kept. Inputs of the previous tick are
+        // kept to predict inputs of the next ticks.
+        let oldest = self
+            .confirmed_tick
+            .min(self.current_tick)
+            .min(self.rollback.unwrap_or(Tick::MAX))
+            .saturating_sub(1);
+        while let Some(tick) = self.inputs.keys().next().cloned() {
+            if tick >= oldest {
+                break;
+            }
+            self.inputs.remove(&tick);
+        }
+    }
+
+    fn predict_input(&self, player: usize, tick: Tick) -> I {
+        self.inputs
+            .range(..=tick)
+            .rev()
+            .find_map(|(_, inputs)| inputs[player].clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns inputs of all players for the current tick and advances to the next tick. Returns
+    /// `None` if the tick can't be simulated yet - some inputs are missing and the prediction limit
+    /// is reached. It should be called in a loop in every fixed step, until it returns `None`.
+    pub fn advance(&mut self) -> Option<Vec<I>> {
+        let tick = self.current_tick;
+        // Local inputs are never predicted.
+        if self
+            .local_tick
+            .map_or(true, |local_tick| tick >= local_tick)
+        {
+            return None;
+        }
+
+        let inputs = if tick < self.confirmed_tick {
+            self.inputs
+                .get(&tick)?
+                .iter()
+                .map(|input| input.clone().unwrap_or_default())
+                .collect()
+        } else if tick - self.confirmed_tick < self.max_prediction {
+            let inputs = (0..self.player_count)
+                .map(|player| self.predict_input(player, tick))
+                .collect::<Vec<_>>();
+            self.predicted.insert(tick, inputs.clone());
+            inputs
+        } else {
+            return None;
+        };
+
+        self.current_tick += 1;
+        self.discard_inputs();
+        Some(inputs)
+    }
+
+    /// Returns the tick, that must be rolled back (if any). The game must restore its state, that
+    /// was saved before the simulation of the tick, and call [`Self::advance`] again to simulate
+    /// the ticks with corrected inputs.
+    pub fn take_rollback(&mut self) -> Option<Tick> {
+        let tick = self.rollback.take()?;
+        self.current_tick = self.current_tick.min(tick);
+        // Predictions for the rolled back ticks will be made again.
+        self.predicted.retain(|predicted, _| *predicted < tick);
+        Some(tick)
+    }
+
+    /// Remembers hash of the local state after the simulation of the given tick. Hashes should be
+    /// recorded only for confirmed ticks (see [`Self::confirmed_tick`]). Returns the desync, if
+    /// there is a remote hash of the tick, that differs.
+    pub fn add_local_hash(&mut self, tick: Tick, hash: u64) -> Result<(), Desync> {
+        self.add_hash(self.local_player, tick, hash)
+    }
+
+    /// Remembers hash of a remote state after the simulation of the given tick. Returns the
+    /// desync, if the local hash of the tick differs.
+    pub fn add_remote_hash(&mut self, player: usize, tick: Tick, hash: u64) -> Result<(), Desync> {
+        if player < self.player_count && player != self.local_player {
+            self.add_hash(player, tick, hash)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_hash(&mut self, player: usize, tick: Tick, hash: u64) -> Result<(), Desync> {
+        // Compared hashes are no longer needed.
+        let oldest = tick.saturating_sub(HASH_HISTORY);
+        while let Some(tick) = self.hashes.keys().next().cloned() {
+            if tick >= oldest {
+                break;
+            }
+            self.hashes.remove(&tick);
+        }
+
+        let player_count = self.player_count;
+        let hashes = self
+            .hashes
+            .entry(tick)
+            .or_insert_with(|| vec![None; player_count]);
+        hashes[player] = Some(hash);
+
+        let local_hash = match hashes[self.local_player] {
+            Some(local_hash) => local_hash,
+            None => return Ok(()),
+        };
+        for (player, remote_hash) in hashes.iter().enumerate() {
+            if let Some(remote_hash) = *remote_hash {
+                if remote_hash != local_hash {
+                    return Err(Desync {
+                        tick,
+                        player,
+                        local_hash,
+                        remote_hash,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Ring buffer of state snapshots for rollbacks. A snapshot of a tick is the state before the
+/// simulation of the tick (see [`save_state`]).
+pub struct RollbackBuffer<S> {
+    snapshots: VecDeque<(Tick, S)>,
+    capacity: usize,
+}
+
+impl<S> Default for RollbackBuffer<S> {
+    fn default() -> Self {
+        Self {
+            snapshots: Default::default(),
+            capacity: Self::DEFAULT_CAPACITY,
+        }
+    }
+}
+
+impl<S> RollbackBuffer<S> {
+    /// Default maximum amount of snapshots.
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    /// Creates new empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets maximum amount of snapshots, it should be greater than maximum prediction of a
+    /// session. Minimal value is 1.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Returns maximum amount of snapshots.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Adds a snapshot of the state before the simulation of the given tick. Snapshots of the same
+    /// or newer ticks are replaced.
+    pub fn push(&mut self, tick: Tick, snapshot: S) {
+        while self
+            .snapshots
+            .back()
+            .map_or(false, |(other, _)| *other >= tick)
+        {
+            self.snapshots.pop_back();
+        }
+        self.snapshots.push_back((tick, snapshot));
+        if self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Returns a snapshot of the given tick.
+    pub fn get(&self, tick: Tick) -> Option<&S> {
+        self.snapshots
+            .iter()
+            .find(|(other, _)| *other == tick)
+            .map(|(_, snapshot)| snapshot)
+    }
+
+    /// Removes snapshots older than the given tick, they're no longer needed when the tick is
+    /// confirmed.
+    pub fn discard_before(&mut self, tick: Tick) {
+        while self
+            .snapshots
+            .front()
+            .map_or(false, |(other, _)| *other < tick)
+        {
+            self.snapshots.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::network::lockstep::{
+        hash_state, load_state, save_state, LockstepSession, RollbackBuffer,
+    };
+
+    #[test]
+    fn test_lockstep() {
+        let mut a = LockstepSession::<i32>::new(2, 0);
+        a.set_input_delay(1);
+        let mut b = LockstepSession::<i32>::new(2, 1);
+        b.set_input_delay(1);
+
+        // Pure lockstep waits for the remote input.
+        let tick = a.add_local_input(1);
+        assert_eq!(tick, 1);
+        // Inputs of the first tick are default because of the delay.
+        assert_eq!(a.advance(), Some(vec![0, 0]));
+        assert_eq!(a.advance(), None);
+        a.add_remote_input(1, 1, 2);
+        assert_eq!(a.advance(), Some(vec![1, 2]));
+        assert_eq!(a.advance(), None);
+        assert_eq!(a.confirmed_tick(), 2);
+
+        // Prediction repeats the last input of a player and rolls back on mismatch.
+        b.set_max_prediction(4);
+        let mut state = 0;
+        let mut snapshots = RollbackBuffer::new();
+        let mut simulate = |session: &mut LockstepSession<i32>, state: &mut i32| {
+            if let Some(tick) = session.take_rollback() {
+                *state = *snapshots.get(tick).unwrap();
+            }
+            loop {
+                let tick = session.current_tick();
+                snapshots.push(tick, *state);
+                match session.advance() {
+                    Some(inputs) => *state += inputs[0] * 10 + inputs[1],
+                    None => break,
+                }
+            }
+        };
+        b.add_local_input(2);
+        b.add_local_input(3);
+        b.add_remote_input(0, 1, 1);
+        simulate(&mut b, &mut state);
+        // Third tick uses predicted input of the first player.
+        assert_eq!(state, 10 + 2 + 10 + 3);
+        b.add_remote_input(0, 2, 5);
+        simulate(&mut b, &mut state);
+        assert_eq!(state, 10 + 2 + 50 + 3);
+        assert_eq!(b.confirmed_tick(), 3);
+
+        // Hashes of different states.
+        let mut value = 42u32;
+        let hash = hash_state(&mut value).unwrap();
+        assert_ne!(hash, hash_state(&mut 43u32).unwrap());
+        assert!(a.add_local_hash(1, hash).is_ok());
+        assert!(a.add_remote_hash(1, 1, hash).is_ok());
+        let desync = a
+            .add_remote_hash(1, 2, hash)
+            .and(a.add_local_hash(2, hash + 1));
+        assert_eq!(desync.unwrap_err().tick, 2);
+
+        let data = save_state(&mut value).unwrap();
+        let mut restored = 0u32;
+        load_state(&mut restored, data).unwrap();
+        assert_eq!(restored, 42);
+    }
+}
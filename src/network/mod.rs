@@ -0,0 +1,51 @@
+//! Networking for multiplayer games. The module consists of two layers: [`transport`], that moves
+//! raw messages between peers, and [`replication`], that keeps state of game entities on clients
+//! in sync with the authoritative server. There are also few helpers for networked movement:
+//! [`prediction`] hides latency for the local player and [`interpolation`] smooths motion of
+//! remote entities. Games with deterministic simulation could use [`lockstep`] instead of
+//! replication.
+
+#![warn(missing_docs)]
+
+pub mod interpolation;
+pub mod lockstep;
+pub mod prediction;
+pub mod replication;
+pub mod transport;
+
+use crate::network::transport::ConnectionId;
+use std::fmt::{Display, Formatter};
+
+/// Index of a fixed simulation step (see [`crate::engine::timestep::FixedTimestep`]). Both a
+/// client and a server count ticks, so inputs and states could be matched between them.
+pub type Tick = u32;
+
+/// An error, that may occur during network communication.
+#[derive(Debug)]
+pub enum NetworkError {
+    /// An I/O error has occurred.
+    Io(std::io::Error),
+    /// A message could not be decoded.
+    InvalidMessage(String),
+    /// There is no connection with the given id.
+    UnknownConnection(ConnectionId),
+    /// The connection was closed.
+    Disconnected,
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Io(v) => write!(f, "An i/o error has occurred {:?}", v),
+            NetworkError::InvalidMessage(v) => write!(f, "Invalid message: {}", v),
+            NetworkError::UnknownConnection(v) => write!(f, "Unknown connection {:?}", v),
+            NetworkError::Disconnected => write!(f, "The connection was closed"),
+        }
+    }
+}
+
+impl From<std::io::Error> for NetworkError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
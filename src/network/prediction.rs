@@ -0,0 +1,289 @@
+//! Client-side prediction with server reconciliation. See [`ClientPrediction`] docs for more info.
+
+use crate::network::Tick;
+use std::collections::{BTreeMap, VecDeque};
+
+struct InputRecord<I, S> {
+    tick: Tick,
+    input: I,
+    // Predicted state after the input was applied.
+    state: S,
+}
+
+/// Client-side prediction hides latency for the local player. Instead of waiting for the server,
+/// the client applies its inputs immediately and remembers them. When an authoritative state
+/// arrives from the server (it contains the tick of the last processed input), the prediction is
+/// checked against it - if they differ, the client takes the server state and re-applies all
+/// inputs, that the server has not processed yet.
+///
+/// Simulation function must be deterministic and it must be the same on both sides, otherwise
+/// every server update will cause a correction.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::network::prediction::ClientPrediction;
+///
+/// fn simulate(position: &f32, velocity: &f32) -> f32 {
+///     position + velocity / 60.0
+/// }
+///
+/// let mut prediction = ClientPrediction::new(0.0f32);
+///
+/// // Fixed update of the client.
+/// let tick = prediction.predict(1.0, simulate);
+/// // Send the input with its tick to the server...
+///
+/// // The server has processed the input and sent back the authoritative state.
+/// let corrected = prediction.reconcile(tick, 0.01, simulate, |a, b| (a - b).abs() < 0.001);
+/// assert!(corrected);
+/// ```
+pub struct ClientPrediction<I, S> {
+    state: S,
+    tick: Tick,
+    history: VecDeque<InputRecord<I, S>>,
+    capacity: usize,
+}
+
+impl<I, S: Clone> ClientPrediction<I, S> {
+    /// Default maximum amount of inputs, that are not acknowledged by the server.
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// Creates new prediction with the given initial state.
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            tick: 0,
+            history: Default::default(),
+            capacity: Self::DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Sets maximum amount of inputs, that are not acknowledged by the server. Oldest inputs are
+    /// dropped when the limit is reached, which means that the server is too far behind and
+    /// reconciliation is not possible until the server catches up. Minimal value is 1.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.history.len() > self.capacity {
+            self.history.pop_front();
+        }
+    }
+
+    /// Returns maximum amount of inputs, that are not acknowledged by the server.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns current predicted state.
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Returns the tick, that will be assigned to the next input.
+    pub fn tick(&self) -> Tick {
+        self.tick
+    }
+
+    /// Returns an iterator over inputs, that are not acknowledged by the server yet. Inputs are
+    /// usually sent unreliably, so it is a good practice to send few last inputs every tick.
+    pub fn pending_inputs(&self) -> impl Iterator<Item = (Tick, &I)> {
+        self.history
+            .iter()
+            .map(|record| (record.tick, &record.input))
+    }
+
+    /// Applies the input to the current state and remembers it. Must be called once per fixed
+    /// simulation step. Returns the tick of the input, that should be sent to the server along with
+    /// the input.
+    pub fn predict<F>(&mut self, input: I, mut simulate: F) -> Tick
+    where
+        F: FnMut(&S, &I) -> S,
+    {
+        let tick = self.tick;
+        self.state = simulate(&self.state, &input);
+        self.history.push_back(InputRecord {
+            tick,
+            input,
+            state: self.state.clone(),
+        });
+        if self.history.len() > self.capacity {
+            self.history.pop_front();
+        }
+        self.tick = self.tick.wrapping_add(1);
+        tick
+    }
+
+    /// Checks the prediction against the authoritative `state` of the server after it processed the
+    /// input with the given tick. If the predicted state is not close enough to the server state,
+    /// remaining inputs are re-applied to the server state. Returns `true` if the prediction was
+    /// corrected. States of unknown (too old or already acknowledged) ticks are ignored.
+    pub fn reconcile<F, C>(
+        &mut self,
+        tick: Tick,
+        state: S,
+        mut simulate: F,
+        mut is_close: C,
+    ) -> bool
+    where
+        F: FnMut(&S, &I) -> S,
+        C: FnMut(&S, &S) -> bool,
+    {
+        while self
+            .history
+            .front()
+            .map_or(false, |record| record.tick < tick)
+        {
+            self.history.pop_front();
+        }
+
+        let predicted = match self.history.front() {
+            Some(record) if record.tick == tick => self.history.pop_front().unwrap().state,
+            _ => return false,
+        };
+        if is_close(&predicted, &state) {
+            return false;
+        }
+
+        let mut current = state;
+        for record in self.history.iter_mut() {
+            current = simulate(&current, &record.input);
+            record.state = current.clone();
+        }
+        self.state = current;
+        true
+    }
+}
+
+/// Server-side buffer of inputs of a client. Clients usually send few last inputs in every packet
+/// (see [`ClientPrediction::pending_inputs`]), so inputs could arrive duplicated or out of order.
+/// The buffer sorts them and drops the ones, that are already processed.
+pub struct InputBuffer<I> {
+    inputs: BTreeMap<Tick, I>,
+    last_processed: Option<Tick>,
+    capacity: usize,
+}
+
+impl<I> Default for InputBuffer<I> {
+    fn default() -> Self {
+        Self {
+            inputs: Default::default(),
+            last_processed: None,
+            capacity: Self::DEFAULT_CAPACITY,
+        }
+    }
+}
+
+impl<I> InputBuffer<I> {
+    /// Default maximum amount of buffered inputs.
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// Creates new empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets maximum amount of buffered inputs, oldest inputs are dropped when the limit is reached.
+    /// Minimal value is 1.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.trim();
+    }
+
+    /// Returns maximum amount of buffered inputs.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns amount of buffered inputs.
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Returns `true` if there are no buffered inputs.
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Returns the tick of the last processed input. It should be sent to the client along with
+    /// the state, so the client can reconcile its prediction.
+    pub fn last_processed(&self) -> Option<Tick> {
+        self.last_processed
+    }
+
+    /// Adds the input to the buffer, inputs of already processed ticks are ignored.
+    pub fn push(&mut self, tick: Tick, input: I) {
+        if self.last_processed.map_or(false, |last| tick <= last) {
+            return;
+        }
+        self.inputs.entry(tick).or_insert(input);
+        self.trim();
+    }
+
+    /// Returns the oldest buffered input, that should be processed in the current fixed step.
+    pub fn pop(&mut self) -> Option<(Tick, I)> {
+        let tick = *self.inputs.keys().next()?;
+        let input = self.inputs.remove(&tick)?;
+        self.last_processed = Some(tick);
+        Some((tick, input))
+    }
+
+    fn trim(&mut self) {
+        while self.inputs.len() > self.capacity {
+            if let Some(tick) = self.inputs.keys().next().cloned() {
+                self.inputs.remove(&tick);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::network::prediction::{ClientPrediction, InputBuffer};
+
+    #[test]
+    fn test_prediction() {
+        let simulate = |state: &i32, input: &i32| state + input;
+        let server_simulate = |state: &i32, input: &i32| state + input * 2;
+
+        let mut prediction = ClientPrediction::new(0);
+        let mut inputs = InputBuffer::new();
+        for input in 1..=3 {
+            let tick = prediction.predict(input, simulate);
+            // The second input is delivered twice and the first one arrives late.
+            if tick > 0 {
+                inputs.push(tick, input);
+            }
+            if tick == 2 {
+                inputs.push(tick - 1, input - 1);
+                inputs.push(0, 1);
+            }
+        }
+        assert_eq!(*prediction.state(), 6);
+        assert_eq!(prediction.pending_inputs().count(), 3);
+
+        // The server processes the first input only.
+        let mut server_state = 0;
+        let (tick, input) = inputs.pop().unwrap();
+        assert_eq!(tick, 0);
+        server_state = server_simulate(&server_state, &input);
+        assert_eq!(inputs.len(), 2);
+
+        // The server state differs (2 instead of 1), so the client must re-apply inputs 2 and 3.
+        assert!(prediction.reconcile(tick, server_state, simulate, |a, b| a == b));
+        assert_eq!(*prediction.state(), 7);
+        assert_eq!(prediction.pending_inputs().count(), 2);
+
+        // Already acknowledged state is ignored.
+        assert!(!prediction.reconcile(tick, 100, simulate, |a, b| a == b));
+
+        // Matching state does not cause a correction.
+        let (tick, input) = inputs.pop().unwrap();
+        server_state += input;
+        assert!(!prediction.reconcile(tick, server_state, simulate, |a, b| a == b));
+        assert_eq!(*prediction.state(), 7);
+
+        inputs.push(tick, 10);
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs.last_processed(), Some(1));
+    }
+}
@@ -0,0 +1,144 @@
+//! Delta compression of entity states. A delta is a XOR of a new state and a baseline (a state,
+//! that is known by both sides), encoded as a sequence of runs of zero bytes and literal bytes.
+//! Usually only a small part of a state changes between updates, so deltas are much smaller than
+//! full states.
+
+use crate::network::NetworkError;
+
+/// Maximum length of a state, it protects from allocation of huge buffers for malformed deltas.
+const MAX_STATE_LENGTH: usize = 16 * 1024 * 1024;
+
+fn write_varint(mut value: usize, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn read_varint(data: &[u8], position: &mut usize) -> Result<usize, NetworkError> {
+    let mut value = 0usize;
+    let mut shift = 0;
+    loop {
+        let byte = *data
+            .get(*position)
+            .ok_or_else(|| NetworkError::InvalidMessage("Truncated delta".to_owned()))?;
+        *position += 1;
+        if shift >= usize::BITS {
+            return Err(NetworkError::InvalidMessage(
+                "Varint is too long".to_owned(),
+            ));
+        }
+        value |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn xor_at(data: &[u8], baseline: &[u8], i: usize) -> u8 {
+    data.get(i).copied().unwrap_or_default() ^ baseline.get(i).copied().unwrap_or_default()
+}
+
+/// Encodes the difference between the state and the baseline.
+pub fn encode(baseline: &[u8], state: &[u8], out: &mut Vec<u8>) {
+    write_varint(state.len(), out);
+
+    let mut i = 0;
+    while i < state.len() {
+        let zeros_begin = i;
+        while i < state.len() && xor_at(state, baseline, i) == 0 {
+            i += 1;
+        }
+        if i == state.len() {
+            // Trailing zeros are implied.
+            break;
+        }
+
+        let literal_begin = i;
+        // Short runs of zeros are cheaper to store as literals.
+        while i < state.len()
+            && (xor_at(state, baseline, i) != 0
+                || (i + 1 < state.len() && xor_at(state, baseline, i + 1) != 0))
+        {
+            i += 1;
+        }
+
+        write_varint(literal_begin - zeros_begin, out);
+        write_varint(i - literal_begin, out);
+        out.extend((literal_begin..i).map(|j| xor_at(state, baseline, j)));
+    }
+}
+
+/// Restores a state from a delta and the baseline, that was used to encode the delta.
+pub fn decode(baseline: &[u8], delta: &[u8]) -> Result<Vec<u8>, NetworkError> {
+    let mut position = 0;
+    let length = read_varint(delta, &mut position)?;
+    if length > MAX_STATE_LENGTH {
+        return Err(NetworkError::InvalidMessage("State is too big".to_owned()));
+    }
+
+    let mut state = (0..length)
+        .map(|i| baseline.get(i).copied().unwrap_or_default())
+        .collect::<Vec<_>>();
+
+    let mut i = 0usize;
+    while position < delta.len() {
+        i = i.saturating_add(read_varint(delta, &mut position)?);
+        let count = read_varint(delta, &mut position)?;
+        let literal = delta
+            .get(position..position.saturating_add(count))
+            .ok_or_else(|| NetworkError::InvalidMessage("Truncated delta".to_owned()))?;
+        let target = state
+            .get_mut(i..i.saturating_add(count))
+            .ok_or_else(|| NetworkError::InvalidMessage("Delta is out of bounds".to_owned()))?;
+        for (byte, xor) in target.iter_mut().zip(literal) {
+            *byte ^= xor;
+        }
+        position += count;
+        i += count;
+    }
+
+    Ok(state)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::network::replication::delta::{decode, encode};
+
+    #[test]
+    fn test_delta() {
+        let baseline = (0..200).map(|i| i as u8).collect::<Vec<_>>();
+        let mut state = baseline.clone();
+        state[10] = 0;
+        state[11] = 1;
+        state[150] = 42;
+
+        let mut delta = Vec::new();
+        encode(&baseline, &state, &mut delta);
+        assert!(delta.len() < 16);
+        assert_eq!(decode(&baseline, &delta).unwrap(), state);
+
+        // Unchanged state.
+        delta.clear();
+        encode(&baseline, &baseline, &mut delta);
+        assert_eq!(delta.len(), 2);
+        assert_eq!(decode(&baseline, &delta).unwrap(), baseline);
+
+        // States of different length and an empty baseline (full state).
+        for state in [vec![1, 2, 3], (0..300).map(|i| (i * 7) as u8).collect()] {
+            for baseline in [&baseline[..], &[]] {
+                delta.clear();
+                encode(baseline, &state, &mut delta);
+                assert_eq!(decode(baseline, &delta).unwrap(), state);
+            }
+        }
+
+        assert!(decode(&baseline, &[5, 0, 10, 1]).is_err());
+    }
+}
@@ -0,0 +1,806 @@
+//! Server-authoritative replication of game entities. See [`ReplicationServer`] and
+//! [`ReplicationClient`] docs for more info.
+
+mod delta;
+mod node;
+mod protocol;
+
+use crate::{
+    core::{algebra::Vector3, log::Log, pool::Handle},
+    network::{
+        replication::protocol::Message,
+        transport::{ConnectionId, DeliveryMode, Transport},
+    },
+    scene::node::Node,
+};
+use fxhash::FxHashMap;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Maximum amount of unacknowledged states per entity per client, that are kept by the server.
+const MAX_PENDING_STATES: usize = 32;
+/// Maximum amount of received states per entity, that are kept by the client to decode deltas.
+const MAX_STATE_HISTORY: usize = 64;
+
+/// Unique identifier of a replicated entity, it is the same on the server and on every client.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NetworkId(pub u64);
+
+/// Interest management policy defines which entities are replicated to a client. Entities, that
+/// are not interesting for a client, are despawned on the client and do not consume bandwidth.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum InterestPolicy {
+    /// Every entity is replicated to every client.
+    #[default]
+    Everything,
+    /// Only entities within the given distance from the viewer of a client are replicated. See
+    /// [`ReplicationServer::set_viewer_position`].
+    Distance(f32),
+}
+
+/// An event of [`ReplicationServer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServerEvent {
+    /// A client has connected.
+    ClientConnected(ConnectionId),
+    /// A client has disconnected.
+    ClientDisconnected(ConnectionId),
+    /// A client has sent a remote procedure call.
+    Rpc {
+        /// The client, that sent the call.
+        connection: ConnectionId,
+        /// An entity, that is the target of the call, if any.
+        entity: Option<NetworkId>,
+        /// Name of the procedure.
+        name: String,
+        /// Arguments of the procedure.
+        payload: Vec<u8>,
+    },
+}
+
+/// An event of [`ReplicationClient`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientEvent {
+    /// The client has connected to the server.
+    Connected,
+    /// The client has disconnected from the server, every replicated entity is despawned.
+    Disconnected,
+    /// A new entity has appeared on the client.
+    Spawned {
+        /// Identifier of the entity.
+        entity: NetworkId,
+        /// Kind of the entity, it could be used to choose a prefab to instantiate.
+        kind: String,
+    },
+    /// An entity was despawned.
+    Despawned(NetworkId),
+    /// The state of an entity has changed.
+    StateChanged(NetworkId),
+    /// The server has sent a remote procedure call.
+    Rpc {
+        /// An entity, that is the target of the call, if any.
+        entity: Option<NetworkId>,
+        /// Name of the procedure.
+        name: String,
+        /// Arguments of the procedure.
+        payload: Vec<u8>,
+    },
+}
+
+// Returns `true` if the sequence `a` is newer than `b`, taking wrapping into account.
+fn is_newer(a: u32, b: u32) -> bool {
+    a != b && a.wrapping_sub(b) < u32::MAX / 2
+}
+
+fn send(
+    transport: &mut dyn Transport,
+    connection: ConnectionId,
+    mode: DeliveryMode,
+    message: &Message,
+) {
+    let mut data = Vec::new();
+    message.write(&mut data);
+    if let Err(err) = transport.send(connection, mode, &data) {
+        Log::warn(format!(
+            "Unable to send a replication message to {:?}: {}",
+            connection, err
+        ));
+    }
+}
+
+struct ServerEntity {
+    kind: String,
+    state: Vec<u8>,
+    position: Vector3<f32>,
+    always_relevant: bool,
+    node: Handle<Node>,
+    replicate_script: bool,
+}
+
+struct EntityView {
+    // The latest state, that is acknowledged by the client.
+    baseline: (u32, Vec<u8>),
+    pending: VecDeque<(u32, Vec<u8>)>,
+}
+
+impl EntityView {
+    fn acknowledge(&mut self, sequence: u32) {
+        if let Some(index) = self.pending.iter().position(|(s, _)| *s == sequence) {
+            self.baseline = self.pending.remove(index).unwrap();
+            self.pending.retain(|(s, _)| is_newer(*s, sequence));
+        }
+    }
+}
+
+#[derive(Default)]
+struct ClientView {
+    viewer: Option<Vector3<f32>>,
+    entities: FxHashMap<NetworkId, EntityView>,
+}
+
+/// Replication server owns the authoritative state of replicated entities and sends it to the
+/// connected clients. A replicated entity has a kind (it is used by clients to decide what to
+/// spawn) and a state - an arbitrary array of bytes. The state is sent using delta compression:
+/// the server sends only the difference between the current state and the latest state, that
+/// was acknowledged by a client, unchanged states are not sent at all. Spawning and despawning
+/// of entities on clients is done automatically, taking the [interest policy](InterestPolicy)
+/// into account.
+///
+/// Scene nodes could be replicated directly, see [`Self::replicate_node`]. In this case, the state
+/// of an entity consists of the local transform of the node and (optionally) the state of its
+/// script.
+///
+/// Clients cannot change the state of entities, instead they send remote procedure calls (RPC) to
+/// the server, the server validates them and changes the state.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::network::{
+///     replication::{ReplicationServer, ServerEvent},
+///     transport::Transport,
+/// };
+///
+/// fn update(server: &mut ReplicationServer, transport: &mut dyn Transport) {
+///     server.update(transport);
+///
+///     while let Some(event) = server.pop_event() {
+///         if let ServerEvent::Rpc { connection, name, .. } = event {
+///             if name == "Ping" {
+///                 server.send_rpc(Some(connection), None, "Pong", Vec::new());
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct ReplicationServer {
+    entities: BTreeMap<NetworkId, ServerEntity>,
+    clients: BTreeMap<ConnectionId, ClientView>,
+    interest_policy: InterestPolicy,
+    next_id: u64,
+    sequence: u32,
+    events: VecDeque<ServerEvent>,
+    rpcs: Vec<(Option<ConnectionId>, Message)>,
+}
+
+impl ReplicationServer {
+    /// Creates new replication server.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets interest management policy.
+    pub fn set_interest_policy(&mut self, policy: InterestPolicy) {
+        self.interest_policy = policy;
+    }
+
+    /// Returns interest management policy.
+    pub fn interest_policy(&self) -> InterestPolicy {
+        self.interest_policy
+    }
+
+    /// Sets position of the viewer (usually a camera or a player character) of a client, it is
+    /// used by [`InterestPolicy::Distance`]. Until the position is set, only the
+    /// [always relevant](Self::set_always_relevant) entities are replicated to the client.
+    pub fn set_viewer_position(&mut self, connection: ConnectionId, position: Vector3<f32>) {
+        if let Some(client) = self.clients.get_mut(&connection) {
+            client.viewer = Some(position);
+        }
+    }
+
+    /// Returns an iterator over the connected clients.
+    pub fn clients(&self) -> impl Iterator<Item = ConnectionId> + '_ {
+        self.clients.keys().copied()
+    }
+
+    /// Starts replication of a new entity with the given kind and the initial state.
+    pub fn spawn(&mut self, kind: impl Into<String>, state: Vec<u8>) -> NetworkId {
+        let id = NetworkId(self.next_id);
+        self.next_id += 1;
+        self.entities.insert(
+            id,
+            ServerEntity {
+                kind: kind.into(),
+                state,
+                position: Default::default(),
+                always_relevant: false,
+                node: Handle::NONE,
+                replicate_script: false,
+            },
+        );
+        id
+    }
+
+    /// Stops replication of an entity, the entity will be despawned on every client. Returns
+    /// `false` if there is no such entity.
+    pub fn despawn(&mut self, entity: NetworkId) -> bool {
+        self.entities.remove(&entity).is_some()
+    }
+
+    /// Returns `true` if the entity is replicated.
+    pub fn is_replicated(&self, entity: NetworkId) -> bool {
+        self.entities.contains_key(&entity)
+    }
+
+    /// Sets new state of an entity, the state will be sent to clients on the next update.
+    pub fn set_state(&mut self, entity: NetworkId, state: Vec<u8>) {
+        if let Some(entity) = self.entities.get_mut(&entity) {
+            entity.state = state;
+        }
+    }
+
+    /// Returns the current state of an entity.
+    pub fn state(&self, entity: NetworkId) -> Option<&[u8]> {
+        self.entities.get(&entity).map(|e| e.state.as_slice())
+    }
+
+    /// Sets position of an entity, it is used for interest management.
+    pub fn set_position(&mut self, entity: NetworkId, position: Vector3<f32>) {
+        if let Some(entity) = self.entities.get_mut(&entity) {
+            entity.position = position;
+        }
+    }
+
+    /// Defines whether an entity is replicated to every client regardless of the interest policy
+    /// or not. It is useful for global entities, such as game rules or scores.
+    pub fn set_always_relevant(&mut self, entity: NetworkId, always_relevant: bool) {
+        if let Some(entity) = self.entities.get_mut(&entity) {
+            entity.always_relevant = always_relevant;
+        }
+    }
+
+    /// Queues a remote procedure call, it will be sent on the next update using reliable delivery.
+    /// If `connection` is `None`, the call is sent to every client.
+    pub fn send_rpc(
+        &mut self,
+        connection: Option<ConnectionId>,
+        entity: Option<NetworkId>,
+        name: impl Into<String>,
+        payload: Vec<u8>,
+    ) {
+        self.rpcs.push((
+            connection,
+            Message::Rpc {
+                entity,
+                name: name.into(),
+                payload,
+            },
+        ));
+    }
+
+    /// Returns the next event of the server, if any.
+    pub fn pop_event(&mut self) -> Option<ServerEvent> {
+        self.events.pop_front()
+    }
+
+    fn handle_message(&mut self, connection: ConnectionId, data: &[u8]) {
+        match Message::read(data) {
+            Ok(Message::Ack { entity, sequence }) => {
+                if let Some(view) = self
+                    .clients
+                    .get_mut(&connection)
+                    .and_then(|client| client.entities.get_mut(&entity))
+                {
+                    view.acknowledge(sequence);
+                }
+            }
+            Ok(Message::Rpc {
+                entity,
+                name,
+                payload,
+            }) => self.events.push_back(ServerEvent::Rpc {
+                connection,
+                entity,
+                name,
+                payload,
+            }),
+            // The server is authoritative, clients are not allowed to change entities.
+            Ok(_) => Log::warn(format!(
+                "Client {:?} has sent a message, that is allowed only for the server!",
+                connection
+            )),
+            Err(err) => Log::warn(format!(
+                "Invalid replication message from {:?}: {}",
+                connection, err
+            )),
+        }
+    }
+
+    /// Handles incoming messages and sends the changes of replicated entities to clients. Must be
+    /// called regularly, usually once per network tick.
+    pub fn update(&mut self, transport: &mut dyn Transport) {
+        use crate::network::transport::TransportEvent;
+
+        while let Some(event) = transport.poll_event() {
+            match event {
+                TransportEvent::Connected(connection) => {
+                    self.clients.insert(connection, Default::default());
+                    self.events
+                        .push_back(ServerEvent::ClientConnected(connection));
+                }
+                TransportEvent::Disconnected(connection) => {
+                    if self.clients.remove(&connection).is_some() {
+                        self.events
+                            .push_back(ServerEvent::ClientDisconnected(connection));
+                    }
+                }
+                TransportEvent::Message { connection, data } => {
+                    self.handle_message(connection, &data)
+                }
+            }
+        }
+
+        let sequence = self.sequence;
+        for (&connection, client) in self.clients.iter_mut() {
+            let entities = &self.entities;
+            let despawned = client
+                .entities
+                .keys()
+                .filter(|entity| !entities.contains_key(entity))
+                .copied()
+                .collect::<Vec<_>>();
+            for entity in despawned {
+                client.entities.remove(&entity);
+                let message = Message::Despawn { entity };
+                send(
+                    transport,
+                    connection,
+                    DeliveryMode::ReliableOrdered,
+                    &message,
+                );
+            }
+
+            for (&id, entity) in self.entities.iter() {
+                let is_relevant = entity.always_relevant
+                    || match self.interest_policy {
+                        InterestPolicy::Everything => true,
+                        InterestPolicy::Distance(distance) => match client.viewer {
+                            Some(viewer) => viewer.metric_distance(&entity.position) <= distance,
+                            None => false,
+                        },
+                    };
+
+                match client.entities.get_mut(&id) {
+                    Some(view) if is_relevant => {
+                        if view.pending.is_empty() && view.baseline.1 == entity.state {
+                            continue;
+                        }
+
+                        let mut delta = Vec::new();
+                        delta::encode(&view.baseline.1, &entity.state, &mut delta);
+                        let message = Message::State {
+                            entity: id,
+                            sequence,
+                            baseline: view.baseline.0,
+                            delta,
+                        };
+                        send(transport, connection, DeliveryMode::Unreliable, &message);
+
+                        view.pending.push_back((sequence, entity.state.clone()));
+                        if view.pending.len() > MAX_PENDING_STATES {
+                            view.pending.pop_front();
+                        }
+                    }
+                    Some(_) => {
+                        client.entities.remove(&id);
+                        let message = Message::Despawn { entity: id };
+                        send(
+                            transport,
+                            connection,
+                            DeliveryMode::ReliableOrdered,
+                            &message,
+                        );
+                    }
+                    None if is_relevant => {
+                        let message = Message::Spawn {
+                            entity: id,
+                            kind: entity.kind.clone(),
+                            sequence,
+                            state: entity.state.clone(),
+                        };
+                        send(
+                            transport,
+                            connection,
+                            DeliveryMode::ReliableOrdered,
+                            &message,
+                        );
+
+                        // Spawn messages are reliable, so the initial state is a valid baseline.
+                        client.entities.insert(
+                            id,
+                            EntityView {
+                                baseline: (sequence, entity.state.clone()),
+                                pending: Default::default(),
+                            },
+                        );
+                    }
+                    None => (),
+                }
+            }
+        }
+
+        for (connection, message) in self.rpcs.drain(..) {
+            match connection {
+                Some(connection) => send(
+                    transport,
+                    connection,
+                    DeliveryMode::ReliableOrdered,
+                    &message,
+                ),
+                None => {
+                    for &connection in self.clients.keys() {
+                        send(
+                            transport,
+                            connection,
+                            DeliveryMode::ReliableOrdered,
+                            &message,
+                        );
+                    }
+                }
+            }
+        }
+
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+}
+
+struct ClientEntity {
+    kind: String,
+    sequence: u32,
+    history: VecDeque<(u32, Vec<u8>)>,
+    node: Handle<Node>,
+    changed: bool,
+}
+
+/// Replication client receives entities from a [`ReplicationServer`] and keeps their states up to
+/// date. The client does not create any objects by itself, instead it reports spawned and
+/// despawned entities using [events](ClientEvent), so a game could create and destroy
+/// corresponding objects. For scene nodes, there is [`Self::apply_to_graph`] method, that
+/// instantiates nodes and applies their states automatically.
+#[derive(Default)]
+pub struct ReplicationClient {
+    server: Option<ConnectionId>,
+    entities: BTreeMap<NetworkId, ClientEntity>,
+    events: VecDeque<ClientEvent>,
+    rpcs: Vec<Message>,
+    despawned_nodes: Vec<Handle<Node>>,
+}
+
+impl ReplicationClient {
+    /// Creates new replication client.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the client is connected to the server.
+    pub fn is_connected(&self) -> bool {
+        self.server.is_some()
+    }
+
+    /// Returns an iterator over the replicated entities.
+    pub fn entities(&self) -> impl Iterator<Item = NetworkId> + '_ {
+        self.entities.keys().copied()
+    }
+
+    /// Returns kind of an entity.
+    pub fn kind(&self, entity: NetworkId) -> Option<&str> {
+        self.entities.get(&entity).map(|e| e.kind.as_str())
+    }
+
+    /// Returns the latest received state of an entity.
+    pub fn state(&self, entity: NetworkId) -> Option<&[u8]> {
+        self.entities
+            .get(&entity)
+            .and_then(|e| e.history.back())
+            .map(|(_, state)| state.as_slice())
+    }
+
+    /// Returns a scene node, that represents an entity (see [`Self::apply_to_graph`]).
+    pub fn node(&self, entity: NetworkId) -> Handle<Node> {
+        self.entities
+            .get(&entity)
+            .map(|e| e.node)
+            .unwrap_or_default()
+    }
+
+    /// Queues a remote procedure call to the server, it will be sent on the next update using
+    /// reliable delivery.
+    pub fn send_rpc(
+        &mut self,
+        entity: Option<NetworkId>,
+        name: impl Into<String>,
+        payload: Vec<u8>,
+    ) {
+        self.rpcs.push(Message::Rpc {
+            entity,
+            name: name.into(),
+            payload,
+        });
+    }
+
+    /// Returns the next event of the client, if any.
+    pub fn pop_event(&mut self) -> Option<ClientEvent> {
+        self.events.pop_front()
+    }
+
+    fn remove_entity(&mut self, id: NetworkId) {
+        if let Some(entity) = self.entities.remove(&id) {
+            if entity.node.is_some() {
+                self.despawned_nodes.push(entity.node);
+            }
+            self.events.push_back(ClientEvent::Despawned(id));
+        }
+    }
+
+    fn handle_message(&mut self, transport: &mut dyn Transport, server: ConnectionId, data: &[u8]) {
+        match Message::read(data) {
+            Ok(Message::Spawn {
+                entity,
+                kind,
+                sequence,
+                state,
+            }) => {
+                // Respawn (for example, when the entity becomes relevant again).
+                self.remove_entity(entity);
+                self.entities.insert(
+                    entity,
+                    ClientEntity {
+                        kind: kind.clone(),
+                        sequence,
+                        history: vec![(sequence, state)].into(),
+                        node: Handle::NONE,
+                        changed: true,
+                    },
+                );
+                self.events.push_back(ClientEvent::Spawned { entity, kind });
+            }
+            Ok(Message::Despawn { entity }) => self.remove_entity(entity),
+            Ok(Message::State {
+                entity: id,
+                sequence,
+                baseline,
+                delta,
+            }) => {
+                // States could arrive before spawn messages or out of order, such states are
+                // ignored.
+                let entity = match self.entities.get_mut(&id) {
+                    Some(entity) if is_newer(sequence, entity.sequence) => entity,
+                    _ => return,
+                };
+
+                let baseline = match entity.history.iter().find(|(s, _)| *s == baseline) {
+                    Some((_, baseline)) => baseline,
+                    None => return,
+                };
+
+                match delta::decode(baseline, &delta) {
+                    Ok(state) => {
+                        entity.history.push_back((sequence, state));
+                        if entity.history.len() > MAX_STATE_HISTORY {
+                            entity.history.pop_front();
+                        }
+                        entity.sequence = sequence;
+                        entity.changed = true;
+                        self.events.push_back(ClientEvent::StateChanged(id));
+
+                        let message = Message::Ack {
+                            entity: id,
+                            sequence,
+                        };
+                        send(transport, server, DeliveryMode::Unreliable, &message);
+                    }
+                    Err(err) => Log::warn(format!("Unable to decode state of {:?}: {}", id, err)),
+                }
+            }
+            Ok(Message::Rpc {
+                entity,
+                name,
+                payload,
+            }) => self.events.push_back(ClientEvent::Rpc {
+                entity,
+                name,
+                payload,
+            }),
+            Ok(Message::Ack { .. }) => (),
+            Err(err) => Log::warn(format!("Invalid replication message: {}", err)),
+        }
+    }
+
+    /// Handles incoming messages and sends queued remote procedure calls. Must be called
+    /// regularly, usually every frame.
+    pub fn update(&mut self, transport: &mut dyn Transport) {
+        use crate::network::transport::TransportEvent;
+
+        while let Some(event) = transport.poll_event() {
+            match event {
+                TransportEvent::Connected(connection) => {
+                    self.server = Some(connection);
+                    self.events.push_back(ClientEvent::Connected);
+                }
+                TransportEvent::Disconnected(_) => {
+                    self.server = None;
+                    let entities = self.entities.keys().copied().collect::<Vec<_>>();
+                    for entity in entities {
+                        self.remove_entity(entity);
+                    }
+                    self.events.push_back(ClientEvent::Disconnected);
+                }
+                TransportEvent::Message { connection, data } => {
+                    self.handle_message(transport, connection, &data)
+                }
+            }
+        }
+
+        if let Some(server) = self.server {
+            for message in self.rpcs.drain(..) {
+                send(transport, server, DeliveryMode::ReliableOrdered, &message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        network::{
+            replication::{
+                ClientEvent, InterestPolicy, ReplicationClient, ReplicationServer, ServerEvent,
+            },
+            transport::{LocalTransport, Transport},
+        },
+    };
+
+    fn tick(
+        server: &mut ReplicationServer,
+        server_transport: &mut dyn Transport,
+        client: &mut ReplicationClient,
+        client_transport: &mut dyn Transport,
+    ) {
+        server.update(server_transport);
+        client.update(client_transport);
+    }
+
+    #[test]
+    fn test_replication() {
+        let (mut server_transport, mut client_transport) = LocalTransport::pair();
+        let mut server = ReplicationServer::new();
+        let mut client = ReplicationClient::new();
+
+        let player = server.spawn("Player", vec![0; 64]);
+        tick(
+            &mut server,
+            &mut server_transport,
+            &mut client,
+            &mut client_transport,
+        );
+        assert_eq!(
+            server.pop_event(),
+            Some(ServerEvent::ClientConnected(LocalTransport::REMOTE))
+        );
+        assert_eq!(client.pop_event(), Some(ClientEvent::Connected));
+        assert_eq!(
+            client.pop_event(),
+            Some(ClientEvent::Spawned {
+                entity: player,
+                kind: "Player".to_owned()
+            })
+        );
+        assert_eq!(client.state(player), Some(&[0; 64][..]));
+
+        // Changed state.
+        let mut state = vec![0; 64];
+        state[5] = 1;
+        server.set_state(player, state.clone());
+        tick(
+            &mut server,
+            &mut server_transport,
+            &mut client,
+            &mut client_transport,
+        );
+        assert_eq!(client.pop_event(), Some(ClientEvent::StateChanged(player)));
+        assert_eq!(client.state(player), Some(&state[..]));
+
+        // The state is acknowledged, so unchanged state is not sent anymore.
+        tick(
+            &mut server,
+            &mut server_transport,
+            &mut client,
+            &mut client_transport,
+        );
+        tick(
+            &mut server,
+            &mut server_transport,
+            &mut client,
+            &mut client_transport,
+        );
+        assert_eq!(client.pop_event(), None);
+
+        // Interest management.
+        server.set_interest_policy(InterestPolicy::Distance(10.0));
+        server.set_viewer_position(LocalTransport::REMOTE, Vector3::new(100.0, 0.0, 0.0));
+        tick(
+            &mut server,
+            &mut server_transport,
+            &mut client,
+            &mut client_transport,
+        );
+        assert_eq!(client.pop_event(), Some(ClientEvent::Despawned(player)));
+        assert_eq!(client.entities().count(), 0);
+
+        server.set_position(player, Vector3::new(95.0, 0.0, 0.0));
+        tick(
+            &mut server,
+            &mut server_transport,
+            &mut client,
+            &mut client_transport,
+        );
+        assert!(matches!(
+            client.pop_event(),
+            Some(ClientEvent::Spawned { .. })
+        ));
+        assert_eq!(client.state(player), Some(&state[..]));
+
+        // Remote procedure calls.
+        client.send_rpc(Some(player), "Jump", vec![1, 2, 3]);
+        tick(
+            &mut server,
+            &mut server_transport,
+            &mut client,
+            &mut client_transport,
+        );
+        tick(
+            &mut server,
+            &mut server_transport,
+            &mut client,
+            &mut client_transport,
+        );
+        assert_eq!(
+            server.pop_event(),
+            Some(ServerEvent::Rpc {
+                connection: LocalTransport::REMOTE,
+                entity: Some(player),
+                name: "Jump".to_owned(),
+                payload: vec![1, 2, 3]
+            })
+        );
+
+        server.send_rpc(None, None, "GameOver", Vec::new());
+        assert!(server.despawn(player));
+        tick(
+            &mut server,
+            &mut server_transport,
+            &mut client,
+            &mut client_transport,
+        );
+        assert_eq!(client.pop_event(), Some(ClientEvent::Despawned(player)));
+        assert!(
+            matches!(client.pop_event(), Some(ClientEvent::Rpc { name, .. }) if name == "GameOver")
+        );
+
+        drop(server_transport);
+        client.update(&mut client_transport);
+        assert_eq!(client.pop_event(), Some(ClientEvent::Disconnected));
+        assert!(!client.is_connected());
+    }
+}
@@ -0,0 +1,157 @@
+//! Replication of scene nodes. State of a node consists of its local transform and, optionally,
+//! of the state of its script.
+
+use crate::{
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector3},
+        byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt},
+        log::Log,
+        pool::Handle,
+        visitor::Visitor,
+    },
+    network::{
+        replication::{NetworkId, ReplicationClient, ReplicationServer},
+        NetworkError,
+    },
+    scene::{graph::Graph, node::Node},
+};
+use std::{io::Cursor, ops::DerefMut};
+
+fn write_node_state(node: &mut Node, replicate_script: bool, out: &mut Vec<u8>) {
+    let transform = node.local_transform();
+    let position = **transform.position();
+    let rotation = transform.rotation().coords;
+    let scale = **transform.scale();
+    // Writing to a vector never fails.
+    for value in position.iter().chain(rotation.iter()).chain(scale.iter()) {
+        out.write_f32::<LittleEndian>(*value).unwrap();
+    }
+
+    if replicate_script {
+        if let Some(script) = node.script_mut() {
+            let mut visitor = Visitor::new();
+            // Visit the instance only, initialization flags of the script are local.
+            match script
+                .deref_mut()
+                .visit("Data", &mut visitor)
+                .and_then(|_| visitor.save_binary_to_vec())
+            {
+                Ok(data) => out.extend(data),
+                Err(err) => Log::err(format!("Unable to serialize a script: {:?}", err)),
+            }
+        }
+    }
+}
+
+fn read_node_state(node: &mut Node, state: &[u8]) -> Result<(), NetworkError> {
+    let mut cursor = Cursor::new(state);
+    let mut values = [0.0f32; 10];
+    for value in values.iter_mut() {
+        *value = cursor.read_f32::<LittleEndian>()?;
+    }
+
+    node.local_transform_mut()
+        .set_position(Vector3::new(values[0], values[1], values[2]))
+        .set_rotation(UnitQuaternion::new_unchecked(Quaternion::new(
+            values[6], values[3], values[4], values[5],
+        )))
+        .set_scale(Vector3::new(values[7], values[8], values[9]));
+
+    let script_data = &state[cursor.position() as usize..];
+    if !script_data.is_empty() {
+        if let Some(script) = node.script_mut() {
+            let mut visitor = Visitor::load_from_memory(script_data.to_vec())
+                .map_err(|err| NetworkError::InvalidMessage(format!("{:?}", err)))?;
+            script
+                .deref_mut()
+                .visit("Data", &mut visitor)
+                .map_err(|err| NetworkError::InvalidMessage(format!("{:?}", err)))?;
+        }
+    }
+
+    Ok(())
+}
+
+impl ReplicationServer {
+    /// Starts replication of a scene node. State of the node (local transform and, if
+    /// `replicate_script` is `true`, the state of its script) is captured by
+    /// [`Self::capture_graph`]. Clients instantiate replicated nodes using
+    /// [`ReplicationClient::apply_to_graph`].
+    pub fn replicate_node(
+        &mut self,
+        node: Handle<Node>,
+        kind: impl Into<String>,
+        replicate_script: bool,
+    ) -> NetworkId {
+        let id = self.spawn(kind, Vec::new());
+        let entity = self.entities.get_mut(&id).unwrap();
+        entity.node = node;
+        entity.replicate_script = replicate_script;
+        id
+    }
+
+    /// Returns the id of the entity, that replicates the given node, if any.
+    pub fn node_entity(&self, node: Handle<Node>) -> Option<NetworkId> {
+        self.entities
+            .iter()
+            .find(|(_, entity)| entity.node == node)
+            .map(|(id, _)| *id)
+    }
+
+    /// Captures states and positions of replicated nodes. Entities of deleted nodes are
+    /// despawned. Must be called before [`Self::update`].
+    pub fn capture_graph(&mut self, graph: &mut Graph) {
+        self.entities.retain(|_, entity| {
+            if entity.node.is_none() {
+                return true;
+            }
+
+            match graph.try_get_mut(entity.node) {
+                Some(node) => {
+                    entity.position = node.global_position();
+                    entity.state.clear();
+                    write_node_state(node, entity.replicate_script, &mut entity.state);
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+}
+
+impl ReplicationClient {
+    /// Instantiates nodes of new entities using the given spawner (it should create a node by the
+    /// kind of an entity, usually by instantiating a prefab), applies the latest states to the
+    /// nodes and deletes the nodes of despawned entities. Only entities, that were replicated
+    /// using [`ReplicationServer::replicate_node`], should be applied to a graph.
+    pub fn apply_to_graph<F>(&mut self, graph: &mut Graph, mut spawner: F)
+    where
+        F: FnMut(&mut Graph, NetworkId, &str) -> Handle<Node>,
+    {
+        for node in self.despawned_nodes.drain(..) {
+            if graph.is_valid_handle(node) {
+                graph.remove_node(node);
+            }
+        }
+
+        for (id, entity) in self.entities.iter_mut() {
+            if entity.node.is_none() {
+                entity.node = spawner(graph, *id, &entity.kind);
+                entity.changed = true;
+            }
+
+            if !entity.changed {
+                continue;
+            }
+            entity.changed = false;
+
+            if let (Some(node), Some((_, state))) =
+                (graph.try_get_mut(entity.node), entity.history.back())
+            {
+                if let Err(err) = read_node_state(node, state) {
+                    Log::warn(format!("Unable to apply state of {:?}: {}", id, err));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Wire format of replication messages.
+
+use crate::{
+    core::byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt},
+    network::{replication::NetworkId, NetworkError},
+};
+use std::io::{Cursor, Read};
+
+const SPAWN: u8 = 0;
+const DESPAWN: u8 = 1;
+const STATE: u8 = 2;
+const ACK: u8 = 3;
+const RPC: u8 = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Spawn {
+        entity: NetworkId,
+        kind: String,
+        sequence: u32,
+        state: Vec<u8>,
+    },
+    Despawn {
+        entity: NetworkId,
+    },
+    State {
+        entity: NetworkId,
+        sequence: u32,
+        // Sequence of a state, that was used as a baseline for the delta.
+        baseline: u32,
+        delta: Vec<u8>,
+    },
+    Ack {
+        entity: NetworkId,
+        sequence: u32,
+    },
+    Rpc {
+        entity: Option<NetworkId>,
+        name: String,
+        payload: Vec<u8>,
+    },
+}
+
+fn write_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    out.write_u32::<LittleEndian>(bytes.len() as u32).unwrap();
+    out.extend_from_slice(bytes);
+}
+
+fn read_bytes(cursor: &mut Cursor<&[u8]>) -> Result<Vec<u8>, NetworkError> {
+    let length = cursor.read_u32::<LittleEndian>()? as usize;
+    let remaining = cursor.get_ref().len() - cursor.position() as usize;
+    if length > remaining {
+        return Err(NetworkError::InvalidMessage(
+            "Length of a field exceeds length of the message".to_owned(),
+        ));
+    }
+    let mut bytes = vec![0; length];
+    cursor.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_string(cursor: &mut Cursor<&[u8]>) -> Result<String, NetworkError> {
+    String::from_utf8(read_bytes(cursor)?)
+        .map_err(|_| NetworkError::InvalidMessage("Invalid string".to_owned()))
+}
+
+impl Message {
+    pub fn write(&self, out: &mut Vec<u8>) {
+        // Writing to a vector never fails.
+        match self {
+            Message::Spawn {
+                entity,
+                kind,
+                sequence,
+                state,
+            } => {
+                out.push(SPAWN);
+                out.write_u64::<LittleEndian>(entity.0).unwrap();
+                write_bytes(kind.as_bytes(), out);
+                out.write_u32::<LittleEndian>(*sequence).unwrap();
+                write_bytes(state, out);
+            }
+            Message::Despawn { entity } => {
+                out.push(DESPAWN);
+                out.write_u64::<LittleEndian>(entity.0).unwrap();
+            }
+            Message::State {
+                entity,
+                sequence,
+                baseline,
+                delta,
+            } => {
+                out.push(STATE);
+                out.write_u64::<LittleEndian>(entity.0).unwrap();
+                out.write_u32::<LittleEndian>(*sequence).unwrap();
+                out.write_u32::<LittleEndian>(*baseline).unwrap();
+                write_bytes(delta, out);
+            }
+            Message::Ack { entity, sequence } => {
+                out.push(ACK);
+                out.write_u64::<LittleEndian>(entity.0).unwrap();
+                out.write_u32::<LittleEndian>(*sequence).unwrap();
+            }
+            Message::Rpc {
+                entity,
+                name,
+                payload,
+            } => {
+                out.push(RPC);
+                match entity {
+                    Some(entity) => {
+                        out.push(1);
+                        out.write_u64::<LittleEndian>(entity.0).unwrap();
+                    }
+                    None => out.push(0),
+                }
+                write_bytes(name.as_bytes(), out);
+                write_bytes(payload, out);
+            }
+        }
+    }
+
+    pub fn read(data: &[u8]) -> Result<Self, NetworkError> {
+        let mut cursor = Cursor::new(data);
+        let id = cursor.read_u8()?;
+        Ok(match id {
+            SPAWN => Message::Spawn {
+                entity: NetworkId(cursor.read_u64::<LittleEndian>()?),
+                kind: read_string(&mut cursor)?,
+                sequence: cursor.read_u32::<LittleEndian>()?,
+                state: read_bytes(&mut cursor)?,
+            },
+            DESPAWN => Message::Despawn {
+                entity: NetworkId(cursor.read_u64::<LittleEndian>()?),
+            },
+            STATE => Message::State {
+                entity: NetworkId(cursor.read_u64::<LittleEndian>()?),
+                sequence: cursor.read_u32::<LittleEndian>()?,
+                baseline: cursor.read_u32::<LittleEndian>()?,
+                delta: read_bytes(&mut cursor)?,
+            },
+            ACK => Message::Ack {
+                entity: NetworkId(cursor.read_u64::<LittleEndian>()?),
+                sequence: cursor.read_u32::<LittleEndian>()?,
+            },
+            RPC => Message::Rpc {
+                entity: match cursor.read_u8()? {
+                    0 => None,
+                    _ => Some(NetworkId(cursor.read_u64::<LittleEndian>()?)),
+                },
+                name: read_string(&mut cursor)?,
+                payload: read_bytes(&mut cursor)?,
+            },
+            _ => {
+                return Err(NetworkError::InvalidMessage(format!(
+                    "Unknown message id {}",
+                    id
+                )))
+            }
+        })
+    }
+}
@@ -0,0 +1,169 @@
+//! Transport layer moves raw messages between peers. See [`Transport`] docs for more info.
+//!
+//! There are few built-in transports:
+//!
+//! - [`LocalTransport`] - in-memory transport for peers in the same process.
+//! - [`udp::UdpTransport`] - connection-oriented transport over UDP with reliable and unreliable
+//!   channels. It is the main transport for native clients and servers.
+//! - [`websocket`] transports - for WASM clients, that cannot use UDP sockets. Browsers connect to
+//!   a native WebSocket server (which could run alongside the UDP one).
+
+pub mod websocket;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod udp;
+
+use crate::network::NetworkError;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+/// Unique (in the scope of a transport) identifier of a connection.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConnectionId(pub u64);
+
+/// Defines delivery guarantees of a message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DeliveryMode {
+    /// The message could be lost, duplicated or arrive out of order. It is the cheapest mode, that
+    /// should be used for frequently updated data, where only the most recent value matters.
+    Unreliable,
+    /// The message could be lost, but it is never duplicated and older messages that arrive after
+    /// newer ones are dropped.
+    UnreliableSequenced,
+    /// The message is guaranteed to be delivered once, but possibly out of order.
+    ReliableUnordered,
+    /// The message is guaranteed to be delivered once, in the order of sending (relative to other
+    /// reliable messages).
+    ReliableOrdered,
+}
+
+impl DeliveryMode {
+    /// Returns `true` if the mode guarantees delivery.
+    pub fn is_reliable(self) -> bool {
+        matches!(
+            self,
+            DeliveryMode::ReliableUnordered | DeliveryMode::ReliableOrdered
+        )
+    }
+}
+
+/// An event of a transport.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransportEvent {
+    /// A new connection was established.
+    Connected(ConnectionId),
+    /// A connection was closed (either by the remote side, or because of a timeout).
+    Disconnected(ConnectionId),
+    /// A message was received.
+    Message {
+        /// The connection, that sent the message.
+        connection: ConnectionId,
+        /// Content of the message.
+        data: Vec<u8>,
+    },
+}
+
+/// Transport is a pluggable low-level layer, that moves raw messages between peers. A server-side
+/// transport usually has many connections, while a client-side transport has only one connection -
+/// to the server. Transports are non-blocking, incoming messages are queued internally and should
+/// be polled regularly (usually every frame) using [`Self::poll_event`]. Transports are not required
+/// to be [`Send`], because browser-based transports are bound to the main thread.
+pub trait Transport {
+    /// Sends a message to the given connection.
+    fn send(
+        &mut self,
+        connection: ConnectionId,
+        mode: DeliveryMode,
+        data: &[u8],
+    ) -> Result<(), NetworkError>;
+
+    /// Returns the next event of the transport, if any.
+    fn poll_event(&mut self) -> Option<TransportEvent>;
+
+    /// Closes the given connection.
+    fn disconnect(&mut self, connection: ConnectionId);
+}
+
+/// In-memory transport, that connects two peers in the same process. It could be used for
+/// listen servers (when a server and a client run in the same process), or for testing. Both
+/// sides see each other as [`LocalTransport::REMOTE`] connection.
+pub struct LocalTransport {
+    sender: Option<Sender<Vec<u8>>>,
+    receiver: Receiver<Vec<u8>>,
+    connected: bool,
+    closed: bool,
+}
+
+impl LocalTransport {
+    /// Identifier of the remote side of a local transport.
+    pub const REMOTE: ConnectionId = ConnectionId(0);
+
+    /// Creates a pair of connected transports.
+    pub fn pair() -> (Self, Self) {
+        let (a_sender, a_receiver) = mpsc::channel();
+        let (b_sender, b_receiver) = mpsc::channel();
+        (
+            Self {
+                sender: Some(a_sender),
+                receiver: b_receiver,
+                connected: false,
+                closed: false,
+            },
+            Self {
+                sender: Some(b_sender),
+                receiver: a_receiver,
+                connected: false,
+                closed: false,
+            },
+        )
+    }
+}
+
+impl Transport for LocalTransport {
+    fn send(
+        &mut self,
+        connection: ConnectionId,
+        _mode: DeliveryMode,
+        data: &[u8],
+    ) -> Result<(), NetworkError> {
+        if connection != Self::REMOTE {
+            return Err(NetworkError::UnknownConnection(connection));
+        }
+
+        match self.sender.as_ref() {
+            Some(sender) => sender
+                .send(data.to_vec())
+                .map_err(|_| NetworkError::Disconnected),
+            None => Err(NetworkError::Disconnected),
+        }
+    }
+
+    fn poll_event(&mut self) -> Option<TransportEvent> {
+        if self.closed {
+            return None;
+        }
+
+        if !self.connected {
+            self.connected = true;
+            return Some(TransportEvent::Connected(Self::REMOTE));
+        }
+
+        match self.receiver.try_recv() {
+            Ok(data) => Some(TransportEvent::Message {
+                connection: Self::REMOTE,
+                data,
+            }),
+            Err(TryRecvError::Empty) if self.sender.is_some() => None,
+            Err(_) => {
+                self.closed = true;
+                self.sender = None;
+                Some(TransportEvent::Disconnected(Self::REMOTE))
+            }
+        }
+    }
+
+    fn disconnect(&mut self, connection: ConnectionId) {
+        if connection == Self::REMOTE {
+            self.sender = None;
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! Channels implement delivery guarantees on top of unreliable packets. Every delivery mode has
+//! its own channel, so, for example, a lost reliable message never delays unreliable ones.
+//!
+//! Large messages are split into fragments, every fragment takes its own message id, so the id of
+//! the first fragment (the start of a group) is `id - fragment_index`.
+
+use crate::{
+    core::instant::Instant,
+    network::{
+        transport::{udp::packet::WireMessage, DeliveryMode},
+        NetworkError,
+    },
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{collections::VecDeque, time::Duration};
+
+/// Maximum amount of fragments of a single message.
+pub const MAX_FRAGMENTS: usize = 1024;
+
+// Maximum distance between the oldest unacknowledged message and a message in flight. Receivers
+// drop messages outside of the window, so it must be larger than a fragmented message.
+const WINDOW: u16 = 4 * MAX_FRAGMENTS as u16;
+
+// Maximum amount of partially received messages in unreliable channels. Fragments of lost
+// messages will never be completed, so the oldest ones are dropped.
+const MAX_PARTIAL_MESSAGES: usize = 16;
+
+fn is_newer(a: u16, b: u16) -> bool {
+    a != b && a.wrapping_sub(b) < 0x8000
+}
+
+struct OutgoingFragment {
+    message: WireMessage,
+    last_sent: Option<Instant>,
+}
+
+struct PartialMessage {
+    fragments: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+pub struct Channel {
+    index: u8,
+    mode: DeliveryMode,
+    next_id: u16,
+    outgoing: VecDeque<OutgoingFragment>,
+    // Id of the oldest message, that is not received yet (reliable channels only).
+    next_expected: u16,
+    // Messages, that are received ahead of `next_expected`.
+    received: FxHashSet<u16>,
+    ordered: FxHashMap<u16, WireMessage>,
+    // Group start of the most recent delivered message (sequenced channel only).
+    newest: Option<u16>,
+    partial: FxHashMap<u16, PartialMessage>,
+    partial_order: VecDeque<u16>,
+}
+
+impl Channel {
+    pub fn new(index: u8, mode: DeliveryMode) -> Self {
+        Self {
+            index,
+            mode,
+            next_id: 0,
+            outgoing: Default::default(),
+            next_expected: 0,
+            received: Default::default(),
+            ordered: Default::default(),
+            newest: None,
+            partial: Default::default(),
+            partial_order: Default::default(),
+        }
+    }
+
+    /// Queues the message, splitting it into fragments of the given size if needed.
+    pub fn send(&mut self, data: &[u8], fragment_size: usize) -> Result<(), NetworkError> {
+        let fragment_count = ((data.len() + fragment_size - 1) / fragment_size).max(1);
+        if fragment_count > MAX_FRAGMENTS {
+            return Err(NetworkError::InvalidMessage(format!(
+                "Message is too big ({} bytes), max size is {} bytes",
+                data.len(),
+                MAX_FRAGMENTS * fragment_size
+            )));
+        }
+
+        for fragment_index in 0..fragment_count {
+            let begin = fragment_index * fragment_size;
+            let end = (begin + fragment_size).min(data.len());
+            self.outgoing.push_back(OutgoingFragment {
+                message: WireMessage {
+                    channel: self.index,
+                    id: self.next_id,
+                    fragment_index: fragment_index as u16,
+                    fragment_count: fragment_count as u16,
+                    data: data[begin..end].to_vec(),
+                },
+                last_sent: None,
+            });
+            self.next_id = self.next_id.wrapping_add(1);
+        }
+
+        Ok(())
+    }
+
+    /// Collects messages, that should be sent now. Unreliable messages are sent once, reliable
+    /// ones are resent until acknowledged.
+    pub fn collect(&mut self, now: Instant, resend_interval: Duration, out: &mut Vec<WireMessage>) {
+        if !self.mode.is_reliable() {
+            out.extend(self.outgoing.drain(..).map(|fragment| fragment.message));
+            return;
+        }
+
+        let oldest = match self.outgoing.front() {
+            Some(fragment) => fragment.message.id,
+            None => return,
+        };
+        for fragment in self.outgoing.iter_mut() {
+            if fragment.message.id.wrapping_sub(oldest) >= WINDOW {
+                break;
+            }
+            let should_send = fragment
+                .last_sent
+                .map_or(true, |last_sent| now - last_sent >= resend_interval);
+            if should_send {
+                fragment.last_sent = Some(now);
+                out.push(fragment.message.clone());
+            }
+        }
+    }
+
+    /// Marks the reliable message as delivered.
+    pub fn acknowledge(&mut self, id: u16) {
+        if let Some(position) = self
+            .outgoing
+            .iter()
+            .position(|fragment| fragment.message.id == id)
+        {
+            self.outgoing.remove(position);
+        }
+    }
+
+    /// Handles a received message and passes complete messages (if any) to the given closure.
+    pub fn receive<F: FnMut(Vec<u8>)>(&mut self, message: WireMessage, mut deliver: F) {
+        if message.fragment_count as usize > MAX_FRAGMENTS {
+            return;
+        }
+
+        match self.mode {
+            DeliveryMode::Unreliable => {
+                if let Some(data) = self.assemble(message) {
+                    deliver(data);
+                }
+            }
+            DeliveryMode::UnreliableSequenced => {
+                let start = message.id.wrapping_sub(message.fragment_index);
+                if self.newest.map_or(false, |newest| !is_newer(start, newest)) {
+                    return;
+                }
+                if let Some(data) = self.assemble(message) {
+                    self.newest = Some(start);
+                    deliver(data);
+                }
+            }
+            DeliveryMode::ReliableUnordered => {
+                if !self.is_in_window(message.id) || !self.received.insert(message.id) {
+                    return;
+                }
+                while self.received.remove(&self.next_expected) {
+                    self.next_expected = self.next_expected.wrapping_add(1);
+                }
+                if let Some(data) = self.assemble(message) {
+                    deliver(data);
+                }
+            }
+            DeliveryMode::ReliableOrdered => {
+                if !self.is_in_window(message.id) || self.ordered.contains_key(&message.id) {
+                    return;
+                }
+                self.ordered.insert(message.id, message);
+                while let Some(message) = self.ordered.remove(&self.next_expected) {
+                    self.next_expected = self.next_expected.wrapping_add(1);
+                    if let Some(data) = self.assemble(message) {
+                        deliver(data);
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_in_window(&self, id: u16) -> bool {
+        id.wrapping_sub(self.next_expected) < WINDOW
+    }
+
+    fn assemble(&mut self, message: WireMessage) -> Option<Vec<u8>> {
+        if message.fragment_count == 1 {
+            return Some(message.data);
+        }
+
+        let start = message.id.wrapping_sub(message.fragment_index);
+        let fragment_count = message.fragment_count as usize;
+        if !self.partial.contains_key(&start) {
+            if !self.mode.is_reliable() && self.partial_order.len() >= MAX_PARTIAL_MESSAGES {
+                if let Some(oldest) = self.partial_order.pop_front() {
+                    self.partial.remove(&oldest);
+                }
+            }
+            self.partial_order.push_back(start);
+            self.partial.insert(
+                start,
+                PartialMessage {
+                    fragments: vec![None; fragment_count],
+                    received: 0,
+                },
+            );
+        }
+
+        let partial = self.partial.get_mut(&start)?;
+        if partial.fragments.len() != fragment_count {
+            return None;
+        }
+        let slot = &mut partial.fragments[message.fragment_index as usize];
+        if slot.is_none() {
+            *slot = Some(message.data);
+            partial.received += 1;
+        }
+        if partial.received != fragment_count {
+            return None;
+        }
+
+        let partial = self.partial.remove(&start)?;
+        self.partial_order.retain(|other| *other != start);
+        Some(partial.fragments.into_iter().flatten().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::instant::Instant,
+        network::transport::{udp::channel::Channel, DeliveryMode},
+    };
+    use std::time::Duration;
+
+    fn transmit(
+        sender: &mut Channel,
+        receiver: &mut Channel,
+        filter: impl Fn(usize) -> bool,
+    ) -> Vec<Vec<u8>> {
+        let mut messages = Vec::new();
+        sender.collect(Instant::now(), Duration::default(), &mut messages);
+        // Deliver in reverse order to check reordering.
+        let mut received = Vec::new();
+        for (i, message) in messages.into_iter().enumerate().rev() {
+            if filter(i) {
+                let id = message.id;
+                receiver.receive(message, |data| received.push(data));
+                sender.acknowledge(id);
+            }
+        }
+        received
+    }
+
+    #[test]
+    fn test_channels() {
+        let big = (0..100).collect::<Vec<u8>>();
+
+        let mut sender = Channel::new(0, DeliveryMode::ReliableOrdered);
+        let mut receiver = Channel::new(0, DeliveryMode::ReliableOrdered);
+        sender.send(&[1], 16).unwrap();
+        sender.send(&big, 16).unwrap();
+        sender.send(&[2], 16).unwrap();
+        // The first message is lost, nothing could be delivered.
+        assert!(transmit(&mut sender, &mut receiver, |i| i != 0).is_empty());
+        assert_eq!(
+            transmit(&mut sender, &mut receiver, |_| true),
+            vec![vec![1], big.clone(), vec![2]]
+        );
+        assert!(transmit(&mut sender, &mut receiver, |_| true).is_empty());
+
+        let mut sender = Channel::new(0, DeliveryMode::ReliableUnordered);
+        let mut receiver = Channel::new(0, DeliveryMode::ReliableUnordered);
+        sender.send(&[1], 16).unwrap();
+        sender.send(&[2], 16).unwrap();
+        assert_eq!(
+            transmit(&mut sender, &mut receiver, |i| i != 0),
+            vec![vec![2]]
+        );
+        assert_eq!(
+            transmit(&mut sender, &mut receiver, |_| true),
+            vec![vec![1]]
+        );
+
+        let mut sender = Channel::new(0, DeliveryMode::UnreliableSequenced);
+        let mut receiver = Channel::new(0, DeliveryMode::UnreliableSequenced);
+        sender.send(&[1], 16).unwrap();
+        sender.send(&[2], 16).unwrap();
+        // The older message arrives after the newer one and must be dropped.
+        assert_eq!(
+            transmit(&mut sender, &mut receiver, |_| true),
+            vec![vec![2]]
+        );
+        sender.send(&big, 16).unwrap();
+        assert!(transmit(&mut sender, &mut receiver, |i| i != 3).is_empty());
+        // Lost fragments are never resent.
+        assert!(transmit(&mut sender, &mut receiver, |_| true).is_empty());
+
+        assert!(sender.send(&vec![0; 1024 * 16 + 1], 16).is_err());
+    }
+}
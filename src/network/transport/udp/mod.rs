@@ -0,0 +1,1082 @@
+//! Connection-oriented transport over UDP. See [`UdpTransport`] docs for more info.
+
+mod channel;
+mod packet;
+
+use crate::{
+    core::{
+        crypto_secretbox::{
+            aead::{AeadInPlace, KeyInit},
+            XSalsa20Poly1305,
+        },
+        instant::Instant,
+        log::Log,
+        rand,
+    },
+    network::{
+        transport::{
+            udp::{
+                channel::Channel,
+                packet::{Packet, WireMessage, MESSAGE_HEADER_SIZE, PAYLOAD_HEADER_SIZE},
+            },
+            ConnectionId, DeliveryMode, Transport, TransportEvent,
+        },
+        NetworkError,
+    },
+};
+use fxhash::FxHashMap;
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+// Handshake packets are resent with this interval until a response arrives.
+const HANDSHAKE_RESEND_INTERVAL: Duration = Duration::from_millis(250);
+
+// Disconnect packets are not acknowledged, so they're sent few times to make sure that at least
+// one of them will arrive.
+const DISCONNECT_PACKET_COUNT: usize = 3;
+
+// Amount of sent packets, that are remembered to match acknowledgements with messages.
+const SENT_PACKET_HISTORY: u32 = 1024;
+
+// Biggest possible size of a UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 65536;
+
+// Size of the authentication tag and of the copy of the acknowledgement fields, that are added to
+// the body of an encrypted payload.
+const CIPHER_OVERHEAD: usize = 16 + 4 + 4;
+
+// Sequence is a part of the nonce of encrypted payloads, a nonce must never be reused with the same
+// key, so an encrypted connection is closed once the sequence reaches this value.
+const LAST_ENCRYPTED_SEQUENCE: u32 = u32::MAX;
+
+const CHANNEL_MODES: [DeliveryMode; 4] = [
+    DeliveryMode::Unreliable,
+    DeliveryMode::UnreliableSequenced,
+    DeliveryMode::ReliableUnordered,
+    DeliveryMode::ReliableOrdered,
+];
+
+fn channel_index(mode: DeliveryMode) -> usize {
+    match mode {
+        DeliveryMode::Unreliable => 0,
+        DeliveryMode::UnreliableSequenced => 1,
+        DeliveryMode::ReliableUnordered => 2,
+        DeliveryMode::ReliableOrdered => 3,
+    }
+}
+
+/// Settings of a [`UdpTransport`]. Both sides must use the same protocol id and key.
+#[derive(Clone, Debug)]
+pub struct UdpTransportConfig {
+    /// Unique id of an application, packets with other ids are ignored. It is a good practice to
+    /// change it when the protocol of the game changes, so old clients can't connect.
+    pub protocol_id: u32,
+    /// Maximum amount of clients of a server.
+    pub max_connections: usize,
+    /// Maximum size of a packet. Messages, that do not fit into a packet, are fragmented. Default
+    /// value (1200 bytes) is safe for almost any network.
+    pub mtu: usize,
+    /// A connection is closed if there were no packets from the remote side for this time.
+    pub timeout: Duration,
+    /// Empty packets are sent with this interval if there is nothing to send, so the remote side
+    /// knows that the connection is alive.
+    pub keep_alive_interval: Duration,
+    /// Unacknowledged reliable messages are resent with this interval.
+    pub resend_interval: Duration,
+    /// Optional shared key to encrypt payloads with (XSalsa20-Poly1305). Encryption hides the content
+    /// of messages and authenticates them, forged or modified payloads are dropped. Handshake packets
+    /// are not encrypted, so it is not a replacement of a secure protocol. An encrypted connection is
+    /// closed after `2^32 - 1` sent packets, because the packet sequence is a part of the nonce, which
+    /// must never be reused.
+    pub key: Option<[u8; 32]>,
+}
+
+impl Default for UdpTransportConfig {
+    fn default() -> Self {
+        Self {
+            protocol_id: 0,
+            max_connections: 64,
+            mtu: 1200,
+            timeout: Duration::from_secs(10),
+            keep_alive_interval: Duration::from_secs(1),
+            resend_interval: Duration::from_millis(200),
+            key: None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ConnectionState {
+    // Client side, waiting for a challenge.
+    Requesting { client_salt: u64 },
+    // Client side, waiting for acceptance.
+    Responding,
+    // Server side, waiting for a response to the challenge.
+    Challenged { client_salt: u64, server_salt: u64 },
+    Connected,
+}
+
+struct Connection {
+    address: SocketAddr,
+    state: ConnectionState,
+    session: u64,
+    last_received: Instant,
+    last_sent: Option<Instant>,
+    // Sequence of the next sent packet.
+    local_sequence: u32,
+    // The most recent sequence received from the remote side, and bits of previous sequences.
+    remote_sequence: u32,
+    remote_bits: u32,
+    ack_pending: bool,
+    // Reliable messages (channel, id) of sent packets.
+    sent_packets: FxHashMap<u32, Vec<(u8, u16)>>,
+    channels: Vec<Channel>,
+}
+
+impl Connection {
+    fn new(address: SocketAddr, state: ConnectionState, now: Instant) -> Self {
+        Self {
+            address,
+            state,
+            session: 0,
+            last_received: now,
+            last_sent: None,
+            // Zero is never sent, because it is acknowledged by a peer, that has not received
+            // anything yet.
+            local_sequence: 1,
+            remote_sequence: 0,
+            remote_bits: 0,
+            ack_pending: false,
+            sent_packets: Default::default(),
+            channels: CHANNEL_MODES
+                .iter()
+                .enumerate()
+                .map(|(index, mode)| Channel::new(index as u8, *mode))
+                .collect(),
+        }
+    }
+
+    fn should_resend_handshake(&self, now: Instant) -> bool {
+        self.last_sent.map_or(true, |last_sent| {
+            now - last_sent >= HANDSHAKE_RESEND_INTERVAL
+        })
+    }
+
+    // Returns `false` if a packet with the given sequence was already received, or if the packet is
+    // too old to tell that (it is out of the acknowledgement window).
+    fn is_new_sequence(&self, sequence: u32) -> bool {
+        let ahead = sequence.wrapping_sub(self.remote_sequence);
+        if ahead == 0 {
+            false
+        } else if ahead < 0x8000_0000 {
+            true
+        } else {
+            let distance = self.remote_sequence.wrapping_sub(sequence);
+            distance <= 32 && self.remote_bits & (1 << (distance - 1)) == 0
+        }
+    }
+
+    fn receive_sequence(&mut self, sequence: u32) {
+        if sequence.wrapping_sub(self.remote_sequence) < 0x8000_0000 {
+            let shift = sequence.wrapping_sub(self.remote_sequence);
+            self.remote_bits = if shift >= 32 {
+                0
+            } else if shift > 0 {
+                (self.remote_bits << shift) | (1 << (shift - 1))
+            } else {
+                self.remote_bits
+            };
+            self.remote_sequence = sequence;
+        } else {
+            let distance = self.remote_sequence.wrapping_sub(sequence);
+            if distance <= 32 {
+                self.remote_bits |= 1 << (distance - 1);
+            }
+        }
+    }
+
+    fn acknowledge(&mut self, ack: u32, ack_bits: u32) {
+        for i in 0..=32 {
+            if i > 0 && ack_bits & (1 << (i - 1)) == 0 {
+                continue;
+            }
+            if let Some(messages) = self.sent_packets.remove(&ack.wrapping_sub(i)) {
+                for (channel, id) in messages {
+                    self.channels[channel as usize].acknowledge(id);
+                }
+            }
+        }
+    }
+
+    fn flush(
+        &mut self,
+        socket: &UdpSocket,
+        config: &UdpTransportConfig,
+        is_server: bool,
+        now: Instant,
+    ) {
+        let mut messages = Vec::new();
+        for channel in self.channels.iter_mut() {
+            channel.collect(now, config.resend_interval, &mut messages);
+        }
+
+        let max_body_size = config.mtu.saturating_sub(payload_overhead(config));
+        let mut body = Vec::new();
+        let mut reliable = Vec::new();
+        let mut sent_any = false;
+        for message in messages {
+            if !body.is_empty() && body.len() + message.size() > max_body_size {
+                let body = std::mem::take(&mut body);
+                let reliable = std::mem::take(&mut reliable);
+                self.send_payload(socket, config, is_server, body, reliable, now);
+                sent_any = true;
+            }
+            if CHANNEL_MODES[message.channel as usize].is_reliable() {
+                reliable.push((message.channel, message.id));
+            }
+            message.write(&mut body);
+        }
+
+        let should_keep_alive = self.last_sent.map_or(true, |last_sent| {
+            now - last_sent >= config.keep_alive_interval
+        });
+        if !body.is_empty() || (!sent_any && (self.ack_pending || should_keep_alive)) {
+            self.send_payload(socket, config, is_server, body, reliable, now);
+        }
+    }
+
+    fn send_payload(
+        &mut self,
+        socket: &UdpSocket,
+        config: &UdpTransportConfig,
+        is_server: bool,
+        mut body: Vec<u8>,
+        reliable: Vec<(u8, u16)>,
+        now: Instant,
+    ) {
+        let sequence = self.local_sequence;
+
+        if let Some(key) = config.key.as_ref() {
+            // The packet is dropped, the connection will be closed on the next update.
+            if sequence == LAST_ENCRYPTED_SEQUENCE {
+                return;
+            }
+            let nonce = make_nonce(self.session, sequence, is_server);
+            seal(
+                key,
+                &nonce,
+                self.remote_sequence,
+                self.remote_bits,
+                &mut body,
+            );
+        }
+        self.local_sequence = self.local_sequence.wrapping_add(1).max(1);
+
+        if !reliable.is_empty() {
+            self.sent_packets.insert(sequence, reliable);
+        }
+        self.sent_packets
+            .remove(&sequence.wrapping_sub(SENT_PACKET_HISTORY));
+        self.ack_pending = false;
+        self.last_sent = Some(now);
+
+        let packet = Packet::Payload {
+            session: self.session,
+            sequence,
+            ack: self.remote_sequence,
+            ack_bits: self.remote_bits,
+            body,
+        };
+        send_packet(socket, config.protocol_id, self.address, &packet);
+    }
+}
+
+// Size of the packet header and of the data, that is added to the body by the encryption.
+fn payload_overhead(config: &UdpTransportConfig) -> usize {
+    if config.key.is_some() {
+        PAYLOAD_HEADER_SIZE + CIPHER_OVERHEAD
+    } else {
+        PAYLOAD_HEADER_SIZE
+    }
+}
+
+fn make_nonce(session: u64, sequence: u32, from_server: bool) -> [u8; 24] {
+    // Both directions use the same session, so the direction is a part of the nonce too.
+    let mut nonce = [0u8; 24];
+    nonce[..8].copy_from_slice(&session.to_le_bytes());
+    nonce[8..12].copy_from_slice(&sequence.to_le_bytes());
+    nonce[12] = from_server as u8;
+    nonce
+}
+
+// Encrypts the body of a payload. Session and sequence are authenticated as parts of the nonce, the
+// acknowledgement fields are not encrypted in the header, so their copy is encrypted together with the
+// body.
+fn seal(key: &[u8; 32], nonce: &[u8; 24], ack: u32, ack_bits: u32, body: &mut Vec<u8>) {
+    body.extend_from_slice(&ack.to_le_bytes());
+    body.extend_from_slice(&ack_bits.to_le_bytes());
+    if XSalsa20Poly1305::new(key.into())
+        .encrypt_in_place(nonce.into(), &[], body)
+        .is_err()
+    {
+        // Encryption fails only if there's no memory, so the packet is dropped.
+        body.clear();
+    }
+}
+
+// Decrypts the body of a payload, returns `false` if the payload was forged or modified.
+fn open(key: &[u8; 32], nonce: &[u8; 24], ack: u32, ack_bits: u32, body: &mut Vec<u8>) -> bool {
+    if XSalsa20Poly1305::new(key.into())
+        .decrypt_in_place(nonce.into(), &[], body)
+        .is_err()
+    {
+        return false;
+    }
+    let acks_start = match body.len().checked_sub(8) {
+        Some(acks_start) => acks_start,
+        None => return false,
+    };
+    let acks = body.split_off(acks_start);
+    acks[..4] == ack.to_le_bytes() && acks[4..] == ack_bits.to_le_bytes()
+}
+
+/// Connection-oriented transport over UDP, that supports all delivery modes. It could be used
+/// standalone or beneath the [replication](crate::network::replication) framework.
+///
+/// ## Features
+///
+/// - Connection handshake - a client sends a request, the server responds with a challenge
+///   containing a random salt, the client must echo both salts back. It prevents connections with
+///   spoofed addresses.
+/// - Keep-alive and timeouts - see [`UdpTransportConfig`].
+/// - Channels - every [`DeliveryMode`] has its own channel. Reliable messages are resent until
+///   acknowledged, acknowledgements are piggybacked on outgoing packets.
+/// - Fragmentation - messages bigger than the MTU are split into fragments and reassembled on the
+///   receiving side.
+/// - Optional authenticated encryption of payloads with a pre-shared key.
+///
+/// There is no congestion control, so the transport is not suitable for bulk transfers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use fyrox::network::transport::{
+///     udp::{UdpTransport, UdpTransportConfig},
+///     DeliveryMode, Transport, TransportEvent,
+/// };
+///
+/// let mut client = UdpTransport::connect("127.0.0.1:7777", UdpTransportConfig::default()).unwrap();
+///
+/// // Game loop.
+/// loop {
+///     while let Some(event) = client.poll_event() {
+///         if let TransportEvent::Connected(server) = event {
+///             client
+///                 .send(server, DeliveryMode::ReliableOrdered, b"Hello")
+///                 .unwrap();
+///         }
+///     }
+/// }
+/// ```
+pub struct UdpTransport {
+    socket: UdpSocket,
+    config: UdpTransportConfig,
+    is_server: bool,
+    connections: FxHashMap<ConnectionId, Connection>,
+    addresses: FxHashMap<SocketAddr, ConnectionId>,
+    next_connection_id: u64,
+    events: VecDeque<TransportEvent>,
+    buffer: Vec<u8>,
+}
+
+impl UdpTransport {
+    /// Identifier of the server connection on a client side.
+    pub const SERVER: ConnectionId = ConnectionId(0);
+
+    fn new(socket: UdpSocket, config: UdpTransportConfig, is_server: bool) -> Self {
+        Self {
+            socket,
+            config,
+            is_server,
+            connections: Default::default(),
+            addresses: Default::default(),
+            next_connection_id: 0,
+            events: Default::default(),
+            buffer: vec![0; MAX_DATAGRAM_SIZE],
+        }
+    }
+
+    /// Creates a server-side transport, that listens for connections at the given address.
+    pub fn bind<A: ToSocketAddrs>(
+        address: A,
+        config: UdpTransportConfig,
+    ) -> Result<Self, NetworkError> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self::new(socket, config, true))
+    }
+
+    /// Creates a client-side transport, that connects to a server at the given address. The
+    /// connection is established in the background, [`TransportEvent::Connected`] with
+    /// [`Self::SERVER`] connection is emitted when it is done. Messages, that are sent before, are
+    /// queued.
+    pub fn connect<A: ToSocketAddrs>(
+        address: A,
+        config: UdpTransportConfig,
+    ) -> Result<Self, NetworkError> {
+        let server_address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            NetworkError::Io(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "No address to connect to",
+            ))
+        })?;
+        let local_address: SocketAddr = if server_address.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(local_address)?;
+        socket.set_nonblocking(true)?;
+
+        let mut transport = Self::new(socket, config, false);
+        let state = ConnectionState::Requesting {
+            client_salt: rand::random(),
+        };
+        transport.add_connection(server_address, state);
+        Ok(transport)
+    }
+
+    /// Returns the local address of the socket.
+    pub fn local_address(&self) -> Result<SocketAddr, NetworkError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Returns the remote address of the given connection.
+    pub fn connection_address(&self, connection: ConnectionId) -> Option<SocketAddr> {
+        self.connections
+            .get(&connection)
+            .map(|connection| connection.address)
+    }
+
+    /// Returns an iterator over established connections.
+    pub fn connections(&self) -> impl Iterator<Item = ConnectionId> + '_ {
+        self.connections
+            .iter()
+            .filter(|(_, connection)| connection.state == ConnectionState::Connected)
+            .map(|(id, _)| *id)
+    }
+
+    /// Receives incoming packets, handles timeouts and sends queued messages. It is called
+    /// automatically by [`Transport::poll_event`], when there are no pending events.
+    pub fn update(&mut self) {
+        let now = Instant::now();
+
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((size, address)) => {
+                    if let Some(packet) =
+                        Packet::read(self.config.protocol_id, &self.buffer[..size])
+                    {
+                        self.handle_packet(address, packet, now);
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                // ICMP "port unreachable" responses are reported as errors on some platforms,
+                // they're handled by timeouts.
+                Err(err) if err.kind() == ErrorKind::ConnectionReset => continue,
+                Err(err) => {
+                    Log::warn(format!("Unable to receive a UDP packet: {:?}", err));
+                    break;
+                }
+            }
+        }
+
+        let socket = &self.socket;
+        let config = &self.config;
+        let mut timed_out = Vec::new();
+        let mut exhausted = Vec::new();
+        for (id, connection) in self.connections.iter_mut() {
+            if now - connection.last_received > self.config.timeout {
+                timed_out.push(*id);
+                continue;
+            }
+
+            if config.key.is_some() && connection.local_sequence == LAST_ENCRYPTED_SEQUENCE {
+                exhausted.push(*id);
+                continue;
+            }
+
+            match connection.state {
+                ConnectionState::Requesting { client_salt } => {
+                    if connection.should_resend_handshake(now) {
+                        connection.last_sent = Some(now);
+                        let packet = Packet::ConnectRequest { client_salt };
+                        send_packet(socket, config.protocol_id, connection.address, &packet);
+                    }
+                }
+                ConnectionState::Responding => {
+                    if connection.should_resend_handshake(now) {
+                        connection.last_sent = Some(now);
+                        let packet = Packet::ChallengeResponse {
+                            session: connection.session,
+                        };
+                        send_packet(socket, config.protocol_id, connection.address, &packet);
+                    }
+                }
+                ConnectionState::Challenged { .. } => (),
+                ConnectionState::Connected => connection.flush(socket, config, self.is_server, now),
+            }
+        }
+
+        for id in timed_out {
+            self.remove_connection(id);
+        }
+
+        for id in exhausted {
+            Log::warn(format!(
+                "Encrypted connection {:?} has run out of packet sequences and will be closed.",
+                id
+            ));
+            let is_known = !self.is_server
+                || self.connections.get(&id).map(|c| c.state) == Some(ConnectionState::Connected);
+            self.disconnect(id);
+            if is_known {
+                self.events.push_back(TransportEvent::Disconnected(id));
+            }
+        }
+    }
+
+    fn add_connection(&mut self, address: SocketAddr, state: ConnectionState) -> ConnectionId {
+        let id = ConnectionId(self.next_connection_id);
+        self.next_connection_id += 1;
+        self.connections
+            .insert(id, Connection::new(address, state, Instant::now()));
+        self.addresses.insert(address, id);
+        id
+    }
+
+    fn remove_connection(&mut self, id: ConnectionId) {
+        if let Some(connection) = self.connections.remove(&id) {
+            self.addresses.remove(&connection.address);
+            // Server-side connections, that did not complete the handshake, are unknown to users.
+            if !self.is_server || connection.state == ConnectionState::Connected {
+                self.events.push_back(TransportEvent::Disconnected(id));
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, address: SocketAddr, packet: Packet, now: Instant) {
+        let id = self.addresses.get(&address).cloned();
+
+        if self.is_server {
+            if let Packet::ConnectRequest { client_salt } = packet {
+                match id.and_then(|id| self.connections.get(&id)) {
+                    Some(connection) => {
+                        // The challenge was lost, send it again.
+                        if let ConnectionState::Challenged {
+                            client_salt: expected_salt,
+                            server_salt,
+                        } = connection.state
+                        {
+                            if client_salt == expected_salt {
+                                let packet = Packet::Challenge {
+                                    client_salt,
+                                    server_salt,
+                                };
+                                send_packet(
+                                    &self.socket,
+                                    self.config.protocol_id,
+                                    address,
+                                    &packet,
+                                );
+                            }
+                        }
+                    }
+                    None if self.connections.len() >= self.config.max_connections => {
+                        send_packet(
+                            &self.socket,
+                            self.config.protocol_id,
+                            address,
+                            &Packet::Denied { client_salt },
+                        );
+                    }
+                    None => {
+                        let server_salt = rand::random();
+                        self.add_connection(
+                            address,
+                            ConnectionState::Challenged {
+                                client_salt,
+                                server_salt,
+                            },
+                        );
+                        let packet = Packet::Challenge {
+                            client_salt,
+                            server_salt,
+                        };
+                        send_packet(&self.socket, self.config.protocol_id, address, &packet);
+                    }
+                }
+                return;
+            }
+        }
+
+        let id = match id {
+            Some(id) => id,
+            None => return,
+        };
+        let connection = match self.connections.get_mut(&id) {
+            Some(connection) => connection,
+            None => return,
+        };
+
+        match packet {
+            Packet::Challenge {
+                client_salt,
+                server_salt,
+            } => {
+                if connection.state == (ConnectionState::Requesting { client_salt }) {
+                    connection.state = ConnectionState::Responding;
+                    connection.session = client_salt ^ server_salt;
+                    connection.last_received = now;
+                    connection.last_sent = None;
+                }
+            }
+            Packet::ChallengeResponse { session } => match connection.state {
+                ConnectionState::Challenged {
+                    client_salt,
+                    server_salt,
+                } if session == client_salt ^ server_salt => {
+                    connection.state = ConnectionState::Connected;
+                    connection.session = session;
+                    connection.last_received = now;
+                    self.events.push_back(TransportEvent::Connected(id));
+                    send_packet(
+                        &self.socket,
+                        self.config.protocol_id,
+                        address,
+                        &Packet::Accepted { session },
+                    );
+                }
+                // Acceptance was lost, send it again.
+                ConnectionState::Connected if session == connection.session => {
+                    send_packet(
+                        &self.socket,
+                        self.config.protocol_id,
+                        address,
+                        &Packet::Accepted { session },
+                    );
+                }
+                _ => (),
+            },
+            Packet::Accepted { session } => {
+                if connection.state == ConnectionState::Responding && session == connection.session
+                {
+                    connection.state = ConnectionState::Connected;
+                    connection.last_received = now;
+                    self.events.push_back(TransportEvent::Connected(id));
+                }
+            }
+            Packet::Denied { client_salt } => {
+                if connection.state == (ConnectionState::Requesting { client_salt }) {
+                    self.remove_connection(id);
+                }
+            }
+            Packet::Disconnect { session } => {
+                if connection.state == ConnectionState::Connected && session == connection.session {
+                    self.remove_connection(id);
+                }
+            }
+            Packet::Payload {
+                session,
+                sequence,
+                ack,
+                ack_bits,
+                mut body,
+            } => {
+                if session != connection.session
+                    || !matches!(
+                        connection.state,
+                        ConnectionState::Connected | ConnectionState::Responding
+                    )
+                {
+                    return;
+                }
+
+                if let Some(key) = self.config.key.as_ref() {
+                    let nonce = make_nonce(session, sequence, !self.is_server);
+                    if !open(key, &nonce, ack, ack_bits, &mut body) {
+                        return;
+                    }
+                }
+                // Replayed (or duplicated by the network) payloads are dropped, so their messages are
+                // not delivered twice.
+                if !connection.is_new_sequence(sequence) {
+                    return;
+                }
+                let messages = match WireMessage::read_all(&body) {
+                    Some(messages) => messages,
+                    None => return,
+                };
+
+                // The server sends payloads only after acceptance, so acceptance was lost.
+                if connection.state == ConnectionState::Responding {
+                    connection.state = ConnectionState::Connected;
+                    self.events.push_back(TransportEvent::Connected(id));
+                }
+
+                connection.last_received = now;
+                connection.receive_sequence(sequence);
+                connection.acknowledge(ack, ack_bits);
+                connection.ack_pending |= !messages.is_empty();
+
+                let events = &mut self.events;
+                for message in messages {
+                    if let Some(channel) = connection.channels.get_mut(message.channel as usize) {
+                        channel.receive(message, |data| {
+                            events.push_back(TransportEvent::Message {
+                                connection: id,
+                                data,
+                            })
+                        });
+                    }
+                }
+            }
+            Packet::ConnectRequest { .. } => (),
+        }
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(
+        &mut self,
+        connection: ConnectionId,
+        mode: DeliveryMode,
+        data: &[u8],
+    ) -> Result<(), NetworkError> {
+        let fragment_size = self
+            .config
+            .mtu
+            .saturating_sub(payload_overhead(&self.config) + MESSAGE_HEADER_SIZE)
+            .max(1);
+        match self.connections.get_mut(&connection) {
+            Some(state) if !matches!(state.state, ConnectionState::Challenged { .. }) => {
+                state.channels[channel_index(mode)].send(data, fragment_size)
+            }
+            _ => Err(NetworkError::UnknownConnection(connection)),
+        }
+    }
+
+    fn poll_event(&mut self) -> Option<TransportEvent> {
+        if self.events.is_empty() {
+            self.update();
+        }
+        self.events.pop_front()
+    }
+
+    fn disconnect(&mut self, connection: ConnectionId) {
+        if let Some(connection) = self.connections.remove(&connection) {
+            self.addresses.remove(&connection.address);
+            if connection.state == ConnectionState::Connected {
+                let packet = Packet::Disconnect {
+                    session: connection.session,
+                };
+                for _ in 0..DISCONNECT_PACKET_COUNT {
+                    send_packet(
+                        &self.socket,
+                        self.config.protocol_id,
+                        connection.address,
+                        &packet,
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Drop for UdpTransport {
+    fn drop(&mut self) {
+        let connections = self.connections.keys().cloned().collect::<Vec<_>>();
+        for connection in connections {
+            self.disconnect(connection);
+        }
+    }
+}
+
+fn send_packet(socket: &UdpSocket, protocol_id: u32, address: SocketAddr, packet: &Packet) {
+    let mut data = Vec::new();
+    packet.write(protocol_id, &mut data);
+    match socket.send_to(&data, address) {
+        Ok(_) => (),
+        // The packet is dropped, it is the same as if it was lost in the network.
+        Err(err) if err.kind() == ErrorKind::WouldBlock => (),
+        Err(err) => Log::warn(format!("Unable to send a UDP packet: {:?}", err)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::network::transport::{
+        udp::{
+            make_nonce, open,
+            packet::{Packet, WireMessage},
+            seal, ConnectionState, UdpTransport, UdpTransportConfig,
+        },
+        ConnectionId, DeliveryMode, Transport, TransportEvent,
+    };
+    use std::{
+        net::UdpSocket,
+        time::{Duration, Instant},
+    };
+
+    fn pump(
+        server: &mut UdpTransport,
+        client: &mut UdpTransport,
+        mut condition: impl FnMut(&TransportEvent, bool) -> bool,
+    ) {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            while let Some(event) = server.poll_event() {
+                if condition(&event, true) {
+                    return;
+                }
+            }
+            while let Some(event) = client.poll_event() {
+                if condition(&event, false) {
+                    return;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Timeout");
+    }
+
+    #[test]
+    fn test_udp_transport() {
+        let config = UdpTransportConfig {
+            protocol_id: 42,
+            key: Some([7; 32]),
+            resend_interval: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let mut server = UdpTransport::bind("127.0.0.1:0", config.clone()).unwrap();
+        let address = server.local_address().unwrap();
+        let mut client = UdpTransport::connect(address, config).unwrap();
+
+        // Queued before the connection is established.
+        let big = (0..10000).map(|i| i as u8).collect::<Vec<_>>();
+        client
+            .send(
+                UdpTransport::SERVER,
+                DeliveryMode::ReliableOrdered,
+                b"Hello",
+            )
+            .unwrap();
+        client
+            .send(UdpTransport::SERVER, DeliveryMode::ReliableOrdered, &big)
+            .unwrap();
+
+        let mut connections = 0;
+        let mut received = Vec::new();
+        pump(&mut server, &mut client, |event, is_server| {
+            match event {
+                TransportEvent::Connected(_) => connections += 1,
+                TransportEvent::Message { connection, data } if is_server => {
+                    assert_eq!(*connection, ConnectionId(0));
+                    received.push(data.clone());
+                }
+                _ => (),
+            }
+            received.len() == 2
+        });
+        assert_eq!(connections, 2);
+        assert_eq!(received, vec![b"Hello".to_vec(), big]);
+        assert_eq!(server.connections().count(), 1);
+
+        server
+            .send(ConnectionId(0), DeliveryMode::Unreliable, b"World")
+            .unwrap();
+        pump(&mut server, &mut client, |event, is_server| {
+            !is_server
+                && *event
+                    == TransportEvent::Message {
+                        connection: UdpTransport::SERVER,
+                        data: b"World".to_vec(),
+                    }
+        });
+
+        assert!(server
+            .send(ConnectionId(1), DeliveryMode::Unreliable, b"")
+            .is_err());
+
+        // The client notifies the server when dropped.
+        drop(client);
+        let start = Instant::now();
+        while server.poll_event() != Some(TransportEvent::Disconnected(ConnectionId(0))) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+        }
+        assert_eq!(server.connections().count(), 0);
+    }
+
+    #[test]
+    fn test_sealed_payload() {
+        let key = [3; 32];
+        let nonce = make_nonce(123, 5, true);
+        let mut sealed = b"payload".to_vec();
+        seal(&key, &nonce, 10, 0b101, &mut sealed);
+        assert_ne!(&sealed[..7], b"payload");
+
+        let mut body = sealed.clone();
+        assert!(open(&key, &nonce, 10, 0b101, &mut body));
+        assert_eq!(body, b"payload");
+
+        // Modified body.
+        let mut body = sealed.clone();
+        body[0] ^= 1;
+        assert!(!open(&key, &nonce, 10, 0b101, &mut body));
+
+        // Modified acknowledgement in the header.
+        let mut body = sealed.clone();
+        assert!(!open(&key, &nonce, 11, 0b101, &mut body));
+
+        // Replayed in the other direction or with another sequence.
+        let mut body = sealed.clone();
+        assert!(!open(
+            &key,
+            &make_nonce(123, 5, false),
+            10,
+            0b101,
+            &mut body
+        ));
+        let mut body = sealed;
+        assert!(!open(&key, &make_nonce(123, 6, true), 10, 0b101, &mut body));
+    }
+
+    #[test]
+    fn test_sequence_exhaustion() {
+        let config = UdpTransportConfig {
+            protocol_id: 42,
+            key: Some([7; 32]),
+            resend_interval: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let mut server = UdpTransport::bind("127.0.0.1:0", config.clone()).unwrap();
+        let address = server.local_address().unwrap();
+        let mut client = UdpTransport::connect(address, config).unwrap();
+
+        let mut connections = 0;
+        pump(&mut server, &mut client, |event, _| {
+            if let TransportEvent::Connected(_) = event {
+                connections += 1;
+            }
+            connections == 2
+        });
+
+        // The nonce must never be reused, so the connection is closed instead of wrapping. The server
+        // is moved forward too, otherwise the last packet would be dropped as too old.
+        server
+            .connections
+            .get_mut(&ConnectionId(0))
+            .unwrap()
+            .remote_sequence = 0x8000_0000;
+        client
+            .connections
+            .get_mut(&UdpTransport::SERVER)
+            .unwrap()
+            .local_sequence = u32::MAX - 1;
+        client
+            .send(UdpTransport::SERVER, DeliveryMode::Unreliable, b"Last")
+            .unwrap();
+
+        let mut received = false;
+        let mut disconnected = 0;
+        pump(&mut server, &mut client, |event, is_server| {
+            match event {
+                TransportEvent::Message { data, .. } if is_server => {
+                    assert_eq!(data, b"Last");
+                    received = true;
+                }
+                TransportEvent::Disconnected(_) => disconnected += 1,
+                _ => (),
+            }
+            disconnected == 2
+        });
+        assert!(received);
+        assert_eq!(client.connections().count(), 0);
+        assert_eq!(server.connections().count(), 0);
+    }
+
+    #[test]
+    fn test_forged_and_replayed_payloads() {
+        let key = [7; 32];
+        let config = UdpTransportConfig {
+            key: Some(key),
+            ..Default::default()
+        };
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        let mut client = UdpTransport::connect(address, config).unwrap();
+        let connection = client.connections.get_mut(&UdpTransport::SERVER).unwrap();
+        connection.state = ConnectionState::Responding;
+        connection.session = 123;
+
+        let payload = |sequence: u32, sealed: bool| {
+            let mut body = Vec::new();
+            WireMessage {
+                channel: 0,
+                id: 0,
+                fragment_index: 0,
+                fragment_count: 1,
+                data: b"Hello".to_vec(),
+            }
+            .write(&mut body);
+            if sealed {
+                seal(&key, &make_nonce(123, sequence, true), 0, 0, &mut body);
+            }
+            Packet::Payload {
+                session: 123,
+                sequence,
+                ack: 0,
+                ack_bits: 0,
+                body,
+            }
+        };
+        let now = Instant::now();
+
+        // A forged payload does not complete the handshake.
+        client.handle_packet(address, payload(1, false), now);
+        assert!(client.events.is_empty());
+        assert_eq!(
+            client.connections[&UdpTransport::SERVER].state,
+            ConnectionState::Responding
+        );
+
+        let message = TransportEvent::Message {
+            connection: UdpTransport::SERVER,
+            data: b"Hello".to_vec(),
+        };
+        client.handle_packet(address, payload(1, true), now);
+        assert_eq!(
+            client.events.drain(..).collect::<Vec<_>>(),
+            vec![
+                TransportEvent::Connected(UdpTransport::SERVER),
+                message.clone()
+            ]
+        );
+
+        // Replayed payloads are dropped, delayed ones are delivered unless they're too old.
+        client.handle_packet(address, payload(1, true), now);
+        client.handle_packet(address, payload(40, true), now);
+        client.handle_packet(address, payload(40, true), now);
+        client.handle_packet(address, payload(30, true), now);
+        client.handle_packet(address, payload(30, true), now);
+        client.handle_packet(address, payload(2, true), now);
+        assert_eq!(
+            client.events.drain(..).collect::<Vec<_>>(),
+            vec![message.clone(), message]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Wire format of UDP packets.
+//!
+//! Every packet starts with a protocol id (packets of other applications are ignored) and a kind.
+//! Payload packets carry a list of messages, every message has the following header:
+//!
+//! ```text
+//! channel: u8, id: u16, fragment index: u16, fragment count: u16, length: u16
+//! ```
+
+use crate::core::byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Cursor, Read};
+
+const CONNECT_REQUEST: u8 = 0;
+const CHALLENGE: u8 = 1;
+const CHALLENGE_RESPONSE: u8 = 2;
+const ACCEPTED: u8 = 3;
+const DENIED: u8 = 4;
+const DISCONNECT: u8 = 5;
+const PAYLOAD: u8 = 6;
+
+/// Size of the header of a payload packet.
+pub const PAYLOAD_HEADER_SIZE: usize = 4 + 1 + 8 + 4 + 4 + 4;
+
+/// Size of the header of a message inside of a payload packet.
+pub const MESSAGE_HEADER_SIZE: usize = 1 + 2 + 2 + 2 + 2;
+
+// Connection requests are padded to the size of a challenge, so the server never responds with
+// more data than it receives.
+const CONNECT_REQUEST_PADDING: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Packet {
+    ConnectRequest {
+        client_salt: u64,
+    },
+    Challenge {
+        client_salt: u64,
+        server_salt: u64,
+    },
+    ChallengeResponse {
+        session: u64,
+    },
+    Accepted {
+        session: u64,
+    },
+    Denied {
+        client_salt: u64,
+    },
+    Disconnect {
+        session: u64,
+    },
+    Payload {
+        session: u64,
+        sequence: u32,
+        // The most recent sequence, received from the remote side.
+        ack: u32,
+        // Bit `n` is set if packet `ack - n - 1` was received.
+        ack_bits: u32,
+        body: Vec<u8>,
+    },
+}
+
+impl Packet {
+    pub fn write(&self, protocol_id: u32, out: &mut Vec<u8>) {
+        // Writing to a vector never fails.
+        out.write_u32::<LittleEndian>(protocol_id).unwrap();
+        match self {
+            Packet::ConnectRequest { client_salt } => {
+                out.push(CONNECT_REQUEST);
+                out.write_u64::<LittleEndian>(*client_salt).unwrap();
+                out.extend_from_slice(&[0; CONNECT_REQUEST_PADDING]);
+            }
+            Packet::Challenge {
+                client_salt,
+                server_salt,
+            } => {
+                out.push(CHALLENGE);
+                out.write_u64::<LittleEndian>(*client_salt).unwrap();
+                out.write_u64::<LittleEndian>(*server_salt).unwrap();
+            }
+            Packet::ChallengeResponse { session } => {
+                out.push(CHALLENGE_RESPONSE);
+                out.write_u64::<LittleEndian>(*session).unwrap();
+            }
+            Packet::Accepted { session } => {
+                out.push(ACCEPTED);
+                out.write_u64::<LittleEndian>(*session).unwrap();
+            }
+            Packet::Denied { client_salt } => {
+                out.push(DENIED);
+                out.write_u64::<LittleEndian>(*client_salt).unwrap();
+            }
+            Packet::Disconnect { session } => {
+                out.push(DISCONNECT);
+                out.write_u64::<LittleEndian>(*session).unwrap();
+            }
+            Packet::Payload {
+                session,
+                sequence,
+                ack,
+                ack_bits,
+                body,
+            } => {
+                out.push(PAYLOAD);
+                out.write_u64::<LittleEndian>(*session).unwrap();
+                out.write_u32::<LittleEndian>(*sequence).unwrap();
+                out.write_u32::<LittleEndian>(*ack).unwrap();
+                out.write_u32::<LittleEndian>(*ack_bits).unwrap();
+                out.extend_from_slice(body);
+            }
+        }
+    }
+
+    /// Reads a packet. Returns `None` if the packet is malformed or belongs to other protocol.
+    pub fn read(protocol_id: u32, data: &[u8]) -> Option<Self> {
+        let mut cursor = Cursor::new(data);
+        if cursor.read_u32::<LittleEndian>().ok()? != protocol_id {
+            return None;
+        }
+        Some(match cursor.read_u8().ok()? {
+            CONNECT_REQUEST => {
+                if data.len() < 13 + CONNECT_REQUEST_PADDING {
+                    return None;
+                }
+                Packet::ConnectRequest {
+                    client_salt: cursor.read_u64::<LittleEndian>().ok()?,
+                }
+            }
+            CHALLENGE => Packet::Challenge {
+                client_salt: cursor.read_u64::<LittleEndian>().ok()?,
+                server_salt: cursor.read_u64::<LittleEndian>().ok()?,
+            },
+            CHALLENGE_RESPONSE => Packet::ChallengeResponse {
+                session: cursor.read_u64::<LittleEndian>().ok()?,
+            },
+            ACCEPTED => Packet::Accepted {
+                session: cursor.read_u64::<LittleEndian>().ok()?,
+            },
+            DENIED => Packet::Denied {
+                client_salt: cursor.read_u64::<LittleEndian>().ok()?,
+            },
+            DISCONNECT => Packet::Disconnect {
+                session: cursor.read_u64::<LittleEndian>().ok()?,
+            },
+            PAYLOAD => Packet::Payload {
+                session: cursor.read_u64::<LittleEndian>().ok()?,
+                sequence: cursor.read_u32::<LittleEndian>().ok()?,
+                ack: cursor.read_u32::<LittleEndian>().ok()?,
+                ack_bits: cursor.read_u32::<LittleEndian>().ok()?,
+                body: data[PAYLOAD_HEADER_SIZE..].to_vec(),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// A message (or a fragment of a message) inside of a payload packet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WireMessage {
+    pub channel: u8,
+    pub id: u16,
+    pub fragment_index: u16,
+    pub fragment_count: u16,
+    pub data: Vec<u8>,
+}
+
+impl WireMessage {
+    pub fn size(&self) -> usize {
+        MESSAGE_HEADER_SIZE + self.data.len()
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.push(self.channel);
+        out.write_u16::<LittleEndian>(self.id).unwrap();
+        out.write_u16::<LittleEndian>(self.fragment_index).unwrap();
+        out.write_u16::<LittleEndian>(self.fragment_count).unwrap();
+        out.write_u16::<LittleEndian>(self.data.len() as u16)
+            .unwrap();
+        out.extend_from_slice(&self.data);
+    }
+
+    /// Reads all messages of a packet body. Returns `None` if the body is malformed.
+    pub fn read_all(body: &[u8]) -> Option<Vec<WireMessage>> {
+        let mut cursor = Cursor::new(body);
+        let mut messages = Vec::new();
+        while (cursor.position() as usize) < body.len() {
+            let channel = cursor.read_u8().ok()?;
+            let id = cursor.read_u16::<LittleEndian>().ok()?;
+            let fragment_index = cursor.read_u16::<LittleEndian>().ok()?;
+            let fragment_count = cursor.read_u16::<LittleEndian>().ok()?;
+            let length = cursor.read_u16::<LittleEndian>().ok()? as usize;
+            if fragment_index >= fragment_count {
+                return None;
+            }
+            let mut data = vec![0; length];
+            cursor.read_exact(&mut data).ok()?;
+            messages.push(WireMessage {
+                channel,
+                id,
+                fragment_index,
+                fragment_count,
+                data,
+            });
+        }
+        Some(messages)
+    }
+}
@@ -0,0 +1,649 @@
+//! WebSocket transports. Browsers cannot use UDP sockets (and WebRTC data channels require a
+//! signaling server and a huge native stack), so WebSockets are the way to connect WASM clients to
+//! a game server:
+//!
+//! - [`WebSocketTransport`] - client-side transport for WASM builds, that uses the browser API.
+//! - [`WebSocketServerTransport`] - native server-side transport, it could run alongside a
+//!   [`super::udp::UdpTransport`] to serve both native and browser clients.
+//!
+//! WebSockets work over TCP, so every message is delivered reliably and in order regardless of
+//! the requested [`DeliveryMode`].
+
+// The protocol is implemented by the browser on WASM.
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
+
+use crate::network::{
+    transport::{ConnectionId, DeliveryMode, Transport, TransportEvent},
+    NetworkError,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::WebSocketServerTransport;
+
+#[cfg(target_arch = "wasm32")]
+pub use client::WebSocketTransport;
+
+// Maximum size of a message, it protects from allocation of huge buffers for malformed frames.
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    data: Vec<u8>,
+}
+
+fn encode_frame(opcode: u8, data: &[u8], mask: Option<[u8; 4]>, out: &mut Vec<u8>) {
+    out.push(0x80 | opcode);
+    let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+    if data.len() < 126 {
+        out.push(mask_bit | data.len() as u8);
+    } else if data.len() <= u16::MAX as usize {
+        out.push(mask_bit | 126);
+        out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    } else {
+        out.push(mask_bit | 127);
+        out.extend_from_slice(&(data.len() as u64).to_be_bytes());
+    }
+    match mask {
+        Some(mask) => {
+            out.extend_from_slice(&mask);
+            out.extend(data.iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+        }
+        None => out.extend_from_slice(data),
+    }
+}
+
+/// Decodes a frame from the beginning of the buffer. Returns the frame and its size, or `None` if
+/// the buffer does not contain the whole frame yet.
+fn decode_frame(buffer: &[u8]) -> Result<Option<(Frame, usize)>, NetworkError> {
+    if buffer.len() < 2 {
+        return Ok(None);
+    }
+
+    let mut position = 2;
+    let length = match buffer[1] & 0x7F {
+        126 => match buffer.get(2..4) {
+            Some(bytes) => {
+                position += 2;
+                u16::from_be_bytes([bytes[0], bytes[1]]) as u64
+            }
+            None => return Ok(None),
+        },
+        127 => match buffer.get(2..10) {
+            Some(bytes) => {
+                position += 8;
+                let mut length = [0; 8];
+                length.copy_from_slice(bytes);
+                u64::from_be_bytes(length)
+            }
+            None => return Ok(None),
+        },
+        length => length as u64,
+    };
+    if length > MAX_MESSAGE_SIZE as u64 {
+        return Err(NetworkError::InvalidMessage("Frame is too big".to_owned()));
+    }
+    let length = length as usize;
+
+    let mask = if buffer[1] & 0x80 != 0 {
+        match buffer.get(position..position + 4) {
+            Some(bytes) => {
+                position += 4;
+                [bytes[0], bytes[1], bytes[2], bytes[3]]
+            }
+            None => return Ok(None),
+        }
+    } else {
+        [0; 4]
+    };
+
+    let data = match buffer.get(position..position + length) {
+        Some(data) => data
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ mask[i % 4])
+            .collect(),
+        None => return Ok(None),
+    };
+
+    Ok(Some((
+        Frame {
+            fin: buffer[0] & 0x80 != 0,
+            opcode: buffer[0] & 0x0F,
+            data,
+        },
+        position + length,
+    )))
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (value, delta) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(delta);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+/// Computes the value of `Sec-WebSocket-Accept` header for the given `Sec-WebSocket-Key`.
+fn accept_key(key: &str) -> String {
+    use base64::Engine;
+
+    const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+    base64::engine::general_purpose::STANDARD.encode(sha1(format!("{}{}", key, GUID).as_bytes()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use super::*;
+    use fxhash::FxHashMap;
+    use std::{
+        collections::VecDeque,
+        io::{ErrorKind, Read, Write},
+        net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    };
+
+    // Maximum size of an HTTP upgrade request.
+    const MAX_HANDSHAKE_SIZE: usize = 8192;
+
+    struct Connection {
+        stream: TcpStream,
+        // `true` if the handshake is done.
+        is_open: bool,
+        is_closed: bool,
+        input: Vec<u8>,
+        output: Vec<u8>,
+        // Data of a fragmented message.
+        message: Vec<u8>,
+    }
+
+    impl Connection {
+        fn receive(&mut self, id: ConnectionId, events: &mut VecDeque<TransportEvent>) {
+            let mut buffer = [0; 4096];
+            // Data, that was received before the end of the stream, is still handled.
+            let mut is_finished = false;
+            loop {
+                match self.stream.read(&mut buffer) {
+                    Ok(0) => {
+                        is_finished = true;
+                        break;
+                    }
+                    Ok(count) => self.input.extend_from_slice(&buffer[..count]),
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                    Err(_) => {
+                        is_finished = true;
+                        break;
+                    }
+                }
+            }
+
+            if !self.is_open {
+                self.handshake(id, events);
+            }
+
+            while self.is_open && !self.is_closed {
+                match decode_frame(&self.input) {
+                    Ok(Some((frame, size))) => {
+                        self.input.drain(..size);
+                        self.handle_frame(id, frame, events);
+                    }
+                    Ok(None) => break,
+                    Err(_) => self.is_closed = true,
+                }
+            }
+
+            self.flush();
+            self.is_closed |= is_finished;
+        }
+
+        fn handshake(&mut self, id: ConnectionId, events: &mut VecDeque<TransportEvent>) {
+            let end = match self.input.windows(4).position(|w| w == b"\r\n\r\n") {
+                Some(end) => end + 4,
+                None => {
+                    if self.input.len() > MAX_HANDSHAKE_SIZE {
+                        self.is_closed = true;
+                    }
+                    return;
+                }
+            };
+
+            let request = String::from_utf8_lossy(&self.input[..end]).into_owned();
+            self.input.drain(..end);
+
+            let key = request.lines().skip(1).find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                    Some(value.trim().to_owned())
+                } else {
+                    None
+                }
+            });
+
+            match key {
+                Some(key) => {
+                    let response = format!(
+                        "HTTP/1.1 101 Switching Protocols\r\n\
+                        Upgrade: websocket\r\n\
+                        Connection: Upgrade\r\n\
+                        Sec-WebSocket-Accept: {}\r\n\r\n",
+                        accept_key(&key)
+                    );
+                    self.output.extend_from_slice(response.as_bytes());
+                    self.is_open = true;
+                    events.push_back(TransportEvent::Connected(id));
+                }
+                None => {
+                    self.output
+                        .extend_from_slice(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+                    self.flush();
+                    self.is_closed = true;
+                }
+            }
+        }
+
+        fn handle_frame(
+            &mut self,
+            id: ConnectionId,
+            frame: Frame,
+            events: &mut VecDeque<TransportEvent>,
+        ) {
+            match frame.opcode {
+                OPCODE_CONTINUATION | OPCODE_TEXT | OPCODE_BINARY => {
+                    if self.message.len() + frame.data.len() > MAX_MESSAGE_SIZE {
+                        self.is_closed = true;
+                        return;
+                    }
+                    self.message.extend(frame.data);
+                    if frame.fin {
+                        events.push_back(TransportEvent::Message {
+                            connection: id,
+                            data: std::mem::take(&mut self.message),
+                        });
+                    }
+                }
+                OPCODE_CLOSE => {
+                    encode_frame(OPCODE_CLOSE, &[], None, &mut self.output);
+                    self.flush();
+                    self.is_closed = true;
+                }
+                OPCODE_PING => encode_frame(OPCODE_PONG, &frame.data, None, &mut self.output),
+                _ => (),
+            }
+        }
+
+        fn flush(&mut self) {
+            while !self.output.is_empty() {
+                match self.stream.write(&self.output) {
+                    Ok(0) => {
+                        self.is_closed = true;
+                        return;
+                    }
+                    Ok(count) => {
+                        self.output.drain(..count);
+                    }
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => return,
+                    Err(_) => {
+                        self.is_closed = true;
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Server-side WebSocket transport for browser clients. It accepts connections on a TCP
+    /// socket, performs the HTTP upgrade handshake and exchanges binary messages. It does not
+    /// support TLS, use a reverse proxy if secure connections (`wss://`) are required.
+    pub struct WebSocketServerTransport {
+        listener: TcpListener,
+        connections: FxHashMap<ConnectionId, Connection>,
+        next_connection_id: u64,
+        events: VecDeque<TransportEvent>,
+    }
+
+    impl WebSocketServerTransport {
+        /// Creates a transport, that listens for connections at the given address.
+        pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self, NetworkError> {
+            let listener = TcpListener::bind(address)?;
+            listener.set_nonblocking(true)?;
+            Ok(Self {
+                listener,
+                connections: Default::default(),
+                next_connection_id: 0,
+                events: Default::default(),
+            })
+        }
+
+        /// Returns the local address of the socket.
+        pub fn local_address(&self) -> Result<SocketAddr, NetworkError> {
+            Ok(self.listener.local_addr()?)
+        }
+
+        /// Accepts new connections, receives incoming messages and sends queued ones. It is called
+        /// automatically by [`Transport::poll_event`], when there are no pending events.
+        pub fn update(&mut self) {
+            loop {
+                match self.listener.accept() {
+                    Ok((stream, _)) => {
+                        if stream.set_nonblocking(true).is_err() {
+                            continue;
+                        }
+                        let _ = stream.set_nodelay(true);
+                        let id = ConnectionId(self.next_connection_id);
+                        self.next_connection_id += 1;
+                        self.connections.insert(
+                            id,
+                            Connection {
+                                stream,
+                                is_open: false,
+                                is_closed: false,
+                                input: Default::default(),
+                                output: Default::default(),
+                                message: Default::default(),
+                            },
+                        );
+                    }
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                    // Failed connection attempts do not affect other connections.
+                    Err(_) => break,
+                }
+            }
+
+            for (id, connection) in self.connections.iter_mut() {
+                connection.receive(*id, &mut self.events);
+            }
+
+            let events = &mut self.events;
+            self.connections.retain(|id, connection| {
+                if connection.is_closed && connection.is_open {
+                    events.push_back(TransportEvent::Disconnected(*id));
+                }
+                !connection.is_closed
+            });
+        }
+    }
+
+    impl Transport for WebSocketServerTransport {
+        fn send(
+            &mut self,
+            connection: ConnectionId,
+            _mode: DeliveryMode,
+            data: &[u8],
+        ) -> Result<(), NetworkError> {
+            match self.connections.get_mut(&connection) {
+                Some(state) if state.is_open && !state.is_closed => {
+                    encode_frame(OPCODE_BINARY, data, None, &mut state.output);
+                    state.flush();
+                    Ok(())
+                }
+                _ => Err(NetworkError::UnknownConnection(connection)),
+            }
+        }
+
+        fn poll_event(&mut self) -> Option<TransportEvent> {
+            if self.events.is_empty() {
+                self.update();
+            }
+            self.events.pop_front()
+        }
+
+        fn disconnect(&mut self, connection: ConnectionId) {
+            if let Some(mut connection) = self.connections.remove(&connection) {
+                if connection.is_open {
+                    encode_frame(OPCODE_CLOSE, &[], None, &mut connection.output);
+                    connection.flush();
+                }
+                let _ = connection.stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod client {
+    use super::*;
+    use crate::core::{
+        js_sys::{ArrayBuffer, Uint8Array},
+        wasm_bindgen::{closure::Closure, JsCast, JsValue},
+        web_sys::{BinaryType, MessageEvent, WebSocket},
+    };
+    use std::{cell::RefCell, collections::VecDeque, io::ErrorKind, rc::Rc};
+
+    fn js_error(err: JsValue) -> NetworkError {
+        NetworkError::Io(std::io::Error::new(ErrorKind::Other, format!("{:?}", err)))
+    }
+
+    /// Client-side WebSocket transport for WASM builds. It uses the WebSocket API of the browser,
+    /// the server should use [`super::WebSocketServerTransport`] (or any other WebSocket server,
+    /// that exchanges binary messages).
+    pub struct WebSocketTransport {
+        socket: WebSocket,
+        events: Rc<RefCell<VecDeque<TransportEvent>>>,
+        _on_open: Closure<dyn FnMut(JsValue)>,
+        _on_message: Closure<dyn FnMut(MessageEvent)>,
+        _on_close: Closure<dyn FnMut(JsValue)>,
+    }
+
+    impl WebSocketTransport {
+        /// Identifier of the server connection.
+        pub const SERVER: ConnectionId = ConnectionId(0);
+
+        /// Starts connecting to the given url (for example `ws://127.0.0.1:7778`).
+        /// [`TransportEvent::Connected`] is emitted when the connection is established.
+        pub fn connect(url: &str) -> Result<Self, NetworkError> {
+            let socket = WebSocket::new(url).map_err(js_error)?;
+            socket.set_binary_type(BinaryType::Arraybuffer);
+
+            let events = Rc::new(RefCell::new(VecDeque::new()));
+
+            let on_open = {
+                let events = events.clone();
+                Closure::wrap(Box::new(move |_: JsValue| {
+                    events
+                        .borrow_mut()
+                        .push_back(TransportEvent::Connected(Self::SERVER));
+                }) as Box<dyn FnMut(JsValue)>)
+            };
+            let on_message = {
+                let events = events.clone();
+                Closure::wrap(Box::new(move |event: MessageEvent| {
+                    if let Ok(buffer) = event.data().dyn_into::<ArrayBuffer>() {
+                        events.borrow_mut().push_back(TransportEvent::Message {
+                            connection: Self::SERVER,
+                            data: Uint8Array::new(&buffer).to_vec(),
+                        });
+                    }
+                }) as Box<dyn FnMut(MessageEvent)>)
+            };
+            let on_close = {
+                let events = events.clone();
+                Closure::wrap(Box::new(move |_: JsValue| {
+                    events
+                        .borrow_mut()
+                        .push_back(TransportEvent::Disconnected(Self::SERVER));
+                }) as Box<dyn FnMut(JsValue)>)
+            };
+
+            socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+            socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+            socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+            Ok(Self {
+                socket,
+                events,
+                _on_open: on_open,
+                _on_message: on_message,
+                _on_close: on_close,
+            })
+        }
+    }
+
+    impl Transport for WebSocketTransport {
+        fn send(
+            &mut self,
+            connection: ConnectionId,
+            _mode: DeliveryMode,
+            data: &[u8],
+        ) -> Result<(), NetworkError> {
+            if connection != Self::SERVER {
+                return Err(NetworkError::UnknownConnection(connection));
+            }
+            if self.socket.ready_state() != WebSocket::OPEN {
+                return Err(NetworkError::Disconnected);
+            }
+            self.socket.send_with_u8_array(data).map_err(js_error)
+        }
+
+        fn poll_event(&mut self) -> Option<TransportEvent> {
+            self.events.borrow_mut().pop_front()
+        }
+
+        fn disconnect(&mut self, connection: ConnectionId) {
+            if connection == Self::SERVER {
+                let _ = self.socket.close();
+            }
+        }
+    }
+
+    impl Drop for WebSocketTransport {
+        fn drop(&mut self) {
+            // Closures are dropped with the transport, so they must be detached first.
+            self.socket.set_onopen(None);
+            self.socket.set_onmessage(None);
+            self.socket.set_onclose(None);
+            let _ = self.socket.close();
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use crate::network::transport::{
+        websocket::{accept_key, decode_frame, encode_frame, WebSocketServerTransport},
+        ConnectionId, DeliveryMode, Transport, TransportEvent,
+    };
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn test_websocket_server() {
+        // Example from RFC 6455.
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+
+        let mut server = WebSocketServerTransport::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(server.local_address().unwrap()).unwrap();
+        client
+            .write_all(
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                Sec-WebSocket-Version: 13\r\n\r\n",
+            )
+            .unwrap();
+        let mut frame = Vec::new();
+        let big = (0..1000).map(|i| i as u8).collect::<Vec<_>>();
+        encode_frame(2, &big, Some([1, 2, 3, 4]), &mut frame);
+        client.write_all(&frame).unwrap();
+
+        let start = Instant::now();
+        let mut events = Vec::new();
+        while events.len() < 2 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            events.extend(server.poll_event());
+        }
+        assert_eq!(
+            events,
+            vec![
+                TransportEvent::Connected(ConnectionId(0)),
+                TransportEvent::Message {
+                    connection: ConnectionId(0),
+                    data: big
+                }
+            ]
+        );
+
+        server
+            .send(ConnectionId(0), DeliveryMode::Unreliable, b"Hello")
+            .unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut input = Vec::new();
+        let mut buffer = [0; 256];
+        let frame = loop {
+            let count = client.read(&mut buffer).unwrap();
+            assert_ne!(count, 0);
+            input.extend_from_slice(&buffer[..count]);
+            let end = match input.windows(4).position(|w| w == b"\r\n\r\n") {
+                Some(end) => end + 4,
+                None => continue,
+            };
+            let response = String::from_utf8_lossy(&input[..end]).into_owned();
+            assert!(response.contains("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+            if let Some((frame, _)) = decode_frame(&input[end..]).unwrap() {
+                break frame;
+            }
+        };
+        assert_eq!(frame.data, b"Hello");
+
+        drop(client);
+        while server.poll_event() != Some(TransportEvent::Disconnected(ConnectionId(0))) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+        }
+    }
+}
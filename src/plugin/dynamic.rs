@@ -0,0 +1,329 @@
+//! Dynamic plugins, that are loaded from dynamic libraries and could be reloaded at runtime. See
+//! [`DynamicPlugin`] docs for more info.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{
+        log::Log,
+        pool::Handle,
+        uuid::Uuid,
+        visitor::{Visit, Visitor},
+    },
+    engine::SerializationContext,
+    plugin::PluginConstructor,
+    scene::{graph::Graph, node::Node},
+};
+use libloading::Library;
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+/// Name of a function, that must be exported by a dynamic library of a plugin. The function must
+/// have [`PluginEntryPoint`] signature.
+pub const PLUGIN_ENTRY_POINT: &str = "fyrox_plugin_constructor";
+
+/// Signature of the entry point of a dynamic plugin. See [`DynamicPlugin`] docs for more info.
+pub type PluginEntryPoint = fn() -> Box<dyn PluginConstructor>;
+
+/// All possible errors that may occur during dynamic plugin loading.
+#[derive(Debug)]
+pub enum DynamicPluginError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Unable to load a library or to find an entry point in it.
+    Library(libloading::Error),
+    /// The plugin registers custom scene nodes and can't be reloaded.
+    NotReloadable,
+}
+
+impl Display for DynamicPluginError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DynamicPluginError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            DynamicPluginError::Library(v) => write!(f, "Unable to load plugin library: {v}"),
+            DynamicPluginError::NotReloadable => write!(
+                f,
+                "The plugin registers custom scene nodes and can't be reloaded!"
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for DynamicPluginError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<libloading::Error> for DynamicPluginError {
+    fn from(e: libloading::Error) -> Self {
+        Self::Library(e)
+    }
+}
+
+/// Dynamic plugin is a plugin, that is loaded from a dynamic library and could be reloaded at
+/// runtime when the library is rebuilt. It allows to change game logic without restarting the
+/// game, which significantly speeds up iterations on gameplay. Use
+/// [`crate::engine::Engine::add_dynamic_plugin`] to add a dynamic plugin to the engine.
+///
+/// # Reloading
+///
+/// When the library file changes, the engine serializes (using [`crate::core::visitor::Visit`])
+/// every script instance of the types registered by the plugin, destroys the plugin instance,
+/// unloads the library, loads the new version and restores the scripts from the serialized data.
+/// Script instances won't be re-initialized, but everything that is not serialized by scripts will
+/// be lost. Plugin instance is re-created using [`PluginConstructor::create_instance`], so the
+/// plugin should keep its state in scenes (or in scripts) to survive reloading. Custom scene nodes
+/// can't be reloaded, so plugins that register them are never reloaded.
+///
+/// # Safety
+///
+/// Rust does not have stable ABI, so the plugin library **must** be built with exactly the same
+/// compiler version, the same engine version and the same build profile as the executable that
+/// loads it. Otherwise, the behavior is undefined. Dynamic plugins are intended to be used for
+/// development only, shipped games should link their plugins statically.
+///
+/// # Example
+///
+/// The plugin crate must be compiled as a dynamic library (`crate-type = ["cdylib"]`) and must
+/// export its entry point:
+///
+/// ```rust,no_run
+/// use fyrox::plugin::{Plugin, PluginConstructor, PluginContext};
+/// # use fyrox::{core::pool::Handle, scene::Scene};
+///
+/// # struct Game;
+/// # impl Plugin for Game {}
+/// # struct GameConstructor;
+/// # impl PluginConstructor for GameConstructor {
+/// #     fn create_instance(&self, _: Handle<Scene>, _: PluginContext) -> Box<dyn Plugin> {
+/// #         Box::new(Game)
+/// #     }
+/// # }
+/// #[no_mangle]
+/// pub fn fyrox_plugin_constructor() -> Box<dyn PluginConstructor> {
+///     Box::new(GameConstructor)
+/// }
+/// ```
+pub struct DynamicPlugin {
+    // Constructor must be destroyed before the library, because its code is in the library.
+    pub(crate) constructor: Option<Box<dyn PluginConstructor>>,
+    library: Option<Library>,
+    source_path: PathBuf,
+    loaded_path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    reload_when_changed: bool,
+    generation: usize,
+    // Types of scripts and nodes registered by the plugin.
+    pub(crate) script_types: Vec<Uuid>,
+    pub(crate) has_custom_nodes: bool,
+}
+
+impl DynamicPlugin {
+    /// Loads a plugin from the given dynamic library. If `reload_when_changed` is set, the engine
+    /// will reload the plugin every time when the library file is changed.
+    pub fn load<P: AsRef<Path>>(
+        path: P,
+        reload_when_changed: bool,
+    ) -> Result<Self, DynamicPluginError> {
+        let mut plugin = Self {
+            constructor: None,
+            library: None,
+            source_path: path.as_ref().to_owned(),
+            loaded_path: None,
+            modified: None,
+            reload_when_changed,
+            generation: 0,
+            script_types: Default::default(),
+            has_custom_nodes: false,
+        };
+        plugin.load_library()?;
+        Ok(plugin)
+    }
+
+    /// Returns a path to the library of the plugin.
+    pub fn path(&self) -> &Path {
+        &self.source_path
+    }
+
+    /// Returns `true` if the plugin will be reloaded when its library file is changed.
+    pub fn is_reload_when_changed(&self) -> bool {
+        self.reload_when_changed
+    }
+
+    /// Returns `true` if the plugin is loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.library.is_some()
+    }
+
+    /// Returns `true` if the library file was changed since the last loading.
+    pub fn is_changed(&self) -> bool {
+        modification_time(&self.source_path).map_or(false, |modified| {
+            self.modified.map_or(true, |loaded| modified > loaded)
+        })
+    }
+
+    pub(crate) fn load_library(&mut self) -> Result<(), DynamicPluginError> {
+        self.modified = modification_time(&self.source_path);
+
+        // The library is copied first, otherwise it won't be possible to rebuild it (on Windows) or
+        // the OS may return the cached version of the library (on Linux).
+        let mut file_name = self
+            .source_path
+            .file_stem()
+            .map(|stem| stem.to_os_string())
+            .unwrap_or_default();
+        file_name.push(format!("_hot_reload_{}", self.generation));
+        let mut loaded_path = self.source_path.with_file_name(file_name);
+        if let Some(extension) = self.source_path.extension() {
+            loaded_path.set_extension(extension);
+        }
+        std::fs::copy(&self.source_path, &loaded_path)?;
+        self.generation += 1;
+
+        // SAFETY: The library must be built with the same compiler and the same engine version,
+        // see type docs.
+        let result = unsafe {
+            Library::new(&loaded_path).and_then(|library| {
+                let constructor = library
+                    .get::<PluginEntryPoint>(PLUGIN_ENTRY_POINT.as_bytes())
+                    .map(|entry_point| (entry_point)())?;
+                Ok((library, constructor))
+            })
+        };
+
+        match result {
+            Ok((library, constructor)) => {
+                self.library = Some(library);
+                self.constructor = Some(constructor);
+                self.loaded_path = Some(loaded_path);
+                Ok(())
+            }
+            Err(err) => {
+                let _ = std::fs::remove_file(&loaded_path);
+                Err(err.into())
+            }
+        }
+    }
+
+    pub(crate) fn unload(&mut self) {
+        // Order is important here, see fields declaration.
+        self.constructor = None;
+        self.library = None;
+        if let Some(loaded_path) = self.loaded_path.take() {
+            let _ = std::fs::remove_file(loaded_path);
+        }
+    }
+}
+
+impl Drop for DynamicPlugin {
+    fn drop(&mut self) {
+        self.unload();
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+// Serialized state of a script, that is stored while its plugin is reloading.
+pub(crate) struct SavedScript {
+    node: Handle<Node>,
+    type_uuid: Uuid,
+    started: bool,
+    data: Vec<u8>,
+}
+
+// Removes scripts of the given types from the graph and serializes them.
+pub(crate) fn take_scripts(graph: &mut Graph, types: &[Uuid]) -> Vec<SavedScript> {
+    let mut saved = Vec::new();
+    for (handle, node) in graph.pair_iter_mut() {
+        let script = node.script_inner();
+        if !script
+            .as_ref()
+            .map_or(false, |script| types.contains(&script.id()))
+        {
+            continue;
+        }
+
+        if let Some(mut script) = script.take() {
+            let type_uuid = script.id();
+            let mut visitor = Visitor::new();
+            match script
+                .visit("Script", &mut visitor)
+                .and_then(|_| visitor.save_binary_to_vec())
+            {
+                Ok(data) => saved.push(SavedScript {
+                    node: handle,
+                    type_uuid,
+                    started: script.started,
+                    data,
+                }),
+                Err(err) => Log::err(format!(
+                    "Unable to save state of script {} of node {}: {:?}. The script will be lost!",
+                    type_uuid,
+                    node.name(),
+                    err
+                )),
+            }
+        }
+    }
+    saved
+}
+
+// Creates new instances of the saved scripts (using current script constructors) and restores their
+// state.
+pub(crate) fn restore_scripts(
+    graph: &mut Graph,
+    scripts: Vec<SavedScript>,
+    serialization_context: &Arc<SerializationContext>,
+    resource_manager: &ResourceManager,
+) {
+    for saved in scripts {
+        let node = match graph.try_get_mut(saved.node) {
+            Some(node) => node,
+            None => continue,
+        };
+
+        let mut script = match serialization_context
+            .script_constructors
+            .try_create(&saved.type_uuid)
+        {
+            Some(script) => script,
+            None => {
+                Log::warn(format!(
+                    "Script {} of node {} does not exist anymore and it was removed!",
+                    saved.type_uuid,
+                    node.name()
+                ));
+                continue;
+            }
+        };
+
+        let result = Visitor::load_from_memory(saved.data).and_then(|mut visitor| {
+            visitor.blackboard.register(serialization_context.clone());
+            visitor
+                .blackboard
+                .register(Arc::new(resource_manager.clone()));
+            script.visit("Script", &mut visitor)
+        });
+
+        match result {
+            Ok(_) => {
+                script.started = saved.started;
+                *node.script_inner() = Some(script);
+            }
+            Err(err) => Log::err(format!(
+                "Unable to restore state of script {} of node {}: {:?}. The script will be lost!",
+                saved.type_uuid,
+                node.name(),
+                err
+            )),
+        }
+    }
+}
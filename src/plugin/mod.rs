@@ -2,15 +2,22 @@
 
 #![warn(missing_docs)]
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod dynamic;
+#[cfg(feature = "wasm_mods")]
+pub mod wasm;
+
 use crate::engine::ScriptProcessor;
 use crate::{
     asset::manager::ResourceManager,
-    core::pool::Handle,
+    core::{arena::FrameArena, pool::Handle},
     engine::{GraphicsContext, PerformanceStatistics, SerializationContext},
     event::Event,
     event_loop::ControlFlow,
     gui::{message::UiMessage, UserInterface},
+    input::Input,
     scene::{Scene, SceneContainer},
+    utils::session::GameSession,
 };
 use std::{any::Any, sync::Arc};
 
@@ -92,6 +99,22 @@ pub struct PluginContext<'a, 'b> {
 
     /// Script processor is used to run script methods in a strict order.
     pub script_processor: &'a ScriptProcessor,
+
+    /// Global time scale of the game, see [`crate::engine::Engine::set_time_scale`] for more info.
+    pub time_scale: &'a mut f32,
+
+    /// A reference to action-based input of the engine. Plugins could change its input map, for
+    /// example to apply bindings changed by a player. See [`Input`] docs for more info.
+    pub input: &'a mut Input,
+
+    /// A reference to persistent data of the current game session. See [`GameSession`] docs for more
+    /// info.
+    pub session: &'a mut GameSession,
+
+    /// A reference to frame arena, that could be used for temporary allocations, that live no longer
+    /// than a single update tick. The arena is reset at the beginning of each tick, see [`FrameArena`]
+    /// docs for more info.
+    pub frame_arena: &'a FrameArena,
 }
 
 /// Base plugin automatically implements type casting for plugins.
@@ -144,6 +167,15 @@ impl dyn Plugin {
 /// `#[repr(C)]` attribute which is not always easy and even possible (because some structures could
 /// be re-exported from dependencies). These are the main reasons why the engine uses static plugins.
 ///
+/// However, it is possible to load a plugin from a dynamic library built with exactly the same
+/// compiler and engine version, and reload it when the library is rebuilt. It is unsafe and
+/// intended for development only, but it significantly speeds up iterations on game logic. See
+/// [`dynamic::DynamicPlugin`] docs for more info.
+///
+/// Plugins have full access to the engine, so they must never be loaded from untrusted sources.
+/// User mods should be implemented as WebAssembly modules instead, they're executed in a sandbox
+/// with a restricted engine API (see `wasm` module, it is available with `wasm_mods` feature).
+///
 /// # Example
 ///
 /// ```rust
@@ -181,7 +213,8 @@ pub trait Plugin: BasePlugin {
     fn on_deinit(&mut self, #[allow(unused_variables)] context: PluginContext) {}
 
     /// Updates the plugin internals at fixed rate (see [`PluginContext::dt`] parameter for more
-    /// info).
+    /// info). This is the fixed update (simulation) phase of the game loop, it could be called zero
+    /// or more times per frame. See [`crate::engine::timestep::FixedTimestep`] docs for more info.
     fn update(
         &mut self,
         #[allow(unused_variables)] context: &mut PluginContext,
@@ -189,6 +222,19 @@ pub trait Plugin: BasePlugin {
     ) {
     }
 
+    /// The method is called exactly once per frame, after every fixed update of the frame. This is
+    /// the variable update phase of the game loop, [`PluginContext::dt`] contains (scaled) time of
+    /// the frame. `interpolation_factor` is a value in `[0; 1]` range, that defines how far the
+    /// real time is from the last simulation step. It could be used to interpolate visual state of
+    /// objects between previous and current simulation steps to get smooth motion on any frame rate.
+    fn frame_update(
+        &mut self,
+        #[allow(unused_variables)] context: &mut PluginContext,
+        #[allow(unused_variables)] interpolation_factor: f32,
+        #[allow(unused_variables)] control_flow: &mut ControlFlow,
+    ) {
+    }
+
     /// The method is called when the main window receives an event from the OS. The main use of
     /// the method is to respond to some external events, for example an event from keyboard or
     /// gamepad. See [`Event`] docs for more info.
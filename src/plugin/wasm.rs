@@ -0,0 +1,921 @@
+//! WASM mods - sandboxed extensions of a game, that are loaded from untrusted WebAssembly modules.
+//! See [`Mod`] and [`ModRuntime`] docs for more info.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        log::Log,
+        pool::Handle,
+    },
+    resource::model::{Model, ModelResource, ModelResourceExtension},
+    scene::{node::Node, Scene},
+};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use wasmi::{
+    core::{Trap, TrapCode, F32},
+    Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder,
+};
+
+/// Name of the module, from which a mod imports engine functions.
+pub const HOST_MODULE: &str = "fyrox";
+
+// Amount of fuel, that is spent on every log message in addition to one unit per byte of the
+// message. Logging is slow, so a mod must not be able to flood the log for free.
+const LOG_FUEL_COST: u64 = 1000;
+
+/// All possible errors that may occur during mod loading or execution.
+#[derive(Debug)]
+pub enum ModError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Module is invalid, it has exceeded its limits or it has trapped.
+    Wasm(wasmi::Error),
+    /// The mod does not export a function or a memory that is needed for an operation.
+    MissingExport(&'static str),
+    /// The mod has failed earlier and it was disabled.
+    Faulted,
+}
+
+impl Display for ModError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            ModError::Wasm(v) => write!(f, "WASM error: {v}"),
+            ModError::MissingExport(v) => write!(f, "The mod does not export `{v}`!"),
+            ModError::Faulted => write!(f, "The mod has failed and it was disabled!"),
+        }
+    }
+}
+
+impl From<std::io::Error> for ModError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<wasmi::Error> for ModError {
+    fn from(e: wasmi::Error) -> Self {
+        Self::Wasm(e)
+    }
+}
+
+/// Resource limits of a mod.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModLimits {
+    /// Maximum size (in bytes) of the linear memory of the mod.
+    pub max_memory: usize,
+    /// Amount of fuel, that the mod receives for every call. Fuel is spent on every executed
+    /// instruction, so it limits the execution time of the mod. A mod that runs out of fuel is
+    /// interrupted and disabled.
+    pub fuel_per_call: u64,
+    /// Maximum amount of prefabs, that the mod can spawn per update.
+    pub max_spawns_per_update: usize,
+    /// Maximum amount of messages, that the mod can post per update.
+    pub max_messages_per_update: usize,
+    /// Maximum size (in bytes) of a single message and of a single string passed to the engine.
+    pub max_message_size: usize,
+    /// Maximum amount of elements in a table of the mod.
+    pub max_table_elements: u32,
+    /// Maximum size (in bytes) of a single log message, longer messages are truncated.
+    pub max_log_size: usize,
+}
+
+impl Default for ModLimits {
+    fn default() -> Self {
+        Self {
+            max_memory: 16 * 1024 * 1024,
+            fuel_per_call: 10_000_000,
+            max_spawns_per_update: 16,
+            max_messages_per_update: 64,
+            max_message_size: 64 * 1024,
+            max_table_elements: 10_000,
+            max_log_size: 1024,
+        }
+    }
+}
+
+/// Defines what a mod is allowed to do.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModPermissions {
+    /// Paths of prefabs (or folders with prefabs), that the mod is allowed to spawn.
+    pub prefabs: Vec<PathBuf>,
+}
+
+impl ModPermissions {
+    /// Adds a prefab (or a folder with prefabs), that the mod is allowed to spawn.
+    pub fn with_prefab<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.prefabs.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// Returns `true` if the mod is allowed to spawn the given prefab. Paths that contain anything
+    /// except normal components (such as `..`) are never allowed.
+    pub fn is_prefab_allowed(&self, path: &Path) -> bool {
+        path.components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+            && self.prefabs.iter().any(|allowed| path.starts_with(allowed))
+    }
+}
+
+/// A message, that is posted by a mod or sent to a mod.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModMessage {
+    /// Name of the sender mod, or an empty string if the message was sent by the game.
+    pub sender: String,
+    /// Name of the message.
+    pub name: String,
+    /// Arbitrary data of the message, its format is defined by the game.
+    pub data: Vec<u8>,
+}
+
+/// A read-only information about a scene node, that is visible to mods.
+#[derive(Clone, Debug)]
+pub struct ModNodeInfo {
+    /// Handle of the node.
+    pub handle: Handle<Node>,
+    /// Name of the node.
+    pub name: String,
+    /// Global position of the node.
+    pub position: Vector3<f32>,
+}
+
+/// A request to spawn a prefab, that was made by a mod.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpawnRequest {
+    /// Path of the prefab.
+    pub path: PathBuf,
+    /// Global position of the new instance.
+    pub position: Vector3<f32>,
+}
+
+struct HostState {
+    name: String,
+    limits: ModLimits,
+    permissions: ModPermissions,
+    store_limits: StoreLimits,
+    nodes: Arc<Vec<ModNodeInfo>>,
+    spawns: Vec<SpawnRequest>,
+    messages: Vec<ModMessage>,
+}
+
+// Handles are passed to mods as a single 64-bit integer, -1 means "no node".
+fn encode_handle(handle: Handle<Node>) -> i64 {
+    if handle.is_none() {
+        -1
+    } else {
+        ((handle.index() as u64) << 32 | handle.generation() as u64) as i64
+    }
+}
+
+fn decode_handle(handle: i64) -> Handle<Node> {
+    if handle < 0 {
+        Handle::NONE
+    } else {
+        Handle::new((handle as u64 >> 32) as u32, handle as u32)
+    }
+}
+
+fn memory(caller: &Caller<'_, HostState>) -> Result<Memory, Trap> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Trap::new("The mod does not export its memory!"))
+}
+
+fn read_bytes(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Result<Vec<u8>, Trap> {
+    if len < 0 || len as usize > caller.data().limits.max_message_size {
+        return Err(Trap::new(format!("Invalid data length {len}!")));
+    }
+    let mut buffer = vec![0; len as usize];
+    memory(caller)?
+        .read(caller, ptr as u32 as usize, &mut buffer)
+        .map_err(|_| Trap::from(TrapCode::MemoryOutOfBounds))?;
+    Ok(buffer)
+}
+
+fn read_string(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Result<String, Trap> {
+    String::from_utf8(read_bytes(caller, ptr, len)?)
+        .map_err(|_| Trap::new("A string passed to the engine is not valid UTF-8!"))
+}
+
+fn write_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, data: &[u8]) -> Result<(), Trap> {
+    memory(caller)?
+        .write(caller, ptr as u32 as usize, data)
+        .map_err(|_| Trap::from(TrapCode::MemoryOutOfBounds))
+}
+
+fn node_info(caller: &Caller<'_, HostState>, handle: i64) -> Option<ModNodeInfo> {
+    let handle = decode_handle(handle);
+    caller
+        .data()
+        .nodes
+        .iter()
+        .find(|info| info.handle == handle)
+        .cloned()
+}
+
+fn create_linker(engine: &Engine) -> Result<Linker<HostState>, wasmi::Error> {
+    let mut linker = Linker::new(engine);
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> Result<(), Trap> {
+            if len < 0 {
+                return Err(Trap::new(format!("Invalid data length {len}!")));
+            }
+            let size = (len as usize).min(caller.data().limits.max_log_size);
+            caller
+                .consume_fuel(LOG_FUEL_COST + size as u64)
+                .map_err(|_| Trap::from(TrapCode::OutOfFuel))?;
+            let bytes = read_bytes(&caller, ptr, size as i32)?;
+            // Truncation could split a character, so invalid sequences are replaced.
+            let mut text = String::from_utf8_lossy(&bytes).into_owned();
+            if size < len as usize {
+                text.push_str("...");
+            }
+            Log::info(format!("[{}]: {}", caller.data().name, text));
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "node_count",
+        |caller: Caller<'_, HostState>| -> i32 { caller.data().nodes.len() as i32 },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "node_at",
+        |caller: Caller<'_, HostState>, index: i32| -> i64 {
+            caller
+                .data()
+                .nodes
+                .get(index as u32 as usize)
+                .map_or(-1, |info| encode_handle(info.handle))
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "find_node",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> Result<i64, Trap> {
+            let name = read_string(&caller, ptr, len)?;
+            // Searching is not free, otherwise a mod could bypass its time limit.
+            let cost = caller.data().nodes.len() as u64;
+            caller
+                .consume_fuel(cost)
+                .map_err(|_| Trap::from(TrapCode::OutOfFuel))?;
+            Ok(caller
+                .data()
+                .nodes
+                .iter()
+                .find(|info| info.name == name)
+                .map_or(-1, |info| encode_handle(info.handle)))
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "node_name",
+        |mut caller: Caller<'_, HostState>,
+         handle: i64,
+         ptr: i32,
+         capacity: i32|
+         -> Result<i32, Trap> {
+            match node_info(&caller, handle) {
+                Some(info) => {
+                    let name = info.name.as_bytes();
+                    let len = name.len().min(capacity.max(0) as usize);
+                    write_bytes(&mut caller, ptr, &name[..len])?;
+                    Ok(name.len() as i32)
+                }
+                None => Ok(-1),
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "node_position",
+        |mut caller: Caller<'_, HostState>, handle: i64, ptr: i32| -> Result<i32, Trap> {
+            match node_info(&caller, handle) {
+                Some(info) => {
+                    let mut bytes = [0; 12];
+                    for (chunk, component) in bytes.chunks_mut(4).zip(info.position.iter()) {
+                        chunk.copy_from_slice(&component.to_le_bytes());
+                    }
+                    write_bytes(&mut caller, ptr, &bytes)?;
+                    Ok(1)
+                }
+                None => Ok(0),
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "spawn_prefab",
+        |mut caller: Caller<'_, HostState>,
+         ptr: i32,
+         len: i32,
+         x: F32,
+         y: F32,
+         z: F32|
+         -> Result<i32, Trap> {
+            let path = PathBuf::from(read_string(&caller, ptr, len)?);
+            let state = caller.data_mut();
+            if !state.permissions.is_prefab_allowed(&path) {
+                Log::warn(format!(
+                    "Mod {} is not allowed to spawn {}!",
+                    state.name,
+                    path.display()
+                ));
+                return Ok(0);
+            }
+            if state.spawns.len() >= state.limits.max_spawns_per_update {
+                return Ok(0);
+            }
+            state.spawns.push(SpawnRequest {
+                path,
+                position: Vector3::new(x.to_float(), y.to_float(), z.to_float()),
+            });
+            Ok(1)
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "post_message",
+        |mut caller: Caller<'_, HostState>,
+         name_ptr: i32,
+         name_len: i32,
+         data_ptr: i32,
+         data_len: i32|
+         -> Result<i32, Trap> {
+            let name = read_string(&caller, name_ptr, name_len)?;
+            let data = read_bytes(&caller, data_ptr, data_len)?;
+            let state = caller.data_mut();
+            if state.messages.len() >= state.limits.max_messages_per_update {
+                return Ok(0);
+            }
+            let sender = state.name.clone();
+            state.messages.push(ModMessage { sender, name, data });
+            Ok(1)
+        },
+    )?;
+
+    Ok(linker)
+}
+
+/// Mod is an untrusted WebAssembly module, that is executed in a sandbox. A mod has no access to
+/// the file system, the network or the memory of the game; it can only use a small set of engine
+/// functions (see below) and it can't exceed its [`ModLimits`]. A mod that traps, runs out of fuel
+/// or otherwise fails is disabled (see [`Self::is_faulted`]) and never called again.
+///
+/// # Engine API
+///
+/// A mod can import the following functions from the `fyrox` module. Strings are passed as a
+/// pointer and a length (in bytes) of UTF-8 data in the memory of the mod, node handles are passed
+/// as `i64` where `-1` means "no node".
+///
+/// - `log(ptr: i32, len: i32)` - writes a message to the log.
+/// - `node_count() -> i32` - returns amount of nodes, that are visible to the mod.
+/// - `node_at(index: i32) -> i64` - returns handle of a node with the given index.
+/// - `find_node(ptr: i32, len: i32) -> i64` - searches for a node with the given name.
+/// - `node_name(handle: i64, ptr: i32, capacity: i32) -> i32` - writes the name of a node to the
+///   given buffer and returns the full length of the name, or `-1` if there is no such node.
+/// - `node_position(handle: i64, ptr: i32) -> i32` - writes global position of a node as three
+///   little-endian `f32` to the given buffer and returns `1`, or `0` if there is no such node.
+/// - `spawn_prefab(ptr: i32, len: i32, x: f32, y: f32, z: f32) -> i32` - requests to spawn a
+///   prefab at the given position, returns `0` if the prefab is not allowed by [`ModPermissions`] or
+///   if the mod has reached its spawn limit.
+/// - `post_message(name_ptr: i32, name_len: i32, data_ptr: i32, data_len: i32) -> i32` - posts a
+///   message to the game, returns `0` if the mod has reached its message limit.
+///
+/// Nodes are seen by a mod as they were at the beginning of its update, all changes requested by
+/// the mod are applied after the update.
+///
+/// # Exports
+///
+/// A mod must export its `memory` and may export the following functions:
+///
+/// - `init()` - called once, before the first update.
+/// - `update(dt: f32)` - called every frame.
+/// - `alloc(size: i32) -> i32` - allocates a buffer in the memory of the mod, it is needed to pass
+///   messages to the mod.
+/// - `on_message(name_ptr: i32, name_len: i32, data_ptr: i32, data_len: i32)` - called for every
+///   message sent to the mod (see [`ModRuntime::send_message`]).
+pub struct Mod {
+    store: Store<HostState>,
+    instance: Instance,
+    initialized: bool,
+    faulted: bool,
+}
+
+impl Mod {
+    /// Loads a mod from the given `.wasm` file. Name of the mod is the name of the file without
+    /// extension.
+    pub fn from_file<P: AsRef<Path>>(
+        path: P,
+        limits: ModLimits,
+        permissions: ModPermissions,
+    ) -> Result<Self, ModError> {
+        let name = path
+            .as_ref()
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(name, &bytes, limits, permissions)
+    }
+
+    /// Loads a mod from the given WebAssembly binary.
+    pub fn from_bytes<N: Into<String>>(
+        name: N,
+        bytes: &[u8],
+        limits: ModLimits,
+        permissions: ModPermissions,
+    ) -> Result<Self, ModError> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, bytes)?;
+
+        let store_limits = StoreLimitsBuilder::new()
+            .memory_size(limits.max_memory)
+            .memories(1)
+            .tables(1)
+            .table_elements(limits.max_table_elements)
+            .instances(1)
+            .build();
+        let mut store = Store::new(
+            &engine,
+            HostState {
+                name: name.into(),
+                limits,
+                permissions,
+                store_limits,
+                nodes: Default::default(),
+                spawns: Default::default(),
+                messages: Default::default(),
+            },
+        );
+        store.limiter(|state| &mut state.store_limits);
+
+        let linker = create_linker(&engine)?;
+        refuel(&mut store)?;
+        let instance = linker.instantiate(&mut store, &module)?.start(&mut store)?;
+
+        Ok(Self {
+            store,
+            instance,
+            initialized: false,
+            faulted: false,
+        })
+    }
+
+    /// Returns name of the mod.
+    pub fn name(&self) -> &str {
+        &self.store.data().name
+    }
+
+    /// Returns limits of the mod.
+    pub fn limits(&self) -> &ModLimits {
+        &self.store.data().limits
+    }
+
+    /// Returns permissions of the mod.
+    pub fn permissions(&self) -> &ModPermissions {
+        &self.store.data().permissions
+    }
+
+    /// Returns `true` if the mod has failed and it was disabled.
+    pub fn is_faulted(&self) -> bool {
+        self.faulted
+    }
+
+    /// Calls `init` (once) and `update` functions of the mod. `nodes` is a set of nodes, that are
+    /// visible to the mod. Returns a list of prefabs, that the mod wants to spawn, posted messages
+    /// could be obtained by [`Self::take_messages`].
+    pub fn update(
+        &mut self,
+        nodes: Arc<Vec<ModNodeInfo>>,
+        dt: f32,
+    ) -> Result<Vec<SpawnRequest>, ModError> {
+        if self.faulted {
+            return Err(ModError::Faulted);
+        }
+
+        self.store.data_mut().nodes = nodes;
+
+        if !self.initialized {
+            self.initialized = true;
+            self.call::<(), ()>("init", ())?;
+        }
+        self.call::<F32, ()>("update", F32::from(dt))?;
+
+        let state = self.store.data_mut();
+        state.nodes = Default::default();
+        Ok(std::mem::take(&mut state.spawns))
+    }
+
+    /// Passes the given message to `on_message` function of the mod. Does nothing if the mod does
+    /// not export `on_message` function.
+    pub fn send_message(&mut self, message: &ModMessage) -> Result<(), ModError> {
+        if self.faulted {
+            return Err(ModError::Faulted);
+        }
+
+        if self
+            .instance
+            .get_typed_func::<(i32, i32, i32, i32), ()>(&self.store, "on_message")
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let size = message.name.len() + message.data.len();
+        if size > self.limits().max_message_size {
+            return Ok(());
+        }
+
+        let ptr = self
+            .call::<i32, i32>("alloc", size as i32)?
+            .ok_or(ModError::MissingExport("alloc"))?;
+        let memory = self
+            .instance
+            .get_memory(&self.store, "memory")
+            .ok_or(ModError::MissingExport("memory"))?;
+        let result = memory
+            .write(
+                &mut self.store,
+                ptr as u32 as usize,
+                message.name.as_bytes(),
+            )
+            .and_then(|_| {
+                memory.write(
+                    &mut self.store,
+                    ptr as u32 as usize + message.name.len(),
+                    &message.data,
+                )
+            });
+        if result.is_err() {
+            return Err(self.fault(wasmi::Error::from(Trap::from(TrapCode::MemoryOutOfBounds))));
+        }
+
+        self.call::<(i32, i32, i32, i32), ()>(
+            "on_message",
+            (
+                ptr,
+                message.name.len() as i32,
+                ptr + message.name.len() as i32,
+                message.data.len() as i32,
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns messages, that were posted by the mod since the last call of the method.
+    pub fn take_messages(&mut self) -> Vec<ModMessage> {
+        std::mem::take(&mut self.store.data_mut().messages)
+    }
+
+    // Calls a function of the mod with a fresh amount of fuel, returns `None` if there is no such
+    // function.
+    fn call<P, R>(&mut self, name: &str, params: P) -> Result<Option<R>, ModError>
+    where
+        P: wasmi::WasmParams,
+        R: wasmi::WasmResults,
+    {
+        let func = match self.instance.get_typed_func::<P, R>(&self.store, name) {
+            Ok(func) => func,
+            Err(_) => return Ok(None),
+        };
+
+        match refuel(&mut self.store).and_then(|_| {
+            func.call(&mut self.store, params)
+                .map_err(wasmi::Error::from)
+        }) {
+            Ok(result) => Ok(Some(result)),
+            Err(err) => Err(self.fault(err)),
+        }
+    }
+
+    fn fault(&mut self, err: wasmi::Error) -> ModError {
+        Log::err(format!(
+            "Mod {} has failed and it will be disabled. Reason: {}",
+            self.name(),
+            err
+        ));
+        self.faulted = true;
+        ModError::Wasm(err)
+    }
+}
+
+fn refuel(store: &mut Store<HostState>) -> Result<(), wasmi::Error> {
+    let fuel_per_call = store.data().limits.fuel_per_call;
+    let remaining = store.consume_fuel(0).map_err(wasmi::Error::from)?;
+    if remaining < fuel_per_call {
+        store
+            .add_fuel(fuel_per_call - remaining)
+            .map_err(wasmi::Error::from)?;
+    }
+    Ok(())
+}
+
+/// Mod runtime manages a set of mods and connects them with a scene: it collects information about
+/// scene nodes for mods, updates the mods, spawns prefabs requested by them and collects messages
+/// posted by them.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     asset::manager::ResourceManager,
+///     plugin::wasm::{ModLimits, ModPermissions, ModRuntime},
+///     scene::Scene,
+/// };
+///
+/// fn update_mods(
+///     runtime: &mut ModRuntime,
+///     scene: &mut Scene,
+///     resource_manager: &ResourceManager,
+///     dt: f32,
+/// ) {
+///     runtime.update(scene, resource_manager, dt);
+///
+///     while let Some(message) = runtime.pop_message() {
+///         println!("{} says {}", message.sender, message.name);
+///     }
+/// }
+///
+/// let mut runtime = ModRuntime::new();
+/// runtime
+///     .load(
+///         "mods/my_mod.wasm",
+///         ModLimits::default(),
+///         ModPermissions::default().with_prefab("data/mods/my_mod"),
+///     )
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct ModRuntime {
+    mods: Vec<Mod>,
+    messages: VecDeque<ModMessage>,
+    pending_spawns: Vec<(ModelResource, Vector3<f32>)>,
+}
+
+impl ModRuntime {
+    /// Creates new empty mod runtime.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a mod from the given file and adds it to the runtime.
+    pub fn load<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        limits: ModLimits,
+        permissions: ModPermissions,
+    ) -> Result<(), ModError> {
+        self.add(Mod::from_file(path, limits, permissions)?);
+        Ok(())
+    }
+
+    /// Adds a mod to the runtime.
+    pub fn add(&mut self, mod_: Mod) {
+        self.mods.push(mod_);
+    }
+
+    /// Returns a reference to every mod in the runtime.
+    pub fn mods(&self) -> &[Mod] {
+        &self.mods
+    }
+
+    /// Removes every mod with the given name.
+    pub fn remove(&mut self, name: &str) {
+        self.mods.retain(|mod_| mod_.name() != name);
+    }
+
+    /// Sends the given message to every working mod.
+    pub fn send_message(&mut self, message: &ModMessage) {
+        for mod_ in self.mods.iter_mut().filter(|mod_| !mod_.is_faulted()) {
+            let _ = mod_.send_message(message);
+        }
+    }
+
+    /// Extracts the oldest message posted by mods.
+    pub fn pop_message(&mut self) -> Option<ModMessage> {
+        self.messages.pop_front()
+    }
+
+    /// Updates every working mod and applies their requests to the given scene.
+    pub fn update(&mut self, scene: &mut Scene, resource_manager: &ResourceManager, dt: f32) {
+        self.spawn_loaded_prefabs(scene);
+
+        if self.mods.iter().all(|mod_| mod_.is_faulted()) {
+            return;
+        }
+
+        let nodes = Arc::new(
+            scene
+                .graph
+                .pair_iter()
+                .map(|(handle, node)| ModNodeInfo {
+                    handle,
+                    name: node.name_owned(),
+                    position: node.global_position(),
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        for mod_ in self.mods.iter_mut().filter(|mod_| !mod_.is_faulted()) {
+            if let Ok(spawns) = mod_.update(nodes.clone(), dt) {
+                for spawn in spawns {
+                    self.pending_spawns.push((
+                        resource_manager.request::<Model, _>(spawn.path),
+                        spawn.position,
+                    ));
+                }
+            }
+            self.messages.extend(mod_.take_messages());
+        }
+
+        self.spawn_loaded_prefabs(scene);
+    }
+
+    fn spawn_loaded_prefabs(&mut self, scene: &mut Scene) {
+        self.pending_spawns.retain(|(prefab, position)| {
+            if prefab.is_loading() {
+                true
+            } else {
+                if prefab.is_ok() {
+                    prefab.instantiate_at(scene, *position, UnitQuaternion::identity());
+                } else {
+                    Log::err(format!(
+                        "Unable to spawn prefab {} requested by a mod!",
+                        prefab.path().display()
+                    ));
+                }
+                false
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MOD: &str = r#"
+        (module
+            (import "fyrox" "find_node" (func $find_node (param i32 i32) (result i64)))
+            (import "fyrox" "node_position" (func $node_position (param i64 i32) (result i32)))
+            (import "fyrox" "spawn_prefab"
+                (func $spawn_prefab (param i32 i32 f32 f32 f32) (result i32)))
+            (import "fyrox" "post_message" (func $post_message (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "Player")
+            (data (i32.const 16) "position")
+            (data (i32.const 32) "data/mods/crate.rgs")
+            (data (i32.const 64) "data/secret.rgs")
+            (global $heap (mut i32) (i32.const 1024))
+            (func (export "alloc") (param $size i32) (result i32)
+                (global.get $heap))
+            (func (export "update") (param $dt f32)
+                ;; Query position of the player and post it back.
+                (drop (call $node_position (call $find_node (i32.const 0) (i32.const 6)) (i32.const 128)))
+                (drop (call $post_message (i32.const 16) (i32.const 8) (i32.const 128) (i32.const 12)))
+                (drop (call $spawn_prefab (i32.const 32) (i32.const 19)
+                    (f32.const 1) (f32.const 2) (f32.const 3)))
+                (drop (call $spawn_prefab (i32.const 64) (i32.const 15)
+                    (f32.const 0) (f32.const 0) (f32.const 0))))
+            (func (export "on_message") (param i32 i32 i32 i32)
+                (loop $forever (br $forever))))
+    "#;
+
+    fn load(source: &str, limits: ModLimits) -> Result<Mod, ModError> {
+        Mod::from_bytes(
+            "test",
+            &wat::parse_str(source).unwrap(),
+            limits,
+            ModPermissions::default().with_prefab("data/mods"),
+        )
+    }
+
+    #[test]
+    fn test_mod_api() {
+        let mut mod_ = load(MOD, ModLimits::default()).unwrap();
+
+        let nodes = Arc::new(vec![ModNodeInfo {
+            handle: Handle::new(3, 1),
+            name: "Player".to_string(),
+            position: Vector3::new(1.0, 2.0, 3.0),
+        }]);
+        let spawns = mod_.update(nodes, 0.1).unwrap();
+
+        // Prefab outside of allowed folder must be rejected.
+        assert_eq!(
+            spawns,
+            vec![SpawnRequest {
+                path: "data/mods/crate.rgs".into(),
+                position: Vector3::new(1.0, 2.0, 3.0)
+            }]
+        );
+
+        let messages = mod_.take_messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].sender, "test");
+        assert_eq!(messages[0].name, "position");
+        let position = messages[0]
+            .data
+            .chunks(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect::<Vec<_>>();
+        assert_eq!(position, vec![1.0, 2.0, 3.0]);
+
+        // Infinite loop must be interrupted and the mod must be disabled.
+        assert!(mod_.send_message(&ModMessage::default()).is_err());
+        assert!(mod_.is_faulted());
+        assert!(matches!(
+            mod_.update(Default::default(), 0.1),
+            Err(ModError::Faulted)
+        ));
+    }
+
+    #[test]
+    fn test_mod_memory_limit() {
+        let limits = ModLimits {
+            max_memory: 65536,
+            ..Default::default()
+        };
+
+        assert!(load(r#"(module (memory (export "memory") 2))"#, limits.clone()).is_err());
+
+        let mut mod_ = load(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "update") (param f32)
+                    (if (i32.ne (memory.grow (i32.const 1)) (i32.const -1))
+                        (then unreachable))))
+            "#,
+            limits,
+        )
+        .unwrap();
+        assert!(mod_.update(Default::default(), 0.1).is_ok());
+    }
+
+    #[test]
+    fn test_prefab_permissions() {
+        let permissions = ModPermissions::default().with_prefab("data/mods");
+        assert!(permissions.is_prefab_allowed(Path::new("data/mods/a.rgs")));
+        assert!(!permissions.is_prefab_allowed(Path::new("data/mods/../b.rgs")));
+        assert!(!permissions.is_prefab_allowed(Path::new("data/b.rgs")));
+        assert!(!permissions.is_prefab_allowed(Path::new("/data/mods/a.rgs")));
+    }
+
+    #[test]
+    fn test_log_limits() {
+        const LOG_MOD: &str = r#"
+            (module
+                (import "fyrox" "log" (func $log (param i32 i32)))
+                (memory (export "memory") 1)
+                (func (export "update") (param f32)
+                    (call $log (i32.const 0) (i32.const 1000000))))
+        "#;
+
+        // A huge message is truncated instead of being copied from the memory of the mod.
+        let mut mod_ = load(LOG_MOD, ModLimits::default()).unwrap();
+        assert!(mod_.update(Default::default(), 0.1).is_ok());
+
+        // Logging is not free.
+        let limits = ModLimits {
+            fuel_per_call: LOG_FUEL_COST / 2,
+            ..Default::default()
+        };
+        let mut mod_ = load(LOG_MOD, limits).unwrap();
+        assert!(mod_.update(Default::default(), 0.1).is_err());
+        assert!(mod_.is_faulted());
+    }
+
+    #[test]
+    fn test_mod_table_limit() {
+        let limits = ModLimits {
+            max_table_elements: 16,
+            ..Default::default()
+        };
+        assert!(load(r#"(module (table 32 funcref))"#, limits.clone()).is_err());
+        assert!(load(r#"(module (table 8 funcref))"#, limits).is_ok());
+    }
+}
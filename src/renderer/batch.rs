@@ -4,6 +4,7 @@ use crate::scene::node::Node;
 use crate::{
     core::{
         algebra::{Matrix4, Vector3},
+        arena::FrameArena,
         math::frustum::Frustum,
         sstorage::ImmutableString,
     },
@@ -64,6 +65,21 @@ pub struct RenderContext<'a> {
     pub render_pass_name: &'a ImmutableString,
     /// Handle of the node being rendered.
     pub node_handle: Handle<Node>,
+    frustum_visibility: &'a [bool],
+}
+
+impl<'a> RenderContext<'a> {
+    /// Returns `true` if the world-space bounding box of the node being rendered intersects the frustum
+    /// of the observer, `false` - otherwise. Frustum tests for every node of the graph are done in
+    /// parallel and cached before render data collection, so this method is much faster than doing the
+    /// test manually. Nodes with disabled frustum culling (see [`crate::scene::base::Base::frustum_culling`])
+    /// are always considered visible.
+    pub fn is_node_in_frustum(&self) -> bool {
+        self.frustum_visibility
+            .get(self.node_handle.index() as usize)
+            .cloned()
+            .unwrap_or(true)
+    }
 }
 
 /// Persistent identifier marks drawing data, telling the renderer that the data is the same, no matter from which
@@ -86,6 +102,100 @@ impl PersistentIdentifier {
     }
 }
 
+/// A 64-bit key that defines the order in which render batches are drawn. The key is packed so that
+/// a simple integer comparison sorts the batches by layer first, then by material and then by depth:
+///
+/// | Bits    | Content                                                      |
+/// |---------|--------------------------------------------------------------|
+/// | 63..56  | Sort layer (explicit draw order, for example terrain layers) |
+/// | 55..24  | Material key (folded to 32 bits)                             |
+/// | 23..0   | Normalized depth of the nearest instance (front-to-back)     |
+///
+/// Such order groups batches with the same material together, which minimizes amount of state changes,
+/// while still drawing close objects first to reduce overdraw.
+#[derive(Copy, Clone, Default, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SortKey(pub u64);
+
+impl SortKey {
+    const LAYER_SHIFT: u32 = 56;
+    const MATERIAL_SHIFT: u32 = 24;
+    const MATERIAL_MASK: u64 = 0xFFFF_FFFF;
+    const DEPTH_MASK: u64 = 0xFF_FFFF;
+
+    /// Creates a new sort key from the given layer, material key and normalized depth. Depth is clamped
+    /// to `[0; 1]` range.
+    pub fn new(layer: u8, material: u64, depth: f32) -> Self {
+        let material = (material ^ (material >> 32)) & Self::MATERIAL_MASK;
+        let depth = (depth.clamp(0.0, 1.0) * Self::DEPTH_MASK as f32) as u64 & Self::DEPTH_MASK;
+        Self(((layer as u64) << Self::LAYER_SHIFT) | (material << Self::MATERIAL_SHIFT) | depth)
+    }
+
+    /// Returns sort layer of the key.
+    pub fn layer(self) -> u8 {
+        (self.0 >> Self::LAYER_SHIFT) as u8
+    }
+
+    /// Returns folded material key of the key.
+    pub fn material(self) -> u32 {
+        ((self.0 >> Self::MATERIAL_SHIFT) & Self::MATERIAL_MASK) as u32
+    }
+
+    /// Returns quantized depth of the key.
+    pub fn depth(self) -> u32 {
+        (self.0 & Self::DEPTH_MASK) as u32
+    }
+}
+
+/// Sorts the given items by a 64-bit key using LSD radix sort. The sort is stable, so items with equal keys
+/// keep their relative order, which makes the result deterministic. Passes over bytes that are the same for
+/// every key are skipped.
+pub(crate) fn radix_sort_by_key<T, F>(items: &mut Vec<T>, key: F)
+where
+    F: Fn(&T) -> u64,
+{
+    if items.len() < 2 {
+        return;
+    }
+
+    let mut keys = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| (key(item), i))
+        .collect::<Vec<_>>();
+    let mut scratch = vec![(0u64, 0usize); keys.len()];
+
+    for pass in 0..8 {
+        let shift = pass * 8;
+
+        let mut offsets = [0usize; 256];
+        for (k, _) in keys.iter() {
+            offsets[((k >> shift) & 0xFF) as usize] += 1;
+        }
+
+        if offsets.iter().any(|&count| count == keys.len()) {
+            continue;
+        }
+
+        let mut offset = 0;
+        for slot in offsets.iter_mut() {
+            let count = *slot;
+            *slot = offset;
+            offset += count;
+        }
+
+        for &(k, i) in keys.iter() {
+            let bucket = &mut offsets[((k >> shift) & 0xFF) as usize];
+            scratch[*bucket] = (k, i);
+            *bucket += 1;
+        }
+
+        std::mem::swap(&mut keys, &mut scratch);
+    }
+
+    let mut slots = items.drain(..).map(Some).collect::<Vec<_>>();
+    items.extend(keys.iter().map(|&(_, i)| slots[i].take().unwrap()));
+}
+
 /// A set of data of a surface for rendering.  
 pub struct SurfaceInstanceData {
     /// A world matrix.
@@ -102,6 +212,9 @@ pub struct SurfaceInstanceData {
     /// Persistent identifier of the instance. In most cases it can be generated by [`PersistentIdentifier::new_combined`]
     /// method.
     pub persistent_identifier: PersistentIdentifier,
+    /// Handle of the node the instance belongs to. It is used to fetch additional per-node data, such as
+    /// light probes.
+    pub node_handle: Handle<Node>,
 }
 
 /// A set of surface instances that share the same vertex/index data and a material.
@@ -118,7 +231,16 @@ pub struct RenderDataBatch {
     pub render_path: RenderPath,
     /// A decal layer index of the batch.
     pub decal_layer_index: u8,
-    sort_index: u64,
+    sort_layer: u8,
+    sort_key: SortKey,
+    batch_key: u64,
+}
+
+impl RenderDataBatch {
+    /// Returns the sort key of the batch. See [`SortKey`] docs for more info.
+    pub fn sort_key(&self) -> SortKey {
+        self.sort_key
+    }
 }
 
 impl Debug for RenderDataBatch {
@@ -137,7 +259,10 @@ impl Debug for RenderDataBatch {
 #[derive(Default)]
 pub struct RenderDataBatchStorage {
     batch_map: FxHashMap<u64, usize>,
-    /// A sorted list of batches.
+    observer_position: Vector3<f32>,
+    z_near: f32,
+    z_far: f32,
+    /// A list of batches sorted by their [`SortKey`].
     pub batches: Vec<RenderDataBatch>,
 }
 
@@ -145,19 +270,41 @@ impl RenderDataBatchStorage {
     /// Creates a new render batch storage from the given graph and observer info. It "asks" every node in the
     /// graph one-by-one to give render data which is then put in the storage, sorted and ready for rendering.
     /// Frustum culling is done on scene node side ([`crate::scene::node::NodeTrait::collect_render_data`]).
+    /// Temporary data (LOD and visibility flags) is allocated in the given frame arena.
     pub fn from_graph(
         graph: &Graph,
         observer_info: ObserverInfo,
         render_pass_name: ImmutableString,
+        frame_arena: &FrameArena,
     ) -> Self {
+        Self::from_graph_filtered(graph, observer_info, render_pass_name, frame_arena, |_| {
+            true
+        })
+    }
+
+    /// Same as [`Self::from_graph`], but collects render data only from the nodes that pass the given
+    /// filter.
+    pub fn from_graph_filtered<F>(
+        graph: &Graph,
+        observer_info: ObserverInfo,
+        render_pass_name: ImmutableString,
+        frame_arena: &FrameArena,
+        mut filter: F,
+    ) -> Self
+    where
+        F: FnMut(Handle<Node>) -> bool,
+    {
         // Aim for the worst-case scenario when every node has unique render data.
         let capacity = graph.node_count() as usize;
         let mut storage = Self {
             batch_map: FxHashMap::with_capacity_and_hasher(capacity, FxBuildHasher::default()),
+            observer_position: observer_info.observer_position,
+            z_near: observer_info.z_near,
+            z_far: observer_info.z_far,
             batches: Vec::with_capacity(capacity),
         };
 
-        let mut lod_filter = vec![true; graph.capacity() as usize];
+        let lod_filter = frame_arena.alloc_slice_fill_copy(graph.capacity() as usize, true);
         for node in graph.linear_iter() {
             if let Some(lod_group) = node.lod_group() {
                 for level in lod_group.levels.iter() {
@@ -182,6 +329,8 @@ impl RenderDataBatchStorage {
         )
         .unwrap_or_default();
 
+        let frustum_visibility = graph.visibility_cache.cull(graph, &frustum, frame_arena);
+
         let mut ctx = RenderContext {
             observer_position: &observer_info.observer_position,
             z_near: observer_info.z_near,
@@ -193,12 +342,13 @@ impl RenderDataBatchStorage {
             graph,
             render_pass_name: &render_pass_name,
             node_handle: Default::default(),
+            frustum_visibility,
         };
 
         for (handle, node) in graph.pair_iter() {
             ctx.node_handle = handle;
 
-            if lod_filter[handle.index() as usize] {
+            if lod_filter[handle.index() as usize] && filter(handle) {
                 node.collect_render_data(&mut ctx);
             }
         }
@@ -211,13 +361,16 @@ impl RenderDataBatchStorage {
     /// Adds a new surface instance to the storage. The method will automatically put the instance in the appropriate
     /// batch. Batch selection is done using the material, surface data, render path, decal layer index, skinning flag.
     /// If only one of these parameters is different, then the surface instance will be put in a separate batch.
+    ///
+    /// `sort_layer` defines explicit draw order of the batch (batches with lower layer are drawn first), in most
+    /// cases it should be zero. See [`SortKey`] docs for more info about the order of batches.
     pub fn push(
         &mut self,
         data: &SurfaceSharedData,
         material: &SharedMaterial,
         render_path: RenderPath,
         decal_layer_index: u8,
-        sort_index: u64,
+        sort_layer: u8,
         instance_data: SurfaceInstanceData,
     ) {
         let is_skinned = !instance_data.bone_matrices.is_empty();
@@ -227,16 +380,21 @@ impl RenderDataBatchStorage {
         hasher.write_u64(data.key());
         hasher.write_u8(if is_skinned { 1 } else { 0 });
         hasher.write_u8(decal_layer_index);
+        hasher.write_u8(sort_layer);
         hasher.write_u32(render_path as u32);
         let key = hasher.finish();
 
+        let depth = self.normalized_depth(&instance_data.world_transform);
+
         let batch = if let Some(&batch_index) = self.batch_map.get(&key) {
             self.batches.get_mut(batch_index).unwrap()
         } else {
             self.batch_map.insert(key, self.batches.len());
             self.batches.push(RenderDataBatch {
                 data: data.clone(),
-                sort_index,
+                sort_layer,
+                sort_key: SortKey::new(sort_layer, material.key(), depth),
+                batch_key: key,
                 instances: Default::default(),
                 material: material.clone(),
                 is_skinned,
@@ -246,11 +404,82 @@ impl RenderDataBatchStorage {
             self.batches.last_mut().unwrap()
         };
 
+        // The batch is sorted by its nearest instance.
+        if depth < batch.sort_key.depth() as f32 / SortKey::DEPTH_MASK as f32 {
+            batch.sort_key = SortKey::new(batch.sort_layer, batch.material.key(), depth);
+        }
+
         batch.instances.push(instance_data)
     }
 
-    /// Sorts the batches by their respective sort index.
+    fn normalized_depth(&self, world_transform: &Matrix4<f32>) -> f32 {
+        let position = Vector3::new(
+            world_transform[12],
+            world_transform[13],
+            world_transform[14],
+        );
+        let distance = self.observer_position.metric_distance(&position);
+        let z_range = (self.z_far - self.z_near).max(f32::EPSILON);
+        ((distance - self.z_near) / z_range).clamp(0.0, 1.0)
+    }
+
+    /// Sorts the batches by their respective sort keys. The sort is stable, so the order of batches is
+    /// deterministic.
     pub fn sort(&mut self) {
-        self.batches.sort_unstable_by_key(|b| b.sort_index);
+        radix_sort_by_key(&mut self.batches, |b| b.sort_key.0);
+
+        for (index, batch) in self.batches.iter().enumerate() {
+            self.batch_map.insert(batch.batch_key, index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::batch::{radix_sort_by_key, SortKey};
+
+    #[test]
+    fn test_sort_key_packing() {
+        let key = SortKey::new(3, 0x1234_5678, 0.5);
+        assert_eq!(key.layer(), 3);
+        assert_eq!(key.material(), 0x1234_5678);
+        assert_eq!(key.depth(), (0.5 * 0xFF_FFFF as f32) as u32);
+
+        // Layer has priority over material, material has priority over depth.
+        assert!(SortKey::new(0, u64::MAX, 1.0) < SortKey::new(1, 0, 0.0));
+        assert!(SortKey::new(0, 1, 1.0) < SortKey::new(0, 2, 0.0));
+        assert!(SortKey::new(0, 1, 0.25) < SortKey::new(0, 1, 0.75));
+
+        // Depth is clamped.
+        assert_eq!(SortKey::new(0, 0, -1.0).depth(), 0);
+        assert_eq!(SortKey::new(0, 0, 2.0).depth(), 0xFF_FFFF);
+    }
+
+    #[test]
+    fn test_radix_sort_by_key() {
+        let mut items = vec![
+            (5u64, 'a'),
+            (u64::MAX, 'b'),
+            (0, 'c'),
+            (5, 'd'),
+            (1 << 40, 'e'),
+            (0, 'f'),
+        ];
+        radix_sort_by_key(&mut items, |(k, _)| *k);
+        assert_eq!(
+            items,
+            vec![
+                (0, 'c'),
+                (0, 'f'),
+                (5, 'a'),
+                (5, 'd'),
+                (1 << 40, 'e'),
+                (u64::MAX, 'b')
+            ]
+        );
+
+        let mut empty = Vec::<u64>::new();
+        radix_sort_by_key(&mut empty, |k| *k);
+        assert!(empty.is_empty());
     }
 }
@@ -1,5 +1,6 @@
 use crate::{
     core::{scope_profile, sparse::SparseBuffer},
+    engine::gc::TimeBudget,
     renderer::framework::{
         geometry_buffer::{GeometryBuffer, GeometryBufferKind},
         state::PipelineState,
@@ -11,7 +12,9 @@ use fyrox_resource::entry::DEFAULT_RESOURCE_LIFETIME;
 
 struct CacheEntry {
     buffer: GeometryBuffer,
-    data_hash: u64,
+    vertices_hash: u64,
+    triangles_hash: u64,
+    vertex_count: u32,
     layout_hash: u64,
     time_to_live: f32,
 }
@@ -22,7 +25,7 @@ pub struct GeometryCache {
 }
 
 fn create_geometry_buffer(
-    data: &SurfaceData,
+    data: &mut SurfaceData,
     state: &mut PipelineState,
     buffer: &mut SparseBuffer<CacheEntry>,
 ) -> AtomicIndex {
@@ -32,10 +35,15 @@ fn create_geometry_buffer(
     let index = buffer.spawn(CacheEntry {
         buffer: geometry_buffer,
         time_to_live: DEFAULT_RESOURCE_LIFETIME,
-        data_hash: data.content_hash(),
+        vertices_hash: data.vertex_buffer.data_hash(),
+        triangles_hash: data.geometry_buffer.data_hash(),
+        vertex_count: data.vertex_buffer.vertex_count(),
         layout_hash: data.vertex_buffer.layout_hash(),
     });
 
+    // Everything is uploaded already.
+    data.vertex_buffer.take_modified_range();
+
     data.cache_entry.set(index.get());
 
     index
@@ -49,35 +57,61 @@ impl GeometryCache {
     ) -> &'a mut GeometryBuffer {
         scope_profile!();
 
-        let data = data.lock();
+        let mut data = data.lock();
 
         if let Some(entry) = self.buffer.get_mut(&data.cache_entry) {
             // We also must check if buffer's layout changed, and if so - recreate the entire
             // buffer.
             if entry.layout_hash == data.vertex_buffer.layout_hash() {
-                let data_hash = data.content_hash();
-                if data_hash != entry.data_hash {
-                    // Content has changed, upload new content.
-                    entry
-                        .buffer
-                        .set_buffer_data(state, 0, data.vertex_buffer.raw_data());
-                    entry
-                        .buffer
-                        .bind(state)
-                        .set_triangles(data.geometry_buffer.triangles_ref());
-
-                    entry.data_hash = data_hash;
+                let vertices_hash = data.vertex_buffer.data_hash();
+                let triangles_hash = data.geometry_buffer.data_hash();
+                let vertex_count = data.vertex_buffer.vertex_count();
+                let modified_range = data.vertex_buffer.take_modified_range();
+
+                if vertices_hash != entry.vertices_hash || triangles_hash != entry.triangles_hash {
+                    match modified_range {
+                        // Only a part of the vertices has changed, upload just that part.
+                        Some(range)
+                            if triangles_hash == entry.triangles_hash
+                                && vertex_count == entry.vertex_count =>
+                        {
+                            let vertex_size = data.vertex_buffer.vertex_size() as usize;
+                            let bytes = &data.vertex_buffer.raw_data()[range.start as usize
+                                * vertex_size
+                                ..range.end as usize * vertex_size];
+                            entry.buffer.set_buffer_sub_data(
+                                state,
+                                0,
+                                range.start as usize * vertex_size,
+                                bytes,
+                            );
+                        }
+                        _ => {
+                            // Content has changed, upload new content.
+                            entry
+                                .buffer
+                                .set_buffer_data(state, 0, data.vertex_buffer.raw_data());
+                            entry
+                                .buffer
+                                .bind(state)
+                                .set_triangles(data.geometry_buffer.triangles_ref());
+                        }
+                    }
+
+                    entry.vertices_hash = vertices_hash;
+                    entry.triangles_hash = triangles_hash;
+                    entry.vertex_count = vertex_count;
                 }
 
                 entry.time_to_live = DEFAULT_RESOURCE_LIFETIME;
 
                 &mut self.buffer.get_mut(&data.cache_entry).unwrap().buffer
             } else {
-                let index = create_geometry_buffer(&data, state, &mut self.buffer);
+                let index = create_geometry_buffer(&mut data, state, &mut self.buffer);
                 &mut self.buffer.get_mut(&index).unwrap().buffer
             }
         } else {
-            let index = create_geometry_buffer(&data, state, &mut self.buffer);
+            let index = create_geometry_buffer(&mut data, state, &mut self.buffer);
             &mut self.buffer.get_mut(&index).unwrap().buffer
         }
     }
@@ -88,8 +122,18 @@ impl GeometryCache {
         for entry in self.buffer.iter_mut() {
             entry.time_to_live -= dt;
         }
+    }
+
+    /// Destroys expired entries until the budget is exhausted, remaining ones will be destroyed on
+    /// next calls.
+    pub fn collect_garbage(&mut self, budget: &TimeBudget) {
+        scope_profile!();
 
         for i in 0..self.buffer.len() {
+            if budget.is_exhausted() {
+                break;
+            }
+
             if let Some(entry) = self.buffer.get_raw(i) {
                 if entry.time_to_live <= 0.0 {
                     self.buffer.free_raw(i);
@@ -5,6 +5,7 @@ use crate::{
         sparse::SparseBuffer,
         sstorage::ImmutableString,
     },
+    engine::gc::TimeBudget,
     material::shader::{Shader, ShaderResource},
     renderer::{
         cache::CacheEntry,
@@ -112,8 +113,18 @@ impl ShaderCache {
         for entry in self.buffer.iter_mut() {
             entry.time_to_live -= dt;
         }
+    }
+
+    /// Destroys expired entries until the budget is exhausted, remaining ones will be destroyed on
+    /// next calls.
+    pub fn collect_garbage(&mut self, budget: &TimeBudget) {
+        scope_profile!();
 
         for i in 0..self.buffer.len() {
+            if budget.is_exhausted() {
+                break;
+            }
+
             if let Some(entry) = self.buffer.get_raw(i) {
                 if entry.time_to_live <= 0.0 {
                     self.buffer.free_raw(i);
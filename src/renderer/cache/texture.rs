@@ -4,6 +4,7 @@ use crate::{
         log::{Log, MessageKind},
         scope_profile,
     },
+    engine::gc::TimeBudget,
     renderer::{
         cache::CacheEntry,
         framework::{
@@ -187,8 +188,15 @@ impl TextureCache {
         for entry in self.map.values_mut() {
             entry.time_to_live -= dt;
         }
+    }
+
+    /// Destroys expired textures until the budget is exhausted, remaining ones will be destroyed on
+    /// next calls.
+    pub fn collect_garbage(&mut self, budget: &TimeBudget) {
+        scope_profile!();
 
-        self.map.retain(|_, v| v.time_to_live > 0.0);
+        self.map
+            .retain(|_, v| v.time_to_live > 0.0 || budget.is_exhausted());
     }
 
     pub fn clear(&mut self) {
@@ -0,0 +1,298 @@
+use crate::{
+    core::{
+        algebra::Vector4,
+        math::{frustum::Frustum, Rect},
+        pool::Handle,
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            geometry_buffer::{
+                AttributeDefinition, AttributeKind, BufferBuilder, ElementKind, GeometryBuffer,
+                GeometryBufferBuilder, GeometryBufferKind,
+            },
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::GpuTexture,
+            state::PipelineState,
+        },
+        RenderPassStatistics, TextureCache,
+    },
+    scene::{
+        camera::Camera,
+        foliage::{Foliage, FoliageInstance, FoliageLayer},
+        graph::Graph,
+        node::Node,
+    },
+};
+use fxhash::FxHashMap;
+use fyrox_resource::entry::{TimedEntry, DEFAULT_RESOURCE_LIFETIME};
+use std::{cell::RefCell, rc::Rc};
+
+struct FoliageShader {
+    program: GpuProgram,
+    view_projection_matrix: UniformLocation,
+    world_matrix: UniformLocation,
+    camera_position: UniformLocation,
+    frustum_planes: UniformLocation,
+    bounding_radius: UniformLocation,
+    draw_distance: UniformLocation,
+    fade_distance: UniformLocation,
+    time: UniformLocation,
+    wind_parameters: UniformLocation,
+    wind_influence: UniformLocation,
+    diffuse_texture: UniformLocation,
+    color: UniformLocation,
+}
+
+impl FoliageShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/foliage_fs.glsl");
+        let vertex_source = include_str!("shaders/foliage_vs.glsl");
+        let program =
+            GpuProgram::from_source(state, "FoliageShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            view_projection_matrix: program
+                .uniform_location(state, &ImmutableString::new("viewProjectionMatrix"))?,
+            world_matrix: program.uniform_location(state, &ImmutableString::new("worldMatrix"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            frustum_planes: program
+                .uniform_location(state, &ImmutableString::new("frustumPlanes"))?,
+            bounding_radius: program
+                .uniform_location(state, &ImmutableString::new("boundingRadius"))?,
+            draw_distance: program
+                .uniform_location(state, &ImmutableString::new("drawDistance"))?,
+            fade_distance: program
+                .uniform_location(state, &ImmutableString::new("fadeDistance"))?,
+            time: program.uniform_location(state, &ImmutableString::new("time"))?,
+            wind_parameters: program
+                .uniform_location(state, &ImmutableString::new("windParameters"))?,
+            wind_influence: program
+                .uniform_location(state, &ImmutableString::new("windInfluence"))?,
+            diffuse_texture: program
+                .uniform_location(state, &ImmutableString::new("diffuseTexture"))?,
+            color: program.uniform_location(state, &ImmutableString::new("color"))?,
+            program,
+        })
+    }
+}
+
+// Geometry of a layer: vertices of the layer's surface and a buffer with instances.
+struct LayerGeometry {
+    buffer: GeometryBuffer,
+    surface_hash: u64,
+    surface_radius: f32,
+    instances_hash: u64,
+    instance_count: usize,
+}
+
+fn create_layer_geometry(
+    state: &mut PipelineState,
+    layer: &FoliageLayer,
+) -> Result<LayerGeometry, FrameworkError> {
+    // Must be calculated before locking the surface.
+    let surface_radius = layer.surface_radius();
+
+    let data = layer.surface.lock();
+
+    let buffer = GeometryBufferBuilder::new(ElementKind::Triangle)
+        .with_buffer_builder(BufferBuilder::from_vertex_buffer(
+            &data.vertex_buffer,
+            GeometryBufferKind::StaticDraw,
+        ))
+        // Buffer for instance data.
+        .with_buffer_builder(
+            BufferBuilder::new::<FoliageInstance>(
+                GeometryBufferKind::StaticDraw,
+                Some(layer.instances()),
+            )
+            // Position + rotation.
+            .with_attribute(AttributeDefinition {
+                location: 8,
+                kind: AttributeKind::Float4,
+                normalized: false,
+                divisor: 1,
+            })
+            // Scale.
+            .with_attribute(AttributeDefinition {
+                location: 9,
+                kind: AttributeKind::Float,
+                normalized: false,
+                divisor: 1,
+            }),
+        )
+        .build(state)?;
+
+    buffer
+        .bind(state)
+        .set_triangles(data.geometry_buffer.triangles_ref());
+
+    Ok(LayerGeometry {
+        buffer,
+        surface_hash: data.content_hash(),
+        surface_radius,
+        instances_hash: layer.instances_hash(),
+        instance_count: layer.instances().len(),
+    })
+}
+
+/// Renders foliage layers into G-Buffer using hardware instancing.
+pub struct FoliageRenderer {
+    shader: FoliageShader,
+    geometry: FxHashMap<(Handle<Node>, usize, u64), TimedEntry<LayerGeometry>>,
+}
+
+pub(crate) struct FoliageRenderContext<'a, 'b, 'c> {
+    pub state: &'a mut PipelineState,
+    pub framebuffer: &'b mut FrameBuffer,
+    pub graph: &'c Graph,
+    pub camera: &'c Camera,
+    pub white_dummy: Rc<RefCell<GpuTexture>>,
+    pub viewport: Rect<i32>,
+    pub textures: &'a mut TextureCache,
+}
+
+impl FoliageRenderer {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: FoliageShader::new(state)?,
+            geometry: Default::default(),
+        })
+    }
+
+    pub fn update_caches(&mut self, dt: f32) {
+        for entry in self.geometry.values_mut() {
+            entry.time_to_live -= dt;
+        }
+        self.geometry.retain(|_, v| v.time_to_live > 0.0);
+    }
+
+    pub fn flush(&mut self) {
+        self.geometry.clear();
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        args: FoliageRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let FoliageRenderContext {
+            state,
+            framebuffer,
+            graph,
+            camera,
+            white_dummy,
+            viewport,
+            textures,
+        } = args;
+
+        let view_projection = camera.view_projection_matrix();
+        let frustum = Frustum::from_view_projection_matrix(view_projection).unwrap_or_default();
+        let frustum_planes = frustum
+            .planes()
+            .iter()
+            .map(|p| Vector4::new(p.normal.x, p.normal.y, p.normal.z, p.d))
+            .collect::<Vec<_>>();
+        let camera_position = camera.global_position();
+
+        for (handle, foliage) in graph.pair_iter().filter_map(|(handle, node)| {
+            if !node.global_visibility() || !node.is_globally_enabled() {
+                return None;
+            }
+
+            node.cast::<Foliage>().map(|foliage| (handle, foliage))
+        }) {
+            if !frustum.is_intersects_aabb(&foliage.world_bounding_box()) {
+                continue;
+            }
+
+            let world_matrix = foliage.global_transform();
+            // Culling spheres must take the scale of the foliage into account.
+            let max_scale = (0..3)
+                .map(|i| world_matrix.column(i).xyz().norm())
+                .fold(0.0, f32::max);
+            let wind_direction = foliage.wind_direction();
+            let wind_parameters = Vector4::new(
+                wind_direction.x,
+                wind_direction.y,
+                foliage.wind_strength(),
+                foliage.wind_frequency(),
+            );
+
+            for (layer_index, layer) in foliage.layers().iter().enumerate() {
+                if layer.instances().is_empty() || layer.draw_distance <= 0.0 {
+                    continue;
+                }
+
+                let key = (handle, layer_index, layer.surface.key());
+                let surface_hash = layer.surface.lock().content_hash();
+                let is_outdated = match self.geometry.get(&key) {
+                    Some(entry) => {
+                        entry.surface_hash != surface_hash
+                            || entry.instances_hash != layer.instances_hash()
+                            || entry.instance_count != layer.instances().len()
+                    }
+                    None => true,
+                };
+                if is_outdated {
+                    self.geometry.insert(
+                        key,
+                        TimedEntry {
+                            value: create_layer_geometry(state, layer)?,
+                            time_to_live: DEFAULT_RESOURCE_LIFETIME,
+                        },
+                    );
+                }
+                let geometry = self.geometry.get_mut(&key).unwrap();
+                geometry.time_to_live = DEFAULT_RESOURCE_LIFETIME;
+
+                let diffuse_texture = layer
+                    .diffuse_texture
+                    .as_ref()
+                    .and_then(|t| textures.get(state, t))
+                    .unwrap_or_else(|| white_dummy.clone());
+
+                let shader = &self.shader;
+                statistics += framebuffer.draw_instances(
+                    geometry.instance_count,
+                    &geometry.buffer,
+                    state,
+                    viewport,
+                    &shader.program,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write: Default::default(),
+                        depth_write: true,
+                        stencil_test: None,
+                        depth_test: true,
+                        blend: None,
+                        stencil_op: Default::default(),
+                    },
+                    |mut program_binding| {
+                        program_binding
+                            .set_matrix4(&shader.view_projection_matrix, &view_projection)
+                            .set_matrix4(&shader.world_matrix, &world_matrix)
+                            .set_vector3(&shader.camera_position, &camera_position)
+                            .set_vector4_slice(&shader.frustum_planes, &frustum_planes)
+                            .set_f32(&shader.bounding_radius, geometry.surface_radius * max_scale)
+                            .set_f32(&shader.draw_distance, layer.draw_distance)
+                            .set_f32(&shader.fade_distance, layer.fade_distance)
+                            .set_f32(&shader.time, foliage.time())
+                            .set_vector4(&shader.wind_parameters, &wind_parameters)
+                            .set_f32(&shader.wind_influence, layer.wind_influence)
+                            .set_texture(&shader.diffuse_texture, &diffuse_texture)
+                            .set_srgb_color(&shader.color, &layer.color);
+                    },
+                );
+            }
+        }
+
+        Ok(statistics)
+    }
+}
@@ -126,6 +126,7 @@ impl ForwardRenderer {
                                 light_position: &Default::default(),
                                 blend_shapes_storage: blend_shapes_storage.as_ref(),
                                 blend_shapes_weights: &instance.blend_shapes_weights,
+                                light_probe: &Default::default(),
                                 normal_dummy: normal_dummy.clone(),
                                 white_dummy: white_dummy.clone(),
                                 black_dummy: black_dummy.clone(),
@@ -0,0 +1,559 @@
+//! Frame graph is a description of a frame as a set of render passes and resources (render
+//! targets) they use. See [`FrameGraph`] docs for more info.
+
+use crate::renderer::framework::{
+    error::FrameworkError,
+    framebuffer::{Attachment, AttachmentKind, FrameBuffer},
+    gpu_texture::{GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter, PixelKind},
+    state::PipelineState,
+};
+use fxhash::FxHashMap;
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+    rc::Rc,
+};
+
+/// A handle of a resource of a frame graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceHandle(usize);
+
+/// Description of a transient render target, that is used to find a compatible target in the
+/// pool.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TransientTextureDescriptor {
+    /// Width of the target in pixels.
+    pub width: usize,
+    /// Height of the target in pixels.
+    pub height: usize,
+    /// Pixel format of the target.
+    pub pixel_kind: PixelKind,
+}
+
+#[derive(Debug)]
+enum ResourceKind {
+    // A resource, that is owned by someone else (for example the back buffer or the G-Buffer of a
+    // scene).
+    Imported,
+    // A resource, that lives only while the graph is executed. Its memory is taken from a pool and
+    // could be shared with other transient resources.
+    Transient(TransientTextureDescriptor),
+}
+
+#[derive(Debug)]
+struct ResourceNode {
+    name: String,
+    kind: ResourceKind,
+    output: bool,
+}
+
+/// A render pass of a frame graph. `P` is a user-defined payload of a pass, usually an
+/// identifier of the code that must be executed.
+#[derive(Debug)]
+pub struct PassNode<P> {
+    name: String,
+    payload: P,
+    reads: Vec<ResourceHandle>,
+    writes: Vec<ResourceHandle>,
+    side_effects: bool,
+}
+
+impl<P> PassNode<P> {
+    /// Declares that the pass reads the given resource.
+    pub fn read(&mut self, resource: ResourceHandle) -> &mut Self {
+        self.reads.push(resource);
+        self
+    }
+
+    /// Declares that the pass writes the given resource.
+    pub fn write(&mut self, resource: ResourceHandle) -> &mut Self {
+        self.writes.push(resource);
+        self
+    }
+
+    /// Declares that the pass does something, that is not described by its outputs (for example,
+    /// reads the pixels back to CPU). Such pass is never culled.
+    pub fn with_side_effects(&mut self) -> &mut Self {
+        self.side_effects = true;
+        self
+    }
+
+    /// Returns name of the pass.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns payload of the pass.
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+}
+
+/// All possible errors that may occur during compilation of a frame graph.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FrameGraphError {
+    /// A pass reads a transient resource, that is not written by any previous pass.
+    UninitializedRead {
+        /// Name of the pass.
+        pass: String,
+        /// Name of the resource.
+        resource: String,
+    },
+}
+
+impl Display for FrameGraphError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameGraphError::UninitializedRead { pass, resource } => write!(
+                f,
+                "Pass {pass} reads transient resource {resource}, that is not written before"
+            ),
+        }
+    }
+}
+
+impl From<FrameGraphError> for FrameworkError {
+    fn from(e: FrameGraphError) -> Self {
+        FrameworkError::Custom(e.to_string())
+    }
+}
+
+/// Frame graph is a description of a frame as a set of render passes and resources they use. Each
+/// pass declares which resources it reads and writes, this information is used to:
+///
+/// - Cull passes, that do not contribute to the outputs of the graph. For example, a
+///   post-processing effect, whose result is not used by anything, will not be executed.
+/// - Allocate transient render targets (intermediate targets, that are needed only during the
+///   frame) from a [`TransientResourcePool`]. Transient resources with non-overlapping lifetimes
+///   and compatible descriptors share the same render target (aliasing), and the pool is shared by
+///   every graph of the renderer, so scenes and cameras don't need their own intermediate targets.
+///
+/// Passes are executed in the order they were added, so a pass must be added after every pass,
+/// that writes resources it reads. Execution itself is done by the user of the graph: a compiled
+/// graph yields the passes, that survived culling, and the payload of each pass tells what code
+/// to run.
+///
+/// ```rust
+/// use fyrox::renderer::{
+///     framegraph::{FrameGraph, TransientTextureDescriptor},
+///     framework::gpu_texture::PixelKind,
+/// };
+///
+/// let mut graph = FrameGraph::new();
+/// let frame = graph.import("Frame");
+/// let temp = graph.create_transient(
+///     "Temp",
+///     TransientTextureDescriptor {
+///         width: 1920,
+///         height: 1080,
+///         pixel_kind: PixelKind::RGBA8,
+///     },
+/// );
+/// graph.add_pass("Scene", "Scene").write(frame);
+/// graph.add_pass("Blur", "Blur").read(frame).write(temp);
+/// graph.add_pass("Resolve", "Resolve").read(temp).write(frame);
+/// // Nobody reads the output of this pass, it will be culled.
+/// graph.add_pass("Unused", "Unused").read(frame).write(temp);
+/// graph.mark_output(frame);
+///
+/// let compiled = graph.compile().unwrap();
+/// let passes = compiled.passes().map(|pass| *pass.payload()).collect::<Vec<_>>();
+/// assert_eq!(passes, ["Scene", "Blur", "Resolve"]);
+/// ```
+#[derive(Debug)]
+pub struct FrameGraph<P> {
+    resources: Vec<ResourceNode>,
+    passes: Vec<PassNode<P>>,
+}
+
+impl<P> Default for FrameGraph<P> {
+    fn default() -> Self {
+        Self {
+            resources: Default::default(),
+            passes: Default::default(),
+        }
+    }
+}
+
+impl<P> FrameGraph<P> {
+    /// Creates a new empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_resource(&mut self, name: &str, kind: ResourceKind) -> ResourceHandle {
+        self.resources.push(ResourceNode {
+            name: name.to_owned(),
+            kind,
+            output: false,
+        });
+        ResourceHandle(self.resources.len() - 1)
+    }
+
+    /// Registers a resource, that is owned by someone else (for example the back buffer).
+    pub fn import(&mut self, name: &str) -> ResourceHandle {
+        self.add_resource(name, ResourceKind::Imported)
+    }
+
+    /// Registers a transient render target, that will be taken from a pool.
+    pub fn create_transient(
+        &mut self,
+        name: &str,
+        descriptor: TransientTextureDescriptor,
+    ) -> ResourceHandle {
+        self.add_resource(name, ResourceKind::Transient(descriptor))
+    }
+
+    /// Marks a resource as an output of the graph. Only passes, that contribute to the outputs
+    /// (directly or indirectly), are executed.
+    pub fn mark_output(&mut self, resource: ResourceHandle) {
+        self.resources[resource.0].output = true;
+    }
+
+    /// Adds a new pass. Use the returned reference to declare resources of the pass.
+    pub fn add_pass(&mut self, name: &str, payload: P) -> &mut PassNode<P> {
+        self.passes.push(PassNode {
+            name: name.to_owned(),
+            payload,
+            reads: Default::default(),
+            writes: Default::default(),
+            side_effects: false,
+        });
+        self.passes.last_mut().unwrap()
+    }
+
+    /// Validates the graph, culls unused passes and assigns transient resources to render
+    /// targets.
+    pub fn compile(self) -> Result<CompiledFrameGraph<P>, FrameGraphError> {
+        let is_transient = |handle: &ResourceHandle| {
+            matches!(self.resources[handle.0].kind, ResourceKind::Transient(_))
+        };
+
+        // Validate reads of transient resources.
+        let mut written = vec![false; self.resources.len()];
+        for pass in self.passes.iter() {
+            for read in pass.reads.iter().filter(|read| is_transient(read)) {
+                if !written[read.0] {
+                    return Err(FrameGraphError::UninitializedRead {
+                        pass: pass.name.clone(),
+                        resource: self.resources[read.0].name.clone(),
+                    });
+                }
+            }
+            for write in pass.writes.iter() {
+                written[write.0] = true;
+            }
+        }
+
+        // Cull passes, that do not contribute to the outputs, walking from the last pass to the
+        // first one.
+        let mut needed = self
+            .resources
+            .iter()
+            .map(|resource| resource.output)
+            .collect::<Vec<_>>();
+        let mut alive = vec![false; self.passes.len()];
+        for (index, pass) in self.passes.iter().enumerate().rev() {
+            if pass.side_effects || pass.writes.iter().any(|write| needed[write.0]) {
+                alive[index] = true;
+                for read in pass.reads.iter() {
+                    needed[read.0] = true;
+                }
+            }
+        }
+
+        let mut culled_passes = Vec::new();
+        let mut passes = Vec::new();
+        for (pass, alive) in self.passes.into_iter().zip(alive) {
+            if alive {
+                passes.push(pass);
+            } else {
+                culled_passes.push(pass.name);
+            }
+        }
+
+        // Compute lifetimes of transient resources in terms of indices of alive passes.
+        let mut lifetimes = vec![None::<(usize, usize)>; self.resources.len()];
+        for (index, pass) in passes.iter().enumerate() {
+            for resource in pass.reads.iter().chain(pass.writes.iter()) {
+                let lifetime = lifetimes[resource.0].get_or_insert((index, index));
+                lifetime.1 = index;
+            }
+        }
+
+        // Assign transient resources to render targets. Resources are processed in order of their
+        // first use, a target could be reused if its previous resource is not used anymore.
+        let mut transients = self
+            .resources
+            .iter()
+            .enumerate()
+            .filter_map(|(index, resource)| match resource.kind {
+                ResourceKind::Transient(descriptor) => {
+                    lifetimes[index].map(|lifetime| (index, descriptor, lifetime))
+                }
+                ResourceKind::Imported => None,
+            })
+            .collect::<Vec<_>>();
+        transients.sort_by_key(|(_, _, (first, _))| *first);
+
+        let mut targets: Vec<(TransientTextureDescriptor, usize)> = Vec::new();
+        let mut resource_targets = vec![None; self.resources.len()];
+        for (resource, descriptor, (first, last)) in transients {
+            let target = match targets
+                .iter()
+                .position(|(other, other_last)| *other == descriptor && *other_last < first)
+            {
+                Some(target) => {
+                    targets[target].1 = last;
+                    target
+                }
+                None => {
+                    targets.push((descriptor, last));
+                    targets.len() - 1
+                }
+            };
+            resource_targets[resource] = Some(target);
+        }
+
+        Ok(CompiledFrameGraph {
+            passes,
+            culled_passes,
+            resource_targets,
+            targets: targets
+                .into_iter()
+                .map(|(descriptor, _)| descriptor)
+                .collect(),
+        })
+    }
+}
+
+/// A frame graph, that is ready for execution. See [`FrameGraph`] docs for more info.
+#[derive(Debug)]
+pub struct CompiledFrameGraph<P> {
+    passes: Vec<PassNode<P>>,
+    culled_passes: Vec<String>,
+    resource_targets: Vec<Option<usize>>,
+    targets: Vec<TransientTextureDescriptor>,
+}
+
+impl<P> CompiledFrameGraph<P> {
+    /// Returns the passes, that must be executed, in execution order.
+    pub fn passes(&self) -> impl Iterator<Item = &PassNode<P>> {
+        self.passes.iter()
+    }
+
+    /// Returns names of the passes, that were culled.
+    pub fn culled_passes(&self) -> &[String] {
+        &self.culled_passes
+    }
+
+    /// Returns index of the render target, that is used by the given transient resource. `None`
+    /// if the resource is not transient or it is not used by any executed pass.
+    pub fn target_index(&self, resource: ResourceHandle) -> Option<usize> {
+        self.resource_targets.get(resource.0).cloned().flatten()
+    }
+
+    /// Returns descriptors of every render target, that is needed to execute the graph.
+    pub fn targets(&self) -> &[TransientTextureDescriptor] {
+        &self.targets
+    }
+}
+
+struct PooledTarget {
+    framebuffer: FrameBuffer,
+    unused_frames: usize,
+}
+
+/// A pool of render targets, that are used for transient resources of frame graphs. The same
+/// pool is shared by every graph, and targets, that were not used for a while, are released.
+#[derive(Default)]
+pub struct TransientResourcePool {
+    targets: FxHashMap<TransientTextureDescriptor, Vec<PooledTarget>>,
+    // Targets of the graph, that was prepared last. Each entry is a descriptor and an index of the
+    // target in the list of targets of this descriptor.
+    bindings: Vec<(TransientTextureDescriptor, usize)>,
+}
+
+impl TransientResourcePool {
+    /// Amount of frames, after which an unused target is released.
+    pub const MAX_UNUSED_FRAMES: usize = 120;
+
+    /// Makes sure that there are enough render targets to execute the given graph, and binds them
+    /// to transient resources of the graph.
+    pub fn prepare<P>(
+        &mut self,
+        state: &mut PipelineState,
+        graph: &CompiledFrameGraph<P>,
+    ) -> Result<(), FrameworkError> {
+        self.bindings.clear();
+
+        let mut used = FxHashMap::<TransientTextureDescriptor, usize>::default();
+        for descriptor in graph.targets() {
+            let index = used.entry(*descriptor).or_default();
+            let targets = self.targets.entry(*descriptor).or_default();
+            if *index == targets.len() {
+                targets.push(PooledTarget {
+                    framebuffer: make_transient_framebuffer(state, descriptor)?,
+                    unused_frames: 0,
+                });
+            }
+            targets[*index].unused_frames = 0;
+            self.bindings.push((*descriptor, *index));
+            *index += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Returns a frame buffer of the given transient resource of the graph, that was prepared
+    /// last.
+    pub fn framebuffer<P>(
+        &mut self,
+        graph: &CompiledFrameGraph<P>,
+        resource: ResourceHandle,
+    ) -> Option<&mut FrameBuffer> {
+        let (descriptor, index) = *self.bindings.get(graph.target_index(resource)?)?;
+        self.targets
+            .get_mut(&descriptor)
+            .and_then(|targets| targets.get_mut(index))
+            .map(|target| &mut target.framebuffer)
+    }
+
+    /// Returns a color texture of the given transient resource of the graph, that was prepared
+    /// last.
+    pub fn texture<P>(
+        &mut self,
+        graph: &CompiledFrameGraph<P>,
+        resource: ResourceHandle,
+    ) -> Option<Rc<RefCell<GpuTexture>>> {
+        self.framebuffer(graph, resource)
+            .map(|framebuffer| framebuffer.color_attachments()[0].texture.clone())
+    }
+
+    /// Releases render targets, that were not used for [`Self::MAX_UNUSED_FRAMES`] frames. Must be
+    /// called once per frame.
+    pub fn update(&mut self) {
+        for targets in self.targets.values_mut() {
+            for target in targets.iter_mut() {
+                target.unused_frames += 1;
+            }
+            // Targets are always taken from the beginning of the list, so unused ones are at its end.
+            while let Some(target) = targets.last() {
+                if target.unused_frames > Self::MAX_UNUSED_FRAMES {
+                    targets.pop();
+                } else {
+                    break;
+                }
+            }
+        }
+        self.targets.retain(|_, targets| !targets.is_empty());
+    }
+
+    /// Returns total amount of render targets in the pool.
+    pub fn target_count(&self) -> usize {
+        self.targets.values().map(|targets| targets.len()).sum()
+    }
+}
+
+fn make_transient_framebuffer(
+    state: &mut PipelineState,
+    descriptor: &TransientTextureDescriptor,
+) -> Result<FrameBuffer, FrameworkError> {
+    let texture = GpuTexture::new(
+        state,
+        GpuTextureKind::Rectangle {
+            width: descriptor.width,
+            height: descriptor.height,
+        },
+        descriptor.pixel_kind,
+        MinificationFilter::Linear,
+        MagnificationFilter::Linear,
+        1,
+        None,
+    )?;
+
+    FrameBuffer::new(
+        state,
+        None,
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(texture)),
+        }],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn descriptor(width: usize) -> TransientTextureDescriptor {
+        TransientTextureDescriptor {
+            width,
+            height: 100,
+            pixel_kind: PixelKind::RGBA8,
+        }
+    }
+
+    #[test]
+    fn test_frame_graph_compilation() {
+        let mut graph = FrameGraph::new();
+        let frame = graph.import("Frame");
+        let a = graph.create_transient("A", descriptor(100));
+        let b = graph.create_transient("B", descriptor(100));
+        let c = graph.create_transient("C", descriptor(100));
+        let small = graph.create_transient("Small", descriptor(50));
+        let unused = graph.create_transient("Unused", descriptor(100));
+
+        graph.add_pass("Scene", 0).write(frame);
+        graph.add_pass("First", 1).read(frame).write(a);
+        graph.add_pass("Second", 2).read(a).write(b);
+        graph.add_pass("Third", 3).read(b).write(c).write(small);
+        graph
+            .add_pass("Resolve", 4)
+            .read(c)
+            .read(small)
+            .write(frame);
+        graph.add_pass("Unused", 5).read(frame).write(unused);
+        graph
+            .add_pass("Readback", 6)
+            .read(frame)
+            .with_side_effects();
+        graph.mark_output(frame);
+
+        let compiled = graph.compile().unwrap();
+        assert_eq!(
+            compiled
+                .passes()
+                .map(|pass| *pass.payload())
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4, 6]
+        );
+        assert_eq!(compiled.culled_passes(), ["Unused".to_string()]);
+
+        // A and C have non-overlapping lifetimes, so they share the same target.
+        assert_eq!(compiled.target_index(a), Some(0));
+        assert_eq!(compiled.target_index(b), Some(1));
+        assert_eq!(compiled.target_index(c), Some(0));
+        assert_eq!(compiled.target_index(small), Some(2));
+        assert_eq!(compiled.target_index(unused), None);
+        assert_eq!(compiled.target_index(frame), None);
+        assert_eq!(
+            compiled.targets(),
+            [descriptor(100), descriptor(100), descriptor(50)]
+        );
+    }
+
+    #[test]
+    fn test_frame_graph_uninitialized_read() {
+        let mut graph = FrameGraph::new();
+        let temp = graph.create_transient("Temp", descriptor(100));
+        graph.add_pass("Broken", ()).read(temp).with_side_effects();
+        assert_eq!(
+            graph.compile().unwrap_err(),
+            FrameGraphError::UninitializedRead {
+                pass: "Broken".to_string(),
+                resource: "Temp".to_string()
+            }
+        );
+    }
+}
@@ -219,6 +219,78 @@ impl FrameBuffer {
         self.fbo
     }
 
+    /// Reads RGBA8 pixels of the given region of the first color attachment (or the back buffer).
+    /// Rows are returned in OpenGL order, from bottom to top. It stalls the pipeline until every
+    /// previous command is finished, so it should not be used often.
+    pub fn read_pixels(&self, state: &mut PipelineState, region: Rect<i32>) -> Vec<u8> {
+        let mut pixels = vec![0; region.w().max(0) as usize * region.h().max(0) as usize * 4];
+
+        unsafe {
+            state.set_framebuffer(self.fbo);
+            state.gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+            state.gl.read_pixels(
+                region.x(),
+                region.y(),
+                region.w(),
+                region.h(),
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut pixels),
+            );
+        }
+
+        pixels
+    }
+
+    /// Same as [`Self::read_pixels`], but reads RGBA pixels as 32-bit floats. It should be used
+    /// with floating-point attachments to preserve the precision and the range of the values.
+    pub fn read_pixels_f32(&self, state: &mut PipelineState, region: Rect<i32>) -> Vec<f32> {
+        let mut bytes = vec![0; region.w().max(0) as usize * region.h().max(0) as usize * 4 * 4];
+
+        unsafe {
+            state.set_framebuffer(self.fbo);
+            state.gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+            state.gl.read_pixels(
+                region.x(),
+                region.y(),
+                region.w(),
+                region.h(),
+                glow::RGBA,
+                glow::FLOAT,
+                glow::PixelPackData::Slice(&mut bytes),
+            );
+        }
+
+        bytes
+            .chunks_exact(4)
+            .map(|c| f32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+            .collect()
+    }
+
+    /// Same as [`Self::read_pixels`], but reads the color attachment with the given index. Must
+    /// not be used with the back buffer.
+    pub fn read_attachment_pixels(
+        &self,
+        state: &mut PipelineState,
+        attachment_index: usize,
+        region: Rect<i32>,
+    ) -> Vec<u8> {
+        state.set_framebuffer(self.fbo);
+        unsafe {
+            state
+                .gl
+                .read_buffer(glow::COLOR_ATTACHMENT0 + attachment_index as u32);
+        }
+
+        let pixels = self.read_pixels(state, region);
+
+        unsafe {
+            state.gl.read_buffer(glow::COLOR_ATTACHMENT0);
+        }
+
+        pixels
+    }
+
     pub fn clear(
         &mut self,
         state: &mut PipelineState,
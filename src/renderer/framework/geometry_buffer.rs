@@ -334,6 +334,33 @@ impl GeometryBuffer {
         buffer.size_bytes = size;
     }
 
+    /// Overwrites a part of the given buffer starting from the given offset (in bytes). The buffer
+    /// must be large enough to contain the data.
+    pub fn set_buffer_sub_data<T>(
+        &mut self,
+        state: &mut PipelineState,
+        buffer: usize,
+        offset_bytes: usize,
+        data: &[T],
+    ) {
+        scope_profile!();
+
+        let buffer = &mut self.buffers[buffer];
+
+        let size = std::mem::size_of_val(data);
+        assert!(offset_bytes + size <= buffer.size_bytes);
+
+        state.set_vertex_buffer_object(Some(buffer.id));
+
+        unsafe {
+            state.gl.buffer_sub_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                offset_bytes as i32,
+                array_as_u8_slice(data),
+            );
+        }
+    }
+
     pub fn bind<'a>(&'a self, state: &'a mut PipelineState) -> GeometryBufferBinding<'a> {
         scope_profile!();
 
@@ -6,7 +6,10 @@ use crate::{
         log::{Log, MessageKind},
         sstorage::ImmutableString,
     },
-    renderer::framework::{error::FrameworkError, gpu_texture::GpuTexture, state::PipelineState},
+    renderer::framework::{
+        error::FrameworkError, gpu_texture::GpuTexture, program_cache::program_key,
+        state::PipelineState,
+    },
 };
 use fxhash::FxHashMap;
 use glow::HasContext;
@@ -34,6 +37,7 @@ pub enum BuiltInUniform {
     BlendShapesStorage,
     BlendShapesWeights,
     BlendShapesCount,
+    LightProbe,
     // Must be last.
     Count,
 }
@@ -92,10 +96,15 @@ fn prepare_source_code(code: &str, gl_kind: GlKind) -> String {
     let mut full_source_code = "#version 330 core\n// include 'shared.glsl'\n".to_owned();
 
     if gl_kind == GlKind::OpenGLES {
-        full_source_code += r#"    
+        // Default precision of samplers is low on OpenGL ES, it is not enough for floating-point
+        // textures (depth, positions, matrices, etc.) on most of mobile GPUs.
+        full_source_code += r#"
             precision highp float;
-            precision lowp usampler2D;
-            precision lowp sampler3D;
+            precision highp int;
+            precision highp sampler2D;
+            precision highp samplerCube;
+            precision highp usampler2D;
+            precision highp sampler3D;
         "#;
     }
 
@@ -438,10 +447,88 @@ fn fetch_built_in_uniform_locations(
         fetch_uniform_location(state, program, "fyrox_blendShapesWeights");
     locations[BuiltInUniform::BlendShapesCount as usize] =
         fetch_uniform_location(state, program, "fyrox_blendShapesCount");
+    locations[BuiltInUniform::LightProbe as usize] =
+        fetch_uniform_location(state, program, "fyrox_lightProbe");
 
     locations
 }
 
+fn log_link_result(name: &str, link_message: &str) {
+    let msg = if link_message.is_empty() || link_message.chars().all(|c| c.is_whitespace()) {
+        format!("Shader {} linked successfully!", name)
+    } else {
+        format!(
+            "Shader {} linked successfully!\nAdditional info: {}",
+            name, link_message
+        )
+    };
+
+    Log::writeln(MessageKind::Information, msg);
+}
+
+// Tries to create a program from its binary stored in the program binary cache. Binaries could be
+// rejected by the driver (for example after driver update), such binaries are removed from the cache.
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn create_program_from_cache(
+    state: &mut PipelineState,
+    name: &str,
+    key: u64,
+) -> Option<glow::Program> {
+    let functions = state.program_binary_functions()?;
+    let (format, data) = state
+        .program_binary_cache()?
+        .get(key)
+        .map(|(format, data)| (format, data.to_vec()))?;
+
+    let program = state.gl.create_program().ok()?;
+    functions.set(program, format, &data);
+    if state.gl.get_program_link_status(program) {
+        Log::info(format!("Shader {} was loaded from the binary cache.", name));
+        Some(program)
+    } else {
+        state.gl.delete_program(program);
+        if let Some(cache) = state.program_binary_cache_mut() {
+            cache.remove(key);
+        }
+        None
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+unsafe fn create_program_from_cache(
+    _state: &mut PipelineState,
+    _name: &str,
+    _key: u64,
+) -> Option<glow::Program> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn store_program_in_cache(state: &mut PipelineState, key: u64, program: glow::Program) {
+    if let Some(functions) = state.program_binary_functions() {
+        if let Some((format, data)) = functions.get(program) {
+            if let Some(cache) = state.program_binary_cache_mut() {
+                cache.insert(key, format, data);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn mark_program_retrievable(state: &PipelineState, program: glow::Program) {
+    if state.program_binary_cache().is_some() {
+        if let Some(functions) = state.program_binary_functions() {
+            functions.set_retrievable(program);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+unsafe fn mark_program_retrievable(_state: &PipelineState, _program: glow::Program) {}
+
+#[cfg(target_arch = "wasm32")]
+unsafe fn store_program_in_cache(_state: &mut PipelineState, _key: u64, _program: glow::Program) {}
+
 impl GpuProgram {
     pub fn from_source(
         state: &mut PipelineState,
@@ -450,6 +537,16 @@ impl GpuProgram {
         fragment_source: &str,
     ) -> Result<GpuProgram, FrameworkError> {
         unsafe {
+            let key = program_key(
+                &prepare_source_code(vertex_source, state.gl_kind()),
+                &prepare_source_code(fragment_source, state.gl_kind()),
+                state.gl_kind(),
+            );
+
+            if let Some(program) = create_program_from_cache(state, name, key) {
+                return Ok(Self::from_id(state, program));
+            }
+
             let vertex_shader = create_shader(
                 state,
                 format!("{}_VertexShader", name),
@@ -469,6 +566,7 @@ impl GpuProgram {
             state.gl.delete_shader(vertex_shader);
             state.gl.attach_shader(program, fragment_shader);
             state.gl.delete_shader(fragment_shader);
+            mark_program_retrievable(state, program);
             state.gl.link_program(program);
             let status = state.gl.get_program_link_status(program);
             let link_message = state.gl.get_program_info_log(program);
@@ -483,29 +581,25 @@ impl GpuProgram {
                     error_message: link_message,
                 })
             } else {
-                let msg =
-                    if link_message.is_empty() || link_message.chars().all(|c| c.is_whitespace()) {
-                        format!("Shader {} linked successfully!", name)
-                    } else {
-                        format!(
-                            "Shader {} linked successfully!\nAdditional info: {}",
-                            name, link_message
-                        )
-                    };
-
-                Log::writeln(MessageKind::Information, msg);
-
-                Ok(Self {
-                    state,
-                    id: program,
-                    thread_mark: PhantomData,
-                    uniform_locations: Default::default(),
-                    built_in_uniform_locations: fetch_built_in_uniform_locations(state, program),
-                })
+                log_link_result(name, &link_message);
+
+                store_program_in_cache(state, key, program);
+
+                Ok(Self::from_id(state, program))
             }
         }
     }
 
+    fn from_id(state: &mut PipelineState, program: glow::Program) -> Self {
+        Self {
+            state,
+            id: program,
+            thread_mark: PhantomData,
+            uniform_locations: Default::default(),
+            built_in_uniform_locations: fetch_built_in_uniform_locations(state, program),
+        }
+    }
+
     pub fn uniform_location_internal(
         &self,
         state: &PipelineState,
@@ -67,7 +67,7 @@ impl GpuTextureKind {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum PixelKind {
     R32F,
     R16F,
@@ -103,6 +103,9 @@ pub enum PixelKind {
     RG8RGTC,
     R11G11B10F,
     RGB10A2,
+    ETC2RGB,
+    ETC2RGBA,
+    ASTC4x4RGBA,
 }
 
 impl From<TexturePixelKind> for PixelKind {
@@ -133,6 +136,9 @@ impl From<TexturePixelKind> for PixelKind {
             TexturePixelKind::LuminanceAlpha16 => Self::LA16,
             TexturePixelKind::R32F => Self::R32F,
             TexturePixelKind::R16F => Self::R16F,
+            TexturePixelKind::ETC2RGB => Self::ETC2RGB,
+            TexturePixelKind::ETC2RGBA => Self::ETC2RGBA,
+            TexturePixelKind::ASTC4x4RGBA => Self::ASTC4x4RGBA,
         }
     }
 }
@@ -175,7 +181,10 @@ impl PixelKind {
             | Self::DXT3RGBA
             | Self::DXT5RGBA
             | Self::R8RGTC
-            | Self::RG8RGTC => None,
+            | Self::RG8RGTC
+            | Self::ETC2RGB
+            | Self::ETC2RGBA
+            | Self::ASTC4x4RGBA => None,
         }
     }
 
@@ -186,7 +195,10 @@ impl PixelKind {
             | Self::DXT3RGBA
             | Self::DXT5RGBA
             | Self::R8RGTC
-            | Self::RG8RGTC => true,
+            | Self::RG8RGTC
+            | Self::ETC2RGB
+            | Self::ETC2RGBA
+            | Self::ASTC4x4RGBA => true,
             // Explicit match for rest of formats instead of _ will help to not forget
             // to add new entry here.
             Self::RGBA16
@@ -250,6 +262,9 @@ impl PixelKind {
             | Self::DXT5RGBA
             | Self::R8RGTC
             | Self::RG8RGTC
+            | Self::ETC2RGB
+            | Self::ETC2RGBA
+            | Self::ASTC4x4RGBA
             | Self::RGB10A2
             | Self::LA8
             | Self::L8
@@ -304,11 +319,15 @@ fn image_3d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize, depth
         | PixelKind::D16
         | PixelKind::R16F => 2 * pixel_count,
         PixelKind::R8 | PixelKind::L8 | PixelKind::R8UI => pixel_count,
-        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC => {
+        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC | PixelKind::ETC2RGB => {
             let block_size = 8;
             ceil_div_4(width) * ceil_div_4(height) * ceil_div_4(depth) * block_size
         }
-        PixelKind::DXT3RGBA | PixelKind::DXT5RGBA | PixelKind::RG8RGTC => {
+        PixelKind::DXT3RGBA
+        | PixelKind::DXT5RGBA
+        | PixelKind::RG8RGTC
+        | PixelKind::ETC2RGBA
+        | PixelKind::ASTC4x4RGBA => {
             let block_size = 16;
             ceil_div_4(width) * ceil_div_4(height) * ceil_div_4(depth) * block_size
         }
@@ -340,11 +359,15 @@ fn image_2d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize) -> us
         | PixelKind::D16
         | PixelKind::R16F => 2 * pixel_count,
         PixelKind::R8 | PixelKind::L8 | PixelKind::R8UI => pixel_count,
-        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC => {
+        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC | PixelKind::ETC2RGB => {
             let block_size = 8;
             ceil_div_4(width) * ceil_div_4(height) * block_size
         }
-        PixelKind::DXT3RGBA | PixelKind::DXT5RGBA | PixelKind::RG8RGTC => {
+        PixelKind::DXT3RGBA
+        | PixelKind::DXT5RGBA
+        | PixelKind::RG8RGTC
+        | PixelKind::ETC2RGBA
+        | PixelKind::ASTC4x4RGBA => {
             let block_size = 16;
             ceil_div_4(width) * ceil_div_4(height) * block_size
         }
@@ -375,11 +398,15 @@ fn image_1d_size_bytes(pixel_kind: PixelKind, length: usize) -> usize {
         | PixelKind::D16
         | PixelKind::R16F => 2 * length,
         PixelKind::R8 | PixelKind::L8 | PixelKind::R8UI => length,
-        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC => {
+        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC | PixelKind::ETC2RGB => {
             let block_size = 8;
             ceil_div_4(length) * block_size
         }
-        PixelKind::DXT3RGBA | PixelKind::DXT5RGBA | PixelKind::RG8RGTC => {
+        PixelKind::DXT3RGBA
+        | PixelKind::DXT5RGBA
+        | PixelKind::RG8RGTC
+        | PixelKind::ETC2RGBA
+        | PixelKind::ASTC4x4RGBA => {
             let block_size = 16;
             ceil_div_4(length) * block_size
         }
@@ -718,6 +745,9 @@ impl<'a> TextureBinding<'a> {
                 PixelKind::DXT5RGBA => (0, 0, GL_COMPRESSED_RGBA_S3TC_DXT5_EXT, None),
                 PixelKind::R8RGTC => (0, 0, COMPRESSED_RED_RGTC1, None),
                 PixelKind::RG8RGTC => (0, 0, COMPRESSED_RG_RGTC2, None),
+                PixelKind::ETC2RGB => (0, 0, GL_COMPRESSED_RGB8_ETC2, None),
+                PixelKind::ETC2RGBA => (0, 0, GL_COMPRESSED_RGBA8_ETC2_EAC, None),
+                PixelKind::ASTC4x4RGBA => (0, 0, GL_COMPRESSED_RGBA_ASTC_4X4_KHR, None),
                 PixelKind::RGB32F => (glow::FLOAT, glow::RGB, glow::RGB32F, None),
                 PixelKind::RGBA32F => (glow::FLOAT, glow::RGBA, glow::RGBA32F, None),
                 PixelKind::RGBA16F => (glow::HALF_FLOAT, glow::RGBA, glow::RGBA16F, None),
@@ -976,6 +1006,9 @@ const GL_COMPRESSED_RGB_S3TC_DXT1_EXT: u32 = 0x83F0;
 const GL_COMPRESSED_RGBA_S3TC_DXT1_EXT: u32 = 0x83F1;
 const GL_COMPRESSED_RGBA_S3TC_DXT3_EXT: u32 = 0x83F2;
 const GL_COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+const GL_COMPRESSED_RGB8_ETC2: u32 = 0x9274;
+const GL_COMPRESSED_RGBA8_ETC2_EAC: u32 = 0x9278;
+const GL_COMPRESSED_RGBA_ASTC_4X4_KHR: u32 = 0x93B0;
 
 impl GpuTexture {
     /// Creates new GPU texture of specified kind. Mip count must be at least 1, it means
@@ -0,0 +1,326 @@
+//! Program binary cache allows to skip compilation and linking of GPU programs, that were already
+//! compiled on previous runs of an application. Compiled programs are stored in driver-specific binary
+//! format, so the cache is valid only for the same GPU, driver version and OpenGL version. The cache
+//! is automatically invalidated if any of these were changed.
+
+use crate::renderer::framework::state::{GlKind, PipelineState};
+use fxhash::{FxHashMap, FxHasher};
+use glow::HasContext;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{ffi::CStr, os::raw::c_void};
+use std::{
+    hash::{Hash, Hasher},
+    path::Path,
+};
+
+const MAGIC: &[u8; 4] = b"FPBC";
+const VERSION: u32 = 1;
+
+struct ProgramBinary {
+    format: u32,
+    data: Vec<u8>,
+}
+
+/// A set of compiled GPU programs in binary form, that could be saved to disk and loaded back.
+#[derive(Default)]
+pub struct ProgramBinaryCache {
+    device_id: u64,
+    binaries: FxHashMap<u64, ProgramBinary>,
+    modified: bool,
+}
+
+/// Calculates unique id of the current device, driver version and OpenGL version. Program binaries
+/// are compatible only between the same devices.
+pub fn device_id(state: &PipelineState) -> u64 {
+    let mut hasher = FxHasher::default();
+    unsafe {
+        state
+            .gl
+            .get_parameter_string(glow::VENDOR)
+            .hash(&mut hasher);
+        state
+            .gl
+            .get_parameter_string(glow::RENDERER)
+            .hash(&mut hasher);
+        state
+            .gl
+            .get_parameter_string(glow::VERSION)
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Calculates a key of a GPU program with the given source code.
+pub fn program_key(vertex_source: &str, fragment_source: &str, gl_kind: GlKind) -> u64 {
+    let mut hasher = FxHasher::default();
+    vertex_source.hash(&mut hasher);
+    fragment_source.hash(&mut hasher);
+    (gl_kind == GlKind::OpenGL).hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type GetProgramIvFn = unsafe extern "system" fn(u32, u32, *mut i32);
+#[cfg(not(target_arch = "wasm32"))]
+type GetProgramBinaryFn = unsafe extern "system" fn(u32, i32, *mut i32, *mut u32, *mut c_void);
+#[cfg(not(target_arch = "wasm32"))]
+type ProgramBinaryFn = unsafe extern "system" fn(u32, u32, *const c_void, i32);
+#[cfg(not(target_arch = "wasm32"))]
+type ProgramParameteriFn = unsafe extern "system" fn(u32, u32, i32);
+
+/// Raw OpenGL functions to get and set binaries of GPU programs. glow does not wrap these functions,
+/// so they are loaded separately using the same loader function as the OpenGL context.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Copy, Clone)]
+pub struct ProgramBinaryFunctions {
+    get_program_iv: GetProgramIvFn,
+    get_program_binary: GetProgramBinaryFn,
+    program_binary: ProgramBinaryFn,
+    program_parameter_i: ProgramParameteriFn,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ProgramBinaryFunctions {
+    /// Loads the functions using the given loader function. Returns `None` if the driver does not
+    /// support program binaries (OpenGL 4.1 or `GL_ARB_get_program_binary` is required).
+    ///
+    /// # Safety
+    ///
+    /// The loader must return pointers to the functions of the OpenGL context the renderer uses.
+    pub unsafe fn load<F>(mut loader: F) -> Option<Self>
+    where
+        F: FnMut(&CStr) -> *const c_void,
+    {
+        let mut load = |name: &[u8]| {
+            let pointer = loader(CStr::from_bytes_with_nul(name).ok()?);
+            if pointer.is_null() {
+                None
+            } else {
+                Some(pointer)
+            }
+        };
+
+        Some(Self {
+            get_program_iv: std::mem::transmute::<*const c_void, GetProgramIvFn>(load(
+                b"glGetProgramiv\0",
+            )?),
+            get_program_binary: std::mem::transmute::<*const c_void, GetProgramBinaryFn>(load(
+                b"glGetProgramBinary\0",
+            )?),
+            program_binary: std::mem::transmute::<*const c_void, ProgramBinaryFn>(load(
+                b"glProgramBinary\0",
+            )?),
+            program_parameter_i: std::mem::transmute::<*const c_void, ProgramParameteriFn>(load(
+                b"glProgramParameteri\0",
+            )?),
+        })
+    }
+
+    /// Tells the driver that the binary of the given program will be retrieved, must be called before
+    /// linking.
+    ///
+    /// # Safety
+    ///
+    /// The program must be created in the context the functions were loaded from.
+    pub unsafe fn set_retrievable(&self, program: glow::Program) {
+        (self.program_parameter_i)(program.0.get(), glow::PROGRAM_BINARY_RETRIEVABLE_HINT, 1);
+    }
+
+    /// Returns format and binary data of the given linked program.
+    ///
+    /// # Safety
+    ///
+    /// The program must be created in the context the functions were loaded from.
+    pub unsafe fn get(&self, program: glow::Program) -> Option<(u32, Vec<u8>)> {
+        let mut length = 0;
+        (self.get_program_iv)(program.0.get(), glow::PROGRAM_BINARY_LENGTH, &mut length);
+        if length <= 0 {
+            return None;
+        }
+
+        let mut data = vec![0u8; length as usize];
+        let mut written = 0;
+        let mut format = 0;
+        (self.get_program_binary)(
+            program.0.get(),
+            length,
+            &mut written,
+            &mut format,
+            data.as_mut_ptr() as *mut c_void,
+        );
+        if written <= 0 {
+            return None;
+        }
+        data.truncate(written as usize);
+        Some((format, data))
+    }
+
+    /// Loads the given binary into the program. Link status of the program must be checked after this
+    /// call, drivers reject binaries made by other drivers.
+    ///
+    /// # Safety
+    ///
+    /// The program must be created in the context the functions were loaded from.
+    pub unsafe fn set(&self, program: glow::Program, format: u32, data: &[u8]) {
+        (self.program_binary)(
+            program.0.get(),
+            format,
+            data.as_ptr() as *const c_void,
+            data.len() as i32,
+        );
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: &mut usize) -> Option<u32> {
+    let value = u32::from_le_bytes(bytes.get(*offset..*offset + 4)?.try_into().ok()?);
+    *offset += 4;
+    Some(value)
+}
+
+fn read_u64(bytes: &[u8], offset: &mut usize) -> Option<u64> {
+    let value = u64::from_le_bytes(bytes.get(*offset..*offset + 8)?.try_into().ok()?);
+    *offset += 8;
+    Some(value)
+}
+
+impl ProgramBinaryCache {
+    /// Creates new empty cache for a device with the given id (see [`device_id`]).
+    pub fn new(device_id: u64) -> Self {
+        Self {
+            device_id,
+            binaries: Default::default(),
+            modified: false,
+        }
+    }
+
+    /// Returns id of the device for which the cache was created.
+    pub fn device_id(&self) -> u64 {
+        self.device_id
+    }
+
+    /// Returns amount of programs in the cache.
+    pub fn len(&self) -> usize {
+        self.binaries.len()
+    }
+
+    /// Returns `true` if the cache is empty, `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.binaries.is_empty()
+    }
+
+    /// Returns `true` if the cache was changed since it was created or loaded.
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Adds new binary of a program with the given key (see [`program_key`]).
+    pub fn insert(&mut self, key: u64, format: u32, data: Vec<u8>) {
+        self.binaries.insert(key, ProgramBinary { format, data });
+        self.modified = true;
+    }
+
+    /// Removes a binary of a program with the given key. It is used to discard binaries, that were
+    /// rejected by the driver.
+    pub fn remove(&mut self, key: u64) {
+        if self.binaries.remove(&key).is_some() {
+            self.modified = true;
+        }
+    }
+
+    /// Returns format and binary data of a program with the given key.
+    pub fn get(&self, key: u64) -> Option<(u32, &[u8])> {
+        self.binaries
+            .get(&key)
+            .map(|binary| (binary.format, binary.data.as_slice()))
+    }
+
+    /// Serializes the cache into bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.device_id.to_le_bytes());
+        bytes.extend_from_slice(&(self.binaries.len() as u32).to_le_bytes());
+        for (key, binary) in self.binaries.iter() {
+            bytes.extend_from_slice(&key.to_le_bytes());
+            bytes.extend_from_slice(&binary.format.to_le_bytes());
+            bytes.extend_from_slice(&(binary.data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&binary.data);
+        }
+        bytes
+    }
+
+    /// Deserializes the cache from bytes. Returns `None` if the data is corrupted, or it was made for
+    /// a different device.
+    pub fn from_bytes(bytes: &[u8], device_id: u64) -> Option<Self> {
+        if bytes.get(0..4)? != MAGIC {
+            return None;
+        }
+
+        let mut offset = 4;
+        if read_u32(bytes, &mut offset)? != VERSION || read_u64(bytes, &mut offset)? != device_id {
+            return None;
+        }
+
+        let count = read_u32(bytes, &mut offset)?;
+        let mut binaries = FxHashMap::default();
+        for _ in 0..count {
+            let key = read_u64(bytes, &mut offset)?;
+            let format = read_u32(bytes, &mut offset)?;
+            let len = read_u32(bytes, &mut offset)? as usize;
+            let data = bytes.get(offset..offset + len)?.to_vec();
+            offset += len;
+            binaries.insert(key, ProgramBinary { format, data });
+        }
+
+        Some(Self {
+            device_id,
+            binaries,
+            modified: false,
+        })
+    }
+
+    /// Tries to load the cache from the given file. Returns empty cache if there's no such file, or
+    /// the file is not compatible with the given device.
+    pub fn load<P: AsRef<Path>>(path: P, device_id: u64) -> Self {
+        std::fs::read(path)
+            .ok()
+            .and_then(|bytes| Self::from_bytes(&bytes, device_id))
+            .unwrap_or_else(|| Self::new(device_id))
+    }
+
+    /// Saves the cache to the given file.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())?;
+        self.modified = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::framework::program_cache::ProgramBinaryCache;
+
+    #[test]
+    fn test_program_binary_cache_serialization() {
+        let mut cache = ProgramBinaryCache::new(123);
+        cache.insert(1, 10, vec![1, 2, 3]);
+        cache.insert(2, 20, vec![]);
+        assert!(cache.is_modified());
+
+        let bytes = cache.to_bytes();
+
+        let loaded = ProgramBinaryCache::from_bytes(&bytes, 123).unwrap();
+        assert!(!loaded.is_modified());
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get(1), Some((10, &[1u8, 2, 3][..])));
+        assert_eq!(loaded.get(2), Some((20, &[] as &[u8])));
+        assert_eq!(loaded.get(3), None);
+
+        // Different device.
+        assert!(ProgramBinaryCache::from_bytes(&bytes, 321).is_none());
+        // Corrupted data.
+        assert!(ProgramBinaryCache::from_bytes(&bytes[..bytes.len() - 1], 123).is_none());
+        assert!(ProgramBinaryCache::from_bytes(&[], 123).is_none());
+    }
+}
@@ -139,6 +139,13 @@ vec3 S_PBR_CalculateLight(TPBRContext ctx) {
     return (kD * ctx.albedo / PI + specular) * ctx.lightColor * NdotL;
 }
 
+// Cheap diffuse-only approximation of S_PBR_CalculateLight, it is used for distant lights.
+vec3 S_Lambert_CalculateLight(TPBRContext ctx) {
+    float NdotL = max(dot(ctx.fragmentNormal, ctx.fragmentToLight), 0.0);
+
+    return ctx.albedo / PI * ctx.lightColor * NdotL;
+}
+
 // Returns scatter amount for given parameters.
 // https://cseweb.ucsd.edu/~ravir/papers/singlescat/scattering.pdf
 // https://blog.mmacklin.com/2010/05/29/in-scattering-demo/
@@ -179,6 +186,60 @@ bool S_RaySphereIntersection(vec3 origin, vec3 dir, vec3 center, float radius, o
     return S_SolveQuadraticEq(a, b, c, minT, maxT);
 }
 
+// Shadow filtering methods, must be in sync with `ShadowFilter` enum.
+const int SHADOW_FILTER_PCF = 0;
+const int SHADOW_FILTER_POISSON_DISC = 1;
+const int SHADOW_FILTER_PCSS = 2;
+
+const int MAX_SHADOW_SAMPLES = 32;
+
+// Size of the light source (in shadow map space) that is used to calculate penumbra size in PCSS.
+const float PCSS_LIGHT_SIZE = 0.5;
+
+// Angular size of a point shadow map texel.
+const float POINT_SHADOW_TEXEL_ANGLE = 0.0025;
+
+const vec2 ShadowPoissonDisc[MAX_SHADOW_SAMPLES] = vec2[MAX_SHADOW_SAMPLES](
+    vec2(0.0987, 0.6247), vec2(0.7596, -0.6362), vec2(0.0320, -0.9040), vec2(-0.7287, -0.2039),
+    vec2(-0.3122, -0.7575), vec2(0.7023, 0.1760), vec2(0.6250, 0.5725), vec2(-0.2033, 0.3037),
+    vec2(-0.0481, 0.9310), vec2(-0.7764, -0.5124), vec2(-0.8158, 0.5615), vec2(0.4239, 0.8941),
+    vec2(-0.8173, 0.2504), vec2(-0.4547, 0.8817), vec2(0.2744, -0.2963), vec2(0.4195, -0.0176),
+    vec2(0.7948, -0.1888), vec2(0.3399, -0.6188), vec2(-0.4372, -0.0223), vec2(-0.1798, -0.3090),
+    vec2(0.3078, 0.3833), vec2(-0.2231, 0.6535), vec2(-0.1150, -0.0140), vec2(-0.5104, 0.2926),
+    vec2(-0.4682, -0.4889), vec2(-0.0391, -0.5904), vec2(0.5583, -0.4036), vec2(0.4026, -0.9146),
+    vec2(0.9141, 0.3960), vec2(-0.9897, -0.0351), vec2(0.9851, 0.0508), vec2(-0.6138, -0.7820)
+);
+
+// Random rotation of Poisson disc for the given fragment, it trades banding for noise.
+mat2 Internal_ShadowDiscRotation(vec3 fragmentPosition) {
+    float angle = 6.283185 * fract(sin(dot(fragmentPosition, vec3(12.9898, 78.233, 37.719))) * 43758.5453);
+    float s = sin(angle);
+    float c = cos(angle);
+    return mat2(c, s, -s, c);
+}
+
+int Internal_ShadowSampleCount(int filterKind, int kernelSize, int sampleCount) {
+    if (filterKind == SHADOW_FILTER_PCF) {
+        return kernelSize * kernelSize;
+    }
+    return clamp(sampleCount, 1, MAX_SHADOW_SAMPLES);
+}
+
+// Returns offset of the i-th sample of a shadow filter in [-1; 1] range. PCF uses regular grid
+// of `kernelSize x kernelSize` samples, other filters use rotated Poisson disc.
+vec2 Internal_ShadowSampleOffset(int filterKind, int kernelSize, int i, mat2 rotation) {
+    if (filterKind == SHADOW_FILTER_PCF) {
+        float halfSize = max(float(kernelSize - 1) * 0.5, 1.0);
+        return (vec2(float(i % kernelSize), float(i / kernelSize)) - float(kernelSize - 1) * 0.5) / halfSize;
+    }
+    return rotation * ShadowPoissonDisc[i];
+}
+
+// Radius of a shadow filter in texels, it is defined by kernel size for every filter.
+float Internal_ShadowFilterRadius(int kernelSize, float softness) {
+    return softness * max(float(kernelSize - 1) * 0.5, 1.0);
+}
+
 // Calculates point shadow factor where 1.0 - no shadow, 0.0 - fully in shadow.
 // Why value is inversed? To be able to directly multiply color to shadow factor.
 float S_PointShadow(
@@ -187,31 +248,60 @@ float S_PointShadow(
     float fragmentDistance,
     float shadowBias,
     vec3 toLight,
-    in samplerCube shadowMap)
+    in samplerCube shadowMap,
+    int filterKind,
+    int kernelSize,
+    int sampleCount,
+    float softness)
 {
     if (shadowsEnabled)
     {
         float biasedFragmentDistance = fragmentDistance - shadowBias;
 
-        if (softShadows)
+        if (softShadows && softness > 0.0)
         {
-            const int samples = 20;
+            vec3 direction = -normalize(toLight);
+            vec3 up = abs(direction.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+            vec3 tangent = normalize(cross(up, direction));
+            vec3 bitangent = cross(direction, tangent);
+
+            mat2 rotation = Internal_ShadowDiscRotation(direction * fragmentDistance);
+            int count = Internal_ShadowSampleCount(filterKind, kernelSize, sampleCount);
+            float radius = POINT_SHADOW_TEXEL_ANGLE * Internal_ShadowFilterRadius(kernelSize, softness);
+
+            if (filterKind == SHADOW_FILTER_PCSS)
+            {
+                // Blocker search.
+                float searchRadius = 2.0 * radius;
+                float blockerDistance = 0.0;
+                int blockerCount = 0;
+                for (int i = 0; i < count; ++i)
+                {
+                    vec2 offset = rotation * ShadowPoissonDisc[i] * searchRadius;
+                    float sampleDistance = texture(shadowMap, direction + tangent * offset.x + bitangent * offset.y).r;
+                    if (sampleDistance < biasedFragmentDistance)
+                    {
+                        blockerDistance += sampleDistance;
+                        blockerCount += 1;
+                    }
+                }
 
-            const vec3 directions[samples] = vec3[samples] (
-            vec3(1, 1, 1), vec3(1, -1, 1), vec3(-1, -1, 1), vec3(-1, 1, 1),
-            vec3(1, 1, -1), vec3(1, -1, -1), vec3(-1, -1, -1), vec3(-1, 1, -1),
-            vec3(1, 1, 0), vec3(1, -1, 0), vec3(-1, -1, 0), vec3(-1, 1, 0),
-            vec3(1, 0, 1), vec3(-1, 0, 1), vec3(1, 0, -1), vec3(-1, 0, -1),
-            vec3(0, 1, 1), vec3(0, -1, 1), vec3(0, -1, -1), vec3(0, 1, -1)
-            );
+                if (blockerCount == 0)
+                {
+                    return 1.0;
+                }
 
-            const float diskRadius = 0.0025;
+                blockerDistance /= float(blockerCount);
+                float penumbra = (biasedFragmentDistance - blockerDistance) / max(blockerDistance, 0.0001);
+                radius = clamp(penumbra * softness * PCSS_LIGHT_SIZE, POINT_SHADOW_TEXEL_ANGLE, searchRadius);
+            }
 
             float accumulator = 0.0;
 
-            for (int i = 0; i < samples; ++i)
+            for (int i = 0; i < count; ++i)
             {
-                vec3 fetchDirection = -toLight + directions[i] * diskRadius;
+                vec2 offset = Internal_ShadowSampleOffset(filterKind, kernelSize, i, rotation) * radius;
+                vec3 fetchDirection = direction + tangent * offset.x + bitangent * offset.y;
                 float shadowDistanceToLight = texture(shadowMap, fetchDirection).r;
                 if (biasedFragmentDistance > shadowDistanceToLight)
                 {
@@ -219,7 +309,7 @@ float S_PointShadow(
                 }
             }
 
-            return clamp(1.0 - accumulator / float(samples), 0.0, 1.0);
+            return clamp(1.0 - accumulator / float(count), 0.0, 1.0);
         }
         else
         {
@@ -240,7 +330,11 @@ float S_SpotShadowFactor(
     vec3 fragmentPosition,
     mat4 lightViewProjMatrix,
     float shadowMapInvSize,
-    in sampler2D spotShadowTexture)
+    in sampler2D spotShadowTexture,
+    int filterKind,
+    int kernelSize,
+    int sampleCount,
+    float softness)
 {
     if (shadowsEnabled)
     {
@@ -248,23 +342,51 @@ float S_SpotShadowFactor(
 
         float biasedLightSpaceFragmentDepth = lightSpacePosition.z - shadowBias;
 
-        if (softShadows)
+        if (softShadows && softness > 0.0)
         {
-            float accumulator = 0.0;
+            mat2 rotation = Internal_ShadowDiscRotation(fragmentPosition);
+            int count = Internal_ShadowSampleCount(filterKind, kernelSize, sampleCount);
+            float radius = shadowMapInvSize * Internal_ShadowFilterRadius(kernelSize, softness);
 
-            for (float y = -0.5; y <= 0.5; y += 0.5)
+            if (filterKind == SHADOW_FILTER_PCSS)
             {
-                for (float x = -0.5; x <= 0.5; x += 0.5)
+                // Blocker search.
+                float searchRadius = 2.0 * radius;
+                float blockerDepth = 0.0;
+                int blockerCount = 0;
+                for (int i = 0; i < count; ++i)
                 {
-                    vec2 fetchTexCoord = lightSpacePosition.xy + vec2(x, y) * shadowMapInvSize;
-                    if (biasedLightSpaceFragmentDepth > texture(spotShadowTexture, fetchTexCoord).r)
+                    vec2 fetchTexCoord = lightSpacePosition.xy + rotation * ShadowPoissonDisc[i] * searchRadius;
+                    float sampleDepth = texture(spotShadowTexture, fetchTexCoord).r;
+                    if (sampleDepth < biasedLightSpaceFragmentDepth)
                     {
-                        accumulator += 1.0;
+                        blockerDepth += sampleDepth;
+                        blockerCount += 1;
                     }
                 }
+
+                if (blockerCount == 0)
+                {
+                    return 1.0;
+                }
+
+                blockerDepth /= float(blockerCount);
+                float penumbra = (biasedLightSpaceFragmentDepth - blockerDepth) / max(blockerDepth, 0.0001);
+                radius = clamp(penumbra * softness * PCSS_LIGHT_SIZE, shadowMapInvSize, searchRadius);
             }
 
-            return clamp(1.0 - accumulator / 9.0, 0.0, 1.0);
+            float accumulator = 0.0;
+
+            for (int i = 0; i < count; ++i)
+            {
+                vec2 fetchTexCoord = lightSpacePosition.xy + Internal_ShadowSampleOffset(filterKind, kernelSize, i, rotation) * radius;
+                if (biasedLightSpaceFragmentDepth > texture(spotShadowTexture, fetchTexCoord).r)
+                {
+                    accumulator += 1.0;
+                }
+            }
+
+            return clamp(1.0 - accumulator / float(count), 0.0, 1.0);
         }
         else
         {
@@ -365,4 +487,15 @@ TBlendShapeOffsets S_FetchBlendShapeOffsets(in sampler3D storage, int vertexInde
     vec3 normal = texelFetch(storage, ivec3(pos.x + 1, pos.y, pos.z), 0).xyz;
     vec3 tangent = texelFetch(storage, ivec3(pos.x + 2, pos.y, pos.z), 0).xyz;
     return TBlendShapeOffsets(position, normal, tangent);
-}
\ No newline at end of file
+}
+// Calculates irradiance from second order spherical harmonics of incoming light for a surface
+// with the given normal.
+vec3 S_LightProbeIrradiance(in vec3 sh[9], vec3 n) {
+    vec3 irradiance =
+        3.141593 * 0.282095 * sh[0]
+        + 2.094395 * 0.488603 * (sh[1] * n.y + sh[2] * n.z + sh[3] * n.x)
+        + 0.785398 * (1.092548 * (sh[4] * n.x * n.y + sh[5] * n.y * n.z + sh[7] * n.x * n.z)
+            + 0.315392 * sh[6] * (3.0 * n.z * n.z - 1.0)
+            + 0.546274 * sh[8] * (n.x * n.x - n.y * n.y));
+    return max(irradiance, vec3(0.0));
+}
@@ -1,3 +1,5 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::renderer::framework::program_cache::ProgramBinaryFunctions;
 use crate::{
     core::{
         color::Color,
@@ -6,7 +8,10 @@ use crate::{
         reflect::prelude::*,
         visitor::prelude::*,
     },
-    renderer::framework::framebuffer::{CullFace, DrawParameters},
+    renderer::framework::{
+        framebuffer::{CullFace, DrawParameters},
+        program_cache::ProgramBinaryCache,
+    },
 };
 use glow::{Framebuffer, HasContext};
 use serde::Deserialize;
@@ -251,6 +256,10 @@ pub struct PipelineState {
 
     frame_statistics: PipelineStatistics,
     gl_kind: GlKind,
+
+    program_binary_cache: Option<ProgramBinaryCache>,
+    #[cfg(not(target_arch = "wasm32"))]
+    program_binary_functions: Option<ProgramBinaryFunctions>,
 }
 
 #[derive(Copy, Clone)]
@@ -479,6 +488,9 @@ impl PipelineState {
             frame_statistics: Default::default(),
             blend_equation: Default::default(),
             gl_kind,
+            program_binary_cache: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            program_binary_functions: None,
         }
     }
 
@@ -486,6 +498,47 @@ impl PipelineState {
         self.gl_kind
     }
 
+    /// Returns vendor, name and OpenGL version of the GPU.
+    pub fn gpu_info(&self) -> String {
+        unsafe {
+            format!(
+                "{} {} (OpenGL {})",
+                self.gl.get_parameter_string(glow::VENDOR),
+                self.gl.get_parameter_string(glow::RENDERER),
+                self.gl.get_parameter_string(glow::VERSION)
+            )
+        }
+    }
+
+    /// Sets new program binary cache, that will be used to skip compilation of already compiled GPU
+    /// programs. Returns previous cache.
+    pub fn set_program_binary_cache(
+        &mut self,
+        cache: Option<ProgramBinaryCache>,
+    ) -> Option<ProgramBinaryCache> {
+        std::mem::replace(&mut self.program_binary_cache, cache)
+    }
+
+    /// Sets raw OpenGL functions to get and set program binaries, the program binary cache is used
+    /// only if the functions are set.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_program_binary_functions(&mut self, functions: Option<ProgramBinaryFunctions>) {
+        self.program_binary_functions = functions;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn program_binary_functions(&self) -> Option<ProgramBinaryFunctions> {
+        self.program_binary_functions
+    }
+
+    pub fn program_binary_cache(&self) -> Option<&ProgramBinaryCache> {
+        self.program_binary_cache.as_ref()
+    }
+
+    pub fn program_binary_cache_mut(&mut self) -> Option<&mut ProgramBinaryCache> {
+        self.program_binary_cache.as_mut()
+    }
+
     pub fn set_polygon_fill_mode(
         &mut self,
         polygon_face: PolygonFace,
@@ -495,9 +548,12 @@ impl PipelineState {
             self.polygon_fill_mode = polygon_fill_mode;
             self.polygon_face = polygon_face;
 
-            unsafe {
-                self.gl
-                    .polygon_mode(self.polygon_face as u32, self.polygon_fill_mode as u32)
+            // OpenGL ES does not support polygon modes, everything is drawn filled.
+            if self.gl_kind == GlKind::OpenGL {
+                unsafe {
+                    self.gl
+                        .polygon_mode(self.polygon_face as u32, self.polygon_fill_mode as u32)
+                }
             }
         }
     }
@@ -910,8 +966,9 @@ impl PipelineState {
                     format!("{} error has occurred! Stability is not guaranteed!", code),
                 );
 
-                #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
-                {
+                // Debug output is not available on OpenGL ES 3.0.
+                #[cfg(not(target_arch = "wasm32"))]
+                if self.gl_kind == GlKind::OpenGL {
                     for entry in self.gl.get_debug_message_log(64) {
                         Log::writeln(MessageKind::Error, format!("OpenGL message: {:?}", entry))
                     }
@@ -21,6 +21,7 @@ use crate::{
         apply_material,
         batch::RenderDataBatchStorage,
         cache::shader::ShaderCache,
+        foliage_renderer::{FoliageRenderContext, FoliageRenderer},
         framework::{
             error::FrameworkError,
             framebuffer::{
@@ -44,6 +45,7 @@ use crate::{
         graph::Graph,
         mesh::{surface::SurfaceData, RenderPath},
     },
+    utils::{baking::IrradianceVolume, lightmap::Lightmap},
 };
 use std::{cell::RefCell, rc::Rc};
 
@@ -74,7 +76,10 @@ pub(crate) struct GBufferRenderContext<'a, 'b> {
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
     pub use_parallax_mapping: bool,
     pub graph: &'b Graph,
+    pub light_probes: Option<&'b IrradianceVolume>,
+    pub lightmap: Option<&'b Lightmap>,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub foliage_renderer: &'a mut FoliageRenderer,
 }
 
 impl GBuffer {
@@ -282,7 +287,10 @@ impl GBuffer {
             black_dummy,
             volume_dummy,
             graph,
+            light_probes,
+            lightmap,
             matrix_storage,
+            foliage_renderer,
             ..
         } = args;
 
@@ -316,6 +324,18 @@ impl GBuffer {
                 .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
             {
                 for instance in batch.instances.iter() {
+                    // Lightmapped meshes already have indirect lighting baked in.
+                    let is_lightmapped = lightmap
+                        .map(|lightmap| lightmap.map.contains_key(&instance.node_handle))
+                        .unwrap_or(false);
+                    let light_probe = match light_probes {
+                        Some(light_probes) if !is_lightmapped => graph
+                            .try_get(instance.node_handle)
+                            .map(|node| light_probes.sample(node.world_bounding_box().center()))
+                            .unwrap_or_default(),
+                        _ => Default::default(),
+                    };
+
                     let apply_uniforms = |mut program_binding: GpuProgramBinding| {
                         let view_projection = if instance.depth_offset != 0.0 {
                             let mut projection = camera.projection_matrix();
@@ -339,6 +359,7 @@ impl GBuffer {
                             light_position: &Default::default(),
                             blend_shapes_storage: blend_shapes_storage.as_ref(),
                             blend_shapes_weights: &instance.blend_shapes_weights,
+                            light_probe: &light_probe,
                             normal_dummy: normal_dummy.clone(),
                             white_dummy: white_dummy.clone(),
                             black_dummy: black_dummy.clone(),
@@ -360,6 +381,16 @@ impl GBuffer {
             }
         }
 
+        statistics += foliage_renderer.render(FoliageRenderContext {
+            state,
+            framebuffer: &mut self.framebuffer,
+            graph,
+            camera,
+            white_dummy: white_dummy.clone(),
+            viewport,
+            textures: texture_cache,
+        })?;
+
         let inv_view_proj = initial_view_projection.try_inverse().unwrap_or_default();
         let depth = self.depth();
         let decal_mask = self.decal_mask_texture();
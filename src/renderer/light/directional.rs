@@ -28,6 +28,10 @@ pub struct DirectionalLightShader {
     pub shadow_bias: UniformLocation,
     pub shadows_enabled: UniformLocation,
     pub soft_shadows: UniformLocation,
+    pub shadow_filter: UniformLocation,
+    pub shadow_kernel_size: UniformLocation,
+    pub shadow_sample_count: UniformLocation,
+    pub shadow_softness: UniformLocation,
     pub shadow_map_inv_size: UniformLocation,
 }
 
@@ -76,6 +80,14 @@ impl DirectionalLightShader {
             shadows_enabled: program
                 .uniform_location(state, &ImmutableString::new("shadowsEnabled"))?,
             soft_shadows: program.uniform_location(state, &ImmutableString::new("softShadows"))?,
+            shadow_filter: program
+                .uniform_location(state, &ImmutableString::new("shadowFilter"))?,
+            shadow_kernel_size: program
+                .uniform_location(state, &ImmutableString::new("shadowKernelSize"))?,
+            shadow_sample_count: program
+                .uniform_location(state, &ImmutableString::new("shadowSampleCount"))?,
+            shadow_softness: program
+                .uniform_location(state, &ImmutableString::new("shadowSoftness"))?,
             shadow_map_inv_size: program
                 .uniform_location(state, &ImmutableString::new("shadowMapInvSize"))?,
             program,
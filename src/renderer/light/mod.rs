@@ -1,8 +1,10 @@
 use crate::{
     core::{
-        algebra::{Matrix4, Point3, Vector2, Vector3},
+        algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3},
+        arena::FrameArena,
         color::Color,
         math::{frustum::Frustum, Matrix4Ext, Rect, TriangleDefinition},
+        pool::Handle,
         scope_profile,
     },
     renderer::{
@@ -24,15 +26,17 @@ use crate::{
             point::PointLightShader, spot::SpotLightShader,
         },
         light_volume::LightVolumeRenderer,
+        screen_coverage,
         shadow::{
             csm::{CsmRenderContext, CsmRenderer},
             point::{PointShadowMapRenderContext, PointShadowMapRenderer},
             spot::SpotShadowMapRenderer,
         },
+        sky_shader::SkyShader,
         skybox_shader::SkyboxShader,
         ssao::ScreenSpaceAmbientOcclusionRenderer,
         storage::MatrixStorageCache,
-        GeometryCache, QualitySettings, RenderPassStatistics, TextureCache,
+        GeometryCache, LightLod, QualitySettings, RenderPassStatistics, TextureCache,
     },
     scene::{
         camera::Camera,
@@ -42,11 +46,15 @@ use crate::{
             surface::SurfaceData,
             vertex::SimpleVertex,
         },
+        node::Node,
+        sky::{Sky, MIE_COEFFICIENT},
         Scene,
     },
 };
+use fxhash::FxHashMap;
 use std::{
     cell::RefCell,
+    cmp::Ordering,
     fmt::{Display, Formatter},
     ops::AddAssign,
     rc::Rc,
@@ -65,6 +73,7 @@ pub struct LightingStatistics {
     pub spot_lights_rendered: usize,
     pub spot_shadow_maps_rendered: usize,
     pub directional_lights_rendered: usize,
+    pub lights_culled_by_lod: usize,
 }
 
 impl AddAssign for LightingStatistics {
@@ -75,6 +84,7 @@ impl AddAssign for LightingStatistics {
         self.spot_shadow_maps_rendered += rhs.spot_shadow_maps_rendered;
         self.directional_lights_rendered += rhs.directional_lights_rendered;
         self.csm_rendered += rhs.csm_rendered;
+        self.lights_culled_by_lod += rhs.lights_culled_by_lod;
     }
 }
 
@@ -88,17 +98,103 @@ impl Display for LightingStatistics {
             \tDirectional Lights: {}\n\
             \tPoint Shadow Maps: {}\n\
             \tSpot Shadow Maps: {}\n\
-            \tSpot Shadow Maps: {}\n",
+            \tSpot Shadow Maps: {}\n\
+            \tCulled By LOD: {}\n",
             self.point_lights_rendered,
             self.spot_lights_rendered,
             self.directional_lights_rendered,
             self.point_shadow_maps_rendered,
             self.spot_shadow_maps_rendered,
-            self.csm_rendered
+            self.csm_rendered,
+            self.lights_culled_by_lod
         )
     }
 }
 
+// Returns radius of the bounding sphere of a point or spot light and a flag, that tells whether
+// the light should cast shadows with the given settings.
+fn local_light_bounds(
+    light: &Node,
+    settings: &QualitySettings,
+    camera_position: Vector3<f32>,
+) -> Option<(f32, bool)> {
+    let distance_to_camera = (light.global_position() - camera_position).norm();
+    let (raw_radius, cast_shadows) = if let Some(spot_light) = light.cast::<SpotLight>() {
+        (
+            spot_light.distance(),
+            spot_light.base_light_ref().is_cast_shadows()
+                && distance_to_camera <= settings.spot_shadows_distance
+                && settings.spot_shadows_enabled,
+        )
+    } else if let Some(point_light) = light.cast::<PointLight>() {
+        (
+            point_light.radius(),
+            point_light.base_light_ref().is_cast_shadows()
+                && distance_to_camera <= settings.point_shadows_distance
+                && settings.point_shadows_enabled,
+        )
+    } else {
+        return None;
+    };
+    let scale = light.local_transform().scale();
+    Some((raw_radius * scale.x.max(scale.y).max(scale.z), cast_shadows))
+}
+
+// Calculates levels of detail of every visible point and spot light. Lights, that should cast
+// shadows, but do not fit in the budget of shadow casters are demoted to `LightLod::NoShadows`.
+fn calculate_light_lods(
+    scene: &Scene,
+    settings: &QualitySettings,
+    frustum: &Frustum,
+    camera: &Camera,
+) -> FxHashMap<Handle<Node>, LightLod> {
+    let lod_settings = &settings.light_lod_settings;
+
+    let mut lods = FxHashMap::default();
+    if !lod_settings.enabled {
+        return lods;
+    }
+
+    let view_projection = camera.view_projection_matrix();
+    let projection = camera.projection_matrix();
+
+    let mut shadow_casters = Vec::new();
+    for (handle, light) in scene.graph.pair_iter() {
+        if !light.global_visibility() || !light.is_globally_enabled() {
+            continue;
+        }
+
+        let (radius, cast_shadows) =
+            match local_light_bounds(light, settings, camera.global_position()) {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+
+        let position = light.global_position();
+        if !frustum.is_intersects_sphere(position, radius) {
+            continue;
+        }
+
+        let coverage = screen_coverage(&view_projection, &projection, position, radius);
+        let lod = lod_settings.lod(coverage);
+        if lod == LightLod::Full && cast_shadows {
+            shadow_casters.push((handle, coverage));
+        }
+        lods.insert(handle, lod);
+    }
+
+    // Most important lights go first.
+    shadow_casters.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+    for (handle, _) in shadow_casters
+        .into_iter()
+        .skip(lod_settings.max_shadow_casting_lights)
+    {
+        lods.insert(handle, LightLod::NoShadows);
+    }
+
+    lods
+}
+
 pub struct DeferredLightRenderer {
     pub ssao_renderer: ScreenSpaceAmbientOcclusionRenderer,
     spot_light_shader: SpotLightShader,
@@ -110,6 +206,7 @@ pub struct DeferredLightRenderer {
     skybox: GeometryBuffer,
     flat_shader: FlatShader,
     skybox_shader: SkyboxShader,
+    sky_shader: SkyShader,
     spot_shadow_map_renderer: SpotShadowMapRenderer,
     point_shadow_map_renderer: PointShadowMapRenderer,
     csm_renderer: CsmRenderer,
@@ -132,6 +229,7 @@ pub(crate) struct DeferredRendererContext<'a> {
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub frame_arena: &'a FrameArena,
 }
 
 impl DeferredLightRenderer {
@@ -219,6 +317,7 @@ impl DeferredLightRenderer {
             ),
             flat_shader: FlatShader::new(state)?,
             skybox_shader: SkyboxShader::new(state)?,
+            sky_shader: SkyShader::new(state)?,
             spot_shadow_map_renderer: SpotShadowMapRenderer::new(
                 state,
                 settings.spot_shadow_map_size,
@@ -312,6 +411,7 @@ impl DeferredLightRenderer {
             black_dummy,
             volume_dummy,
             matrix_storage,
+            frame_arena,
         } = args;
 
         let viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
@@ -347,8 +447,63 @@ impl DeferredLightRenderer {
             )?;
         }
 
-        // Render skybox (if any).
-        if let Some(skybox) = camera.skybox_ref() {
+        let sky = scene.graph.linear_iter().find_map(|node| {
+            node.cast::<Sky>()
+                .filter(|sky| sky.global_visibility() && sky.is_globally_enabled())
+        });
+
+        if let Some(sky) = sky {
+            // Procedural sky replaces skybox of the camera.
+            let size = camera.projection().z_far() / 2.0f32.sqrt();
+            let scale = Matrix4::new_scaling(size);
+            let wvp = Matrix4::new_translation(&camera.global_position()) * scale;
+            let world_to_sky = sky
+                .global_transform()
+                .basis()
+                .try_inverse()
+                .unwrap_or_else(Matrix3::identity);
+            let atmosphere = sky.atmosphere();
+            let night_color = sky.night_color().srgb_to_linear_f32().xyz();
+
+            let shader = &self.sky_shader;
+            pass_stats += frame_buffer.draw(
+                &self.skybox,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Specific {
+                    offset: 0,
+                    count: 12,
+                },
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.wvp_matrix, &(view_projection * wvp))
+                        .set_matrix3(&shader.world_to_sky, &world_to_sky)
+                        .set_vector3(&shader.sun_direction, &atmosphere.sun_direction)
+                        .set_f32(&shader.sun_intensity, atmosphere.sun_intensity)
+                        .set_f32(
+                            &shader.mie_coefficient,
+                            MIE_COEFFICIENT * atmosphere.turbidity,
+                        )
+                        .set_f32(&shader.mie_anisotropy, atmosphere.mie_anisotropy)
+                        .set_f32(
+                            &shader.sun_disk_cos,
+                            (sky.sun_disk_size().to_radians() * 0.5).cos(),
+                        )
+                        .set_vector3(&shader.night_color, &night_color);
+                },
+            )?;
+        } else if let Some(skybox) = camera.skybox_ref() {
+            // Render skybox (if any).
             let size = camera.projection().z_far() / 2.0f32.sqrt();
             let scale = Matrix4::new_scaling(size);
             let wvp = Matrix4::new_translation(&camera.global_position()) * scale;
@@ -434,11 +589,22 @@ impl DeferredLightRenderer {
             },
         )?;
 
+        let light_lods = calculate_light_lods(scene, settings, &frustum, camera);
+
         for (light_handle, light) in scene.graph.pair_iter() {
             if !light.global_visibility() || !light.is_globally_enabled() {
                 continue;
             }
 
+            let lod = light_lods
+                .get(&light_handle)
+                .cloned()
+                .unwrap_or(LightLod::Full);
+            if lod == LightLod::Culled {
+                light_stats.lights_culled_by_lod += 1;
+                continue;
+            }
+
             let distance_to_camera = (light.global_position() - camera.global_position()).norm();
 
             let (raw_radius, shadows_distance, shadows_enabled) = if let Some(spot_light) =
@@ -484,6 +650,9 @@ impl DeferredLightRenderer {
                 continue;
             }
 
+            let shadows_enabled = shadows_enabled && lod == LightLod::Full;
+            let simple_lighting = lod == LightLod::Simple;
+
             let b1 = shadows_distance * 0.2;
             let b2 = shadows_distance * 0.4;
             let cascade_index =
@@ -536,6 +705,7 @@ impl DeferredLightRenderer {
                         black_dummy.clone(),
                         volume_dummy.clone(),
                         matrix_storage,
+                        frame_arena,
                     )?;
 
                     light_stats.spot_shadow_maps_rendered += 1;
@@ -556,6 +726,7 @@ impl DeferredLightRenderer {
                                 black_dummy: black_dummy.clone(),
                                 volume_dummy: volume_dummy.clone(),
                                 matrix_storage,
+                                frame_arena,
                             })?;
 
                     light_stats.point_shadow_maps_rendered += 1;
@@ -574,6 +745,7 @@ impl DeferredLightRenderer {
                         black_dummy: black_dummy.clone(),
                         volume_dummy: volume_dummy.clone(),
                         matrix_storage,
+                        frame_arena,
                     })?;
 
                     light_stats.csm_rendered += 1;
@@ -665,6 +837,7 @@ impl DeferredLightRenderer {
                 }),
             };
 
+            let shadow_filter = settings.shadow_filter_settings;
             let quad = &self.quad;
 
             pass_stats += if let Some(spot_light) = light.cast::<SpotLight>() {
@@ -693,15 +866,29 @@ impl DeferredLightRenderer {
                     |mut program_binding| {
                         program_binding
                             .set_bool(&shader.shadows_enabled, shadows_enabled)
+                            .set_bool(&shader.simple_lighting, simple_lighting)
                             .set_matrix4(&shader.light_view_proj_matrix, &light_view_projection)
                             .set_bool(&shader.soft_shadows, settings.spot_soft_shadows)
+                            .set_i32(&shader.shadow_filter, shadow_filter.filter as i32)
+                            .set_i32(
+                                &shader.shadow_kernel_size,
+                                shadow_filter.clamped_kernel_size() as i32,
+                            )
+                            .set_i32(
+                                &shader.shadow_sample_count,
+                                shadow_filter.clamped_sample_count() as i32,
+                            )
+                            .set_f32(
+                                &shader.shadow_softness,
+                                spot_light.base_light_ref().shadow_softness(),
+                            )
                             .set_vector3(&shader.light_position, &light_position)
                             .set_vector3(&shader.light_direction, &emit_direction)
                             .set_f32(&shader.light_radius, light_radius)
                             .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
                             .set_linear_color(
                                 &shader.light_color,
-                                &spot_light.base_light_ref().color(),
+                                &spot_light.base_light_ref().animated_color(),
                             )
                             .set_f32(
                                 &shader.half_hotspot_cone_angle_cos,
@@ -731,7 +918,7 @@ impl DeferredLightRenderer {
                             .set_f32(&shader.shadow_bias, spot_light.shadow_bias())
                             .set_f32(
                                 &shader.light_intensity,
-                                spot_light.base_light_ref().intensity(),
+                                spot_light.base_light_ref().animated_intensity(),
                             );
                     },
                 )?
@@ -750,20 +937,34 @@ impl DeferredLightRenderer {
                     |mut program_binding| {
                         program_binding
                             .set_bool(&shader.shadows_enabled, shadows_enabled)
+                            .set_bool(&shader.simple_lighting, simple_lighting)
                             .set_bool(&shader.soft_shadows, settings.point_soft_shadows)
+                            .set_i32(&shader.shadow_filter, shadow_filter.filter as i32)
+                            .set_i32(
+                                &shader.shadow_kernel_size,
+                                shadow_filter.clamped_kernel_size() as i32,
+                            )
+                            .set_i32(
+                                &shader.shadow_sample_count,
+                                shadow_filter.clamped_sample_count() as i32,
+                            )
+                            .set_f32(
+                                &shader.shadow_softness,
+                                point_light.base_light_ref().shadow_softness(),
+                            )
                             .set_vector3(&shader.light_position, &light_position)
                             .set_f32(&shader.light_radius, light_radius)
                             .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
                             .set_linear_color(
                                 &shader.light_color,
-                                &point_light.base_light_ref().color(),
+                                &point_light.base_light_ref().animated_color(),
                             )
                             .set_matrix4(&shader.wvp_matrix, &frame_matrix)
                             .set_vector3(&shader.camera_position, &camera_global_position)
                             .set_f32(&shader.shadow_bias, point_light.shadow_bias())
                             .set_f32(
                                 &shader.light_intensity,
-                                point_light.base_light_ref().intensity(),
+                                point_light.base_light_ref().animated_intensity(),
                             )
                             .set_texture(&shader.depth_sampler, &gbuffer_depth_map)
                             .set_texture(&shader.color_sampler, &gbuffer_diffuse_map)
@@ -818,13 +1019,13 @@ impl DeferredLightRenderer {
                             .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
                             .set_linear_color(
                                 &shader.light_color,
-                                &directional.base_light_ref().color(),
+                                &directional.base_light_ref().animated_color(),
                             )
                             .set_matrix4(&shader.wvp_matrix, &frame_matrix)
                             .set_vector3(&shader.camera_position, &camera_global_position)
                             .set_f32(
                                 &shader.light_intensity,
-                                directional.base_light_ref().intensity(),
+                                directional.base_light_ref().animated_intensity(),
                             )
                             .set_texture(&shader.depth_sampler, &gbuffer_depth_map)
                             .set_texture(&shader.color_sampler, &gbuffer_diffuse_map)
@@ -848,6 +1049,19 @@ impl DeferredLightRenderer {
                             .set_f32(&shader.shadow_bias, directional.csm_options.shadow_bias())
                             .set_bool(&shader.shadows_enabled, shadows_enabled)
                             .set_bool(&shader.soft_shadows, settings.csm_settings.pcf)
+                            .set_i32(&shader.shadow_filter, shadow_filter.filter as i32)
+                            .set_i32(
+                                &shader.shadow_kernel_size,
+                                shadow_filter.clamped_kernel_size() as i32,
+                            )
+                            .set_i32(
+                                &shader.shadow_sample_count,
+                                shadow_filter.clamped_sample_count() as i32,
+                            )
+                            .set_f32(
+                                &shader.shadow_softness,
+                                directional.base_light_ref().shadow_softness(),
+                            )
                             .set_f32(&shader.shadow_map_inv_size, 1.0 / csm_map_size);
                     },
                 )?
@@ -855,7 +1069,7 @@ impl DeferredLightRenderer {
                 unreachable!()
             };
 
-            if settings.light_scatter_enabled {
+            if settings.light_scatter_enabled && !simple_lighting {
                 pass_stats += self.light_volume.render_volume(
                     state,
                     light,
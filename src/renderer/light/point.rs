@@ -14,7 +14,12 @@ pub struct PointLightShader {
     pub material_sampler: UniformLocation,
     pub point_shadow_texture: UniformLocation,
     pub shadows_enabled: UniformLocation,
+    pub simple_lighting: UniformLocation,
     pub soft_shadows: UniformLocation,
+    pub shadow_filter: UniformLocation,
+    pub shadow_kernel_size: UniformLocation,
+    pub shadow_sample_count: UniformLocation,
+    pub shadow_softness: UniformLocation,
     pub light_position: UniformLocation,
     pub light_radius: UniformLocation,
     pub light_color: UniformLocation,
@@ -45,7 +50,17 @@ impl PointLightShader {
                 .uniform_location(state, &ImmutableString::new("pointShadowTexture"))?,
             shadows_enabled: program
                 .uniform_location(state, &ImmutableString::new("shadowsEnabled"))?,
+            simple_lighting: program
+                .uniform_location(state, &ImmutableString::new("simpleLighting"))?,
             soft_shadows: program.uniform_location(state, &ImmutableString::new("softShadows"))?,
+            shadow_filter: program
+                .uniform_location(state, &ImmutableString::new("shadowFilter"))?,
+            shadow_kernel_size: program
+                .uniform_location(state, &ImmutableString::new("shadowKernelSize"))?,
+            shadow_sample_count: program
+                .uniform_location(state, &ImmutableString::new("shadowSampleCount"))?,
+            shadow_softness: program
+                .uniform_location(state, &ImmutableString::new("shadowSoftness"))?,
             light_position: program.uniform_location(state, &ImmutableString::new("lightPos"))?,
             light_radius: program.uniform_location(state, &ImmutableString::new("lightRadius"))?,
             light_color: program.uniform_location(state, &ImmutableString::new("lightColor"))?,
@@ -17,7 +17,12 @@ pub struct SpotLightShader {
     pub cookie_texture: UniformLocation,
     pub light_view_proj_matrix: UniformLocation,
     pub shadows_enabled: UniformLocation,
+    pub simple_lighting: UniformLocation,
     pub soft_shadows: UniformLocation,
+    pub shadow_filter: UniformLocation,
+    pub shadow_kernel_size: UniformLocation,
+    pub shadow_sample_count: UniformLocation,
+    pub shadow_softness: UniformLocation,
     pub shadow_map_inv_size: UniformLocation,
     pub light_position: UniformLocation,
     pub light_radius: UniformLocation,
@@ -58,7 +63,17 @@ impl SpotLightShader {
                 .uniform_location(state, &ImmutableString::new("lightViewProjMatrix"))?,
             shadows_enabled: program
                 .uniform_location(state, &ImmutableString::new("shadowsEnabled"))?,
+            simple_lighting: program
+                .uniform_location(state, &ImmutableString::new("simpleLighting"))?,
             soft_shadows: program.uniform_location(state, &ImmutableString::new("softShadows"))?,
+            shadow_filter: program
+                .uniform_location(state, &ImmutableString::new("shadowFilter"))?,
+            shadow_kernel_size: program
+                .uniform_location(state, &ImmutableString::new("shadowKernelSize"))?,
+            shadow_sample_count: program
+                .uniform_location(state, &ImmutableString::new("shadowSampleCount"))?,
+            shadow_softness: program
+                .uniform_location(state, &ImmutableString::new("shadowSoftness"))?,
             shadow_map_inv_size: program
                 .uniform_location(state, &ImmutableString::new("shadowMapInvSize"))?,
             light_position: program.uniform_location(state, &ImmutableString::new("lightPos"))?,
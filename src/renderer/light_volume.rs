@@ -277,10 +277,17 @@ impl LightVolumeRenderer {
                         .set_texture(&shader.depth_sampler, &depth_map)
                         .set_vector3(
                             &shader.light_color,
-                            &spot.base_light_ref().color().srgb_to_linear_f32().xyz(),
+                            &spot
+                                .base_light_ref()
+                                .animated_color()
+                                .srgb_to_linear_f32()
+                                .xyz(),
                         )
                         .set_vector3(&shader.scatter_factor, &spot.base_light_ref().scatter())
-                        .set_f32(&shader.intensity, spot.base_light_ref().intensity());
+                        .set_f32(
+                            &shader.intensity,
+                            spot.base_light_ref().animated_intensity(),
+                        );
                 },
             )?
         } else if let Some(point) = light.cast::<PointLight>() {
@@ -367,10 +374,17 @@ impl LightVolumeRenderer {
                         .set_f32(&shader.light_radius, point.radius())
                         .set_vector3(
                             &shader.light_color,
-                            &point.base_light_ref().color().srgb_to_linear_f32().xyz(),
+                            &point
+                                .base_light_ref()
+                                .animated_color()
+                                .srgb_to_linear_f32()
+                                .xyz(),
                         )
                         .set_vector3(&shader.scatter_factor, &point.base_light_ref().scatter())
-                        .set_f32(&shader.intensity, point.base_light_ref().intensity());
+                        .set_f32(
+                            &shader.intensity,
+                            point.base_light_ref().animated_intensity(),
+                        );
                 },
             )?
         }
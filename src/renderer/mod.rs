@@ -16,12 +16,17 @@ pub mod framework;
 pub mod batch;
 pub mod cache;
 pub mod debug_renderer;
+pub mod framegraph;
+pub mod paint;
 pub mod renderer2d;
 pub mod storage;
 pub mod ui_renderer;
 
+pub(crate) mod snapshot;
+
 mod bloom;
 mod flat_shader;
+mod foliage_renderer;
 mod forward_renderer;
 mod fxaa;
 mod gbuffer;
@@ -30,14 +35,17 @@ mod light;
 mod light_volume;
 mod particle_system_renderer;
 mod shadow;
+mod sky_shader;
 mod skybox_shader;
 mod sprite_renderer;
 mod ssao;
+mod water_renderer;
 
 use crate::{
     asset::{event::ResourceEvent, manager::ResourceManager},
     core::{
         algebra::{Matrix4, Vector2, Vector3},
+        arena::FrameArena,
         color::Color,
         instant,
         log::{Log, MessageKind},
@@ -47,6 +55,7 @@ use crate::{
         scope_profile,
         sstorage::ImmutableString,
     },
+    engine::{crash::CrashHandler, gc::TimeBudget},
     gui::{draw::DrawingContext, UserInterface},
     material::{
         shader::{SamplerFallback, Shader, ShaderResource, ShaderResourceExtension},
@@ -58,7 +67,11 @@ use crate::{
         cache::{geometry::GeometryCache, shader::ShaderCache, texture::TextureCache, CacheEntry},
         debug_renderer::DebugRenderer,
         flat_shader::FlatShader,
+        foliage_renderer::FoliageRenderer,
         forward_renderer::{ForwardRenderContext, ForwardRenderer},
+        framegraph::{
+            FrameGraph, ResourceHandle, TransientResourcePool, TransientTextureDescriptor,
+        },
         framework::{
             error::FrameworkError,
             framebuffer::{Attachment, AttachmentKind, DrawParameters, FrameBuffer},
@@ -70,20 +83,30 @@ use crate::{
                 Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
                 PixelKind, WrapMode,
             },
+            program_cache::{self, ProgramBinaryCache},
             state::{GlKind, PipelineState, PipelineStatistics, PolygonFace, PolygonFillMode},
         },
         fxaa::FxaaRenderer,
         gbuffer::{GBuffer, GBufferRenderContext},
         hdr::HighDynamicRangeRenderer,
         light::{DeferredLightRenderer, DeferredRendererContext, LightingStatistics},
+        paint::{PaintCommandList, PaintContext, TexturePainter},
         particle_system_renderer::{ParticleSystemRenderContext, ParticleSystemRenderer},
         renderer2d::Renderer2d,
         sprite_renderer::{SpriteRenderContext, SpriteRenderer},
         storage::MatrixStorageCache,
         ui_renderer::{UiRenderContext, UiRenderer},
+        water_renderer::{WaterRenderContext, WaterRenderer},
     },
     resource::texture::{Texture, TextureKind, TextureResource},
-    scene::{camera::Camera, mesh::surface::SurfaceData, Scene, SceneContainer},
+    scene::{
+        camera::Camera,
+        mesh::surface::{SurfaceData, SurfaceSharedData},
+        node::Node,
+        water::Water,
+        Scene,
+    },
+    utils::baking::SphericalHarmonics,
 };
 use fxhash::FxHashMap;
 use glow::HasContext;
@@ -99,6 +122,7 @@ use std::{
     cell::RefCell,
     collections::hash_map::Entry,
     fmt::{Display, Formatter},
+    path::Path,
     rc::Rc,
     sync::mpsc::Receiver,
 };
@@ -256,6 +280,174 @@ impl Default for CsmSettings {
     }
 }
 
+/// Shadow filtering method. Filtering smooths edges of shadows, see [`ShadowFilterSettings`] for
+/// more info.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum ShadowFilter {
+    /// Percentage-closer filtering, takes `kernel_size x kernel_size` samples from a shadow map
+    /// arranged in a regular grid.
+    #[default]
+    Pcf,
+    /// Percentage-closer filtering with samples arranged in a randomly rotated Poisson disc. It
+    /// gives smoother results than regular PCF with the same amount of samples, by trading banding
+    /// for a slight noise.
+    PoissonDisc,
+    /// Percentage-closer soft shadows. Searches for shadow casters around a fragment first and then
+    /// filters the shadow using Poisson disc, which size depends on the distance between the
+    /// caster and the receiver. Shadows are sharp near contact points and become softer with
+    /// distance ("contact hardening"). It is the most expensive method, it takes two times more
+    /// samples than Poisson disc.
+    Pcss,
+}
+
+/// Shadow filtering settings. Filtering is applied only to the shadows of the light sources with
+/// enabled soft shadows (see [`QualitySettings::point_soft_shadows`],
+/// [`QualitySettings::spot_soft_shadows`] and [`CsmSettings::pcf`]). Size of the filter is also
+/// scaled by softness of each light source (see [`crate::scene::light::BaseLight::set_shadow_softness`]).
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct ShadowFilterSettings {
+    /// Filtering method.
+    pub filter: ShadowFilter,
+
+    /// Size of the filter kernel in shadow map texels. It defines the amount of samples for
+    /// [`ShadowFilter::Pcf`] and the size of the disc for other methods. Must be in `[1; 7]` range.
+    #[reflect(min_value = 1.0, max_value = 7.0, step = 1.0)]
+    pub kernel_size: u32,
+
+    /// Amount of samples for [`ShadowFilter::PoissonDisc`] and [`ShadowFilter::Pcss`]. Must be in
+    /// `[1; 32]` range.
+    #[reflect(min_value = 1.0, max_value = 32.0, step = 1.0)]
+    pub sample_count: u32,
+}
+
+impl Default for ShadowFilterSettings {
+    fn default() -> Self {
+        Self {
+            filter: ShadowFilter::Pcf,
+            kernel_size: 3,
+            sample_count: 16,
+        }
+    }
+}
+
+impl ShadowFilterSettings {
+    /// Returns kernel size clamped to the supported range.
+    pub fn clamped_kernel_size(&self) -> u32 {
+        self.kernel_size.clamp(1, 7)
+    }
+
+    /// Returns amount of samples clamped to the supported range.
+    pub fn clamped_sample_count(&self) -> u32 {
+        self.sample_count.clamp(1, 32)
+    }
+}
+
+/// Level of detail of a light source. See [`LightLodSettings`] docs for more info.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LightLod {
+    /// The light is rendered with every effect enabled for it.
+    Full,
+    /// The light does not cast shadows.
+    NoShadows,
+    /// The light does not cast shadows and uses simplified (diffuse-only) lighting model without
+    /// light scattering.
+    Simple,
+    /// The light is not rendered at all.
+    Culled,
+}
+
+/// Level-of-detail settings for point and spot lights. Importance of a light is defined by its
+/// screen coverage - a fraction of the frame height, that is covered by the bounding sphere of
+/// the light. Distant or small lights have small coverage, their shadows and precise lighting
+/// are barely noticeable, so they're rendered using cheaper methods. This allows scenes with lots
+/// of lights (for example, a city at night) to stay within performance budget.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct LightLodSettings {
+    /// Whether the level-of-detail system is enabled or not. If disabled, every light is
+    /// rendered at full quality.
+    pub enabled: bool,
+
+    /// Minimal screen coverage of a light to cast shadows.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub shadows_screen_coverage: f32,
+
+    /// Minimal screen coverage of a light to use full lighting model. Lights with lower coverage
+    /// use diffuse-only lighting.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub full_lighting_screen_coverage: f32,
+
+    /// Minimal screen coverage of a light to be rendered at all.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.001)]
+    pub cull_screen_coverage: f32,
+
+    /// Maximum amount of point and spot lights, that can cast shadows in one frame. If there are
+    /// more such lights, only the most important ones will cast shadows.
+    pub max_shadow_casting_lights: usize,
+}
+
+impl Default for LightLodSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            shadows_screen_coverage: 0.1,
+            full_lighting_screen_coverage: 0.04,
+            cull_screen_coverage: 0.005,
+            max_shadow_casting_lights: 8,
+        }
+    }
+}
+
+impl LightLodSettings {
+    /// Returns level of detail for a light with the given screen coverage. Shadow casters budget
+    /// is not taken into account here.
+    pub fn lod(&self, screen_coverage: f32) -> LightLod {
+        if !self.enabled {
+            LightLod::Full
+        } else if screen_coverage < self.cull_screen_coverage {
+            LightLod::Culled
+        } else if screen_coverage < self.full_lighting_screen_coverage {
+            LightLod::Simple
+        } else if screen_coverage < self.shadows_screen_coverage {
+            LightLod::NoShadows
+        } else {
+            LightLod::Full
+        }
+    }
+}
+
+/// Calculates a fraction of the frame height, that is covered by a sphere with the given radius
+/// and position in world space. The result is clamped to `[0; 1]` range, it is `1.0` if the camera
+/// is inside the sphere.
+pub fn screen_coverage(
+    view_projection_matrix: &Matrix4<f32>,
+    projection_matrix: &Matrix4<f32>,
+    position: Vector3<f32>,
+    radius: f32,
+) -> f32 {
+    // W component of clip-space position is the distance along view direction for perspective
+    // projections and 1.0 for orthographic ones.
+    let w = (view_projection_matrix * position.push(1.0)).w;
+    if w <= radius {
+        1.0
+    } else {
+        (radius * projection_matrix[(1, 1)].abs() / w).min(1.0)
+    }
+}
+
 /// Quality settings allows you to find optimal balance between performance and
 /// graphics quality.
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
@@ -263,7 +455,7 @@ pub struct QualitySettings {
     /// Point shadows
     /// Size of cube map face of shadow map texture in pixels.
     pub point_shadow_map_size: usize,
-    /// Use or not percentage close filtering (smoothing) for point shadows.
+    /// Use or not filtering (smoothing) for point shadows. See [`Self::shadow_filter_settings`].
     pub point_soft_shadows: bool,
     /// Point shadows enabled or not.
     pub point_shadows_enabled: bool,
@@ -276,7 +468,7 @@ pub struct QualitySettings {
     /// Spot shadows
     /// Size of square shadow map texture in pixels
     pub spot_shadow_map_size: usize,
-    /// Use or not percentage close filtering (smoothing) for spot shadows.
+    /// Use or not filtering (smoothing) for spot shadows. See [`Self::shadow_filter_settings`].
     pub spot_soft_shadows: bool,
     /// Spot shadows enabled or not.
     pub spot_shadows_enabled: bool,
@@ -307,6 +499,14 @@ pub struct QualitySettings {
 
     /// Whether to use bloom effect.
     pub use_bloom: bool,
+
+    /// Level-of-detail settings for point and spot lights.
+    #[serde(default)]
+    pub light_lod_settings: LightLodSettings,
+
+    /// Shadow filtering settings for every kind of light sources.
+    #[serde(default)]
+    pub shadow_filter_settings: ShadowFilterSettings,
 }
 
 impl Default for QualitySettings {
@@ -344,6 +544,19 @@ impl QualitySettings {
             use_parallax_mapping: false, // TODO: Enable when it is fixed!
 
             csm_settings: Default::default(),
+
+            light_lod_settings: LightLodSettings {
+                enabled: true,
+                shadows_screen_coverage: 0.05,
+                full_lighting_screen_coverage: 0.02,
+                cull_screen_coverage: 0.002,
+                max_shadow_casting_lights: 16,
+            },
+            shadow_filter_settings: ShadowFilterSettings {
+                filter: ShadowFilter::Pcss,
+                kernel_size: 5,
+                sample_count: 32,
+            },
         }
     }
 
@@ -380,6 +593,19 @@ impl QualitySettings {
                 precision: ShadowMapPrecision::Full,
                 pcf: true,
             },
+
+            light_lod_settings: LightLodSettings {
+                enabled: true,
+                shadows_screen_coverage: 0.1,
+                full_lighting_screen_coverage: 0.04,
+                cull_screen_coverage: 0.005,
+                max_shadow_casting_lights: 8,
+            },
+            shadow_filter_settings: ShadowFilterSettings {
+                filter: ShadowFilter::PoissonDisc,
+                kernel_size: 3,
+                sample_count: 16,
+            },
         }
     }
 
@@ -416,6 +642,70 @@ impl QualitySettings {
                 precision: ShadowMapPrecision::Full,
                 pcf: false,
             },
+
+            light_lod_settings: LightLodSettings {
+                enabled: true,
+                shadows_screen_coverage: 0.2,
+                full_lighting_screen_coverage: 0.08,
+                cull_screen_coverage: 0.01,
+                max_shadow_casting_lights: 4,
+            },
+            shadow_filter_settings: ShadowFilterSettings {
+                filter: ShadowFilter::Pcf,
+                kernel_size: 3,
+                sample_count: 16,
+            },
+        }
+    }
+
+    /// Graphics quality for mobile GPUs (OpenGL ES 3.0), it is used by default on Android and iOS.
+    /// Point shadows, SSAO, light scattering and bloom are disabled, because they require a lot of
+    /// fill rate and floating-point render targets.
+    pub fn mobile() -> Self {
+        Self {
+            point_shadow_map_size: 1, // Zero is unsupported.
+            point_shadows_distance: 0.0,
+            point_shadows_enabled: false,
+            point_soft_shadows: false,
+
+            spot_shadow_map_size: 512,
+            spot_shadows_distance: 5.0,
+            spot_shadows_enabled: true,
+            spot_soft_shadows: false,
+
+            use_ssao: false,
+            ssao_radius: 0.5,
+
+            light_scatter_enabled: false,
+
+            point_shadow_map_precision: ShadowMapPrecision::Half,
+            spot_shadow_map_precision: ShadowMapPrecision::Half,
+
+            fxaa: true,
+
+            use_bloom: false,
+
+            use_parallax_mapping: false,
+
+            csm_settings: CsmSettings {
+                enabled: true,
+                size: 1024,
+                precision: ShadowMapPrecision::Half,
+                pcf: false,
+            },
+
+            light_lod_settings: LightLodSettings {
+                enabled: true,
+                shadows_screen_coverage: 0.25,
+                full_lighting_screen_coverage: 0.1,
+                cull_screen_coverage: 0.015,
+                max_shadow_casting_lights: 2,
+            },
+            shadow_filter_settings: ShadowFilterSettings {
+                filter: ShadowFilter::Pcf,
+                kernel_size: 1,
+                sample_count: 8,
+            },
         }
     }
 
@@ -452,6 +742,19 @@ impl QualitySettings {
                 precision: ShadowMapPrecision::Half,
                 pcf: false,
             },
+
+            light_lod_settings: LightLodSettings {
+                enabled: true,
+                shadows_screen_coverage: 0.3,
+                full_lighting_screen_coverage: 0.15,
+                cull_screen_coverage: 0.02,
+                max_shadow_casting_lights: 0,
+            },
+            shadow_filter_settings: ShadowFilterSettings {
+                filter: ShadowFilter::Pcf,
+                kernel_size: 1,
+                sample_count: 8,
+            },
         }
     }
 }
@@ -508,6 +811,26 @@ impl Default for Statistics {
     }
 }
 
+// Passes of the frame graph of a camera, see `Renderer::render_frame`.
+#[derive(Copy, Clone, Debug)]
+enum ScenePass {
+    GBuffer,
+    Lighting,
+    WaterRefraction { target: ResourceHandle },
+    Water { refraction: ResourceHandle },
+    Particles,
+    Sprites,
+    Renderer2d,
+    Forward,
+    CustomHdr,
+    Bloom,
+    ToneMapping,
+    Fxaa { target: ResourceHandle },
+    FxaaResolve { source: ResourceHandle },
+    Debug,
+    CustomLdr,
+}
+
 /// A set of frame buffers, renderers, that contains scene-specific data.
 pub struct AssociatedSceneData {
     /// G-Buffer of the scene.
@@ -519,9 +842,6 @@ pub struct AssociatedSceneData {
     /// Final frame of the scene. Tone mapped + gamma corrected.
     pub ldr_scene_framebuffer: FrameBuffer,
 
-    /// Additional frame buffer for post processing.
-    pub ldr_temp_framebuffer: FrameBuffer,
-
     /// HDR renderer has be created per scene, because it contains
     /// scene luminance.
     pub hdr_renderer: HighDynamicRangeRenderer,
@@ -589,29 +909,6 @@ impl AssociatedSceneData {
         )?;
 
         let ldr_scene_framebuffer = FrameBuffer::new(
-            state,
-            Some(Attachment {
-                kind: AttachmentKind::DepthStencil,
-                texture: depth_stencil.clone(),
-            }),
-            vec![Attachment {
-                kind: AttachmentKind::Color,
-                texture: Rc::new(RefCell::new(ldr_frame_texture)),
-            }],
-        )?;
-
-        let ldr_temp_texture = GpuTexture::new(
-            state,
-            GpuTextureKind::Rectangle { width, height },
-            // Final scene frame is in standard sRGB space.
-            PixelKind::RGBA8,
-            MinificationFilter::Linear,
-            MagnificationFilter::Linear,
-            1,
-            None,
-        )?;
-
-        let ldr_temp_framebuffer = FrameBuffer::new(
             state,
             Some(Attachment {
                 kind: AttachmentKind::DepthStencil,
@@ -619,7 +916,7 @@ impl AssociatedSceneData {
             }),
             vec![Attachment {
                 kind: AttachmentKind::Color,
-                texture: Rc::new(RefCell::new(ldr_temp_texture)),
+                texture: Rc::new(RefCell::new(ldr_frame_texture)),
             }],
         )?;
 
@@ -629,7 +926,6 @@ impl AssociatedSceneData {
             bloom_renderer: BloomRenderer::new(state, width, height)?,
             hdr_scene_framebuffer,
             ldr_scene_framebuffer,
-            ldr_temp_framebuffer,
         })
     }
 
@@ -664,13 +960,6 @@ impl AssociatedSceneData {
             .texture
             .clone()
     }
-
-    /// Returns low-dynamic range frame buffer texture (accumulation frame).
-    pub fn ldr_temp_frame_texture(&self) -> Rc<RefCell<GpuTexture>> {
-        self.ldr_temp_framebuffer.color_attachments()[0]
-            .texture
-            .clone()
-    }
 }
 
 pub(crate) fn make_viewport_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
@@ -688,6 +977,43 @@ pub(crate) fn make_viewport_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
     ))
 }
 
+/// Pixels of a rendered frame, see [`Renderer::request_frame_capture`] for more info.
+#[derive(Clone, Debug)]
+pub struct CapturedFrame {
+    /// Size of the frame in pixels.
+    pub size: Vector2<u32>,
+    /// RGBA8 pixels of the frame, rows are stored from top to bottom.
+    pub pixels: Vec<u8>,
+}
+
+/// Contents of a G-Buffer, see [`Renderer::render_scene_gbuffer`] for more info.
+#[derive(Clone, Debug)]
+pub struct GBufferCapture {
+    /// Albedo of the objects in sRGB space, alpha channel is zero where there are no objects.
+    pub diffuse: CapturedFrame,
+    /// World-space normals of the objects, packed in `[0; 1]` range.
+    pub normal: CapturedFrame,
+}
+
+/// High dynamic range pixels of a rendered frame, see [`Renderer::render_scene_hdr`] for more info.
+#[derive(Clone, Debug)]
+pub struct HdrCapturedFrame {
+    /// Size of the frame in pixels.
+    pub size: Vector2<u32>,
+    /// RGBA pixels of the frame in linear color space, rows are stored from top to bottom.
+    pub pixels: Vec<f32>,
+}
+
+// OpenGL stores rows from bottom to top, this function reverses the order of RGBA rows.
+fn flip_rows<T: Clone>(pixels: Vec<T>, width: u32) -> Vec<T> {
+    pixels
+        .chunks_exact(width as usize * 4)
+        .rev()
+        .flatten()
+        .cloned()
+        .collect()
+}
+
 /// See module docs.
 pub struct Renderer {
     backbuffer: FrameBuffer,
@@ -696,6 +1022,8 @@ pub struct Renderer {
     flat_shader: FlatShader,
     sprite_renderer: SpriteRenderer,
     particle_system_renderer: ParticleSystemRenderer,
+    foliage_renderer: FoliageRenderer,
+    water_renderer: WaterRenderer,
     /// Dummy white one pixel texture which will be used as stub when rendering
     /// something without texture specified.
     pub white_dummy: Rc<RefCell<GpuTexture>>,
@@ -733,6 +1061,14 @@ pub struct Renderer {
     // TextureId -> FrameBuffer mapping. This mapping is used for temporal frame buffers
     // like ones used to render UI instances.
     ui_frame_buffers: FxHashMap<usize, FrameBuffer>,
+    texture_painter: TexturePainter,
+    frame_capture_requested: bool,
+    captured_frame: Option<CapturedFrame>,
+    // Render targets for transient resources of frame graphs, shared by every scene and camera.
+    transient_pool: TransientResourcePool,
+    // Temporary per-frame allocations (batch building, culling), the arena is reset at the beginning
+    // of each frame.
+    frame_arena: FrameArena,
     // MUST BE LAST! Otherwise you'll get crash, because other parts of the renderer will
     // contain **pointer** to pipeline state. It must be dropped last!
     /// Pipeline state.
@@ -946,6 +1282,7 @@ pub(crate) struct MaterialContext<'a, 'b, 'c> {
     pub light_position: &'a Vector3<f32>,
     pub blend_shapes_storage: Option<&'a TextureResource>,
     pub blend_shapes_weights: &'a [f32],
+    pub light_probe: &'a SphericalHarmonics,
 
     // Fallback samplers.
     pub normal_dummy: Rc<RefCell<GpuTexture>>,
@@ -1017,6 +1354,10 @@ pub(crate) fn apply_material(ctx: MaterialContext) {
         ctx.program_binding
             .set_i32(location, ctx.blend_shapes_weights.len() as i32);
     }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::LightProbe as usize] {
+        ctx.program_binding
+            .set_vector3_slice(location, &ctx.light_probe.coefficients);
+    }
 
     // Apply material properties.
     for (name, value) in ctx.material.properties() {
@@ -1106,7 +1447,11 @@ impl Renderer {
         resource_manager: &ResourceManager,
         gl_kind: GlKind,
     ) -> Result<Self, FrameworkError> {
-        let settings = QualitySettings::default();
+        let settings = if cfg!(any(target_os = "android", target_os = "ios")) {
+            QualitySettings::mobile()
+        } else {
+            QualitySettings::default()
+        };
 
         let (texture_event_sender, texture_event_receiver) = std::sync::mpsc::channel();
 
@@ -1126,12 +1471,30 @@ impl Renderer {
         // it must have constant address.
         let mut state = Box::new(PipelineState::new(context, gl_kind));
 
+        let gpu_info = state.gpu_info();
+        Log::info(format!("GPU: {}", gpu_info));
+        CrashHandler::set_gpu_info(gpu_info);
+
         // Dump available GL extensions to the log, this will help debugging graphical issues.
         Log::info(format!(
             "Supported GL Extensions: {:?}",
             state.gl.supported_extensions()
         ));
 
+        // OpenGL ES 3.0 can't render to floating-point textures without the extension, but the
+        // renderer needs them for G-Buffer and HDR.
+        if gl_kind == GlKind::OpenGLES
+            && !state
+                .gl
+                .supported_extensions()
+                .contains("EXT_color_buffer_float")
+        {
+            Log::warn(
+                "EXT_color_buffer_float is not supported, rendering to floating-point textures \
+                may fail on this device!",
+            );
+        }
+
         let mut shader_cache = ShaderCache::default();
 
         for shader in ShaderResource::standard_shaders() {
@@ -1231,6 +1594,8 @@ impl Renderer {
             ),
             ui_renderer: UiRenderer::new(&mut state)?,
             particle_system_renderer: ParticleSystemRenderer::new(&mut state)?,
+            foliage_renderer: FoliageRenderer::new(&mut state)?,
+            water_renderer: WaterRenderer::new(&mut state)?,
             quality_settings: settings,
             debug_renderer: DebugRenderer::new(&mut state)?,
             scene_data_map: Default::default(),
@@ -1239,6 +1604,7 @@ impl Renderer {
             geometry_cache: Default::default(),
             forward_renderer: ForwardRenderer::new(),
             ui_frame_buffers: Default::default(),
+            texture_painter: TexturePainter::new(&mut state)?,
             fxaa_renderer: FxaaRenderer::new(&mut state)?,
             statistics: Statistics::default(),
             renderer2d: Renderer2d::new(&mut state)?,
@@ -1247,6 +1613,10 @@ impl Renderer {
             shader_cache,
             scene_render_passes: Default::default(),
             matrix_storage: MatrixStorageCache::new(&mut state)?,
+            frame_capture_requested: false,
+            transient_pool: Default::default(),
+            frame_arena: Default::default(),
+            captured_frame: None,
             state,
         })
     }
@@ -1287,11 +1657,316 @@ impl Renderer {
         self.texture_cache.unload(texture)
     }
 
+    /// Uploads the given texture to GPU memory, if it is not uploaded yet. Normally textures are
+    /// uploaded on demand, when they are used for the first time, this method could be used to do
+    /// this beforehand (for example, during a loading screen) to prevent hitches. The texture must
+    /// be loaded.
+    pub fn prewarm_texture(&mut self, texture: &TextureResource) {
+        self.texture_cache.get(&mut self.state, texture);
+    }
+
+    /// Uploads the given surface data to GPU memory, if it is not uploaded yet. See
+    /// [`Self::prewarm_texture`] for more info.
+    pub fn prewarm_surface_data(&mut self, data: &SurfaceSharedData) {
+        self.geometry_cache.get(&mut self.state, data);
+    }
+
+    /// Compiles GPU programs of the given shader, if they are not compiled yet. See
+    /// [`Self::prewarm_texture`] for more info. The shader must be loaded.
+    pub fn prewarm_shader(&mut self, shader: &ShaderResource) {
+        self.shader_cache.get(&mut self.state, shader);
+    }
+
+    /// Compiles GPU programs of every shader used by the given scene (see [`Scene::collect_used_shaders`]),
+    /// so there will be no hitches when new materials appear on screen. It is best to call this method
+    /// while the scene is loading, in combination with [`Self::load_pipeline_cache`] it makes the
+    /// compilation almost instant. Returns amount of shaders, that were compiled successfully.
+    pub fn precompile_scene_shaders(&mut self, scene: &Scene) -> usize {
+        scene
+            .collect_used_shaders()
+            .iter()
+            .filter(|shader| self.shader_cache.get(&mut self.state, shader).is_some())
+            .count()
+    }
+
+    /// Enables pipeline cache, that stores compiled GPU programs in the given file. GPU programs
+    /// stored in the cache will be loaded in binary form, instead of compiling them from source code
+    /// which is much faster. The cache is driver-specific, and it will be discarded if GPU, driver
+    /// or OpenGL version were changed. Built-in shaders of the renderer are compiled when the renderer
+    /// is created, so the cache affects only shaders created after this call (materials, etc.). Use
+    /// [`Self::save_pipeline_cache`] to write new programs to the file. Returns amount of programs
+    /// loaded from the file. Has no effect on WebAssembly and on drivers without program binaries
+    /// support (OpenGL 4.1 or `GL_ARB_get_program_binary` is required).
+    pub fn load_pipeline_cache<P: AsRef<Path>>(&mut self, path: P) -> usize {
+        #[cfg(not(target_arch = "wasm32"))]
+        let supported = self.state.program_binary_functions().is_some();
+        #[cfg(target_arch = "wasm32")]
+        let supported = false;
+        if !supported {
+            Log::warn("Pipeline cache is not supported by the driver.");
+            return 0;
+        }
+
+        let device_id = program_cache::device_id(&self.state);
+        let cache = ProgramBinaryCache::load(path, device_id);
+        let count = cache.len();
+        self.state.set_program_binary_cache(Some(cache));
+        count
+    }
+
+    /// Saves the pipeline cache (see [`Self::load_pipeline_cache`]) to the given file. The file is
+    /// written only if there are new programs in the cache.
+    pub fn save_pipeline_cache<P: AsRef<Path>>(&mut self, path: P) -> Result<(), FrameworkError> {
+        match self.state.program_binary_cache_mut() {
+            Some(cache) => {
+                if cache.is_modified() {
+                    cache.save(path).map_err(|e| {
+                        FrameworkError::Custom(format!(
+                            "Unable to save pipeline cache. Reason: {}",
+                            e
+                        ))
+                    })?;
+                }
+                Ok(())
+            }
+            None => Err(FrameworkError::Custom(
+                "Pipeline cache is not enabled!".to_string(),
+            )),
+        }
+    }
+
     /// Sets color which will be used to fill screen when there is nothing to render.
     pub fn set_backbuffer_clear_color(&mut self, color: Color) {
         self.backbuffer_clear_color = color;
     }
 
+    /// Requests the content of the back buffer (scenes and UI) to be read when the next frame is
+    /// rendered. The frame could then be taken using [`Self::take_captured_frame`]. Reading of
+    /// the pixels stalls the GPU pipeline, so it should be done only when necessary (for
+    /// screenshots or video capture).
+    pub fn request_frame_capture(&mut self) {
+        self.frame_capture_requested = true;
+    }
+
+    /// Takes the frame, that was captured after [`Self::request_frame_capture`] call.
+    pub fn take_captured_frame(&mut self) -> Option<CapturedFrame> {
+        self.captured_frame.take()
+    }
+
+    fn capture_frame(&mut self) {
+        if !std::mem::take(&mut self.frame_capture_requested) {
+            return;
+        }
+
+        let (width, height) = self.frame_size;
+        let pixels = self.backbuffer.read_pixels(
+            &mut self.state,
+            Rect::new(0, 0, width as i32, height as i32),
+        );
+
+        self.captured_frame = Some(CapturedFrame {
+            size: Vector2::new(width, height),
+            pixels: flip_rows(pixels, width),
+        });
+    }
+
+    /// Renders albedo and normals of the given scene, as seen from the given camera, into an
+    /// off-screen G-Buffer of the given size and reads them back. Lighting and post-effects are
+    /// not applied. This is useful to bake "flat" representations of objects, such as impostors.
+    /// Reading of the pixels stalls the GPU pipeline, so it should not be done every frame.
+    pub fn render_scene_gbuffer(
+        &mut self,
+        scene: &Scene,
+        camera: Handle<Node>,
+        size: Vector2<u32>,
+    ) -> Result<GBufferCapture, FrameworkError> {
+        let camera = scene
+            .graph
+            .try_get(camera)
+            .and_then(|node| node.cast::<Camera>())
+            .ok_or_else(|| FrameworkError::Custom("Camera handle is invalid!".to_string()))?;
+
+        self.render_scene_gbuffer_filtered(scene, camera, size, |_| true)
+    }
+
+    /// Same as [`Self::render_scene_gbuffer`], but the camera does not have to be a part of the
+    /// scene (its matrices must be calculated beforehand) and only the nodes that pass the given
+    /// filter are rendered.
+    pub(crate) fn render_scene_gbuffer_filtered<F>(
+        &mut self,
+        scene: &Scene,
+        camera: &Camera,
+        size: Vector2<u32>,
+        filter: F,
+    ) -> Result<GBufferCapture, FrameworkError>
+    where
+        F: FnMut(Handle<Node>) -> bool,
+    {
+        let (width, height) = (size.x.max(1), size.y.max(1));
+        let mut gbuffer = GBuffer::new(&mut self.state, width as usize, height as usize)?;
+
+        let batch_storage = RenderDataBatchStorage::from_graph_filtered(
+            &scene.graph,
+            ObserverInfo {
+                observer_position: camera.global_position(),
+                z_near: camera.projection().z_near(),
+                z_far: camera.projection().z_far(),
+                view_matrix: camera.view_matrix(),
+                projection_matrix: camera.projection_matrix(),
+            },
+            GBUFFER_PASS_NAME.clone(),
+            &self.frame_arena,
+            filter,
+        );
+
+        self.statistics += gbuffer.fill(GBufferRenderContext {
+            state: &mut self.state,
+            camera,
+            geom_cache: &mut self.geometry_cache,
+            batch_storage: &batch_storage,
+            texture_cache: &mut self.texture_cache,
+            shader_cache: &mut self.shader_cache,
+            environment_dummy: self.environment_dummy.clone(),
+            use_parallax_mapping: self.quality_settings.use_parallax_mapping,
+            normal_dummy: self.normal_dummy.clone(),
+            white_dummy: self.white_dummy.clone(),
+            black_dummy: self.black_dummy.clone(),
+            volume_dummy: self.volume_dummy.clone(),
+            graph: &scene.graph,
+            light_probes: scene.light_probes(),
+            lightmap: scene.lightmap(),
+            matrix_storage: &mut self.matrix_storage,
+            foliage_renderer: &mut self.foliage_renderer,
+        })?;
+
+        let region = Rect::new(0, 0, width as i32, height as i32);
+        let framebuffer = gbuffer.framebuffer();
+        let diffuse = framebuffer.read_attachment_pixels(&mut self.state, 0, region);
+        let normal = framebuffer.read_attachment_pixels(&mut self.state, 1, region);
+
+        Ok(GBufferCapture {
+            diffuse: CapturedFrame {
+                size: Vector2::new(width, height),
+                pixels: flip_rows(diffuse, width),
+            },
+            normal: CapturedFrame {
+                size: Vector2::new(width, height),
+                pixels: flip_rows(normal, width),
+            },
+        })
+    }
+
+    /// Renders the given scene, as seen from the given camera, into an off-screen high dynamic range
+    /// frame of the given size and reads it back. Only opaque (deferred) and forward geometry is
+    /// rendered with lighting, particles, sprites and post-effects (including tone mapping) are not
+    /// applied, so the result contains linear radiance of the scene. This is useful to bake lighting
+    /// data, such as environment maps or light probes. Reading of the pixels stalls the GPU
+    /// pipeline, so it should not be done every frame.
+    pub fn render_scene_hdr(
+        &mut self,
+        scene: &Scene,
+        camera: Handle<Node>,
+        size: Vector2<u32>,
+    ) -> Result<HdrCapturedFrame, FrameworkError> {
+        let camera = scene
+            .graph
+            .try_get(camera)
+            .and_then(|node| node.cast::<Camera>())
+            .ok_or_else(|| FrameworkError::Custom("Camera handle is invalid!".to_string()))?;
+
+        let (width, height) = (size.x.max(1), size.y.max(1));
+        let viewport = Rect::new(0, 0, width as i32, height as i32);
+        let state = &mut self.state;
+        let mut scene_data = AssociatedSceneData::new(state, width as usize, height as usize)?;
+
+        let batch_storage = RenderDataBatchStorage::from_graph(
+            &scene.graph,
+            ObserverInfo {
+                observer_position: camera.global_position(),
+                z_near: camera.projection().z_near(),
+                z_far: camera.projection().z_far(),
+                view_matrix: camera.view_matrix(),
+                projection_matrix: camera.projection_matrix(),
+            },
+            GBUFFER_PASS_NAME.clone(),
+            &self.frame_arena,
+        );
+
+        self.statistics += scene_data.gbuffer.fill(GBufferRenderContext {
+            state,
+            camera,
+            geom_cache: &mut self.geometry_cache,
+            batch_storage: &batch_storage,
+            texture_cache: &mut self.texture_cache,
+            shader_cache: &mut self.shader_cache,
+            environment_dummy: self.environment_dummy.clone(),
+            use_parallax_mapping: self.quality_settings.use_parallax_mapping,
+            normal_dummy: self.normal_dummy.clone(),
+            white_dummy: self.white_dummy.clone(),
+            black_dummy: self.black_dummy.clone(),
+            volume_dummy: self.volume_dummy.clone(),
+            graph: &scene.graph,
+            light_probes: scene.light_probes(),
+            lightmap: scene.lightmap(),
+            matrix_storage: &mut self.matrix_storage,
+            foliage_renderer: &mut self.foliage_renderer,
+        })?;
+
+        scene_data.copy_depth_stencil_to_scene_framebuffer(state);
+        scene_data
+            .hdr_scene_framebuffer
+            .clear(state, viewport, Some(Color::BLACK), None, Some(0));
+
+        let (pass_stats, light_stats) =
+            self.deferred_light_renderer
+                .render(DeferredRendererContext {
+                    state,
+                    scene,
+                    camera,
+                    gbuffer: &mut scene_data.gbuffer,
+                    white_dummy: self.white_dummy.clone(),
+                    ambient_color: scene.ambient_lighting_color,
+                    settings: &self.quality_settings,
+                    textures: &mut self.texture_cache,
+                    geometry_cache: &mut self.geometry_cache,
+                    frame_buffer: &mut scene_data.hdr_scene_framebuffer,
+                    shader_cache: &mut self.shader_cache,
+                    normal_dummy: self.normal_dummy.clone(),
+                    black_dummy: self.black_dummy.clone(),
+                    volume_dummy: self.volume_dummy.clone(),
+                    matrix_storage: &mut self.matrix_storage,
+                    frame_arena: &self.frame_arena,
+                })?;
+        self.statistics.lighting += light_stats;
+        self.statistics.geometry += pass_stats;
+
+        self.statistics += self.forward_renderer.render(ForwardRenderContext {
+            state,
+            camera,
+            geom_cache: &mut self.geometry_cache,
+            texture_cache: &mut self.texture_cache,
+            shader_cache: &mut self.shader_cache,
+            batch_storage: &batch_storage,
+            framebuffer: &mut scene_data.hdr_scene_framebuffer,
+            viewport,
+            quality_settings: &self.quality_settings,
+            white_dummy: self.white_dummy.clone(),
+            normal_dummy: self.normal_dummy.clone(),
+            black_dummy: self.black_dummy.clone(),
+            volume_dummy: self.volume_dummy.clone(),
+            matrix_storage: &mut self.matrix_storage,
+        })?;
+
+        let pixels = scene_data
+            .hdr_scene_framebuffer
+            .read_pixels_f32(state, viewport);
+
+        Ok(HdrCapturedFrame {
+            size: Vector2::new(width, height),
+            pixels: flip_rows(pixels, width),
+        })
+    }
+
     /// Returns a reference to current pipeline state.
     pub fn pipeline_state(&mut self) -> &mut PipelineState {
         &mut self.state
@@ -1349,6 +2024,70 @@ impl Renderer {
         self.texture_cache.clear();
         self.geometry_cache.clear();
         self.renderer2d.flush();
+        self.foliage_renderer.flush();
+        self.water_renderer.flush();
+    }
+
+    /// Executes the given painting commands on the given texture. Painting is done on GPU, the result
+    /// is kept in GPU memory and it is used by every renderer instead of the data of the texture. This
+    /// means that the data of the texture on CPU side remains unchanged, so painting is not saved
+    /// together with the texture. If the data of the texture is modified, the painted content will be
+    /// overwritten. Only uncompressed rectangle textures can be painted, painting affects only the
+    /// first mip level.
+    ///
+    /// ```rust,no_run
+    /// use fyrox::{
+    ///     core::{algebra::Vector2, color::Color},
+    ///     renderer::{
+    ///         framework::error::FrameworkError,
+    ///         paint::{PaintBlendMode, PaintBrush, PaintCommandList},
+    ///         Renderer,
+    ///     },
+    ///     resource::texture::TextureResource,
+    /// };
+    ///
+    /// // Reveals a circular region of a fog-of-war map around the player.
+    /// fn reveal(
+    ///     renderer: &mut Renderer,
+    ///     fog_of_war: &TextureResource,
+    ///     player_position: Vector2<f32>,
+    /// ) -> Result<(), FrameworkError> {
+    ///     let mut commands = PaintCommandList::new();
+    ///     commands.stamp(
+    ///         player_position,
+    ///         PaintBrush {
+    ///             radius: 32.0,
+    ///             hardness: 0.25,
+    ///             blend_mode: PaintBlendMode::Erase,
+    ///             ..Default::default()
+    ///         },
+    ///     );
+    ///     renderer.paint_texture(fog_of_war, &commands)
+    /// }
+    /// ```
+    pub fn paint_texture(
+        &mut self,
+        texture: &TextureResource,
+        commands: &PaintCommandList,
+    ) -> Result<(), FrameworkError> {
+        self.statistics += self.texture_painter.paint(
+            PaintContext {
+                state: &mut self.state,
+                texture_cache: &mut self.texture_cache,
+                white_dummy: self.white_dummy.clone(),
+            },
+            texture,
+            commands,
+        )?;
+
+        Ok(())
+    }
+
+    /// Discards everything that was painted on the given texture (see [`Self::paint_texture`]), the
+    /// texture will be re-uploaded to GPU from its data.
+    pub fn discard_texture_paint(&mut self, texture: &TextureResource) {
+        self.texture_painter
+            .discard(&mut self.texture_cache, texture);
     }
 
     /// Renders given UI into specified render target. This method is especially useful if you need
@@ -1466,7 +2205,8 @@ impl Renderer {
         self.shader_cache.update(dt)
     }
 
-    /// Update caches - this will remove timed out resources.
+    /// Update caches - this will decrease lifetime of cached resources, timed out textures, shaders and
+    /// geometry buffers are then destroyed by [`Self::collect_garbage`].
     ///
     /// Normally, this is called from `Engine::update()`.
     /// You should only call this manually if you don't use that method.
@@ -1475,20 +2215,38 @@ impl Renderer {
         self.update_shader_cache(dt);
         self.geometry_cache.update(dt);
         self.renderer2d.update_caches(dt);
+        self.foliage_renderer.update_caches(dt);
+        self.water_renderer.update_caches(dt);
     }
 
+    /// Destroys timed out GPU resources (textures, shaders, geometry buffers) until the given time budget
+    /// is exhausted, remaining ones will be destroyed on next calls.
+    ///
+    /// Normally, this is called from `Engine::update()`.
+    /// You should only call this manually if you don't use that method.
+    pub fn collect_garbage(&mut self, budget: &TimeBudget) {
+        self.texture_cache.collect_garbage(budget);
+        self.shader_cache.collect_garbage(budget);
+        self.geometry_cache.collect_garbage(budget);
+    }
+
+    /// Renders every enabled scene of the given list. The list contains handles of all the scenes
+    /// paired with the scenes themselves (or their snapshots, see [`snapshot::RenderSnapshot`]);
+    /// scenes without a pair are not rendered, but their associated data is kept.
     fn render_frame(
         &mut self,
-        scenes: &SceneContainer,
+        scenes: &[(Handle<Scene>, Option<&Scene>)],
         drawing_context: &DrawingContext,
     ) -> Result<(), FrameworkError> {
         scope_profile!();
 
         self.matrix_storage.begin_frame();
+        self.transient_pool.update();
+        self.frame_arena.reset();
 
         // Make sure to drop associated data for destroyed scenes.
         self.scene_data_map
-            .retain(|h, _| scenes.is_valid_handle(*h));
+            .retain(|h, _| scenes.iter().any(|(handle, _)| handle == h));
 
         // We have to invalidate resource bindings cache because some textures or programs,
         // or other GL resources can be destroyed and then on their "names" some new resource
@@ -1510,7 +2268,11 @@ impl Renderer {
         let backbuffer_width = self.frame_size.0 as f32;
         let backbuffer_height = self.frame_size.1 as f32;
 
-        for (scene_handle, scene) in scenes.pair_iter().filter(|(_, s)| s.enabled) {
+        for (scene_handle, scene) in scenes
+            .iter()
+            .filter_map(|(handle, scene)| scene.map(|scene| (*handle, scene)))
+            .filter(|(_, s)| s.enabled)
+        {
             let graph = &scene.graph;
 
             let frame_size = scene
@@ -1596,226 +2358,406 @@ impl Renderer {
                         projection_matrix: camera.projection_matrix(),
                     },
                     GBUFFER_PASS_NAME.clone(),
+                    &self.frame_arena,
                 );
 
-                state.set_polygon_fill_mode(
-                    PolygonFace::FrontAndBack,
-                    scene.polygon_rasterization_mode,
-                );
-
-                self.statistics += scene_associated_data.gbuffer.fill(GBufferRenderContext {
-                    state,
-                    camera,
-                    geom_cache: &mut self.geometry_cache,
-                    batch_storage: &batch_storage,
-                    texture_cache: &mut self.texture_cache,
-                    shader_cache: &mut self.shader_cache,
-                    environment_dummy: self.environment_dummy.clone(),
-                    use_parallax_mapping: self.quality_settings.use_parallax_mapping,
-                    normal_dummy: self.normal_dummy.clone(),
-                    white_dummy: self.white_dummy.clone(),
-                    black_dummy: self.black_dummy.clone(),
-                    volume_dummy: self.volume_dummy.clone(),
-                    graph,
-                    matrix_storage: &mut self.matrix_storage,
-                })?;
-
-                state.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
-
-                scene_associated_data.copy_depth_stencil_to_scene_framebuffer(state);
-
-                scene_associated_data.hdr_scene_framebuffer.clear(
-                    state,
-                    viewport,
-                    Some(self.backbuffer_clear_color),
-                    None, // Keep depth, we've just copied valid data in it.
-                    Some(0),
-                );
-
-                let (pass_stats, light_stats) =
-                    self.deferred_light_renderer
-                        .render(DeferredRendererContext {
-                            state,
-                            scene,
-                            camera,
-                            gbuffer: &mut scene_associated_data.gbuffer,
-                            white_dummy: self.white_dummy.clone(),
-                            ambient_color: scene.ambient_lighting_color,
-                            settings: &self.quality_settings,
-                            textures: &mut self.texture_cache,
-                            geometry_cache: &mut self.geometry_cache,
-                            frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                            shader_cache: &mut self.shader_cache,
-                            normal_dummy: self.normal_dummy.clone(),
-                            black_dummy: self.black_dummy.clone(),
-                            volume_dummy: self.volume_dummy.clone(),
-                            matrix_storage: &mut self.matrix_storage,
-                        })?;
-
-                self.statistics.lighting += light_stats;
-                self.statistics.geometry += pass_stats;
-
-                let depth = scene_associated_data.gbuffer.depth();
-
-                self.statistics +=
-                    self.particle_system_renderer
-                        .render(ParticleSystemRenderContext {
-                            state,
-                            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                            graph,
-                            camera,
-                            white_dummy: self.white_dummy.clone(),
-                            depth,
-                            frame_width: frame_size.x,
-                            frame_height: frame_size.y,
-                            viewport,
-                            texture_cache: &mut self.texture_cache,
-                        })?;
-
-                self.statistics += self.sprite_renderer.render(SpriteRenderContext {
-                    state,
-                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                    graph,
-                    camera,
-                    white_dummy: self.white_dummy.clone(),
-                    viewport,
-                    textures: &mut self.texture_cache,
-                })?;
-
-                self.statistics += self.renderer2d.render(
-                    state,
-                    camera,
-                    &mut scene_associated_data.hdr_scene_framebuffer,
-                    viewport,
-                    graph,
-                    &mut self.texture_cache,
-                    self.white_dummy.clone(),
-                    scene.ambient_lighting_color,
-                )?;
-
-                self.statistics += self.forward_renderer.render(ForwardRenderContext {
-                    state,
-                    camera,
-                    geom_cache: &mut self.geometry_cache,
-                    texture_cache: &mut self.texture_cache,
-                    shader_cache: &mut self.shader_cache,
-                    batch_storage: &batch_storage,
-                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                    viewport,
-                    quality_settings: &self.quality_settings,
-                    white_dummy: self.white_dummy.clone(),
-                    normal_dummy: self.normal_dummy.clone(),
-                    black_dummy: self.black_dummy.clone(),
-                    volume_dummy: self.volume_dummy.clone(),
-                    matrix_storage: &mut self.matrix_storage,
-                })?;
-
-                for render_pass in self.scene_render_passes.iter() {
-                    self.statistics +=
-                        render_pass
-                            .borrow_mut()
-                            .on_hdr_render(SceneRenderPassContext {
-                                pipeline_state: state,
-                                texture_cache: &mut self.texture_cache,
-                                geometry_cache: &mut self.geometry_cache,
-                                quality_settings: &self.quality_settings,
-                                batch_storage: &batch_storage,
-                                viewport,
-                                scene,
-                                camera,
-                                scene_handle,
-                                white_dummy: self.white_dummy.clone(),
-                                normal_dummy: self.normal_dummy.clone(),
-                                metallic_dummy: self.metallic_dummy.clone(),
-                                environment_dummy: self.environment_dummy.clone(),
-                                black_dummy: self.black_dummy.clone(),
-                                depth_texture: scene_associated_data.gbuffer.depth(),
-                                normal_texture: scene_associated_data.gbuffer.normal_texture(),
-                                ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
-                                framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                                ui_renderer: &mut self.ui_renderer,
-                            })?;
+                let mut frame_graph = FrameGraph::new();
+                let gbuffer = frame_graph.import("GBuffer");
+                let hdr_frame = frame_graph.import("HdrFrame");
+                let bloom = frame_graph.import("Bloom");
+                let ldr_frame = frame_graph.import("LdrFrame");
+
+                frame_graph
+                    .add_pass("GBuffer", ScenePass::GBuffer)
+                    .write(gbuffer);
+                frame_graph
+                    .add_pass("Lighting", ScenePass::Lighting)
+                    .read(gbuffer)
+                    .write(hdr_frame);
+                if graph
+                    .linear_iter()
+                    .any(|node| node.cast::<Water>().is_some())
+                {
+                    let refraction = frame_graph.create_transient(
+                        "WaterRefraction",
+                        TransientTextureDescriptor {
+                            width: frame_size.x as usize,
+                            height: frame_size.y as usize,
+                            pixel_kind: PixelKind::RGBA16F,
+                        },
+                    );
+                    frame_graph
+                        .add_pass(
+                            "WaterRefraction",
+                            ScenePass::WaterRefraction { target: refraction },
+                        )
+                        .read(hdr_frame)
+                        .write(refraction);
+                    frame_graph
+                        .add_pass("Water", ScenePass::Water { refraction })
+                        .read(gbuffer)
+                        .read(refraction)
+                        .write(hdr_frame);
                 }
-
-                let quad = &self.quad;
-
-                // Prepare glow map.
-                self.statistics.geometry += scene_associated_data.bloom_renderer.render(
-                    state,
-                    quad,
-                    scene_associated_data.hdr_scene_frame_texture(),
-                )?;
-
-                // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
-                self.statistics.geometry += scene_associated_data.hdr_renderer.render(
-                    state,
-                    scene_associated_data.hdr_scene_frame_texture(),
-                    scene_associated_data.bloom_renderer.result(),
-                    &mut scene_associated_data.ldr_scene_framebuffer,
-                    viewport,
-                    quad,
-                    dt,
-                    camera.exposure(),
-                    camera.color_grading_lut_ref(),
-                    camera.color_grading_enabled(),
-                    &mut self.texture_cache,
-                )?;
-
-                // Apply FXAA if needed.
+                frame_graph
+                    .add_pass("Particles", ScenePass::Particles)
+                    .read(gbuffer)
+                    .write(hdr_frame);
+                frame_graph
+                    .add_pass("Sprites", ScenePass::Sprites)
+                    .write(hdr_frame);
+                frame_graph
+                    .add_pass("Renderer2d", ScenePass::Renderer2d)
+                    .write(hdr_frame);
+                frame_graph
+                    .add_pass("Forward", ScenePass::Forward)
+                    .write(hdr_frame);
+                frame_graph
+                    .add_pass("CustomHdr", ScenePass::CustomHdr)
+                    .read(gbuffer)
+                    .write(hdr_frame);
+                frame_graph
+                    .add_pass("Bloom", ScenePass::Bloom)
+                    .read(hdr_frame)
+                    .write(bloom);
+                frame_graph
+                    .add_pass("ToneMapping", ScenePass::ToneMapping)
+                    .read(hdr_frame)
+                    .read(bloom)
+                    .write(ldr_frame);
                 if self.quality_settings.fxaa {
-                    self.statistics.geometry += self.fxaa_renderer.render(
-                        state,
-                        viewport,
-                        scene_associated_data.ldr_scene_frame_texture(),
-                        &mut scene_associated_data.ldr_temp_framebuffer,
-                    )?;
-
-                    let quad = &self.quad;
-                    let temp_frame_texture = scene_associated_data.ldr_temp_frame_texture();
-                    self.statistics.geometry += blit_pixels(
-                        state,
-                        &mut scene_associated_data.ldr_scene_framebuffer,
-                        temp_frame_texture,
-                        &self.flat_shader,
-                        viewport,
-                        quad,
-                    )?;
+                    let fxaa_frame = frame_graph.create_transient(
+                        "FxaaFrame",
+                        TransientTextureDescriptor {
+                            width: frame_size.x as usize,
+                            height: frame_size.y as usize,
+                            pixel_kind: PixelKind::RGBA8,
+                        },
+                    );
+                    frame_graph
+                        .add_pass("Fxaa", ScenePass::Fxaa { target: fxaa_frame })
+                        .read(ldr_frame)
+                        .write(fxaa_frame);
+                    frame_graph
+                        .add_pass("FxaaResolve", ScenePass::FxaaResolve { source: fxaa_frame })
+                        .read(fxaa_frame)
+                        .write(ldr_frame);
                 }
+                frame_graph
+                    .add_pass("Debug", ScenePass::Debug)
+                    .write(ldr_frame);
+                frame_graph
+                    .add_pass("CustomLdr", ScenePass::CustomLdr)
+                    .read(gbuffer)
+                    .write(ldr_frame);
+                frame_graph.mark_output(ldr_frame);
+
+                let frame_graph = frame_graph.compile()?;
+                self.transient_pool.prepare(state, &frame_graph)?;
+
+                for pass in frame_graph.passes() {
+                    match *pass.payload() {
+                        ScenePass::GBuffer => {
+                            state.set_polygon_fill_mode(
+                                PolygonFace::FrontAndBack,
+                                scene.polygon_rasterization_mode,
+                            );
 
-                // Render debug geometry in the LDR frame buffer.
-                self.statistics += self.debug_renderer.render(
-                    state,
-                    viewport,
-                    &mut scene_associated_data.ldr_scene_framebuffer,
-                    &scene.drawing_context,
-                    camera,
-                )?;
+                            self.statistics +=
+                                scene_associated_data.gbuffer.fill(GBufferRenderContext {
+                                    state,
+                                    camera,
+                                    geom_cache: &mut self.geometry_cache,
+                                    batch_storage: &batch_storage,
+                                    texture_cache: &mut self.texture_cache,
+                                    shader_cache: &mut self.shader_cache,
+                                    environment_dummy: self.environment_dummy.clone(),
+                                    use_parallax_mapping: self
+                                        .quality_settings
+                                        .use_parallax_mapping,
+                                    normal_dummy: self.normal_dummy.clone(),
+                                    white_dummy: self.white_dummy.clone(),
+                                    black_dummy: self.black_dummy.clone(),
+                                    volume_dummy: self.volume_dummy.clone(),
+                                    graph,
+                                    light_probes: scene.light_probes(),
+                                    lightmap: scene.lightmap(),
+                                    matrix_storage: &mut self.matrix_storage,
+                                    foliage_renderer: &mut self.foliage_renderer,
+                                })?;
+
+                            state.set_polygon_fill_mode(
+                                PolygonFace::FrontAndBack,
+                                PolygonFillMode::Fill,
+                            );
+                        }
+                        ScenePass::Lighting => {
+                            scene_associated_data.copy_depth_stencil_to_scene_framebuffer(state);
 
-                for render_pass in self.scene_render_passes.iter() {
-                    self.statistics +=
-                        render_pass
-                            .borrow_mut()
-                            .on_ldr_render(SceneRenderPassContext {
-                                pipeline_state: state,
-                                texture_cache: &mut self.texture_cache,
-                                geometry_cache: &mut self.geometry_cache,
-                                quality_settings: &self.quality_settings,
-                                batch_storage: &batch_storage,
+                            scene_associated_data.hdr_scene_framebuffer.clear(
+                                state,
                                 viewport,
-                                scene,
+                                Some(self.backbuffer_clear_color),
+                                None, // Keep depth, we've just copied valid data in it.
+                                Some(0),
+                            );
+
+                            let (pass_stats, light_stats) =
+                                self.deferred_light_renderer
+                                    .render(DeferredRendererContext {
+                                        state,
+                                        scene,
+                                        camera,
+                                        gbuffer: &mut scene_associated_data.gbuffer,
+                                        white_dummy: self.white_dummy.clone(),
+                                        ambient_color: scene.ambient_lighting_color,
+                                        settings: &self.quality_settings,
+                                        textures: &mut self.texture_cache,
+                                        geometry_cache: &mut self.geometry_cache,
+                                        frame_buffer: &mut scene_associated_data
+                                            .hdr_scene_framebuffer,
+                                        shader_cache: &mut self.shader_cache,
+                                        normal_dummy: self.normal_dummy.clone(),
+                                        black_dummy: self.black_dummy.clone(),
+                                        volume_dummy: self.volume_dummy.clone(),
+                                        matrix_storage: &mut self.matrix_storage,
+                                        frame_arena: &self.frame_arena,
+                                    })?;
+
+                            self.statistics.lighting += light_stats;
+                            self.statistics.geometry += pass_stats;
+                        }
+                        ScenePass::WaterRefraction { target } => {
+                            // Water refracts and reflects the scene, so it needs a copy of the
+                            // frame, that was rendered so far.
+                            let target = self
+                                .transient_pool
+                                .framebuffer(&frame_graph, target)
+                                .expect("Water refraction target must be prepared!");
+                            state.blit_framebuffer(
+                                scene_associated_data.hdr_scene_framebuffer.id(),
+                                target.id(),
+                                0,
+                                0,
+                                frame_size.x as i32,
+                                frame_size.y as i32,
+                                0,
+                                0,
+                                frame_size.x as i32,
+                                frame_size.y as i32,
+                                true,
+                                false,
+                                false,
+                            );
+                        }
+                        ScenePass::Water { refraction } => {
+                            let scene_color = self
+                                .transient_pool
+                                .texture(&frame_graph, refraction)
+                                .expect("Water refraction target must be prepared!");
+
+                            self.statistics += self.water_renderer.render(WaterRenderContext {
+                                state,
+                                framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                                graph,
                                 camera,
-                                scene_handle,
-                                white_dummy: self.white_dummy.clone(),
+                                scene_color,
+                                depth: scene_associated_data.gbuffer.depth(),
                                 normal_dummy: self.normal_dummy.clone(),
-                                metallic_dummy: self.metallic_dummy.clone(),
                                 environment_dummy: self.environment_dummy.clone(),
-                                black_dummy: self.black_dummy.clone(),
-                                depth_texture: scene_associated_data.gbuffer.depth(),
-                                normal_texture: scene_associated_data.gbuffer.normal_texture(),
-                                ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
-                                framebuffer: &mut scene_associated_data.ldr_scene_framebuffer,
-                                ui_renderer: &mut self.ui_renderer,
+                                ambient_color: scene.ambient_lighting_color,
+                                viewport,
+                                frame_size,
+                                textures: &mut self.texture_cache,
                             })?;
+                        }
+                        ScenePass::Particles => {
+                            let depth = scene_associated_data.gbuffer.depth();
+
+                            self.statistics += self.particle_system_renderer.render(
+                                ParticleSystemRenderContext {
+                                    state,
+                                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                                    graph,
+                                    camera,
+                                    white_dummy: self.white_dummy.clone(),
+                                    depth,
+                                    frame_width: frame_size.x,
+                                    frame_height: frame_size.y,
+                                    viewport,
+                                    texture_cache: &mut self.texture_cache,
+                                },
+                            )?;
+                        }
+                        ScenePass::Sprites => {
+                            self.statistics +=
+                                self.sprite_renderer.render(SpriteRenderContext {
+                                    state,
+                                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                                    graph,
+                                    camera,
+                                    white_dummy: self.white_dummy.clone(),
+                                    viewport,
+                                    textures: &mut self.texture_cache,
+                                })?;
+                        }
+                        ScenePass::Renderer2d => {
+                            self.statistics += self.renderer2d.render(
+                                state,
+                                camera,
+                                &mut scene_associated_data.hdr_scene_framebuffer,
+                                viewport,
+                                graph,
+                                &mut self.texture_cache,
+                                self.white_dummy.clone(),
+                                scene.ambient_lighting_color,
+                            )?;
+                        }
+                        ScenePass::Forward => {
+                            self.statistics +=
+                                self.forward_renderer.render(ForwardRenderContext {
+                                    state,
+                                    camera,
+                                    geom_cache: &mut self.geometry_cache,
+                                    texture_cache: &mut self.texture_cache,
+                                    shader_cache: &mut self.shader_cache,
+                                    batch_storage: &batch_storage,
+                                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                                    viewport,
+                                    quality_settings: &self.quality_settings,
+                                    white_dummy: self.white_dummy.clone(),
+                                    normal_dummy: self.normal_dummy.clone(),
+                                    black_dummy: self.black_dummy.clone(),
+                                    volume_dummy: self.volume_dummy.clone(),
+                                    matrix_storage: &mut self.matrix_storage,
+                                })?;
+                        }
+                        ScenePass::CustomHdr => {
+                            for render_pass in self.scene_render_passes.iter() {
+                                self.statistics += render_pass.borrow_mut().on_hdr_render(
+                                    SceneRenderPassContext {
+                                        pipeline_state: state,
+                                        texture_cache: &mut self.texture_cache,
+                                        geometry_cache: &mut self.geometry_cache,
+                                        quality_settings: &self.quality_settings,
+                                        batch_storage: &batch_storage,
+                                        viewport,
+                                        scene,
+                                        camera,
+                                        scene_handle,
+                                        white_dummy: self.white_dummy.clone(),
+                                        normal_dummy: self.normal_dummy.clone(),
+                                        metallic_dummy: self.metallic_dummy.clone(),
+                                        environment_dummy: self.environment_dummy.clone(),
+                                        black_dummy: self.black_dummy.clone(),
+                                        depth_texture: scene_associated_data.gbuffer.depth(),
+                                        normal_texture: scene_associated_data
+                                            .gbuffer
+                                            .normal_texture(),
+                                        ambient_texture: scene_associated_data
+                                            .gbuffer
+                                            .ambient_texture(),
+                                        framebuffer: &mut scene_associated_data
+                                            .hdr_scene_framebuffer,
+                                        ui_renderer: &mut self.ui_renderer,
+                                    },
+                                )?;
+                            }
+                        }
+                        ScenePass::Bloom => {
+                            // Prepare glow map.
+                            self.statistics.geometry +=
+                                scene_associated_data.bloom_renderer.render(
+                                    state,
+                                    &self.quad,
+                                    scene_associated_data.hdr_scene_frame_texture(),
+                                )?;
+                        }
+                        ScenePass::ToneMapping => {
+                            // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
+                            self.statistics.geometry += scene_associated_data.hdr_renderer.render(
+                                state,
+                                scene_associated_data.hdr_scene_frame_texture(),
+                                scene_associated_data.bloom_renderer.result(),
+                                &mut scene_associated_data.ldr_scene_framebuffer,
+                                viewport,
+                                &self.quad,
+                                dt,
+                                camera.exposure(),
+                                camera.color_grading_lut_ref(),
+                                camera.color_grading_enabled(),
+                                &mut self.texture_cache,
+                            )?;
+                        }
+                        ScenePass::Fxaa { target } => {
+                            let target = self
+                                .transient_pool
+                                .framebuffer(&frame_graph, target)
+                                .expect("FXAA target must be prepared!");
+                            self.statistics.geometry += self.fxaa_renderer.render(
+                                state,
+                                viewport,
+                                scene_associated_data.ldr_scene_frame_texture(),
+                                target,
+                            )?;
+                        }
+                        ScenePass::FxaaResolve { source } => {
+                            let source = self
+                                .transient_pool
+                                .texture(&frame_graph, source)
+                                .expect("FXAA target must be prepared!");
+                            self.statistics.geometry += blit_pixels(
+                                state,
+                                &mut scene_associated_data.ldr_scene_framebuffer,
+                                source,
+                                &self.flat_shader,
+                                viewport,
+                                &self.quad,
+                            )?;
+                        }
+                        ScenePass::Debug => {
+                            // Render debug geometry in the LDR frame buffer.
+                            self.statistics += self.debug_renderer.render(
+                                state,
+                                viewport,
+                                &mut scene_associated_data.ldr_scene_framebuffer,
+                                &scene.drawing_context,
+                                camera,
+                            )?;
+                        }
+                        ScenePass::CustomLdr => {
+                            for render_pass in self.scene_render_passes.iter() {
+                                self.statistics += render_pass.borrow_mut().on_ldr_render(
+                                    SceneRenderPassContext {
+                                        pipeline_state: state,
+                                        texture_cache: &mut self.texture_cache,
+                                        geometry_cache: &mut self.geometry_cache,
+                                        quality_settings: &self.quality_settings,
+                                        batch_storage: &batch_storage,
+                                        viewport,
+                                        scene,
+                                        camera,
+                                        scene_handle,
+                                        white_dummy: self.white_dummy.clone(),
+                                        normal_dummy: self.normal_dummy.clone(),
+                                        metallic_dummy: self.metallic_dummy.clone(),
+                                        environment_dummy: self.environment_dummy.clone(),
+                                        black_dummy: self.black_dummy.clone(),
+                                        depth_texture: scene_associated_data.gbuffer.depth(),
+                                        normal_texture: scene_associated_data
+                                            .gbuffer
+                                            .normal_texture(),
+                                        ambient_texture: scene_associated_data
+                                            .gbuffer
+                                            .ambient_texture(),
+                                        framebuffer: &mut scene_associated_data
+                                            .ldr_scene_framebuffer,
+                                        ui_renderer: &mut self.ui_renderer,
+                                    },
+                                )?;
+                            }
+                        }
+                    }
                 }
             }
 
@@ -1854,12 +2796,13 @@ impl Renderer {
     #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn render_and_swap_buffers(
         &mut self,
-        scenes: &SceneContainer,
+        scenes: &[(Handle<Scene>, Option<&Scene>)],
         drawing_context: &DrawingContext,
         surface: &Surface<WindowSurface>,
         context: &PossiblyCurrentContext,
     ) -> Result<(), FrameworkError> {
         self.render_frame(scenes, drawing_context)?;
+        self.capture_frame();
         self.statistics.end_frame();
         surface.swap_buffers(context)?;
         self.state.check_error();
@@ -1871,14 +2814,141 @@ impl Renderer {
     #[cfg(target_arch = "wasm32")]
     pub(crate) fn render_and_swap_buffers(
         &mut self,
-        scenes: &SceneContainer,
+        scenes: &[(Handle<Scene>, Option<&Scene>)],
         drawing_context: &DrawingContext,
     ) -> Result<(), FrameworkError> {
         self.render_frame(scenes, drawing_context)?;
+        self.capture_frame();
         self.statistics.end_frame();
         self.state.check_error();
         self.statistics.finalize();
         self.statistics.pipeline = self.state.pipeline_statistics();
         Ok(())
     }
+
+    /// Renders the given UI drawing context directly into the back buffer of the given surface and
+    /// swaps its buffers. It is used to draw the content of secondary windows, which share the same
+    /// graphics context (and all GPU resources) with the main window. The surface must be current
+    /// when calling this method.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn render_ui_and_swap_buffers(
+        &mut self,
+        drawing_context: &DrawingContext,
+        frame_size: (u32, u32),
+        surface: &Surface<WindowSurface>,
+        context: &PossiblyCurrentContext,
+    ) -> Result<(), FrameworkError> {
+        let frame_width = frame_size.0.max(1);
+        let frame_height = frame_size.1.max(1);
+        let viewport = Rect::new(0, 0, frame_width as i32, frame_height as i32);
+
+        self.state.invalidate_resource_bindings_cache();
+
+        self.backbuffer.clear(
+            &mut self.state,
+            viewport,
+            Some(self.backbuffer_clear_color),
+            Some(1.0),
+            Some(0),
+        );
+
+        self.statistics += self.ui_renderer.render(UiRenderContext {
+            state: &mut self.state,
+            viewport,
+            frame_buffer: &mut self.backbuffer,
+            frame_width: frame_width as f32,
+            frame_height: frame_height as f32,
+            drawing_context,
+            white_dummy: self.white_dummy.clone(),
+            texture_cache: &mut self.texture_cache,
+        })?;
+
+        surface.swap_buffers(context)?;
+        self.state.check_error();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Point3, Vector3},
+        renderer::{
+            screen_coverage, LightLod, LightLodSettings, QualitySettings, ShadowFilter,
+            ShadowFilterSettings,
+        },
+    };
+
+    #[test]
+    fn test_light_lod() {
+        let projection = Matrix4::new_perspective(1.0, 90.0f32.to_radians(), 0.1, 1000.0);
+        let view = Matrix4::look_at_rh(
+            &Point3::origin(),
+            &Point3::new(0.0, 0.0, -1.0),
+            &Vector3::y(),
+        );
+        let view_projection = projection * view;
+
+        let coverage = |z: f32, radius: f32| {
+            screen_coverage(
+                &view_projection,
+                &projection,
+                Vector3::new(0.0, 0.0, z),
+                radius,
+            )
+        };
+
+        // Camera is inside the light.
+        assert_eq!(coverage(-1.0, 2.0), 1.0);
+        // Half of the frame height at distance of 10 units is 10 units (FOV is 90 degrees).
+        assert!((coverage(-10.0, 1.0) - 0.1).abs() < 0.001);
+        assert!(coverage(-100.0, 1.0) < coverage(-50.0, 1.0));
+
+        let settings = LightLodSettings {
+            enabled: true,
+            shadows_screen_coverage: 0.1,
+            full_lighting_screen_coverage: 0.05,
+            cull_screen_coverage: 0.01,
+            max_shadow_casting_lights: 1,
+        };
+        assert_eq!(settings.lod(0.5), LightLod::Full);
+        assert_eq!(settings.lod(0.07), LightLod::NoShadows);
+        assert_eq!(settings.lod(0.02), LightLod::Simple);
+        assert_eq!(settings.lod(0.001), LightLod::Culled);
+
+        let disabled = LightLodSettings {
+            enabled: false,
+            ..settings
+        };
+        assert_eq!(disabled.lod(0.001), LightLod::Full);
+    }
+
+    #[test]
+    fn test_shadow_filter_settings() {
+        // Must be in sync with the constants in shared.glsl.
+        assert_eq!(ShadowFilter::Pcf as i32, 0);
+        assert_eq!(ShadowFilter::PoissonDisc as i32, 1);
+        assert_eq!(ShadowFilter::Pcss as i32, 2);
+
+        let settings = ShadowFilterSettings {
+            filter: ShadowFilter::Pcss,
+            kernel_size: 0,
+            sample_count: 100,
+        };
+        assert_eq!(settings.clamped_kernel_size(), 1);
+        assert_eq!(settings.clamped_sample_count(), 32);
+
+        // Settings saved before shadow filtering was configurable must still be loadable.
+        let mut value = serde_json::to_value(QualitySettings::high()).unwrap();
+        value
+            .as_object_mut()
+            .unwrap()
+            .remove("shadow_filter_settings");
+        let settings: QualitySettings = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            settings.shadow_filter_settings,
+            ShadowFilterSettings::default()
+        );
+    }
 }
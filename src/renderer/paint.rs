@@ -0,0 +1,571 @@
+//! Runtime texture painting. It allows you to draw into a texture resource using brush stamps, lines
+//! and filled regions. Painting is done entirely on GPU, so it is fast enough to be used every frame
+//! for things like damage masks, fog-of-war maps or in-game painting mechanics. See
+//! [`crate::renderer::Renderer::paint_texture`] for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        math::Rect,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        cache::{texture::TextureCache, CacheEntry},
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, DrawParameters, FrameBuffer,
+            },
+            geometry_buffer::{ElementRange, GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{GpuTexture, GpuTextureKind, PixelKind},
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        RenderPassStatistics,
+    },
+    resource::texture::{TextureKind, TextureResource},
+    scene::mesh::surface::SurfaceData,
+};
+use fxhash::FxHashMap;
+use std::{cell::RefCell, collections::hash_map::Entry, rc::Rc};
+
+/// Defines how the color of a brush is combined with the pixels of a texture.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PaintBlendMode {
+    /// Usual alpha blending, the color of the brush is drawn over the pixels of a texture.
+    #[default]
+    Alpha = 0,
+    /// The color of the brush is added to the pixels of a texture.
+    Additive = 1,
+    /// The pixels of a texture are multiplied by the color of the brush.
+    Multiply = 2,
+    /// Makes the pixels of a texture transparent. The color of the brush is ignored, only its alpha
+    /// is used as the strength of erasing.
+    Erase = 3,
+    /// Overwrites the pixels of a texture with the color of the brush (including alpha). Soft edges of
+    /// the brush are ignored.
+    Replace = 4,
+}
+
+/// Shape of a brush.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PaintBrushShape {
+    /// Round brush.
+    #[default]
+    Circle,
+    /// Square brush.
+    Square,
+}
+
+/// Brush defines how every stamp looks like.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaintBrush {
+    /// Shape of the brush.
+    pub shape: PaintBrushShape,
+    /// Radius (half-size for square brushes) of the brush in pixels.
+    pub radius: f32,
+    /// Defines the size of the solid part of the brush in `[0; 1]` range. Zero means that the brush
+    /// fades out from its center to its edges, one means that the brush has sharp edges.
+    pub hardness: f32,
+    /// Color of the brush.
+    pub color: Color,
+    /// Optional texture of the brush, it is multiplied with the color of the brush.
+    pub texture: Option<TextureResource>,
+    /// Rotation of the brush in radians. It makes sense only for square or textured brushes.
+    pub rotation: f32,
+    /// Distance between stamps of a line, relative to the diameter of the brush.
+    pub spacing: f32,
+    /// Blending mode of the brush.
+    pub blend_mode: PaintBlendMode,
+}
+
+impl Default for PaintBrush {
+    fn default() -> Self {
+        Self {
+            shape: Default::default(),
+            radius: 8.0,
+            hardness: 0.5,
+            color: Color::WHITE,
+            texture: None,
+            rotation: 0.0,
+            spacing: 0.25,
+            blend_mode: Default::default(),
+        }
+    }
+}
+
+/// A single painting command.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaintCommand {
+    /// Fills the entire texture with the given color.
+    Clear {
+        /// Fill color.
+        color: Color,
+    },
+    /// Draws a single stamp of a brush.
+    Stamp {
+        /// Position of the center of the stamp in pixels.
+        position: Vector2<f32>,
+        /// Brush to stamp with.
+        brush: PaintBrush,
+    },
+    /// Draws a line as a sequence of stamps of a brush, see [`PaintBrush::spacing`].
+    Line {
+        /// Beginning of the line in pixels.
+        begin: Vector2<f32>,
+        /// End of the line in pixels.
+        end: Vector2<f32>,
+        /// Brush to draw the line with.
+        brush: PaintBrush,
+    },
+    /// Fills a rectangular region of the texture.
+    FillRect {
+        /// Region in pixels.
+        rect: Rect<f32>,
+        /// Fill color.
+        color: Color,
+        /// Blending mode of the fill.
+        blend_mode: PaintBlendMode,
+    },
+}
+
+/// A list of painting commands that will be executed in order. All positions are defined in pixels,
+/// `(0, 0)` corresponds to the first pixel of the texture data, which is sampled at `(0, 0)` texture
+/// coordinates. This means that a position could be calculated as `uv * texture_size`, for example
+/// from texture coordinates of a ray hit point.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PaintCommandList {
+    /// Commands of the list.
+    pub commands: Vec<PaintCommand>,
+}
+
+impl PaintCommandList {
+    /// Creates new empty command list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds [`PaintCommand::Clear`] command.
+    pub fn clear(&mut self, color: Color) -> &mut Self {
+        self.commands.push(PaintCommand::Clear { color });
+        self
+    }
+
+    /// Adds [`PaintCommand::Stamp`] command.
+    pub fn stamp(&mut self, position: Vector2<f32>, brush: PaintBrush) -> &mut Self {
+        self.commands.push(PaintCommand::Stamp { position, brush });
+        self
+    }
+
+    /// Adds [`PaintCommand::Line`] command.
+    pub fn line(&mut self, begin: Vector2<f32>, end: Vector2<f32>, brush: PaintBrush) -> &mut Self {
+        self.commands.push(PaintCommand::Line { begin, end, brush });
+        self
+    }
+
+    /// Adds [`PaintCommand::FillRect`] command.
+    pub fn fill_rect(
+        &mut self,
+        rect: Rect<f32>,
+        color: Color,
+        blend_mode: PaintBlendMode,
+    ) -> &mut Self {
+        self.commands.push(PaintCommand::FillRect {
+            rect,
+            color,
+            blend_mode,
+        });
+        self
+    }
+
+    /// Returns `true` if the list has no commands, `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+// Returns positions of stamps along the line, the first and the last stamps are always at the ends of
+// the line.
+fn line_stamps(begin: Vector2<f32>, end: Vector2<f32>, brush: &PaintBrush) -> Vec<Vector2<f32>> {
+    let step = (brush.radius * 2.0 * brush.spacing).max(0.5);
+    let length = (end - begin).norm();
+    let count = (length / step).ceil() as usize;
+    if count == 0 {
+        vec![begin]
+    } else {
+        (0..=count)
+            .map(|i| begin.lerp(&end, i as f32 / count as f32))
+            .collect()
+    }
+}
+
+fn blend_parameters(blend_mode: PaintBlendMode) -> Option<BlendParameters> {
+    let func = match blend_mode {
+        PaintBlendMode::Alpha => BlendFunc::new_separate(
+            BlendFactor::SrcAlpha,
+            BlendFactor::OneMinusSrcAlpha,
+            BlendFactor::One,
+            BlendFactor::OneMinusSrcAlpha,
+        ),
+        PaintBlendMode::Additive => BlendFunc::new_separate(
+            BlendFactor::SrcAlpha,
+            BlendFactor::One,
+            BlendFactor::One,
+            BlendFactor::One,
+        ),
+        PaintBlendMode::Multiply => BlendFunc::new_separate(
+            BlendFactor::DstColor,
+            BlendFactor::Zero,
+            BlendFactor::Zero,
+            BlendFactor::One,
+        ),
+        PaintBlendMode::Erase => BlendFunc::new(BlendFactor::Zero, BlendFactor::OneMinusSrcAlpha),
+        PaintBlendMode::Replace => return None,
+    };
+
+    Some(BlendParameters {
+        func,
+        equation: Default::default(),
+    })
+}
+
+struct PaintShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    brush_texture: UniformLocation,
+    brush_color: UniformLocation,
+    hardness: UniformLocation,
+    shape: UniformLocation,
+    blend_mode: UniformLocation,
+}
+
+impl PaintShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/paint_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "PaintShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            brush_texture: program
+                .uniform_location(state, &ImmutableString::new("brushTexture"))?,
+            brush_color: program.uniform_location(state, &ImmutableString::new("brushColor"))?,
+            hardness: program.uniform_location(state, &ImmutableString::new("hardness"))?,
+            shape: program.uniform_location(state, &ImmutableString::new("shape"))?,
+            blend_mode: program.uniform_location(state, &ImmutableString::new("blendMode"))?,
+            program,
+        })
+    }
+}
+
+struct Canvas {
+    frame_buffer: FrameBuffer,
+    width: usize,
+    height: usize,
+}
+
+// Parameters of a single quad drawn by the painter.
+struct Dab<'a> {
+    center: Vector2<f32>,
+    size: Vector2<f32>,
+    rotation: f32,
+    shape: PaintBrushShape,
+    hardness: f32,
+    color: Color,
+    texture: Option<&'a TextureResource>,
+    blend_mode: PaintBlendMode,
+}
+
+pub(crate) struct PaintContext<'a> {
+    pub state: &'a mut PipelineState,
+    pub texture_cache: &'a mut TextureCache,
+    pub white_dummy: Rc<RefCell<GpuTexture>>,
+}
+
+pub(crate) struct TexturePainter {
+    shader: PaintShader,
+    quad: GeometryBuffer,
+    // TextureId -> Canvas mapping.
+    canvases: FxHashMap<usize, Canvas>,
+}
+
+impl TexturePainter {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: PaintShader::new(state)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            ),
+            canvases: Default::default(),
+        })
+    }
+
+    fn make_canvas(
+        state: &mut PipelineState,
+        texture: &TextureResource,
+    ) -> Result<Canvas, FrameworkError> {
+        let texture = texture.data_ref();
+
+        let (width, height) = match texture.kind() {
+            TextureKind::Rectangle { width, height } => (width as usize, height as usize),
+            _ => {
+                return Err(FrameworkError::Custom(
+                    "Only rectangle textures can be painted!".to_string(),
+                ))
+            }
+        };
+
+        let pixel_kind = PixelKind::from(texture.pixel_kind());
+        if pixel_kind.is_compressed() {
+            return Err(FrameworkError::Custom(
+                "Compressed textures cannot be painted!".to_string(),
+            ));
+        }
+
+        // Only the first mip level is used, painting does not update mip levels.
+        let data = if texture.data().is_empty() {
+            None
+        } else {
+            Some(texture.mip_level_data(0))
+        };
+
+        let color_texture = GpuTexture::new(
+            state,
+            GpuTextureKind::Rectangle { width, height },
+            pixel_kind,
+            texture.minification_filter().into(),
+            texture.magnification_filter().into(),
+            1,
+            data,
+        )?;
+
+        let frame_buffer = FrameBuffer::new(
+            state,
+            None,
+            vec![Attachment {
+                kind: AttachmentKind::Color,
+                texture: Rc::new(RefCell::new(color_texture)),
+            }],
+        )?;
+
+        Ok(Canvas {
+            frame_buffer,
+            width,
+            height,
+        })
+    }
+
+    fn draw_dab(
+        &mut self,
+        ctx: &mut PaintContext,
+        canvas_key: usize,
+        dab: Dab,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut statistics = RenderPassStatistics::default();
+
+        let brush_texture = dab
+            .texture
+            .and_then(|texture| ctx.texture_cache.get(ctx.state, texture))
+            .unwrap_or_else(|| ctx.white_dummy.clone());
+
+        let canvas = match self.canvases.get_mut(&canvas_key) {
+            Some(canvas) => canvas,
+            None => return Ok(statistics),
+        };
+
+        let viewport = Rect::new(0, 0, canvas.width as i32, canvas.height as i32);
+
+        // The first row of the texture data is at the bottom of the frame buffer, so there is no need
+        // to flip anything.
+        let projection = Matrix4::new_orthographic(
+            0.0,
+            canvas.width as f32,
+            0.0,
+            canvas.height as f32,
+            -1.0,
+            1.0,
+        );
+        let wvp_matrix = projection
+            * Matrix4::new_translation(&Vector3::new(dab.center.x, dab.center.y, 0.0))
+            * Matrix4::from_axis_angle(&Vector3::z_axis(), dab.rotation)
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(dab.size.x, dab.size.y, 1.0))
+            * Matrix4::new_translation(&Vector3::new(-0.5, -0.5, 0.0));
+
+        let shader = &self.shader;
+        statistics += canvas.frame_buffer.draw(
+            &self.quad,
+            ctx.state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: blend_parameters(dab.blend_mode),
+                stencil_op: Default::default(),
+            },
+            ElementRange::Full,
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &wvp_matrix)
+                    .set_texture(&shader.brush_texture, &brush_texture)
+                    .set_srgb_color(&shader.brush_color, &dab.color)
+                    .set_f32(&shader.hardness, dab.hardness.clamp(0.0, 1.0))
+                    .set_i32(
+                        &shader.shape,
+                        match dab.shape {
+                            PaintBrushShape::Circle => 0,
+                            PaintBrushShape::Square => 1,
+                        },
+                    )
+                    .set_i32(&shader.blend_mode, dab.blend_mode as i32);
+            },
+        )?;
+
+        Ok(statistics)
+    }
+
+    fn draw_stamp(
+        &mut self,
+        ctx: &mut PaintContext,
+        canvas_key: usize,
+        position: Vector2<f32>,
+        brush: &PaintBrush,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        self.draw_dab(
+            ctx,
+            canvas_key,
+            Dab {
+                center: position,
+                size: Vector2::repeat(brush.radius.max(0.0) * 2.0),
+                rotation: brush.rotation,
+                shape: brush.shape,
+                hardness: brush.hardness,
+                color: brush.color,
+                texture: brush.texture.as_ref(),
+                blend_mode: brush.blend_mode,
+            },
+        )
+    }
+
+    pub fn paint(
+        &mut self,
+        mut ctx: PaintContext,
+        texture: &TextureResource,
+        commands: &PaintCommandList,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut statistics = RenderPassStatistics::default();
+
+        if !texture.is_ok() {
+            return Err(FrameworkError::Custom(
+                "Texture is not loaded yet!".to_string(),
+            ));
+        }
+
+        let key = texture.key();
+        if let Entry::Vacant(entry) = self.canvases.entry(key) {
+            entry.insert(Self::make_canvas(ctx.state, texture)?);
+        }
+
+        for command in commands.commands.iter() {
+            match command {
+                PaintCommand::Clear { color } => {
+                    if let Some(canvas) = self.canvases.get_mut(&key) {
+                        let viewport = Rect::new(0, 0, canvas.width as i32, canvas.height as i32);
+                        canvas
+                            .frame_buffer
+                            .clear(ctx.state, viewport, Some(*color), None, None);
+                    }
+                }
+                PaintCommand::Stamp { position, brush } => {
+                    statistics += self.draw_stamp(&mut ctx, key, *position, brush)?;
+                }
+                PaintCommand::Line { begin, end, brush } => {
+                    for position in line_stamps(*begin, *end, brush) {
+                        statistics += self.draw_stamp(&mut ctx, key, position, brush)?;
+                    }
+                }
+                PaintCommand::FillRect {
+                    rect,
+                    color,
+                    blend_mode,
+                } => {
+                    statistics += self.draw_dab(
+                        &mut ctx,
+                        key,
+                        Dab {
+                            center: rect.center(),
+                            size: rect.size,
+                            rotation: 0.0,
+                            shape: PaintBrushShape::Square,
+                            hardness: 1.0,
+                            color: *color,
+                            texture: None,
+                            blend_mode: *blend_mode,
+                        },
+                    )?;
+                }
+            }
+        }
+
+        // Register (or re-register if the cache was flushed) the canvas in the texture cache, so it
+        // will be used instead of the texture data by every renderer. The hash of the data is used to
+        // prevent the cache from overwriting the canvas with the data of the texture, however it will
+        // be overwritten if the data is changed.
+        if let Some(canvas) = self.canvases.get(&key) {
+            ctx.texture_cache.map.insert(
+                key,
+                CacheEntry {
+                    value: canvas.frame_buffer.color_attachments()[0].texture.clone(),
+                    time_to_live: f32::INFINITY,
+                    value_hash: texture.data_ref().data_hash(),
+                },
+            );
+        }
+
+        Ok(statistics)
+    }
+
+    pub fn discard(&mut self, texture_cache: &mut TextureCache, texture: &TextureResource) {
+        if self.canvases.remove(&texture.key()).is_some() {
+            texture_cache.unload(texture.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        renderer::paint::{line_stamps, PaintBrush},
+    };
+
+    #[test]
+    fn test_line_stamps() {
+        let brush = PaintBrush {
+            radius: 2.0,
+            spacing: 0.5,
+            ..Default::default()
+        };
+
+        // Step is 2 pixels.
+        let stamps = line_stamps(Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0), &brush);
+        assert_eq!(stamps.len(), 6);
+        assert_eq!(stamps.first(), Some(&Vector2::new(0.0, 0.0)));
+        assert_eq!(stamps.last(), Some(&Vector2::new(10.0, 0.0)));
+        for pair in stamps.windows(2) {
+            assert!((pair[1].x - pair[0].x - 2.0).abs() < 0.001);
+        }
+
+        // Degenerate line is a single stamp.
+        let stamps = line_stamps(Vector2::new(1.0, 1.0), Vector2::new(1.0, 1.0), &brush);
+        assert_eq!(stamps, vec![Vector2::new(1.0, 1.0)]);
+    }
+}
@@ -21,7 +21,10 @@ use crate::{
     },
     scene::{
         camera::Camera,
-        dim2::rectangle::Rectangle,
+        dim2::{
+            rectangle::Rectangle,
+            tilemap::{TileMap, CHUNK_SIZE},
+        },
         graph::Graph,
         light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
     },
@@ -86,10 +89,46 @@ struct SpriteBatchStorage {
 }
 
 impl SpriteBatchStorage {
+    fn batch_mut(
+        &mut self,
+        batch_index: &mut usize,
+        texture: &Rc<RefCell<GpuTexture>>,
+        z: f32,
+    ) -> &mut Batch {
+        let mut hasher = FxHasher::default();
+        // Objects with different Z coordinate will go into separate batches.
+        hasher.write(value_as_u8_slice(&z));
+        // Objects with different textures will go into separate batches.
+        hasher.write_u64(&*texture.borrow() as *const _ as u64);
+        let batch_id = hasher.finish();
+
+        let index = *self.index_map.entry(batch_id).or_insert_with(|| {
+            let index = *batch_index;
+            *batch_index += 1;
+            index
+        });
+
+        // Reuse old batches to prevent redundant memory allocations
+        if index < self.batches.len() {
+            let batch = &mut self.batches[index];
+            batch.texture = texture.clone();
+            batch.z = z;
+            batch
+        } else {
+            self.batches.push(Batch {
+                instances: Default::default(),
+                texture: texture.clone(),
+                z,
+            });
+            self.batches.last_mut().unwrap()
+        }
+    }
+
     fn generate_batches(
         &mut self,
         state: &mut PipelineState,
         graph: &Graph,
+        frustum: &Frustum,
         texture_cache: &mut TextureCache,
         white_dummy: Rc<RefCell<GpuTexture>>,
     ) {
@@ -116,32 +155,7 @@ impl SpriteBatchStorage {
 
                 let z = rectangle.global_position().z;
 
-                let mut hasher = FxHasher::default();
-                // Objects with different Z coordinate will go into separate batches.
-                hasher.write(value_as_u8_slice(&z));
-                // Objects with different textures will go into separate batches.
-                hasher.write_u64(&*texture.borrow() as *const _ as u64);
-                let batch_id = hasher.finish();
-
-                let index = *self.index_map.entry(batch_id).or_insert_with(|| {
-                    let index = batch_index;
-                    batch_index += 1;
-                    index as usize
-                });
-
-                // Reuse old batches to prevent redundant memory allocations
-                let batch = if let Some(batch) = self.batches.get_mut(index) {
-                    batch.texture = texture.clone();
-                    batch.z = z;
-                    batch
-                } else {
-                    self.batches.push(Batch {
-                        instances: Default::default(),
-                        texture: texture.clone(),
-                        z,
-                    });
-                    self.batches.last_mut().unwrap()
-                };
+                let batch = self.batch_mut(&mut batch_index, &texture, z);
 
                 let uv_rect = rectangle.uv_rect();
                 let uv_transform = Vector4::new(uv_rect.x(), uv_rect.y(), uv_rect.w(), uv_rect.h());
@@ -154,6 +168,88 @@ impl SpriteBatchStorage {
                     },
                     aabb: rectangle.world_bounding_box(),
                 });
+            } else if let Some(tile_map) = node.cast::<TileMap>() {
+                if !tile_map.global_visibility() {
+                    continue;
+                }
+
+                let global_transform = tile_map.global_transform();
+                let tile_set = tile_map.tile_set();
+
+                // Resolve textures once per tile definition, not per tile.
+                let mut textures = vec![None; tile_set.tiles.len()];
+
+                for (layer_index, layer) in tile_map.layers().iter().enumerate() {
+                    if !layer.visible {
+                        continue;
+                    }
+
+                    let color = layer.color.srgb_to_linear();
+
+                    for (chunk_position, chunk) in layer.chunks() {
+                        let aabb = tile_map
+                            .chunk_bounding_box(layer_index, chunk_position)
+                            .transform(&global_transform);
+                        if !frustum.is_intersects_aabb(&aabb) {
+                            continue;
+                        }
+
+                        let origin = chunk_position * CHUNK_SIZE;
+                        for (position, tile) in chunk.tiles() {
+                            let definition_index = tile_set
+                                .animated_definition(tile.definition, tile_map.animation_time())
+                                as usize;
+                            let definition = match tile_set.tiles.get(definition_index) {
+                                Some(definition) => definition,
+                                None => continue,
+                            };
+
+                            let texture = textures[definition_index]
+                                .get_or_insert_with(|| {
+                                    definition.texture.as_ref().map_or_else(
+                                        || white_dummy.clone(),
+                                        |t| {
+                                            texture_cache
+                                                .get(state, t)
+                                                .unwrap_or_else(|| white_dummy.clone())
+                                        },
+                                    )
+                                })
+                                .clone();
+
+                            let world_matrix = global_transform
+                                * tile_map.tile_transform(
+                                    layer_index,
+                                    origin + position,
+                                    definition,
+                                );
+
+                            let uv_rect = definition.uv_rect;
+                            let mut uv_transform =
+                                Vector4::new(uv_rect.x(), uv_rect.y(), uv_rect.w(), uv_rect.h());
+                            if tile.flip_x {
+                                uv_transform.x += uv_transform.z;
+                                uv_transform.z = -uv_transform.z;
+                            }
+                            if tile.flip_y {
+                                uv_transform.y += uv_transform.w;
+                                uv_transform.w = -uv_transform.w;
+                            }
+
+                            let batch =
+                                self.batch_mut(&mut batch_index, &texture, world_matrix[14]);
+
+                            batch.instances.push(Instance {
+                                gpu_data: InstanceData {
+                                    color,
+                                    uv_transform,
+                                    world_matrix,
+                                },
+                                aabb,
+                            });
+                        }
+                    }
+                }
             }
         }
 
@@ -210,14 +306,14 @@ impl Renderer2d {
         let mut stats = RenderPassStatistics::default();
         let quad = self.geometry_cache.get(state, &self.quad);
 
-        self.batch_storage
-            .generate_batches(state, graph, texture_cache, white_dummy);
-
         let view_projection = camera.view_projection_matrix();
 
         let frustum = Frustum::from_view_projection_matrix(camera.view_projection_matrix())
             .unwrap_or_default();
 
+        self.batch_storage
+            .generate_batches(state, graph, &frustum, texture_cache, white_dummy);
+
         const MAX_LIGHTS: usize = 16;
         let mut light_count = 0;
         let mut light_color_radius = [Vector4::default(); MAX_LIGHTS];
@@ -236,21 +332,21 @@ impl Renderer2d {
                         point.radius(),
                         std::f32::consts::PI.cos(),
                         std::f32::consts::PI.cos(),
-                        point.base_light_ref().color().as_frgb(),
+                        point.base_light_ref().animated_color().as_frgb(),
                     )
                 } else if let Some(spot) = light.cast::<SpotLight>() {
                     (
                         spot.distance(),
                         (spot.hotspot_cone_angle() * 0.5).cos(),
                         (spot.full_cone_angle() * 0.5).cos(),
-                        spot.base_light_ref().color().as_frgb(),
+                        spot.base_light_ref().animated_color().as_frgb(),
                     )
                 } else if let Some(directional) = light.cast::<DirectionalLight>() {
                     (
                         f32::INFINITY,
                         std::f32::consts::PI.cos(),
                         std::f32::consts::PI.cos(),
-                        directional.base_light_ref().color().as_frgb(),
+                        directional.base_light_ref().animated_color().as_frgb(),
                     )
                 } else {
                     continue;
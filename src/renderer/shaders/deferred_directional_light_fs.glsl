@@ -21,6 +21,10 @@ uniform sampler2D shadowCascade2;
 
 uniform bool shadowsEnabled;
 uniform float shadowBias;
+uniform int shadowFilter;
+uniform int shadowKernelSize;
+uniform int shadowSampleCount;
+uniform float shadowSoftness;
 uniform bool softShadows;
 uniform float shadowMapInvSize;
 
@@ -30,7 +34,8 @@ out vec4 FragColor;
 // Returns **inverted** shadow factor where 1 - fully bright, 0 - fully in shadow.
 float CsmGetShadow(in sampler2D sampler, in vec3 fragmentPosition, in mat4 lightViewProjMatrix)
 {
-    return S_SpotShadowFactor(shadowsEnabled, softShadows, shadowBias, fragmentPosition, lightViewProjMatrix, shadowMapInvSize, sampler);
+    return S_SpotShadowFactor(shadowsEnabled, softShadows, shadowBias, fragmentPosition, lightViewProjMatrix, shadowMapInvSize, sampler,
+        shadowFilter, shadowKernelSize, shadowSampleCount, shadowSoftness);
 }
 
 void main()
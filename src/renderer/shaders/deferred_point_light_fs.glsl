@@ -11,7 +11,12 @@ uniform mat4 invViewProj;
 uniform vec3 cameraPosition;
 uniform bool softShadows;
 uniform bool shadowsEnabled;
+uniform bool simpleLighting;
 uniform float shadowBias;
+uniform int shadowFilter;
+uniform int shadowKernelSize;
+uniform int shadowSampleCount;
+uniform float shadowSoftness;
 uniform float lightIntensity;
 
 in vec2 texCoord;
@@ -34,12 +39,13 @@ void main()
     ctx.roughness = material.y;
     ctx.viewVector = normalize(cameraPosition - fragmentPosition);
 
-    vec3 lighting = S_PBR_CalculateLight(ctx);
+    vec3 lighting = simpleLighting ? S_Lambert_CalculateLight(ctx) : S_PBR_CalculateLight(ctx);
 
     float distanceAttenuation = S_LightDistanceAttenuation(distance, lightRadius);
 
     float shadow = S_PointShadow(
-        shadowsEnabled, softShadows, distance, shadowBias, ctx.fragmentToLight, pointShadowTexture);
+        shadowsEnabled, softShadows, distance, shadowBias, ctx.fragmentToLight, pointShadowTexture,
+        shadowFilter, shadowKernelSize, shadowSampleCount, shadowSoftness);
 
     FragColor = vec4(lightIntensity * distanceAttenuation * shadow * lighting, 1.0);
 }
@@ -15,9 +15,14 @@ uniform float halfConeAngleCos;
 uniform mat4 invViewProj;
 uniform vec3 cameraPosition;
 uniform bool shadowsEnabled;
+uniform bool simpleLighting;
 uniform bool softShadows;
 uniform float shadowMapInvSize;
 uniform float shadowBias;
+uniform int shadowFilter;
+uniform int shadowKernelSize;
+uniform int shadowSampleCount;
+uniform float shadowSoftness;
 uniform bool cookieEnabled;
 uniform float lightIntensity;
 
@@ -41,7 +46,7 @@ void main()
     ctx.roughness = material.y;
     ctx.viewVector = normalize(cameraPosition - fragmentPosition);
 
-    vec3 lighting = S_PBR_CalculateLight(ctx);
+    vec3 lighting = simpleLighting ? S_Lambert_CalculateLight(ctx) : S_PBR_CalculateLight(ctx);
 
     float distanceAttenuation = S_LightDistanceAttenuation(distance, lightRadius);
 
@@ -50,7 +55,8 @@ void main()
 
     float shadow = S_SpotShadowFactor(
         shadowsEnabled, softShadows, shadowBias, fragmentPosition,
-            lightViewProjMatrix, shadowMapInvSize, spotShadowTexture);
+            lightViewProjMatrix, shadowMapInvSize, spotShadowTexture,
+            shadowFilter, shadowKernelSize, shadowSampleCount, shadowSoftness);
 
     vec4 cookieAttenuation = vec4(1.0);
     if (cookieEnabled) {
@@ -0,0 +1,47 @@
+layout(location = 0) out vec4 outColor;
+layout(location = 1) out vec4 outNormal;
+layout(location = 2) out vec4 outAmbient;
+layout(location = 3) out vec4 outMaterial;
+layout(location = 4) out uint outDecalMask;
+
+uniform sampler2D diffuseTexture;
+uniform vec4 color;
+
+in vec2 texCoord;
+in vec3 normal;
+in float fade;
+
+// 4x4 Bayer matrix, used to fade instances out without blending.
+const float ditherThresholds[16] = float[](
+    0.0625, 0.5625, 0.1875, 0.6875,
+    0.8125, 0.3125, 0.9375, 0.4375,
+    0.25, 0.75, 0.125, 0.625,
+    1.0, 0.5, 0.875, 0.375
+);
+
+void main()
+{
+    outColor = color * texture(diffuseTexture, texCoord);
+
+    // Alpha test.
+    if (outColor.a < 0.5) {
+        discard;
+    }
+    outColor.a = 1.0;
+
+    ivec2 pixel = ivec2(gl_FragCoord.xy) % 4;
+    if (fade < ditherThresholds[pixel.y * 4 + pixel.x]) {
+        discard;
+    }
+
+    // Foliage is two-sided, but the normal is the same for both sides. It works well for grass,
+    // which usually has normals pointing up.
+    outNormal = vec4(normalize(normal) * 0.5 + 0.5, 1.0);
+
+    // Non-metallic, rough surface without ambient occlusion.
+    outMaterial = vec4(0.0, 1.0, 1.0, 1.0);
+
+    outAmbient = vec4(0.0, 0.0, 0.0, 1.0);
+
+    outDecalMask = 0u;
+}
@@ -0,0 +1,65 @@
+layout(location = 0) in vec3 vertexPosition;
+layout(location = 1) in vec2 vertexTexCoord;
+layout(location = 2) in vec3 vertexNormal;
+// Instance data: xyz - position in local space of foliage, w - rotation around vertical axis.
+layout(location = 8) in vec4 instancePositionRotation;
+layout(location = 9) in float instanceScale;
+
+uniform mat4 viewProjectionMatrix;
+uniform mat4 worldMatrix;
+uniform vec3 cameraPosition;
+uniform vec4 frustumPlanes[6];
+uniform float boundingRadius;
+uniform float drawDistance;
+uniform float fadeDistance;
+uniform float time;
+// xy - direction, z - strength, w - frequency.
+uniform vec4 windParameters;
+uniform float windInfluence;
+
+out vec2 texCoord;
+out vec3 normal;
+out float fade;
+
+void main()
+{
+    texCoord = vertexTexCoord;
+    normal = vec3(0.0, 1.0, 0.0);
+
+    vec3 instanceCenter = (worldMatrix * vec4(instancePositionRotation.xyz, 1.0)).xyz;
+    float radius = boundingRadius * instanceScale;
+
+    fade = clamp((drawDistance - distance(instanceCenter, cameraPosition)) / max(fadeDistance, 0.0001), 0.0, 1.0);
+
+    bool visible = fade > 0.0;
+    for (int i = 0; i < 6; ++i) {
+        if (dot(frustumPlanes[i].xyz, instanceCenter) + frustumPlanes[i].w < -radius) {
+            visible = false;
+        }
+    }
+
+    if (!visible) {
+        // Collapse every vertex of the instance into a single point outside of the clip volume,
+        // so the instance won't produce any fragments.
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+        return;
+    }
+
+    float s = sin(instancePositionRotation.w);
+    float c = cos(instancePositionRotation.w);
+    mat3 rotation = mat3(c, 0.0, -s, 0.0, 1.0, 0.0, s, 0.0, c);
+
+    vec3 localPosition = instancePositionRotation.xyz + rotation * (vertexPosition * instanceScale);
+    vec4 worldPosition = worldMatrix * vec4(localPosition, 1.0);
+
+    // The wind bends the upper part of an instance, while its base stays in place.
+    float height = max(vertexPosition.y * instanceScale, 0.0);
+    float phase = dot(instanceCenter.xz, windParameters.xy) * 0.5 + dot(instanceCenter.xz, vec2(0.37, 0.61));
+    float t = time * windParameters.w;
+    float sway = sin(t + phase) * 0.7 + sin(2.3 * t + 1.7 * phase) * 0.3;
+    worldPosition.xz += windParameters.xy * (windParameters.z * windInfluence * sway * height * height);
+
+    normal = normalize(mat3(worldMatrix) * (rotation * vertexNormal));
+
+    gl_Position = viewProjectionMatrix * worldPosition;
+}
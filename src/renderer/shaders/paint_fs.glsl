@@ -0,0 +1,33 @@
+uniform sampler2D brushTexture;
+uniform vec4 brushColor;
+uniform float hardness;
+// 0 - circle, 1 - rectangle.
+uniform int shape;
+// Must be in sync with PaintBlendMode.
+uniform int blendMode;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    vec2 p = texCoord * 2.0 - 1.0;
+    float distance = shape == 0 ? length(p) : max(abs(p.x), abs(p.y));
+    float coverage = clamp((1.0 - distance) / max(1.0 - hardness, 0.0001), 0.0, 1.0);
+
+    vec4 color = brushColor * texture(brushTexture, texCoord);
+    float alpha = color.a * coverage;
+
+    if (blendMode == 2) {
+        // Multiply
+        FragColor = vec4(mix(vec3(1.0), color.rgb, alpha), 1.0);
+    } else if (blendMode == 4) {
+        // Replace
+        if (coverage < 0.5) {
+            discard;
+        }
+        FragColor = color;
+    } else {
+        FragColor = vec4(color.rgb, alpha);
+    }
+}
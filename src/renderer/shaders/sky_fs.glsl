@@ -0,0 +1,122 @@
+// Single scattering atmosphere. Must match the code, that is used to calculate ambient lighting on
+// CPU (see Atmosphere::sky_radiance).
+
+uniform mat3 worldToSky;
+uniform vec3 sunDirection;
+uniform float sunIntensity;
+uniform float mieCoefficient;
+uniform float mieAnisotropy;
+uniform float sunDiskCos;
+uniform vec3 nightColor;
+
+in vec3 texCoord;
+
+out vec4 FragColor;
+
+const float planetRadius = 6360000.0;
+const float atmosphereRadius = 6420000.0;
+const float observerHeight = 100.0;
+const float rayleighScaleHeight = 7994.0;
+const float mieScaleHeight = 1200.0;
+const vec3 rayleighCoefficients = vec3(5.5e-6, 13.0e-6, 22.4e-6);
+const int viewSamples = 16;
+const int lightSamples = 8;
+
+float RaySphereExit(vec3 origin, vec3 direction, float radius)
+{
+    float b = dot(origin, direction);
+    float c = dot(origin, origin) - radius * radius;
+    float d = b * b - c;
+    return d < 0.0 ? -1.0 : -b + sqrt(d);
+}
+
+float RayHitsPlanet(vec3 origin, vec3 direction)
+{
+    float b = dot(origin, direction);
+    float c = dot(origin, origin) - planetRadius * planetRadius;
+    float d = b * b - c;
+    if (d < 0.0) {
+        return -1.0;
+    }
+    return -b - sqrt(d);
+}
+
+vec3 Extinction(float depthR, float depthM)
+{
+    return exp(-(rayleighCoefficients * depthR + vec3(mieCoefficient * 1.1 * depthM)));
+}
+
+bool OpticalDepth(vec3 origin, vec3 direction, out float depthR, out float depthM)
+{
+    depthR = 0.0;
+    depthM = 0.0;
+    if (RayHitsPlanet(origin, direction) > 0.0) {
+        return false;
+    }
+    float len = RaySphereExit(origin, direction, atmosphereRadius);
+    if (len < 0.0) {
+        return false;
+    }
+    float stepLength = len / float(lightSamples);
+    for (int i = 0; i < lightSamples; ++i) {
+        vec3 p = origin + direction * ((float(i) + 0.5) * stepLength);
+        float height = max(length(p) - planetRadius, 0.0);
+        depthR += exp(-height / rayleighScaleHeight) * stepLength;
+        depthM += exp(-height / mieScaleHeight) * stepLength;
+    }
+    return true;
+}
+
+void main()
+{
+    vec3 direction = normalize(worldToSky * texCoord);
+    vec3 origin = vec3(0.0, planetRadius + observerHeight, 0.0);
+
+    float len = RaySphereExit(origin, direction, atmosphereRadius);
+    float ground = RayHitsPlanet(origin, direction);
+    if (ground > 0.0) {
+        len = min(len, ground);
+    }
+
+    float mu = dot(direction, sunDirection);
+    float phaseR = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+    float g = clamp(mieAnisotropy, -0.99, 0.99);
+    float phaseM = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+        / ((2.0 + g * g) * pow(max(1.0 + g * g - 2.0 * g * mu, 1.0e-6), 1.5));
+
+    float stepLength = len / float(viewSamples);
+    float depthR = 0.0;
+    float depthM = 0.0;
+    vec3 sumR = vec3(0.0);
+    vec3 sumM = vec3(0.0);
+    for (int i = 0; i < viewSamples; ++i) {
+        vec3 p = origin + direction * ((float(i) + 0.5) * stepLength);
+        float height = max(length(p) - planetRadius, 0.0);
+        float hr = exp(-height / rayleighScaleHeight) * stepLength;
+        float hm = exp(-height / mieScaleHeight) * stepLength;
+        depthR += hr;
+        depthM += hm;
+
+        float lightR;
+        float lightM;
+        if (OpticalDepth(p, sunDirection, lightR, lightM)) {
+            vec3 attenuation = Extinction(depthR + lightR, depthM + lightM);
+            sumR += attenuation * hr;
+            sumM += attenuation * hm;
+        }
+    }
+
+    vec3 color = (sumR * rayleighCoefficients * phaseR + sumM * mieCoefficient * phaseM) * sunIntensity;
+
+    // Sun disk, it is visible only above the horizon.
+    if (ground < 0.0 && mu > sunDiskCos) {
+        float sunR;
+        float sunM;
+        if (OpticalDepth(origin, direction, sunR, sunM)) {
+            float edge = smoothstep(sunDiskCos, mix(sunDiskCos, 1.0, 0.1), mu);
+            color += Extinction(sunR, sunM) * sunIntensity * edge;
+        }
+    }
+
+    FragColor = vec4(color + nightColor, 1.0);
+}
@@ -7,6 +7,8 @@ uniform vec3 cameraUpVector;
 uniform vec3 cameraSideVector;
 uniform float size;
 uniform float rotation;
+// Portion of the texture: xy - position, zw - size.
+uniform vec4 uvRect;
 
 out vec2 texCoord;
 
@@ -20,7 +22,7 @@ vec2 rotateVec2(vec2 v, float angle)
 
 void main()
 {
-    texCoord = vertexTexCoord;
+    texCoord = uvRect.xy + vertexTexCoord * uvRect.zw;
     vec2 vertexOffset = rotateVec2(vertexTexCoord * 2.0 - 1.0, rotation);
     vec4 worldPosition = worldMatrix * vec4(vertexPosition, 1.0);
     vec3 offset = (vertexOffset.x * cameraSideVector + vertexOffset.y * cameraUpVector) * size;
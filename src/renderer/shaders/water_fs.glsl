@@ -0,0 +1,156 @@
+uniform sampler2D sceneColorTexture;
+uniform sampler2D depthTexture;
+uniform sampler2D normalTexture;
+uniform samplerCube skyboxTexture;
+
+uniform mat4 viewMatrix;
+uniform mat4 projectionMatrix;
+uniform vec3 cameraPosition;
+uniform vec2 invScreenSize;
+// xy - origin, zw - size of the viewport in texture coordinates of the frame.
+uniform vec4 viewportBounds;
+uniform vec2 projParams;
+uniform float time;
+
+uniform float normalMapScale;
+uniform vec3 shallowColor;
+uniform vec3 deepColor;
+uniform vec3 foamColor;
+uniform float absorption;
+uniform float refractionStrength;
+uniform float reflectionStrength;
+uniform float foamDistance;
+
+// Direction to the light source.
+uniform vec3 lightDirection;
+uniform vec3 lightColor;
+uniform vec3 ambientColor;
+
+in vec3 worldPosition;
+in vec3 normal;
+
+out vec4 FragColor;
+
+float LinearDepth(float z)
+{
+    float far = projParams.x;
+    float near = projParams.y;
+    return (far * near) / (far - z * (far - near));
+}
+
+float SceneDepth(vec2 uv)
+{
+    return LinearDepth(texture(depthTexture, uv).r);
+}
+
+vec3 DetailNormal(vec3 n)
+{
+    // Two layers of the normal map scroll in different directions, which hides the tiling.
+    vec2 uv = worldPosition.xz * normalMapScale;
+    vec3 a = texture(normalTexture, uv + vec2(0.021, 0.013) * time).xyz * 2.0 - 1.0;
+    vec3 b = texture(normalTexture, uv * 1.37 - vec2(0.017, 0.029) * time).xyz * 2.0 - 1.0;
+    vec3 detail = normalize(vec3(a.xy + b.xy, a.z * b.z));
+    // Tangent frame of the surface is aligned with world X and Z axes.
+    vec3 t = normalize(cross(n, vec3(0.0, 0.0, 1.0)));
+    vec3 bt = cross(t, n);
+    return normalize(t * detail.x + bt * detail.y + n * detail.z);
+}
+
+// Marches the reflected ray in view space and checks it against the depth buffer. Returns true
+// if the ray hits visible geometry, uv of the hit is written to hitUV.
+bool TraceScreenSpace(vec3 origin, vec3 direction, out vec2 hitUV, out float confidence)
+{
+    const int steps = 32;
+    float stepLength = 0.25;
+    float travelled = 0.0;
+    for (int i = 0; i < steps; ++i) {
+        travelled += stepLength;
+        stepLength *= 1.15;
+
+        vec3 p = origin + direction * travelled;
+        vec4 clip = projectionMatrix * vec4(p, 1.0);
+        if (clip.w <= 0.0) {
+            return false;
+        }
+        vec2 viewportUV = clip.xy / clip.w * 0.5 + 0.5;
+        if (viewportUV.x < 0.0 || viewportUV.x > 1.0 || viewportUV.y < 0.0 || viewportUV.y > 1.0) {
+            return false;
+        }
+        vec2 uv = viewportBounds.xy + viewportUV * viewportBounds.zw;
+
+        float rayDepth = -p.z;
+        float sceneDepth = SceneDepth(uv);
+        float difference = rayDepth - sceneDepth;
+        if (difference > 0.0 && difference < stepLength * 2.0) {
+            hitUV = uv;
+            // Fade the reflection near screen edges and at the end of the ray.
+            vec2 edge = min(viewportUV, 1.0 - viewportUV);
+            confidence = clamp(min(edge.x, edge.y) * 10.0, 0.0, 1.0) * (1.0 - float(i) / float(steps));
+            return true;
+        }
+    }
+    return false;
+}
+
+void main()
+{
+    vec2 screenUV = gl_FragCoord.xy * invScreenSize;
+    vec3 n = DetailNormal(normalize(normal));
+    if (!gl_FrontFacing) {
+        n = -n;
+    }
+
+    vec3 toCamera = cameraPosition - worldPosition;
+    float distanceToCamera = length(toCamera);
+    vec3 v = toCamera / max(distanceToCamera, 0.0001);
+
+    float waterDepth = LinearDepth(gl_FragCoord.z);
+
+    // Refraction. Distortion is taken from the part of the normal, that deviates from the vertical
+    // axis, and it is weakened with distance to prevent swimming far away.
+    vec2 distortion = n.xz * refractionStrength / max(waterDepth * 0.1, 1.0);
+    vec2 refractionUV = clamp(screenUV + distortion, viewportBounds.xy, viewportBounds.xy + viewportBounds.zw);
+    float sceneDepth = SceneDepth(refractionUV);
+    if (sceneDepth < waterDepth) {
+        // Distorted sample is in front of the water, use undistorted one instead.
+        refractionUV = screenUV;
+        sceneDepth = SceneDepth(refractionUV);
+    }
+    // Thickness of the water layer along the view ray.
+    float thickness = max(sceneDepth - waterDepth, 0.0);
+    float transmittance = exp(-thickness * absorption);
+    vec3 sceneColor = texture(sceneColorTexture, refractionUV).rgb;
+    vec3 scatteredColor = deepColor * (ambientColor + lightColor * max(lightDirection.y, 0.0));
+    vec3 refracted = mix(scatteredColor, sceneColor * shallowColor, transmittance);
+
+    // Reflection.
+    vec3 r = reflect(-v, n);
+    vec3 reflected = S_SRGBToLinear(texture(skyboxTexture, r)).rgb;
+    vec3 viewPosition = (viewMatrix * vec4(worldPosition, 1.0)).xyz;
+    vec3 viewReflection = normalize(mat3(viewMatrix) * r);
+    vec2 hitUV;
+    float confidence;
+    if (TraceScreenSpace(viewPosition, viewReflection, hitUV, confidence)) {
+        reflected = mix(reflected, texture(sceneColorTexture, hitUV).rgb, confidence);
+    }
+
+    // Water has refractive index of 1.33, so F0 is 0.02.
+    float fresnel = S_FresnelSchlick(max(dot(n, v), 0.0), vec3(0.02)).x;
+    vec3 color = mix(refracted, reflected, fresnel * reflectionStrength);
+
+    // Specular highlight of the light source.
+    vec3 h = normalize(lightDirection + v);
+    color += lightColor * pow(max(dot(n, h), 0.0), 512.0) * 4.0 * fresnel;
+
+    // Shoreline foam, broken up with a simple animated pattern.
+    if (foamDistance > 0.0) {
+        float foamFactor = 1.0 - clamp(thickness / foamDistance, 0.0, 1.0);
+        vec2 p = worldPosition.xz * 3.0;
+        float pattern = sin(p.x + time * 1.3 + sin(p.y * 1.7)) * sin(p.y * 1.1 - time * 0.9 + sin(p.x * 1.3));
+        float foam = smoothstep(0.0, 0.3, foamFactor * foamFactor + pattern * 0.25 * foamFactor);
+        vec3 foamLighting = ambientColor + lightColor * max(dot(n, lightDirection), 0.0);
+        color = mix(color, foamColor * foamLighting, foam);
+    }
+
+    FragColor = vec4(color, 1.0);
+}
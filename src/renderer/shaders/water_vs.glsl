@@ -0,0 +1,48 @@
+// Position of a vertex of the grid in [0; 1] range.
+layout(location = 0) in vec2 vertexPosition;
+
+uniform mat4 viewProjectionMatrix;
+uniform mat4 worldMatrix;
+uniform vec2 size;
+uniform float time;
+uniform int waveCount;
+// xy - direction, z - wave number, w - amplitude.
+uniform vec4 wavesA[8];
+// x - angular frequency, y - steepness.
+uniform vec2 wavesB[8];
+
+out vec3 worldPosition;
+out vec3 normal;
+
+void main()
+{
+    vec2 restPosition = (vertexPosition - 0.5) * size;
+
+    // Sum of Gerstner waves. It must match the code, that is used for height queries on CPU.
+    vec3 displacement = vec3(0.0);
+    vec3 n = vec3(0.0, 1.0, 0.0);
+    for (int i = 0; i < waveCount; ++i) {
+        vec2 direction = wavesA[i].xy;
+        float waveNumber = wavesA[i].z;
+        float amplitude = wavesA[i].w;
+        float angularFrequency = wavesB[i].x;
+        float steepness = wavesB[i].y;
+
+        float phase = waveNumber * dot(direction, restPosition) - angularFrequency * time;
+        float s = sin(phase);
+        float c = cos(phase);
+
+        displacement.xz += direction * (steepness * amplitude * c);
+        displacement.y += amplitude * s;
+
+        float wa = waveNumber * amplitude;
+        n.xz -= direction * (wa * c);
+        n.y -= steepness * wa * s;
+    }
+
+    vec3 localPosition = vec3(restPosition.x, 0.0, restPosition.y) + displacement;
+    worldPosition = (worldMatrix * vec4(localPosition, 1.0)).xyz;
+    normal = normalize(mat3(worldMatrix) * n);
+
+    gl_Position = viewProjectionMatrix * vec4(worldPosition, 1.0);
+}
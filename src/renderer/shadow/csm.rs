@@ -1,6 +1,7 @@
 use crate::{
     core::{
         algebra::{Matrix4, Point3, Vector2, Vector3},
+        arena::FrameArena,
         math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, Rect},
     },
     renderer::{
@@ -105,6 +106,7 @@ pub(crate) struct CsmRenderContext<'a, 'c> {
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub frame_arena: &'c FrameArena,
 }
 
 impl CsmRenderer {
@@ -156,6 +158,7 @@ impl CsmRenderer {
             black_dummy,
             volume_dummy,
             matrix_storage,
+            frame_arena,
         } = ctx;
 
         let light_direction = -light
@@ -219,6 +222,7 @@ impl CsmRenderer {
                     projection_matrix,
                 },
                 DIRECTIONAL_SHADOW_PASS_NAME.clone(),
+                frame_arena,
             );
 
             let mut aabb = AxisAlignedBoundingBox::default();
@@ -302,6 +306,7 @@ impl CsmRenderer {
                                     light_position: &Default::default(),
                                     blend_shapes_storage: blend_shapes_storage.as_ref(),
                                     blend_shapes_weights: &instance.blend_shapes_weights,
+                                    light_probe: &Default::default(),
                                     normal_dummy: normal_dummy.clone(),
                                     white_dummy: white_dummy.clone(),
                                     black_dummy: black_dummy.clone(),
@@ -1,6 +1,7 @@
 use crate::{
     core::{
         algebra::{Matrix4, Point3, Vector3},
+        arena::FrameArena,
         color::Color,
         math::Rect,
         scope_profile,
@@ -54,6 +55,7 @@ pub(crate) struct PointShadowMapRenderContext<'a> {
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub volume_dummy: Rc<RefCell<GpuTexture>>,
     pub matrix_storage: &'a mut MatrixStorageCache,
+    pub frame_arena: &'a FrameArena,
 }
 
 impl PointShadowMapRenderer {
@@ -207,6 +209,7 @@ impl PointShadowMapRenderer {
             black_dummy,
             volume_dummy,
             matrix_storage,
+            frame_arena,
         } = args;
 
         let framebuffer = &mut self.cascades[cascade];
@@ -246,6 +249,7 @@ impl PointShadowMapRenderer {
                     projection_matrix: light_projection_matrix,
                 },
                 POINT_SHADOW_PASS_NAME.clone(),
+                frame_arena,
             );
 
             for batch in batches.batches.iter() {
@@ -286,6 +290,7 @@ impl PointShadowMapRenderer {
                                     light_position: &light_pos,
                                     blend_shapes_storage: blend_shapes_storage.as_ref(),
                                     blend_shapes_weights: &instance.blend_shapes_weights,
+                                    light_probe: &Default::default(),
                                     normal_dummy: normal_dummy.clone(),
                                     white_dummy: white_dummy.clone(),
                                     black_dummy: black_dummy.clone(),
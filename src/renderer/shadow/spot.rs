@@ -1,6 +1,7 @@
 use crate::{
     core::{
         algebra::{Matrix4, Vector3},
+        arena::FrameArena,
         color::Color,
         math::Rect,
         scope_profile,
@@ -133,6 +134,7 @@ impl SpotShadowMapRenderer {
         black_dummy: Rc<RefCell<GpuTexture>>,
         volume_dummy: Rc<RefCell<GpuTexture>>,
         matrix_storage: &mut MatrixStorageCache,
+        frame_arena: &FrameArena,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         scope_profile!();
 
@@ -156,6 +158,7 @@ impl SpotShadowMapRenderer {
                 projection_matrix: light_projection_matrix,
             },
             SPOT_SHADOW_PASS_NAME.clone(),
+            frame_arena,
         );
 
         for batch in batches.batches.iter() {
@@ -203,6 +206,7 @@ impl SpotShadowMapRenderer {
                                 light_position: &Default::default(),
                                 blend_shapes_storage: blend_shapes_storage.as_ref(),
                                 blend_shapes_weights: &instance.blend_shapes_weights,
+                                light_probe: &Default::default(),
                                 normal_dummy: normal_dummy.clone(),
                                 white_dummy: white_dummy.clone(),
                                 black_dummy: black_dummy.clone(),
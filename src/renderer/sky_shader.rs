@@ -0,0 +1,43 @@
+use crate::core::sstorage::ImmutableString;
+use crate::renderer::framework::{
+    error::FrameworkError,
+    gpu_program::{GpuProgram, UniformLocation},
+    state::PipelineState,
+};
+
+pub struct SkyShader {
+    pub program: GpuProgram,
+    pub wvp_matrix: UniformLocation,
+    pub world_to_sky: UniformLocation,
+    pub sun_direction: UniformLocation,
+    pub sun_intensity: UniformLocation,
+    pub mie_coefficient: UniformLocation,
+    pub mie_anisotropy: UniformLocation,
+    pub sun_disk_cos: UniformLocation,
+    pub night_color: UniformLocation,
+}
+
+impl SkyShader {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/sky_fs.glsl");
+        let vertex_source = include_str!("shaders/skybox_vs.glsl");
+
+        let program = GpuProgram::from_source(state, "SkyShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            world_to_sky: program.uniform_location(state, &ImmutableString::new("worldToSky"))?,
+            sun_direction: program
+                .uniform_location(state, &ImmutableString::new("sunDirection"))?,
+            sun_intensity: program
+                .uniform_location(state, &ImmutableString::new("sunIntensity"))?,
+            mie_coefficient: program
+                .uniform_location(state, &ImmutableString::new("mieCoefficient"))?,
+            mie_anisotropy: program
+                .uniform_location(state, &ImmutableString::new("mieAnisotropy"))?,
+            sun_disk_cos: program.uniform_location(state, &ImmutableString::new("sunDiskCos"))?,
+            night_color: program.uniform_location(state, &ImmutableString::new("nightColor"))?,
+            program,
+        })
+    }
+}
@@ -0,0 +1,145 @@
+//! Render snapshot is an immutable copy of the state of all enabled scenes, that allows rendering of a
+//! frame to overlap with the update of the next one. See [`RenderSnapshot`] docs for more info.
+
+use crate::{
+    core::pool::Handle,
+    fxhash::FxHashMap,
+    scene::{Scene, SceneContainer},
+};
+
+/// Render snapshot is an immutable copy of every enabled scene at the end of a frame (see
+/// [`Scene::copy_for_rendering`]). Together with the scenes themselves it forms a double buffer of the
+/// scene state: the renderer draws the snapshot of the frame N on the main thread (which owns the
+/// graphics context), while the scenes are updated to the frame N + 1 on the task system. Copies of
+/// the scenes are kept between frames and only changed nodes are copied again, unchanged nodes keep
+/// their memory and cached visibility.
+#[derive(Default)]
+pub(crate) struct RenderSnapshot {
+    // Handles of all scenes at the moment of extraction, paired with copies of enabled scenes. Handles
+    // of disabled scenes are kept, so the renderer won't drop the data associated with them.
+    scenes: Vec<(Handle<Scene>, Option<Scene>)>,
+    // The snapshot was extracted, but wasn't rendered yet.
+    pending: bool,
+}
+
+impl RenderSnapshot {
+    /// Copies current state of every enabled scene into the snapshot and marks it as pending. Returns
+    /// the amount of copied nodes.
+    pub(crate) fn extract(&mut self, scenes: &mut SceneContainer) -> usize {
+        let mut copies = self
+            .scenes
+            .drain(..)
+            .filter_map(|(handle, copy)| copy.map(|copy| (handle, copy)))
+            .collect::<FxHashMap<_, _>>();
+
+        let mut copied = 0;
+        for (handle, scene) in scenes.pair_iter_mut() {
+            let copy = if scene.enabled {
+                let mut copy = copies.remove(&handle).unwrap_or_default();
+                copied += scene.copy_for_rendering(&mut copy);
+                Some(copy)
+            } else {
+                None
+            };
+            self.scenes.push((handle, copy));
+        }
+
+        self.pending = true;
+
+        copied
+    }
+
+    /// Returns `true` if the snapshot is pending and marks it as rendered. The caller must render the
+    /// snapshot then.
+    pub(crate) fn take_pending(&mut self) -> bool {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Returns handles of all scenes of the snapshot, paired with copies of enabled scenes.
+    pub(crate) fn scenes(&self) -> Vec<(Handle<Scene>, Option<&Scene>)> {
+        self.scenes
+            .iter()
+            .map(|(handle, copy)| (*handle, copy.as_ref()))
+            .collect()
+    }
+
+    /// Removes every copy of the scenes. Pending snapshot is discarded.
+    pub(crate) fn clear(&mut self) {
+        self.scenes.clear();
+        self.pending = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            instant,
+        },
+        renderer::snapshot::RenderSnapshot,
+        scene::{
+            base::BaseBuilder,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+                MeshBuilder,
+            },
+            Scene, SceneContainer,
+        },
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_snapshot_is_cheaper_than_update() {
+        let mut scene = Scene::new();
+        let surface_data = SurfaceSharedData::new(SurfaceData::make_cube(Matrix4::identity()));
+        let meshes = (0..10_000)
+            .map(|_| {
+                MeshBuilder::new(BaseBuilder::new())
+                    .with_surfaces(vec![SurfaceBuilder::new(surface_data.clone()).build()])
+                    .build(&mut scene.graph)
+            })
+            .collect::<Vec<_>>();
+        let mut scenes = SceneContainer::new(Default::default());
+        let scene = scenes.add(scene);
+
+        let mut snapshot = RenderSnapshot::default();
+        let mut update_time = Duration::default();
+        let mut extract_time = Duration::default();
+        for frame in 0..10 {
+            // A single mesh is moving.
+            scenes[scene].graph[meshes[0]]
+                .local_transform_mut()
+                .set_position(Vector3::new(frame as f32, 0.0, 0.0));
+
+            let time = instant::Instant::now();
+            scenes[scene].update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+            update_time += instant::Instant::now() - time;
+
+            let time = instant::Instant::now();
+            let copied = snapshot.extract(&mut scenes);
+            if frame == 0 {
+                // The first snapshot is always full.
+                assert_eq!(copied, meshes.len() + 1);
+            } else {
+                // Only the moving mesh is copied on next frames, and the copy of the snapshot is
+                // much cheaper than the update that runs in parallel with the rendering of the snapshot,
+                // so the overlap pays off.
+                assert_eq!(copied, 1);
+                extract_time += instant::Instant::now() - time;
+            }
+        }
+
+        assert!(
+            extract_time < update_time,
+            "{extract_time:?} >= {update_time:?}"
+        );
+
+        let scenes = snapshot.scenes();
+        let copy = scenes[0].1.unwrap();
+        assert_eq!(
+            copy.graph[meshes[0]].global_position(),
+            Vector3::new(9.0, 0.0, 0.0)
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     core::{
+        algebra::Vector4,
         math::{Matrix4Ext, Rect},
         scope_profile,
         sstorage::ImmutableString,
@@ -29,6 +30,7 @@ struct SpriteShader {
     diffuse_texture: UniformLocation,
     size: UniformLocation,
     rotation: UniformLocation,
+    uv_rect: UniformLocation,
 }
 
 impl SpriteShader {
@@ -50,6 +52,7 @@ impl SpriteShader {
                 .uniform_location(state, &ImmutableString::new("diffuseTexture"))?,
             color: program.uniform_location(state, &ImmutableString::new("color"))?,
             rotation: program.uniform_location(state, &ImmutableString::new("rotation"))?,
+            uv_rect: program.uniform_location(state, &ImmutableString::new("uvRect"))?,
             program,
         })
     }
@@ -134,6 +137,9 @@ impl SpriteRenderer {
                 white_dummy.clone()
             };
 
+            let uv_rect = sprite.uv_rect();
+            let uv_rect = Vector4::new(uv_rect.x(), uv_rect.y(), uv_rect.w(), uv_rect.h());
+
             statistics += framebuffer.draw(
                 &self.collapsed_quad,
                 state,
@@ -161,7 +167,8 @@ impl SpriteRenderer {
                         .set_vector3(&self.shader.camera_side_vector, &camera_side)
                         .set_f32(&self.shader.size, sprite.size())
                         .set_linear_color(&self.shader.color, &sprite.color())
-                        .set_f32(&self.shader.rotation, sprite.rotation());
+                        .set_f32(&self.shader.rotation, sprite.rotation())
+                        .set_vector4(&self.shader.uv_rect, &uv_rect);
                 },
             )?;
         }
@@ -0,0 +1,362 @@
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        color::Color,
+        math::{frustum::Frustum, Rect, TriangleDefinition},
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            geometry_buffer::{
+                AttributeDefinition, AttributeKind, BufferBuilder, ElementKind, ElementRange,
+                GeometryBuffer, GeometryBufferBuilder, GeometryBufferKind,
+            },
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::GpuTexture,
+            state::PipelineState,
+        },
+        RenderPassStatistics, TextureCache,
+    },
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        light::directional::DirectionalLight,
+        water::{Water, MAX_WAVES},
+    },
+};
+use fxhash::FxHashMap;
+use fyrox_resource::entry::{TimedEntry, DEFAULT_RESOURCE_LIFETIME};
+use std::{cell::RefCell, collections::hash_map::Entry, rc::Rc};
+
+struct WaterShader {
+    program: GpuProgram,
+    view_projection_matrix: UniformLocation,
+    world_matrix: UniformLocation,
+    size: UniformLocation,
+    time: UniformLocation,
+    wave_count: UniformLocation,
+    waves_a: UniformLocation,
+    waves_b: UniformLocation,
+    scene_color_texture: UniformLocation,
+    depth_texture: UniformLocation,
+    normal_texture: UniformLocation,
+    skybox_texture: UniformLocation,
+    view_matrix: UniformLocation,
+    projection_matrix: UniformLocation,
+    camera_position: UniformLocation,
+    inv_screen_size: UniformLocation,
+    viewport_bounds: UniformLocation,
+    proj_params: UniformLocation,
+    normal_map_scale: UniformLocation,
+    shallow_color: UniformLocation,
+    deep_color: UniformLocation,
+    foam_color: UniformLocation,
+    absorption: UniformLocation,
+    refraction_strength: UniformLocation,
+    reflection_strength: UniformLocation,
+    foam_distance: UniformLocation,
+    light_direction: UniformLocation,
+    light_color: UniformLocation,
+    ambient_color: UniformLocation,
+}
+
+impl WaterShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/water_fs.glsl");
+        let vertex_source = include_str!("shaders/water_vs.glsl");
+        let program =
+            GpuProgram::from_source(state, "WaterShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            view_projection_matrix: program
+                .uniform_location(state, &ImmutableString::new("viewProjectionMatrix"))?,
+            world_matrix: program.uniform_location(state, &ImmutableString::new("worldMatrix"))?,
+            size: program.uniform_location(state, &ImmutableString::new("size"))?,
+            time: program.uniform_location(state, &ImmutableString::new("time"))?,
+            wave_count: program.uniform_location(state, &ImmutableString::new("waveCount"))?,
+            waves_a: program.uniform_location(state, &ImmutableString::new("wavesA"))?,
+            waves_b: program.uniform_location(state, &ImmutableString::new("wavesB"))?,
+            scene_color_texture: program
+                .uniform_location(state, &ImmutableString::new("sceneColorTexture"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            normal_texture: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            skybox_texture: program
+                .uniform_location(state, &ImmutableString::new("skyboxTexture"))?,
+            view_matrix: program.uniform_location(state, &ImmutableString::new("viewMatrix"))?,
+            projection_matrix: program
+                .uniform_location(state, &ImmutableString::new("projectionMatrix"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            inv_screen_size: program
+                .uniform_location(state, &ImmutableString::new("invScreenSize"))?,
+            viewport_bounds: program
+                .uniform_location(state, &ImmutableString::new("viewportBounds"))?,
+            proj_params: program.uniform_location(state, &ImmutableString::new("projParams"))?,
+            normal_map_scale: program
+                .uniform_location(state, &ImmutableString::new("normalMapScale"))?,
+            shallow_color: program
+                .uniform_location(state, &ImmutableString::new("shallowColor"))?,
+            deep_color: program.uniform_location(state, &ImmutableString::new("deepColor"))?,
+            foam_color: program.uniform_location(state, &ImmutableString::new("foamColor"))?,
+            absorption: program.uniform_location(state, &ImmutableString::new("absorption"))?,
+            refraction_strength: program
+                .uniform_location(state, &ImmutableString::new("refractionStrength"))?,
+            reflection_strength: program
+                .uniform_location(state, &ImmutableString::new("reflectionStrength"))?,
+            foam_distance: program
+                .uniform_location(state, &ImmutableString::new("foamDistance"))?,
+            light_direction: program
+                .uniform_location(state, &ImmutableString::new("lightDirection"))?,
+            light_color: program.uniform_location(state, &ImmutableString::new("lightColor"))?,
+            ambient_color: program
+                .uniform_location(state, &ImmutableString::new("ambientColor"))?,
+            program,
+        })
+    }
+}
+
+// Creates a regular grid in [0; 1] range with the given amount of cells along each side.
+fn create_grid(
+    state: &mut PipelineState,
+    resolution: u32,
+) -> Result<GeometryBuffer, FrameworkError> {
+    let side = resolution + 1;
+
+    let mut vertices = Vec::with_capacity((side * side) as usize);
+    for z in 0..side {
+        for x in 0..side {
+            vertices.push(Vector2::new(
+                x as f32 / resolution as f32,
+                z as f32 / resolution as f32,
+            ));
+        }
+    }
+
+    let mut triangles = Vec::with_capacity((resolution * resolution * 2) as usize);
+    for z in 0..resolution {
+        for x in 0..resolution {
+            let i0 = z * side + x;
+            let i1 = i0 + 1;
+            let i2 = i0 + side;
+            let i3 = i2 + 1;
+            triangles.push(TriangleDefinition([i0, i2, i1]));
+            triangles.push(TriangleDefinition([i1, i2, i3]));
+        }
+    }
+
+    let buffer = GeometryBufferBuilder::new(ElementKind::Triangle)
+        .with_buffer_builder(
+            BufferBuilder::new::<Vector2<f32>>(GeometryBufferKind::StaticDraw, Some(&vertices))
+                .with_attribute(AttributeDefinition {
+                    location: 0,
+                    kind: AttributeKind::Float2,
+                    normalized: false,
+                    divisor: 0,
+                }),
+        )
+        .build(state)?;
+
+    buffer.bind(state).set_triangles(&triangles);
+
+    Ok(buffer)
+}
+
+fn linear_rgb(color: Color) -> Vector3<f32> {
+    color.srgb_to_linear_f32().xyz()
+}
+
+/// Renders water surfaces into HDR frame. Water needs a copy of the frame (without water) to
+/// render refraction and screen-space reflections.
+pub struct WaterRenderer {
+    shader: WaterShader,
+    grids: FxHashMap<u32, TimedEntry<GeometryBuffer>>,
+}
+
+pub(crate) struct WaterRenderContext<'a, 'b, 'c> {
+    pub state: &'a mut PipelineState,
+    pub framebuffer: &'b mut FrameBuffer,
+    pub graph: &'c Graph,
+    pub camera: &'c Camera,
+    pub scene_color: Rc<RefCell<GpuTexture>>,
+    pub depth: Rc<RefCell<GpuTexture>>,
+    pub normal_dummy: Rc<RefCell<GpuTexture>>,
+    pub environment_dummy: Rc<RefCell<GpuTexture>>,
+    pub ambient_color: Color,
+    pub viewport: Rect<i32>,
+    pub frame_size: Vector2<f32>,
+    pub textures: &'a mut TextureCache,
+}
+
+impl WaterRenderer {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: WaterShader::new(state)?,
+            grids: Default::default(),
+        })
+    }
+
+    pub fn update_caches(&mut self, dt: f32) {
+        for entry in self.grids.values_mut() {
+            entry.time_to_live -= dt;
+        }
+        self.grids.retain(|_, v| v.time_to_live > 0.0);
+    }
+
+    pub fn flush(&mut self) {
+        self.grids.clear();
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        args: WaterRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let WaterRenderContext {
+            state,
+            framebuffer,
+            graph,
+            camera,
+            scene_color,
+            depth,
+            normal_dummy,
+            environment_dummy,
+            ambient_color,
+            viewport,
+            frame_size,
+            textures,
+        } = args;
+
+        let view_projection = camera.view_projection_matrix();
+        let frustum = Frustum::from_view_projection_matrix(view_projection).unwrap_or_default();
+        let inv_screen_size = Vector2::new(1.0 / frame_size.x, 1.0 / frame_size.y);
+        // Bounds of the viewport in texture coordinates of the frame.
+        let viewport_bounds = Vector4::new(
+            viewport.x() as f32 * inv_screen_size.x,
+            viewport.y() as f32 * inv_screen_size.y,
+            viewport.w() as f32 * inv_screen_size.x,
+            viewport.h() as f32 * inv_screen_size.y,
+        );
+        let proj_params = Vector2::new(camera.projection().z_far(), camera.projection().z_near());
+        let camera_position = camera.global_position();
+
+        let skybox = camera
+            .skybox_ref()
+            .and_then(|skybox| skybox.cubemap_ref())
+            .and_then(|cubemap| textures.get(state, cubemap))
+            .unwrap_or(environment_dummy);
+
+        // The first directional light is used as the sun.
+        let (light_direction, light_color) = graph
+            .linear_iter()
+            .filter(|node| node.global_visibility() && node.is_globally_enabled())
+            .find_map(|node| node.cast::<DirectionalLight>())
+            .map(|light| {
+                (
+                    light
+                        .up_vector()
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y),
+                    linear_rgb(light.base_light_ref().animated_color())
+                        * light.base_light_ref().animated_intensity(),
+                )
+            })
+            .unwrap_or_else(|| (Vector3::y(), Vector3::default()));
+        let ambient_color = linear_rgb(ambient_color);
+
+        for water in graph.linear_iter().filter_map(|node| {
+            if !node.global_visibility() || !node.is_globally_enabled() {
+                return None;
+            }
+
+            node.cast::<Water>()
+        }) {
+            if !frustum.is_intersects_aabb(&water.world_bounding_box()) {
+                continue;
+            }
+
+            let resolution = water.resolution();
+            let grid = match self.grids.entry(resolution) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(TimedEntry {
+                    value: create_grid(state, resolution)?,
+                    time_to_live: DEFAULT_RESOURCE_LIFETIME,
+                }),
+            };
+            grid.time_to_live = DEFAULT_RESOURCE_LIFETIME;
+
+            let waves = water.wave_parameters();
+            let waves_a = waves
+                .iter()
+                .map(|w| Vector4::new(w.direction.x, w.direction.y, w.wave_number, w.amplitude))
+                .collect::<Vec<_>>();
+            let waves_b = waves
+                .iter()
+                .map(|w| Vector2::new(w.angular_frequency, w.steepness))
+                .collect::<Vec<_>>();
+            debug_assert!(waves.len() <= MAX_WAVES);
+
+            let normal_texture = water
+                .normal_map()
+                .and_then(|t| textures.get(state, t))
+                .unwrap_or_else(|| normal_dummy.clone());
+
+            let shader = &self.shader;
+            statistics += framebuffer.draw(
+                grid,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: true,
+                    stencil_test: None,
+                    depth_test: true,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                ElementRange::Full,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.view_projection_matrix, &view_projection)
+                        .set_matrix4(&shader.world_matrix, &water.global_transform())
+                        .set_vector2(&shader.size, &water.size())
+                        .set_f32(&shader.time, water.time())
+                        .set_i32(&shader.wave_count, waves.len() as i32)
+                        .set_vector4_slice(&shader.waves_a, &waves_a)
+                        .set_vector2_slice(&shader.waves_b, &waves_b)
+                        .set_texture(&shader.scene_color_texture, &scene_color)
+                        .set_texture(&shader.depth_texture, &depth)
+                        .set_texture(&shader.normal_texture, &normal_texture)
+                        .set_texture(&shader.skybox_texture, &skybox)
+                        .set_matrix4(&shader.view_matrix, &camera.view_matrix())
+                        .set_matrix4(&shader.projection_matrix, &camera.projection_matrix())
+                        .set_vector3(&shader.camera_position, &camera_position)
+                        .set_vector2(&shader.inv_screen_size, &inv_screen_size)
+                        .set_vector4(&shader.viewport_bounds, &viewport_bounds)
+                        .set_vector2(&shader.proj_params, &proj_params)
+                        .set_f32(&shader.normal_map_scale, 1.0 / water.normal_map_tile_size())
+                        .set_vector3(&shader.shallow_color, &linear_rgb(water.shallow_color()))
+                        .set_vector3(&shader.deep_color, &linear_rgb(water.deep_color()))
+                        .set_vector3(&shader.foam_color, &linear_rgb(water.foam_color()))
+                        .set_f32(&shader.absorption, water.absorption())
+                        .set_f32(&shader.refraction_strength, water.refraction_strength())
+                        .set_f32(&shader.reflection_strength, water.reflection_strength())
+                        .set_f32(&shader.foam_distance, water.foam_distance())
+                        .set_vector3(&shader.light_direction, &light_direction)
+                        .set_vector3(&shader.light_color, &light_color)
+                        .set_vector3(&shader.ambient_color, &ambient_color);
+                },
+            )?;
+        }
+
+        Ok(statistics)
+    }
+}
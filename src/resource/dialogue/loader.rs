@@ -0,0 +1,23 @@
+//! Dialogue graph loader.
+
+use crate::{
+    asset::custom::CustomResourceLoader,
+    resource::dialogue::{DialogueError, DialogueGraph},
+};
+use std::path::Path;
+
+/// Default implementation for dialogue graph loading.
+pub struct DialogueGraphLoader;
+
+impl CustomResourceLoader for DialogueGraphLoader {
+    type Data = DialogueGraph;
+    type Error = DialogueError;
+
+    fn extensions(&self) -> &[&str] {
+        &["dialogue"]
+    }
+
+    fn load_from_bytes(&self, path: &Path, bytes: Vec<u8>) -> Result<DialogueGraph, DialogueError> {
+        DialogueGraph::from_bytes(&bytes, path)
+    }
+}
@@ -0,0 +1,537 @@
+//! Dialogue graph resource. It describes a conversation as a set of nodes (lines of speakers), that are
+//! connected with each other directly or via choices of a player. See [`DialogueGraph`] docs for more info
+//! and [`crate::utils::dialogue`] module to play dialogues.
+
+use crate::{
+    asset::{Resource, ResourceData},
+    core::{
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    utils::behavior::blackboard::{Blackboard, BlackboardValue},
+};
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    borrow::Cow,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+pub mod loader;
+
+/// An error that may occur during dialogue graph loading.
+#[derive(Debug)]
+pub enum DialogueError {
+    /// A parsing error has occurred.
+    ParseError(ron::error::SpannedError),
+
+    /// There are two or more nodes with the same name.
+    DuplicateNode(String),
+
+    /// A node, a choice or the entry list references a node that does not exist.
+    UnknownNode(String),
+
+    /// A node, a choice or the entry list references a node index that does not exist.
+    InvalidNodeIndex(u32),
+}
+
+impl Display for DialogueError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DialogueError::ParseError(v) => {
+                write!(f, "A parsing error has occurred {v:?}")
+            }
+            DialogueError::DuplicateNode(name) => {
+                write!(f, "There are multiple nodes with {name} name!")
+            }
+            DialogueError::UnknownNode(name) => {
+                write!(f, "Node {name} does not exist!")
+            }
+            DialogueError::InvalidNodeIndex(index) => {
+                write!(f, "Node with {index} index does not exist!")
+            }
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for DialogueError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+/// A value, that could be used in conditions and events of dialogues. It is a subset of
+/// [`BlackboardValue`], that could be written in text files.
+#[derive(Clone, Debug, PartialEq, Visit, Serialize, Deserialize)]
+pub enum DialogueValue {
+    /// Boolean value.
+    Bool(bool),
+    /// Integer value.
+    Integer(i64),
+    /// Floating-point value.
+    Float(f32),
+    /// String value.
+    String(String),
+}
+
+impl Default for DialogueValue {
+    fn default() -> Self {
+        Self::Bool(false)
+    }
+}
+
+impl From<DialogueValue> for BlackboardValue {
+    fn from(value: DialogueValue) -> Self {
+        match value {
+            DialogueValue::Bool(v) => BlackboardValue::Bool(v),
+            DialogueValue::Integer(v) => BlackboardValue::Integer(v),
+            DialogueValue::Float(v) => BlackboardValue::Float(v),
+            DialogueValue::String(v) => BlackboardValue::String(v),
+        }
+    }
+}
+
+impl DialogueValue {
+    fn matches(&self, value: &BlackboardValue) -> bool {
+        match (self, value) {
+            (DialogueValue::Bool(a), BlackboardValue::Bool(b)) => a == b,
+            (DialogueValue::Integer(a), BlackboardValue::Integer(b)) => a == b,
+            (DialogueValue::Float(a), BlackboardValue::Float(b)) => a == b,
+            (DialogueValue::String(a), BlackboardValue::String(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+fn numeric(value: &BlackboardValue) -> Option<f64> {
+    match value {
+        BlackboardValue::Integer(v) => Some(*v as f64),
+        BlackboardValue::Float(v) => Some(*v as f64),
+        _ => None,
+    }
+}
+
+/// A condition of a node or a choice. Conditions are checked against a blackboard, which should contain
+/// all the facts about the game state, that are needed by dialogues (quest progress, amount of gold,
+/// relationship with a character, etc.).
+#[derive(Clone, Debug, PartialEq, Visit, Serialize, Deserialize)]
+pub enum DialogueCondition {
+    /// The blackboard has a value for the key.
+    IsSet(String),
+    /// The blackboard does not have a value for the key.
+    IsNotSet(String),
+    /// The blackboard has the exact value for the key.
+    Equals(String, DialogueValue),
+    /// The blackboard does not have the exact value for the key.
+    NotEquals(String, DialogueValue),
+    /// The blackboard has a numeric (integer or floating-point) value for the key, that is greater than
+    /// the given one.
+    GreaterThan(String, f32),
+    /// The blackboard has a numeric (integer or floating-point) value for the key, that is less than the
+    /// given one.
+    LessThan(String, f32),
+}
+
+impl Default for DialogueCondition {
+    fn default() -> Self {
+        Self::IsSet(Default::default())
+    }
+}
+
+impl DialogueCondition {
+    /// Checks the condition against the given blackboard.
+    pub fn is_satisfied(&self, blackboard: &Blackboard) -> bool {
+        match self {
+            DialogueCondition::IsSet(key) => blackboard.contains(key),
+            DialogueCondition::IsNotSet(key) => !blackboard.contains(key),
+            DialogueCondition::Equals(key, expected) => blackboard
+                .value(key)
+                .map_or(false, |value| expected.matches(value)),
+            DialogueCondition::NotEquals(key, expected) => !blackboard
+                .value(key)
+                .map_or(false, |value| expected.matches(value)),
+            DialogueCondition::GreaterThan(key, threshold) => blackboard
+                .value(key)
+                .and_then(numeric)
+                .map_or(false, |value| value > *threshold as f64),
+            DialogueCondition::LessThan(key, threshold) => blackboard
+                .value(key)
+                .and_then(numeric)
+                .map_or(false, |value| value < *threshold as f64),
+        }
+    }
+}
+
+/// An event, that happens when a node is entered or a choice is selected.
+#[derive(Clone, Debug, PartialEq, Visit, Serialize, Deserialize)]
+pub enum DialogueEvent {
+    /// Sets the value for the key in the blackboard.
+    Set(String, DialogueValue),
+    /// Removes the value for the key from the blackboard.
+    Remove(String),
+    /// A game-specific event, that is reported to the game. It could be used to give an item, start a
+    /// quest, play a sound, etc.
+    Custom(String),
+}
+
+impl Default for DialogueEvent {
+    fn default() -> Self {
+        Self::Custom(Default::default())
+    }
+}
+
+/// A choice of a player in a dialogue node.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct DialogueChoice {
+    /// Localization key of the text of the choice.
+    pub text: String,
+    /// Conditions, that must be satisfied for the choice to be available.
+    pub conditions: Vec<DialogueCondition>,
+    /// Events, that happen when the choice is selected.
+    pub events: Vec<DialogueEvent>,
+    /// Indices of the nodes, that could follow the choice. The first one with satisfied conditions is
+    /// selected, the dialogue ends if there's no such node.
+    pub next: Vec<u32>,
+}
+
+/// A single line of a dialogue.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct DialogueNode {
+    /// Name of the node, it is unique within a graph.
+    pub name: String,
+    /// Name (or localization key of the name) of the speaker.
+    pub speaker: String,
+    /// Localization key of the text of the line.
+    pub text: String,
+    /// Conditions, that must be satisfied for the node to be entered.
+    pub conditions: Vec<DialogueCondition>,
+    /// Events, that happen when the node is entered.
+    pub events: Vec<DialogueEvent>,
+    /// Choices of a player. If there are no available choices, the dialogue continues with [`Self::next`]
+    /// nodes.
+    pub choices: Vec<DialogueChoice>,
+    /// Indices of the nodes, that could follow the node. The first one with satisfied conditions is
+    /// selected, the dialogue ends if there's no such node.
+    pub next: Vec<u32>,
+}
+
+/// Serializable description of a dialogue choice, see [`DialogueChoice`] docs for more info.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DialogueChoiceDefinition {
+    /// See [`DialogueChoice::text`].
+    pub text: String,
+    /// See [`DialogueChoice::conditions`].
+    #[serde(default)]
+    pub conditions: Vec<DialogueCondition>,
+    /// See [`DialogueChoice::events`].
+    #[serde(default)]
+    pub events: Vec<DialogueEvent>,
+    /// Names of the nodes, see [`DialogueChoice::next`].
+    #[serde(default)]
+    pub next: Vec<String>,
+}
+
+/// Serializable description of a dialogue node, see [`DialogueNode`] docs for more info.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DialogueNodeDefinition {
+    /// See [`DialogueNode::name`].
+    pub name: String,
+    /// See [`DialogueNode::speaker`].
+    #[serde(default)]
+    pub speaker: String,
+    /// See [`DialogueNode::text`].
+    pub text: String,
+    /// See [`DialogueNode::conditions`].
+    #[serde(default)]
+    pub conditions: Vec<DialogueCondition>,
+    /// See [`DialogueNode::events`].
+    #[serde(default)]
+    pub events: Vec<DialogueEvent>,
+    /// See [`DialogueNode::choices`].
+    #[serde(default)]
+    pub choices: Vec<DialogueChoiceDefinition>,
+    /// Names of the nodes, see [`DialogueNode::next`].
+    #[serde(default)]
+    pub next: Vec<String>,
+}
+
+/// Serializable description of a dialogue graph. It is stored in `.dialogue` files in RON format, nodes
+/// reference each other by names:
+///
+/// ```text
+/// (
+///     entry: ["greeting_again", "greeting"],
+///     nodes: [
+///         (
+///             name: "greeting",
+///             speaker: "Guard",
+///             text: "guard.greeting",
+///             events: [Set("MetGuard", Bool(true))],
+///             choices: [
+///                 (text: "guard.ask_gate", next: ["gate_closed"]),
+///                 (
+///                     text: "guard.bribe",
+///                     conditions: [GreaterThan("Gold", 10.0)],
+///                     events: [Custom("TakeGold")],
+///                     next: ["gate_open"],
+///                 ),
+///             ],
+///         ),
+///         (
+///             name: "greeting_again",
+///             speaker: "Guard",
+///             text: "guard.greeting_again",
+///             conditions: [Equals("MetGuard", Bool(true))],
+///             next: ["gate_closed"],
+///         ),
+///         (name: "gate_closed", speaker: "Guard", text: "guard.gate_closed"),
+///         (name: "gate_open", speaker: "Guard", text: "guard.gate_open", events: [Custom("OpenGate")]),
+///     ],
+/// )
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DialogueGraphDefinition {
+    /// Names of the nodes, that could start the dialogue. The first one with satisfied conditions is
+    /// selected.
+    pub entry: Vec<String>,
+    /// A list of nodes.
+    pub nodes: Vec<DialogueNodeDefinition>,
+}
+
+impl DialogueGraphDefinition {
+    /// Converts the definition to a list of nodes and a list of entry nodes, node names are resolved to
+    /// indices.
+    pub fn resolve(self) -> Result<(Vec<DialogueNode>, Vec<u32>), DialogueError> {
+        let mut indices = FxHashMap::default();
+        for (index, node) in self.nodes.iter().enumerate() {
+            if indices.insert(node.name.clone(), index as u32).is_some() {
+                return Err(DialogueError::DuplicateNode(node.name.clone()));
+            }
+        }
+
+        let resolve = |names: Vec<String>| {
+            names
+                .into_iter()
+                .map(|name| match indices.get(&name) {
+                    Some(index) => Ok(*index),
+                    None => Err(DialogueError::UnknownNode(name)),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let entry = resolve(self.entry)?;
+        let nodes = self
+            .nodes
+            .into_iter()
+            .map(|node| {
+                Ok(DialogueNode {
+                    name: node.name,
+                    speaker: node.speaker,
+                    text: node.text,
+                    conditions: node.conditions,
+                    events: node.events,
+                    choices: node
+                        .choices
+                        .into_iter()
+                        .map(|choice| {
+                            Ok(DialogueChoice {
+                                text: choice.text,
+                                conditions: choice.conditions,
+                                events: choice.events,
+                                next: resolve(choice.next)?,
+                            })
+                        })
+                        .collect::<Result<Vec<_>, DialogueError>>()?,
+                    next: resolve(node.next)?,
+                })
+            })
+            .collect::<Result<Vec<_>, DialogueError>>()?;
+
+        Ok((nodes, entry))
+    }
+}
+
+/// Dialogue graph is a set of nodes (lines of speakers), connected with each other. Every node could have
+/// conditions, that must be satisfied to enter the node, events that happen when the node is entered and
+/// choices of a player. Conditions and events work with a [`Blackboard`], that stores the facts about the
+/// game state. Texts of nodes and choices are localization keys, they're translated by the dialogue player.
+///
+/// Dialogue graphs are usually loaded from `.dialogue` files (see [`DialogueGraphDefinition`] for the
+/// format), but they could also be created from code using [`DialogueGraph::new`]. Use
+/// [`crate::utils::dialogue::DialoguePlayer`] to play a dialogue.
+#[derive(Debug, Default, Visit, Reflect)]
+pub struct DialogueGraph {
+    #[reflect(hidden)]
+    pub(crate) path: PathBuf,
+    #[reflect(hidden)]
+    nodes: Vec<DialogueNode>,
+    #[reflect(hidden)]
+    entry: Vec<u32>,
+}
+
+impl ResourceData for DialogueGraph {
+    fn path(&self) -> Cow<Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+}
+
+impl TypeUuidProvider for DialogueGraph {
+    fn type_uuid() -> Uuid {
+        uuid!("b6a3e7d2-5f1c-4e8a-9d47-2c8e1f0b3a69")
+    }
+}
+
+impl DialogueGraph {
+    /// Creates new dialogue graph from the given nodes and indices of entry nodes. Nodes must reference
+    /// existing nodes only.
+    pub fn new(nodes: Vec<DialogueNode>, entry: Vec<u32>) -> Result<Self, DialogueError> {
+        let references = entry.iter().chain(nodes.iter().flat_map(|node| {
+            node.next
+                .iter()
+                .chain(node.choices.iter().flat_map(|choice| choice.next.iter()))
+        }));
+        for index in references {
+            if *index as usize >= nodes.len() {
+                return Err(DialogueError::InvalidNodeIndex(*index));
+            }
+        }
+
+        Ok(Self {
+            path: Default::default(),
+            nodes,
+            entry,
+        })
+    }
+
+    /// Creates new dialogue graph from its serialized description (see [`DialogueGraphDefinition`]).
+    pub fn from_bytes(bytes: &[u8], path: &Path) -> Result<Self, DialogueError> {
+        let (nodes, entry) = ron::de::from_bytes::<DialogueGraphDefinition>(bytes)?.resolve()?;
+        let mut graph = Self::new(nodes, entry)?;
+        graph.path = path.to_path_buf();
+        Ok(graph)
+    }
+
+    /// Returns a list of nodes of the graph.
+    pub fn nodes(&self) -> &[DialogueNode] {
+        &self.nodes
+    }
+
+    /// Tries to get a node by its index.
+    pub fn node(&self, index: u32) -> Option<&DialogueNode> {
+        self.nodes.get(index as usize)
+    }
+
+    /// Tries to find a node by its name, returns its index.
+    pub fn find_node(&self, name: &str) -> Option<u32> {
+        self.nodes
+            .iter()
+            .position(|node| node.name == name)
+            .map(|index| index as u32)
+    }
+
+    /// Returns indices of the nodes, that could start the dialogue.
+    pub fn entry(&self) -> &[u32] {
+        &self.entry
+    }
+
+    /// Returns index of the first node from the given list, whose conditions are satisfied.
+    pub fn select(&self, candidates: &[u32], blackboard: &Blackboard) -> Option<u32> {
+        candidates.iter().cloned().find(|index| {
+            self.node(*index).map_or(false, |node| {
+                node.conditions
+                    .iter()
+                    .all(|condition| condition.is_satisfied(blackboard))
+            })
+        })
+    }
+}
+
+/// Type alias for dialogue graph resources.
+pub type DialogueGraphResource = Resource<DialogueGraph>;
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        resource::dialogue::{DialogueCondition, DialogueError, DialogueGraph, DialogueValue},
+        utils::behavior::blackboard::Blackboard,
+    };
+    use std::path::Path;
+
+    #[test]
+    fn test_dialogue_graph_loading() {
+        let graph = DialogueGraph::from_bytes(
+            br#"(
+                entry: ["again", "greeting"],
+                nodes: [
+                    (
+                        name: "greeting",
+                        speaker: "Guard",
+                        text: "guard.greeting",
+                        choices: [(text: "guard.bye", next: ["bye"])],
+                    ),
+                    (name: "again", text: "guard.again", conditions: [IsSet("MetGuard")]),
+                    (name: "bye", text: "guard.bye"),
+                ],
+            )"#,
+            Path::new("guard.dialogue"),
+        )
+        .unwrap();
+
+        assert_eq!(graph.nodes().len(), 3);
+        assert_eq!(graph.find_node("bye"), Some(2));
+        assert_eq!(graph.node(0).unwrap().choices[0].next, vec![2]);
+
+        let mut blackboard = Blackboard::default();
+        assert_eq!(graph.select(graph.entry(), &blackboard), Some(0));
+        blackboard.set("MetGuard", true);
+        assert_eq!(graph.select(graph.entry(), &blackboard), Some(1));
+
+        assert!(matches!(
+            DialogueGraph::from_bytes(
+                br#"(entry: ["start"], nodes: [(name: "begin", text: "")])"#,
+                Path::new("invalid.dialogue"),
+            ),
+            Err(DialogueError::UnknownNode(name)) if name == "start"
+        ));
+    }
+
+    #[test]
+    fn test_dialogue_conditions() {
+        let mut blackboard = Blackboard::default();
+        blackboard.set("Gold", 15i64);
+        blackboard.set("Name", "Bob".to_string());
+
+        let check = |condition: DialogueCondition| condition.is_satisfied(&blackboard);
+        assert!(check(DialogueCondition::GreaterThan("Gold".into(), 10.0)));
+        assert!(!check(DialogueCondition::LessThan("Gold".into(), 10.0)));
+        assert!(check(DialogueCondition::Equals(
+            "Name".into(),
+            DialogueValue::String("Bob".into())
+        )));
+        assert!(check(DialogueCondition::NotEquals(
+            "Gold".into(),
+            DialogueValue::Float(15.0)
+        )));
+        assert!(check(DialogueCondition::IsNotSet("Quest".into())));
+    }
+}
@@ -0,0 +1,70 @@
+//! Contains all possible errors that can occur during glTF loading and conversion.
+
+use crate::core::io::FileLoadError;
+use std::fmt::{Display, Formatter};
+
+/// See module docs.
+#[derive(Debug)]
+pub enum GltfError {
+    /// The document is malformed or does not pass validation.
+    Gltf(gltf::Error),
+
+    /// An error occurred during file loading.
+    FileLoadError(FileLoadError),
+
+    /// A buffer is referenced, but its content is not available (for example `BIN` chunk is
+    /// missing in a binary glTF).
+    MissingBuffer(usize),
+
+    /// A primitive does not have vertex positions.
+    MissingPositions,
+
+    /// A buffer uses unsupported data URI.
+    InvalidDataUri,
+
+    /// Arbitrary error that can have any meaning.
+    Custom(String),
+}
+
+impl Display for GltfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GltfError::Gltf(v) => {
+                write!(f, "glTF: Invalid document: {v}")
+            }
+            GltfError::FileLoadError(v) => {
+                write!(f, "glTF: File load error {v:?}.")
+            }
+            GltfError::MissingBuffer(v) => {
+                write!(f, "glTF: Content of buffer {v} is not available.")
+            }
+            GltfError::MissingPositions => {
+                write!(f, "glTF: A primitive does not have vertex positions.")
+            }
+            GltfError::InvalidDataUri => {
+                write!(f, "glTF: Invalid or unsupported data URI.")
+            }
+            GltfError::Custom(v) => {
+                write!(f, "glTF: An error has occurred: {v}")
+            }
+        }
+    }
+}
+
+impl From<gltf::Error> for GltfError {
+    fn from(err: gltf::Error) -> Self {
+        GltfError::Gltf(err)
+    }
+}
+
+impl From<FileLoadError> for GltfError {
+    fn from(err: FileLoadError) -> Self {
+        GltfError::FileLoadError(err)
+    }
+}
+
+impl From<String> for GltfError {
+    fn from(err: String) -> Self {
+        GltfError::Custom(err)
+    }
+}
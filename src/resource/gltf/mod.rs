@@ -0,0 +1,1042 @@
+//! Contains all methods to load and convert glTF 2.0 model format.
+//!
+//! glTF is an open and widely supported format for transmission of 3D scenes. It can store meshes
+//! with PBR materials, skeletal animation, morph targets (blend shapes), keyframe animation, cameras
+//! and lights (via `KHR_lights_punctual` extension). Both flavors of the format are supported: text
+//! (`.gltf`) with external or embedded (data URI) buffers and binary (`.glb`).
+//!
+//! Normally you should never use methods from this module directly, use resource manager to load
+//! models and create their instances.
+
+pub mod error;
+
+use crate::{
+    animation::{track::Track, Animation, AnimationContainer},
+    asset::{manager::ResourceManager, Resource},
+    core::{
+        algebra::{Matrix4, Quaternion, UnitQuaternion, Vector2, Vector3, Vector4},
+        color::Color,
+        curve::{CurveKey, CurveKeyKind},
+        instant::Instant,
+        io,
+        log::Log,
+        math::TriangleDefinition,
+        pool::Handle,
+        sstorage::ImmutableString,
+        uuid::Uuid,
+    },
+    material::{shader::SamplerFallback, Material, PropertyValue, SharedMaterial},
+    resource::{
+        gltf::error::GltfError,
+        model::{MaterialSearchOptions, ModelImportOptions},
+        texture::{
+            CompressionOptions, MipFilter, Texture, TextureKind, TexturePixelKind, TextureResource,
+        },
+    },
+    scene::{
+        animation::AnimationPlayerBuilder,
+        base::{BaseBuilder, InstanceId},
+        camera::{CameraBuilder, OrthographicProjection, PerspectiveProjection, Projection},
+        graph::Graph,
+        light::{
+            directional::DirectionalLightBuilder, point::PointLightBuilder, spot::SpotLightBuilder,
+            BaseLightBuilder,
+        },
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{
+                BlendShape, BlendShapesContainer, InputBlendShapeData, Surface, SurfaceData,
+                SurfaceSharedData,
+            },
+            vertex::{AnimatedVertex, StaticVertex},
+            Mesh, MeshBuilder,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+    utils,
+};
+use base64::Engine;
+use fxhash::{FxHashMap, FxHashSet};
+use gltf::{
+    animation::{util::ReadOutputs, Interpolation},
+    khr_lights_punctual::Kind,
+    mesh::Mode,
+    Document, Gltf,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+/// Decodes percent-encoded characters of a URI.
+fn decode_percents(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Decodes content of a data URI (`data:[<media type>][;base64],<data>`), `uri` must not contain
+/// `data:` prefix.
+fn decode_data_uri(uri: &str) -> Result<Vec<u8>, GltfError> {
+    let (header, data) = uri.split_once(',').ok_or(GltfError::InvalidDataUri)?;
+    if header.ends_with(";base64") {
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|_| GltfError::InvalidDataUri)
+    } else {
+        Ok(decode_percents(data).into_bytes())
+    }
+}
+
+async fn load_uri(uri: &str, base_path: &Path) -> Result<Vec<u8>, GltfError> {
+    if let Some(data_uri) = uri.strip_prefix("data:") {
+        decode_data_uri(data_uri)
+    } else {
+        Ok(io::load_file(base_path.join(decode_percents(uri))).await?)
+    }
+}
+
+async fn load_buffers(
+    document: &Document,
+    mut blob: Option<Vec<u8>>,
+    base_path: &Path,
+) -> Result<Vec<Vec<u8>>, GltfError> {
+    let mut buffers = Vec::new();
+    for buffer in document.buffers() {
+        let data = match buffer.source() {
+            gltf::buffer::Source::Bin => blob
+                .take()
+                .ok_or(GltfError::MissingBuffer(buffer.index()))?,
+            gltf::buffer::Source::Uri(uri) => load_uri(uri, base_path).await?,
+        };
+        if data.len() < buffer.length() {
+            return Err(GltfError::MissingBuffer(buffer.index()));
+        }
+        buffers.push(data);
+    }
+    Ok(buffers)
+}
+
+/// Converts primitive indices to a list of triangles. Returns `None` if the mode is not
+/// a triangle-based one.
+fn make_triangles(mode: Mode, indices: &[u32]) -> Option<Vec<TriangleDefinition>> {
+    match mode {
+        Mode::Triangles => Some(
+            indices
+                .chunks_exact(3)
+                .map(|t| TriangleDefinition([t[0], t[1], t[2]]))
+                .collect(),
+        ),
+        Mode::TriangleStrip => Some(
+            indices
+                .windows(3)
+                .enumerate()
+                .map(|(i, t)| {
+                    // Every odd triangle of a strip has flipped winding order.
+                    if i % 2 == 0 {
+                        TriangleDefinition([t[0], t[1], t[2]])
+                    } else {
+                        TriangleDefinition([t[1], t[0], t[2]])
+                    }
+                })
+                .collect(),
+        ),
+        Mode::TriangleFan => Some(
+            indices
+                .get(1..)
+                .unwrap_or_default()
+                .windows(2)
+                .map(|t| TriangleDefinition([indices[0], t[0], t[1]]))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Converts a sequence of rotations to a sequence of Euler angles (XYZ order), which is used by
+/// rotation tracks. Angles are kept continuous between neighbouring keys, otherwise interpolation
+/// would take the "long way" around.
+fn quaternions_to_euler(rotations: impl Iterator<Item = [f32; 4]>) -> Vec<Vector3<f32>> {
+    let mut angles: Vec<Vector3<f32>> = Vec::new();
+    for [x, y, z, w] in rotations {
+        let (roll, pitch, yaw) =
+            UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z)).euler_angles();
+        let mut euler = Vector3::new(roll, pitch, yaw);
+        if let Some(previous) = angles.last() {
+            let unwrap = |mut euler: Vector3<f32>| {
+                for i in 0..3 {
+                    euler[i] += ((previous[i] - euler[i]) / TAU).round() * TAU;
+                }
+                euler
+            };
+            // Every rotation could be represented by two sets of angles, pick the closest one.
+            let a = unwrap(euler);
+            let b = unwrap(Vector3::new(roll + PI, PI - pitch, yaw + PI));
+            euler = if (a - previous).norm() <= (b - previous).norm() {
+                a
+            } else {
+                b
+            };
+        }
+        angles.push(euler);
+    }
+    angles
+}
+
+fn fill_track(
+    track: &mut Track,
+    times: &[f32],
+    values: &[Vector3<f32>],
+    interpolation: Interpolation,
+) {
+    let curves = track.data_container_mut().curves_mut();
+    match interpolation {
+        Interpolation::CubicSpline => {
+            // Each key is stored as a triple (in-tangent, value, out-tangent).
+            for (&time, triple) in times.iter().zip(values.chunks_exact(3)) {
+                for (i, curve) in curves.iter_mut().enumerate() {
+                    curve.add_key(CurveKey::new(
+                        time,
+                        triple[1][i],
+                        CurveKeyKind::Cubic {
+                            left_tangent: triple[0][i],
+                            right_tangent: triple[2][i],
+                        },
+                    ));
+                }
+            }
+        }
+        Interpolation::Linear | Interpolation::Step => {
+            let kind = if interpolation == Interpolation::Step {
+                CurveKeyKind::Constant
+            } else {
+                CurveKeyKind::Linear
+            };
+            for (&time, value) in times.iter().zip(values) {
+                for (i, curve) in curves.iter_mut().enumerate() {
+                    curve.add_key(CurveKey::new(time, value[i], kind.clone()));
+                }
+            }
+        }
+    }
+}
+
+fn make_instance_id(index: usize, name: &str) -> InstanceId {
+    // glTF does not have persistent unique ids for nodes, so generate one from the index and the
+    // name of a node. This id must be stable between loads, otherwise parent-child relations
+    // between prefabs will break.
+    let mut hasher = DefaultHasher::new();
+    index.hash(&mut hasher);
+    name.hash(&mut hasher);
+    let hash = hasher.finish();
+    InstanceId(Uuid::from_u64_pair(hash, hash))
+}
+
+fn node_name(node: &gltf::Node) -> String {
+    node.name()
+        .map(|name| name.to_owned())
+        .unwrap_or_else(|| format!("Node{}", node.index()))
+}
+
+fn convert_node_to_base(node: &gltf::Node) -> BaseBuilder {
+    let name = node_name(node);
+    let (translation, [x, y, z, w], scale) = node.transform().decomposed();
+    BaseBuilder::new()
+        .with_instance_id(make_instance_id(node.index(), &name))
+        .with_name(name)
+        .with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::from(translation))
+                .with_local_rotation(UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z)))
+                .with_local_scale(Vector3::from(scale))
+                .build(),
+        )
+}
+
+/// Cameras and lights in glTF are looking along -Z axis, which does not match the engine's
+/// conventions. Such nodes are converted to a pivot (which takes the transform, animations and
+/// children of the source node) with a child node, that is rotated to compensate the difference.
+fn make_oriented_child_base(
+    node: &gltf::Node,
+    graph: &mut Graph,
+    base: BaseBuilder,
+    suffix: &str,
+    rotation: UnitQuaternion<f32>,
+) -> (Handle<Node>, BaseBuilder) {
+    let name = format!("{}_{}", node_name(node), suffix);
+    let pivot = PivotBuilder::new(base).build(graph);
+    let child_base = BaseBuilder::new()
+        .with_instance_id(make_instance_id(node.index(), &name))
+        .with_name(name)
+        .with_local_transform(
+            TransformBuilder::new()
+                .with_local_rotation(rotation)
+                .build(),
+        );
+    (pivot, child_base)
+}
+
+fn set_material_property(material: &mut Material, name: &str, value: PropertyValue) {
+    if let Err(e) = material.set_property(&ImmutableString::new(name), value) {
+        Log::err(format!(
+            "Unable to set material property {} for glTF material! Reason: {:?}",
+            name, e
+        ));
+    }
+}
+
+struct Converter<'a> {
+    buffers: &'a [Vec<u8>],
+    base_path: &'a Path,
+    model_path: &'a Path,
+    resource_manager: ResourceManager,
+    model_import_options: &'a ModelImportOptions,
+    textures: FxHashMap<usize, Option<TextureResource>>,
+    metallic_roughness_textures: FxHashMap<usize, Option<(TextureResource, TextureResource)>>,
+    materials: FxHashMap<Option<usize>, SharedMaterial>,
+}
+
+impl<'a> Converter<'a> {
+    fn texture_path(&self, uri: &str) -> PathBuf {
+        let relative_path = PathBuf::from(decode_percents(uri));
+        match self.model_import_options.material_search_options {
+            MaterialSearchOptions::MaterialsDirectory(ref directory) => {
+                match relative_path.file_name() {
+                    Some(file_name) => directory.join(file_name),
+                    None => self.base_path.join(relative_path),
+                }
+            }
+            // glTF stores paths relative to the document, so there is no need to search for textures.
+            _ => self.base_path.join(relative_path),
+        }
+    }
+
+    async fn image_data(&self, image: &gltf::Image<'_>) -> Result<Vec<u8>, GltfError> {
+        match image.source() {
+            gltf::image::Source::View { view, .. } => {
+                let buffer = self
+                    .buffers
+                    .get(view.buffer().index())
+                    .ok_or(GltfError::MissingBuffer(view.buffer().index()))?;
+                buffer
+                    .get(view.offset()..view.offset() + view.length())
+                    .map(|data| data.to_vec())
+                    .ok_or(GltfError::MissingBuffer(view.buffer().index()))
+            }
+            gltf::image::Source::Uri { uri, .. } => {
+                if let Some(data_uri) = uri.strip_prefix("data:") {
+                    decode_data_uri(data_uri)
+                } else {
+                    Ok(io::load_file(self.texture_path(uri)).await?)
+                }
+            }
+        }
+    }
+
+    async fn load_texture(&self, image: &gltf::Image<'_>) -> Result<TextureResource, GltfError> {
+        if let gltf::image::Source::Uri { uri, .. } = image.source() {
+            if !uri.starts_with("data:") {
+                // External textures are shared resources, so request them via resource manager.
+                return Ok(self
+                    .resource_manager
+                    .request::<Texture, _>(self.texture_path(uri)));
+            }
+        }
+
+        let data = self.image_data(image).await?;
+        let texture = Texture::load_from_memory(
+            &data,
+            CompressionOptions::NoCompression,
+            true,
+            MipFilter::default(),
+        )
+        .map_err(|e| GltfError::Custom(format!("Unable to load embedded texture: {:?}", e)))?;
+        Ok(Resource::new_ok(texture))
+    }
+
+    async fn texture(&mut self, texture: gltf::Texture<'_>) -> Option<TextureResource> {
+        let image = texture.source();
+        if let Some(texture) = self.textures.get(&image.index()) {
+            return texture.clone();
+        }
+
+        let texture = match self.load_texture(&image).await {
+            Ok(texture) => Some(texture),
+            Err(e) => {
+                Log::err(format!(
+                    "Unable to load texture {} for 3D model {:?}. Reason: {}",
+                    image.index(),
+                    self.model_path,
+                    e
+                ));
+                None
+            }
+        };
+        self.textures.insert(image.index(), texture.clone());
+        texture
+    }
+
+    /// glTF packs metallic (blue channel) and roughness (green channel) maps in a single texture,
+    /// while the standard shader expects them in red channel of separate textures. So the texture
+    /// is split in two.
+    async fn split_metallic_roughness_texture(
+        &self,
+        image: &gltf::Image<'_>,
+    ) -> Result<(TextureResource, TextureResource), GltfError> {
+        let data = self.image_data(image).await?;
+        let rgb = ::image::load_from_memory(&data)
+            .map_err(|e| GltfError::Custom(format!("Unable to decode image: {:?}", e)))?
+            .into_rgb8();
+        let kind = TextureKind::Rectangle {
+            width: rgb.width(),
+            height: rgb.height(),
+        };
+        let metallic = rgb.pixels().map(|pixel| pixel[2]).collect::<Vec<_>>();
+        let roughness = rgb.pixels().map(|pixel| pixel[1]).collect::<Vec<_>>();
+        let make_texture = |bytes| {
+            Texture::from_bytes(kind, TexturePixelKind::R8, bytes, false)
+                .map(Resource::new_ok)
+                .ok_or_else(|| GltfError::Custom("Invalid image size.".to_string()))
+        };
+        Ok((make_texture(metallic)?, make_texture(roughness)?))
+    }
+
+    async fn metallic_roughness_texture(
+        &mut self,
+        texture: gltf::Texture<'_>,
+    ) -> Option<(TextureResource, TextureResource)> {
+        let image = texture.source();
+        if let Some(textures) = self.metallic_roughness_textures.get(&image.index()) {
+            return textures.clone();
+        }
+
+        let textures = match self.split_metallic_roughness_texture(&image).await {
+            Ok(textures) => Some(textures),
+            Err(e) => {
+                Log::err(format!(
+                    "Unable to load metallic-roughness texture {} for 3D model {:?}. Reason: {}",
+                    image.index(),
+                    self.model_path,
+                    e
+                ));
+                None
+            }
+        };
+        self.metallic_roughness_textures
+            .insert(image.index(), textures.clone());
+        textures
+    }
+
+    async fn material(&mut self, source: gltf::Material<'_>) -> SharedMaterial {
+        if let Some(material) = self.materials.get(&source.index()) {
+            return material.clone();
+        }
+
+        let mut material = Material::standard();
+
+        let pbr = source.pbr_metallic_roughness();
+        set_material_property(
+            &mut material,
+            "diffuseColor",
+            PropertyValue::Color(Color::from(Vector4::from(pbr.base_color_factor()))),
+        );
+
+        let mut samplers = Vec::new();
+        if let Some(info) = pbr.base_color_texture() {
+            if let Some(texture) = self.texture(info.texture()).await {
+                samplers.push(("diffuseTexture", texture, SamplerFallback::White));
+            }
+        }
+        if let Some(info) = pbr.metallic_roughness_texture() {
+            if let Some((metallic, roughness)) =
+                self.metallic_roughness_texture(info.texture()).await
+            {
+                samplers.push(("metallicTexture", metallic, SamplerFallback::Black));
+                samplers.push(("roughnessTexture", roughness, SamplerFallback::White));
+            }
+        }
+        if let Some(normal) = source.normal_texture() {
+            if let Some(texture) = self.texture(normal.texture()).await {
+                samplers.push(("normalTexture", texture, SamplerFallback::Normal));
+            }
+        }
+        if let Some(occlusion) = source.occlusion_texture() {
+            if let Some(texture) = self.texture(occlusion.texture()).await {
+                samplers.push(("aoTexture", texture, SamplerFallback::White));
+            }
+        }
+        if let Some(info) = source.emissive_texture() {
+            if let Some(texture) = self.texture(info.texture()).await {
+                samplers.push(("emissionTexture", texture, SamplerFallback::Black));
+                set_material_property(
+                    &mut material,
+                    "emissionStrength",
+                    PropertyValue::Vector3(Vector3::from(source.emissive_factor())),
+                );
+            }
+        }
+
+        for (name, texture, fallback) in samplers {
+            set_material_property(
+                &mut material,
+                name,
+                PropertyValue::Sampler {
+                    value: Some(texture),
+                    fallback,
+                },
+            );
+        }
+
+        let material = SharedMaterial::new(material);
+        self.materials.insert(source.index(), material.clone());
+        material
+    }
+
+    async fn convert_mesh(
+        &mut self,
+        base: BaseBuilder,
+        node: &gltf::Node<'_>,
+        mesh: gltf::Mesh<'_>,
+        graph: &mut Graph,
+    ) -> Result<Handle<Node>, GltfError> {
+        let buffers = self.buffers;
+        let is_skinned = node.skin().is_some();
+        let default_weights = node
+            .weights()
+            .or_else(|| mesh.weights())
+            .unwrap_or_default();
+
+        let mut surfaces = Vec::new();
+        let mut blend_shapes = Vec::new();
+        for primitive in mesh.primitives() {
+            let reader =
+                primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| data.as_slice()));
+
+            let positions = reader
+                .read_positions()
+                .ok_or(GltfError::MissingPositions)?
+                .map(Vector3::from)
+                .collect::<Vec<_>>();
+            let vertex_count = positions.len();
+            let normals = reader
+                .read_normals()
+                .map(|normals| normals.map(Vector3::from).collect::<Vec<_>>())
+                .unwrap_or_else(|| vec![Vector3::y(); vertex_count]);
+            let tex_coords = reader
+                .read_tex_coords(0)
+                .map(|tex_coords| tex_coords.into_f32().map(Vector2::from).collect::<Vec<_>>())
+                .unwrap_or_else(|| vec![Vector2::default(); vertex_count]);
+            let tangents = reader
+                .read_tangents()
+                .map(|tangents| tangents.map(Vector4::from).collect::<Vec<_>>());
+            let indices = reader
+                .read_indices()
+                .map(|indices| indices.into_u32().collect::<Vec<_>>())
+                .unwrap_or_else(|| (0..vertex_count as u32).collect());
+
+            let triangles = match make_triangles(primitive.mode(), &indices) {
+                Some(triangles) => triangles,
+                None => {
+                    Log::warn(format!(
+                        "glTF: Primitive mode {:?} of mesh {} is not supported, the primitive is skipped.",
+                        primitive.mode(),
+                        mesh.index()
+                    ));
+                    continue;
+                }
+            };
+
+            if normals.len() != vertex_count || tex_coords.len() != vertex_count {
+                return Err(GltfError::Custom(format!(
+                    "Vertex attributes of mesh {} have mismatching lengths.",
+                    mesh.index()
+                )));
+            }
+
+            let tangent_at = |i: usize| {
+                tangents
+                    .as_ref()
+                    .and_then(|tangents| tangents.get(i).cloned())
+                    .unwrap_or_else(|| Vector4::new(0.0, 1.0, 0.0, 1.0))
+            };
+
+            let vertex_buffer = if is_skinned {
+                let joints = reader
+                    .read_joints(0)
+                    .map(|joints| joints.into_u16().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                let weights = reader
+                    .read_weights(0)
+                    .map(|weights| weights.into_f32().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                let vertices = (0..vertex_count)
+                    .map(|i| {
+                        let joints = joints.get(i).cloned().unwrap_or_default();
+                        AnimatedVertex {
+                            position: positions[i],
+                            tex_coord: tex_coords[i],
+                            normal: normals[i],
+                            tangent: tangent_at(i),
+                            bone_weights: weights.get(i).cloned().unwrap_or_default(),
+                            bone_indices: joints.map(|joint| joint as u8),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                VertexBuffer::new(vertex_count, vertices)
+            } else {
+                let vertices = (0..vertex_count)
+                    .map(|i| StaticVertex {
+                        position: positions[i],
+                        tex_coord: tex_coords[i],
+                        normal: normals[i],
+                        tangent: tangent_at(i),
+                    })
+                    .collect::<Vec<_>>();
+                VertexBuffer::new(vertex_count, vertices)
+            }
+            .map_err(|e| GltfError::Custom(format!("Invalid vertex buffer: {:?}", e)))?;
+
+            let mut input_blend_shapes = Vec::new();
+            for (i, (positions, normals, tangents)) in reader.read_morph_targets().enumerate() {
+                fn collect<I: Iterator<Item = [f32; 3]>>(
+                    displacements: Option<I>,
+                ) -> FxHashMap<u32, Vector3<half::f16>> {
+                    displacements
+                        .map(|displacements| {
+                            displacements
+                                .enumerate()
+                                .map(|(index, v)| {
+                                    (index as u32, utils::vec3_f16_from_f32(Vector3::from(v)))
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                }
+
+                input_blend_shapes.push(InputBlendShapeData {
+                    // Weights of blend shapes are in [0; 100] range in the engine.
+                    default_weight: default_weights.get(i).cloned().unwrap_or_default() * 100.0,
+                    name: format!("Target{}", i),
+                    positions: collect(positions),
+                    normals: collect(normals),
+                    tangents: collect(tangents),
+                });
+            }
+
+            if blend_shapes.is_empty() {
+                blend_shapes = input_blend_shapes
+                    .iter()
+                    .map(|shape| BlendShape {
+                        weight: shape.default_weight,
+                        name: shape.name.clone(),
+                    })
+                    .collect();
+            }
+
+            let mut data = SurfaceData::new(vertex_buffer, TriangleBuffer::new(triangles), false);
+            if !input_blend_shapes.is_empty() {
+                data.blend_shapes_container = Some(BlendShapesContainer::from_lists(
+                    &data.vertex_buffer,
+                    &input_blend_shapes,
+                ));
+            }
+            if tangents.is_none() {
+                if let Err(e) = data.calculate_tangents() {
+                    Log::err(format!(
+                        "glTF: Unable to calculate tangents for mesh {}. Reason: {:?}",
+                        mesh.index(),
+                        e
+                    ));
+                }
+            }
+
+            let mut surface = Surface::new(SurfaceSharedData::new(data));
+            surface.set_material(self.material(primitive.material()).await);
+            surfaces.push(surface);
+        }
+
+        Ok(MeshBuilder::new(base)
+            .with_blend_shapes(blend_shapes)
+            .with_surfaces(surfaces)
+            .build(graph))
+    }
+
+    async fn convert_node(
+        &mut self,
+        node: &gltf::Node<'_>,
+        graph: &mut Graph,
+    ) -> Result<Handle<Node>, GltfError> {
+        let base = convert_node_to_base(node);
+
+        if let Some(mesh) = node.mesh() {
+            self.convert_mesh(base, node, mesh, graph).await
+        } else if let Some(camera) = node.camera() {
+            let (pivot, camera_base) = make_oriented_child_base(
+                node,
+                graph,
+                base,
+                "Camera",
+                UnitQuaternion::from_axis_angle(&Vector3::y_axis(), PI),
+            );
+            let projection = match camera.projection() {
+                gltf::camera::Projection::Perspective(perspective) => {
+                    Projection::Perspective(PerspectiveProjection {
+                        fov: perspective.yfov(),
+                        z_near: perspective.znear(),
+                        z_far: perspective
+                            .zfar()
+                            .unwrap_or_else(|| PerspectiveProjection::default().z_far),
+                    })
+                }
+                gltf::camera::Projection::Orthographic(orthographic) => {
+                    Projection::Orthographic(OrthographicProjection {
+                        z_near: orthographic.znear(),
+                        z_far: orthographic.zfar(),
+                        vertical_size: orthographic.ymag(),
+                    })
+                }
+            };
+            let camera = CameraBuilder::new(camera_base)
+                .with_projection(projection)
+                .build(graph);
+            graph.link_nodes(camera, pivot);
+            Ok(pivot)
+        } else if let Some(light) = node.light() {
+            let (pivot, light_base) = make_oriented_child_base(
+                node,
+                graph,
+                base,
+                "Light",
+                UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2),
+            );
+            let base_light = BaseLightBuilder::new(light_base)
+                .with_color(Color::from(Vector3::from(light.color())));
+            let light_handle = match light.kind() {
+                Kind::Directional => DirectionalLightBuilder::new(base_light).build(graph),
+                Kind::Point => {
+                    let mut builder = PointLightBuilder::new(base_light);
+                    if let Some(range) = light.range() {
+                        builder = builder.with_radius(range);
+                    }
+                    builder.build(graph)
+                }
+                Kind::Spot {
+                    inner_cone_angle,
+                    outer_cone_angle,
+                } => {
+                    // glTF angles are measured from the center of the cone, while the engine
+                    // uses full angles.
+                    let mut builder = SpotLightBuilder::new(base_light)
+                        .with_hotspot_cone_angle(2.0 * inner_cone_angle)
+                        .with_falloff_angle_delta(2.0 * (outer_cone_angle - inner_cone_angle));
+                    if let Some(range) = light.range() {
+                        builder = builder.with_distance(range);
+                    }
+                    builder.build(graph)
+                }
+            };
+            graph.link_nodes(light_handle, pivot);
+            Ok(pivot)
+        } else {
+            Ok(PivotBuilder::new(base).build(graph))
+        }
+    }
+}
+
+fn convert_animations(
+    document: &Document,
+    buffers: &[Vec<u8>],
+    node_handles: &[Handle<Node>],
+) -> AnimationContainer {
+    let mut animations = AnimationContainer::new();
+
+    for source in document.animations() {
+        let mut animation = Animation::default();
+        animation.set_name(
+            source
+                .name()
+                .map(|name| name.to_owned())
+                .unwrap_or_else(|| format!("Animation{}", source.index())),
+        );
+
+        for channel in source.channels() {
+            let target = node_handles[channel.target().node().index()];
+            let interpolation = channel.sampler().interpolation();
+            let reader =
+                channel.reader(|buffer| buffers.get(buffer.index()).map(|data| data.as_slice()));
+            let (times, outputs) = match (reader.read_inputs(), reader.read_outputs()) {
+                (Some(inputs), Some(outputs)) => (inputs.collect::<Vec<_>>(), outputs),
+                _ => continue,
+            };
+
+            let (mut track, values, interpolation) = match outputs {
+                ReadOutputs::Translations(translations) => (
+                    Track::new_position(),
+                    translations.map(Vector3::from).collect::<Vec<_>>(),
+                    interpolation,
+                ),
+                ReadOutputs::Scales(scales) => (
+                    Track::new_scale(),
+                    scales.map(Vector3::from).collect::<Vec<_>>(),
+                    interpolation,
+                ),
+                ReadOutputs::Rotations(rotations) => {
+                    let rotations = rotations.into_f32();
+                    // Tangents of quaternions cannot be converted to tangents of Euler angles, so
+                    // cubic spline keys are approximated with linear ones.
+                    let (rotations, interpolation) = if interpolation == Interpolation::CubicSpline
+                    {
+                        (
+                            rotations.skip(1).step_by(3).collect::<Vec<_>>(),
+                            Interpolation::Linear,
+                        )
+                    } else {
+                        (rotations.collect::<Vec<_>>(), interpolation)
+                    };
+                    (
+                        Track::new_rotation(),
+                        quaternions_to_euler(rotations.into_iter()),
+                        interpolation,
+                    )
+                }
+                ReadOutputs::MorphTargetWeights(_) => {
+                    Log::warn(format!(
+                        "glTF: Morph target weights animation of animation {} is not supported.",
+                        source.index()
+                    ));
+                    continue;
+                }
+            };
+
+            track.set_target(target);
+            fill_track(&mut track, &times, &values, interpolation);
+            animation.add_track(track);
+        }
+
+        animation.fit_length_to_content();
+        animations.add(animation);
+    }
+
+    animations
+}
+
+///
+/// Converts glTF document to native engine representation.
+///
+async fn convert(
+    document: &Document,
+    buffers: &[Vec<u8>],
+    resource_manager: ResourceManager,
+    scene: &mut Scene,
+    model_path: &Path,
+    model_import_options: &ModelImportOptions,
+) -> Result<(), GltfError> {
+    let base_path = model_path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut converter = Converter {
+        buffers,
+        base_path,
+        model_path,
+        resource_manager,
+        model_import_options,
+        textures: Default::default(),
+        metallic_roughness_textures: Default::default(),
+        materials: Default::default(),
+    };
+
+    let root = scene.graph.get_root();
+    let mut node_handles = Vec::new();
+    for node in document.nodes() {
+        let handle = converter.convert_node(&node, &mut scene.graph).await?;
+        scene.graph.link_nodes(handle, root);
+        node_handles.push(handle);
+    }
+
+    // Link according to hierarchy.
+    for node in document.nodes() {
+        for child in node.children() {
+            scene
+                .graph
+                .link_nodes(node_handles[child.index()], node_handles[node.index()]);
+        }
+    }
+
+    // Bind skinned meshes to their skeletons.
+    for node in document.nodes() {
+        if let Some(skin) = node.skin() {
+            let bones = skin
+                .joints()
+                .map(|joint| node_handles[joint.index()])
+                .collect::<Vec<_>>();
+
+            let reader =
+                skin.reader(|buffer| buffers.get(buffer.index()).map(|data| data.as_slice()));
+            if let Some(inv_bind_matrices) = reader.read_inverse_bind_matrices() {
+                for (&bone, matrix) in bones.iter().zip(inv_bind_matrices) {
+                    scene.graph[bone].inv_bind_pose_transform = Matrix4::from(matrix);
+                }
+            }
+
+            if let Some(mesh) = scene.graph[node_handles[node.index()]].cast_mut::<Mesh>() {
+                for surface in mesh.surfaces_mut() {
+                    surface.bones.set_value_silent(bones.clone());
+                }
+            }
+        }
+    }
+
+    scene.graph.update_hierarchical_data();
+
+    let animations = convert_animations(document, buffers, &node_handles);
+    // Do not create animation player if there's no animation content.
+    if animations
+        .iter()
+        .any(|animation| !animation.tracks().is_empty())
+    {
+        AnimationPlayerBuilder::new(BaseBuilder::new().with_name("AnimationPlayer"))
+            .with_animations(animations)
+            .build(&mut scene.graph);
+    }
+
+    Ok(())
+}
+
+/// Tries to load and convert glTF (or its binary version - GLB) from given path.
+///
+/// Normally you should never use this method, use resource manager to load models.
+pub async fn load_to_scene<P: AsRef<Path>>(
+    scene: &mut Scene,
+    resource_manager: ResourceManager,
+    path: P,
+    model_import_options: &ModelImportOptions,
+) -> Result<(), GltfError> {
+    let path = path.as_ref();
+    let start_time = Instant::now();
+
+    Log::info(format!("Trying to load {:?}", path));
+
+    let now = Instant::now();
+    let data = io::load_file(path).await?;
+    let Gltf { document, blob } = Gltf::from_slice(&data)?;
+    let buffers = load_buffers(
+        &document,
+        blob,
+        path.parent().unwrap_or_else(|| Path::new("")),
+    )
+    .await?;
+    let parsing_time = now.elapsed().as_millis();
+
+    let now = Instant::now();
+    convert(
+        &document,
+        &buffers,
+        resource_manager,
+        scene,
+        path,
+        model_import_options,
+    )
+    .await?;
+    let conversion_time = now.elapsed().as_millis();
+
+    Log::info(format!(
+        "glTF {:?} loaded in {} ms\n\t- Parsing - {} ms\n\t- Conversion - {} ms",
+        path,
+        start_time.elapsed().as_millis(),
+        parsing_time,
+        conversion_time
+    ));
+
+    // Nodes of models are matched by names on instantiation, so warn about duplicates.
+    let mut names = FxHashSet::<String>::default();
+    for node in scene.graph.linear_iter() {
+        if !names.insert(node.name_owned()) {
+            Log::err(format!(
+                "A node with existing name {} was found during the load of {} resource! \
+                Please fix names in your model, otherwise engine won't be able to correctly \
+                restore data from your resource!",
+                node.name(),
+                path.display()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            math::{quat_from_euler, RotationOrder, TriangleDefinition},
+        },
+        resource::gltf::{decode_data_uri, decode_percents, make_triangles, quaternions_to_euler},
+    };
+    use gltf::mesh::Mode;
+
+    #[test]
+    fn test_decode_uri() {
+        assert_eq!(decode_percents("my%20texture.png"), "my texture.png");
+        assert_eq!(decode_percents("100%"), "100%");
+        assert_eq!(
+            decode_data_uri("application/octet-stream;base64,AQID").unwrap(),
+            vec![1, 2, 3]
+        );
+        assert!(decode_data_uri("no-comma").is_err());
+    }
+
+    #[test]
+    fn test_make_triangles() {
+        assert_eq!(
+            make_triangles(Mode::Triangles, &[0, 1, 2, 2, 1, 3]).unwrap(),
+            vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([2, 1, 3])]
+        );
+        assert_eq!(
+            make_triangles(Mode::TriangleStrip, &[0, 1, 2, 3]).unwrap(),
+            vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([2, 1, 3])]
+        );
+        assert_eq!(
+            make_triangles(Mode::TriangleFan, &[0, 1, 2, 3]).unwrap(),
+            vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([0, 2, 3])]
+        );
+        assert!(make_triangles(Mode::Lines, &[0, 1]).is_none());
+    }
+
+    #[test]
+    fn test_quaternions_to_euler() {
+        let rotations = [
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 170.0f32.to_radians()),
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 190.0f32.to_radians()),
+        ];
+        let angles = quaternions_to_euler(rotations.iter().map(|q| q.coords.into()));
+
+        for (angle, rotation) in angles.iter().zip(rotations.iter()) {
+            let restored = quat_from_euler(*angle, RotationOrder::XYZ);
+            assert!(restored.angle_to(rotation) < 1.0e-4);
+        }
+
+        // Angles must not jump over the full circle between neighbouring keys.
+        assert!((angles[1] - angles[0]).norm() < 1.0);
+    }
+}
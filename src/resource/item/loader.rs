@@ -0,0 +1,26 @@
+//! Item loader.
+
+use crate::{
+    asset::{custom::CustomResourceLoader, manager::ResourceManager},
+    resource::item::{Item, ItemError},
+};
+use std::path::Path;
+
+/// Default implementation for item loading.
+pub struct ItemLoader {
+    /// Resource manager to request icons of items.
+    pub resource_manager: ResourceManager,
+}
+
+impl CustomResourceLoader for ItemLoader {
+    type Data = Item;
+    type Error = ItemError;
+
+    fn extensions(&self) -> &[&str] {
+        &["item"]
+    }
+
+    fn load_from_bytes(&self, path: &Path, bytes: Vec<u8>) -> Result<Item, ItemError> {
+        Item::from_bytes(&bytes, path, &self.resource_manager)
+    }
+}
@@ -0,0 +1,304 @@
+//! Item resource. It describes a kind of items (a sword, a potion, coins), that could be stored in
+//! inventories. See [`Item`] docs for more info and [`crate::utils::inventory`] module for inventories.
+
+use crate::{
+    asset::{manager::ResourceManager, Resource, ResourceData},
+    core::{
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    resource::texture::{Texture, TextureResource},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    borrow::Cow,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+pub mod loader;
+
+/// An error that may occur during item loading.
+#[derive(Debug)]
+pub enum ItemError {
+    /// A parsing error has occurred.
+    ParseError(ron::error::SpannedError),
+
+    /// Maximum stack size of an item is zero.
+    InvalidStackSize,
+}
+
+impl Display for ItemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemError::ParseError(v) => {
+                write!(f, "A parsing error has occurred {v:?}")
+            }
+            ItemError::InvalidStackSize => {
+                write!(f, "Maximum stack size must be greater than zero!")
+            }
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for ItemError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+fn default_max_stack() -> u32 {
+    1
+}
+
+/// Serializable description of an item. It is stored in `.item` files in RON format:
+///
+/// ```text
+/// (
+///     name: "item.health_potion",
+///     description: "item.health_potion.description",
+///     icon: Some("icons/health_potion.png"),
+///     max_stack: 10,
+///     weight: 0.25,
+///     tags: ["consumable", "potion"],
+/// )
+/// ```
+///
+/// Equippable items list the names of equipment slots, that they fit in:
+///
+/// ```text
+/// (
+///     name: "item.iron_sword",
+///     icon: Some("icons/iron_sword.png"),
+///     weight: 3.0,
+///     equipment_slots: ["MainHand", "OffHand"],
+/// )
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ItemDefinition {
+    /// See [`Item::name`].
+    pub name: String,
+
+    /// See [`Item::description`].
+    #[serde(default)]
+    pub description: String,
+
+    /// A path to the icon texture, relative to the item file.
+    #[serde(default)]
+    pub icon: Option<PathBuf>,
+
+    /// See [`Item::max_stack`].
+    #[serde(default = "default_max_stack")]
+    pub max_stack: u32,
+
+    /// See [`Item::weight`].
+    #[serde(default)]
+    pub weight: f32,
+
+    /// See [`Item::tags`].
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// See [`Item::equipment_slots`].
+    #[serde(default)]
+    pub equipment_slots: Vec<String>,
+}
+
+impl Default for ItemDefinition {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            description: Default::default(),
+            icon: None,
+            max_stack: default_max_stack(),
+            weight: 0.0,
+            tags: Default::default(),
+            equipment_slots: Default::default(),
+        }
+    }
+}
+
+/// Item is a description of a kind of items, that could be stored in inventories. Actual items are
+/// stored in inventories as stacks - an item resource and an amount of items (see
+/// [`crate::utils::inventory::ItemStack`]), so items of the same kind share their description.
+///
+/// Items are usually loaded from `.item` files (see [`ItemDefinition`] for the format), but they could
+/// also be created from code using [`Item::new`].
+#[derive(Debug, Default, Visit, Reflect)]
+pub struct Item {
+    #[reflect(hidden)]
+    pub(crate) path: PathBuf,
+    name: String,
+    description: String,
+    icon: Option<TextureResource>,
+    max_stack: u32,
+    weight: f32,
+    tags: Vec<String>,
+    equipment_slots: Vec<String>,
+}
+
+impl ResourceData for Item {
+    fn path(&self) -> Cow<Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+}
+
+impl TypeUuidProvider for Item {
+    fn type_uuid() -> Uuid {
+        uuid!("8e1c5a3f-6b2d-4f7e-a0c9-3d5b7e9f1a24")
+    }
+}
+
+impl Item {
+    /// Creates new item from the given definition, the icon of the definition is ignored. Use
+    /// [`Item::set_icon`] to set an icon.
+    pub fn new(definition: ItemDefinition) -> Result<Self, ItemError> {
+        if definition.max_stack == 0 {
+            return Err(ItemError::InvalidStackSize);
+        }
+
+        Ok(Self {
+            path: Default::default(),
+            name: definition.name,
+            description: definition.description,
+            icon: None,
+            max_stack: definition.max_stack,
+            weight: definition.weight,
+            tags: definition.tags,
+            equipment_slots: definition.equipment_slots,
+        })
+    }
+
+    /// Creates new item from its serialized description (see [`ItemDefinition`]). Icon path is resolved
+    /// relative to the given path of the item.
+    pub fn from_bytes(
+        bytes: &[u8],
+        path: &Path,
+        resource_manager: &ResourceManager,
+    ) -> Result<Self, ItemError> {
+        let definition = ron::de::from_bytes::<ItemDefinition>(bytes)?;
+        let icon = definition.icon.as_ref().map(|icon| {
+            let base_path = path.parent().unwrap_or_else(|| Path::new(""));
+            resource_manager.request::<Texture, _>(base_path.join(icon))
+        });
+        let mut item = Self::new(definition)?;
+        item.icon = icon;
+        item.path = path.to_path_buf();
+        Ok(item)
+    }
+
+    /// Returns name (or localization key of the name) of the item.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns description (or localization key of the description) of the item.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the icon of the item.
+    pub fn icon(&self) -> Option<TextureResource> {
+        self.icon.clone()
+    }
+
+    /// Sets new icon of the item.
+    pub fn set_icon(&mut self, icon: Option<TextureResource>) {
+        self.icon = icon;
+    }
+
+    /// Returns maximum amount of items in a single stack. It is always greater than zero, `1` means
+    /// that the items are not stackable.
+    pub fn max_stack(&self) -> u32 {
+        self.max_stack
+    }
+
+    /// Returns weight of a single item.
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    /// Returns a list of arbitrary tags of the item. Tags could be used to restrict the kinds of items,
+    /// that could be stored in an inventory.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Returns `true` if the item has the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Returns names of the equipment slots, that the item could be equipped to.
+    pub fn equipment_slots(&self) -> &[String] {
+        &self.equipment_slots
+    }
+
+    /// Returns `true` if the item could be equipped to the equipment slot with the given name.
+    pub fn fits_equipment_slot(&self, slot: &str) -> bool {
+        self.equipment_slots.iter().any(|s| s == slot)
+    }
+}
+
+/// Type alias for item resources.
+pub type ItemResource = Resource<Item>;
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        resource::item::{Item, ItemError},
+    };
+    use std::path::Path;
+
+    #[test]
+    fn test_item_loading() {
+        let item = Item::from_bytes(
+            br#"(
+                name: "item.iron_sword",
+                weight: 3.0,
+                tags: ["weapon"],
+                equipment_slots: ["MainHand"],
+            )"#,
+            Path::new("iron_sword.item"),
+            &ResourceManager::new(),
+        )
+        .unwrap();
+
+        assert_eq!(item.name(), "item.iron_sword");
+        assert_eq!(item.max_stack(), 1);
+        assert_eq!(item.weight(), 3.0);
+        assert!(item.has_tag("weapon"));
+        assert!(item.fits_equipment_slot("MainHand"));
+        assert!(!item.fits_equipment_slot("Head"));
+        assert!(item.icon().is_none());
+
+        assert!(matches!(
+            Item::from_bytes(
+                br#"(name: "item.nothing", max_stack: 0)"#,
+                Path::new("invalid.item"),
+                &ResourceManager::new(),
+            ),
+            Err(ItemError::InvalidStackSize)
+        ));
+    }
+}
@@ -3,6 +3,14 @@
 #![warn(missing_docs)]
 
 pub mod curve;
+pub mod dialogue;
 pub mod fbx;
+pub mod gltf;
+pub mod item;
 pub mod model;
+pub mod obj;
+pub mod quest;
+pub mod spritesheet;
+pub mod stl;
 pub mod texture;
+pub mod tiled;
@@ -27,7 +27,9 @@ pub struct ModelLoader {
 
 impl ResourceLoader for ModelLoader {
     fn extensions(&self) -> &[&str] {
-        &["rgs", "fbx"]
+        &[
+            "rgs", "fbx", "gltf", "glb", "obj", "stl", "tmx", "tmj", "variant",
+        ]
     }
 
     fn into_any(self: Box<Self>) -> Box<dyn Any> {
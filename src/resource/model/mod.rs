@@ -15,14 +15,17 @@
 //!
 //! # Supported formats
 //!
-//! Currently only FBX (common format in game industry for storing complex 3d models)
-//! and RGS (native Fyroxed format) formats are supported.
+//! Currently FBX (common format in game industry for storing complex 3d models), glTF 2.0
+//! (both `.gltf` and `.glb` flavors), OBJ (with MTL materials), STL (both ASCII and binary) and
+//! RGS (native Fyroxed format) formats are supported. OBJ and STL can store only static meshes,
+//! they're useful for quick prototyping and content from CAD software. 2D levels made in Tiled
+//! (both `.tmx` and `.tmj` flavors) are loaded as tile maps.
 
 use crate::{
     animation::Animation,
     asset::{
-        manager::ResourceManager, options::ImportOptions, Resource, ResourceData,
-        MODEL_RESOURCE_UUID,
+        manager::ResourceManager, memory::ResourceMemoryUsage, options::ImportOptions, Resource,
+        ResourceData, MODEL_RESOURCE_UUID,
     },
     core::{
         algebra::{UnitQuaternion, Vector3},
@@ -35,25 +38,40 @@ use crate::{
         TypeUuidProvider,
     },
     engine::SerializationContext,
-    resource::fbx::{self, error::FbxError},
+    resource::{
+        fbx::{self, error::FbxError},
+        gltf::{self, error::GltfError},
+        model::variant::{PrefabVariantDefinition, PrefabVariantError},
+        obj::{self, error::ObjError},
+        stl::{self, error::StlError},
+        tiled::{self, error::TiledError},
+    },
     scene::{
         animation::AnimationPlayer,
+        base::BaseBuilder,
         graph::{map::NodeHandleMap, Graph},
+        mesh::Mesh,
         node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
         Scene, SceneLoader,
     },
 };
+use fxhash::FxHashSet;
 use serde::{Deserialize, Serialize};
 use std::{
     any::Any,
     borrow::Cow,
+    f32::consts::FRAC_PI_2,
     fmt::{Display, Formatter},
+    ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
 };
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 pub mod loader;
+pub mod variant;
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Reflect)]
 #[repr(u32)]
@@ -323,6 +341,23 @@ impl ResourceData for Model {
     fn type_uuid(&self) -> Uuid {
         <Self as TypeUuidProvider>::type_uuid()
     }
+
+    fn memory_usage(&self) -> ResourceMemoryUsage {
+        // Textures of the model are separate resources, so only geometry is counted here.
+        let mut usage = ResourceMemoryUsage::default();
+        for node in self.scene.graph.linear_iter() {
+            if let Some(mesh) = node.cast::<Mesh>() {
+                for surface in mesh.surfaces() {
+                    let data = surface.data();
+                    let data = data.lock();
+                    let size = data.vertex_buffer.raw_data().len()
+                        + std::mem::size_of_val(data.geometry_buffer.triangles_ref());
+                    usage += ResourceMemoryUsage::new(size, size);
+                }
+            }
+        }
+        usage
+    }
 }
 
 impl Default for Model {
@@ -408,6 +443,100 @@ impl MaterialSearchOptions {
     }
 }
 
+/// Up axis of a source model. The engine uses Y axis as up axis, models with other up axes will be
+/// rotated on import.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum ModelUpAxis {
+    /// X axis is up.
+    X,
+    /// Y axis is up, no conversion is needed.
+    Y,
+    /// Z axis is up (for example, models exported from Blender or 3ds Max without axis conversion).
+    Z,
+}
+
+impl Default for ModelUpAxis {
+    fn default() -> Self {
+        Self::Y
+    }
+}
+
+/// A set of optional optimization passes, that will be applied to every mesh of a model on import.
+/// All passes are disabled by default, because they increase loading time.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub struct MeshOptimizationOptions {
+    /// Merges vertices with exactly the same content into one. See
+    /// [`crate::scene::mesh::surface::SurfaceData::deduplicate_vertices`] for more info.
+    #[serde(default)]
+    pub deduplicate_vertices: bool,
+    /// Reorders triangles and vertices for better efficiency of vertex caches of a GPU. See
+    /// [`crate::scene::mesh::surface::SurfaceData::optimize_vertex_cache`] for more info.
+    #[serde(default)]
+    pub optimize_vertex_cache: bool,
+    /// Calculates tangents even if the source model already has them. It is useful when the
+    /// tangents in the source model are broken or calculated in incompatible way, which results in
+    /// incorrect lighting. Tangents are always calculated if the source model has none.
+    #[serde(default)]
+    pub recalculate_tangents: bool,
+}
+
+impl MeshOptimizationOptions {
+    fn apply(&self, scene: &mut Scene) {
+        if !self.deduplicate_vertices && !self.optimize_vertex_cache && !self.recalculate_tangents {
+            return;
+        }
+
+        // Surface data could be shared between multiple meshes, every instance must be processed once.
+        let mut processed = FxHashSet::default();
+
+        for node in scene.graph.linear_iter() {
+            if let Some(mesh) = node.cast::<Mesh>() {
+                for surface in mesh.surfaces() {
+                    if !processed.insert(surface.data_ref().key()) {
+                        continue;
+                    }
+
+                    let mut data = surface.data_ref().lock();
+
+                    if self.deduplicate_vertices {
+                        data.deduplicate_vertices();
+                    }
+
+                    if self.recalculate_tangents {
+                        if let Err(err) = data.calculate_tangents() {
+                            Log::err(format!(
+                                "Unable to calculate tangents for mesh {}. Reason: {:?}",
+                                node.name(),
+                                err
+                            ));
+                        }
+                    }
+
+                    if self.optimize_vertex_cache {
+                        data.optimize_vertex_cache();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
 /// A set of options that will be applied to a model resource when loading it from external source.
 ///
 /// # Details
@@ -418,16 +547,116 @@ impl MaterialSearchOptions {
 ///
 /// ```text
 /// (
-///     material_search_options: RecursiveUp
+///     material_search_options: RecursiveUp,
+///     scale: 0.01,
+///     up_axis: Z,
+///     animation_time_slice: Some((start: 0.5, end: 2.0)),
+///     mesh_optimization: (
+///         deduplicate_vertices: true,
+///         optimize_vertex_cache: true,
+///     ),
 /// )
 /// ```
 ///
-/// Check documentation of the field of the structure for more info about each parameter.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect, Eq)]
+/// Every field is optional, missing fields will have their default values. Check documentation of the
+/// field of the structure for more info about each parameter. Options are applied only to imported
+/// formats (FBX, glTF, OBJ, STL and Tiled maps), native scenes are loaded as is.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
 pub struct ModelImportOptions {
     /// See [`MaterialSearchOptions`] docs for more info.
     #[serde(default)]
     pub material_search_options: MaterialSearchOptions,
+    /// Uniform scale that will be applied to the model. It is useful to convert units of a model, for
+    /// example `0.01` converts centimeters to meters.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Up axis of the source model, see [`ModelUpAxis`] docs for more info.
+    #[serde(default)]
+    pub up_axis: ModelUpAxis,
+    /// A time interval (in seconds) to which every animation of the model will be trimmed. It is
+    /// useful to cut unneeded frames from the beginning or the end of animations. The interval is
+    /// clamped to the actual length of each animation.
+    #[serde(default)]
+    pub animation_time_slice: Option<Range<f32>>,
+    /// See [`MeshOptimizationOptions`] docs for more info.
+    #[serde(default)]
+    pub mesh_optimization: MeshOptimizationOptions,
+}
+
+impl Default for ModelImportOptions {
+    fn default() -> Self {
+        Self {
+            material_search_options: Default::default(),
+            scale: default_scale(),
+            up_axis: Default::default(),
+            animation_time_slice: None,
+            mesh_optimization: Default::default(),
+        }
+    }
+}
+
+impl ModelImportOptions {
+    // Import transform is applied using a separate pivot node, this way it does not interfere with
+    // animations of the model.
+    fn apply_import_transform(&self, scene: &mut Scene) {
+        if self.scale == 1.0 && self.up_axis == ModelUpAxis::Y {
+            return;
+        }
+
+        let rotation = match self.up_axis {
+            ModelUpAxis::X => UnitQuaternion::from_axis_angle(&Vector3::z_axis(), FRAC_PI_2),
+            ModelUpAxis::Y => UnitQuaternion::identity(),
+            ModelUpAxis::Z => UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -FRAC_PI_2),
+        };
+
+        let root = scene.graph.get_root();
+        let children = scene.graph[root].children().to_vec();
+
+        let pivot = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("ImportTransform")
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_rotation(rotation)
+                        .with_local_scale(Vector3::repeat(self.scale))
+                        .build(),
+                ),
+        )
+        .build(&mut scene.graph);
+
+        for child in children {
+            scene.graph.link_nodes(child, pivot);
+        }
+    }
+
+    fn apply_animation_time_slice(&self, scene: &mut Scene) {
+        let time_slice = match self.animation_time_slice.as_ref() {
+            Some(time_slice) => time_slice,
+            None => return,
+        };
+
+        for node in scene.graph.linear_iter_mut() {
+            if let Some(animation_player) = node.query_component_mut::<AnimationPlayer>() {
+                for animation in animation_player
+                    .animations_mut()
+                    .get_value_mut_silent()
+                    .iter_mut()
+                {
+                    let current = animation.time_slice();
+                    let start = time_slice.start.clamp(current.start, current.end);
+                    let end = time_slice.end.clamp(start, current.end);
+                    animation.set_time_slice(start..end);
+                    animation.rewind();
+                }
+            }
+        }
+    }
+
+    fn apply(&self, scene: &mut Scene) {
+        self.apply_import_transform(scene);
+        self.apply_animation_time_slice(scene);
+        self.mesh_optimization.apply(scene);
+    }
 }
 
 impl ImportOptions for ModelImportOptions {}
@@ -442,6 +671,16 @@ pub enum ModelLoadError {
     NotSupported(String),
     /// An error occurred while loading FBX file.
     Fbx(FbxError),
+    /// An error occurred while loading glTF file.
+    Gltf(GltfError),
+    /// An error occurred while loading OBJ file.
+    Obj(ObjError),
+    /// An error occurred while loading STL file.
+    Stl(StlError),
+    /// An error occurred while loading Tiled map.
+    Tiled(TiledError),
+    /// An error occurred while loading prefab variant.
+    Variant(PrefabVariantError),
 }
 
 impl Display for ModelLoadError {
@@ -454,6 +693,11 @@ impl Display for ModelLoadError {
                 write!(f, "Model format is not supported: {v}")
             }
             ModelLoadError::Fbx(v) => v.fmt(f),
+            ModelLoadError::Gltf(v) => v.fmt(f),
+            ModelLoadError::Obj(v) => v.fmt(f),
+            ModelLoadError::Stl(v) => v.fmt(f),
+            ModelLoadError::Tiled(v) => v.fmt(f),
+            ModelLoadError::Variant(v) => v.fmt(f),
         }
     }
 }
@@ -464,6 +708,36 @@ impl From<FbxError> for ModelLoadError {
     }
 }
 
+impl From<GltfError> for ModelLoadError {
+    fn from(gltf: GltfError) -> Self {
+        ModelLoadError::Gltf(gltf)
+    }
+}
+
+impl From<ObjError> for ModelLoadError {
+    fn from(obj: ObjError) -> Self {
+        ModelLoadError::Obj(obj)
+    }
+}
+
+impl From<StlError> for ModelLoadError {
+    fn from(stl: StlError) -> Self {
+        ModelLoadError::Stl(stl)
+    }
+}
+
+impl From<TiledError> for ModelLoadError {
+    fn from(tiled: TiledError) -> Self {
+        ModelLoadError::Tiled(tiled)
+    }
+}
+
+impl From<PrefabVariantError> for ModelLoadError {
+    fn from(variant: PrefabVariantError) -> Self {
+        ModelLoadError::Variant(variant)
+    }
+}
+
 impl From<VisitError> for ModelLoadError {
     fn from(e: VisitError) -> Self {
         ModelLoadError::Visit(e)
@@ -471,6 +745,15 @@ impl From<VisitError> for ModelLoadError {
 }
 
 impl Model {
+    /// Creates a model from the given scene, that will be used as a prefab.
+    pub(crate) fn new(path: PathBuf, mapping: NodeMapping, scene: Scene) -> Self {
+        Self {
+            path,
+            mapping,
+            scene,
+        }
+    }
+
     pub(crate) async fn load<P: AsRef<Path>>(
         path: P,
         serialization_context: Arc<SerializationContext>,
@@ -498,10 +781,78 @@ impl Model {
                     &model_import_options,
                 )
                 .await?;
+                model_import_options.apply(&mut scene);
                 // Set NodeMapping::UseNames as mapping here because FBX does not have
                 // any persistent unique ids, and we have to use names.
                 (scene, NodeMapping::UseNames)
             }
+            "gltf" | "glb" => {
+                let mut scene = Scene::new();
+                if let Some(filename) = path.as_ref().file_name() {
+                    let root = scene.graph.get_root();
+                    scene.graph[root].set_name(&filename.to_string_lossy());
+                }
+                gltf::load_to_scene(
+                    &mut scene,
+                    resource_manager,
+                    path.as_ref(),
+                    &model_import_options,
+                )
+                .await?;
+                model_import_options.apply(&mut scene);
+                // glTF nodes do not have persistent unique ids either, indices of nodes
+                // could change on every export.
+                (scene, NodeMapping::UseNames)
+            }
+            "obj" => {
+                let mut scene = Scene::new();
+                if let Some(filename) = path.as_ref().file_name() {
+                    let root = scene.graph.get_root();
+                    scene.graph[root].set_name(&filename.to_string_lossy());
+                }
+                obj::load_to_scene(
+                    &mut scene,
+                    resource_manager,
+                    path.as_ref(),
+                    &model_import_options,
+                )
+                .await?;
+                model_import_options.apply(&mut scene);
+                // Meshes are named after objects (or groups) of the file.
+                (scene, NodeMapping::UseNames)
+            }
+            "stl" => {
+                let mut scene = Scene::new();
+                if let Some(filename) = path.as_ref().file_name() {
+                    let root = scene.graph.get_root();
+                    scene.graph[root].set_name(&filename.to_string_lossy());
+                }
+                stl::load_to_scene(
+                    &mut scene,
+                    resource_manager,
+                    path.as_ref(),
+                    &model_import_options,
+                )
+                .await?;
+                model_import_options.apply(&mut scene);
+                (scene, NodeMapping::UseNames)
+            }
+            "tmx" | "tmj" => {
+                let mut scene = Scene::new();
+                if let Some(filename) = path.as_ref().file_name() {
+                    let root = scene.graph.get_root();
+                    scene.graph[root].set_name(&filename.to_string_lossy());
+                }
+                tiled::load_to_scene(
+                    &mut scene,
+                    resource_manager,
+                    path.as_ref(),
+                    &model_import_options,
+                )
+                .await?;
+                model_import_options.apply(&mut scene);
+                (scene, NodeMapping::UseNames)
+            }
             // Scene can be used directly as model resource. Such scenes can be created in
             // Fyroxed.
             "rgs" => (
@@ -515,6 +866,24 @@ impl Model {
                 .await,
                 NodeMapping::UseHandles,
             ),
+            // Variant is a copy of its base prefab with a set of overridden properties.
+            variant::PREFAB_VARIANT_EXTENSION => {
+                let definition = PrefabVariantDefinition::from_file(path.as_ref()).await?;
+                let base = resource_manager
+                    .request::<Model, _>(&definition.base)
+                    .await
+                    .map_err(|error| PrefabVariantError::Base {
+                        path: definition.base.clone(),
+                        error,
+                    })?;
+                let base = base.data_ref();
+                // Handles must be preserved, because instances of the variant could store them.
+                let mut scene = Scene::default();
+                scene.graph = base.scene.graph.clone_preserving_handles();
+                let root = scene.graph.get_root();
+                definition.apply(&mut scene.graph, root, &resource_manager);
+                (scene, base.mapping)
+            }
             // TODO: Add more formats.
             _ => {
                 return Err(ModelLoadError::NotSupported(format!(
@@ -548,3 +917,43 @@ impl Model {
         &mut self.scene
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        resource::model::{ModelImportOptions, ModelUpAxis},
+        scene::{base::BaseBuilder, pivot::PivotBuilder, Scene},
+    };
+
+    #[test]
+    fn test_model_import_options_defaults() {
+        let options = ron::de::from_str::<ModelImportOptions>("(up_axis: Z)").unwrap();
+        assert_eq!(options.scale, 1.0);
+        assert_eq!(options.up_axis, ModelUpAxis::Z);
+        assert_eq!(options.animation_time_slice, None);
+    }
+
+    #[test]
+    fn test_import_transform() {
+        let mut scene = Scene::new();
+        let root = scene.graph.get_root();
+        let node = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+
+        let options = ModelImportOptions {
+            scale: 0.5,
+            up_axis: ModelUpAxis::Z,
+            ..Default::default()
+        };
+        options.apply(&mut scene);
+
+        let pivot = scene.graph[node].parent();
+        assert_ne!(pivot, root);
+        assert_eq!(scene.graph[pivot].parent(), root);
+
+        let transform = scene.graph[pivot].local_transform();
+        assert_eq!(**transform.scale(), Vector3::repeat(0.5));
+        let up = transform.rotation().transform_vector(&Vector3::z());
+        assert!((up - Vector3::y()).norm() < 1.0e-5);
+    }
+}
@@ -0,0 +1,463 @@
+//! Prefab variants.
+//!
+//! A prefab variant is a model resource that is defined as a base prefab plus a set of property
+//! overrides. Variant files store only the overrides, the rest of the data is taken from the base
+//! prefab every time the variant is loaded. This allows dozens of flavors of the same object (for
+//! example, enemies with different colors, health or speed) to share one source prefab and to get
+//! all the fixes made in it automatically.
+//!
+//! Variants are stored in RON format in files with `.variant` extension:
+//!
+//! ```text
+//! (
+//!     base: "data/models/enemy.rgs",
+//!     overrides: [
+//!         (node: "Body", property: "base.local_transform.local_scale", value: Vector3((1.5, 1.5, 1.5))),
+//!         (node: "Body/Sprite", property: "texture", value: Texture(Some("data/textures/red.png"))),
+//!     ],
+//! )
+//! ```
+//!
+//! Nodes are identified by paths of their names relative to the root of the base prefab, the
+//! root itself has empty path. Properties are identified by their reflection paths. A variant can
+//! be made from a modified prefab instance using [`PrefabVariantDefinition::from_instance`].
+//!
+//! Variants are loaded as ordinary [`super::ModelResource`], so they can be instantiated (and
+//! used as a base of other variants) like any other prefab.
+
+use crate::{
+    asset::{manager::ResourceManager, ResourceLoadError},
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3, Vector4},
+        color::Color,
+        io::{self, FileLoadError},
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+    },
+    resource::texture::{Texture, TextureResource},
+    scene::{graph::Graph, node::Node},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Extension of prefab variant files.
+pub const PREFAB_VARIANT_EXTENSION: &str = "variant";
+
+/// All possible errors that may occur while loading a prefab variant.
+#[derive(Debug)]
+pub enum PrefabVariantError {
+    /// Unable to read the file.
+    Io(FileLoadError),
+    /// Unable to parse the definition.
+    Parse(ron::error::SpannedError),
+    /// Unable to load the base prefab.
+    Base {
+        /// Path of the base prefab.
+        path: PathBuf,
+        /// Load error of the base prefab, if any.
+        error: Option<Arc<dyn ResourceLoadError>>,
+    },
+}
+
+impl Display for PrefabVariantError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(v) => write!(f, "Unable to read prefab variant: {:?}", v),
+            Self::Parse(v) => write!(f, "Unable to parse prefab variant: {}", v),
+            Self::Base { path, error } => write!(
+                f,
+                "Unable to load base prefab {}: {:?}",
+                path.display(),
+                error
+            ),
+        }
+    }
+}
+
+impl From<FileLoadError> for PrefabVariantError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for PrefabVariantError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// A value of an overridden property. Only a limited set of types is supported, properties of other
+/// types cannot be overridden by variants.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OverrideValue {
+    /// `bool` value.
+    Bool(bool),
+    /// `i32` value.
+    I32(i32),
+    /// `u32` value.
+    U32(u32),
+    /// `i64` value.
+    I64(i64),
+    /// `u64` value.
+    U64(u64),
+    /// `f32` value.
+    F32(f32),
+    /// `f64` value.
+    F64(f64),
+    /// `String` value.
+    String(String),
+    /// `Vector2<f32>` value.
+    Vector2(Vector2<f32>),
+    /// `Vector3<f32>` value.
+    Vector3(Vector3<f32>),
+    /// `Vector4<f32>` value.
+    Vector4(Vector4<f32>),
+    /// `UnitQuaternion<f32>` value.
+    Rotation(UnitQuaternion<f32>),
+    /// `Color` value, stored as RGBA.
+    Color([u8; 4]),
+    /// `Option<TextureResource>` value, stored as a path to the texture.
+    Texture(Option<PathBuf>),
+}
+
+fn try_get<T: Reflect + Clone>(value: &dyn Reflect) -> Option<T> {
+    let mut result = None;
+    value.downcast_ref::<T>(&mut |v| result = v.cloned());
+    result
+}
+
+impl OverrideValue {
+    /// Tries to create the value from a property value. Returns `None` if the type of the property is
+    /// not supported, or if the property is a procedural texture (which has no path).
+    pub fn from_reflect(value: &dyn Reflect) -> Option<Self> {
+        try_get::<bool>(value)
+            .map(Self::Bool)
+            .or_else(|| try_get::<i32>(value).map(Self::I32))
+            .or_else(|| try_get::<u32>(value).map(Self::U32))
+            .or_else(|| try_get::<i64>(value).map(Self::I64))
+            .or_else(|| try_get::<u64>(value).map(Self::U64))
+            .or_else(|| try_get::<f32>(value).map(Self::F32))
+            .or_else(|| try_get::<f64>(value).map(Self::F64))
+            .or_else(|| try_get::<String>(value).map(Self::String))
+            .or_else(|| try_get::<Vector2<f32>>(value).map(Self::Vector2))
+            .or_else(|| try_get::<Vector3<f32>>(value).map(Self::Vector3))
+            .or_else(|| try_get::<Vector4<f32>>(value).map(Self::Vector4))
+            .or_else(|| try_get::<UnitQuaternion<f32>>(value).map(Self::Rotation))
+            .or_else(|| try_get::<Color>(value).map(|c| Self::Color([c.r, c.g, c.b, c.a])))
+            .or_else(|| match try_get::<Option<TextureResource>>(value)? {
+                Some(texture) => {
+                    let path = texture.path();
+                    if path.as_os_str().is_empty() {
+                        None
+                    } else {
+                        Some(Self::Texture(Some(path)))
+                    }
+                }
+                None => Some(Self::Texture(None)),
+            })
+    }
+
+    /// Converts the value to a property value. Textures are requested from the given resource manager.
+    pub fn into_reflect(self, resource_manager: &ResourceManager) -> Box<dyn Reflect> {
+        match self {
+            Self::Bool(v) => Box::new(v),
+            Self::I32(v) => Box::new(v),
+            Self::U32(v) => Box::new(v),
+            Self::I64(v) => Box::new(v),
+            Self::U64(v) => Box::new(v),
+            Self::F32(v) => Box::new(v),
+            Self::F64(v) => Box::new(v),
+            Self::String(v) => Box::new(v),
+            Self::Vector2(v) => Box::new(v),
+            Self::Vector3(v) => Box::new(v),
+            Self::Vector4(v) => Box::new(v),
+            Self::Rotation(v) => Box::new(v),
+            Self::Color([r, g, b, a]) => Box::new(Color::from_rgba(r, g, b, a)),
+            Self::Texture(path) => {
+                Box::new(path.map(|path| resource_manager.request::<Texture, _>(path)))
+            }
+        }
+    }
+}
+
+/// A single property override.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PropertyOverride {
+    /// A path of names of the node, relative to the root of the base prefab (for example
+    /// `Body/Weapon`). The root itself has empty path.
+    pub node: String,
+    /// A reflection path of the property (for example `base.local_transform.local_scale`).
+    pub property: String,
+    /// New value of the property.
+    pub value: OverrideValue,
+}
+
+/// Serializable definition of a prefab variant. See module docs for more info.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrefabVariantDefinition {
+    /// A path to the base prefab.
+    pub base: PathBuf,
+    /// A list of property overrides.
+    #[serde(default)]
+    pub overrides: Vec<PropertyOverride>,
+}
+
+/// Returns a path of names from `root` (exclusive) to `node`, or `None` if the node is not a
+/// descendant of `root`.
+fn node_path(graph: &Graph, root: Handle<Node>, node: Handle<Node>) -> Option<String> {
+    let mut names = Vec::new();
+    let mut current = node;
+    while current != root {
+        let node = graph.try_get(current)?;
+        names.push(node.name());
+        current = node.parent();
+    }
+    names.reverse();
+    Some(names.join("/"))
+}
+
+fn find_node(graph: &Graph, root: Handle<Node>, path: &str) -> Option<Handle<Node>> {
+    let mut current = root;
+    if !path.is_empty() {
+        for name in path.split('/') {
+            current = graph
+                .try_get(current)?
+                .children()
+                .iter()
+                .cloned()
+                .find(|child| graph[*child].name() == name)?;
+        }
+    }
+    Some(current)
+}
+
+impl PrefabVariantDefinition {
+    /// Loads the definition from a file.
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, PrefabVariantError> {
+        let bytes = io::load_file(path).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    /// Serializes the definition to a string.
+    pub fn to_ron_string(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    /// Saves the definition to a file with the given path. The path should have
+    /// [`PREFAB_VARIANT_EXTENSION`] extension, otherwise the resource manager won't be able to load it.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        let string = self
+            .to_ron_string()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        std::fs::write(path, string)
+    }
+
+    /// Creates a variant definition from a prefab instance. Every modified property of the instance
+    /// becomes an override, except the position and the rotation of the instance root, which define
+    /// placement of the instance. Nodes that were added to the instance and properties of types not
+    /// supported by [`OverrideValue`] are skipped with a warning. Returns `None` if the node is not
+    /// a root of a prefab instance.
+    pub fn from_instance(graph: &Graph, instance_root: Handle<Node>) -> Option<Self> {
+        let root = graph.try_get(instance_root)?;
+        if !root.is_resource_instance_root() {
+            return None;
+        }
+        let resource = root.resource()?;
+        let base_root = root.original_handle_in_resource;
+
+        // The path must be fetched before the data is locked, both use the same lock.
+        let base = resource.path();
+        let data = resource.data_ref();
+        let base_graph = &data.get_scene().graph;
+
+        let mut overrides = Vec::new();
+        for handle in graph.traverse_handle_iter(instance_root) {
+            let node = &graph[handle];
+            let path = if node.resource().as_ref() == Some(&resource) {
+                node_path(base_graph, base_root, node.original_handle_in_resource)
+            } else {
+                None
+            };
+            let path = match path {
+                Some(path) => path,
+                None => {
+                    Log::warn(format!(
+                        "Node {} is not a part of the base prefab, it will be skipped.",
+                        node.name()
+                    ));
+                    continue;
+                }
+            };
+
+            node.as_reflect(&mut |node_reflect| {
+                node_reflect.enumerate_fields_recursively(&mut |property, _, value| {
+                    if handle == instance_root
+                        && (property.ends_with("local_transform.local_position")
+                            || property.ends_with("local_transform.local_rotation"))
+                    {
+                        return;
+                    }
+
+                    value.as_inheritable_variable(&mut |variable| {
+                        if let Some(variable) = variable {
+                            if variable.is_modified() {
+                                match OverrideValue::from_reflect(variable.inner_value_ref()) {
+                                    Some(value) => overrides.push(PropertyOverride {
+                                        node: path.clone(),
+                                        property: property.to_string(),
+                                        value,
+                                    }),
+                                    None => Log::warn(format!(
+                                        "Property {} of node {} has unsupported type, \
+                                        it will be skipped.",
+                                        property,
+                                        node.name()
+                                    )),
+                                }
+                            }
+                        }
+                    })
+                })
+            });
+        }
+
+        Some(Self { base, overrides })
+    }
+
+    /// Applies the overrides to the node hierarchy starting from the given root. Overrides that
+    /// cannot be applied (for example, if a node was renamed or removed in the base prefab) are
+    /// skipped with a warning.
+    pub fn apply(&self, graph: &mut Graph, root: Handle<Node>, resource_manager: &ResourceManager) {
+        for property_override in self.overrides.iter() {
+            let node = match find_node(graph, root, &property_override.node) {
+                Some(node) => node,
+                None => {
+                    Log::warn(format!(
+                        "Unable to apply override of {} - there is no node {} in {}.",
+                        property_override.property,
+                        property_override.node,
+                        self.base.display()
+                    ));
+                    continue;
+                }
+            };
+
+            let mut value = Some(
+                property_override
+                    .value
+                    .clone()
+                    .into_reflect(resource_manager),
+            );
+            graph[node].as_reflect_mut(&mut |node_reflect| {
+                node_reflect.set_field_by_path(
+                    &property_override.property,
+                    value.take().unwrap(),
+                    &mut |result| {
+                        if result.is_err() {
+                            Log::warn(format!(
+                                "Unable to apply override of {} of node {} in {}.",
+                                property_override.property,
+                                property_override.node,
+                                self.base.display()
+                            ));
+                        }
+                    },
+                )
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::Resource,
+        core::{algebra::Vector3, reflect::prelude::*},
+        resource::model::{
+            variant::{find_node, node_path, OverrideValue, PrefabVariantDefinition},
+            Model, ModelResourceExtension, NodeMapping,
+        },
+        scene::{base::BaseBuilder, graph::Graph, pivot::PivotBuilder, Scene},
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_override_value_conversion() {
+        let value = 1.5f32;
+        assert_eq!(
+            OverrideValue::from_reflect(&value as &dyn Reflect),
+            Some(OverrideValue::F32(1.5))
+        );
+        let value = vec![1u8];
+        assert_eq!(OverrideValue::from_reflect(&value as &dyn Reflect), None);
+    }
+
+    #[test]
+    fn test_node_paths() {
+        let mut graph = Graph::new();
+        let weapon = PivotBuilder::new(BaseBuilder::new().with_name("Weapon")).build(&mut graph);
+        let body = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Body")
+                .with_children(&[weapon]),
+        )
+        .build(&mut graph);
+        let root = graph.get_root();
+
+        assert_eq!(node_path(&graph, root, weapon).unwrap(), "Body/Weapon");
+        assert_eq!(node_path(&graph, root, root).unwrap(), "");
+        assert_eq!(node_path(&graph, weapon, body), None);
+        assert_eq!(find_node(&graph, root, "Body/Weapon"), Some(weapon));
+        assert_eq!(find_node(&graph, root, ""), Some(root));
+        assert_eq!(find_node(&graph, root, "Body/Shield"), None);
+    }
+
+    #[test]
+    fn test_variant_from_instance() {
+        let mut prefab = Scene::new();
+        PivotBuilder::new(BaseBuilder::new().with_name("Body")).build(&mut prefab.graph);
+        let resource = Resource::new_ok(Model {
+            path: PathBuf::from("enemy.rgs"),
+            mapping: NodeMapping::UseHandles,
+            scene: prefab,
+        });
+
+        let mut scene = Scene::new();
+        let instance = resource.instantiate(&mut scene);
+        let body = scene
+            .graph
+            .find_by_name(instance, "Body")
+            .map(|(h, _)| h)
+            .unwrap();
+        scene.graph[instance]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 2.0, 3.0));
+        scene.graph[body].set_visibility(false);
+
+        let definition = PrefabVariantDefinition::from_instance(&scene.graph, instance).unwrap();
+        assert_eq!(definition.base, PathBuf::from("enemy.rgs"));
+        // Position of the instance root must be ignored.
+        assert_eq!(definition.overrides.len(), 1);
+        assert_eq!(definition.overrides[0].node, "Body");
+        assert_eq!(definition.overrides[0].value, OverrideValue::Bool(false));
+
+        let string = definition.to_ron_string().unwrap();
+        assert_eq!(
+            ron::de::from_str::<PrefabVariantDefinition>(&string).unwrap(),
+            definition
+        );
+
+        // Check that overrides could be applied back.
+        let mut graph = Graph::new();
+        let body = PivotBuilder::new(BaseBuilder::new().with_name("Body")).build(&mut graph);
+        assert!(graph[body].visibility());
+        let root = graph.get_root();
+        definition.apply(&mut graph, root, &Default::default());
+        assert!(!graph[body].visibility());
+    }
+}
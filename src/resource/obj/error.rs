@@ -0,0 +1,38 @@
+//! Contains all possible errors that can occur during OBJ loading.
+
+use crate::core::io::FileLoadError;
+use std::fmt::{Display, Formatter};
+
+/// See module docs.
+#[derive(Debug)]
+pub enum ObjError {
+    /// An error occurred during file loading.
+    FileLoadError(FileLoadError),
+
+    /// A line of the file could not be parsed.
+    Syntax {
+        /// Number of the line (starting from 1).
+        line: usize,
+        /// Description of the error.
+        message: String,
+    },
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjError::FileLoadError(v) => {
+                write!(f, "OBJ: File load error {v:?}.")
+            }
+            ObjError::Syntax { line, message } => {
+                write!(f, "OBJ: Syntax error at line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for ObjError {
+    fn from(err: FileLoadError) -> Self {
+        ObjError::FileLoadError(err)
+    }
+}
@@ -0,0 +1,671 @@
+//! Contains all methods to load Wavefront OBJ model format.
+//!
+//! OBJ is a simple text format, that is supported by almost every 3D modelling tool. It can store
+//! only static meshes with materials (in separate `.mtl` files), there is no support for hierarchies,
+//! skinning or animation, so it is mostly useful for quick prototyping. Every object (`o`) or group
+//! (`g`) of the file becomes a separate mesh node, and every material used by an object becomes a
+//! separate surface.
+//!
+//! Normally you should never use methods from this module directly, use resource manager to load
+//! models and create their instances.
+
+pub mod error;
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        color::Color,
+        instant::Instant,
+        io,
+        log::Log,
+        math::TriangleDefinition,
+        sstorage::ImmutableString,
+    },
+    material::{shader::SamplerFallback, Material, PropertyValue, SharedMaterial},
+    resource::{
+        model::{MaterialSearchOptions, ModelImportOptions},
+        obj::error::ObjError,
+        texture::Texture,
+    },
+    scene::{
+        base::BaseBuilder,
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{Surface, SurfaceData, SurfaceSharedData},
+            vertex::StaticVertex,
+            MeshBuilder,
+        },
+        Scene,
+    },
+};
+use fxhash::FxHashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+struct ObjVertex {
+    position: usize,
+    tex_coord: Option<usize>,
+    normal: Option<usize>,
+}
+
+#[derive(Debug)]
+struct ObjGroup {
+    material: Option<String>,
+    triangles: Vec<[ObjVertex; 3]>,
+}
+
+#[derive(Debug)]
+struct ObjObject {
+    name: String,
+    groups: Vec<ObjGroup>,
+}
+
+impl ObjObject {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            groups: Default::default(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.groups.iter().all(|group| group.triangles.is_empty())
+    }
+
+    fn group_mut(&mut self, material: Option<&str>) -> &mut ObjGroup {
+        match self
+            .groups
+            .iter()
+            .position(|group| group.material.as_deref() == material)
+        {
+            Some(index) => &mut self.groups[index],
+            None => {
+                self.groups.push(ObjGroup {
+                    material: material.map(|material| material.to_string()),
+                    triangles: Default::default(),
+                });
+                self.groups.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+struct ObjDocument {
+    positions: Vec<Vector3<f32>>,
+    tex_coords: Vec<Vector2<f32>>,
+    normals: Vec<Vector3<f32>>,
+    objects: Vec<ObjObject>,
+    material_libraries: Vec<String>,
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct MtlMaterial {
+    name: String,
+    diffuse_color: Option<Vector3<f32>>,
+    dissolve: Option<f32>,
+    emission_color: Option<Vector3<f32>>,
+    diffuse_map: Option<String>,
+    normal_map: Option<String>,
+    emission_map: Option<String>,
+    metallic_map: Option<String>,
+    roughness_map: Option<String>,
+}
+
+fn syntax_error<S: Into<String>>(line: usize, message: S) -> ObjError {
+    ObjError::Syntax {
+        line,
+        message: message.into(),
+    }
+}
+
+fn parse_floats<'a, const N: usize>(
+    mut tokens: impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<[f32; N], ObjError> {
+    let mut values = [0.0; N];
+    for value in values.iter_mut() {
+        let token = tokens
+            .next()
+            .ok_or_else(|| syntax_error(line, format!("Expected {} numbers", N)))?;
+        *value = token
+            .parse()
+            .map_err(|_| syntax_error(line, format!("Invalid number {}", token)))?;
+    }
+    Ok(values)
+}
+
+// Indices in OBJ start from 1, negative indices are relative to the end of the current list.
+fn parse_index(token: &str, count: usize, line: usize) -> Result<usize, ObjError> {
+    let index = token
+        .parse::<i64>()
+        .map_err(|_| syntax_error(line, format!("Invalid index {}", token)))?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved >= 0 && (resolved as usize) < count {
+        Ok(resolved as usize)
+    } else {
+        Err(syntax_error(
+            line,
+            format!("Index {} is out of bounds", token),
+        ))
+    }
+}
+
+fn parse_vertex(token: &str, document: &ObjDocument, line: usize) -> Result<ObjVertex, ObjError> {
+    let mut parts = token.split('/');
+    let position = parse_index(
+        parts.next().unwrap_or_default(),
+        document.positions.len(),
+        line,
+    )?;
+    let tex_coord = match parts.next() {
+        Some(part) if !part.is_empty() => Some(parse_index(part, document.tex_coords.len(), line)?),
+        _ => None,
+    };
+    let normal = match parts.next() {
+        Some(part) if !part.is_empty() => Some(parse_index(part, document.normals.len(), line)?),
+        _ => None,
+    };
+    Ok(ObjVertex {
+        position,
+        tex_coord,
+        normal,
+    })
+}
+
+fn parse_obj(text: &str) -> Result<ObjDocument, ObjError> {
+    let mut document = ObjDocument::default();
+    let mut material = None;
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+
+        match keyword {
+            "v" => document
+                .positions
+                .push(Vector3::from(parse_floats::<3>(tokens, line_number)?)),
+            "vt" => {
+                let [u, v] = parse_floats::<2>(tokens, line_number)?;
+                // Invert Y because OpenGL has origin at left *bottom* corner.
+                document.tex_coords.push(Vector2::new(u, 1.0 - v));
+            }
+            "vn" => document
+                .normals
+                .push(Vector3::from(parse_floats::<3>(tokens, line_number)?)),
+            "f" => {
+                let vertices = tokens
+                    .map(|token| parse_vertex(token, &document, line_number))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if vertices.len() < 3 {
+                    return Err(syntax_error(
+                        line_number,
+                        "A face must have at least three vertices",
+                    ));
+                }
+
+                if document.objects.is_empty() {
+                    document.objects.push(ObjObject::new(Default::default()));
+                }
+                let group = document
+                    .objects
+                    .last_mut()
+                    .unwrap()
+                    .group_mut(material.as_deref());
+
+                // Polygons are triangulated as fans, OBJ polygons are expected to be convex.
+                for i in 1..vertices.len() - 1 {
+                    group
+                        .triangles
+                        .push([vertices[0], vertices[i], vertices[i + 1]]);
+                }
+            }
+            "o" | "g" => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                match document.objects.last_mut() {
+                    // Some exporters write both `o` and `g` for every object.
+                    Some(object) if object.is_empty() => object.name = name,
+                    _ => document.objects.push(ObjObject::new(name)),
+                }
+            }
+            "usemtl" => material = Some(tokens.collect::<Vec<_>>().join(" ")),
+            "mtllib" => document
+                .material_libraries
+                .extend(tokens.map(|token| token.to_string())),
+            // Smoothing groups, lines, points, free-form geometry and other rarely used statements
+            // are ignored.
+            _ => (),
+        }
+    }
+
+    Ok(document)
+}
+
+// Texture maps could have options before the file name (`map_Kd -blendu on texture.png`).
+fn parse_map(tokens: &[&str]) -> Option<String> {
+    tokens.last().map(|path| path.replace('\\', "/"))
+}
+
+fn parse_color(tokens: &[&str]) -> Option<Vector3<f32>> {
+    parse_floats::<3>(tokens.iter().copied(), 0)
+        .ok()
+        .map(Vector3::from)
+}
+
+fn parse_mtl(text: &str) -> Vec<MtlMaterial> {
+    let mut materials = Vec::<MtlMaterial>::new();
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            materials.push(MtlMaterial {
+                name: tokens.collect::<Vec<_>>().join(" "),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let material = match materials.last_mut() {
+            Some(material) => material,
+            None => continue,
+        };
+
+        let tokens = tokens.collect::<Vec<_>>();
+        match keyword {
+            "Kd" => material.diffuse_color = parse_color(&tokens),
+            "Ke" => material.emission_color = parse_color(&tokens),
+            "d" => material.dissolve = tokens.last().and_then(|d| d.parse().ok()),
+            "Tr" => {
+                material.dissolve = tokens
+                    .last()
+                    .and_then(|tr| tr.parse::<f32>().ok())
+                    .map(|tr| 1.0 - tr)
+            }
+            "map_Kd" => material.diffuse_map = parse_map(&tokens),
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_map = parse_map(&tokens),
+            "map_Ke" => material.emission_map = parse_map(&tokens),
+            "map_Pm" => material.metallic_map = parse_map(&tokens),
+            "map_Pr" => material.roughness_map = parse_map(&tokens),
+            _ => (),
+        }
+    }
+
+    materials
+}
+
+fn set_material_property(material: &mut Material, name: &str, value: PropertyValue) {
+    if let Err(e) = material.set_property(&ImmutableString::new(name), value) {
+        Log::err(format!(
+            "Unable to set material property {} for OBJ material! Reason: {:?}",
+            name, e
+        ));
+    }
+}
+
+fn texture_path(base_path: &Path, path: &str, options: &ModelImportOptions) -> PathBuf {
+    let relative_path = PathBuf::from(path);
+    match options.material_search_options {
+        MaterialSearchOptions::MaterialsDirectory(ref directory) => {
+            match relative_path.file_name() {
+                Some(file_name) => directory.join(file_name),
+                None => base_path.join(relative_path),
+            }
+        }
+        // MTL stores paths relative to the material library, so there is no need to search for
+        // textures.
+        _ => base_path.join(relative_path),
+    }
+}
+
+fn convert_material(
+    source: &MtlMaterial,
+    base_path: &Path,
+    resource_manager: &ResourceManager,
+    options: &ModelImportOptions,
+) -> SharedMaterial {
+    let mut material = Material::standard();
+
+    let diffuse_color = source.diffuse_color.unwrap_or_else(|| Vector3::repeat(1.0));
+    set_material_property(
+        &mut material,
+        "diffuseColor",
+        PropertyValue::Color(Color::from(Vector4::new(
+            diffuse_color.x,
+            diffuse_color.y,
+            diffuse_color.z,
+            source.dissolve.unwrap_or(1.0),
+        ))),
+    );
+
+    if source.emission_map.is_some() {
+        set_material_property(
+            &mut material,
+            "emissionStrength",
+            PropertyValue::Vector3(
+                source
+                    .emission_color
+                    .unwrap_or_else(|| Vector3::repeat(1.0)),
+            ),
+        );
+    }
+
+    for (name, path, fallback) in [
+        (
+            "diffuseTexture",
+            &source.diffuse_map,
+            SamplerFallback::White,
+        ),
+        ("normalTexture", &source.normal_map, SamplerFallback::Normal),
+        (
+            "emissionTexture",
+            &source.emission_map,
+            SamplerFallback::Black,
+        ),
+        (
+            "metallicTexture",
+            &source.metallic_map,
+            SamplerFallback::Black,
+        ),
+        (
+            "roughnessTexture",
+            &source.roughness_map,
+            SamplerFallback::White,
+        ),
+    ] {
+        if let Some(path) = path {
+            let texture =
+                resource_manager.request::<Texture, _>(texture_path(base_path, path, options));
+            set_material_property(
+                &mut material,
+                name,
+                PropertyValue::Sampler {
+                    value: Some(texture),
+                    fallback,
+                },
+            );
+        }
+    }
+
+    SharedMaterial::new(material)
+}
+
+async fn load_materials(
+    document: &ObjDocument,
+    base_path: &Path,
+    resource_manager: &ResourceManager,
+    options: &ModelImportOptions,
+) -> FxHashMap<String, SharedMaterial> {
+    let mut materials = FxHashMap::default();
+    for library in document.material_libraries.iter() {
+        let library_path = base_path.join(library);
+        match io::load_file(&library_path).await {
+            Ok(data) => {
+                let library_base_path = library_path.parent().unwrap_or_else(|| Path::new(""));
+                for material in parse_mtl(&String::from_utf8_lossy(&data)) {
+                    let converted =
+                        convert_material(&material, library_base_path, resource_manager, options);
+                    materials.insert(material.name, converted);
+                }
+            }
+            Err(e) => Log::err(format!(
+                "Unable to load material library {}. Reason: {:?}",
+                library_path.display(),
+                e
+            )),
+        }
+    }
+    materials
+}
+
+// Smooth normals for vertices, that do not have normals in the file.
+fn calculate_smooth_normals(document: &ObjDocument) -> Vec<Vector3<f32>> {
+    let mut normals = vec![Vector3::default(); document.positions.len()];
+    for object in document.objects.iter() {
+        for group in object.groups.iter() {
+            for triangle in group.triangles.iter() {
+                let a = document.positions[triangle[0].position];
+                let b = document.positions[triangle[1].position];
+                let c = document.positions[triangle[2].position];
+                // Non-normalized cross product gives area-weighted normals.
+                let normal = (b - a).cross(&(c - a));
+                for vertex in triangle.iter() {
+                    normals[vertex.position] += normal;
+                }
+            }
+        }
+    }
+    for normal in normals.iter_mut() {
+        *normal = normal
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+    }
+    normals
+}
+
+fn convert_group(
+    document: &ObjDocument,
+    group: &ObjGroup,
+    smooth_normals: &[Vector3<f32>],
+) -> SurfaceData {
+    let mut indices = FxHashMap::default();
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::with_capacity(group.triangles.len());
+
+    for triangle in group.triangles.iter() {
+        let mut definition = [0; 3];
+        for (index, vertex) in definition.iter_mut().zip(triangle.iter()) {
+            *index = *indices.entry(*vertex).or_insert_with(|| {
+                vertices.push(StaticVertex {
+                    position: document.positions[vertex.position],
+                    tex_coord: vertex
+                        .tex_coord
+                        .map(|i| document.tex_coords[i])
+                        .unwrap_or_default(),
+                    normal: vertex
+                        .normal
+                        .map(|i| document.normals[i])
+                        .unwrap_or_else(|| smooth_normals[vertex.position]),
+                    tangent: Vector4::new(0.0, 1.0, 0.0, 1.0),
+                });
+                vertices.len() as u32 - 1
+            });
+        }
+        triangles.push(TriangleDefinition(definition));
+    }
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+        false,
+    )
+}
+
+/// Tries to load and convert OBJ model from given path.
+///
+/// Normally you should never use this method, use resource manager to load models.
+pub async fn load_to_scene<P: AsRef<Path>>(
+    scene: &mut Scene,
+    resource_manager: ResourceManager,
+    path: P,
+    model_import_options: &ModelImportOptions,
+) -> Result<(), ObjError> {
+    let path = path.as_ref();
+    let start_time = Instant::now();
+
+    Log::info(format!("Trying to load {:?}", path));
+
+    let data = io::load_file(path).await?;
+    let document = parse_obj(&String::from_utf8_lossy(&data))?;
+
+    let base_path = path.parent().unwrap_or_else(|| Path::new(""));
+    let materials = load_materials(
+        &document,
+        base_path,
+        &resource_manager,
+        model_import_options,
+    )
+    .await;
+    let default_material = SharedMaterial::new(Material::standard());
+
+    let smooth_normals = if document.objects.iter().any(|object| {
+        object.groups.iter().any(|group| {
+            group
+                .triangles
+                .iter()
+                .any(|triangle| triangle.iter().any(|vertex| vertex.normal.is_none()))
+        })
+    }) {
+        calculate_smooth_normals(&document)
+    } else {
+        Default::default()
+    };
+
+    let file_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    for object in document.objects.iter().filter(|object| !object.is_empty()) {
+        let mut surfaces = Vec::new();
+        for group in object.groups.iter().filter(|g| !g.triangles.is_empty()) {
+            let mut data = convert_group(&document, group, &smooth_normals);
+            if let Err(e) = data.calculate_tangents() {
+                Log::err(format!(
+                    "OBJ: Unable to calculate tangents for object {}. Reason: {:?}",
+                    object.name, e
+                ));
+            }
+
+            let material = group
+                .material
+                .as_ref()
+                .and_then(|name| materials.get(name))
+                .cloned()
+                .unwrap_or_else(|| default_material.clone());
+
+            let mut surface = Surface::new(SurfaceSharedData::new(data));
+            surface.set_material(material);
+            surfaces.push(surface);
+        }
+
+        let name = if object.name.is_empty() {
+            &file_name
+        } else {
+            &object.name
+        };
+
+        MeshBuilder::new(BaseBuilder::new().with_name(name))
+            .with_surfaces(surfaces)
+            .build(&mut scene.graph);
+    }
+
+    Log::info(format!(
+        "OBJ {:?} loaded in {} ms",
+        path,
+        start_time.elapsed().as_millis()
+    ));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_obj() {
+        let document = parse_obj(
+            r"
+            # A quad and a triangle.
+            mtllib materials.mtl
+            o Quad
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vt 0 0
+            vn 0 0 1
+            usemtl Red
+            f 1/1/1 2/1/1 3/1/1 4/1/1
+            g Triangle
+            usemtl Green
+            f -4 -3 -2
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(document.positions.len(), 4);
+        assert_eq!(document.tex_coords, vec![Vector2::new(0.0, 1.0)]);
+        assert_eq!(document.material_libraries, vec!["materials.mtl"]);
+        assert_eq!(document.objects.len(), 2);
+
+        let quad = &document.objects[0];
+        assert_eq!(quad.name, "Quad");
+        assert_eq!(quad.groups[0].material.as_deref(), Some("Red"));
+        assert_eq!(quad.groups[0].triangles.len(), 2);
+        assert_eq!(quad.groups[0].triangles[1][2].position, 3);
+        assert_eq!(quad.groups[0].triangles[1][2].normal, Some(0));
+
+        let triangle = &document.objects[1];
+        assert_eq!(triangle.name, "Triangle");
+        assert_eq!(triangle.groups[0].material.as_deref(), Some("Green"));
+        assert_eq!(
+            triangle.groups[0].triangles[0].map(|v| v.position),
+            [0, 1, 2]
+        );
+        assert_eq!(triangle.groups[0].triangles[0][0].normal, None);
+
+        assert!(matches!(
+            parse_obj("v 0 0 0\nf 1 2 3"),
+            Err(ObjError::Syntax { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_mtl() {
+        let materials = parse_mtl(
+            r"
+            newmtl Red
+            Kd 1 0 0
+            d 0.5
+            map_Kd -bm 1.0 textures\red.png
+            newmtl Green
+            Kd 0 1 0
+            ",
+        );
+
+        assert_eq!(
+            materials,
+            vec![
+                MtlMaterial {
+                    name: "Red".to_string(),
+                    diffuse_color: Some(Vector3::new(1.0, 0.0, 0.0)),
+                    dissolve: Some(0.5),
+                    diffuse_map: Some("textures/red.png".to_string()),
+                    ..Default::default()
+                },
+                MtlMaterial {
+                    name: "Green".to_string(),
+                    diffuse_color: Some(Vector3::new(0.0, 1.0, 0.0)),
+                    ..Default::default()
+                }
+            ]
+        );
+    }
+}
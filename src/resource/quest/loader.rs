@@ -0,0 +1,23 @@
+//! Quest loader.
+
+use crate::{
+    asset::custom::CustomResourceLoader,
+    resource::quest::{Quest, QuestError},
+};
+use std::path::Path;
+
+/// Default implementation for quest loading.
+pub struct QuestLoader;
+
+impl CustomResourceLoader for QuestLoader {
+    type Data = Quest;
+    type Error = QuestError;
+
+    fn extensions(&self) -> &[&str] {
+        &["quest"]
+    }
+
+    fn load_from_bytes(&self, path: &Path, bytes: Vec<u8>) -> Result<Quest, QuestError> {
+        Quest::from_bytes(&bytes, path)
+    }
+}
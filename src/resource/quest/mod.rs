@@ -0,0 +1,311 @@
+//! Quest resource. It describes a quest as a set of objectives, that are completed by quest messages sent
+//! by the game (killed enemies, collected items, finished conversations, etc.). See [`Quest`] docs for
+//! more info and [`crate::utils::quest`] module to track quests.
+
+use crate::{
+    asset::{Resource, ResourceData},
+    core::{
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    borrow::Cow,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+pub mod loader;
+
+/// An error that may occur during quest loading.
+#[derive(Debug)]
+pub enum QuestError {
+    /// A parsing error has occurred.
+    ParseError(ron::error::SpannedError),
+
+    /// Required amount of messages of an objective is zero.
+    InvalidObjectiveCount(String),
+
+    /// There's more than one objective with the same name.
+    DuplicateObjective(String),
+}
+
+impl Display for QuestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuestError::ParseError(v) => {
+                write!(f, "A parsing error has occurred {v:?}")
+            }
+            QuestError::InvalidObjectiveCount(name) => {
+                write!(f, "Objective {name} must require at least one message!")
+            }
+            QuestError::DuplicateObjective(name) => {
+                write!(f, "There's more than one objective with name {name}!")
+            }
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for QuestError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// An objective of a quest. The objective is completed when the quest log receives required amount of
+/// quest messages with the given name (see [`crate::utils::quest::QuestMessage`]).
+#[derive(Clone, Debug, PartialEq, Visit, Serialize, Deserialize)]
+pub struct QuestObjective {
+    /// Name of the objective, it must be unique within the quest.
+    pub name: String,
+
+    /// Description (or localization key of the description) of the objective.
+    #[serde(default)]
+    pub description: String,
+
+    /// Name of the quest message, that advances the objective.
+    pub message: String,
+
+    /// Required amount of the messages.
+    #[serde(default = "default_count")]
+    pub count: u32,
+
+    /// Optional objectives are not required to complete the quest.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+impl Default for QuestObjective {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            description: Default::default(),
+            message: Default::default(),
+            count: default_count(),
+            optional: false,
+        }
+    }
+}
+
+/// Serializable description of a quest. It is stored in `.quest` files in RON format:
+///
+/// ```text
+/// (
+///     name: "quest.wolves",
+///     title: "quest.wolves.title",
+///     description: "quest.wolves.description",
+///     prerequisites: ["quest.village"],
+///     objectives: [
+///         (name: "hunt", message: "killed:wolf", count: 5),
+///         (name: "pelts", message: "collected:wolf_pelt", count: 3, optional: true),
+///         (name: "report", message: "talked:elder"),
+///     ],
+///     fail_messages: ["killed:elder"],
+/// )
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuestDefinition {
+    /// See [`Quest::name`].
+    pub name: String,
+
+    /// See [`Quest::title`].
+    #[serde(default)]
+    pub title: String,
+
+    /// See [`Quest::description`].
+    #[serde(default)]
+    pub description: String,
+
+    /// See [`Quest::prerequisites`].
+    #[serde(default)]
+    pub prerequisites: Vec<String>,
+
+    /// See [`Quest::objectives`].
+    #[serde(default)]
+    pub objectives: Vec<QuestObjective>,
+
+    /// See [`Quest::fail_messages`].
+    #[serde(default)]
+    pub fail_messages: Vec<String>,
+}
+
+/// Quest is a description of a task for a player. It consists of a set of objectives, every objective is
+/// advanced by quest messages with a particular name. The quest is completed when all its required
+/// objectives are completed and it is failed when it receives any of its fail messages. A quest could
+/// be started only after all its prerequisites (other quests) are completed.
+///
+/// Quests are usually loaded from `.quest` files (see [`QuestDefinition`] for the format), but they could
+/// also be created from code using [`Quest::new`]. Use [`crate::utils::quest::QuestLog`] to track the
+/// state of quests.
+#[derive(Debug, Default, Visit, Reflect)]
+pub struct Quest {
+    #[reflect(hidden)]
+    pub(crate) path: PathBuf,
+    name: String,
+    title: String,
+    description: String,
+    prerequisites: Vec<String>,
+    #[reflect(hidden)]
+    objectives: Vec<QuestObjective>,
+    fail_messages: Vec<String>,
+}
+
+impl ResourceData for Quest {
+    fn path(&self) -> Cow<Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+}
+
+impl TypeUuidProvider for Quest {
+    fn type_uuid() -> Uuid {
+        uuid!("4f2d8b6e-1a7c-4c3e-b5d9-7e0a2c4f6b18")
+    }
+}
+
+impl Quest {
+    /// Creates new quest from the given definition. Objectives must have unique names and must require
+    /// at least one message.
+    pub fn new(definition: QuestDefinition) -> Result<Self, QuestError> {
+        for (index, objective) in definition.objectives.iter().enumerate() {
+            if objective.count == 0 {
+                return Err(QuestError::InvalidObjectiveCount(objective.name.clone()));
+            }
+            if definition.objectives[..index]
+                .iter()
+                .any(|other| other.name == objective.name)
+            {
+                return Err(QuestError::DuplicateObjective(objective.name.clone()));
+            }
+        }
+
+        Ok(Self {
+            path: Default::default(),
+            name: definition.name,
+            title: definition.title,
+            description: definition.description,
+            prerequisites: definition.prerequisites,
+            objectives: definition.objectives,
+            fail_messages: definition.fail_messages,
+        })
+    }
+
+    /// Creates new quest from its serialized description (see [`QuestDefinition`]).
+    pub fn from_bytes(bytes: &[u8], path: &Path) -> Result<Self, QuestError> {
+        let mut quest = Self::new(ron::de::from_bytes::<QuestDefinition>(bytes)?)?;
+        quest.path = path.to_path_buf();
+        Ok(quest)
+    }
+
+    /// Returns unique name of the quest. It is used to identify the quest in quest logs and
+    /// prerequisites of other quests.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns title (or localization key of the title) of the quest.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns description (or localization key of the description) of the quest.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns names of the quests, that must be completed before the quest could be started.
+    pub fn prerequisites(&self) -> &[String] {
+        &self.prerequisites
+    }
+
+    /// Returns a list of objectives of the quest.
+    pub fn objectives(&self) -> &[QuestObjective] {
+        &self.objectives
+    }
+
+    /// Tries to find an objective by its name, returns its index.
+    pub fn find_objective(&self, name: &str) -> Option<usize> {
+        self.objectives
+            .iter()
+            .position(|objective| objective.name == name)
+    }
+
+    /// Returns names of the quest messages, that fail the quest.
+    pub fn fail_messages(&self) -> &[String] {
+        &self.fail_messages
+    }
+}
+
+/// Type alias for quest resources.
+pub type QuestResource = Resource<Quest>;
+
+#[cfg(test)]
+mod test {
+    use crate::resource::quest::{Quest, QuestError};
+    use std::path::Path;
+
+    #[test]
+    fn test_quest_loading() {
+        let quest = Quest::from_bytes(
+            br#"(
+                name: "quest.wolves",
+                prerequisites: ["quest.village"],
+                objectives: [
+                    (name: "hunt", message: "killed:wolf", count: 5),
+                    (name: "report", message: "talked:elder"),
+                ],
+            )"#,
+            Path::new("wolves.quest"),
+        )
+        .unwrap();
+
+        assert_eq!(quest.name(), "quest.wolves");
+        assert_eq!(quest.prerequisites(), ["quest.village".to_string()]);
+        assert_eq!(quest.objectives().len(), 2);
+        assert_eq!(quest.objectives()[1].count, 1);
+        assert!(!quest.objectives()[1].optional);
+        assert_eq!(quest.find_objective("report"), Some(1));
+        assert!(quest.fail_messages().is_empty());
+
+        assert!(matches!(
+            Quest::from_bytes(
+                br#"(name: "quest.broken", objectives: [(name: "a", message: "b", count: 0)])"#,
+                Path::new("broken.quest"),
+            ),
+            Err(QuestError::InvalidObjectiveCount(_))
+        ));
+        assert!(matches!(
+            Quest::from_bytes(
+                br#"(
+                    name: "quest.broken",
+                    objectives: [(name: "a", message: "b"), (name: "a", message: "c")],
+                )"#,
+                Path::new("broken.quest"),
+            ),
+            Err(QuestError::DuplicateObjective(_))
+        ));
+    }
+}
@@ -0,0 +1,30 @@
+//! Sprite sheet loader.
+
+use crate::{
+    asset::{custom::CustomResourceLoader, manager::ResourceManager},
+    resource::spritesheet::{SpriteSheet, SpriteSheetError},
+};
+use std::path::Path;
+
+/// Default implementation for sprite sheet loading.
+pub struct SpriteSheetLoader {
+    /// Resource manager to request textures of sprite sheets.
+    pub resource_manager: ResourceManager,
+}
+
+impl CustomResourceLoader for SpriteSheetLoader {
+    type Data = SpriteSheet;
+    type Error = SpriteSheetError;
+
+    fn extensions(&self) -> &[&str] {
+        &["spritesheet"]
+    }
+
+    fn load_from_bytes(
+        &self,
+        path: &Path,
+        bytes: Vec<u8>,
+    ) -> Result<SpriteSheet, SpriteSheetError> {
+        SpriteSheet::from_bytes(&bytes, path, &self.resource_manager)
+    }
+}
@@ -0,0 +1,503 @@
+//! Sprite sheet (texture atlas) resource. It describes a set of frames (rectangular regions of a
+//! single texture) with their pivots, and a set of named animations that reference the frames. See
+//! [`SpriteSheet`] docs for more info.
+
+use crate::{
+    asset::{manager::ResourceManager, Resource, ResourceData},
+    core::{
+        algebra::Vector2,
+        math::Rect,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    resource::texture::{Texture, TextureResource},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    borrow::Cow,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+pub mod loader;
+
+/// An error that may occur during sprite sheet loading.
+#[derive(Debug)]
+pub enum SpriteSheetError {
+    /// A parsing error has occurred.
+    ParseError(ron::error::SpannedError),
+
+    /// A frame of a packed sprite sheet lies outside of the texture.
+    InvalidFrame(usize),
+
+    /// An animation references a frame that does not exist.
+    InvalidAnimationFrame {
+        /// Name of the animation.
+        animation: String,
+        /// Index of the frame.
+        frame: u32,
+    },
+}
+
+impl Display for SpriteSheetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpriteSheetError::ParseError(v) => {
+                write!(f, "A parsing error has occurred {v:?}")
+            }
+            SpriteSheetError::InvalidFrame(index) => {
+                write!(f, "Frame {index} lies outside of the texture!")
+            }
+            SpriteSheetError::InvalidAnimationFrame { animation, frame } => {
+                write!(
+                    f,
+                    "Animation {animation} references frame {frame} that does not exist!"
+                )
+            }
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for SpriteSheetError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+fn default_pivot() -> Vector2<f32> {
+    Vector2::new(0.5, 0.5)
+}
+
+fn default_speed() -> f32 {
+    10.0
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+/// A single frame of a sprite sheet.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct SpriteSheetFrame {
+    /// Name of the frame, it could be empty.
+    pub name: String,
+
+    /// A region of the texture occupied by the frame. The coordinates are normalized, which means
+    /// that `[0; 0]` corresponds to top-left corner of the texture and `[1; 1]` corresponds to
+    /// right-bottom corner. The rectangle could be used directly in
+    /// [`crate::scene::dim2::rectangle::Rectangle::set_uv_rect`],
+    /// [`crate::scene::sprite::Sprite::set_uv_rect`] or in UI image widget.
+    pub uv_rect: Rect<f32>,
+
+    /// A point of the frame that should be aligned with the position of an object, that uses the
+    /// frame. The coordinates are normalized and relative to the frame: `[0; 0]` is the top-left
+    /// corner of the frame, `[0.5; 0.5]` is its center.
+    pub pivot: Vector2<f32>,
+}
+
+impl Default for SpriteSheetFrame {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            pivot: default_pivot(),
+        }
+    }
+}
+
+/// A named animation of a sprite sheet, it is a sequence of frames. See
+/// [`crate::animation::spritesheet::SpriteSheetAnimation::new_from_sprite_sheet`] to play such
+/// animations.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, Serialize, Deserialize)]
+pub struct SpriteSheetAnimationDefinition {
+    /// Name of the animation.
+    pub name: String,
+
+    /// Indices of the frames of the sprite sheet, that will be played one after another. The same
+    /// frame could be used multiple times.
+    pub frames: Vec<u32>,
+
+    /// Playback speed in frames per second.
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+
+    /// Whether the animation should start over when it ends.
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+}
+
+impl Default for SpriteSheetAnimationDefinition {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            frames: Default::default(),
+            speed: default_speed(),
+            looping: default_looping(),
+        }
+    }
+}
+
+/// A frame of [`SpriteSheetLayout::Packed`] sprite sheet.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PackedFrameDefinition {
+    /// Name of the frame.
+    #[serde(default)]
+    pub name: String,
+    /// Horizontal position of the top-left corner of the frame in pixels.
+    pub x: u32,
+    /// Vertical position of the top-left corner of the frame in pixels.
+    pub y: u32,
+    /// Width of the frame in pixels.
+    pub width: u32,
+    /// Height of the frame in pixels.
+    pub height: u32,
+    /// See [`SpriteSheetFrame::pivot`].
+    #[serde(default = "default_pivot")]
+    pub pivot: Vector2<f32>,
+}
+
+/// Defines how frames are placed in the texture of a sprite sheet.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpriteSheetLayout {
+    /// All frames have the same size and form a grid. Frames are numbered row-by-row starting from
+    /// the top-left corner.
+    Grid {
+        /// Amount of frames in a row.
+        columns: u32,
+        /// Amount of frames in a column.
+        rows: u32,
+        /// See [`SpriteSheetFrame::pivot`]. It is the same for every frame.
+        #[serde(default = "default_pivot")]
+        pivot: Vector2<f32>,
+    },
+    /// Frames have arbitrary sizes and positions, this is what texture packing tools produce.
+    Packed {
+        /// Width of the texture in pixels.
+        width: u32,
+        /// Height of the texture in pixels.
+        height: u32,
+        /// A list of frames.
+        frames: Vec<PackedFrameDefinition>,
+    },
+}
+
+/// Serializable description of a sprite sheet. It is stored in `.spritesheet` files in RON format:
+///
+/// ```text
+/// (
+///     texture: Some("hero.png"),
+///     layout: Grid(columns: 4, rows: 2),
+///     animations: [
+///         (name: "run", frames: [0, 1, 2, 3], speed: 12.0),
+///         (name: "jump", frames: [4, 5, 6, 7], looping: false),
+///     ],
+/// )
+/// ```
+///
+/// Packed sprite sheets list their frames explicitly:
+///
+/// ```text
+/// (
+///     texture: Some("atlas.png"),
+///     layout: Packed(
+///         width: 256,
+///         height: 128,
+///         frames: [
+///             (name: "idle", x: 0, y: 0, width: 32, height: 48, pivot: (0.5, 1.0)),
+///             (name: "crouch", x: 32, y: 16, width: 40, height: 32, pivot: (0.5, 1.0)),
+///         ],
+///     ),
+/// )
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpriteSheetDefinition {
+    /// A path to the texture, relative to the sprite sheet file.
+    #[serde(default)]
+    pub texture: Option<PathBuf>,
+
+    /// Layout of the frames.
+    pub layout: SpriteSheetLayout,
+
+    /// A list of animations.
+    #[serde(default)]
+    pub animations: Vec<SpriteSheetAnimationDefinition>,
+}
+
+impl SpriteSheetDefinition {
+    /// Creates a list of frames described by the layout.
+    pub fn frames(&self) -> Result<Vec<SpriteSheetFrame>, SpriteSheetError> {
+        match self.layout {
+            SpriteSheetLayout::Grid {
+                columns,
+                rows,
+                pivot,
+            } => {
+                let columns = columns.max(1);
+                let rows = rows.max(1);
+                let size = Vector2::new(1.0 / columns as f32, 1.0 / rows as f32);
+                Ok((0..rows)
+                    .flat_map(|y| (0..columns).map(move |x| (x, y)))
+                    .map(|(x, y)| SpriteSheetFrame {
+                        name: Default::default(),
+                        uv_rect: Rect::new(x as f32 * size.x, y as f32 * size.y, size.x, size.y),
+                        pivot,
+                    })
+                    .collect())
+            }
+            SpriteSheetLayout::Packed {
+                width,
+                height,
+                ref frames,
+            } => frames
+                .iter()
+                .enumerate()
+                .map(|(index, frame)| {
+                    if frame.x.saturating_add(frame.width) > width
+                        || frame.y.saturating_add(frame.height) > height
+                    {
+                        return Err(SpriteSheetError::InvalidFrame(index));
+                    }
+
+                    Ok(SpriteSheetFrame {
+                        name: frame.name.clone(),
+                        uv_rect: Rect::new(
+                            frame.x as f32 / width as f32,
+                            frame.y as f32 / height as f32,
+                            frame.width as f32 / width as f32,
+                            frame.height as f32 / height as f32,
+                        ),
+                        pivot: frame.pivot,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Sprite sheet (texture atlas) is a single texture with a set of frames (rectangular regions of the
+/// texture) and a set of named animations, that reference the frames. It is the main building block
+/// for 2D games: characters, tiles, UI icons are usually packed in a few atlases to reduce the amount
+/// of draw calls and texture switches.
+///
+/// Sprite sheets are usually loaded from `.spritesheet` files (see [`SpriteSheetDefinition`] for the
+/// format), but they could also be created from code using [`SpriteSheet::new`].
+///
+/// ```rust
+/// use fyrox::{
+///     animation::spritesheet::SpriteSheetAnimation,
+///     asset::manager::ResourceManager,
+///     core::pool::Handle,
+///     resource::spritesheet::SpriteSheet,
+///     scene::{base::BaseBuilder, dim2::rectangle::RectangleBuilder, graph::Graph, node::Node},
+/// };
+///
+/// async fn create_hero(
+///     resource_manager: ResourceManager,
+///     graph: &mut Graph,
+/// ) -> (Handle<Node>, SpriteSheetAnimation) {
+///     let sprite_sheet = resource_manager
+///         .request::<SpriteSheet, _>("data/hero.spritesheet")
+///         .await
+///         .unwrap();
+///
+///     let mut run = SpriteSheetAnimation::new_from_sprite_sheet(sprite_sheet, "run");
+///     run.play();
+///
+///     let mut builder = RectangleBuilder::new(BaseBuilder::new());
+///     if let Some(texture) = run.texture() {
+///         builder = builder.with_texture(texture);
+///     }
+///     if let Some(uv_rect) = run.current_frame_uv_rect() {
+///         builder = builder.with_uv_rect(uv_rect);
+///     }
+///
+///     (builder.build(graph), run)
+/// }
+/// ```
+#[derive(Debug, Default, Visit, Reflect)]
+pub struct SpriteSheet {
+    #[reflect(hidden)]
+    pub(crate) path: PathBuf,
+    texture: Option<TextureResource>,
+    frames: Vec<SpriteSheetFrame>,
+    animations: Vec<SpriteSheetAnimationDefinition>,
+}
+
+impl ResourceData for SpriteSheet {
+    fn path(&self) -> Cow<Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+}
+
+impl TypeUuidProvider for SpriteSheet {
+    fn type_uuid() -> Uuid {
+        uuid!("4a2d8b6c-2c4e-4a63-9a2e-4c1b7f7e8d15")
+    }
+}
+
+impl SpriteSheet {
+    /// Creates new sprite sheet from the given texture, frames and animations. Animations must
+    /// reference existing frames only.
+    pub fn new(
+        texture: Option<TextureResource>,
+        frames: Vec<SpriteSheetFrame>,
+        animations: Vec<SpriteSheetAnimationDefinition>,
+    ) -> Result<Self, SpriteSheetError> {
+        for animation in animations.iter() {
+            if let Some(frame) = animation
+                .frames
+                .iter()
+                .find(|frame| **frame as usize >= frames.len())
+            {
+                return Err(SpriteSheetError::InvalidAnimationFrame {
+                    animation: animation.name.clone(),
+                    frame: *frame,
+                });
+            }
+        }
+
+        Ok(Self {
+            path: Default::default(),
+            texture,
+            frames,
+            animations,
+        })
+    }
+
+    /// Creates new sprite sheet from its serialized description (see [`SpriteSheetDefinition`]).
+    /// Texture path is resolved relative to the given path of the sprite sheet.
+    pub fn from_bytes(
+        bytes: &[u8],
+        path: &Path,
+        resource_manager: &ResourceManager,
+    ) -> Result<Self, SpriteSheetError> {
+        let definition = ron::de::from_bytes::<SpriteSheetDefinition>(bytes)?;
+        let texture = definition.texture.as_ref().map(|texture| {
+            let base_path = path.parent().unwrap_or_else(|| Path::new(""));
+            resource_manager.request::<Texture, _>(base_path.join(texture))
+        });
+        let frames = definition.frames()?;
+        let mut sprite_sheet = Self::new(texture, frames, definition.animations)?;
+        sprite_sheet.path = path.to_path_buf();
+        Ok(sprite_sheet)
+    }
+
+    /// Returns the texture of the sprite sheet.
+    pub fn texture(&self) -> Option<TextureResource> {
+        self.texture.clone()
+    }
+
+    /// Returns a list of frames of the sprite sheet.
+    pub fn frames(&self) -> &[SpriteSheetFrame] {
+        &self.frames
+    }
+
+    /// Tries to get a frame by its index.
+    pub fn frame(&self, index: usize) -> Option<&SpriteSheetFrame> {
+        self.frames.get(index)
+    }
+
+    /// Tries to find a frame by its name.
+    pub fn find_frame(&self, name: &str) -> Option<&SpriteSheetFrame> {
+        self.frames.iter().find(|frame| frame.name == name)
+    }
+
+    /// Returns a list of animations of the sprite sheet.
+    pub fn animations(&self) -> &[SpriteSheetAnimationDefinition] {
+        &self.animations
+    }
+
+    /// Tries to find an animation by its name.
+    pub fn animation(&self, name: &str) -> Option<&SpriteSheetAnimationDefinition> {
+        self.animations
+            .iter()
+            .find(|animation| animation.name == name)
+    }
+}
+
+/// Type alias for sprite sheet resources.
+pub type SpriteSheetResource = Resource<SpriteSheet>;
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        core::{algebra::Vector2, math::Rect},
+        resource::spritesheet::{SpriteSheet, SpriteSheetError},
+    };
+    use std::path::Path;
+
+    #[test]
+    fn test_grid_sprite_sheet() {
+        let sprite_sheet = SpriteSheet::from_bytes(
+            br#"(
+                layout: Grid(columns: 4, rows: 2),
+                animations: [(name: "run", frames: [4, 5, 6, 7], speed: 12.0)],
+            )"#,
+            Path::new("hero.spritesheet"),
+            &ResourceManager::new(),
+        )
+        .unwrap();
+
+        assert_eq!(sprite_sheet.frames().len(), 8);
+        assert_eq!(
+            sprite_sheet.frame(5).unwrap().uv_rect,
+            Rect::new(0.25, 0.5, 0.25, 0.5)
+        );
+
+        let run = sprite_sheet.animation("run").unwrap();
+        assert_eq!(run.speed, 12.0);
+        assert!(run.looping);
+    }
+
+    #[test]
+    fn test_packed_sprite_sheet() {
+        let sprite_sheet = SpriteSheet::from_bytes(
+            br#"(
+                layout: Packed(
+                    width: 128,
+                    height: 64,
+                    frames: [(name: "idle", x: 32, y: 16, width: 64, height: 32, pivot: (0.5, 1.0))],
+                ),
+            )"#,
+            Path::new("atlas.spritesheet"),
+            &ResourceManager::new(),
+        )
+        .unwrap();
+
+        let idle = sprite_sheet.find_frame("idle").unwrap();
+        assert_eq!(idle.uv_rect, Rect::new(0.25, 0.25, 0.5, 0.5));
+        assert_eq!(idle.pivot, Vector2::new(0.5, 1.0));
+
+        assert!(matches!(
+            SpriteSheet::from_bytes(
+                br#"(
+                    layout: Grid(columns: 2, rows: 1),
+                    animations: [(name: "run", frames: [0, 2])],
+                )"#,
+                Path::new("invalid.spritesheet"),
+                &ResourceManager::new(),
+            ),
+            Err(SpriteSheetError::InvalidAnimationFrame { frame: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,38 @@
+//! Contains all possible errors that can occur during STL loading.
+
+use crate::core::io::FileLoadError;
+use std::fmt::{Display, Formatter};
+
+/// See module docs.
+#[derive(Debug)]
+pub enum StlError {
+    /// An error occurred during file loading.
+    FileLoadError(FileLoadError),
+
+    /// A line of the file could not be parsed.
+    Syntax {
+        /// Number of the line (starting from 1).
+        line: usize,
+        /// Description of the error.
+        message: String,
+    },
+}
+
+impl Display for StlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StlError::FileLoadError(v) => {
+                write!(f, "STL: File load error {v:?}.")
+            }
+            StlError::Syntax { line, message } => {
+                write!(f, "STL: Syntax error at line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for StlError {
+    fn from(err: FileLoadError) -> Self {
+        StlError::FileLoadError(err)
+    }
+}
@@ -0,0 +1,274 @@
+//! Contains all methods to load STL model format.
+//!
+//! STL is a format that is widely used by CAD software and 3D printing. It stores just a list of
+//! triangles with their normals, there are no texture coordinates, materials or hierarchies. Both
+//! flavors of the format are supported: text (ASCII) and binary. A file is loaded as a single mesh
+//! with flat shading and the standard material. CAD software often uses millimeters and Z axis as
+//! "up" direction, use [`ModelImportOptions::scale`] and [`ModelImportOptions::up_axis`] to convert
+//! such models.
+//!
+//! Normally you should never use methods from this module directly, use resource manager to load
+//! models and create their instances.
+
+pub mod error;
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        instant::Instant,
+        io,
+        log::Log,
+        math::TriangleDefinition,
+    },
+    material::{Material, SharedMaterial},
+    resource::{model::ModelImportOptions, stl::error::StlError},
+    scene::{
+        base::BaseBuilder,
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{Surface, SurfaceData, SurfaceSharedData},
+            vertex::StaticVertex,
+            MeshBuilder,
+        },
+        Scene,
+    },
+};
+use std::path::Path;
+
+const BINARY_HEADER_SIZE: usize = 84;
+const BINARY_TRIANGLE_SIZE: usize = 50;
+
+#[derive(Clone, Debug, PartialEq)]
+struct StlTriangle {
+    normal: Vector3<f32>,
+    vertices: [Vector3<f32>; 3],
+}
+
+fn syntax_error<S: Into<String>>(line: usize, message: S) -> StlError {
+    StlError::Syntax {
+        line,
+        message: message.into(),
+    }
+}
+
+fn read_vector(data: &[u8]) -> Vector3<f32> {
+    let read = |i: usize| f32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    Vector3::new(read(0), read(4), read(8))
+}
+
+// Binary files could also start with `solid` (many exporters do that), so the only reliable way
+// to distinguish the flavors is to check the size of the file.
+fn parse_binary(data: &[u8]) -> Option<Vec<StlTriangle>> {
+    let count = data.get(80..BINARY_HEADER_SIZE)?;
+    let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize;
+    if data.len() != BINARY_HEADER_SIZE + count * BINARY_TRIANGLE_SIZE {
+        return None;
+    }
+
+    Some(
+        data[BINARY_HEADER_SIZE..]
+            .chunks_exact(BINARY_TRIANGLE_SIZE)
+            .map(|triangle| StlTriangle {
+                normal: read_vector(&triangle[0..12]),
+                vertices: [
+                    read_vector(&triangle[12..24]),
+                    read_vector(&triangle[24..36]),
+                    read_vector(&triangle[36..48]),
+                ],
+            })
+            .collect(),
+    )
+}
+
+fn parse_vector<'a>(
+    mut tokens: impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<Vector3<f32>, StlError> {
+    let mut vector = Vector3::default();
+    for i in 0..3 {
+        let token = tokens
+            .next()
+            .ok_or_else(|| syntax_error(line, "Expected 3 numbers"))?;
+        vector[i] = token
+            .parse()
+            .map_err(|_| syntax_error(line, format!("Invalid number {}", token)))?;
+    }
+    Ok(vector)
+}
+
+fn parse_ascii(text: &str) -> Result<Vec<StlTriangle>, StlError> {
+    let mut triangles = Vec::new();
+    let mut normal = Vector3::default();
+    let mut vertices = Vec::with_capacity(3);
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("facet") => {
+                // `facet normal nx ny nz`
+                tokens.next();
+                normal = parse_vector(tokens, line_number)?;
+                vertices.clear();
+            }
+            Some("vertex") => vertices.push(parse_vector(tokens, line_number)?),
+            Some("endfacet") => {
+                if vertices.len() != 3 {
+                    return Err(syntax_error(
+                        line_number,
+                        "A facet must have exactly three vertices",
+                    ));
+                }
+                triangles.push(StlTriangle {
+                    normal,
+                    vertices: [vertices[0], vertices[1], vertices[2]],
+                });
+            }
+            // `solid`, `outer loop`, `endloop` and `endsolid` do not carry any data.
+            _ => (),
+        }
+    }
+
+    Ok(triangles)
+}
+
+fn parse(data: &[u8]) -> Result<Vec<StlTriangle>, StlError> {
+    match parse_binary(data) {
+        Some(triangles) => Ok(triangles),
+        None => parse_ascii(&String::from_utf8_lossy(data)),
+    }
+}
+
+fn convert(triangles: &[StlTriangle]) -> SurfaceData {
+    let mut vertices = Vec::with_capacity(triangles.len() * 3);
+    let mut indices = Vec::with_capacity(triangles.len());
+
+    for triangle in triangles {
+        let [a, b, c] = triangle.vertices;
+        // Normals in files are not reliable, some exporters just write zeros.
+        let normal = triangle
+            .normal
+            .try_normalize(f32::EPSILON)
+            .or_else(|| (b - a).cross(&(c - a)).try_normalize(f32::EPSILON))
+            .unwrap_or_else(Vector3::y);
+
+        // There are no texture coordinates, so any vector perpendicular to the normal is good
+        // enough for tangent.
+        let axis = if normal.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::y()
+        };
+        let tangent = normal.cross(&axis).normalize();
+
+        let first = vertices.len() as u32;
+        for position in triangle.vertices {
+            vertices.push(StaticVertex {
+                position,
+                tex_coord: Vector2::default(),
+                normal,
+                tangent: Vector4::new(tangent.x, tangent.y, tangent.z, 1.0),
+            });
+        }
+        indices.push(TriangleDefinition([first, first + 1, first + 2]));
+    }
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(indices),
+        false,
+    )
+}
+
+/// Tries to load and convert STL model from given path.
+///
+/// Normally you should never use this method, use resource manager to load models.
+pub async fn load_to_scene<P: AsRef<Path>>(
+    scene: &mut Scene,
+    _resource_manager: ResourceManager,
+    path: P,
+    _model_import_options: &ModelImportOptions,
+) -> Result<(), StlError> {
+    let path = path.as_ref();
+    let start_time = Instant::now();
+
+    Log::info(format!("Trying to load {:?}", path));
+
+    let data = io::load_file(path).await?;
+    let triangles = parse(&data)?;
+
+    let mut surface = Surface::new(SurfaceSharedData::new(convert(&triangles)));
+    surface.set_material(SharedMaterial::new(Material::standard()));
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    MeshBuilder::new(BaseBuilder::new().with_name(name))
+        .with_surfaces(vec![surface])
+        .build(&mut scene.graph);
+
+    Log::info(format!(
+        "STL {:?} loaded in {} ms",
+        path,
+        start_time.elapsed().as_millis()
+    ));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn triangle() -> StlTriangle {
+        StlTriangle {
+            normal: Vector3::new(0.0, 0.0, 1.0),
+            vertices: [
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 1.0, 0.0),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_ascii() {
+        let text = r"
+            solid triangle
+              facet normal 0 0 1
+                outer loop
+                  vertex 0 0 0
+                  vertex 1 0 0
+                  vertex 0 1 0
+                endloop
+              endfacet
+            endsolid triangle
+            ";
+        assert_eq!(parse(text.as_bytes()).unwrap(), vec![triangle()]);
+
+        assert!(matches!(
+            parse(b"solid\nfacet normal 0 0 1\nvertex 0 0 0\nendfacet"),
+            Err(StlError::Syntax { line: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        // Binary files may start with `solid` too.
+        let mut data = b"solid".to_vec();
+        data.resize(80, 0);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        let triangle = triangle();
+        for vector in std::iter::once(triangle.normal).chain(triangle.vertices) {
+            for component in vector.iter() {
+                data.extend_from_slice(&component.to_le_bytes());
+            }
+        }
+        data.extend_from_slice(&0u16.to_le_bytes());
+
+        assert_eq!(parse(&data).unwrap(), vec![triangle]);
+    }
+}
@@ -0,0 +1,324 @@
+//! ASTC texture compression. ASTC is supported by most modern mobile GPUs (every iOS device since
+//! A8 and most of Android devices) and gives better quality than ETC2 with the same bit rate.
+//!
+//! The encoder produces 4x4 blocks with a single partition, direct RGBA endpoints (8 bits per
+//! channel) and 2-bit weights. It is far from what offline encoders could achieve, but it is fast
+//! and does not require any external tools.
+
+use super::{ceil_div_4, read_block_rgba8};
+
+// 4x4 weight grid, single plane, weights in [0; 3] range.
+const BLOCK_MODE: u128 = 0x042;
+// LDR RGBA, direct.
+const COLOR_ENDPOINT_MODE: u128 = 12;
+// Unquantized values of 2-bit weights.
+const WEIGHTS: [i32; 4] = [0, 21, 43, 64];
+
+pub(super) fn interpolate(e0: i32, e1: i32, weight: i32) -> i32 {
+    let c0 = e0 << 8 | e0;
+    let c1 = e1 << 8 | e1;
+    ((c0 * (64 - weight) + c1 * weight + 32) >> 6) >> 8
+}
+
+fn select_weights(pixels: &[[u8; 4]; 16], e0: &[i32; 4], e1: &[i32; 4]) -> ([usize; 16], u32) {
+    let mut weights = [0; 16];
+    let mut total_error = 0;
+    for (pixel, weight) in pixels.iter().zip(weights.iter_mut()) {
+        let mut best_error = u32::MAX;
+        for (index, unquantized) in WEIGHTS.iter().enumerate() {
+            let mut error = 0;
+            for channel in 0..4 {
+                let diff =
+                    interpolate(e0[channel], e1[channel], *unquantized) - pixel[channel] as i32;
+                error += (diff * diff) as u32;
+            }
+            if error < best_error {
+                best_error = error;
+                *weight = index;
+            }
+        }
+        total_error += best_error;
+    }
+    (weights, total_error)
+}
+
+fn principal_axis(pixels: &[[u8; 4]; 16], mean: &[f32; 4]) -> [f32; 4] {
+    let mut covariance = [[0.0f32; 4]; 4];
+    for pixel in pixels {
+        for i in 0..4 {
+            for j in 0..4 {
+                covariance[i][j] += (pixel[i] as f32 - mean[i]) * (pixel[j] as f32 - mean[j]);
+            }
+        }
+    }
+
+    // Power iteration converges quickly enough for such small matrices.
+    let mut axis = [1.0f32; 4];
+    for _ in 0..8 {
+        let mut next = [0.0f32; 4];
+        for (i, next) in next.iter_mut().enumerate() {
+            for (j, axis) in axis.iter().enumerate() {
+                *next += covariance[i][j] * axis;
+            }
+        }
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length <= f32::EPSILON {
+            break;
+        }
+        axis = next.map(|v| v / length);
+    }
+    axis
+}
+
+fn initial_endpoints(pixels: &[[u8; 4]; 16]) -> ([i32; 4], [i32; 4]) {
+    let mut mean = [0.0f32; 4];
+    for pixel in pixels {
+        for channel in 0..4 {
+            mean[channel] += pixel[channel] as f32 / 16.0;
+        }
+    }
+
+    let axis = principal_axis(pixels, &mean);
+
+    let mut min = f32::MAX;
+    let mut max = f32::MIN;
+    for pixel in pixels {
+        let projection = (0..4)
+            .map(|channel| (pixel[channel] as f32 - mean[channel]) * axis[channel])
+            .sum::<f32>();
+        min = min.min(projection);
+        max = max.max(projection);
+    }
+
+    let point = |t: f32| -> [i32; 4] {
+        let mut point = [0; 4];
+        for channel in 0..4 {
+            point[channel] = (mean[channel] + axis[channel] * t)
+                .round()
+                .clamp(0.0, 255.0) as i32;
+        }
+        point
+    };
+
+    (point(min), point(max))
+}
+
+// Finds endpoints, that minimize the error for given weights, using least squares.
+fn refine_endpoints(pixels: &[[u8; 4]; 16], weights: &[usize; 16]) -> Option<([i32; 4], [i32; 4])> {
+    let mut aa = 0.0f32;
+    let mut bb = 0.0f32;
+    let mut ab = 0.0f32;
+    let mut ax = [0.0f32; 4];
+    let mut bx = [0.0f32; 4];
+    for (pixel, weight) in pixels.iter().zip(weights) {
+        let b = WEIGHTS[*weight] as f32 / 64.0;
+        let a = 1.0 - b;
+        aa += a * a;
+        bb += b * b;
+        ab += a * b;
+        for channel in 0..4 {
+            ax[channel] += a * pixel[channel] as f32;
+            bx[channel] += b * pixel[channel] as f32;
+        }
+    }
+
+    let determinant = aa * bb - ab * ab;
+    if determinant.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    let mut e0 = [0; 4];
+    let mut e1 = [0; 4];
+    for channel in 0..4 {
+        e0[channel] = ((ax[channel] * bb - bx[channel] * ab) / determinant)
+            .round()
+            .clamp(0.0, 255.0) as i32;
+        e1[channel] = ((bx[channel] * aa - ax[channel] * ab) / determinant)
+            .round()
+            .clamp(0.0, 255.0) as i32;
+    }
+    Some((e0, e1))
+}
+
+/// Encodes a single 4x4 block of RGBA pixels into ASTC block.
+fn encode_block(pixels: &[[u8; 4]; 16], refine: bool) -> u128 {
+    let (mut e0, mut e1) = initial_endpoints(pixels);
+    let (mut weights, error) = select_weights(pixels, &e0, &e1);
+
+    if refine {
+        if let Some((refined_e0, refined_e1)) = refine_endpoints(pixels, &weights) {
+            let (refined_weights, refined_error) = select_weights(pixels, &refined_e0, &refined_e1);
+            if refined_error < error {
+                e0 = refined_e0;
+                e1 = refined_e1;
+                weights = refined_weights;
+            }
+        }
+    }
+
+    // Decoder applies "blue contraction" when sum of RGB of the second endpoint is less than the
+    // first one, to prevent this the endpoints must be swapped.
+    if e1[0] + e1[1] + e1[2] < e0[0] + e0[1] + e0[2] {
+        std::mem::swap(&mut e0, &mut e1);
+        for weight in weights.iter_mut() {
+            *weight = 3 - *weight;
+        }
+    }
+
+    let mut block = BLOCK_MODE | (COLOR_ENDPOINT_MODE << 13);
+
+    // Endpoints are stored as (r0, r1, g0, g1, b0, b1, a0, a1).
+    for channel in 0..4 {
+        block |= (e0[channel] as u128) << (17 + channel * 16);
+        block |= (e1[channel] as u128) << (17 + channel * 16 + 8);
+    }
+
+    // Weights are stored in reversed bit order, starting from the most significant bit of the block.
+    for (i, weight) in weights.iter().enumerate() {
+        for bit in 0..2 {
+            block |= (((*weight >> bit) & 1) as u128) << (127 - (i * 2 + bit));
+        }
+    }
+
+    block
+}
+
+/// Encodes RGB8 or RGBA8 image into ASTC 4x4 RGBA image. `refine` enables endpoint refinement,
+/// which gives better quality at the cost of encoding speed.
+pub(super) fn encode_image_astc_4x4(
+    bytes: &[u8],
+    channels: usize,
+    width: usize,
+    height: usize,
+    refine: bool,
+) -> Vec<u8> {
+    let blocks_x = ceil_div_4(width as u32) as usize;
+    let blocks_y = ceil_div_4(height as u32) as usize;
+    let mut output = Vec::with_capacity(blocks_x * blocks_y * 16);
+    for block_y in 0..blocks_y {
+        for block_x in 0..blocks_x {
+            let pixels = read_block_rgba8(bytes, channels, width, height, block_x, block_y);
+            output.extend_from_slice(&encode_block(&pixels, refine).to_le_bytes());
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resource::texture::test::from_hex;
+
+    fn decode_block(block: u128) -> [[u8; 4]; 16] {
+        assert_eq!(block & 0x7FF, BLOCK_MODE);
+        assert_eq!((block >> 13) & 0xF, COLOR_ENDPOINT_MODE);
+
+        let value = |i: usize| ((block >> (17 + i * 8)) & 0xFF) as i32;
+        let e0 = [value(0), value(2), value(4), value(6)];
+        let e1 = [value(1), value(3), value(5), value(7)];
+        assert!(e1[0] + e1[1] + e1[2] >= e0[0] + e0[1] + e0[2]);
+
+        let mut pixels = [[0; 4]; 16];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let low = (block >> (127 - i * 2)) & 1;
+            let high = (block >> (127 - (i * 2 + 1))) & 1;
+            let weight = WEIGHTS[(low | (high << 1)) as usize];
+            for channel in 0..4 {
+                pixel[channel] = interpolate(e0[channel], e1[channel], weight) as u8;
+            }
+        }
+        pixels
+    }
+
+    // Input pixels (empty for hand-made blocks), ASTC 4x4 block and its pixels decoded by Mesa
+    // (llvmpipe), which implements the specification independently of this module. All pixels are
+    // RGBA8 in row-major order.
+    const REFERENCE_BLOCKS: [(&str, &str, &str); 9] = [
+        (
+            "282828ff464646f5646464eb828282e1323232eb505050e16e6e6ed78c8c8ccd3c3c3cd75a5a5acd787878c3969696b9464646c3646464b9828282afa0a0a0a5",
+            "42804d444d444d44e961010097a72529",
+            "262626f44e4e4ede4e4e4ede797979c7262626f44e4e4ede797979c7797979c74e4e4ede4e4e4ede797979c7a2a2a2b04e4e4ede797979c7797979c7a2a2a2b0",
+        ),
+        (
+            "c81e14ffc81e14ff0a3cdcff0a3cdcffc81e14ffc81e14ff0a3cdcff0a3cdcffc81e14ffc81e14ff0a3cdcff0a3cdcffc81e14ffc81e14ff0a3cdcff0a3cdcff",
+            "428091153c7828b8ffff01000f0f0f0f",
+            "c81e14ffc81e14ff0a3cdcff0a3cdcffc81e14ffc81e14ff0a3cdcff0a3cdcffc81e14ffc81e14ff0a3cdcff0a3cdcffc81e14ffc81e14ff0a3cdcff0a3cdcff",
+        ),
+        (
+            "faf00a00faf00f00faf01400faf01900faf00a00faf00f00faf01400faf01900055a64ff055a64ff055a64ff055a64ff055a64ff055a64ff055a64ff055a64ff",
+            "42800bf6b5e2c922fe0100000000ffff",
+            "fbf11100fbf11100fbf11100fbf11100fbf11100fbf11100fbf11100fbf11100055a64ff055a64ff055a64ff055a64ff055a64ff055a64ff055a64ff055a64ff",
+        ),
+        (
+            "dc0465aa1fad1d5adae5ac1b1e5f1370796cfd10ff19af601d04acb41d022b4678733af2df5faeb70859d1ee3910cb4895b5cc892911ff06b6622edf3cf935fd",
+            "428053580174d95d1cda01004fdaaa55",
+            "817d6ca4817d6ca4817d6ca4817d6ca4543dae57543dae57543dae57543dae57acba2eed817d6ca4543dae57543dae57817d6ca42900ec0eacba2eedacba2eed",
+        ),
+        (
+            "03fc0303fc03fcfc03fc0303fc03fcfcfc03fcfc03fc0303fc03fcfc03fc030303fc0303fc03fcfc03fc0303fc03fcfcfc03fcfc03fc0303fc03fcfc03fc0303",
+            "428007f8f90706f807f80100cc33cc33",
+            "03fc0303fc03fcfc03fc0303fc03fcfcfc03fcfc03fc0303fc03fcfc03fc030303fc0303fc03fcfc03fc0303fc03fcfcfc03fcfc03fc0303fc03fcfc03fc0303",
+        ),
+        (
+            "",
+            "42808d6eb9851deda89b0000feb5c008",
+            "46dc8ed446dc8ed46bd486a846dc8ed4b7c2764d46dc8ed446dc8ed446dc8ed46bd486a8b7c2764d92cb7e7992cb7e79b7c2764db7c2764db7c2764d6bd486a8",
+        ),
+        (
+            "",
+            "42803fc2c3a64470d45c0100162aa58a",
+            "5f5c29801f61226a5f5c29805f5c29805f5c29805f5c2980a1573098a15730981f61226a5f5c29805f5c29805f5c29801f61226aa1573098a15730985f5c2980",
+        ),
+        (
+            "",
+            "42805d6c0be57651abe400001c2a59fd",
+            "b672a872b672a872b672a8728978ae688978ae688978ae685a7fb55e8978ae682e85bb555a7fb55e5a7fb55e5a7fb55e2e85bb558978ae68b672a8722e85bb55",
+        ),
+        (
+            "",
+            "4280656974e952e0e1c8000026d7361f",
+            "b2ba29705d8baf683474f0643474f064b2ba29703474f0645d8baf6889a36a6c3474f0645d8baf685d8baf683474f064b2ba297089a36a6c5d8baf6889a36a6c",
+        ),
+    ];
+
+    #[test]
+    fn test_astc_reference_blocks() {
+        for (input, block, expected) in REFERENCE_BLOCKS {
+            let block = from_hex(block);
+            if !input.is_empty() {
+                assert_eq!(
+                    encode_image_astc_4x4(&from_hex(input), 4, 4, 4, false),
+                    block
+                );
+            }
+
+            let decoded = decode_block(u128::from_le_bytes(block[..].try_into().unwrap()));
+            for (pixel, expected) in decoded.iter().zip(from_hex(expected).chunks_exact(4)) {
+                assert_eq!(pixel[..], expected[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_astc_gradient() {
+        let mut image = Vec::new();
+        for y in 0..4u8 {
+            for x in 0..4u8 {
+                let v = x * 40 + y * 10;
+                image.extend_from_slice(&[v, 255 - v, v / 2, 255]);
+            }
+        }
+
+        for refine in [false, true] {
+            let encoded = encode_image_astc_4x4(&image, 4, 4, 4, refine);
+            assert_eq!(encoded.len(), 16);
+
+            let decoded = decode_block(u128::from_le_bytes(encoded[..].try_into().unwrap()));
+            for (pixel, expected) in decoded.iter().zip(image.chunks_exact(4)) {
+                for channel in 0..4 {
+                    assert!((pixel[channel] as i32 - expected[channel] as i32).abs() <= 24);
+                }
+            }
+        }
+    }
+}
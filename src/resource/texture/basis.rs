@@ -0,0 +1,1243 @@
+//! Basis Universal transcoder. Basis Universal textures are stored in one of two intermediate block
+//! formats, which are quickly converted at load time to a format supported by the target platform:
+//!
+//! - ETC1S is a subset of ETC1, so it is transcoded to ETC2 without any quality loss. Its blocks
+//!   are additionally compressed with BasisLZ - they're stored as indices in global codebooks of
+//!   endpoints and selectors, and the indices are Huffman-coded.
+//! - UASTC is a subset of ASTC 4x4, so it is transcoded to ASTC 4x4 without any quality loss.
+//!
+//! Both formats could also be decoded to RGBA8 pixels, which then could be compressed to any other
+//! format (or used as is).
+
+use super::{
+    astc::interpolate,
+    ceil_div_4,
+    etc2::{encode_alpha_block, expand5, pixel_bit_index, ETC1_MODIFIERS},
+    ktx2::Ktx2Error,
+};
+
+/// Reads bits from a byte stream, starting from the least significant bit of the first byte.
+/// Reading past the end of the stream gives zeros.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn read(&mut self, count: u32) -> u32 {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = self
+                .data
+                .get(self.position / 8)
+                .copied()
+                .unwrap_or_default();
+            value |= (((byte >> (self.position % 8)) & 1) as u32) << i;
+            self.position += 1;
+        }
+        value
+    }
+
+    /// Reads a number, that is stored in chunks of `chunk_bits` bits, each chunk is followed by a
+    /// bit that tells whether there are more chunks.
+    fn read_vlc(&mut self, chunk_bits: u32) -> Result<u32, Ktx2Error> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let chunk = self.read(chunk_bits + 1);
+            value |= (chunk & ((1 << chunk_bits) - 1)) << shift;
+            if chunk >> chunk_bits == 0 {
+                return Ok(value);
+            }
+            shift += chunk_bits;
+            if shift >= 32 {
+                return Err(Ktx2Error::InvalidData);
+            }
+        }
+    }
+
+    fn read_huffman_table(&mut self) -> Result<HuffmanTable, Ktx2Error> {
+        let symbol_count = self.read(14) as usize;
+        if symbol_count == 0 {
+            return HuffmanTable::new(&[]);
+        }
+
+        // Code lengths are Huffman-coded too.
+        let code_length_code_count = self.read(5) as usize;
+        if !(1..=CODE_LENGTH_CODE_ORDER.len()).contains(&code_length_code_count) {
+            return Err(Ktx2Error::InvalidData);
+        }
+        let mut code_length_code_lengths = [0; CODE_LENGTH_CODE_ORDER.len()];
+        for code in &CODE_LENGTH_CODE_ORDER[..code_length_code_count] {
+            code_length_code_lengths[*code] = self.read(3) as u8;
+        }
+        let code_length_table = HuffmanTable::new(&code_length_code_lengths)?;
+
+        let mut lengths = vec![0; symbol_count];
+        let mut i = 0;
+        while i < symbol_count {
+            let (length, count) = match code_length_table.decode(self)? {
+                length @ 0..=16 => (length as u8, 1),
+                17 => (0, self.read(3) as usize + 3),
+                18 => (0, self.read(7) as usize + 11),
+                code => {
+                    // Repeats the previous non-zero length.
+                    let previous = match i.checked_sub(1).map(|previous| lengths[previous]) {
+                        Some(previous) if previous != 0 => previous,
+                        _ => return Err(Ktx2Error::InvalidData),
+                    };
+                    let count = if code == 19 {
+                        self.read(2) + 3
+                    } else {
+                        self.read(7) + 7
+                    };
+                    (previous, count as usize)
+                }
+            };
+            let end = i + count;
+            lengths
+                .get_mut(i..end)
+                .ok_or(Ktx2Error::InvalidData)?
+                .fill(length);
+            i = end;
+        }
+
+        HuffmanTable::new(&lengths)
+    }
+}
+
+// Order in which lengths of code length codes are stored, the least used codes go last.
+const CODE_LENGTH_CODE_ORDER: [usize; 21] = [
+    17, 18, 19, 20, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15, 16,
+];
+
+/// Canonical Huffman code with codes up to 16 bits long.
+struct HuffmanTable {
+    // Amount of codes of each length.
+    counts: [u16; 17],
+    // Symbols sorted by their code lengths.
+    symbols: Vec<u16>,
+}
+
+impl HuffmanTable {
+    fn new(lengths: &[u8]) -> Result<Self, Ktx2Error> {
+        let mut counts = [0; 17];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // Over-subscribed set of lengths can't be decoded unambiguously.
+        let mut left = 1;
+        for count in &counts[1..] {
+            left = left * 2 - *count as i32;
+            if left < 0 {
+                return Err(Ktx2Error::InvalidData);
+            }
+        }
+
+        let mut offsets = [0; 17];
+        for length in 1..16 {
+            offsets[length + 1] = offsets[length] + counts[length] as usize;
+        }
+        let mut symbols = vec![0; offsets[16] + counts[16] as usize];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize]] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u32, Ktx2Error> {
+        // Codes are stored starting from their most significant bit.
+        let mut code = 0;
+        let mut first = 0;
+        let mut index = 0;
+        for count in &self.counts[1..] {
+            code |= reader.read(1) as i32;
+            let count = *count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize] as u32);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(Ktx2Error::InvalidData)
+    }
+}
+
+/// ETC1S endpoint - 5-bit base color and index of intensity modifier table.
+#[derive(Copy, Clone, Default)]
+struct Endpoint {
+    color: [u8; 3],
+    intensity: u8,
+}
+
+// Maps selectors, which are sorted from the lowest modifier to the highest one, to ETC1 pixel
+// indices.
+const SELECTOR_TO_ETC1: [usize; 4] = [3, 2, 0, 1];
+
+const ENDPOINT_PRED_REPEAT_LAST_SYMBOL: u32 = 256;
+const ENDPOINT_PRED_MIN_REPEAT_COUNT: u32 = 3;
+const SELECTOR_HISTORY_RLE_MIN_COUNT: usize = 3;
+const SELECTOR_HISTORY_RLE_LONG_RUN_SYMBOL: u32 = 63;
+
+/// Approximate move-to-front list of recently used selectors.
+struct SelectorHistory {
+    values: Vec<usize>,
+    rover: usize,
+}
+
+impl SelectorHistory {
+    fn new(size: usize) -> Self {
+        Self {
+            values: vec![0; size],
+            rover: size / 2,
+        }
+    }
+
+    fn add(&mut self, value: usize) {
+        self.values[self.rover] = value;
+        self.rover += 1;
+        if self.rover == self.values.len() {
+            self.rover = self.values.len() / 2;
+        }
+    }
+
+    fn get(&mut self, index: usize) -> Result<usize, Ktx2Error> {
+        let value = *self.values.get(index).ok_or(Ktx2Error::InvalidData)?;
+        // Moves the value closer to the front.
+        self.values.swap(index / 2, index);
+        Ok(value)
+    }
+}
+
+/// Endpoint and selector indices of an ETC1S block.
+#[derive(Copy, Clone)]
+pub(super) struct Etc1sBlock {
+    endpoint: usize,
+    selector: usize,
+}
+
+/// Codebooks and Huffman tables shared by all ETC1S images of a texture.
+pub(super) struct Etc1sCodebook {
+    endpoints: Vec<Endpoint>,
+    // Each selector is stored as 4 rows of 2-bit values.
+    selectors: Vec<[u8; 4]>,
+    endpoint_pred_table: HuffmanTable,
+    delta_endpoint_table: HuffmanTable,
+    selector_table: HuffmanTable,
+    selector_history_rle_table: HuffmanTable,
+    selector_history_size: usize,
+}
+
+fn read_endpoints(count: usize, data: &[u8]) -> Result<Vec<Endpoint>, Ktx2Error> {
+    let mut reader = BitReader::new(data);
+    let color_delta_tables = [
+        reader.read_huffman_table()?,
+        reader.read_huffman_table()?,
+        reader.read_huffman_table()?,
+    ];
+    let intensity_delta_table = reader.read_huffman_table()?;
+    let grayscale = reader.read(1) == 1;
+
+    // Endpoints are delta-coded, the table for color delta is selected by the previous value.
+    let mut previous = Endpoint {
+        color: [16; 3],
+        intensity: 0,
+    };
+    let mut endpoints = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut endpoint = Endpoint {
+            intensity: ((intensity_delta_table.decode(&mut reader)? + previous.intensity as u32)
+                & 7) as u8,
+            ..Default::default()
+        };
+        for channel in 0..if grayscale { 1 } else { 3 } {
+            let table = match previous.color[channel] {
+                0..=9 => &color_delta_tables[0],
+                10..=21 => &color_delta_tables[1],
+                _ => &color_delta_tables[2],
+            };
+            endpoint.color[channel] =
+                ((table.decode(&mut reader)? + previous.color[channel] as u32) & 31) as u8;
+        }
+        if grayscale {
+            endpoint.color = [endpoint.color[0]; 3];
+        }
+        endpoints.push(endpoint);
+        previous = endpoint;
+    }
+    Ok(endpoints)
+}
+
+fn read_selectors(count: usize, data: &[u8]) -> Result<Vec<[u8; 4]>, Ktx2Error> {
+    let mut reader = BitReader::new(data);
+    // Global and hybrid selector codebooks are deprecated and aren't produced by modern encoders.
+    if reader.read(1) == 1 || reader.read(1) == 1 {
+        return Err(Ktx2Error::BasisUniversal);
+    }
+
+    let mut selectors = Vec::with_capacity(count);
+    if reader.read(1) == 1 {
+        for _ in 0..count {
+            selectors.push([0; 4].map(|_| reader.read(8) as u8));
+        }
+    } else {
+        // Each row of a selector is XOR-ed with the same row of the previous selector.
+        let delta_table = reader.read_huffman_table()?;
+        let mut previous = [0; 4];
+        for i in 0..count {
+            for row in previous.iter_mut() {
+                *row = if i == 0 {
+                    reader.read(8) as u8
+                } else {
+                    *row ^ delta_table.decode(&mut reader)? as u8
+                };
+            }
+            selectors.push(previous);
+        }
+    }
+    Ok(selectors)
+}
+
+impl Etc1sCodebook {
+    pub(super) fn new(
+        endpoint_count: usize,
+        endpoints: &[u8],
+        selector_count: usize,
+        selectors: &[u8],
+        tables: &[u8],
+    ) -> Result<Self, Ktx2Error> {
+        let mut reader = BitReader::new(tables);
+        let codebook = Self {
+            endpoints: read_endpoints(endpoint_count, endpoints)?,
+            selectors: read_selectors(selector_count, selectors)?,
+            endpoint_pred_table: reader.read_huffman_table()?,
+            delta_endpoint_table: reader.read_huffman_table()?,
+            selector_table: reader.read_huffman_table()?,
+            selector_history_rle_table: reader.read_huffman_table()?,
+            selector_history_size: reader.read(13) as usize,
+        };
+        if codebook.endpoints.is_empty()
+            || codebook.selectors.is_empty()
+            || codebook.selector_history_size == 0
+        {
+            return Err(Ktx2Error::InvalidData);
+        }
+        Ok(codebook)
+    }
+
+    /// Decodes endpoint and selector indices of every block of a slice (an image or its alpha
+    /// channel).
+    pub(super) fn decode_slice(
+        &self,
+        data: &[u8],
+        blocks_x: usize,
+        blocks_y: usize,
+    ) -> Result<Vec<Etc1sBlock>, Ktx2Error> {
+        let mut reader = BitReader::new(data);
+        let mut blocks = Vec::with_capacity(blocks_x * blocks_y);
+
+        // Endpoint indices are predicted from neighbour blocks, predictors of each 2x2 group of
+        // blocks are coded as a single symbol. Predictors of the second row of the group are kept
+        // until the next row.
+        let mut next_row_pred_bits = vec![0; blocks_x];
+        let mut upper_endpoints = vec![0; blocks_x];
+        let mut current_endpoints = vec![0; blocks_x];
+        let mut pred_bits = 0;
+        let mut last_pred_symbol = 0;
+        let mut pred_repeat_count = 0;
+        let mut previous_endpoint = 0;
+
+        let mut selector_history = SelectorHistory::new(self.selector_history_size);
+        let selector_history_rle_symbol = self.selectors.len() + self.selector_history_size;
+        let mut selector_repeat_count = 0;
+
+        for y in 0..blocks_y {
+            for x in 0..blocks_x {
+                if x % 2 == 0 {
+                    if y % 2 == 0 {
+                        if pred_repeat_count > 0 {
+                            pred_repeat_count -= 1;
+                            pred_bits = last_pred_symbol;
+                        } else {
+                            let symbol = self.endpoint_pred_table.decode(&mut reader)?;
+                            if symbol == ENDPOINT_PRED_REPEAT_LAST_SYMBOL {
+                                pred_repeat_count =
+                                    reader.read_vlc(4)? + ENDPOINT_PRED_MIN_REPEAT_COUNT - 1;
+                                pred_bits = last_pred_symbol;
+                            } else {
+                                pred_bits = symbol;
+                                last_pred_symbol = symbol;
+                            }
+                        }
+                        next_row_pred_bits[x] = pred_bits >> 4;
+                    } else {
+                        pred_bits = next_row_pred_bits[x];
+                    }
+                }
+
+                let endpoint = match pred_bits & 3 {
+                    // Left.
+                    0 if x > 0 => previous_endpoint,
+                    // Upper.
+                    1 if y > 0 => upper_endpoints[x],
+                    // Upper left.
+                    2 if x > 0 && y > 0 => upper_endpoints[x - 1],
+                    3 => {
+                        let endpoint = previous_endpoint
+                            + self.delta_endpoint_table.decode(&mut reader)? as usize;
+                        if endpoint >= self.endpoints.len() {
+                            endpoint - self.endpoints.len()
+                        } else {
+                            endpoint
+                        }
+                    }
+                    _ => return Err(Ktx2Error::InvalidData),
+                };
+                pred_bits >>= 2;
+                current_endpoints[x] = endpoint;
+                previous_endpoint = endpoint;
+
+                // Selectors are either coded directly, or as an index in the history of recently
+                // used selectors. The first selector of the history could be repeated many times
+                // using run-length encoding.
+                let selector = if selector_repeat_count > 0 {
+                    selector_repeat_count -= 1;
+                    selector_history.get(0)?
+                } else {
+                    let symbol = self.selector_table.decode(&mut reader)? as usize;
+                    if symbol == selector_history_rle_symbol {
+                        let run = self.selector_history_rle_table.decode(&mut reader)?;
+                        selector_repeat_count = SELECTOR_HISTORY_RLE_MIN_COUNT - 1
+                            + if run == SELECTOR_HISTORY_RLE_LONG_RUN_SYMBOL {
+                                reader.read_vlc(7)? as usize
+                            } else {
+                                run as usize
+                            };
+                        if selector_repeat_count >= blocks_x * blocks_y {
+                            return Err(Ktx2Error::InvalidData);
+                        }
+                        selector_history.get(0)?
+                    } else if symbol >= self.selectors.len() {
+                        selector_history.get(symbol - self.selectors.len())?
+                    } else {
+                        selector_history.add(symbol);
+                        symbol
+                    }
+                };
+
+                if endpoint >= self.endpoints.len() || selector >= self.selectors.len() {
+                    return Err(Ktx2Error::InvalidData);
+                }
+                blocks.push(Etc1sBlock { endpoint, selector });
+            }
+            std::mem::swap(&mut upper_endpoints, &mut current_endpoints);
+        }
+
+        Ok(blocks)
+    }
+
+    fn etc1_block(&self, block: Etc1sBlock) -> u64 {
+        let endpoint = self.endpoints[block.endpoint];
+        let selector = self.selectors[block.selector];
+
+        // Differential mode with zero offset, so both sub-blocks have the same color and table.
+        let intensity = endpoint.intensity as u64;
+        let mut etc1 = (intensity << 37) | (intensity << 34) | (1 << 33);
+        for (channel, color) in endpoint.color.iter().enumerate() {
+            etc1 |= (*color as u64) << (59 - channel * 8);
+        }
+        for (y, row) in selector.iter().enumerate() {
+            for x in 0..4 {
+                let index = SELECTOR_TO_ETC1[((row >> (x * 2)) & 3) as usize] as u64;
+                let bit = pixel_bit_index(x, y);
+                etc1 |= (index >> 1) << (16 + bit);
+                etc1 |= (index & 1) << bit;
+            }
+        }
+        etc1
+    }
+
+    fn decode_block(&self, block: Etc1sBlock) -> [[u8; 4]; 16] {
+        let endpoint = self.endpoints[block.endpoint];
+        let selector = self.selectors[block.selector];
+        let mut pixels = [[255; 4]; 16];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let index = SELECTOR_TO_ETC1[((selector[i / 4] >> ((i % 4) * 2)) & 3) as usize];
+            let modifier = ETC1_MODIFIERS[endpoint.intensity as usize][index];
+            for (value, color) in pixel.iter_mut().zip(endpoint.color) {
+                *value = (expand5(color as i32) + modifier).clamp(0, 255) as u8;
+            }
+        }
+        pixels
+    }
+
+    /// Decodes color and (optional) alpha blocks into RGBA pixels. Alpha slices store alpha as
+    /// grayscale colors.
+    fn decode_block_rgba(&self, color: Etc1sBlock, alpha: Option<Etc1sBlock>) -> [[u8; 4]; 16] {
+        let mut pixels = self.decode_block(color);
+        if let Some(alpha) = alpha {
+            for (pixel, alpha) in pixels.iter_mut().zip(self.decode_block(alpha)) {
+                pixel[3] = alpha[1];
+            }
+        }
+        pixels
+    }
+
+    /// Transcodes ETC1S image into ETC2 RGB image, or into ETC2 RGBA image if it has alpha slice.
+    pub(super) fn transcode_etc2(
+        &self,
+        color: &[Etc1sBlock],
+        alpha: Option<&[Etc1sBlock]>,
+    ) -> Vec<u8> {
+        let mut output = Vec::with_capacity(color.len() * if alpha.is_some() { 16 } else { 8 });
+        for (i, block) in color.iter().enumerate() {
+            if let Some(alpha) = alpha {
+                let pixels = self.decode_block_rgba(*block, Some(alpha[i]));
+                output.extend_from_slice(&encode_alpha_block(&pixels).to_be_bytes());
+            }
+            output.extend_from_slice(&self.etc1_block(*block).to_be_bytes());
+        }
+        output
+    }
+
+    /// Decodes ETC1S image into RGBA8 pixels.
+    pub(super) fn decode_rgba8(
+        &self,
+        color: &[Etc1sBlock],
+        alpha: Option<&[Etc1sBlock]>,
+        width: usize,
+        height: usize,
+    ) -> Vec<u8> {
+        let mut image = vec![0; width * height * 4];
+        let blocks_x = ceil_div_4(width as u32) as usize;
+        for (i, block) in color.iter().enumerate() {
+            let pixels = self.decode_block_rgba(*block, alpha.map(|alpha| alpha[i]));
+            write_block(
+                &pixels,
+                &mut image,
+                width,
+                height,
+                i % blocks_x,
+                i / blocks_x,
+            );
+        }
+        image
+    }
+}
+
+fn write_block(
+    pixels: &[[u8; 4]; 16],
+    image: &mut [u8],
+    width: usize,
+    height: usize,
+    block_x: usize,
+    block_y: usize,
+) {
+    for (i, pixel) in pixels.iter().enumerate() {
+        let x = block_x * 4 + i % 4;
+        let y = block_y * 4 + i / 4;
+        if x < width && y < height {
+            let offset = (y * width + x) * 4;
+            image[offset..offset + 4].copy_from_slice(pixel);
+        }
+    }
+}
+
+type UastcMode = (u32, u32, u32, u32, usize, usize, usize, usize);
+
+// Modes of UASTC blocks: Huffman code of the mode (stored from the least significant bit), its
+// length, amount of hint bits for transcoding to other formats (not needed for ASTC), bits per
+// weight, ASTC endpoint range, amount of subsets, amount of weight planes and color components.
+// Mode 8 is a solid color block.
+#[rustfmt::skip]
+const UASTC_MODES: [UastcMode; 19] = [
+    (0x01, 4, 15, 4, 19, 1, 1, 3),
+    (0x35, 6, 15, 2, 20, 1, 1, 3),
+    (0x1D, 5, 15, 3, 8, 2, 1, 3),
+    (0x03, 5, 15, 2, 7, 3, 1, 3),
+    (0x13, 5, 15, 2, 12, 2, 1, 3),
+    (0x0B, 5, 15, 3, 20, 1, 1, 3),
+    (0x1B, 5, 15, 2, 18, 1, 2, 3),
+    (0x07, 5, 15, 2, 12, 2, 1, 3),
+    (0x17, 5, 0, 0, 0, 0, 0, 4),
+    (0x0F, 5, 23, 2, 8, 2, 1, 4),
+    (0x02, 3, 17, 4, 13, 1, 1, 4),
+    (0x00, 2, 17, 2, 13, 1, 2, 4),
+    (0x06, 3, 17, 3, 19, 1, 1, 4),
+    (0x1F, 5, 23, 1, 20, 1, 2, 4),
+    (0x0D, 5, 23, 2, 20, 1, 1, 4),
+    (0x05, 7, 23, 4, 20, 1, 1, 2),
+    (0x15, 6, 23, 2, 20, 2, 1, 2),
+    (0x25, 6, 23, 2, 20, 1, 2, 2),
+    (0x09, 4, 15, 5, 11, 1, 1, 3),
+];
+
+const UASTC_SOLID_COLOR_MODE: usize = 8;
+
+// ASTC partition seeds of 2-subset patterns that match BC7 2-subset patterns.
+const PARTITIONS_2: [u32; 30] = [
+    28, 20, 16, 29, 91, 9, 107, 72, 149, 204, 50, 114, 496, 17, 78, 39, 252, 828, 43, 156, 116,
+    210, 476, 273, 684, 359, 246, 195, 694, 524,
+];
+
+// ASTC partition seeds of 3-subset patterns that match BC7 3-subset patterns.
+const PARTITIONS_3: [u32; 11] = [260, 74, 32, 156, 183, 15, 745, 0, 335, 902, 254];
+
+// ASTC partition seeds of 2-subset patterns that match BC7 3-subset patterns with two merged
+// subsets.
+const PARTITIONS_2_FROM_3: [u32; 19] = [
+    36, 48, 61, 137, 161, 183, 226, 281, 302, 307, 479, 495, 593, 594, 605, 799, 812, 988, 993,
+];
+
+fn uastc_partitions(mode: usize) -> &'static [u32] {
+    match mode {
+        2 | 4 | 9 | 16 => &PARTITIONS_2,
+        3 => &PARTITIONS_3,
+        7 => &PARTITIONS_2_FROM_3,
+        _ => &[],
+    }
+}
+
+// Amount of bits, trits and quints of each ASTC integer sequence encoding range.
+const BISE_RANGES: [(u32, u32, u32); 21] = [
+    (1, 0, 0),
+    (0, 1, 0),
+    (2, 0, 0),
+    (0, 0, 1),
+    (1, 1, 0),
+    (3, 0, 0),
+    (1, 0, 1),
+    (2, 1, 0),
+    (4, 0, 0),
+    (2, 0, 1),
+    (3, 1, 0),
+    (5, 0, 0),
+    (3, 0, 1),
+    (4, 1, 0),
+    (6, 0, 0),
+    (4, 0, 1),
+    (5, 1, 0),
+    (7, 0, 0),
+    (5, 0, 1),
+    (6, 1, 0),
+    (8, 0, 0),
+];
+
+const fn decode_trits(packed: u32) -> [u32; 5] {
+    let (c, t4, t3) = if (packed >> 2) & 7 == 7 {
+        ((((packed >> 5) & 7) << 2) | (packed & 3), 2, 2)
+    } else if (packed >> 5) & 3 == 3 {
+        (packed & 0x1F, 2, (packed >> 7) & 1)
+    } else {
+        (packed & 0x1F, (packed >> 7) & 1, (packed >> 5) & 3)
+    };
+    let (t2, t1, t0) = if c & 3 == 3 {
+        (2, (c >> 4) & 1, (c >> 2) & 2 | ((c >> 2) & !(c >> 3) & 1))
+    } else if (c >> 2) & 3 == 3 {
+        (2, 2, c & 3)
+    } else {
+        ((c >> 4) & 1, (c >> 2) & 3, (c & 2) | (c & !(c >> 1) & 1))
+    };
+    [t0, t1, t2, t3, t4]
+}
+
+const fn decode_quints(packed: u32) -> [u32; 3] {
+    if (packed >> 1) & 3 == 3 && (packed >> 5) & 3 == 0 {
+        let q2 = ((packed & 1) << 2)
+            | ((((packed >> 4) & !packed) & 1) << 1)
+            | ((packed >> 3) & !packed & 1);
+        return [4, 4, q2];
+    }
+    let (c, q2) = if (packed >> 1) & 3 == 3 {
+        (
+            (((packed >> 3) & 3) << 3) | ((!(packed >> 5) & 3) << 1) | (packed & 1),
+            4,
+        )
+    } else {
+        (packed & 0x1F, (packed >> 5) & 3)
+    };
+    if c & 7 == 5 {
+        [(c >> 3) & 3, 4, q2]
+    } else {
+        [c & 7, (c >> 3) & 3, q2]
+    }
+}
+
+// Packed representations of every combination of 5 trits (the first trit is the least
+// significant digit). The smallest representation is used, so trailing zero trits could be
+// omitted.
+const TRIT_ENCODING: [u8; 243] = {
+    let mut table = [0; 243];
+    let mut packed = 256;
+    while packed > 0 {
+        packed -= 1;
+        let t = decode_trits(packed);
+        table[(t[0] + 3 * t[1] + 9 * t[2] + 27 * t[3] + 81 * t[4]) as usize] = packed as u8;
+    }
+    table
+};
+
+// Packed representations of every combination of 3 quints.
+const QUINT_ENCODING: [u8; 125] = {
+    let mut table = [0; 125];
+    let mut packed = 128;
+    while packed > 0 {
+        packed -= 1;
+        let q = decode_quints(packed);
+        table[(q[0] + 5 * q[1] + 25 * q[2]) as usize] = packed as u8;
+    }
+    table
+};
+
+/// Writes values (trit or quint is stored above bits of each value) in ASTC integer sequence
+/// encoding.
+fn write_bise(block: &mut u128, position: &mut u32, values: &[u32], range: usize) {
+    let (bits, trits, quints) = BISE_RANGES[range];
+    let mut write = |value: u32, count: u32| {
+        *block |= ((value & ((1 << count) - 1)) as u128) << *position;
+        *position += count;
+    };
+
+    // Positions of packed trits (quints) bits after each value of a group.
+    let (group_size, packed_bits): (usize, &[(u32, u32)]) = if trits != 0 {
+        (5, &[(0, 2), (2, 2), (4, 1), (5, 2), (7, 1)])
+    } else if quints != 0 {
+        (3, &[(0, 3), (3, 2), (5, 2)])
+    } else {
+        (1, &[(0, 0)])
+    };
+
+    for group in values.chunks(group_size) {
+        let mut digits = [0; 5];
+        for (digit, value) in digits.iter_mut().zip(group) {
+            *digit = value >> bits;
+        }
+        let packed = if trits != 0 {
+            TRIT_ENCODING[(digits[0]
+                + 3 * digits[1]
+                + 9 * digits[2]
+                + 27 * digits[3]
+                + 81 * digits[4]) as usize] as u32
+        } else if quints != 0 {
+            QUINT_ENCODING[(digits[0] + 5 * digits[1] + 25 * digits[2]) as usize] as u32
+        } else {
+            0
+        };
+        for (value, (shift, count)) in group.iter().zip(packed_bits) {
+            write(*value, bits);
+            write(packed >> shift, *count);
+        }
+    }
+}
+
+fn unquantize_color(value: u32, range: usize) -> i32 {
+    let (bits, trits, quints) = BISE_RANGES[range];
+    if trits + quints == 0 {
+        return replicate(value, bits, 8);
+    }
+
+    let low = value & ((1 << bits) - 1);
+    let digit = value >> bits;
+    let bit = |n: u32| (low >> n) & 1;
+    let a = if low & 1 == 1 { 0x1FF } else { 0 };
+    let (b, c) = if trits != 0 {
+        match bits {
+            1 => (0, 204),
+            2 => (bit(1) * 0b100010110, 93),
+            3 => (bit(2) * 0b100001010 + bit(1) * 0b010000101, 44),
+            4 => (
+                bit(3) * 0b100000100 + bit(2) * 0b010000010 + bit(1) * 0b001000001,
+                22,
+            ),
+            5 => (
+                bit(4) * 0b100000010
+                    + bit(3) * 0b010000001
+                    + bit(2) * 0b001000000
+                    + bit(1) * 0b000100000,
+                11,
+            ),
+            _ => (
+                bit(5) * 0b100000001
+                    + bit(4) * 0b010000000
+                    + bit(3) * 0b001000000
+                    + bit(2) * 0b000100000
+                    + bit(1) * 0b000010000,
+                5,
+            ),
+        }
+    } else {
+        match bits {
+            1 => (0, 113),
+            2 => (bit(1) * 0b100001100, 54),
+            3 => (bit(2) * 0b100000101 + bit(1) * 0b010000010, 26),
+            4 => (
+                bit(3) * 0b100000010 + bit(2) * 0b010000001 + bit(1) * 0b001000000,
+                13,
+            ),
+            _ => (
+                bit(4) * 0b100000001
+                    + bit(3) * 0b010000000
+                    + bit(2) * 0b001000000
+                    + bit(1) * 0b000100000,
+                6,
+            ),
+        }
+    };
+    let t = (digit * c + b) ^ a;
+    ((a & 0x80) | (t >> 2)) as i32
+}
+
+fn unquantize_weight(value: u32, bits: u32) -> i32 {
+    let weight = replicate(value, bits, 6);
+    if weight > 32 {
+        weight + 1
+    } else {
+        weight
+    }
+}
+
+/// Expands a value by replicating its bits.
+fn replicate(value: u32, bits: u32, target_bits: u32) -> i32 {
+    let mut result = 0;
+    let mut shift = target_bits as i32;
+    while shift > 0 {
+        shift -= bits as i32;
+        result |= if shift >= 0 {
+            value << shift
+        } else {
+            value >> -shift
+        };
+    }
+    result as i32
+}
+
+fn hash52(mut value: u32) -> u32 {
+    value ^= value >> 15;
+    value = value.wrapping_mul(0xEEDE0891);
+    value ^= value >> 5;
+    value = value.wrapping_add(value << 16);
+    value ^= value >> 7;
+    value ^= value >> 3;
+    value ^= value << 6;
+    value ^= value >> 17;
+    value
+}
+
+/// Calculates subset of every texel of a 4x4 block from ASTC partition seed.
+fn partition_pattern(seed: u32, subsets: usize) -> [usize; 16] {
+    let mut pattern = [0; 16];
+    if subsets < 2 {
+        return pattern;
+    }
+
+    let seed = seed + (subsets as u32 - 1) * 1024;
+    let random = hash52(seed);
+    let (shift1, shift2) = if seed & 1 == 1 {
+        (
+            if seed & 2 == 2 { 4 } else { 5 },
+            if subsets == 3 { 6 } else { 5 },
+        )
+    } else {
+        (
+            if subsets == 3 { 6 } else { 5 },
+            if seed & 2 == 2 { 4 } else { 5 },
+        )
+    };
+    let factor = |shift: u32, shifted_by: u32| {
+        let value = (random >> shift) & 0xF;
+        (value * value) >> shifted_by
+    };
+    let (a1, a2) = (factor(0, shift1), factor(4, shift2));
+    let (b1, b2) = (factor(8, shift1), factor(12, shift2));
+    let (c1, c2) = (factor(16, shift1), factor(20, shift2));
+
+    for (i, subset) in pattern.iter_mut().enumerate() {
+        // Small blocks use doubled coordinates.
+        let x = (i as u32 % 4) * 2;
+        let y = (i as u32 / 4) * 2;
+        let a = (a1 * x + a2 * y + (random >> 14)) & 0x3F;
+        let b = (b1 * x + b2 * y + (random >> 10)) & 0x3F;
+        let c = if subsets == 3 {
+            (c1 * x + c2 * y + (random >> 6)) & 0x3F
+        } else {
+            0
+        };
+        *subset = if a >= b && a >= c {
+            0
+        } else if b >= c {
+            1
+        } else {
+            2
+        };
+    }
+    pattern
+}
+
+/// UASTC block with all fields unpacked, endpoints and weights are stored quantized, the same
+/// way as ASTC stores them.
+struct UastcBlock {
+    weight_bits: u32,
+    endpoint_range: usize,
+    subsets: usize,
+    planes: usize,
+    components: usize,
+    partition_seed: u32,
+    // Color component that uses the second plane of weights.
+    plane2_component: u32,
+    endpoints: [u32; 18],
+    // Weights of both planes are interleaved.
+    weights: [u32; 32],
+}
+
+#[allow(clippy::large_enum_variant)]
+enum Uastc {
+    Solid([u8; 4]),
+    Block(UastcBlock),
+}
+
+fn unpack_uastc(block: &[u8]) -> Result<Uastc, Ktx2Error> {
+    let bits = u128::from_le_bytes(block.try_into().map_err(|_| Ktx2Error::InvalidData)?);
+    let mode = UASTC_MODES
+        .iter()
+        .position(|(code, length, ..)| bits & ((1 << length) - 1) == *code as u128)
+        .ok_or(Ktx2Error::InvalidData)?;
+    let (_, code_length, hint_bits, weight_bits, endpoint_range, subsets, planes, components) =
+        UASTC_MODES[mode];
+
+    let mut position = code_length;
+    let mut read = |count: u32| {
+        let value = ((bits >> position) & ((1 << count) - 1)) as u32;
+        position += count;
+        value
+    };
+
+    if mode == UASTC_SOLID_COLOR_MODE {
+        return Ok(Uastc::Solid([0; 4].map(|_| read(8) as u8)));
+    }
+
+    read(hint_bits);
+
+    let partitions = uastc_partitions(mode);
+    let partition_seed = if partitions.is_empty() {
+        0
+    } else {
+        let index_bits = u32::BITS - (partitions.len() as u32 - 1).leading_zeros();
+        *partitions
+            .get(read(index_bits) as usize)
+            .ok_or(Ktx2Error::InvalidData)?
+    };
+
+    let plane2_component = match (planes, components) {
+        // Alpha of luminance-alpha blocks.
+        (2, 2) => 3,
+        (2, _) => read(2),
+        _ => 0,
+    };
+
+    // Trits (quints) of all endpoints are stored first, each group of up to 5 trits (3 quints) is
+    // stored as a base-3 (base-5) number.
+    let endpoint_count = components * 2 * subsets;
+    let (endpoint_bits, trits, quints) = BISE_RANGES[endpoint_range];
+    let mut digits = [0; 18];
+    if trits + quints != 0 {
+        let (base, group_size, group_bits): (u32, usize, &[u32]) = if trits != 0 {
+            (3, 5, &[0, 2, 4, 5, 7, 8])
+        } else {
+            (5, 3, &[0, 3, 5, 7])
+        };
+        for group in digits[..endpoint_count].chunks_mut(group_size) {
+            let mut packed = read(group_bits[group.len()]);
+            for digit in group {
+                *digit = packed % base;
+                packed /= base;
+            }
+        }
+    }
+    let mut endpoints = [0; 18];
+    for (endpoint, digit) in endpoints[..endpoint_count].iter_mut().zip(digits) {
+        *endpoint = read(endpoint_bits) | (digit << endpoint_bits);
+    }
+
+    // The first texel of each subset is an "anchor", the most significant bit of its weight is
+    // always zero and isn't stored.
+    let pattern = partition_pattern(partition_seed, subsets);
+    let mut weights = [0; 32];
+    for (i, weight) in weights[..16 * planes].iter_mut().enumerate() {
+        let texel = i / planes;
+        let anchor = !pattern[..texel].contains(&pattern[texel]);
+        *weight = read(weight_bits - anchor as u32);
+    }
+
+    Ok(Uastc::Block(UastcBlock {
+        weight_bits,
+        endpoint_range,
+        subsets,
+        planes,
+        components,
+        partition_seed,
+        plane2_component,
+        endpoints,
+        weights,
+    }))
+}
+
+fn blue_contract(color: [i32; 4]) -> [i32; 4] {
+    [
+        (color[0] + color[2]) >> 1,
+        (color[1] + color[2]) >> 1,
+        color[2],
+        color[3],
+    ]
+}
+
+fn decode_endpoints(values: &[i32], components: usize) -> ([i32; 4], [i32; 4]) {
+    if components == 2 {
+        return (
+            [values[0], values[0], values[0], values[2]],
+            [values[1], values[1], values[1], values[3]],
+        );
+    }
+
+    let (a0, a1) = if components == 4 {
+        (values[6], values[7])
+    } else {
+        (255, 255)
+    };
+    let e0 = [values[0], values[2], values[4], a0];
+    let e1 = [values[1], values[3], values[5], a1];
+    if values[1] + values[3] + values[5] >= values[0] + values[2] + values[4] {
+        (e0, e1)
+    } else {
+        (blue_contract(e1), blue_contract(e0))
+    }
+}
+
+fn decode_uastc(block: &Uastc) -> [[u8; 4]; 16] {
+    let block = match block {
+        Uastc::Solid(color) => return [*color; 16],
+        Uastc::Block(block) => block,
+    };
+
+    let values_per_subset = block.components * 2;
+    let mut values = [0; 18];
+    for (value, endpoint) in values
+        .iter_mut()
+        .zip(&block.endpoints[..values_per_subset * block.subsets])
+    {
+        *value = unquantize_color(*endpoint, block.endpoint_range);
+    }
+    let pattern = partition_pattern(block.partition_seed, block.subsets);
+
+    let mut pixels = [[0; 4]; 16];
+    for (texel, pixel) in pixels.iter_mut().enumerate() {
+        let subset = pattern[texel] * values_per_subset;
+        let (e0, e1) = decode_endpoints(&values[subset..], block.components);
+        for (channel, value) in pixel.iter_mut().enumerate() {
+            let weight = if block.planes == 2 && channel as u32 == block.plane2_component {
+                block.weights[texel * 2 + 1]
+            } else {
+                block.weights[texel * block.planes]
+            };
+            let weight = unquantize_weight(weight, block.weight_bits);
+            *value = interpolate(e0[channel], e1[channel], weight) as u8;
+        }
+    }
+    pixels
+}
+
+fn uastc_to_astc(block: &Uastc) -> u128 {
+    let block = match block {
+        Uastc::Solid(color) => {
+            // Void-extent block, that is not limited by any extent.
+            let mut astc = 0xFFFF_FFFF_FFFF_FDFC;
+            for (channel, value) in color.iter().enumerate() {
+                astc |= (*value as u128 * 0x101) << (64 + channel * 16);
+            }
+            return astc;
+        }
+        Uastc::Block(block) => block,
+    };
+
+    // 4x4 weight grid, weight range is defined by 3 bits and "high precision" bit.
+    let (range, high_precision) = match block.weight_bits {
+        1 => (2, 0),
+        2 => (4, 0),
+        3 => (7, 0),
+        4 => (4, 1),
+        _ => (7, 1),
+    };
+    let mut astc: u128 = ((range >> 1) & 3)
+        | ((range & 1) << 4)
+        | (2 << 5)
+        | (high_precision << 9)
+        | (((block.planes == 2) as u128) << 10)
+        | ((block.subsets as u128 - 1) << 11);
+
+    let color_endpoint_mode = match block.components {
+        // LDR luminance-alpha, direct.
+        2 => 4,
+        // LDR RGB, direct.
+        3 => 8,
+        // LDR RGBA, direct.
+        _ => 12,
+    };
+    let mut position = if block.subsets == 1 {
+        astc |= color_endpoint_mode << 13;
+        17
+    } else {
+        // All subsets use the same color endpoint mode.
+        astc |= (block.partition_seed as u128) << 13;
+        astc |= color_endpoint_mode << 25;
+        29
+    };
+
+    write_bise(
+        &mut astc,
+        &mut position,
+        &block.endpoints[..block.components * 2 * block.subsets],
+        block.endpoint_range,
+    );
+
+    // Weights are stored in reversed bit order, starting from the most significant bit of the block.
+    let weight_count = 16 * block.planes;
+    for (i, weight) in block.weights[..weight_count].iter().enumerate() {
+        for bit in 0..block.weight_bits {
+            let position = i as u32 * block.weight_bits + bit;
+            astc |= (((weight >> bit) & 1) as u128) << (127 - position);
+        }
+    }
+
+    // Color component selector is stored right below weights.
+    if block.planes == 2 {
+        let position = 126 - weight_count as u32 * block.weight_bits;
+        astc |= (block.plane2_component as u128) << position;
+    }
+
+    astc
+}
+
+/// Transcodes UASTC blocks into ASTC 4x4 blocks.
+pub(super) fn transcode_uastc_astc(blocks: &[u8]) -> Result<Vec<u8>, Ktx2Error> {
+    let mut output = Vec::with_capacity(blocks.len());
+    for block in blocks.chunks_exact(16) {
+        output.extend_from_slice(&uastc_to_astc(&unpack_uastc(block)?).to_le_bytes());
+    }
+    Ok(output)
+}
+
+/// Decodes UASTC blocks into RGBA8 pixels.
+pub(super) fn decode_uastc_rgba8(
+    blocks: &[u8],
+    width: usize,
+    height: usize,
+) -> Result<Vec<u8>, Ktx2Error> {
+    let mut image = vec![0; width * height * 4];
+    let blocks_x = ceil_div_4(width as u32) as usize;
+    for (i, block) in blocks.chunks_exact(16).enumerate() {
+        let pixels = decode_uastc(&unpack_uastc(block)?);
+        write_block(
+            &pixels,
+            &mut image,
+            width,
+            height,
+            i % blocks_x,
+            i / blocks_x,
+        );
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resource::texture::test::from_hex;
+
+    // UASTC block, ASTC 4x4 block it is transcoded to and pixels of the ASTC block decoded by Mesa
+    // (llvmpipe), which implements the specification independently of this module. Pixels are
+    // RGBA8 in row-major order.
+    const REFERENCE_BLOCKS: [(&str, &str, &str); 8] = [
+        // Mode 0.
+        (
+            "c1b40e663298635bdc72d231171c1794",
+            "4202a78c18c7db0529e838e88c4b4e6b",
+            "8a6dd7ff26d7e7ffc133ceff7d7bd9ffc133ceff26d7e7ffcd25cbffb441d0ff7d7bd9ffcd25cbff33c9e5ffcd25cbff7d7bd9ffcd25cbffa452d2ff5d9ddeff",
+        ),
+        // Mode 3.
+        (
+            "43a3286b863b0e0be8b77c9c1ed40300",
+            "421004103d41e4dcd792da23b0153c39",
+            "2ea38bffd1a35cff63a37cff9ca36bff2ed117ff74ff2eff74ff2eff2ed117ff2ed117ff5df026ff5df026ff5df026ff935c45ffd174a3ff745117ff745117ff",
+        ),
+        // Mode 4.
+        (
+            "d384ff78aad00a371a70cedbee450a00",
+            "42e80410b816ea0963cbcb0f28a2779b",
+            "74a4b3ff7ac4d0ff74a4b3ff7fe2ecff7ac4d0ff7fe2ecff7ac4d0ff7fe2ecff74a4b3ff74a4b3ff6f8697ff74a4b3ff006eabff2f4ec5ff2f4ec5ff006eabff",
+        ),
+        // Mode 6.
+        (
+            "5b9e8f03b664ba74fcf3448740089d3a",
+            "420417a5895bfa085cb91002e122cfaf",
+            "9cbcc2ff939a56ffa09a56ff939a56ff9cccf6ff9cccf6ff9c9a56ff97ccf6ffa0ccf6ff9cccf6ff97ccf6ffa0ccf6ff93bcc2ff97bcc2ff97ab8bffa09a56ff",
+        ),
+        // Mode 7.
+        (
+            "4704eb4c7575eb0985826c595d0b3700",
+            "42a84bf08b89d6b0479c2d0db6d0ba1a",
+            "5f7cb8ff947eabff797db2ff797db2ff797db2ffae7fa4ff797db2ff797db2ffae7fa4ff947eabff5f7cb8ff5f7cb8ff795422ffae7fa4ff947eabff795422ff",
+        ),
+        // Mode 8 (solid color).
+        (
+            "975c5637050000000000000000000000",
+            "fcfdffffffffffffe4e4b2b2baba2929",
+            "e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29e4b2ba29",
+        ),
+        // Mode 13.
+        (
+            "1f8d682d0d3ceb7dfd8a317d071a880a",
+            "418469e059efeb578ce901801581052e",
+            "34acf5c6f0f7f5f4f0f72bf4f0f7f5f434acf5c634acf5c634ac2bc634ac2bc6f0f7f5f434acf5c634acf5c634ac2bc634acf5c634ac2bc634ac2bc634ac2bc6",
+        ),
+        // Mode 17.
+        (
+            "652eaafa435d2821aac1a18461f3b303",
+            "42843ed4851200c06e7e360c291cac82",
+            "616161421f1f1f2f6161612feaeaea421f1f1f1beaeaea421f1f1f2f6161611b1f1f1f42eaeaea421f1f1f09a8a8a82fa8a8a809eaeaea2fa8a8a82feaeaea2f",
+        ),
+    ];
+
+    #[test]
+    fn test_uastc_reference_blocks() {
+        for (uastc, astc, expected) in REFERENCE_BLOCKS {
+            let uastc = from_hex(uastc);
+            assert_eq!(transcode_uastc_astc(&uastc).unwrap(), from_hex(astc));
+            assert_eq!(
+                decode_uastc_rgba8(&uastc, 4, 4).unwrap(),
+                from_hex(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_huffman_table() {
+        // Canonical codes: 0 -> symbol 1, 10 -> symbol 0, 11 -> symbol 2.
+        let table = HuffmanTable::new(&[2, 1, 2]).unwrap();
+        // Bits of the stream are read starting from the least significant one: 0, 10, 11, 0.
+        let mut reader = BitReader::new(&[0b0001_1010]);
+        let symbols = (0..4)
+            .map(|_| table.decode(&mut reader).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(symbols, [1, 0, 2, 1]);
+    }
+}
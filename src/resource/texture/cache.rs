@@ -0,0 +1,114 @@
+//! Cache of processed textures. See [`TextureCache`] docs for more info.
+
+use crate::{
+    core::{
+        log::Log,
+        visitor::{Visit, Visitor},
+    },
+    resource::texture::{data_hash, Texture, TextureProcessingSettings},
+};
+use fxhash::FxHasher;
+use std::{
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+// Must be increased every time when processing of textures is changed, so outdated entries
+// won't be used.
+const VERSION: u32 = 1;
+
+/// Texture cache stores processed (compressed, with generated mip-maps) textures on disk, so the
+/// processing is done only once. Every entry is keyed by the hash of the source data and the
+/// processing settings, so the entry becomes outdated automatically when the source file or its
+/// import options are changed. Outdated entries are not removed automatically, use
+/// [`Self::clear`] to remove every entry.
+///
+/// The cache requires a writable file system, so it can't be used on WebAssembly.
+#[derive(Clone, Debug)]
+pub struct TextureCache {
+    path: PathBuf,
+}
+
+impl TextureCache {
+    /// Creates new texture cache that stores its entries in the given directory. The directory is
+    /// created on demand.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns a path of the directory, where the cache stores its entries.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn entry_path(&self, source: &[u8], settings: &TextureProcessingSettings) -> PathBuf {
+        let mut hasher = FxHasher::default();
+        VERSION.hash(&mut hasher);
+        source.hash(&mut hasher);
+        settings.hash(&mut hasher);
+        self.path.join(format!("{:016x}.tex", hasher.finish()))
+    }
+
+    /// Tries to load processed texture for the given source data and processing settings.
+    pub fn load(&self, source: &[u8], settings: &TextureProcessingSettings) -> Option<Texture> {
+        let data = std::fs::read(self.entry_path(source, settings)).ok()?;
+
+        let mut visitor = match Visitor::load_from_memory(data) {
+            Ok(visitor) => visitor,
+            Err(err) => {
+                Log::warn(format!("Corrupted texture cache entry! Reason: {:?}", err));
+                return None;
+            }
+        };
+
+        let mut texture = Texture::default();
+        if let Err(err) = texture.visit("Texture", &mut visitor) {
+            Log::warn(format!("Corrupted texture cache entry! Reason: {:?}", err));
+            return None;
+        }
+        texture.serialize_content = false;
+        texture.data_hash = data_hash(&texture.bytes);
+
+        Some(texture)
+    }
+
+    /// Stores processed texture for the given source data and processing settings. Errors are
+    /// logged, because the cache is optional.
+    pub fn store(&self, source: &[u8], settings: &TextureProcessingSettings, texture: &Texture) {
+        if let Err(err) = std::fs::create_dir_all(&self.path) {
+            Log::err(format!(
+                "Unable to create texture cache directory {}! Reason: {:?}",
+                self.path.display(),
+                err
+            ));
+            return;
+        }
+
+        let mut texture = texture.clone();
+        texture.serialize_content = true;
+
+        let mut visitor = Visitor::new();
+        let path = self.entry_path(source, settings);
+        if let Err(err) = texture
+            .visit("Texture", &mut visitor)
+            .and_then(|_| visitor.save_binary(&path))
+        {
+            Log::err(format!(
+                "Unable to write texture cache entry {}! Reason: {:?}",
+                path.display(),
+                err
+            ));
+        }
+    }
+
+    /// Removes every entry of the cache.
+    pub fn clear(&self) -> std::io::Result<()> {
+        if self.path.exists() {
+            std::fs::remove_dir_all(&self.path)
+        } else {
+            Ok(())
+        }
+    }
+}
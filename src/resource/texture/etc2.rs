@@ -0,0 +1,468 @@
+//! ETC2 texture compression. ETC2 is supported by every OpenGL ES 3.0+ device, so it is the most
+//! portable compression format for Android. The encoder uses only ETC1-compatible block modes
+//! (individual and differential), which is enough for decent quality and keeps it simple.
+
+use super::{ceil_div_4, read_block_rgba8};
+
+pub(super) const ETC1_MODIFIERS: [[i32; 4]; 8] = [
+    [2, 8, -2, -8],
+    [5, 17, -5, -17],
+    [9, 29, -9, -29],
+    [13, 42, -13, -42],
+    [18, 60, -18, -60],
+    [24, 80, -24, -80],
+    [33, 106, -33, -106],
+    [47, 183, -47, -183],
+];
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+fn clamp_u8(value: i32) -> i32 {
+    value.clamp(0, 255)
+}
+
+// ETC uses column-major order of pixels in a block.
+pub(super) fn pixel_bit_index(x: usize, y: usize) -> usize {
+    x * 4 + y
+}
+
+fn is_in_first_sub_block(x: usize, y: usize, flip: bool) -> bool {
+    if flip {
+        y < 2
+    } else {
+        x < 2
+    }
+}
+
+struct SubBlockFit {
+    table: u64,
+    indices: [u8; 16],
+    error: u32,
+}
+
+fn fit_sub_block(pixels: &[[u8; 4]; 16], base: [i32; 3], flip: bool, first: bool) -> SubBlockFit {
+    let mut best = SubBlockFit {
+        table: 0,
+        indices: [0; 16],
+        error: u32::MAX,
+    };
+
+    for (table, modifiers) in ETC1_MODIFIERS.iter().enumerate() {
+        let mut indices = [0; 16];
+        let mut error = 0;
+        for y in 0..4 {
+            for x in 0..4 {
+                if is_in_first_sub_block(x, y, flip) != first {
+                    continue;
+                }
+
+                let pixel = &pixels[y * 4 + x];
+                let mut best_pixel_error = u32::MAX;
+                for (index, modifier) in modifiers.iter().enumerate() {
+                    let mut pixel_error = 0;
+                    for channel in 0..3 {
+                        let diff = clamp_u8(base[channel] + modifier) - pixel[channel] as i32;
+                        pixel_error += (diff * diff) as u32;
+                    }
+                    if pixel_error < best_pixel_error {
+                        best_pixel_error = pixel_error;
+                        indices[pixel_bit_index(x, y)] = index as u8;
+                    }
+                }
+                error += best_pixel_error;
+            }
+        }
+
+        if error < best.error {
+            best = SubBlockFit {
+                table: table as u64,
+                indices,
+                error,
+            };
+        }
+    }
+
+    best
+}
+
+fn sub_block_average(pixels: &[[u8; 4]; 16], flip: bool, first: bool) -> [f32; 3] {
+    let mut sum = [0.0; 3];
+    for y in 0..4 {
+        for x in 0..4 {
+            if is_in_first_sub_block(x, y, flip) == first {
+                for (channel, sum) in sum.iter_mut().enumerate() {
+                    *sum += pixels[y * 4 + x][channel] as f32;
+                }
+            }
+        }
+    }
+    sum.map(|s| s / 8.0)
+}
+
+fn quantize(value: f32, max: f32) -> i32 {
+    (value / 255.0 * max).round() as i32
+}
+
+fn expand4(value: i32) -> i32 {
+    (value << 4) | value
+}
+
+pub(super) fn expand5(value: i32) -> i32 {
+    (value << 3) | (value >> 2)
+}
+
+fn assemble_block(
+    colors: u64,
+    differential: bool,
+    flip: bool,
+    first: &SubBlockFit,
+    second: &SubBlockFit,
+) -> u64 {
+    let mut block = colors
+        | (first.table << 37)
+        | (second.table << 34)
+        | ((differential as u64) << 33)
+        | ((flip as u64) << 32);
+
+    for y in 0..4 {
+        for x in 0..4 {
+            let bit = pixel_bit_index(x, y);
+            let index = if is_in_first_sub_block(x, y, flip) {
+                first.indices[bit]
+            } else {
+                second.indices[bit]
+            } as u64;
+            block |= (index >> 1) << (16 + bit);
+            block |= (index & 1) << bit;
+        }
+    }
+
+    block
+}
+
+/// Encodes a single 4x4 block of RGB pixels (alpha is ignored) into ETC1-compatible ETC2 block.
+fn encode_color_block(pixels: &[[u8; 4]; 16]) -> u64 {
+    let mut best_block = 0;
+    let mut best_error = u32::MAX;
+
+    for flip in [false, true] {
+        let first_average = sub_block_average(pixels, flip, true);
+        let second_average = sub_block_average(pixels, flip, false);
+
+        // Individual mode - each sub-block has its own 4-bit color.
+        let first_color = first_average.map(|c| quantize(c, 15.0));
+        let second_color = second_average.map(|c| quantize(c, 15.0));
+        let first = fit_sub_block(pixels, first_color.map(expand4), flip, true);
+        let second = fit_sub_block(pixels, second_color.map(expand4), flip, false);
+        if first.error + second.error < best_error {
+            best_error = first.error + second.error;
+            let mut colors = 0;
+            for channel in 0..3 {
+                colors |= (first_color[channel] as u64) << (60 - channel * 8);
+                colors |= (second_color[channel] as u64) << (56 - channel * 8);
+            }
+            best_block = assemble_block(colors, false, flip, &first, &second);
+        }
+
+        // Differential mode - 5-bit base color and 3-bit signed offset for the second sub-block.
+        // The offset must not go out of bounds, otherwise ETC2 decoders will treat the block as
+        // one of the ETC2-specific modes.
+        let first_color = first_average.map(|c| quantize(c, 31.0));
+        let second_color = second_average.map(|c| quantize(c, 31.0));
+        if (0..3).all(|channel| (-4..=3).contains(&(second_color[channel] - first_color[channel])))
+        {
+            let first = fit_sub_block(pixels, first_color.map(expand5), flip, true);
+            let second = fit_sub_block(pixels, second_color.map(expand5), flip, false);
+            if first.error + second.error < best_error {
+                best_error = first.error + second.error;
+                let mut colors = 0;
+                for channel in 0..3 {
+                    let delta = (second_color[channel] - first_color[channel]) & 0b111;
+                    colors |= (first_color[channel] as u64) << (59 - channel * 8);
+                    colors |= (delta as u64) << (56 - channel * 8);
+                }
+                best_block = assemble_block(colors, true, flip, &first, &second);
+            }
+        }
+    }
+
+    best_block
+}
+
+fn fit_alpha(alphas: &[i32; 16], base: i32, multiplier: i32, table: usize) -> (u64, u32) {
+    let mut indices = 0;
+    let mut error = 0;
+    for (bit, alpha) in alphas.iter().enumerate() {
+        let mut best_index = 0;
+        let mut best_error = u32::MAX;
+        for (index, modifier) in EAC_MODIFIERS[table].iter().enumerate() {
+            let diff = clamp_u8(base + modifier * multiplier) - alpha;
+            let pixel_error = (diff * diff) as u32;
+            if pixel_error < best_error {
+                best_error = pixel_error;
+                best_index = index as u64;
+            }
+        }
+        indices |= best_index << (45 - bit * 3);
+        error += best_error;
+    }
+    (indices, error)
+}
+
+/// Encodes alpha channel of a 4x4 block into EAC block.
+pub(super) fn encode_alpha_block(pixels: &[[u8; 4]; 16]) -> u64 {
+    let mut alphas = [0; 16];
+    for y in 0..4 {
+        for x in 0..4 {
+            alphas[pixel_bit_index(x, y)] = pixels[y * 4 + x][3] as i32;
+        }
+    }
+
+    let min = *alphas.iter().min().unwrap();
+    let max = *alphas.iter().max().unwrap();
+
+    let mut best_block = 0;
+    let mut best_error = u32::MAX;
+    for (table, modifiers) in EAC_MODIFIERS.iter().enumerate() {
+        let low = modifiers[3];
+        let high = modifiers[7];
+        let ideal_multiplier = ((max - min) as f32 / (high - low) as f32).round() as i32;
+        for multiplier in (ideal_multiplier - 1)..=(ideal_multiplier + 1) {
+            let multiplier = multiplier.clamp(1, 15);
+            let base = clamp_u8((min + max - (low + high) * multiplier + 1) / 2);
+            let (indices, error) = fit_alpha(&alphas, base, multiplier, table);
+            if error < best_error {
+                best_error = error;
+                best_block = ((base as u64) << 56)
+                    | ((multiplier as u64) << 52)
+                    | ((table as u64) << 48)
+                    | indices;
+            }
+        }
+    }
+
+    best_block
+}
+
+fn encode_image<F>(
+    bytes: &[u8],
+    channels: usize,
+    width: usize,
+    height: usize,
+    block_size: usize,
+    mut encode: F,
+) -> Vec<u8>
+where
+    F: FnMut(&[[u8; 4]; 16], &mut Vec<u8>),
+{
+    let blocks_x = ceil_div_4(width as u32) as usize;
+    let blocks_y = ceil_div_4(height as u32) as usize;
+    let mut output = Vec::with_capacity(blocks_x * blocks_y * block_size);
+    for block_y in 0..blocks_y {
+        for block_x in 0..blocks_x {
+            let pixels = read_block_rgba8(bytes, channels, width, height, block_x, block_y);
+            encode(&pixels, &mut output);
+        }
+    }
+    output
+}
+
+/// Encodes RGB8 or RGBA8 image into ETC2 RGB8 image (alpha is discarded).
+pub(super) fn encode_image_etc2_rgb8(
+    bytes: &[u8],
+    channels: usize,
+    width: usize,
+    height: usize,
+) -> Vec<u8> {
+    encode_image(bytes, channels, width, height, 8, |pixels, output| {
+        output.extend_from_slice(&encode_color_block(pixels).to_be_bytes());
+    })
+}
+
+/// Encodes RGBA8 image into ETC2 RGBA8 (EAC alpha) image.
+pub(super) fn encode_image_etc2_rgba8(bytes: &[u8], width: usize, height: usize) -> Vec<u8> {
+    encode_image(bytes, 4, width, height, 16, |pixels, output| {
+        output.extend_from_slice(&encode_alpha_block(pixels).to_be_bytes());
+        output.extend_from_slice(&encode_color_block(pixels).to_be_bytes());
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resource::texture::test::from_hex;
+
+    fn decode_color_block(block: u64) -> [[u8; 3]; 16] {
+        let differential = (block >> 33) & 1 == 1;
+        let flip = (block >> 32) & 1 == 1;
+        let mut first = [0; 3];
+        let mut second = [0; 3];
+        for channel in 0..3 {
+            if differential {
+                let base = ((block >> (59 - channel * 8)) & 0b11111) as i32;
+                let delta = ((block >> (56 - channel * 8)) & 0b111) as i32;
+                let delta = if delta >= 4 { delta - 8 } else { delta };
+                first[channel] = expand5(base);
+                second[channel] = expand5(base + delta);
+            } else {
+                first[channel] = expand4(((block >> (60 - channel * 8)) & 0xF) as i32);
+                second[channel] = expand4(((block >> (56 - channel * 8)) & 0xF) as i32);
+            }
+        }
+        let first_table = ((block >> 37) & 0b111) as usize;
+        let second_table = ((block >> 34) & 0b111) as usize;
+
+        let mut pixels = [[0; 3]; 16];
+        for y in 0..4 {
+            for x in 0..4 {
+                let bit = pixel_bit_index(x, y);
+                let index = ((((block >> (16 + bit)) & 1) << 1) | ((block >> bit) & 1)) as usize;
+                let (base, table) = if is_in_first_sub_block(x, y, flip) {
+                    (first, first_table)
+                } else {
+                    (second, second_table)
+                };
+                for channel in 0..3 {
+                    pixels[y * 4 + x][channel] =
+                        clamp_u8(base[channel] + ETC1_MODIFIERS[table][index]) as u8;
+                }
+            }
+        }
+        pixels
+    }
+
+    fn decode_alpha_block(block: u64) -> [u8; 16] {
+        let base = (block >> 56) as i32;
+        let multiplier = ((block >> 52) & 0xF) as i32;
+        let table = ((block >> 48) & 0xF) as usize;
+        let mut alphas = [0; 16];
+        for y in 0..4 {
+            for x in 0..4 {
+                let bit = pixel_bit_index(x, y);
+                let index = ((block >> (45 - bit * 3)) & 0b111) as usize;
+                alphas[y * 4 + x] = clamp_u8(base + EAC_MODIFIERS[table][index] * multiplier) as u8;
+            }
+        }
+        alphas
+    }
+
+    // Input pixels (empty for hand-made blocks), ETC2 RGBA8 block (EAC alpha block followed by color
+    // block) and its pixels decoded by Mesa (llvmpipe), which implements the specification
+    // independently of this module. All pixels are RGBA8 in row-major order.
+    const REFERENCE_BLOCKS: [(&str, &str, &str); 9] = [
+        (
+            "282828ff464646f5646464eb828282e1323232eb505050e16e6e6ed78c8c8ccd3c3c3cd75a5a5acd787878c3969696b9464646c3646464b9828282afa0a0a0a5",
+            "d44bfa1f42d0ba13484848481f0783c1",
+            "272727f84d4d4df86b6b6bec7f7f7fe43b3b3bec4d4d4de46b6b6bd8919191cc3b3b3bd8616161cc7f7f7fc0919191b84d4d4dc0616161b87f7f7faca5a5a5ac",
+        ),
+        (
+            "c81e14ffc81e14ff0a3cdcff0a3cdcffc81e14ffc81e14ff0a3cdcff0a3cdcffc81e14ffc81e14ff0a3cdcff0a3cdcffc81e14ffc81e14ff0a3cdcff0a3cdcff",
+            "ff10924924924924c1241d04ffff0000",
+            "ca200fffca200fff0c3fd8ff0c3fd8ffca200fffca200fff0c3fd8ff0c3fd8ffca200fffca200fff0c3fd8ff0c3fd8ffca200fffca200fff0c3fd8ff0c3fd8ff",
+        ),
+        (
+            "faf00a00faf00f00faf01400faf01900faf00a00faf00f00faf01400faf01900055a64ff055a64ff055a64ff055a64ff055a64ff055a64ff055a64ff055a64ff",
+            "84906ff6ff6ff6fff0e5160100330000",
+            "fdec0f00fdec0f00fff01300fff01300fdec0f00fdec0f00fff01300fff01300025768ff025768ff025768ff025768ff025768ff025768ff025768ff025768ff",
+        ),
+        (
+            "dc0465aa1fad1d5adae5ac1b1e5f1370796cfd10ff19af601d04acb41d022b4678733af2df5faeb70859d1ee3910cb4895b5cc892911ff06b6622edf3cf935fd",
+            "89eaab422b57604f955697747295315e",
+            "8c488cb36f2b6f51a5b6c7190516276dc37fc319a662a66d3d4e5fb3051627516f2b6febc37fc3b36d7e8feb3d4e5f51c37fc3978c488c006d7e8feb6d7e8fff",
+        ),
+        (
+            "03fc0303fc03fcfc03fc0303fc03fcfcfc03fcfc03fc0303fc03fcfc03fc030303fc0303fc03fcfc03fc0303fc03fcfcfc03fcfc03fc0303fc03fcfc03fc0303",
+            "85a17dfefb7dfefb8080806ea5a5ffff",
+            "5a5a5a03aeaeaefd5a5a5a03aeaeaefdaeaeaefd5a5a5a03aeaeaefd5a5a5a035a5a5a03aeaeaefd5a5a5a03aeaeaefdaeaeaefd5a5a5a03aeaeaefd5a5a5a03",
+        ),
+        (
+            "",
+            "a53c0c5ca6a3a4503b1612d83d9c1724",
+            "5432329c120000b7510000ae51000090543232879d7b7b90ffd08c9c9a4501990000009c543232ab510000c0dc874390120000ae120000b79a450190dc87439c",
+        ),
+        (
+            "",
+            "2e9dd3ac0f21ddb6b55cb20e8e81973e",
+            "b358b340bd62bd40c663f01cc663f040bd62bd2ebd62bd25720f9c25a946d340bd62bd7fb75cb71c720f9c00a946d340bd62bd13b358b37f8f2cb937720f9c40",
+        ),
+        (
+            "",
+            "445d8a6a24ede6a49536b3207f150524",
+            "942eb644942eb6494d5e2b71536431129e38c035aa44cc3f53643112536431358822aa449e38c0444d5e2b12536431449e38c04e9e38c0445364314e57683544",
+        ),
+        (
+            "",
+            "dada95e77731af10916f4f33e00902c7",
+            "835a39e799704f5899704fa699704fffa57c5bff99704fffa57c5be78f6645e7d89f7e58d89f7effae7554588a5130728a5130ffd89f7effae7554728a5130c0",
+        ),
+    ];
+
+    #[test]
+    fn test_etc2_reference_blocks() {
+        for (input, block, expected) in REFERENCE_BLOCKS {
+            let block = from_hex(block);
+            if !input.is_empty() {
+                assert_eq!(encode_image_etc2_rgba8(&from_hex(input), 4, 4), block);
+            }
+
+            let alpha = decode_alpha_block(u64::from_be_bytes(block[0..8].try_into().unwrap()));
+            let color = decode_color_block(u64::from_be_bytes(block[8..16].try_into().unwrap()));
+            for (i, pixel) in from_hex(expected).chunks_exact(4).enumerate() {
+                assert_eq!(color[i], pixel[..3]);
+                assert_eq!(alpha[i], pixel[3]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_etc2_round_trip() {
+        let mut image = Vec::new();
+        for y in 0..4u8 {
+            for x in 0..4u8 {
+                let luminance = 40 + x * 30 + y * 10;
+                image.extend_from_slice(&[luminance, luminance, luminance, 255 - x * 10 - y * 20]);
+            }
+        }
+
+        let encoded = encode_image_etc2_rgba8(&image, 4, 4);
+        assert_eq!(encoded.len(), 16);
+
+        let alpha = decode_alpha_block(u64::from_be_bytes(encoded[0..8].try_into().unwrap()));
+        let color = decode_color_block(u64::from_be_bytes(encoded[8..16].try_into().unwrap()));
+        for (i, pixel) in image.chunks_exact(4).enumerate() {
+            for channel in 0..3 {
+                assert!((color[i][channel] as i32 - pixel[channel] as i32).abs() <= 16);
+            }
+            assert!((alpha[i] as i32 - pixel[3] as i32).abs() <= 8);
+        }
+    }
+
+    #[test]
+    fn test_etc2_solid_color() {
+        let image = [200u8, 100, 50].repeat(16);
+        let encoded = encode_image_etc2_rgb8(&image, 3, 4, 4);
+        let color = decode_color_block(u64::from_be_bytes(encoded[0..8].try_into().unwrap()));
+        for pixel in color {
+            assert!((pixel[0] as i32 - 200).abs() <= 4);
+            assert!((pixel[1] as i32 - 100).abs() <= 4);
+            assert!((pixel[2] as i32 - 50).abs() <= 4);
+        }
+    }
+}
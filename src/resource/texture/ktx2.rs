@@ -0,0 +1,743 @@
+//! KTX2 container support. KTX2 could store textures in almost any GPU format and could
+//! additionally "supercompress" their data with Zstandard or ZLIB, which significantly reduces
+//! download size (which is especially important for WebAssembly builds).
+//!
+//! Uncompressed single-level rectangle images are processed the same way as common image formats
+//! (see [`TextureProcessingSettings`]), so they're compressed to the best format of the target
+//! platform at runtime. Images in other formats are used as is.
+//!
+//! Basis Universal textures (BasisLZ/ETC1S and UASTC) are transcoded at runtime to the best format
+//! of the target platform: ETC1S is transcoded to ETC2 and UASTC to ASTC 4x4 without quality loss,
+//! for other platforms decoded pixels are compressed according to [`TextureProcessingSettings`].
+//! Decoded RGBA8 (or RGB8) pixels are used if compression is disabled.
+
+use crate::resource::texture::{
+    basis::{self, Etc1sBlock, Etc1sCodebook},
+    ceil_div_4, data_hash, try_compress, CompressionFormatFamily, CompressionOptions, Texture,
+    TextureError, TextureKind, TexturePixelKind, TextureProcessingSettings,
+};
+use std::fmt::{Display, Formatter};
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+const HEADER_SIZE: usize = 80;
+const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+// Dimensions are 32-bit, so a texture can't have more mip levels.
+const MAX_LEVEL_COUNT: u32 = 32;
+
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_BASIS_LZ: u32 = 1;
+const SUPERCOMPRESSION_ZSTD: u32 = 2;
+const SUPERCOMPRESSION_ZLIB: u32 = 3;
+
+// Color models of data format descriptor of Basis Universal textures.
+const DFD_MODEL_ETC1S: u8 = 163;
+const DFD_MODEL_UASTC: u8 = 166;
+// UASTC channel ids of textures with alpha.
+const DFD_CHANNEL_UASTC_RGBA: u8 = 3;
+const DFD_CHANNEL_UASTC_RRRG: u8 = 5;
+
+const SGD_HEADER_SIZE: usize = 20;
+const IMAGE_DESC_SIZE: usize = 20;
+// Images of videos could be predicted from the previous frame.
+const IMAGE_FLAG_P_FRAME: u32 = 2;
+
+/// An error that may occur during KTX2 container parsing.
+#[derive(Debug)]
+pub enum Ktx2Error {
+    /// The file is truncated or contains invalid offsets.
+    InvalidData,
+    /// Texture arrays are not supported.
+    UnsupportedArray,
+    /// The texture has pixel format (`VkFormat`) that is not supported by the engine.
+    UnsupportedFormat(u32),
+    /// The texture uses unknown supercompression scheme.
+    UnsupportedSupercompression(u32),
+    /// The texture uses Basis Universal features that are not supported (video frames or deprecated
+    /// selector codebooks).
+    BasisUniversal,
+    /// Supercompressed data could not be decompressed.
+    Decompression(String),
+}
+
+impl Display for Ktx2Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ktx2Error::InvalidData => write!(f, "The file is truncated or corrupted!"),
+            Ktx2Error::UnsupportedArray => write!(f, "Texture arrays are not supported!"),
+            Ktx2Error::UnsupportedFormat(format) => {
+                write!(f, "VkFormat {format} is not supported!")
+            }
+            Ktx2Error::UnsupportedSupercompression(scheme) => {
+                write!(f, "Supercompression scheme {scheme} is not supported!")
+            }
+            Ktx2Error::BasisUniversal => {
+                write!(f, "The texture uses unsupported Basis Universal features!")
+            }
+            Ktx2Error::Decompression(reason) => {
+                write!(f, "Unable to decompress data. Reason: {reason}")
+            }
+        }
+    }
+}
+
+pub(super) fn is_ktx2(data: &[u8]) -> bool {
+    data.starts_with(&IDENTIFIER)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, Ktx2Error> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(Ktx2Error::InvalidData)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Ktx2Error> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or(Ktx2Error::InvalidData)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, Ktx2Error> {
+    let low = read_u32(data, offset)? as u64;
+    let high = read_u32(data, offset + 4)? as u64;
+    Ok(low | (high << 32))
+}
+
+fn pixel_kind_from_vk_format(vk_format: u32) -> Result<TexturePixelKind, Ktx2Error> {
+    // sRGB formats are mapped to their linear counterparts, the engine does color space conversion
+    // in shaders.
+    Ok(match vk_format {
+        9 | 15 => TexturePixelKind::R8,
+        16 | 22 => TexturePixelKind::RG8,
+        23 | 29 => TexturePixelKind::RGB8,
+        30 | 36 => TexturePixelKind::BGR8,
+        37 | 43 => TexturePixelKind::RGBA8,
+        44 | 50 => TexturePixelKind::BGRA8,
+        70 => TexturePixelKind::R16,
+        76 => TexturePixelKind::R16F,
+        77 => TexturePixelKind::RG16,
+        84 => TexturePixelKind::RGB16,
+        90 => TexturePixelKind::RGB16F,
+        91 => TexturePixelKind::RGBA16,
+        100 => TexturePixelKind::R32F,
+        106 => TexturePixelKind::RGB32F,
+        109 => TexturePixelKind::RGBA32F,
+        131 | 132 => TexturePixelKind::DXT1RGB,
+        133 | 134 => TexturePixelKind::DXT1RGBA,
+        135 | 136 => TexturePixelKind::DXT3RGBA,
+        137 | 138 => TexturePixelKind::DXT5RGBA,
+        139 => TexturePixelKind::R8RGTC,
+        141 => TexturePixelKind::RG8RGTC,
+        147 | 148 => TexturePixelKind::ETC2RGB,
+        151 | 152 => TexturePixelKind::ETC2RGBA,
+        157 | 158 => TexturePixelKind::ASTC4x4RGBA,
+        _ => return Err(Ktx2Error::UnsupportedFormat(vk_format)),
+    })
+}
+
+fn decompress(scheme: u32, data: &[u8], uncompressed_length: usize) -> Result<Vec<u8>, Ktx2Error> {
+    let decompressed = match scheme {
+        SUPERCOMPRESSION_NONE => data.to_vec(),
+        SUPERCOMPRESSION_ZSTD => {
+            use std::io::Read;
+
+            let mut source = data;
+            let decoder = ruzstd::streaming_decoder::StreamingDecoder::new(&mut source)
+                .map_err(|err| Ktx2Error::Decompression(format!("{:?}", err)))?;
+            // The length is checked against the size of the texture by the caller, and the output is
+            // limited by it, so a corrupted stream can't produce more data than the texture needs.
+            let mut decompressed = Vec::with_capacity(uncompressed_length);
+            decoder
+                .take(uncompressed_length as u64 + 1)
+                .read_to_end(&mut decompressed)
+                .map_err(|err| Ktx2Error::Decompression(err.to_string()))?;
+            decompressed
+        }
+        SUPERCOMPRESSION_ZLIB => {
+            inflate::inflate_bytes_zlib(data).map_err(Ktx2Error::Decompression)?
+        }
+        _ => return Err(Ktx2Error::UnsupportedSupercompression(scheme)),
+    };
+
+    if decompressed.len() == uncompressed_length {
+        Ok(decompressed)
+    } else {
+        Err(Ktx2Error::InvalidData)
+    }
+}
+
+fn can_be_processed(pixel_kind: TexturePixelKind) -> bool {
+    matches!(
+        pixel_kind,
+        TexturePixelKind::R8
+            | TexturePixelKind::RG8
+            | TexturePixelKind::RGB8
+            | TexturePixelKind::RGBA8
+            | TexturePixelKind::BGR8
+            | TexturePixelKind::BGRA8
+            | TexturePixelKind::R16
+            | TexturePixelKind::RG16
+            | TexturePixelKind::RGB16
+            | TexturePixelKind::RGBA16
+            | TexturePixelKind::R32F
+    )
+}
+
+fn sub_slice(data: &[u8], offset: usize, length: usize) -> Result<&[u8], Ktx2Error> {
+    offset
+        .checked_add(length)
+        .and_then(|end| data.get(offset..end))
+        .ok_or(Ktx2Error::InvalidData)
+}
+
+/// Returns length of a mip level (of all its faces and slices) in bytes, `None` means that the level
+/// is too large to fit in memory.
+fn level_length(kind: TextureKind, pixel_kind: TexturePixelKind, level: usize) -> Option<usize> {
+    let (width, height, depth, face_count) = match kind {
+        TextureKind::Line { length } => (length, 1, 1, 1),
+        TextureKind::Rectangle { width, height } => (width, height, 1, 1),
+        TextureKind::Cube { width, height } => (width, height, 1, 6),
+        TextureKind::Volume {
+            width,
+            height,
+            depth,
+        } => (width, height, depth, 1),
+    };
+    let (block_size, block_length) = match pixel_kind.size_in_bytes() {
+        Some(pixel_size) => (1, pixel_size as u64),
+        None => match pixel_kind {
+            TexturePixelKind::DXT1RGB
+            | TexturePixelKind::DXT1RGBA
+            | TexturePixelKind::R8RGTC
+            | TexturePixelKind::ETC2RGB => (4, 8),
+            _ => (4, 16),
+        },
+    };
+    let level_size = |size: u32| (size >> level).max(1) as u64;
+    let block_count = |size: u32| (level_size(size) + block_size - 1) / block_size;
+    block_count(width)
+        .checked_mul(block_count(height))?
+        .checked_mul(level_size(depth))?
+        .checked_mul(face_count)?
+        .checked_mul(block_length)?
+        .try_into()
+        .ok()
+}
+
+/// Returns data of a mip level, checks that its uncompressed length is equal to the expected one.
+fn read_level(data: &[u8], level: usize, expected_length: usize) -> Result<&[u8], Ktx2Error> {
+    let entry = HEADER_SIZE + level * LEVEL_INDEX_ENTRY_SIZE;
+    let offset = read_u64(data, entry)? as usize;
+    let length = read_u64(data, entry + 8)? as usize;
+    let uncompressed_length = read_u64(data, entry + 16)?;
+    if uncompressed_length != expected_length as u64 {
+        return Err(Ktx2Error::InvalidData);
+    }
+    sub_slice(data, offset, length)
+}
+
+pub(super) fn load(
+    data: &[u8],
+    settings: &TextureProcessingSettings,
+) -> Result<Texture, TextureError> {
+    let vk_format = read_u32(data, 12)?;
+    let width = read_u32(data, 20)?;
+    let height = read_u32(data, 24)?;
+    let depth = read_u32(data, 28)?;
+    let layer_count = read_u32(data, 32)?;
+    let face_count = read_u32(data, 36)?;
+    let level_count = read_u32(data, 40)?;
+    let supercompression = read_u32(data, 44)?;
+
+    if layer_count > 1 {
+        return Err(Ktx2Error::UnsupportedArray.into());
+    }
+
+    if level_count > MAX_LEVEL_COUNT {
+        return Err(Ktx2Error::InvalidData.into());
+    }
+
+    let kind = if face_count == 6 {
+        TextureKind::Cube { width, height }
+    } else if depth > 0 {
+        TextureKind::Volume {
+            width,
+            height,
+            depth,
+        }
+    } else if height == 0 {
+        TextureKind::Line { length: width }
+    } else {
+        TextureKind::Rectangle { width, height }
+    };
+
+    // VK_FORMAT_UNDEFINED is used by Basis Universal textures, their actual format is stored in
+    // the data format descriptor.
+    if vk_format == 0 {
+        return load_basis(data, kind, level_count, supercompression, settings);
+    }
+
+    let pixel_kind = pixel_kind_from_vk_format(vk_format)?;
+
+    // Zero level count means that mip-maps must be generated at runtime.
+    let stored_level_count = level_count.max(1);
+
+    // Levels are stored from the smallest to the largest, but the index goes from the largest.
+    let mut bytes = Vec::new();
+    for level in 0..stored_level_count as usize {
+        let uncompressed_length =
+            level_length(kind, pixel_kind, level).ok_or(Ktx2Error::InvalidData)?;
+        let level_data = read_level(data, level, uncompressed_length)?;
+        bytes.extend_from_slice(&decompress(
+            supercompression,
+            level_data,
+            uncompressed_length,
+        )?);
+    }
+
+    if let TextureKind::Rectangle { width, height } = kind {
+        if stored_level_count == 1 && can_be_processed(pixel_kind) {
+            let mut settings = *settings;
+            settings.gen_mip_maps |= level_count == 0;
+            return Texture::process_image(pixel_kind, width, height, &bytes, &settings);
+        }
+    }
+
+    Ok(Texture {
+        pixel_kind,
+        kind,
+        data_hash: data_hash(&bytes),
+        bytes: bytes.into(),
+        mip_count: stored_level_count,
+        ..Default::default()
+    })
+}
+
+/// Location of ETC1S slices of an image, offsets are relative to the data of its mip level.
+struct ImageDesc {
+    flags: u32,
+    color_offset: usize,
+    color_length: usize,
+    alpha_offset: usize,
+    alpha_length: usize,
+}
+
+/// Reads the codebook and image descriptions from the supercompression global data of a BasisLZ
+/// texture.
+fn read_basis_lz_global_data(
+    data: &[u8],
+    image_count: usize,
+) -> Result<(Etc1sCodebook, Vec<ImageDesc>), Ktx2Error> {
+    let sgd_offset = read_u64(data, 64)? as usize;
+    let sgd_length = read_u64(data, 72)? as usize;
+    let sgd = sub_slice(data, sgd_offset, sgd_length)?;
+
+    let endpoint_count = read_u16(sgd, 0)? as usize;
+    let selector_count = read_u16(sgd, 2)? as usize;
+    let endpoints_length = read_u32(sgd, 4)? as usize;
+    let selectors_length = read_u32(sgd, 8)? as usize;
+    let tables_length = read_u32(sgd, 12)? as usize;
+
+    let image_descs = (0..image_count)
+        .map(|image| {
+            let offset = SGD_HEADER_SIZE + image * IMAGE_DESC_SIZE;
+            Ok(ImageDesc {
+                flags: read_u32(sgd, offset)?,
+                color_offset: read_u32(sgd, offset + 4)? as usize,
+                color_length: read_u32(sgd, offset + 8)? as usize,
+                alpha_offset: read_u32(sgd, offset + 12)? as usize,
+                alpha_length: read_u32(sgd, offset + 16)? as usize,
+            })
+        })
+        .collect::<Result<Vec<_>, Ktx2Error>>()?;
+
+    let endpoints_offset = SGD_HEADER_SIZE + image_count * IMAGE_DESC_SIZE;
+    let endpoints = sub_slice(sgd, endpoints_offset, endpoints_length)?;
+    let selectors_offset = endpoints_offset + endpoints_length;
+    let selectors = sub_slice(sgd, selectors_offset, selectors_length)?;
+    let tables = sub_slice(sgd, selectors_offset + selectors_length, tables_length)?;
+
+    let codebook =
+        Etc1sCodebook::new(endpoint_count, endpoints, selector_count, selectors, tables)?;
+
+    Ok((codebook, image_descs))
+}
+
+/// Blocks of a single Basis Universal image (a mip level of a face).
+enum BasisImage<'a> {
+    Etc1s {
+        codebook: &'a Etc1sCodebook,
+        color: Vec<Etc1sBlock>,
+        alpha: Option<Vec<Etc1sBlock>>,
+    },
+    Uastc {
+        blocks: Vec<u8>,
+        has_alpha: bool,
+    },
+}
+
+impl BasisImage<'_> {
+    fn has_alpha(&self) -> bool {
+        match self {
+            BasisImage::Etc1s { alpha, .. } => alpha.is_some(),
+            BasisImage::Uastc { has_alpha, .. } => *has_alpha,
+        }
+    }
+
+    /// Decodes the image into RGBA8 pixels, or into RGB8 pixels if it has no alpha.
+    fn decode(
+        &self,
+        width: usize,
+        height: usize,
+    ) -> Result<(TexturePixelKind, Vec<u8>), Ktx2Error> {
+        let pixels = match self {
+            BasisImage::Etc1s {
+                codebook,
+                color,
+                alpha,
+            } => codebook.decode_rgba8(color, alpha.as_deref(), width, height),
+            BasisImage::Uastc { blocks, .. } => basis::decode_uastc_rgba8(blocks, width, height)?,
+        };
+
+        if self.has_alpha() {
+            Ok((TexturePixelKind::RGBA8, pixels))
+        } else {
+            let pixels = pixels
+                .chunks_exact(4)
+                .flat_map(|pixel| &pixel[..3])
+                .copied()
+                .collect();
+            Ok((TexturePixelKind::RGB8, pixels))
+        }
+    }
+
+    /// Transcodes the image into the best format of the target platform. ETC1S is transcoded to
+    /// ETC2 and UASTC to ASTC directly, for other formats decoded pixels are compressed.
+    fn transcode(
+        &self,
+        width: usize,
+        height: usize,
+        settings: &TextureProcessingSettings,
+    ) -> Result<(TexturePixelKind, Vec<u8>), Ktx2Error> {
+        if settings.compression != CompressionOptions::NoCompression {
+            match (self, settings.compression_family) {
+                (
+                    BasisImage::Etc1s {
+                        codebook,
+                        color,
+                        alpha,
+                    },
+                    CompressionFormatFamily::Etc2,
+                ) => {
+                    let pixel_kind = if alpha.is_some() {
+                        TexturePixelKind::ETC2RGBA
+                    } else {
+                        TexturePixelKind::ETC2RGB
+                    };
+                    return Ok((pixel_kind, codebook.transcode_etc2(color, alpha.as_deref())));
+                }
+                (BasisImage::Uastc { blocks, .. }, CompressionFormatFamily::Astc) => {
+                    return Ok((
+                        TexturePixelKind::ASTC4x4RGBA,
+                        basis::transcode_uastc_astc(blocks)?,
+                    ));
+                }
+                _ => (),
+            }
+        }
+
+        let (pixel_kind, pixels) = self.decode(width, height)?;
+        Ok(try_compress(
+            pixel_kind,
+            &pixels,
+            width,
+            height,
+            settings.compression,
+            settings.compression_family,
+        )
+        .map(|(bytes, pixel_kind)| (pixel_kind, bytes))
+        .unwrap_or((pixel_kind, pixels)))
+    }
+}
+
+fn load_basis(
+    data: &[u8],
+    kind: TextureKind,
+    level_count: u32,
+    supercompression: u32,
+    settings: &TextureProcessingSettings,
+) -> Result<Texture, TextureError> {
+    // Basis Universal supports only 2D images.
+    let (width, height, face_count) = match kind {
+        TextureKind::Rectangle { width, height } => (width, height, 1),
+        TextureKind::Cube { width, height } => (width, height, 6),
+        _ => return Err(Ktx2Error::InvalidData.into()),
+    };
+
+    let dfd_offset = read_u32(data, 48)? as usize;
+    let color_model = *data.get(dfd_offset + 12).ok_or(Ktx2Error::InvalidData)?;
+    let channel = data.get(dfd_offset + 31).ok_or(Ktx2Error::InvalidData)? & 0xF;
+
+    let stored_level_count = level_count.max(1);
+
+    let image_count = stored_level_count
+        .checked_mul(face_count)
+        .ok_or(Ktx2Error::InvalidData)?;
+    let global_data = match (color_model, supercompression) {
+        (DFD_MODEL_ETC1S, SUPERCOMPRESSION_BASIS_LZ) => {
+            Some(read_basis_lz_global_data(data, image_count as usize)?)
+        }
+        (DFD_MODEL_UASTC, _) => None,
+        _ => return Err(Ktx2Error::UnsupportedFormat(0).into()),
+    };
+
+    let mut levels = Vec::with_capacity(stored_level_count as usize);
+    for level in 0..stored_level_count as usize {
+        let level_width = (width >> level).max(1);
+        let level_height = (height >> level).max(1);
+        let blocks_x = ceil_div_4(level_width) as usize;
+        let blocks_y = ceil_div_4(level_height) as usize;
+
+        let images = if let Some((codebook, image_descs)) = global_data.as_ref() {
+            // Uncompressed length of ETC1S levels is always zero, since their data is never
+            // supercompressed as a whole.
+            let level_data = read_level(data, level, 0)?;
+            (0..face_count as usize)
+                .map(|face| {
+                    let desc = &image_descs[level * face_count as usize + face];
+                    if desc.flags & IMAGE_FLAG_P_FRAME != 0 {
+                        return Err(Ktx2Error::BasisUniversal);
+                    }
+
+                    let color = sub_slice(level_data, desc.color_offset, desc.color_length)?;
+                    let alpha = if desc.alpha_length > 0 {
+                        let alpha = sub_slice(level_data, desc.alpha_offset, desc.alpha_length)?;
+                        Some(codebook.decode_slice(alpha, blocks_x, blocks_y)?)
+                    } else {
+                        None
+                    };
+
+                    Ok(BasisImage::Etc1s {
+                        codebook,
+                        color: codebook.decode_slice(color, blocks_x, blocks_y)?,
+                        alpha,
+                    })
+                })
+                .collect::<Result<Vec<_>, Ktx2Error>>()?
+        } else {
+            // UASTC blocks have the same size as ASTC 4x4 blocks.
+            let uncompressed_length = level_length(kind, TexturePixelKind::ASTC4x4RGBA, level)
+                .ok_or(Ktx2Error::InvalidData)?;
+            let level_data = read_level(data, level, uncompressed_length)?;
+            let blocks = decompress(supercompression, level_data, uncompressed_length)?;
+            let face_length = blocks_x * blocks_y * 16;
+
+            blocks
+                .chunks_exact(face_length)
+                .map(|blocks| BasisImage::Uastc {
+                    blocks: blocks.to_vec(),
+                    has_alpha: matches!(channel, DFD_CHANNEL_UASTC_RGBA | DFD_CHANNEL_UASTC_RRRG),
+                })
+                .collect()
+        };
+
+        levels.push((level_width, level_height, images));
+    }
+
+    // Mip-maps must be generated at runtime, so decoded pixels are processed the same way as common
+    // image formats.
+    if let TextureKind::Rectangle { .. } = kind {
+        if stored_level_count == 1 && (level_count == 0 || settings.gen_mip_maps) {
+            let (pixel_kind, pixels) = levels[0].2[0].decode(width as usize, height as usize)?;
+            let mut settings = *settings;
+            settings.gen_mip_maps = true;
+            return Texture::process_image(pixel_kind, width, height, &pixels, &settings);
+        }
+    }
+
+    let mut pixel_kind = TexturePixelKind::RGBA8;
+    let mut bytes = Vec::new();
+    for (level_width, level_height, images) in levels {
+        for image in images {
+            let (image_pixel_kind, image_bytes) =
+                image.transcode(level_width as usize, level_height as usize, settings)?;
+            pixel_kind = image_pixel_kind;
+            bytes.extend_from_slice(&image_bytes);
+        }
+    }
+
+    Ok(Texture {
+        pixel_kind,
+        kind,
+        data_hash: data_hash(&bytes),
+        bytes: bytes.into(),
+        mip_count: stored_level_count,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resource::texture::MipFilter;
+
+    const ETC1S_SAMPLE: &[u8] = include_bytes!("test_data/etc1s.ktx2");
+    const UASTC_SAMPLE: &[u8] = include_bytes!("test_data/uastc.ktx2");
+
+    fn make_ktx2(vk_format: u32, supercompression: u32, pixels: &[u8]) -> Vec<u8> {
+        let mut data = IDENTIFIER.to_vec();
+        for value in [vk_format, 1, 2, 2, 0, 0, 1, 1, supercompression] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        // Empty DFD, KVD and SGD.
+        data.resize(HEADER_SIZE, 0);
+        let offset = (HEADER_SIZE + LEVEL_INDEX_ENTRY_SIZE) as u64;
+        for value in [offset, pixels.len() as u64, pixels.len() as u64] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(pixels);
+        data
+    }
+
+    fn settings() -> TextureProcessingSettings {
+        TextureProcessingSettings {
+            compression: CompressionOptions::NoCompression,
+            compression_family: CompressionFormatFamily::Bc,
+            gen_mip_maps: false,
+            mip_filter: MipFilter::Nearest,
+            srgb: false,
+        }
+    }
+
+    #[test]
+    fn test_load_ktx2() {
+        let pixels = (0..16).collect::<Vec<u8>>();
+        let data = make_ktx2(37, SUPERCOMPRESSION_NONE, &pixels);
+        assert!(is_ktx2(&data));
+
+        let texture = load(&data, &settings()).unwrap();
+        assert_eq!(texture.pixel_kind(), TexturePixelKind::RGBA8);
+        assert_eq!(texture.mip_count(), 1);
+        assert_eq!(texture.data(), pixels.as_slice());
+    }
+
+    // Source image of Basis Universal samples.
+    fn sample_pixel(x: usize, y: usize) -> [u8; 4] {
+        let alpha = if x < 8 { 255 } else { (y * 16 + 15) as u8 };
+        if x >= 16 {
+            [30, 90, 160, alpha]
+        } else if (x / 4 + y / 4) & 1 == 0 {
+            [200, 40, 40, alpha]
+        } else {
+            let luminance = (40 + x * 8 + y * 4) as u8;
+            [luminance, luminance, luminance, alpha]
+        }
+    }
+
+    fn check_sample_pixels(pixels: &[u8], width: usize, height: usize, tolerance: i32) {
+        for y in 0..height {
+            for x in 0..width {
+                let offset = (y * width + x) * 4;
+                for (value, expected) in pixels[offset..offset + 4].iter().zip(sample_pixel(x, y)) {
+                    assert!((*value as i32 - expected as i32).abs() <= tolerance);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_etc1s() {
+        let texture = load(ETC1S_SAMPLE, &settings()).unwrap();
+        assert_eq!(texture.pixel_kind(), TexturePixelKind::RGBA8);
+        assert_eq!(texture.mip_count(), 2);
+        assert_eq!(texture.data().len(), (64 * 16 + 32 * 8) * 4);
+        check_sample_pixels(&texture.data()[..64 * 16 * 4], 64, 16, 10);
+    }
+
+    #[test]
+    fn test_transcode_etc1s_to_etc2() {
+        let settings = TextureProcessingSettings {
+            compression: CompressionOptions::Quality,
+            compression_family: CompressionFormatFamily::Etc2,
+            ..settings()
+        };
+        let texture = load(ETC1S_SAMPLE, &settings).unwrap();
+        assert_eq!(texture.pixel_kind(), TexturePixelKind::ETC2RGBA);
+        assert_eq!(texture.mip_count(), 2);
+        assert_eq!(texture.data().len(), (16 * 4 + 8 * 2) * 16);
+    }
+
+    #[test]
+    fn test_load_uastc() {
+        let texture = load(UASTC_SAMPLE, &settings()).unwrap();
+        assert_eq!(texture.pixel_kind(), TexturePixelKind::RGBA8);
+        assert_eq!(texture.mip_count(), 1);
+        assert_eq!(texture.data().len(), 14 * 10 * 4);
+        check_sample_pixels(texture.data(), 14, 10, 16);
+    }
+
+    #[test]
+    fn test_transcode_uastc_to_astc() {
+        let settings = TextureProcessingSettings {
+            compression: CompressionOptions::Quality,
+            compression_family: CompressionFormatFamily::Astc,
+            ..settings()
+        };
+        let texture = load(UASTC_SAMPLE, &settings).unwrap();
+        assert_eq!(texture.pixel_kind(), TexturePixelKind::ASTC4x4RGBA);
+        assert_eq!(texture.data().len(), 4 * 3 * 16);
+    }
+
+    #[test]
+    fn test_basis_video_frames_are_rejected() {
+        // Mark the first image as a P-frame.
+        let mut data = ETC1S_SAMPLE.to_vec();
+        let image_desc = read_u64(&data, 64).unwrap() as usize + SGD_HEADER_SIZE;
+        data[image_desc..image_desc + 4].copy_from_slice(&IMAGE_FLAG_P_FRAME.to_le_bytes());
+        assert!(matches!(
+            load(&data, &settings()),
+            Err(TextureError::Ktx2(Ktx2Error::BasisUniversal))
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_headers_are_rejected() {
+        let is_invalid = |data: &[u8]| {
+            matches!(
+                load(data, &settings()),
+                Err(TextureError::Ktx2(Ktx2Error::InvalidData))
+            )
+        };
+
+        let pixels = (0..16).collect::<Vec<u8>>();
+        let valid = make_ktx2(37, SUPERCOMPRESSION_NONE, &pixels);
+        for sample in [valid.as_slice(), ETC1S_SAMPLE, UASTC_SAMPLE] {
+            for length in IDENTIFIER.len()..sample.len() {
+                assert!(is_invalid(&sample[..length]));
+            }
+        }
+
+        let with_header_field = |offset: usize, value: u32| {
+            let mut data = valid.clone();
+            data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            data
+        };
+        // Too many levels.
+        assert!(is_invalid(&with_header_field(40, 33)));
+        // Huge texture, that doesn't match the length of the level.
+        assert!(is_invalid(&with_header_field(20, u32::MAX)));
+        assert!(is_invalid(&with_header_field(24, u32::MAX)));
+
+        // Uncompressed length of the level, that doesn't match the size of the texture.
+        let mut data = valid.clone();
+        data[HEADER_SIZE + 16..HEADER_SIZE + 24].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(is_invalid(&data));
+
+        // Level offset and length, that overflow.
+        let mut data = valid;
+        data[HEADER_SIZE..HEADER_SIZE + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(is_invalid(&data));
+    }
+}
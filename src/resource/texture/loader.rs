@@ -8,7 +8,7 @@ use crate::{
         untyped::UntypedResource,
     },
     core::{instant, log::Log},
-    resource::texture::{Texture, TextureImportOptions},
+    resource::texture::{cache::TextureCache, Texture, TextureImportOptions},
 };
 use std::any::Any;
 
@@ -16,11 +16,17 @@ use std::any::Any;
 pub struct TextureLoader {
     /// Default import options for textures.
     pub default_import_options: TextureImportOptions,
+    /// Optional cache of processed textures. Processing (compression, mip-map generation) could
+    /// take a lot of time, the cache allows to do it only once per texture. See [`TextureCache`]
+    /// docs for more info.
+    pub cache: Option<TextureCache>,
 }
 
 impl ResourceLoader for TextureLoader {
     fn extensions(&self) -> &[&str] {
-        &["jpg", "jpeg", "tga", "gif", "bmp", "png", "tiff", "dds"]
+        &[
+            "jpg", "jpeg", "tga", "gif", "bmp", "png", "tiff", "dds", "ktx2",
+        ]
     }
 
     fn into_any(self: Box<Self>) -> Box<dyn Any> {
@@ -42,6 +48,7 @@ impl ResourceLoader for TextureLoader {
         reload: bool,
     ) -> BoxedLoaderFuture {
         let default_import_options = self.default_import_options.clone();
+        let cache = self.cache.clone();
 
         Box::pin(async move {
             let path = texture.path().to_path_buf();
@@ -50,17 +57,10 @@ impl ResourceLoader for TextureLoader {
                 .await
                 .unwrap_or(default_import_options);
 
-            let gen_mip_maps = import_options.minification_filter.is_using_mip_mapping();
+            let settings = import_options.processing_settings();
 
             let time = instant::Instant::now();
-            match Texture::load_from_file(
-                &path,
-                import_options.compression,
-                gen_mip_maps,
-                import_options.mip_filter,
-            )
-            .await
-            {
+            match Texture::load_from_file(&path, &settings, cache.as_ref()).await {
                 Ok(mut raw_texture) => {
                     Log::info(format!(
                         "Texture {:?} is loaded in {:?}!",
@@ -7,11 +7,14 @@
 //! ## Supported formats
 //!
 //! To load images and decode them, Fyrox uses image and ddsfile crates. Here is the list of
-//! supported formats: png, tga, bmp, dds, jpg, gif, tiff, dds.
+//! supported formats: png, tga, bmp, dds, jpg, gif, tiff, dds, ktx2.
 //!
 //! ## Compressed textures
 //!
-//! Fyrox supports most commonly used formats of compressed textures: DXT1, DXT3, DXT5.
+//! Fyrox supports most commonly used formats of compressed textures: DXT1, DXT3, DXT5, ETC2 and ASTC.
+//! Uncompressed textures could be compressed on import, the format is selected according to the
+//! target platform (see [`TargetPlatform`]). Since compression is slow, processed textures could be
+//! stored in [`cache::TextureCache`].
 //!
 //! ## Render target
 //!
@@ -21,7 +24,10 @@
 //! access to pixels of render target.
 
 use crate::{
-    asset::{options::ImportOptions, Resource, ResourceData, TEXTURE_RESOURCE_UUID},
+    asset::{
+        memory::ResourceMemoryUsage, options::ImportOptions, Resource, ResourceData,
+        TEXTURE_RESOURCE_UUID,
+    },
     core::{
         algebra::{Vector2, Vector3},
         futures::io::Error,
@@ -49,8 +55,15 @@ use std::{
 };
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
+mod astc;
+mod basis;
+pub mod cache;
+mod etc2;
+mod ktx2;
 pub mod loader;
 
+pub use ktx2::Ktx2Error;
+
 /// Texture kind.
 #[derive(Copy, Clone, Debug, Reflect)]
 pub enum TextureKind {
@@ -281,6 +294,18 @@ impl ResourceData for Texture {
     fn type_uuid(&self) -> Uuid {
         <Self as TypeUuidProvider>::type_uuid()
     }
+
+    fn memory_usage(&self) -> ResourceMemoryUsage {
+        if self.is_render_target {
+            // Render targets live on GPU only.
+            let gpu = (0..self.mip_count.max(1) as usize)
+                .map(|mip| bytes_in_mip_level(self.kind, self.pixel_kind, mip) as usize)
+                .sum();
+            ResourceMemoryUsage::new(0, gpu)
+        } else {
+            ResourceMemoryUsage::new(self.bytes.len(), self.bytes.len())
+        }
+    }
 }
 
 impl Visit for Texture {
@@ -348,6 +373,9 @@ impl Default for Texture {
     Default,
     Copy,
     Clone,
+    PartialEq,
+    Eq,
+    Hash,
     Deserialize,
     Serialize,
     Debug,
@@ -400,7 +428,9 @@ impl MipFilter {
 ///     s_wrap_mode: Repeat,
 ///     t_wrap_mode: ClampToEdge,
 ///     anisotropy: 8.0,
-///     compression: NoCompression,    
+///     compression: NoCompression,
+///     srgb: true,
+///     target_platform: Some(Android),
 /// )
 /// ```
 #[derive(Clone, Deserialize, Serialize, Debug, Reflect)]
@@ -419,6 +449,10 @@ pub struct TextureImportOptions {
     pub(crate) compression: CompressionOptions,
     #[serde(default)]
     pub(crate) mip_filter: MipFilter,
+    #[serde(default)]
+    pub(crate) srgb: bool,
+    #[serde(default)]
+    pub(crate) target_platform: Option<TargetPlatform>,
 }
 
 impl Default for TextureImportOptions {
@@ -431,6 +465,8 @@ impl Default for TextureImportOptions {
             anisotropy: 16.0,
             compression: CompressionOptions::default(),
             mip_filter: Default::default(),
+            srgb: false,
+            target_platform: None,
         }
     }
 }
@@ -519,6 +555,147 @@ impl TextureImportOptions {
     pub fn set_compression(&mut self, compression: CompressionOptions) {
         self.compression = compression;
     }
+
+    /// Defines whether the texture contains colors in sRGB color space or not. Mip-maps of sRGB
+    /// textures are generated in linear color space, which prevents darkening of distant surfaces.
+    /// It should be enabled for color (albedo, diffuse) textures and disabled for textures with
+    /// non-color data (normal maps, roughness maps, etc.).
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Defines whether the texture contains colors in sRGB color space or not. See
+    /// [`Self::with_srgb`] for more info.
+    pub fn set_srgb(&mut self, srgb: bool) {
+        self.srgb = srgb;
+    }
+
+    /// Sets the platform for which the texture will be compressed. `None` means the platform
+    /// for which the engine is compiled.
+    pub fn with_target_platform(mut self, target_platform: Option<TargetPlatform>) -> Self {
+        self.target_platform = target_platform;
+        self
+    }
+
+    /// Sets the platform for which the texture will be compressed. `None` means the platform
+    /// for which the engine is compiled.
+    pub fn set_target_platform(&mut self, target_platform: Option<TargetPlatform>) {
+        self.target_platform = target_platform;
+    }
+
+    /// Returns texture processing settings, defined by the import options.
+    pub fn processing_settings(&self) -> TextureProcessingSettings {
+        TextureProcessingSettings {
+            compression: self.compression,
+            compression_family: self
+                .target_platform
+                .unwrap_or_else(TargetPlatform::current)
+                .compression_family(),
+            gen_mip_maps: self.minification_filter.is_using_mip_mapping(),
+            mip_filter: self.mip_filter,
+            srgb: self.srgb,
+        }
+    }
+}
+
+/// A family of block compression formats. GPUs usually support only one of them, so the family
+/// must be selected according to the target platform.
+#[derive(
+    Copy,
+    Clone,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum CompressionFormatFamily {
+    /// S3TC (DXT1, DXT5) and RGTC formats, supported by every desktop GPU.
+    Bc,
+    /// ETC2 formats, supported by every OpenGL ES 3.0+ device.
+    Etc2,
+    /// ASTC formats, supported by most modern mobile GPUs.
+    Astc,
+}
+
+/// A platform for which textures are processed on import.
+#[derive(
+    Copy,
+    Clone,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum TargetPlatform {
+    /// Windows, Linux, macOS.
+    Desktop,
+    /// Android devices.
+    Android,
+    /// iOS devices.
+    Ios,
+    /// Web browsers.
+    WebAssembly,
+}
+
+impl Default for TargetPlatform {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+impl TargetPlatform {
+    /// Returns the platform for which the engine is compiled.
+    pub fn current() -> Self {
+        if cfg!(target_os = "android") {
+            Self::Android
+        } else if cfg!(target_os = "ios") {
+            Self::Ios
+        } else if cfg!(target_arch = "wasm32") {
+            Self::WebAssembly
+        } else {
+            Self::Desktop
+        }
+    }
+
+    /// Returns the family of compression formats, that is used for the platform.
+    pub fn compression_family(self) -> CompressionFormatFamily {
+        match self {
+            // Most of WebGL implementations on desktops support S3TC.
+            Self::Desktop | Self::WebAssembly => CompressionFormatFamily::Bc,
+            Self::Android => CompressionFormatFamily::Etc2,
+            Self::Ios => CompressionFormatFamily::Astc,
+        }
+    }
+}
+
+/// A set of settings, that defines how a source image is processed on import. It is also used as a
+/// part of the key in [`cache::TextureCache`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TextureProcessingSettings {
+    /// Compression quality. See [`CompressionOptions`] docs for more info.
+    pub compression: CompressionOptions,
+    /// Family of compression formats.
+    pub compression_family: CompressionFormatFamily,
+    /// Whether to generate mip-maps or not.
+    pub gen_mip_maps: bool,
+    /// Filter used for mip-map generation.
+    pub mip_filter: MipFilter,
+    /// Whether the image contains colors in sRGB color space or not. See
+    /// [`TextureImportOptions::with_srgb`] for more info.
+    pub srgb: bool,
 }
 
 /// Type alias for texture resources.
@@ -873,6 +1050,15 @@ pub enum TexturePixelKind {
 
     /// Red component as 2-byte, half-precision float.
     R16F = 24,
+
+    /// Compressed ETC2 RGB.
+    ETC2RGB = 25,
+
+    /// Compressed ETC2 RGBA (with EAC alpha).
+    ETC2RGBA = 26,
+
+    /// Compressed ASTC RGBA with 4x4 blocks.
+    ASTC4x4RGBA = 27,
 }
 
 impl TexturePixelKind {
@@ -903,6 +1089,9 @@ impl TexturePixelKind {
             22 => Ok(Self::RGB16F),
             23 => Ok(Self::R32F),
             24 => Ok(Self::R16F),
+            25 => Ok(Self::ETC2RGB),
+            26 => Ok(Self::ETC2RGBA),
+            27 => Ok(Self::ASTC4x4RGBA),
             _ => Err(format!("Invalid texture kind {}!", id)),
         }
     }
@@ -932,7 +1121,10 @@ impl TexturePixelKind {
             | Self::DXT3RGBA
             | Self::DXT5RGBA
             | Self::R8RGTC
-            | Self::RG8RGTC => None,
+            | Self::RG8RGTC
+            | Self::ETC2RGB
+            | Self::ETC2RGBA
+            | Self::ASTC4x4RGBA => None,
         }
     }
 }
@@ -948,6 +1140,8 @@ pub enum TextureError {
     Image(image::ImageError),
     /// An error occurred during file loading.
     FileLoadError(FileLoadError),
+    /// An error occurred during KTX2 container parsing.
+    Ktx2(Ktx2Error),
 }
 
 impl Display for TextureError {
@@ -965,6 +1159,9 @@ impl Display for TextureError {
             TextureError::FileLoadError(v) => {
                 write!(f, "A file load error has occurred {v:?}")
             }
+            TextureError::Ktx2(v) => {
+                write!(f, "KTX2 error: {v}")
+            }
         }
     }
 }
@@ -975,6 +1172,12 @@ impl From<FileLoadError> for TextureError {
     }
 }
 
+impl From<Ktx2Error> for TextureError {
+    fn from(v: Ktx2Error) -> Self {
+        Self::Ktx2(v)
+    }
+}
+
 impl From<image::ImageError> for TextureError {
     fn from(v: ImageError) -> Self {
         Self::Image(v)
@@ -1005,6 +1208,7 @@ fn ceil_div_4(x: u32) -> u32 {
     Serialize,
     PartialEq,
     Eq,
+    Hash,
     Debug,
     Reflect,
     EnumVariantNames,
@@ -1021,6 +1225,9 @@ pub enum CompressionOptions {
     /// Compression ratio is 1:8 (without alpha) or 1:6 (with 1-bit alpha).
     /// This option provides maximum speed by having lowest requirements of memory
     /// bandwidth.
+    ///
+    /// On platforms that use ETC2 or ASTC, RGB images are encoded via ETC2 RGB or ASTC 4x4
+    /// and RGBA images via ETC2 RGBA or ASTC 4x4.
     Speed = 1,
 
     /// An image will be encoded via DXT5 (BC5) compression with high quality if it is
@@ -1028,6 +1235,9 @@ pub enum CompressionOptions {
     /// Compression ratio is 1:4 (including alpha)
     /// This option is faster than `NoCompression` speed by lower requirements of memory
     /// bandwidth.
+    ///
+    /// On platforms that use ETC2 or ASTC, the same formats as for [`Self::Speed`] are used, but
+    /// ASTC encoder spends more time to find better endpoints.
     Quality = 2,
 }
 
@@ -1079,6 +1289,72 @@ fn compress_rg8_bc4<T: tbc::color::ColorRedGreen8>(
     tbc::encode_image_bc4_rg8_conv_u8::<T>(transmute_slice::<T>(bytes), width, height)
 }
 
+/// Reads a 4x4 block of pixels from an RGB8 or RGBA8 image, pixels outside of the image are
+/// clamped to its edges. Alpha is set to 255 for RGB8 images.
+fn read_block_rgba8(
+    bytes: &[u8],
+    channels: usize,
+    width: usize,
+    height: usize,
+    block_x: usize,
+    block_y: usize,
+) -> [[u8; 4]; 16] {
+    let mut pixels = [[255; 4]; 16];
+    for y in 0..4 {
+        for x in 0..4 {
+            let image_x = (block_x * 4 + x).min(width - 1);
+            let image_y = (block_y * 4 + y).min(height - 1);
+            let offset = (image_y * width + image_x) * channels;
+            pixels[y * 4 + x][..channels].copy_from_slice(&bytes[offset..offset + channels]);
+        }
+    }
+    pixels
+}
+
+fn srgb_to_linear_u16(bytes: &[u8], channels: usize) -> Vec<u8> {
+    let table = (0..256)
+        .map(|value| {
+            let value = value as f32 / 255.0;
+            let linear = if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            };
+            (linear * 65535.0).round() as u16
+        })
+        .collect::<Vec<_>>();
+
+    let mut linear = Vec::with_capacity(bytes.len() * 2);
+    for (i, byte) in bytes.iter().enumerate() {
+        // Alpha is always linear.
+        let value = if i % channels == 3 {
+            *byte as u16 * 257
+        } else {
+            table[*byte as usize]
+        };
+        linear.extend_from_slice(&value.to_ne_bytes());
+    }
+    linear
+}
+
+fn linear_u16_to_srgb(bytes: &[u8], channels: usize) -> Vec<u8> {
+    bytes
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let value = u16::from_ne_bytes([pair[0], pair[1]]) as f32 / 65535.0;
+            let srgb = if i % channels == 3 {
+                value
+            } else if value <= 0.0031308 {
+                value * 12.92
+            } else {
+                1.055 * value.powf(1.0 / 2.4) - 0.055
+            };
+            (srgb * 255.0).round().clamp(0.0, 255.0) as u8
+        })
+        .collect()
+}
+
 fn data_hash(data: &[u8]) -> u64 {
     let mut hasher = FxHasher::default();
     data.hash(&mut hasher);
@@ -1091,6 +1367,48 @@ fn try_compress(
     w: usize,
     h: usize,
     compression: CompressionOptions,
+    family: CompressionFormatFamily,
+) -> Option<(Vec<u8>, TexturePixelKind)> {
+    if compression == CompressionOptions::NoCompression {
+        return None;
+    }
+
+    match family {
+        CompressionFormatFamily::Bc => try_compress_bc(pixel_kind, bytes, w, h, compression),
+        CompressionFormatFamily::Etc2 => match pixel_kind {
+            TexturePixelKind::RGB8 => Some((
+                etc2::encode_image_etc2_rgb8(bytes, 3, w, h),
+                TexturePixelKind::ETC2RGB,
+            )),
+            TexturePixelKind::RGBA8 => Some((
+                etc2::encode_image_etc2_rgba8(bytes, w, h),
+                TexturePixelKind::ETC2RGBA,
+            )),
+            _ => None,
+        },
+        CompressionFormatFamily::Astc => {
+            let refine = compression == CompressionOptions::Quality;
+            match pixel_kind {
+                TexturePixelKind::RGB8 => Some((
+                    astc::encode_image_astc_4x4(bytes, 3, w, h, refine),
+                    TexturePixelKind::ASTC4x4RGBA,
+                )),
+                TexturePixelKind::RGBA8 => Some((
+                    astc::encode_image_astc_4x4(bytes, 4, w, h, refine),
+                    TexturePixelKind::ASTC4x4RGBA,
+                )),
+                _ => None,
+            }
+        }
+    }
+}
+
+fn try_compress_bc(
+    pixel_kind: TexturePixelKind,
+    bytes: &[u8],
+    w: usize,
+    h: usize,
+    compression: CompressionOptions,
 ) -> Option<(Vec<u8>, TexturePixelKind)> {
     match (pixel_kind, compression) {
         (TexturePixelKind::RGB8, CompressionOptions::Speed) => Some((
@@ -1163,14 +1481,20 @@ fn bytes_in_mip_level(kind: TextureKind, pixel_kind: TexturePixelKind, mip: usiz
         | TexturePixelKind::DXT3RGBA
         | TexturePixelKind::DXT5RGBA
         | TexturePixelKind::R8RGTC
-        | TexturePixelKind::RG8RGTC => {
+        | TexturePixelKind::RG8RGTC
+        | TexturePixelKind::ETC2RGB
+        | TexturePixelKind::ETC2RGBA
+        | TexturePixelKind::ASTC4x4RGBA => {
             let block_size = match pixel_kind {
                 TexturePixelKind::DXT1RGB
                 | TexturePixelKind::DXT1RGBA
-                | TexturePixelKind::R8RGTC => 8,
+                | TexturePixelKind::R8RGTC
+                | TexturePixelKind::ETC2RGB => 8,
                 TexturePixelKind::DXT3RGBA
                 | TexturePixelKind::DXT5RGBA
-                | TexturePixelKind::RG8RGTC => 16,
+                | TexturePixelKind::RG8RGTC
+                | TexturePixelKind::ETC2RGBA
+                | TexturePixelKind::ASTC4x4RGBA => 16,
                 _ => unreachable!(),
             };
             match kind {
@@ -1228,7 +1552,8 @@ impl Texture {
     /// The data can be compressed if needed to improve performance on GPU side. Mip-maps can be generated as well.
     /// **CAVEAT:** Compression and mip-map generation **won't** be taken into account in case of **DDS** textures,
     /// because DDS can already contain such data, you should generate mips and compress DDS textures manually using
-    /// some offline tool like DirectXTexTool or similar.
+    /// some offline tool like DirectXTexTool or similar. The same applies to **KTX2** textures, except uncompressed
+    /// ones without mip-maps.
     ///
     /// # Important notes
     ///
@@ -1247,6 +1572,29 @@ impl Texture {
         gen_mip_maps: bool,
         mip_filter: MipFilter,
     ) -> Result<Self, TextureError> {
+        Self::load_from_memory_with_settings(
+            data,
+            &TextureProcessingSettings {
+                compression,
+                compression_family: TargetPlatform::current().compression_family(),
+                gen_mip_maps,
+                mip_filter,
+                srgb: false,
+            },
+        )
+    }
+
+    /// Does the same as [`Self::load_from_memory`], but gives full control over processing of the
+    /// image: sRGB handling and compression format family. See [`TextureProcessingSettings`] docs
+    /// for more info.
+    pub fn load_from_memory_with_settings(
+        data: &[u8],
+        settings: &TextureProcessingSettings,
+    ) -> Result<Self, TextureError> {
+        if ktx2::is_ktx2(data) {
+            return ktx2::load(data, settings);
+        }
+
         // DDS is special. It can contain various kinds of textures as well as textures with
         // various pixel formats.
         //
@@ -1342,7 +1690,7 @@ impl Texture {
             let width = dyn_img.width();
             let height = dyn_img.height();
 
-            let mut pixel_kind = match dyn_img {
+            let pixel_kind = match dyn_img {
                 DynamicImage::ImageLuma8(_) => TexturePixelKind::Luminance8,
                 DynamicImage::ImageLumaA8(_) => TexturePixelKind::LuminanceAlpha8,
                 DynamicImage::ImageRgb8(_) => TexturePixelKind::RGB8,
@@ -1356,93 +1704,133 @@ impl Texture {
                 _ => return Err(TextureError::UnsupportedFormat),
             };
 
-            let mut mip_count = 0;
-            let mut bytes = Vec::with_capacity(
-                width as usize * height as usize * pixel_kind.size_in_bytes().unwrap_or(4),
-            );
-
-            if gen_mip_maps {
-                let pixel_type = convert_pixel_type_enum(pixel_kind);
-                let mut level_width = width;
-                let mut level_height = height;
-                let mut current_level = fr::Image::from_vec_u8(
-                    NonZeroU32::new(level_width).unwrap(),
-                    NonZeroU32::new(level_height).unwrap(),
-                    dyn_img.as_bytes().to_vec(),
-                    pixel_type,
-                )
-                .map_err(|_| TextureError::UnsupportedFormat)?;
-
-                while level_width != 0 && level_height != 0 {
-                    if mip_count != 0 {
-                        let mut dst_img = fr::Image::new(
-                            NonZeroU32::new(level_width).unwrap(),
-                            NonZeroU32::new(level_height).unwrap(),
-                            pixel_type,
-                        );
-
-                        let mut resizer = fr::Resizer::new(fr::ResizeAlg::Convolution(
-                            mip_filter.into_filter_type(),
-                        ));
+            Self::process_image(pixel_kind, width, height, dyn_img.as_bytes(), settings)
+        }
+    }
 
-                        resizer
-                            .resize(&current_level.view(), &mut dst_img.view_mut())
-                            .expect("Pixel types must match!");
+    /// Generates mip-maps and compresses an uncompressed rectangle image according to the given
+    /// settings.
+    fn process_image(
+        source_pixel_kind: TexturePixelKind,
+        width: u32,
+        height: u32,
+        source_bytes: &[u8],
+        settings: &TextureProcessingSettings,
+    ) -> Result<Self, TextureError> {
+        let mut pixel_kind = source_pixel_kind;
+        let mut mip_count = 0;
+        let mut bytes = Vec::with_capacity(
+            width as usize * height as usize * pixel_kind.size_in_bytes().unwrap_or(4),
+        );
+
+        let mut encode_level = |level: &[u8], level_width: u32, level_height: u32| {
+            if let Some((compressed_data, new_pixel_kind)) = try_compress(
+                source_pixel_kind,
+                level,
+                level_width as usize,
+                level_height as usize,
+                settings.compression,
+                settings.compression_family,
+            ) {
+                pixel_kind = new_pixel_kind;
+                bytes.extend_from_slice(&compressed_data);
+            } else {
+                bytes.extend_from_slice(level);
+            }
+        };
 
-                        current_level = dst_img;
-                    }
+        if settings.gen_mip_maps {
+            // Mip levels of sRGB images must be calculated in linear color space, otherwise
+            // they will be darker than they should be. 16 bits per channel are used to not
+            // lose precision in dark areas.
+            let channels = match source_pixel_kind {
+                TexturePixelKind::RGB8 => 3,
+                TexturePixelKind::RGBA8 => 4,
+                _ => 0,
+            };
+            let linearize = settings.srgb && channels != 0;
 
-                    mip_count += 1;
-
-                    if compression == CompressionOptions::NoCompression {
-                        bytes.extend_from_slice(current_level.buffer())
-                    } else if let Some((compressed_data, new_pixel_kind)) = try_compress(
-                        pixel_kind,
-                        current_level.buffer(),
-                        level_width as usize,
-                        level_height as usize,
-                        compression,
-                    ) {
-                        pixel_kind = new_pixel_kind;
-                        bytes.extend_from_slice(&compressed_data);
+            let (pixel_type, level_bytes) = if linearize {
+                (
+                    if channels == 3 {
+                        fr::PixelType::U16x3
                     } else {
-                        bytes.extend_from_slice(current_level.buffer())
-                    }
-
-                    level_width = level_width.checked_shr(1).unwrap_or_default();
-                    level_height = level_height.checked_shr(1).unwrap_or_default();
-                }
+                        fr::PixelType::U16x4
+                    },
+                    srgb_to_linear_u16(source_bytes, channels),
+                )
             } else {
-                mip_count = 1;
-
-                if compression == CompressionOptions::NoCompression {
-                    bytes.extend_from_slice(dyn_img.as_bytes());
-                } else if let Some((compressed_data, new_pixel_kind)) = try_compress(
-                    pixel_kind,
-                    dyn_img.as_bytes(),
-                    width as usize,
-                    height as usize,
-                    compression,
-                ) {
-                    pixel_kind = new_pixel_kind;
-                    bytes.extend_from_slice(&compressed_data);
+                (
+                    convert_pixel_type_enum(source_pixel_kind),
+                    source_bytes.to_vec(),
+                )
+            };
+
+            let mut level_width = width;
+            let mut level_height = height;
+            let mut current_level = fr::Image::from_vec_u8(
+                NonZeroU32::new(level_width).unwrap(),
+                NonZeroU32::new(level_height).unwrap(),
+                level_bytes,
+                pixel_type,
+            )
+            .map_err(|_| TextureError::UnsupportedFormat)?;
+
+            while level_width != 0 && level_height != 0 {
+                if mip_count == 0 {
+                    // The first level is the source image itself.
+                    encode_level(source_bytes, level_width, level_height);
                 } else {
-                    bytes.extend_from_slice(dyn_img.as_bytes())
+                    let mut dst_img = fr::Image::new(
+                        NonZeroU32::new(level_width).unwrap(),
+                        NonZeroU32::new(level_height).unwrap(),
+                        pixel_type,
+                    );
+
+                    let mut resizer = fr::Resizer::new(fr::ResizeAlg::Convolution(
+                        settings.mip_filter.into_filter_type(),
+                    ));
+
+                    resizer
+                        .resize(&current_level.view(), &mut dst_img.view_mut())
+                        .expect("Pixel types must match!");
+
+                    current_level = dst_img;
+
+                    if linearize {
+                        encode_level(
+                            &linear_u16_to_srgb(current_level.buffer(), channels),
+                            level_width,
+                            level_height,
+                        );
+                    } else {
+                        encode_level(current_level.buffer(), level_width, level_height);
+                    }
                 }
+
+                mip_count += 1;
+
+                level_width = level_width.checked_shr(1).unwrap_or_default();
+                level_height = level_height.checked_shr(1).unwrap_or_default();
             }
+        } else {
+            mip_count = 1;
 
-            Ok(Self {
-                pixel_kind,
-                kind: TextureKind::Rectangle { width, height },
-                data_hash: data_hash(&bytes),
-                bytes: bytes.into(),
-                mip_count,
-                ..Default::default()
-            })
+            encode_level(source_bytes, width, height);
         }
+
+        Ok(Self {
+            pixel_kind,
+            kind: TextureKind::Rectangle { width, height },
+            data_hash: data_hash(&bytes),
+            bytes: bytes.into(),
+            mip_count,
+            ..Default::default()
+        })
     }
 
-    /// Tries to load a texture from a file.
+    /// Tries to load a texture from a file. If the cache is specified, processed texture is taken
+    /// from it or put in it after processing.
     ///
     /// # Notes
     ///
@@ -1450,12 +1838,24 @@ impl Texture {
     /// resources.
     pub(crate) async fn load_from_file<P: AsRef<Path>>(
         path: P,
-        compression: CompressionOptions,
-        gen_mip_maps: bool,
-        mip_filter: MipFilter,
+        settings: &TextureProcessingSettings,
+        cache: Option<&cache::TextureCache>,
     ) -> Result<Self, TextureError> {
         let data = io::load_file(path.as_ref()).await?;
-        let mut texture = Self::load_from_memory(&data, compression, gen_mip_maps, mip_filter)?;
+
+        // DDS and KTX2 textures are usually not processed, so there is no need to cache them.
+        let cache = cache.filter(|_| !data.starts_with(b"DDS ") && !ktx2::is_ktx2(&data));
+
+        let mut texture = match cache.and_then(|cache| cache.load(&data, settings)) {
+            Some(texture) => texture,
+            None => {
+                let texture = Self::load_from_memory_with_settings(&data, settings)?;
+                if let Some(cache) = cache {
+                    cache.store(&data, settings, &texture);
+                }
+                texture
+            }
+        };
         texture.path = path.as_ref().to_path_buf();
         Ok(texture)
     }
@@ -1486,6 +1886,36 @@ impl Texture {
         }
     }
 
+    /// Creates new texture instance from given parameters. Unlike [`Self::from_bytes`], the data
+    /// must contain the given amount of mip levels, starting from the largest one. Each mip level is
+    /// two times smaller than the previous one in every dimension. For cube textures, every mip
+    /// level contains all six faces.
+    pub fn from_bytes_with_mips(
+        kind: TextureKind,
+        pixel_kind: TexturePixelKind,
+        mip_count: u32,
+        bytes: Vec<u8>,
+        serialize_content: bool,
+    ) -> Option<Self> {
+        let total_size = (0..mip_count.max(1) as usize)
+            .map(|mip| bytes_in_mip_level(kind, pixel_kind, mip) as usize)
+            .sum::<usize>();
+        if total_size != bytes.len() {
+            None
+        } else {
+            Some(Self {
+                path: Default::default(),
+                kind,
+                data_hash: data_hash(&bytes),
+                bytes: bytes.into(),
+                pixel_kind,
+                mip_count: mip_count.max(1),
+                serialize_content,
+                ..Default::default()
+            })
+        }
+    }
+
     /// Sets new minification filter. It is used when texture becomes smaller.
     pub fn set_minification_filter(&mut self, filter: TextureMinificationFilter) {
         self.minification_filter = filter;
@@ -1663,7 +2093,10 @@ impl Texture {
             | TexturePixelKind::BGRA8
             | TexturePixelKind::RGB16F
             | TexturePixelKind::R32F
-            | TexturePixelKind::R16F => return Err(TextureError::UnsupportedFormat),
+            | TexturePixelKind::R16F
+            | TexturePixelKind::ETC2RGB
+            | TexturePixelKind::ETC2RGBA
+            | TexturePixelKind::ASTC4x4RGBA => return Err(TextureError::UnsupportedFormat),
         };
         if let TextureKind::Rectangle { width, height } = self.kind {
             Ok(image::save_buffer(
@@ -1756,4 +2189,12 @@ pub mod test {
         )
         .unwrap()
     }
+
+    /// Converts a string of hexadecimal digits into bytes, used to store reference data in tests.
+    pub fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..(i + 2)], 16).unwrap())
+            .collect()
+    }
 }
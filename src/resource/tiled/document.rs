@@ -0,0 +1,1000 @@
+//! Format-agnostic representation of Tiled maps and tile sets, and parsers for both flavors of the
+//! format: XML (`.tmx`/`.tsx`) and JSON (`.tmj`/`.tsj`).
+
+use crate::{
+    core::{algebra::Vector2, color::Color},
+    resource::tiled::error::TiledError,
+};
+use base64::Engine;
+use serde_json::Value;
+use std::str::FromStr;
+use xml::reader::{EventReader, XmlEvent};
+
+/// An image of a tile set or of a single tile.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledImage {
+    /// Path to the image, relative to the file that refers to it.
+    pub source: String,
+    /// Width of the image in pixels.
+    pub width: u32,
+    /// Height of the image in pixels.
+    pub height: u32,
+}
+
+/// A frame of tile animation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledFrame {
+    /// Local id of a tile in the same tile set.
+    pub tile_id: u32,
+    /// Duration of the frame in milliseconds.
+    pub duration: u32,
+}
+
+/// An object of a tile collision group. Coordinates are in pixels relative to the top-left corner
+/// of a tile, Y axis points down.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledObject {
+    /// Horizontal position of the object in pixels.
+    pub x: f32,
+    /// Vertical position of the object in pixels.
+    pub y: f32,
+    /// Width of the object in pixels.
+    pub width: f32,
+    /// Height of the object in pixels.
+    pub height: f32,
+    /// Clockwise rotation of the object around its position in degrees.
+    pub rotation: f32,
+    /// Points of the polygon relative to the object position, `None` for rectangles.
+    pub polygon: Option<Vec<Vector2<f32>>>,
+    /// Points do not have any area and cannot be used for collision.
+    pub point: bool,
+}
+
+/// Additional information about a tile in a tile set.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledTile {
+    /// Local id of the tile in its tile set.
+    pub id: u32,
+    /// Image of the tile, tiles of "collection of images" tile sets have their own images.
+    pub image: Option<TiledImage>,
+    /// Animation frames of the tile, empty if the tile is not animated.
+    pub animation: Vec<TiledFrame>,
+    /// Collision objects of the tile.
+    pub objects: Vec<TiledObject>,
+}
+
+/// A set of tiles that share the same size, either cut from a single image or made of separate
+/// images.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledTileset {
+    /// Width of a tile in pixels.
+    pub tile_width: u32,
+    /// Height of a tile in pixels.
+    pub tile_height: u32,
+    /// Spacing between adjacent tiles of the image in pixels.
+    pub spacing: u32,
+    /// Margin around the tiles of the image in pixels.
+    pub margin: u32,
+    /// Number of tile columns in the image.
+    pub columns: u32,
+    /// Total number of tiles in the tile set.
+    pub tile_count: u32,
+    /// Image of the tile set, `None` for "collection of images" tile sets.
+    pub image: Option<TiledImage>,
+    /// Tiles that have additional information, tiles without it are not listed.
+    pub tiles: Vec<TiledTile>,
+}
+
+/// A tile set of a map. External tile sets are stored in separate files and they must be loaded
+/// separately, `tileset` is empty for them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledTilesetEntry {
+    /// Global id of the first tile of the tile set.
+    pub first_gid: u32,
+    /// Path to the external tile set file, `None` for embedded tile sets.
+    pub source: Option<String>,
+    /// Contents of the tile set.
+    pub tileset: TiledTileset,
+}
+
+/// A rectangular block of a tile layer. Finite maps have exactly one chunk per layer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledChunk {
+    /// Horizontal position of the chunk in tiles.
+    pub x: i32,
+    /// Vertical position of the chunk in tiles.
+    pub y: i32,
+    /// Width of the chunk in tiles.
+    pub width: u32,
+    /// Height of the chunk in tiles.
+    pub height: u32,
+    /// Global tile ids (with flip flags) in row-major order.
+    pub gids: Vec<u32>,
+}
+
+/// A tile layer, group layers are flattened, so their visibility, opacity and tint are already
+/// applied to the layers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledLayer {
+    /// Name of the layer.
+    pub name: String,
+    /// Whether the layer and all its parent groups are visible.
+    pub visible: bool,
+    /// Opacity of the layer multiplied by the opacity of its parent groups.
+    pub opacity: f32,
+    /// Tint color of the layer multiplied by the tint of its parent groups.
+    pub tint: Color,
+    /// Tile data of the layer.
+    pub chunks: Vec<TiledChunk>,
+}
+
+/// A map made of tile layers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledMap {
+    /// Orientation of the map, for example `orthogonal` or `isometric`.
+    pub orientation: String,
+    /// Width of a tile of the map grid in pixels.
+    pub tile_width: u32,
+    /// Height of a tile of the map grid in pixels.
+    pub tile_height: u32,
+    /// Tile sets used by the map.
+    pub tilesets: Vec<TiledTilesetEntry>,
+    /// Tile layers of the map in drawing order.
+    pub layers: Vec<TiledLayer>,
+}
+
+fn invalid_data<S: Into<String>>(message: S) -> TiledError {
+    TiledError::InvalidData(message.into())
+}
+
+/// Parses tint color in `#RRGGBB` or `#AARRGGBB` form.
+fn parse_color(text: &str) -> Result<Color, TiledError> {
+    let hex = text.trim_start_matches('#');
+    let value = u32::from_str_radix(hex, 16)
+        .map_err(|_| invalid_data(format!("Invalid color {}", text)))?;
+    let [a, r, g, b] = value.to_be_bytes();
+    match hex.len() {
+        6 => Ok(Color::from_rgba(r, g, b, 255)),
+        8 => Ok(Color::from_rgba(r, g, b, a)),
+        _ => Err(invalid_data(format!("Invalid color {}", text))),
+    }
+}
+
+fn multiply_colors(a: Color, b: Color) -> Color {
+    let mul = |a: u8, b: u8| ((a as u32 * b as u32) / 255) as u8;
+    Color::from_rgba(mul(a.r, b.r), mul(a.g, b.g), mul(a.b, b.b), mul(a.a, b.a))
+}
+
+// Tiled writes gzip streams with standard header, skip it and inflate the rest.
+fn gzip_payload(data: &[u8]) -> Result<&[u8], TiledError> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+
+    let corrupted = || TiledError::Decompression("Corrupted gzip header".to_string());
+
+    if data.len() < 18 || data[0] != 0x1f || data[1] != 0x8b || data[2] != 8 {
+        return Err(corrupted());
+    }
+
+    let flags = data[3];
+    let mut position = 10;
+    if flags & FEXTRA != 0 {
+        let length = data.get(position..position + 2).ok_or_else(corrupted)?;
+        position += 2 + u16::from_le_bytes([length[0], length[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = data
+                .get(position..)
+                .and_then(|rest| rest.iter().position(|byte| *byte == 0))
+                .ok_or_else(corrupted)?;
+            position += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        position += 2;
+    }
+
+    // The stream ends with CRC32 and size of the uncompressed data.
+    data.get(position..data.len() - 8).ok_or_else(corrupted)
+}
+
+fn decompress(data: Vec<u8>, compression: Option<&str>) -> Result<Vec<u8>, TiledError> {
+    match compression {
+        None | Some("") => Ok(data),
+        Some("zlib") => inflate::inflate_bytes_zlib(&data).map_err(TiledError::Decompression),
+        Some("gzip") => {
+            inflate::inflate_bytes(gzip_payload(&data)?).map_err(TiledError::Decompression)
+        }
+        Some("zstd") => {
+            use std::io::Read;
+
+            let mut source = data.as_slice();
+            let mut decoder = ruzstd::streaming_decoder::StreamingDecoder::new(&mut source)
+                .map_err(|err| TiledError::Decompression(format!("{:?}", err)))?;
+            let mut decompressed = Vec::new();
+            decoder
+                .read_to_end(&mut decompressed)
+                .map_err(|err| TiledError::Decompression(err.to_string()))?;
+            Ok(decompressed)
+        }
+        Some(other) => Err(TiledError::UnsupportedEncoding(other.to_string())),
+    }
+}
+
+/// Decodes tile layer data encoded as CSV or (optionally compressed) base64.
+fn decode_data(
+    text: &str,
+    encoding: &str,
+    compression: Option<&str>,
+) -> Result<Vec<u32>, TiledError> {
+    match encoding {
+        "csv" => text
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                token
+                    .parse()
+                    .map_err(|_| invalid_data(format!("Invalid tile id {}", token)))
+            })
+            .collect(),
+        "base64" => {
+            let text = text
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>();
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .map_err(|err| invalid_data(format!("Invalid base64 data: {}", err)))?;
+            let bytes = decompress(bytes, compression)?;
+            if bytes.len() % 4 != 0 {
+                return Err(invalid_data("Size of layer data must be a multiple of 4"));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        other => Err(TiledError::UnsupportedEncoding(other.to_string())),
+    }
+}
+
+fn check_chunk_size(chunk: &TiledChunk) -> Result<(), TiledError> {
+    if chunk.gids.len() == (chunk.width * chunk.height) as usize {
+        Ok(())
+    } else {
+        Err(invalid_data(format!(
+            "Layer data has {} tiles, but {} tiles were expected",
+            chunk.gids.len(),
+            chunk.width * chunk.height
+        )))
+    }
+}
+
+#[derive(Default, Debug)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find_map(|(key, value)| {
+            if key == name {
+                Some(value.as_str())
+            } else {
+                None
+            }
+        })
+    }
+
+    fn parse_attribute<T: FromStr>(&self, name: &str, default: T) -> Result<T, TiledError> {
+        match self.attribute(name) {
+            Some(value) => value.parse().map_err(|_| {
+                invalid_data(format!(
+                    "Invalid value {} of attribute {} of element {}",
+                    value, name, self.name
+                ))
+            }),
+            None => Ok(default),
+        }
+    }
+
+    fn required_attribute<T: FromStr>(&self, name: &str) -> Result<T, TiledError> {
+        let value = self.attribute(name).ok_or_else(|| {
+            invalid_data(format!(
+                "Element {} does not have attribute {}",
+                self.name, name
+            ))
+        })?;
+        value.parse().map_err(|_| {
+            invalid_data(format!(
+                "Invalid value {} of attribute {} of element {}",
+                value, name, self.name
+            ))
+        })
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+fn parse_xml(data: &[u8]) -> Result<XmlElement, TiledError> {
+    let mut stack = vec![XmlElement::default()];
+
+    for event in EventReader::new(data) {
+        match event.map_err(|err| TiledError::Xml(err.to_string()))? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => stack.push(XmlElement {
+                name: name.local_name,
+                attributes: attributes
+                    .into_iter()
+                    .map(|attribute| (attribute.name.local_name, attribute.value))
+                    .collect(),
+                ..Default::default()
+            }),
+            XmlEvent::EndElement { .. } => {
+                // The reader guarantees that elements are balanced.
+                if let Some(element) = stack.pop() {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(element);
+                    }
+                }
+            }
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    stack
+        .pop()
+        .and_then(|document| document.children.into_iter().next())
+        .ok_or_else(|| TiledError::Xml("Document has no root element".to_string()))
+}
+
+fn parse_xml_image(element: &XmlElement) -> Result<TiledImage, TiledError> {
+    Ok(TiledImage {
+        source: element.required_attribute("source")?,
+        width: element.parse_attribute("width", 0)?,
+        height: element.parse_attribute("height", 0)?,
+    })
+}
+
+fn parse_points(text: &str) -> Result<Vec<Vector2<f32>>, TiledError> {
+    text.split_whitespace()
+        .map(|point| {
+            point
+                .split_once(',')
+                .and_then(|(x, y)| Some(Vector2::new(x.parse().ok()?, y.parse().ok()?)))
+                .ok_or_else(|| invalid_data(format!("Invalid point {}", point)))
+        })
+        .collect()
+}
+
+fn parse_xml_object(element: &XmlElement) -> Result<TiledObject, TiledError> {
+    Ok(TiledObject {
+        x: element.parse_attribute("x", 0.0)?,
+        y: element.parse_attribute("y", 0.0)?,
+        width: element.parse_attribute("width", 0.0)?,
+        height: element.parse_attribute("height", 0.0)?,
+        rotation: element.parse_attribute("rotation", 0.0)?,
+        polygon: match element.child("polygon") {
+            Some(polygon) => Some(parse_points(
+                polygon.attribute("points").unwrap_or_default(),
+            )?),
+            None => None,
+        },
+        point: element.child("point").is_some(),
+    })
+}
+
+fn parse_xml_tileset(element: &XmlElement) -> Result<TiledTileset, TiledError> {
+    let mut tiles = Vec::new();
+    for tile in element.children("tile") {
+        tiles.push(TiledTile {
+            id: tile.required_attribute("id")?,
+            image: tile.child("image").map(parse_xml_image).transpose()?,
+            animation: tile
+                .child("animation")
+                .map(|animation| {
+                    animation
+                        .children("frame")
+                        .map(|frame| {
+                            Ok(TiledFrame {
+                                tile_id: frame.required_attribute("tileid")?,
+                                duration: frame.required_attribute("duration")?,
+                            })
+                        })
+                        .collect::<Result<Vec<_>, TiledError>>()
+                })
+                .transpose()?
+                .unwrap_or_default(),
+            objects: tile
+                .child("objectgroup")
+                .map(|group| {
+                    group
+                        .children("object")
+                        .map(parse_xml_object)
+                        .collect::<Result<Vec<_>, TiledError>>()
+                })
+                .transpose()?
+                .unwrap_or_default(),
+        });
+    }
+
+    Ok(TiledTileset {
+        tile_width: element.required_attribute("tilewidth")?,
+        tile_height: element.required_attribute("tileheight")?,
+        spacing: element.parse_attribute("spacing", 0)?,
+        margin: element.parse_attribute("margin", 0)?,
+        columns: element.parse_attribute("columns", 0)?,
+        tile_count: element.parse_attribute("tilecount", 0)?,
+        image: element.child("image").map(parse_xml_image).transpose()?,
+        tiles,
+    })
+}
+
+// Encoding of chunks is defined by their data element, so `data` and `content` are different
+// elements for infinite maps.
+fn parse_xml_data(
+    data: &XmlElement,
+    content: &XmlElement,
+    chunk: &mut TiledChunk,
+) -> Result<(), TiledError> {
+    chunk.gids = match data.attribute("encoding") {
+        Some(encoding) => decode_data(&content.text, encoding, data.attribute("compression"))?,
+        // Deprecated, but still supported by Tiled: each tile is a separate element.
+        None => content
+            .children("tile")
+            .map(|tile| tile.parse_attribute("gid", 0))
+            .collect::<Result<Vec<_>, TiledError>>()?,
+    };
+    check_chunk_size(chunk)
+}
+
+struct LayerParameters {
+    visible: bool,
+    opacity: f32,
+    tint: Color,
+}
+
+impl Default for LayerParameters {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            opacity: 1.0,
+            tint: Color::WHITE,
+        }
+    }
+}
+
+impl LayerParameters {
+    fn combine(&self, visible: bool, opacity: f32, tint: Option<Color>) -> Self {
+        Self {
+            visible: self.visible && visible,
+            opacity: self.opacity * opacity,
+            tint: tint.map_or(self.tint, |tint| multiply_colors(self.tint, tint)),
+        }
+    }
+}
+
+fn parse_xml_layers(
+    parent: &XmlElement,
+    parameters: &LayerParameters,
+    map_size: (u32, u32),
+    layers: &mut Vec<TiledLayer>,
+) -> Result<(), TiledError> {
+    for element in parent.children.iter() {
+        let tint = element
+            .attribute("tintcolor")
+            .map(parse_color)
+            .transpose()?;
+        let parameters = parameters.combine(
+            element.parse_attribute("visible", 1)? != 0,
+            element.parse_attribute("opacity", 1.0)?,
+            tint,
+        );
+
+        match element.name.as_str() {
+            "layer" => {
+                let mut chunks = Vec::new();
+                if let Some(data) = element.child("data") {
+                    if data.child("chunk").is_some() {
+                        // Infinite maps store layers as a set of chunks.
+                        for chunk_element in data.children("chunk") {
+                            let mut chunk = TiledChunk {
+                                x: chunk_element.required_attribute("x")?,
+                                y: chunk_element.required_attribute("y")?,
+                                width: chunk_element.required_attribute("width")?,
+                                height: chunk_element.required_attribute("height")?,
+                                gids: Default::default(),
+                            };
+                            parse_xml_data(data, chunk_element, &mut chunk)?;
+                            chunks.push(chunk);
+                        }
+                    } else {
+                        let mut chunk = TiledChunk {
+                            x: 0,
+                            y: 0,
+                            width: element.parse_attribute("width", map_size.0)?,
+                            height: element.parse_attribute("height", map_size.1)?,
+                            gids: Default::default(),
+                        };
+                        parse_xml_data(data, data, &mut chunk)?;
+                        chunks.push(chunk);
+                    }
+                }
+
+                layers.push(TiledLayer {
+                    name: element.parse_attribute("name", String::new())?,
+                    visible: parameters.visible,
+                    opacity: parameters.opacity,
+                    tint: parameters.tint,
+                    chunks,
+                });
+            }
+            "group" => parse_xml_layers(element, &parameters, map_size, layers)?,
+            // Object and image layers are not supported.
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Parses a map in TMX (XML) format.
+pub fn parse_tmx(data: &[u8]) -> Result<TiledMap, TiledError> {
+    let root = parse_xml(data)?;
+    if root.name != "map" {
+        return Err(invalid_data("Root element must be map"));
+    }
+
+    let mut tilesets = Vec::new();
+    for element in root.children("tileset") {
+        let source = element.attribute("source").map(ToOwned::to_owned);
+        tilesets.push(TiledTilesetEntry {
+            first_gid: element.required_attribute("firstgid")?,
+            tileset: if source.is_some() {
+                Default::default()
+            } else {
+                parse_xml_tileset(element)?
+            },
+            source,
+        });
+    }
+
+    let mut layers = Vec::new();
+    parse_xml_layers(
+        &root,
+        &Default::default(),
+        (
+            root.parse_attribute("width", 0)?,
+            root.parse_attribute("height", 0)?,
+        ),
+        &mut layers,
+    )?;
+
+    Ok(TiledMap {
+        orientation: root.parse_attribute("orientation", "orthogonal".to_string())?,
+        tile_width: root.required_attribute("tilewidth")?,
+        tile_height: root.required_attribute("tileheight")?,
+        tilesets,
+        layers,
+    })
+}
+
+/// Parses an external tile set in TSX (XML) format.
+pub fn parse_tsx(data: &[u8]) -> Result<TiledTileset, TiledError> {
+    let root = parse_xml(data)?;
+    if root.name != "tileset" {
+        return Err(invalid_data("Root element must be tileset"));
+    }
+    parse_xml_tileset(&root)
+}
+
+fn json_u32(value: &Value, name: &str, default: u32) -> Result<u32, TiledError> {
+    match value.get(name) {
+        Some(field) => field
+            .as_u64()
+            .map(|field| field as u32)
+            .ok_or_else(|| invalid_data(format!("Field {} must be a positive integer", name))),
+        None => Ok(default),
+    }
+}
+
+fn json_i32(value: &Value, name: &str) -> Result<i32, TiledError> {
+    value
+        .get(name)
+        .and_then(Value::as_i64)
+        .map(|field| field as i32)
+        .ok_or_else(|| invalid_data(format!("Field {} must be an integer", name)))
+}
+
+fn json_f32(value: &Value, name: &str, default: f32) -> f32 {
+    value
+        .get(name)
+        .and_then(Value::as_f64)
+        .map_or(default, |field| field as f32)
+}
+
+fn json_str<'a>(value: &'a Value, name: &str) -> Option<&'a str> {
+    value.get(name).and_then(Value::as_str)
+}
+
+fn json_array<'a>(value: &'a Value, name: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .get(name)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn required_u32(value: &Value, name: &str) -> Result<u32, TiledError> {
+    if value.get(name).is_some() {
+        json_u32(value, name, 0)
+    } else {
+        Err(invalid_data(format!("Required field {} is missing", name)))
+    }
+}
+
+fn parse_json_image(
+    value: &Value,
+    source: &str,
+    width: &str,
+    height: &str,
+) -> Result<Option<TiledImage>, TiledError> {
+    match json_str(value, source) {
+        Some(path) if !path.is_empty() => Ok(Some(TiledImage {
+            source: path.to_string(),
+            width: json_u32(value, width, 0)?,
+            height: json_u32(value, height, 0)?,
+        })),
+        _ => Ok(None),
+    }
+}
+
+fn parse_json_object(value: &Value) -> TiledObject {
+    TiledObject {
+        x: json_f32(value, "x", 0.0),
+        y: json_f32(value, "y", 0.0),
+        width: json_f32(value, "width", 0.0),
+        height: json_f32(value, "height", 0.0),
+        rotation: json_f32(value, "rotation", 0.0),
+        polygon: value
+            .get("polygon")
+            .and_then(Value::as_array)
+            .map(|points| {
+                points
+                    .iter()
+                    .map(|point| Vector2::new(json_f32(point, "x", 0.0), json_f32(point, "y", 0.0)))
+                    .collect()
+            }),
+        point: value.get("point").and_then(Value::as_bool).unwrap_or(false),
+    }
+}
+
+fn parse_json_tileset(value: &Value) -> Result<TiledTileset, TiledError> {
+    let mut tiles = Vec::new();
+    for tile in json_array(value, "tiles") {
+        tiles.push(TiledTile {
+            id: required_u32(tile, "id")?,
+            image: parse_json_image(tile, "image", "imagewidth", "imageheight")?,
+            animation: json_array(tile, "animation")
+                .map(|frame| {
+                    Ok(TiledFrame {
+                        tile_id: required_u32(frame, "tileid")?,
+                        duration: required_u32(frame, "duration")?,
+                    })
+                })
+                .collect::<Result<Vec<_>, TiledError>>()?,
+            objects: tile
+                .get("objectgroup")
+                .map(|group| {
+                    json_array(group, "objects")
+                        .map(parse_json_object)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        });
+    }
+
+    Ok(TiledTileset {
+        tile_width: required_u32(value, "tilewidth")?,
+        tile_height: required_u32(value, "tileheight")?,
+        spacing: json_u32(value, "spacing", 0)?,
+        margin: json_u32(value, "margin", 0)?,
+        columns: json_u32(value, "columns", 0)?,
+        tile_count: json_u32(value, "tilecount", 0)?,
+        image: parse_json_image(value, "image", "imagewidth", "imageheight")?,
+        tiles,
+    })
+}
+
+fn parse_json_data(
+    value: &Value,
+    encoding: &str,
+    compression: Option<&str>,
+    chunk: &mut TiledChunk,
+) -> Result<(), TiledError> {
+    chunk.gids = match value.get("data") {
+        Some(Value::Array(gids)) => gids
+            .iter()
+            .map(|gid| {
+                gid.as_u64()
+                    .map(|gid| gid as u32)
+                    .ok_or_else(|| invalid_data(format!("Invalid tile id {}", gid)))
+            })
+            .collect::<Result<Vec<_>, TiledError>>()?,
+        Some(Value::String(text)) => decode_data(text, encoding, compression)?,
+        _ => return Err(invalid_data("Layer does not have data")),
+    };
+    check_chunk_size(chunk)
+}
+
+fn parse_json_layers(
+    parent: &Value,
+    parameters: &LayerParameters,
+    layers: &mut Vec<TiledLayer>,
+) -> Result<(), TiledError> {
+    for value in json_array(parent, "layers") {
+        let tint = json_str(value, "tintcolor").map(parse_color).transpose()?;
+        let parameters = parameters.combine(
+            value
+                .get("visible")
+                .and_then(Value::as_bool)
+                .unwrap_or(true),
+            json_f32(value, "opacity", 1.0),
+            tint,
+        );
+
+        match json_str(value, "type") {
+            Some("tilelayer") => {
+                let encoding = json_str(value, "encoding").unwrap_or("csv");
+                let compression = json_str(value, "compression");
+
+                let mut chunks = Vec::new();
+                if value.get("chunks").is_some() {
+                    for chunk_value in json_array(value, "chunks") {
+                        let mut chunk = TiledChunk {
+                            x: json_i32(chunk_value, "x")?,
+                            y: json_i32(chunk_value, "y")?,
+                            width: required_u32(chunk_value, "width")?,
+                            height: required_u32(chunk_value, "height")?,
+                            gids: Default::default(),
+                        };
+                        parse_json_data(chunk_value, encoding, compression, &mut chunk)?;
+                        chunks.push(chunk);
+                    }
+                } else {
+                    let mut chunk = TiledChunk {
+                        x: 0,
+                        y: 0,
+                        width: required_u32(value, "width")?,
+                        height: required_u32(value, "height")?,
+                        gids: Default::default(),
+                    };
+                    parse_json_data(value, encoding, compression, &mut chunk)?;
+                    chunks.push(chunk);
+                }
+
+                layers.push(TiledLayer {
+                    name: json_str(value, "name").unwrap_or_default().to_string(),
+                    visible: parameters.visible,
+                    opacity: parameters.opacity,
+                    tint: parameters.tint,
+                    chunks,
+                });
+            }
+            Some("group") => parse_json_layers(value, &parameters, layers)?,
+            // Object and image layers are not supported.
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Parses a map in TMJ (JSON) format.
+pub fn parse_tmj(data: &[u8]) -> Result<TiledMap, TiledError> {
+    let root: Value =
+        serde_json::from_slice(data).map_err(|err| TiledError::Json(err.to_string()))?;
+
+    let mut tilesets = Vec::new();
+    for value in json_array(&root, "tilesets") {
+        let source = json_str(value, "source").map(ToOwned::to_owned);
+        tilesets.push(TiledTilesetEntry {
+            first_gid: required_u32(value, "firstgid")?,
+            tileset: if source.is_some() {
+                Default::default()
+            } else {
+                parse_json_tileset(value)?
+            },
+            source,
+        });
+    }
+
+    let mut layers = Vec::new();
+    parse_json_layers(&root, &Default::default(), &mut layers)?;
+
+    Ok(TiledMap {
+        orientation: json_str(&root, "orientation")
+            .unwrap_or("orthogonal")
+            .to_string(),
+        tile_width: required_u32(&root, "tilewidth")?,
+        tile_height: required_u32(&root, "tileheight")?,
+        tilesets,
+        layers,
+    })
+}
+
+/// Parses an external tile set in TSJ (JSON) format.
+pub fn parse_tsj(data: &[u8]) -> Result<TiledTileset, TiledError> {
+    let root: Value =
+        serde_json::from_slice(data).map_err(|err| TiledError::Json(err.to_string()))?;
+    parse_json_tileset(&root)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode_data() {
+        assert_eq!(
+            decode_data("1,2,\n3,0", "csv", None).unwrap(),
+            vec![1, 2, 3, 0]
+        );
+
+        // [1, 2] as little-endian u32.
+        assert_eq!(
+            decode_data(" AQAAAAIAAAA= \n", "base64", None).unwrap(),
+            vec![1, 2]
+        );
+        // The same data compressed with zlib.
+        assert_eq!(
+            decode_data("eJxjZGBgYAJiAAAYAAQ=", "base64", Some("zlib")).unwrap(),
+            vec![1, 2]
+        );
+        // The same data compressed with gzip.
+        assert_eq!(
+            decode_data(
+                "H4sIAAAAAAACA2NkYGBgAmIAfBeBAwgAAAA=",
+                "base64",
+                Some("gzip")
+            )
+            .unwrap(),
+            vec![1, 2]
+        );
+        assert!(matches!(
+            decode_data("AQAAAAIAAAA=", "base64", Some("lzma")),
+            Err(TiledError::UnsupportedEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_tmx() {
+        let tmx = br##"<?xml version="1.0" encoding="UTF-8"?>
+            <map version="1.10" orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+             <tileset firstgid="1" name="tiles" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+              <image source="tiles.png" width="32" height="32"/>
+              <tile id="1">
+               <objectgroup>
+                <object id="1" x="0" y="8" width="16" height="8"/>
+                <object id="2" x="0" y="0"><polygon points="0,0 16,0 0,16"/></object>
+               </objectgroup>
+               <animation>
+                <frame tileid="1" duration="100"/>
+                <frame tileid="2" duration="200"/>
+               </animation>
+              </tile>
+             </tileset>
+             <tileset firstgid="5" source="external.tsx"/>
+             <group name="Group" opacity="0.5">
+              <layer id="1" name="Ground" width="2" height="2" tintcolor="#ff0000">
+               <data encoding="csv">1,2,0,2147483653</data>
+              </layer>
+             </group>
+             <objectgroup id="2" name="Objects"/>
+            </map>"##;
+
+        let map = parse_tmx(tmx).unwrap();
+        assert_eq!(map.orientation, "orthogonal");
+        assert_eq!((map.tile_width, map.tile_height), (16, 16));
+
+        assert_eq!(map.tilesets.len(), 2);
+        let tileset = &map.tilesets[0].tileset;
+        assert_eq!(tileset.columns, 2);
+        assert_eq!(tileset.image.as_ref().unwrap().source, "tiles.png");
+        assert_eq!(tileset.tiles[0].id, 1);
+        assert_eq!(tileset.tiles[0].animation.len(), 2);
+        assert_eq!(tileset.tiles[0].animation[1].duration, 200);
+        assert_eq!(tileset.tiles[0].objects[0].height, 8.0);
+        assert_eq!(
+            tileset.tiles[0].objects[1].polygon.as_ref().unwrap()[1],
+            Vector2::new(16.0, 0.0)
+        );
+        assert_eq!(map.tilesets[1].first_gid, 5);
+        assert_eq!(map.tilesets[1].source.as_deref(), Some("external.tsx"));
+
+        assert_eq!(map.layers.len(), 1);
+        let layer = &map.layers[0];
+        assert_eq!(layer.name, "Ground");
+        assert_eq!(layer.opacity, 0.5);
+        assert_eq!(layer.tint, Color::from_rgba(255, 0, 0, 255));
+        assert_eq!(layer.chunks[0].gids, vec![1, 2, 0, 0x80000005]);
+    }
+
+    #[test]
+    fn test_parse_tmj() {
+        let tmj = br##"{
+            "orientation": "orthogonal",
+            "tilewidth": 32,
+            "tileheight": 16,
+            "infinite": true,
+            "tilesets": [
+                {
+                    "firstgid": 1,
+                    "tilewidth": 32,
+                    "tileheight": 32,
+                    "tilecount": 1,
+                    "columns": 0,
+                    "tiles": [
+                        {
+                            "id": 0,
+                            "image": "tree.png",
+                            "imagewidth": 32,
+                            "imageheight": 64,
+                            "objectgroup": {
+                                "objects": [{ "x": 8, "y": 32, "width": 16, "height": 32 }]
+                            }
+                        }
+                    ]
+                }
+            ],
+            "layers": [
+                {
+                    "type": "tilelayer",
+                    "name": "Trees",
+                    "visible": false,
+                    "chunks": [
+                        { "x": -16, "y": 0, "width": 2, "height": 1, "data": [0, 1] }
+                    ]
+                },
+                { "type": "objectgroup", "name": "Objects", "objects": [] }
+            ]
+        }"##;
+
+        let map = parse_tmj(tmj).unwrap();
+        assert_eq!((map.tile_width, map.tile_height), (32, 16));
+
+        let tile = &map.tilesets[0].tileset.tiles[0];
+        assert_eq!(tile.image.as_ref().unwrap().height, 64);
+        assert_eq!(tile.objects[0].x, 8.0);
+
+        assert_eq!(map.layers.len(), 1);
+        assert!(!map.layers[0].visible);
+        assert_eq!(
+            map.layers[0].chunks,
+            vec![TiledChunk {
+                x: -16,
+                y: 0,
+                width: 2,
+                height: 1,
+                gids: vec![0, 1]
+            }]
+        );
+    }
+}
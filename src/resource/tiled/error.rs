@@ -0,0 +1,66 @@
+//! Contains all possible errors that can occur during Tiled map loading.
+
+use crate::core::io::FileLoadError;
+use std::fmt::{Display, Formatter};
+
+/// See module docs.
+#[derive(Debug)]
+pub enum TiledError {
+    /// An error occurred during file loading.
+    FileLoadError(FileLoadError),
+
+    /// A TMX (or TSX) file is not a valid XML document.
+    Xml(String),
+
+    /// A TMJ (or TSJ) file is not a valid JSON document.
+    Json(String),
+
+    /// A document is well-formed, but its content is not a valid Tiled map.
+    InvalidData(String),
+
+    /// Only orthogonal maps are supported.
+    UnsupportedOrientation(String),
+
+    /// Layer data uses unknown encoding or compression.
+    UnsupportedEncoding(String),
+
+    /// Compressed layer data could not be decompressed.
+    Decompression(String),
+}
+
+impl Display for TiledError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TiledError::FileLoadError(v) => {
+                write!(f, "Tiled: File load error {v:?}.")
+            }
+            TiledError::Xml(v) => {
+                write!(f, "Tiled: Invalid XML document: {v}")
+            }
+            TiledError::Json(v) => {
+                write!(f, "Tiled: Invalid JSON document: {v}")
+            }
+            TiledError::InvalidData(v) => {
+                write!(f, "Tiled: Invalid map data: {v}")
+            }
+            TiledError::UnsupportedOrientation(v) => {
+                write!(
+                    f,
+                    "Tiled: Map orientation {v} is not supported, only orthogonal maps can be loaded."
+                )
+            }
+            TiledError::UnsupportedEncoding(v) => {
+                write!(f, "Tiled: Unsupported layer data encoding {v}.")
+            }
+            TiledError::Decompression(v) => {
+                write!(f, "Tiled: Unable to decompress layer data: {v}")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for TiledError {
+    fn from(err: FileLoadError) -> Self {
+        TiledError::FileLoadError(err)
+    }
+}
@@ -0,0 +1,431 @@
+//! Contains all methods to load maps made in [Tiled](https://www.mapeditor.org/) map editor.
+//!
+//! Both flavors of maps are supported: XML (`.tmx`) and JSON (`.tmj`), as well as external tile
+//! sets (`.tsx` and `.tsj`). A map is loaded as a single [`TileMap`] node with one layer per tile
+//! layer of the map (group layers are flattened). Tile layer data can be stored in any encoding
+//! supported by Tiled: CSV, XML and base64 with optional zlib, gzip or zstd compression. Infinite
+//! maps, animated tiles and tile collision shapes are supported as well, collision shapes are
+//! converted to 2D colliders of a static rigid body named `Collision`, which is attached to the
+//! tile map.
+//!
+//! Limitations:
+//!
+//! - Only orthogonal maps are supported.
+//! - Object and image layers are ignored.
+//! - Tiles flipped diagonally (rotated) are loaded without rotation.
+//! - Rotated collision objects are loaded without rotation, ellipses are loaded as rectangles.
+//!
+//! Cells of the map have width of one unit, a map with 16x16 pixels tiles will have 1x1 cells,
+//! use [`ModelImportOptions::scale`] to change it.
+//!
+//! Normally you should never use methods from this module directly, use resource manager to load
+//! models and create their instances.
+
+pub mod document;
+pub mod error;
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{algebra::Vector2, color::Color, instant::Instant, io, log::Log, math::Rect},
+    resource::{
+        model::ModelImportOptions,
+        texture::Texture,
+        tiled::{
+            document::{TiledLayer, TiledObject, TiledTileset},
+            error::TiledError,
+        },
+    },
+    scene::{
+        base::BaseBuilder,
+        dim2::tilemap::{
+            Tile, TileAnimationFrame, TileCollisionShape, TileDefinition, TileMap, TileMapBuilder,
+            TileMapLayer, TileSet,
+        },
+        Scene,
+    },
+};
+use fxhash::FxHashMap;
+use std::path::{Path, PathBuf};
+
+const FLIPPED_HORIZONTALLY_FLAG: u32 = 0x80000000;
+const FLIPPED_VERTICALLY_FLAG: u32 = 0x40000000;
+const FLIPPED_DIAGONALLY_FLAG: u32 = 0x20000000;
+// Hexagonal maps use one more flag, it is not used by orthogonal maps, but must be masked too.
+const GID_MASK: u32 = 0x0FFFFFFF;
+
+fn is_json(path: &Path) -> bool {
+    path.extension().map_or(false, |extension| {
+        let extension = extension.to_string_lossy().to_lowercase();
+        extension == "tmj" || extension == "tsj" || extension == "json"
+    })
+}
+
+fn convert_object(
+    object: &TiledObject,
+    tile_height: f32,
+    cell_size: Vector2<f32>,
+) -> Option<TileCollisionShape> {
+    if object.point {
+        return None;
+    }
+
+    if object.rotation != 0.0 {
+        Log::warn(format!(
+            "Tiled: Rotation of a collision object is not supported and will be ignored ({} degrees).",
+            object.rotation
+        ));
+    }
+
+    // Tiled uses pixels with Y axis pointing down, tile map uses cells with Y axis pointing up.
+    let to_cells = |x: f32, y: f32| Vector2::new(x / cell_size.x, (tile_height - y) / cell_size.y);
+
+    match object.polygon {
+        Some(ref points) => {
+            if points.len() < 3 {
+                return None;
+            }
+            Some(TileCollisionShape::Polygon(
+                points
+                    .iter()
+                    .map(|point| to_cells(object.x + point.x, object.y + point.y))
+                    .collect(),
+            ))
+        }
+        None => {
+            if object.width <= 0.0 || object.height <= 0.0 {
+                return None;
+            }
+            let position = to_cells(object.x, object.y + object.height);
+            Some(TileCollisionShape::Rectangle(Rect::new(
+                position.x,
+                position.y,
+                object.width / cell_size.x,
+                object.height / cell_size.y,
+            )))
+        }
+    }
+}
+
+/// Converts tile sets of a map into a single tile set, returns the tile set and a map from global
+/// ids of the map to tile definition indices.
+fn convert_tile_sets(
+    tilesets: &[(u32, PathBuf, TiledTileset)],
+    cell_size: Vector2<f32>,
+    resource_manager: &ResourceManager,
+) -> (TileSet, FxHashMap<u32, u32>) {
+    let mut tile_set = TileSet::default();
+    let mut gid_map = FxHashMap::default();
+
+    for (first_gid, directory, tileset) in tilesets {
+        let mut tile_heights = FxHashMap::default();
+
+        if let Some(ref image) = tileset.image {
+            let texture = resource_manager.request::<Texture, _>(directory.join(&image.source));
+
+            let stride_x = tileset.tile_width + tileset.spacing;
+            let stride_y = tileset.tile_height + tileset.spacing;
+            let columns = if tileset.columns > 0 {
+                tileset.columns
+            } else {
+                (image.width.saturating_sub(2 * tileset.margin) + tileset.spacing) / stride_x.max(1)
+            };
+            let tile_count = if tileset.tile_count > 0 {
+                tileset.tile_count
+            } else {
+                columns
+                    * ((image.height.saturating_sub(2 * tileset.margin) + tileset.spacing)
+                        / stride_y.max(1))
+            };
+
+            let image_size = Vector2::new(image.width.max(1) as f32, image.height.max(1) as f32);
+            for id in 0..tile_count {
+                let column = id % columns.max(1);
+                let row = id / columns.max(1);
+                gid_map.insert(first_gid + id, tile_set.tiles.len() as u32);
+                tile_heights.insert(id, tileset.tile_height as f32);
+                tile_set.tiles.push(TileDefinition {
+                    texture: Some(texture.clone()),
+                    uv_rect: Rect::new(
+                        (tileset.margin + column * stride_x) as f32 / image_size.x,
+                        (tileset.margin + row * stride_y) as f32 / image_size.y,
+                        tileset.tile_width as f32 / image_size.x,
+                        tileset.tile_height as f32 / image_size.y,
+                    ),
+                    size: Vector2::new(
+                        tileset.tile_width as f32 / cell_size.x,
+                        tileset.tile_height as f32 / cell_size.y,
+                    ),
+                    ..Default::default()
+                });
+            }
+        } else {
+            // "Collection of images" tile set, every tile has its own image.
+            for tile in tileset.tiles.iter() {
+                if let Some(ref image) = tile.image {
+                    gid_map.insert(first_gid + tile.id, tile_set.tiles.len() as u32);
+                    tile_heights.insert(tile.id, image.height as f32);
+                    tile_set.tiles.push(TileDefinition {
+                        texture: Some(
+                            resource_manager.request::<Texture, _>(directory.join(&image.source)),
+                        ),
+                        size: Vector2::new(
+                            image.width as f32 / cell_size.x,
+                            image.height as f32 / cell_size.y,
+                        ),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+
+        for tile in tileset.tiles.iter() {
+            let (index, tile_height) = match (
+                gid_map.get(&(first_gid + tile.id)),
+                tile_heights.get(&tile.id),
+            ) {
+                (Some(index), Some(tile_height)) => (*index as usize, *tile_height),
+                _ => continue,
+            };
+
+            let animation = tile
+                .animation
+                .iter()
+                .filter_map(|frame| {
+                    gid_map
+                        .get(&(first_gid + frame.tile_id))
+                        .map(|definition| TileAnimationFrame {
+                            tile: *definition,
+                            duration: frame.duration as f32 / 1000.0,
+                        })
+                })
+                .collect();
+
+            let definition = &mut tile_set.tiles[index];
+            definition.animation = animation;
+            definition.collision = tile
+                .objects
+                .iter()
+                .filter_map(|object| convert_object(object, tile_height, cell_size))
+                .collect();
+        }
+    }
+
+    (tile_set, gid_map)
+}
+
+fn convert_layer(layer: &TiledLayer, gid_map: &FxHashMap<u32, u32>) -> TileMapLayer {
+    let mut result = TileMapLayer::new(&layer.name);
+    result.visible = layer.visible;
+    result.color = Color::from_rgba(
+        layer.tint.r,
+        layer.tint.g,
+        layer.tint.b,
+        (layer.tint.a as f32 * layer.opacity.clamp(0.0, 1.0)) as u8,
+    );
+
+    let mut has_rotated_tiles = false;
+    let mut has_unknown_tiles = false;
+    for chunk in layer.chunks.iter() {
+        for (index, gid) in chunk.gids.iter().enumerate() {
+            if *gid == 0 {
+                continue;
+            }
+
+            let definition = match gid_map.get(&(gid & GID_MASK)) {
+                Some(definition) => *definition,
+                None => {
+                    has_unknown_tiles = true;
+                    continue;
+                }
+            };
+
+            has_rotated_tiles |= gid & FLIPPED_DIAGONALLY_FLAG != 0;
+
+            let x = chunk.x + (index as u32 % chunk.width) as i32;
+            let y = chunk.y + (index as u32 / chunk.width) as i32;
+
+            // Rows of Tiled maps go down, rows of tile maps go up.
+            result.set_tile(
+                Vector2::new(x, -y - 1),
+                Some(
+                    Tile::new(definition)
+                        .with_flip_x(gid & FLIPPED_HORIZONTALLY_FLAG != 0)
+                        .with_flip_y(gid & FLIPPED_VERTICALLY_FLAG != 0),
+                ),
+            );
+        }
+    }
+
+    if has_rotated_tiles {
+        Log::warn(format!(
+            "Tiled: Layer {} has rotated tiles, rotation is not supported and will be ignored.",
+            layer.name
+        ));
+    }
+    if has_unknown_tiles {
+        Log::warn(format!(
+            "Tiled: Layer {} refers to tiles that are not defined in any tile set.",
+            layer.name
+        ));
+    }
+
+    result
+}
+
+/// Tries to load and convert Tiled map from given path.
+///
+/// Normally you should never use this method, use resource manager to load models.
+pub async fn load_to_scene<P: AsRef<Path>>(
+    scene: &mut Scene,
+    resource_manager: ResourceManager,
+    path: P,
+    _model_import_options: &ModelImportOptions,
+) -> Result<(), TiledError> {
+    let path = path.as_ref();
+    let start_time = Instant::now();
+
+    Log::info(format!("Trying to load {:?}", path));
+
+    let data = io::load_file(path).await?;
+    let map = if is_json(path) {
+        document::parse_tmj(&data)?
+    } else {
+        document::parse_tmx(&data)?
+    };
+
+    if map.orientation != "orthogonal" {
+        return Err(TiledError::UnsupportedOrientation(map.orientation));
+    }
+
+    let directory = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+
+    // Paths of images are relative to the file with the tile set, so each tile set stores its
+    // directory.
+    let mut tilesets = Vec::with_capacity(map.tilesets.len());
+    for entry in map.tilesets {
+        match entry.source {
+            Some(source) => {
+                let tileset_path = directory.join(source);
+                let data = io::load_file(&tileset_path).await?;
+                let tileset = if is_json(&tileset_path) {
+                    document::parse_tsj(&data)?
+                } else {
+                    document::parse_tsx(&data)?
+                };
+                let tileset_directory = tileset_path
+                    .parent()
+                    .map(ToOwned::to_owned)
+                    .unwrap_or_default();
+                tilesets.push((entry.first_gid, tileset_directory, tileset));
+            }
+            None => tilesets.push((entry.first_gid, directory.clone(), entry.tileset)),
+        }
+    }
+
+    let cell_size = Vector2::new(map.tile_width.max(1) as f32, map.tile_height.max(1) as f32);
+    let (tile_set, gid_map) = convert_tile_sets(&tilesets, cell_size, &resource_manager);
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let tile_map = TileMapBuilder::new(BaseBuilder::new().with_name(name))
+        .with_tile_set(tile_set)
+        .with_tile_size(Vector2::new(1.0, cell_size.y / cell_size.x))
+        .with_layers(
+            map.layers
+                .iter()
+                .map(|layer| convert_layer(layer, &gid_map))
+                .collect(),
+        )
+        .build(&mut scene.graph);
+
+    TileMap::create_colliders(&mut scene.graph, tile_map);
+
+    Log::info(format!(
+        "Tiled map {:?} loaded in {} ms",
+        path,
+        start_time.elapsed().as_millis()
+    ));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_convert_object() {
+        let cell_size = Vector2::new(16.0, 16.0);
+
+        // Bottom half of a 16x32 tile.
+        let rectangle = TiledObject {
+            x: 0.0,
+            y: 16.0,
+            width: 16.0,
+            height: 16.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            convert_object(&rectangle, 32.0, cell_size),
+            Some(TileCollisionShape::Rectangle(Rect::new(0.0, 0.0, 1.0, 1.0)))
+        );
+
+        let polygon = TiledObject {
+            x: 8.0,
+            y: 0.0,
+            polygon: Some(vec![
+                Vector2::new(0.0, 0.0),
+                Vector2::new(8.0, 16.0),
+                Vector2::new(-8.0, 16.0),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            convert_object(&polygon, 16.0, cell_size),
+            Some(TileCollisionShape::Polygon(vec![
+                Vector2::new(0.5, 1.0),
+                Vector2::new(1.0, 0.0),
+                Vector2::new(0.0, 0.0),
+            ]))
+        );
+
+        let point = TiledObject {
+            point: true,
+            ..Default::default()
+        };
+        assert_eq!(convert_object(&point, 16.0, cell_size), None);
+    }
+
+    #[test]
+    fn test_convert_layer() {
+        let mut gid_map = FxHashMap::default();
+        gid_map.insert(1, 0);
+        gid_map.insert(2, 1);
+
+        let layer = TiledLayer {
+            name: "Ground".to_string(),
+            visible: true,
+            opacity: 0.5,
+            tint: Color::WHITE,
+            chunks: vec![document::TiledChunk {
+                x: 0,
+                y: 0,
+                width: 2,
+                height: 2,
+                gids: vec![1, 0, 0, 2 | FLIPPED_HORIZONTALLY_FLAG],
+            }],
+        };
+
+        let layer = convert_layer(&layer, &gid_map);
+        assert_eq!(layer.color.a, 127);
+        assert_eq!(layer.tile(Vector2::new(0, -1)), Some(Tile::new(0)));
+        assert_eq!(
+            layer.tile(Vector2::new(1, -2)),
+            Some(Tile::new(1).with_flip_x(true))
+        );
+        assert_eq!(layer.tiles().count(), 2);
+    }
+}
@@ -2,7 +2,7 @@
 //! mixes them in arbitrary way into one animation. See [`AnimationBlendingStateMachine`] docs for more info.
 
 use crate::{
-    animation::machine::Machine,
+    animation::{machine::Machine, AnimationContainer},
     core::{
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
@@ -108,6 +108,10 @@ pub struct AnimationBlendingStateMachine {
     base: Base,
     machine: InheritableVariable<Machine>,
     animation_player: InheritableVariable<Handle<Node>>,
+    // `true` if the pose was already sampled in parallel during current frame.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pose_sampled: bool,
 }
 
 impl AnimationBlendingStateMachine {
@@ -137,6 +141,13 @@ impl AnimationBlendingStateMachine {
     pub fn animation_player(&self) -> Handle<Node> {
         *self.animation_player
     }
+
+    pub(super) fn sample_pose(&mut self, animations: &mut AnimationContainer, dt: f32) {
+        self.machine
+            .get_value_mut_silent()
+            .evaluate_pose(animations, dt);
+        self.pose_sampled = true;
+    }
 }
 
 impl TypeUuidProvider for AnimationBlendingStateMachine {
@@ -175,6 +186,11 @@ impl NodeTrait for AnimationBlendingStateMachine {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        if std::mem::take(&mut self.pose_sampled) {
+            self.machine.pose().apply_internal(context.nodes);
+            return;
+        }
+
         if let Some(animation_player) = context
             .nodes
             .try_borrow_mut(*self.animation_player)
@@ -193,6 +209,10 @@ impl NodeTrait for AnimationBlendingStateMachine {
         }
     }
 
+    fn updates_render_data(&self) -> bool {
+        false
+    }
+
     fn validate(&self, scene: &Scene) -> Result<(), String> {
         if scene
             .graph
@@ -246,6 +266,7 @@ impl AnimationBlendingStateMachineBuilder {
             base: self.base_builder.build_base(),
             machine: self.machine.into(),
             animation_player: self.animation_player.into(),
+            pose_sampled: false,
         })
     }
 
@@ -13,11 +13,15 @@ use crate::{
         TypeUuidProvider,
     },
     scene::{
+        animation::absm::AnimationBlendingStateMachine,
         base::{Base, BaseBuilder},
-        graph::Graph,
+        graph::{Graph, NodePool},
         node::{Node, NodeTrait, UpdateContext},
+        update_culling::{UpdateCulling, UpdateObserver},
     },
 };
+use fxhash::{FxHashMap, FxHashSet};
+use rayon::prelude::*;
 use std::ops::{Deref, DerefMut};
 
 pub mod absm;
@@ -96,6 +100,13 @@ pub struct AnimationPlayer {
     base: Base,
     animations: InheritableVariable<AnimationContainer>,
     auto_apply: bool,
+    #[visit(optional)]
+    #[reflect(setter = "set_update_culling")]
+    update_culling: InheritableVariable<UpdateCulling>,
+    // `true` if the animations were already sampled in parallel during current frame.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pose_sampled: bool,
 }
 
 impl Default for AnimationPlayer {
@@ -104,6 +115,8 @@ impl Default for AnimationPlayer {
             base: Default::default(),
             animations: Default::default(),
             auto_apply: true,
+            update_culling: Default::default(),
+            pose_sampled: false,
         }
     }
 }
@@ -134,6 +147,18 @@ impl AnimationPlayer {
         &mut self.animations
     }
 
+    /// Sets new update culling settings, that could be used to pause or slow down the animations when
+    /// the animation player is far away from cameras. See [`UpdateCulling`] docs for more info.
+    pub fn set_update_culling(&mut self, update_culling: UpdateCulling) -> UpdateCulling {
+        self.update_culling
+            .set_value_and_mark_modified(update_culling)
+    }
+
+    /// Returns current update culling settings.
+    pub fn update_culling(&self) -> &UpdateCulling {
+        &self.update_culling
+    }
+
     /// Sets new animations container of the animation player.
     pub fn set_animations(&mut self, animations: AnimationContainer) {
         self.animations.set_value_and_mark_modified(animations);
@@ -176,12 +201,115 @@ impl NodeTrait for AnimationPlayer {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
-        self.animations.get_value_mut_silent().update_animations(
-            context.nodes,
-            self.auto_apply,
+        if std::mem::take(&mut self.pose_sampled) {
+            if self.auto_apply {
+                self.animations.apply_poses(context.nodes);
+            }
+            return;
+        }
+
+        let bounds = self.world_bounding_box();
+        if let Some(dt) = self.update_culling.get_value_mut_silent().update(
+            &bounds,
+            context.observers,
             context.dt,
-        );
+        ) {
+            self.animations.get_value_mut_silent().update_animations(
+                context.nodes,
+                self.auto_apply,
+                dt,
+            );
+        }
+    }
+
+    fn updates_render_data(&self) -> bool {
+        false
+    }
+}
+
+struct PoseSamplingJob<'a> {
+    player: &'a mut AnimationPlayer,
+    machine: Option<&'a mut AnimationBlendingStateMachine>,
+}
+
+impl<'a> PoseSamplingJob<'a> {
+    fn run(&mut self, observers: &[UpdateObserver], dt: f32) {
+        let bounds = self.player.world_bounding_box();
+        if let Some(dt) = self
+            .player
+            .update_culling
+            .get_value_mut_silent()
+            .update(&bounds, observers, dt)
+        {
+            self.player
+                .animations
+                .get_value_mut_silent()
+                .tick_animations(dt);
+            self.player.pose_sampled = true;
+        }
+
+        if let Some(machine) = self.machine.as_mut() {
+            // The machine applies the pose instead of the player.
+            self.player.set_auto_apply(false);
+            machine.sample_pose(self.player.animations.get_value_mut_silent(), dt);
+        }
+    }
+}
+
+/// Samples animation poses of every animation player and animation blending state machine, that will be
+/// updated this frame, in parallel. Every job owns a single animation player and at most one state machine
+/// that uses the player, so the jobs never touch the same data. State machines that share an animation
+/// player with other machines are not sampled here, they're updated sequentially as usual. Sampled poses
+/// are applied to the graph later (merge phase) in [`NodeTrait::update`] of respective nodes.
+pub(crate) fn sample_poses_in_parallel(
+    nodes: &mut NodePool,
+    observers: &[UpdateObserver],
+    dt: f32,
+    node_overrides: Option<&FxHashSet<Handle<Node>>>,
+) {
+    let mut players = FxHashMap::default();
+    let mut machines = Vec::new();
+    for (handle, node) in nodes.pair_iter_mut() {
+        if !node.is_globally_enabled() || !node_overrides.map_or(true, |o| o.contains(&handle)) {
+            continue;
+        }
+
+        // Internal state of the players and the machines is not used for rendering, sampled poses are
+        // applied to the animated nodes through the usual (tracked) access.
+        let node = node.untracked_mut().as_any_ref_mut();
+        if node.is::<AnimationPlayer>() {
+            players.insert(handle, node.downcast_mut::<AnimationPlayer>().unwrap());
+        } else if node.is::<AnimationBlendingStateMachine>() {
+            machines.push(
+                node.downcast_mut::<AnimationBlendingStateMachine>()
+                    .unwrap(),
+            );
+        }
+    }
+
+    let mut users = FxHashMap::<Handle<Node>, usize>::default();
+    for machine in machines.iter() {
+        *users.entry(machine.animation_player()).or_default() += 1;
+    }
+
+    let mut jobs = Vec::with_capacity(players.len());
+    for machine in machines {
+        let player_handle = machine.animation_player();
+        if users[&player_handle] == 1 {
+            if let Some(player) = players.remove(&player_handle) {
+                jobs.push(PoseSamplingJob {
+                    player,
+                    machine: Some(machine),
+                });
+            }
+        }
     }
+    jobs.extend(players.into_values().map(|player| PoseSamplingJob {
+        player,
+        machine: None,
+    }));
+
+    jobs.par_iter_mut().for_each(|job| job.run(observers, dt));
 }
 
 /// A builder for [`AnimationPlayer`] node.
@@ -189,6 +317,7 @@ pub struct AnimationPlayerBuilder {
     base_builder: BaseBuilder,
     animations: AnimationContainer,
     auto_apply: bool,
+    update_culling: UpdateCulling,
 }
 
 impl AnimationPlayerBuilder {
@@ -198,6 +327,7 @@ impl AnimationPlayerBuilder {
             base_builder,
             animations: AnimationContainer::new(),
             auto_apply: true,
+            update_culling: Default::default(),
         }
     }
 
@@ -213,12 +343,20 @@ impl AnimationPlayerBuilder {
         self
     }
 
+    /// Sets desired update culling settings.
+    pub fn with_update_culling(mut self, update_culling: UpdateCulling) -> Self {
+        self.update_culling = update_culling;
+        self
+    }
+
     /// Creates an instance of [`AnimationPlayer`] node.
     pub fn build_node(self) -> Node {
         Node::new(AnimationPlayer {
             base: self.base_builder.build_base(),
             animations: self.animations.into(),
             auto_apply: self.auto_apply,
+            update_culling: self.update_culling.into(),
+            pose_sampled: false,
         })
     }
 
@@ -22,6 +22,116 @@ use crate::{
 use std::{any::Any, cell::Cell, sync::mpsc::Sender};
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
+/// Sends the handle of a changed node to the dirty list of its graph, see [`HierarchyCache`].
+#[derive(Clone, Debug)]
+pub(crate) struct HierarchyNotifier {
+    handle: Handle<Node>,
+    sender: Sender<Handle<Node>>,
+}
+
+impl HierarchyNotifier {
+    pub(crate) fn new(handle: Handle<Node>, sender: Sender<Handle<Node>>) -> Self {
+        Self { handle, sender }
+    }
+
+    pub(crate) fn notify(&self) {
+        // The graph could be already destroyed, when a node is dropped.
+        let _ = self.sender.send(self.handle);
+    }
+}
+
+/// State of a node, that is used by the graph to calculate global data (transform, visibility, enabled
+/// flag) only for changed nodes and their descendants. A node is put to the dirty list of its graph when it
+/// is changed: when its local transform is modified (through [`Base::local_transform_mut`], [`Transform`]
+/// setters, including reflection), when its local visibility or enabled flag is set or when it is linked
+/// to another parent. The graph then checks whether the inputs of the node actually differ from the ones,
+/// that were used last time. Cloned or deserialized nodes always have empty cache.
+#[derive(Debug, Default)]
+pub(crate) struct HierarchyCache {
+    // Parent handle, local visibility and local enabled flag, that were used last time. `None` means that
+    // global data of the node was never calculated.
+    inputs: Cell<Option<(Handle<Node>, bool, bool)>>,
+    // The node is in the dirty list of the graph.
+    queued: Cell<bool>,
+    // The node must be updated, it is set while the graph collects changed nodes.
+    dirty: Cell<bool>,
+    notifier: Option<HierarchyNotifier>,
+}
+
+impl Clone for HierarchyCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl HierarchyCache {
+    /// Connects the node to the dirty list of a graph and puts the node into the list.
+    pub(crate) fn attach(&mut self, notifier: HierarchyNotifier) {
+        self.inputs.set(None);
+        self.queued.set(true);
+        notifier.notify();
+        self.notifier = Some(notifier);
+    }
+
+    pub(crate) fn notifier(&self) -> Option<&HierarchyNotifier> {
+        self.notifier.as_ref()
+    }
+
+    /// Puts the node into the dirty list of its graph, if it is not there yet.
+    pub(crate) fn mark_dirty(&self) {
+        if !self.queued.replace(true) {
+            if let Some(notifier) = self.notifier.as_ref() {
+                notifier.notify();
+            }
+        }
+    }
+
+    /// Puts the node into the dirty list again, if it is still waiting there. Must be called when the node is
+    /// put back to its graph, because the graph skips the nodes that are taken out of it.
+    pub(crate) fn requeue(&self) {
+        if self.queued.get() {
+            if let Some(notifier) = self.notifier.as_ref() {
+                notifier.notify();
+            }
+        }
+    }
+
+    /// Must be called when the node is taken from the dirty list.
+    pub(crate) fn take_queued(&self) {
+        self.queued.set(false);
+    }
+
+    /// Checks whether the node with the given inputs was changed since the last update and marks it as
+    /// dirty if so. Returns `true` if the node was not marked as dirty before.
+    pub(crate) fn check(
+        &self,
+        parent: Handle<Node>,
+        visibility: bool,
+        enabled: bool,
+        transform_changed: bool,
+    ) -> bool {
+        let dirty = transform_changed || self.inputs.get() != Some((parent, visibility, enabled));
+        dirty && !self.dirty.replace(true)
+    }
+
+    /// Returns `true` if the node is dirty.
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.dirty.get()
+    }
+
+    /// Resets the dirty flag and remembers the inputs that were used to calculate global data of the node.
+    pub(crate) fn set_inputs(&self, parent: Handle<Node>, visibility: bool, enabled: bool) {
+        self.dirty.set(false);
+        self.inputs.set(Some((parent, visibility, enabled)));
+    }
+
+    /// Forces the next calculation of global data of the node.
+    pub(crate) fn invalidate(&self) {
+        self.inputs.set(None);
+        self.mark_dirty();
+    }
+}
+
 /// Level of detail is a collection of objects for given normalized distance range.
 /// Objects will be rendered **only** if they're in specified range.
 /// Normalized distance is a distance in (0; 1) range where 0 - closest to camera,
@@ -294,6 +404,7 @@ pub struct Base {
     #[reflect(setter = "set_name_internal")]
     pub(crate) name: String,
 
+    #[reflect(setter = "set_local_transform_internal")]
     pub(crate) local_transform: Transform,
 
     #[reflect(setter = "set_visibility")]
@@ -350,6 +461,9 @@ pub struct Base {
     #[reflect(hidden)]
     pub(crate) global_transform: Cell<Matrix4<f32>>,
 
+    #[reflect(hidden)]
+    pub(crate) hierarchy_cache: HierarchyCache,
+
     // Bone-specific matrix. Non-serializable.
     #[reflect(hidden)]
     pub(crate) inv_bind_pose_transform: Matrix4<f32>,
@@ -378,10 +492,17 @@ pub struct Base {
     #[reflect(setter = "set_script_internal")]
     pub(crate) script: Option<Script>,
 
+    #[reflect(setter = "set_enabled_internal")]
     enabled: InheritableVariable<bool>,
 
     #[reflect(hidden)]
     pub(crate) global_enabled: Cell<bool>,
+
+    // Indicates that the node was changed since the last time it was copied to the render snapshot, see
+    // [`crate::scene::graph::Graph::copy_for_rendering`]. It is set on every mutable access to the node
+    // (see [`Node`]), and when the graph calculates new global data of the node.
+    #[reflect(hidden)]
+    pub(crate) render_data_modified: Cell<bool>,
 }
 
 impl Drop for Base {
@@ -425,13 +546,38 @@ impl Base {
     #[inline]
     pub fn local_transform_mut(&mut self) -> &mut Transform {
         self.transform_modified.set(true);
+        // The transform could be changed without its setters (for example, replaced by another one).
+        self.hierarchy_cache.mark_dirty();
         &mut self.local_transform
     }
 
     /// Sets new local transform of a node.
     #[inline]
     pub fn set_local_transform(&mut self, transform: Transform) {
-        self.local_transform = transform;
+        self.set_local_transform_internal(transform);
+    }
+
+    fn set_local_transform_internal(&mut self, mut transform: Transform) -> Transform {
+        transform.set_notifier(self.hierarchy_cache.notifier().cloned());
+        self.hierarchy_cache.mark_dirty();
+        let mut prev = std::mem::replace(&mut self.local_transform, transform);
+        prev.set_notifier(None);
+        prev
+    }
+
+    /// Connects the node to the dirty list of a graph, see [`HierarchyCache`].
+    pub(crate) fn set_hierarchy_notifier(&mut self, notifier: HierarchyNotifier) {
+        self.local_transform.set_notifier(Some(notifier.clone()));
+        self.hierarchy_cache.attach(notifier);
+    }
+
+    /// Reconnects the local transform to the dirty list of the graph, if the transform was replaced
+    /// through [`Self::local_transform_mut`].
+    pub(crate) fn restore_transform_notifier(&mut self) {
+        if !self.local_transform.has_notifier() {
+            self.local_transform
+                .set_notifier(self.hierarchy_cache.notifier().cloned());
+        }
     }
 
     /// Tries to find properties by the name. The method returns an iterator because it possible
@@ -522,6 +668,7 @@ impl Base {
     /// Sets local visibility of a node.
     #[inline]
     pub fn set_visibility(&mut self, visibility: bool) -> bool {
+        self.hierarchy_cache.mark_dirty();
         self.visibility.set_value_and_mark_modified(visibility)
     }
 
@@ -672,7 +819,8 @@ impl Base {
         *self.frustum_culling
     }
 
-    /// Sets whether to use frustum culling or not
+    /// Sets whether to use frustum culling or not. Nodes with disabled frustum culling will be rendered
+    /// even if their world-space bounding box is outside of camera's frustum.
     #[inline]
     pub fn set_frustum_culling(&mut self, frustum_culling: bool) -> bool {
         self.frustum_culling
@@ -825,7 +973,12 @@ impl Base {
     /// returns `true`.
     #[inline]
     pub fn set_enabled(&mut self, enabled: bool) {
-        self.enabled.set_value_and_mark_modified(enabled);
+        self.set_enabled_internal(enabled);
+    }
+
+    fn set_enabled_internal(&mut self, enabled: bool) -> bool {
+        self.hierarchy_cache.mark_dirty();
+        self.enabled.set_value_and_mark_modified(enabled)
     }
 
     /// Returns `true` if the node is enabled, `false` - otherwise. The return value does **not** include the state
@@ -917,6 +1070,10 @@ impl Visit for Base {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut region = visitor.enter_region(name)?;
 
+        if region.is_reading() {
+            self.hierarchy_cache.invalidate();
+        }
+
         if self.name.visit("Name", &mut region).is_err() {
             // Name was wrapped into `InheritableVariable` previously, so we must maintain
             // backward compatibility here.
@@ -1137,6 +1294,7 @@ impl BaseBuilder {
             global_visibility: Cell::new(true),
             parent: Handle::NONE,
             global_transform: Cell::new(Matrix4::identity()),
+            hierarchy_cache: Default::default(),
             inv_bind_pose_transform: self.inv_bind_pose_transform,
             resource: None,
             original_handle_in_resource: Handle::NONE,
@@ -1153,6 +1311,7 @@ impl BaseBuilder {
             instance_id: InstanceId(Uuid::new_v4()),
             enabled: self.enabled.into(),
             global_enabled: Cell::new(true),
+            render_data_modified: Cell::new(true),
         }
     }
 }
@@ -153,18 +153,110 @@ impl OrthographicProjection {
     }
 }
 
+/// Perspective projection with independent angles of each side of the viewing frustum. The frustum
+/// could be asymmetric (off-axis), this is what head-mounted displays require: each eye looks
+/// through its own lens, that is not centered relative to the eye. The aspect ratio is defined by
+/// the angles, so the size of the frame does not affect the projection.
+#[derive(Reflect, Clone, Debug, PartialEq, Visit)]
+pub struct AsymmetricProjection {
+    /// Angle between the view direction and the left side of the frustum, in radians. It is
+    /// negative for typical frustums.
+    #[reflect(min_value = -1.57, max_value = 1.57, step = 0.01)]
+    pub angle_left: f32,
+    /// Angle between the view direction and the right side of the frustum, in radians.
+    #[reflect(min_value = -1.57, max_value = 1.57, step = 0.01)]
+    pub angle_right: f32,
+    /// Angle between the view direction and the top side of the frustum, in radians.
+    #[reflect(min_value = -1.57, max_value = 1.57, step = 0.01)]
+    pub angle_up: f32,
+    /// Angle between the view direction and the bottom side of the frustum, in radians. It is
+    /// negative for typical frustums.
+    #[reflect(min_value = -1.57, max_value = 1.57, step = 0.01)]
+    pub angle_down: f32,
+    /// Location of the near clipping plane. If it is larger than [`Self::z_far`] then it will be
+    /// treated like far clipping plane.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub z_near: f32,
+    /// Location of the far clipping plane. If it is less than [`Self::z_near`] then it will be
+    /// treated like near clipping plane.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub z_far: f32,
+}
+
+impl Default for AsymmetricProjection {
+    fn default() -> Self {
+        Self {
+            angle_left: -45.0f32.to_radians(),
+            angle_right: 45.0f32.to_radians(),
+            angle_up: 45.0f32.to_radians(),
+            angle_down: -45.0f32.to_radians(),
+            z_near: 0.025,
+            z_far: 2048.0,
+        }
+    }
+}
+
+impl AsymmetricProjection {
+    /// Returns off-axis perspective projection matrix.
+    #[inline]
+    pub fn matrix(&self) -> Matrix4<f32> {
+        let limit = 10.0 * f32::EPSILON;
+
+        let z_near = self.z_far.min(self.z_near).max(limit);
+        let mut z_far = self.z_far.max(self.z_near);
+
+        // Prevent planes from superimposing which could cause panic.
+        if z_far - z_near < limit {
+            z_far += limit;
+        }
+
+        let left = self.angle_left.tan() * z_near;
+        let mut right = self.angle_right.tan() * z_near;
+        let bottom = self.angle_down.tan() * z_near;
+        let mut top = self.angle_up.tan() * z_near;
+
+        // Prevent collapsing the frustum.
+        if right - left < limit {
+            right = left + limit;
+        }
+        if top - bottom < limit {
+            top = bottom + limit;
+        }
+
+        let mut matrix = Matrix4::zeros();
+        matrix[(0, 0)] = 2.0 * z_near / (right - left);
+        matrix[(0, 2)] = (right + left) / (right - left);
+        matrix[(1, 1)] = 2.0 * z_near / (top - bottom);
+        matrix[(1, 2)] = (top + bottom) / (top - bottom);
+        matrix[(2, 2)] = -(z_far + z_near) / (z_far - z_near);
+        matrix[(2, 3)] = -2.0 * z_far * z_near / (z_far - z_near);
+        matrix[(3, 2)] = -1.0;
+        matrix
+    }
+
+    /// Returns total vertical field of view, in radians.
+    #[inline]
+    pub fn vertical_fov(&self) -> f32 {
+        self.angle_up - self.angle_down
+    }
+}
+
 /// A method of projection. Different projection types suitable for different purposes:
 ///
 /// 1) Perspective projection most useful for 3D games, it makes a scene to look most natural,
 /// objects will look smaller with increasing distance.
 /// 2) Orthographic projection most useful for 2D games, objects won't look smaller with increasing
 /// distance.  
+/// 3) Asymmetric projection is a perspective projection with an off-axis frustum, it is used for
+/// stereo rendering for head-mounted displays.
 #[derive(Reflect, Clone, Debug, PartialEq, Visit, AsRefStr, EnumString, EnumVariantNames)]
 pub enum Projection {
     /// See [`PerspectiveProjection`] docs.
     Perspective(PerspectiveProjection),
     /// See [`OrthographicProjection`] docs.
     Orthographic(OrthographicProjection),
+    /// See [`AsymmetricProjection`] docs.
+    Asymmetric(AsymmetricProjection),
 }
 
 impl Projection {
@@ -175,6 +267,7 @@ impl Projection {
         match self {
             Projection::Perspective(ref mut v) => v.z_near = z_near,
             Projection::Orthographic(ref mut v) => v.z_near = z_near,
+            Projection::Asymmetric(ref mut v) => v.z_near = z_near,
         }
         self
     }
@@ -186,6 +279,7 @@ impl Projection {
         match self {
             Projection::Perspective(ref mut v) => v.z_far = z_far,
             Projection::Orthographic(ref mut v) => v.z_far = z_far,
+            Projection::Asymmetric(ref mut v) => v.z_far = z_far,
         }
         self
     }
@@ -196,6 +290,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_near = z_near,
             Projection::Orthographic(v) => v.z_near = z_near,
+            Projection::Asymmetric(v) => v.z_near = z_near,
         }
     }
 
@@ -205,6 +300,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_far = z_far,
             Projection::Orthographic(v) => v.z_far = z_far,
+            Projection::Asymmetric(v) => v.z_far = z_far,
         }
     }
 
@@ -214,6 +310,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_near,
             Projection::Orthographic(v) => v.z_near,
+            Projection::Asymmetric(v) => v.z_near,
         }
     }
 
@@ -223,6 +320,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_far,
             Projection::Orthographic(v) => v.z_far,
+            Projection::Asymmetric(v) => v.z_far,
         }
     }
 
@@ -232,6 +330,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.matrix(frame_size),
             Projection::Orthographic(v) => v.matrix(frame_size),
+            Projection::Asymmetric(v) => v.matrix(),
         }
     }
 }
@@ -583,6 +682,14 @@ impl Camera {
                     position: aabb.center() - look_vector.scale(distance),
                 }
             }
+            Projection::Asymmetric(asymmetric) => {
+                let radius = aabb.half_extents().max();
+                let distance = radius / (asymmetric.vertical_fov() * 0.5).sin();
+
+                FitParameters::Perspective {
+                    position: aabb.center() - look_vector.scale(distance),
+                }
+            }
             Projection::Orthographic(_) => {
                 let mut min_x = f32::MAX;
                 let mut min_y = f32::MAX;
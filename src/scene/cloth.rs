@@ -0,0 +1,826 @@
+//! Cloth is a simple soft-body solver that deforms a surface of a mesh.
+//!
+//! For more info see [`Cloth`]
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait, VertexWriteTrait},
+            surface::SurfaceData,
+            Mesh,
+        },
+        node::{Node, NodeTrait, UpdateContext},
+    },
+};
+use fxhash::FxHashMap;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Shape of a cloth collider.
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Default,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum ClothColliderKind {
+    /// A sphere with the center at the position of the collider node.
+    #[default]
+    Sphere,
+    /// A capsule with the center at the position of the collider node, it is oriented along local Y
+    /// axis of the node.
+    Capsule,
+}
+
+/// A simple shape that prevents the cloth from penetrating into it. Colliders are attached to scene
+/// nodes, so they could follow bones of a character (to prevent a cape from penetrating the body, for
+/// example). Scale of the collider node is ignored.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct ClothCollider {
+    /// A node that defines position and orientation of the collider.
+    pub node: Handle<Node>,
+    /// Shape of the collider.
+    pub kind: ClothColliderKind,
+    /// Radius of the sphere or the capsule.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub radius: f32,
+    /// Half-height of the cylindrical part of the capsule. Not used by spheres.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub half_height: f32,
+}
+
+impl Default for ClothCollider {
+    fn default() -> Self {
+        Self {
+            node: Default::default(),
+            kind: Default::default(),
+            radius: 0.5,
+            half_height: 0.5,
+        }
+    }
+}
+
+/// World-space collider in the form of a capsule (a sphere is a capsule with zero height).
+#[derive(Copy, Clone, Debug)]
+struct WorldCollider {
+    begin: Vector3<f32>,
+    end: Vector3<f32>,
+    radius: f32,
+}
+
+impl WorldCollider {
+    fn push_out(&self, point: Vector3<f32>) -> Vector3<f32> {
+        let axis = self.end - self.begin;
+        let length_sqr = axis.norm_squared();
+        let t = if length_sqr > f32::EPSILON {
+            ((point - self.begin).dot(&axis) / length_sqr).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let closest = self.begin + axis.scale(t);
+        let delta = point - closest;
+        let distance = delta.norm();
+        if distance < self.radius && distance > f32::EPSILON {
+            closest + delta.scale(self.radius / distance)
+        } else {
+            point
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SolverSettings {
+    acceleration: Vector3<f32>,
+    damping: f32,
+    stiffness: f32,
+    iterations: u32,
+}
+
+#[derive(Clone, Debug)]
+struct ClothParticle {
+    position: Vector3<f32>,
+    prev_position: Vector3<f32>,
+    // Position in local coordinates of the mesh, pinned particles are attached to it.
+    local_position: Vector3<f32>,
+    pinned: bool,
+}
+
+#[derive(Clone, Debug)]
+struct DistanceConstraint {
+    a: u32,
+    b: u32,
+    rest_length: f32,
+}
+
+#[derive(Clone, Debug, Default)]
+struct ClothState {
+    data_key: u64,
+    // Index of a particle for every vertex. Vertices with the same position (on UV seams, for example)
+    // share the same particle, otherwise the cloth will be torn apart at the seams.
+    vertex_particles: Vec<u32>,
+    particles: Vec<ClothParticle>,
+    constraints: Vec<DistanceConstraint>,
+}
+
+impl ClothState {
+    fn new(
+        data: &SurfaceData,
+        data_key: u64,
+        pinned_vertices: &[u32],
+        transform: &Matrix4<f32>,
+    ) -> Option<Self> {
+        let mut particles = Vec::<ClothParticle>::new();
+        let mut vertex_particles = Vec::new();
+        let mut particle_map = FxHashMap::default();
+        for vertex in data.vertex_buffer.iter() {
+            let position = vertex.read_3_f32(VertexAttributeUsage::Position).ok()?;
+            let key = [
+                position.x.to_bits(),
+                position.y.to_bits(),
+                position.z.to_bits(),
+            ];
+            let index = *particle_map.entry(key).or_insert_with(|| {
+                let world_position = transform.transform_point(&Point3::from(position)).coords;
+                particles.push(ClothParticle {
+                    position: world_position,
+                    prev_position: world_position,
+                    local_position: position,
+                    pinned: false,
+                });
+                particles.len() as u32 - 1
+            });
+            vertex_particles.push(index);
+        }
+
+        for &vertex in pinned_vertices {
+            if let Some(&particle) = vertex_particles.get(vertex as usize) {
+                particles[particle as usize].pinned = true;
+            }
+        }
+
+        let mut edges = FxHashMap::default();
+        for triangle in data.geometry_buffer.iter() {
+            for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+                let pa = *vertex_particles.get(triangle[a] as usize)?;
+                let pb = *vertex_particles.get(triangle[b] as usize)?;
+                if pa != pb {
+                    edges.entry((pa.min(pb), pa.max(pb))).or_insert_with(|| {
+                        (particles[pa as usize].position - particles[pb as usize].position).norm()
+                    });
+                }
+            }
+        }
+
+        let mut constraints = edges
+            .into_iter()
+            .map(|((a, b), rest_length)| DistanceConstraint { a, b, rest_length })
+            .collect::<Vec<_>>();
+        // Keep the order stable to make the simulation deterministic.
+        constraints.sort_by_key(|c| (c.a, c.b));
+
+        Some(Self {
+            data_key,
+            vertex_particles,
+            particles,
+            constraints,
+        })
+    }
+
+    fn step(
+        &mut self,
+        dt: f32,
+        transform: &Matrix4<f32>,
+        settings: &SolverSettings,
+        colliders: &[WorldCollider],
+    ) {
+        let damping = 1.0 - settings.damping.clamp(0.0, 1.0);
+        for particle in self.particles.iter_mut() {
+            if particle.pinned {
+                particle.position = transform
+                    .transform_point(&Point3::from(particle.local_position))
+                    .coords;
+                particle.prev_position = particle.position;
+            } else {
+                // Verlet integration.
+                let velocity = (particle.position - particle.prev_position).scale(damping);
+                particle.prev_position = particle.position;
+                particle.position += velocity + settings.acceleration.scale(dt * dt);
+            }
+        }
+
+        let stiffness = settings.stiffness.clamp(0.0, 1.0);
+        for _ in 0..settings.iterations {
+            for constraint in self.constraints.iter() {
+                let a = &self.particles[constraint.a as usize];
+                let b = &self.particles[constraint.b as usize];
+                let wa = if a.pinned { 0.0 } else { 1.0 };
+                let wb = if b.pinned { 0.0 } else { 1.0 };
+                if wa + wb == 0.0 {
+                    continue;
+                }
+
+                let delta = b.position - a.position;
+                let distance = delta.norm();
+                if distance <= f32::EPSILON {
+                    continue;
+                }
+
+                let correction =
+                    delta.scale((distance - constraint.rest_length) / distance * stiffness);
+                self.particles[constraint.a as usize].position += correction.scale(wa / (wa + wb));
+                self.particles[constraint.b as usize].position -= correction.scale(wb / (wa + wb));
+            }
+
+            for particle in self.particles.iter_mut().filter(|p| !p.pinned) {
+                for collider in colliders {
+                    particle.position = collider.push_out(particle.position);
+                }
+            }
+        }
+    }
+
+    fn write(
+        &self,
+        data: &mut SurfaceData,
+        inv_transform: &Matrix4<f32>,
+        recalculate_normals: bool,
+    ) {
+        // Pinned vertices do not move relative to the mesh, so only the range of free vertices is
+        // modified.
+        let is_free =
+            |vertex: usize| !self.particles[self.vertex_particles[vertex] as usize].pinned;
+        let count = self.vertex_particles.len();
+        let (first, last) = match (
+            (0..count).find(|&i| is_free(i)),
+            (0..count).rfind(|&i| is_free(i)),
+        ) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return,
+        };
+
+        let mut vertices = data.vertex_buffer.modify_range(first..last + 1);
+        for vertex in first..=last {
+            let particle = &self.particles[self.vertex_particles[vertex] as usize];
+            if !particle.pinned {
+                let position = inv_transform
+                    .transform_point(&Point3::from(particle.position))
+                    .coords;
+                if let Some(mut view) = vertices.get_mut(vertex) {
+                    let _ = view.write_3_f32(VertexAttributeUsage::Position, position);
+                }
+            }
+        }
+        drop(vertices);
+
+        if recalculate_normals {
+            let _ = data.recalculate_normals(first..last + 1);
+        }
+    }
+}
+
+/// Cloth is a simple position-based soft-body solver that deforms a surface of a mesh node. It is
+/// intended for flags, capes, curtains and similar secondary motion, it is not a replacement of a
+/// proper physics simulation.
+///
+/// # How it works
+///
+/// Every unique vertex of the surface becomes a particle, edges of the triangles become distance
+/// constraints that keep the original shape of the surface. Particles are simulated in world space,
+/// so the cloth reacts on the movement of the mesh. Pinned vertices (see [`Cloth::set_pinned_vertices`])
+/// are not simulated, they are attached to the mesh and move with it. The cloth does not collide with
+/// physics bodies, instead it uses a set of simple [`ClothCollider`]s attached to scene nodes.
+///
+/// The surface of the mesh becomes unique on the first update (see [`crate::scene::mesh::surface::Surface::make_unique_data`]),
+/// so other instances of the same model will not be affected by the simulation.
+///
+/// # Example
+///
+/// ```
+/// use fyrox::{
+///     core::{algebra::Vector3, pool::Handle},
+///     scene::{
+///         base::BaseBuilder,
+///         cloth::{select_vertices, ClothBuilder},
+///         graph::Graph,
+///         mesh::Mesh,
+///         node::Node,
+///     },
+/// };
+///
+/// fn create_flag(graph: &mut Graph, flag_mesh: Handle<Node>) -> Handle<Node> {
+///     // Pin the vertices at the flagpole.
+///     let pinned = graph[flag_mesh]
+///         .cast::<Mesh>()
+///         .and_then(|mesh| mesh.surfaces().first())
+///         .map(|surface| select_vertices(&surface.data().lock(), |p| p.x <= 0.0))
+///         .unwrap_or_default();
+///
+///     ClothBuilder::new(BaseBuilder::new())
+///         .with_mesh(flag_mesh)
+///         .with_pinned_vertices(pinned)
+///         .with_wind(Vector3::new(3.0, 0.0, 1.0))
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Clone, Visit, Reflect)]
+pub struct Cloth {
+    base: Base,
+
+    #[reflect(setter = "set_mesh")]
+    mesh: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_surface_index")]
+    surface_index: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_pinned_vertices")]
+    pinned_vertices: InheritableVariable<Vec<u32>>,
+
+    #[reflect(setter = "set_colliders")]
+    colliders: InheritableVariable<Vec<ClothCollider>>,
+
+    #[reflect(setter = "set_gravity")]
+    gravity: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(setter = "set_wind")]
+    wind: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    #[reflect(setter = "set_damping")]
+    damping: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    #[reflect(setter = "set_stiffness")]
+    stiffness: InheritableVariable<f32>,
+
+    #[reflect(min_value = 1.0, max_value = 32.0)]
+    #[reflect(setter = "set_iterations")]
+    iterations: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_recalculate_normals")]
+    recalculate_normals: InheritableVariable<bool>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    state: Option<ClothState>,
+}
+
+impl Default for Cloth {
+    fn default() -> Self {
+        ClothBuilder::new(BaseBuilder::new()).build_cloth()
+    }
+}
+
+impl Deref for Cloth {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Cloth {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Cloth {
+    fn type_uuid() -> Uuid {
+        uuid!("f5e4b1c7-8d2a-4a6f-9c3e-7b1d0a2e5f48")
+    }
+}
+
+/// Returns indices of the vertices of the given surface data, whose positions (in local coordinates of
+/// the mesh) satisfy the predicate. It could be used to select vertices to pin.
+pub fn select_vertices<F>(data: &SurfaceData, mut predicate: F) -> Vec<u32>
+where
+    F: FnMut(Vector3<f32>) -> bool,
+{
+    data.vertex_buffer
+        .iter()
+        .enumerate()
+        .filter_map(|(i, vertex)| {
+            vertex
+                .read_3_f32(VertexAttributeUsage::Position)
+                .ok()
+                .filter(|position| predicate(*position))
+                .map(|_| i as u32)
+        })
+        .collect()
+}
+
+impl Cloth {
+    /// Sets a mesh node, which surface will be deformed by the cloth. The simulation is restarted.
+    pub fn set_mesh(&mut self, mesh: Handle<Node>) -> Handle<Node> {
+        self.state = None;
+        self.mesh.set_value_and_mark_modified(mesh)
+    }
+
+    /// Returns a handle of the mesh node, which surface is deformed by the cloth.
+    pub fn mesh(&self) -> Handle<Node> {
+        *self.mesh
+    }
+
+    /// Sets an index of the surface of the mesh that will be deformed. The simulation is restarted.
+    pub fn set_surface_index(&mut self, index: u32) -> u32 {
+        self.state = None;
+        self.surface_index.set_value_and_mark_modified(index)
+    }
+
+    /// Returns an index of the surface of the mesh that is deformed.
+    pub fn surface_index(&self) -> u32 {
+        *self.surface_index
+    }
+
+    /// Sets indices of the vertices that will be attached to the mesh and won't be simulated. See
+    /// [`select_vertices`] for an easy way to find such vertices. The simulation is restarted.
+    pub fn set_pinned_vertices(&mut self, vertices: Vec<u32>) -> Vec<u32> {
+        self.state = None;
+        self.pinned_vertices.set_value_and_mark_modified(vertices)
+    }
+
+    /// Returns indices of the pinned vertices.
+    pub fn pinned_vertices(&self) -> &[u32] {
+        &self.pinned_vertices
+    }
+
+    /// Sets new set of colliders.
+    pub fn set_colliders(&mut self, colliders: Vec<ClothCollider>) -> Vec<ClothCollider> {
+        self.colliders.set_value_and_mark_modified(colliders)
+    }
+
+    /// Returns current set of colliders.
+    pub fn colliders(&self) -> &[ClothCollider] {
+        &self.colliders
+    }
+
+    /// Sets new gravity acceleration for the cloth.
+    pub fn set_gravity(&mut self, gravity: Vector3<f32>) -> Vector3<f32> {
+        self.gravity.set_value_and_mark_modified(gravity)
+    }
+
+    /// Returns current gravity acceleration of the cloth.
+    pub fn gravity(&self) -> Vector3<f32> {
+        *self.gravity
+    }
+
+    /// Sets wind acceleration for the cloth. It is applied to every particle in addition to gravity.
+    pub fn set_wind(&mut self, wind: Vector3<f32>) -> Vector3<f32> {
+        self.wind.set_value_and_mark_modified(wind)
+    }
+
+    /// Returns current wind acceleration of the cloth.
+    pub fn wind(&self) -> Vector3<f32> {
+        *self.wind
+    }
+
+    /// Sets velocity damping of the particles in `[0; 1]` range.
+    pub fn set_damping(&mut self, damping: f32) -> f32 {
+        self.damping
+            .set_value_and_mark_modified(damping.clamp(0.0, 1.0))
+    }
+
+    /// Returns velocity damping of the particles.
+    pub fn damping(&self) -> f32 {
+        *self.damping
+    }
+
+    /// Sets stiffness of the cloth in `[0; 1]` range. Lower values make the cloth more stretchy.
+    pub fn set_stiffness(&mut self, stiffness: f32) -> f32 {
+        self.stiffness
+            .set_value_and_mark_modified(stiffness.clamp(0.0, 1.0))
+    }
+
+    /// Returns stiffness of the cloth.
+    pub fn stiffness(&self) -> f32 {
+        *self.stiffness
+    }
+
+    /// Sets amount of solver iterations per update. More iterations make the cloth stiffer and more
+    /// stable at the cost of performance.
+    pub fn set_iterations(&mut self, iterations: u32) -> u32 {
+        self.iterations
+            .set_value_and_mark_modified(iterations.clamp(1, 32))
+    }
+
+    /// Returns amount of solver iterations per update.
+    pub fn iterations(&self) -> u32 {
+        *self.iterations
+    }
+
+    /// Defines whether normals of the deformed surface should be recalculated or not.
+    pub fn set_recalculate_normals(&mut self, recalculate: bool) -> bool {
+        self.recalculate_normals
+            .set_value_and_mark_modified(recalculate)
+    }
+
+    /// Returns `true` if normals of the deformed surface are recalculated, `false` - otherwise.
+    pub fn is_recalculating_normals(&self) -> bool {
+        *self.recalculate_normals
+    }
+
+    /// Restarts the simulation from the original shape of the surface.
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+impl NodeTrait for Cloth {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let colliders = self
+            .colliders
+            .iter()
+            .filter_map(|collider| {
+                let node = context.nodes.try_borrow(collider.node)?;
+                let center = node.global_position();
+                let axis = if collider.kind == ClothColliderKind::Capsule {
+                    node.up_vector()
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_default()
+                        .scale(collider.half_height)
+                } else {
+                    Vector3::default()
+                };
+                Some(WorldCollider {
+                    begin: center - axis,
+                    end: center + axis,
+                    radius: collider.radius,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mesh = match context
+            .nodes
+            .try_borrow_mut(*self.mesh)
+            .and_then(|node| node.cast_mut::<Mesh>())
+        {
+            Some(mesh) => mesh,
+            None => {
+                self.state = None;
+                return;
+            }
+        };
+
+        let transform = mesh.global_transform();
+        let inv_transform = transform.try_inverse().unwrap_or_else(Matrix4::identity);
+        let surface = match mesh.surfaces_mut().get_mut(*self.surface_index as usize) {
+            Some(surface) => surface,
+            None => {
+                self.state = None;
+                return;
+            }
+        };
+        let data = surface.make_unique_data();
+        let mut data = data.lock();
+
+        let data_key = surface.data_ref().key();
+        if self
+            .state
+            .as_ref()
+            .map(|state| state.data_key != data_key)
+            .unwrap_or(true)
+        {
+            self.state = ClothState::new(&data, data_key, &self.pinned_vertices, &transform);
+        }
+
+        if let Some(state) = self.state.as_mut() {
+            // Large time steps make the simulation unstable.
+            let dt = context.dt.min(1.0 / 30.0);
+            let settings = SolverSettings {
+                acceleration: *self.gravity + *self.wind,
+                damping: *self.damping,
+                stiffness: *self.stiffness,
+                iterations: *self.iterations,
+            };
+            state.step(dt, &transform, &settings, &colliders);
+            state.write(&mut data, &inv_transform, *self.recalculate_normals);
+        }
+    }
+}
+
+/// Allows you to create a cloth in a declarative manner.
+pub struct ClothBuilder {
+    base_builder: BaseBuilder,
+    mesh: Handle<Node>,
+    surface_index: u32,
+    pinned_vertices: Vec<u32>,
+    colliders: Vec<ClothCollider>,
+    gravity: Vector3<f32>,
+    wind: Vector3<f32>,
+    damping: f32,
+    stiffness: f32,
+    iterations: u32,
+    recalculate_normals: bool,
+}
+
+impl ClothBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            mesh: Default::default(),
+            surface_index: 0,
+            pinned_vertices: Default::default(),
+            colliders: Default::default(),
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            wind: Default::default(),
+            damping: 0.02,
+            stiffness: 1.0,
+            iterations: 4,
+            recalculate_normals: true,
+        }
+    }
+
+    /// Sets desired mesh node, which surface will be deformed.
+    pub fn with_mesh(mut self, mesh: Handle<Node>) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    /// Sets desired index of the surface of the mesh.
+    pub fn with_surface_index(mut self, index: u32) -> Self {
+        self.surface_index = index;
+        self
+    }
+
+    /// Sets desired indices of pinned vertices.
+    pub fn with_pinned_vertices(mut self, vertices: Vec<u32>) -> Self {
+        self.pinned_vertices = vertices;
+        self
+    }
+
+    /// Sets desired set of colliders.
+    pub fn with_colliders(mut self, colliders: Vec<ClothCollider>) -> Self {
+        self.colliders = colliders;
+        self
+    }
+
+    /// Sets desired gravity acceleration.
+    pub fn with_gravity(mut self, gravity: Vector3<f32>) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Sets desired wind acceleration.
+    pub fn with_wind(mut self, wind: Vector3<f32>) -> Self {
+        self.wind = wind;
+        self
+    }
+
+    /// Sets desired velocity damping.
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Sets desired stiffness.
+    pub fn with_stiffness(mut self, stiffness: f32) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    /// Sets desired amount of solver iterations.
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Defines whether normals of the deformed surface should be recalculated or not.
+    pub fn with_recalculate_normals(mut self, recalculate: bool) -> Self {
+        self.recalculate_normals = recalculate;
+        self
+    }
+
+    /// Creates new cloth.
+    pub fn build_cloth(self) -> Cloth {
+        Cloth {
+            base: self.base_builder.build_base(),
+            mesh: self.mesh.into(),
+            surface_index: self.surface_index.into(),
+            pinned_vertices: self.pinned_vertices.into(),
+            colliders: self.colliders.into(),
+            gravity: self.gravity.into(),
+            wind: self.wind.into(),
+            damping: self.damping.clamp(0.0, 1.0).into(),
+            stiffness: self.stiffness.clamp(0.0, 1.0).into(),
+            iterations: self.iterations.clamp(1, 32).into(),
+            recalculate_normals: self.recalculate_normals.into(),
+            state: None,
+        }
+    }
+
+    /// Creates new cloth node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_cloth())
+    }
+
+    /// Creates new instance of cloth node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector3},
+        scene::{
+            cloth::{select_vertices, ClothState, SolverSettings, WorldCollider},
+            mesh::{
+                buffer::{VertexAttributeUsage, VertexReadTrait},
+                surface::SurfaceData,
+            },
+        },
+    };
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_cloth_simulation() {
+        // Unit quad lying horizontally, its edge at local Y = 1 is pinned.
+        let mut data = SurfaceData::make_unit_xy_quad();
+        let pinned = select_vertices(&data, |p| p.y > 0.5);
+        assert_eq!(pinned.len(), 2);
+
+        let transform = Matrix4::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2);
+        let inv_transform = transform.try_inverse().unwrap();
+        let mut state = ClothState::new(&data, 0, &pinned, &transform).unwrap();
+        assert_eq!(state.particles.len(), 4);
+        assert_eq!(state.constraints.len(), 5);
+
+        // The free edge swings down and hits the obstacle.
+        let obstacle = WorldCollider {
+            begin: Vector3::new(0.0, -1.0, 1.0),
+            end: Vector3::new(0.0, -1.0, 1.0),
+            radius: 0.1,
+        };
+        let settings = SolverSettings {
+            acceleration: Vector3::new(0.0, -9.81, 0.0),
+            damping: 0.02,
+            stiffness: 1.0,
+            iterations: 16,
+        };
+        for _ in 0..300 {
+            state.step(1.0 / 60.0, &transform, &settings, &[obstacle]);
+        }
+
+        for particle in state.particles.iter().filter(|p| !p.pinned) {
+            assert!(particle.position.y < -0.5);
+            assert!(particle.position.y > -1.1);
+            assert!((particle.position - obstacle.begin).norm() >= obstacle.radius - 0.001);
+        }
+        for constraint in state.constraints.iter() {
+            let a = state.particles[constraint.a as usize].position;
+            let b = state.particles[constraint.b as usize].position;
+            assert!(((a - b).norm() - constraint.rest_length).abs() < 0.1);
+        }
+
+        state.write(&mut data, &inv_transform, true);
+        for (vertex, particle) in state.vertex_particles.iter().enumerate() {
+            let particle = &state.particles[*particle as usize];
+            let position = data
+                .vertex_buffer
+                .get(vertex)
+                .unwrap()
+                .read_3_f32(VertexAttributeUsage::Position)
+                .unwrap();
+            let expected = inv_transform
+                .transform_point(&particle.position.into())
+                .coords;
+            assert!((position - expected).norm() < 0.001);
+        }
+    }
+}
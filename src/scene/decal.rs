@@ -207,6 +207,10 @@ impl NodeTrait for Decal {
     fn id(&self) -> Uuid {
         Self::type_uuid()
     }
+
+    fn updates_render_data(&self) -> bool {
+        false
+    }
 }
 
 /// Allows you to create a Decal in a declarative manner.
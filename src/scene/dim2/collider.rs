@@ -547,6 +547,10 @@ impl NodeTrait for Collider {
         Self::type_uuid()
     }
 
+    fn updates_render_data(&self) -> bool {
+        false
+    }
+
     fn on_removed_from_graph(&mut self, graph: &mut Graph) {
         graph.physics2d.remove_collider(self.native.get());
         self.native.set(ColliderHandle::invalid());
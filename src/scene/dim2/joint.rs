@@ -242,6 +242,10 @@ impl NodeTrait for Joint {
         Self::type_uuid()
     }
 
+    fn updates_render_data(&self) -> bool {
+        false
+    }
+
     fn on_removed_from_graph(&mut self, graph: &mut Graph) {
         graph.physics2d.remove_joint(self.native.get());
         self.native.set(ImpulseJointHandle::invalid());
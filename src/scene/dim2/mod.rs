@@ -6,3 +6,4 @@ pub mod joint;
 pub mod physics;
 pub mod rectangle;
 pub mod rigidbody;
+pub mod tilemap;
@@ -450,6 +450,10 @@ impl NodeTrait for RigidBody {
         );
     }
 
+    fn updates_render_data(&self) -> bool {
+        false
+    }
+
     fn validate(&self, scene: &Scene) -> Result<(), String> {
         for &child in self.children() {
             if scene.graph.try_get_of_type::<Collider>(child).is_some() {
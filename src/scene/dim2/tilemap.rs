@@ -0,0 +1,889 @@
+//! Tile map is a 2D grid of tiles, that is used to build 2D levels.
+//!
+//! See [`TileMap`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, Rect},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::{prelude::*, PodVecView},
+        TypeUuidProvider,
+    },
+    resource::texture::TextureResource,
+    scene::{
+        base::{Base, BaseBuilder},
+        dim2::{
+            collider::{ColliderBuilder, ColliderShape, TriangleShape},
+            rigidbody::RigidBodyBuilder,
+        },
+        graph::Graph,
+        node::{Node, NodeTrait, UpdateContext},
+        rigidbody::RigidBodyType,
+        transform::TransformBuilder,
+    },
+};
+use fxhash::FxHashMap;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Width and height of a chunk of a layer (in cells).
+pub const CHUNK_SIZE: i32 = 16;
+
+// Z offset between adjacent layers, it is small enough to not interfere with other objects, but
+// large enough to keep tiles of upper layers in front of tiles of lower layers.
+pub(crate) const LAYER_DEPTH_STEP: f32 = 0.0001;
+
+const FLIP_X_FLAG: u32 = 1 << 31;
+const FLIP_Y_FLAG: u32 = 1 << 30;
+const DEFINITION_MASK: u32 = !(FLIP_X_FLAG | FLIP_Y_FLAG);
+
+/// A single frame of tile animation.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct TileAnimationFrame {
+    /// Index of a tile definition in the tile set, that should be shown during the frame.
+    pub tile: u32,
+    /// Duration of the frame in seconds.
+    pub duration: f32,
+}
+
+/// Collision shape of a tile. Coordinates are in cells, relative to the bottom-left corner of a
+/// tile with Y axis pointing up.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames)]
+pub enum TileCollisionShape {
+    /// Axis-aligned rectangle, its position defines bottom-left corner.
+    Rectangle(Rect<f32>),
+    /// Convex or concave polygon, defined by its points.
+    Polygon(Vec<Vector2<f32>>),
+}
+
+impl Default for TileCollisionShape {
+    fn default() -> Self {
+        Self::Rectangle(Rect::new(0.0, 0.0, 1.0, 1.0))
+    }
+}
+
+/// Tile definition describes how a tile looks and behaves.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct TileDefinition {
+    /// Texture of the tile.
+    pub texture: Option<TextureResource>,
+    /// A region of the texture, that will be used for the tile. The coordinates are normalized
+    /// which means `[0; 0]` corresponds to top-left corner of the texture and `[1; 1]` corresponds
+    /// to right-bottom corner.
+    pub uv_rect: Rect<f32>,
+    /// Size of the tile in cells. Tiles that are larger than a cell are anchored at the
+    /// bottom-left corner of their cell and overlap neighbouring cells to the right and up.
+    pub size: Vector2<f32>,
+    /// A set of collision shapes of the tile, could be empty.
+    pub collision: Vec<TileCollisionShape>,
+    /// A set of animation frames of the tile. Empty set means that the tile is not animated.
+    pub animation: Vec<TileAnimationFrame>,
+}
+
+impl Default for TileDefinition {
+    fn default() -> Self {
+        Self {
+            texture: None,
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            size: Vector2::new(1.0, 1.0),
+            collision: Default::default(),
+            animation: Default::default(),
+        }
+    }
+}
+
+impl TileDefinition {
+    /// Returns total duration of the animation of the tile (in seconds).
+    pub fn animation_duration(&self) -> f32 {
+        self.animation.iter().map(|frame| frame.duration).sum()
+    }
+}
+
+/// Tile set is a set of tile definitions, that are used by a tile map.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct TileSet {
+    /// Tile definitions, tiles in tile map layers refer to them by their indices.
+    pub tiles: Vec<TileDefinition>,
+}
+
+impl TileSet {
+    /// Returns index of a tile definition, that should be shown at the given time. It takes
+    /// animation of the tile into account and returns the given index for static tiles.
+    pub fn animated_definition(&self, definition: u32, time: f32) -> u32 {
+        let tile = match self.tiles.get(definition as usize) {
+            Some(tile) => tile,
+            None => return definition,
+        };
+
+        let duration = tile.animation_duration();
+        if duration <= 0.0 {
+            return definition;
+        }
+
+        let mut time = time.rem_euclid(duration);
+        for frame in tile.animation.iter() {
+            if time < frame.duration {
+                return frame.tile;
+            }
+            time -= frame.duration;
+        }
+
+        tile.animation.last().map_or(definition, |frame| frame.tile)
+    }
+}
+
+/// A tile in a cell of a tile map layer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Tile {
+    /// Index of a tile definition in the tile set of a tile map.
+    pub definition: u32,
+    /// Whether the tile is mirrored horizontally or not.
+    pub flip_x: bool,
+    /// Whether the tile is mirrored vertically or not.
+    pub flip_y: bool,
+}
+
+impl Tile {
+    /// Creates new tile that uses the given tile definition.
+    pub fn new(definition: u32) -> Self {
+        Self {
+            definition,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    /// Sets whether the tile should be mirrored horizontally or not.
+    pub fn with_flip_x(mut self, flip_x: bool) -> Self {
+        self.flip_x = flip_x;
+        self
+    }
+
+    /// Sets whether the tile should be mirrored vertically or not.
+    pub fn with_flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+
+    // Zero is reserved for empty cells, so the definition is stored with offset of one.
+    fn encode(self) -> u32 {
+        let mut value = (self.definition + 1) & DEFINITION_MASK;
+        if self.flip_x {
+            value |= FLIP_X_FLAG;
+        }
+        if self.flip_y {
+            value |= FLIP_Y_FLAG;
+        }
+        value
+    }
+
+    fn decode(value: u32) -> Option<Self> {
+        let definition = value & DEFINITION_MASK;
+        if definition == 0 {
+            None
+        } else {
+            Some(Self {
+                definition: definition - 1,
+                flip_x: value & FLIP_X_FLAG != 0,
+                flip_y: value & FLIP_Y_FLAG != 0,
+            })
+        }
+    }
+}
+
+/// A square block of [`CHUNK_SIZE`] x [`CHUNK_SIZE`] cells of a tile map layer. Layers are split
+/// into chunks so they can be unbounded and so the renderer could skip invisible parts of a map
+/// quickly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileMapChunk {
+    tiles: Vec<u32>,
+}
+
+impl Default for TileMapChunk {
+    fn default() -> Self {
+        Self {
+            tiles: vec![0; (CHUNK_SIZE * CHUNK_SIZE) as usize],
+        }
+    }
+}
+
+impl Visit for TileMapChunk {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        let mut view = PodVecView::from_pod_vec(&mut self.tiles);
+        view.visit("Tiles", &mut region)?;
+
+        if region.is_reading() {
+            self.tiles.resize((CHUNK_SIZE * CHUNK_SIZE) as usize, 0);
+        }
+
+        Ok(())
+    }
+}
+
+impl TileMapChunk {
+    fn index(position: Vector2<i32>) -> usize {
+        (position.y * CHUNK_SIZE + position.x) as usize
+    }
+
+    /// Returns a tile at the given position in the chunk.
+    pub fn tile(&self, position: Vector2<i32>) -> Option<Tile> {
+        self.tiles
+            .get(Self::index(position))
+            .and_then(|value| Tile::decode(*value))
+    }
+
+    /// Returns an iterator over all non-empty cells of the chunk. Positions are relative to the
+    /// chunk.
+    pub fn tiles(&self) -> impl Iterator<Item = (Vector2<i32>, Tile)> + '_ {
+        self.tiles.iter().enumerate().filter_map(|(index, value)| {
+            Tile::decode(*value).map(|tile| {
+                let index = index as i32;
+                (Vector2::new(index % CHUNK_SIZE, index / CHUNK_SIZE), tile)
+            })
+        })
+    }
+
+    /// Returns `true` if the chunk has no tiles, `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.iter().all(|value| *value == 0)
+    }
+}
+
+/// Splits cell position into a position of a chunk and a position in the chunk.
+fn split_position(position: Vector2<i32>) -> (Vector2<i32>, Vector2<i32>) {
+    (
+        Vector2::new(
+            position.x.div_euclid(CHUNK_SIZE),
+            position.y.div_euclid(CHUNK_SIZE),
+        ),
+        Vector2::new(
+            position.x.rem_euclid(CHUNK_SIZE),
+            position.y.rem_euclid(CHUNK_SIZE),
+        ),
+    )
+}
+
+/// A layer of a tile map. Layers are drawn in order, so tiles of a layer are drawn on top of tiles
+/// of the previous layers.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct TileMapLayer {
+    /// Name of the layer.
+    pub name: String,
+    /// Whether the layer is visible or not.
+    pub visible: bool,
+    /// Color of the layer, it is multiplied with colors of tile textures.
+    pub color: Color,
+    #[reflect(hidden)]
+    chunks: FxHashMap<Vector2<i32>, TileMapChunk>,
+}
+
+impl Default for TileMapLayer {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            visible: true,
+            color: Color::WHITE,
+            chunks: Default::default(),
+        }
+    }
+}
+
+impl TileMapLayer {
+    /// Creates new empty layer with the given name.
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a tile at the given cell.
+    pub fn tile(&self, position: Vector2<i32>) -> Option<Tile> {
+        let (chunk, local) = split_position(position);
+        self.chunks.get(&chunk).and_then(|chunk| chunk.tile(local))
+    }
+
+    /// Puts a tile in the given cell, `None` clears the cell. Returns previous tile of the cell.
+    pub fn set_tile(&mut self, position: Vector2<i32>, tile: Option<Tile>) -> Option<Tile> {
+        let (chunk_position, local) = split_position(position);
+        let index = TileMapChunk::index(local);
+
+        match tile {
+            Some(tile) => {
+                let chunk = self.chunks.entry(chunk_position).or_default();
+                Tile::decode(std::mem::replace(&mut chunk.tiles[index], tile.encode()))
+            }
+            None => {
+                let chunk = self.chunks.get_mut(&chunk_position)?;
+                let previous = Tile::decode(std::mem::replace(&mut chunk.tiles[index], 0));
+                if chunk.is_empty() {
+                    self.chunks.remove(&chunk_position);
+                }
+                previous
+            }
+        }
+    }
+
+    /// Removes every tile from the layer.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Returns an iterator over non-empty chunks of the layer with their positions (in chunks).
+    pub fn chunks(&self) -> impl Iterator<Item = (Vector2<i32>, &TileMapChunk)> {
+        self.chunks
+            .iter()
+            .map(|(position, chunk)| (*position, chunk))
+    }
+
+    /// Returns an iterator over all tiles of the layer with their cell positions.
+    pub fn tiles(&self) -> impl Iterator<Item = (Vector2<i32>, Tile)> + '_ {
+        self.chunks.iter().flat_map(|(chunk_position, chunk)| {
+            let origin = chunk_position * CHUNK_SIZE;
+            chunk
+                .tiles()
+                .map(move |(position, tile)| (origin + position, tile))
+        })
+    }
+}
+
+/// Tile map is a 2D grid of tiles, that is used to build 2D levels. It consists of a tile set and
+/// a number of layers, each layer stores indices of tile definitions of the tile set in its cells.
+///
+/// ## Coordinate system
+///
+/// Cells are addressed by integer coordinates, X axis of a grid points right (on the screen) and
+/// Y axis points up. Cell `[0; 0]` is located at the origin of the node, its bottom-left corner
+/// (on the screen) matches the origin. Keep in mind that 2D camera looks along Z axis, which means
+/// that "right" on the screen is -X in local coordinates of the node. Use [`Self::cell_to_local`]
+/// and [`Self::local_to_cell`] to convert coordinates.
+///
+/// ## Rendering
+///
+/// Tile maps are rendered by the 2D renderer, tiles are batched by their textures and layers are
+/// split into chunks, so only visible parts of a map are drawn. Animated tiles are switched using
+/// internal timer of the node.
+///
+/// ## Physics
+///
+/// Tiles can have collision shapes, use [`TileMap::create_colliders`] to create 2D colliders for
+/// them.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector2, pool::Handle},
+///     resource::texture::TextureResource,
+///     scene::{
+///         base::BaseBuilder,
+///         dim2::tilemap::{Tile, TileDefinition, TileMapBuilder, TileMapLayer, TileSet},
+///         graph::Graph,
+///         node::Node,
+///     },
+/// };
+///
+/// fn create_tile_map(graph: &mut Graph, texture: TextureResource) -> Handle<Node> {
+///     let mut layer = TileMapLayer::new("Ground");
+///     for x in 0..10 {
+///         layer.set_tile(Vector2::new(x, 0), Some(Tile::new(0)));
+///     }
+///
+///     TileMapBuilder::new(BaseBuilder::new())
+///         .with_tile_set(TileSet {
+///             tiles: vec![TileDefinition {
+///                 texture: Some(texture),
+///                 ..Default::default()
+///             }],
+///         })
+///         .with_layers(vec![layer])
+///         .build(graph)
+/// }
+/// ```
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct TileMap {
+    base: Base,
+
+    #[reflect(setter = "set_tile_set")]
+    tile_set: InheritableVariable<TileSet>,
+
+    #[reflect(setter = "set_tile_size")]
+    tile_size: InheritableVariable<Vector2<f32>>,
+
+    #[reflect(setter = "set_layers")]
+    layers: InheritableVariable<Vec<TileMapLayer>>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+}
+
+impl Default for TileMap {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            tile_set: Default::default(),
+            tile_size: InheritableVariable::new_modified(Vector2::new(1.0, 1.0)),
+            layers: Default::default(),
+            time: 0.0,
+        }
+    }
+}
+
+impl Deref for TileMap {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for TileMap {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for TileMap {
+    fn type_uuid() -> Uuid {
+        uuid!("6a6b9c0e-4f87-4a2f-9b6c-3d1e0c7b5a42")
+    }
+}
+
+impl TileMap {
+    /// Returns a reference to the tile set of the tile map.
+    pub fn tile_set(&self) -> &TileSet {
+        &self.tile_set
+    }
+
+    /// Returns a mutable reference to the tile set of the tile map.
+    pub fn tile_set_mut(&mut self) -> &mut TileSet {
+        self.tile_set.get_value_mut_and_mark_modified()
+    }
+
+    /// Sets new tile set of the tile map.
+    pub fn set_tile_set(&mut self, tile_set: TileSet) -> TileSet {
+        self.tile_set.set_value_and_mark_modified(tile_set)
+    }
+
+    /// Returns size of a cell in local coordinates of the node.
+    pub fn tile_size(&self) -> Vector2<f32> {
+        *self.tile_size
+    }
+
+    /// Sets size of a cell in local coordinates of the node.
+    pub fn set_tile_size(&mut self, tile_size: Vector2<f32>) -> Vector2<f32> {
+        self.tile_size.set_value_and_mark_modified(tile_size)
+    }
+
+    /// Returns a slice with layers of the tile map.
+    pub fn layers(&self) -> &[TileMapLayer] {
+        &self.layers
+    }
+
+    /// Returns a mutable reference to the layers of the tile map.
+    pub fn layers_mut(&mut self) -> &mut Vec<TileMapLayer> {
+        self.layers.get_value_mut_and_mark_modified()
+    }
+
+    /// Sets new layers of the tile map.
+    pub fn set_layers(&mut self, layers: Vec<TileMapLayer>) -> Vec<TileMapLayer> {
+        self.layers.set_value_and_mark_modified(layers)
+    }
+
+    /// Returns a tile at the given cell of the given layer.
+    pub fn tile(&self, layer: usize, position: Vector2<i32>) -> Option<Tile> {
+        self.layers
+            .get(layer)
+            .and_then(|layer| layer.tile(position))
+    }
+
+    /// Puts a tile in the given cell of the given layer, `None` clears the cell. Returns previous
+    /// tile of the cell.
+    pub fn set_tile(
+        &mut self,
+        layer: usize,
+        position: Vector2<i32>,
+        tile: Option<Tile>,
+    ) -> Option<Tile> {
+        self.layers_mut()
+            .get_mut(layer)
+            .and_then(|layer| layer.set_tile(position, tile))
+    }
+
+    /// Returns current time of the tile animations (in seconds).
+    pub fn animation_time(&self) -> f32 {
+        self.time
+    }
+
+    /// Sets current time of the tile animations (in seconds).
+    pub fn set_animation_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    /// Returns position of the bottom-left (on the screen) corner of the given cell in local
+    /// coordinates of the node.
+    pub fn cell_to_local(&self, position: Vector2<i32>) -> Vector2<f32> {
+        Vector2::new(
+            -position.x as f32 * self.tile_size.x,
+            position.y as f32 * self.tile_size.y,
+        )
+    }
+
+    /// Returns a cell, that contains the given point in local coordinates of the node.
+    pub fn local_to_cell(&self, point: Vector2<f32>) -> Vector2<i32> {
+        Vector2::new(
+            (-point.x / self.tile_size.x).floor() as i32,
+            (point.y / self.tile_size.y).floor() as i32,
+        )
+    }
+
+    /// Returns local transform of a quad of the given tile definition in the given cell of the
+    /// given layer. The quad is a unit rectangle centered at the origin.
+    pub fn tile_transform(
+        &self,
+        layer: usize,
+        position: Vector2<i32>,
+        definition: &TileDefinition,
+    ) -> Matrix4<f32> {
+        let size = definition.size.component_mul(&self.tile_size);
+        let corner = self.cell_to_local(position);
+        Matrix4::new_translation(&Vector3::new(
+            corner.x - size.x * 0.5,
+            corner.y + size.y * 0.5,
+            -(layer as f32) * LAYER_DEPTH_STEP,
+        )) * Matrix4::new_nonuniform_scaling(&Vector3::new(size.x, size.y, 1.0))
+    }
+
+    /// Returns local bounding box of the given chunk of the given layer, it takes tiles larger
+    /// than a cell into account.
+    pub fn chunk_bounding_box(
+        &self,
+        layer: usize,
+        chunk_position: Vector2<i32>,
+    ) -> AxisAlignedBoundingBox {
+        let max_size = self
+            .tile_set
+            .tiles
+            .iter()
+            .fold(Vector2::new(1.0f32, 1.0f32), |size, tile| {
+                Vector2::new(size.x.max(tile.size.x), size.y.max(tile.size.y))
+            });
+
+        let min = self.cell_to_local(chunk_position * CHUNK_SIZE);
+        let max = Vector2::new(
+            min.x - (CHUNK_SIZE as f32 - 1.0 + max_size.x) * self.tile_size.x,
+            min.y + (CHUNK_SIZE as f32 - 1.0 + max_size.y) * self.tile_size.y,
+        );
+        let z = -(layer as f32) * LAYER_DEPTH_STEP;
+
+        let mut aabb = AxisAlignedBoundingBox::default();
+        aabb.add_point(Vector3::new(min.x, min.y, z));
+        aabb.add_point(Vector3::new(max.x, max.y, z));
+        aabb
+    }
+
+    /// Returns collision shapes of every tile in every layer of the tile map. Each shape is
+    /// returned as a list of points (in local coordinates of the node) with a flag, that tells
+    /// whether the shape is an axis-aligned rectangle (in this case there are exactly two points:
+    /// its min and max corners) or a polygon.
+    fn collision_polygons(&self) -> Vec<(bool, Vec<Vector2<f32>>)> {
+        let mut shapes = Vec::new();
+        for layer in self.layers.iter() {
+            for (position, tile) in layer.tiles() {
+                let definition = match self.tile_set.tiles.get(tile.definition as usize) {
+                    Some(definition) => definition,
+                    None => continue,
+                };
+
+                let to_local = |point: Vector2<f32>| {
+                    let x = if tile.flip_x {
+                        definition.size.x - point.x
+                    } else {
+                        point.x
+                    };
+                    let y = if tile.flip_y {
+                        definition.size.y - point.y
+                    } else {
+                        point.y
+                    };
+                    Vector2::new(
+                        -(position.x as f32 + x) * self.tile_size.x,
+                        (position.y as f32 + y) * self.tile_size.y,
+                    )
+                };
+
+                for shape in definition.collision.iter() {
+                    match shape {
+                        TileCollisionShape::Rectangle(rect) => {
+                            shapes.push((
+                                true,
+                                vec![to_local(rect.position), to_local(rect.position + rect.size)],
+                            ));
+                        }
+                        TileCollisionShape::Polygon(points) => {
+                            if points.len() >= 3 {
+                                shapes
+                                    .push((false, points.iter().cloned().map(to_local).collect()));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        shapes
+    }
+
+    /// Creates a static 2D rigid body with colliders for every collision shape of every tile in
+    /// the given tile map. The rigid body is attached to the tile map, so it moves together with
+    /// it. Polygons are triangulated as fans, so they must be convex to be precise. Returns
+    /// handle of the rigid body or [`Handle::NONE`] if the tile map has no collision shapes.
+    pub fn create_colliders(graph: &mut Graph, tile_map: Handle<Node>) -> Handle<Node> {
+        let shapes = graph
+            .try_get(tile_map)
+            .and_then(|node| node.cast::<TileMap>())
+            .map(|tile_map| tile_map.collision_polygons())
+            .unwrap_or_default();
+
+        if shapes.is_empty() {
+            return Handle::NONE;
+        }
+
+        let mut colliders = Vec::new();
+        for (is_rectangle, points) in shapes {
+            if is_rectangle {
+                let (a, b) = (points[0], points[1]);
+                let center = (a + b).scale(0.5);
+                colliders.push(
+                    ColliderBuilder::new(
+                        BaseBuilder::new().with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(Vector3::new(center.x, center.y, 0.0))
+                                .build(),
+                        ),
+                    )
+                    .with_shape(ColliderShape::cuboid(
+                        (b.x - a.x).abs() * 0.5,
+                        (b.y - a.y).abs() * 0.5,
+                    ))
+                    .build(graph),
+                );
+            } else {
+                for i in 1..points.len() - 1 {
+                    colliders.push(
+                        ColliderBuilder::new(BaseBuilder::new())
+                            .with_shape(ColliderShape::Triangle(TriangleShape {
+                                a: points[0],
+                                b: points[i],
+                                c: points[i + 1],
+                            }))
+                            .build(graph),
+                    );
+                }
+            }
+        }
+
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_name("Collision")
+                .with_children(&colliders),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(graph);
+
+        graph.link_nodes(body, tile_map);
+
+        body
+    }
+}
+
+impl NodeTrait for TileMap {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = AxisAlignedBoundingBox::default();
+        for (index, layer) in self.layers.iter().enumerate() {
+            for (chunk_position, _) in layer.chunks() {
+                aabb.add_box(self.chunk_bounding_box(index, chunk_position));
+            }
+        }
+        if aabb.is_invalid_or_degenerate() {
+            AxisAlignedBoundingBox::collapsed()
+        } else {
+            aabb
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        self.time += context.dt;
+    }
+}
+
+/// Allows you to create tile maps in declarative manner.
+pub struct TileMapBuilder {
+    base_builder: BaseBuilder,
+    tile_set: TileSet,
+    tile_size: Vector2<f32>,
+    layers: Vec<TileMapLayer>,
+}
+
+impl TileMapBuilder {
+    /// Creates new tile map builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            tile_set: Default::default(),
+            tile_size: Vector2::new(1.0, 1.0),
+            layers: Default::default(),
+        }
+    }
+
+    /// Sets desired tile set of the tile map.
+    pub fn with_tile_set(mut self, tile_set: TileSet) -> Self {
+        self.tile_set = tile_set;
+        self
+    }
+
+    /// Sets desired size of a cell of the tile map.
+    pub fn with_tile_size(mut self, tile_size: Vector2<f32>) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+
+    /// Sets desired layers of the tile map.
+    pub fn with_layers(mut self, layers: Vec<TileMapLayer>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Creates new [`TileMap`] instance.
+    pub fn build_tile_map(self) -> TileMap {
+        TileMap {
+            base: self.base_builder.build_base(),
+            tile_set: self.tile_set.into(),
+            tile_size: self.tile_size.into(),
+            layers: self.layers.into(),
+            time: 0.0,
+        }
+    }
+
+    /// Creates new [`TileMap`] instance.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_tile_map())
+    }
+
+    /// Creates new [`TileMap`] instance and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tile_encoding() {
+        for tile in [
+            Tile::new(0),
+            Tile::new(123).with_flip_x(true),
+            Tile::new(7).with_flip_x(true).with_flip_y(true),
+        ] {
+            assert_eq!(Tile::decode(tile.encode()), Some(tile));
+        }
+        assert_eq!(Tile::decode(0), None);
+    }
+
+    #[test]
+    fn test_layer_chunks() {
+        let mut layer = TileMapLayer::new("Test");
+        assert_eq!(
+            layer.set_tile(Vector2::new(-1, -1), Some(Tile::new(1))),
+            None
+        );
+        assert_eq!(
+            layer.set_tile(Vector2::new(20, 3), Some(Tile::new(2))),
+            None
+        );
+        assert_eq!(layer.chunks().count(), 2);
+        assert_eq!(layer.tile(Vector2::new(-1, -1)), Some(Tile::new(1)));
+        assert_eq!(layer.tile(Vector2::new(20, 3)), Some(Tile::new(2)));
+        assert_eq!(layer.tile(Vector2::new(0, 0)), None);
+
+        let mut tiles = layer.tiles().collect::<Vec<_>>();
+        tiles.sort_by_key(|(position, _)| position.x);
+        assert_eq!(
+            tiles,
+            vec![
+                (Vector2::new(-1, -1), Tile::new(1)),
+                (Vector2::new(20, 3), Tile::new(2))
+            ]
+        );
+
+        // Empty chunks must be removed.
+        assert_eq!(
+            layer.set_tile(Vector2::new(-1, -1), None),
+            Some(Tile::new(1))
+        );
+        assert_eq!(layer.chunks().count(), 1);
+    }
+
+    #[test]
+    fn test_animated_definition() {
+        let tile_set = TileSet {
+            tiles: vec![
+                TileDefinition {
+                    animation: vec![
+                        TileAnimationFrame {
+                            tile: 1,
+                            duration: 0.5,
+                        },
+                        TileAnimationFrame {
+                            tile: 2,
+                            duration: 0.25,
+                        },
+                    ],
+                    ..Default::default()
+                },
+                Default::default(),
+                Default::default(),
+            ],
+        };
+
+        assert_eq!(tile_set.animated_definition(0, 0.1), 1);
+        assert_eq!(tile_set.animated_definition(0, 0.6), 2);
+        assert_eq!(tile_set.animated_definition(0, 0.8), 1);
+        assert_eq!(tile_set.animated_definition(1, 0.6), 1);
+    }
+
+    #[test]
+    fn test_cell_conversion() {
+        let tile_map = TileMapBuilder::new(BaseBuilder::new())
+            .with_tile_size(Vector2::new(2.0, 0.5))
+            .build_tile_map();
+
+        let cell = Vector2::new(3, -2);
+        let corner = tile_map.cell_to_local(cell);
+        assert_eq!(corner, Vector2::new(-6.0, -1.0));
+        assert_eq!(
+            tile_map.local_to_cell(corner + Vector2::new(-1.0, 0.25)),
+            cell
+        );
+    }
+}
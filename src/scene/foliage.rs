@@ -0,0 +1,875 @@
+//! Contains all structures and methods to create and manage foliage (grass, bushes, flowers, etc.).
+//!
+//! For more info see [`Foliage`].
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector2, Vector3, Vector4},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, ray::Ray, Rect, TriangleDefinition},
+        pool::Handle,
+        rand::{rngs::StdRng, Rng, SeedableRng},
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    resource::texture::{TexturePixelKind, TextureResource},
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        mesh::{
+            buffer::{TriangleBuffer, VertexAttributeUsage, VertexBuffer, VertexReadTrait},
+            surface::{SurfaceData, SurfaceSharedData},
+            vertex::StaticVertex,
+            Mesh,
+        },
+        node::{Node, NodeTrait, UpdateContext},
+        terrain::Terrain,
+    },
+    utils::array_as_u8_slice,
+};
+use fxhash::FxHasher;
+use std::{
+    cell::Cell,
+    hash::Hasher,
+    ops::{Deref, DerefMut},
+};
+
+/// A single instance of a foliage layer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit, Reflect)]
+#[repr(C)] // Instances are uploaded to GPU as is.
+pub struct FoliageInstance {
+    /// Position of the instance in local coordinates of the foliage node.
+    pub position: Vector3<f32>,
+    /// Rotation of the instance around vertical axis (in radians).
+    pub rotation: f32,
+    /// Uniform scale of the instance.
+    pub scale: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct InstancesInfo {
+    data_hash: u64,
+    bounding_box: AxisAlignedBoundingBox,
+}
+
+/// Creates a surface with two crossing vertical quads, which is the most common geometry for grass.
+/// Quads are one meter wide and one meter tall with their base at the origin. Normals of the
+/// surface point up, so grass is lit the same as the ground it grows on.
+pub fn make_grass_surface_data() -> SurfaceData {
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+
+    for side in [Vector3::x(), Vector3::z()] {
+        let first = vertices.len() as u32;
+        for (offset, height, tex_coord) in [
+            (-0.5, 0.0, Vector2::new(0.0, 1.0)),
+            (0.5, 0.0, Vector2::new(1.0, 1.0)),
+            (0.5, 1.0, Vector2::new(1.0, 0.0)),
+            (-0.5, 1.0, Vector2::new(0.0, 0.0)),
+        ] {
+            vertices.push(StaticVertex {
+                position: side.scale(offset) + Vector3::new(0.0, height, 0.0),
+                tex_coord,
+                normal: Vector3::y(),
+                tangent: Vector4::default(),
+            });
+        }
+        triangles.push(TriangleDefinition([first, first + 1, first + 2]));
+        triangles.push(TriangleDefinition([first, first + 2, first + 3]));
+    }
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+        true,
+    )
+}
+
+/// Foliage layer is a set of instances of the same object (a clump of grass, a bush, a flower, etc.)
+/// with the same appearance settings.
+#[derive(Debug, Clone, Visit, Reflect)]
+pub struct FoliageLayer {
+    /// Name of the layer.
+    pub name: String,
+
+    /// Geometry of a single instance. Instances are rendered using the first three vertex
+    /// attributes of the surface: position, texture coordinates and normal.
+    pub surface: SurfaceSharedData,
+
+    /// Diffuse texture of instances. Pixels with alpha less than 0.5 are discarded.
+    pub diffuse_texture: Option<TextureResource>,
+
+    /// Color, that is multiplied with the diffuse texture.
+    pub color: Color,
+
+    /// Amount of instances per square meter, that will be created by [`scatter`] and [`paint`].
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub density: f32,
+
+    /// Optional density map stretched over the area of the foliage. Red channel of the map
+    /// modulates [`Self::density`]. Only uncompressed 8-bit and `R32F` textures are supported,
+    /// other formats are treated as full density.
+    pub density_map: Option<TextureResource>,
+
+    /// Minimal random scale of instances.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub min_scale: f32,
+
+    /// Maximal random scale of instances.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub max_scale: f32,
+
+    /// Distance from the camera, at which instances disappear completely.
+    #[reflect(min_value = 0.0, step = 1.0)]
+    pub draw_distance: f32,
+
+    /// Length of the band before [`Self::draw_distance`], where instances gradually fade out.
+    #[reflect(min_value = 0.0, step = 1.0)]
+    pub fade_distance: f32,
+
+    /// Defines how much the wind affects instances of the layer. Zero should be used for rigid
+    /// objects, like stones.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub wind_influence: f32,
+
+    #[reflect(hidden)]
+    instances: Vec<FoliageInstance>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    instances_info: Cell<Option<InstancesInfo>>,
+}
+
+impl Default for FoliageLayer {
+    fn default() -> Self {
+        Self {
+            name: "Grass".to_string(),
+            surface: SurfaceSharedData::new(make_grass_surface_data()),
+            diffuse_texture: None,
+            color: Color::WHITE,
+            density: 4.0,
+            density_map: None,
+            min_scale: 0.8,
+            max_scale: 1.2,
+            draw_distance: 50.0,
+            fade_distance: 10.0,
+            wind_influence: 1.0,
+            instances: Default::default(),
+            instances_info: Default::default(),
+        }
+    }
+}
+
+// Cached info about instances is derived from the instances, so it is not compared.
+impl PartialEq for FoliageLayer {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.surface == other.surface
+            && self.diffuse_texture == other.diffuse_texture
+            && self.color == other.color
+            && self.density == other.density
+            && self.density_map == other.density_map
+            && self.min_scale == other.min_scale
+            && self.max_scale == other.max_scale
+            && self.draw_distance == other.draw_distance
+            && self.fade_distance == other.fade_distance
+            && self.wind_influence == other.wind_influence
+            && self.instances == other.instances
+    }
+}
+
+impl FoliageLayer {
+    /// Returns a reference to the instances of the layer.
+    pub fn instances(&self) -> &[FoliageInstance] {
+        &self.instances
+    }
+
+    /// Sets new instances of the layer and returns old ones.
+    pub fn set_instances(&mut self, instances: Vec<FoliageInstance>) -> Vec<FoliageInstance> {
+        self.instances_info.set(None);
+        std::mem::replace(&mut self.instances, instances)
+    }
+
+    /// Adds new instances to the layer.
+    pub fn add_instances<I>(&mut self, instances: I)
+    where
+        I: IntoIterator<Item = FoliageInstance>,
+    {
+        self.instances_info.set(None);
+        self.instances.extend(instances)
+    }
+
+    /// Removes every instance that does not satisfy the given predicate.
+    pub fn retain_instances<F>(&mut self, func: F)
+    where
+        F: FnMut(&FoliageInstance) -> bool,
+    {
+        self.instances_info.set(None);
+        self.instances.retain(func)
+    }
+
+    fn instances_info(&self) -> InstancesInfo {
+        if let Some(info) = self.instances_info.get() {
+            return info;
+        }
+
+        let mut hasher = FxHasher::default();
+        hasher.write(array_as_u8_slice(&self.instances));
+
+        let mut bounding_box = AxisAlignedBoundingBox::default();
+        let mut max_scale = 0.0f32;
+        for instance in self.instances.iter() {
+            bounding_box.add_point(instance.position);
+            max_scale = max_scale.max(instance.scale);
+        }
+        if self.instances.is_empty() {
+            bounding_box = AxisAlignedBoundingBox::collapsed();
+        } else {
+            // Instances rotate around vertical axis, so their size is the radius of the surface.
+            bounding_box.inflate(Vector3::repeat(2.0 * max_scale * self.surface_radius()));
+        }
+
+        let info = InstancesInfo {
+            data_hash: hasher.finish(),
+            bounding_box,
+        };
+        self.instances_info.set(Some(info));
+        info
+    }
+
+    /// Returns hash of the instances data. The hash could be used to check if the instances have
+    /// changed.
+    pub fn instances_hash(&self) -> u64 {
+        self.instances_info().data_hash
+    }
+
+    /// Returns the radius of a sphere, centered at the origin of the surface and containing all of
+    /// its vertices.
+    pub fn surface_radius(&self) -> f32 {
+        let data = self.surface.lock();
+        data.vertex_buffer
+            .iter()
+            .filter_map(|v| v.read_3_f32(VertexAttributeUsage::Position).ok())
+            .map(|p| p.norm())
+            .fold(0.0, f32::max)
+    }
+
+    fn density_at(&self, uv: Vector2<f32>) -> f32 {
+        let map = match self.density_map.as_ref() {
+            Some(map) => map,
+            None => return self.density,
+        };
+
+        let map = map.data_ref();
+        let (width, height) = match map.kind().rectangle_size() {
+            Some(size) => (size.x, size.y),
+            None => return self.density,
+        };
+
+        let x = ((uv.x.clamp(0.0, 1.0) * width as f32) as u32).min(width - 1);
+        let y = ((uv.y.clamp(0.0, 1.0) * height as f32) as u32).min(height - 1);
+        let index = (y * width + x) as usize;
+
+        let factor = match map.pixel_kind() {
+            TexturePixelKind::R8 | TexturePixelKind::Luminance8 => map.data()[index] as f32 / 255.0,
+            TexturePixelKind::RG8 | TexturePixelKind::LuminanceAlpha8 => {
+                map.data()[index * 2] as f32 / 255.0
+            }
+            TexturePixelKind::RGB8 => map.data()[index * 3] as f32 / 255.0,
+            TexturePixelKind::BGR8 => map.data()[index * 3 + 2] as f32 / 255.0,
+            TexturePixelKind::RGBA8 => map.data()[index * 4] as f32 / 255.0,
+            TexturePixelKind::BGRA8 => map.data()[index * 4 + 2] as f32 / 255.0,
+            TexturePixelKind::R32F => map
+                .data_of_type::<f32>()
+                .and_then(|data| data.get(index).cloned())
+                .unwrap_or(1.0),
+            _ => 1.0,
+        };
+
+        self.density * factor
+    }
+}
+
+/// Foliage is a node that renders huge amounts of small objects (grass, bushes, flowers, small stones)
+/// scattered over terrains or meshes. Objects are grouped in [layers](FoliageLayer), each layer is
+/// rendered using hardware instancing - one draw call per layer.
+///
+/// # Culling and fading
+///
+/// Every instance is culled individually on GPU - instances outside of the camera frustum or further
+/// than [`FoliageLayer::draw_distance`] are collapsed in the vertex shader. Instances near the draw
+/// distance are smoothly faded out using dithering, so there's no popping.
+///
+/// # Wind
+///
+/// Instances sway in the wind, which is defined by [`Foliage::set_wind_direction`],
+/// [`Foliage::set_wind_strength`] and [`Foliage::set_wind_frequency`]. The base of an instance
+/// stays in place, while its top bends the most.
+///
+/// # Placement
+///
+/// The area of the foliage is a rectangle of [`Foliage::size`] in local XZ plane centered at the
+/// origin of the node. Use [`scatter`] to fill the area with instances using density of a layer,
+/// or [`paint`] to add or erase instances with a brush. Instances are placed on the surface of
+/// given terrains and meshes.
+///
+/// # Limitations
+///
+/// Foliage does not cast shadows and it is rendered in deferred mode only.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector2, pool::Handle},
+///     scene::{
+///         base::BaseBuilder,
+///         foliage::{self, FoliageBuilder, FoliageLayer},
+///         graph::Graph,
+///         node::Node,
+///     },
+/// };
+///
+/// fn create_grass(graph: &mut Graph, terrain: Handle<Node>) -> Handle<Node> {
+///     let grass = FoliageBuilder::new(BaseBuilder::new())
+///         .with_size(Vector2::new(64.0, 64.0))
+///         .with_layers(vec![FoliageLayer::default()])
+///         .build(graph);
+///     foliage::scatter(graph, grass, 0, &[terrain], 123);
+///     grass
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct Foliage {
+    base: Base,
+
+    #[reflect(setter = "set_layers")]
+    layers: InheritableVariable<Vec<FoliageLayer>>,
+
+    #[reflect(
+        min_value = 0.0,
+        description = "Size of the area of the foliage in local XZ plane, in meters.",
+        setter = "set_size"
+    )]
+    size: InheritableVariable<Vector2<f32>>,
+
+    #[reflect(setter = "set_wind_direction")]
+    wind_direction: InheritableVariable<Vector2<f32>>,
+
+    #[reflect(min_value = 0.0, step = 0.05, setter = "set_wind_strength")]
+    wind_strength: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1, setter = "set_wind_frequency")]
+    wind_frequency: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    time: f32,
+}
+
+impl Default for Foliage {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            layers: Default::default(),
+            size: InheritableVariable::new_modified(Vector2::new(16.0, 16.0)),
+            wind_direction: InheritableVariable::new_modified(Vector2::new(1.0, 0.0)),
+            wind_strength: InheritableVariable::new_modified(0.1),
+            wind_frequency: InheritableVariable::new_modified(1.5),
+            time: 0.0,
+        }
+    }
+}
+
+impl Deref for Foliage {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Foliage {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Foliage {
+    fn type_uuid() -> Uuid {
+        uuid!("5c1a2f0e-2d6b-4a8e-9f43-7c0b9d6e1a27")
+    }
+}
+
+impl Foliage {
+    /// Sets new layers of the foliage and returns old ones.
+    pub fn set_layers(&mut self, layers: Vec<FoliageLayer>) -> Vec<FoliageLayer> {
+        self.layers.set_value_and_mark_modified(layers)
+    }
+
+    /// Returns a reference to the layers of the foliage.
+    pub fn layers(&self) -> &[FoliageLayer] {
+        &self.layers
+    }
+
+    /// Returns a mutable reference to the layers of the foliage.
+    pub fn layers_mut(&mut self) -> &mut [FoliageLayer] {
+        self.layers.get_value_mut_and_mark_modified()
+    }
+
+    /// Sets new size of the area of the foliage. Existing instances are not affected.
+    pub fn set_size(&mut self, size: Vector2<f32>) -> Vector2<f32> {
+        self.size.set_value_and_mark_modified(size)
+    }
+
+    /// Returns size of the area of the foliage.
+    pub fn size(&self) -> Vector2<f32> {
+        *self.size
+    }
+
+    /// Sets new direction of the wind in world XZ plane. The direction will be normalized.
+    pub fn set_wind_direction(&mut self, direction: Vector2<f32>) -> Vector2<f32> {
+        self.wind_direction.set_value_and_mark_modified(
+            direction
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector2::x),
+        )
+    }
+
+    /// Returns current direction of the wind.
+    pub fn wind_direction(&self) -> Vector2<f32> {
+        *self.wind_direction
+    }
+
+    /// Sets new strength of the wind. It defines how far (in meters) the top of a one meter tall
+    /// instance could deviate from its rest position.
+    pub fn set_wind_strength(&mut self, strength: f32) -> f32 {
+        self.wind_strength
+            .set_value_and_mark_modified(strength.max(0.0))
+    }
+
+    /// Returns current strength of the wind.
+    pub fn wind_strength(&self) -> f32 {
+        *self.wind_strength
+    }
+
+    /// Sets new frequency of the wind gusts (in radians per second).
+    pub fn set_wind_frequency(&mut self, frequency: f32) -> f32 {
+        self.wind_frequency
+            .set_value_and_mark_modified(frequency.max(0.0))
+    }
+
+    /// Returns current frequency of the wind gusts.
+    pub fn wind_frequency(&self) -> f32 {
+        *self.wind_frequency
+    }
+
+    /// Returns the time, that is used to animate the wind.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn area(&self) -> Rect<f32> {
+        Rect::new(
+            -self.size.x * 0.5,
+            -self.size.y * 0.5,
+            self.size.x,
+            self.size.y,
+        )
+    }
+}
+
+impl NodeTrait for Foliage {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let mut bounding_box = AxisAlignedBoundingBox::default();
+        for layer in self.layers.iter() {
+            if !layer.instances.is_empty() {
+                bounding_box.add_box(layer.instances_info().bounding_box);
+            }
+        }
+        if self.layers.iter().all(|l| l.instances.is_empty()) {
+            self.base.local_bounding_box()
+        } else {
+            bounding_box
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        // Wrap the time to keep enough precision for the wind animation.
+        self.time = (self.time + context.dt) % 3600.0;
+    }
+}
+
+struct SurfaceProbe<'a> {
+    terrains: Vec<&'a Terrain>,
+    meshes: Vec<&'a Mesh>,
+    min_height: f32,
+    max_height: f32,
+}
+
+impl<'a> SurfaceProbe<'a> {
+    fn new(graph: &'a Graph, targets: &[Handle<Node>]) -> Self {
+        let mut probe = Self {
+            terrains: Default::default(),
+            meshes: Default::default(),
+            min_height: f32::MAX,
+            max_height: -f32::MAX,
+        };
+
+        for &target in targets {
+            if let Some(node) = graph.try_get(target) {
+                let bounds = node.world_bounding_box();
+                probe.min_height = probe.min_height.min(bounds.min.y);
+                probe.max_height = probe.max_height.max(bounds.max.y);
+
+                if let Some(terrain) = node.cast::<Terrain>() {
+                    probe.terrains.push(terrain);
+                } else if let Some(mesh) = node.cast::<Mesh>() {
+                    probe.meshes.push(mesh);
+                }
+            }
+        }
+
+        probe
+    }
+
+    // Finds the highest point of the surfaces below the given world-space point.
+    fn probe(&self, point: Vector3<f32>) -> Option<Vector3<f32>> {
+        let mut result: Option<Vector3<f32>> = None;
+        let mut add = |p: Vector3<f32>| {
+            let is_higher = match result {
+                Some(r) => p.y > r.y,
+                None => true,
+            };
+            if is_higher {
+                result = Some(p);
+            }
+        };
+
+        for terrain in self.terrains.iter() {
+            if let Some(local) = terrain.project(point) {
+                if let Some(height) = terrain.height_at(local) {
+                    add(terrain
+                        .global_transform()
+                        .transform_point(&Point3::new(local.x, height, local.y))
+                        .coords);
+                }
+            }
+        }
+
+        let ray = Ray::from_two_points(
+            Vector3::new(point.x, self.max_height + 1.0, point.z),
+            Vector3::new(point.x, self.min_height - 1.0, point.z),
+        );
+        for mesh in self.meshes.iter() {
+            let inv_transform = match mesh.global_transform().try_inverse() {
+                Some(inv_transform) => inv_transform,
+                None => continue,
+            };
+            let local_ray = ray.transform(inv_transform);
+            for surface in mesh.surfaces() {
+                let data = surface.data_ref().lock();
+                for triangle in data.geometry_buffer.iter() {
+                    let vertex = |i: usize| {
+                        data.vertex_buffer
+                            .get(triangle[i] as usize)
+                            .and_then(|v| v.read_3_f32(VertexAttributeUsage::Position).ok())
+                    };
+                    if let (Some(a), Some(b), Some(c)) = (vertex(0), vertex(1), vertex(2)) {
+                        if let Some((toi, _)) = local_ray.triangle_intersection(&[a, b, c]) {
+                            add(ray.get_point(toi));
+                        }
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+fn generate_instances(
+    foliage: &Foliage,
+    layer: &FoliageLayer,
+    region: Rect<f32>,
+    density_scale: f32,
+    probe: &SurfaceProbe,
+    seed: u64,
+) -> Vec<FoliageInstance> {
+    let mut instances = Vec::new();
+
+    let density = layer.density * density_scale;
+    if density <= 0.0 {
+        return instances;
+    }
+
+    let (transform, inv_transform) = match foliage.global_transform().try_inverse() {
+        Some(inv_transform) => (foliage.global_transform(), inv_transform),
+        None => return instances,
+    };
+
+    let area = foliage.area();
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // Jittered grid gives uniform distribution without visible patterns.
+    let spacing = 1.0 / density.sqrt();
+    let columns = (region.w() / spacing).ceil() as usize;
+    let rows = (region.h() / spacing).ceil() as usize;
+    for row in 0..rows {
+        for column in 0..columns {
+            let x = region.x() + (column as f32 + rng.gen::<f32>()) * spacing;
+            let z = region.y() + (row as f32 + rng.gen::<f32>()) * spacing;
+            let rotation = rng.gen_range(0.0..std::f32::consts::TAU);
+            let scale = if layer.max_scale > layer.min_scale {
+                rng.gen_range(layer.min_scale..layer.max_scale)
+            } else {
+                layer.min_scale
+            };
+            let chance = rng.gen::<f32>();
+
+            if !region.contains(Vector2::new(x, z)) || !area.contains(Vector2::new(x, z)) {
+                continue;
+            }
+
+            let uv = Vector2::new(
+                (x - area.x()) / area.w().max(f32::EPSILON),
+                (z - area.y()) / area.h().max(f32::EPSILON),
+            );
+            if chance * layer.density > layer.density_at(uv) {
+                continue;
+            }
+
+            let world = transform.transform_point(&Point3::new(x, 0.0, z)).coords;
+            if let Some(hit) = probe.probe(world) {
+                instances.push(FoliageInstance {
+                    position: inv_transform.transform_point(&Point3::from(hit)).coords,
+                    rotation,
+                    scale,
+                });
+            }
+        }
+    }
+
+    instances
+}
+
+/// Fills the whole area of the given foliage layer with instances, placed on the surface of the given
+/// targets (terrains and meshes). Existing instances of the layer are replaced. Amount of instances
+/// is defined by [`FoliageLayer::density`] and [`FoliageLayer::density_map`]; the same seed
+/// always produces the same result. Returns the amount of created instances.
+///
+/// # Performance
+///
+/// Placement on meshes tests every triangle of the meshes for each instance, so it could be slow
+/// for large meshes. Placement on terrains is much faster.
+pub fn scatter(
+    graph: &mut Graph,
+    foliage: Handle<Node>,
+    layer_index: usize,
+    targets: &[Handle<Node>],
+    seed: u64,
+) -> usize {
+    let instances = match graph
+        .try_get(foliage)
+        .and_then(|n| n.cast::<Foliage>())
+        .and_then(|f| f.layers.get(layer_index).map(|l| (f, l)))
+    {
+        Some((foliage, layer)) => generate_instances(
+            foliage,
+            layer,
+            foliage.area(),
+            1.0,
+            &SurfaceProbe::new(graph, targets),
+            seed,
+        ),
+        None => return 0,
+    };
+
+    let count = instances.len();
+    if let Some(foliage) = graph[foliage].cast_mut::<Foliage>() {
+        foliage.layers_mut()[layer_index].set_instances(instances);
+    }
+    count
+}
+
+/// Defines an operation, that will be performed by a [`FoliageBrush`].
+#[derive(Copy, Clone, Debug, PartialEq, Reflect)]
+pub enum FoliageBrushMode {
+    /// Replaces instances under the brush with new ones.
+    Paint {
+        /// A multiplier for the density of the layer, usually in `[0; 1]` range.
+        density: f32,
+    },
+    /// Removes all instances under the brush.
+    Erase,
+}
+
+/// Brush is used to add or remove foliage instances of a layer in a circular area.
+#[derive(Copy, Clone, Debug, Reflect)]
+pub struct FoliageBrush {
+    /// World-space center of the brush.
+    #[reflect(hidden)]
+    pub center: Vector3<f32>,
+    /// Radius of the brush, in meters.
+    pub radius: f32,
+    /// Index of a layer to paint on.
+    pub layer: usize,
+    /// An operation of the brush.
+    pub mode: FoliageBrushMode,
+}
+
+/// Paints on a layer of the given foliage using the brush. New instances are placed on the surface of
+/// the given targets (terrains and meshes) and only inside of the area of the foliage. Repeated
+/// painting over the same place does not increase the density.
+pub fn paint(
+    graph: &mut Graph,
+    foliage: Handle<Node>,
+    targets: &[Handle<Node>],
+    brush: &FoliageBrush,
+) {
+    let (center, new_instances) = match graph
+        .try_get(foliage)
+        .and_then(|n| n.cast::<Foliage>())
+        .and_then(|f| f.layers.get(brush.layer).map(|l| (f, l)))
+    {
+        Some((foliage, layer)) => {
+            let center = match foliage.global_transform().try_inverse() {
+                Some(inv_transform) => {
+                    inv_transform
+                        .transform_point(&Point3::from(brush.center))
+                        .coords
+                }
+                None => return,
+            };
+
+            let new_instances = match brush.mode {
+                FoliageBrushMode::Paint { density } => {
+                    // Derive the seed from the brush position to get the same result for the same stroke.
+                    let mut hasher = FxHasher::default();
+                    hasher.write(array_as_u8_slice(center.as_slice()));
+
+                    generate_instances(
+                        foliage,
+                        layer,
+                        Rect::new(
+                            center.x - brush.radius,
+                            center.z - brush.radius,
+                            2.0 * brush.radius,
+                            2.0 * brush.radius,
+                        ),
+                        density,
+                        &SurfaceProbe::new(graph, targets),
+                        hasher.finish(),
+                    )
+                    .into_iter()
+                    .filter(|i| (i.position.xz() - center.xz()).norm() < brush.radius)
+                    .collect()
+                }
+                FoliageBrushMode::Erase => Vec::new(),
+            };
+
+            (center, new_instances)
+        }
+        None => return,
+    };
+
+    if let Some(foliage) = graph[foliage].cast_mut::<Foliage>() {
+        let layer = &mut foliage.layers_mut()[brush.layer];
+        layer.retain_instances(|i| (i.position.xz() - center.xz()).norm() >= brush.radius);
+        layer.add_instances(new_instances);
+    }
+}
+
+/// Allows you to create foliage in a declarative manner.
+pub struct FoliageBuilder {
+    base_builder: BaseBuilder,
+    layers: Vec<FoliageLayer>,
+    size: Vector2<f32>,
+    wind_direction: Vector2<f32>,
+    wind_strength: f32,
+    wind_frequency: f32,
+}
+
+impl FoliageBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        let defaults = Foliage::default();
+        Self {
+            base_builder,
+            layers: Default::default(),
+            size: *defaults.size,
+            wind_direction: *defaults.wind_direction,
+            wind_strength: *defaults.wind_strength,
+            wind_frequency: *defaults.wind_frequency,
+        }
+    }
+
+    /// Sets desired layers.
+    pub fn with_layers(mut self, layers: Vec<FoliageLayer>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Sets desired size of the area.
+    pub fn with_size(mut self, size: Vector2<f32>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets desired direction of the wind.
+    pub fn with_wind_direction(mut self, direction: Vector2<f32>) -> Self {
+        self.wind_direction = direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector2::x);
+        self
+    }
+
+    /// Sets desired strength of the wind.
+    pub fn with_wind_strength(mut self, strength: f32) -> Self {
+        self.wind_strength = strength;
+        self
+    }
+
+    /// Sets desired frequency of the wind gusts.
+    pub fn with_wind_frequency(mut self, frequency: f32) -> Self {
+        self.wind_frequency = frequency;
+        self
+    }
+
+    /// Creates new foliage.
+    pub fn build_foliage(self) -> Foliage {
+        Foliage {
+            base: self.base_builder.build_base(),
+            layers: self.layers.into(),
+            size: self.size.into(),
+            wind_direction: self.wind_direction.into(),
+            wind_strength: self.wind_strength.into(),
+            wind_frequency: self.wind_frequency.into(),
+            time: 0.0,
+        }
+    }
+
+    /// Creates new foliage node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_foliage())
+    }
+
+    /// Creates new foliage node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
@@ -34,23 +34,27 @@ use crate::{
         variable::try_inherit_properties,
         visitor::{Visit, VisitResult, Visitor},
     },
+    engine::gc::TimeBudget,
     material::SharedMaterial,
     resource::model::{ModelResource, ModelResourceExtension, NodeMapping},
     scene::{
         self,
-        base::NodeScriptMessage,
+        base::{HierarchyNotifier, NodeScriptMessage},
         camera::Camera,
         dim2::{self},
         graph::{
             event::{GraphEvent, GraphEventBroadcaster},
             map::NodeHandleMap,
             physics::{PhysicsPerformanceStatistics, PhysicsWorld},
+            query::QueryCache,
+            visibility::VisibilityCache,
         },
         mesh::Mesh,
         node::{container::NodeContainer, Node, NodeTrait, SyncContext, UpdateContext},
         pivot::Pivot,
         sound::context::SoundContext,
         transform::TransformBuilder,
+        update_culling::UpdateObserver,
     },
     script::ScriptTrait,
 };
@@ -59,6 +63,7 @@ use fyrox_core::math::aabb::AxisAlignedBoundingBox;
 use rapier3d::geometry::ColliderHandle;
 use std::{
     any::Any,
+    collections::VecDeque,
     fmt::Debug,
     ops::{Index, IndexMut},
     sync::mpsc::{channel, Receiver, Sender},
@@ -68,6 +73,8 @@ use std::{
 pub mod event;
 pub mod map;
 pub mod physics;
+pub mod query;
+pub(crate) mod visibility;
 
 /// Graph performance statistics. Allows you to find out "hot" parts of the scene graph, which
 /// parts takes the most time to update.
@@ -89,6 +96,10 @@ pub struct GraphPerformanceStatistics {
 
     /// A time which was required to render sounds.
     pub sound_update_time: Duration,
+
+    /// A time which was required to sample poses of animation players and animation blending state
+    /// machines.
+    pub animation_sampling_time: Duration,
 }
 
 impl GraphPerformanceStatistics {
@@ -99,6 +110,7 @@ impl GraphPerformanceStatistics {
             + self.physics.total()
             + self.physics2d.total()
             + self.sound_update_time
+            + self.animation_sampling_time
     }
 }
 
@@ -138,11 +150,30 @@ pub struct Graph {
     pub(crate) script_message_sender: Sender<NodeScriptMessage>,
     #[reflect(hidden)]
     pub(crate) script_message_receiver: Receiver<NodeScriptMessage>,
+
+    // Dirty list of the graph, see [`HierarchyCache`].
+    #[reflect(hidden)]
+    hierarchy_sender: Sender<Handle<Node>>,
+    #[reflect(hidden)]
+    hierarchy_receiver: Receiver<Handle<Node>>,
+    #[reflect(hidden)]
+    hierarchy_batch: HierarchyBatch,
+
+    #[reflect(hidden)]
+    pub(crate) query_cache: QueryCache,
+
+    #[reflect(hidden)]
+    pub(crate) visibility_cache: VisibilityCache,
+
+    // Removed nodes, that are waiting to be destroyed. See [`Graph::collect_garbage`].
+    #[reflect(hidden)]
+    garbage: VecDeque<Node>,
 }
 
 impl Default for Graph {
     fn default() -> Self {
         let (tx, rx) = channel();
+        let (hierarchy_sender, hierarchy_receiver) = channel();
 
         Self {
             physics: PhysicsWorld::new(),
@@ -155,10 +186,37 @@ impl Default for Graph {
             event_broadcaster: Default::default(),
             script_message_receiver: rx,
             script_message_sender: tx,
+            hierarchy_sender,
+            hierarchy_receiver,
+            hierarchy_batch: Default::default(),
+            query_cache: Default::default(),
+            visibility_cache: Default::default(),
+            garbage: Default::default(),
         }
     }
 }
 
+// Scratch storage for batched calculation of global transforms of changed nodes, see
+// [`Graph::update_hierarchical_data`].
+#[derive(Debug, Default)]
+struct HierarchyBatch {
+    dirty: Vec<Handle<Node>>,
+    handles: Vec<Handle<Node>>,
+    parents: Vec<Option<usize>>,
+    local_transforms: Vec<Matrix4<f32>>,
+    global_transforms: Vec<Matrix4<f32>>,
+}
+
+impl HierarchyBatch {
+    fn clear(&mut self) {
+        self.dirty.clear();
+        self.handles.clear();
+        self.parents.clear();
+        self.local_transforms.clear();
+        self.global_transforms.clear();
+    }
+}
+
 /// Sub-graph is a piece of graph that was extracted from a graph. It has ownership
 /// over its nodes. It is used to temporarily take ownership of a sub-graph. This could
 /// be used if you making a scene editor with a command stack - once you reverted a command,
@@ -248,6 +306,7 @@ impl Graph {
     #[inline]
     pub fn new() -> Self {
         let (tx, rx) = channel();
+        let (hierarchy_sender, hierarchy_receiver) = channel();
 
         // Create root node.
         let mut root_node = Pivot::default();
@@ -258,6 +317,7 @@ impl Graph {
         let mut pool = Pool::new();
         let root = pool.spawn(Node::new(root_node));
         pool[root].self_handle = root;
+        pool[root].set_hierarchy_notifier(HierarchyNotifier::new(root, hierarchy_sender.clone()));
 
         Self {
             physics: Default::default(),
@@ -270,6 +330,12 @@ impl Graph {
             event_broadcaster: Default::default(),
             script_message_receiver: rx,
             script_message_sender: tx,
+            hierarchy_sender,
+            hierarchy_receiver,
+            hierarchy_batch: Default::default(),
+            query_cache: Default::default(),
+            visibility_cache: Default::default(),
+            garbage: Default::default(),
         }
     }
 
@@ -317,6 +383,7 @@ impl Graph {
         node.children.clear();
         let has_script = node.script.is_some();
         let handle = self.pool.spawn(node);
+        self.query_cache.invalidate();
 
         if self.root.is_none() {
             self.root = handle;
@@ -336,9 +403,11 @@ impl Graph {
         }
 
         let sender = self.script_message_sender.clone();
+        let notifier = HierarchyNotifier::new(handle, self.hierarchy_sender.clone());
         let node = &mut self[handle];
         node.self_handle = handle;
         node.script_message_sender = Some(sender);
+        node.set_hierarchy_notifier(notifier);
 
         handle
     }
@@ -471,10 +540,12 @@ impl Graph {
     }
 
     /// Destroys the node and its children recursively. Scripts of the destroyed nodes will be removed in the next
-    /// update tick.
+    /// update tick. Handles of the nodes become invalid immediately, but the nodes themselves are finalized over
+    /// multiple frames, see [`Self::collect_garbage`] for more info.
     #[inline]
     pub fn remove_node(&mut self, node_handle: Handle<Node>) {
         self.unlink_internal(node_handle);
+        self.query_cache.invalidate();
 
         self.stack.clear();
         self.stack.push(node_handle);
@@ -486,12 +557,38 @@ impl Graph {
             // Remove associated entities.
             let mut node = self.pool.free(handle);
             node.on_removed_from_graph(self);
+            // The script must be destroyed in the next update tick, no matter when the node itself
+            // will be finalized.
+            node.set_script(None);
+            self.garbage.push_back(node);
 
             self.event_broadcaster
                 .broadcast(GraphEvent::Removed(handle));
         }
     }
 
+    /// Finalizes removed nodes and destroys native sound sources of removed sound nodes until the given time
+    /// budget is exhausted, remaining ones will be finalized on next calls. Removal of a large hierarchy could
+    /// take significant amount of time (mostly because of deallocation of meshes, terrains, etc.), so it is
+    /// amortized over multiple frames. The engine calls this method at the end of every update tick with the time
+    /// budget from [`crate::engine::gc::GcSettings`]. If the graph is used without the engine, this method must be
+    /// called explicitly, otherwise removed nodes will never be finalized.
+    pub fn collect_garbage(&mut self, budget: &TimeBudget) {
+        while !budget.is_exhausted() {
+            match self.garbage.pop_front() {
+                Some(node) => drop(node),
+                None => break,
+            }
+        }
+
+        self.sound_context.collect_garbage(budget);
+    }
+
+    /// Returns amount of removed nodes, that are waiting to be finalized.
+    pub fn garbage_count(&self) -> usize {
+        self.garbage.len()
+    }
+
     fn unlink_internal(&mut self, node_handle: Handle<Node>) {
         // Replace parent handle of child
         let parent_handle = std::mem::replace(&mut self.pool[node_handle].parent, Handle::NONE);
@@ -523,7 +620,9 @@ impl Graph {
     #[inline]
     pub fn link_nodes(&mut self, child: Handle<Node>, parent: Handle<Node>) {
         self.unlink_internal(child);
-        self.pool[child].parent = parent;
+        let child_ref = &mut self.pool[child];
+        child_ref.parent = parent;
+        child_ref.hierarchy_cache.mark_dirty();
         self.pool[parent].children.push(child);
     }
 
@@ -1062,6 +1161,10 @@ impl Graph {
         for (handle, node) in self.pool.pair_iter_mut() {
             node.self_handle = handle;
             node.script_message_sender = Some(self.script_message_sender.clone());
+            node.set_hierarchy_notifier(HierarchyNotifier::new(
+                handle,
+                self.hierarchy_sender.clone(),
+            ));
         }
     }
 
@@ -1097,53 +1200,106 @@ impl Graph {
         Log::writeln(MessageKind::Information, "Graph resolved successfully!");
     }
 
-    pub(crate) fn update_hierarchical_data_recursively(
-        nodes: &NodePool,
-        sound_context: &mut SoundContext,
-        physics: &mut PhysicsWorld,
-        physics2d: &mut dim2::physics::PhysicsWorld,
-        node_handle: Handle<Node>,
-    ) {
-        let node = &nodes[node_handle];
-
-        let (parent_global_transform, parent_visibility, parent_enabled) =
-            if let Some(parent) = nodes.try_borrow(node.parent()) {
-                (
-                    parent.global_transform(),
-                    parent.global_visibility(),
-                    parent.is_globally_enabled(),
-                )
-            } else {
-                (Matrix4::identity(), true, true)
+    // Calculates global data of the nodes from the dirty list and their descendants. Global transforms are
+    // calculated in a single pass over flat arrays, where parents always precede their children.
+    fn update_dirty_nodes(&mut self) {
+        let batch = &mut self.hierarchy_batch;
+        batch.clear();
+
+        for handle in self.hierarchy_receiver.try_iter() {
+            // Removed nodes are skipped as well as the nodes that are taken out of the graph, the latter
+            // ones will be put to the dirty list again when they're returned back.
+            if let Some(node) = self.pool.try_borrow_mut(handle) {
+                node.hierarchy_cache.take_queued();
+                node.untracked_mut().restore_transform_notifier();
+                let newly_dirty = node.hierarchy_cache.check(
+                    node.parent(),
+                    node.visibility(),
+                    node.is_enabled(),
+                    node.local_transform().take_changed(),
+                );
+                if newly_dirty {
+                    batch.dirty.push(handle);
+                }
+            }
+        }
+
+        // Only the topmost dirty nodes are needed, all their descendants will be updated anyway.
+        for &handle in batch.dirty.iter() {
+            let parent = self.pool[handle].parent();
+            let mut ancestor = parent;
+            let mut has_dirty_ancestor = false;
+            while let Some(ancestor_ref) = self.pool.try_borrow(ancestor) {
+                if ancestor_ref.hierarchy_cache.is_dirty() {
+                    has_dirty_ancestor = true;
+                    break;
+                }
+                ancestor = ancestor_ref.parent();
+            }
+
+            if !has_dirty_ancestor {
+                batch.handles.push(handle);
+                batch.parents.push(None);
+                batch.global_transforms.push(
+                    self.pool
+                        .try_borrow(parent)
+                        .map_or_else(Matrix4::identity, |p| p.global_transform()),
+                );
+            }
+        }
+
+        let mut i = 0;
+        while i < batch.handles.len() {
+            let node = &self.pool[batch.handles[i]];
+            batch.local_transforms.push(node.local_transform().matrix());
+            for &child in node.children() {
+                if self.pool.is_valid_handle(child) {
+                    batch.handles.push(child);
+                    batch.parents.push(Some(i));
+                    batch.global_transforms.push(Matrix4::identity());
+                }
+            }
+            i += 1;
+        }
+
+        for i in 0..batch.handles.len() {
+            let parent_global_transform = match batch.parents[i] {
+                Some(parent) => batch.global_transforms[parent],
+                // Global transform of a parent of a root of a subtree is stored in place.
+                None => batch.global_transforms[i],
             };
+            batch.global_transforms[i] = parent_global_transform * batch.local_transforms[i];
+        }
 
-        let new_global_transform = parent_global_transform * node.local_transform().matrix();
-
-        // TODO: Detect changes from user code here.
-        node.sync_transform(
-            &new_global_transform,
-            &mut SyncContext {
-                nodes,
-                physics,
-                physics2d,
-                sound_context,
-                switches: None,
-            },
-        );
+        for (&handle, global_transform) in batch.handles.iter().zip(batch.global_transforms.iter())
+        {
+            let node = &self.pool[handle];
+
+            let (parent_visibility, parent_enabled) = self
+                .pool
+                .try_borrow(node.parent())
+                .map_or((true, true), |p| {
+                    (p.global_visibility(), p.is_globally_enabled())
+                });
 
-        node.global_transform.set(new_global_transform);
-        node.global_visibility
-            .set(parent_visibility && node.visibility());
-        node.global_enabled.set(parent_enabled && node.is_enabled());
-
-        for &child in node.children() {
-            Self::update_hierarchical_data_recursively(
-                nodes,
-                sound_context,
-                physics,
-                physics2d,
-                child,
+            node.sync_transform(
+                global_transform,
+                &mut SyncContext {
+                    nodes: &self.pool,
+                    physics: &mut self.physics,
+                    physics2d: &mut self.physics2d,
+                    sound_context: &mut self.sound_context,
+                    switches: None,
+                },
             );
+
+            node.global_transform.set(*global_transform);
+            node.global_visibility
+                .set(parent_visibility && node.visibility());
+            node.global_enabled.set(parent_enabled && node.is_enabled());
+            node.hierarchy_cache
+                .set_inputs(node.parent(), node.visibility(), node.is_enabled());
+            node.render_data_modified.set(true);
         }
     }
 
@@ -1175,32 +1331,24 @@ impl Graph {
 
     /// Calculates local and global transform, global visibility for each node in graph starting from the
     /// specified node and down the tree. The main use case of the method is to update global position (etc.)
-    /// of an hierarchy of the nodes of some new prefab instance.
+    /// of an hierarchy of the nodes of some new prefab instance. Global data of the rest of changed nodes
+    /// is updated as well.
     #[inline]
     pub fn update_hierarchical_data_for_descendants(&mut self, node_handle: Handle<Node>) {
-        Self::update_hierarchical_data_recursively(
-            &self.pool,
-            &mut self.sound_context,
-            &mut self.physics,
-            &mut self.physics2d,
-            node_handle,
-        );
+        if let Some(node) = self.pool.try_borrow(node_handle) {
+            node.hierarchy_cache.invalidate();
+        }
+        self.update_dirty_nodes();
     }
 
     /// Calculates local and global transform, global visibility for each node in graph.
     /// Normally you not need to call this method directly, it will be called automatically
     /// on each frame. However there is one use case - when you setup complex hierarchy and
     /// need to know global transform of nodes before entering update loop, then you can call
-    /// this method.
+    /// this method. Only changed nodes and their descendants are updated.
     #[inline]
     pub fn update_hierarchical_data(&mut self) {
-        Self::update_hierarchical_data_recursively(
-            &self.pool,
-            &mut self.sound_context,
-            &mut self.physics,
-            &mut self.physics2d,
-            self.root,
-        );
+        self.update_dirty_nodes();
     }
 
     /// Checks whether given node handle is valid or not.
@@ -1229,6 +1377,7 @@ impl Graph {
         frame_size: Vector2<f32>,
         dt: f32,
         delete_dead_nodes: bool,
+        observers: &[UpdateObserver],
     ) {
         if let Some((ticket, mut node)) = self.pool.try_take_reserve(handle) {
             node.transform_modified.set(false);
@@ -1236,17 +1385,25 @@ impl Graph {
             let mut is_alive = node.is_alive();
 
             if node.is_globally_enabled() {
-                node.update(&mut UpdateContext {
+                // Update is tracked separately, otherwise every node would be copied to the render
+                // snapshot on every frame.
+                let node_ref = node.untracked_mut();
+                node_ref.update(&mut UpdateContext {
                     frame_size,
                     dt,
                     nodes: &mut self.pool,
                     physics: &mut self.physics,
                     physics2d: &mut self.physics2d,
                     sound_context: &mut self.sound_context,
+                    observers,
                 });
 
+                if node_ref.updates_render_data() {
+                    node_ref.render_data_modified.set(true);
+                }
+
                 if delete_dead_nodes {
-                    if let Some(lifetime) = node.lifetime.get_value_mut_silent().as_mut() {
+                    if let Some(lifetime) = node_ref.lifetime.get_value_mut_silent().as_mut() {
                         *lifetime -= dt;
                         if *lifetime <= 0.0 {
                             is_alive = false;
@@ -1255,7 +1412,8 @@ impl Graph {
                 }
             }
 
-            self.pool.put_back(ticket, node);
+            let handle = self.pool.put_back(ticket, node);
+            self.pool[handle].hierarchy_cache.requeue();
 
             if !is_alive && delete_dead_nodes {
                 self.remove_node(handle);
@@ -1300,9 +1458,28 @@ impl Graph {
         self.performance_statistics.sound_update_time =
             self.sound_context.state().full_render_duration();
 
+        let observers = UpdateObserver::collect(&self.pool);
+
+        // Animations are embarrassingly parallel, so their poses are sampled up front and then applied
+        // in the sequential update of respective nodes.
+        let last_time = instant::Instant::now();
+        scene::animation::sample_poses_in_parallel(
+            &mut self.pool,
+            &observers,
+            dt,
+            switches.node_overrides.as_ref(),
+        );
+        self.performance_statistics.animation_sampling_time = instant::Instant::now() - last_time;
+
         if let Some(overrides) = switches.node_overrides.as_ref() {
             for handle in overrides {
-                self.update_node(*handle, frame_size, dt, switches.delete_dead_nodes);
+                self.update_node(
+                    *handle,
+                    frame_size,
+                    dt,
+                    switches.delete_dead_nodes,
+                    &observers,
+                );
             }
         } else {
             for i in 0..self.pool.get_capacity() {
@@ -1311,6 +1488,7 @@ impl Graph {
                     frame_size,
                     dt,
                     switches.delete_dead_nodes,
+                    &observers,
                 );
             }
         }
@@ -1412,7 +1590,10 @@ impl Graph {
     }
 
     pub(crate) fn put_back_internal(&mut self, ticket: Ticket<Node>, node: Node) -> Handle<Node> {
-        self.pool.put_back(ticket, node)
+        self.query_cache.invalidate();
+        let handle = self.pool.put_back(ticket, node);
+        self.pool[handle].hierarchy_cache.requeue();
+        handle
     }
 
     /// Makes node handle vacant again.
@@ -1451,8 +1632,9 @@ impl Graph {
     /// parent.
     #[inline]
     pub fn put_sub_graph_back(&mut self, sub_graph: SubGraph) -> Handle<Node> {
+        self.query_cache.invalidate();
         for (ticket, node) in sub_graph.descendants {
-            self.pool.put_back(ticket, node);
+            self.put_back_internal(ticket, node);
         }
 
         let (ticket, node) = sub_graph.root;
@@ -1524,6 +1706,74 @@ impl Graph {
         (copy, old_new_map)
     }
 
+    /// Creates deep copy of the entire graph. Unlike [`Self::clone`], every node of the copy has exactly
+    /// the same handle as its original, so handles to the nodes of the original graph (for example, the
+    /// ones stored in instances of a prefab) remain valid for the copy.
+    pub fn clone_preserving_handles(&self) -> Self {
+        let mut copy = Self {
+            sound_context: self.sound_context.deep_clone(),
+            ..Default::default()
+        };
+
+        for (handle, node) in self.pool.pair_iter() {
+            let mut node_copy = node.clone_box();
+            node_copy.self_handle = handle;
+            node_copy.script_message_sender = Some(copy.script_message_sender.clone());
+            node_copy.set_hierarchy_notifier(HierarchyNotifier::new(
+                handle,
+                copy.hierarchy_sender.clone(),
+            ));
+            // The pool of the copy is empty, so the spawn can't fail.
+            let _ = copy.pool.spawn_at_handle(handle, node_copy);
+        }
+        copy.root = self.root;
+
+        copy.update_hierarchical_data();
+
+        copy
+    }
+
+    /// Turns the given graph into an immutable copy of this graph, that is used only for rendering
+    /// (see [`crate::renderer::snapshot`]). Every node of the copy has exactly the same handle and
+    /// global data (transform, visibility, etc.) as its original, but scripts are not copied and the
+    /// copy has no sound. The copy is incremental: only the nodes, that were added, replaced or changed
+    /// since the last call, are copied (see [`NodeTrait::updates_render_data`]), so the same copy must be
+    /// passed on every call. This method needs mutable access only to move scripts out of the nodes
+    /// while they're copied. Returns the amount of copied nodes.
+    pub(crate) fn copy_for_rendering(&mut self, copy: &mut Graph) -> usize {
+        let mut copied = 0;
+
+        for index in 0..self.pool.get_capacity().max(copy.pool.get_capacity()) {
+            let handle = self.pool.handle_from_index(index);
+            let copy_handle = copy.pool.handle_from_index(index);
+            let is_same_node = handle == copy_handle && copy.pool.is_valid_handle(copy_handle);
+
+            match self.pool.at_mut(index) {
+                Some(node) => {
+                    if !node.render_data_modified.replace(false) && is_same_node {
+                        continue;
+                    }
+
+                    let node_copy = node.clone_for_rendering();
+                    if is_same_node {
+                        copy.pool.replace(handle, node_copy);
+                    } else {
+                        copy.pool.try_free(copy_handle);
+                        // The record is free now, so the spawn can't fail.
+                        let _ = copy.pool.spawn_at_handle(handle, node_copy);
+                    }
+                    copied += 1;
+                }
+                None => {
+                    copy.pool.try_free(copy_handle);
+                }
+            }
+        }
+        copy.root = self.root;
+
+        copied
+    }
+
     /// Returns local transformation matrix of a node without scale.
     #[inline]
     pub fn local_transform_no_scale(&self, node: Handle<Node>) -> Matrix4<f32> {
@@ -1771,6 +2021,10 @@ impl Visit for Graph {
             panic!("Graph pool must be empty on load!")
         }
 
+        if visitor.is_reading() {
+            self.query_cache.invalidate();
+        }
+
         let mut region = visitor.enter_region(name)?;
 
         self.root.visit("Root", &mut region)?;
@@ -1788,9 +2042,19 @@ mod test {
     use crate::scene::base::BaseBuilder;
     use crate::scene::pivot::PivotBuilder;
     use crate::{
-        core::pool::Handle,
-        scene::{graph::Graph, node::Node, pivot::Pivot},
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            pool::Handle,
+            reflect::prelude::*,
+            uuid::Uuid,
+            visitor::prelude::*,
+        },
+        engine::gc::TimeBudget,
+        impl_component_provider,
+        scene::{graph::Graph, node::Node, pivot::Pivot, transform::TransformBuilder},
+        script::{Script, ScriptTrait},
     };
+    use std::time::Duration;
 
     #[test]
     fn graph_init_test() {
@@ -1918,4 +2182,207 @@ mod test {
 
         assert!(graph[b].children.is_empty());
     }
+
+    #[test]
+    fn test_hierarchical_data_dirty_propagation() {
+        let mut graph = Graph::new();
+
+        let b;
+        let a = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, 0.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[{
+                    b = PivotBuilder::new(
+                        BaseBuilder::new().with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                                .build(),
+                        ),
+                    )
+                    .build(&mut graph);
+                    b
+                }]),
+        )
+        .build(&mut graph);
+        let c = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 1.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+
+        graph.update_hierarchical_data();
+        assert_eq!(graph[b].global_position(), Vector3::new(1.0, 1.0, 0.0));
+
+        // Unchanged nodes must not be recalculated.
+        graph[b].global_transform.set(Matrix4::identity());
+        graph.update_hierarchical_data();
+        assert_eq!(graph[b].global_position(), Vector3::default());
+
+        // Changes of the parent must be propagated to its children.
+        graph[a]
+            .local_transform_mut()
+            .set_position(Vector3::new(2.0, 0.0, 0.0));
+        graph.update_hierarchical_data();
+        assert_eq!(graph[a].global_position(), Vector3::new(2.0, 0.0, 0.0));
+        assert_eq!(graph[b].global_position(), Vector3::new(2.0, 1.0, 0.0));
+
+        // Changes of the hierarchy must be detected too.
+        graph.link_nodes(b, c);
+        graph.update_hierarchical_data();
+        assert_eq!(graph[b].global_position(), Vector3::new(0.0, 1.0, 1.0));
+
+        // As well as local transform changes, that were made without marking the node as modified.
+        let mut transform = graph[b].local_transform().clone();
+        transform.set_position(Vector3::new(0.0, 2.0, 0.0));
+        graph[b].set_local_transform(transform);
+        graph.update_hierarchical_data();
+        assert_eq!(graph[b].global_position(), Vector3::new(0.0, 2.0, 1.0));
+
+        // Subtrees without changes must be skipped, even if other parts of the graph were changed.
+        graph[b].global_transform.set(Matrix4::identity());
+        graph[a]
+            .local_transform_mut()
+            .set_position(Vector3::new(3.0, 0.0, 0.0));
+        graph.update_hierarchical_data();
+        assert_eq!(graph[a].global_position(), Vector3::new(3.0, 0.0, 0.0));
+        assert_eq!(graph[b].global_position(), Vector3::default());
+
+        // Visibility changes must be propagated to children as well.
+        graph[c].set_visibility(false);
+        graph.update_hierarchical_data();
+        assert!(!graph[b].global_visibility());
+
+        // As well as changes of the enabled flag.
+        graph[c].set_enabled(false);
+        graph.update_hierarchical_data();
+        assert!(!graph[b].is_globally_enabled());
+
+        // Changes made through reflection must be detected too.
+        let mut is_ok = false;
+        graph[c].as_reflect_mut(&mut |node| {
+            node.set_field_by_path(
+                "base.local_transform.local_position",
+                Box::new(Vector3::new(0.0f32, 0.0, 2.0)),
+                &mut |result| is_ok = result.is_ok(),
+            )
+        });
+        assert!(is_ok);
+        graph.update_hierarchical_data();
+        assert_eq!(graph[b].global_position(), Vector3::new(0.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn test_amortized_node_removal() {
+        let mut graph = Graph::new();
+        let a = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let b = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let c = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph.link_nodes(b, a);
+        graph.link_nodes(c, b);
+
+        // Handles become invalid right away.
+        graph.remove_node(a);
+        assert!(!graph.is_valid_handle(a));
+        assert!(!graph.is_valid_handle(c));
+        assert_eq!(graph.garbage_count(), 3);
+
+        // Nothing is finalized if there's no time left.
+        graph.collect_garbage(&TimeBudget::new(Duration::ZERO));
+        assert_eq!(graph.garbage_count(), 3);
+
+        graph.collect_garbage(&TimeBudget::unlimited());
+        assert_eq!(graph.garbage_count(), 0);
+
+        // Cleanup of a standalone graph is up to its owner, the update does not spend any time on it.
+        let d = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph.remove_node(d);
+        assert_eq!(graph.garbage_count(), 1);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        assert_eq!(graph.garbage_count(), 1);
+    }
+
+    #[derive(Reflect, Visit, Debug, Default)]
+    struct NonClonableScript;
+
+    impl Clone for NonClonableScript {
+        fn clone(&self) -> Self {
+            panic!("Scripts must not be copied to the render snapshot!")
+        }
+    }
+
+    impl_component_provider!(NonClonableScript);
+
+    impl ScriptTrait for NonClonableScript {
+        fn id(&self) -> Uuid {
+            Default::default()
+        }
+    }
+
+    #[test]
+    fn test_copy_for_rendering() {
+        let mut graph = Graph::new();
+        let a = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let c;
+        let b = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                        .build(),
+                )
+                .with_children(&[{
+                    c = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+                    c
+                }]),
+        )
+        .build(&mut graph);
+        let d = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph[d].script = Some(Script::new(NonClonableScript));
+        graph.remove_node(a);
+        graph.update_hierarchical_data();
+
+        // The first copy is full, but without scripts.
+        let mut copy = Graph::new();
+        assert_eq!(graph.copy_for_rendering(&mut copy), 4);
+        assert_eq!(copy.root, graph.root);
+        assert_eq!(copy.node_count(), graph.node_count());
+        assert!(!copy.is_valid_handle(a));
+        assert_eq!(copy[b].global_position(), Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(copy[c].global_position(), Vector3::new(1.0, 2.0, 3.0));
+        assert!(copy[d].script.is_none());
+        assert!(graph[d].script.is_some());
+
+        // Nothing is copied, if nothing was changed.
+        assert_eq!(graph.copy_for_rendering(&mut copy), 0);
+
+        // Nodes with changed global data are copied, as well as the nodes that were borrowed mutably.
+        graph[b]
+            .local_transform_mut()
+            .set_position(Vector3::new(3.0, 2.0, 1.0));
+        graph.update_hierarchical_data();
+        assert_eq!(graph.copy_for_rendering(&mut copy), 2);
+        assert_eq!(copy[b].global_position(), Vector3::new(3.0, 2.0, 1.0));
+        assert_eq!(copy[c].global_position(), Vector3::new(3.0, 2.0, 1.0));
+
+        graph[d].set_name("D");
+        assert_eq!(graph.copy_for_rendering(&mut copy), 1);
+        assert_eq!(copy[d].name(), "D");
+
+        // Removed nodes are removed from the copy, new nodes (even at the same index) are copied. The
+        // parent is copied too, because its children were changed.
+        graph.remove_node(d);
+        assert_eq!(graph.copy_for_rendering(&mut copy), 1);
+        assert!(!copy.is_valid_handle(d));
+        let e = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        assert_eq!(graph.copy_for_rendering(&mut copy), 2);
+        assert!(copy.is_valid_handle(e));
+        assert_eq!(copy.node_count(), graph.node_count());
+    }
 }
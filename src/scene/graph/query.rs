@@ -0,0 +1,352 @@
+//! Typed queries over scripts and components of scene nodes.
+//!
+//! Queries allow gameplay systems to iterate over the objects they're interested in (for example,
+//! every node with a `Health` script) without scanning every node of the graph each frame:
+//!
+//! ```rust
+//! # use fyrox::{
+//! #     core::{reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
+//! #     impl_component_provider,
+//! #     scene::Scene,
+//! #     script::ScriptTrait,
+//! # };
+//! #[derive(Visit, Reflect, Default, Debug, Clone)]
+//! struct Health {
+//!     amount: f32,
+//! }
+//! # impl_component_provider!(Health);
+//! # impl ScriptTrait for Health {
+//! #     fn id(&self) -> Uuid {
+//! #         Uuid::default()
+//! #     }
+//! # }
+//!
+//! fn kill_everyone_outside_of_the_world(scene: &mut Scene) {
+//!     scene
+//!         .graph
+//!         .scripts_of_type_mut::<Health>()
+//!         .filter(|node| node.global_position().y < -100.0)
+//!         .for_each(|_, health| health.amount = 0.0);
+//! }
+//!
+//! fn count_alive(scene: &Scene) -> usize {
+//!     scene
+//!         .scripts_of_type::<Health>()
+//!         .iter()
+//!         .filter(|(_, health)| health.amount > 0.0)
+//!         .count()
+//! }
+//! ```
+//!
+//! Every graph keeps a cache of node handles for each queried type. The cache is rebuilt only when
+//! the set of nodes in the graph changes (a node was added or removed) or when a script was assigned
+//! to an existing node. Scripts that were assigned to existing nodes appear in query results once
+//! they're initialized by the engine. Nodes whose scripts were removed or replaced are filtered out
+//! immediately.
+
+use crate::{
+    core::pool::Handle,
+    scene::{graph::Graph, node::Node, Scene},
+    script::ScriptTrait,
+};
+use fxhash::FxHashMap;
+use std::{any::TypeId, cell::RefCell, sync::Arc};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum QueryKey {
+    Script(TypeId),
+    Component(TypeId),
+}
+
+#[derive(Debug)]
+struct CachedQuery {
+    version: u64,
+    handles: Arc<Vec<Handle<Node>>>,
+}
+
+/// A cache of node handles for typed queries, see module docs for more info.
+#[derive(Debug, Default)]
+pub(crate) struct QueryCache {
+    version: u64,
+    queries: RefCell<FxHashMap<QueryKey, CachedQuery>>,
+}
+
+impl QueryCache {
+    /// Invalidates every cached query. Must be called when the set of nodes of the graph changes.
+    pub(crate) fn invalidate(&mut self) {
+        self.version = self.version.wrapping_add(1);
+    }
+
+    fn handles(
+        &self,
+        key: QueryKey,
+        graph: &Graph,
+        matches: &dyn Fn(&Node) -> bool,
+    ) -> Arc<Vec<Handle<Node>>> {
+        let mut queries = self.queries.borrow_mut();
+        if let Some(query) = queries.get(&key) {
+            if query.version == self.version {
+                return query.handles.clone();
+            }
+        }
+
+        let handles = Arc::new(
+            graph
+                .pool
+                .pair_iter()
+                .filter_map(|(handle, node)| if matches(node) { Some(handle) } else { None })
+                .collect::<Vec<_>>(),
+        );
+        queries.insert(
+            key,
+            CachedQuery {
+                version: self.version,
+                handles: handles.clone(),
+            },
+        );
+        handles
+    }
+}
+
+type Filter<'a> = Box<dyn Fn(&Node) -> bool + 'a>;
+
+fn passes(filters: &[Filter], node: &Node) -> bool {
+    filters.iter().all(|filter| filter(node))
+}
+
+/// A query over the nodes with a script or a component of type `T`. See module docs for more info.
+pub struct NodeQuery<'a, T> {
+    graph: &'a Graph,
+    handles: Arc<Vec<Handle<Node>>>,
+    access: fn(&Node) -> Option<&T>,
+    filters: Vec<Filter<'a>>,
+}
+
+impl<'a, T: 'a> NodeQuery<'a, T> {
+    /// Adds a node predicate to the query, only the nodes that satisfy every predicate are returned.
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&Node) -> bool + 'a,
+    {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Returns an iterator over handles of the matching nodes and respective scripts (or components).
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<Node>, &'a T)> + '_ {
+        self.handles.iter().filter_map(move |&handle| {
+            let node = self.graph.try_get(handle)?;
+            if passes(&self.filters, node) {
+                (self.access)(node).map(|item| (handle, item))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns handles of the matching nodes.
+    pub fn handles(&self) -> Vec<Handle<Node>> {
+        self.iter().map(|(handle, _)| handle).collect()
+    }
+
+    /// Returns the first matching node and its script (or component).
+    pub fn first(&self) -> Option<(Handle<Node>, &'a T)> {
+        self.iter().next()
+    }
+
+    /// Returns the amount of matching nodes.
+    pub fn count(&self) -> usize {
+        self.iter().count()
+    }
+}
+
+/// A query over the nodes with a script or a component of type `T` that allows to modify the
+/// scripts (or components). See module docs for more info.
+pub struct NodeQueryMut<'a, T> {
+    graph: &'a mut Graph,
+    handles: Arc<Vec<Handle<Node>>>,
+    access: fn(&mut Node) -> Option<&mut T>,
+    filters: Vec<Filter<'a>>,
+}
+
+impl<'a, T> NodeQueryMut<'a, T> {
+    /// Adds a node predicate to the query, only the nodes that satisfy every predicate are visited.
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&Node) -> bool + 'a,
+    {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Calls the given closure for every matching node with its handle and its script (or
+    /// component).
+    pub fn for_each<F>(self, mut func: F)
+    where
+        F: FnMut(Handle<Node>, &mut T),
+    {
+        for &handle in self.handles.iter() {
+            if let Some(node) = self.graph.try_get_mut(handle) {
+                if passes(&self.filters, node) {
+                    if let Some(item) = (self.access)(node) {
+                        func(handle, item);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn script_ref<T: ScriptTrait>(node: &Node) -> Option<&T> {
+    node.try_get_script::<T>()
+}
+
+fn script_mut<T: ScriptTrait>(node: &mut Node) -> Option<&mut T> {
+    node.try_get_script_mut::<T>()
+}
+
+fn component_ref<T: 'static>(node: &Node) -> Option<&T> {
+    node.query_component_ref::<T>()
+}
+
+fn component_mut<T: 'static>(node: &mut Node) -> Option<&mut T> {
+    node.query_component_mut::<T>()
+}
+
+impl Graph {
+    /// Creates a query over every node that has a script of type `T`. See
+    /// [module docs](crate::scene::graph::query) for more info.
+    pub fn scripts_of_type<T: ScriptTrait>(&self) -> NodeQuery<'_, T> {
+        NodeQuery {
+            handles: self
+                .query_cache
+                .handles(QueryKey::Script(TypeId::of::<T>()), self, &|node| {
+                    node.has_script::<T>()
+                }),
+            graph: self,
+            access: script_ref::<T>,
+            filters: Default::default(),
+        }
+    }
+
+    /// Creates a query over every node that has a script of type `T`, the query allows to modify
+    /// the scripts. See [module docs](crate::scene::graph::query) for more info.
+    pub fn scripts_of_type_mut<T: ScriptTrait>(&mut self) -> NodeQueryMut<'_, T> {
+        NodeQueryMut {
+            handles: self
+                .query_cache
+                .handles(QueryKey::Script(TypeId::of::<T>()), self, &|node| {
+                    node.has_script::<T>()
+                }),
+            graph: self,
+            access: script_mut::<T>,
+            filters: Default::default(),
+        }
+    }
+
+    /// Creates a query over every node that has a component of type `T` (see
+    /// [`Node::query_component_ref`]), for example every [`crate::scene::mesh::Mesh`].
+    pub fn components_of_type<T: 'static>(&self) -> NodeQuery<'_, T> {
+        NodeQuery {
+            handles: self.query_cache.handles(
+                QueryKey::Component(TypeId::of::<T>()),
+                self,
+                &|node| node.query_component_ref::<T>().is_some(),
+            ),
+            graph: self,
+            access: component_ref::<T>,
+            filters: Default::default(),
+        }
+    }
+
+    /// Creates a query over every node that has a component of type `T` (see
+    /// [`Node::query_component_mut`]), the query allows to modify the components.
+    pub fn components_of_type_mut<T: 'static>(&mut self) -> NodeQueryMut<'_, T> {
+        NodeQueryMut {
+            handles: self.query_cache.handles(
+                QueryKey::Component(TypeId::of::<T>()),
+                self,
+                &|node| node.query_component_ref::<T>().is_some(),
+            ),
+            graph: self,
+            access: component_mut::<T>,
+            filters: Default::default(),
+        }
+    }
+}
+
+impl Scene {
+    /// Creates a query over every node of the scene graph that has a script of type `T`. It is a
+    /// shortcut for [`Graph::scripts_of_type`].
+    pub fn scripts_of_type<T: ScriptTrait>(&self) -> NodeQuery<'_, T> {
+        self.graph.scripts_of_type()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{reflect::prelude::*, uuid::Uuid, visitor::prelude::*},
+        impl_component_provider,
+        scene::{base::BaseBuilder, graph::Graph, light::point::PointLight, pivot::PivotBuilder},
+        script::{Script, ScriptTrait},
+    };
+
+    #[derive(Visit, Reflect, Default, Debug, Clone)]
+    struct Health {
+        amount: f32,
+    }
+
+    impl_component_provider!(Health);
+
+    impl ScriptTrait for Health {
+        fn id(&self) -> Uuid {
+            Uuid::default()
+        }
+    }
+
+    #[test]
+    fn test_script_query() {
+        let mut graph = Graph::new();
+        let a = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("A")
+                .with_script(Script::new(Health { amount: 1.0 })),
+        )
+        .build(&mut graph);
+        PivotBuilder::new(BaseBuilder::new().with_name("B")).build(&mut graph);
+
+        assert_eq!(graph.scripts_of_type::<Health>().handles(), vec![a]);
+        assert_eq!(graph.components_of_type::<PointLight>().count(), 0);
+
+        // Newly added nodes must invalidate the cache.
+        let c = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("C")
+                .with_script(Script::new(Health { amount: 2.0 })),
+        )
+        .build(&mut graph);
+        assert_eq!(graph.scripts_of_type::<Health>().count(), 2);
+
+        // Filters. The query borrows the graph, so it must be dropped before the graph is modified.
+        {
+            let query = graph
+                .scripts_of_type::<Health>()
+                .filter(|node| node.name() == "C");
+            assert_eq!(query.first().map(|(h, s)| (h, s.amount)), Some((c, 2.0)));
+        }
+
+        graph
+            .scripts_of_type_mut::<Health>()
+            .filter(|node| node.name() == "A")
+            .for_each(|_, health| health.amount = 0.0);
+        assert_eq!(
+            graph[a].try_get_script::<Health>().map(|h| h.amount),
+            Some(0.0)
+        );
+
+        // Removed nodes must disappear from the results.
+        graph.remove_node(a);
+        assert_eq!(graph.scripts_of_type::<Health>().handles(), vec![c]);
+    }
+}
@@ -0,0 +1,214 @@
+//! Frustum culling of scene nodes with per-node visibility caching.
+//!
+//! Every graph keeps visibility of its nodes for a limited amount of observers (cameras, light
+//! sources, etc.). When render data is collected for an observer, the world-space bounding boxes of
+//! the nodes are compared with the cached ones and only the nodes that were changed since the last
+//! time are tested against the observer's frustum. The tests are split in chunks and performed in
+//! parallel. Observers are identified by their frustums, so static observers (like shadow-casting
+//! light sources that don't move) will do almost no work at all, even if the same frustum is used by
+//! multiple render passes.
+
+use crate::{
+    core::{
+        arena::FrameArena,
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum},
+    },
+    scene::graph::Graph,
+};
+use rayon::prelude::*;
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+};
+
+/// Maximum amount of observers that could be cached. Least recently used observer is discarded when
+/// the limit is reached.
+const MAX_OBSERVERS: usize = 32;
+
+/// Amount of nodes tested by a single task.
+const CHUNK_SIZE: usize = 256;
+
+#[derive(Copy, Clone)]
+struct CachedVisibility {
+    world_aabb: AxisAlignedBoundingBox,
+    visible: bool,
+    valid: bool,
+}
+
+impl Default for CachedVisibility {
+    fn default() -> Self {
+        Self {
+            world_aabb: Default::default(),
+            visible: true,
+            valid: false,
+        }
+    }
+}
+
+struct ObserverVisibility {
+    frustum: Frustum,
+    last_used: u64,
+    entries: Vec<CachedVisibility>,
+}
+
+fn is_same_aabb(a: &AxisAlignedBoundingBox, b: &AxisAlignedBoundingBox) -> bool {
+    a.min == b.min && a.max == b.max
+}
+
+/// Updates the cached visibility for the given set of bounding boxes (`None` means that the node must
+/// be always visible). Returns amount of performed frustum tests.
+fn update_visibility(
+    entries: &mut [CachedVisibility],
+    world_aabbs: &[Option<AxisAlignedBoundingBox>],
+    frustum: &Frustum,
+) -> usize {
+    entries
+        .par_chunks_mut(CHUNK_SIZE)
+        .zip(world_aabbs.par_chunks(CHUNK_SIZE))
+        .map(|(entries, world_aabbs)| {
+            let mut tests = 0;
+            for (entry, world_aabb) in entries.iter_mut().zip(world_aabbs) {
+                match world_aabb {
+                    Some(world_aabb) => {
+                        if !entry.valid || !is_same_aabb(&entry.world_aabb, world_aabb) {
+                            entry.visible = frustum.is_intersects_aabb(world_aabb);
+                            entry.world_aabb = *world_aabb;
+                            entry.valid = true;
+                            tests += 1;
+                        }
+                    }
+                    None => {
+                        entry.visible = true;
+                        entry.valid = false;
+                    }
+                }
+            }
+            tests
+        })
+        .sum()
+}
+
+/// Visibility cache of a graph, see module docs for more info.
+#[derive(Default)]
+pub(crate) struct VisibilityCache {
+    stamp: Cell<u64>,
+    observers: RefCell<Vec<ObserverVisibility>>,
+}
+
+impl Debug for VisibilityCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "VisibilityCache")
+    }
+}
+
+impl VisibilityCache {
+    /// Calculates visibility of every node of the graph from the point of view of an observer with
+    /// the given frustum. Returns a set of flags, where each flag corresponds to a node at the same
+    /// index in the graph. Nodes with disabled frustum culling are always visible. The flags and
+    /// temporary data are allocated in the given frame arena.
+    pub(crate) fn cull<'a>(
+        &self,
+        graph: &Graph,
+        frustum: &Frustum,
+        frame_arena: &'a FrameArena,
+    ) -> &'a [bool] {
+        let capacity = graph.capacity() as usize;
+
+        // Bounding boxes could be calculated lazily by nodes, so this must be done on current thread.
+        let world_aabbs = frame_arena.alloc_slice_fill_copy(capacity, None);
+        for (handle, node) in graph.pair_iter() {
+            if node.frustum_culling() {
+                world_aabbs[handle.index() as usize] = Some(node.world_bounding_box());
+            }
+        }
+
+        let stamp = self.stamp.get().wrapping_add(1);
+        self.stamp.set(stamp);
+
+        let mut observers = self.observers.borrow_mut();
+
+        let index = match observers
+            .iter()
+            .position(|observer| observer.frustum == *frustum)
+        {
+            Some(index) => index,
+            None => {
+                let observer = ObserverVisibility {
+                    frustum: *frustum,
+                    last_used: stamp,
+                    entries: Vec::new(),
+                };
+
+                if observers.len() < MAX_OBSERVERS {
+                    observers.push(observer);
+                    observers.len() - 1
+                } else {
+                    let (index, _) = observers
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, observer)| observer.last_used)
+                        .unwrap();
+                    observers[index] = observer;
+                    index
+                }
+            }
+        };
+
+        let observer = &mut observers[index];
+        observer.last_used = stamp;
+        observer.entries.resize(capacity, Default::default());
+
+        update_visibility(&mut observer.entries, world_aabbs, frustum);
+
+        frame_arena.alloc_slice_fill_with(capacity, |i| observer.entries[i].visible)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector3},
+            math::{aabb::AxisAlignedBoundingBox, frustum::Frustum},
+        },
+        scene::graph::visibility::{update_visibility, CachedVisibility},
+    };
+
+    #[test]
+    fn test_visibility_caching() {
+        let frustum = Frustum::from_view_projection_matrix(Matrix4::new_orthographic(
+            -1.0, 1.0, -1.0, 1.0, -1.0, 1.0,
+        ))
+        .unwrap();
+
+        let visible = AxisAlignedBoundingBox::from_radius(0.5);
+        let invisible = AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(10.0, 10.0, 0.0),
+            Vector3::new(11.0, 11.0, 0.0),
+        );
+
+        let mut world_aabbs = vec![Some(visible), Some(invisible), None];
+        world_aabbs.extend((0..1000).map(|_| Some(invisible)));
+
+        let mut entries = vec![CachedVisibility::default(); world_aabbs.len()];
+
+        assert_eq!(
+            update_visibility(&mut entries, &world_aabbs, &frustum),
+            1002
+        );
+        assert!(entries[0].visible);
+        assert!(!entries[1].visible);
+        assert!(entries[2].visible);
+        assert!(entries[3..].iter().all(|entry| !entry.visible));
+
+        // Nothing has changed, everything must be taken from the cache.
+        assert_eq!(update_visibility(&mut entries, &world_aabbs, &frustum), 0);
+
+        // Only changed boxes must be tested again.
+        world_aabbs[0] = Some(invisible);
+        world_aabbs[1] = Some(visible);
+        assert_eq!(update_visibility(&mut entries, &world_aabbs, &frustum), 2);
+        assert!(!entries[0].visible);
+        assert!(entries[1].visible);
+    }
+}
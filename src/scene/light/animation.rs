@@ -0,0 +1,182 @@
+//! Built-in animations of light sources, they cover common effects (flickering torches, pulsing crystals,
+//! strobe lights, etc.) without a need to write scripts. See [`LightAnimation`] docs for more info.
+
+use crate::core::{
+    color::Color, color_gradient::ColorGradient, reflect::prelude::*, visitor::prelude::*,
+};
+use std::f32::consts::TAU;
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Animation of a light source. Animations do not modify the properties of the light, they modulate the
+/// intensity (and the color) of the light during rendering (see [`super::BaseLight::animated_intensity`]
+/// and [`super::BaseLight::animated_color`]). Animations are driven by the update of the scene, so they're
+/// not played in the editor.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames)]
+pub enum LightAnimation {
+    /// The light is not animated.
+    None,
+
+    /// Random flickering, like a torch or a broken lamp. The intensity is smoothly interpolated between
+    /// random values, the sequence of random values is defined by the seed, so multiple lights with the
+    /// same settings could flicker differently.
+    Flicker {
+        /// Seed of the random sequence.
+        seed: u64,
+        /// Amount of random values per second.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        frequency: f32,
+        /// Max fraction of the intensity, that could be removed. 0.0 - no flickering, 1.0 - the light
+        /// could go completely dark.
+        #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+        amount: f32,
+    },
+
+    /// Smooth periodic change of the intensity between full and reduced intensity.
+    Pulse {
+        /// Amount of pulses per second.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        frequency: f32,
+        /// Fraction of the intensity, that is removed at the lowest point of a pulse.
+        #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+        amount: f32,
+    },
+
+    /// The light is periodically switched on and off.
+    Strobe {
+        /// Amount of flashes per second.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        frequency: f32,
+        /// Fraction of a period, when the light is on.
+        #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+        duty_cycle: f32,
+    },
+
+    /// The color of the light is periodically taken from the gradient.
+    ColorCycle {
+        /// Colors of a single cycle.
+        gradient: ColorGradient,
+        /// Duration of a single cycle in seconds.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        period: f32,
+    },
+}
+
+impl Default for LightAnimation {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl LightAnimation {
+    /// Returns intensity multiplier at the given time (in seconds) of the animation.
+    pub fn intensity_factor(&self, time: f32) -> f32 {
+        match self {
+            LightAnimation::None | LightAnimation::ColorCycle { .. } => 1.0,
+            LightAnimation::Flicker {
+                seed,
+                frequency,
+                amount,
+            } => {
+                let position = (time * frequency).max(0.0);
+                let index = position as u64;
+                let t = position.fract();
+                let a = noise(*seed, index);
+                let b = noise(*seed, index.wrapping_add(1));
+                // Smooth step between two random values.
+                let noise = a + (b - a) * t * t * (3.0 - 2.0 * t);
+                1.0 - amount * noise
+            }
+            LightAnimation::Pulse { frequency, amount } => {
+                1.0 - amount * (0.5 - 0.5 * (TAU * frequency * time).cos())
+            }
+            LightAnimation::Strobe {
+                frequency,
+                duty_cycle,
+            } => {
+                if (time * frequency).fract() < *duty_cycle {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// Returns the color of the light at the given time (in seconds) of the animation. Only `ColorCycle`
+    /// animation changes the color, the given color of the light is returned for any other animation.
+    pub fn color(&self, time: f32, color: Color) -> Color {
+        match self {
+            LightAnimation::ColorCycle { gradient, period } if *period > 0.0 => {
+                gradient.get_color((time / period).fract())
+            }
+            _ => color,
+        }
+    }
+}
+
+// Returns a pseudo-random value in [0; 1] range for the given seed and index.
+fn noise(seed: u64, index: u64) -> f32 {
+    // SplitMix64 finalizer.
+    let mut x = seed ^ index.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^= x >> 31;
+    (x >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            color::Color,
+            color_gradient::{ColorGradientBuilder, GradientPoint},
+        },
+        scene::light::animation::LightAnimation,
+    };
+
+    #[test]
+    fn test_light_animations() {
+        assert_eq!(LightAnimation::None.intensity_factor(1.5), 1.0);
+
+        let pulse = LightAnimation::Pulse {
+            frequency: 1.0,
+            amount: 0.5,
+        };
+        assert!((pulse.intensity_factor(0.0) - 1.0).abs() < 1.0e-5);
+        assert!((pulse.intensity_factor(0.5) - 0.5).abs() < 1.0e-5);
+
+        let strobe = LightAnimation::Strobe {
+            frequency: 2.0,
+            duty_cycle: 0.25,
+        };
+        assert_eq!(strobe.intensity_factor(0.1), 1.0);
+        assert_eq!(strobe.intensity_factor(0.2), 0.0);
+        assert_eq!(strobe.intensity_factor(0.6), 1.0);
+
+        let flicker = |seed| LightAnimation::Flicker {
+            seed,
+            frequency: 10.0,
+            amount: 0.8,
+        };
+        let samples = |seed| {
+            (0..50)
+                .map(|i| flicker(seed).intensity_factor(i as f32 * 0.037))
+                .collect::<Vec<_>>()
+        };
+        assert!(samples(1).iter().all(|v| (0.2..=1.0).contains(v)));
+        assert_eq!(samples(1), samples(1));
+        assert_ne!(samples(1), samples(2));
+
+        let cycle = LightAnimation::ColorCycle {
+            gradient: ColorGradientBuilder::new()
+                .with_point(GradientPoint::new(0.0, Color::RED))
+                .with_point(GradientPoint::new(1.0, Color::BLUE))
+                .build(),
+            period: 2.0,
+        };
+        assert_eq!(cycle.intensity_factor(1.0), 1.0);
+        assert_eq!(cycle.color(0.0, Color::WHITE), Color::RED);
+        assert_eq!(cycle.color(2.0, Color::WHITE), Color::RED);
+        assert_eq!(pulse.color(0.3, Color::WHITE), Color::WHITE);
+    }
+}
@@ -23,7 +23,7 @@ use crate::{
         debug::SceneDrawingContext,
         graph::Graph,
         light::{BaseLight, BaseLightBuilder},
-        node::{Node, NodeTrait},
+        node::{Node, NodeTrait, UpdateContext},
     },
 };
 use std::ops::{Deref, DerefMut};
@@ -165,6 +165,14 @@ impl NodeTrait for DirectionalLight {
         Self::type_uuid()
     }
 
+    fn update(&mut self, context: &mut UpdateContext) {
+        self.base_light.update_animation(context.dt);
+    }
+
+    fn updates_render_data(&self) -> bool {
+        self.base_light.is_animated()
+    }
+
     fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
         ctx.draw_arrow(
             16,
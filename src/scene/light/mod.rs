@@ -24,10 +24,14 @@ use crate::{
         variable::InheritableVariable,
         visitor::{Visit, VisitResult, Visitor},
     },
-    scene::base::{Base, BaseBuilder},
+    scene::{
+        base::{Base, BaseBuilder},
+        light::animation::LightAnimation,
+    },
 };
 use std::ops::{Deref, DerefMut};
 
+pub mod animation;
 pub mod directional;
 pub mod point;
 pub mod spot;
@@ -67,6 +71,19 @@ pub struct BaseLight {
     #[reflect(min_value = 0.0, step = 0.1)]
     #[reflect(setter = "set_intensity")]
     intensity: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_shadow_softness")]
+    shadow_softness: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_animation")]
+    animation: InheritableVariable<LightAnimation>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    animation_time: f32,
 }
 
 impl Deref for BaseLight {
@@ -96,6 +113,9 @@ impl Default for BaseLight {
             )),
             scatter_enabled: InheritableVariable::new_modified(true),
             intensity: InheritableVariable::new_modified(1.0),
+            shadow_softness: InheritableVariable::new_modified(1.0),
+            animation: Default::default(),
+            animation_time: 0.0,
         }
     }
 }
@@ -175,6 +195,59 @@ impl BaseLight {
     pub fn is_scatter_enabled(&self) -> bool {
         *self.scatter_enabled
     }
+
+    /// Sets new shadow softness. Default is 1.0. Softness scales the size of shadow filter (see
+    /// [`crate::renderer::ShadowFilterSettings`]), larger values produce softer shadows, zero
+    /// disables filtering for the light. For percentage-closer soft shadows it is proportional to
+    /// the size of the light source.
+    #[inline]
+    pub fn set_shadow_softness(&mut self, softness: f32) -> f32 {
+        self.shadow_softness
+            .set_value_and_mark_modified(softness.max(0.0))
+    }
+
+    /// Returns current shadow softness.
+    #[inline]
+    pub fn shadow_softness(&self) -> f32 {
+        *self.shadow_softness
+    }
+
+    /// Sets new animation of the light, see [`LightAnimation`] docs for more info. The animation is
+    /// restarted.
+    #[inline]
+    pub fn set_animation(&mut self, animation: LightAnimation) -> LightAnimation {
+        self.animation_time = 0.0;
+        self.animation.set_value_and_mark_modified(animation)
+    }
+
+    /// Returns current animation of the light.
+    #[inline]
+    pub fn animation(&self) -> &LightAnimation {
+        &self.animation
+    }
+
+    /// Returns color of the light at the current time of its animation, this color is used for rendering.
+    #[inline]
+    pub fn animated_color(&self) -> Color {
+        self.animation.color(self.animation_time, *self.color)
+    }
+
+    /// Returns intensity of the light at the current time of its animation, this intensity is used for
+    /// rendering.
+    #[inline]
+    pub fn animated_intensity(&self) -> f32 {
+        *self.intensity * self.animation.intensity_factor(self.animation_time)
+    }
+
+    pub(crate) fn update_animation(&mut self, dt: f32) {
+        if self.is_animated() {
+            self.animation_time += dt;
+        }
+    }
+
+    pub(crate) fn is_animated(&self) -> bool {
+        *self.animation != LightAnimation::None
+    }
 }
 
 /// Light scene node builder. Provides easy declarative way of creating light scene
@@ -186,6 +259,8 @@ pub struct BaseLightBuilder {
     scatter_factor: Vector3<f32>,
     scatter_enabled: bool,
     intensity: f32,
+    shadow_softness: f32,
+    animation: LightAnimation,
 }
 
 impl BaseLightBuilder {
@@ -201,6 +276,8 @@ impl BaseLightBuilder {
             scatter_factor: Vector3::new(DEFAULT_SCATTER_R, DEFAULT_SCATTER_G, DEFAULT_SCATTER_B),
             scatter_enabled: true,
             intensity: 1.0,
+            shadow_softness: 1.0,
+            animation: LightAnimation::None,
         }
     }
 
@@ -234,6 +311,18 @@ impl BaseLightBuilder {
         self
     }
 
+    /// Sets desired shadow softness, see [`BaseLight::set_shadow_softness`] for more info.
+    pub fn with_shadow_softness(mut self, softness: f32) -> Self {
+        self.shadow_softness = softness;
+        self
+    }
+
+    /// Sets desired animation of the light, see [`LightAnimation`] docs for more info.
+    pub fn with_animation(mut self, animation: LightAnimation) -> Self {
+        self.animation = animation;
+        self
+    }
+
     /// Creates new instance of base light.
     pub fn build(self) -> BaseLight {
         BaseLight {
@@ -243,6 +332,9 @@ impl BaseLightBuilder {
             scatter: self.scatter_factor.into(),
             scatter_enabled: self.scatter_enabled.into(),
             intensity: self.intensity.into(),
+            shadow_softness: self.shadow_softness.max(0.0).into(),
+            animation: self.animation.into(),
+            animation_time: 0.0,
         }
     }
 }
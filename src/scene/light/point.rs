@@ -33,7 +33,7 @@ use crate::{
         debug::SceneDrawingContext,
         graph::Graph,
         light::{BaseLight, BaseLightBuilder},
-        node::{Node, NodeTrait},
+        node::{Node, NodeTrait, UpdateContext},
     },
 };
 use std::ops::{Deref, DerefMut};
@@ -124,6 +124,14 @@ impl NodeTrait for PointLight {
         Self::type_uuid()
     }
 
+    fn update(&mut self, context: &mut UpdateContext) {
+        self.base_light.update_animation(context.dt);
+    }
+
+    fn updates_render_data(&self) -> bool {
+        self.base_light.is_animated()
+    }
+
     fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
         ctx.draw_wire_sphere(self.global_position(), self.radius(), 30, Color::GREEN);
     }
@@ -0,0 +1,617 @@
+//! Lines is a scene node that renders a persistent set of wide lines and quads.
+//!
+//! For more info see [`Lines`]
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    material::{
+        shader::{ShaderResource, ShaderResourceExtension},
+        Material, SharedMaterial,
+    },
+    renderer::{
+        self,
+        batch::{PersistentIdentifier, RenderContext, SurfaceInstanceData},
+        framework::geometry_buffer::ElementRange,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{SurfaceData, SurfaceSharedData},
+            RenderPath,
+        },
+        node::{Node, NodeTrait, UpdateContext},
+        trail::TrailVertex,
+    },
+};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
+
+/// A wide line that goes through a set of points. The line is always facing the camera.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct Polyline {
+    /// Points of the line in local coordinates of the node.
+    pub points: Vec<Vector3<f32>>,
+    /// Width of the line.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub width: f32,
+    /// Color of the line. It is multiplied with the color of the material.
+    pub color: Color,
+    /// If `true`, then the last point will be connected with the first one.
+    pub closed: bool,
+}
+
+impl Default for Polyline {
+    fn default() -> Self {
+        Self {
+            points: Default::default(),
+            width: 0.05,
+            color: Color::WHITE,
+            closed: false,
+        }
+    }
+}
+
+impl Polyline {
+    /// Creates a straight line between the two given points.
+    pub fn segment(begin: Vector3<f32>, end: Vector3<f32>, width: f32, color: Color) -> Self {
+        Self {
+            points: vec![begin, end],
+            width,
+            color,
+            closed: false,
+        }
+    }
+}
+
+/// A flat textured quad. Useful for targeting indicators, markers, etc.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct LineQuad {
+    /// Center of the quad in local coordinates of the node.
+    pub center: Vector3<f32>,
+    /// Size of the quad along its local X and Y axes.
+    pub size: Vector2<f32>,
+    /// Rotation of the quad. Non-rotated quad lies in XY plane of the node.
+    pub rotation: UnitQuaternion<f32>,
+    /// Color of the quad. It is multiplied with the color of the material.
+    pub color: Color,
+}
+
+impl Default for LineQuad {
+    fn default() -> Self {
+        Self {
+            center: Default::default(),
+            size: Vector2::new(1.0, 1.0),
+            rotation: UnitQuaternion::identity(),
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// Calculates a set of points of a rope with the given length hanging between two points (under gravity
+/// that acts along negative Y axis). The result contains `segments + 1` points, the first and the last
+/// points are equal to `begin` and `end` respectively. If the length is less or equal than the distance
+/// between the points, then the rope is considered tight and a straight line is returned.
+///
+/// It is intended to be used with [`Polyline`] to draw ropes, cables, wires, etc.
+pub fn catenary(
+    begin: Vector3<f32>,
+    end: Vector3<f32>,
+    length: f32,
+    segments: usize,
+) -> Vec<Vector3<f32>> {
+    let segments = segments.max(1);
+    let delta = end - begin;
+    let horizontal = Vector3::new(delta.x, 0.0, delta.z);
+    let h = horizontal.norm();
+    let v = delta.y;
+
+    let straight_line = || {
+        (0..=segments)
+            .map(|i| begin + delta.scale(i as f32 / segments as f32))
+            .collect::<Vec<_>>()
+    };
+
+    if length <= delta.norm() || h <= f32::EPSILON {
+        return straight_line();
+    }
+
+    // A curve `y = a * cosh((x - x0) / a) + c` that goes through both points and has the given length
+    // satisfies `sinh(k) / k = sqrt(length^2 - v^2) / h`, where `k = h / (2 * a)`. The left side is
+    // monotonic, so it could be solved by simple bisection.
+    let s = (length * length - v * v).sqrt();
+    let ratio = s / h;
+    let (mut lo, mut hi) = (f32::EPSILON, 1.0f32);
+    while hi.sinh() / hi < ratio && hi < 80.0 {
+        hi *= 2.0;
+    }
+    for _ in 0..64 {
+        let mid = 0.5 * (lo + hi);
+        if mid.sinh() / mid < ratio {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    let k = 0.5 * (lo + hi);
+    let a = h / (2.0 * k);
+    let x0 = 0.5 * h - a * (v / s).asinh();
+    let c = -a * (x0 / a).cosh();
+
+    let direction = horizontal.scale(1.0 / h);
+    (0..=segments)
+        .map(|i| {
+            if i == segments {
+                end
+            } else {
+                let x = h * i as f32 / segments as f32;
+                let y = a * ((x - x0) / a).cosh() + c;
+                begin + direction.scale(x) + Vector3::new(0.0, y, 0.0)
+            }
+        })
+        .collect()
+}
+
+/// Lines is a scene node that renders a persistent set of wide lines ([`Polyline`]) and flat quads
+/// ([`LineQuad`]) using a material. Unlike debug drawing, which is intended for development purposes
+/// only, this node is a part of the scene and could be used for gameplay needs - laser beams, ropes
+/// (see [`catenary`]), targeting indicators, trajectories, etc.
+///
+/// # Performance
+///
+/// Geometry of the node is generated only when its primitives were changed, every other frame it is
+/// rendered as an ordinary mesh. All the primitives of a node are drawn in a single draw call.
+///
+/// # Rendering
+///
+/// The node is rendered using Forward render path with the standard trail shader (see
+/// [`ShaderResourceExtension::standard_trail`]), lines use `[0; 1]` range of texture coordinates along
+/// their length, quads use the same range on both axes. It is possible to use a custom material, its
+/// shader must have `Forward` pass and use the vertex layout of [`TrailVertex`]. The node does not cast
+/// shadows.
+///
+/// # Example
+///
+/// ```
+/// use fyrox::{
+///     core::{algebra::Vector3, color::Color, pool::Handle},
+///     scene::{
+///         base::BaseBuilder,
+///         graph::Graph,
+///         lines::{catenary, LinesBuilder, Polyline},
+///         node::Node,
+///     },
+/// };
+///
+/// fn create_rope(graph: &mut Graph) -> Handle<Node> {
+///     let points = catenary(Vector3::new(0.0, 2.0, 0.0), Vector3::new(4.0, 2.0, 0.0), 5.0, 32);
+///
+///     LinesBuilder::new(BaseBuilder::new())
+///         .with_lines(vec![Polyline {
+///             points,
+///             width: 0.03,
+///             color: Color::opaque(120, 90, 60),
+///             closed: false,
+///         }])
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Reflect)]
+pub struct Lines {
+    base: Base,
+
+    #[reflect(setter = "set_material")]
+    material: InheritableVariable<SharedMaterial>,
+
+    #[reflect(setter = "set_lines")]
+    lines: InheritableVariable<Vec<Polyline>>,
+
+    #[reflect(setter = "set_quads")]
+    quads: InheritableVariable<Vec<LineQuad>>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    surface_data: SurfaceSharedData,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    geometry_dirty: Cell<bool>,
+}
+
+impl Clone for Lines {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            material: self.material.clone(),
+            lines: self.lines.clone(),
+            quads: self.quads.clone(),
+            // Geometry could be changed at any time, it must not be shared between clones.
+            surface_data: self.surface_data.deep_clone(),
+            geometry_dirty: Cell::new(true),
+        }
+    }
+}
+
+impl Default for Lines {
+    fn default() -> Self {
+        LinesBuilder::new(BaseBuilder::new()).build_lines()
+    }
+}
+
+impl Deref for Lines {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Lines {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Lines {
+    fn type_uuid() -> Uuid {
+        uuid!("a9b2c6e1-4f3d-4d8a-b5e7-0c1f92d7e843")
+    }
+}
+
+impl Lines {
+    /// Sets new material of the node.
+    pub fn set_material(&mut self, material: SharedMaterial) -> SharedMaterial {
+        self.material.set_value_and_mark_modified(material)
+    }
+
+    /// Returns current material of the node.
+    pub fn material(&self) -> &SharedMaterial {
+        &self.material
+    }
+
+    /// Sets new set of lines.
+    pub fn set_lines(&mut self, lines: Vec<Polyline>) -> Vec<Polyline> {
+        self.geometry_dirty.set(true);
+        self.lines.set_value_and_mark_modified(lines)
+    }
+
+    /// Returns current set of lines.
+    pub fn lines(&self) -> &[Polyline] {
+        &self.lines
+    }
+
+    /// Returns a mutable reference to the current set of lines. Geometry of the node will be
+    /// regenerated on next update.
+    pub fn lines_mut(&mut self) -> &mut Vec<Polyline> {
+        self.geometry_dirty.set(true);
+        self.lines.get_value_mut_and_mark_modified()
+    }
+
+    /// Sets new set of quads.
+    pub fn set_quads(&mut self, quads: Vec<LineQuad>) -> Vec<LineQuad> {
+        self.geometry_dirty.set(true);
+        self.quads.set_value_and_mark_modified(quads)
+    }
+
+    /// Returns current set of quads.
+    pub fn quads(&self) -> &[LineQuad] {
+        &self.quads
+    }
+
+    /// Returns a mutable reference to the current set of quads. Geometry of the node will be
+    /// regenerated on next update.
+    pub fn quads_mut(&mut self) -> &mut Vec<LineQuad> {
+        self.geometry_dirty.set(true);
+        self.quads.get_value_mut_and_mark_modified()
+    }
+
+    /// Removes every line and quad of the node.
+    pub fn clear(&mut self) {
+        self.lines_mut().clear();
+        self.quads_mut().clear();
+    }
+
+    fn rebuild_geometry(&self) {
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        for line in self.lines.iter() {
+            let count = line.points.len();
+            if count < 2 {
+                continue;
+            }
+
+            let segment_count = if line.closed { count } else { count - 1 };
+            let point = |i: usize| line.points[i % count];
+            let total_length = (0..segment_count)
+                .map(|i| (point(i + 1) - point(i)).norm())
+                .sum::<f32>()
+                .max(f32::EPSILON);
+            let color = line.color.srgb_to_linear_f32();
+            let half_width = 0.5 * line.width;
+
+            let first = vertices.len() as u32;
+            let mut distance = 0.0;
+            for i in 0..=segment_count {
+                if i > 0 {
+                    distance += (point(i) - point(i - 1)).norm();
+                }
+
+                let (prev, next) = if line.closed {
+                    (point(i + count - 1), point(i + 1))
+                } else {
+                    (point(i.saturating_sub(1)), point((i + 1).min(count - 1)))
+                };
+                let tangent = (next - prev)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::x);
+
+                let u = distance / total_length;
+                let position = point(i);
+                vertices.push(TrailVertex {
+                    position,
+                    tex_coord: Vector2::new(u, 0.0),
+                    color,
+                    direction: tangent.push(-half_width),
+                });
+                vertices.push(TrailVertex {
+                    position,
+                    tex_coord: Vector2::new(u, 1.0),
+                    color,
+                    direction: tangent.push(half_width),
+                });
+            }
+
+            for i in 0..segment_count as u32 {
+                let a = first + i * 2;
+                triangles.push(TriangleDefinition([a, a + 1, a + 2]));
+                triangles.push(TriangleDefinition([a + 1, a + 3, a + 2]));
+            }
+        }
+
+        for quad in self.quads.iter() {
+            let color = quad.color.srgb_to_linear_f32();
+            let half_size = quad.size.scale(0.5);
+            let first = vertices.len() as u32;
+            for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                let offset = Vector3::new(x * half_size.x, y * half_size.y, 0.0);
+                vertices.push(TrailVertex {
+                    position: quad.center + quad.rotation * offset,
+                    tex_coord: Vector2::new((x + 1.0) * 0.5, (1.0 - y) * 0.5),
+                    color,
+                    direction: Default::default(),
+                });
+            }
+            triangles.push(TriangleDefinition([first, first + 1, first + 2]));
+            triangles.push(TriangleDefinition([first, first + 2, first + 3]));
+        }
+
+        let mut data = self.surface_data.lock();
+        data.vertex_buffer = VertexBuffer::new(vertices.len(), vertices).unwrap();
+        data.geometry_buffer = TriangleBuffer::new(triangles);
+        self.geometry_dirty.set(false);
+    }
+}
+
+impl NodeTrait for Lines {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let mut bounding_box = AxisAlignedBoundingBox::default();
+        for line in self.lines.iter() {
+            for point in line.points.iter() {
+                bounding_box.add_point(*point);
+            }
+        }
+        let max_width = self
+            .lines
+            .iter()
+            .map(|line| line.width)
+            .fold(0.0f32, f32::max);
+        if bounding_box.is_valid() {
+            bounding_box.inflate(Vector3::repeat(0.5 * max_width));
+        }
+        for quad in self.quads.iter() {
+            let half_size = quad.size.scale(0.5);
+            for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                let offset = Vector3::new(x * half_size.x, y * half_size.y, 0.0);
+                bounding_box.add_point(quad.center + quad.rotation * offset);
+            }
+        }
+        if bounding_box.is_valid() {
+            bounding_box
+        } else {
+            self.base.local_bounding_box()
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, _context: &mut UpdateContext) {
+        if self.geometry_dirty.get() {
+            self.rebuild_geometry();
+        }
+    }
+
+    fn updates_render_data(&self) -> bool {
+        false
+    }
+
+    fn collect_render_data(&self, ctx: &mut RenderContext) {
+        if !self.global_visibility()
+            || !self.is_globally_enabled()
+            || renderer::is_shadow_pass(ctx.render_pass_name)
+            || !ctx.is_node_in_frustum()
+        {
+            return;
+        }
+
+        if self.geometry_dirty.get() {
+            self.rebuild_geometry();
+        }
+
+        ctx.storage.push(
+            &self.surface_data,
+            &self.material,
+            RenderPath::Forward,
+            0,
+            0,
+            SurfaceInstanceData {
+                world_transform: self.global_transform(),
+                bone_matrices: Default::default(),
+                depth_offset: 0.0,
+                blend_shapes_weights: Default::default(),
+                element_range: ElementRange::Full,
+                persistent_identifier: PersistentIdentifier::new_combined(
+                    &self.surface_data,
+                    ctx.node_handle,
+                    0,
+                ),
+                node_handle: ctx.node_handle,
+            },
+        );
+    }
+}
+
+/// Allows you to create Lines node in a declarative manner.
+pub struct LinesBuilder {
+    base_builder: BaseBuilder,
+    material: Option<SharedMaterial>,
+    lines: Vec<Polyline>,
+    quads: Vec<LineQuad>,
+}
+
+impl LinesBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            material: None,
+            lines: Default::default(),
+            quads: Default::default(),
+        }
+    }
+
+    /// Sets desired material of the node. By default, the node uses a material with the standard
+    /// trail shader.
+    pub fn with_material(mut self, material: SharedMaterial) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    /// Sets desired set of lines.
+    pub fn with_lines(mut self, lines: Vec<Polyline>) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// Sets desired set of quads.
+    pub fn with_quads(mut self, quads: Vec<LineQuad>) -> Self {
+        self.quads = quads;
+        self
+    }
+
+    /// Creates new Lines node.
+    pub fn build_lines(self) -> Lines {
+        let material = self.material.unwrap_or_else(|| {
+            SharedMaterial::new(Material::from_shader(
+                ShaderResource::standard_trail(),
+                None,
+            ))
+        });
+
+        Lines {
+            base: self.base_builder.build_base(),
+            material: material.into(),
+            lines: self.lines.into(),
+            quads: self.quads.into(),
+            surface_data: SurfaceSharedData::new(SurfaceData::new(
+                VertexBuffer::new::<TrailVertex>(0, Vec::new()).unwrap(),
+                TriangleBuffer::default(),
+                false,
+            )),
+            geometry_dirty: Cell::new(true),
+        }
+    }
+
+    /// Creates new Lines node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_lines())
+    }
+
+    /// Creates new instance of Lines node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, color::Color},
+        scene::{
+            base::BaseBuilder,
+            lines::{catenary, LineQuad, LinesBuilder, Polyline},
+        },
+    };
+
+    #[test]
+    fn test_catenary() {
+        let begin = Vector3::new(0.0, 1.0, 0.0);
+        let end = Vector3::new(4.0, 2.0, 0.0);
+        let points = catenary(begin, end, 6.0, 64);
+        assert_eq!(points.len(), 65);
+        assert_eq!(points[0], begin);
+        assert_eq!(points[64], end);
+
+        // The rope sags below both of its ends and has (approximately) requested length.
+        assert!(points.iter().any(|p| p.y < begin.y - 0.5));
+        let length = points.windows(2).map(|w| (w[1] - w[0]).norm()).sum::<f32>();
+        assert!((length - 6.0).abs() < 0.05, "{}", length);
+
+        // Tight rope is a straight line.
+        let points = catenary(begin, end, 1.0, 4);
+        assert!(points.iter().all(|p| p.z == 0.0 && p.y >= begin.y));
+    }
+
+    #[test]
+    fn test_lines_geometry() {
+        let lines = LinesBuilder::new(BaseBuilder::new())
+            .with_lines(vec![
+                Polyline::segment(Vector3::default(), Vector3::x(), 0.1, Color::WHITE),
+                Polyline {
+                    points: vec![Vector3::default(), Vector3::x(), Vector3::y()],
+                    closed: true,
+                    ..Default::default()
+                },
+            ])
+            .with_quads(vec![LineQuad::default()])
+            .build_lines();
+
+        lines.rebuild_geometry();
+
+        let data = lines.surface_data.lock();
+        assert_eq!(data.vertex_buffer.vertex_count(), 4 + 8 + 4);
+        assert_eq!(data.geometry_buffer.len(), 2 + 6 + 2);
+    }
+}
@@ -2,14 +2,170 @@
 
 use crate::{
     asset::manager::ResourceManager,
-    core::parking_lot::Mutex,
+    core::{instant::Instant, parking_lot::Mutex},
     engine::SerializationContext,
-    scene::{Scene, SceneLoader},
+    material::shader::ShaderResource,
+    renderer::Renderer,
+    resource::texture::{Texture, TextureResource},
+    scene::{mesh::surface::SurfaceSharedData, mesh::Mesh, Scene, SceneLoader},
 };
-use std::{path::PathBuf, sync::Arc};
+use fxhash::FxHashSet;
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+/// Current phase of scene loading.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SceneLoadingPhase {
+    /// The scene is being read from its file.
+    Deserializing,
+    /// Resources used by the scene are being loaded.
+    LoadingResources {
+        /// Amount of loaded resources.
+        loaded: usize,
+        /// Total amount of resources used by the scene.
+        total: usize,
+    },
+    /// The scene is being synchronized with its resources.
+    Resolving,
+    /// Resources of the scene are being uploaded to GPU. See [`SceneWarmup`] docs for more info.
+    WarmingUp {
+        /// Amount of uploaded items (textures, shaders and surfaces).
+        uploaded: usize,
+        /// Total amount of items to upload.
+        total: usize,
+    },
+    /// The scene is fully loaded.
+    Finished,
+}
+
+impl SceneLoadingPhase {
+    /// Returns approximate loading progress in `[0; 1]` range. It could be used for progress bars.
+    pub fn progress(&self) -> f32 {
+        // Interpolates between the bounds of a phase, the bounds are returned exactly at the ends of
+        // the phase, so the progress does not jump back and forth between the phases.
+        fn interpolate(from: f32, to: f32, done: usize, total: usize) -> f32 {
+            let t = if total == 0 {
+                1.0
+            } else {
+                done as f32 / total as f32
+            };
+            (1.0 - t) * from + t * to
+        }
+
+        match *self {
+            SceneLoadingPhase::Deserializing => 0.0,
+            SceneLoadingPhase::LoadingResources { loaded, total } => {
+                interpolate(0.1, 0.7, loaded, total)
+            }
+            SceneLoadingPhase::Resolving => 0.7,
+            SceneLoadingPhase::WarmingUp { uploaded, total } => {
+                interpolate(0.75, 1.0, uploaded, total)
+            }
+            SceneLoadingPhase::Finished => 1.0,
+        }
+    }
+}
+
+/// Scene warm-up uploads textures, surface data and shaders of a scene to GPU over multiple frames, so
+/// the first frames of the scene won't hitch because of on-demand uploads and shader compilation.
+/// Warm-up is done in steps with a time budget, see [`Self::step`].
+pub struct SceneWarmup {
+    shaders: Vec<ShaderResource>,
+    textures: Vec<TextureResource>,
+    surfaces: Vec<SurfaceSharedData>,
+    uploaded: usize,
+    total: usize,
+}
+
+impl SceneWarmup {
+    /// Collects everything that should be uploaded to GPU from the given scene.
+    pub fn new(scene: &Scene) -> Self {
+        let shaders = scene.collect_used_shaders();
+        let textures = scene
+            .collect_used_resources()
+            .into_iter()
+            .filter_map(|resource| resource.try_cast::<Texture>())
+            .collect::<FxHashSet<_>>();
+        let mut surfaces = Vec::new();
+        let mut surface_keys = FxHashSet::default();
+
+        for mesh in scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Mesh>())
+        {
+            for surface in mesh.surfaces() {
+                if surface_keys.insert(surface.data_ref().key()) {
+                    surfaces.push(surface.data());
+                }
+            }
+        }
+
+        // Only loaded resources could be uploaded.
+        let shaders = shaders
+            .into_iter()
+            .filter(|shader| shader.is_ok())
+            .collect::<Vec<_>>();
+        let textures = textures
+            .into_iter()
+            .filter(|texture| texture.is_ok())
+            .collect::<Vec<_>>();
+
+        let total = shaders.len() + textures.len() + surfaces.len();
+
+        Self {
+            shaders,
+            textures,
+            surfaces,
+            uploaded: 0,
+            total,
+        }
+    }
+
+    /// Uploads items to GPU until the given time budget is exceeded (at least one item is uploaded per
+    /// step). Shaders are compiled first, because it is the slowest part. Returns `true` if everything
+    /// is uploaded, `false` - otherwise.
+    pub fn step(&mut self, renderer: &mut Renderer, time_budget: Duration) -> bool {
+        let start = Instant::now();
+
+        loop {
+            if let Some(shader) = self.shaders.pop() {
+                renderer.prewarm_shader(&shader);
+            } else if let Some(texture) = self.textures.pop() {
+                renderer.prewarm_texture(&texture);
+            } else if let Some(surface) = self.surfaces.pop() {
+                renderer.prewarm_surface_data(&surface);
+            } else {
+                return true;
+            }
+
+            self.uploaded += 1;
+
+            if start.elapsed() >= time_budget {
+                return self.is_finished();
+            }
+        }
+    }
+
+    /// Returns `true` if everything is uploaded, `false` - otherwise.
+    pub fn is_finished(&self) -> bool {
+        self.uploaded >= self.total
+    }
+
+    /// Returns amount of uploaded items.
+    pub fn uploaded(&self) -> usize {
+        self.uploaded
+    }
+
+    /// Returns total amount of items to upload.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
 
 struct LoaderState {
     scene: Option<Result<Scene, String>>,
+    phase: SceneLoadingPhase,
+    warmup: Option<(SceneWarmup, Scene)>,
 }
 
 /// Asynchronous scene loader is a cross-platform scene loader, including platforms
@@ -65,6 +221,14 @@ struct LoaderState {
 ///     }
 /// }
 /// ```
+///
+/// # Warm-up
+///
+/// [`Self::fetch_result`] returns the scene as soon as it is loaded, which means that its textures,
+/// geometry and shaders will be uploaded to GPU on demand during the first frames, which may cause
+/// hitches. Use [`Self::poll`] instead, to upload everything over multiple frames (see
+/// [`SceneWarmup`]) before the scene is returned. Current phase of loading could be fetched using
+/// [`Self::phase`].
 #[derive(Clone)]
 pub struct AsyncSceneLoader {
     state: Arc<Mutex<LoaderState>>,
@@ -77,7 +241,11 @@ impl AsyncSceneLoader {
         serialization_context: Arc<SerializationContext>,
         resource_manager: ResourceManager,
     ) -> Self {
-        let state = Arc::new(Mutex::new(LoaderState { scene: None }));
+        let state = Arc::new(Mutex::new(LoaderState {
+            scene: None,
+            phase: SceneLoadingPhase::Deserializing,
+            warmup: None,
+        }));
 
         let inner_state = state.clone();
         let future = async move {
@@ -85,7 +253,10 @@ impl AsyncSceneLoader {
                 .await
             {
                 Ok(loader) => {
-                    inner_state.lock().scene = Some(Ok(loader.finish().await));
+                    let scene = loader
+                        .finish_with_progress(|phase| inner_state.lock().phase = phase)
+                        .await;
+                    inner_state.lock().scene = Some(Ok(scene));
                 }
                 Err(e) => {
                     inner_state.lock().scene = Some(Err(format!(
@@ -110,8 +281,92 @@ impl AsyncSceneLoader {
         Self { state }
     }
 
-    /// Tries to get scene loading result. See [`AsyncSceneLoader`] docs for usage examples.
+    /// Returns current phase of loading.
+    pub fn phase(&self) -> SceneLoadingPhase {
+        self.state.lock().phase
+    }
+
+    /// Tries to get scene loading result. See [`AsyncSceneLoader`] docs for usage examples. If the
+    /// scene is being warmed up (see [`Self::poll`]), the warm-up is cancelled and the scene is
+    /// returned immediately.
     pub fn fetch_result(&self) -> Option<Result<Scene, String>> {
-        self.state.lock().scene.take()
+        let mut state = self.state.lock();
+        let result = state
+            .scene
+            .take()
+            .or_else(|| state.warmup.take().map(|(_, scene)| Ok(scene)));
+        if result.is_some() {
+            state.phase = SceneLoadingPhase::Finished;
+        }
+        result
+    }
+
+    /// Same as [`Self::fetch_result`], but uploads resources of the loaded scene to GPU before
+    /// returning it. The upload is done in steps, each step takes approximately the given amount of
+    /// time. This method must be called every frame until it returns the result.
+    pub fn poll(
+        &self,
+        renderer: &mut Renderer,
+        time_budget: Duration,
+    ) -> Option<Result<Scene, String>> {
+        let mut state = self.state.lock();
+
+        match state.scene.take() {
+            Some(Ok(scene)) => {
+                state.warmup = Some((SceneWarmup::new(&scene), scene));
+            }
+            Some(Err(e)) => return Some(Err(e)),
+            None => (),
+        }
+
+        let (warmup, _) = state.warmup.as_mut()?;
+        let finished = warmup.step(renderer, time_budget);
+        let phase = SceneLoadingPhase::WarmingUp {
+            uploaded: warmup.uploaded(),
+            total: warmup.total(),
+        };
+        state.phase = phase;
+
+        if finished {
+            state.phase = SceneLoadingPhase::Finished;
+            state.warmup.take().map(|(_, scene)| Ok(scene))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::loader::SceneLoadingPhase;
+
+    #[test]
+    fn test_loading_progress() {
+        assert_eq!(SceneLoadingPhase::Deserializing.progress(), 0.0);
+        assert_eq!(
+            SceneLoadingPhase::LoadingResources {
+                loaded: 0,
+                total: 0
+            }
+            .progress(),
+            0.7
+        );
+        assert_eq!(
+            SceneLoadingPhase::WarmingUp {
+                uploaded: 1,
+                total: 2
+            }
+            .progress(),
+            0.875
+        );
+        assert_eq!(SceneLoadingPhase::Finished.progress(), 1.0);
+
+        let mut last = 0.0;
+        for loaded in 0..=10 {
+            let progress = SceneLoadingPhase::LoadingResources { loaded, total: 10 }.progress();
+            assert!(progress >= last);
+            last = progress;
+        }
+        assert!(SceneLoadingPhase::Resolving.progress() >= last);
     }
 }
@@ -18,7 +18,7 @@ use std::{
     hash::{Hash, Hasher},
     marker::PhantomData,
     mem::MaybeUninit,
-    ops::{Deref, DerefMut, Index, IndexMut, RangeBounds},
+    ops::{Deref, DerefMut, Index, IndexMut, Range, RangeBounds},
     vec::Drain,
 };
 
@@ -316,6 +316,10 @@ pub struct VertexBuffer {
     data_hash: u64,
     #[visit(optional)]
     layout_hash: u64,
+    // A range of vertices that was modified since the last upload to GPU. It allows the renderer
+    // to upload only the changed part of the buffer.
+    #[visit(skip)]
+    modified_range: Option<Range<u32>>,
 }
 
 fn calculate_layout_hash(layout: &[VertexAttribute]) -> u64 {
@@ -339,6 +343,9 @@ impl<'a> Drop for VertexBufferRefMut<'a> {
     fn drop(&mut self) {
         // Recalculate data hash.
         self.vertex_buffer.data_hash = calculate_data_hash(&self.vertex_buffer.data);
+        // Any part of the buffer could be modified.
+        let vertex_count = self.vertex_buffer.vertex_count;
+        self.vertex_buffer.mark_modified(0..vertex_count);
     }
 }
 
@@ -356,6 +363,67 @@ impl<'a> DerefMut for VertexBufferRefMut<'a> {
     }
 }
 
+/// See [`VertexBuffer::modify_range`] for more info.
+pub struct VertexBufferRangeRefMut<'a> {
+    vertex_buffer: &'a mut VertexBuffer,
+    range: Range<usize>,
+}
+
+impl<'a> Drop for VertexBufferRangeRefMut<'a> {
+    fn drop(&mut self) {
+        // Recalculate data hash.
+        self.vertex_buffer.data_hash = calculate_data_hash(&self.vertex_buffer.data);
+        let range = self.range.start as u32..self.range.end as u32;
+        if !range.is_empty() {
+            self.vertex_buffer.mark_modified(range);
+        }
+    }
+}
+
+impl<'a> Deref for VertexBufferRangeRefMut<'a> {
+    type Target = VertexBuffer;
+
+    fn deref(&self) -> &Self::Target {
+        self.vertex_buffer
+    }
+}
+
+impl<'a> VertexBufferRangeRefMut<'a> {
+    /// Returns the range of vertices that could be modified.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Creates iterator that emits read/write accessors for vertices in the range.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = VertexViewMut<'_>> + '_ {
+        let vertex_size = self.vertex_buffer.vertex_size as usize;
+        let sparse_layout = &self.vertex_buffer.sparse_layout;
+        self.vertex_buffer.data.as_slice_mut()
+            [self.range.start * vertex_size..self.range.end * vertex_size]
+            .chunks_exact_mut(vertex_size)
+            .map(move |vertex_data| VertexViewMut {
+                vertex_data,
+                sparse_layout,
+            })
+    }
+
+    /// Returns a read/write accessor of n-th vertex of the buffer, or [`None`] if the vertex is
+    /// outside the range.
+    pub fn get_mut(&mut self, n: usize) -> Option<VertexViewMut<'_>> {
+        if self.range.contains(&n) {
+            let vertex_size = self.vertex_buffer.vertex_size as usize;
+            let offset = n * vertex_size;
+            Some(VertexViewMut {
+                vertex_data: &mut self.vertex_buffer.data.as_slice_mut()
+                    [offset..(offset + vertex_size)],
+                sparse_layout: &self.vertex_buffer.sparse_layout,
+            })
+        } else {
+            None
+        }
+    }
+}
+
 impl<'a> VertexBufferRefMut<'a> {
     /// Tries to append a vertex to the buffer.
     ///
@@ -477,6 +545,24 @@ impl<'a> VertexBufferRefMut<'a> {
         }
     }
 
+    /// Rebuilds the buffer, so it will contain the vertices with the given indices in the given order.
+    /// An index could be used multiple times or not used at all, so this method could be used to
+    /// reorder, duplicate or remove vertices in a single pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the indices is out of bounds.
+    pub fn remap(&mut self, indices: &[u32]) {
+        let vertex_size = self.vertex_buffer.vertex_size as usize;
+        let mut bytes = Vec::with_capacity(indices.len() * vertex_size);
+        for &index in indices {
+            let offset = index as usize * vertex_size;
+            bytes.extend_from_slice(&self.vertex_buffer.data[offset..(offset + vertex_size)]);
+        }
+        self.vertex_buffer.data = BytesStorage::new(bytes);
+        self.vertex_buffer.vertex_count = indices.len() as u32;
+    }
+
     /// Duplicates n-th vertex and puts it at the back of the buffer.
     pub fn duplicate(&mut self, n: usize) {
         // Vertex cannot be larger than 256 bytes, so having temporary array of
@@ -672,6 +758,7 @@ impl VertexBuffer {
             layout_hash: calculate_layout_hash(&dense_layout),
             sparse_layout,
             dense_layout,
+            modified_range: None,
         })
     }
 
@@ -747,6 +834,35 @@ impl VertexBuffer {
         }
     }
 
+    /// Provides mutable access to a range of vertices of the buffer. Unlike [`Self::modify`], it does
+    /// not allow to change the size of the buffer and the renderer will upload only the modified range
+    /// of vertices to GPU, which is much faster for large buffers that change partially (deformable
+    /// meshes, for example). The range is clamped to the actual amount of vertices in the buffer.
+    ///
+    /// The same performance considerations as for [`Self::modify`] apply here - hold the returned
+    /// structure as long as possible while modifying the vertices.
+    pub fn modify_range(&mut self, range: Range<usize>) -> VertexBufferRangeRefMut<'_> {
+        let end = range.end.min(self.vertex_count as usize);
+        let start = range.start.min(end);
+        VertexBufferRangeRefMut {
+            vertex_buffer: self,
+            range: start..end,
+        }
+    }
+
+    fn mark_modified(&mut self, range: Range<u32>) {
+        self.modified_range = Some(match self.modified_range.take() {
+            Some(modified) => modified.start.min(range.start)..modified.end.max(range.end),
+            None => range,
+        });
+    }
+
+    /// Returns a range of vertices that was modified since the last call of this method, [`None`]
+    /// means that the range is unknown and the entire buffer must be considered modified.
+    pub(crate) fn take_modified_range(&mut self) -> Option<Range<u32>> {
+        self.modified_range.take()
+    }
+
     /// Checks if an attribute of `usage` exists.
     pub fn has_attribute(&self, usage: VertexAttributeUsage) -> bool {
         self.sparse_layout[usage as usize].is_some()
@@ -1267,7 +1383,7 @@ mod test {
         core::algebra::{Vector2, Vector3, Vector4},
         scene::mesh::buffer::{
             VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage, VertexBuffer,
-            VertexReadTrait,
+            VertexReadTrait, VertexWriteTrait,
         },
     };
 
@@ -1429,6 +1545,42 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_modify_range() {
+        let mut buffer = create_test_buffer();
+        assert_eq!(buffer.take_modified_range(), None);
+
+        let hash = buffer.data_hash();
+        {
+            let mut range = buffer.modify_range(1..10);
+            assert_eq!(range.range(), 1..3);
+            assert!(range.get_mut(0).is_none());
+            assert_eq!(range.iter_mut().count(), 2);
+            range
+                .get_mut(2)
+                .unwrap()
+                .write_3_f32(VertexAttributeUsage::Position, Vector3::new(3.0, 2.0, 1.0))
+                .unwrap();
+        }
+        assert_ne!(buffer.data_hash(), hash);
+        assert_eq!(
+            buffer
+                .get(2)
+                .unwrap()
+                .read_3_f32(VertexAttributeUsage::Position)
+                .unwrap(),
+            Vector3::new(3.0, 2.0, 1.0)
+        );
+
+        // Ranges are merged until taken.
+        drop(buffer.modify_range(0..1));
+        assert_eq!(buffer.take_modified_range(), Some(0..3));
+        assert_eq!(buffer.take_modified_range(), None);
+
+        drop(buffer.modify());
+        assert_eq!(buffer.take_modified_range(), Some(0..3));
+    }
+
     #[test]
     fn test_vertex_duplication() {
         let mut buffer = create_test_buffer();
@@ -36,6 +36,7 @@ use std::{
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 pub mod buffer;
+mod optimizer;
 pub mod surface;
 pub mod vertex;
 
@@ -344,34 +345,32 @@ impl NodeTrait for Mesh {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
-        if self.surfaces.iter().any(|s| !s.bones.is_empty()) {
-            let mut world_aabb = self
-                .local_bounding_box()
-                .transform(&self.global_transform());
+        let mut world_aabb = self
+            .local_bounding_box()
+            .transform(&self.global_transform());
 
-            // Special case for skinned meshes.
-            for surface in self.surfaces.iter() {
-                for &bone in surface.bones() {
-                    if let Some(node) = context.nodes.try_borrow(bone) {
-                        world_aabb.add_point(node.global_position())
-                    }
+        // Special case for skinned meshes.
+        for surface in self.surfaces.iter() {
+            for &bone in surface.bones() {
+                if let Some(node) = context.nodes.try_borrow(bone) {
+                    world_aabb.add_point(node.global_position())
                 }
             }
+        }
 
-            self.world_bounding_box.set(world_aabb)
-        } else {
-            self.world_bounding_box.set(
-                self.local_bounding_box()
-                    .transform(&self.global_transform()),
-            );
+        let prev_world_aabb = self.world_bounding_box.replace(world_aabb);
+        if prev_world_aabb.min != world_aabb.min || prev_world_aabb.max != world_aabb.max {
+            self.render_data_modified.set(true);
         }
     }
 
+    fn updates_render_data(&self) -> bool {
+        // The mesh is marked as modified by the update only when its world bounding box changes.
+        false
+    }
+
     fn collect_render_data(&self, ctx: &mut RenderContext) {
-        if !self.global_visibility()
-            || !self.is_globally_enabled()
-            || !ctx.frustum.is_intersects_aabb(&self.world_bounding_box())
-        {
+        if !self.global_visibility() || !self.is_globally_enabled() || !ctx.is_node_in_frustum() {
             return;
         }
 
@@ -393,7 +392,7 @@ impl NodeTrait for Mesh {
                 surface.material(),
                 self.render_path(),
                 self.decal_layer_index(),
-                surface.material().key(),
+                0,
                 SurfaceInstanceData {
                     world_transform: world,
                     bone_matrices: surface
@@ -419,6 +418,7 @@ impl NodeTrait for Mesh {
                         ctx.node_handle,
                         index,
                     ),
+                    node_handle: ctx.node_handle,
                 },
             );
         }
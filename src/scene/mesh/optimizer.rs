@@ -0,0 +1,267 @@
+//! Mesh optimization algorithms. See [`super::surface::SurfaceData::optimize_vertex_cache`] and
+//! [`super::surface::SurfaceData::deduplicate_vertices`].
+
+use crate::core::math::TriangleDefinition;
+use fxhash::FxHashMap;
+
+// Parameters of the "Linear-Speed Vertex Cache Optimisation" algorithm by Tom Forsyth. Simulated
+// cache size is larger than the actual post-transform cache of most GPUs, it works better in
+// practice.
+const CACHE_SIZE: usize = 32;
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+fn vertex_score(cache_position: Option<usize>, remaining_valence: u32) -> f32 {
+    if remaining_valence == 0 {
+        // The vertex is not used by any of the remaining triangles.
+        return -1.0;
+    }
+
+    let cache_score = match cache_position {
+        None => 0.0,
+        // Vertices of the last triangle are scored lower intentionally, otherwise the algorithm
+        // tends to produce long thin strips.
+        Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+        Some(position) => {
+            let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scale).powf(CACHE_DECAY_POWER)
+        }
+    };
+
+    // Boost vertices with few remaining triangles, so lonely triangles won't be left at the end.
+    cache_score + VALENCE_BOOST_SCALE * (remaining_valence as f32).powf(-VALENCE_BOOST_POWER)
+}
+
+/// Reorders triangles to maximize the hit rate of post-transform vertex cache of a GPU.
+pub(super) fn optimize_triangle_order(
+    triangles: &[TriangleDefinition],
+    vertex_count: usize,
+) -> Vec<TriangleDefinition> {
+    let mut valence = vec![0u32; vertex_count];
+    for triangle in triangles {
+        for &index in triangle.0.iter() {
+            valence[index as usize] += 1;
+        }
+    }
+
+    // Triangles adjacent to every vertex, packed in a single array.
+    let mut offsets = Vec::with_capacity(vertex_count + 1);
+    let mut total = 0;
+    for &count in valence.iter() {
+        offsets.push(total);
+        total += count as usize;
+    }
+    offsets.push(total);
+
+    let mut adjacency = vec![0; total];
+    let mut fill = offsets.clone();
+    for (triangle_index, triangle) in triangles.iter().enumerate() {
+        for &index in triangle.0.iter() {
+            adjacency[fill[index as usize]] = triangle_index;
+            fill[index as usize] += 1;
+        }
+    }
+
+    let mut vertex_scores = valence
+        .iter()
+        .map(|&valence| vertex_score(None, valence))
+        .collect::<Vec<_>>();
+    let triangle_score = |triangle: &TriangleDefinition, vertex_scores: &[f32]| {
+        triangle
+            .0
+            .iter()
+            .map(|&index| vertex_scores[index as usize])
+            .sum::<f32>()
+    };
+
+    let mut emitted = vec![false; triangles.len()];
+    let mut result = Vec::with_capacity(triangles.len());
+    let mut cache = Vec::<u32>::with_capacity(CACHE_SIZE + 3);
+    let mut best_triangle = None;
+    let mut cursor = 0;
+
+    while result.len() < triangles.len() {
+        let current = match best_triangle {
+            Some(best_triangle) => best_triangle,
+            None => {
+                // Nothing in the cache is useful, continue with the first remaining triangle.
+                while emitted[cursor] {
+                    cursor += 1;
+                }
+                cursor
+            }
+        };
+
+        emitted[current] = true;
+        let triangle = &triangles[current];
+        result.push(triangle.clone());
+
+        let mut new_cache = Vec::with_capacity(CACHE_SIZE + 3);
+        for &index in triangle.0.iter() {
+            valence[index as usize] -= 1;
+            if !new_cache.contains(&index) {
+                new_cache.push(index);
+            }
+        }
+        for &index in cache.iter() {
+            if !new_cache.contains(&index) {
+                new_cache.push(index);
+            }
+        }
+
+        for (position, &index) in new_cache.iter().enumerate() {
+            let cache_position = if position < CACHE_SIZE {
+                Some(position)
+            } else {
+                None
+            };
+            vertex_scores[index as usize] = vertex_score(cache_position, valence[index as usize]);
+        }
+
+        // Only triangles that use the cached vertices could have their score changed.
+        best_triangle = None;
+        let mut best_score = f32::MIN;
+        for &index in new_cache.iter() {
+            let index = index as usize;
+            for &triangle_index in &adjacency[offsets[index]..offsets[index + 1]] {
+                if !emitted[triangle_index] {
+                    let score = triangle_score(&triangles[triangle_index], &vertex_scores);
+                    if score > best_score {
+                        best_score = score;
+                        best_triangle = Some(triangle_index);
+                    }
+                }
+            }
+        }
+
+        new_cache.truncate(CACHE_SIZE);
+        cache = new_cache;
+    }
+
+    result
+}
+
+/// Returns a vertex remapping table that makes vertices ordered by their first use in the given
+/// triangles and rewrites triangle indices accordingly. Unused vertices are removed. Such order
+/// improves the hit rate of pre-transform vertex cache (memory fetches).
+pub(super) fn optimize_vertex_order(triangles: &mut [TriangleDefinition]) -> Vec<u32> {
+    let mut new_indices = FxHashMap::default();
+    let mut remap = Vec::new();
+    for triangle in triangles.iter_mut() {
+        for index in triangle.0.iter_mut() {
+            *index = *new_indices.entry(*index).or_insert_with(|| {
+                remap.push(*index);
+                remap.len() as u32 - 1
+            });
+        }
+    }
+    remap
+}
+
+/// Returns a vertex remapping table that keeps only unique vertices (with exactly the same bytes)
+/// and rewrites triangle indices accordingly.
+pub(super) fn deduplicate_vertices(
+    raw_data: &[u8],
+    vertex_size: usize,
+    triangles: &mut [TriangleDefinition],
+) -> Vec<u32> {
+    let mut unique = FxHashMap::default();
+    let mut remap = Vec::new();
+    let new_indices = raw_data
+        .chunks_exact(vertex_size)
+        .enumerate()
+        .map(|(index, vertex)| {
+            *unique.entry(vertex).or_insert_with(|| {
+                remap.push(index as u32);
+                remap.len() as u32 - 1
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for triangle in triangles.iter_mut() {
+        for index in triangle.0.iter_mut() {
+            *index = new_indices[*index as usize];
+        }
+    }
+
+    remap
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Simulates FIFO post-transform cache and returns average amount of cache misses per triangle.
+    fn acmr(triangles: &[TriangleDefinition], cache_size: usize) -> f32 {
+        let mut cache = Vec::new();
+        let mut misses = 0;
+        for triangle in triangles {
+            for index in triangle.0.iter() {
+                if !cache.contains(index) {
+                    misses += 1;
+                    cache.push(*index);
+                    if cache.len() > cache_size {
+                        cache.remove(0);
+                    }
+                }
+            }
+        }
+        misses as f32 / triangles.len() as f32
+    }
+
+    fn grid(size: u32) -> Vec<TriangleDefinition> {
+        let mut triangles = Vec::new();
+        // Column-major order is the worst case for the cache.
+        for x in 0..size {
+            for y in 0..size {
+                let i0 = y * (size + 1) + x;
+                let i1 = i0 + 1;
+                let i2 = i0 + size + 1;
+                let i3 = i2 + 1;
+                triangles.push(TriangleDefinition([i0, i2, i1]));
+                triangles.push(TriangleDefinition([i1, i2, i3]));
+            }
+        }
+        triangles
+    }
+
+    #[test]
+    fn test_optimize_triangle_order() {
+        let size = 64;
+        let triangles = grid(size);
+        let vertex_count = ((size + 1) * (size + 1)) as usize;
+
+        let optimized = optimize_triangle_order(&triangles, vertex_count);
+        assert_eq!(optimized.len(), triangles.len());
+
+        let mut sorted = optimized.clone();
+        sorted.sort_by_key(|t| t.0);
+        let mut expected = triangles.clone();
+        expected.sort_by_key(|t| t.0);
+        assert_eq!(sorted, expected);
+
+        assert!(acmr(&optimized, 16) < acmr(&triangles, 16));
+    }
+
+    #[test]
+    fn test_deduplicate_and_reorder_vertices() {
+        let vertices = [1u8, 2, 3, 1, 2, 4];
+        let mut triangles = vec![TriangleDefinition([2, 0, 1]), TriangleDefinition([3, 4, 5])];
+
+        let remap = deduplicate_vertices(&vertices, 1, &mut triangles);
+        assert_eq!(remap, vec![0, 1, 2, 5]);
+        assert_eq!(
+            triangles,
+            vec![TriangleDefinition([2, 0, 1]), TriangleDefinition([0, 1, 3])]
+        );
+
+        let remap = optimize_vertex_order(&mut triangles);
+        assert_eq!(remap, vec![2, 0, 1, 3]);
+        assert_eq!(
+            triangles,
+            vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([1, 2, 3])]
+        );
+    }
+}
@@ -22,6 +22,7 @@ use crate::{
                 TriangleBuffer, VertexAttributeUsage, VertexBuffer, VertexFetchError,
                 VertexReadTrait, VertexWriteTrait,
             },
+            optimizer,
             vertex::StaticVertex,
         },
         node::Node,
@@ -33,7 +34,7 @@ use crate::{
 };
 use fxhash::{FxHashMap, FxHasher};
 use half::f16;
-use std::{hash::Hasher, sync::Arc};
+use std::{hash::Hasher, ops::Range, sync::Arc};
 
 /// A target shape for blending.
 #[derive(Debug, Clone, Visit, Reflect, PartialEq)]
@@ -341,6 +342,47 @@ impl SurfaceData {
         Ok(())
     }
 
+    /// Merges vertices with exactly the same content into one. Meshes exported from DCC tools often
+    /// have lots of duplicated vertices, merging them reduces memory usage and improves efficiency of
+    /// vertex cache of a GPU.
+    ///
+    /// Does nothing if the surface has blend shapes, because blend shapes refer to vertices by their
+    /// indices.
+    pub fn deduplicate_vertices(&mut self) {
+        if self.blend_shapes_container.is_some() {
+            return;
+        }
+
+        let mut triangles = self.geometry_buffer.triangles_ref().to_vec();
+        let remap = optimizer::deduplicate_vertices(
+            self.vertex_buffer.raw_data(),
+            self.vertex_buffer.vertex_size() as usize,
+            &mut triangles,
+        );
+        self.vertex_buffer.modify().remap(&remap);
+        self.geometry_buffer.set_triangles(triangles);
+    }
+
+    /// Reorders triangles and vertices of the surface to maximize efficiency of vertex caches of a GPU
+    /// (using "Linear-Speed Vertex Cache Optimisation" algorithm by Tom Forsyth). Vertices that are not
+    /// used by any triangle are removed. The visual result stays the same.
+    ///
+    /// Does nothing if the surface has blend shapes, because blend shapes refer to vertices by their
+    /// indices.
+    pub fn optimize_vertex_cache(&mut self) {
+        if self.blend_shapes_container.is_some() {
+            return;
+        }
+
+        let mut triangles = optimizer::optimize_triangle_order(
+            self.geometry_buffer.triangles_ref(),
+            self.vertex_buffer.vertex_count() as usize,
+        );
+        let remap = optimizer::optimize_vertex_order(&mut triangles);
+        self.vertex_buffer.modify().remap(&remap);
+        self.geometry_buffer.set_triangles(triangles);
+    }
+
     /// Creates a quad oriented on oXY plane with unit width and height.
     pub fn make_unit_xy_quad() -> Self {
         let vertices = vec![
@@ -500,6 +542,79 @@ impl SurfaceData {
         Ok(())
     }
 
+    /// Recalculates smooth (area-weighted) normals of every vertex that shares a triangle with at least
+    /// one vertex from the given range. It is intended to be used after deformation of a range of
+    /// vertices (see [`VertexBuffer::modify_range`]), only the affected part of the vertex buffer will
+    /// be uploaded to GPU.
+    pub fn recalculate_normals(&mut self, range: Range<usize>) -> Result<(), VertexFetchError> {
+        let vertex_count = self.vertex_buffer.vertex_count() as usize;
+        let end = range.end.min(vertex_count);
+        let start = range.start.min(end);
+        if start == end {
+            return Ok(());
+        }
+
+        // Find every vertex, whose normal depends on the modified vertices.
+        let mut affected = vec![false; vertex_count];
+        for triangle in self.geometry_buffer.iter() {
+            if triangle
+                .0
+                .iter()
+                .any(|&i| (start..end).contains(&(i as usize)))
+            {
+                for &i in triangle.0.iter() {
+                    affected[i as usize] = true;
+                }
+            }
+        }
+
+        let mut normals = vec![Vector3::<f32>::default(); vertex_count];
+        for triangle in self.geometry_buffer.iter() {
+            if triangle.0.iter().any(|&i| affected[i as usize]) {
+                let a = self
+                    .vertex_buffer
+                    .get(triangle[0] as usize)
+                    .unwrap()
+                    .read_3_f32(VertexAttributeUsage::Position)?;
+                let b = self
+                    .vertex_buffer
+                    .get(triangle[1] as usize)
+                    .unwrap()
+                    .read_3_f32(VertexAttributeUsage::Position)?;
+                let c = self
+                    .vertex_buffer
+                    .get(triangle[2] as usize)
+                    .unwrap()
+                    .read_3_f32(VertexAttributeUsage::Position)?;
+
+                // Non-normalized cross product, so larger triangles have more influence.
+                let normal = (b - a).cross(&(c - a));
+                for &i in triangle.0.iter() {
+                    normals[i as usize] += normal;
+                }
+            }
+        }
+
+        if let (Some(first), Some(last)) = (
+            affected.iter().position(|a| *a),
+            affected.iter().rposition(|a| *a),
+        ) {
+            let mut vertex_buffer = self.vertex_buffer.modify_range(first..last + 1);
+            for i in first..=last {
+                if affected[i] {
+                    if let Some(normal) = normals[i].try_normalize(f32::EPSILON) {
+                        vertex_buffer
+                            .get_mut(i)
+                            .unwrap()
+                            .write_3_f32(VertexAttributeUsage::Normal, normal)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Creates sphere of specified radius with given slices and stacks. The larger the `slices` and `stacks`, the smoother the sphere will be.
     /// Typical values are [16..32]. The sphere is then transformed by the given transformation matrix, which could be [`Matrix4::identity`]
     /// to not modify the sphere at all.
@@ -1150,7 +1265,10 @@ impl SurfaceSharedData {
 
     /// Creates a deep clone of the data.
     pub fn deep_clone(&self) -> Self {
-        Self::new(self.lock().clone())
+        let mut data = self.lock().clone();
+        // The copy must not share GPU resources with the original data.
+        data.cache_entry = AtomicIndex::unassigned();
+        Self::new(data)
     }
 
     /// Returns total amount of uses of the shared data.
@@ -1367,6 +1485,51 @@ impl Surface {
     pub fn set_unique_material(&mut self, unique: bool) {
         self.unique_material.set_value_and_mark_modified(unique);
     }
+
+    /// Makes vertex and index data of the surface unique, so it could be modified without affecting
+    /// other surfaces that share the same data (for example, other instances of the same model). Does
+    /// nothing if the data is used only by this surface. Returns the data of the surface.
+    ///
+    /// # Deformation
+    ///
+    /// Use this method before modifying vertices of a surface instance on CPU side. The modification
+    /// itself could be done via [`VertexBuffer::modify_range`], which allows the renderer to upload
+    /// only the changed part of the vertices to GPU, normals could be recalculated after that using
+    /// [`SurfaceData::recalculate_normals`]:
+    ///
+    /// ```
+    /// use fyrox::{
+    ///     core::algebra::Vector3,
+    ///     scene::mesh::{
+    ///         buffer::{VertexAttributeUsage, VertexReadTrait, VertexWriteTrait},
+    ///         surface::Surface,
+    ///     },
+    /// };
+    ///
+    /// fn push_vertices(surface: &mut Surface, range: std::ops::Range<usize>, offset: Vector3<f32>) {
+    ///     let data = surface.make_unique_data();
+    ///     let mut data = data.lock();
+    ///
+    ///     let mut vertices = data.vertex_buffer.modify_range(range.clone());
+    ///     for mut vertex in vertices.iter_mut() {
+    ///         let position = vertex.read_3_f32(VertexAttributeUsage::Position).unwrap();
+    ///         vertex
+    ///             .write_3_f32(VertexAttributeUsage::Position, position + offset)
+    ///             .unwrap();
+    ///     }
+    ///     drop(vertices);
+    ///
+    ///     data.recalculate_normals(range).unwrap();
+    /// }
+    /// ```
+    pub fn make_unique_data(&mut self) -> SurfaceSharedData {
+        if self.data.use_count() > 1 {
+            // Deformed data is a runtime state, so it must not be saved as a modified property.
+            let unique = self.data.deep_clone();
+            self.data.set_value_silent(unique);
+        }
+        (*self.data).clone()
+    }
 }
 
 /// Surface builder allows you to create surfaces in declarative manner.
@@ -8,13 +8,16 @@ pub mod accel;
 pub mod animation;
 pub mod base;
 pub mod camera;
+pub mod cloth;
 pub mod collider;
 pub mod debug;
 pub mod decal;
 pub mod dim2;
+pub mod foliage;
 pub mod graph;
 pub mod joint;
 pub mod light;
+pub mod lines;
 pub mod loader;
 pub mod mesh;
 pub mod navmesh;
@@ -23,10 +26,15 @@ pub mod particle_system;
 pub mod pivot;
 pub mod ragdoll;
 pub mod rigidbody;
+pub mod sky;
 pub mod sound;
 pub mod sprite;
 pub mod terrain;
+pub mod trail;
 pub mod transform;
+pub mod update_culling;
+pub mod validation;
+pub mod water;
 
 use crate::{
     asset::{self, manager::ResourceManager, untyped::UntypedResource},
@@ -41,7 +49,10 @@ use crate::{
         visitor::{Visit, VisitError, VisitResult, Visitor},
     },
     engine::SerializationContext,
-    material::{shader::SamplerFallback, PropertyValue},
+    material::{
+        shader::{SamplerFallback, Shader, ShaderResource},
+        PropertyValue,
+    },
     renderer::framework::state::PolygonFillMode,
     resource::texture::TextureResource,
     scene::{
@@ -49,6 +60,7 @@ use crate::{
         camera::Camera,
         debug::SceneDrawingContext,
         graph::{map::NodeHandleMap, Graph, GraphPerformanceStatistics, GraphUpdateSwitches},
+        loader::SceneLoadingPhase,
         mesh::{
             buffer::{
                 VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
@@ -58,9 +70,10 @@ use crate::{
         },
         navmesh::NavigationalMeshBuilder,
         node::Node,
+        sky::Sky,
         sound::SoundEngine,
     },
-    utils::{lightmap::Lightmap, navmesh::Navmesh},
+    utils::{baking::IrradianceVolume, lightmap::Lightmap, navmesh::Navmesh},
 };
 use fxhash::{FxHashMap, FxHashSet};
 use std::path::PathBuf;
@@ -172,6 +185,10 @@ pub struct Scene {
     /// Current lightmap.
     lightmap: Option<Lightmap>,
 
+    /// Light probes that are used to light dynamic objects.
+    #[reflect(hidden)]
+    light_probes: Option<IrradianceVolume>,
+
     /// Performance statistics from last `update` call.
     #[reflect(hidden)]
     pub performance_statistics: PerformanceStatistics,
@@ -199,6 +216,7 @@ impl Default for Scene {
             graph: Default::default(),
             render_target: None,
             lightmap: None,
+            light_probes: None,
             drawing_context: Default::default(),
             performance_statistics: Default::default(),
             ambient_lighting_color: Color::opaque(100, 100, 100),
@@ -228,7 +246,8 @@ impl Display for PerformanceStatistics {
             \tPhysics 2D: {:?}\n\
             \t\tSimulation: {:?}\n\
             \t\tRay cast: {:?}\n\
-            \tHierarchy: {:?}",
+            \tHierarchy: {:?}\n\
+            \tAnimation: {:?}",
             self.graph.total(),
             self.graph.sync_time,
             self.graph.sound_update_time,
@@ -239,6 +258,7 @@ impl Display for PerformanceStatistics {
             self.graph.physics2d.step_time,
             self.graph.physics2d.total_ray_cast_time.get(),
             self.graph.hierarchical_properties_time,
+            self.graph.animation_sampling_time,
         )
     }
 }
@@ -292,6 +312,14 @@ impl SceneLoader {
 
     /// Finishes scene loading.
     pub async fn finish(self) -> Scene {
+        self.finish_with_progress(|_| ()).await
+    }
+
+    /// Same as [`Self::finish`], but reports loading progress using the given callback.
+    pub async fn finish_with_progress<F>(self, mut on_progress: F) -> Scene
+    where
+        F: FnMut(SceneLoadingPhase),
+    {
         let mut scene = self.scene;
 
         Log::info("SceneLoader::finish() - Collecting resources used by the scene...");
@@ -318,8 +346,20 @@ impl SceneLoader {
             used_resources_count
         ));
 
-        // Wait everything.
-        join_all(used_resources.into_iter()).await;
+        on_progress(SceneLoadingPhase::LoadingResources {
+            loaded: 0,
+            total: used_resources_count,
+        });
+
+        // Wait everything. Resources are already loading at this point, so waiting them one-by-one
+        // does not slow down the loading, but allows to track the progress.
+        for (i, resource) in used_resources.into_iter().enumerate() {
+            resource.await;
+            on_progress(SceneLoadingPhase::LoadingResources {
+                loaded: i + 1,
+                total: used_resources_count,
+            });
+        }
 
         Log::info(format!(
             "SceneLoader::finish() - All {} resources have finished loading.",
@@ -339,9 +379,16 @@ impl SceneLoader {
         }
         join_all(skybox_textures).await;
 
+        on_progress(SceneLoadingPhase::Resolving);
+
         // And do resolve to extract correct graphical data and so on.
         scene.resolve();
 
+        let report = scene.validate();
+        for warning in report.warnings.iter() {
+            Log::warn(format!("SceneLoader::finish() - {}", warning));
+        }
+
         scene
     }
 }
@@ -360,6 +407,7 @@ impl Scene {
             graph: Graph::new(),
             render_target: None,
             lightmap: None,
+            light_probes: None,
             drawing_context: Default::default(),
             performance_statistics: Default::default(),
             ambient_lighting_color: Color::opaque(100, 100, 100),
@@ -476,6 +524,29 @@ impl Scene {
         collection
     }
 
+    /// Collects all shaders used by the scene - both by materials of mesh surfaces and by any other
+    /// entity that references a shader resource (see [`Self::collect_used_resources`]). It could be
+    /// used to compile every shader of the scene before it is shown.
+    pub fn collect_used_shaders(&self) -> FxHashSet<ShaderResource> {
+        let mut shaders = self
+            .collect_used_resources()
+            .into_iter()
+            .filter_map(|resource| resource.try_cast::<Shader>())
+            .collect::<FxHashSet<_>>();
+
+        for mesh in self
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Mesh>())
+        {
+            for surface in mesh.surfaces() {
+                shaders.insert(surface.material().lock().shader().clone());
+            }
+        }
+
+        shaders
+    }
+
     /// Tries to set new lightmap to scene.
     pub fn set_lightmap(&mut self, lightmap: Lightmap) -> Result<Option<Lightmap>, &'static str> {
         // Assign textures to surfaces.
@@ -510,12 +581,43 @@ impl Scene {
         Ok(std::mem::replace(&mut self.lightmap, Some(lightmap)))
     }
 
+    /// Returns current lightmap (if any).
+    pub fn lightmap(&self) -> Option<&Lightmap> {
+        self.lightmap.as_ref()
+    }
+
+    /// Sets new light probes of the scene and returns previous ones. Light probes provide ambient
+    /// lighting for meshes that are not lightmapped (usually dynamic objects, such as characters),
+    /// so they match lighting of lightmapped static geometry. Every such mesh is lit by the probes
+    /// interpolated at the center of its bounding box. Probes are added to [`Self::ambient_lighting_color`],
+    /// so it should be lowered (or set to black) when light probes are used.
+    ///
+    /// Light probes could be baked using [`IrradianceVolume::bake`].
+    pub fn set_light_probes(
+        &mut self,
+        light_probes: Option<IrradianceVolume>,
+    ) -> Option<IrradianceVolume> {
+        std::mem::replace(&mut self.light_probes, light_probes)
+    }
+
+    /// Returns current light probes of the scene (if any).
+    pub fn light_probes(&self) -> Option<&IrradianceVolume> {
+        self.light_probes.as_ref()
+    }
+
     /// Performs single update tick with given delta time from last frame. Internally
     /// it updates physics, animations, and each graph node. In most cases there is
     /// no need to call it directly, engine automatically updates all available scenes.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32, switches: GraphUpdateSwitches) {
         self.graph.update(frame_size, dt, switches);
         self.performance_statistics.graph = self.graph.performance_statistics.clone();
+
+        if let Some(sky) = self.graph.linear_iter().find_map(|node| {
+            node.cast::<Sky>()
+                .filter(|sky| sky.is_globally_enabled() && sky.is_update_ambient())
+        }) {
+            self.ambient_lighting_color = sky.ambient_color();
+        }
     }
 
     /// Creates deep copy of a scene, filter predicate allows you to filter out nodes
@@ -543,6 +645,7 @@ impl Scene {
                 // will redraw frame completely.
                 render_target: Default::default(),
                 lightmap,
+                light_probes: self.light_probes.clone(),
                 drawing_context: self.drawing_context.clone(),
                 performance_statistics: Default::default(),
                 ambient_lighting_color: self.ambient_lighting_color,
@@ -553,11 +656,26 @@ impl Scene {
         )
     }
 
+    /// Turns the given scene into an immutable copy of this scene, that is used only for rendering.
+    /// See [`Graph::copy_for_rendering`] for more info. Returns the amount of copied nodes.
+    pub(crate) fn copy_for_rendering(&mut self, copy: &mut Scene) -> usize {
+        let copied = self.graph.copy_for_rendering(&mut copy.graph);
+        copy.render_target = self.render_target.clone();
+        copy.drawing_context.clone_from(&self.drawing_context);
+        copy.lightmap.clone_from(&self.lightmap);
+        copy.light_probes.clone_from(&self.light_probes);
+        copy.ambient_lighting_color = self.ambient_lighting_color;
+        copy.enabled = self.enabled;
+        copy.polygon_rasterization_mode = self.polygon_rasterization_mode;
+        copied
+    }
+
     fn visit(&mut self, region_name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut region = visitor.enter_region(region_name)?;
 
         self.graph.visit("Graph", &mut region)?;
         self.lightmap.visit("Lightmap", &mut region)?;
+        let _ = self.light_probes.visit("LightProbes", &mut region);
         self.ambient_lighting_color
             .visit("AmbientLightingColor", &mut region)?;
         self.enabled.visit("Enabled", &mut region)?;
@@ -150,6 +150,10 @@ impl NodeTrait for NavigationalMesh {
         Self::type_uuid()
     }
 
+    fn updates_render_data(&self) -> bool {
+        false
+    }
+
     fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
         for vertex in self.navmesh.vertices().iter() {
             ctx.draw_sphere(vertex.position, 6, 6, 0.1, Color::GREEN);
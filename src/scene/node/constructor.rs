@@ -6,18 +6,24 @@ use crate::{
         self,
         animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
         camera::Camera,
+        cloth::Cloth,
         decal::Decal,
         dim2::{self, rectangle::Rectangle},
+        foliage::Foliage,
         light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
+        lines::Lines,
         mesh::Mesh,
         navmesh::NavigationalMesh,
         node::{Node, NodeTrait},
         particle_system::ParticleSystem,
         pivot::Pivot,
         ragdoll::Ragdoll,
+        sky::Sky,
         sound::{listener::Listener, Sound},
         sprite::Sprite,
         terrain::Terrain,
+        trail::Trail,
+        water::Water,
     },
 };
 use fxhash::FxHashMap;
@@ -40,6 +46,7 @@ impl NodeConstructorContainer {
         container.add::<dim2::joint::Joint>();
         container.add::<Rectangle>();
         container.add::<dim2::rigidbody::RigidBody>();
+        container.add::<dim2::tilemap::TileMap>();
         container.add::<DirectionalLight>();
         container.add::<PointLight>();
         container.add::<SpotLight>();
@@ -59,6 +66,12 @@ impl NodeConstructorContainer {
         container.add::<AnimationBlendingStateMachine>();
         container.add::<NavigationalMesh>();
         container.add::<Ragdoll>();
+        container.add::<Foliage>();
+        container.add::<Water>();
+        container.add::<Sky>();
+        container.add::<Trail>();
+        container.add::<Lines>();
+        container.add::<Cloth>();
 
         container
     }
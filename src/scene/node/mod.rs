@@ -33,6 +33,7 @@ use crate::{
         sound::{context::SoundContext, listener::Listener, Sound},
         sprite::Sprite,
         terrain::Terrain,
+        update_culling::UpdateObserver,
         Scene,
     },
 };
@@ -104,6 +105,9 @@ pub struct UpdateContext<'a> {
     pub physics2d: &'a mut dim2::physics::PhysicsWorld,
     /// A mutable reference to sound context.
     pub sound_context: &'a mut SoundContext,
+    /// A set of observers (enabled cameras) of the graph, that could be used for update culling.
+    /// See [`crate::scene::update_culling`] module docs for more info.
+    pub observers: &'a [UpdateObserver],
 }
 
 /// Implements [`NodeTrait::query_component_ref`] and [`NodeTrait::query_component_mut`] in a much
@@ -200,6 +204,14 @@ pub trait NodeTrait: BaseNodeTrait + Reflect + Visit {
     /// Updates internal state of the node.
     fn update(&mut self, #[allow(unused_variables)] context: &mut UpdateContext) {}
 
+    /// Returns `true` if [`Self::update`] could change the data of the node, that is used for rendering
+    /// (for example, particle systems simulate their particles on every update). Such nodes are copied to
+    /// the render snapshot after every update, the rest of the nodes are copied only when they're changed
+    /// from the outside. See [`crate::engine::Engine::set_pipelined_rendering`] for more info.
+    fn updates_render_data(&self) -> bool {
+        true
+    }
+
     /// Allows the node to emit a set of render data. This is a high-level rendering method which can only
     /// do culling and provide render data. Render data is just a surface (vertex + index buffers) and a
     /// material.
@@ -309,7 +321,7 @@ impl Deref for Node {
 
 impl DerefMut for Node {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.0.deref_mut()
+        self.tracked_mut()
     }
 }
 
@@ -379,7 +391,7 @@ impl Node {
     /// ```
     #[inline]
     pub fn cast_mut<T: NodeTrait>(&mut self) -> Option<&mut T> {
-        self.0.as_any_ref_mut().downcast_mut::<T>()
+        self.tracked_mut().as_any_ref_mut().downcast_mut::<T>()
     }
 
     /// Allows a node to provide access to a component of specified type.
@@ -433,11 +445,37 @@ impl Node {
     where
         T: 'static,
     {
-        self.0
+        self.tracked_mut()
             .query_component_mut(TypeId::of::<T>())
             .and_then(|c| c.downcast_mut::<T>())
     }
 
+    // Every mutable access to the node must be done through this method, so the node will be copied to
+    // the render snapshot again. See [`crate::scene::graph::Graph::copy_for_rendering`].
+    fn tracked_mut(&mut self) -> &mut dyn NodeTrait {
+        let node = self.0.deref_mut();
+        node.render_data_modified.set(true);
+        node
+    }
+
+    /// Gives mutable access to the node without marking it as modified for the render snapshot. It must
+    /// be used only to change the data, that is not used for rendering (scripts, internal state of
+    /// animation players, etc.), or the data, that is tracked separately (see [`Self::updates_render_data`]).
+    pub(crate) fn untracked_mut(&mut self) -> &mut dyn NodeTrait {
+        self.0.deref_mut()
+    }
+
+    /// Creates raw copy of the node (see [`BaseNodeTrait::clone_box`]) for the render snapshot. The script
+    /// of the node is not copied and the copy is not connected to any graph.
+    pub(crate) fn clone_for_rendering(&mut self) -> Node {
+        // The script is moved out of the node temporarily, so it won't be cloned.
+        let script = self.untracked_mut().script.take();
+        let mut copy = self.0.clone_box();
+        self.untracked_mut().script = script;
+        copy.untracked_mut().script_message_sender = None;
+        copy
+    }
+
     pub(crate) fn mark_inheritable_variables_as_modified(&mut self) {
         variable::mark_inheritable_properties_modified(self)
     }
@@ -469,7 +507,7 @@ impl Node {
 
 impl Visit for Node {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
-        self.0.visit(name, visitor)
+        self.tracked_mut().visit(name, visitor)
     }
 }
 
@@ -495,7 +533,7 @@ impl Reflect for Node {
     }
 
     fn as_any_mut(&mut self, func: &mut dyn FnMut(&mut dyn Any)) {
-        self.0.deref_mut().as_any_mut(func)
+        self.tracked_mut().as_any_mut(func)
     }
 
     fn as_reflect(&self, func: &mut dyn FnMut(&dyn Reflect)) {
@@ -503,11 +541,11 @@ impl Reflect for Node {
     }
 
     fn as_reflect_mut(&mut self, func: &mut dyn FnMut(&mut dyn Reflect)) {
-        self.0.deref_mut().as_reflect_mut(func)
+        self.tracked_mut().as_reflect_mut(func)
     }
 
     fn set(&mut self, value: Box<dyn Reflect>) -> Result<Box<dyn Reflect>, Box<dyn Reflect>> {
-        self.0.deref_mut().set(value)
+        self.tracked_mut().set(value)
     }
 
     fn set_field(
@@ -516,7 +554,7 @@ impl Reflect for Node {
         value: Box<dyn Reflect>,
         func: &mut dyn FnMut(Result<Box<dyn Reflect>, Box<dyn Reflect>>),
     ) {
-        self.0.deref_mut().set_field(field, value, func)
+        self.tracked_mut().set_field(field, value, func)
     }
 
     fn fields(&self, func: &mut dyn FnMut(Vec<&dyn Reflect>)) {
@@ -524,7 +562,7 @@ impl Reflect for Node {
     }
 
     fn fields_mut(&mut self, func: &mut dyn FnMut(Vec<&mut dyn Reflect>)) {
-        self.0.deref_mut().fields_mut(func)
+        self.tracked_mut().fields_mut(func)
     }
 
     fn field(&self, name: &str, func: &mut dyn FnMut(Option<&dyn Reflect>)) {
@@ -532,7 +570,7 @@ impl Reflect for Node {
     }
 
     fn field_mut(&mut self, name: &str, func: &mut dyn FnMut(Option<&mut dyn Reflect>)) {
-        self.0.deref_mut().field_mut(name, func)
+        self.tracked_mut().field_mut(name, func)
     }
 }
 
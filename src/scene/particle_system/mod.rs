@@ -24,6 +24,7 @@ use crate::{
             emitter::{Emit, Emitter},
             particle::Particle,
         },
+        update_culling::UpdateCulling,
     },
 };
 use std::{
@@ -206,6 +207,10 @@ pub struct ParticleSystem {
 
     #[visit(optional)]
     rng: ParticleSystemRng,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_update_culling")]
+    update_culling: InheritableVariable<UpdateCulling>,
 }
 
 impl Deref for ParticleSystem {
@@ -262,6 +267,18 @@ impl ParticleSystem {
         *self.is_playing
     }
 
+    /// Sets new update culling settings, that could be used to pause or slow down the simulation of
+    /// particles when the particle system is not visible. See [`UpdateCulling`] docs for more info.
+    pub fn set_update_culling(&mut self, update_culling: UpdateCulling) -> UpdateCulling {
+        self.update_culling
+            .set_value_and_mark_modified(update_culling)
+    }
+
+    /// Returns current update culling settings.
+    pub fn update_culling(&self) -> &UpdateCulling {
+        &self.update_culling
+    }
+
     /// Sets soft boundary sharpness factor. This value defines how wide soft boundary will be.
     /// The greater the factor is the more thin the boundary will be, and vice versa. This
     /// parameter allows you to manipulate particle "softness" - the engine automatically adds
@@ -488,10 +505,17 @@ impl NodeTrait for ParticleSystem {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
-        let dt = context.dt;
-
-        if *self.is_playing {
-            self.tick(dt);
+        let bounds = self.world_bounding_box();
+        let dt = self.update_culling.get_value_mut_silent().update(
+            &bounds,
+            context.observers,
+            context.dt,
+        );
+
+        if let Some(dt) = dt {
+            if *self.is_playing {
+                self.tick(dt);
+            }
         }
     }
 }
@@ -508,6 +532,7 @@ pub struct ParticleSystemBuilder {
     soft_boundary_sharpness_factor: f32,
     is_playing: bool,
     rng: ParticleSystemRng,
+    update_culling: UpdateCulling,
 }
 
 impl ParticleSystemBuilder {
@@ -523,6 +548,7 @@ impl ParticleSystemBuilder {
             soft_boundary_sharpness_factor: 2.5,
             is_playing: true,
             rng: ParticleSystemRng::default(),
+            update_culling: Default::default(),
         }
     }
 
@@ -581,6 +607,12 @@ impl ParticleSystemBuilder {
         self
     }
 
+    /// Sets desired update culling settings.
+    pub fn with_update_culling(mut self, update_culling: UpdateCulling) -> Self {
+        self.update_culling = update_culling;
+        self
+    }
+
     fn build_particle_system(self) -> ParticleSystem {
         ParticleSystem {
             base: self.base_builder.build_base(),
@@ -593,6 +625,7 @@ impl ParticleSystemBuilder {
             soft_boundary_sharpness_factor: self.soft_boundary_sharpness_factor.into(),
             is_playing: self.is_playing.into(),
             rng: self.rng,
+            update_culling: self.update_culling.into(),
         }
     }
 
@@ -62,6 +62,10 @@ impl NodeTrait for Pivot {
     fn id(&self) -> Uuid {
         Self::type_uuid()
     }
+
+    fn updates_render_data(&self) -> bool {
+        false
+    }
 }
 
 /// Allows you to create pivot node in declarative manner.
@@ -162,6 +162,10 @@ impl NodeTrait for Ragdoll {
             }
         }
     }
+
+    fn updates_render_data(&self) -> bool {
+        false
+    }
 }
 
 impl Ragdoll {
@@ -0,0 +1,924 @@
+//! Contains all structures and methods to create and manage procedural sky with day-night cycle.
+//!
+//! For more info see [`Sky`].
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        light::directional::DirectionalLight,
+        node::{Node, NodeTrait, UpdateContext},
+    },
+};
+use std::{
+    f32::consts::PI,
+    ops::{Deref, DerefMut},
+};
+
+/// Radius of the planet in meters.
+pub const PLANET_RADIUS: f32 = 6_360_000.0;
+/// Radius of the outer boundary of the atmosphere in meters.
+pub const ATMOSPHERE_RADIUS: f32 = 6_420_000.0;
+/// Height of the observer above the ground in meters.
+pub const OBSERVER_HEIGHT: f32 = 100.0;
+/// Scale height of the Rayleigh scattering (air molecules) in meters.
+pub const RAYLEIGH_SCALE_HEIGHT: f32 = 7994.0;
+/// Scale height of the Mie scattering (aerosols) in meters.
+pub const MIE_SCALE_HEIGHT: f32 = 1200.0;
+/// Rayleigh scattering coefficients at the sea level for red, green and blue wavelengths.
+pub const RAYLEIGH_COEFFICIENTS: Vector3<f32> = Vector3::new(5.5e-6, 13.0e-6, 22.4e-6);
+/// Mie scattering coefficient at the sea level for clear air (turbidity of one).
+pub const MIE_COEFFICIENT: f32 = 21.0e-6;
+
+const VIEW_SAMPLES: usize = 16;
+const LIGHT_SAMPLES: usize = 8;
+
+/// Parameters of the atmosphere, that are used to calculate scattering.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Atmosphere {
+    /// Normalized direction to the sun.
+    pub sun_direction: Vector3<f32>,
+    /// Intensity of the sunlight outside of the atmosphere.
+    pub sun_intensity: f32,
+    /// Amount of aerosols relative to the clear air.
+    pub turbidity: f32,
+    /// Anisotropy of the Mie scattering, defines size of the halo around the sun.
+    pub mie_anisotropy: f32,
+}
+
+fn ray_sphere_exit(origin: Vector3<f32>, direction: Vector3<f32>, radius: f32) -> Option<f32> {
+    let b = origin.dot(&direction);
+    let c = origin.norm_squared() - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        None
+    } else {
+        Some(-b + discriminant.sqrt())
+    }
+}
+
+fn ray_hits_planet(origin: Vector3<f32>, direction: Vector3<f32>) -> Option<f32> {
+    let b = origin.dot(&direction);
+    let c = origin.norm_squared() - PLANET_RADIUS * PLANET_RADIUS;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = -b - discriminant.sqrt();
+    if t > 0.0 {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+impl Atmosphere {
+    fn mie_coefficient(&self) -> f32 {
+        MIE_COEFFICIENT * self.turbidity.max(0.0)
+    }
+
+    fn observer() -> Vector3<f32> {
+        Vector3::new(0.0, PLANET_RADIUS + OBSERVER_HEIGHT, 0.0)
+    }
+
+    // Returns Rayleigh and Mie optical depth along the ray from the given point to the boundary
+    // of the atmosphere, or None if the ray hits the planet.
+    fn optical_depth(&self, origin: Vector3<f32>, direction: Vector3<f32>) -> Option<(f32, f32)> {
+        if ray_hits_planet(origin, direction).is_some() {
+            return None;
+        }
+        let length = ray_sphere_exit(origin, direction, ATMOSPHERE_RADIUS)?;
+        let step = length / LIGHT_SAMPLES as f32;
+        let mut depth_r = 0.0;
+        let mut depth_m = 0.0;
+        for i in 0..LIGHT_SAMPLES {
+            let p = origin + direction * ((i as f32 + 0.5) * step);
+            let height = (p.norm() - PLANET_RADIUS).max(0.0);
+            depth_r += (-height / RAYLEIGH_SCALE_HEIGHT).exp() * step;
+            depth_m += (-height / MIE_SCALE_HEIGHT).exp() * step;
+        }
+        Some((depth_r, depth_m))
+    }
+
+    fn extinction(&self, depth_r: f32, depth_m: f32) -> Vector3<f32> {
+        let tau = RAYLEIGH_COEFFICIENTS * depth_r
+            + Vector3::repeat(self.mie_coefficient() * 1.1 * depth_m);
+        tau.map(|t| (-t).exp())
+    }
+
+    /// Calculates radiance of the sky in the given direction using single scattering model. This
+    /// is exactly the same calculation that is done on GPU to render the sky.
+    pub fn sky_radiance(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        let origin = Self::observer();
+        let direction = match direction.try_normalize(f32::EPSILON) {
+            Some(direction) => direction,
+            None => return Vector3::default(),
+        };
+
+        let mut length = match ray_sphere_exit(origin, direction, ATMOSPHERE_RADIUS) {
+            Some(length) => length,
+            None => return Vector3::default(),
+        };
+        if let Some(ground) = ray_hits_planet(origin, direction) {
+            length = length.min(ground);
+        }
+
+        let mu = direction.dot(&self.sun_direction);
+        let phase_r = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+        let g = self.mie_anisotropy.clamp(-0.99, 0.99);
+        let phase_m = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+            / ((2.0 + g * g) * (1.0 + g * g - 2.0 * g * mu).max(1.0e-6).powf(1.5));
+
+        let step = length / VIEW_SAMPLES as f32;
+        let mut depth_r = 0.0;
+        let mut depth_m = 0.0;
+        let mut sum_r = Vector3::default();
+        let mut sum_m = Vector3::default();
+        for i in 0..VIEW_SAMPLES {
+            let p = origin + direction * ((i as f32 + 0.5) * step);
+            let height = (p.norm() - PLANET_RADIUS).max(0.0);
+            let hr = (-height / RAYLEIGH_SCALE_HEIGHT).exp() * step;
+            let hm = (-height / MIE_SCALE_HEIGHT).exp() * step;
+            depth_r += hr;
+            depth_m += hm;
+
+            if let Some((light_r, light_m)) = self.optical_depth(p, self.sun_direction) {
+                let attenuation = self.extinction(depth_r + light_r, depth_m + light_m);
+                sum_r += attenuation * hr;
+                sum_m += attenuation * hm;
+            }
+        }
+
+        (sum_r.component_mul(&RAYLEIGH_COEFFICIENTS) * phase_r
+            + sum_m * (self.mie_coefficient() * phase_m))
+            * self.sun_intensity
+    }
+
+    /// Calculates transmittance of the atmosphere for the sunlight, that reaches the observer.
+    /// Returns zero if the sun is below the horizon.
+    pub fn sun_transmittance(&self) -> Vector3<f32> {
+        match self.optical_depth(Self::observer(), self.sun_direction) {
+            Some((depth_r, depth_m)) => self.extinction(depth_r, depth_m),
+            None => Vector3::default(),
+        }
+    }
+}
+
+/// Calculates direction to the sun in a coordinate system, where Y axis points up, X axis points
+/// to the east and negative Z axis points to the north.
+///
+/// - `time_of_day` - local solar time in hours, `[0; 24)`, where 12 is the noon.
+/// - `day_of_year` - day of the year, `[0; 365)`, defines declination of the sun (seasons).
+/// - `latitude` - latitude of the observer in degrees, positive values are in the northern
+///   hemisphere.
+pub fn sun_direction(time_of_day: f32, day_of_year: f32, latitude: f32) -> Vector3<f32> {
+    let declination = (-23.44f32).to_radians() * (2.0 * PI / 365.0 * (day_of_year + 10.0)).cos();
+    let hour_angle = (15.0 * (time_of_day - 12.0)).to_radians();
+    let latitude = latitude.clamp(-90.0, 90.0).to_radians();
+
+    let east = -declination.cos() * hour_angle.sin();
+    let north =
+        latitude.cos() * declination.sin() - latitude.sin() * declination.cos() * hour_angle.cos();
+    let up =
+        latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+
+    Vector3::new(east, up, -north)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(Vector3::y)
+}
+
+fn linear_to_color(linear: Vector3<f32>) -> Color {
+    Color::from(linear.map(|c| c.max(0.0).powf(1.0 / 2.2)))
+}
+
+/// Part of a day.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DayPhase {
+    /// The sun is more than 6 degrees below the horizon.
+    Night,
+    /// The sun is close to the horizon before the noon.
+    Dawn,
+    /// The sun is more than 6 degrees above the horizon.
+    Day,
+    /// The sun is close to the horizon after the noon.
+    Dusk,
+}
+
+impl DayPhase {
+    /// Defines the phase from elevation of the sun (in radians) and time of day (in hours).
+    pub fn from_sun(elevation: f32, time_of_day: f32) -> Self {
+        let twilight = 6.0f32.to_radians();
+        if elevation < -twilight {
+            Self::Night
+        } else if elevation > twilight {
+            Self::Day
+        } else if time_of_day < 12.0 {
+            Self::Dawn
+        } else {
+            Self::Dusk
+        }
+    }
+}
+
+/// An event, that is produced by the day-night cycle of a [`Sky`]. See [`Sky::take_events`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SkyEvent {
+    /// Part of the day has changed.
+    PhaseChanged {
+        /// Previous phase.
+        old: DayPhase,
+        /// New phase.
+        new: DayPhase,
+    },
+    /// The time of day went past the midnight.
+    NewDay,
+}
+
+/// Procedural sky with physically-based atmospheric scattering and day-night cycle.
+///
+/// # Sun
+///
+/// Position of the sun is calculated from time of day, day of year and latitude of the observer.
+/// Rotation of the sky node defines orientation of the cardinal directions: in local coordinates
+/// of the node, the sun rises in the east (positive X axis) and at the noon it is in the south
+/// (positive Z axis) for the northern hemisphere.
+///
+/// The sky could drive a directional light (see [`Sky::set_sun_light`]): the light is rotated
+/// towards the sun, and its color and intensity are defined by the amount of sunlight, that passes
+/// through the atmosphere. The light should not have a rotated parent, because its local rotation
+/// is changed. Ambient lighting of the scene is updated as well, unless
+/// [`Sky::set_update_ambient`] is set to `false`.
+///
+/// # Scattering
+///
+/// Color of the sky is calculated using single scattering model with Rayleigh (air molecules) and
+/// Mie (aerosols) scattering. Amount of aerosols is defined by [`Sky::turbidity`]. Only one sky
+/// per scene is rendered, it replaces skybox of the cameras.
+///
+/// # Day-night cycle
+///
+/// Time of day advances automatically if [`Sky::day_duration`] is non-zero. Changes of parts of
+/// the day are reported as [`SkyEvent`]s, which could be fetched by [`Sky::take_events`], for
+/// example to turn street lights on at dusk.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::pool::Handle,
+///     scene::{
+///         base::BaseBuilder,
+///         graph::Graph,
+///         light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+///         node::Node,
+///         sky::SkyBuilder,
+///     },
+/// };
+///
+/// fn create_sky(graph: &mut Graph) -> Handle<Node> {
+///     let sun = DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+///         .build(graph);
+///
+///     SkyBuilder::new(BaseBuilder::new())
+///         .with_sun_light(sun)
+///         .with_time_of_day(7.5)
+///         // Full day takes 20 minutes.
+///         .with_day_duration(20.0 * 60.0)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct Sky {
+    base: Base,
+
+    #[reflect(
+        min_value = 0.0,
+        max_value = 24.0,
+        step = 0.1,
+        description = "Local solar time in hours, 12 is the noon.",
+        setter = "set_time_of_day"
+    )]
+    time_of_day: InheritableVariable<f32>,
+
+    #[reflect(
+        min_value = 0.0,
+        step = 1.0,
+        description = "Duration of a full day in seconds. Zero stops the time.",
+        setter = "set_day_duration"
+    )]
+    day_duration: InheritableVariable<f32>,
+
+    #[reflect(
+        min_value = 0.0,
+        max_value = 365.0,
+        step = 1.0,
+        setter = "set_day_of_year"
+    )]
+    day_of_year: InheritableVariable<f32>,
+
+    #[reflect(
+        min_value = -90.0,
+        max_value = 90.0,
+        step = 1.0,
+        description = "Latitude of the observer in degrees.",
+        setter = "set_latitude"
+    )]
+    latitude: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 1.0, setter = "set_sun_intensity")]
+    sun_intensity: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1, setter = "set_turbidity")]
+    turbidity: InheritableVariable<f32>,
+
+    #[reflect(
+        min_value = 0.0,
+        max_value = 0.99,
+        step = 0.01,
+        setter = "set_mie_anisotropy"
+    )]
+    mie_anisotropy: InheritableVariable<f32>,
+
+    #[reflect(
+        min_value = 0.0,
+        step = 0.1,
+        description = "Angular diameter of the sun disk in degrees.",
+        setter = "set_sun_disk_size"
+    )]
+    sun_disk_size: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_night_color")]
+    night_color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_sun_light")]
+    sun_light: InheritableVariable<Handle<Node>>,
+
+    #[reflect(min_value = 0.0, step = 0.1, setter = "set_sun_light_intensity")]
+    sun_light_intensity: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_update_ambient")]
+    update_ambient: InheritableVariable<bool>,
+
+    #[reflect(min_value = 0.0, step = 0.05, setter = "set_ambient_intensity")]
+    ambient_intensity: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    phase: Option<DayPhase>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    events: Vec<SkyEvent>,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            time_of_day: InheritableVariable::new_modified(10.0),
+            day_duration: InheritableVariable::new_modified(0.0),
+            day_of_year: InheritableVariable::new_modified(172.0),
+            latitude: InheritableVariable::new_modified(45.0),
+            sun_intensity: InheritableVariable::new_modified(20.0),
+            turbidity: InheritableVariable::new_modified(1.0),
+            mie_anisotropy: InheritableVariable::new_modified(0.76),
+            sun_disk_size: InheritableVariable::new_modified(0.53),
+            night_color: InheritableVariable::new_modified(Color::opaque(4, 6, 12)),
+            sun_light: Default::default(),
+            sun_light_intensity: InheritableVariable::new_modified(1.0),
+            update_ambient: InheritableVariable::new_modified(true),
+            ambient_intensity: InheritableVariable::new_modified(0.5),
+            phase: None,
+            events: Default::default(),
+        }
+    }
+}
+
+impl Deref for Sky {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Sky {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Sky {
+    fn type_uuid() -> Uuid {
+        uuid!("6e3a1d2c-8b47-4f0e-a5d9-2c7b1e4f9a30")
+    }
+}
+
+impl Sky {
+    /// Maximal amount of events, that are kept until they're taken by [`Self::take_events`].
+    pub const MAX_EVENTS: usize = 16;
+
+    /// Sets local solar time in hours. The value is wrapped into `[0; 24)` range.
+    pub fn set_time_of_day(&mut self, time_of_day: f32) -> f32 {
+        self.time_of_day
+            .set_value_and_mark_modified(time_of_day.rem_euclid(24.0))
+    }
+
+    /// Returns local solar time in hours.
+    pub fn time_of_day(&self) -> f32 {
+        *self.time_of_day
+    }
+
+    /// Sets duration of a full day in seconds. Zero stops the time.
+    pub fn set_day_duration(&mut self, duration: f32) -> f32 {
+        self.day_duration
+            .set_value_and_mark_modified(duration.max(0.0))
+    }
+
+    /// Returns duration of a full day in seconds.
+    pub fn day_duration(&self) -> f32 {
+        *self.day_duration
+    }
+
+    /// Sets day of the year, it defines declination of the sun. The value is wrapped into
+    /// `[0; 365)` range.
+    pub fn set_day_of_year(&mut self, day: f32) -> f32 {
+        self.day_of_year
+            .set_value_and_mark_modified(day.rem_euclid(365.0))
+    }
+
+    /// Returns day of the year.
+    pub fn day_of_year(&self) -> f32 {
+        *self.day_of_year
+    }
+
+    /// Sets latitude of the observer in degrees.
+    pub fn set_latitude(&mut self, latitude: f32) -> f32 {
+        self.latitude
+            .set_value_and_mark_modified(latitude.clamp(-90.0, 90.0))
+    }
+
+    /// Returns latitude of the observer in degrees.
+    pub fn latitude(&self) -> f32 {
+        *self.latitude
+    }
+
+    /// Sets intensity of the sunlight outside of the atmosphere. It defines brightness of the sky.
+    pub fn set_sun_intensity(&mut self, intensity: f32) -> f32 {
+        self.sun_intensity
+            .set_value_and_mark_modified(intensity.max(0.0))
+    }
+
+    /// Returns intensity of the sunlight outside of the atmosphere.
+    pub fn sun_intensity(&self) -> f32 {
+        *self.sun_intensity
+    }
+
+    /// Sets amount of aerosols relative to the clear air. Higher values make the sky hazy.
+    pub fn set_turbidity(&mut self, turbidity: f32) -> f32 {
+        self.turbidity
+            .set_value_and_mark_modified(turbidity.max(0.0))
+    }
+
+    /// Returns amount of aerosols relative to the clear air.
+    pub fn turbidity(&self) -> f32 {
+        *self.turbidity
+    }
+
+    /// Sets anisotropy of the Mie scattering, higher values give smaller and brighter halo around
+    /// the sun.
+    pub fn set_mie_anisotropy(&mut self, anisotropy: f32) -> f32 {
+        self.mie_anisotropy
+            .set_value_and_mark_modified(anisotropy.clamp(0.0, 0.99))
+    }
+
+    /// Returns anisotropy of the Mie scattering.
+    pub fn mie_anisotropy(&self) -> f32 {
+        *self.mie_anisotropy
+    }
+
+    /// Sets angular diameter of the sun disk in degrees.
+    pub fn set_sun_disk_size(&mut self, size: f32) -> f32 {
+        self.sun_disk_size
+            .set_value_and_mark_modified(size.max(0.0))
+    }
+
+    /// Returns angular diameter of the sun disk in degrees.
+    pub fn sun_disk_size(&self) -> f32 {
+        *self.sun_disk_size
+    }
+
+    /// Sets color of the sky at night.
+    pub fn set_night_color(&mut self, color: Color) -> Color {
+        self.night_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns color of the sky at night.
+    pub fn night_color(&self) -> Color {
+        *self.night_color
+    }
+
+    /// Sets a directional light, that will be driven by the sky. Pass [`Handle::NONE`] to stop
+    /// driving the light.
+    pub fn set_sun_light(&mut self, light: Handle<Node>) -> Handle<Node> {
+        self.sun_light.set_value_and_mark_modified(light)
+    }
+
+    /// Returns a handle of the directional light, that is driven by the sky.
+    pub fn sun_light(&self) -> Handle<Node> {
+        *self.sun_light
+    }
+
+    /// Sets intensity of the sun light, when the sun is in the zenith.
+    pub fn set_sun_light_intensity(&mut self, intensity: f32) -> f32 {
+        self.sun_light_intensity
+            .set_value_and_mark_modified(intensity.max(0.0))
+    }
+
+    /// Returns intensity of the sun light, when the sun is in the zenith.
+    pub fn sun_light_intensity(&self) -> f32 {
+        *self.sun_light_intensity
+    }
+
+    /// Defines whether the sky should update ambient lighting of the scene or not.
+    pub fn set_update_ambient(&mut self, update: bool) -> bool {
+        self.update_ambient.set_value_and_mark_modified(update)
+    }
+
+    /// Returns `true` if the sky updates ambient lighting of the scene.
+    pub fn is_update_ambient(&self) -> bool {
+        *self.update_ambient
+    }
+
+    /// Sets a multiplier for the ambient lighting, that is calculated from the color of the sky.
+    pub fn set_ambient_intensity(&mut self, intensity: f32) -> f32 {
+        self.ambient_intensity
+            .set_value_and_mark_modified(intensity.max(0.0))
+    }
+
+    /// Returns a multiplier for the ambient lighting.
+    pub fn ambient_intensity(&self) -> f32 {
+        *self.ambient_intensity
+    }
+
+    /// Returns direction to the sun in world coordinates.
+    pub fn sun_direction(&self) -> Vector3<f32> {
+        let local = sun_direction(*self.time_of_day, *self.day_of_year, *self.latitude);
+        self.global_transform()
+            .transform_vector(&local)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(local)
+    }
+
+    /// Returns elevation of the sun above the horizon in radians.
+    pub fn sun_elevation(&self) -> f32 {
+        sun_direction(*self.time_of_day, *self.day_of_year, *self.latitude)
+            .y
+            .clamp(-1.0, 1.0)
+            .asin()
+    }
+
+    /// Returns current part of the day.
+    pub fn day_phase(&self) -> DayPhase {
+        DayPhase::from_sun(self.sun_elevation(), *self.time_of_day)
+    }
+
+    /// Returns parameters of the atmosphere for the current state of the sky.
+    pub fn atmosphere(&self) -> Atmosphere {
+        // Scattering is calculated in the local space of the sky, where Y axis points up.
+        Atmosphere {
+            sun_direction: sun_direction(*self.time_of_day, *self.day_of_year, *self.latitude),
+            sun_intensity: *self.sun_intensity,
+            turbidity: *self.turbidity,
+            mie_anisotropy: *self.mie_anisotropy,
+        }
+    }
+
+    /// Returns color and intensity of the sunlight, that reaches the ground.
+    pub fn sun_light_color(&self) -> (Color, f32) {
+        let transmittance = self.atmosphere().sun_transmittance();
+        let max = transmittance.max();
+        if max <= 0.0 {
+            (Color::BLACK, 0.0)
+        } else {
+            (
+                linear_to_color(transmittance / max),
+                max * *self.sun_light_intensity,
+            )
+        }
+    }
+
+    /// Returns ambient lighting color, that is calculated from the color of the sky.
+    pub fn ambient_color(&self) -> Color {
+        let atmosphere = self.atmosphere();
+        // Average of the zenith and a few directions above the horizon.
+        let elevation = 30.0f32.to_radians();
+        let (sin, cos) = elevation.sin_cos();
+        let directions = [
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(cos, sin, 0.0),
+            Vector3::new(-cos, sin, 0.0),
+            Vector3::new(0.0, sin, cos),
+            Vector3::new(0.0, sin, -cos),
+        ];
+        let sky = directions
+            .iter()
+            .map(|d| atmosphere.sky_radiance(*d))
+            .sum::<Vector3<f32>>()
+            / directions.len() as f32;
+        let night = self.night_color.srgb_to_linear_f32().xyz();
+        linear_to_color(sky * *self.ambient_intensity + night)
+    }
+
+    /// Takes events of the day-night cycle, that were produced since the last call.
+    pub fn take_events(&mut self) -> Vec<SkyEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn push_event(&mut self, event: SkyEvent) {
+        if self.events.len() >= Self::MAX_EVENTS {
+            self.events.remove(0);
+        }
+        self.events.push(event);
+    }
+}
+
+impl NodeTrait for Sky {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if *self.day_duration > 0.0 {
+            let time_of_day = *self.time_of_day + context.dt * 24.0 / *self.day_duration;
+            if time_of_day >= 24.0 {
+                let day_of_year = *self.day_of_year + 1.0;
+                self.set_day_of_year(day_of_year);
+                self.push_event(SkyEvent::NewDay);
+            }
+            self.set_time_of_day(time_of_day);
+        }
+
+        let phase = self.day_phase();
+        match self.phase {
+            Some(old) if old != phase => {
+                self.push_event(SkyEvent::PhaseChanged { old, new: phase })
+            }
+            _ => (),
+        }
+        self.phase = Some(phase);
+
+        let sun_direction = self.sun_direction();
+        let (color, intensity) = self.sun_light_color();
+        if let Some(light) = context
+            .nodes
+            .try_borrow_mut(*self.sun_light)
+            .and_then(|node| node.cast_mut::<DirectionalLight>())
+        {
+            // Directional light emits light along its up vector.
+            let rotation = UnitQuaternion::rotation_between(&Vector3::y(), &sun_direction)
+                .unwrap_or_else(|| UnitQuaternion::from_axis_angle(&Vector3::x_axis(), PI));
+            light.local_transform_mut().set_rotation(rotation);
+            light.base_light_mut().set_color(color);
+            light.base_light_mut().set_intensity(intensity);
+        }
+    }
+}
+
+/// Allows you to create sky in a declarative manner.
+pub struct SkyBuilder {
+    base_builder: BaseBuilder,
+    time_of_day: f32,
+    day_duration: f32,
+    day_of_year: f32,
+    latitude: f32,
+    sun_intensity: f32,
+    turbidity: f32,
+    mie_anisotropy: f32,
+    sun_disk_size: f32,
+    night_color: Color,
+    sun_light: Handle<Node>,
+    sun_light_intensity: f32,
+    update_ambient: bool,
+    ambient_intensity: f32,
+}
+
+impl SkyBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        let defaults = Sky::default();
+        Self {
+            base_builder,
+            time_of_day: *defaults.time_of_day,
+            day_duration: *defaults.day_duration,
+            day_of_year: *defaults.day_of_year,
+            latitude: *defaults.latitude,
+            sun_intensity: *defaults.sun_intensity,
+            turbidity: *defaults.turbidity,
+            mie_anisotropy: *defaults.mie_anisotropy,
+            sun_disk_size: *defaults.sun_disk_size,
+            night_color: *defaults.night_color,
+            sun_light: Handle::NONE,
+            sun_light_intensity: *defaults.sun_light_intensity,
+            update_ambient: *defaults.update_ambient,
+            ambient_intensity: *defaults.ambient_intensity,
+        }
+    }
+
+    /// Sets desired time of day in hours.
+    pub fn with_time_of_day(mut self, time_of_day: f32) -> Self {
+        self.time_of_day = time_of_day.rem_euclid(24.0);
+        self
+    }
+
+    /// Sets desired duration of a full day in seconds.
+    pub fn with_day_duration(mut self, duration: f32) -> Self {
+        self.day_duration = duration.max(0.0);
+        self
+    }
+
+    /// Sets desired day of the year.
+    pub fn with_day_of_year(mut self, day: f32) -> Self {
+        self.day_of_year = day.rem_euclid(365.0);
+        self
+    }
+
+    /// Sets desired latitude of the observer in degrees.
+    pub fn with_latitude(mut self, latitude: f32) -> Self {
+        self.latitude = latitude.clamp(-90.0, 90.0);
+        self
+    }
+
+    /// Sets desired intensity of the sunlight outside of the atmosphere.
+    pub fn with_sun_intensity(mut self, intensity: f32) -> Self {
+        self.sun_intensity = intensity;
+        self
+    }
+
+    /// Sets desired turbidity of the atmosphere.
+    pub fn with_turbidity(mut self, turbidity: f32) -> Self {
+        self.turbidity = turbidity;
+        self
+    }
+
+    /// Sets desired anisotropy of the Mie scattering.
+    pub fn with_mie_anisotropy(mut self, anisotropy: f32) -> Self {
+        self.mie_anisotropy = anisotropy;
+        self
+    }
+
+    /// Sets desired angular diameter of the sun disk in degrees.
+    pub fn with_sun_disk_size(mut self, size: f32) -> Self {
+        self.sun_disk_size = size;
+        self
+    }
+
+    /// Sets desired color of the sky at night.
+    pub fn with_night_color(mut self, color: Color) -> Self {
+        self.night_color = color;
+        self
+    }
+
+    /// Sets desired directional light, that will be driven by the sky.
+    pub fn with_sun_light(mut self, light: Handle<Node>) -> Self {
+        self.sun_light = light;
+        self
+    }
+
+    /// Sets desired intensity of the sun light in the zenith.
+    pub fn with_sun_light_intensity(mut self, intensity: f32) -> Self {
+        self.sun_light_intensity = intensity;
+        self
+    }
+
+    /// Defines whether the sky should update ambient lighting of the scene or not.
+    pub fn with_update_ambient(mut self, update: bool) -> Self {
+        self.update_ambient = update;
+        self
+    }
+
+    /// Sets desired multiplier of the ambient lighting.
+    pub fn with_ambient_intensity(mut self, intensity: f32) -> Self {
+        self.ambient_intensity = intensity;
+        self
+    }
+
+    /// Creates new sky.
+    pub fn build_sky(self) -> Sky {
+        Sky {
+            base: self.base_builder.build_base(),
+            time_of_day: self.time_of_day.into(),
+            day_duration: self.day_duration.into(),
+            day_of_year: self.day_of_year.into(),
+            latitude: self.latitude.into(),
+            sun_intensity: self.sun_intensity.into(),
+            turbidity: self.turbidity.into(),
+            mie_anisotropy: self.mie_anisotropy.into(),
+            sun_disk_size: self.sun_disk_size.into(),
+            night_color: self.night_color.into(),
+            sun_light: self.sun_light.into(),
+            sun_light_intensity: self.sun_light_intensity.into(),
+            update_ambient: self.update_ambient.into(),
+            ambient_intensity: self.ambient_intensity.into(),
+            phase: None,
+            events: Default::default(),
+        }
+    }
+
+    /// Creates new sky node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_sky())
+    }
+
+    /// Creates new sky node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::sky::{sun_direction, Atmosphere, DayPhase},
+    };
+
+    fn assert_direction(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!((a - b).norm() < 1.0e-3, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_sun_direction() {
+        // Equinox at the equator: the sun rises in the east, passes the zenith and sets in the
+        // west.
+        let equinox = 81.25;
+        assert_direction(sun_direction(6.0, equinox, 0.0), Vector3::x());
+        assert_direction(sun_direction(12.0, equinox, 0.0), Vector3::y());
+        assert_direction(sun_direction(18.0, equinox, 0.0), -Vector3::x());
+        assert_direction(sun_direction(0.0, equinox, 0.0), -Vector3::y());
+
+        // At the noon in the northern hemisphere the sun is in the south.
+        let noon = sun_direction(12.0, equinox, 45.0);
+        let expected = Vector3::new(0.0, 1.0, 1.0).normalize();
+        assert_direction(noon, expected);
+    }
+
+    #[test]
+    fn test_day_phase() {
+        assert_eq!(DayPhase::from_sun(0.5, 12.0), DayPhase::Day);
+        assert_eq!(DayPhase::from_sun(-0.5, 0.0), DayPhase::Night);
+        assert_eq!(DayPhase::from_sun(0.0, 6.0), DayPhase::Dawn);
+        assert_eq!(DayPhase::from_sun(0.0, 18.0), DayPhase::Dusk);
+    }
+
+    #[test]
+    fn test_scattering() {
+        let noon = Atmosphere {
+            sun_direction: Vector3::y(),
+            sun_intensity: 20.0,
+            turbidity: 1.0,
+            mie_anisotropy: 0.76,
+        };
+
+        // Clear sky is blue.
+        let zenith = noon.sky_radiance(Vector3::new(0.0, 1.0, 1.0));
+        assert!(zenith.z > zenith.x);
+
+        // Low sun is red.
+        let sunset = Atmosphere {
+            sun_direction: Vector3::new(1.0, 0.02, 0.0).normalize(),
+            ..noon
+        };
+        let transmittance = sunset.sun_transmittance();
+        assert!(transmittance.x > transmittance.z);
+        assert!(noon.sun_transmittance().x > transmittance.x);
+
+        // No direct sunlight at night.
+        let night = Atmosphere {
+            sun_direction: -Vector3::y(),
+            ..noon
+        };
+        assert_eq!(night.sun_transmittance(), Vector3::default());
+    }
+}
@@ -2,10 +2,12 @@
 
 use crate::{
     core::{
+        algebra::Vector3,
         log::{Log, MessageKind},
         pool::Handle,
         visitor::prelude::*,
     },
+    engine::gc::TimeBudget,
     scene::{node::Node, sound::Sound},
 };
 use fxhash::FxHashSet;
@@ -22,6 +24,9 @@ use std::{sync::MutexGuard, time::Duration};
 pub struct SoundContext {
     #[visit(optional)]
     pub(crate) native: fyrox_sound::context::SoundContext,
+    // Sources of removed sound nodes, they're stopped immediately, but destroyed over multiple frames.
+    #[visit(skip)]
+    pending_removal: Vec<Handle<SoundSource>>,
 }
 
 /// Proxy for guarded access to the sound context.
@@ -101,6 +106,7 @@ impl Default for SoundContext {
     fn default() -> Self {
         Self {
             native: fyrox_sound::context::SoundContext::new(),
+            pending_removal: Default::default(),
         }
     }
 }
@@ -114,6 +120,7 @@ impl SoundContext {
     pub fn deep_clone(&self) -> Self {
         Self {
             native: self.native.deep_clone(),
+            pending_removal: self.pending_removal.clone(),
         }
     }
 
@@ -126,8 +133,10 @@ impl SoundContext {
 
     pub(crate) fn remove_sound(&mut self, sound: Handle<SoundSource>, name: &str) {
         let mut state = self.native.state();
-        if state.is_valid_handle(sound) {
-            state.remove_source(sound);
+        if let Some(source) = state.try_get_source_mut(sound) {
+            // The source must be silent right away, actual destruction is amortized.
+            Log::verify(source.stop());
+            self.pending_removal.push(sound);
 
             Log::info(format!(
                 "Native sound source was removed for node: {}",
@@ -136,14 +145,34 @@ impl SoundContext {
         }
     }
 
-    pub(crate) fn set_sound_position(&mut self, sound: &Sound) {
+    /// Returns amount of native sound sources, that are waiting to be destroyed.
+    pub fn pending_removal_count(&self) -> usize {
+        self.pending_removal.len()
+    }
+
+    /// Destroys native sound sources of removed sound nodes until the given time budget is exhausted,
+    /// remaining ones will be destroyed on next calls.
+    pub fn collect_garbage(&mut self, budget: &TimeBudget) {
+        let mut state = self.native.state();
+        while !budget.is_exhausted() {
+            match self.pending_removal.pop() {
+                Some(source) if state.is_valid_handle(source) => state.remove_source(source),
+                Some(_) => (),
+                None => break,
+            }
+        }
+    }
+
+    pub(crate) fn set_sound_position(&mut self, sound: &Sound, position: Vector3<f32>) {
         if let Some(source) = self.native.state().try_get_source_mut(sound.native.get()) {
-            source.set_position(sound.global_position());
+            source.set_position(position);
         }
     }
 
     pub(crate) fn sync_with_sound(&self, sound: &mut Sound) {
         if let Some(source) = self.native.state().try_get_source_mut(sound.native.get()) {
+            source.set_velocity(sound.velocity());
+
             // Sync back.
             sound.status.set_value_silent(source.status());
             sound.playback_time.set_value_silent(source.playback_time());
@@ -221,6 +250,7 @@ impl SoundContext {
                 .with_status(sound.status())
                 .with_playback_time(sound.playback_time())
                 .with_position(sound.global_position())
+                .with_velocity(sound.velocity())
                 .with_radius(sound.radius())
                 .with_max_distance(sound.max_distance())
                 .with_bus(sound.audio_bus())
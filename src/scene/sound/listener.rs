@@ -5,6 +5,7 @@
 
 use crate::{
     core::{
+        algebra::Vector3,
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
         reflect::prelude::*,
@@ -15,7 +16,7 @@ use crate::{
     scene::{
         base::{Base, BaseBuilder},
         graph::Graph,
-        node::{Node, NodeTrait, SyncContext},
+        node::{Node, NodeTrait, SyncContext, UpdateContext},
     },
 };
 use std::ops::{Deref, DerefMut};
@@ -34,9 +35,20 @@ use std::ops::{Deref, DerefMut};
 ///
 /// 2D sound sources (with spatial blend == 0.0) are not influenced by listener's position and
 /// orientation.
+///
+/// Velocity of the listener is calculated using its global position on every update, it is used to
+/// simulate the Doppler effect.
 #[derive(Visit, Reflect, Default, Clone, Debug)]
 pub struct Listener {
     base: Base,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    velocity: Vector3<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    prev_position: Option<Vector3<f32>>,
 }
 
 impl Deref for Listener {
@@ -53,6 +65,13 @@ impl DerefMut for Listener {
     }
 }
 
+impl Listener {
+    /// Returns velocity of the listener in world space (in units per second).
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+}
+
 impl TypeUuidProvider for Listener {
     fn type_uuid() -> Uuid {
         uuid!("2c7dabc1-5666-4256-b020-01532701e4c6")
@@ -89,6 +108,27 @@ impl NodeTrait for Listener {
         native.set_position(self.global_position());
         native.set_orientation_lh(self.look_vector(), self.up_vector());
     }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let position = self.global_position();
+        if let Some(prev_position) = self.prev_position {
+            if context.dt > 0.0 {
+                self.velocity = (position - prev_position).scale(1.0 / context.dt);
+            }
+        }
+        self.prev_position = Some(position);
+
+        context
+            .sound_context
+            .native
+            .state()
+            .listener_mut()
+            .set_velocity(self.velocity);
+    }
+
+    fn updates_render_data(&self) -> bool {
+        false
+    }
 }
 
 /// Allows you to create listener in declarative manner.
@@ -106,6 +146,8 @@ impl ListenerBuilder {
     pub fn build_listener(self) -> Listener {
         Listener {
             base: self.base_builder.build_base(),
+            velocity: Default::default(),
+            prev_position: None,
         }
     }
 
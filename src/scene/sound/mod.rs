@@ -2,8 +2,8 @@
 
 use crate::{
     core::{
-        algebra::Matrix4,
-        math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq},
+        algebra::{Matrix4, Vector3},
+        math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq, Matrix4Ext},
         pool::Handle,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
@@ -59,6 +59,16 @@ pub struct Sound {
     #[reflect(setter = "set_play_once")]
     play_once: InheritableVariable<bool>,
 
+    #[visit(optional)]
+    #[reflect(setter = "set_play_on_start")]
+    play_on_start: InheritableVariable<bool>,
+
+    // Whether the sound was started because of play-on-start flag or not. It is serialized to prevent
+    // restarting of the sound when a saved game is loaded.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    started: bool,
+
     #[reflect(min_value = 0.0, step = 0.05)]
     #[reflect(setter = "set_gain")]
     gain: InheritableVariable<f32>,
@@ -101,6 +111,14 @@ pub struct Sound {
     )]
     audio_bus: InheritableVariable<String>,
 
+    #[reflect(hidden)]
+    #[visit(skip)]
+    velocity: Vector3<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    prev_position: Option<Vector3<f32>>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) native: Cell<Handle<SoundSource>>,
@@ -126,6 +144,8 @@ impl Default for Sound {
             base: Default::default(),
             buffer: InheritableVariable::new_modified(None),
             play_once: InheritableVariable::new_modified(false),
+            play_on_start: InheritableVariable::new_modified(false),
+            started: false,
             gain: InheritableVariable::new_modified(1.0),
             panning: InheritableVariable::new_modified(0.0),
             status: InheritableVariable::new_modified(Status::Stopped),
@@ -137,6 +157,8 @@ impl Default for Sound {
             playback_time: Default::default(),
             spatial_blend: InheritableVariable::new_modified(1.0),
             audio_bus: InheritableVariable::new_modified(AudioBusGraph::PRIMARY_BUS.to_string()),
+            velocity: Default::default(),
+            prev_position: None,
             native: Default::default(),
         }
     }
@@ -148,6 +170,9 @@ impl Clone for Sound {
             base: self.base.clone(),
             buffer: self.buffer.clone(),
             play_once: self.play_once.clone(),
+            play_on_start: self.play_on_start.clone(),
+            // The copy is a new sound, so it must be started on its own.
+            started: false,
             gain: self.gain.clone(),
             panning: self.panning.clone(),
             status: self.status.clone(),
@@ -159,6 +184,8 @@ impl Clone for Sound {
             playback_time: self.playback_time.clone(),
             spatial_blend: self.spatial_blend.clone(),
             audio_bus: self.audio_bus.clone(),
+            velocity: Default::default(),
+            prev_position: None,
             // Do not copy. The copy will have its own native representation.
             native: Default::default(),
         }
@@ -202,6 +229,25 @@ impl Sound {
         *self.play_once
     }
 
+    /// Enables or disables automatic playback of the sound. Such sound will start playing on its first
+    /// update, which happens when the scene with the sound is loaded or when a prefab with the sound is
+    /// instantiated. The sound starts only once, stopping it won't cause a restart.
+    pub fn set_play_on_start(&mut self, play_on_start: bool) -> bool {
+        self.play_on_start
+            .set_value_and_mark_modified(play_on_start)
+    }
+
+    /// Returns true if the sound starts automatically, false - otherwise.
+    pub fn is_play_on_start(&self) -> bool {
+        *self.play_on_start
+    }
+
+    /// Returns velocity of the sound in world space (in units per second). The velocity is calculated
+    /// on every update using global position of the node and it is used to simulate the Doppler effect.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
     /// Sets spatial blend factor. It defines how much the source will be 2D and 3D sound at the same
     /// time. Set it to 0.0 to make the sound fully 2D and 1.0 to make it fully 3D. Middle values
     /// will make sound proportionally 2D and 3D at the same time.
@@ -385,7 +431,9 @@ impl NodeTrait for Sound {
 
     fn sync_transform(&self, new_global_transform: &Matrix4<f32>, context: &mut SyncContext) {
         if !m4x4_approx_eq(new_global_transform, &self.global_transform()) {
-            context.sound_context.set_sound_position(self);
+            context
+                .sound_context
+                .set_sound_position(self, new_global_transform.position());
         }
     }
 
@@ -398,7 +446,24 @@ impl NodeTrait for Sound {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        let position = self.global_position();
+        if let Some(prev_position) = self.prev_position {
+            if context.dt > 0.0 {
+                self.velocity = (position - prev_position).scale(1.0 / context.dt);
+            }
+        }
+        self.prev_position = Some(position);
+
         context.sound_context.sync_with_sound(self);
+
+        if *self.play_on_start && !self.started {
+            self.started = true;
+            self.play();
+        }
+    }
+
+    fn updates_render_data(&self) -> bool {
+        false
     }
 
     fn validate(&self, _scene: &Scene) -> Result<(), String> {
@@ -429,6 +494,7 @@ pub struct SoundBuilder {
     base_builder: BaseBuilder,
     buffer: Option<SoundBufferResource>,
     play_once: bool,
+    play_on_start: bool,
     gain: f32,
     panning: f32,
     status: Status,
@@ -449,6 +515,7 @@ impl SoundBuilder {
             base_builder,
             buffer: None,
             play_once: false,
+            play_on_start: false,
             gain: 1.0,
             panning: 0.0,
             status: Status::Stopped,
@@ -473,6 +540,11 @@ impl SoundBuilder {
         fn with_play_once(play_once: bool)
     );
 
+    define_with!(
+        /// Sets play-on-start mode. See [`Sound::set_play_on_start`] for more info.
+        fn with_play_on_start(play_on_start: bool)
+    );
+
     define_with!(
         /// Sets desired gain. See [`Sound::set_gain`] for more info.
         fn with_gain(gain: f32)
@@ -535,6 +607,8 @@ impl SoundBuilder {
             base: self.base_builder.build_base(),
             buffer: self.buffer.into(),
             play_once: self.play_once.into(),
+            play_on_start: self.play_on_start.into(),
+            started: false,
             gain: self.gain.into(),
             panning: self.panning.into(),
             status: self.status.into(),
@@ -546,6 +620,8 @@ impl SoundBuilder {
             playback_time: self.playback_time.into(),
             spatial_blend: self.spatial_blend.into(),
             audio_bus: self.audio_bus.into(),
+            velocity: Default::default(),
+            prev_position: None,
             native: Default::default(),
         }
     }
@@ -561,3 +637,52 @@ impl SoundBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            sound::{SoundBuilder, Status},
+        },
+    };
+
+    #[test]
+    fn test_sound_node() {
+        let mut graph = Graph::new();
+        let handle = SoundBuilder::new(BaseBuilder::new())
+            .with_play_on_start(true)
+            .with_looping(true)
+            .build(&mut graph);
+
+        let frame_size = Vector2::new(100.0, 100.0);
+        graph.update(frame_size, 0.5, Default::default());
+        let native = graph[handle].as_sound().native.get();
+        assert!(native.is_some());
+        assert_eq!(graph[handle].as_sound().status(), Status::Playing);
+
+        graph[handle]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 0.0, 0.0));
+        graph.update(frame_size, 0.5, Default::default());
+        let sound = graph[handle].as_sound();
+        assert_eq!(sound.velocity(), Vector3::new(2.0, 0.0, 0.0));
+        {
+            let state = graph.sound_context.native.state();
+            let source = state.source(native);
+            assert_eq!(source.status(), Status::Playing);
+            assert_eq!(source.velocity(), Vector3::new(2.0, 0.0, 0.0));
+            assert_eq!(source.position(), Vector3::new(1.0, 0.0, 0.0));
+        }
+
+        // Stopped sound is not restarted.
+        graph[handle].as_sound_mut().stop();
+        graph.update(frame_size, 0.5, Default::default());
+        assert_eq!(graph[handle].as_sound().status(), Status::Stopped);
+
+        graph.remove_node(handle);
+        assert_eq!(graph.sound_context.pending_removal_count(), 1);
+    }
+}
@@ -5,7 +5,7 @@
 use crate::{
     core::{
         color::Color,
-        math::aabb::AxisAlignedBoundingBox,
+        math::{aabb::AxisAlignedBoundingBox, Rect},
         pool::Handle,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
@@ -31,6 +31,12 @@ use std::ops::{Deref, DerefMut};
 /// it could be done by using Forward render pass. You may need this for custom effects. Current implementation
 /// is very simple, but still covers 95% of use cases.
 ///
+/// # Texture atlases
+///
+/// You can specify a portion of the texture that will be used for rendering using [`Self::set_uv_rect`]
+/// method. This is especially useful if you have sprite sheets (texture atlases), see
+/// [`crate::resource::spritesheet::SpriteSheet`] for more info.
+///
 /// # Depth sorting
 ///
 /// Sprites are **not** depth-sorted so there could be some blending issues if multiple sprites are stacked one behind
@@ -78,6 +84,10 @@ pub struct Sprite {
 
     #[reflect(setter = "set_rotation")]
     rotation: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_uv_rect")]
+    uv_rect: InheritableVariable<Rect<f32>>,
 }
 
 impl Deref for Sprite {
@@ -154,6 +164,20 @@ impl Sprite {
     pub fn texture_ref(&self) -> Option<&TextureResource> {
         self.texture.as_ref()
     }
+
+    /// Returns a rectangle that defines the region in texture which will be rendered. The coordinates are normalized
+    /// which means `[0; 0]` corresponds to top-left corner of the texture and `[1; 1]` corresponds to right-bottom
+    /// corner.
+    pub fn uv_rect(&self) -> Rect<f32> {
+        *self.uv_rect
+    }
+
+    /// Sets a rectangle that defines the region in texture which will be rendered. The coordinates are normalized
+    /// which means `[0; 0]` corresponds to top-left corner of the texture and `[1; 1]` corresponds to right-bottom
+    /// corner. The default value is `(0, 0, 1, 1)` rectangle which corresponds to entire texture.
+    pub fn set_uv_rect(&mut self, uv_rect: Rect<f32>) -> Rect<f32> {
+        self.uv_rect.set_value_and_mark_modified(uv_rect)
+    }
 }
 
 impl NodeTrait for Sprite {
@@ -170,6 +194,10 @@ impl NodeTrait for Sprite {
     fn id(&self) -> Uuid {
         Self::type_uuid()
     }
+
+    fn updates_render_data(&self) -> bool {
+        false
+    }
 }
 
 /// Sprite builder allows you to construct sprite in declarative manner.
@@ -180,6 +208,7 @@ pub struct SpriteBuilder {
     color: Color,
     size: f32,
     rotation: f32,
+    uv_rect: Rect<f32>,
 }
 
 impl SpriteBuilder {
@@ -191,6 +220,7 @@ impl SpriteBuilder {
             color: Color::WHITE,
             size: 0.2,
             rotation: 0.0,
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
         }
     }
 
@@ -224,6 +254,13 @@ impl SpriteBuilder {
         self
     }
 
+    /// Sets desired portion of the texture for the sprite. See [`Sprite::set_uv_rect`]
+    /// for more info.
+    pub fn with_uv_rect(mut self, uv_rect: Rect<f32>) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+
     fn build_sprite(self) -> Sprite {
         Sprite {
             base: self.base_builder.build_base(),
@@ -231,6 +268,7 @@ impl SpriteBuilder {
             color: self.color.into(),
             size: self.size.into(),
             rotation: self.rotation.into(),
+            uv_rect: self.uv_rect.into(),
         }
     }
 
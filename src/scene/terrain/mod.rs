@@ -999,6 +999,42 @@ impl Terrain {
         project(self.global_transform(), p)
     }
 
+    /// Returns height of the terrain at the given point expressed in local 2D coordinate system of
+    /// terrain (see [`Self::project`]). The height is interpolated between four neighbour pixels of
+    /// a height map. Returns `None` if the point is outside of the terrain.
+    pub fn height_at(&self, position: Vector2<f32>) -> Option<f32> {
+        for chunk in self.chunks.iter() {
+            let local = position - chunk.local_position();
+            if local.x < 0.0
+                || local.y < 0.0
+                || local.x > chunk.physical_size.x
+                || local.y > chunk.physical_size.y
+                || chunk.height_map_size.x < 2
+                || chunk.height_map_size.y < 2
+            {
+                continue;
+            }
+
+            let texture = chunk.heightmap.as_ref().unwrap().data_ref();
+            let height_map = texture.data_of_type::<f32>().unwrap();
+
+            let fx = local.x / chunk.physical_size.x * (chunk.height_map_size.x - 1) as f32;
+            let fz = local.y / chunk.physical_size.y * (chunk.height_map_size.y - 1) as f32;
+            let ix = (fx as u32).min(chunk.height_map_size.x - 2);
+            let iz = (fz as u32).min(chunk.height_map_size.y - 2);
+            let tx = fx - ix as f32;
+            let tz = fz - iz as f32;
+
+            let pixel = |x: u32, z: u32| height_map[(z * chunk.height_map_size.x + x) as usize];
+            let near = pixel(ix, iz) + (pixel(ix + 1, iz) - pixel(ix, iz)) * tx;
+            let far = pixel(ix, iz + 1) + (pixel(ix + 1, iz + 1) - pixel(ix, iz + 1)) * tx;
+
+            return Some(near + (far - near) * tz);
+        }
+
+        None
+    }
+
     /// Applies the given function to each pixel of the height map.
     pub fn for_each_height_map_pixel<F>(&mut self, mut func: F)
     where
@@ -1439,11 +1475,12 @@ impl NodeTrait for Terrain {
         Self::type_uuid()
     }
 
+    fn updates_render_data(&self) -> bool {
+        false
+    }
+
     fn collect_render_data(&self, ctx: &mut RenderContext) {
-        if !self.global_visibility()
-            || !self.is_globally_enabled()
-            || !ctx.frustum.is_intersects_aabb(&self.world_bounding_box())
-        {
+        if !self.global_visibility() || !self.is_globally_enabled() || !ctx.is_node_in_frustum() {
             return;
         }
 
@@ -1535,7 +1572,7 @@ impl NodeTrait for Terrain {
                             &material,
                             RenderPath::Deferred,
                             self.decal_layer_index(),
-                            layer_index as u64,
+                            layer_index as u8,
                             SurfaceInstanceData {
                                 world_transform: node_transform,
                                 bone_matrices: Default::default(),
@@ -1547,6 +1584,7 @@ impl NodeTrait for Terrain {
                                     ctx.node_handle,
                                     node.persistent_index,
                                 ),
+                                node_handle: ctx.node_handle,
                             },
                         );
                     } else {
@@ -1557,7 +1595,7 @@ impl NodeTrait for Terrain {
                                     &material,
                                     RenderPath::Deferred,
                                     self.decal_layer_index(),
-                                    layer_index as u64,
+                                    layer_index as u8,
                                     SurfaceInstanceData {
                                         world_transform: node_transform,
                                         bone_matrices: Default::default(),
@@ -1569,6 +1607,7 @@ impl NodeTrait for Terrain {
                                             ctx.node_handle,
                                             node.persistent_index,
                                         ),
+                                        node_handle: ctx.node_handle,
                                     },
                                 );
                             }
@@ -0,0 +1,900 @@
+//! Trail is a textured strip that follows the movement of a node.
+//!
+//! For more info see [`Trail`]
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        color::Color,
+        color_gradient::{ColorGradient, GradientPoint},
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    material::{
+        shader::{ShaderResource, ShaderResourceExtension},
+        Material, SharedMaterial,
+    },
+    renderer::{
+        self,
+        batch::{PersistentIdentifier, RenderContext, SurfaceInstanceData},
+        framework::geometry_buffer::ElementRange,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        mesh::{
+            buffer::{
+                TriangleBuffer, VertexAttributeDataType, VertexAttributeDescriptor,
+                VertexAttributeUsage, VertexBuffer, VertexTrait,
+            },
+            surface::{SurfaceData, SurfaceSharedData},
+            RenderPath,
+        },
+        node::{Node, NodeTrait, UpdateContext},
+    },
+};
+use std::{
+    hash::{Hash, Hasher},
+    ops::{Deref, DerefMut},
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Defines how the strip of a trail is oriented in space.
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Default,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum TrailAlignment {
+    /// The strip is always facing the camera. It is the best option for missiles, magic projectiles and
+    /// other effects that must look the same from any side.
+    #[default]
+    View,
+
+    /// The strip is spanned along local X axis of the node at the moment of emission. Useful for sword
+    /// slashes, where the node is placed in the middle of a blade and its X axis is the blade direction.
+    LocalX,
+
+    /// The strip is spanned along local Y axis of the node at the moment of emission.
+    LocalY,
+
+    /// The strip is spanned along local Z axis of the node at the moment of emission.
+    LocalZ,
+
+    /// The strip lies flat on horizontal plane. Useful for tire marks and footprints.
+    Horizontal,
+}
+
+/// Defines how texture coordinates are generated along a trail.
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Default,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum TrailTextureMode {
+    /// Texture is stretched over the entire length of the trail.
+    #[default]
+    Stretch,
+
+    /// Texture is repeated along the trail, every [`Trail::texture_tile_length`] units of length use a
+    /// new copy of the texture. Useful for tire marks.
+    Tile,
+}
+
+/// A single point of a trail.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct TrailPoint {
+    /// World-space position of the point.
+    pub position: Vector3<f32>,
+    /// World-space direction along which the strip is spanned at the point. It is used only by
+    /// [`TrailAlignment::LocalX`], [`TrailAlignment::LocalY`] and [`TrailAlignment::LocalZ`] modes.
+    pub side: Vector3<f32>,
+    /// Amount of time (in seconds) since the point was emitted.
+    pub age: f32,
+}
+
+/// A vertex of the trail geometry. It is also used by [`crate::scene::lines::Lines`], both nodes are
+/// rendered with the standard trail shader.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(C)] // OpenGL expects this structure packed as in C
+pub struct TrailVertex {
+    /// Position of the vertex in local coordinates of its node. For view-aligned strips it is a position
+    /// on the center line of the strip.
+    pub position: Vector3<f32>,
+    /// Texture coordinates.
+    pub tex_coord: Vector2<f32>,
+    /// Linear RGBA color of the vertex.
+    pub color: Vector4<f32>,
+    /// Tangent of the strip (`xyz`) and signed half-width of the strip (`w`) at the vertex. `w` is
+    /// non-zero only for view-aligned strips, the vertex shader uses it to push the vertex sideways.
+    pub direction: Vector4<f32>,
+}
+
+impl VertexTrait for TrailVertex {
+    fn layout() -> &'static [VertexAttributeDescriptor] {
+        static LAYOUT: [VertexAttributeDescriptor; 4] = [
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Position,
+                data_type: VertexAttributeDataType::F32,
+                size: 3,
+                divisor: 0,
+                shader_location: 0,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::TexCoord0,
+                data_type: VertexAttributeDataType::F32,
+                size: 2,
+                divisor: 0,
+                shader_location: 1,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::TexCoord1,
+                data_type: VertexAttributeDataType::F32,
+                size: 4,
+                divisor: 0,
+                shader_location: 2,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::TexCoord2,
+                data_type: VertexAttributeDataType::F32,
+                size: 4,
+                divisor: 0,
+                shader_location: 3,
+            },
+        ];
+        &LAYOUT
+    }
+}
+
+// This is safe because Vertex is tightly packed struct with C representation
+// there is no padding bytes which may contain garbage data. This is strictly
+// required because vertices will be directly passed on GPU.
+impl Hash for TrailVertex {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        #[allow(unsafe_code)]
+        unsafe {
+            let bytes = self as *const Self as *const u8;
+            state.write(std::slice::from_raw_parts(
+                bytes,
+                std::mem::size_of::<Self>(),
+            ))
+        }
+    }
+}
+
+/// Trail is a textured strip that follows the movement of a node. Sword slashes, tire marks, missile
+/// smoke and similar effects are done via trails.
+///
+/// # How it works
+///
+/// While the trail is emitting, it leaves points behind the node every time the node moves further than
+/// [`Trail::min_segment_length`] from the previous point. The newest point always follows the node, so the
+/// trail never lags behind. Every point lives for [`Trail::lifetime`] seconds and then disappears, so the
+/// trail shrinks from its tail when the node stops or the emission is disabled.
+///
+/// # Appearance
+///
+/// Width of the trail is interpolated from [`Trail::start_width`] (newest points) to [`Trail::end_width`]
+/// (oldest points), color is taken from a color gradient over lifetime of the points. Orientation of the
+/// strip is defined by [`TrailAlignment`]. Texture coordinates are generated according to [`TrailTextureMode`]
+/// and could be scrolled along the trail with a constant speed.
+///
+/// # Rendering
+///
+/// Trails are rendered using Forward render path with the standard trail shader (see
+/// [`ShaderResourceExtension::standard_trail`]). It is possible to use a custom material, its shader must have
+/// `Forward` pass and use the vertex layout of [`TrailVertex`]. Trails do not cast shadows.
+///
+/// # Example
+///
+/// ```
+/// use fyrox::{
+///     core::{color::Color, color_gradient::{ColorGradient, GradientPoint}, pool::Handle},
+///     scene::{
+///         base::BaseBuilder,
+///         graph::Graph,
+///         node::Node,
+///         trail::{TrailAlignment, TrailBuilder},
+///     },
+/// };
+///
+/// fn create_sword_trail(graph: &mut Graph) -> Handle<Node> {
+///     let mut gradient = ColorGradient::new();
+///     gradient.add_point(GradientPoint::new(0.0, Color::from_rgba(255, 255, 255, 200)));
+///     gradient.add_point(GradientPoint::new(1.0, Color::from_rgba(255, 255, 255, 0)));
+///
+///     TrailBuilder::new(BaseBuilder::new())
+///         .with_lifetime(0.25)
+///         .with_start_width(1.2)
+///         .with_end_width(1.2)
+///         .with_alignment(TrailAlignment::LocalY)
+///         .with_color_over_lifetime(gradient)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Reflect)]
+pub struct Trail {
+    base: Base,
+
+    #[reflect(setter = "set_material")]
+    material: InheritableVariable<SharedMaterial>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_lifetime")]
+    lifetime: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_start_width")]
+    start_width: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_end_width")]
+    end_width: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_color_over_lifetime")]
+    color_over_lifetime: InheritableVariable<ColorGradient>,
+
+    #[reflect(min_value = 0.001, step = 0.05)]
+    #[reflect(setter = "set_min_segment_length")]
+    min_segment_length: InheritableVariable<f32>,
+
+    #[reflect(min_value = 1.0)]
+    #[reflect(setter = "set_max_segments")]
+    max_segments: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_alignment")]
+    alignment: InheritableVariable<TrailAlignment>,
+
+    #[reflect(setter = "set_texture_mode")]
+    texture_mode: InheritableVariable<TrailTextureMode>,
+
+    #[reflect(min_value = 0.001, step = 0.05)]
+    #[reflect(setter = "set_texture_tile_length")]
+    texture_tile_length: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_uv_scroll_speed")]
+    uv_scroll_speed: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_emitting")]
+    emitting: InheritableVariable<bool>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    points: Vec<TrailPoint>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    uv_offset: f32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    surface_data: SurfaceSharedData,
+}
+
+impl Clone for Trail {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            material: self.material.clone(),
+            lifetime: self.lifetime.clone(),
+            start_width: self.start_width.clone(),
+            end_width: self.end_width.clone(),
+            color_over_lifetime: self.color_over_lifetime.clone(),
+            min_segment_length: self.min_segment_length.clone(),
+            max_segments: self.max_segments.clone(),
+            alignment: self.alignment.clone(),
+            texture_mode: self.texture_mode.clone(),
+            texture_tile_length: self.texture_tile_length.clone(),
+            uv_scroll_speed: self.uv_scroll_speed.clone(),
+            emitting: self.emitting.clone(),
+            points: self.points.clone(),
+            uv_offset: self.uv_offset,
+            // Geometry is regenerated every frame, it must not be shared between clones.
+            surface_data: self.surface_data.deep_clone(),
+        }
+    }
+}
+
+impl Default for Trail {
+    fn default() -> Self {
+        TrailBuilder::new(BaseBuilder::new()).build_trail()
+    }
+}
+
+impl Deref for Trail {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Trail {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Trail {
+    fn type_uuid() -> Uuid {
+        uuid!("3d0f7a52-6d0b-4c1e-9a8f-2b7c51e4d6a9")
+    }
+}
+
+impl Trail {
+    /// Sets new material of the trail.
+    pub fn set_material(&mut self, material: SharedMaterial) -> SharedMaterial {
+        self.material.set_value_and_mark_modified(material)
+    }
+
+    /// Returns current material of the trail.
+    pub fn material(&self) -> &SharedMaterial {
+        &self.material
+    }
+
+    /// Sets new lifetime (in seconds) of the points of the trail. It defines the length of the trail.
+    pub fn set_lifetime(&mut self, lifetime: f32) -> f32 {
+        self.lifetime.set_value_and_mark_modified(lifetime.max(0.0))
+    }
+
+    /// Returns current lifetime (in seconds) of the points of the trail.
+    pub fn lifetime(&self) -> f32 {
+        *self.lifetime
+    }
+
+    /// Sets width of the trail at its newest point.
+    pub fn set_start_width(&mut self, width: f32) -> f32 {
+        self.start_width.set_value_and_mark_modified(width.max(0.0))
+    }
+
+    /// Returns width of the trail at its newest point.
+    pub fn start_width(&self) -> f32 {
+        *self.start_width
+    }
+
+    /// Sets width of the trail at the end of the lifetime of its points.
+    pub fn set_end_width(&mut self, width: f32) -> f32 {
+        self.end_width.set_value_and_mark_modified(width.max(0.0))
+    }
+
+    /// Returns width of the trail at the end of the lifetime of its points.
+    pub fn end_width(&self) -> f32 {
+        *self.end_width
+    }
+
+    /// Sets new color gradient that will evaluate color of the trail over lifetime of its points.
+    pub fn set_color_over_lifetime(&mut self, gradient: ColorGradient) -> ColorGradient {
+        self.color_over_lifetime
+            .set_value_and_mark_modified(gradient)
+    }
+
+    /// Returns current color gradient of the trail.
+    pub fn color_over_lifetime(&self) -> &ColorGradient {
+        &self.color_over_lifetime
+    }
+
+    /// Sets minimal distance the node must travel to leave a new point behind. Smaller values
+    /// produce smoother trails at the cost of more geometry.
+    pub fn set_min_segment_length(&mut self, length: f32) -> f32 {
+        self.min_segment_length
+            .set_value_and_mark_modified(length.max(0.001))
+    }
+
+    /// Returns minimal distance between points of the trail.
+    pub fn min_segment_length(&self) -> f32 {
+        *self.min_segment_length
+    }
+
+    /// Sets maximum amount of segments of the trail. Oldest points are removed when the limit is
+    /// exceeded.
+    pub fn set_max_segments(&mut self, max_segments: u32) -> u32 {
+        self.max_segments
+            .set_value_and_mark_modified(max_segments.max(1))
+    }
+
+    /// Returns maximum amount of segments of the trail.
+    pub fn max_segments(&self) -> u32 {
+        *self.max_segments
+    }
+
+    /// Sets new alignment of the trail. See [`TrailAlignment`] docs for more info.
+    pub fn set_alignment(&mut self, alignment: TrailAlignment) -> TrailAlignment {
+        self.alignment.set_value_and_mark_modified(alignment)
+    }
+
+    /// Returns current alignment of the trail.
+    pub fn alignment(&self) -> TrailAlignment {
+        *self.alignment
+    }
+
+    /// Sets new texture mode of the trail. See [`TrailTextureMode`] docs for more info.
+    pub fn set_texture_mode(&mut self, mode: TrailTextureMode) -> TrailTextureMode {
+        self.texture_mode.set_value_and_mark_modified(mode)
+    }
+
+    /// Returns current texture mode of the trail.
+    pub fn texture_mode(&self) -> TrailTextureMode {
+        *self.texture_mode
+    }
+
+    /// Sets length of a single texture tile along the trail. Used only by [`TrailTextureMode::Tile`].
+    pub fn set_texture_tile_length(&mut self, length: f32) -> f32 {
+        self.texture_tile_length
+            .set_value_and_mark_modified(length.max(0.001))
+    }
+
+    /// Returns length of a single texture tile along the trail.
+    pub fn texture_tile_length(&self) -> f32 {
+        *self.texture_tile_length
+    }
+
+    /// Sets speed (in texture coordinates per second) of texture scrolling along the trail.
+    pub fn set_uv_scroll_speed(&mut self, speed: f32) -> f32 {
+        self.uv_scroll_speed.set_value_and_mark_modified(speed)
+    }
+
+    /// Returns speed of texture scrolling along the trail.
+    pub fn uv_scroll_speed(&self) -> f32 {
+        *self.uv_scroll_speed
+    }
+
+    /// Enables or disables emission of new points. Existing points will fade out when the emission
+    /// is disabled.
+    pub fn set_emitting(&mut self, emitting: bool) -> bool {
+        self.emitting.set_value_and_mark_modified(emitting)
+    }
+
+    /// Returns `true` if the trail emits new points, `false` - otherwise.
+    pub fn is_emitting(&self) -> bool {
+        *self.emitting
+    }
+
+    /// Returns a slice with current points of the trail, from the oldest to the newest one.
+    pub fn points(&self) -> &[TrailPoint] {
+        &self.points
+    }
+
+    /// Removes every point of the trail. Useful to prevent a long stripe after teleportation.
+    pub fn clear_points(&mut self) {
+        self.points.clear();
+        self.rebuild_geometry();
+    }
+
+    fn emission_side(&self) -> Vector3<f32> {
+        let side = match *self.alignment {
+            TrailAlignment::LocalX => self.side_vector(),
+            TrailAlignment::LocalY => self.up_vector(),
+            TrailAlignment::LocalZ => self.look_vector(),
+            TrailAlignment::View | TrailAlignment::Horizontal => return Vector3::default(),
+        };
+        side.try_normalize(f32::EPSILON).unwrap_or_default()
+    }
+
+    fn tick(&mut self, dt: f32, emitter: Option<(Vector3<f32>, Vector3<f32>)>) {
+        let lifetime = *self.lifetime;
+        for point in self.points.iter_mut() {
+            point.age += dt;
+        }
+        self.points.retain(|point| point.age < lifetime);
+
+        if let Some((position, side)) = emitter {
+            let new_point = TrailPoint {
+                position,
+                side,
+                age: 0.0,
+            };
+
+            let count = self.points.len();
+            if count >= 2
+                && (self.points[count - 2].position - position).norm() < *self.min_segment_length
+            {
+                // The newest point follows the emitter until it moves far enough from the previous one.
+                self.points[count - 1] = new_point;
+            } else if self
+                .points
+                .last()
+                .map(|last| last.position != position)
+                .unwrap_or(true)
+            {
+                self.points.push(new_point);
+            }
+        }
+
+        let max_points = *self.max_segments as usize + 1;
+        if self.points.len() > max_points {
+            let excess = self.points.len() - max_points;
+            self.points.drain(..excess);
+        }
+
+        self.uv_offset = (self.uv_offset + *self.uv_scroll_speed * dt).fract();
+
+        self.rebuild_geometry();
+    }
+
+    fn rebuild_geometry(&mut self) {
+        let mut vertices = Vec::with_capacity(self.points.len() * 2);
+        let mut triangles = Vec::with_capacity(self.points.len().saturating_sub(1) * 2);
+
+        if self.points.len() >= 2 {
+            let total_length = self
+                .points
+                .windows(2)
+                .map(|pair| (pair[1].position - pair[0].position).norm())
+                .sum::<f32>()
+                .max(f32::EPSILON);
+
+            let lifetime = self.lifetime.max(f32::EPSILON);
+            let last = self.points.len() - 1;
+            // Distance is measured from the newest point, so the texture stays attached to the emitter.
+            let mut distance = 0.0;
+            for i in (0..=last).rev() {
+                let point = &self.points[i];
+
+                if i < last {
+                    distance += (self.points[i + 1].position - point.position).norm();
+                }
+
+                let prev = self.points[i.saturating_sub(1)].position;
+                let next = self.points[(i + 1).min(last)].position;
+                let tangent = (next - prev)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::x);
+
+                let t = (point.age / lifetime).min(1.0);
+                let half_width =
+                    0.5 * (*self.start_width + (*self.end_width - *self.start_width) * t);
+                let color = self.color_over_lifetime.get_color(t).srgb_to_linear_f32();
+
+                let u = match *self.texture_mode {
+                    TrailTextureMode::Stretch => distance / total_length,
+                    TrailTextureMode::Tile => distance / *self.texture_tile_length,
+                } - self.uv_offset;
+
+                let (left, right, direction) = match *self.alignment {
+                    TrailAlignment::View => (
+                        point.position,
+                        point.position,
+                        [tangent.push(-half_width), tangent.push(half_width)],
+                    ),
+                    TrailAlignment::Horizontal => {
+                        let side = tangent
+                            .cross(&Vector3::y())
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or_else(Vector3::x)
+                            .scale(half_width);
+                        (
+                            point.position - side,
+                            point.position + side,
+                            [tangent.push(0.0), tangent.push(0.0)],
+                        )
+                    }
+                    TrailAlignment::LocalX | TrailAlignment::LocalY | TrailAlignment::LocalZ => {
+                        let side = point.side.scale(half_width);
+                        (
+                            point.position - side,
+                            point.position + side,
+                            [tangent.push(0.0), tangent.push(0.0)],
+                        )
+                    }
+                };
+
+                vertices.push(TrailVertex {
+                    position: left,
+                    tex_coord: Vector2::new(u, 0.0),
+                    color,
+                    direction: direction[0],
+                });
+                vertices.push(TrailVertex {
+                    position: right,
+                    tex_coord: Vector2::new(u, 1.0),
+                    color,
+                    direction: direction[1],
+                });
+            }
+
+            for i in 0..last as u32 {
+                let a = i * 2;
+                triangles.push(TriangleDefinition([a, a + 1, a + 2]));
+                triangles.push(TriangleDefinition([a + 1, a + 3, a + 2]));
+            }
+        }
+
+        let mut data = self.surface_data.lock();
+        data.vertex_buffer = VertexBuffer::new(vertices.len(), vertices).unwrap();
+        data.geometry_buffer = TriangleBuffer::new(triangles);
+    }
+}
+
+impl NodeTrait for Trail {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if self.points.is_empty() {
+            return self.base.world_bounding_box();
+        }
+
+        let mut bounding_box = AxisAlignedBoundingBox::default();
+        for point in self.points.iter() {
+            bounding_box.add_point(point.position);
+        }
+        let half_width = 0.5 * self.start_width.max(*self.end_width);
+        bounding_box.inflate(Vector3::repeat(half_width));
+        bounding_box
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let emitter = if *self.emitting && self.is_globally_enabled() {
+            Some((self.global_position(), self.emission_side()))
+        } else {
+            None
+        };
+
+        self.tick(context.dt, emitter);
+    }
+
+    fn collect_render_data(&self, ctx: &mut RenderContext) {
+        if self.points.len() < 2
+            || !self.global_visibility()
+            || !self.is_globally_enabled()
+            || renderer::is_shadow_pass(ctx.render_pass_name)
+            || !ctx.is_node_in_frustum()
+        {
+            return;
+        }
+
+        ctx.storage.push(
+            &self.surface_data,
+            &self.material,
+            RenderPath::Forward,
+            0,
+            0,
+            SurfaceInstanceData {
+                // Geometry of the trail is already in world space.
+                world_transform: Matrix4::identity(),
+                bone_matrices: Default::default(),
+                depth_offset: 0.0,
+                blend_shapes_weights: Default::default(),
+                element_range: ElementRange::Full,
+                persistent_identifier: PersistentIdentifier::new_combined(
+                    &self.surface_data,
+                    ctx.node_handle,
+                    0,
+                ),
+                node_handle: ctx.node_handle,
+            },
+        );
+    }
+}
+
+/// Allows you to create a trail in a declarative manner.
+pub struct TrailBuilder {
+    base_builder: BaseBuilder,
+    material: Option<SharedMaterial>,
+    lifetime: f32,
+    start_width: f32,
+    end_width: f32,
+    color_over_lifetime: ColorGradient,
+    min_segment_length: f32,
+    max_segments: u32,
+    alignment: TrailAlignment,
+    texture_mode: TrailTextureMode,
+    texture_tile_length: f32,
+    uv_scroll_speed: f32,
+    emitting: bool,
+}
+
+impl TrailBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        let mut color_over_lifetime = ColorGradient::new();
+        color_over_lifetime.add_point(GradientPoint::new(0.0, Color::WHITE));
+        color_over_lifetime.add_point(GradientPoint::new(1.0, Color::from_rgba(255, 255, 255, 0)));
+
+        Self {
+            base_builder,
+            material: None,
+            lifetime: 1.0,
+            start_width: 0.5,
+            end_width: 0.0,
+            color_over_lifetime,
+            min_segment_length: 0.1,
+            max_segments: 128,
+            alignment: Default::default(),
+            texture_mode: Default::default(),
+            texture_tile_length: 1.0,
+            uv_scroll_speed: 0.0,
+            emitting: true,
+        }
+    }
+
+    /// Sets desired material of the trail. By default, the trail uses a material with the standard
+    /// trail shader.
+    pub fn with_material(mut self, material: SharedMaterial) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    /// Sets desired lifetime (in seconds) of the points of the trail.
+    pub fn with_lifetime(mut self, lifetime: f32) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Sets desired width of the trail at its newest point.
+    pub fn with_start_width(mut self, width: f32) -> Self {
+        self.start_width = width;
+        self
+    }
+
+    /// Sets desired width of the trail at the end of the lifetime of its points.
+    pub fn with_end_width(mut self, width: f32) -> Self {
+        self.end_width = width;
+        self
+    }
+
+    /// Sets desired color gradient over lifetime of the points of the trail.
+    pub fn with_color_over_lifetime(mut self, gradient: ColorGradient) -> Self {
+        self.color_over_lifetime = gradient;
+        self
+    }
+
+    /// Sets desired minimal distance between points of the trail.
+    pub fn with_min_segment_length(mut self, length: f32) -> Self {
+        self.min_segment_length = length;
+        self
+    }
+
+    /// Sets desired maximum amount of segments of the trail.
+    pub fn with_max_segments(mut self, max_segments: u32) -> Self {
+        self.max_segments = max_segments;
+        self
+    }
+
+    /// Sets desired alignment of the trail.
+    pub fn with_alignment(mut self, alignment: TrailAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets desired texture mode of the trail.
+    pub fn with_texture_mode(mut self, mode: TrailTextureMode) -> Self {
+        self.texture_mode = mode;
+        self
+    }
+
+    /// Sets desired length of a single texture tile along the trail.
+    pub fn with_texture_tile_length(mut self, length: f32) -> Self {
+        self.texture_tile_length = length;
+        self
+    }
+
+    /// Sets desired speed of texture scrolling along the trail.
+    pub fn with_uv_scroll_speed(mut self, speed: f32) -> Self {
+        self.uv_scroll_speed = speed;
+        self
+    }
+
+    /// Sets whether the trail should emit new points or not.
+    pub fn with_emitting(mut self, emitting: bool) -> Self {
+        self.emitting = emitting;
+        self
+    }
+
+    /// Creates new trail.
+    pub fn build_trail(self) -> Trail {
+        let material = self.material.unwrap_or_else(|| {
+            SharedMaterial::new(Material::from_shader(
+                ShaderResource::standard_trail(),
+                None,
+            ))
+        });
+
+        Trail {
+            base: self.base_builder.build_base(),
+            material: material.into(),
+            lifetime: self.lifetime.max(0.0).into(),
+            start_width: self.start_width.max(0.0).into(),
+            end_width: self.end_width.max(0.0).into(),
+            color_over_lifetime: self.color_over_lifetime.into(),
+            min_segment_length: self.min_segment_length.max(0.001).into(),
+            max_segments: self.max_segments.max(1).into(),
+            alignment: self.alignment.into(),
+            texture_mode: self.texture_mode.into(),
+            texture_tile_length: self.texture_tile_length.max(0.001).into(),
+            uv_scroll_speed: self.uv_scroll_speed.into(),
+            emitting: self.emitting.into(),
+            points: Default::default(),
+            uv_offset: 0.0,
+            surface_data: SurfaceSharedData::new(SurfaceData::new(
+                VertexBuffer::new::<TrailVertex>(0, Vec::new()).unwrap(),
+                TriangleBuffer::default(),
+                false,
+            )),
+        }
+    }
+
+    /// Creates new trail node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_trail())
+    }
+
+    /// Creates new instance of trail node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            trail::{TrailAlignment, TrailBuilder},
+        },
+    };
+
+    #[test]
+    fn test_trail_points_and_geometry() {
+        let mut trail = TrailBuilder::new(BaseBuilder::new())
+            .with_lifetime(1.0)
+            .with_min_segment_length(1.0)
+            .with_alignment(TrailAlignment::Horizontal)
+            .build_trail();
+
+        let side = Vector3::default();
+        trail.tick(0.1, Some((Vector3::new(0.0, 0.0, 0.0), side)));
+        trail.tick(0.1, Some((Vector3::new(0.5, 0.0, 0.0), side)));
+        assert_eq!(trail.points().len(), 2);
+
+        // The newest point follows the emitter while it is close to the previous point.
+        trail.tick(0.1, Some((Vector3::new(0.8, 0.0, 0.0), side)));
+        assert_eq!(trail.points().len(), 2);
+        assert_eq!(trail.points()[1].position, Vector3::new(0.8, 0.0, 0.0));
+
+        trail.tick(0.1, Some((Vector3::new(1.5, 0.0, 0.0), side)));
+        assert_eq!(trail.points().len(), 3);
+
+        {
+            let data = trail.surface_data.lock();
+            assert_eq!(data.vertex_buffer.vertex_count(), 6);
+            assert_eq!(data.geometry_buffer.len(), 4);
+        }
+
+        // Every point expires when the emitter stops.
+        trail.tick(2.0, None);
+        assert!(trail.points().is_empty());
+        assert_eq!(trail.surface_data.lock().vertex_buffer.vertex_count(), 0);
+    }
+}
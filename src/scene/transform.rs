@@ -51,8 +51,44 @@ use crate::core::{
     variable::InheritableVariable,
     visitor::{Visit, VisitResult, Visitor},
 };
+use crate::scene::base::HierarchyNotifier;
 use std::cell::Cell;
 
+// Indicates that some property of a transform has changed since the last time the scene graph has
+// calculated global transform of its node. Unlike the `dirty` flag of the transform, it is not reset when
+// the matrix is fetched. The first change puts the node to the dirty list of its graph. Copies of a
+// transform are always marked as changed, because they're usually assigned to some other node.
+#[derive(Debug)]
+struct ChangeFlag {
+    changed: Cell<bool>,
+    notifier: Option<HierarchyNotifier>,
+}
+
+impl Default for ChangeFlag {
+    fn default() -> Self {
+        Self {
+            changed: Cell::new(true),
+            notifier: None,
+        }
+    }
+}
+
+impl Clone for ChangeFlag {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl ChangeFlag {
+    fn mark(&self) {
+        if !self.changed.replace(true) {
+            if let Some(notifier) = self.notifier.as_ref() {
+                notifier.notify();
+            }
+        }
+    }
+}
+
 /// See module docs.
 #[derive(Clone, Debug, Reflect)]
 pub struct Transform {
@@ -61,6 +97,9 @@ pub struct Transform {
     #[reflect(hidden)]
     dirty: Cell<bool>,
 
+    #[reflect(hidden)]
+    changed: ChangeFlag,
+
     #[reflect(
         description = "Local scale of the transform",
         setter = "set_scale_internal",
@@ -151,6 +190,7 @@ impl Visit for Transform {
         if visitor.is_reading() {
             self.post_rotation_matrix =
                 build_post_rotation_matrix(self.post_rotation.clone_inner());
+            self.invalidate();
         }
 
         Ok(())
@@ -183,6 +223,7 @@ impl Transform {
     pub fn identity() -> Self {
         Self {
             dirty: Cell::new(true),
+            changed: Default::default(),
             local_position: InheritableVariable::new_modified(Vector3::default()),
             local_scale: InheritableVariable::new_modified(Vector3::new(1.0, 1.0, 1.0)),
             local_rotation: InheritableVariable::new_modified(UnitQuaternion::identity()),
@@ -214,7 +255,7 @@ impl Transform {
 
     #[inline]
     fn set_position_internal(&mut self, local_position: Vector3<f32>) -> Vector3<f32> {
-        self.dirty.set(true);
+        self.invalidate();
         self.local_position
             .set_value_and_mark_modified(local_position)
     }
@@ -239,7 +280,7 @@ impl Transform {
         &mut self,
         local_rotation: UnitQuaternion<f32>,
     ) -> UnitQuaternion<f32> {
-        self.dirty.set(true);
+        self.invalidate();
         self.local_rotation
             .set_value_and_mark_modified(local_rotation)
     }
@@ -261,7 +302,7 @@ impl Transform {
 
     #[inline]
     fn set_scale_internal(&mut self, local_scale: Vector3<f32>) -> Vector3<f32> {
-        self.dirty.set(true);
+        self.invalidate();
         self.local_scale.set_value_and_mark_modified(local_scale)
     }
 
@@ -281,7 +322,7 @@ impl Transform {
         &mut self,
         pre_rotation: UnitQuaternion<f32>,
     ) -> UnitQuaternion<f32> {
-        self.dirty.set(true);
+        self.invalidate();
         self.pre_rotation.set_value_and_mark_modified(pre_rotation)
     }
 
@@ -308,7 +349,7 @@ impl Transform {
         post_rotation: UnitQuaternion<f32>,
     ) -> UnitQuaternion<f32> {
         self.post_rotation_matrix = build_post_rotation_matrix(post_rotation);
-        self.dirty.set(true);
+        self.invalidate();
         self.post_rotation
             .set_value_and_mark_modified(post_rotation)
     }
@@ -331,7 +372,7 @@ impl Transform {
 
     #[inline]
     fn set_rotation_offset_internal(&mut self, rotation_offset: Vector3<f32>) -> Vector3<f32> {
-        self.dirty.set(true);
+        self.invalidate();
         self.rotation_offset
             .set_value_and_mark_modified(rotation_offset)
     }
@@ -355,7 +396,7 @@ impl Transform {
 
     #[inline]
     fn set_rotation_pivot_internal(&mut self, rotation_pivot: Vector3<f32>) -> Vector3<f32> {
-        self.dirty.set(true);
+        self.invalidate();
         self.rotation_pivot
             .set_value_and_mark_modified(rotation_pivot)
     }
@@ -372,14 +413,14 @@ impl Transform {
     pub fn set_scaling_offset(&mut self, scaling_offset: Vector3<f32>) -> &mut Self {
         if self.dirty.get() || *self.scaling_offset != scaling_offset {
             self.set_scaling_offset_internal(scaling_offset);
-            self.dirty.set(true);
+            self.invalidate();
         }
         self
     }
 
     #[inline]
     fn set_scaling_offset_internal(&mut self, scaling_offset: Vector3<f32>) -> Vector3<f32> {
-        self.dirty.set(true);
+        self.invalidate();
         self.scaling_offset
             .set_value_and_mark_modified(scaling_offset)
     }
@@ -396,14 +437,14 @@ impl Transform {
     pub fn set_scaling_pivot(&mut self, scaling_pivot: Vector3<f32>) -> &mut Self {
         if self.dirty.get() || *self.scaling_pivot != scaling_pivot {
             self.set_scaling_pivot_internal(scaling_pivot);
-            self.dirty.set(true);
+            self.invalidate();
         }
         self
     }
 
     #[inline]
     fn set_scaling_pivot_internal(&mut self, scaling_pivot: Vector3<f32>) -> Vector3<f32> {
-        self.dirty.set(true);
+        self.invalidate();
         self.scaling_pivot
             .set_value_and_mark_modified(scaling_pivot)
     }
@@ -420,10 +461,30 @@ impl Transform {
     pub fn offset(&mut self, vec: Vector3<f32>) -> &mut Self {
         self.local_position
             .set_value_and_mark_modified(*self.local_position + vec);
-        self.dirty.set(true);
+        self.invalidate();
         self
     }
 
+    #[inline]
+    fn invalidate(&self) {
+        self.dirty.set(true);
+        self.changed.mark();
+    }
+
+    /// Returns `true` if any property of the transform has changed since the last call of this method. It
+    /// is used by the scene graph to skip global transform calculation for unchanged nodes.
+    pub(crate) fn take_changed(&self) -> bool {
+        self.changed.changed.replace(false)
+    }
+
+    pub(crate) fn set_notifier(&mut self, notifier: Option<HierarchyNotifier>) {
+        self.changed.notifier = notifier;
+    }
+
+    pub(crate) fn has_notifier(&self) -> bool {
+        self.changed.notifier.is_some()
+    }
+
     fn calculate_local_transform(&self) -> Matrix4<f32> {
         // Make shortcuts to remove visual clutter.
         let por = &self.post_rotation_matrix;
@@ -651,6 +712,7 @@ impl TransformBuilder {
     pub fn build(self) -> Transform {
         Transform {
             dirty: Cell::new(true),
+            changed: Default::default(),
             local_scale: self.local_scale.into(),
             local_position: self.local_position.into(),
             local_rotation: self.local_rotation.into(),
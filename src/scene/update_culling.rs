@@ -0,0 +1,227 @@
+//! Update culling allows to pause (or slow down) the update of expensive scene nodes (such as
+//! particle systems or animation players), when they're outside of every active camera frustum or
+//! too far away from every camera. See [`UpdateCulling`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::{camera::Camera, graph::NodePool},
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Defines what happens with a node when it is culled.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Reflect, Visit, Default, AsRefStr, EnumString, EnumVariantNames,
+)]
+pub enum UpdateCullingMode {
+    /// Update culling is disabled, the node is updated every frame.
+    #[default]
+    Disabled,
+    /// The node is not updated while it is culled, its time is "frozen".
+    Pause,
+    /// The node is updated with reduced rate while it is culled. Time that was passed between
+    /// updates is accumulated and passed to the next update, so the node won't fall behind.
+    ReducedRate {
+        /// Minimal time (in seconds) between two updates of a culled node.
+        interval: f32,
+    },
+}
+
+/// A point of view that is used to decide whether a node should be culled or not. Observers are
+/// collected from every enabled camera of a graph on each update.
+#[derive(Clone, Debug)]
+pub struct UpdateObserver {
+    /// Position of the observer in world coordinates.
+    pub position: Vector3<f32>,
+    /// Frustum of the observer in world coordinates.
+    pub frustum: Frustum,
+}
+
+impl UpdateObserver {
+    pub(crate) fn collect(nodes: &NodePool) -> Vec<Self> {
+        nodes
+            .iter()
+            .filter_map(|node| node.cast::<Camera>())
+            .filter(|camera| camera.is_enabled() && camera.is_globally_enabled())
+            .map(|camera| Self {
+                position: camera.global_position(),
+                frustum: camera.frustum(),
+            })
+            .collect()
+    }
+}
+
+/// Update culling settings of a node. A node is culled when its bounds are outside of every
+/// observer frustum or when it is farther than [`Self::max_distance`] from every observer. What
+/// happens with a culled node is defined by [`Self::mode`].
+///
+/// # Hysteresis
+///
+/// To prevent nodes from rapid switching between culled and non-culled states (which could produce
+/// visible "pops"), a node that is updated normally becomes culled only when it is farther than
+/// [`Self::hysteresis`] units away from the culling boundary. Culled node returns to normal update
+/// as soon as it crosses the boundary back.
+///
+/// # Important notes
+///
+/// Culling is disabled when a graph has no enabled cameras, for example on dedicated servers.
+#[derive(Clone, Debug, PartialEq, Reflect, Visit)]
+pub struct UpdateCulling {
+    /// Defines what happens with the node when it is culled.
+    pub mode: UpdateCullingMode,
+    /// Maximum distance from an observer at which the node is updated normally.
+    #[reflect(min_value = 0.0)]
+    pub max_distance: f32,
+    /// Additional radius that is added to the bounds of the node. Bounds of some nodes (such as
+    /// particle systems) do not include their visual extents, this margin could be used to prevent
+    /// them from culling while they're still visible.
+    #[reflect(min_value = 0.0)]
+    pub margin: f32,
+    /// Distance (in world units) the node must go beyond the culling boundary before it becomes
+    /// culled.
+    #[reflect(min_value = 0.0)]
+    pub hysteresis: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    culled: bool,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    accumulated_time: f32,
+}
+
+impl Default for UpdateCulling {
+    fn default() -> Self {
+        Self {
+            mode: Default::default(),
+            max_distance: f32::MAX,
+            margin: 1.0,
+            hysteresis: 1.0,
+            culled: false,
+            accumulated_time: 0.0,
+        }
+    }
+}
+
+impl UpdateCulling {
+    /// Returns `true` if the node was culled on last update.
+    pub fn is_culled(&self) -> bool {
+        self.culled
+    }
+
+    /// Checks whether the node with the given bounds should be culled or not and returns the time
+    /// the node should be advanced by. `None` means that the node must not be updated this frame.
+    pub fn update(
+        &mut self,
+        bounds: &AxisAlignedBoundingBox,
+        observers: &[UpdateObserver],
+        dt: f32,
+    ) -> Option<f32> {
+        if self.mode == UpdateCullingMode::Disabled || observers.is_empty() {
+            self.culled = false;
+            return Some(dt + std::mem::take(&mut self.accumulated_time));
+        }
+
+        let extra = if self.culled { 0.0 } else { self.hysteresis };
+        let center = bounds.center();
+        let radius = bounds.half_extents().norm() + self.margin + extra;
+        let max_distance = self.max_distance + extra;
+
+        let visible = observers.iter().any(|observer| {
+            (observer.position - center).norm() <= max_distance
+                && observer.frustum.is_intersects_sphere(center, radius)
+        });
+
+        self.culled = !visible;
+
+        if visible {
+            return Some(dt + std::mem::take(&mut self.accumulated_time));
+        }
+
+        match self.mode {
+            UpdateCullingMode::Disabled => unreachable!(),
+            UpdateCullingMode::Pause => None,
+            UpdateCullingMode::ReducedRate { interval } => {
+                self.accumulated_time += dt;
+                if self.accumulated_time >= interval {
+                    Some(std::mem::take(&mut self.accumulated_time))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Point3, Vector3},
+            math::{aabb::AxisAlignedBoundingBox, frustum::Frustum},
+        },
+        scene::update_culling::{UpdateCulling, UpdateCullingMode, UpdateObserver},
+    };
+
+    fn observer() -> UpdateObserver {
+        // Looks along -Z from the origin.
+        let view = Matrix4::look_at_rh(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Point3::new(0.0, 0.0, -1.0),
+            &Vector3::y(),
+        );
+        let projection = Matrix4::new_perspective(1.0, 90.0f32.to_radians(), 0.1, 1000.0);
+        UpdateObserver {
+            position: Vector3::default(),
+            frustum: Frustum::from_view_projection_matrix(projection * view).unwrap(),
+        }
+    }
+
+    fn bounds_at(position: Vector3<f32>) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::from_min_max(
+            position - Vector3::repeat(0.5),
+            position + Vector3::repeat(0.5),
+        )
+    }
+
+    #[test]
+    fn test_update_culling() {
+        let observers = [observer()];
+        let visible = bounds_at(Vector3::new(0.0, 0.0, -10.0));
+        let behind = bounds_at(Vector3::new(0.0, 0.0, 10.0));
+
+        let mut culling = UpdateCulling {
+            mode: UpdateCullingMode::Pause,
+            ..Default::default()
+        };
+        assert_eq!(culling.update(&visible, &observers, 0.1), Some(0.1));
+        assert_eq!(culling.update(&behind, &observers, 0.1), None);
+        assert!(culling.is_culled());
+
+        // No observers - no culling.
+        assert_eq!(culling.update(&behind, &[], 0.1), Some(0.1));
+
+        let mut culling = UpdateCulling {
+            mode: UpdateCullingMode::ReducedRate { interval: 0.3 },
+            max_distance: 20.0,
+            hysteresis: 5.0,
+            ..Default::default()
+        };
+        // Inside hysteresis zone - still updated normally.
+        let far = bounds_at(Vector3::new(0.0, 0.0, -23.0));
+        assert_eq!(culling.update(&far, &observers, 0.125), Some(0.125));
+        let farther = bounds_at(Vector3::new(0.0, 0.0, -30.0));
+        assert_eq!(culling.update(&farther, &observers, 0.125), None);
+        assert_eq!(culling.update(&farther, &observers, 0.125), None);
+        // Culled node must cross the boundary itself to become non-culled.
+        assert_eq!(culling.update(&far, &observers, 0.125), Some(0.375));
+        assert!(culling.is_culled());
+        // Accumulated time is flushed when the node becomes visible again.
+        assert_eq!(culling.update(&farther, &observers, 0.125), None);
+        assert_eq!(culling.update(&visible, &observers, 0.125), Some(0.25));
+        assert!(!culling.is_culled());
+    }
+}
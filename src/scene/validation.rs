@@ -0,0 +1,296 @@
+//! Contains all structures and methods to validate scenes and to gather statistics about them.
+//!
+//! For more info see [`Scene::validate`].
+
+use crate::{
+    asset::{self, memory::ResourceMemoryUsage, state::ResourceState},
+    core::{pool::Handle, reflect::Reflect},
+    resource::texture::Texture,
+    scene::{
+        light::{point::PointLight, spot::SpotLight},
+        mesh::Mesh,
+        node::Node,
+        Scene,
+    },
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+/// A kind of a problem found during scene validation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneWarningKind {
+    /// A resource used by the node has failed to load.
+    MissingResource {
+        /// Path of the resource.
+        path: PathBuf,
+        /// Description of the load error.
+        reason: String,
+    },
+    /// Local transform of the node contains NaN or infinite values.
+    NonFiniteTransform,
+    /// Local scale of the node has at least one zero component, which makes the transform
+    /// non-invertible.
+    ZeroScale,
+    /// A light source has zero (or negative) radius and does not light anything.
+    ZeroLightRadius,
+    /// The node has reported a problem by itself (see [`crate::scene::node::NodeTrait::validate`]).
+    /// For example, rigid bodies without colliders are reported this way.
+    InvalidNode(String),
+}
+
+impl Display for SceneWarningKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingResource { path, reason } => {
+                write!(f, "Resource {} failed to load: {}", path.display(), reason)
+            }
+            Self::NonFiniteTransform => write!(f, "Local transform contains NaN or infinity"),
+            Self::ZeroScale => write!(f, "Local scale has zero component"),
+            Self::ZeroLightRadius => write!(f, "Light has zero radius"),
+            Self::InvalidNode(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// A problem found during scene validation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneWarning {
+    /// Handle of the node with the problem.
+    pub node: Handle<Node>,
+    /// Name of the node with the problem.
+    pub node_name: String,
+    /// Kind of the problem.
+    pub kind: SceneWarningKind,
+}
+
+impl Display for SceneWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.node_name, self.node, self.kind)
+    }
+}
+
+/// A summary of the scene contents.
+#[derive(Clone, Debug, Default)]
+pub struct SceneStatistics {
+    /// Total amount of nodes in the scene.
+    pub node_count: usize,
+    /// Amount of nodes of each type. Keys are short type names (`Mesh`, `PointLight`, etc.).
+    pub nodes_by_type: FxHashMap<&'static str, usize>,
+    /// Total amount of triangles of all mesh surfaces in the scene.
+    pub triangle_count: usize,
+    /// Amount of unique textures used by the scene.
+    pub texture_count: usize,
+    /// Memory occupied by the textures used by the scene.
+    pub texture_memory: ResourceMemoryUsage,
+}
+
+impl Display for SceneStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Nodes: {}", self.node_count)?;
+        let mut nodes_by_type = self.nodes_by_type.iter().collect::<Vec<_>>();
+        nodes_by_type.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (type_name, count) in nodes_by_type {
+            writeln!(f, "\t{}: {}", type_name, count)?;
+        }
+        writeln!(f, "Triangles: {}", self.triangle_count)?;
+        write!(
+            f,
+            "Textures: {} ({} KiB RAM, {} KiB VRAM)",
+            self.texture_count,
+            self.texture_memory.cpu / 1024,
+            self.texture_memory.gpu / 1024
+        )
+    }
+}
+
+/// Result of scene validation, see [`Scene::validate`] for more info.
+#[derive(Clone, Debug, Default)]
+pub struct SceneValidationReport {
+    /// A list of problems found in the scene.
+    pub warnings: Vec<SceneWarning>,
+    /// A summary of the scene contents.
+    pub statistics: SceneStatistics,
+}
+
+impl SceneValidationReport {
+    /// Returns `true` if there is no problems in the scene.
+    pub fn is_ok(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+impl Display for SceneValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Warnings: {}", self.warnings.len())?;
+        for warning in self.warnings.iter() {
+            writeln!(f, "\t{}", warning)?;
+        }
+        write!(f, "{}", self.statistics)
+    }
+}
+
+fn short_type_name(type_name: &'static str) -> &'static str {
+    // Strip generic arguments first, they could contain `::` too.
+    let type_name = match type_name.find('<') {
+        Some(index) => &type_name[..index],
+        None => type_name,
+    };
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
+impl Scene {
+    /// Checks the scene for common problems and gathers statistics about its contents. It reports
+    /// resources that failed to load, degenerate transforms, lights that cannot light anything and
+    /// every problem reported by [`crate::scene::node::NodeTrait::validate`] (for example, rigid
+    /// bodies without colliders). The method is called at the end of scene loading (the warnings
+    /// are written to the log), but it could also be used by tools at any time.
+    pub fn validate(&self) -> SceneValidationReport {
+        let mut report = SceneValidationReport::default();
+        // Resources are keyed by their stable address, resources themselves are interior-mutable.
+        let mut resources = FxHashMap::default();
+
+        for (handle, node) in self.graph.pair_iter() {
+            let mut warn = |kind| {
+                report.warnings.push(SceneWarning {
+                    node: handle,
+                    node_name: node.name_owned(),
+                    kind,
+                })
+            };
+
+            let mut node_resources = FxHashSet::default();
+            asset::collect_used_resources(node, &mut node_resources);
+            for resource in node_resources.iter() {
+                if let ResourceState::LoadError { path, error, .. } = &*resource.0.lock() {
+                    warn(SceneWarningKind::MissingResource {
+                        path: path.clone(),
+                        reason: match error {
+                            Some(error) => format!("{:?}", error),
+                            None => "Unknown error".to_string(),
+                        },
+                    });
+                }
+            }
+            resources.extend(
+                node_resources
+                    .into_iter()
+                    .map(|resource| (resource.key(), resource)),
+            );
+
+            let transform = node.local_transform();
+            let position = **transform.position();
+            let rotation = **transform.rotation();
+            let scale = **transform.scale();
+            if position.iter().any(|v| !v.is_finite())
+                || rotation.coords.iter().any(|v| !v.is_finite())
+                || scale.iter().any(|v| !v.is_finite())
+            {
+                warn(SceneWarningKind::NonFiniteTransform);
+            } else if scale.iter().any(|v| *v == 0.0) {
+                warn(SceneWarningKind::ZeroScale);
+            }
+
+            if let Some(point_light) = node.cast::<PointLight>() {
+                if point_light.radius() <= 0.0 {
+                    warn(SceneWarningKind::ZeroLightRadius);
+                }
+            } else if let Some(spot_light) = node.cast::<SpotLight>() {
+                if spot_light.distance() <= 0.0 {
+                    warn(SceneWarningKind::ZeroLightRadius);
+                }
+            }
+
+            if let Err(message) = node.validate(self) {
+                warn(SceneWarningKind::InvalidNode(message));
+            }
+
+            let statistics = &mut report.statistics;
+            statistics.node_count += 1;
+            *statistics
+                .nodes_by_type
+                .entry(short_type_name(node.type_name()))
+                .or_default() += 1;
+            if let Some(mesh) = node.cast::<Mesh>() {
+                for surface in mesh.surfaces() {
+                    statistics.triangle_count +=
+                        surface.data().lock().geometry_buffer.triangles_ref().len();
+                }
+            }
+        }
+
+        for resource in resources.values() {
+            if resource.try_cast::<Texture>().is_some() {
+                report.statistics.texture_count += 1;
+                report.statistics.texture_memory += resource.memory_usage();
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            light::{point::PointLightBuilder, BaseLightBuilder},
+            pivot::PivotBuilder,
+            rigidbody::RigidBodyBuilder,
+            transform::TransformBuilder,
+            validation::{short_type_name, SceneWarningKind},
+            Scene,
+        },
+    };
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name("fyrox::scene::mesh::Mesh"), "Mesh");
+        assert_eq!(short_type_name("Foo<bar::Baz>"), "Foo");
+    }
+
+    #[test]
+    fn test_scene_validation() {
+        let mut scene = Scene::new();
+
+        let light = PointLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .with_radius(0.0)
+            .build(&mut scene.graph);
+        let pivot = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(f32::NAN, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+        let body = RigidBodyBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+
+        let report = scene.validate();
+
+        let kind_of = |handle| {
+            report
+                .warnings
+                .iter()
+                .find(|w| w.node == handle)
+                .map(|w| w.kind.clone())
+        };
+        assert_eq!(kind_of(light), Some(SceneWarningKind::ZeroLightRadius));
+        assert_eq!(kind_of(pivot), Some(SceneWarningKind::NonFiniteTransform));
+        assert!(matches!(
+            kind_of(body),
+            Some(SceneWarningKind::InvalidNode(_))
+        ));
+        assert_eq!(report.warnings.len(), 3);
+
+        // Root + 3 nodes.
+        assert_eq!(report.statistics.node_count, 4);
+        assert_eq!(report.statistics.nodes_by_type["Pivot"], 2);
+        assert_eq!(report.statistics.nodes_by_type["PointLight"], 1);
+        assert_eq!(report.statistics.nodes_by_type["RigidBody"], 1);
+    }
+}
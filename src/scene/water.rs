@@ -0,0 +1,764 @@
+//! Contains all structures and methods to create and manage water surfaces (seas, lakes, rivers,
+//! etc.).
+//!
+//! For more info see [`Water`].
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    resource::texture::TextureResource,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, UpdateContext},
+    },
+};
+use std::{
+    f32::consts::PI,
+    ops::{Deref, DerefMut},
+};
+
+/// Maximum amount of waves, that could be rendered. Waves beyond this limit are ignored both by
+/// the renderer and by the height queries.
+pub const MAX_WAVES: usize = 8;
+
+const GRAVITY: f32 = 9.81;
+
+/// A single Gerstner wave. Waves of a water surface are summed together to produce the final shape
+/// of the surface. Speed of a wave is defined by its length using deep water dispersion relation,
+/// so long waves travel faster than short ones.
+#[derive(Copy, Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct WaterWave {
+    /// Direction of travel of the wave in local XZ plane of the water. Does not need to be
+    /// normalized.
+    pub direction: Vector2<f32>,
+
+    /// Height of the crests of the wave above the rest level (in meters).
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub amplitude: f32,
+
+    /// Distance between two neighbouring crests of the wave (in meters).
+    #[reflect(min_value = 0.01, step = 0.1)]
+    pub wavelength: f32,
+
+    /// Defines how sharp the crests of the wave are. Zero gives a sine wave, one gives the sharpest
+    /// possible crests without loops.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub steepness: f32,
+}
+
+impl Default for WaterWave {
+    fn default() -> Self {
+        Self {
+            direction: Vector2::new(1.0, 0.0),
+            amplitude: 0.1,
+            wavelength: 8.0,
+            steepness: 0.5,
+        }
+    }
+}
+
+/// Wave parameters in a form, that is suitable for evaluation. The same values are passed to the
+/// shader, so the height queries match the rendered surface.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WaveParameters {
+    /// Normalized direction of the wave.
+    pub direction: Vector2<f32>,
+    /// Wave number (`2 * PI / wavelength`).
+    pub wave_number: f32,
+    /// Amplitude of the wave.
+    pub amplitude: f32,
+    /// Angular frequency of the wave.
+    pub angular_frequency: f32,
+    /// Horizontal displacement factor, that defines steepness of crests.
+    pub steepness: f32,
+}
+
+/// Converts the given waves into a set of parameters, that is used to evaluate the surface.
+pub fn wave_parameters(waves: &[WaterWave]) -> Vec<WaveParameters> {
+    let count = waves.len().min(MAX_WAVES);
+    waves
+        .iter()
+        .take(count)
+        .map(|wave| {
+            let wave_number = 2.0 * PI / wave.wavelength.max(0.01);
+            let amplitude = wave.amplitude.max(0.0);
+            let steepness = if amplitude > 0.0 {
+                wave.steepness.clamp(0.0, 1.0) / (wave_number * amplitude * count as f32)
+            } else {
+                0.0
+            };
+            WaveParameters {
+                direction: wave
+                    .direction
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector2::x),
+                wave_number,
+                amplitude,
+                angular_frequency: (GRAVITY * wave_number).sqrt(),
+                steepness,
+            }
+        })
+        .collect()
+}
+
+/// Calculates displacement of a point of the surface, that is at the given position when the
+/// surface is at rest. Returns the displacement and the normal of the surface at the point.
+pub fn evaluate_waves(
+    waves: &[WaveParameters],
+    rest_position: Vector2<f32>,
+    time: f32,
+) -> (Vector3<f32>, Vector3<f32>) {
+    let mut displacement = Vector3::default();
+    let mut normal = Vector3::new(0.0, 1.0, 0.0);
+    for wave in waves {
+        let phase =
+            wave.wave_number * wave.direction.dot(&rest_position) - wave.angular_frequency * time;
+        let (sin, cos) = phase.sin_cos();
+        let horizontal = wave.direction * (wave.steepness * wave.amplitude * cos);
+        displacement.x += horizontal.x;
+        displacement.y += wave.amplitude * sin;
+        displacement.z += horizontal.y;
+
+        let wa = wave.wave_number * wave.amplitude;
+        normal.x -= wave.direction.x * wa * cos;
+        normal.y -= wave.steepness * wa * sin;
+        normal.z -= wave.direction.y * wa * cos;
+    }
+    (
+        displacement,
+        normal
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y),
+    )
+}
+
+/// A point of a water surface, found by [`sample_surface`] or [`Water::sample`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WaterSample {
+    /// Position of the point on the surface.
+    pub position: Vector3<f32>,
+    /// Normal of the surface at the point.
+    pub normal: Vector3<f32>,
+}
+
+/// Finds a point of the surface exactly above (or below) the given position in XZ plane. Gerstner
+/// waves move points of the surface horizontally, so the rest position of the point is found
+/// iteratively.
+pub fn sample_surface(waves: &[WaveParameters], position: Vector2<f32>, time: f32) -> WaterSample {
+    let mut rest_position = position;
+    let mut result = evaluate_waves(waves, rest_position, time);
+    for _ in 0..6 {
+        let offset = position - (rest_position + result.0.xz());
+        if offset.norm_squared() < 1.0e-8 {
+            break;
+        }
+        rest_position += offset;
+        result = evaluate_waves(waves, rest_position, time);
+    }
+    let (displacement, normal) = result;
+    WaterSample {
+        position: Vector3::new(position.x, displacement.y, position.y),
+        normal,
+    }
+}
+
+/// Water is a large animated surface, that refracts and reflects the scene. It is suitable for
+/// seas, lakes, ponds, etc.
+///
+/// # Waves
+///
+/// Shape of the surface is defined by a set of Gerstner waves (see [`WaterWave`]), up to
+/// [`MAX_WAVES`]. Waves are evaluated on GPU for rendering, the same waves could be evaluated on
+/// CPU using [`Water::sample`] and [`Water::height_at`], which is useful to make objects floating
+/// (buoyancy). Small details are added by an optional scrolling normal map.
+///
+/// # Appearance
+///
+/// Water refracts the scene behind it, and the light is absorbed depending on the thickness of
+/// the water layer - shallow parts have [`Water::shallow_color`] and gradually become
+/// [`Water::deep_color`]. The scene is reflected using screen-space reflections, and parts of the
+/// reflection, that are not visible on screen, are taken from the skybox of the camera. Foam is
+/// added at the shoreline, where the water is thinner than [`Water::foam_distance`].
+///
+/// # Limitations
+///
+/// Water is rendered after the lighting pass, so it is not lit by point and spot lights and it
+/// does not receive shadows. Only the first directional light of the scene produces specular
+/// highlights. Water does not cast shadows.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector2, pool::Handle},
+///     scene::{
+///         base::BaseBuilder,
+///         graph::Graph,
+///         node::Node,
+///         water::{Water, WaterBuilder},
+///     },
+/// };
+///
+/// fn create_sea(graph: &mut Graph) -> Handle<Node> {
+///     WaterBuilder::new(BaseBuilder::new())
+///         .with_size(Vector2::new(256.0, 256.0))
+///         .build(graph)
+/// }
+///
+/// fn float(graph: &Graph, water: Handle<Node>, x: f32, z: f32) -> Option<f32> {
+///     graph[water]
+///         .cast::<Water>()
+///         .and_then(|water| water.height_at(Vector2::new(x, z)))
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct Water {
+    base: Base,
+
+    #[reflect(
+        min_value = 0.0,
+        description = "Size of the water surface in local XZ plane, in meters.",
+        setter = "set_size"
+    )]
+    size: InheritableVariable<Vector2<f32>>,
+
+    #[reflect(
+        min_value = 1.0,
+        description = "Amount of cells of the surface grid along each side.",
+        setter = "set_resolution"
+    )]
+    resolution: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_waves")]
+    waves: InheritableVariable<Vec<WaterWave>>,
+
+    #[reflect(setter = "set_normal_map")]
+    normal_map: InheritableVariable<Option<TextureResource>>,
+
+    #[reflect(
+        min_value = 0.0,
+        step = 0.1,
+        description = "Size of a tile of the normal map, in meters.",
+        setter = "set_normal_map_tile_size"
+    )]
+    normal_map_tile_size: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_shallow_color")]
+    shallow_color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_deep_color")]
+    deep_color: InheritableVariable<Color>,
+
+    #[reflect(
+        min_value = 0.0,
+        step = 0.01,
+        description = "Defines how fast light is absorbed by the water, per meter.",
+        setter = "set_absorption"
+    )]
+    absorption: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.01, setter = "set_refraction_strength")]
+    refraction_strength: InheritableVariable<f32>,
+
+    #[reflect(
+        min_value = 0.0,
+        max_value = 1.0,
+        step = 0.05,
+        setter = "set_reflection_strength"
+    )]
+    reflection_strength: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_foam_color")]
+    foam_color: InheritableVariable<Color>,
+
+    #[reflect(min_value = 0.0, step = 0.05, setter = "set_foam_distance")]
+    foam_distance: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    time: f32,
+}
+
+impl Default for Water {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            size: InheritableVariable::new_modified(Vector2::new(64.0, 64.0)),
+            resolution: InheritableVariable::new_modified(128),
+            waves: InheritableVariable::new_modified(vec![
+                WaterWave {
+                    direction: Vector2::new(1.0, 0.0),
+                    amplitude: 0.15,
+                    wavelength: 12.0,
+                    steepness: 0.6,
+                },
+                WaterWave {
+                    direction: Vector2::new(0.7, 0.7),
+                    amplitude: 0.08,
+                    wavelength: 5.0,
+                    steepness: 0.5,
+                },
+                WaterWave {
+                    direction: Vector2::new(0.3, -0.9),
+                    amplitude: 0.04,
+                    wavelength: 2.5,
+                    steepness: 0.4,
+                },
+            ]),
+            normal_map: Default::default(),
+            normal_map_tile_size: InheritableVariable::new_modified(4.0),
+            shallow_color: InheritableVariable::new_modified(Color::opaque(60, 170, 180)),
+            deep_color: InheritableVariable::new_modified(Color::opaque(5, 35, 55)),
+            absorption: InheritableVariable::new_modified(0.35),
+            refraction_strength: InheritableVariable::new_modified(0.05),
+            reflection_strength: InheritableVariable::new_modified(1.0),
+            foam_color: InheritableVariable::new_modified(Color::WHITE),
+            foam_distance: InheritableVariable::new_modified(0.4),
+            time: 0.0,
+        }
+    }
+}
+
+impl Deref for Water {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Water {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Water {
+    fn type_uuid() -> Uuid {
+        uuid!("b0f3c6a4-1e2d-4d5b-8a7c-3f9e2d1c0b6a")
+    }
+}
+
+impl Water {
+    /// Sets new size of the surface in local XZ plane.
+    pub fn set_size(&mut self, size: Vector2<f32>) -> Vector2<f32> {
+        self.size.set_value_and_mark_modified(size)
+    }
+
+    /// Returns size of the surface.
+    pub fn size(&self) -> Vector2<f32> {
+        *self.size
+    }
+
+    /// Sets amount of cells of the surface grid along each side. Higher values give smoother
+    /// waves, but take more time to render.
+    pub fn set_resolution(&mut self, resolution: u32) -> u32 {
+        self.resolution
+            .set_value_and_mark_modified(resolution.clamp(1, 1024))
+    }
+
+    /// Returns amount of cells of the surface grid along each side.
+    pub fn resolution(&self) -> u32 {
+        *self.resolution
+    }
+
+    /// Sets new waves of the surface. Only first [`MAX_WAVES`] waves are used.
+    pub fn set_waves(&mut self, waves: Vec<WaterWave>) -> Vec<WaterWave> {
+        self.waves.set_value_and_mark_modified(waves)
+    }
+
+    /// Returns a reference to the waves of the surface.
+    pub fn waves(&self) -> &[WaterWave] {
+        &self.waves
+    }
+
+    /// Returns a mutable reference to the waves of the surface.
+    pub fn waves_mut(&mut self) -> &mut Vec<WaterWave> {
+        self.waves.get_value_mut_and_mark_modified()
+    }
+
+    /// Sets new normal map, that adds small details to the surface. The normal map is scrolled
+    /// in two directions and sampled twice, so it should be tileable.
+    pub fn set_normal_map(
+        &mut self,
+        normal_map: Option<TextureResource>,
+    ) -> Option<TextureResource> {
+        self.normal_map.set_value_and_mark_modified(normal_map)
+    }
+
+    /// Returns current normal map.
+    pub fn normal_map(&self) -> Option<&TextureResource> {
+        self.normal_map.as_ref()
+    }
+
+    /// Sets size of a single tile of the normal map in meters.
+    pub fn set_normal_map_tile_size(&mut self, size: f32) -> f32 {
+        self.normal_map_tile_size
+            .set_value_and_mark_modified(size.max(0.01))
+    }
+
+    /// Returns size of a single tile of the normal map.
+    pub fn normal_map_tile_size(&self) -> f32 {
+        *self.normal_map_tile_size
+    }
+
+    /// Sets color of the shallow water.
+    pub fn set_shallow_color(&mut self, color: Color) -> Color {
+        self.shallow_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns color of the shallow water.
+    pub fn shallow_color(&self) -> Color {
+        *self.shallow_color
+    }
+
+    /// Sets color of the deep water.
+    pub fn set_deep_color(&mut self, color: Color) -> Color {
+        self.deep_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns color of the deep water.
+    pub fn deep_color(&self) -> Color {
+        *self.deep_color
+    }
+
+    /// Sets how fast light is absorbed by the water. The higher the value, the faster shallow water
+    /// becomes deep.
+    pub fn set_absorption(&mut self, absorption: f32) -> f32 {
+        self.absorption
+            .set_value_and_mark_modified(absorption.max(0.0))
+    }
+
+    /// Returns how fast light is absorbed by the water.
+    pub fn absorption(&self) -> f32 {
+        *self.absorption
+    }
+
+    /// Sets how much the waves distort the refracted scene.
+    pub fn set_refraction_strength(&mut self, strength: f32) -> f32 {
+        self.refraction_strength
+            .set_value_and_mark_modified(strength.max(0.0))
+    }
+
+    /// Returns how much the waves distort the refracted scene.
+    pub fn refraction_strength(&self) -> f32 {
+        *self.refraction_strength
+    }
+
+    /// Sets strength of the reflections in `[0; 1]` range.
+    pub fn set_reflection_strength(&mut self, strength: f32) -> f32 {
+        self.reflection_strength
+            .set_value_and_mark_modified(strength.clamp(0.0, 1.0))
+    }
+
+    /// Returns strength of the reflections.
+    pub fn reflection_strength(&self) -> f32 {
+        *self.reflection_strength
+    }
+
+    /// Sets color of the shoreline foam.
+    pub fn set_foam_color(&mut self, color: Color) -> Color {
+        self.foam_color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns color of the shoreline foam.
+    pub fn foam_color(&self) -> Color {
+        *self.foam_color
+    }
+
+    /// Sets thickness of the water layer (in meters), below which the foam appears. Zero disables
+    /// the foam.
+    pub fn set_foam_distance(&mut self, distance: f32) -> f32 {
+        self.foam_distance
+            .set_value_and_mark_modified(distance.max(0.0))
+    }
+
+    /// Returns thickness of the water layer, below which the foam appears.
+    pub fn foam_distance(&self) -> f32 {
+        *self.foam_distance
+    }
+
+    /// Sets the time, that is used to animate the waves. Could be used to synchronize the waves
+    /// over network.
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    /// Returns the time, that is used to animate the waves.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Returns the waves in a form, that is suitable for evaluation.
+    pub fn wave_parameters(&self) -> Vec<WaveParameters> {
+        wave_parameters(&self.waves)
+    }
+
+    /// Finds a point of the surface above (or below) the given point in world XZ plane. Returns
+    /// `None` if the point is outside of the surface. The point and the normal are in world
+    /// coordinates.
+    pub fn sample(&self, position: Vector2<f32>) -> Option<WaterSample> {
+        let transform = self.global_transform();
+        let inv_transform = transform.try_inverse()?;
+        let local =
+            inv_transform.transform_point(&Vector3::new(position.x, 0.0, position.y).into());
+        let half_size = *self.size * 0.5;
+        if local.x.abs() > half_size.x || local.z.abs() > half_size.y {
+            return None;
+        }
+
+        let sample = sample_surface(&self.wave_parameters(), local.xz().coords, self.time);
+
+        Some(WaterSample {
+            position: transform.transform_point(&sample.position.into()).coords,
+            normal: transform
+                .transform_vector(&sample.normal)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y),
+        })
+    }
+
+    /// Returns height of the surface (in world coordinates) above (or below) the given point in
+    /// world XZ plane. Returns `None` if the point is outside of the surface.
+    pub fn height_at(&self, position: Vector2<f32>) -> Option<f32> {
+        self.sample(position).map(|sample| sample.position.y)
+    }
+
+    /// Returns depth of the given point in world coordinates under the surface. Negative values
+    /// mean that the point is above the surface. It could be used to calculate buoyancy force.
+    /// Returns `None` if the point is outside of the surface.
+    pub fn depth_at(&self, point: Vector3<f32>) -> Option<f32> {
+        self.height_at(point.xz()).map(|height| height - point.y)
+    }
+
+    /// Returns maximal possible height of the waves.
+    pub fn max_wave_height(&self) -> f32 {
+        self.waves
+            .iter()
+            .take(MAX_WAVES)
+            .map(|wave| wave.amplitude.max(0.0))
+            .sum()
+    }
+}
+
+impl NodeTrait for Water {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        // Horizontal displacement of the edges is ignored, it is less than the height of the waves.
+        let height = self.max_wave_height();
+        let half_size = *self.size * 0.5;
+        AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(-half_size.x - height, -height, -half_size.y - height),
+            Vector3::new(half_size.x + height, height, half_size.y + height),
+        )
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        self.time += context.dt;
+    }
+}
+
+/// Allows you to create water in a declarative manner.
+pub struct WaterBuilder {
+    base_builder: BaseBuilder,
+    size: Vector2<f32>,
+    resolution: u32,
+    waves: Vec<WaterWave>,
+    normal_map: Option<TextureResource>,
+    normal_map_tile_size: f32,
+    shallow_color: Color,
+    deep_color: Color,
+    absorption: f32,
+    refraction_strength: f32,
+    reflection_strength: f32,
+    foam_color: Color,
+    foam_distance: f32,
+}
+
+impl WaterBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        let defaults = Water::default();
+        Self {
+            base_builder,
+            size: *defaults.size,
+            resolution: *defaults.resolution,
+            waves: (*defaults.waves).clone(),
+            normal_map: None,
+            normal_map_tile_size: *defaults.normal_map_tile_size,
+            shallow_color: *defaults.shallow_color,
+            deep_color: *defaults.deep_color,
+            absorption: *defaults.absorption,
+            refraction_strength: *defaults.refraction_strength,
+            reflection_strength: *defaults.reflection_strength,
+            foam_color: *defaults.foam_color,
+            foam_distance: *defaults.foam_distance,
+        }
+    }
+
+    /// Sets desired size of the surface.
+    pub fn with_size(mut self, size: Vector2<f32>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets desired resolution of the surface grid.
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution.clamp(1, 1024);
+        self
+    }
+
+    /// Sets desired waves.
+    pub fn with_waves(mut self, waves: Vec<WaterWave>) -> Self {
+        self.waves = waves;
+        self
+    }
+
+    /// Sets desired normal map.
+    pub fn with_normal_map(mut self, normal_map: Option<TextureResource>) -> Self {
+        self.normal_map = normal_map;
+        self
+    }
+
+    /// Sets desired size of a tile of the normal map.
+    pub fn with_normal_map_tile_size(mut self, size: f32) -> Self {
+        self.normal_map_tile_size = size;
+        self
+    }
+
+    /// Sets desired color of the shallow water.
+    pub fn with_shallow_color(mut self, color: Color) -> Self {
+        self.shallow_color = color;
+        self
+    }
+
+    /// Sets desired color of the deep water.
+    pub fn with_deep_color(mut self, color: Color) -> Self {
+        self.deep_color = color;
+        self
+    }
+
+    /// Sets desired absorption of the light.
+    pub fn with_absorption(mut self, absorption: f32) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Sets desired strength of the refraction.
+    pub fn with_refraction_strength(mut self, strength: f32) -> Self {
+        self.refraction_strength = strength;
+        self
+    }
+
+    /// Sets desired strength of the reflections.
+    pub fn with_reflection_strength(mut self, strength: f32) -> Self {
+        self.reflection_strength = strength;
+        self
+    }
+
+    /// Sets desired color of the foam.
+    pub fn with_foam_color(mut self, color: Color) -> Self {
+        self.foam_color = color;
+        self
+    }
+
+    /// Sets desired thickness of the water layer, below which the foam appears.
+    pub fn with_foam_distance(mut self, distance: f32) -> Self {
+        self.foam_distance = distance;
+        self
+    }
+
+    /// Creates new water.
+    pub fn build_water(self) -> Water {
+        Water {
+            base: self.base_builder.build_base(),
+            size: self.size.into(),
+            resolution: self.resolution.into(),
+            waves: self.waves.into(),
+            normal_map: self.normal_map.into(),
+            normal_map_tile_size: self.normal_map_tile_size.into(),
+            shallow_color: self.shallow_color.into(),
+            deep_color: self.deep_color.into(),
+            absorption: self.absorption.into(),
+            refraction_strength: self.refraction_strength.into(),
+            reflection_strength: self.reflection_strength.into(),
+            foam_color: self.foam_color.into(),
+            foam_distance: self.foam_distance.into(),
+            time: 0.0,
+        }
+    }
+
+    /// Creates new water node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_water())
+    }
+
+    /// Creates new water node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::water::{evaluate_waves, sample_surface, wave_parameters, WaterWave},
+    };
+
+    #[test]
+    fn test_flat_water() {
+        let (displacement, normal) = evaluate_waves(&[], Vector2::new(1.0, 2.0), 3.0);
+        assert_eq!(displacement, Vector3::default());
+        assert_eq!(normal, Vector3::y());
+    }
+
+    #[test]
+    fn test_sample_surface_matches_displaced_point() {
+        let waves = wave_parameters(&[
+            WaterWave {
+                direction: Vector2::new(1.0, 0.3),
+                amplitude: 0.5,
+                wavelength: 6.0,
+                steepness: 0.9,
+            },
+            WaterWave {
+                direction: Vector2::new(-0.2, 1.0),
+                amplitude: 0.2,
+                wavelength: 3.0,
+                steepness: 0.7,
+            },
+        ]);
+
+        let time = 1.7;
+        for i in 0..32 {
+            let rest_position = Vector2::new(i as f32 * 0.37, i as f32 * -0.21);
+            let (displacement, _) = evaluate_waves(&waves, rest_position, time);
+            let surface_point = rest_position + displacement.xz();
+
+            let sample = sample_surface(&waves, surface_point, time);
+            assert!((sample.position.y - displacement.y).abs() < 1.0e-3);
+            assert!(sample.normal.y > 0.0);
+        }
+    }
+}
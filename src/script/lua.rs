@@ -0,0 +1,855 @@
+//! Lua scripting support (requires `lua` feature). See [`LuaScript`] docs for more info.
+
+use crate::{
+    asset::{
+        custom::CustomResourceLoader, manager::ResourceManager, Resource, ResourceData,
+        ResourceStateRef,
+    },
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+        TypeUuidProvider,
+    },
+    event::{ElementState, Event, WindowEvent},
+    gui::message::KeyCode,
+    impl_component_provider,
+    resource::model::{Model, ModelResource, ModelResourceExtension},
+    scene::{node::Node, Scene},
+    script::{
+        ScriptContext, ScriptDeinitContext, ScriptMessageContext, ScriptMessagePayload,
+        ScriptMessageSender, ScriptTrait,
+    },
+    utils::translate_key,
+};
+use fxhash::FxHashSet;
+use mlua::{
+    ChunkMode, FromLua, Function, IntoLua, IntoLuaMulti, Lua, LuaOptions, MetaMethod, StdLib,
+    Table, UserData, UserDataFields, UserDataMethods, Value,
+};
+use std::{
+    any::Any,
+    borrow::Cow,
+    convert::Infallible,
+    fmt::{Debug, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+// Name of the registry value, that holds `self` table of a script instance.
+const SELF_KEY: &str = "fyrox_self";
+
+// Functions of the base library, that could load code from files or precompiled chunks. The base
+// library is always loaded, so these functions are removed from every Lua state.
+const UNSAFE_BASE_FUNCTIONS: [&str; 3] = ["dofile", "loadfile", "load"];
+
+/// Source code of a Lua script. Sources are loaded by the resource manager (`.lua` files), so a
+/// script does not block the main thread while its source is loading.
+#[derive(Debug, Default, Visit, Reflect)]
+pub struct LuaSource {
+    #[reflect(hidden)]
+    path: PathBuf,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    bytes: Vec<u8>,
+}
+
+impl ResourceData for LuaSource {
+    fn path(&self) -> Cow<Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+}
+
+impl TypeUuidProvider for LuaSource {
+    fn type_uuid() -> Uuid {
+        uuid!("3b9e7d21-5c4a-4f08-8e6b-1d2a9c7f5e34")
+    }
+}
+
+/// Loader of [`LuaSource`] resources, it is registered by the engine when `lua` feature is enabled.
+pub struct LuaSourceLoader;
+
+impl CustomResourceLoader for LuaSourceLoader {
+    type Data = LuaSource;
+    type Error = Infallible;
+
+    fn extensions(&self) -> &[&str] {
+        &["lua"]
+    }
+
+    fn load_from_bytes(&self, path: &Path, bytes: Vec<u8>) -> Result<LuaSource, Infallible> {
+        Ok(LuaSource {
+            path: path.to_path_buf(),
+            bytes,
+        })
+    }
+}
+
+/// A value, that could be passed between Lua and native code: stored in properties of
+/// [`LuaScript`] or sent in a [`LuaMessage`].
+#[derive(
+    Clone, Debug, Default, PartialEq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames,
+)]
+pub enum LuaData {
+    /// `nil` value.
+    #[default]
+    Nil,
+    /// Boolean value.
+    Bool(bool),
+    /// Number value. Lua integers are converted to floating-point numbers.
+    Number(f64),
+    /// String value.
+    String(String),
+}
+
+impl<'lua> IntoLua<'lua> for LuaData {
+    fn into_lua(self, lua: &'lua Lua) -> mlua::Result<Value<'lua>> {
+        Ok(match self {
+            LuaData::Nil => Value::Nil,
+            LuaData::Bool(value) => Value::Boolean(value),
+            LuaData::Number(value) => Value::Number(value),
+            LuaData::String(value) => Value::String(lua.create_string(&value)?),
+        })
+    }
+}
+
+impl<'lua> FromLua<'lua> for LuaData {
+    fn from_lua(value: Value<'lua>, _lua: &'lua Lua) -> mlua::Result<Self> {
+        match value {
+            Value::Nil => Ok(LuaData::Nil),
+            Value::Boolean(value) => Ok(LuaData::Bool(value)),
+            Value::Integer(value) => Ok(LuaData::Number(value as f64)),
+            Value::Number(value) => Ok(LuaData::Number(value)),
+            Value::String(value) => Ok(LuaData::String(value.to_str()?.to_owned())),
+            _ => Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "LuaData",
+                message: Some("only nil, booleans, numbers and strings are supported".to_string()),
+            }),
+        }
+    }
+}
+
+/// A named value in the `self` table of a [`LuaScript`].
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct LuaProperty {
+    /// Name of the property.
+    pub name: String,
+    /// Value of the property.
+    pub value: LuaData,
+}
+
+/// A script message, that is sent by `ctx:send` method in Lua scripts. Native scripts could send
+/// and receive such messages too, this way Lua scripts and native scripts can communicate with
+/// each other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LuaMessage {
+    /// Name of the message.
+    pub name: String,
+    /// Payload of the message.
+    pub value: LuaData,
+}
+
+/// A handle of a scene node in Lua scripts.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LuaNodeHandle(pub Handle<Node>);
+
+impl UserData for LuaNodeHandle {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("is_some", |_, this, ()| Ok(this.0.is_some()));
+        methods.add_meta_method(MetaMethod::Eq, |_, this, other: LuaNodeHandle| {
+            Ok(this.0 == other.0)
+        });
+        methods.add_meta_method(MetaMethod::ToString, |_, this, ()| Ok(this.0.to_string()));
+    }
+}
+
+impl<'lua> FromLua<'lua> for LuaNodeHandle {
+    fn from_lua(value: Value<'lua>, _lua: &'lua Lua) -> mlua::Result<Self> {
+        match value {
+            Value::UserData(data) => Ok(*data.borrow::<Self>()?),
+            _ => Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "LuaNodeHandle",
+                message: None,
+            }),
+        }
+    }
+}
+
+// A model, that was requested by `ctx:instantiate` and is still loading.
+struct PendingInstance {
+    path: String,
+    model: ModelResource,
+}
+
+// Part of the engine state, that is passed to Lua callbacks.
+struct LuaEnv<'a> {
+    handle: Handle<Node>,
+    scene: &'a mut Scene,
+    resource_manager: &'a ResourceManager,
+    message_sender: &'a ScriptMessageSender,
+    dt: f32,
+    elapsed_time: f32,
+}
+
+// Engine API, that is available to Lua scripts as `ctx` argument of their callbacks.
+struct LuaContext<'a> {
+    handle: Handle<Node>,
+    scene: &'a mut Scene,
+    resource_manager: &'a ResourceManager,
+    message_sender: &'a ScriptMessageSender,
+    dt: f32,
+    elapsed_time: f32,
+    pressed_keys: &'a FxHashSet<KeyCode>,
+    pending_instances: &'a mut Vec<PendingInstance>,
+}
+
+impl LuaContext<'_> {
+    fn node(&self, node: LuaNodeHandle) -> mlua::Result<&Node> {
+        self.scene
+            .graph
+            .try_get(node.0)
+            .ok_or_else(|| invalid_handle(node))
+    }
+
+    fn node_mut(&mut self, node: LuaNodeHandle) -> mlua::Result<&mut Node> {
+        self.scene
+            .graph
+            .try_get_mut(node.0)
+            .ok_or_else(|| invalid_handle(node))
+    }
+}
+
+fn invalid_handle(node: LuaNodeHandle) -> mlua::Error {
+    mlua::Error::RuntimeError(format!("Invalid node handle {}!", node.0))
+}
+
+impl UserData for LuaContext<'_> {
+    fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("handle", |_, this| Ok(LuaNodeHandle(this.handle)));
+        fields.add_field_method_get("dt", |_, this| Ok(this.dt));
+        fields.add_field_method_get("elapsed_time", |_, this| Ok(this.elapsed_time));
+    }
+
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        // Scene graph.
+        methods.add_method("find", |_, this, name: String| {
+            Ok(this
+                .scene
+                .graph
+                .find_by_name_from_root(&name)
+                .map(|(handle, _)| LuaNodeHandle(handle)))
+        });
+        methods.add_method("name", |_, this, node: LuaNodeHandle| {
+            Ok(this.node(node)?.name().to_owned())
+        });
+        methods.add_method("parent", |_, this, node: LuaNodeHandle| {
+            Ok(LuaNodeHandle(this.node(node)?.parent()))
+        });
+        methods.add_method("children", |_, this, node: LuaNodeHandle| {
+            Ok(this
+                .node(node)?
+                .children()
+                .iter()
+                .map(|child| LuaNodeHandle(*child))
+                .collect::<Vec<_>>())
+        });
+        methods.add_method("is_enabled", |_, this, node: LuaNodeHandle| {
+            Ok(this.node(node)?.is_enabled())
+        });
+        methods.add_method_mut(
+            "set_enabled",
+            |_, this, (node, enabled): (LuaNodeHandle, bool)| {
+                this.node_mut(node)?.set_enabled(enabled);
+                Ok(())
+            },
+        );
+        methods.add_method_mut("remove", |_, this, node: LuaNodeHandle| {
+            this.node(node)?;
+            this.scene.graph.remove_node(node.0);
+            Ok(())
+        });
+
+        // Transform.
+        methods.add_method("position", |_, this, node: LuaNodeHandle| {
+            let position = **this.node(node)?.local_transform().position();
+            Ok((position.x, position.y, position.z))
+        });
+        methods.add_method_mut(
+            "set_position",
+            |_, this, (node, x, y, z): (LuaNodeHandle, f32, f32, f32)| {
+                this.node_mut(node)?
+                    .local_transform_mut()
+                    .set_position(Vector3::new(x, y, z));
+                Ok(())
+            },
+        );
+        methods.add_method("global_position", |_, this, node: LuaNodeHandle| {
+            let position = this.node(node)?.global_position();
+            Ok((position.x, position.y, position.z))
+        });
+        methods.add_method("rotation", |_, this, node: LuaNodeHandle| {
+            Ok(this.node(node)?.local_transform().rotation().euler_angles())
+        });
+        methods.add_method_mut(
+            "set_rotation",
+            |_, this, (node, x, y, z): (LuaNodeHandle, f32, f32, f32)| {
+                this.node_mut(node)?
+                    .local_transform_mut()
+                    .set_rotation(UnitQuaternion::from_euler_angles(x, y, z));
+                Ok(())
+            },
+        );
+        methods.add_method("scale", |_, this, node: LuaNodeHandle| {
+            let scale = **this.node(node)?.local_transform().scale();
+            Ok((scale.x, scale.y, scale.z))
+        });
+        methods.add_method_mut(
+            "set_scale",
+            |_, this, (node, x, y, z): (LuaNodeHandle, f32, f32, f32)| {
+                this.node_mut(node)?
+                    .local_transform_mut()
+                    .set_scale(Vector3::new(x, y, z));
+                Ok(())
+            },
+        );
+
+        // Resources.
+        methods.add_method_mut("instantiate", |_, this, path: String| {
+            let model = this.resource_manager.request::<Model, _>(&path);
+            if model.is_ok() {
+                Ok(Some(LuaNodeHandle(model.instantiate(this.scene))))
+            } else {
+                // The model will be instantiated when it is loaded, see `LuaState::poll`.
+                this.pending_instances.push(PendingInstance { path, model });
+                Ok(None)
+            }
+        });
+
+        // Input.
+        methods.add_method("is_key_pressed", |_, this, key: String| {
+            let key = KeyCode::from_str(&key)
+                .map_err(|_| mlua::Error::RuntimeError(format!("Unknown key {key}!")))?;
+            Ok(this.pressed_keys.contains(&key))
+        });
+
+        // Messages.
+        methods.add_method(
+            "send",
+            |_, this, (target, name, value): (Option<LuaNodeHandle>, String, LuaData)| {
+                let message = LuaMessage { name, value };
+                match target {
+                    Some(target) => this.message_sender.send_to_target(target.0, message),
+                    None => this.message_sender.send_global(message),
+                }
+                Ok(())
+            },
+        );
+
+        methods.add_method("log", |_, _, message: String| {
+            Log::info(message);
+            Ok(())
+        });
+    }
+}
+
+#[derive(Default)]
+struct LuaState {
+    lua: Option<Lua>,
+    source: Option<Resource<LuaSource>>,
+    // Callbacks, that were called while the source was loading. They're called when it is loaded.
+    deferred: Vec<&'static str>,
+    pending_instances: Vec<PendingInstance>,
+    pressed_keys: FxHashSet<KeyCode>,
+}
+
+impl Clone for LuaState {
+    fn clone(&self) -> Self {
+        // Every instance must have its own Lua state, it is created on initialization.
+        Self::default()
+    }
+}
+
+impl Debug for LuaState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LuaState")
+    }
+}
+
+impl LuaState {
+    fn call<A>(&mut self, function: &str, env: &mut LuaEnv, args: A)
+    where
+        A: for<'lua> IntoLuaMulti<'lua>,
+    {
+        let lua = match self.lua.as_ref() {
+            Some(lua) => lua,
+            None => return,
+        };
+
+        let context = LuaContext {
+            handle: env.handle,
+            scene: &mut *env.scene,
+            resource_manager: env.resource_manager,
+            message_sender: env.message_sender,
+            dt: env.dt,
+            elapsed_time: env.elapsed_time,
+            pressed_keys: &self.pressed_keys,
+            pending_instances: &mut self.pending_instances,
+        };
+
+        let result = lua.scope(|scope| {
+            // Every callback is optional.
+            let function = match lua.globals().get::<_, Option<Function>>(function)? {
+                Some(function) => function,
+                None => return Ok(()),
+            };
+
+            let mut arguments = args.into_lua_multi(lua)?;
+            arguments.push_front(Value::UserData(scope.create_nonstatic_userdata(context)?));
+            arguments.push_front(Value::Table(lua.named_registry_value::<Table>(SELF_KEY)?));
+
+            function.call::<_, ()>(arguments)
+        });
+
+        if let Err(err) = result {
+            Log::err(format!("Lua: {function} has failed: {err}"));
+        }
+    }
+
+    // Calls the given callback, or defers the call until the source is loaded.
+    fn call_or_defer(&mut self, function: &'static str, env: &mut LuaEnv) {
+        if self.lua.is_some() {
+            self.call(function, env, ());
+        } else if self.source.is_some() {
+            self.deferred.push(function);
+        }
+    }
+
+    // Finishes the work, that is waiting for the resource manager: creates Lua state when the
+    // source is loaded (and calls deferred callbacks) and instantiates the models, requested by
+    // `ctx:instantiate`, when they're loaded.
+    fn poll(&mut self, properties: &[LuaProperty], env: &mut LuaEnv) {
+        if let Some(source) = self.source.as_ref() {
+            let result = match source.state().get() {
+                ResourceStateRef::Pending { .. } => return,
+                ResourceStateRef::LoadError { path, error, .. } => Err(format!(
+                    "Unable to load script {}: {:?}",
+                    path.display(),
+                    error
+                )),
+                ResourceStateRef::Ok(source) => create_state(source, properties).map_err(|err| {
+                    format!("Unable to load script {}: {}", source.path.display(), err)
+                }),
+            };
+            self.source = None;
+
+            match result {
+                Ok(lua) => {
+                    self.lua = Some(lua);
+                    for function in std::mem::take(&mut self.deferred) {
+                        self.call(function, env, ());
+                    }
+                }
+                Err(err) => {
+                    self.deferred.clear();
+                    Log::err(format!("Lua: {err}"));
+                }
+            }
+        }
+
+        let mut loading = Vec::new();
+        for instance in std::mem::take(&mut self.pending_instances) {
+            if instance.model.is_loading() {
+                loading.push(instance);
+                continue;
+            }
+            if let ResourceStateRef::LoadError { error, .. } = instance.model.state().get() {
+                Log::err(format!(
+                    "Lua: Unable to instantiate {}: {:?}",
+                    instance.path, error
+                ));
+                continue;
+            }
+
+            let handle = instance.model.instantiate(env.scene);
+            self.call(
+                "on_instantiated",
+                env,
+                (instance.path, LuaNodeHandle(handle)),
+            );
+        }
+        // New requests could be made by `on_instantiated` callbacks.
+        self.pending_instances.extend(loading);
+    }
+}
+
+// Creates new Lua state for the given source. Only `table`, `string` and `math` standard libraries
+// are loaded, so scripts have no access to the file system, the OS and native modules.
+fn create_state(source: &LuaSource, properties: &[LuaProperty]) -> mlua::Result<Lua> {
+    let lua = Lua::new_with(
+        StdLib::TABLE | StdLib::STRING | StdLib::MATH,
+        LuaOptions::default(),
+    )?;
+
+    {
+        let globals = lua.globals();
+        for name in UNSAFE_BASE_FUNCTIONS {
+            globals.set(name, Value::Nil)?;
+        }
+
+        lua.load(source.bytes.as_slice())
+            .set_name(source.path.to_string_lossy())
+            .set_mode(ChunkMode::Text)
+            .exec()?;
+
+        let this = lua.create_table()?;
+        for property in properties {
+            this.set(property.name.as_str(), property.value.clone())?;
+        }
+        lua.set_named_registry_value(SELF_KEY, this)?;
+    }
+
+    Ok(lua)
+}
+
+/// Lua script is a native script, that runs a script written in Lua. It could be attached to any
+/// scene node as any other script and it is an alternative to native scripts, that is useful for
+/// modding and rapid iteration. Every instance has its own Lua state, the script file is loaded
+/// on initialization by the resource manager (see [`LuaSource`]), so the main thread is never
+/// blocked. Callbacks, that are called while the file is loading, are deferred until it is loaded
+/// (`on_init` and `on_start`) or skipped (the rest).
+///
+/// Scripts are sandboxed: only `table`, `string` and `math` standard libraries are available,
+/// base functions that load code (`dofile`, `loadfile`, `load`) are removed and precompiled
+/// chunks are rejected.
+///
+/// # Callbacks
+///
+/// A Lua script could define the following global functions, every function is optional:
+///
+/// - `on_init(self, ctx)`, `on_start(self, ctx)`, `on_update(self, ctx)`, `on_deinit(self, ctx)` -
+/// they're called at the same moments as respective methods of [`ScriptTrait`].
+/// - `on_key(self, ctx, key, pressed)` - called when a key is pressed or released, `key` is a name
+/// of a [`KeyCode`] variant (for example `"KeyW"` or `"Space"`).
+/// - `on_message(self, ctx, name, value)` - called when the node receives a [`LuaMessage`].
+/// - `on_instantiated(self, ctx, path, node)` - called when a model, requested by
+/// `ctx:instantiate`, is loaded and instantiated.
+///
+/// `self` is a table, that holds the state of the instance. It is initialized from
+/// [`LuaScript::properties`] and it is saved together with the script, but only values of simple
+/// types (see [`LuaData`]) are saved.
+///
+/// # Engine API
+///
+/// `ctx` gives access to the engine:
+///
+/// - `ctx.handle`, `ctx.dt`, `ctx.elapsed_time` - handle of the node of the script, delta time and
+/// elapsed time.
+/// - `ctx:find(name)`, `ctx:name(node)`, `ctx:parent(node)`, `ctx:children(node)`,
+/// `ctx:is_enabled(node)`, `ctx:set_enabled(node, enabled)`, `ctx:remove(node)` - scene graph.
+/// - `ctx:position(node)`, `ctx:set_position(node, x, y, z)`, `ctx:global_position(node)`,
+/// `ctx:rotation(node)`, `ctx:set_rotation(node, x, y, z)` (Euler angles in radians),
+/// `ctx:scale(node)`, `ctx:set_scale(node, x, y, z)` - local transform.
+/// - `ctx:instantiate(path)` - instantiates a model (prefab) in the scene. If the model is already
+/// loaded, returns the handle of the instance, otherwise returns `nil` and calls `on_instantiated`
+/// when the model is loaded and instantiated.
+/// - `ctx:is_key_pressed(key)` - input.
+/// - `ctx:send(target, name, value)` - sends a [`LuaMessage`] to the given node, or to every
+/// node if `target` is `nil`.
+/// - `ctx:log(message)` - writes a message to the log.
+///
+/// # Example
+///
+/// ```lua
+/// function on_init(self, ctx)
+///     self.speed = self.speed or 2.0
+/// end
+///
+/// function on_update(self, ctx)
+///     if ctx:is_key_pressed("KeyW") then
+///         local x, y, z = ctx:position(ctx.handle)
+///         ctx:set_position(ctx.handle, x, y, z + self.speed * ctx.dt)
+///     end
+/// end
+///
+/// function on_message(self, ctx, name, value)
+///     if name == "damage" then
+///         ctx:log("Received " .. value .. " damage!")
+///     end
+/// end
+/// ```
+#[derive(Clone, Debug, Default, Reflect)]
+pub struct LuaScript {
+    /// Path to a Lua source file.
+    pub path: PathBuf,
+    /// Initial content of the `self` table of the script.
+    pub properties: Vec<LuaProperty>,
+    #[reflect(hidden)]
+    state: LuaState,
+}
+
+impl Visit for LuaScript {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.path.visit("Path", &mut region)?;
+
+        if !region.is_reading() {
+            self.sync_properties();
+        }
+        self.properties.visit("Properties", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl TypeUuidProvider for LuaScript {
+    fn type_uuid() -> Uuid {
+        uuid!("8d5e2c3a-6f41-4b7e-9a0d-2c1f7e4b9a63")
+    }
+}
+
+impl_component_provider!(LuaScript);
+
+impl LuaScript {
+    /// Creates new Lua script, that will run a script from the given file.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a property to the `self` table of the script.
+    pub fn with_property(mut self, name: &str, value: LuaData) -> Self {
+        self.properties.push(LuaProperty {
+            name: name.to_owned(),
+            value,
+        });
+        self
+    }
+
+    fn request_source(&mut self, resource_manager: &ResourceManager) {
+        if self.state.lua.is_none() && self.state.source.is_none() {
+            self.state.source = Some(resource_manager.request::<LuaSource, _>(&self.path));
+        }
+    }
+
+    // Copies the content of `self` table to the properties, so it could be saved.
+    fn sync_properties(&mut self) {
+        if let Some(lua) = self.state.lua.as_ref() {
+            if let Ok(this) = lua.named_registry_value::<Table>(SELF_KEY) {
+                let mut properties = this
+                    .pairs::<String, LuaData>()
+                    .filter_map(|pair| pair.ok())
+                    .map(|(name, value)| LuaProperty { name, value })
+                    .collect::<Vec<_>>();
+                properties.sort_by(|a, b| a.name.cmp(&b.name));
+                self.properties = properties;
+            }
+        }
+    }
+}
+
+impl ScriptTrait for LuaScript {
+    fn on_init(&mut self, ctx: &mut ScriptContext) {
+        self.request_source(ctx.resource_manager);
+        let mut env = LuaEnv {
+            handle: ctx.handle,
+            scene: ctx.scene,
+            resource_manager: ctx.resource_manager,
+            message_sender: ctx.message_sender,
+            dt: ctx.dt,
+            elapsed_time: ctx.elapsed_time,
+        };
+        self.state.poll(&self.properties, &mut env);
+        self.state.call_or_defer("on_init", &mut env);
+    }
+
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        // A script could be loaded from a save file, in this case `on_init` is not called.
+        self.request_source(ctx.resource_manager);
+
+        ctx.message_dispatcher
+            .subscribe_to::<LuaMessage>(ctx.handle);
+
+        let mut env = LuaEnv {
+            handle: ctx.handle,
+            scene: ctx.scene,
+            resource_manager: ctx.resource_manager,
+            message_sender: ctx.message_sender,
+            dt: ctx.dt,
+            elapsed_time: ctx.elapsed_time,
+        };
+        self.state.poll(&self.properties, &mut env);
+        self.state.call_or_defer("on_start", &mut env);
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        self.state.call(
+            "on_deinit",
+            &mut LuaEnv {
+                handle: ctx.node_handle,
+                scene: ctx.scene,
+                resource_manager: ctx.resource_manager,
+                message_sender: ctx.message_sender,
+                dt: 0.0,
+                elapsed_time: ctx.elapsed_time,
+            },
+            (),
+        );
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, ctx: &mut ScriptContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event, .. },
+            ..
+        } = event
+        {
+            let key = translate_key(event.physical_key);
+            let pressed = event.state == ElementState::Pressed;
+            if pressed {
+                if !self.state.pressed_keys.insert(key) {
+                    // Ignore repeated events.
+                    return;
+                }
+            } else {
+                self.state.pressed_keys.remove(&key);
+            }
+
+            self.state.call(
+                "on_key",
+                &mut LuaEnv {
+                    handle: ctx.handle,
+                    scene: ctx.scene,
+                    resource_manager: ctx.resource_manager,
+                    message_sender: ctx.message_sender,
+                    dt: ctx.dt,
+                    elapsed_time: ctx.elapsed_time,
+                },
+                (key.as_ref().to_owned(), pressed),
+            );
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let mut env = LuaEnv {
+            handle: ctx.handle,
+            scene: ctx.scene,
+            resource_manager: ctx.resource_manager,
+            message_sender: ctx.message_sender,
+            dt: ctx.dt,
+            elapsed_time: ctx.elapsed_time,
+        };
+        self.state.poll(&self.properties, &mut env);
+        self.state.call("on_update", &mut env, ());
+    }
+
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        ctx: &mut ScriptMessageContext,
+    ) {
+        if let Some(message) = message.downcast_ref::<LuaMessage>() {
+            self.state.call(
+                "on_message",
+                &mut LuaEnv {
+                    handle: ctx.handle,
+                    scene: ctx.scene,
+                    resource_manager: ctx.resource_manager,
+                    message_sender: ctx.message_sender,
+                    dt: ctx.dt,
+                    elapsed_time: ctx.elapsed_time,
+                },
+                (message.name.clone(), message.value.clone()),
+            );
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lua_script_properties() {
+        let source = LuaSource {
+            path: "test.lua".into(),
+            bytes: b"counter_step = 2".to_vec(),
+        };
+
+        let mut script =
+            LuaScript::new(&source.path).with_property("counter", LuaData::Number(1.0));
+        script.state.lua = Some(create_state(&source, &script.properties).unwrap());
+
+        let lua = script.state.lua.as_ref().unwrap();
+        assert_eq!(lua.globals().get::<_, f64>("counter_step").unwrap(), 2.0);
+
+        let this = lua.named_registry_value::<Table>(SELF_KEY).unwrap();
+        assert_eq!(this.get::<_, f64>("counter").unwrap(), 1.0);
+        this.set("counter", 3.0).unwrap();
+        this.set("name", "player").unwrap();
+
+        script.sync_properties();
+        assert_eq!(
+            script.properties,
+            vec![
+                LuaProperty {
+                    name: "counter".to_string(),
+                    value: LuaData::Number(3.0)
+                },
+                LuaProperty {
+                    name: "name".to_string(),
+                    value: LuaData::String("player".to_string())
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lua_sandbox() {
+        let source = LuaSource {
+            path: "test.lua".into(),
+            bytes: b"has_math = math ~= nil".to_vec(),
+        };
+        let lua = create_state(&source, &[]).unwrap();
+        let globals = lua.globals();
+        assert!(globals.get::<_, bool>("has_math").unwrap());
+        for name in [
+            "os", "io", "package", "require", "dofile", "loadfile", "load",
+        ] {
+            assert_eq!(globals.get::<_, Value>(name).unwrap(), Value::Nil);
+        }
+
+        let binary = LuaSource {
+            path: "binary.lua".into(),
+            bytes: b"\x1bLua".to_vec(),
+        };
+        assert!(create_state(&binary, &[]).is_err());
+    }
+}
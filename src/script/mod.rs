@@ -13,9 +13,10 @@ use crate::{
     },
     engine::ScriptMessageDispatcher,
     event::Event,
+    input::Input,
     plugin::Plugin,
     scene::{node::Node, Scene},
-    utils::component::ComponentProvider,
+    utils::{component::ComponentProvider, session::GameSession},
 };
 use std::{
     any::{Any, TypeId},
@@ -25,6 +26,8 @@ use std::{
 };
 
 pub mod constructor;
+#[cfg(feature = "lua")]
+pub mod lua;
 
 /// A script message's payload.
 pub trait ScriptMessagePayload: Any + Send {
@@ -219,6 +222,14 @@ pub struct ScriptContext<'a, 'b, 'c> {
     /// A message dispatcher. If you need to receive messages of a particular type, you must subscribe to a type
     /// explicitly. See [`ScriptTrait::on_message`] for more examples.
     pub message_dispatcher: &'c mut ScriptMessageDispatcher,
+
+    /// A reference to action-based input of the engine. Use it to check states of actions instead of
+    /// matching raw OS events, or to play rumble effects on gamepads. See [`Input`] docs for more info.
+    pub input: &'a mut Input,
+
+    /// A reference to persistent data of the current game session, use it to store the data, that must
+    /// outlive the scene. See [`GameSession`] docs for more info.
+    pub session: &'a mut GameSession,
 }
 
 /// A set of data, that provides contextual information for script methods.
@@ -0,0 +1,614 @@
+//! Path finding on uniform 2D grids. See [`GridPathFinder`] docs for more info.
+
+use crate::{
+    core::algebra::Vector2,
+    utils::astar::{OpenNode, PathError, PathKind},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::collections::BinaryHeap;
+
+/// Defines whether a path could go diagonally between grid cells or not.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DiagonalMovement {
+    /// Only horizontal and vertical moves are allowed.
+    Never,
+    /// Diagonal moves are allowed only if both adjacent cells are walkable, so a path never cuts
+    /// corners of obstacles.
+    #[default]
+    IfNoObstacles,
+    /// Diagonal moves are always allowed, even between two diagonally adjacent obstacles.
+    Always,
+}
+
+/// Search algorithm, that is used by [`GridPathFinder`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GridSearchMode {
+    /// Classic A* search, that takes traversal costs of cells into account.
+    #[default]
+    AStar,
+    /// Jump Point Search - an optimization of A* for uniform grids, that skips the most of cells
+    /// of open areas and is usually an order of magnitude faster than A*. Traversal costs of
+    /// walkable cells are ignored in this mode, every walkable cell is considered to have unit
+    /// cost.
+    JumpPointSearch,
+}
+
+/// Grid path finder searches paths on a rectangular grid of cells, every cell has its own
+/// traversal cost. It is much more efficient than [`super::PathFinder`] for grid-based maps
+/// (strategy games, roguelikes, etc.), because the grid does not store links between cells.
+///
+/// Traversal cost of a cell is a multiplier of the length of a step into the cell, so a cell with
+/// cost `2.0` is twice as hard to travel through as a cell with cost `1.0`. Costs should not be less
+/// than `1.0`, otherwise the paths might be not the shortest ones. Cells with infinite cost are
+/// impassable.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::algebra::Vector2,
+///     utils::astar::{
+///         grid::{DiagonalMovement, GridPathFinder, GridSearchMode},
+///         PathKind,
+///     },
+/// };
+///
+/// let mut grid = GridPathFinder::new(16, 16);
+/// grid.set_diagonal_movement(DiagonalMovement::IfNoObstacles);
+/// grid.set_mode(GridSearchMode::JumpPointSearch);
+/// // Vertical wall with a gap at the bottom.
+/// for y in 1..16 {
+///     grid.set_walkable(Vector2::new(8, y), false);
+/// }
+///
+/// let mut path = Vec::new();
+/// let kind = grid
+///     .build(Vector2::new(0, 15), Vector2::new(15, 15), &mut path)
+///     .unwrap();
+/// assert_eq!(kind, PathKind::Full);
+/// assert!(path.contains(&Vector2::new(8, 0)));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridPathFinder {
+    width: usize,
+    height: usize,
+    costs: Vec<f32>,
+    diagonal_movement: DiagonalMovement,
+    mode: GridSearchMode,
+}
+
+fn out_of_bounds(cell: Vector2<i32>) -> PathError {
+    PathError::Custom(format!("Cell {:?} is out of bounds of the grid.", cell))
+}
+
+impl GridPathFinder {
+    /// Creates new grid of the given size, every cell of the grid is walkable and has unit cost.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            costs: vec![1.0; width * height],
+            diagonal_movement: Default::default(),
+            mode: Default::default(),
+        }
+    }
+
+    /// Returns width of the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns height of the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Sets diagonal movement rule.
+    pub fn set_diagonal_movement(&mut self, diagonal_movement: DiagonalMovement) {
+        self.diagonal_movement = diagonal_movement;
+    }
+
+    /// Returns diagonal movement rule.
+    pub fn diagonal_movement(&self) -> DiagonalMovement {
+        self.diagonal_movement
+    }
+
+    /// Sets search algorithm.
+    pub fn set_mode(&mut self, mode: GridSearchMode) {
+        self.mode = mode;
+    }
+
+    /// Returns search algorithm.
+    pub fn mode(&self) -> GridSearchMode {
+        self.mode
+    }
+
+    fn index(&self, cell: Vector2<i32>) -> Option<usize> {
+        if cell.x >= 0
+            && cell.y >= 0
+            && (cell.x as usize) < self.width
+            && (cell.y as usize) < self.height
+        {
+            Some(cell.y as usize * self.width + cell.x as usize)
+        } else {
+            None
+        }
+    }
+
+    fn cell(&self, index: usize) -> Vector2<i32> {
+        Vector2::new((index % self.width) as i32, (index / self.width) as i32)
+    }
+
+    /// Sets traversal cost of a cell. Infinite cost makes the cell impassable. Does nothing if the
+    /// cell is out of bounds.
+    pub fn set_cost(&mut self, cell: Vector2<i32>, cost: f32) {
+        if let Some(index) = self.index(cell) {
+            self.costs[index] = cost;
+        }
+    }
+
+    /// Returns traversal cost of a cell, or `None` if the cell is out of bounds.
+    pub fn cost(&self, cell: Vector2<i32>) -> Option<f32> {
+        self.index(cell).map(|index| self.costs[index])
+    }
+
+    /// Makes a cell either walkable (with unit cost) or impassable.
+    pub fn set_walkable(&mut self, cell: Vector2<i32>, walkable: bool) {
+        self.set_cost(cell, if walkable { 1.0 } else { f32::INFINITY });
+    }
+
+    /// Returns `true` if the cell is inside the grid and it is not impassable.
+    pub fn is_walkable(&self, cell: Vector2<i32>) -> bool {
+        match self.index(cell) {
+            Some(index) => self.costs[index].is_finite(),
+            None => false,
+        }
+    }
+
+    fn walkable(&self, x: i32, y: i32) -> bool {
+        self.is_walkable(Vector2::new(x, y))
+    }
+
+    fn heuristic(&self, a: Vector2<i32>, b: Vector2<i32>) -> f32 {
+        let dx = (a.x - b.x).abs() as f32;
+        let dy = (a.y - b.y).abs() as f32;
+        match self.diagonal_movement {
+            DiagonalMovement::Never => dx + dy,
+            // Octile distance.
+            _ => dx + dy + (std::f32::consts::SQRT_2 - 2.0) * dx.min(dy),
+        }
+    }
+
+    fn can_move(&self, x: i32, y: i32, dx: i32, dy: i32) -> bool {
+        if !self.walkable(x + dx, y + dy) {
+            return false;
+        }
+        if dx == 0 || dy == 0 {
+            return true;
+        }
+        match self.diagonal_movement {
+            DiagonalMovement::Never => false,
+            DiagonalMovement::IfNoObstacles => self.walkable(x + dx, y) && self.walkable(x, y + dy),
+            DiagonalMovement::Always => true,
+        }
+    }
+
+    // Every cell, that is reachable from the given cell by a single step.
+    fn neighbours(&self, cell: Vector2<i32>, neighbours: &mut Vec<Vector2<i32>>) {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx != 0 || dy != 0) && self.can_move(cell.x, cell.y, dx, dy) {
+                    neighbours.push(Vector2::new(cell.x + dx, cell.y + dy));
+                }
+            }
+        }
+    }
+
+    // Neighbours of a jump point, that could not be reached in a more optimal way through its
+    // parent.
+    fn pruned_neighbours(
+        &self,
+        cell: Vector2<i32>,
+        parent: Option<Vector2<i32>>,
+        neighbours: &mut Vec<Vector2<i32>>,
+    ) {
+        let parent = match parent {
+            Some(parent) => parent,
+            None => return self.neighbours(cell, neighbours),
+        };
+
+        let (x, y) = (cell.x, cell.y);
+        let dx = (x - parent.x).signum();
+        let dy = (y - parent.y).signum();
+        let mut push = |x: i32, y: i32| neighbours.push(Vector2::new(x, y));
+
+        match self.diagonal_movement {
+            DiagonalMovement::Never => {
+                if dx != 0 {
+                    for (nx, ny) in [(x, y - 1), (x, y + 1), (x + dx, y)] {
+                        if self.walkable(nx, ny) {
+                            push(nx, ny);
+                        }
+                    }
+                } else {
+                    for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y + dy)] {
+                        if self.walkable(nx, ny) {
+                            push(nx, ny);
+                        }
+                    }
+                }
+            }
+            DiagonalMovement::IfNoObstacles => {
+                if dx != 0 && dy != 0 {
+                    let vertical = self.walkable(x, y + dy);
+                    let horizontal = self.walkable(x + dx, y);
+                    if vertical {
+                        push(x, y + dy);
+                    }
+                    if horizontal {
+                        push(x + dx, y);
+                    }
+                    if vertical && horizontal && self.walkable(x + dx, y + dy) {
+                        push(x + dx, y + dy);
+                    }
+                } else if dx != 0 {
+                    let next = self.walkable(x + dx, y);
+                    let top = self.walkable(x, y + 1);
+                    let bottom = self.walkable(x, y - 1);
+                    if next {
+                        push(x + dx, y);
+                        if top && self.walkable(x + dx, y + 1) {
+                            push(x + dx, y + 1);
+                        }
+                        if bottom && self.walkable(x + dx, y - 1) {
+                            push(x + dx, y - 1);
+                        }
+                    }
+                    if top {
+                        push(x, y + 1);
+                    }
+                    if bottom {
+                        push(x, y - 1);
+                    }
+                } else {
+                    let next = self.walkable(x, y + dy);
+                    let right = self.walkable(x + 1, y);
+                    let left = self.walkable(x - 1, y);
+                    if next {
+                        push(x, y + dy);
+                        if right && self.walkable(x + 1, y + dy) {
+                            push(x + 1, y + dy);
+                        }
+                        if left && self.walkable(x - 1, y + dy) {
+                            push(x - 1, y + dy);
+                        }
+                    }
+                    if right {
+                        push(x + 1, y);
+                    }
+                    if left {
+                        push(x - 1, y);
+                    }
+                }
+            }
+            DiagonalMovement::Always => {
+                let mut candidates = Vec::with_capacity(5);
+                if dx != 0 && dy != 0 {
+                    candidates.extend_from_slice(&[(x, y + dy), (x + dx, y), (x + dx, y + dy)]);
+                    if !self.walkable(x - dx, y) {
+                        candidates.push((x - dx, y + dy));
+                    }
+                    if !self.walkable(x, y - dy) {
+                        candidates.push((x + dx, y - dy));
+                    }
+                } else if dx != 0 {
+                    candidates.push((x + dx, y));
+                    if !self.walkable(x, y + 1) {
+                        candidates.push((x + dx, y + 1));
+                    }
+                    if !self.walkable(x, y - 1) {
+                        candidates.push((x + dx, y - 1));
+                    }
+                } else {
+                    candidates.push((x, y + dy));
+                    if !self.walkable(x + 1, y) {
+                        candidates.push((x + 1, y + dy));
+                    }
+                    if !self.walkable(x - 1, y) {
+                        candidates.push((x - 1, y + dy));
+                    }
+                }
+                for (nx, ny) in candidates {
+                    if self.walkable(nx, ny) {
+                        push(nx, ny);
+                    }
+                }
+            }
+        }
+    }
+
+    // Moves from the given cell in the given direction until it finds a jump point (a cell with
+    // forced neighbours, or the goal). Returns `None` if the direction leads to a dead end.
+    fn jump(
+        &self,
+        mut x: i32,
+        mut y: i32,
+        dx: i32,
+        dy: i32,
+        goal: Vector2<i32>,
+    ) -> Option<Vector2<i32>> {
+        loop {
+            if !self.walkable(x, y) {
+                return None;
+            }
+            if x == goal.x && y == goal.y {
+                return Some(goal);
+            }
+
+            let is_jump_point = match self.diagonal_movement {
+                DiagonalMovement::Never => {
+                    if dx != 0 {
+                        (self.walkable(x, y - 1) && !self.walkable(x - dx, y - 1))
+                            || (self.walkable(x, y + 1) && !self.walkable(x - dx, y + 1))
+                    } else {
+                        (self.walkable(x - 1, y) && !self.walkable(x - 1, y - dy))
+                            || (self.walkable(x + 1, y) && !self.walkable(x + 1, y - dy))
+                            // Moving vertically, horizontal jump points must be checked too.
+                            || self.jump(x + 1, y, 1, 0, goal).is_some()
+                            || self.jump(x - 1, y, -1, 0, goal).is_some()
+                    }
+                }
+                DiagonalMovement::IfNoObstacles => {
+                    if dx != 0 && dy != 0 {
+                        self.jump(x + dx, y, dx, 0, goal).is_some()
+                            || self.jump(x, y + dy, 0, dy, goal).is_some()
+                    } else if dx != 0 {
+                        (self.walkable(x, y - 1) && !self.walkable(x - dx, y - 1))
+                            || (self.walkable(x, y + 1) && !self.walkable(x - dx, y + 1))
+                    } else {
+                        (self.walkable(x - 1, y) && !self.walkable(x - 1, y - dy))
+                            || (self.walkable(x + 1, y) && !self.walkable(x + 1, y - dy))
+                    }
+                }
+                DiagonalMovement::Always => {
+                    if dx != 0 && dy != 0 {
+                        (self.walkable(x - dx, y + dy) && !self.walkable(x - dx, y))
+                            || (self.walkable(x + dx, y - dy) && !self.walkable(x, y - dy))
+                            || self.jump(x + dx, y, dx, 0, goal).is_some()
+                            || self.jump(x, y + dy, 0, dy, goal).is_some()
+                    } else if dx != 0 {
+                        (self.walkable(x + dx, y + 1) && !self.walkable(x, y + 1))
+                            || (self.walkable(x + dx, y - 1) && !self.walkable(x, y - 1))
+                    } else {
+                        (self.walkable(x + 1, y + dy) && !self.walkable(x + 1, y))
+                            || (self.walkable(x - 1, y + dy) && !self.walkable(x - 1, y))
+                    }
+                }
+            };
+
+            if is_jump_point {
+                return Some(Vector2::new(x, y));
+            }
+
+            if self.diagonal_movement == DiagonalMovement::IfNoObstacles
+                && dx != 0
+                && dy != 0
+                && !(self.walkable(x + dx, y) && self.walkable(x, y + dy))
+            {
+                return None;
+            }
+
+            x += dx;
+            y += dy;
+        }
+    }
+
+    /// Tries to build a path between two cells of the grid. The path is ordered from the first cell
+    /// to the last one and includes both of them. Returns path kind:
+    ///
+    /// - Full: there is a path from begin to end.
+    /// - Partial: there is no path from begin to end, the path leads to the closest reachable cell.
+    /// - Empty: no path available - the grid is empty.
+    pub fn build(
+        &self,
+        from: Vector2<i32>,
+        to: Vector2<i32>,
+        path: &mut Vec<Vector2<i32>>,
+    ) -> Result<PathKind, PathError> {
+        path.clear();
+
+        if self.costs.is_empty() {
+            return Ok(PathKind::Empty);
+        }
+
+        let start = self.index(from).ok_or_else(|| out_of_bounds(from))?;
+        let goal = self.index(to).ok_or_else(|| out_of_bounds(to))?;
+
+        let mut costs = FxHashMap::default();
+        let mut parents = FxHashMap::<usize, usize>::default();
+        let mut closed = FxHashSet::default();
+        let mut open = BinaryHeap::new();
+        let mut neighbours = Vec::new();
+        let mut directions = Vec::new();
+
+        costs.insert(start, 0.0);
+        open.push(OpenNode {
+            estimate: self.heuristic(from, to),
+            node: start,
+        });
+
+        let mut closest = start;
+        let mut closest_distance = f32::MAX;
+        while let Some(OpenNode { node, .. }) = open.pop() {
+            if !closed.insert(node) {
+                continue;
+            }
+
+            let cell = self.cell(node);
+            let distance = self.heuristic(cell, to);
+            if distance < closest_distance {
+                closest_distance = distance;
+                closest = node;
+            }
+
+            if node == goal {
+                break;
+            }
+
+            neighbours.clear();
+            match self.mode {
+                GridSearchMode::AStar => self.neighbours(cell, &mut neighbours),
+                GridSearchMode::JumpPointSearch => {
+                    let parent = parents.get(&node).map(|parent| self.cell(*parent));
+                    directions.clear();
+                    self.pruned_neighbours(cell, parent, &mut directions);
+                    neighbours.extend(directions.iter().filter_map(|next| {
+                        self.jump(next.x, next.y, next.x - cell.x, next.y - cell.y, to)
+                    }));
+                }
+            }
+
+            let cost = costs[&node];
+            for neighbour in neighbours.iter() {
+                let index = self.index(*neighbour).unwrap();
+                let step = (neighbour - cell).cast::<f32>().norm();
+                let neighbour_cost = match self.mode {
+                    GridSearchMode::AStar => cost + step * self.costs[index],
+                    GridSearchMode::JumpPointSearch => cost + step,
+                };
+                let is_better = match costs.get(&index) {
+                    Some(existing) => neighbour_cost < *existing,
+                    None => true,
+                };
+                if is_better {
+                    costs.insert(index, neighbour_cost);
+                    parents.insert(index, node);
+                    open.push(OpenNode {
+                        estimate: neighbour_cost + self.heuristic(*neighbour, to),
+                        node: index,
+                    });
+                }
+            }
+        }
+
+        // Reconstruct the path, the segments between jump points are straight lines, so they
+        // could be filled in by simple stepping.
+        let mut current = closest;
+        path.push(self.cell(current));
+        while let Some(parent) = parents.get(&current) {
+            let (begin, end) = (self.cell(*parent), self.cell(current));
+            let step = Vector2::new((begin.x - end.x).signum(), (begin.y - end.y).signum());
+            let mut cell = end;
+            while cell != begin {
+                cell += step;
+                path.push(cell);
+            }
+            current = *parent;
+        }
+        path.reverse();
+
+        if closest == goal {
+            Ok(PathKind::Full)
+        } else {
+            Ok(PathKind::Partial)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        utils::astar::{
+            grid::{DiagonalMovement, GridPathFinder, GridSearchMode},
+            PathKind,
+        },
+    };
+
+    fn path_length(path: &[Vector2<i32>]) -> f32 {
+        path.windows(2)
+            .map(|w| (w[1] - w[0]).cast::<f32>().norm())
+            .sum()
+    }
+
+    fn is_valid(grid: &GridPathFinder, path: &[Vector2<i32>]) -> bool {
+        path.iter().all(|cell| grid.is_walkable(*cell))
+            && path.windows(2).all(|w| {
+                let d = w[1] - w[0];
+                d.x.abs() <= 1
+                    && d.y.abs() <= 1
+                    && match grid.diagonal_movement() {
+                        DiagonalMovement::Never => d.x == 0 || d.y == 0,
+                        DiagonalMovement::IfNoObstacles => {
+                            grid.is_walkable(Vector2::new(w[0].x + d.x, w[0].y))
+                                && grid.is_walkable(Vector2::new(w[0].x, w[0].y + d.y))
+                        }
+                        DiagonalMovement::Always => true,
+                    }
+            })
+    }
+
+    #[test]
+    fn test_grid_path() {
+        let mut grid = GridPathFinder::new(20, 20);
+        // Walls with gaps on the opposite sides.
+        for i in 0..18 {
+            grid.set_walkable(Vector2::new(5, i), false);
+            grid.set_walkable(Vector2::new(12, 19 - i), false);
+        }
+        grid.set_walkable(Vector2::new(16, 10), false);
+        grid.set_walkable(Vector2::new(17, 11), false);
+
+        let from = Vector2::new(0, 0);
+        let to = Vector2::new(19, 19);
+        let mut path = Vec::new();
+        let mut jps_path = Vec::new();
+        for diagonal_movement in [
+            DiagonalMovement::Never,
+            DiagonalMovement::IfNoObstacles,
+            DiagonalMovement::Always,
+        ] {
+            grid.set_diagonal_movement(diagonal_movement);
+
+            grid.set_mode(GridSearchMode::AStar);
+            assert_eq!(grid.build(from, to, &mut path).unwrap(), PathKind::Full);
+            grid.set_mode(GridSearchMode::JumpPointSearch);
+            assert_eq!(grid.build(from, to, &mut jps_path).unwrap(), PathKind::Full);
+
+            for path in [&path, &jps_path] {
+                assert_eq!(path.first(), Some(&from));
+                assert_eq!(path.last(), Some(&to));
+                assert!(is_valid(&grid, path));
+            }
+            // Both algorithms must find the shortest path.
+            assert!((path_length(&path) - path_length(&jps_path)).abs() < 1.0e-3);
+        }
+
+        // Expensive cells are avoided by A*.
+        let mut grid = GridPathFinder::new(5, 3);
+        grid.set_diagonal_movement(DiagonalMovement::Never);
+        for x in 1..4 {
+            grid.set_cost(Vector2::new(x, 1), 10.0);
+        }
+        grid.build(Vector2::new(0, 1), Vector2::new(4, 1), &mut path)
+            .unwrap();
+        assert_eq!(path.len(), 7);
+        assert!(path
+            .iter()
+            .all(|cell| cell.y != 1 || cell.x == 0 || cell.x == 4));
+
+        // Unreachable cell.
+        grid.set_walkable(Vector2::new(3, 0), false);
+        grid.set_walkable(Vector2::new(3, 1), false);
+        grid.set_walkable(Vector2::new(3, 2), false);
+        assert_eq!(
+            grid.build(Vector2::new(0, 1), Vector2::new(4, 1), &mut path)
+                .unwrap(),
+            PathKind::Partial
+        );
+        assert_eq!(path.last(), Some(&Vector2::new(2, 1)));
+        assert!(grid
+            .build(Vector2::new(0, 1), Vector2::new(5, 1), &mut path)
+            .is_err());
+    }
+}
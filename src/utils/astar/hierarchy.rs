@@ -0,0 +1,623 @@
+//! Hierarchical path finding (HPA*) over a [`PathFinder`] graph. See [`HierarchicalPathFinder`]
+//! docs for more info.
+
+use crate::{
+    core::algebra::Vector3,
+    utils::astar::{search_graph, PathError, PathFinder, PathKind},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+};
+
+type Cluster = (i32, i32);
+
+#[derive(Clone, Debug)]
+struct Entrance {
+    vertex: usize,
+    cluster: Cluster,
+    // Abstract edges to other entrances along with their costs.
+    edges: Vec<(usize, f32)>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Node {
+    Start,
+    Goal,
+    Entrance(usize),
+}
+
+/// Hierarchical path finder splits a graph into square clusters (on XZ plane) and builds an
+/// abstract graph, that consists of entrances between adjacent clusters. A path query searches the
+/// abstract graph first, which is much smaller than the original graph, and then refines only the
+/// requested amount of segments of the abstract path. The rest of the path could be refined later
+/// using [`Self::refine`], for example a few segments per frame while an agent moves.
+///
+/// Hierarchical path finding is useful on huge maps, where a regular search over the entire graph
+/// could take too much time. Keep in mind, that the paths are slightly longer than optimal ones.
+///
+/// The hierarchy is built for a specific state of a graph, it must be rebuilt (using
+/// [`Self::rebuild`]) when the graph changes.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::algebra::Vector3,
+///     utils::astar::{
+///         hierarchy::{HierarchicalPath, HierarchicalPathFinder},
+///         PathFinder, PathVertex,
+///     },
+/// };
+///
+/// let mut graph = PathFinder::new();
+/// graph.set_vertices(
+///     (0..64)
+///         .map(|i| PathVertex::new(Vector3::new(i as f32, 0.0, 0.0)))
+///         .collect(),
+/// );
+/// for i in 0..63 {
+///     graph.link_bidirect(i, i + 1);
+/// }
+///
+/// let hierarchy = HierarchicalPathFinder::new(&graph, 8.0);
+/// let mut path = HierarchicalPath::default();
+/// // Refine only the first segment, the rest will be refined on demand.
+/// hierarchy.build(&graph, 0, 63, 1, &mut path).unwrap();
+/// while !path.is_fully_refined() {
+///     hierarchy.refine(&graph, &mut path, 1);
+/// }
+/// assert_eq!(path.points().len(), 64);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HierarchicalPathFinder {
+    cluster_size: f32,
+    clusters: Vec<Cluster>,
+    reverse_neighbours: Vec<Vec<u32>>,
+    entrances: Vec<Entrance>,
+    cluster_entrances: FxHashMap<Cluster, Vec<usize>>,
+}
+
+/// A path, that was built by [`HierarchicalPathFinder`]. The path consists of waypoints (vertices
+/// of the abstract path) and points of the refined part of the path.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HierarchicalPath {
+    waypoints: Vec<usize>,
+    points: Vec<Vector3<f32>>,
+    refined_segments: usize,
+}
+
+impl HierarchicalPath {
+    /// Returns indices of graph vertices, that form the abstract path.
+    pub fn waypoints(&self) -> &[usize] {
+        &self.waypoints
+    }
+
+    /// Returns points of the refined part of the path. Unlike [`PathFinder::build`], the points
+    /// are ordered from the beginning to the end of the path.
+    pub fn points(&self) -> &[Vector3<f32>] {
+        &self.points
+    }
+
+    /// Returns amount of refined segments (pairs of consecutive waypoints) of the path.
+    pub fn refined_segments(&self) -> usize {
+        self.refined_segments
+    }
+
+    /// Returns `true` if every segment of the path is refined.
+    pub fn is_fully_refined(&self) -> bool {
+        self.refined_segments + 1 >= self.waypoints.len()
+    }
+
+    /// Clears the path.
+    pub fn clear(&mut self) {
+        self.waypoints.clear();
+        self.points.clear();
+        self.refined_segments = 0;
+    }
+}
+
+impl HierarchicalPathFinder {
+    /// Creates new hierarchy for the given graph. `cluster_size` defines the size of a cluster
+    /// on XZ plane, bigger clusters make abstract search faster, but refinement slower.
+    pub fn new(graph: &PathFinder, cluster_size: f32) -> Self {
+        let mut hierarchy = Self {
+            cluster_size: cluster_size.max(f32::EPSILON),
+            ..Default::default()
+        };
+        hierarchy.rebuild(graph);
+        hierarchy
+    }
+
+    /// Returns the size of a cluster.
+    pub fn cluster_size(&self) -> f32 {
+        self.cluster_size
+    }
+
+    /// Returns amount of nodes of the abstract graph.
+    pub fn entrance_count(&self) -> usize {
+        self.entrances.len()
+    }
+
+    /// Rebuilds the hierarchy for the given graph. Must be called every time when the graph is
+    /// changed.
+    pub fn rebuild(&mut self, graph: &PathFinder) {
+        let vertices = graph.vertices();
+
+        self.clusters = vertices
+            .iter()
+            .map(|v| {
+                (
+                    (v.position.x / self.cluster_size).floor() as i32,
+                    (v.position.z / self.cluster_size).floor() as i32,
+                )
+            })
+            .collect();
+
+        self.reverse_neighbours = vec![Vec::new(); vertices.len()];
+        for (i, vertex) in vertices.iter().enumerate() {
+            for &neighbour in vertex.neighbours() {
+                if let Some(reverse) = self.reverse_neighbours.get_mut(neighbour as usize) {
+                    reverse.push(i as u32);
+                }
+            }
+        }
+
+        // Collect edges, that cross borders between clusters.
+        let mut crossings = FxHashMap::<(Cluster, Cluster), Vec<(usize, usize)>>::default();
+        for (a, vertex) in vertices.iter().enumerate() {
+            for &b in vertex.neighbours() {
+                let b = b as usize;
+                if b < vertices.len() && self.clusters[a] != self.clusters[b] {
+                    crossings
+                        .entry((self.clusters[a], self.clusters[b]))
+                        .or_default()
+                        .push((a, b));
+                }
+            }
+        }
+
+        self.entrances.clear();
+        self.cluster_entrances.clear();
+        let mut entrance_of = FxHashMap::<usize, usize>::default();
+        for edges in crossings.values() {
+            // Each group of adjacent crossing edges forms a single entrance, it is represented by
+            // the edge closest to the middle of the group.
+            for group in group_edges(graph, edges) {
+                let middle = group
+                    .iter()
+                    .map(|&(a, _)| vertices[a].position)
+                    .sum::<Vector3<f32>>()
+                    .scale(1.0 / group.len() as f32);
+                let mut best = group[0];
+                let mut best_distance = f32::MAX;
+                for &(a, b) in group.iter() {
+                    let distance = vertices[a].position.metric_distance(&middle);
+                    if distance < best_distance {
+                        best_distance = distance;
+                        best = (a, b);
+                    }
+                }
+
+                let (a, b) = best;
+                let a = self.add_entrance(a, &mut entrance_of);
+                let b = self.add_entrance(b, &mut entrance_of);
+                let cost = vertices[self.entrances[a].vertex]
+                    .position
+                    .metric_distance(&vertices[self.entrances[b].vertex].position);
+                self.entrances[a].edges.push((b, cost));
+            }
+        }
+
+        // Connect entrances inside of each cluster.
+        for entrance in 0..self.entrances.len() {
+            let Entrance {
+                vertex, cluster, ..
+            } = self.entrances[entrance];
+            let costs = self.local_costs(graph, vertex, cluster, false);
+            for &other in self.cluster_entrances[&cluster].iter() {
+                if other != entrance {
+                    if let Some(cost) = costs.get(&self.entrances[other].vertex) {
+                        self.entrances[entrance].edges.push((other, *cost));
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_entrance(&mut self, vertex: usize, entrance_of: &mut FxHashMap<usize, usize>) -> usize {
+        if let Some(entrance) = entrance_of.get(&vertex) {
+            return *entrance;
+        }
+
+        let index = self.entrances.len();
+        let cluster = self.clusters[vertex];
+        self.entrances.push(Entrance {
+            vertex,
+            cluster,
+            edges: Default::default(),
+        });
+        self.cluster_entrances
+            .entry(cluster)
+            .or_default()
+            .push(index);
+        entrance_of.insert(vertex, index);
+        index
+    }
+
+    // Dijkstra search, that is restricted by a cluster. Returns costs of every reachable vertex
+    // of the cluster. Reversed search returns costs of paths to the vertex instead.
+    fn local_costs(
+        &self,
+        graph: &PathFinder,
+        from: usize,
+        cluster: Cluster,
+        reversed: bool,
+    ) -> FxHashMap<usize, f32> {
+        let vertices = graph.vertices();
+
+        let mut costs = FxHashMap::default();
+        let mut open = BinaryHeap::new();
+        costs.insert(from, 0.0);
+        open.push(OpenVertex {
+            cost: 0.0,
+            vertex: from,
+        });
+
+        while let Some(OpenVertex { cost, vertex }) = open.pop() {
+            if cost > costs[&vertex] {
+                continue;
+            }
+
+            let neighbours = if reversed {
+                &self.reverse_neighbours[vertex]
+            } else {
+                vertices[vertex].neighbours()
+            };
+
+            for &neighbour in neighbours {
+                let neighbour = neighbour as usize;
+                if self.clusters.get(neighbour) != Some(&cluster) {
+                    continue;
+                }
+
+                let neighbour_cost = cost
+                    + vertices[vertex]
+                        .position
+                        .metric_distance(&vertices[neighbour].position);
+                let is_better = match costs.get(&neighbour) {
+                    Some(existing) => neighbour_cost < *existing,
+                    None => true,
+                };
+                if is_better {
+                    costs.insert(neighbour, neighbour_cost);
+                    open.push(OpenVertex {
+                        cost: neighbour_cost,
+                        vertex: neighbour,
+                    });
+                }
+            }
+        }
+
+        costs
+    }
+
+    /// Tries to build a path between two vertices of the graph. At first, the method searches the
+    /// abstract graph and then refines at most `refined_segments` segments of the abstract path.
+    /// Returns path kind:
+    ///
+    /// - Full: there is a path from begin to end.
+    /// - Partial: there is no path from begin to end, the path leads to the closest reachable
+    ///   waypoint.
+    /// - Empty: no path available - the graph is empty.
+    ///
+    /// The graph must be the same graph, that was used to build the hierarchy, otherwise an error
+    /// is returned.
+    pub fn build(
+        &self,
+        graph: &PathFinder,
+        from: usize,
+        to: usize,
+        refined_segments: usize,
+        path: &mut HierarchicalPath,
+    ) -> Result<PathKind, PathError> {
+        path.clear();
+
+        let vertices = graph.vertices();
+        if vertices.is_empty() {
+            return Ok(PathKind::Empty);
+        }
+        if vertices.len() != self.clusters.len() {
+            return Err(PathError::Custom(
+                "Hierarchy is out of date, it must be rebuilt!".to_owned(),
+            ));
+        }
+        if from >= vertices.len() {
+            return Err(PathError::InvalidIndex(from));
+        }
+        if to >= vertices.len() {
+            return Err(PathError::InvalidIndex(to));
+        }
+
+        // Vertices of the same cluster might be connected directly, it is cheaper to check it
+        // first.
+        let (start_cluster, goal_cluster) = (self.clusters[from], self.clusters[to]);
+        if start_cluster == goal_cluster
+            && self
+                .local_costs(graph, from, start_cluster, false)
+                .contains_key(&to)
+        {
+            path.waypoints.extend_from_slice(&[from, to]);
+            self.refine(graph, path, refined_segments);
+            return Ok(PathKind::Full);
+        }
+
+        let start_costs = self.local_costs(graph, from, start_cluster, false);
+        let goal_costs = self.local_costs(graph, to, goal_cluster, true);
+        let empty = Vec::new();
+
+        let position = |node| match node {
+            Node::Start => vertices[from].position,
+            Node::Goal => vertices[to].position,
+            Node::Entrance(entrance) => vertices[self.entrances[entrance].vertex].position,
+        };
+
+        let (nodes, reached) = search_graph(Node::Start, Node::Goal, position, |node, edges| {
+            let (cluster, abstract_edges) = match node {
+                Node::Start => {
+                    for &entrance in self.cluster_entrances.get(&start_cluster).unwrap_or(&empty) {
+                        if let Some(cost) = start_costs.get(&self.entrances[entrance].vertex) {
+                            edges.push((Node::Entrance(entrance), (), *cost));
+                        }
+                    }
+                    return;
+                }
+                Node::Goal => return,
+                Node::Entrance(entrance) => {
+                    let entrance = &self.entrances[entrance];
+                    (entrance.cluster, &entrance.edges)
+                }
+            };
+
+            for &(other, cost) in abstract_edges {
+                edges.push((Node::Entrance(other), (), cost));
+            }
+
+            if cluster == goal_cluster {
+                if let Node::Entrance(entrance) = node {
+                    if let Some(cost) = goal_costs.get(&self.entrances[entrance].vertex) {
+                        edges.push((Node::Goal, (), *cost));
+                    }
+                }
+            }
+        });
+
+        for (node, _) in nodes {
+            let vertex = match node {
+                Node::Start => from,
+                Node::Goal => to,
+                Node::Entrance(entrance) => self.entrances[entrance].vertex,
+            };
+            if path.waypoints.last() != Some(&vertex) {
+                path.waypoints.push(vertex);
+            }
+        }
+
+        self.refine(graph, path, refined_segments);
+
+        if reached {
+            Ok(PathKind::Full)
+        } else {
+            Ok(PathKind::Partial)
+        }
+    }
+
+    /// Refines at most `segments` next segments of the path and appends their points to the path.
+    /// Returns amount of refined segments.
+    pub fn refine(
+        &self,
+        graph: &PathFinder,
+        path: &mut HierarchicalPath,
+        segments: usize,
+    ) -> usize {
+        let vertices = graph.vertices();
+        if vertices.len() != self.clusters.len() {
+            return 0;
+        }
+
+        if path.points.is_empty() {
+            if let Some(first) = path.waypoints.first() {
+                path.points.push(vertices[*first].position);
+            }
+        }
+
+        let mut count = 0;
+        while count < segments && !path.is_fully_refined() {
+            let begin = path.waypoints[path.refined_segments];
+            let end = path.waypoints[path.refined_segments + 1];
+            let (begin_cluster, end_cluster) = (self.clusters[begin], self.clusters[end]);
+
+            let (segment, _) = search_graph(
+                begin,
+                end,
+                |vertex| vertices[vertex].position,
+                |vertex, edges| {
+                    for &neighbour in vertices[vertex].neighbours() {
+                        let neighbour = neighbour as usize;
+                        if matches!(self.clusters.get(neighbour), Some(cluster) if *cluster == begin_cluster || *cluster == end_cluster)
+                        {
+                            let cost = vertices[vertex]
+                                .position
+                                .metric_distance(&vertices[neighbour].position);
+                            edges.push((neighbour, (), cost));
+                        }
+                    }
+                },
+            );
+
+            path.points.extend(
+                segment
+                    .into_iter()
+                    .skip(1)
+                    .map(|(vertex, _)| vertices[vertex].position),
+            );
+            path.refined_segments += 1;
+            count += 1;
+        }
+
+        count
+    }
+}
+
+// Splits crossing edges into groups of adjacent edges.
+fn group_edges(graph: &PathFinder, edges: &[(usize, usize)]) -> Vec<Vec<(usize, usize)>> {
+    let sources = edges.iter().map(|(a, _)| *a).collect::<FxHashSet<_>>();
+
+    let mut groups = Vec::new();
+    let mut visited = FxHashSet::default();
+    for &(a, _) in edges {
+        if !visited.insert(a) {
+            continue;
+        }
+
+        // Flood fill over the sources of the edges.
+        let mut group_sources = FxHashSet::default();
+        let mut queue = VecDeque::from(vec![a]);
+        while let Some(source) = queue.pop_front() {
+            group_sources.insert(source);
+            for &neighbour in graph.vertices()[source].neighbours() {
+                let neighbour = neighbour as usize;
+                if sources.contains(&neighbour) && visited.insert(neighbour) {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        groups.push(
+            edges
+                .iter()
+                .filter(|(a, _)| group_sources.contains(a))
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+    }
+    groups
+}
+
+#[derive(Copy, Clone, Debug)]
+struct OpenVertex {
+    cost: f32,
+    vertex: usize,
+}
+
+impl PartialEq for OpenVertex {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for OpenVertex {}
+
+impl PartialOrd for OpenVertex {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenVertex {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, because binary heap is a max-heap.
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        utils::astar::{
+            hierarchy::{HierarchicalPath, HierarchicalPathFinder},
+            PathError, PathFinder, PathKind, PathVertex,
+        },
+    };
+
+    // Grid graph with a wall along x = 10, that has a single gap at z = 17.
+    fn make_graph() -> PathFinder {
+        let size = 20;
+        let is_wall = |x: usize, z: usize| x == 10 && z != 17;
+
+        let mut graph = PathFinder::new();
+        graph.set_vertices(
+            (0..size * size)
+                .map(|i| PathVertex::new(Vector3::new((i % size) as f32, 0.0, (i / size) as f32)))
+                .collect(),
+        );
+        for z in 0..size {
+            for x in 0..size {
+                if is_wall(x, z) {
+                    continue;
+                }
+                if x + 1 < size && !is_wall(x + 1, z) {
+                    graph.link_bidirect(z * size + x, z * size + x + 1);
+                }
+                if z + 1 < size && !is_wall(x, z + 1) {
+                    graph.link_bidirect(z * size + x, (z + 1) * size + x);
+                }
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_hierarchical_path() {
+        let mut graph = make_graph();
+        let hierarchy = HierarchicalPathFinder::new(&graph, 5.0);
+        assert!(hierarchy.entrance_count() > 0);
+
+        let mut path = HierarchicalPath::default();
+        let from = 2 * 20 + 2;
+        let to = 2 * 20 + 18;
+        assert_eq!(
+            hierarchy.build(&graph, from, to, 1, &mut path).unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(path.waypoints().first(), Some(&from));
+        assert_eq!(path.waypoints().last(), Some(&to));
+        assert_eq!(path.refined_segments(), 1);
+        assert!(!path.is_fully_refined());
+
+        while !path.is_fully_refined() {
+            assert_eq!(hierarchy.refine(&graph, &mut path, 1), 1);
+        }
+
+        let points = path.points();
+        assert_eq!(points.first(), Some(&Vector3::new(2.0, 0.0, 2.0)));
+        assert_eq!(points.last(), Some(&Vector3::new(18.0, 0.0, 2.0)));
+        // The path must go through the gap and every step must be a single edge.
+        assert!(points.contains(&Vector3::new(10.0, 0.0, 17.0)));
+        assert!(points
+            .windows(2)
+            .all(|w| (w[0].metric_distance(&w[1]) - 1.0).abs() < 1.0e-5));
+
+        // The same cluster.
+        assert_eq!(
+            hierarchy
+                .build(&graph, 0, 21, usize::MAX, &mut path)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert!(path.is_fully_refined());
+        assert_eq!(path.points().len(), 3);
+
+        // Stale hierarchy.
+        graph.add_vertex(PathVertex::new(Vector3::new(100.0, 0.0, 100.0)));
+        assert!(matches!(
+            hierarchy.build(&graph, from, to, 1, &mut path),
+            Err(PathError::Custom(_))
+        ));
+    }
+}
@@ -6,12 +6,21 @@
 
 #![warn(missing_docs)]
 
+pub mod grid;
+pub mod hierarchy;
+
 use crate::core::{
     algebra::Vector3,
     math::{self, PositionProvider},
     visitor::prelude::*,
 };
-use std::fmt::{Display, Formatter};
+use fxhash::FxHashMap;
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    fmt::{Display, Formatter},
+    hash::Hash,
+};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum PathVertexState {
@@ -434,6 +443,110 @@ impl PathFinder {
     }
 }
 
+// A* search over an abstract graph. Returns a sequence of nodes from the start node to the goal
+// node (or to the node, that is closest to the goal, if the goal is unreachable), along with the
+// edges, that were used to enter each node, and a flag, that indicates whether the goal was reached.
+// `neighbours` must provide every neighbour of a node along with an edge to it and a cost of the edge.
+pub(crate) fn search_graph<N, E, C, F>(
+    start: N,
+    goal: N,
+    center: C,
+    mut neighbours: F,
+) -> (Vec<(N, Option<E>)>, bool)
+where
+    N: Copy + Eq + Hash,
+    C: Fn(N) -> Vector3<f32>,
+    F: FnMut(N, &mut Vec<(N, E, f32)>),
+{
+    let goal_position = center(goal);
+
+    let mut costs = FxHashMap::default();
+    let mut parents = FxHashMap::<N, (N, E)>::default();
+    let mut open = BinaryHeap::new();
+    let mut edges = Vec::new();
+
+    costs.insert(start, 0.0);
+    open.push(OpenNode {
+        estimate: center(start).metric_distance(&goal_position),
+        node: start,
+    });
+
+    let mut closest = start;
+    let mut closest_distance = f32::MAX;
+    while let Some(OpenNode { node, .. }) = open.pop() {
+        let position = center(node);
+        let distance = position.metric_distance(&goal_position);
+        if distance < closest_distance {
+            closest_distance = distance;
+            closest = node;
+        }
+
+        if node == goal {
+            break;
+        }
+
+        let cost = costs[&node];
+        neighbours(node, &mut edges);
+        for (neighbour, edge, edge_cost) in edges.drain(..) {
+            let neighbour_position = center(neighbour);
+            let neighbour_cost = cost + edge_cost;
+            let is_better = match costs.get(&neighbour) {
+                Some(existing) => neighbour_cost < *existing,
+                None => true,
+            };
+            if is_better {
+                costs.insert(neighbour, neighbour_cost);
+                parents.insert(neighbour, (node, edge));
+                open.push(OpenNode {
+                    estimate: neighbour_cost + neighbour_position.metric_distance(&goal_position),
+                    node: neighbour,
+                });
+            }
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut current = closest;
+    while let Some((parent, edge)) = parents.remove(&current) {
+        path.push((current, Some(edge)));
+        current = parent;
+    }
+    path.push((start, None));
+    path.reverse();
+
+    (path, closest == goal)
+}
+
+#[derive(Copy, Clone, Debug)]
+struct OpenNode<N> {
+    estimate: f32,
+    node: N,
+}
+
+impl<N> PartialEq for OpenNode<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl<N> Eq for OpenNode<N> {}
+
+impl<N> PartialOrd for OpenNode<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for OpenNode<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, because binary heap is a max-heap.
+        other
+            .estimate
+            .partial_cmp(&self.estimate)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::rand::Rng;
@@ -536,7 +649,7 @@ mod test {
 
         pathfinder.remove_vertex(0);
 
-        assert_eq!(pathfinder.vertex(0).unwrap().neighbours, vec![]);
+        assert_eq!(pathfinder.vertex(0).unwrap().neighbours, Vec::<u32>::new());
         assert_eq!(pathfinder.vertex(1), None);
         assert_eq!(pathfinder.vertex(2), None);
     }
@@ -559,7 +672,7 @@ mod test {
 
         pathfinder.insert_vertex(0, PathVertex::new(Vector3::new(1.0, 1.0, 1.0)));
 
-        assert_eq!(pathfinder.vertex(0).unwrap().neighbours, vec![]);
+        assert_eq!(pathfinder.vertex(0).unwrap().neighbours, Vec::<u32>::new());
         assert_eq!(pathfinder.vertex(1).unwrap().neighbours, vec![2, 3]);
         assert_eq!(pathfinder.vertex(2).unwrap().neighbours, vec![1, 3]);
         assert_eq!(pathfinder.vertex(3).unwrap().neighbours, vec![2, 1]);
@@ -0,0 +1,797 @@
+//! Lighting baking utilities - cube map rendering, prefiltering of environment maps for image-based
+//! lighting and irradiance volumes. Everything in this module could be used at runtime (for example
+//! to re-bake lighting after a level was generated procedurally), but the baking is quite slow, so
+//! it should not be done every frame.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use fyrox::{
+//!     core::{algebra::Vector3, math::aabb::AxisAlignedBoundingBox},
+//!     renderer::Renderer,
+//!     scene::Scene,
+//!     utils::baking::{CubemapBakingSettings, HdrCubemap, IrradianceVolume},
+//! };
+//!
+//! fn bake(renderer: &mut Renderer, scene: &mut Scene) {
+//!     // Environment map for reflections.
+//!     let cubemap = HdrCubemap::render(
+//!         renderer,
+//!         scene,
+//!         Vector3::new(0.0, 2.0, 0.0),
+//!         &CubemapBakingSettings::default(),
+//!     )
+//!     .unwrap();
+//!     let environment = cubemap.prefilter(64, 5, 64).to_texture();
+//!
+//!     // Diffuse lighting for dynamic objects.
+//!     let volume = IrradianceVolume::bake(
+//!         renderer,
+//!         scene,
+//!         AxisAlignedBoundingBox::from_min_max(
+//!             Vector3::new(-10.0, 0.0, -10.0),
+//!             Vector3::new(10.0, 5.0, 10.0),
+//!         ),
+//!         Vector3::new(8, 3, 8),
+//!         &CubemapBakingSettings {
+//!             size: 16,
+//!             ..Default::default()
+//!         },
+//!     )
+//!     .unwrap();
+//!     let irradiance = volume.to_texture();
+//! }
+//! ```
+
+use crate::{
+    asset::Resource,
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        visitor::prelude::*,
+    },
+    renderer::{framework::error::FrameworkError, Renderer},
+    resource::texture::{
+        Texture, TextureKind, TextureMagnificationFilter, TextureMinificationFilter,
+        TexturePixelKind, TextureResource, TextureWrapMode,
+    },
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder, PerspectiveProjection, Projection},
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::f32::consts::PI;
+
+/// Settings of cube map rendering.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CubemapBakingSettings {
+    /// Size of each face of the cube map in pixels.
+    pub size: u32,
+    /// Distance to the near clipping plane.
+    pub z_near: f32,
+    /// Distance to the far clipping plane.
+    pub z_far: f32,
+}
+
+impl Default for CubemapBakingSettings {
+    fn default() -> Self {
+        Self {
+            size: 128,
+            z_near: 0.025,
+            z_far: 1024.0,
+        }
+    }
+}
+
+/// Returns a normalized direction, that points to the center of the given texel of the given face
+/// of a cube map. Faces are stored in OpenGL order: +X, -X, +Y, -Y, +Z, -Z.
+pub fn cubemap_texel_direction(face: usize, x: u32, y: u32, size: u32) -> Vector3<f32> {
+    let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let direction = match face {
+        0 => Vector3::new(1.0, -v, -u),
+        1 => Vector3::new(-1.0, -v, u),
+        2 => Vector3::new(u, 1.0, v),
+        3 => Vector3::new(u, -1.0, -v),
+        4 => Vector3::new(u, -v, 1.0),
+        _ => Vector3::new(-u, -v, -1.0),
+    };
+    direction.normalize()
+}
+
+/// Returns a face and a texel of a cube map, that the given direction points to. The direction
+/// does not need to be normalized.
+pub fn cubemap_direction_texel(direction: Vector3<f32>, size: u32) -> (usize, u32, u32) {
+    let abs = direction.abs();
+    let (face, sc, tc, ma) = if abs.x >= abs.y && abs.x >= abs.z {
+        if direction.x >= 0.0 {
+            (0, -direction.z, -direction.y, abs.x)
+        } else {
+            (1, direction.z, -direction.y, abs.x)
+        }
+    } else if abs.y >= abs.z {
+        if direction.y >= 0.0 {
+            (2, direction.x, direction.z, abs.y)
+        } else {
+            (3, direction.x, -direction.z, abs.y)
+        }
+    } else if direction.z >= 0.0 {
+        (4, direction.x, -direction.y, abs.z)
+    } else {
+        (5, -direction.x, -direction.y, abs.z)
+    };
+
+    let to_texel = |c: f32| {
+        let c = if ma > 0.0 { c / ma } else { 0.0 };
+        (((c + 1.0) * 0.5 * size as f32) as u32).min(size.saturating_sub(1))
+    };
+
+    (face, to_texel(sc), to_texel(tc))
+}
+
+/// A cube map with high dynamic range linear colors, stored on CPU side.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HdrCubemap {
+    size: u32,
+    faces: [Vec<Vector3<f32>>; 6],
+}
+
+impl HdrCubemap {
+    /// Creates new cube map from the given faces. Faces are stored in OpenGL order: +X, -X, +Y,
+    /// -Y, +Z, -Z, each face must have `size * size` texels stored row by row. Returns `None` if
+    /// any face has wrong size.
+    pub fn new(size: u32, faces: [Vec<Vector3<f32>>; 6]) -> Option<Self> {
+        if faces
+            .iter()
+            .all(|face| face.len() == (size * size) as usize)
+        {
+            Some(Self { size, faces })
+        } else {
+            None
+        }
+    }
+
+    /// Creates new cube map, every texel of which has the given color.
+    pub fn from_color(size: u32, color: Vector3<f32>) -> Self {
+        let face = vec![color; (size * size) as usize];
+        Self {
+            size,
+            faces: [
+                face.clone(),
+                face.clone(),
+                face.clone(),
+                face.clone(),
+                face.clone(),
+                face,
+            ],
+        }
+    }
+
+    /// Renders the scene from the given point into a cube map. A temporary camera is added to the
+    /// scene and removed right after rendering. The camera uses the sky box of the first enabled
+    /// camera of the scene (if any). See [`Renderer::render_scene_hdr`] for the list of rendered
+    /// objects.
+    pub fn render(
+        renderer: &mut Renderer,
+        scene: &mut Scene,
+        position: Vector3<f32>,
+        settings: &CubemapBakingSettings,
+    ) -> Result<Self, FrameworkError> {
+        let size = settings.size.max(1);
+
+        let skybox = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Camera>())
+            .find(|camera| camera.is_enabled())
+            .and_then(|camera| camera.skybox_ref().cloned());
+
+        let camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .with_projection(Projection::Perspective(PerspectiveProjection {
+            fov: PI / 2.0,
+            z_near: settings.z_near,
+            z_far: settings.z_far,
+        }))
+        .build(&mut scene.graph);
+        scene.graph[camera].as_camera_mut().set_skybox(skybox);
+
+        let result = Self::render_faces(renderer, scene, camera, size);
+
+        scene.graph.remove_node(camera);
+
+        result
+    }
+
+    fn render_faces(
+        renderer: &mut Renderer,
+        scene: &mut Scene,
+        camera: Handle<Node>,
+        size: u32,
+    ) -> Result<Self, FrameworkError> {
+        // Up vectors are chosen to match OpenGL cube map conventions.
+        let views = [
+            (Vector3::x(), Vector3::y()),
+            (-Vector3::x(), Vector3::y()),
+            (Vector3::y(), -Vector3::z()),
+            (-Vector3::y(), Vector3::z()),
+            (Vector3::z(), Vector3::y()),
+            (-Vector3::z(), Vector3::y()),
+        ];
+
+        let mut faces: [Vec<Vector3<f32>>; 6] = Default::default();
+        for (face, (look, up)) in faces.iter_mut().zip(views) {
+            scene.graph[camera]
+                .local_transform_mut()
+                .set_rotation(UnitQuaternion::face_towards(&look, &up));
+            scene.graph.update_hierarchical_data();
+            scene.graph[camera]
+                .as_camera_mut()
+                .calculate_matrices(Vector2::new(size as f32, size as f32));
+
+            let frame = renderer.render_scene_hdr(scene, camera, Vector2::new(size, size))?;
+
+            // Rendered image is mirrored horizontally relative to cube map faces.
+            face.reserve((size * size) as usize);
+            for row in frame.pixels.chunks_exact(size as usize * 4) {
+                face.extend(
+                    row.chunks_exact(4)
+                        .rev()
+                        .map(|pixel| Vector3::new(pixel[0], pixel[1], pixel[2])),
+                );
+            }
+        }
+
+        Ok(Self { size, faces })
+    }
+
+    /// Returns size of each face in pixels.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns texels of the given face, see [`Self::new`] for the layout.
+    pub fn face(&self, face: usize) -> &[Vector3<f32>] {
+        &self.faces[face]
+    }
+
+    /// Returns color of the texel, that the given direction points to.
+    pub fn sample(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        let (face, x, y) = cubemap_direction_texel(direction, self.size);
+        self.faces[face][(y * self.size + x) as usize]
+    }
+
+    /// Prefilters the cube map for image-based lighting using GGX distribution. Each level of the
+    /// result is two times smaller than the previous one and corresponds to the roughness
+    /// `level / (mip_count - 1)`, so the result could be used as a mip-mapped environment map,
+    /// where the mip level is selected by the roughness of a surface. `sample_count` defines the
+    /// amount of samples per texel, larger values produce less noisy results.
+    pub fn prefilter(&self, size: u32, mip_count: u32, sample_count: u32) -> PrefilteredCubemap {
+        let mip_count = mip_count.max(1);
+        let sample_count = sample_count.max(1);
+
+        let levels = (0..mip_count)
+            .map(|level| {
+                let level_size = (size >> level).max(1);
+                let roughness = if mip_count > 1 {
+                    level as f32 / (mip_count - 1) as f32
+                } else {
+                    0.0
+                };
+
+                let mut faces: [Vec<Vector3<f32>>; 6] = Default::default();
+                for (face_index, face) in faces.iter_mut().enumerate() {
+                    for y in 0..level_size {
+                        for x in 0..level_size {
+                            let normal = cubemap_texel_direction(face_index, x, y, level_size);
+                            face.push(self.prefilter_texel(normal, roughness, sample_count));
+                        }
+                    }
+                }
+
+                Self {
+                    size: level_size,
+                    faces,
+                }
+            })
+            .collect();
+
+        PrefilteredCubemap { levels }
+    }
+
+    fn prefilter_texel(
+        &self,
+        normal: Vector3<f32>,
+        roughness: f32,
+        sample_count: u32,
+    ) -> Vector3<f32> {
+        if roughness <= 0.0 {
+            return self.sample(normal);
+        }
+
+        // Split-sum approximation assumes that view direction is equal to the normal.
+        let mut color = Vector3::default();
+        let mut total_weight = 0.0;
+        for i in 0..sample_count {
+            let half = importance_sample_ggx(hammersley(i, sample_count), normal, roughness);
+            let light = half.scale(2.0 * normal.dot(&half)) - normal;
+            let n_dot_l = normal.dot(&light);
+            if n_dot_l > 0.0 {
+                color += self.sample(light).scale(n_dot_l);
+                total_weight += n_dot_l;
+            }
+        }
+
+        if total_weight > 0.0 {
+            color.scale(1.0 / total_weight)
+        } else {
+            self.sample(normal)
+        }
+    }
+
+    /// Projects the cube map onto spherical harmonics, that could be used to calculate diffuse
+    /// irradiance for any direction.
+    pub fn irradiance(&self) -> SphericalHarmonics {
+        let mut coefficients = [Vector3::default(); 9];
+        let mut total_weight = 0.0;
+
+        for (face_index, face) in self.faces.iter().enumerate() {
+            for y in 0..self.size {
+                for x in 0..self.size {
+                    let u = (x as f32 + 0.5) / self.size as f32 * 2.0 - 1.0;
+                    let v = (y as f32 + 0.5) / self.size as f32 * 2.0 - 1.0;
+                    // Solid angle of the texel.
+                    let weight =
+                        4.0 / ((self.size * self.size) as f32 * (1.0 + u * u + v * v).powf(1.5));
+
+                    let direction = cubemap_texel_direction(face_index, x, y, self.size);
+                    let color = face[(y * self.size + x) as usize];
+                    for (coefficient, basis) in coefficients.iter_mut().zip(sh_basis(direction)) {
+                        *coefficient += color.scale(basis * weight);
+                    }
+                    total_weight += weight;
+                }
+            }
+        }
+
+        // Compensate the error of solid angle approximation, sum of all solid angles must be 4π.
+        if total_weight > 0.0 {
+            let normalization = 4.0 * PI / total_weight;
+            for coefficient in coefficients.iter_mut() {
+                *coefficient = coefficient.scale(normalization);
+            }
+        }
+
+        SphericalHarmonics { coefficients }
+    }
+
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        for face in self.faces.iter() {
+            for color in face {
+                for component in color.iter() {
+                    bytes.extend_from_slice(&component.to_ne_bytes());
+                }
+            }
+        }
+    }
+
+    /// Creates a cube texture from the cube map. The texture has `RGB32F` pixel format and its
+    /// content is serialized along with the scene it is used in.
+    pub fn to_texture(&self) -> TextureResource {
+        let mut bytes = Vec::new();
+        self.write_bytes(&mut bytes);
+        make_texture(
+            Texture::from_bytes(
+                TextureKind::Cube {
+                    width: self.size,
+                    height: self.size,
+                },
+                TexturePixelKind::RGB32F,
+                bytes,
+                true,
+            )
+            .expect("Cube map size must match its data!"),
+            TextureMinificationFilter::Linear,
+        )
+    }
+}
+
+/// Result of [`HdrCubemap::prefilter`]. Contains a cube map for each roughness level.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrefilteredCubemap {
+    /// Prefiltered cube maps from the smoothest to the roughest.
+    pub levels: Vec<HdrCubemap>,
+}
+
+impl PrefilteredCubemap {
+    /// Creates a mip-mapped cube texture, where each mip level corresponds to a level of the
+    /// prefiltered cube map. The texture has `RGB32F` pixel format and its content is serialized
+    /// along with the scene it is used in.
+    pub fn to_texture(&self) -> TextureResource {
+        let size = self.levels.first().map(|level| level.size).unwrap_or(1);
+        let mut bytes = Vec::new();
+        for level in self.levels.iter() {
+            level.write_bytes(&mut bytes);
+        }
+        make_texture(
+            Texture::from_bytes_with_mips(
+                TextureKind::Cube {
+                    width: size,
+                    height: size,
+                },
+                TexturePixelKind::RGB32F,
+                self.levels.len() as u32,
+                bytes,
+                true,
+            )
+            .expect("Cube map size must match its data!"),
+            TextureMinificationFilter::LinearMipMapLinear,
+        )
+    }
+}
+
+fn make_texture(mut texture: Texture, filter: TextureMinificationFilter) -> TextureResource {
+    texture.set_minification_filter(filter);
+    texture.set_magnification_filter(TextureMagnificationFilter::Linear);
+    texture.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+    texture.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+    Resource::new_ok(texture)
+}
+
+fn hammersley(i: u32, count: u32) -> Vector2<f32> {
+    Vector2::new(
+        i as f32 / count as f32,
+        i.reverse_bits() as f32 * 2.328_306_4e-10,
+    )
+}
+
+fn importance_sample_ggx(xi: Vector2<f32>, normal: Vector3<f32>, roughness: f32) -> Vector3<f32> {
+    let a = roughness * roughness;
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = ((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+
+    let up = if normal.z.abs() < 0.999 {
+        Vector3::z()
+    } else {
+        Vector3::x()
+    };
+    let tangent = up.cross(&normal).normalize();
+    let bitangent = normal.cross(&tangent);
+
+    (tangent.scale(sin_theta * phi.cos())
+        + bitangent.scale(sin_theta * phi.sin())
+        + normal.scale(cos_theta))
+    .normalize()
+}
+
+fn sh_basis(d: Vector3<f32>) -> [f32; 9] {
+    [
+        0.282_095,
+        0.488_603 * d.y,
+        0.488_603 * d.z,
+        0.488_603 * d.x,
+        1.092_548 * d.x * d.y,
+        1.092_548 * d.y * d.z,
+        0.315_392 * (3.0 * d.z * d.z - 1.0),
+        1.092_548 * d.x * d.z,
+        0.546_274 * (d.x * d.x - d.y * d.y),
+    ]
+}
+
+/// Second order spherical harmonics (9 coefficients per color channel) of incoming radiance, which
+/// are enough to represent diffuse lighting with good precision.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit)]
+pub struct SphericalHarmonics {
+    /// Coefficients of the harmonics in the standard order (`L00, L1-1, L10, L11, L2-2, L2-1, L20,
+    /// L21, L22`).
+    pub coefficients: [Vector3<f32>; 9],
+}
+
+impl SphericalHarmonics {
+    /// Calculates irradiance for a surface with the given normal. To get outgoing radiance of a
+    /// Lambertian surface, multiply the irradiance by `albedo / π`.
+    pub fn irradiance(&self, normal: Vector3<f32>) -> Vector3<f32> {
+        // Convolution with clamped cosine lobe.
+        const BAND_FACTORS: [f32; 9] = [
+            PI,
+            2.0 * PI / 3.0,
+            2.0 * PI / 3.0,
+            2.0 * PI / 3.0,
+            PI / 4.0,
+            PI / 4.0,
+            PI / 4.0,
+            PI / 4.0,
+            PI / 4.0,
+        ];
+
+        let mut irradiance = Vector3::default();
+        for ((coefficient, basis), factor) in self
+            .coefficients
+            .iter()
+            .zip(sh_basis(normal))
+            .zip(BAND_FACTORS)
+        {
+            irradiance += coefficient.scale(basis * factor);
+        }
+        irradiance.sup(&Vector3::default())
+    }
+
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let mut result = *self;
+        for (a, b) in result
+            .coefficients
+            .iter_mut()
+            .zip(other.coefficients.iter())
+        {
+            *a = a.lerp(b, t);
+        }
+        result
+    }
+}
+
+/// Irradiance volume is a regular grid of light probes over a region of a scene. Each probe stores
+/// incoming light at its position as [`SphericalHarmonics`], so diffuse lighting of any point of the
+/// region could be approximated by interpolation between the closest probes. It is a cheap
+/// approximation of static global illumination for dynamic objects.
+#[derive(Clone, Debug, Default, Visit)]
+pub struct IrradianceVolume {
+    bounds: AxisAlignedBoundingBox,
+    resolution: Vector3<u32>,
+    probes: Vec<SphericalHarmonics>,
+}
+
+impl IrradianceVolume {
+    /// Creates new irradiance volume from the given probes. Probes are stored along X axis first,
+    /// then along Y and then along Z. Returns `None` if the amount of the probes does not match the
+    /// resolution.
+    pub fn new(
+        bounds: AxisAlignedBoundingBox,
+        resolution: Vector3<u32>,
+        probes: Vec<SphericalHarmonics>,
+    ) -> Option<Self> {
+        let resolution = resolution.sup(&Vector3::repeat(1));
+        if probes.len() == (resolution.x * resolution.y * resolution.z) as usize {
+            Some(Self {
+                bounds,
+                resolution,
+                probes,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Bakes irradiance volume by rendering a cube map at each probe position. Small cube maps
+    /// (16x16 pixels per face) are usually enough, because only low-frequency lighting is stored.
+    pub fn bake(
+        renderer: &mut Renderer,
+        scene: &mut Scene,
+        bounds: AxisAlignedBoundingBox,
+        resolution: Vector3<u32>,
+        settings: &CubemapBakingSettings,
+    ) -> Result<Self, FrameworkError> {
+        let mut volume = Self {
+            bounds,
+            resolution: resolution.sup(&Vector3::repeat(1)),
+            probes: Default::default(),
+        };
+
+        for z in 0..volume.resolution.z {
+            for y in 0..volume.resolution.y {
+                for x in 0..volume.resolution.x {
+                    let position = volume.probe_position(x, y, z);
+                    let cubemap = HdrCubemap::render(renderer, scene, position, settings)?;
+                    volume.probes.push(cubemap.irradiance());
+                }
+            }
+        }
+
+        Ok(volume)
+    }
+
+    /// Returns bounds of the volume.
+    pub fn bounds(&self) -> &AxisAlignedBoundingBox {
+        &self.bounds
+    }
+
+    /// Returns amount of probes along each axis.
+    pub fn resolution(&self) -> Vector3<u32> {
+        self.resolution
+    }
+
+    /// Returns all probes of the volume, see [`Self::new`] for the layout.
+    pub fn probes(&self) -> &[SphericalHarmonics] {
+        &self.probes
+    }
+
+    /// Returns world-space position of the given probe. Probes are placed at the corners of the
+    /// grid cells, if there's only one probe along an axis, it is placed at the center.
+    pub fn probe_position(&self, x: u32, y: u32, z: u32) -> Vector3<f32> {
+        let t = |i: u32, count: u32| {
+            if count > 1 {
+                i as f32 / (count - 1) as f32
+            } else {
+                0.5
+            }
+        };
+        let size = self.bounds.max - self.bounds.min;
+        self.bounds.min
+            + Vector3::new(
+                size.x * t(x, self.resolution.x),
+                size.y * t(y, self.resolution.y),
+                size.z * t(z, self.resolution.z),
+            )
+    }
+
+    fn probe(&self, x: u32, y: u32, z: u32) -> &SphericalHarmonics {
+        &self.probes[((z * self.resolution.y + y) * self.resolution.x + x) as usize]
+    }
+
+    /// Returns incoming light at the given point. The result is interpolated between the closest
+    /// probes, points outside of the volume use the closest probes at its boundary.
+    pub fn sample(&self, position: Vector3<f32>) -> SphericalHarmonics {
+        if self.probes.is_empty() {
+            return Default::default();
+        }
+
+        let size = self.bounds.max - self.bounds.min;
+        // Returns indices of the two closest probes along an axis and interpolation factor.
+        let locate = |p: f32, min: f32, size: f32, count: u32| {
+            if count <= 1 || size <= 0.0 {
+                return (0, 0, 0.0);
+            }
+            let f = ((p - min) / size).clamp(0.0, 1.0) * (count - 1) as f32;
+            let i = (f.floor() as u32).min(count - 2);
+            (i, i + 1, f - i as f32)
+        };
+
+        let (x0, x1, tx) = locate(position.x, self.bounds.min.x, size.x, self.resolution.x);
+        let (y0, y1, ty) = locate(position.y, self.bounds.min.y, size.y, self.resolution.y);
+        let (z0, z1, tz) = locate(position.z, self.bounds.min.z, size.z, self.resolution.z);
+
+        let lerp_x = |y, z| self.probe(x0, y, z).lerp(self.probe(x1, y, z), tx);
+        let lerp_y = |z| lerp_x(y0, z).lerp(&lerp_x(y1, z), ty);
+        lerp_y(z0).lerp(&lerp_y(z1), tz)
+    }
+
+    /// Calculates irradiance at the given point for a surface with the given normal. See
+    /// [`Self::sample`] for more info.
+    pub fn irradiance(&self, position: Vector3<f32>, normal: Vector3<f32>) -> Vector3<f32> {
+        self.sample(position).irradiance(normal)
+    }
+
+    /// Creates a volume texture from the probes. The texture has `RGB32F` pixel format, its size is
+    /// `(resolution.x * 9, resolution.y, resolution.z)` - nine coefficients of each probe are
+    /// stored in consecutive texels along X axis. The content of the texture is serialized along
+    /// with the scene it is used in.
+    pub fn to_texture(&self) -> TextureResource {
+        let mut bytes = Vec::new();
+        for probe in self.probes.iter() {
+            for coefficient in probe.coefficients.iter() {
+                for component in coefficient.iter() {
+                    bytes.extend_from_slice(&component.to_ne_bytes());
+                }
+            }
+        }
+        let mut texture = Texture::from_bytes(
+            TextureKind::Volume {
+                width: self.resolution.x * 9,
+                height: self.resolution.y,
+                depth: self.resolution.z,
+            },
+            TexturePixelKind::RGB32F,
+            bytes,
+            true,
+        )
+        .expect("Volume size must match its data!");
+        // Coefficients must not be blended with each other.
+        texture.set_minification_filter(TextureMinificationFilter::Nearest);
+        texture.set_magnification_filter(TextureMagnificationFilter::Nearest);
+        texture.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+        texture.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+        Resource::new_ok(texture)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, math::aabb::AxisAlignedBoundingBox},
+        resource::texture::TextureKind,
+        utils::baking::{
+            cubemap_direction_texel, cubemap_texel_direction, HdrCubemap, IrradianceVolume,
+        },
+    };
+    use std::f32::consts::PI;
+
+    fn assert_near(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!((a - b).norm() < 1.0e-3, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_cubemap_texels() {
+        let size = 8;
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    let direction = cubemap_texel_direction(face, x, y, size);
+                    assert_eq!(cubemap_direction_texel(direction, size), (face, x, y));
+                }
+            }
+        }
+        assert_eq!(
+            cubemap_direction_texel(Vector3::new(0.0, 0.0, -2.0), 8).0,
+            5
+        );
+    }
+
+    #[test]
+    fn test_constant_environment() {
+        let color = Vector3::new(1.0, 0.5, 0.25);
+        let cubemap = HdrCubemap::from_color(8, color);
+
+        let irradiance = cubemap.irradiance();
+        for normal in [
+            Vector3::x(),
+            -Vector3::y(),
+            Vector3::new(1.0, 1.0, 0.0).normalize(),
+        ] {
+            assert_near(irradiance.irradiance(normal), color.scale(PI));
+        }
+
+        let prefiltered = cubemap.prefilter(8, 4, 16);
+        assert_eq!(prefiltered.levels.len(), 4);
+        assert_eq!(prefiltered.levels[3].size(), 1);
+        for level in prefiltered.levels.iter() {
+            assert_near(level.sample(Vector3::z()), color);
+        }
+        let texture = prefiltered.to_texture();
+        assert_eq!(texture.data_ref().mip_count(), 4);
+    }
+
+    #[test]
+    fn test_irradiance_volume() {
+        let dark = HdrCubemap::from_color(4, Vector3::default()).irradiance();
+        let bright = HdrCubemap::from_color(4, Vector3::repeat(1.0)).irradiance();
+        let volume = IrradianceVolume::new(
+            AxisAlignedBoundingBox::from_min_max(Vector3::default(), Vector3::new(2.0, 1.0, 1.0)),
+            Vector3::new(2, 1, 1),
+            vec![dark, bright],
+        )
+        .unwrap();
+
+        assert_near(volume.probe_position(1, 0, 0), Vector3::new(2.0, 0.5, 0.5));
+        assert_near(
+            volume.irradiance(Vector3::new(1.0, 0.5, 0.5), Vector3::y()),
+            Vector3::repeat(0.5 * PI),
+        );
+        assert_near(
+            volume.irradiance(Vector3::new(-5.0, 0.0, 0.0), Vector3::y()),
+            Vector3::default(),
+        );
+        // Empty volume must not panic.
+        assert_eq!(
+            IrradianceVolume::default().sample(Vector3::default()),
+            Default::default()
+        );
+
+        assert!(matches!(
+            volume.to_texture().data_ref().kind(),
+            TextureKind::Volume {
+                width: 18,
+                height: 1,
+                depth: 1
+            }
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+//! Blackboard is a typed key-value storage shared between all nodes of a behavior tree. It allows
+//! behaviors to exchange facts about the world (for example, a target that was found by one leaf and
+//! then used by another) without putting everything in the user-defined context.
+//!
+//! Keys are strings, any type that implements `AsRef<str>` could be used as a key, including custom
+//! enumerations:
+//!
+//! ```rust
+//! use fyrox::utils::behavior::blackboard::Blackboard;
+//!
+//! enum Fact {
+//!     Ammo,
+//!     SeesEnemy,
+//! }
+//!
+//! impl AsRef<str> for Fact {
+//!     fn as_ref(&self) -> &str {
+//!         match self {
+//!             Fact::Ammo => "Ammo",
+//!             Fact::SeesEnemy => "SeesEnemy",
+//!         }
+//!     }
+//! }
+//!
+//! let mut blackboard = Blackboard::default();
+//! blackboard.set(Fact::Ammo, 30i64);
+//! blackboard.set(Fact::SeesEnemy, true);
+//! assert_eq!(blackboard.get::<i64>(Fact::Ammo), Some(30));
+//! assert_eq!(blackboard.get::<bool>(Fact::SeesEnemy), Some(true));
+//! ```
+
+use crate::core::{
+    algebra::{Vector2, Vector3},
+    pool::ErasedHandle,
+    visitor::prelude::*,
+};
+use fxhash::FxHashMap;
+
+/// A value stored in a blackboard.
+#[derive(Debug, PartialEq, Visit, Clone)]
+pub enum BlackboardValue {
+    /// Boolean value.
+    Bool(bool),
+    /// Integer value.
+    Integer(i64),
+    /// Floating-point value.
+    Float(f32),
+    /// String value.
+    String(String),
+    /// Two-dimensional vector.
+    Vector2(Vector2<f32>),
+    /// Three-dimensional vector.
+    Vector3(Vector3<f32>),
+    /// Type-erased handle of an object (scene node, etc.).
+    Handle(ErasedHandle),
+}
+
+impl Default for BlackboardValue {
+    fn default() -> Self {
+        Self::Bool(false)
+    }
+}
+
+/// A type that could be stored in a blackboard.
+pub trait BlackboardType: Sized {
+    /// Wraps the value.
+    fn into_value(self) -> BlackboardValue;
+
+    /// Tries to extract a value of the type, returns `None` if the value has different type.
+    fn from_value(value: &BlackboardValue) -> Option<Self>;
+}
+
+macro_rules! impl_blackboard_type {
+    ($ty:ty, $variant:ident) => {
+        impl BlackboardType for $ty {
+            fn into_value(self) -> BlackboardValue {
+                BlackboardValue::$variant(self)
+            }
+
+            fn from_value(value: &BlackboardValue) -> Option<Self> {
+                if let BlackboardValue::$variant(value) = value {
+                    Some(value.clone())
+                } else {
+                    None
+                }
+            }
+        }
+    };
+}
+
+impl_blackboard_type!(bool, Bool);
+impl_blackboard_type!(i64, Integer);
+impl_blackboard_type!(f32, Float);
+impl_blackboard_type!(String, String);
+impl_blackboard_type!(Vector2<f32>, Vector2);
+impl_blackboard_type!(Vector3<f32>, Vector3);
+impl_blackboard_type!(ErasedHandle, Handle);
+
+impl BlackboardType for BlackboardValue {
+    fn into_value(self) -> BlackboardValue {
+        self
+    }
+
+    fn from_value(value: &BlackboardValue) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+/// See module docs.
+#[derive(Debug, Default, PartialEq, Visit, Clone)]
+pub struct Blackboard {
+    values: FxHashMap<String, BlackboardValue>,
+}
+
+impl Blackboard {
+    /// Sets new value for the given key, returns previous value (if any).
+    pub fn set<K, T>(&mut self, key: K, value: T) -> Option<BlackboardValue>
+    where
+        K: AsRef<str>,
+        T: BlackboardType,
+    {
+        self.values
+            .insert(key.as_ref().to_owned(), value.into_value())
+    }
+
+    /// Returns a value of the given key. `None` is returned if there's no such key or if the value has
+    /// different type.
+    pub fn get<T>(&self, key: impl AsRef<str>) -> Option<T>
+    where
+        T: BlackboardType,
+    {
+        self.values.get(key.as_ref()).and_then(T::from_value)
+    }
+
+    /// Returns untyped value of the given key.
+    pub fn value(&self, key: impl AsRef<str>) -> Option<&BlackboardValue> {
+        self.values.get(key.as_ref())
+    }
+
+    /// Returns `true` if there's a value for the given key, `false` - otherwise.
+    pub fn contains(&self, key: impl AsRef<str>) -> bool {
+        self.values.contains_key(key.as_ref())
+    }
+
+    /// Removes a value of the given key and returns it.
+    pub fn remove(&mut self, key: impl AsRef<str>) -> Option<BlackboardValue> {
+        self.values.remove(key.as_ref())
+    }
+
+    /// Removes every value from the blackboard.
+    pub fn clear(&mut self) {
+        self.values.clear()
+    }
+
+    /// Returns an iterator over every key-value pair.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &BlackboardValue)> {
+        self.values.iter().map(|(key, value)| (key.as_str(), value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, visitor::prelude::*},
+        utils::behavior::blackboard::{Blackboard, BlackboardValue},
+    };
+
+    #[test]
+    fn test_blackboard() {
+        let mut blackboard = Blackboard::default();
+        assert_eq!(blackboard.set("Health", 100i64), None);
+        blackboard.set("Target", Vector3::new(1.0, 2.0, 3.0));
+        blackboard.set("Name", "Bot".to_string());
+
+        assert_eq!(blackboard.get::<i64>("Health"), Some(100));
+        assert_eq!(blackboard.get::<f32>("Health"), None);
+        assert_eq!(
+            blackboard.get::<Vector3<f32>>("Target"),
+            Some(Vector3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(blackboard.get::<String>("Name"), Some("Bot".to_string()));
+        assert!(!blackboard.contains("Unknown"));
+
+        assert_eq!(
+            blackboard.set("Health", 50i64),
+            Some(BlackboardValue::Integer(100))
+        );
+        assert_eq!(
+            blackboard.remove("Health"),
+            Some(BlackboardValue::Integer(50))
+        );
+        assert!(!blackboard.contains("Health"));
+
+        // Serialization.
+        let mut visitor = Visitor::new();
+        blackboard.visit("Blackboard", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut loaded = Blackboard::default();
+        loaded.visit("Blackboard", &mut visitor).unwrap();
+        assert_eq!(blackboard, loaded);
+    }
+}
@@ -0,0 +1,80 @@
+//! Budgeted evaluation of behavior trees. It allows to amortize the cost of AI of large crowds of agents
+//! over multiple frames. See [`TickBudget`] docs for more info.
+
+use crate::core::instant::Instant;
+use std::time::Duration;
+
+/// Budget of a single call of [`super::BehaviorTree::tick_budgeted`]. When the budget is exhausted, the
+/// tick is suspended: the tree returns [`super::Status::Running`] and the next tick continues from the
+/// same node instead of starting from the root, so nodes, that were already executed, are not executed
+/// again. Only leaves consume the budget, because hard coded nodes are cheap. At least one leaf is ticked
+/// per call, so the tree always progresses.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TickBudget {
+    /// Max amount of leaves, that could be ticked per call. `None` means no limit.
+    pub max_leaves: Option<usize>,
+    /// Max amount of time, that could be spent per call. `None` means no limit. The time is checked only
+    /// before ticking a leaf, so a slow leaf could exceed the budget.
+    pub max_time: Option<Duration>,
+}
+
+impl TickBudget {
+    /// Creates a new budget without any limits.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Sets max amount of leaves, that could be ticked per call.
+    pub fn with_max_leaves(mut self, max_leaves: usize) -> Self {
+        self.max_leaves = Some(max_leaves);
+        self
+    }
+
+    /// Sets max amount of time, that could be spent per call.
+    pub fn with_max_time(mut self, max_time: Duration) -> Self {
+        self.max_time = Some(max_time);
+        self
+    }
+}
+
+// State of the budget of the current tick.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) struct BudgetState {
+    max_leaves: Option<usize>,
+    deadline: Option<Instant>,
+    leaves: usize,
+    suspended: bool,
+}
+
+impl BudgetState {
+    pub(super) fn new(budget: TickBudget) -> Self {
+        Self {
+            max_leaves: budget.max_leaves,
+            deadline: budget.max_time.map(|max_time| Instant::now() + max_time),
+            leaves: 0,
+            suspended: false,
+        }
+    }
+
+    pub(super) fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    // Consumes the budget for a leaf, suspends the tick if the budget is exhausted. Returns `false` if the
+    // tick is suspended.
+    pub(super) fn consume_leaf(&mut self) -> bool {
+        if self.suspended {
+            return false;
+        }
+        let exhausted = self.max_leaves.map_or(false, |max| self.leaves >= max)
+            || self
+                .deadline
+                .map_or(false, |deadline| Instant::now() >= deadline);
+        if self.leaves > 0 && exhausted {
+            self.suspended = true;
+            return false;
+        }
+        self.leaves += 1;
+        true
+    }
+}
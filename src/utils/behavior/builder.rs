@@ -0,0 +1,295 @@
+//! Builder of behavior trees. It allows to describe a tree as a chain of calls, where every composite or
+//! decorator node opens a scope, that must be closed by [`BehaviorTreeBuilder::end`]. Nodes added inside
+//! a scope become children of the node, that opened the scope. For example, the following chain:
+//!
+//! ```text
+//! BehaviorTreeBuilder::new()
+//!     .selector()
+//!         .sequence()
+//!             .leaf(IsEnemyVisible)
+//!             .leaf(Attack)
+//!         .end()
+//!         .leaf(Patrol)
+//!     .end()
+//!     .build()
+//! ```
+//!
+//! creates a selector with two children - a sequence (with two leaves) and a leaf. The structure of the
+//! tree is validated by [`BehaviorTreeBuilder::build`], see [`BehaviorTreeBuilderError`] docs for the list
+//! of possible errors.
+
+use crate::{
+    core::pool::Handle,
+    utils::behavior::{
+        composite::{CompositeNode, ParallelPolicy},
+        decorator::{DecoratorNode, DecoratorNodeKind},
+        inverter::Inverter,
+        leaf::LeafNode,
+        subtree::SubTreeNode,
+        BehaviorNode, BehaviorTree,
+    },
+};
+use std::fmt::{Display, Formatter};
+
+/// All possible errors that may occur during building of a behavior tree.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BehaviorTreeBuilderError {
+    /// A composite node was closed without any children.
+    EmptyComposite,
+    /// A decorator (or an inverter) node was closed with zero or more than one children.
+    InvalidDecoratorChildCount {
+        /// Actual amount of children.
+        count: usize,
+    },
+    /// [`BehaviorTreeBuilder::end`] was called without a matching opening node.
+    UnmatchedEnd,
+    /// Some of the nodes were not closed by [`BehaviorTreeBuilder::end`].
+    UnclosedNodes {
+        /// Amount of unclosed nodes.
+        count: usize,
+    },
+    /// The tree has no nodes.
+    NoEntryNode,
+    /// The tree has more than one top-level node.
+    MultipleEntryNodes {
+        /// Amount of top-level nodes.
+        count: usize,
+    },
+}
+
+impl Display for BehaviorTreeBuilderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BehaviorTreeBuilderError::EmptyComposite => {
+                write!(f, "A composite node must have at least one child")
+            }
+            BehaviorTreeBuilderError::InvalidDecoratorChildCount { count } => {
+                write!(
+                    f,
+                    "A decorator node must have exactly one child, got {count}"
+                )
+            }
+            BehaviorTreeBuilderError::UnmatchedEnd => {
+                write!(
+                    f,
+                    "end() was called without a matching composite or decorator"
+                )
+            }
+            BehaviorTreeBuilderError::UnclosedNodes { count } => {
+                write!(f, "{count} node(s) were not closed by end()")
+            }
+            BehaviorTreeBuilderError::NoEntryNode => write!(f, "The tree has no nodes"),
+            BehaviorTreeBuilderError::MultipleEntryNodes { count } => {
+                write!(f, "The tree must have single entry node, got {count}")
+            }
+        }
+    }
+}
+
+enum ScopeKind<B>
+where
+    B: Clone,
+{
+    Composite(CompositeNode<B>),
+    Decorator(DecoratorNodeKind),
+    Inverter,
+}
+
+struct Scope<B>
+where
+    B: Clone,
+{
+    kind: ScopeKind<B>,
+    children: Vec<Handle<BehaviorNode<B>>>,
+}
+
+/// See module docs.
+pub struct BehaviorTreeBuilder<B>
+where
+    B: Clone,
+{
+    tree: BehaviorTree<B>,
+    scopes: Vec<Scope<B>>,
+    entry_nodes: Vec<Handle<BehaviorNode<B>>>,
+    error: Option<BehaviorTreeBuilderError>,
+}
+
+impl<B> Default for BehaviorTreeBuilder<B>
+where
+    B: Clone + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> BehaviorTreeBuilder<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new builder of an empty tree.
+    pub fn new() -> Self {
+        Self {
+            tree: BehaviorTree::new(),
+            scopes: Default::default(),
+            entry_nodes: Default::default(),
+            error: None,
+        }
+    }
+
+    fn open(mut self, kind: ScopeKind<B>) -> Self {
+        self.scopes.push(Scope {
+            kind,
+            children: Default::default(),
+        });
+        self
+    }
+
+    fn open_composite(self, composite: CompositeNode<B>) -> Self {
+        self.open(ScopeKind::Composite(composite))
+    }
+
+    fn attach(&mut self, handle: Handle<BehaviorNode<B>>) {
+        match self.scopes.last_mut() {
+            Some(scope) => scope.children.push(handle),
+            None => self.entry_nodes.push(handle),
+        }
+    }
+
+    fn fail(&mut self, error: BehaviorTreeBuilderError) {
+        // Only the first error is reported, the rest of them are usually caused by the first one.
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    /// Opens a new sequence node.
+    pub fn sequence(self) -> Self {
+        self.open_composite(CompositeNode::new_sequence(Default::default()))
+    }
+
+    /// Opens a new selector node.
+    pub fn selector(self) -> Self {
+        self.open_composite(CompositeNode::new_selector(Default::default()))
+    }
+
+    /// Opens a new sequence node, that continues execution from its running child on the next tick.
+    pub fn memory_sequence(self) -> Self {
+        self.open_composite(CompositeNode::new_memory_sequence(Default::default()))
+    }
+
+    /// Opens a new selector node, that continues execution from its running child on the next tick.
+    pub fn memory_selector(self) -> Self {
+        self.open_composite(CompositeNode::new_memory_selector(Default::default()))
+    }
+
+    /// Opens a new selector node, that executes only its child with the highest score.
+    pub fn utility_selector(self) -> Self {
+        self.open_composite(CompositeNode::new_utility_selector(Default::default()))
+    }
+
+    /// Opens a new parallel node with the given success and failure policies.
+    pub fn parallel(self, success_policy: ParallelPolicy, failure_policy: ParallelPolicy) -> Self {
+        self.open_composite(CompositeNode::new_parallel(
+            success_policy,
+            failure_policy,
+            Default::default(),
+        ))
+    }
+
+    /// Opens a new composite node. It could be used to add a pre-configured node, for example a node
+    /// with an abort policy.
+    pub fn composite(self, composite: CompositeNode<B>) -> Self {
+        self.open_composite(composite)
+    }
+
+    /// Opens a new inverter node. The node must have exactly one child.
+    pub fn inverter(self) -> Self {
+        self.open(ScopeKind::Inverter)
+    }
+
+    /// Opens a new decorator node of the given kind. The node must have exactly one child.
+    pub fn decorator(self, kind: DecoratorNodeKind) -> Self {
+        self.open(ScopeKind::Decorator(kind))
+    }
+
+    /// Adds a new leaf node to the current scope.
+    pub fn leaf(mut self, behavior: B) -> Self {
+        let handle = LeafNode::new(behavior).add_to(&mut self.tree);
+        self.attach(handle);
+        self
+    }
+
+    /// Adds a new node, that embeds the given tree, to the current scope.
+    pub fn sub_tree(mut self, tree: BehaviorTree<B>) -> Self {
+        let handle = SubTreeNode::new(tree).add_to(&mut self.tree);
+        self.attach(handle);
+        self
+    }
+
+    /// Closes the most recently opened node and adds it to the scope of its parent.
+    pub fn end(mut self) -> Self {
+        let scope = match self.scopes.pop() {
+            Some(scope) => scope,
+            None => {
+                self.fail(BehaviorTreeBuilderError::UnmatchedEnd);
+                return self;
+            }
+        };
+
+        let handle = match scope.kind {
+            ScopeKind::Composite(mut composite) => {
+                if scope.children.is_empty() {
+                    self.fail(BehaviorTreeBuilderError::EmptyComposite);
+                }
+                composite.children = scope.children;
+                composite.add_to(&mut self.tree)
+            }
+            ScopeKind::Decorator(kind) => {
+                let child = self.single_child(&scope.children);
+                DecoratorNode::new(kind, child).add_to(&mut self.tree)
+            }
+            ScopeKind::Inverter => {
+                let child = self.single_child(&scope.children);
+                Inverter::new(child).add_to(&mut self.tree)
+            }
+        };
+
+        self.attach(handle);
+        self
+    }
+
+    fn single_child(&mut self, children: &[Handle<BehaviorNode<B>>]) -> Handle<BehaviorNode<B>> {
+        if children.len() != 1 {
+            self.fail(BehaviorTreeBuilderError::InvalidDecoratorChildCount {
+                count: children.len(),
+            });
+        }
+        children.first().cloned().unwrap_or_default()
+    }
+
+    /// Validates the structure of the tree and returns the tree with its entry node set to the only
+    /// top-level node.
+    pub fn build(mut self) -> Result<BehaviorTree<B>, BehaviorTreeBuilderError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        if !self.scopes.is_empty() {
+            return Err(BehaviorTreeBuilderError::UnclosedNodes {
+                count: self.scopes.len(),
+            });
+        }
+
+        match self.entry_nodes.as_slice() {
+            [] => Err(BehaviorTreeBuilderError::NoEntryNode),
+            [entry] => {
+                self.tree.set_entry_node(*entry);
+                Ok(self.tree)
+            }
+            _ => Err(BehaviorTreeBuilderError::MultipleEntryNodes {
+                count: self.entry_nodes.len(),
+            }),
+        }
+    }
+}
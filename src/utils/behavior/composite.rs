@@ -3,12 +3,91 @@
 //! until `Status::Failure` is returned from any descendant node. In other words `Sequence`
 //! implement AND logical function. `Selector` node will execute children until `Status::Success`
 //! is returned from any descendant node. In other worlds `Selector` implement OR logical
-//! function.
+//! function. `Parallel` node will execute all children on every tick and resolve its status using
+//! [`ParallelPolicy`] for success and failure.
+//!
+//! `MemorySequence` and `MemorySelector` are stateful variants of `Sequence` and `Selector`. They remember
+//! the child that returned `Status::Running` and resume from it on the next tick, instead of re-executing
+//! children that have already finished.
+//!
+//! Since memory composites do not re-execute finished children, they could miss changes in the world. To
+//! react to such changes, a composite node could observe its condition (the first child) using
+//! [`AbortPolicy`] and interrupt running branches when the result of the condition changes.
+//!
+//! `UtilitySelector` mixes utility AI with behavior trees: it scores its children (see
+//! [`crate::utils::behavior::Behavior::score`]) on every tick and executes only the most useful one.
 
 use crate::{
     core::{pool::Handle, visitor::prelude::*},
-    utils::behavior::{BehaviorNode, BehaviorTree},
+    utils::behavior::{BehaviorNode, BehaviorTree, Status},
 };
+use std::cell::Cell;
+
+// Progress of a composite node, that was interrupted because the budget of a tick was exhausted (see
+// `BehaviorTree::tick_budgeted`). `Parallel` nodes also remember the results of the children, that were
+// executed before the interruption.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub(super) struct Suspension {
+    pub(super) child: usize,
+    pub(super) successes: usize,
+    pub(super) failures: usize,
+}
+
+/// Defines how many children of `Parallel` composite node must succeed (or fail) for the node to
+/// succeed (or fail).
+#[derive(Debug, Default, PartialEq, Visit, Eq, Clone, Copy)]
+pub enum ParallelPolicy {
+    /// At least one child must have the status.
+    RequireOne,
+    /// Every child must have the status.
+    #[default]
+    RequireAll,
+}
+
+impl ParallelPolicy {
+    fn is_satisfied(&self, count: usize, total: usize) -> bool {
+        match self {
+            ParallelPolicy::RequireOne => count > 0,
+            ParallelPolicy::RequireAll => count == total,
+        }
+    }
+}
+
+/// Defines which running branches are interrupted when the result of the condition (the first child) of a
+/// composite node changes. Conditions are re-evaluated only by memory composites (`MemorySequence` and
+/// `MemorySelector`), because other kinds of composites re-evaluate all their children on every tick anyway.
+/// Keep in mind, that the conditions could be evaluated more than once per tick, so they should not have
+/// side effects.
+#[derive(Debug, Default, PartialEq, Visit, Eq, Clone, Copy)]
+pub enum AbortPolicy {
+    /// The condition is not observed.
+    #[default]
+    None,
+    /// The condition is re-evaluated while a later child of the same (memory) composite is running. If
+    /// the result has changed, the running branch is aborted and the composite starts over from its first
+    /// child. For example, a sequence `[IsEnemyVisible, MoveToEnemy, Attack]` will stop moving to an enemy
+    /// as soon as the enemy becomes invisible. Also known as "Self" policy.
+    SelfBranch,
+    /// The condition is re-evaluated by the parent (memory) composite while a lower priority sibling (any
+    /// of the next children of the parent) is running. If the result has changed, the parent aborts the
+    /// running sibling and continues execution from this composite. For example, a selector
+    /// `[[IsEnemyVisible, Attack], Patrol]` will stop patrolling as soon as an enemy becomes visible.
+    LowerPriority,
+    /// Combination of [`Self::SelfBranch`] and [`Self::LowerPriority`].
+    Both,
+}
+
+impl AbortPolicy {
+    /// Returns `true` if the policy aborts the own running branch of the composite.
+    pub fn aborts_self(self) -> bool {
+        matches!(self, AbortPolicy::SelfBranch | AbortPolicy::Both)
+    }
+
+    /// Returns `true` if the policy aborts running lower priority siblings of the composite.
+    pub fn aborts_lower_priority(self) -> bool {
+        matches!(self, AbortPolicy::LowerPriority | AbortPolicy::Both)
+    }
+}
 
 /// Defines exact behavior of the composite node.
 #[derive(Debug, PartialEq, Visit, Eq, Clone)]
@@ -21,6 +100,30 @@ pub enum CompositeNodeKind {
     /// is returned from any descendant node. In other worlds `Selector` implement OR logical
     /// function.
     Selector,
+    /// `Parallel` node will execute all children on every tick. The node fails if the failure policy
+    /// is satisfied, otherwise it succeeds if the success policy is satisfied, otherwise it is
+    /// [`Status::Running`].
+    Parallel {
+        /// Defines how many children must succeed for the node to succeed.
+        success_policy: ParallelPolicy,
+        /// Defines how many children must fail for the node to fail.
+        failure_policy: ParallelPolicy,
+    },
+    /// Same as `Sequence`, but remembers the child that returned [`Status::Running`] and continues
+    /// execution from it on the next tick. Children that have already succeeded are not executed
+    /// again until the whole sequence is finished.
+    MemorySequence,
+    /// Same as `Selector`, but remembers the child that returned [`Status::Running`] and continues
+    /// execution from it on the next tick. Children that have already failed are not executed
+    /// again until the whole selector is finished.
+    MemorySelector,
+    /// `UtilitySelector` node scores its children on every tick and executes only the child with the
+    /// highest score (the first one if there are multiple such children), the status of the node is the
+    /// status of the child. The score of a leaf is provided by its behavior (see
+    /// [`crate::utils::behavior::Behavior::score`]), the score of any other node is the score of its
+    /// first child, so a branch could be scored by its condition. When another child is selected, the
+    /// branch of the previously selected child is aborted. The node fails if it has no children.
+    UtilitySelector,
 }
 
 impl Default for CompositeNodeKind {
@@ -39,6 +142,20 @@ where
     pub children: Vec<Handle<BehaviorNode<B>>>,
     /// Current kind of the node.
     pub kind: CompositeNodeKind,
+    /// Defines which running branches are interrupted when the result of the condition (the first child)
+    /// changes. See [`AbortPolicy`] docs for more info.
+    #[visit(optional)]
+    pub abort_policy: AbortPolicy,
+    /// Index of the child that returned [`Status::Running`] on the previous tick (running branch), memory
+    /// kinds of the node continue execution from it.
+    #[visit(optional)]
+    running_child: Cell<u32>,
+    /// Last known result of the condition (the first child), it is used to detect changes of the result.
+    #[visit(optional)]
+    condition: Cell<Option<bool>>,
+    /// Progress of the node, that was interrupted by the budget of the previous tick.
+    #[visit(skip)]
+    suspension: Cell<Option<Suspension>>,
 }
 
 impl<B> Default for CompositeNode<B>
@@ -49,6 +166,10 @@ where
         Self {
             children: Default::default(),
             kind: Default::default(),
+            abort_policy: Default::default(),
+            running_child: Default::default(),
+            condition: Default::default(),
+            suspension: Default::default(),
         }
     }
 }
@@ -59,23 +180,96 @@ where
 {
     /// Creates new composite node of given kind and set of children nodes.
     pub fn new(kind: CompositeNodeKind, children: Vec<Handle<BehaviorNode<B>>>) -> Self {
-        Self { children, kind }
+        Self {
+            children,
+            kind,
+            abort_policy: Default::default(),
+            running_child: Default::default(),
+            condition: Default::default(),
+            suspension: Default::default(),
+        }
     }
 
     /// Creates new sequence composite node with a set of children nodes.
     pub fn new_sequence(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
-        Self {
-            children,
-            kind: CompositeNodeKind::Sequence,
-        }
+        Self::new(CompositeNodeKind::Sequence, children)
     }
 
     /// Creates new selector composite node with a set of children nodes.
     pub fn new_selector(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
-        Self {
+        Self::new(CompositeNodeKind::Selector, children)
+    }
+
+    /// Creates new memory sequence composite node with a set of children nodes.
+    pub fn new_memory_sequence(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
+        Self::new(CompositeNodeKind::MemorySequence, children)
+    }
+
+    /// Creates new memory selector composite node with a set of children nodes.
+    pub fn new_memory_selector(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
+        Self::new(CompositeNodeKind::MemorySelector, children)
+    }
+
+    /// Creates new utility selector composite node with a set of children nodes.
+    pub fn new_utility_selector(children: Vec<Handle<BehaviorNode<B>>>) -> Self {
+        Self::new(CompositeNodeKind::UtilitySelector, children)
+    }
+
+    /// Creates new parallel composite node with the given policies and a set of children nodes.
+    pub fn new_parallel(
+        success_policy: ParallelPolicy,
+        failure_policy: ParallelPolicy,
+        children: Vec<Handle<BehaviorNode<B>>>,
+    ) -> Self {
+        Self::new(
+            CompositeNodeKind::Parallel {
+                success_policy,
+                failure_policy,
+            },
             children,
-            kind: CompositeNodeKind::Selector,
-        }
+        )
+    }
+
+    /// Sets the desired abort policy of the node.
+    pub fn with_abort_policy(mut self, abort_policy: AbortPolicy) -> Self {
+        self.abort_policy = abort_policy;
+        self
+    }
+
+    /// Returns index of the child, that returned [`Status::Running`] on the previous tick (running branch).
+    /// Memory kinds of the node will continue execution from it on the next tick, `UtilitySelector` uses it
+    /// to detect changes of the selected child. It is always zero for `Parallel` kind of the node.
+    pub fn running_child(&self) -> usize {
+        self.running_child.get() as usize
+    }
+
+    /// Resets the remembered running child, so the next tick will start from the first child.
+    pub fn reset(&self) {
+        self.running_child.set(0);
+        self.suspension.set(None);
+    }
+
+    pub(super) fn set_running_child(&self, index: usize) {
+        self.running_child.set(index as u32);
+    }
+
+    pub(super) fn suspend(&self, suspension: Suspension) {
+        self.suspension.set(Some(suspension));
+    }
+
+    pub(super) fn take_suspension(&self) -> Option<Suspension> {
+        self.suspension.take()
+    }
+
+    /// Remembers the result of the condition, returns `true` if the result has changed. Running (or broken)
+    /// condition has no result yet, so it is ignored.
+    pub(super) fn observe_condition(&self, status: &Status) -> bool {
+        let result = match status {
+            Status::Success => true,
+            Status::Failure => false,
+            Status::Running | Status::Error(_) => return false,
+        };
+        self.condition.replace(Some(result)) != Some(result)
     }
 
     /// Adds self to the tree and return handle to self.
@@ -83,3 +277,22 @@ where
         tree.add_node(BehaviorNode::Composite(self))
     }
 }
+
+/// Resolves the status of `Parallel` composite node from the statuses of its children.
+pub(super) fn resolve_parallel_status(
+    success_policy: ParallelPolicy,
+    failure_policy: ParallelPolicy,
+    successes: usize,
+    failures: usize,
+    total: usize,
+) -> Status {
+    if total == 0 {
+        Status::Success
+    } else if failure_policy.is_satisfied(failures, total) {
+        Status::Failure
+    } else if success_policy.is_satisfied(successes, total) {
+        Status::Success
+    } else {
+        Status::Running
+    }
+}
@@ -0,0 +1,253 @@
+//! Decorator node is a node with a single child, that modifies the status returned by its child.
+//! Decorator node could be `Inverter`, `ForceSuccess`, `ForceFailure`, `Repeat`, `RepeatUntilFail`,
+//! `Cooldown` or `TimeLimit`. See [`DecoratorNodeKind`] docs for more info.
+
+use crate::{
+    core::{pool::Handle, visitor::prelude::*},
+    utils::behavior::{inverter, BehaviorNode, BehaviorTree, Status},
+};
+use std::cell::Cell;
+
+/// Defines exact behavior of the decorator node.
+#[derive(Debug, Default, Visit, Clone)]
+pub enum DecoratorNodeKind {
+    /// `Inverter` node inverts its child status ([`Status::Failure`] becomes [`Status::Success`]
+    /// and vice versa, [`Status::Running`] remains unchanged). It works exactly the same as
+    /// [`super::inverter::Inverter`] node.
+    #[default]
+    Inverter,
+    /// `ForceSuccess` (also known as Succeeder) node always returns [`Status::Success`] when its
+    /// child is finished, [`Status::Running`] remains unchanged.
+    ForceSuccess,
+    /// `ForceFailure` (also known as Failer) node always returns [`Status::Failure`] when its
+    /// child is finished, [`Status::Running`] remains unchanged.
+    ForceFailure,
+    /// `Repeat` node executes its child again when it is finished (no matter successfully or not).
+    /// The child is executed once per tick, the node returns [`Status::Running`] until the child was
+    /// executed the given amount of times and then returns [`Status::Success`]. Zero count means that
+    /// the child is not executed at all and the node succeeds immediately. `None` count means infinite
+    /// repetition - the node will always return [`Status::Running`].
+    Repeat {
+        /// Amount of repetitions, `None` - infinite.
+        count: Option<u32>,
+    },
+    /// `RepeatUntilFail` node executes its child again until it fails. The node returns
+    /// [`Status::Running`] while the child succeeds and [`Status::Success`] when the child fails.
+    RepeatUntilFail,
+    /// `Cooldown` node can't succeed more often than once per the given amount of time. When its child
+    /// succeeds, the node returns [`Status::Success`] and then returns [`Status::Failure`] without
+    /// executing the child until the cooldown is over.
+    Cooldown {
+        /// Duration of the cooldown in seconds.
+        duration: f32,
+    },
+    /// `TimeLimit` node returns [`Status::Failure`] once its child has been running for the given amount
+    /// of time, otherwise it returns the status of its child.
+    TimeLimit {
+        /// Maximum running time of the child in seconds.
+        duration: f32,
+    },
+}
+
+/// See module docs.
+#[derive(Debug, Visit, Clone)]
+pub struct DecoratorNode<B>
+where
+    B: Clone,
+{
+    /// A handle of child node, the status of which will be modified.
+    pub child: Handle<BehaviorNode<B>>,
+    /// Current kind of the node.
+    pub kind: DecoratorNodeKind,
+    /// Amount of finished executions of the child node, it is used by repeating kinds of the node.
+    #[visit(optional)]
+    iteration: Cell<u32>,
+    /// Time of the tree at which the cooldown ends, or at which the child started running. It is used by
+    /// timed kinds of the node.
+    #[visit(optional)]
+    timestamp: Cell<Option<f32>>,
+}
+
+impl<B> Default for DecoratorNode<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            child: Default::default(),
+            kind: Default::default(),
+            iteration: Default::default(),
+            timestamp: Default::default(),
+        }
+    }
+}
+
+// Durations and timestamps are compared bitwise, so the comparison is reflexive even for NaN and the nodes
+// could be `Eq`.
+impl PartialEq for DecoratorNodeKind {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Repeat { count: a }, Self::Repeat { count: b }) => a == b,
+            (Self::Cooldown { duration: a }, Self::Cooldown { duration: b })
+            | (Self::TimeLimit { duration: a }, Self::TimeLimit { duration: b }) => {
+                a.to_bits() == b.to_bits()
+            }
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for DecoratorNodeKind {}
+
+impl<B> PartialEq for DecoratorNode<B>
+where
+    B: Clone,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.child == other.child
+            && self.kind == other.kind
+            && self.iteration == other.iteration
+            && self.timestamp.get().map(f32::to_bits) == other.timestamp.get().map(f32::to_bits)
+    }
+}
+
+impl<B> Eq for DecoratorNode<B> where B: Clone {}
+
+impl<B> DecoratorNode<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new decorator node of given kind with the given child node.
+    pub fn new(kind: DecoratorNodeKind, child: Handle<BehaviorNode<B>>) -> Self {
+        Self {
+            child,
+            kind,
+            iteration: Default::default(),
+            timestamp: Default::default(),
+        }
+    }
+
+    /// Creates new inverter decorator node with the given child node.
+    pub fn new_inverter(child: Handle<BehaviorNode<B>>) -> Self {
+        Self::new(DecoratorNodeKind::Inverter, child)
+    }
+
+    /// Creates new decorator node, that always succeeds when its child is finished.
+    pub fn new_force_success(child: Handle<BehaviorNode<B>>) -> Self {
+        Self::new(DecoratorNodeKind::ForceSuccess, child)
+    }
+
+    /// Creates new decorator node, that always fails when its child is finished.
+    pub fn new_force_failure(child: Handle<BehaviorNode<B>>) -> Self {
+        Self::new(DecoratorNodeKind::ForceFailure, child)
+    }
+
+    /// Creates new decorator node, that executes its child the given amount of times (`None` -
+    /// infinitely).
+    pub fn new_repeat(count: Option<u32>, child: Handle<BehaviorNode<B>>) -> Self {
+        Self::new(DecoratorNodeKind::Repeat { count }, child)
+    }
+
+    /// Creates new decorator node, that executes its child until it fails.
+    pub fn new_repeat_until_fail(child: Handle<BehaviorNode<B>>) -> Self {
+        Self::new(DecoratorNodeKind::RepeatUntilFail, child)
+    }
+
+    /// Creates new decorator node, that can't succeed more often than once per the given amount of
+    /// seconds.
+    pub fn new_cooldown(duration: f32, child: Handle<BehaviorNode<B>>) -> Self {
+        Self::new(DecoratorNodeKind::Cooldown { duration }, child)
+    }
+
+    /// Creates new decorator node, that fails when its child is running longer than the given amount
+    /// of seconds.
+    pub fn new_time_limit(duration: f32, child: Handle<BehaviorNode<B>>) -> Self {
+        Self::new(DecoratorNodeKind::TimeLimit { duration }, child)
+    }
+
+    /// Returns amount of finished executions of the child node since the last time the repetition was
+    /// finished. It is always zero for non-repeating kinds of the node.
+    pub fn iteration(&self) -> u32 {
+        self.iteration.get()
+    }
+
+    /// Returns a status, that must be returned by the node at the given time of the tree instead of
+    /// executing its child. It is used by `Cooldown` and `Repeat` kinds of the node, `None` means that
+    /// the child must be executed.
+    pub fn check(&self, time: f32) -> Option<Status> {
+        match (&self.kind, self.timestamp.get()) {
+            (DecoratorNodeKind::Cooldown { .. }, Some(end)) if time < end => Some(Status::Failure),
+            (DecoratorNodeKind::Repeat { count: Some(0) }, _) => Some(Status::Success),
+            _ => None,
+        }
+    }
+
+    /// Modifies the given status of the child node according to the kind of the node. `time` is the
+    /// time of the tree (see [`BehaviorTree::time`]), it is used by timed kinds of the node.
+    pub fn apply(&self, status: Status, time: f32) -> Status {
+        match (&self.kind, status) {
+            (DecoratorNodeKind::TimeLimit { duration }, Status::Running) => {
+                let start = self.timestamp.get().unwrap_or(time);
+                if time - start >= *duration {
+                    self.timestamp.set(None);
+                    Status::Failure
+                } else {
+                    self.timestamp.set(Some(start));
+                    Status::Running
+                }
+            }
+            (DecoratorNodeKind::TimeLimit { .. }, status) => {
+                self.timestamp.set(None);
+                status
+            }
+            (_, Status::Error(error)) => {
+                self.abort();
+                Status::Error(error)
+            }
+            (_, Status::Running) => Status::Running,
+            (DecoratorNodeKind::Inverter, status) => inverter::invert(status),
+            (DecoratorNodeKind::ForceSuccess, _) => Status::Success,
+            (DecoratorNodeKind::ForceFailure, _) => Status::Failure,
+            (DecoratorNodeKind::Repeat { count }, _) => {
+                let iteration = self.iteration.get().saturating_add(1);
+                match count {
+                    Some(count) if iteration >= *count => {
+                        self.iteration.set(0);
+                        Status::Success
+                    }
+                    _ => {
+                        self.iteration.set(iteration);
+                        Status::Running
+                    }
+                }
+            }
+            (DecoratorNodeKind::RepeatUntilFail, Status::Success) => {
+                self.iteration.set(self.iteration.get().saturating_add(1));
+                Status::Running
+            }
+            (DecoratorNodeKind::RepeatUntilFail, Status::Failure) => {
+                self.iteration.set(0);
+                Status::Success
+            }
+            (DecoratorNodeKind::Cooldown { duration }, Status::Success) => {
+                self.timestamp.set(Some(time + *duration));
+                Status::Success
+            }
+            (DecoratorNodeKind::Cooldown { .. }, Status::Failure) => Status::Failure,
+        }
+    }
+
+    /// Resets the state of the node, when the branch it belongs to was aborted. Cooldown is not reset,
+    /// since it must be preserved no matter how the child was finished.
+    pub(super) fn abort(&self) {
+        self.iteration.set(0);
+        if let DecoratorNodeKind::TimeLimit { .. } = self.kind {
+            self.timestamp.set(None);
+        }
+    }
+
+    /// Adds self to the tree and return handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::Decorator(self))
+    }
+}
@@ -0,0 +1,264 @@
+//! Serializable description of the structure of a behavior tree, that is intended for visualization in
+//! the editor, debug overlays or external tools. See [`super::BehaviorTree::export_graph`].
+
+use crate::{
+    core::{color::Color, pool::Handle},
+    utils::behavior::{
+        composite::{AbortPolicy, CompositeNodeKind},
+        decorator::DecoratorNodeKind,
+        trace::TickTrace,
+        BehaviorNode, BehaviorTree, Status,
+    },
+};
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Write};
+
+/// Visual category of a node.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphNodeKind {
+    /// Root node of a tree.
+    Root,
+    /// Sequence, selector or parallel node.
+    Composite,
+    /// Inverter or any other decorator node.
+    Decorator,
+    /// A node with custom logic.
+    Leaf,
+    /// A node, that embeds another tree.
+    SubTree,
+}
+
+/// Status of a node during the traced tick.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphNodeStatus {
+    /// The node was not visited (or there's no trace).
+    NotVisited,
+    /// See [`Status::Success`].
+    Success,
+    /// See [`Status::Failure`].
+    Failure,
+    /// See [`Status::Running`].
+    Running,
+    /// See [`Status::Error`].
+    Error,
+}
+
+impl From<Option<Status>> for GraphNodeStatus {
+    fn from(status: Option<Status>) -> Self {
+        match status {
+            None => Self::NotVisited,
+            Some(Status::Success) => Self::Success,
+            Some(Status::Failure) => Self::Failure,
+            Some(Status::Running) => Self::Running,
+            Some(Status::Error(_)) => Self::Error,
+        }
+    }
+}
+
+impl GraphNodeStatus {
+    /// Returns the color, that is used to show the status.
+    pub fn color(self) -> Color {
+        match self {
+            GraphNodeStatus::NotVisited => Color::opaque(128, 128, 128),
+            GraphNodeStatus::Success => Color::opaque(60, 180, 75),
+            GraphNodeStatus::Failure => Color::opaque(220, 50, 50),
+            GraphNodeStatus::Running => Color::opaque(240, 170, 30),
+            GraphNodeStatus::Error => Color::opaque(200, 40, 200),
+        }
+    }
+}
+
+/// A node of the graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Index and generation of the handle of the node in the tree, it could be used to find the node in
+    /// the tree. `None` for the nodes of embedded trees.
+    pub handle: Option<(u32, u32)>,
+    /// Human-readable name of the node, leaves are named using the debug representation of their behaviors.
+    pub name: String,
+    /// Visual category of the node.
+    pub kind: GraphNodeKind,
+    /// Status of the node during the traced tick.
+    pub status: GraphNodeStatus,
+    /// Color of the status in RGBA format.
+    pub color: [u8; 4],
+}
+
+/// A connection between a parent node and its child.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    /// Index of the parent node in the list of nodes of the graph.
+    pub from: usize,
+    /// Index of the child node in the list of nodes of the graph.
+    pub to: usize,
+}
+
+/// Graph description of a behavior tree. The first node is the root of the tree, nodes are stored in
+/// depth-first order, so parents go before their children, and the edges of a node are stored in the
+/// order of its children. Nodes, that are not attached to the tree, go after the attached ones. Nodes of
+/// embedded trees (see [`super::subtree::SubTreeNode`]) go after the nodes of their host tree, the root
+/// of an embedded tree is the child of its sub-tree node.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BehaviorTreeGraph {
+    /// Nodes of the graph.
+    pub nodes: Vec<GraphNode>,
+    /// Edges of the graph.
+    pub edges: Vec<GraphEdge>,
+}
+
+impl BehaviorTreeGraph {
+    /// Returns indices of the children of the given node.
+    pub fn children_of(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.from == node)
+            .map(|edge| edge.to)
+    }
+
+    /// Writes the graph in Graphviz DOT format, it could be used to inspect trees outside of the engine.
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph BehaviorTree {\n    node [shape=box, style=filled];\n".to_string();
+        for (index, node) in self.nodes.iter().enumerate() {
+            let [r, g, b, _] = node.color;
+            let _ = writeln!(
+                dot,
+                "    {index} [label=\"{}\", fillcolor=\"#{r:02x}{g:02x}{b:02x}\"];",
+                node.name.replace('\\', "\\\\").replace('"', "\\\"")
+            );
+        }
+        for edge in self.edges.iter() {
+            let _ = writeln!(dot, "    {} -> {};", edge.from, edge.to);
+        }
+        dot.push('}');
+        dot
+    }
+}
+
+fn composite_name(kind: &CompositeNodeKind, abort_policy: AbortPolicy) -> String {
+    let name = match kind {
+        CompositeNodeKind::Sequence => "Sequence",
+        CompositeNodeKind::Selector => "Selector",
+        CompositeNodeKind::Parallel { .. } => "Parallel",
+        CompositeNodeKind::MemorySequence => "MemorySequence",
+        CompositeNodeKind::MemorySelector => "MemorySelector",
+        CompositeNodeKind::UtilitySelector => "UtilitySelector",
+    };
+    if abort_policy == AbortPolicy::None {
+        name.to_string()
+    } else {
+        format!("{name} (abort: {abort_policy:?})")
+    }
+}
+
+fn decorator_name(kind: &DecoratorNodeKind) -> String {
+    match kind {
+        DecoratorNodeKind::Inverter => "Inverter".to_string(),
+        DecoratorNodeKind::ForceSuccess => "ForceSuccess".to_string(),
+        DecoratorNodeKind::ForceFailure => "ForceFailure".to_string(),
+        DecoratorNodeKind::Repeat { count: Some(count) } => format!("Repeat ({count})"),
+        DecoratorNodeKind::Repeat { count: None } => "Repeat (infinite)".to_string(),
+        DecoratorNodeKind::RepeatUntilFail => "RepeatUntilFail".to_string(),
+        DecoratorNodeKind::Cooldown { duration } => format!("Cooldown ({duration}s)"),
+        DecoratorNodeKind::TimeLimit { duration } => format!("TimeLimit ({duration}s)"),
+    }
+}
+
+fn describe<B>(node: &BehaviorNode<B>) -> (String, GraphNodeKind)
+where
+    B: Clone + Debug,
+{
+    match node {
+        BehaviorNode::Unknown => ("Unknown".to_string(), GraphNodeKind::Leaf),
+        BehaviorNode::Root(_) => ("Root".to_string(), GraphNodeKind::Root),
+        BehaviorNode::Composite(composite) => (
+            composite_name(&composite.kind, composite.abort_policy),
+            GraphNodeKind::Composite,
+        ),
+        BehaviorNode::Leaf(leaf) => (
+            leaf.behavior
+                .as_ref()
+                .map_or_else(|| "Leaf".to_string(), |b| format!("{:?}", b.borrow())),
+            GraphNodeKind::Leaf,
+        ),
+        BehaviorNode::Inverter(_) => ("Inverter".to_string(), GraphNodeKind::Decorator),
+        BehaviorNode::Decorator(decorator) => {
+            (decorator_name(&decorator.kind), GraphNodeKind::Decorator)
+        }
+        BehaviorNode::SubTree(_) => ("SubTree".to_string(), GraphNodeKind::SubTree),
+    }
+}
+
+fn collect<B>(
+    tree: &BehaviorTree<B>,
+    handle: Handle<BehaviorNode<B>>,
+    order: &mut Vec<Handle<BehaviorNode<B>>>,
+) where
+    B: Clone + 'static,
+{
+    if let Some(node) = tree.nodes.try_borrow(handle) {
+        if !order.contains(&handle) {
+            order.push(handle);
+            for child in node.children() {
+                collect(tree, child, order);
+            }
+        }
+    }
+}
+
+// Adds the nodes of the tree to the graph, returns the index of the root node in the graph.
+pub(super) fn export_tree<B>(
+    tree: &BehaviorTree<B>,
+    trace: Option<&TickTrace<B>>,
+    embedded: bool,
+    graph: &mut BehaviorTreeGraph,
+) -> usize
+where
+    B: Clone + Debug + 'static,
+{
+    let mut order = Vec::new();
+    collect(tree, tree.root, &mut order);
+    for (handle, _) in tree.nodes.pair_iter() {
+        if !order.contains(&handle) {
+            order.push(handle);
+        }
+    }
+
+    let root = graph.nodes.len();
+    let mut indices = FxHashMap::default();
+    for handle in order.iter() {
+        let (name, kind) = describe(&tree.nodes[*handle]);
+        let status = GraphNodeStatus::from(trace.and_then(|trace| trace.status_of(*handle)));
+        let color = status.color();
+        indices.insert(*handle, graph.nodes.len());
+        graph.nodes.push(GraphNode {
+            handle: if embedded {
+                None
+            } else {
+                Some((handle.index(), handle.generation()))
+            },
+            name,
+            kind,
+            status,
+            color: [color.r, color.g, color.b, color.a],
+        });
+    }
+
+    for handle in order.iter() {
+        let from = indices[handle];
+        let node = &tree.nodes[*handle];
+        for child in node.children() {
+            if let Some(to) = indices.get(&child) {
+                graph.edges.push(GraphEdge { from, to: *to });
+            }
+        }
+        if let BehaviorNode::SubTree(sub_tree) = node {
+            if let Some(sub_tree) = sub_tree.tree.as_ref() {
+                let to = export_tree(sub_tree, None, true, graph);
+                graph.edges.push(GraphEdge { from, to });
+            }
+        }
+    }
+
+    root
+}
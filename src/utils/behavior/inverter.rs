@@ -3,7 +3,7 @@
 
 use crate::{
     core::{pool::Handle, visitor::prelude::*},
-    utils::behavior::{BehaviorNode, BehaviorTree},
+    utils::behavior::{BehaviorNode, BehaviorTree, Status},
 };
 
 /// See module docs.
@@ -41,3 +41,13 @@ where
         tree.add_node(BehaviorNode::Inverter(self))
     }
 }
+
+/// Inverts the given status of a child node.
+pub(super) fn invert(status: Status) -> Status {
+    match status {
+        Status::Success => Status::Failure,
+        Status::Failure => Status::Success,
+        Status::Running => Status::Running,
+        Status::Error(error) => Status::Error(error),
+    }
+}
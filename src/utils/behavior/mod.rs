@@ -6,8 +6,30 @@
 //! games. The main concept is in its name. Tree is a set of connected nodes, where each node could
 //! have single parent and zero or more children nodes. Execution path of the tree is defined by the
 //! actions of the nodes. Behavior tree has a set of hard coded nodes as well as leaf nodes with
-//! user-defined logic. Hard coded nodes are: Sequence, Selector, Leaf. Leaf is special - it has
-//! custom method `tick` that can contain any logic you want.
+//! user-defined logic. Hard coded nodes are: Sequence, Selector, MemorySequence, MemorySelector,
+//! UtilitySelector, Parallel, Inverter, Decorator (Inverter, ForceSuccess, ForceFailure, Repeat,
+//! RepeatUntilFail, Cooldown, TimeLimit), SubTree, Leaf. Leaf is special - it has custom method
+//! `tick` that can contain any logic you want. SubTree embeds another behavior tree, which allows to
+//! reuse common branches in multiple trees.
+//!
+//! Trees could be created either node-by-node (see helper functions like [`sequence`], [`selector`],
+//! [`leaf`], etc.) or using [`builder::BehaviorTreeBuilder`].
+//!
+//! Execution path of a tree could be inspected using [`BehaviorTree::tick_traced`], which records every
+//! visited node and its status (see [`trace::TickTrace`]). The structure of a tree together with the
+//! statuses of a traced tick could be exported for visualization using [`BehaviorTree::export_graph`].
+//!
+//! Leaves could report that they're broken (for example, a required entity does not exist) by returning
+//! [`Status::Error`]. Unlike failures, errors are not handled by the tree: they stop execution of every
+//! node up to the root, so broken leaves do not silently look like normal failures. See [`Status`] docs
+//! for the exact propagation rules.
+//!
+//! Evaluation of a tree could be spread over multiple frames using [`BehaviorTree::tick_budgeted`], which
+//! suspends the tick when its budget is exhausted (see [`budget::TickBudget`]).
+//!
+//! Game code could react on the start and the end of a branch (to play an animation or a sound, for
+//! example) by observing status transitions of its node, see [`BehaviorTree::on_enter`],
+//! [`BehaviorTree::on_exit`] and [`BehaviorTree::on_status_change`].
 //!
 //! For more info see:
 //! - [Wikipedia article](https://en.wikipedia.org/wiki/Behavior_tree_(artificial_intelligence,_robotics_and_control))
@@ -15,25 +37,55 @@
 
 use crate::{
     core::{
-        pool::{Handle, Pool},
+        pool::{ErasedHandle, Handle, Pool},
         visitor::prelude::*,
     },
     utils::behavior::{
-        composite::{CompositeNode, CompositeNodeKind},
+        blackboard::Blackboard,
+        budget::{BudgetState, TickBudget},
+        composite::{CompositeNode, CompositeNodeKind, ParallelPolicy, Suspension},
+        decorator::DecoratorNode,
+        graph::BehaviorTreeGraph,
         inverter::Inverter,
         leaf::LeafNode,
+        observer::Observers,
+        subtree::SubTreeNode,
+        trace::TickTrace,
     },
 };
+use rayon::prelude::*;
 use std::{
-    fmt::Debug,
+    cell::{Cell, Ref, RefCell},
+    fmt::{Debug, Display, Formatter},
     ops::{Index, IndexMut},
 };
 
+pub mod blackboard;
+pub mod budget;
+pub mod builder;
 pub mod composite;
+pub mod decorator;
+pub mod graph;
 pub mod inverter;
 pub mod leaf;
+pub mod observer;
+pub mod subtree;
+pub mod trace;
 
 /// Status of execution of behavior tree node.
+///
+/// # Errors
+///
+/// [`Status::Error`] is propagated by the hard coded nodes as is, without any modifications:
+///
+/// - Sequences and selectors (including memory and utility ones) stop on the first error and return it,
+///   selectors do not try the next child, since an error is not a normal failure. Parallel nodes stop on the
+///   first error as well.
+/// - Inverters and decorators do not change errors, even `ForceSuccess` and `ForceFailure` ones. Decorators
+///   reset their state (the same way as if their branch was aborted).
+/// - Sub-trees return errors of their embedded trees, the failing node of such errors is the sub-tree node,
+///   because handles of embedded trees are meaningless for the host tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Status {
     /// Action was successful.
     Success,
@@ -41,6 +93,70 @@ pub enum Status {
     Failure,
     /// Need another iteration to perform an action.
     Running,
+    /// Action is broken and cannot be performed, see [`BehaviorError`].
+    Error(BehaviorError),
+}
+
+/// An error, that has occurred during execution of a leaf. Unlike [`Status::Failure`], which is a normal
+/// result of a node (for example, a condition is not met), an error means that the node is broken (for
+/// example, it refers to an entity, that does not exist).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BehaviorError {
+    node: ErasedHandle,
+    message: String,
+}
+
+impl BehaviorError {
+    /// Creates a new error with the given message. The failing node is set by the tree, when the error
+    /// is returned from a leaf.
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self {
+            node: ErasedHandle::none(),
+            message: message.into(),
+        }
+    }
+
+    /// Returns handle of the node, that has produced the error.
+    pub fn node<B>(&self) -> Handle<BehaviorNode<B>>
+    where
+        B: Clone,
+    {
+        self.node.into()
+    }
+
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for BehaviorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Behavior node {}:{} has failed with an error: {}",
+            self.node.index(),
+            self.node.generation(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for BehaviorError {}
+
+/// Standard context of a single update tick of a behavior tree, it is passed to [`BehaviorTree::tick`]
+/// alongside the user-defined context.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TickContext {
+    /// Amount of time (in seconds) that passed since the previous tick.
+    pub dt: f32,
+}
+
+impl TickContext {
+    /// Creates a new tick context with the given time delta.
+    pub fn new(dt: f32) -> Self {
+        Self { dt }
+    }
 }
 
 /// A trait for user-defined actions for behavior tree.
@@ -50,8 +166,20 @@ pub trait Behavior<'a>: Visit + Default + PartialEq + Debug + Clone {
 
     /// A function that will be called each frame depending on
     /// the current execution path of the behavior tree it belongs
-    /// to.
-    fn tick(&mut self, context: &mut Self::Context) -> Status;
+    /// to. Blackboard of the tree could be used to share data between
+    /// behaviors.
+    fn tick(&mut self, context: &mut Self::Context, blackboard: &mut Blackboard) -> Status;
+
+    /// Returns a score of the behavior, that is used by `UtilitySelector` composite nodes to select the
+    /// most useful child (see [`CompositeNodeKind::UtilitySelector`]). Higher score means more useful
+    /// behavior, default score is zero.
+    fn score(
+        &self,
+        #[allow(unused_variables)] context: &Self::Context,
+        #[allow(unused_variables)] blackboard: &Blackboard,
+    ) -> f32 {
+        0.0
+    }
 }
 
 /// Root node of the tree.
@@ -91,6 +219,10 @@ where
     /// A node, that inverts its child state ([`Status::Failure`] becomes [`Status::Success`] and vice versa, [`Status::Running`] remains
     /// unchanged)
     Inverter(Inverter<B>),
+    /// A node, that modifies its child status. See [`DecoratorNodeKind`] docs for more info.
+    Decorator(DecoratorNode<B>),
+    /// A node, that embeds another behavior tree. See [`SubTreeNode`] docs for more info.
+    SubTree(SubTreeNode<B>),
 }
 
 impl<B> Default for BehaviorNode<B>
@@ -102,6 +234,62 @@ where
     }
 }
 
+impl<B> BehaviorNode<B>
+where
+    B: Clone,
+{
+    /// Returns handles of the direct children of the node.
+    pub fn children(&self) -> Vec<Handle<BehaviorNode<B>>> {
+        let child = match self {
+            BehaviorNode::Composite(composite) => return composite.children.clone(),
+            BehaviorNode::Root(root) => root.child,
+            BehaviorNode::Inverter(inverter) => inverter.child,
+            BehaviorNode::Decorator(decorator) => decorator.child,
+            BehaviorNode::Leaf(_) | BehaviorNode::SubTree(_) | BehaviorNode::Unknown => {
+                Handle::NONE
+            }
+        };
+        if child.is_some() {
+            vec![child]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// An error that may occur during editing of a behavior tree.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BehaviorTreeEditError {
+    /// A handle does not point to a node of the tree.
+    InvalidHandle,
+    /// The root node of the tree cannot be removed or moved.
+    RootNode,
+    /// A node cannot have children (leaves and sub-trees).
+    NotAContainer,
+    /// A node (a decorator, an inverter or the root) can have only one child and it already has one.
+    ChildAlreadySet,
+    /// A node cannot be moved into its own branch.
+    CyclicHierarchy,
+}
+
+impl Display for BehaviorTreeEditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BehaviorTreeEditError::InvalidHandle => write!(f, "The handle is invalid"),
+            BehaviorTreeEditError::RootNode => {
+                write!(f, "The root node cannot be removed or moved")
+            }
+            BehaviorTreeEditError::NotAContainer => write!(f, "The node cannot have children"),
+            BehaviorTreeEditError::ChildAlreadySet => {
+                write!(f, "The node can have only one child and it already has one")
+            }
+            BehaviorTreeEditError::CyclicHierarchy => {
+                write!(f, "A node cannot be moved into its own branch")
+            }
+        }
+    }
+}
+
 /// See module docs.
 #[derive(Debug, PartialEq, Visit, Clone)]
 pub struct BehaviorTree<B>
@@ -110,6 +298,16 @@ where
 {
     nodes: Pool<BehaviorNode<B>>,
     root: Handle<BehaviorNode<B>>,
+    #[visit(optional)]
+    blackboard: RefCell<Blackboard>,
+    #[visit(optional)]
+    time: Cell<f32>,
+    #[visit(skip)]
+    trace: RefCell<Option<TickTrace<B>>>,
+    #[visit(skip)]
+    budget: Cell<Option<BudgetState>>,
+    #[visit(skip)]
+    observers: RefCell<Observers<B>>,
 }
 
 impl<B> Default for BehaviorTree<B>
@@ -120,6 +318,11 @@ where
         Self {
             nodes: Default::default(),
             root: Default::default(),
+            blackboard: Default::default(),
+            time: Default::default(),
+            trace: Default::default(),
+            budget: Default::default(),
+            observers: Default::default(),
         }
     }
 }
@@ -134,7 +337,15 @@ where
         let root = nodes.spawn(BehaviorNode::Root(RootNode {
             child: Default::default(),
         }));
-        Self { nodes, root }
+        Self {
+            nodes,
+            root,
+            blackboard: Default::default(),
+            time: Default::default(),
+            trace: Default::default(),
+            budget: Default::default(),
+            observers: Default::default(),
+        }
     }
 
     /// Adds a node to the tree, returns its handle.
@@ -151,66 +362,572 @@ where
         }
     }
 
-    fn tick_recursive<'a, Ctx>(&self, handle: Handle<BehaviorNode<B>>, context: &mut Ctx) -> Status
+    fn tick_recursive<'a, Ctx>(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        context: &mut Ctx,
+        tick_context: &TickContext,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        if !self.consume_budget(handle) {
+            return Status::Running;
+        }
+
+        let observed = !self.observers.borrow().is_empty();
+        if observed {
+            self.observers.borrow_mut().enter(handle, &self.blackboard);
+        }
+        let entry = self
+            .trace
+            .borrow_mut()
+            .as_mut()
+            .map(|trace| trace.enter(handle));
+        let mut status = self.tick_node(handle, context, tick_context);
+        if let Status::Error(ref mut error) = status {
+            if error.node.is_none() {
+                error.node = handle.into();
+            }
+        }
+        if let (Some(trace), Some(entry)) = (self.trace.borrow_mut().as_mut(), entry) {
+            trace.leave(entry, status.clone());
+        }
+        if observed {
+            self.observers
+                .borrow_mut()
+                .leave(handle, &status, &self.blackboard);
+        }
+        status
+    }
+
+    fn tick_node<'a, Ctx>(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        context: &mut Ctx,
+        tick_context: &TickContext,
+    ) -> Status
     where
         B: Behavior<'a, Context = Ctx>,
     {
         match self.nodes[handle] {
             BehaviorNode::Root(ref root) => {
                 if root.child.is_some() {
-                    self.tick_recursive(root.child, context)
+                    self.tick_recursive(root.child, context, tick_context)
                 } else {
                     Status::Success
                 }
             }
             BehaviorNode::Composite(ref composite) => match composite.kind {
                 CompositeNodeKind::Sequence => {
-                    let mut all_succeeded = true;
-                    for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
-                            Status::Failure => {
-                                all_succeeded = false;
-                                break;
-                            }
-                            Status::Running => {
+                    self.tick_resumable(composite, false, context, tick_context)
+                }
+                CompositeNodeKind::Selector => {
+                    self.tick_resumable(composite, true, context, tick_context)
+                }
+                CompositeNodeKind::Parallel {
+                    success_policy,
+                    failure_policy,
+                } => {
+                    let resume = composite.take_suspension().unwrap_or_default();
+                    let mut successes = resume.successes;
+                    let mut failures = resume.failures;
+                    for (index, child) in composite.children.iter().enumerate().skip(resume.child) {
+                        match self.tick_recursive(*child, context, tick_context) {
+                            Status::Success => successes += 1,
+                            Status::Failure => failures += 1,
+                            Status::Error(error) => return Status::Error(error),
+                            Status::Running if self.is_suspended() => {
+                                composite.suspend(Suspension {
+                                    child: index,
+                                    successes,
+                                    failures,
+                                });
                                 return Status::Running;
                             }
-                            _ => (),
+                            Status::Running => (),
                         }
                     }
-                    if all_succeeded {
-                        Status::Success
-                    } else {
-                        Status::Failure
-                    }
+                    composite::resolve_parallel_status(
+                        success_policy,
+                        failure_policy,
+                        successes,
+                        failures,
+                        composite.children.len(),
+                    )
                 }
-                CompositeNodeKind::Selector => {
-                    for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
-                            Status::Success => return Status::Success,
-                            Status::Running => return Status::Running,
-                            _ => (),
-                        }
-                    }
-                    Status::Failure
+                CompositeNodeKind::MemorySequence => {
+                    let start = self.resolve_aborts(composite, context, tick_context);
+                    self.tick_children(composite, start, false, context, tick_context)
+                }
+                CompositeNodeKind::MemorySelector => {
+                    let start = self.resolve_aborts(composite, context, tick_context);
+                    self.tick_children(composite, start, true, context, tick_context)
+                }
+                CompositeNodeKind::UtilitySelector => {
+                    self.tick_best_child(composite, context, tick_context)
                 }
             },
-            BehaviorNode::Leaf(ref leaf) => {
-                leaf.behavior.as_ref().unwrap().borrow_mut().tick(context)
-            }
+            BehaviorNode::Leaf(ref leaf) => leaf
+                .behavior
+                .as_ref()
+                .unwrap()
+                .borrow_mut()
+                .tick(context, &mut self.blackboard.borrow_mut()),
             BehaviorNode::Inverter(ref inverter) => {
-                match self.tick_recursive(inverter.child, context) {
-                    Status::Success => Status::Failure,
-                    Status::Failure => Status::Success,
-                    Status::Running => Status::Running,
+                inverter::invert(self.tick_recursive(inverter.child, context, tick_context))
+            }
+            BehaviorNode::Decorator(ref decorator) => {
+                let time = self.time.get();
+                match decorator.check(time) {
+                    Some(status) => status,
+                    None => decorator.apply(
+                        self.tick_recursive(decorator.child, context, tick_context),
+                        time,
+                    ),
                 }
             }
+            BehaviorNode::SubTree(ref sub_tree) => match sub_tree.tree {
+                Some(ref tree) => match tree.tick_embedded(self, context, tick_context) {
+                    // The error will be attributed to the sub-tree node of this tree.
+                    Status::Error(error) => Status::Error(BehaviorError {
+                        node: ErasedHandle::none(),
+                        ..error
+                    }),
+                    status => status,
+                },
+                None => Status::Success,
+            },
             BehaviorNode::Unknown => {
                 unreachable!()
             }
         }
     }
 
+    // Ticks the tree as a part of the given host tree. Embedded trees use the blackboard and the time of
+    // the host tree, the blackboards are swapped back and forth to avoid copying.
+    fn tick_embedded<'a, Ctx>(
+        &self,
+        host: &Self,
+        context: &mut Ctx,
+        tick_context: &TickContext,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        self.time.set(host.time.get());
+        self.budget.set(host.budget.get());
+        self.blackboard.swap(&host.blackboard);
+        let status = self.tick_root(context, tick_context);
+        self.blackboard.swap(&host.blackboard);
+        host.budget.set(self.budget.take());
+        status
+    }
+
+    // Checks the budget of the current tick (if any) before ticking the given node. Returns `false` if
+    // the tick is suspended, suspended ticks do not execute any nodes.
+    fn consume_budget(&self, handle: Handle<BehaviorNode<B>>) -> bool {
+        match self.budget.get() {
+            Some(mut budget) => {
+                let proceed = match self.nodes[handle] {
+                    BehaviorNode::Leaf(_) => budget.consume_leaf(),
+                    _ => !budget.is_suspended(),
+                };
+                self.budget.set(Some(budget));
+                proceed
+            }
+            None => true,
+        }
+    }
+
+    fn is_suspended(&self) -> bool {
+        self.budget
+            .get()
+            .map_or(false, |budget| budget.is_suspended())
+    }
+
+    // Ticks children of a sequence or a selector. Continues from the child, that was interrupted by the
+    // budget of the previous tick (if any), and remembers the interrupted child if the budget of this
+    // tick is exhausted.
+    fn tick_resumable<'a, Ctx>(
+        &self,
+        composite: &CompositeNode<B>,
+        is_selector: bool,
+        context: &mut Ctx,
+        tick_context: &TickContext,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let start = composite
+            .take_suspension()
+            .map_or(0, |suspension| suspension.child);
+        let status = self.tick_children(composite, start, is_selector, context, tick_context);
+        if status == Status::Running && self.is_suspended() {
+            composite.suspend(Suspension {
+                child: composite.running_child(),
+                ..Default::default()
+            });
+        }
+        status
+    }
+
+    // Ticks children of a sequence or a selector one-by-one starting from the given index. Selectors
+    // stop on the first successful child, sequences - on the first failed one.
+    fn tick_children<'a, Ctx>(
+        &self,
+        composite: &CompositeNode<B>,
+        start: usize,
+        is_selector: bool,
+        context: &mut Ctx,
+        tick_context: &TickContext,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        for (index, child) in composite.children.iter().enumerate().skip(start) {
+            let status = self.tick_recursive(*child, context, tick_context);
+            if index == 0 {
+                composite.observe_condition(&status);
+            }
+            match (&status, is_selector) {
+                (Status::Running, _) => {
+                    composite.set_running_child(index);
+                    return Status::Running;
+                }
+                (Status::Error(_), _) => {
+                    composite.reset();
+                    return status;
+                }
+                (Status::Success, true) => {
+                    composite.reset();
+                    return Status::Success;
+                }
+                (Status::Failure, false) => {
+                    composite.reset();
+                    return Status::Failure;
+                }
+                _ => (),
+            }
+        }
+        composite.reset();
+        if is_selector {
+            Status::Failure
+        } else {
+            Status::Success
+        }
+    }
+
+    // Ticks the child of a utility selector with the highest score. The branch of the previously selected
+    // child is aborted when the selection changes. The selection is not changed when the node continues
+    // a suspended tick.
+    fn tick_best_child<'a, Ctx>(
+        &self,
+        composite: &CompositeNode<B>,
+        context: &mut Ctx,
+        tick_context: &TickContext,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        if let Some(suspension) = composite.take_suspension() {
+            return self.tick_selected_child(composite, suspension.child, context, tick_context);
+        }
+
+        let best = {
+            let blackboard = self.blackboard.borrow();
+            let mut best: Option<(usize, f32)> = None;
+            for (index, child) in composite.children.iter().enumerate() {
+                let score = self.score(*child, context, &blackboard);
+                if best.map_or(true, |(_, best_score)| score > best_score) {
+                    best = Some((index, score));
+                }
+            }
+            best
+        };
+
+        let index = match best {
+            Some((index, _)) => index,
+            None => return Status::Failure,
+        };
+        let previous = composite.running_child();
+        if previous != index {
+            if let Some(previous) = composite.children.get(previous) {
+                self.abort_branch(*previous);
+            }
+        }
+        composite.set_running_child(index);
+        self.tick_selected_child(composite, index, context, tick_context)
+    }
+
+    fn tick_selected_child<'a, Ctx>(
+        &self,
+        composite: &CompositeNode<B>,
+        index: usize,
+        context: &mut Ctx,
+        tick_context: &TickContext,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let status = self.tick_recursive(composite.children[index], context, tick_context);
+        if status == Status::Running && self.is_suspended() {
+            composite.suspend(Suspension {
+                child: index,
+                ..Default::default()
+            });
+        }
+        status
+    }
+
+    // Calculates the score of a node for utility selectors. Only leaves have their own scores, any other
+    // node is scored by its first child.
+    fn score<'a, Ctx>(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        context: &Ctx,
+        blackboard: &Blackboard,
+    ) -> f32
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        match self.nodes[handle] {
+            BehaviorNode::Leaf(ref leaf) => leaf
+                .behavior
+                .as_ref()
+                .map_or(0.0, |behavior| behavior.borrow().score(context, blackboard)),
+            BehaviorNode::SubTree(ref sub_tree) => sub_tree
+                .tree
+                .as_ref()
+                .map_or(0.0, |tree| tree.score(tree.root, context, blackboard)),
+            ref node => node
+                .children()
+                .first()
+                .map_or(0.0, |child| self.score(*child, context, blackboard)),
+        }
+    }
+
+    // Re-evaluates the conditions, that are observed by the running branch of the given memory composite,
+    // aborts the branch if any of them has changed and returns the index of the child to continue from.
+    fn resolve_aborts<'a, Ctx>(
+        &self,
+        composite: &CompositeNode<B>,
+        context: &mut Ctx,
+        tick_context: &TickContext,
+    ) -> usize
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        let running = composite.running_child();
+        let branch = match composite.children.get(running) {
+            Some(branch) if running > 0 => *branch,
+            _ => return 0,
+        };
+
+        if composite.abort_policy.aborts_self()
+            && self.condition_changed(composite, context, tick_context)
+        {
+            self.abort_branch(branch);
+            composite.reset();
+            return 0;
+        }
+
+        for (index, child) in composite.children[..running].iter().enumerate() {
+            if let BehaviorNode::Composite(ref sibling) = self.nodes[*child] {
+                if sibling.abort_policy.aborts_lower_priority()
+                    && self.condition_changed(sibling, context, tick_context)
+                {
+                    self.abort_branch(branch);
+                    composite.set_running_child(index);
+                    return index;
+                }
+            }
+        }
+
+        running
+    }
+
+    fn condition_changed<'a, Ctx>(
+        &self,
+        composite: &CompositeNode<B>,
+        context: &mut Ctx,
+        tick_context: &TickContext,
+    ) -> bool
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        match composite.children.first() {
+            Some(condition) => {
+                let status = self.tick_recursive(*condition, context, tick_context);
+                composite.observe_condition(&status)
+            }
+            None => false,
+        }
+    }
+
+    // Resets the state of every node in the branch, so the next execution of the branch will start from
+    // scratch.
+    fn abort_branch(&self, handle: Handle<BehaviorNode<B>>) {
+        self.observers.borrow_mut().abort(handle, &self.blackboard);
+        match self.nodes[handle] {
+            BehaviorNode::Composite(ref composite) => {
+                composite.reset();
+                for child in composite.children.iter() {
+                    self.abort_branch(*child);
+                }
+            }
+            BehaviorNode::Decorator(ref decorator) => {
+                decorator.abort();
+                self.abort_branch(decorator.child);
+            }
+            BehaviorNode::Inverter(ref inverter) => self.abort_branch(inverter.child),
+            BehaviorNode::Root(ref root) => {
+                if root.child.is_some() {
+                    self.abort_branch(root.child);
+                }
+            }
+            BehaviorNode::SubTree(ref sub_tree) => {
+                if let Some(ref tree) = sub_tree.tree {
+                    tree.abort_branch(tree.root);
+                }
+            }
+            BehaviorNode::Leaf(_) | BehaviorNode::Unknown => (),
+        }
+    }
+
+    /// Returns handles of the direct children of the given node. See [`BehaviorNode::children`].
+    pub fn children_of(&self, handle: Handle<BehaviorNode<B>>) -> Vec<Handle<BehaviorNode<B>>> {
+        self.nodes
+            .try_borrow(handle)
+            .map(|node| node.children())
+            .unwrap_or_default()
+    }
+
+    /// Returns a handle of the parent of the given node, [`Handle::NONE`] means that the node has no
+    /// parent. Nodes do not store their parents, so this method iterates over all nodes of the tree.
+    pub fn parent_of(&self, handle: Handle<BehaviorNode<B>>) -> Handle<BehaviorNode<B>> {
+        self.nodes
+            .pair_iter()
+            .find(|(_, node)| node.children().contains(&handle))
+            .map(|(parent, _)| parent)
+            .unwrap_or_default()
+    }
+
+    fn collect_branch(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        branch: &mut Vec<Handle<BehaviorNode<B>>>,
+    ) {
+        branch.push(handle);
+        for child in self.children_of(handle) {
+            self.collect_branch(child, branch);
+        }
+    }
+
+    fn check_editable(&self, handle: Handle<BehaviorNode<B>>) -> Result<(), BehaviorTreeEditError> {
+        if !self.nodes.is_valid_handle(handle) {
+            Err(BehaviorTreeEditError::InvalidHandle)
+        } else if handle == self.root {
+            Err(BehaviorTreeEditError::RootNode)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Removes every reference to the given node from its parent(s).
+    fn detach(&mut self, handle: Handle<BehaviorNode<B>>) {
+        for node in self.nodes.iter_mut() {
+            match node {
+                BehaviorNode::Root(root) if root.child == handle => root.child = Handle::NONE,
+                BehaviorNode::Composite(composite) if composite.children.contains(&handle) => {
+                    composite.children.retain(|child| *child != handle);
+                    // Indices of the children have changed, so the running child is no longer valid.
+                    composite.reset();
+                }
+                BehaviorNode::Inverter(inverter) if inverter.child == handle => {
+                    inverter.child = Handle::NONE
+                }
+                BehaviorNode::Decorator(decorator) if decorator.child == handle => {
+                    decorator.child = Handle::NONE
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Removes the node with all its descendants from the tree and disconnects it from its parent.
+    /// Decorators and inverters, that have lost their child, must get a new one (see
+    /// [`Self::reparent_node`]) before the next tick.
+    pub fn remove_node(
+        &mut self,
+        handle: Handle<BehaviorNode<B>>,
+    ) -> Result<(), BehaviorTreeEditError> {
+        self.check_editable(handle)?;
+
+        let mut branch = Vec::new();
+        self.collect_branch(handle, &mut branch);
+        self.detach(handle);
+        for node in branch {
+            // A node could be referenced more than once in hand-made trees.
+            self.nodes.try_free(node);
+            self.observers.get_mut().remove(node);
+        }
+
+        Ok(())
+    }
+
+    /// Detaches the node from its current parent (if any) and attaches it to the new parent. The node is
+    /// added to the end of the children list of a composite node, other kinds of nodes (decorators,
+    /// inverters and the root) must not have a child. Execution state of the moved branch is reset.
+    ///
+    /// If the operation is not possible for more than one reason, the errors are reported in the following
+    /// order: invalid handles, [`BehaviorTreeEditError::NotAContainer`],
+    /// [`BehaviorTreeEditError::CyclicHierarchy`], [`BehaviorTreeEditError::ChildAlreadySet`].
+    pub fn reparent_node(
+        &mut self,
+        handle: Handle<BehaviorNode<B>>,
+        new_parent: Handle<BehaviorNode<B>>,
+    ) -> Result<(), BehaviorTreeEditError> {
+        self.check_editable(handle)?;
+        if !self.nodes.is_valid_handle(new_parent) {
+            return Err(BehaviorTreeEditError::InvalidHandle);
+        }
+
+        let current_child = match self.nodes[new_parent] {
+            BehaviorNode::Composite(_) => Handle::NONE,
+            BehaviorNode::Root(ref root) => root.child,
+            BehaviorNode::Inverter(ref inverter) => inverter.child,
+            BehaviorNode::Decorator(ref decorator) => decorator.child,
+            BehaviorNode::Leaf(_) | BehaviorNode::SubTree(_) | BehaviorNode::Unknown => {
+                return Err(BehaviorTreeEditError::NotAContainer)
+            }
+        };
+
+        let mut branch = Vec::new();
+        self.collect_branch(handle, &mut branch);
+        if branch.contains(&new_parent) {
+            return Err(BehaviorTreeEditError::CyclicHierarchy);
+        }
+
+        if current_child.is_some() && current_child != handle {
+            return Err(BehaviorTreeEditError::ChildAlreadySet);
+        }
+
+        self.abort_branch(handle);
+        self.detach(handle);
+        match self.nodes[new_parent] {
+            BehaviorNode::Composite(ref mut composite) => composite.children.push(handle),
+            BehaviorNode::Root(ref mut root) => root.child = handle,
+            BehaviorNode::Inverter(ref mut inverter) => inverter.child = handle,
+            BehaviorNode::Decorator(ref mut decorator) => decorator.child = handle,
+            BehaviorNode::Leaf(_) | BehaviorNode::SubTree(_) | BehaviorNode::Unknown => {
+                unreachable!()
+            }
+        }
+
+        Ok(())
+    }
+
     /// Tries to get a shared reference to a node by given handle.
     pub fn node(&self, handle: Handle<BehaviorNode<B>>) -> Option<&BehaviorNode<B>> {
         self.nodes.try_borrow(handle)
@@ -221,12 +938,142 @@ where
         self.nodes.try_borrow_mut(handle)
     }
 
+    /// Returns a reference to the blackboard of the tree.
+    pub fn blackboard(&self) -> Ref<'_, Blackboard> {
+        self.blackboard.borrow()
+    }
+
+    /// Returns a mutable reference to the blackboard of the tree.
+    pub fn blackboard_mut(&mut self) -> &mut Blackboard {
+        self.blackboard.get_mut()
+    }
+
+    /// Returns time of the tree (in seconds), it is a sum of all time deltas passed to [`Self::tick`].
+    pub fn time(&self) -> f32 {
+        self.time.get()
+    }
+
     /// Performs a single update tick with given context.
-    pub fn tick<'a, Ctx>(&self, context: &mut Ctx) -> Status
+    pub fn tick<'a, Ctx>(&self, context: &mut Ctx, tick_context: TickContext) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        self.time.set(self.time.get() + tick_context.dt);
+        self.tick_root(context, &tick_context)
+    }
+
+    fn tick_root<'a, Ctx>(&self, context: &mut Ctx, tick_context: &TickContext) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        if self.observers.borrow().is_empty() {
+            return self.tick_recursive(self.root, context, tick_context);
+        }
+
+        self.observers.borrow_mut().begin_tick();
+        let status = self.tick_recursive(self.root, context, tick_context);
+        self.observers
+            .borrow_mut()
+            .end_tick(!self.is_suspended(), &self.blackboard);
+        status
+    }
+
+    /// Adds a callback, that will be called when the given node is entered - when it is ticked while it
+    /// is not running (for the first time or after it has finished). It could be used to start an
+    /// animation or a sound of a branch without modifying the behaviors of its leaves. The callback
+    /// receives the blackboard of the tree, other data (the game context, for example) could be passed to
+    /// the callback using a channel or a shared storage.
+    ///
+    /// Observers are runtime-only: they're not serialized and they're not copied when the tree is cloned.
+    pub fn on_enter<F>(&mut self, handle: Handle<BehaviorNode<B>>, callback: F)
+    where
+        F: FnMut(&mut Blackboard) + Send + 'static,
+    {
+        self.observers
+            .get_mut()
+            .add_enter(handle, Box::new(callback));
+    }
+
+    /// Adds a callback, that will be called when the given node is exited - when it returns any status,
+    /// except [`Status::Running`], or when it is aborted while running. The callback receives the status
+    /// of the node, `None` means that the node was aborted (by an abort policy of its parent composite,
+    /// by a utility selector, which has selected another child, or by a reactive sequence or selector,
+    /// which has stopped ticking the node). See [`Self::on_enter`] for more info.
+    pub fn on_exit<F>(&mut self, handle: Handle<BehaviorNode<B>>, callback: F)
+    where
+        F: FnMut(Option<&Status>, &mut Blackboard) + Send + 'static,
+    {
+        self.observers
+            .get_mut()
+            .add_exit(handle, Box::new(callback));
+    }
+
+    /// Adds a callback, that will be called when the given node returns a status, that differs from the
+    /// status it has returned previously. The callback receives the previous status (`None` if the node has
+    /// never returned a status or if it was aborted) and the new one. See [`Self::on_enter`] for more info.
+    pub fn on_status_change<F>(&mut self, handle: Handle<BehaviorNode<B>>, callback: F)
+    where
+        F: FnMut(Option<&Status>, &Status, &mut Blackboard) + Send + 'static,
+    {
+        self.observers
+            .get_mut()
+            .add_status_change(handle, Box::new(callback));
+    }
+
+    /// Removes every observer of the given node.
+    pub fn remove_observers(&mut self, handle: Handle<BehaviorNode<B>>) {
+        self.observers.get_mut().remove(handle);
+    }
+
+    /// Performs a single update tick with given context within the given budget. If the budget is
+    /// exhausted, the tick is suspended and [`Status::Running`] is returned, the next tick (either
+    /// budgeted or not) continues from the same node. It allows to spread evaluation of large trees (or of
+    /// many trees of a crowd of agents) over multiple frames. See [`TickBudget`] docs for more info.
+    ///
+    /// Keep in mind, that the time of the tree advances on every call, so time-based decorators measure
+    /// real time even if the tick is spread over multiple frames.
+    pub fn tick_budgeted<'a, Ctx>(
+        &self,
+        context: &mut Ctx,
+        tick_context: TickContext,
+        budget: TickBudget,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        self.budget.set(Some(BudgetState::new(budget)));
+        let status = self.tick(context, tick_context);
+        self.budget.set(None);
+        status
+    }
+
+    /// Performs a single update tick with given context and records every visited node with the status
+    /// it returned. It is slower than [`Self::tick`], so it should be used only for debugging. See
+    /// [`TickTrace`] docs for more info.
+    pub fn tick_traced<'a, Ctx>(
+        &self,
+        context: &mut Ctx,
+        tick_context: TickContext,
+    ) -> (Status, TickTrace<B>)
     where
         B: Behavior<'a, Context = Ctx>,
     {
-        self.tick_recursive(self.root, context)
+        self.trace.replace(Some(TickTrace::default()));
+        let status = self.tick(context, tick_context);
+        (status, self.trace.take().unwrap_or_default())
+    }
+
+    /// Exports the structure of the tree into a serializable graph description, that could be rendered in
+    /// the editor or in a debug overlay. Nodes are colored according to their statuses in the given trace
+    /// (see [`Self::tick_traced`]), all nodes are shown as not visited if there's no trace. See
+    /// [`BehaviorTreeGraph`] docs for more info.
+    pub fn export_graph(&self, trace: Option<&TickTrace<B>>) -> BehaviorTreeGraph
+    where
+        B: Debug,
+    {
+        let mut graph = BehaviorTreeGraph::default();
+        graph::export_tree(self, trace, false, &mut graph);
+        graph
     }
 }
 
@@ -244,6 +1091,23 @@ impl<B: Clone + 'static> IndexMut<Handle<BehaviorNode<B>>> for BehaviorTree<B> {
     }
 }
 
+/// Ticks behavior trees of multiple independent agents in parallel. Every agent owns its tree and its
+/// context, so the trees never touch the same data. Resulting statuses are returned in the same order as
+/// the agents, so they could be applied to the game state afterwards on a single thread.
+pub fn tick_parallel<'a, B, Ctx>(
+    agents: &mut [(&mut BehaviorTree<B>, &mut Ctx)],
+    tick_context: TickContext,
+) -> Vec<Status>
+where
+    B: Behavior<'a, Context = Ctx> + Send + 'static,
+    Ctx: Send,
+{
+    agents
+        .par_iter_mut()
+        .map(|(tree, context)| tree.tick(*context, tick_context))
+        .collect()
+}
+
 /// Creates a new sequence.
 pub fn sequence<B, const N: usize>(
     children: [Handle<BehaviorNode<B>>; N],
@@ -266,14 +1130,68 @@ where
     CompositeNode::new_selector(children.to_vec()).add_to(tree)
 }
 
-/// Creates a new leaf.
-pub fn leaf<B>(behavior: B, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>>
+/// Creates a new sequence, that continues execution from its running child on the next tick.
+pub fn memory_sequence<B, const N: usize>(
+    children: [Handle<BehaviorNode<B>>; N],
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    CompositeNode::new_memory_sequence(children.to_vec()).add_to(tree)
+}
+
+/// Creates a new selector, that continues execution from its running child on the next tick.
+pub fn memory_selector<B, const N: usize>(
+    children: [Handle<BehaviorNode<B>>; N],
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    CompositeNode::new_memory_selector(children.to_vec()).add_to(tree)
+}
+
+/// Creates a new selector, that executes only its child with the highest score.
+pub fn utility_selector<B, const N: usize>(
+    children: [Handle<BehaviorNode<B>>; N],
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    CompositeNode::new_utility_selector(children.to_vec()).add_to(tree)
+}
+
+/// Creates a new parallel node with the given success and failure policies.
+pub fn parallel<B, const N: usize>(
+    success_policy: ParallelPolicy,
+    failure_policy: ParallelPolicy,
+    children: [Handle<BehaviorNode<B>>; N],
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    CompositeNode::new_parallel(success_policy, failure_policy, children.to_vec()).add_to(tree)
+}
+
+/// Creates a new leaf.
+pub fn leaf<B>(behavior: B, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>>
 where
     B: Clone + 'static,
 {
     LeafNode::new(behavior).add_to(tree)
 }
 
+/// Creates a new node, that embeds the given tree.
+pub fn sub_tree<B>(sub_tree: BehaviorTree<B>, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    SubTreeNode::new(sub_tree).add_to(tree)
+}
+
 /// Creates a new inverter.
 pub fn inverter<B>(
     child: Handle<BehaviorNode<B>>,
@@ -285,17 +1203,105 @@ where
     Inverter::new(child).add_to(tree)
 }
 
+/// Creates a new decorator, that always succeeds when its child is finished.
+pub fn succeeder<B>(
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    DecoratorNode::new_force_success(child).add_to(tree)
+}
+
+/// Creates a new decorator, that executes its child the given amount of times (`None` - infinitely).
+pub fn repeater<B>(
+    count: Option<u32>,
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    DecoratorNode::new_repeat(count, child).add_to(tree)
+}
+
+/// Creates a new decorator, that executes its child until it fails.
+pub fn repeat_until_fail<B>(
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    DecoratorNode::new_repeat_until_fail(child).add_to(tree)
+}
+
+/// Creates a new decorator, that can't succeed more often than once per the given amount of seconds.
+pub fn cooldown<B>(
+    duration: f32,
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    DecoratorNode::new_cooldown(duration, child).add_to(tree)
+}
+
+/// Creates a new decorator, that fails when its child is running longer than the given amount of seconds.
+pub fn time_limit<B>(
+    duration: f32,
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    DecoratorNode::new_time_limit(duration, child).add_to(tree)
+}
+
+/// Creates a new decorator, that always fails when its child is finished.
+pub fn failer<B>(
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    DecoratorNode::new_force_failure(child).add_to(tree)
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
-        core::{futures::executor::block_on, visitor::prelude::*},
+        core::{futures::executor::block_on, pool::Handle, visitor::prelude::*},
         utils::behavior::{
-            composite::{CompositeNode, CompositeNodeKind},
+            blackboard::Blackboard,
+            budget::TickBudget,
+            builder::{BehaviorTreeBuilder, BehaviorTreeBuilderError},
+            composite::{AbortPolicy, CompositeNode, CompositeNodeKind, ParallelPolicy},
+            cooldown,
+            decorator::{DecoratorNode, DecoratorNodeKind},
+            failer,
+            graph::{BehaviorTreeGraph, GraphNodeKind, GraphNodeStatus},
+            inverter, leaf,
             leaf::LeafNode,
-            Behavior, BehaviorTree, Status,
+            memory_selector, memory_sequence, parallel, repeat_until_fail, repeater, selector,
+            sequence, sub_tree, succeeder, tick_parallel, time_limit, utility_selector, Behavior,
+            BehaviorError, BehaviorNode, BehaviorTree, BehaviorTreeEditError, Status, TickContext,
         },
     };
-    use std::{env, fs::File, io::Write, path::PathBuf};
+    use std::{
+        env,
+        fs::File,
+        io::Write,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    const TICK: TickContext = TickContext { dt: 1.0 / 60.0 };
 
     #[derive(Debug, PartialEq, Default, Visit, Clone)]
     struct WalkAction;
@@ -303,7 +1309,7 @@ mod test {
     impl<'a> Behavior<'a> for WalkAction {
         type Context = Environment;
 
-        fn tick(&mut self, context: &mut Self::Context) -> Status {
+        fn tick(&mut self, context: &mut Self::Context, _blackboard: &mut Blackboard) -> Status {
             if context.distance_to_door <= 0.0 {
                 Status::Success
             } else {
@@ -323,9 +1329,10 @@ mod test {
     impl<'a> Behavior<'a> for OpenDoorAction {
         type Context = Environment;
 
-        fn tick(&mut self, context: &mut Self::Context) -> Status {
+        fn tick(&mut self, context: &mut Self::Context, blackboard: &mut Blackboard) -> Status {
             if !context.door_opened {
                 context.door_opened = true;
+                blackboard.set("DoorOpened", true);
                 println!("Door was opened!");
             }
             Status::Success
@@ -338,7 +1345,7 @@ mod test {
     impl<'a> Behavior<'a> for StepThroughAction {
         type Context = Environment;
 
-        fn tick(&mut self, context: &mut Self::Context) -> Status {
+        fn tick(&mut self, context: &mut Self::Context, _blackboard: &mut Blackboard) -> Status {
             if context.distance_to_door < -1.0 {
                 Status::Success
             } else {
@@ -358,16 +1365,79 @@ mod test {
     impl<'a> Behavior<'a> for CloseDoorAction {
         type Context = Environment;
 
-        fn tick(&mut self, context: &mut Self::Context) -> Status {
-            if context.door_opened {
+        fn tick(&mut self, context: &mut Self::Context, blackboard: &mut Blackboard) -> Status {
+            if blackboard.get::<bool>("DoorOpened") == Some(true) {
                 context.door_opened = false;
                 context.done = true;
+                blackboard.set("DoorOpened", false);
                 println!("Door was closed");
             }
             Status::Success
         }
     }
 
+    #[derive(Debug, PartialEq, Default, Visit, Clone)]
+    struct CountTicksAction;
+
+    impl<'a> Behavior<'a> for CountTicksAction {
+        type Context = Environment;
+
+        fn tick(&mut self, _context: &mut Self::Context, blackboard: &mut Blackboard) -> Status {
+            let ticks = blackboard.get::<i64>("Ticks").unwrap_or_default();
+            blackboard.set("Ticks", ticks + 1);
+            Status::Success
+        }
+    }
+
+    // Takes its score from the blackboard and counts its ticks in the blackboard.
+    #[derive(Debug, PartialEq, Default, Visit, Clone)]
+    struct ScoredAction {
+        name: String,
+    }
+
+    impl<'a> Behavior<'a> for ScoredAction {
+        type Context = Environment;
+
+        fn tick(&mut self, _context: &mut Self::Context, blackboard: &mut Blackboard) -> Status {
+            let key = format!("{}Ticks", self.name);
+            let ticks = blackboard.get::<i64>(&key).unwrap_or_default();
+            blackboard.set(key, ticks + 1);
+            Status::Running
+        }
+
+        fn score(&self, _context: &Self::Context, blackboard: &Blackboard) -> f32 {
+            blackboard
+                .get::<f32>(format!("{}Score", self.name))
+                .unwrap_or_default()
+        }
+    }
+
+    #[derive(Debug, PartialEq, Default, Visit, Clone)]
+    struct BrokenAction;
+
+    impl<'a> Behavior<'a> for BrokenAction {
+        type Context = Environment;
+
+        fn tick(&mut self, _context: &mut Self::Context, _blackboard: &mut Blackboard) -> Status {
+            Status::Error(BehaviorError::new("No target"))
+        }
+    }
+
+    #[derive(Debug, PartialEq, Default, Visit, Clone)]
+    struct SeeEnemyCondition;
+
+    impl<'a> Behavior<'a> for SeeEnemyCondition {
+        type Context = Environment;
+
+        fn tick(&mut self, _context: &mut Self::Context, blackboard: &mut Blackboard) -> Status {
+            if blackboard.get::<bool>("EnemyVisible") == Some(true) {
+                Status::Success
+            } else {
+                Status::Failure
+            }
+        }
+    }
+
     #[derive(Debug, PartialEq, Visit, Clone)]
     enum BotBehavior {
         None,
@@ -375,6 +1445,10 @@ mod test {
         OpenDoor(OpenDoorAction),
         StepThrough(StepThroughAction),
         CloseDoor(CloseDoorAction),
+        CountTicks(CountTicksAction),
+        SeeEnemy(SeeEnemyCondition),
+        Scored(ScoredAction),
+        Broken(BrokenAction),
     }
 
     impl Default for BotBehavior {
@@ -395,13 +1469,24 @@ mod test {
     impl<'a> Behavior<'a> for BotBehavior {
         type Context = Environment;
 
-        fn tick(&mut self, context: &mut Self::Context) -> Status {
+        fn tick(&mut self, context: &mut Self::Context, blackboard: &mut Blackboard) -> Status {
             match self {
                 BotBehavior::None => unreachable!(),
-                BotBehavior::Walk(v) => v.tick(context),
-                BotBehavior::OpenDoor(v) => v.tick(context),
-                BotBehavior::StepThrough(v) => v.tick(context),
-                BotBehavior::CloseDoor(v) => v.tick(context),
+                BotBehavior::Walk(v) => v.tick(context, blackboard),
+                BotBehavior::OpenDoor(v) => v.tick(context, blackboard),
+                BotBehavior::StepThrough(v) => v.tick(context, blackboard),
+                BotBehavior::CloseDoor(v) => v.tick(context, blackboard),
+                BotBehavior::CountTicks(v) => v.tick(context, blackboard),
+                BotBehavior::SeeEnemy(v) => v.tick(context, blackboard),
+                BotBehavior::Scored(v) => v.tick(context, blackboard),
+                BotBehavior::Broken(v) => v.tick(context, blackboard),
+            }
+        }
+
+        fn score(&self, context: &Self::Context, blackboard: &Blackboard) -> f32 {
+            match self {
+                BotBehavior::Scored(v) => v.score(context, blackboard),
+                _ => 0.0,
             }
         }
     }
@@ -436,10 +1521,849 @@ mod test {
         };
 
         while !ctx.done {
-            tree.tick(&mut ctx);
+            tree.tick(&mut ctx, TICK);
         }
     }
 
+    #[test]
+    fn test_blackboard_sharing() {
+        let mut tree = create_tree();
+        tree.blackboard_mut().set("DoorOpened", false);
+
+        let mut ctx = Environment {
+            distance_to_door: 0.0,
+            door_opened: false,
+            done: false,
+        };
+
+        // The door is opened by one leaf and closed by another, the latter relies on the fact that
+        // was written to the blackboard by the former.
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert_eq!(tree.blackboard().get::<bool>("DoorOpened"), Some(true));
+
+        while !ctx.done {
+            tree.tick(&mut ctx, TICK);
+        }
+        assert_eq!(tree.blackboard().get::<bool>("DoorOpened"), Some(false));
+    }
+
+    #[test]
+    fn test_decorators() {
+        for (kind, expected_success) in [
+            (DecoratorNodeKind::Inverter, false),
+            (DecoratorNodeKind::ForceSuccess, true),
+            (DecoratorNodeKind::ForceFailure, false),
+        ] {
+            let mut tree = BehaviorTree::new();
+            let leaf = LeafNode::new(BotBehavior::OpenDoor(OpenDoorAction)).add_to(&mut tree);
+            let decorator = DecoratorNode::new(kind, leaf).add_to(&mut tree);
+            tree.set_entry_node(decorator);
+
+            let mut ctx = Environment::default();
+            let status = tree.tick(&mut ctx, TICK);
+            assert!(ctx.door_opened);
+            assert_eq!(matches!(status, Status::Success), expected_success);
+        }
+
+        // Running status must pass through any decorator.
+        for kind in [
+            DecoratorNodeKind::Inverter,
+            DecoratorNodeKind::ForceSuccess,
+            DecoratorNodeKind::ForceFailure,
+        ] {
+            let mut tree = BehaviorTree::new();
+            let leaf = LeafNode::new(BotBehavior::Walk(WalkAction)).add_to(&mut tree);
+            let decorator = DecoratorNode::new(kind, leaf).add_to(&mut tree);
+            tree.set_entry_node(decorator);
+
+            let mut ctx = Environment {
+                distance_to_door: 1.0,
+                ..Default::default()
+            };
+            assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        }
+    }
+
+    fn iteration(
+        tree: &BehaviorTree<BotBehavior>,
+        handle: Handle<BehaviorNode<BotBehavior>>,
+    ) -> u32 {
+        match tree.node(handle) {
+            Some(BehaviorNode::Decorator(decorator)) => decorator.iteration(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_repeaters() {
+        let mut ctx = Environment::default();
+
+        // Finite repetition.
+        let mut tree = BehaviorTree::new();
+        let child = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let node = repeater(Some(3), child, &mut tree);
+        tree.set_entry_node(node);
+        for _ in 0..2 {
+            assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+            assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+            assert_eq!(iteration(&tree, node), 2);
+            assert!(matches!(tree.tick(&mut ctx, TICK), Status::Success));
+            assert_eq!(iteration(&tree, node), 0);
+        }
+
+        // Zero repetitions, the child must not be executed.
+        let mut tree = BehaviorTree::new();
+        let child = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let node = repeater(Some(0), child, &mut tree);
+        tree.set_entry_node(node);
+        let mut zero_ctx = Environment::default();
+        assert!(matches!(tree.tick(&mut zero_ctx, TICK), Status::Success));
+        assert!(!zero_ctx.door_opened);
+        assert_eq!(iteration(&tree, node), 0);
+
+        // Infinite repetition.
+        let mut tree = BehaviorTree::new();
+        let child = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let node = repeater(None, child, &mut tree);
+        tree.set_entry_node(node);
+        for _ in 0..10 {
+            assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        }
+        assert_eq!(iteration(&tree, node), 10);
+
+        // Running child must not be counted as an iteration.
+        let mut tree = BehaviorTree::new();
+        let child = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let node = repeater(Some(1), child, &mut tree);
+        tree.set_entry_node(node);
+        ctx.distance_to_door = 0.25;
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert_eq!(iteration(&tree, node), 0);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Success));
+
+        // Repeat until fail.
+        let mut tree = BehaviorTree::new();
+        let child = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let node = repeat_until_fail(child, &mut tree);
+        tree.set_entry_node(node);
+        for _ in 0..5 {
+            assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        }
+        assert_eq!(iteration(&tree, node), 5);
+
+        let mut tree = BehaviorTree::new();
+        let child = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let failer_node = failer(child, &mut tree);
+        let node = repeat_until_fail(failer_node, &mut tree);
+        tree.set_entry_node(node);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Success));
+        assert_eq!(iteration(&tree, node), 0);
+    }
+
+    #[test]
+    fn test_parallel() {
+        let mut ctx = Environment {
+            distance_to_door: 0.15,
+            ..Default::default()
+        };
+
+        // Walking takes two ticks, while opening the door succeeds immediately.
+        let create = |success_policy, failure_policy| {
+            let mut tree = BehaviorTree::new();
+            let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+            let open = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+            let node = parallel(success_policy, failure_policy, [walk, open], &mut tree);
+            tree.set_entry_node(node);
+            tree
+        };
+
+        let tree = create(ParallelPolicy::RequireOne, ParallelPolicy::RequireOne);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Success));
+        assert!(ctx.door_opened);
+
+        ctx.distance_to_door = 0.15;
+        let tree = create(ParallelPolicy::RequireAll, ParallelPolicy::RequireOne);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Success));
+
+        // Failure policy has priority.
+        ctx.distance_to_door = 0.0;
+        let mut tree = BehaviorTree::new();
+        let open = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let failing = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let failing = failer(failing, &mut tree);
+        let node = parallel(
+            ParallelPolicy::RequireOne,
+            ParallelPolicy::RequireOne,
+            [open, failing],
+            &mut tree,
+        );
+        tree.set_entry_node(node);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Failure));
+
+        let mut tree = BehaviorTree::new();
+        let open = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let failing = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let failing = failer(failing, &mut tree);
+        let node = parallel(
+            ParallelPolicy::RequireOne,
+            ParallelPolicy::RequireAll,
+            [open, failing],
+            &mut tree,
+        );
+        tree.set_entry_node(node);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Success));
+    }
+
+    #[test]
+    fn test_memory_composites() {
+        fn ticks(tree: &BehaviorTree<BotBehavior>) -> i64 {
+            tree.blackboard().get::<i64>("Ticks").unwrap_or_default()
+        }
+
+        // Walking takes three ticks, regular sequence re-executes the first child on every tick.
+        let mut ctx = Environment {
+            distance_to_door: 0.25,
+            ..Default::default()
+        };
+        let mut tree = BehaviorTree::new();
+        let count = leaf(BotBehavior::CountTicks(CountTicksAction), &mut tree);
+        let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let node = sequence([count, walk], &mut tree);
+        tree.set_entry_node(node);
+        while !matches!(tree.tick(&mut ctx, TICK), Status::Success) {}
+        assert_eq!(ticks(&tree), 4);
+
+        // Memory sequence executes the first child only once.
+        ctx.distance_to_door = 0.25;
+        let mut tree = BehaviorTree::new();
+        let count = leaf(BotBehavior::CountTicks(CountTicksAction), &mut tree);
+        let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let node = memory_sequence([count, walk], &mut tree);
+        tree.set_entry_node(node);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        match tree.node(node) {
+            Some(BehaviorNode::Composite(composite)) => assert_eq!(composite.running_child(), 1),
+            _ => unreachable!(),
+        }
+        while !matches!(tree.tick(&mut ctx, TICK), Status::Success) {}
+        assert_eq!(ticks(&tree), 1);
+
+        // The sequence starts over once it is finished.
+        ctx.distance_to_door = 0.0;
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Success));
+        assert_eq!(ticks(&tree), 2);
+
+        // Memory selector does not re-execute failed children.
+        ctx.distance_to_door = 0.25;
+        let mut tree = BehaviorTree::new();
+        let count = leaf(BotBehavior::CountTicks(CountTicksAction), &mut tree);
+        let count = failer(count, &mut tree);
+        let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let node = memory_selector([count, walk], &mut tree);
+        tree.set_entry_node(node);
+        while !matches!(tree.tick(&mut ctx, TICK), Status::Success) {}
+        assert_eq!(ticks(&tree), 1);
+    }
+
+    #[test]
+    fn test_observer_aborts() {
+        fn set_enemy_visible(tree: &mut BehaviorTree<BotBehavior>, visible: bool) {
+            tree.blackboard_mut().set("EnemyVisible", visible);
+        }
+
+        // Self abort - walking is interrupted as soon as the enemy becomes invisible.
+        let mut ctx = Environment {
+            distance_to_door: 1.0,
+            ..Default::default()
+        };
+        let mut tree = BehaviorTree::new();
+        let condition = leaf(BotBehavior::SeeEnemy(SeeEnemyCondition), &mut tree);
+        let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let node = CompositeNode::new_memory_sequence(vec![condition, walk])
+            .with_abort_policy(AbortPolicy::SelfBranch)
+            .add_to(&mut tree);
+        tree.set_entry_node(node);
+        set_enemy_visible(&mut tree, true);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        set_enemy_visible(&mut tree, false);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Failure));
+        match tree.node(node) {
+            Some(BehaviorNode::Composite(composite)) => assert_eq!(composite.running_child(), 0),
+            _ => unreachable!(),
+        }
+
+        // Lower priority abort - walking is interrupted as soon as the enemy becomes visible, so the
+        // higher priority branch (opening the door) is executed.
+        let mut ctx = Environment {
+            distance_to_door: 1.0,
+            ..Default::default()
+        };
+        let mut tree = BehaviorTree::new();
+        let condition = leaf(BotBehavior::SeeEnemy(SeeEnemyCondition), &mut tree);
+        let open = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let attack = CompositeNode::new_sequence(vec![condition, open])
+            .with_abort_policy(AbortPolicy::LowerPriority)
+            .add_to(&mut tree);
+        let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let node = memory_selector([attack, walk], &mut tree);
+        tree.set_entry_node(node);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert!(!ctx.door_opened);
+        set_enemy_visible(&mut tree, true);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Success));
+        assert!(ctx.door_opened);
+
+        // Without aborts memory selector keeps executing the running branch.
+        let mut ctx = Environment {
+            distance_to_door: 1.0,
+            ..Default::default()
+        };
+        let mut tree = BehaviorTree::new();
+        let condition = leaf(BotBehavior::SeeEnemy(SeeEnemyCondition), &mut tree);
+        let open = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let attack = sequence([condition, open], &mut tree);
+        let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let node = memory_selector([attack, walk], &mut tree);
+        tree.set_entry_node(node);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        set_enemy_visible(&mut tree, true);
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Running));
+        assert!(!ctx.door_opened);
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut ctx = Environment {
+            distance_to_door: 0.3,
+            ..Default::default()
+        };
+        let tree = BehaviorTreeBuilder::new()
+            .sequence()
+            .leaf(BotBehavior::Walk(WalkAction))
+            .leaf(BotBehavior::OpenDoor(OpenDoorAction))
+            .leaf(BotBehavior::StepThrough(StepThroughAction))
+            .leaf(BotBehavior::CloseDoor(CloseDoorAction))
+            .end()
+            .build()
+            .unwrap();
+        while !ctx.done {
+            tree.tick(&mut ctx, TICK);
+        }
+
+        let tree = BehaviorTreeBuilder::new()
+            .selector()
+            .inverter()
+            .leaf(BotBehavior::OpenDoor(OpenDoorAction))
+            .end()
+            .decorator(DecoratorNodeKind::ForceFailure)
+            .leaf(BotBehavior::OpenDoor(OpenDoorAction))
+            .end()
+            .end()
+            .build()
+            .unwrap();
+        assert!(matches!(tree.tick(&mut ctx, TICK), Status::Failure));
+
+        fn error(builder: BehaviorTreeBuilder<BotBehavior>) -> BehaviorTreeBuilderError {
+            builder.build().err().unwrap()
+        }
+
+        assert_eq!(
+            error(BehaviorTreeBuilder::new()),
+            BehaviorTreeBuilderError::NoEntryNode
+        );
+        assert_eq!(
+            error(BehaviorTreeBuilder::new().sequence().end()),
+            BehaviorTreeBuilderError::EmptyComposite
+        );
+        assert_eq!(
+            error(
+                BehaviorTreeBuilder::new()
+                    .leaf(BotBehavior::Walk(WalkAction))
+                    .end()
+            ),
+            BehaviorTreeBuilderError::UnmatchedEnd
+        );
+        assert_eq!(
+            error(BehaviorTreeBuilder::new().selector().sequence()),
+            BehaviorTreeBuilderError::UnclosedNodes { count: 2 }
+        );
+        assert_eq!(
+            error(
+                BehaviorTreeBuilder::new()
+                    .leaf(BotBehavior::Walk(WalkAction))
+                    .leaf(BotBehavior::Walk(WalkAction))
+            ),
+            BehaviorTreeBuilderError::MultipleEntryNodes { count: 2 }
+        );
+        assert_eq!(
+            error(BehaviorTreeBuilder::new().inverter().end()),
+            BehaviorTreeBuilderError::InvalidDecoratorChildCount { count: 0 }
+        );
+    }
+
+    #[test]
+    fn test_sub_tree() {
+        let mut ctx = Environment {
+            distance_to_door: 0.3,
+            ..Default::default()
+        };
+
+        // The same branch is reused twice, every copy has its own execution state.
+        let open_door = create_tree();
+        let mut tree = BehaviorTree::new();
+        let count = leaf(BotBehavior::CountTicks(CountTicksAction), &mut tree);
+        let first = sub_tree(open_door.clone(), &mut tree);
+        let second = sub_tree(open_door, &mut tree);
+        let entry = memory_sequence([count, first, second], &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut status = tree.tick(&mut ctx, TICK);
+        while matches!(status, Status::Running) {
+            status = tree.tick(&mut ctx, TICK);
+        }
+        assert!(matches!(status, Status::Success));
+        assert!(ctx.done && !ctx.door_opened);
+
+        // Leaves of the embedded trees write to the blackboard of the host tree.
+        assert_eq!(tree.blackboard().get::<i64>("Ticks"), Some(1));
+        assert_eq!(tree.blackboard().get::<bool>("DoorOpened"), Some(false));
+        if let BehaviorNode::SubTree(ref sub_tree) = tree[first] {
+            let blackboard = sub_tree.tree.as_ref().unwrap().blackboard();
+            assert_eq!(blackboard.get::<bool>("DoorOpened"), None);
+        } else {
+            unreachable!()
+        }
+
+        let mut visitor = Visitor::new();
+        tree.visit("Tree", &mut visitor).unwrap();
+        let mut visitor = Visitor::load_from_memory(visitor.save_binary_to_vec().unwrap()).unwrap();
+        let mut loaded_tree = BehaviorTree::<BotBehavior>::default();
+        loaded_tree.visit("Tree", &mut visitor).unwrap();
+        assert_eq!(tree, loaded_tree);
+    }
+
+    #[test]
+    fn test_tree_editing() {
+        let mut tree = BehaviorTree::new();
+        let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let open = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let count = leaf(BotBehavior::CountTicks(CountTicksAction), &mut tree);
+        let invert = inverter(count, &mut tree);
+        let entry = sequence([walk, open, invert], &mut tree);
+        tree.set_entry_node(entry);
+        assert_eq!(tree.parent_of(count), invert);
+        assert_eq!(tree.children_of(entry), vec![walk, open, invert]);
+
+        // The whole branch is removed.
+        tree.remove_node(invert).unwrap();
+        assert!(tree.node(invert).is_none());
+        assert!(tree.node(count).is_none());
+        assert_eq!(tree.children_of(entry), vec![walk, open]);
+        assert_eq!(
+            tree.remove_node(invert),
+            Err(BehaviorTreeEditError::InvalidHandle)
+        );
+        assert_eq!(
+            tree.remove_node(tree.parent_of(entry)),
+            Err(BehaviorTreeEditError::RootNode)
+        );
+
+        let repeat = DecoratorNode::new_repeat(Some(2), Handle::NONE).add_to(&mut tree);
+        tree.reparent_node(open, repeat).unwrap();
+        assert_eq!(
+            tree.reparent_node(walk, repeat),
+            Err(BehaviorTreeEditError::ChildAlreadySet)
+        );
+        assert_eq!(
+            tree.reparent_node(entry, walk),
+            Err(BehaviorTreeEditError::NotAContainer)
+        );
+        assert_eq!(
+            tree.reparent_node(entry, open),
+            Err(BehaviorTreeEditError::NotAContainer)
+        );
+        assert_eq!(
+            tree.reparent_node(entry, repeat),
+            Err(BehaviorTreeEditError::ChildAlreadySet)
+        );
+        tree.reparent_node(repeat, entry).unwrap();
+        assert_eq!(
+            tree.reparent_node(entry, repeat),
+            Err(BehaviorTreeEditError::CyclicHierarchy)
+        );
+        assert_eq!(tree.children_of(entry), vec![walk, repeat]);
+        assert_eq!(tree.parent_of(open), repeat);
+
+        let mut ctx = Environment::default();
+        for _ in 0..4 {
+            tree.tick(&mut ctx, TICK);
+        }
+        assert!(ctx.door_opened);
+    }
+
+    #[test]
+    fn test_tick_trace() {
+        let mut tree = BehaviorTree::new();
+        let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let open = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let entry = sequence([walk, open], &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut ctx = Environment {
+            distance_to_door: 0.1,
+            ..Default::default()
+        };
+        let (status, trace) = tree.tick_traced(&mut ctx, TICK);
+        assert_eq!(status, Status::Running);
+        assert_eq!(trace.entries().len(), 3);
+        assert_eq!(trace.entries()[1].node, entry);
+        assert_eq!(trace.entries()[2].depth, 2);
+        assert_eq!(trace.status_of(walk), Some(Status::Running));
+        assert!(!trace.is_visited(open));
+
+        let (status, trace) = tree.tick_traced(&mut ctx, TICK);
+        assert_eq!(status, Status::Success);
+        assert_eq!(trace.status_of(open), Some(Status::Success));
+        assert_eq!(trace.to_string().lines().count(), 4);
+
+        // Regular ticks are not traced.
+        tree.tick(&mut ctx, TICK);
+        assert!(tree.trace.borrow().is_none());
+    }
+
+    #[test]
+    fn test_export_graph() {
+        let mut tree = BehaviorTree::new();
+        let walk = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+        let open = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let forced = succeeder(open, &mut tree);
+        let entry = sequence([walk, forced], &mut tree);
+        tree.set_entry_node(entry);
+        let detached = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+
+        let mut ctx = Environment {
+            distance_to_door: 0.1,
+            ..Default::default()
+        };
+        let (_, trace) = tree.tick_traced(&mut ctx, TICK);
+        let graph = tree.export_graph(Some(&trace));
+
+        let names = graph
+            .nodes
+            .iter()
+            .map(|node| node.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "Root",
+                "Sequence",
+                "Walk(WalkAction)",
+                "ForceSuccess",
+                "OpenDoor(OpenDoorAction)",
+                "Walk(WalkAction)"
+            ]
+        );
+        assert_eq!(graph.nodes[0].kind, GraphNodeKind::Root);
+        assert_eq!(graph.nodes[2].status, GraphNodeStatus::Running);
+        assert_eq!(graph.nodes[4].status, GraphNodeStatus::NotVisited);
+        assert_eq!(
+            graph.nodes[5].handle,
+            Some((detached.index(), detached.generation()))
+        );
+        assert_eq!(graph.children_of(1).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(graph.children_of(5).count(), 0);
+        assert!(graph.to_dot().contains("3 -> 4;"));
+
+        // Nodes of embedded trees are children of their sub-tree nodes.
+        let mut host = BehaviorTree::new();
+        let embedded = sub_tree(tree, &mut host);
+        host.set_entry_node(embedded);
+        let graph = host.export_graph(None);
+        assert_eq!(graph.nodes.len(), 8);
+        assert_eq!(graph.nodes[1].kind, GraphNodeKind::SubTree);
+        assert_eq!(graph.children_of(1).collect::<Vec<_>>(), [2]);
+        assert_eq!(graph.nodes[2].handle, None);
+
+        let data = ron::to_string(&graph).unwrap();
+        assert_eq!(ron::from_str::<BehaviorTreeGraph>(&data).unwrap(), graph);
+    }
+
+    #[test]
+    fn test_utility_selector() {
+        let mut tree = BehaviorTree::new();
+        let scored = |name: &str, tree: &mut BehaviorTree<BotBehavior>| {
+            leaf(
+                BotBehavior::Scored(ScoredAction {
+                    name: name.to_string(),
+                }),
+                tree,
+            )
+        };
+        let attack = scored("Attack", &mut tree);
+        // The branch is scored by its first child.
+        let flee_score = scored("Flee", &mut tree);
+        let flee = repeater(Some(3), flee_score, &mut tree);
+        let flee_branch = memory_sequence([flee], &mut tree);
+        let entry = utility_selector([attack, flee_branch], &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut ctx = Environment::default();
+        let ticks = |tree: &BehaviorTree<BotBehavior>, name: &str| {
+            tree.blackboard()
+                .get::<i64>(format!("{name}Ticks"))
+                .unwrap_or_default()
+        };
+
+        // Equal scores - the first child is selected.
+        assert_eq!(tree.tick(&mut ctx, TICK), Status::Running);
+        assert_eq!(ticks(&tree, "Attack"), 1);
+
+        tree.blackboard_mut().set("FleeScore", 0.8f32);
+        tree.blackboard_mut().set("AttackScore", 0.5f32);
+        tree.tick(&mut ctx, TICK);
+        assert_eq!(ticks(&tree, "Flee"), 1);
+        assert_eq!(ticks(&tree, "Attack"), 1);
+
+        tree.blackboard_mut().set("AttackScore", 0.9f32);
+        tree.tick(&mut ctx, TICK);
+        assert_eq!(ticks(&tree, "Attack"), 2);
+        assert_eq!(ticks(&tree, "Flee"), 1);
+
+        let mut empty = BehaviorTree::<BotBehavior>::new();
+        let entry = utility_selector([], &mut empty);
+        empty.set_entry_node(entry);
+        assert_eq!(empty.tick(&mut ctx, TICK), Status::Failure);
+    }
+
+    #[test]
+    fn test_tick_budgeted() {
+        fn ticks(tree: &BehaviorTree<BotBehavior>) -> i64 {
+            tree.blackboard().get::<i64>("Ticks").unwrap_or_default()
+        }
+
+        let mut tree = BehaviorTree::new();
+        let counters = [(); 6].map(|_| leaf(BotBehavior::CountTicks(CountTicksAction), &mut tree));
+        let first = sequence([counters[0], counters[1], counters[2]], &mut tree);
+        let second = parallel(
+            ParallelPolicy::RequireAll,
+            ParallelPolicy::RequireOne,
+            [counters[3], counters[4], counters[5]],
+            &mut tree,
+        );
+        let entry = sequence([first, second], &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut ctx = Environment::default();
+        let budget = TickBudget::unlimited().with_max_leaves(2);
+
+        // The tick is spread over three calls, finished leaves are not executed again.
+        assert_eq!(tree.tick_budgeted(&mut ctx, TICK, budget), Status::Running);
+        assert_eq!(ticks(&tree), 2);
+        assert_eq!(tree.tick_budgeted(&mut ctx, TICK, budget), Status::Running);
+        assert_eq!(ticks(&tree), 4);
+        assert_eq!(tree.tick_budgeted(&mut ctx, TICK, budget), Status::Success);
+        assert_eq!(ticks(&tree), 6);
+
+        // Unlimited tick continues the suspended one.
+        assert_eq!(tree.tick_budgeted(&mut ctx, TICK, budget), Status::Running);
+        assert_eq!(tree.tick(&mut ctx, TICK), Status::Success);
+        assert_eq!(ticks(&tree), 12);
+
+        // At least one leaf is ticked per call.
+        let budget = TickBudget::unlimited().with_max_leaves(0);
+        for expected in 13..18 {
+            assert_eq!(tree.tick_budgeted(&mut ctx, TICK, budget), Status::Running);
+            assert_eq!(ticks(&tree), expected);
+        }
+        assert_eq!(tree.tick_budgeted(&mut ctx, TICK, budget), Status::Success);
+        assert_eq!(ticks(&tree), 18);
+
+        let budget = TickBudget::unlimited().with_max_time(Duration::from_secs(3600));
+        assert_eq!(tree.tick_budgeted(&mut ctx, TICK, budget), Status::Success);
+        assert_eq!(ticks(&tree), 24);
+    }
+
+    #[test]
+    fn test_error_propagation() {
+        fn error_node(status: Status) -> Handle<BehaviorNode<BotBehavior>> {
+            match status {
+                Status::Error(error) => {
+                    assert_eq!(error.message(), "No target");
+                    error.node()
+                }
+                status => panic!("Unexpected status {status:?}"),
+            }
+        }
+
+        let mut tree = BehaviorTree::new();
+        let broken = leaf(BotBehavior::Broken(BrokenAction), &mut tree);
+        let counter = leaf(BotBehavior::CountTicks(CountTicksAction), &mut tree);
+        let inverted = inverter(broken, &mut tree);
+        let forced = succeeder(inverted, &mut tree);
+        // The error is not treated as a failure, so the selector does not try the next child.
+        let entry = selector([forced, counter], &mut tree);
+        tree.set_entry_node(entry);
+
+        let mut ctx = Environment::default();
+        let (status, trace) = tree.tick_traced(&mut ctx, TICK);
+        assert_eq!(error_node(status), broken);
+        assert!(!trace.is_visited(counter));
+        assert_eq!(trace.status_of(forced), trace.status_of(broken));
+        assert_eq!(
+            tree.export_graph(Some(&trace)).nodes[1].status,
+            GraphNodeStatus::Error
+        );
+
+        // Errors of embedded trees are attributed to sub-tree nodes.
+        let mut host = BehaviorTree::new();
+        let embedded = sub_tree(tree, &mut host);
+        let entry = sequence([embedded], &mut host);
+        host.set_entry_node(entry);
+        assert_eq!(error_node(host.tick(&mut ctx, TICK)), embedded);
+    }
+
+    #[test]
+    fn test_node_observers() {
+        let mut tree = BehaviorTree::new();
+        let see_enemy = leaf(BotBehavior::SeeEnemy(SeeEnemyCondition), &mut tree);
+        let attack = leaf(
+            BotBehavior::Scored(ScoredAction {
+                name: "Attack".to_string(),
+            }),
+            &mut tree,
+        );
+        let entry = sequence([see_enemy, attack], &mut tree);
+        tree.set_entry_node(entry);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = events.clone();
+        tree.on_enter(attack, move |blackboard| {
+            blackboard.set("Attacking", true);
+            log.lock().unwrap().push("enter".to_string());
+        });
+        let log = events.clone();
+        tree.on_exit(attack, move |status, blackboard| {
+            blackboard.set("Attacking", false);
+            log.lock().unwrap().push(format!("exit {status:?}"));
+        });
+        let log = events.clone();
+        tree.on_status_change(see_enemy, move |previous, status, _| {
+            log.lock()
+                .unwrap()
+                .push(format!("{previous:?} -> {status:?}"));
+        });
+        let take_events = || std::mem::take(&mut *events.lock().unwrap());
+
+        let mut ctx = Environment::default();
+        tree.blackboard_mut().set("EnemyVisible", true);
+        tree.tick(&mut ctx, TICK);
+        assert_eq!(take_events(), ["None -> Success", "enter"]);
+        assert_eq!(tree.blackboard().get::<bool>("Attacking"), Some(true));
+
+        // Nothing has changed.
+        tree.tick(&mut ctx, TICK);
+        assert!(take_events().is_empty());
+
+        // The sequence stops ticking the running action, so it is aborted.
+        tree.blackboard_mut().set("EnemyVisible", false);
+        tree.tick(&mut ctx, TICK);
+        assert_eq!(take_events(), ["Some(Success) -> Failure", "exit None"]);
+        assert_eq!(tree.blackboard().get::<bool>("Attacking"), Some(false));
+
+        tree.blackboard_mut().set("EnemyVisible", true);
+        tree.tick(&mut ctx, TICK);
+        assert_eq!(take_events(), ["Some(Failure) -> Success", "enter"]);
+
+        // Observers are not cloned.
+        let copy = tree.clone();
+        copy.tick(&mut ctx, TICK);
+        assert!(take_events().is_empty());
+
+        tree.remove_observers(attack);
+        tree.blackboard_mut().set("EnemyVisible", false);
+        tree.tick(&mut ctx, TICK);
+        assert_eq!(take_events(), ["Some(Success) -> Failure"]);
+    }
+
+    #[test]
+    fn test_timed_decorators() {
+        let tick = TickContext::new(0.5);
+        let mut ctx = Environment::default();
+
+        // Cooldown.
+        let mut tree = BehaviorTree::new();
+        let child = leaf(BotBehavior::OpenDoor(OpenDoorAction), &mut tree);
+        let node = cooldown(1.0, child, &mut tree);
+        tree.set_entry_node(node);
+        assert!(matches!(tree.tick(&mut ctx, tick), Status::Success));
+        assert!(matches!(tree.tick(&mut ctx, tick), Status::Failure));
+        assert!(matches!(tree.tick(&mut ctx, tick), Status::Success));
+        assert_eq!(tree.time(), 1.5);
+
+        // Time limit, walking takes four ticks.
+        let create = |duration| {
+            let mut tree = BehaviorTree::new();
+            let child = leaf(BotBehavior::Walk(WalkAction), &mut tree);
+            let node = time_limit(duration, child, &mut tree);
+            tree.set_entry_node(node);
+            tree
+        };
+
+        ctx.distance_to_door = 0.25;
+        let tree = create(1.0);
+        assert!(matches!(tree.tick(&mut ctx, tick), Status::Running));
+        assert!(matches!(tree.tick(&mut ctx, tick), Status::Running));
+        assert!(matches!(tree.tick(&mut ctx, tick), Status::Failure));
+
+        ctx.distance_to_door = 0.25;
+        let tree = create(10.0);
+        for _ in 0..3 {
+            assert!(matches!(tree.tick(&mut ctx, tick), Status::Running));
+        }
+        assert!(matches!(tree.tick(&mut ctx, tick), Status::Success));
+
+        // Timed decorators are `Eq`, even if durations are NaN.
+        let node = BehaviorNode::<BotBehavior>::Decorator(DecoratorNode::new_cooldown(
+            f32::NAN,
+            Handle::NONE,
+        ));
+        assert_eq!(node, node.clone());
+        assert_ne!(
+            DecoratorNodeKind::Cooldown { duration: 1.0 },
+            DecoratorNodeKind::TimeLimit { duration: 1.0 }
+        );
+    }
+
+    #[test]
+    fn test_tick_parallel() {
+        let mut trees = (0..8).map(|_| create_tree()).collect::<Vec<_>>();
+        let mut contexts = (0..8)
+            .map(|i| Environment {
+                distance_to_door: i as f32 * 0.1,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut agents = trees
+            .iter_mut()
+            .zip(contexts.iter_mut())
+            .collect::<Vec<_>>();
+        let statuses = tick_parallel(&mut agents, TICK);
+        assert_eq!(statuses.len(), 8);
+        // The first agent is at the door already, so it opens the door on the first tick.
+        assert!(matches!(statuses[0], Status::Running));
+
+        assert!(contexts[0].door_opened);
+        assert!(contexts[1..].iter().all(|ctx| !ctx.door_opened));
+    }
+
     #[test]
     fn test_behavior_save_load() {
         let (bin, txt) = {
@@ -0,0 +1,207 @@
+//! Observers of status transitions of behavior tree nodes. They allow game code to react on the start
+//! and the end of a branch (play animations, sounds, etc.) without modifying the behaviors of the leaves.
+//! See [`super::BehaviorTree::on_enter`], [`super::BehaviorTree::on_exit`] and
+//! [`super::BehaviorTree::on_status_change`].
+
+use crate::{
+    core::pool::Handle,
+    utils::behavior::{blackboard::Blackboard, BehaviorNode, Status},
+};
+use fxhash::FxHashMap;
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Formatter},
+};
+
+/// A callback, that is called when an observed node is entered.
+pub type EnterCallback = Box<dyn FnMut(&mut Blackboard) + Send>;
+
+/// A callback, that is called when an observed node is exited. The status is `None` if the node was
+/// aborted.
+pub type ExitCallback = Box<dyn FnMut(Option<&Status>, &mut Blackboard) + Send>;
+
+/// A callback, that is called when an observed node returns a status, that differs from the previous one.
+/// The previous status is `None` if the node has never returned a status or if it was aborted.
+pub type StatusChangeCallback = Box<dyn FnMut(Option<&Status>, &Status, &mut Blackboard) + Send>;
+
+#[derive(Default)]
+struct NodeObservers {
+    on_enter: Vec<EnterCallback>,
+    on_exit: Vec<ExitCallback>,
+    on_status_change: Vec<StatusChangeCallback>,
+    active: bool,
+    last_status: Option<Status>,
+    visited: u64,
+}
+
+impl NodeObservers {
+    fn abort(&mut self, blackboard: &RefCell<Blackboard>) {
+        if self.active {
+            self.active = false;
+            self.last_status = None;
+            let blackboard = &mut blackboard.borrow_mut();
+            for callback in self.on_exit.iter_mut() {
+                callback(None, blackboard);
+            }
+        }
+    }
+}
+
+// Observers of the nodes of a tree together with the tracked state of the observed nodes. Observers are
+// runtime-only, they're neither serialized nor cloned.
+pub(super) struct Observers<B>
+where
+    B: Clone,
+{
+    nodes: FxHashMap<Handle<BehaviorNode<B>>, NodeObservers>,
+    // Index of the current tick, it is not advanced while a budgeted tick is suspended.
+    epoch: u64,
+    complete: bool,
+}
+
+impl<B> Default for Observers<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            nodes: Default::default(),
+            epoch: 0,
+            complete: true,
+        }
+    }
+}
+
+impl<B> Clone for Observers<B>
+where
+    B: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<B> PartialEq for Observers<B>
+where
+    B: Clone,
+{
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<B> Debug for Observers<B>
+where
+    B: Clone,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Observers of {} nodes", self.nodes.len())
+    }
+}
+
+impl<B> Observers<B>
+where
+    B: Clone + 'static,
+{
+    pub(super) fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub(super) fn add_enter(&mut self, node: Handle<BehaviorNode<B>>, callback: EnterCallback) {
+        self.nodes.entry(node).or_default().on_enter.push(callback);
+    }
+
+    pub(super) fn add_exit(&mut self, node: Handle<BehaviorNode<B>>, callback: ExitCallback) {
+        self.nodes.entry(node).or_default().on_exit.push(callback);
+    }
+
+    pub(super) fn add_status_change(
+        &mut self,
+        node: Handle<BehaviorNode<B>>,
+        callback: StatusChangeCallback,
+    ) {
+        self.nodes
+            .entry(node)
+            .or_default()
+            .on_status_change
+            .push(callback);
+    }
+
+    pub(super) fn remove(&mut self, node: Handle<BehaviorNode<B>>) {
+        self.nodes.remove(&node);
+    }
+
+    // Starts a new tick, unless the previous one was suspended and this one continues it.
+    pub(super) fn begin_tick(&mut self) {
+        if self.complete {
+            self.epoch += 1;
+        }
+    }
+
+    // Finishes the tick. Observed nodes, that are still active, but were not visited during a complete
+    // tick, were silently dropped by their parents (for example, a sequence has restarted from its first
+    // child), so they're treated as aborted.
+    pub(super) fn end_tick(&mut self, complete: bool, blackboard: &RefCell<Blackboard>) {
+        self.complete = complete;
+        if complete {
+            let epoch = self.epoch;
+            for observers in self.nodes.values_mut() {
+                if observers.visited != epoch {
+                    observers.abort(blackboard);
+                }
+            }
+        }
+    }
+
+    // Must be called right before the node is ticked.
+    pub(super) fn enter(
+        &mut self,
+        node: Handle<BehaviorNode<B>>,
+        blackboard: &RefCell<Blackboard>,
+    ) {
+        if let Some(observers) = self.nodes.get_mut(&node) {
+            observers.visited = self.epoch;
+            if !observers.active {
+                observers.active = true;
+                let blackboard = &mut blackboard.borrow_mut();
+                for callback in observers.on_enter.iter_mut() {
+                    callback(blackboard);
+                }
+            }
+        }
+    }
+
+    // Must be called right after the node is ticked.
+    pub(super) fn leave(
+        &mut self,
+        node: Handle<BehaviorNode<B>>,
+        status: &Status,
+        blackboard: &RefCell<Blackboard>,
+    ) {
+        if let Some(observers) = self.nodes.get_mut(&node) {
+            let blackboard = &mut blackboard.borrow_mut();
+            if observers.last_status.as_ref() != Some(status) {
+                for callback in observers.on_status_change.iter_mut() {
+                    callback(observers.last_status.as_ref(), status, blackboard);
+                }
+                observers.last_status = Some(status.clone());
+            }
+            if *status != Status::Running {
+                observers.active = false;
+                for callback in observers.on_exit.iter_mut() {
+                    callback(Some(status), blackboard);
+                }
+            }
+        }
+    }
+
+    pub(super) fn abort(
+        &mut self,
+        node: Handle<BehaviorNode<B>>,
+        blackboard: &RefCell<Blackboard>,
+    ) {
+        if let Some(observers) = self.nodes.get_mut(&node) {
+            observers.abort(blackboard);
+        }
+    }
+}
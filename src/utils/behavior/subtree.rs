@@ -0,0 +1,81 @@
+//! Sub-tree node embeds another behavior tree and ticks it as if its nodes were a part of the host tree.
+//! It allows to author common branches (like "flee" or "open the door") once and reuse them in many trees.
+//!
+//! Embedded tree shares the blackboard and the time of the host tree, so its leaves could exchange data with
+//! the leaves of the host tree and its decorators (cooldowns, time limits) measure time of the host tree. Keep
+//! in mind, that every sub-tree node owns its own copy of the tree, because the nodes of a tree store their
+//! execution state (running children, repetition counters, etc.) and this state must not be shared between
+//! multiple agents. To reuse a branch, create it once and clone it for every host tree.
+
+use crate::{
+    core::{pool::Handle, visitor::prelude::*},
+    utils::behavior::{BehaviorNode, BehaviorTree},
+};
+
+/// See module docs.
+#[derive(Debug, Clone)]
+pub struct SubTreeNode<B>
+where
+    B: Clone,
+{
+    /// Embedded tree. `None` is treated as an empty tree, that always succeeds.
+    pub tree: Option<Box<BehaviorTree<B>>>,
+}
+
+impl<B> Default for SubTreeNode<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self { tree: None }
+    }
+}
+
+// Only the nodes of the embedded trees are compared, their own time and blackboard are not used, because the
+// time and the blackboard of the host tree are used instead. It also makes the comparison `Eq`.
+impl<B> PartialEq for SubTreeNode<B>
+where
+    B: Clone + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.tree, &other.tree) {
+            (Some(a), Some(b)) => a.root == b.root && a.nodes == b.nodes,
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<B> Eq for SubTreeNode<B> where B: Clone + Eq {}
+
+// Implemented manually, because derived implementation requires `BehaviorTree<B>: Visit`, which in its turn
+// requires `SubTreeNode<B>: Visit` and causes infinite recursion when resolving trait bounds.
+impl<B> Visit for SubTreeNode<B>
+where
+    B: Clone + Visit + Default + 'static,
+{
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.tree.visit("Tree", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl<B> SubTreeNode<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new sub-tree node, that embeds the given tree.
+    pub fn new(tree: BehaviorTree<B>) -> Self {
+        Self {
+            tree: Some(Box::new(tree)),
+        }
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::SubTree(self))
+    }
+}
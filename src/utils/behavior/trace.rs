@@ -0,0 +1,111 @@
+//! Execution trace of a single tick of a behavior tree. It records every node, that was visited during
+//! the tick, and the status it returned, so the live execution path of an agent could be logged or
+//! visualized. See [`super::BehaviorTree::tick_traced`].
+
+use crate::{
+    core::pool::Handle,
+    utils::behavior::{BehaviorNode, Status},
+};
+use std::fmt::{Display, Formatter};
+
+/// A single visit of a node during a tick.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraceEntry<B>
+where
+    B: Clone,
+{
+    /// Handle of the visited node.
+    pub node: Handle<BehaviorNode<B>>,
+    /// Depth of the node in the execution path, the root node of the tree has zero depth.
+    pub depth: usize,
+    /// Status, that was returned by the node.
+    pub status: Status,
+}
+
+/// Execution trace of a single tick. Entries are stored in the order of visiting (parents before their
+/// children), so the trace could be printed as an indented tree. Keep in mind, that a node could be
+/// visited more than once per tick (for example, conditions of composite nodes with abort policies are
+/// re-evaluated) and nodes of embedded trees (see [`super::subtree::SubTreeNode`]) are not traced.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TickTrace<B>
+where
+    B: Clone,
+{
+    entries: Vec<TraceEntry<B>>,
+    depth: usize,
+}
+
+impl<B> Default for TickTrace<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+            depth: Default::default(),
+        }
+    }
+}
+
+impl<B> TickTrace<B>
+where
+    B: Clone,
+{
+    /// Returns all entries of the trace.
+    pub fn entries(&self) -> &[TraceEntry<B>] {
+        &self.entries
+    }
+
+    /// Returns the status, that was returned by the last visit of the given node during the tick. `None`
+    /// means that the node was not visited.
+    pub fn status_of(&self, node: Handle<BehaviorNode<B>>) -> Option<Status> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.node == node)
+            .map(|entry| entry.status.clone())
+    }
+
+    /// Returns `true` if the given node was visited during the tick.
+    pub fn is_visited(&self, node: Handle<BehaviorNode<B>>) -> bool {
+        self.entries.iter().any(|entry| entry.node == node)
+    }
+
+    // Adds a new entry for the node, that is about to be ticked. The actual status is set when the node
+    // is finished.
+    pub(super) fn enter(&mut self, node: Handle<BehaviorNode<B>>) -> usize {
+        self.entries.push(TraceEntry {
+            node,
+            depth: self.depth,
+            status: Status::Running,
+        });
+        self.depth += 1;
+        self.entries.len() - 1
+    }
+
+    pub(super) fn leave(&mut self, index: usize, status: Status) {
+        self.depth = self.depth.saturating_sub(1);
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.status = status;
+        }
+    }
+}
+
+impl<B> Display for TickTrace<B>
+where
+    B: Clone,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for entry in self.entries.iter() {
+            writeln!(
+                f,
+                "{:indent$}{} - {:?}",
+                "",
+                entry.node,
+                entry.status,
+                indent = entry.depth * 2
+            )?;
+        }
+        Ok(())
+    }
+}
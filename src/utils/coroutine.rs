@@ -0,0 +1,408 @@
+//! Cooperative coroutines for multi-step gameplay sequences (cutscene beats, timed traps, delayed
+//! spawns, etc.). A coroutine is an `async` block, that waits for time, events or conditions using the
+//! awaitables of [`CoroutineContext`]. Coroutines are driven by [`CoroutineScheduler::update`], which is
+//! usually called from `on_update` of a script or a plugin with the delta time of the engine, every
+//! coroutine is polled once per update, so a coroutine runs until its next `await` and then yields
+//! control back to the game.
+//!
+//! Coroutines cannot borrow a scene, so they change the game world using the script message bus (see
+//! [`crate::script::ScriptMessageSender`]) or shared state.
+//!
+//! ```rust
+//! use fyrox::{
+//!     core::pool::Handle,
+//!     scene::node::Node,
+//!     script::ScriptMessageSender,
+//!     utils::coroutine::CoroutineScheduler,
+//! };
+//!
+//! enum TrapMessage {
+//!     Open,
+//!     Close,
+//! }
+//!
+//! fn arm_trap(scheduler: &mut CoroutineScheduler, trap: Handle<Node>, sender: ScriptMessageSender) {
+//!     scheduler.spawn(move |ctx| async move {
+//!         ctx.wait_for_event("player_entered").await;
+//!         sender.send_to_target(trap, TrapMessage::Open);
+//!         ctx.wait_seconds(1.5).await;
+//!         sender.send_to_target(trap, TrapMessage::Close);
+//!     });
+//! }
+//!
+//! fn on_update(scheduler: &mut CoroutineScheduler, dt: f32) {
+//!     scheduler.update(dt);
+//! }
+//! ```
+
+use crate::core::{
+    futures::task::noop_waker_ref,
+    parking_lot::Mutex,
+    pool::{Handle, Pool},
+};
+use std::{
+    fmt::{Debug, Formatter},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+#[derive(Default)]
+struct ClockState {
+    time: f32,
+    frame: u64,
+    // Events of the current update.
+    events: Vec<String>,
+    // Events, that will be visible on the next update.
+    pending_events: Vec<String>,
+}
+
+/// A handle to the clock and events of a scheduler, it is passed to every coroutine and provides
+/// awaitables to suspend the coroutine.
+#[derive(Clone)]
+pub struct CoroutineContext {
+    state: Arc<Mutex<ClockState>>,
+}
+
+impl Debug for CoroutineContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CoroutineContext")
+    }
+}
+
+impl CoroutineContext {
+    /// Returns amount of time (in seconds) that passed since creation of the scheduler.
+    pub fn time(&self) -> f32 {
+        self.state.lock().time
+    }
+
+    /// Returns an awaitable, that is finished when the given amount of time (in seconds) has passed.
+    pub fn wait_seconds(&self, seconds: f32) -> WaitSeconds {
+        WaitSeconds {
+            state: self.state.clone(),
+            seconds,
+            deadline: None,
+        }
+    }
+
+    /// Returns an awaitable, that is finished when an event with the given name is sent to the scheduler
+    /// (see [`CoroutineScheduler::notify`]). Only the events, that were sent after the start of waiting,
+    /// are taken into account.
+    pub fn wait_for_event<S: Into<String>>(&self, event: S) -> WaitForEvent {
+        WaitForEvent {
+            state: self.state.clone(),
+            event: event.into(),
+            since: None,
+        }
+    }
+
+    /// Returns an awaitable, that is finished when the given predicate returns `true`. The predicate is
+    /// checked once per update.
+    pub fn wait_until<F>(&self, predicate: F) -> WaitUntil<F>
+    where
+        F: FnMut() -> bool + Unpin,
+    {
+        WaitUntil { predicate }
+    }
+
+    /// Returns an awaitable, that is finished on the next update.
+    pub fn next_frame(&self) -> NextFrame {
+        NextFrame {
+            state: self.state.clone(),
+            since: None,
+        }
+    }
+
+    /// Sends an event to the scheduler, see [`CoroutineScheduler::notify`].
+    pub fn notify<S: Into<String>>(&self, event: S) {
+        self.state.lock().pending_events.push(event.into());
+    }
+}
+
+/// See [`CoroutineContext::wait_seconds`].
+pub struct WaitSeconds {
+    state: Arc<Mutex<ClockState>>,
+    seconds: f32,
+    deadline: Option<f32>,
+}
+
+impl Future for WaitSeconds {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        let time = self.state.lock().time;
+        let seconds = self.seconds;
+        let deadline = *self.deadline.get_or_insert(time + seconds);
+        if time >= deadline {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// See [`CoroutineContext::wait_for_event`].
+pub struct WaitForEvent {
+    state: Arc<Mutex<ClockState>>,
+    event: String,
+    since: Option<u64>,
+}
+
+impl Future for WaitForEvent {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        let state = self.state.clone();
+        let state = state.lock();
+        let since = *self.since.get_or_insert(state.frame);
+        if state.frame > since && state.events.iter().any(|event| *event == self.event) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// See [`CoroutineContext::wait_until`].
+pub struct WaitUntil<F> {
+    predicate: F,
+}
+
+impl<F> Future for WaitUntil<F>
+where
+    F: FnMut() -> bool + Unpin,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if (self.predicate)() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// See [`CoroutineContext::next_frame`].
+pub struct NextFrame {
+    state: Arc<Mutex<ClockState>>,
+    since: Option<u64>,
+}
+
+impl Future for NextFrame {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        let frame = self.state.lock().frame;
+        if frame > *self.since.get_or_insert(frame) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// A running coroutine.
+pub struct Coroutine {
+    future: Pin<Box<dyn Future<Output = ()> + Send>>,
+}
+
+impl Debug for Coroutine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Coroutine")
+    }
+}
+
+/// Scheduler runs a set of coroutines. See module docs for more info.
+///
+/// Coroutines cannot be serialized or cloned, so the scheduler should be marked with `#[visit(skip)]`
+/// and `#[reflect(hidden)]` when it is stored in a script. A clone of a scheduler has the same clock,
+/// but no coroutines.
+#[derive(Default)]
+pub struct CoroutineScheduler {
+    coroutines: Pool<Coroutine>,
+    state: Arc<Mutex<ClockState>>,
+}
+
+impl Clone for CoroutineScheduler {
+    fn clone(&self) -> Self {
+        let state = self.state.lock();
+        Self {
+            coroutines: Default::default(),
+            state: Arc::new(Mutex::new(ClockState {
+                time: state.time,
+                frame: state.frame,
+                events: Default::default(),
+                pending_events: Default::default(),
+            })),
+        }
+    }
+}
+
+impl Debug for CoroutineScheduler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "CoroutineScheduler {{ coroutines: {} }}",
+            self.coroutines.alive_count()
+        )
+    }
+}
+
+impl CoroutineScheduler {
+    /// Creates new scheduler without coroutines.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a context, that could be used to create awaitables outside of coroutines.
+    pub fn context(&self) -> CoroutineContext {
+        CoroutineContext {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Starts new coroutine. The given function receives a context of the scheduler and returns the
+    /// future of the coroutine. The coroutine is polled for the first time on the next update.
+    pub fn spawn<F, Fut>(&mut self, func: F) -> Handle<Coroutine>
+    where
+        F: FnOnce(CoroutineContext) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let future = func(self.context());
+        self.coroutines.spawn(Coroutine {
+            future: Box::pin(future),
+        })
+    }
+
+    /// Stops the given coroutine. Returns `false` if the coroutine is finished already.
+    pub fn cancel(&mut self, coroutine: Handle<Coroutine>) -> bool {
+        self.coroutines.try_free(coroutine).is_some()
+    }
+
+    /// Stops every coroutine.
+    pub fn cancel_all(&mut self) {
+        self.coroutines.clear();
+    }
+
+    /// Returns `true` if the given coroutine is not finished yet.
+    pub fn is_running(&self, coroutine: Handle<Coroutine>) -> bool {
+        self.coroutines.is_valid_handle(coroutine)
+    }
+
+    /// Returns amount of unfinished coroutines.
+    pub fn len(&self) -> usize {
+        self.coroutines.alive_count() as usize
+    }
+
+    /// Returns `true` if there's no unfinished coroutines.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns amount of time (in seconds) that passed since creation of the scheduler.
+    pub fn time(&self) -> f32 {
+        self.state.lock().time
+    }
+
+    /// Sends an event to the coroutines, the event is visible to the coroutines during the next update.
+    pub fn notify<S: Into<String>>(&self, event: S) {
+        self.state.lock().pending_events.push(event.into());
+    }
+
+    /// Advances the clock by the given amount of time (in seconds) and polls every coroutine once.
+    /// Finished coroutines are removed.
+    pub fn update(&mut self, dt: f32) {
+        {
+            let mut state = self.state.lock();
+            state.time += dt;
+            state.frame += 1;
+            state.events = std::mem::take(&mut state.pending_events);
+        }
+
+        let mut context = Context::from_waker(noop_waker_ref());
+        let mut finished = Vec::new();
+        for (handle, coroutine) in self.coroutines.pair_iter_mut() {
+            if coroutine.future.as_mut().poll(&mut context).is_ready() {
+                finished.push(handle);
+            }
+        }
+        for handle in finished {
+            self.coroutines.free(handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::coroutine::CoroutineScheduler;
+    use std::sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn test_coroutines() {
+        let mut scheduler = CoroutineScheduler::new();
+        let steps = Arc::new(AtomicU32::new(0));
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let coroutine = scheduler.spawn({
+            let steps = steps.clone();
+            let flag = flag.clone();
+            move |ctx| async move {
+                ctx.wait_seconds(1.0).await;
+                steps.store(1, Ordering::SeqCst);
+                ctx.wait_for_event("door_opened").await;
+                steps.store(2, Ordering::SeqCst);
+                ctx.wait_until(move || flag.load(Ordering::SeqCst)).await;
+                steps.store(3, Ordering::SeqCst);
+                ctx.next_frame().await;
+                steps.store(4, Ordering::SeqCst);
+            }
+        });
+
+        // The coroutine starts on the first update and waits for 1 second.
+        for _ in 0..4 {
+            scheduler.update(0.25);
+        }
+        assert_eq!(steps.load(Ordering::SeqCst), 0);
+
+        // Events, that were sent before the start of waiting, are ignored.
+        scheduler.notify("door_opened");
+        scheduler.update(0.25);
+        assert_eq!(steps.load(Ordering::SeqCst), 1);
+        scheduler.update(0.25);
+        assert_eq!(steps.load(Ordering::SeqCst), 1);
+        scheduler.notify("door_opened");
+        scheduler.update(0.25);
+        assert_eq!(steps.load(Ordering::SeqCst), 2);
+
+        scheduler.update(0.25);
+        assert_eq!(steps.load(Ordering::SeqCst), 2);
+        flag.store(true, Ordering::SeqCst);
+        scheduler.update(0.25);
+        assert_eq!(steps.load(Ordering::SeqCst), 3);
+        assert!(scheduler.is_running(coroutine));
+
+        scheduler.update(0.25);
+        assert_eq!(steps.load(Ordering::SeqCst), 4);
+        assert!(!scheduler.is_running(coroutine));
+        assert!(scheduler.is_empty());
+
+        // Cancelled coroutines are never resumed.
+        let coroutine = scheduler.spawn({
+            let steps = steps.clone();
+            move |ctx| async move {
+                ctx.next_frame().await;
+                steps.store(5, Ordering::SeqCst);
+            }
+        });
+        scheduler.update(0.25);
+        assert!(scheduler.cancel(coroutine));
+        scheduler.update(0.25);
+        assert_eq!(steps.load(Ordering::SeqCst), 4);
+        assert!(!scheduler.cancel(coroutine));
+    }
+}
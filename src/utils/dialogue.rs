@@ -0,0 +1,465 @@
+//! Runtime part of dialogues. [`DialoguePlayer`] walks through a [`DialogueGraph`], checks conditions and
+//! applies events of its nodes and choices, [`DialogueView`] shows the current line of a dialogue using
+//! the widgets of a user interface. See [`crate::resource::dialogue`] for the description of dialogue
+//! graphs.
+//!
+//! Texts of dialogue graphs are localization keys, they're translated using [`Localization`] trait, which
+//! is implemented for hash maps (key -> text) and could be implemented for any other string tables.
+//!
+//! ```rust
+//! use fyrox::{
+//!     resource::dialogue::DialogueGraphResource,
+//!     utils::{
+//!         behavior::blackboard::Blackboard,
+//!         dialogue::{DialoguePlayer, DialogueView},
+//!     },
+//!     gui::{message::UiMessage, UserInterface},
+//! };
+//! use std::collections::HashMap;
+//!
+//! struct Conversation {
+//!     player: DialoguePlayer,
+//!     view: DialogueView,
+//!     strings: HashMap<String, String>,
+//! }
+//!
+//! impl Conversation {
+//!     fn start(&mut self, graph: DialogueGraphResource, state: &mut Blackboard, ui: &UserInterface) {
+//!         self.player = DialoguePlayer::new(graph);
+//!         let events = self.player.start(state);
+//!         self.handle_events(events);
+//!         self.sync(state, ui);
+//!     }
+//!
+//!     fn on_ui_message(&mut self, message: &UiMessage, state: &mut Blackboard, ui: &UserInterface) {
+//!         if let Some(choice) = self.view.clicked_choice(message) {
+//!             let events = self.player.choose(choice, state);
+//!             self.handle_events(events);
+//!             self.sync(state, ui);
+//!         }
+//!     }
+//!
+//!     fn sync(&self, state: &Blackboard, ui: &UserInterface) {
+//!         let line = self.player.line(state, &self.strings);
+//!         self.view.sync(line.as_ref(), ui);
+//!     }
+//!
+//!     fn handle_events(&mut self, events: Vec<String>) {
+//!         for event in events {
+//!             // Give items, start quests, etc.
+//!             println!("{event}");
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    asset::ResourceStateRef,
+    core::{pool::Handle, visitor::prelude::*},
+    gui::{
+        button::{ButtonContent, ButtonMessage},
+        message::{MessageDirection, UiMessage},
+        text::TextMessage,
+        widget::WidgetMessage,
+        UiNode, UserInterface,
+    },
+    resource::dialogue::{DialogueEvent, DialogueGraph, DialogueGraphResource},
+    utils::behavior::blackboard::{Blackboard, BlackboardValue},
+};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+/// A source of localized texts.
+pub trait Localization {
+    /// Returns a text for the given localization key. Implementations should return the key itself if
+    /// there's no text for it, so missing translations are easy to spot.
+    fn localize<'a>(&'a self, key: &'a str) -> Cow<'a, str>;
+}
+
+impl<K, S> Localization for HashMap<K, String, S>
+where
+    K: Eq + Hash + Borrow<str>,
+    S: BuildHasher,
+{
+    fn localize<'a>(&'a self, key: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(self.get(key).map_or(key, |text| text.as_str()))
+    }
+}
+
+/// Localization, that returns the keys as is. It could be used when texts of dialogues are not localized.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NoLocalization;
+
+impl Localization for NoLocalization {
+    fn localize<'a>(&'a self, key: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(key)
+    }
+}
+
+/// Localized content of the current node of a dialogue.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DialogueLine {
+    /// Speaker of the line.
+    pub speaker: String,
+    /// Text of the line.
+    pub text: String,
+    /// Texts of the available choices. Their indices should be passed to [`DialoguePlayer::choose`].
+    pub choices: Vec<String>,
+}
+
+/// Plays a dialogue graph. The player stores only the state of a conversation (the graph and the current
+/// node), the game state is passed to its methods as a blackboard, so the player could be saved alongside
+/// other game entities.
+///
+/// Every method, that changes the current node, returns a list of custom events (see
+/// [`DialogueEvent::Custom`]) of the node and the choice (if any), that should be handled by the game.
+/// Other events are applied to the blackboard directly.
+#[derive(Debug, Default, Clone, Visit)]
+pub struct DialoguePlayer {
+    graph: Option<DialogueGraphResource>,
+    current: Option<u32>,
+}
+
+impl DialoguePlayer {
+    /// Creates new dialogue player for the given graph. Call [`Self::start`] to start the dialogue.
+    pub fn new(graph: DialogueGraphResource) -> Self {
+        Self {
+            graph: Some(graph),
+            current: None,
+        }
+    }
+
+    /// Returns the graph of the player.
+    pub fn graph(&self) -> Option<DialogueGraphResource> {
+        self.graph.clone()
+    }
+
+    /// Returns index of the current node of the dialogue, `None` means that the dialogue is not started or
+    /// has ended.
+    pub fn current_node(&self) -> Option<u32> {
+        self.current
+    }
+
+    /// Returns `true` if the dialogue is started and has not ended yet.
+    pub fn is_active(&self) -> bool {
+        self.current.is_some()
+    }
+
+    fn with_graph<F, R>(&self, func: F) -> Option<R>
+    where
+        F: FnOnce(&DialogueGraph) -> R,
+    {
+        let graph = self.graph.as_ref()?;
+        let state = graph.state();
+        if let ResourceStateRef::Ok(graph) = state.get() {
+            Some(func(graph))
+        } else {
+            None
+        }
+    }
+
+    /// Starts (or restarts) the dialogue from the first entry node with satisfied conditions. The dialogue
+    /// won't start if the graph is not loaded yet or if there's no such node.
+    pub fn start(&mut self, blackboard: &mut Blackboard) -> Vec<String> {
+        self.current = None;
+        let mut events = Vec::new();
+        if let Some(graph) = self.graph.clone() {
+            let state = graph.state();
+            if let ResourceStateRef::Ok(graph) = state.get() {
+                self.enter(graph, graph.entry(), blackboard, &mut events);
+            }
+        }
+        events
+    }
+
+    /// Stops the dialogue.
+    pub fn stop(&mut self) {
+        self.current = None;
+    }
+
+    /// Continues the dialogue from the current node to the next one. It does nothing if the current node
+    /// has available choices, use [`Self::choose`] instead. The dialogue ends if there's no next node
+    /// with satisfied conditions.
+    pub fn advance(&mut self, blackboard: &mut Blackboard) -> Vec<String> {
+        let mut events = Vec::new();
+        if let (Some(graph), Some(current)) = (self.graph.clone(), self.current) {
+            let state = graph.state();
+            if let ResourceStateRef::Ok(graph) = state.get() {
+                match graph.node(current) {
+                    Some(node) => {
+                        if available_choices(graph, current, blackboard).is_empty() {
+                            self.enter(graph, &node.next, blackboard, &mut events);
+                        }
+                    }
+                    None => self.current = None,
+                }
+            }
+        }
+        events
+    }
+
+    /// Selects a choice of the current node. The index is an index in the list of available choices (see
+    /// [`DialogueLine::choices`]). It does nothing if there's no such choice.
+    pub fn choose(&mut self, choice: usize, blackboard: &mut Blackboard) -> Vec<String> {
+        let mut events = Vec::new();
+        if let (Some(graph), Some(current)) = (self.graph.clone(), self.current) {
+            let state = graph.state();
+            if let ResourceStateRef::Ok(graph) = state.get() {
+                let choice = available_choices(graph, current, blackboard)
+                    .get(choice)
+                    .and_then(|index| graph.node(current)?.choices.get(*index));
+                if let Some(choice) = choice {
+                    apply_events(&choice.events, blackboard, &mut events);
+                    self.enter(graph, &choice.next, blackboard, &mut events);
+                }
+            }
+        }
+        events
+    }
+
+    fn enter(
+        &mut self,
+        graph: &DialogueGraph,
+        candidates: &[u32],
+        blackboard: &mut Blackboard,
+        events: &mut Vec<String>,
+    ) {
+        self.current = graph.select(candidates, blackboard);
+        if let Some(node) = self.current.and_then(|index| graph.node(index)) {
+            apply_events(&node.events, blackboard, events);
+        }
+    }
+
+    /// Returns localized content of the current node, `None` means that the dialogue is not active.
+    pub fn line(
+        &self,
+        blackboard: &Blackboard,
+        localization: &dyn Localization,
+    ) -> Option<DialogueLine> {
+        let current = self.current?;
+        self.with_graph(|graph| {
+            let node = graph.node(current)?;
+            Some(DialogueLine {
+                speaker: localization.localize(&node.speaker).into_owned(),
+                text: localization.localize(&node.text).into_owned(),
+                choices: available_choices(graph, current, blackboard)
+                    .into_iter()
+                    .map(|index| {
+                        localization
+                            .localize(&node.choices[index].text)
+                            .into_owned()
+                    })
+                    .collect(),
+            })
+        })
+        .flatten()
+    }
+}
+
+// Returns indices of the choices of the node, whose conditions are satisfied.
+fn available_choices(graph: &DialogueGraph, node: u32, blackboard: &Blackboard) -> Vec<usize> {
+    graph.node(node).map_or_else(Vec::new, |node| {
+        node.choices
+            .iter()
+            .enumerate()
+            .filter(|(_, choice)| {
+                choice
+                    .conditions
+                    .iter()
+                    .all(|condition| condition.is_satisfied(blackboard))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    })
+}
+
+fn apply_events(events: &[DialogueEvent], blackboard: &mut Blackboard, output: &mut Vec<String>) {
+    for event in events {
+        match event {
+            DialogueEvent::Set(key, value) => {
+                blackboard.set(key, BlackboardValue::from(value.clone()));
+            }
+            DialogueEvent::Remove(key) => {
+                blackboard.remove(key);
+            }
+            DialogueEvent::Custom(name) => output.push(name.clone()),
+        }
+    }
+}
+
+/// A set of widgets, that shows the current line of a dialogue. Any of the handles could be
+/// [`Handle::NONE`], such widgets are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct DialogueView {
+    /// A widget, that is visible only when a dialogue is active (usually a window or a border with all
+    /// other widgets of the view).
+    pub root: Handle<UiNode>,
+    /// A text widget for the speaker.
+    pub speaker: Handle<UiNode>,
+    /// A text widget for the text of the line.
+    pub text: Handle<UiNode>,
+    /// Buttons for choices. Buttons, that are not needed for the current line, are hidden. If a line has
+    /// more choices than buttons, extra choices are not shown.
+    pub choices: Vec<Handle<UiNode>>,
+}
+
+impl DialogueView {
+    /// Updates the widgets to show the given line, `None` hides the view.
+    pub fn sync(&self, line: Option<&DialogueLine>, ui: &UserInterface) {
+        send_visibility(ui, self.root, line.is_some());
+
+        let line = match line {
+            Some(line) => line,
+            None => return,
+        };
+
+        send_text(ui, self.speaker, &line.speaker);
+        send_text(ui, self.text, &line.text);
+
+        for (index, button) in self.choices.iter().enumerate() {
+            if button.is_none() {
+                continue;
+            }
+
+            match line.choices.get(index) {
+                Some(choice) => {
+                    ui.send_message(ButtonMessage::content(
+                        *button,
+                        MessageDirection::ToWidget,
+                        ButtonContent::text(choice),
+                    ));
+                    send_visibility(ui, *button, true);
+                }
+                None => send_visibility(ui, *button, false),
+            }
+        }
+    }
+
+    /// Returns index of the choice, if the message is a click on one of the choice buttons. The index
+    /// could be passed to [`DialoguePlayer::choose`] directly.
+    pub fn clicked_choice(&self, message: &UiMessage) -> Option<usize> {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.direction() == MessageDirection::FromWidget {
+                return self
+                    .choices
+                    .iter()
+                    .position(|button| button.is_some() && *button == message.destination());
+            }
+        }
+        None
+    }
+}
+
+fn send_visibility(ui: &UserInterface, widget: Handle<UiNode>, visibility: bool) {
+    if widget.is_some() {
+        ui.send_message(WidgetMessage::visibility(
+            widget,
+            MessageDirection::ToWidget,
+            visibility,
+        ));
+    }
+}
+
+fn send_text(ui: &UserInterface, widget: Handle<UiNode>, text: &str) {
+    if widget.is_some() {
+        ui.send_message(TextMessage::text(
+            widget,
+            MessageDirection::ToWidget,
+            text.to_owned(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::Resource,
+        resource::dialogue::{
+            DialogueChoice, DialogueCondition, DialogueEvent, DialogueGraph, DialogueNode,
+            DialogueValue,
+        },
+        utils::{
+            behavior::blackboard::Blackboard,
+            dialogue::{DialoguePlayer, NoLocalization},
+        },
+    };
+    use std::collections::HashMap;
+
+    fn create_graph() -> DialogueGraph {
+        let node = |name: &str, next: Vec<u32>| DialogueNode {
+            name: name.to_string(),
+            speaker: "merchant.name".to_string(),
+            text: format!("merchant.{name}"),
+            next,
+            ..Default::default()
+        };
+
+        let mut greeting = node("greeting", vec![]);
+        greeting.choices = vec![
+            DialogueChoice {
+                text: "merchant.buy".to_string(),
+                conditions: vec![DialogueCondition::GreaterThan("Gold".to_string(), 9.0)],
+                events: vec![
+                    DialogueEvent::Set("Gold".to_string(), DialogueValue::Integer(0)),
+                    DialogueEvent::Custom("GiveSword".to_string()),
+                ],
+                next: vec![1],
+            },
+            DialogueChoice {
+                text: "merchant.leave".to_string(),
+                next: vec![2],
+                ..Default::default()
+            },
+        ];
+
+        let mut thanks = node("thanks", vec![2]);
+        thanks.events = vec![DialogueEvent::Set(
+            "BoughtSword".to_string(),
+            DialogueValue::Bool(true),
+        )];
+
+        DialogueGraph::new(vec![greeting, thanks, node("bye", vec![])], vec![0]).unwrap()
+    }
+
+    #[test]
+    fn test_dialogue_player() {
+        let mut strings = HashMap::new();
+        strings.insert("merchant.name".to_string(), "Merchant".to_string());
+        strings.insert("merchant.buy".to_string(), "Buy a sword".to_string());
+
+        let mut blackboard = Blackboard::default();
+        let mut player = DialoguePlayer::new(Resource::new_ok(create_graph()));
+        assert!(player.start(&mut blackboard).is_empty());
+
+        // Not enough gold, so the only available choice is to leave.
+        let line = player.line(&blackboard, &strings).unwrap();
+        assert_eq!(line.speaker, "Merchant");
+        assert_eq!(line.text, "merchant.greeting");
+        assert_eq!(line.choices, vec!["merchant.leave".to_string()]);
+
+        // Advancing does nothing while there are available choices.
+        player.advance(&mut blackboard);
+        assert_eq!(player.current_node(), Some(0));
+
+        blackboard.set("Gold", 10i64);
+        let line = player.line(&blackboard, &NoLocalization).unwrap();
+        assert_eq!(line.choices.len(), 2);
+
+        assert_eq!(
+            player.choose(0, &mut blackboard),
+            vec!["GiveSword".to_string()]
+        );
+        assert_eq!(player.current_node(), Some(1));
+        assert_eq!(blackboard.get::<i64>("Gold"), Some(0));
+        assert_eq!(blackboard.get::<bool>("BoughtSword"), Some(true));
+
+        player.advance(&mut blackboard);
+        assert_eq!(player.current_node(), Some(2));
+        player.advance(&mut blackboard);
+        assert!(!player.is_active());
+        assert!(player.line(&blackboard, &strings).is_none());
+    }
+}
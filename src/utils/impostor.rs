@@ -0,0 +1,437 @@
+//! Impostors are flat, camera-facing representations of complex objects, that are used to render
+//! distant objects (trees of a dense forest, buildings of a city skyline, etc.) using just two
+//! triangles. See [`Impostor`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3, Vector4},
+        math::TriangleDefinition,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    material::{
+        shader::{SamplerFallback, ShaderResource, ShaderResourceExtension},
+        Material, PropertyValue, SharedMaterial,
+    },
+    renderer::{framework::error::FrameworkError, CapturedFrame, Renderer},
+    resource::texture::{
+        TextureKind, TextureMagnificationFilter, TextureMinificationFilter, TexturePixelKind,
+        TextureResource, TextureResourceExtension, TextureWrapMode,
+    },
+    scene::{
+        base::{BaseBuilder, LevelOfDetail, LodGroup},
+        camera::{CameraBuilder, OrthographicProjection, Projection},
+        graph::Graph,
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+            vertex::StaticVertex,
+            Mesh, MeshBuilder, RenderPath,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::fmt::{Display, Formatter};
+
+/// An error that may occur during impostor baking.
+#[derive(Debug)]
+pub enum ImpostorError {
+    /// The object does not have any visible geometry.
+    EmptyObject,
+    /// An error occurred during rendering.
+    Framework(FrameworkError),
+}
+
+impl Display for ImpostorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImpostorError::EmptyObject => {
+                write!(f, "The object does not have any visible geometry.")
+            }
+            ImpostorError::Framework(e) => {
+                write!(f, "Failed to render the object. Reason: {}", e)
+            }
+        }
+    }
+}
+
+impl From<FrameworkError> for ImpostorError {
+    fn from(e: FrameworkError) -> Self {
+        Self::Framework(e)
+    }
+}
+
+/// Settings of impostor baking.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ImpostorSettings {
+    /// Amount of views of the object, evenly distributed around its vertical axis. The more views
+    /// there are, the less noticeable is switching between them, but the larger the atlas is.
+    pub view_count: u32,
+    /// Size of a single view in the atlas in pixels.
+    pub view_size: u32,
+    /// Amount of pixels, that transparent pixels near the edges of the object will be filled with
+    /// the colors of neighbour pixels. It prevents dark halos, when the atlas is filtered.
+    pub padding: u32,
+}
+
+impl Default for ImpostorSettings {
+    fn default() -> Self {
+        Self {
+            view_count: 16,
+            view_size: 256,
+            padding: 4,
+        }
+    }
+}
+
+/// Impostor is a set of views of an object baked from different angles around its vertical axis
+/// into texture atlases (albedo and normals). At runtime, the impostor is a single quad, that
+/// rotates around vertical axis to face the camera and shows the view baked from the closest
+/// angle. Baked normals allow impostors to be lit just like the original objects.
+///
+/// Impostors are baked using [`Impostor::bake`] and then could be attached to their objects using
+/// [`Impostor::instantiate`], which switches the object to its impostor at a given distance.
+///
+/// # Limitations
+///
+/// - Impostors do not cast shadows.
+/// - Views are baked only around vertical axis, so the impostors look good only when viewed from
+/// the sides, which is fine for trees and buildings, but not for flying objects.
+/// - Animation of the original object is not reflected on impostors.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     core::pool::Handle,
+///     renderer::Renderer,
+///     scene::{node::Node, Scene},
+///     utils::impostor::{Impostor, ImpostorSettings},
+/// };
+///
+/// fn make_impostor(renderer: &mut Renderer, scene: &mut Scene, tree: Handle<Node>) {
+///     let impostor = Impostor::bake(renderer, &scene.graph, tree, &ImpostorSettings::default())
+///         .unwrap();
+///     // Trees further than 20% of the camera's depth range will be rendered as impostors.
+///     impostor.instantiate(&mut scene.graph, tree, 0.2);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Impostor {
+    /// Albedo atlas, alpha channel defines coverage.
+    pub albedo: TextureResource,
+    /// Atlas of normals in local space of the object.
+    pub normal: TextureResource,
+    /// Amount of views in the atlases.
+    pub view_count: u32,
+    /// Amount of columns in the atlases.
+    pub columns: u32,
+    /// Center of the object in its local space.
+    pub center: Vector3<f32>,
+    /// Half of the size of the quad of the impostor.
+    pub half_size: f32,
+}
+
+// Fills transparent pixels near the edges with average color of neighbour opaque pixels,
+// alpha channel is kept intact. Each iteration expands the colors by one pixel.
+fn dilate(albedo: &mut [u8], normal: &mut [u8], width: usize, height: usize, iterations: u32) {
+    let mut filled = albedo
+        .chunks_exact(4)
+        .map(|pixel| pixel[3] > 0)
+        .collect::<Vec<_>>();
+
+    for _ in 0..iterations {
+        let mut changes = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if filled[y * width + x] {
+                    continue;
+                }
+
+                let mut count = 0;
+                let mut color = [0u32; 3];
+                let mut direction = [0u32; 3];
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32 {
+                        continue;
+                    }
+                    let index = ny as usize * width + nx as usize;
+                    if filled[index] {
+                        for i in 0..3 {
+                            color[i] += albedo[index * 4 + i] as u32;
+                            direction[i] += normal[index * 4 + i] as u32;
+                        }
+                        count += 1;
+                    }
+                }
+
+                if count > 0 {
+                    changes.push((
+                        y * width + x,
+                        color.map(|c| (c / count) as u8),
+                        direction.map(|c| (c / count) as u8),
+                    ));
+                }
+            }
+        }
+
+        if changes.is_empty() {
+            break;
+        }
+
+        for (index, color, direction) in changes {
+            albedo[index * 4..index * 4 + 3].copy_from_slice(&color);
+            normal[index * 4..index * 4 + 3].copy_from_slice(&direction);
+            filled[index] = true;
+        }
+    }
+}
+
+fn make_atlas_texture(width: u32, height: u32, pixels: Vec<u8>) -> TextureResource {
+    let texture = TextureResource::from_bytes(
+        TextureKind::Rectangle { width, height },
+        TexturePixelKind::RGBA8,
+        pixels,
+        true,
+    )
+    .unwrap();
+
+    let mut data = texture.data_ref();
+    data.set_minification_filter(TextureMinificationFilter::Linear);
+    data.set_magnification_filter(TextureMagnificationFilter::Linear);
+    data.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+    data.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+    drop(data);
+
+    texture
+}
+
+impl Impostor {
+    /// Bakes an impostor of the given object (including all its descendants). The object is rendered
+    /// off-screen by the given renderer, so this method should not be called during rendering, and
+    /// it stalls the GPU pipeline until the views are rendered.
+    pub fn bake(
+        renderer: &mut Renderer,
+        graph: &Graph,
+        root: Handle<Node>,
+        settings: &ImpostorSettings,
+    ) -> Result<Self, ImpostorError> {
+        let view_count = settings.view_count.max(1);
+        let view_size = settings.view_size.max(1);
+
+        // Views are baked in local space of the object, so the impostor could be attached to it.
+        let mut scene = Scene::new();
+        let (copy, _) = graph.copy_node(root, &mut scene.graph, &mut |_, _| true);
+        scene.graph[copy].set_lod_group(None);
+        scene.graph[copy]
+            .local_transform_mut()
+            .set_position(Default::default())
+            .set_rotation(UnitQuaternion::identity())
+            .set_scale(Vector3::repeat(1.0));
+        scene.graph.update_hierarchical_data();
+
+        let aabb = scene
+            .graph
+            .aabb_of_descendants(copy)
+            .filter(|aabb| !aabb.is_invalid_or_degenerate())
+            .ok_or(ImpostorError::EmptyObject)?;
+        let center = aabb.center();
+        let half_extents = aabb.half_extents();
+        // The quad rotates around vertical axis, so it must contain the object from any angle.
+        let half_size = half_extents
+            .y
+            .max(Vector2::new(half_extents.x, half_extents.z).norm());
+
+        let camera = CameraBuilder::new(BaseBuilder::new())
+            .with_projection(Projection::Orthographic(OrthographicProjection {
+                z_near: 0.0,
+                z_far: 4.0 * half_size,
+                vertical_size: half_size,
+            }))
+            .build(&mut scene.graph);
+
+        let columns = (view_count as f32).sqrt().ceil() as u32;
+        let rows = (view_count + columns - 1) / columns;
+        let atlas_width = (columns * view_size) as usize;
+        let atlas_height = (rows * view_size) as usize;
+        let mut albedo = vec![0u8; atlas_width * atlas_height * 4];
+        let mut normal = vec![0u8; atlas_width * atlas_height * 4];
+
+        for view in 0..view_count {
+            let angle = view as f32 / view_count as f32 * std::f32::consts::TAU;
+            let direction = Vector3::new(angle.sin(), 0.0, angle.cos());
+
+            scene.graph[camera]
+                .local_transform_mut()
+                .set_position(center + direction.scale(2.0 * half_size))
+                // Camera looks along its local Z axis, turn it towards the object.
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    angle + std::f32::consts::PI,
+                ));
+            scene.graph.update_hierarchical_data();
+            scene.graph[camera]
+                .as_camera_mut()
+                .calculate_matrices(Vector2::new(view_size as f32, view_size as f32));
+
+            let capture = renderer.render_scene_gbuffer(
+                &scene,
+                camera,
+                Vector2::new(view_size, view_size),
+            )?;
+
+            let column = (view % columns) as usize;
+            let row = (view / columns) as usize;
+            let copy_view = |frame: &CapturedFrame, atlas: &mut [u8]| {
+                let row_size = view_size as usize * 4;
+                for (y, src) in frame.pixels.chunks_exact(row_size).enumerate() {
+                    let offset = ((row * view_size as usize + y) * atlas_width
+                        + column * view_size as usize)
+                        * 4;
+                    atlas[offset..offset + row_size].copy_from_slice(src);
+                }
+            };
+            copy_view(&capture.diffuse, &mut albedo);
+            copy_view(&capture.normal, &mut normal);
+        }
+
+        dilate(
+            &mut albedo,
+            &mut normal,
+            atlas_width,
+            atlas_height,
+            settings.padding,
+        );
+
+        Ok(Self {
+            albedo: make_atlas_texture(atlas_width as u32, atlas_height as u32, albedo),
+            normal: make_atlas_texture(atlas_width as u32, atlas_height as u32, normal),
+            view_count,
+            columns,
+            center,
+            half_size,
+        })
+    }
+
+    /// Creates a material, that renders the impostor. The material uses
+    /// [`ShaderResourceExtension::standard_impostor`] shader.
+    pub fn make_material(&self) -> Material {
+        let mut material = Material::from_shader(ShaderResource::standard_impostor(), None);
+
+        for (name, value) in [
+            (
+                "diffuseTexture",
+                PropertyValue::Sampler {
+                    value: Some(self.albedo.clone()),
+                    fallback: SamplerFallback::White,
+                },
+            ),
+            (
+                "normalTexture",
+                PropertyValue::Sampler {
+                    value: Some(self.normal.clone()),
+                    fallback: SamplerFallback::Normal,
+                },
+            ),
+            ("impostorCenter", PropertyValue::Vector3(self.center)),
+            ("viewCount", PropertyValue::UInt(self.view_count)),
+            ("atlasColumns", PropertyValue::UInt(self.columns)),
+        ] {
+            material
+                .set_property(&ImmutableString::new(name), value)
+                .expect("Impostor shader must have the property!");
+        }
+
+        material
+    }
+
+    /// Creates a quad, that is used to render the impostor. It is placed in the vertical plane,
+    /// the shader rotates it towards the camera.
+    pub fn make_surface_data(&self) -> SurfaceData {
+        let vertex = |x: f32, y: f32, u: f32, v: f32| StaticVertex {
+            position: self.center + Vector3::new(x, y, 0.0).scale(self.half_size),
+            tex_coord: Vector2::new(u, v),
+            normal: Vector3::z(),
+            tangent: Vector4::default(),
+        };
+
+        let vertices = vec![
+            vertex(-1.0, -1.0, 0.0, 1.0),
+            vertex(1.0, -1.0, 1.0, 1.0),
+            vertex(1.0, 1.0, 1.0, 0.0),
+            vertex(-1.0, 1.0, 0.0, 0.0),
+        ];
+
+        SurfaceData::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(vec![
+                TriangleDefinition([0, 1, 2]),
+                TriangleDefinition([0, 2, 3]),
+            ]),
+            true,
+        )
+    }
+
+    /// Attaches the impostor to the object, it was baked for. Every mesh of the object will be
+    /// replaced with the impostor, when normalized distance to the camera (see [`LevelOfDetail`])
+    /// is larger than `switch_distance`. This method replaces existing LOD group of the object.
+    /// Returns a handle of the impostor node.
+    pub fn instantiate(
+        &self,
+        graph: &mut Graph,
+        root: Handle<Node>,
+        switch_distance: f32,
+    ) -> Handle<Node> {
+        let meshes = graph
+            .traverse_handle_iter(root)
+            .filter(|handle| graph[*handle].cast::<Mesh>().is_some())
+            .collect::<Vec<_>>();
+
+        let impostor = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_name("Impostor")
+                .with_cast_shadows(false)
+                .with_local_transform(TransformBuilder::new().build()),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+            self.make_surface_data(),
+        ))
+        .with_material(SharedMaterial::new(self.make_material()))
+        .build()])
+        .with_render_path(RenderPath::Deferred)
+        .build(graph);
+
+        graph.link_nodes(impostor, root);
+
+        graph[root].set_lod_group(Some(LodGroup {
+            levels: vec![
+                LevelOfDetail::new(0.0, switch_distance, meshes),
+                LevelOfDetail::new(switch_distance, 1.0, vec![impostor]),
+            ],
+        }));
+
+        impostor
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::dilate;
+
+    #[test]
+    fn test_impostor_dilation() {
+        // 3x1 image, where only the left pixel is opaque.
+        let mut albedo = vec![10, 20, 30, 255, 0, 0, 0, 0, 0, 0, 0, 0];
+        let mut normal = vec![128, 255, 128, 255, 0, 0, 0, 0, 0, 0, 0, 0];
+
+        dilate(&mut albedo, &mut normal, 3, 1, 1);
+        assert_eq!(albedo, [10, 20, 30, 255, 10, 20, 30, 0, 0, 0, 0, 0]);
+        assert_eq!(normal, [128, 255, 128, 255, 128, 255, 128, 0, 0, 0, 0, 0]);
+
+        dilate(&mut albedo, &mut normal, 3, 1, 4);
+        assert_eq!(albedo, [10, 20, 30, 255, 10, 20, 30, 0, 10, 20, 30, 0]);
+    }
+}
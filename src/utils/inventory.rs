@@ -0,0 +1,785 @@
+//! Inventories of items. [`Inventory`] is a container with a fixed amount of slots for stacks of items
+//! and a set of named equipment slots. It could restrict the total weight and the kinds of stored items.
+//! Every change of an inventory is reported as an [`InventoryEvent`], so user interface and game logic
+//! could react to it. See [`crate::resource::item`] for the description of items.
+//!
+//! Inventories are serializable, so they could be stored in scripts or plugins and saved alongside other
+//! game entities. [`InventoryView`] connects an inventory with drag'n'drop of the user interface.
+//!
+//! ```rust
+//! use fyrox::{
+//!     resource::item::ItemResource,
+//!     utils::inventory::{Inventory, InventoryEvent},
+//! };
+//!
+//! fn loot(inventory: &mut Inventory, coins: &ItemResource, sword: &ItemResource) {
+//!     // Items, that don't fit, should be left on the ground.
+//!     let _leftover = inventory.add(coins, 250);
+//!     if inventory.add(sword, 1) == 0 {
+//!         if let Some(slot) = inventory.find(sword) {
+//!             inventory.equip(slot, "MainHand").ok();
+//!         }
+//!     }
+//!
+//!     while let Some(event) = inventory.pop_event() {
+//!         if let InventoryEvent::Equipped { slot, .. } = event {
+//!             println!("Equipped to {slot}");
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    asset::ResourceStateRef,
+    core::{pool::Handle, visitor::prelude::*},
+    gui::{
+        message::{MessageDirection, UiMessage},
+        widget::WidgetMessage,
+        UiNode,
+    },
+    resource::item::{Item, ItemResource},
+};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+};
+
+/// An error that may occur during inventory operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryError {
+    /// There's no slot with the given index.
+    InvalidSlot(usize),
+
+    /// The slot with the given index is empty.
+    EmptySlot(usize),
+
+    /// There's no equipment slot with the given name.
+    UnknownEquipmentSlot(String),
+
+    /// The equipment slot with the given name is empty.
+    EmptyEquipmentSlot(String),
+
+    /// An item does not fit the equipment slot with the given name.
+    NotEquippable(String),
+
+    /// There's no free slot in the inventory.
+    NoFreeSlot,
+}
+
+impl Display for InventoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryError::InvalidSlot(slot) => write!(f, "There's no slot {slot}!"),
+            InventoryError::EmptySlot(slot) => write!(f, "Slot {slot} is empty!"),
+            InventoryError::UnknownEquipmentSlot(name) => {
+                write!(f, "There's no equipment slot {name}!")
+            }
+            InventoryError::EmptyEquipmentSlot(name) => {
+                write!(f, "Equipment slot {name} is empty!")
+            }
+            InventoryError::NotEquippable(name) => {
+                write!(f, "The item does not fit equipment slot {name}!")
+            }
+            InventoryError::NoFreeSlot => write!(f, "There's no free slot in the inventory!"),
+        }
+    }
+}
+
+/// A stack of items of the same kind.
+#[derive(Debug, Clone, Default, PartialEq, Visit)]
+pub struct ItemStack {
+    /// Kind of the items.
+    pub item: ItemResource,
+    /// Amount of the items.
+    pub count: u32,
+}
+
+impl ItemStack {
+    /// Creates new stack of the given amount of items.
+    pub fn new(item: ItemResource, count: u32) -> Self {
+        Self { item, count }
+    }
+}
+
+/// A change of an inventory. Moving items between slots is reported as removal followed by addition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryEvent {
+    /// Items were added to a slot.
+    Added {
+        /// Index of the slot.
+        slot: usize,
+        /// Kind of the items.
+        item: ItemResource,
+        /// Amount of the added items.
+        count: u32,
+    },
+    /// Items were removed from a slot.
+    Removed {
+        /// Index of the slot.
+        slot: usize,
+        /// Kind of the items.
+        item: ItemResource,
+        /// Amount of the removed items.
+        count: u32,
+    },
+    /// A stack of items was put into an equipment slot.
+    Equipped {
+        /// Name of the equipment slot.
+        slot: String,
+        /// Kind of the items.
+        item: ItemResource,
+    },
+    /// A stack of items was taken from an equipment slot.
+    Unequipped {
+        /// Name of the equipment slot.
+        slot: String,
+        /// Kind of the items.
+        item: ItemResource,
+    },
+}
+
+/// A location of a stack in an inventory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryLocation {
+    /// A slot with the given index.
+    Slot(usize),
+    /// An equipment slot with the given name.
+    Equipment(String),
+}
+
+/// A named slot for an equipped stack of items, for example "Head" or "MainHand".
+#[derive(Debug, Clone, Default, PartialEq, Visit)]
+pub struct EquipmentSlot {
+    name: String,
+    stack: Option<ItemStack>,
+}
+
+impl EquipmentSlot {
+    /// Returns name of the slot.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns equipped stack (if any).
+    pub fn stack(&self) -> Option<&ItemStack> {
+        self.stack.as_ref()
+    }
+}
+
+// Items, that are not loaded yet, are treated as non-stackable, weightless items without tags.
+fn item_property<R>(item: &ItemResource, default: R, func: impl FnOnce(&Item) -> R) -> R {
+    let state = item.state();
+    match state.get() {
+        ResourceStateRef::Ok(item) => func(item),
+        _ => default,
+    }
+}
+
+fn max_stack(item: &ItemResource) -> u32 {
+    item_property(item, 1, Item::max_stack)
+}
+
+fn unit_weight(item: &ItemResource) -> f32 {
+    item_property(item, 0.0, Item::weight)
+}
+
+fn fits_equipment_slot(item: &ItemResource, slot: &str) -> bool {
+    item_property(item, false, |item| item.fits_equipment_slot(slot))
+}
+
+/// See module docs.
+#[derive(Debug, Clone, Default, Visit)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+    max_weight: Option<f32>,
+    allowed_tags: Vec<String>,
+    equipment: Vec<EquipmentSlot>,
+    #[visit(skip)]
+    events: VecDeque<InventoryEvent>,
+}
+
+impl Inventory {
+    /// Creates new inventory with the given amount of slots.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: vec![None; capacity],
+            ..Default::default()
+        }
+    }
+
+    /// Sets maximum total weight of the items in the inventory (including equipped ones). `None` means
+    /// that the weight is not limited.
+    pub fn with_max_weight(mut self, max_weight: Option<f32>) -> Self {
+        self.max_weight = max_weight;
+        self
+    }
+
+    /// Restricts the kinds of items, that could be added to the inventory, only items with any of the
+    /// given tags will be accepted. Empty list means that any items are accepted.
+    pub fn with_allowed_tags(mut self, tags: Vec<String>) -> Self {
+        self.allowed_tags = tags;
+        self
+    }
+
+    /// Adds new equipment slot with the given name.
+    pub fn with_equipment_slot(mut self, name: &str) -> Self {
+        self.add_equipment_slot(name);
+        self
+    }
+
+    /// Adds new equipment slot with the given name, does nothing if there's such slot already.
+    pub fn add_equipment_slot(&mut self, name: &str) {
+        if self.equipment_index(name).is_err() {
+            self.equipment.push(EquipmentSlot {
+                name: name.to_owned(),
+                stack: None,
+            });
+        }
+    }
+
+    /// Returns amount of slots of the inventory.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns all slots of the inventory.
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    /// Returns a stack in the slot with the given index.
+    pub fn slot(&self, slot: usize) -> Option<&ItemStack> {
+        self.slots.get(slot).and_then(|stack| stack.as_ref())
+    }
+
+    /// Returns all equipment slots of the inventory.
+    pub fn equipment(&self) -> &[EquipmentSlot] {
+        &self.equipment
+    }
+
+    /// Returns a stack in the equipment slot with the given name.
+    pub fn equipped(&self, name: &str) -> Option<&ItemStack> {
+        self.equipment
+            .iter()
+            .find(|slot| slot.name == name)
+            .and_then(|slot| slot.stack.as_ref())
+    }
+
+    /// Returns maximum total weight of the items in the inventory.
+    pub fn max_weight(&self) -> Option<f32> {
+        self.max_weight
+    }
+
+    /// Returns total weight of the items in the inventory, including equipped ones.
+    pub fn weight(&self) -> f32 {
+        self.slots
+            .iter()
+            .flatten()
+            .chain(self.equipment.iter().filter_map(|slot| slot.stack.as_ref()))
+            .map(|stack| unit_weight(&stack.item) * stack.count as f32)
+            .sum()
+    }
+
+    /// Returns `true` if the inventory accepts the items of the given kind.
+    pub fn accepts(&self, item: &ItemResource) -> bool {
+        self.allowed_tags.is_empty()
+            || item_property(item, false, |item| {
+                self.allowed_tags.iter().any(|tag| item.has_tag(tag))
+            })
+    }
+
+    /// Returns total amount of items of the given kind in the slots of the inventory (equipped items
+    /// are not counted).
+    pub fn count(&self, item: &ItemResource) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == *item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    /// Returns index of the first slot with the items of the given kind.
+    pub fn find(&self, item: &ItemResource) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|stack| stack.as_ref().map_or(false, |stack| stack.item == *item))
+    }
+
+    /// Adds the given amount of items to the inventory. Existing stacks of the same items are filled
+    /// first, then the items are put into empty slots. Returns the amount of items, that didn't fit
+    /// into the inventory (because of the amount of free slots, weight limit or allowed tags).
+    pub fn add(&mut self, item: &ItemResource, count: u32) -> u32 {
+        if !self.accepts(item) {
+            return count;
+        }
+
+        let weight = unit_weight(item);
+        let accepted = match self.max_weight {
+            Some(max_weight) if weight > 0.0 => {
+                count.min(((max_weight - self.weight()) / weight).max(0.0).floor() as u32)
+            }
+            _ => count,
+        };
+
+        let limit = max_stack(item);
+        let mut remaining = accepted;
+        for (slot, entry) in self.slots.iter_mut().enumerate() {
+            if remaining == 0 {
+                break;
+            }
+            if let Some(stack) = entry {
+                if stack.item == *item && stack.count < limit {
+                    let amount = remaining.min(limit - stack.count);
+                    stack.count += amount;
+                    remaining -= amount;
+                    self.events.push_back(InventoryEvent::Added {
+                        slot,
+                        item: item.clone(),
+                        count: amount,
+                    });
+                }
+            }
+        }
+
+        for (slot, entry) in self.slots.iter_mut().enumerate() {
+            if remaining == 0 {
+                break;
+            }
+            if entry.is_none() {
+                let amount = remaining.min(limit);
+                *entry = Some(ItemStack::new(item.clone(), amount));
+                remaining -= amount;
+                self.events.push_back(InventoryEvent::Added {
+                    slot,
+                    item: item.clone(),
+                    count: amount,
+                });
+            }
+        }
+
+        count - accepted + remaining
+    }
+
+    /// Removes up to the given amount of items of the given kind from the slots of the inventory.
+    /// Returns the amount of removed items.
+    pub fn remove(&mut self, item: &ItemResource, count: u32) -> u32 {
+        let mut remaining = count;
+        for (slot, entry) in self.slots.iter_mut().enumerate() {
+            if remaining == 0 {
+                break;
+            }
+            if let Some(stack) = entry {
+                if stack.item == *item {
+                    let amount = remaining.min(stack.count);
+                    stack.count -= amount;
+                    remaining -= amount;
+                    if stack.count == 0 {
+                        *entry = None;
+                    }
+                    self.events.push_back(InventoryEvent::Removed {
+                        slot,
+                        item: item.clone(),
+                        count: amount,
+                    });
+                }
+            }
+        }
+        count - remaining
+    }
+
+    /// Takes up to the given amount of items from the slot with the given index.
+    pub fn take(&mut self, slot: usize, count: u32) -> Result<ItemStack, InventoryError> {
+        let entry = self
+            .slots
+            .get_mut(slot)
+            .ok_or(InventoryError::InvalidSlot(slot))?;
+        let stack = entry.as_mut().ok_or(InventoryError::EmptySlot(slot))?;
+        let amount = count.min(stack.count);
+        stack.count -= amount;
+        let taken = ItemStack::new(stack.item.clone(), amount);
+        if stack.count == 0 {
+            *entry = None;
+        }
+        self.events.push_back(InventoryEvent::Removed {
+            slot,
+            item: taken.item.clone(),
+            count: amount,
+        });
+        Ok(taken)
+    }
+
+    /// Moves a stack from one slot to another. If the target slot contains the same items, the stacks are
+    /// merged (the rest of the items stays in the source slot), otherwise the stacks are swapped.
+    pub fn move_stack(&mut self, from: usize, to: usize) -> Result<(), InventoryError> {
+        self.check_slot(from)?;
+        self.check_slot(to)?;
+        if self.slots[from].is_none() {
+            return Err(InventoryError::EmptySlot(from));
+        }
+        if from == to {
+            return Ok(());
+        }
+
+        let source = self.take_stack(from).unwrap();
+        match self.take_stack(to) {
+            None => self.put_stack(to, source),
+            Some(mut target) if target.item == source.item => {
+                let limit = max_stack(&target.item);
+                let amount = source.count.min(limit.saturating_sub(target.count));
+                target.count += amount;
+                self.put_stack(to, target);
+                if source.count > amount {
+                    self.put_stack(from, ItemStack::new(source.item, source.count - amount));
+                }
+            }
+            Some(target) => {
+                self.put_stack(to, source);
+                self.put_stack(from, target);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Equips a stack from the slot with the given index. Previously equipped stack (if any) is put into
+    /// the slot.
+    pub fn equip(&mut self, slot: usize, equipment: &str) -> Result<(), InventoryError> {
+        let index = self.equipment_index(equipment)?;
+        self.check_slot(slot)?;
+        match self.slots[slot] {
+            Some(ref stack) if !fits_equipment_slot(&stack.item, equipment) => {
+                return Err(InventoryError::NotEquippable(equipment.to_owned()))
+            }
+            Some(_) => (),
+            None => return Err(InventoryError::EmptySlot(slot)),
+        }
+
+        let stack = self.take_stack(slot).unwrap();
+        if let Some(previous) = self.take_equipment(index) {
+            self.put_stack(slot, previous);
+        }
+        self.put_equipment(index, stack);
+
+        Ok(())
+    }
+
+    /// Puts the stack from the equipment slot with the given name into the first free slot of the
+    /// inventory, returns the index of the slot.
+    pub fn unequip(&mut self, equipment: &str) -> Result<usize, InventoryError> {
+        let index = self.equipment_index(equipment)?;
+        if self.equipment[index].stack.is_none() {
+            return Err(InventoryError::EmptyEquipmentSlot(equipment.to_owned()));
+        }
+        let slot = self
+            .slots
+            .iter()
+            .position(|stack| stack.is_none())
+            .ok_or(InventoryError::NoFreeSlot)?;
+
+        let stack = self.take_equipment(index).unwrap();
+        self.put_stack(slot, stack);
+
+        Ok(slot)
+    }
+
+    /// Moves a stack between any two locations of the inventory, it is a generalized version of
+    /// [`Self::move_stack`], [`Self::equip`] and [`Self::unequip`]. Stacks in the locations are swapped
+    /// if both are occupied.
+    pub fn transfer(
+        &mut self,
+        from: &InventoryLocation,
+        to: &InventoryLocation,
+    ) -> Result<(), InventoryError> {
+        match (from, to) {
+            (InventoryLocation::Slot(from), InventoryLocation::Slot(to)) => {
+                self.move_stack(*from, *to)
+            }
+            (InventoryLocation::Slot(slot), InventoryLocation::Equipment(equipment)) => {
+                self.equip(*slot, equipment)
+            }
+            (InventoryLocation::Equipment(equipment), InventoryLocation::Slot(slot)) => {
+                let index = self.equipment_index(equipment)?;
+                self.check_slot(*slot)?;
+                if self.equipment[index].stack.is_none() {
+                    Err(InventoryError::EmptyEquipmentSlot(equipment.clone()))
+                } else if self.slots[*slot].is_some() {
+                    self.equip(*slot, equipment)
+                } else {
+                    let stack = self.take_equipment(index).unwrap();
+                    self.put_stack(*slot, stack);
+                    Ok(())
+                }
+            }
+            (InventoryLocation::Equipment(from), InventoryLocation::Equipment(to)) => {
+                self.swap_equipment(from, to)
+            }
+        }
+    }
+
+    fn swap_equipment(&mut self, from: &str, to: &str) -> Result<(), InventoryError> {
+        let source_index = self.equipment_index(from)?;
+        let target_index = self.equipment_index(to)?;
+        let source = self.equipment[source_index]
+            .stack
+            .as_ref()
+            .ok_or_else(|| InventoryError::EmptyEquipmentSlot(from.to_owned()))?;
+        if source_index == target_index {
+            return Ok(());
+        }
+        if !fits_equipment_slot(&source.item, to) {
+            return Err(InventoryError::NotEquippable(to.to_owned()));
+        }
+        if let Some(ref target) = self.equipment[target_index].stack {
+            if !fits_equipment_slot(&target.item, from) {
+                return Err(InventoryError::NotEquippable(from.to_owned()));
+            }
+        }
+
+        let source = self.take_equipment(source_index).unwrap();
+        if let Some(target) = self.take_equipment(target_index) {
+            self.put_equipment(source_index, target);
+        }
+        self.put_equipment(target_index, source);
+
+        Ok(())
+    }
+
+    /// Pops inventory event from internal queue.
+    pub fn pop_event(&mut self) -> Option<InventoryEvent> {
+        self.events.pop_front()
+    }
+
+    fn check_slot(&self, slot: usize) -> Result<(), InventoryError> {
+        if slot < self.slots.len() {
+            Ok(())
+        } else {
+            Err(InventoryError::InvalidSlot(slot))
+        }
+    }
+
+    fn equipment_index(&self, name: &str) -> Result<usize, InventoryError> {
+        self.equipment
+            .iter()
+            .position(|slot| slot.name == name)
+            .ok_or_else(|| InventoryError::UnknownEquipmentSlot(name.to_owned()))
+    }
+
+    fn take_stack(&mut self, slot: usize) -> Option<ItemStack> {
+        let stack = self.slots[slot].take()?;
+        self.events.push_back(InventoryEvent::Removed {
+            slot,
+            item: stack.item.clone(),
+            count: stack.count,
+        });
+        Some(stack)
+    }
+
+    fn put_stack(&mut self, slot: usize, stack: ItemStack) {
+        self.events.push_back(InventoryEvent::Added {
+            slot,
+            item: stack.item.clone(),
+            count: stack.count,
+        });
+        self.slots[slot] = Some(stack);
+    }
+
+    fn take_equipment(&mut self, index: usize) -> Option<ItemStack> {
+        let equipment = &mut self.equipment[index];
+        let stack = equipment.stack.take()?;
+        self.events.push_back(InventoryEvent::Unequipped {
+            slot: equipment.name.clone(),
+            item: stack.item.clone(),
+        });
+        Some(stack)
+    }
+
+    fn put_equipment(&mut self, index: usize, stack: ItemStack) {
+        let equipment = &mut self.equipment[index];
+        self.events.push_back(InventoryEvent::Equipped {
+            slot: equipment.name.clone(),
+            item: stack.item.clone(),
+        });
+        equipment.stack = Some(stack);
+    }
+}
+
+/// Maps widgets of a user interface to the locations of an inventory, so drag'n'drop of the widgets could
+/// be converted to transfers of items. Slot widgets must allow dragging and dropping (see
+/// [`crate::gui::widget::WidgetBuilder::with_allow_drag`] and
+/// [`crate::gui::widget::WidgetBuilder::with_allow_drop`]).
+#[derive(Clone, Debug, Default)]
+pub struct InventoryView {
+    /// Widgets of the slots, the index of a widget is the index of its slot.
+    pub slots: Vec<Handle<UiNode>>,
+    /// Widgets of the equipment slots and the names of the slots.
+    pub equipment: Vec<(String, Handle<UiNode>)>,
+}
+
+impl InventoryView {
+    /// Returns a location of the inventory, that corresponds to the given widget.
+    pub fn location(&self, widget: Handle<UiNode>) -> Option<InventoryLocation> {
+        if widget.is_none() {
+            return None;
+        }
+
+        if let Some(slot) = self.slots.iter().position(|slot| *slot == widget) {
+            return Some(InventoryLocation::Slot(slot));
+        }
+
+        self.equipment
+            .iter()
+            .find(|(_, slot)| *slot == widget)
+            .map(|(name, _)| InventoryLocation::Equipment(name.clone()))
+    }
+
+    /// Returns source and target locations, if the message is a drop of a slot widget onto another slot
+    /// widget. The locations could be passed to [`Inventory::transfer`] directly.
+    pub fn dropped(&self, message: &UiMessage) -> Option<(InventoryLocation, InventoryLocation)> {
+        if let Some(WidgetMessage::Drop(dragged)) = message.data() {
+            if message.direction() == MessageDirection::FromWidget {
+                return Some((
+                    self.location(*dragged)?,
+                    self.location(message.destination())?,
+                ));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::Resource,
+        resource::item::{Item, ItemDefinition, ItemResource},
+        utils::inventory::{
+            Inventory, InventoryError, InventoryEvent, InventoryLocation, ItemStack,
+        },
+    };
+
+    fn create_item(definition: ItemDefinition) -> ItemResource {
+        Resource::new_ok(Item::new(definition).unwrap())
+    }
+
+    fn coins() -> ItemResource {
+        create_item(ItemDefinition {
+            name: "Coin".to_string(),
+            max_stack: 100,
+            weight: 0.25,
+            tags: vec!["valuable".to_string()],
+            ..Default::default()
+        })
+    }
+
+    fn sword() -> ItemResource {
+        create_item(ItemDefinition {
+            name: "Sword".to_string(),
+            weight: 3.0,
+            equipment_slots: vec!["MainHand".to_string(), "OffHand".to_string()],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_inventory_stacking() {
+        let coins = coins();
+        let sword = sword();
+        let mut inventory = Inventory::new(3);
+
+        assert_eq!(inventory.add(&coins, 150), 0);
+        assert_eq!(inventory.slot(0).unwrap().count, 100);
+        assert_eq!(inventory.slot(1).unwrap().count, 50);
+        assert_eq!(inventory.add(&sword, 2), 1);
+        assert_eq!(inventory.add(&coins, 70), 20);
+        assert_eq!(inventory.count(&coins), 200);
+
+        assert_eq!(inventory.remove(&coins, 120), 120);
+        assert_eq!(inventory.count(&coins), 80);
+        assert!(inventory.slot(0).is_none());
+
+        let taken = inventory.take(1, 30).unwrap();
+        assert_eq!(taken, ItemStack::new(coins.clone(), 30));
+        assert_eq!(inventory.take(0, 1), Err(InventoryError::EmptySlot(0)));
+        assert_eq!(inventory.take(5, 1), Err(InventoryError::InvalidSlot(5)));
+    }
+
+    #[test]
+    fn test_inventory_restrictions() {
+        let coins = coins();
+        let sword = sword();
+
+        let mut wallet = Inventory::new(4).with_allowed_tags(vec!["valuable".to_string()]);
+        assert_eq!(wallet.add(&sword, 1), 1);
+        assert_eq!(wallet.add(&coins, 10), 0);
+
+        let mut backpack = Inventory::new(4).with_max_weight(Some(7.0));
+        assert_eq!(backpack.add(&sword, 3), 1);
+        assert_eq!(backpack.weight(), 6.0);
+        assert_eq!(backpack.add(&coins, 200), 196);
+    }
+
+    #[test]
+    fn test_inventory_equipment() {
+        let coins = coins();
+        let sword = sword();
+        let mut inventory = Inventory::new(2)
+            .with_equipment_slot("MainHand")
+            .with_equipment_slot("OffHand")
+            .with_equipment_slot("Head");
+        inventory.add(&sword, 1);
+        inventory.add(&coins, 10);
+        while inventory.pop_event().is_some() {}
+
+        assert_eq!(
+            inventory.equip(1, "MainHand"),
+            Err(InventoryError::NotEquippable("MainHand".to_string()))
+        );
+        inventory.equip(0, "MainHand").unwrap();
+        assert!(inventory.slot(0).is_none());
+        assert_eq!(inventory.equipped("MainHand").unwrap().item, sword);
+        assert_eq!(
+            inventory.pop_event(),
+            Some(InventoryEvent::Removed {
+                slot: 0,
+                item: sword.clone(),
+                count: 1
+            })
+        );
+        assert_eq!(
+            inventory.pop_event(),
+            Some(InventoryEvent::Equipped {
+                slot: "MainHand".to_string(),
+                item: sword.clone()
+            })
+        );
+        assert_eq!(inventory.pop_event(), None);
+
+        let main_hand = InventoryLocation::Equipment("MainHand".to_string());
+        let off_hand = InventoryLocation::Equipment("OffHand".to_string());
+        let head = InventoryLocation::Equipment("Head".to_string());
+        inventory.transfer(&main_hand, &off_hand).unwrap();
+        assert!(inventory.equipped("MainHand").is_none());
+        assert_eq!(
+            inventory.transfer(&off_hand, &head),
+            Err(InventoryError::NotEquippable("Head".to_string()))
+        );
+
+        // Moves.
+        inventory
+            .transfer(&InventoryLocation::Slot(1), &InventoryLocation::Slot(0))
+            .unwrap();
+        assert_eq!(inventory.slot(0).unwrap().count, 10);
+        assert_eq!(inventory.unequip("OffHand"), Ok(1));
+        assert_eq!(
+            inventory.unequip("OffHand"),
+            Err(InventoryError::EmptyEquipmentSlot("OffHand".to_string()))
+        );
+        assert_eq!(inventory.add(&coins, 95), 5);
+
+        // Swaps.
+        inventory.move_stack(1, 0).unwrap();
+        assert_eq!(inventory.slot(0).unwrap().item, sword);
+        assert_eq!(inventory.slot(1).unwrap().count, 100);
+    }
+}
@@ -0,0 +1,414 @@
+//! Minimap generation - an orthographic top-down capture of a scene with world-to-map coordinate
+//! mapping and icon overlay. See [`Minimap`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+    },
+    renderer::{
+        framework::error::FrameworkError,
+        paint::{PaintBlendMode, PaintBrush, PaintBrushShape, PaintCommandList},
+        Renderer,
+    },
+    resource::texture::{
+        TextureKind, TextureMagnificationFilter, TextureMinificationFilter, TexturePixelKind,
+        TextureResource, TextureResourceExtension, TextureWrapMode,
+    },
+    scene::{
+        base::BaseBuilder,
+        camera::{CameraBuilder, OrthographicProjection, Projection},
+        node::Node,
+        Scene,
+    },
+};
+use fxhash::FxHashSet;
+use std::fmt::{Display, Formatter};
+
+/// An error that may occur during minimap rendering.
+#[derive(Debug)]
+pub enum MinimapError {
+    /// The region of the minimap has zero area.
+    EmptyRegion,
+    /// An error occurred during rendering.
+    Framework(FrameworkError),
+}
+
+impl Display for MinimapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MinimapError::EmptyRegion => {
+                write!(f, "The region of the minimap has zero area.")
+            }
+            MinimapError::Framework(e) => {
+                write!(f, "Failed to render the minimap. Reason: {}", e)
+            }
+        }
+    }
+}
+
+impl From<FrameworkError> for MinimapError {
+    fn from(e: FrameworkError) -> Self {
+        Self::Framework(e)
+    }
+}
+
+/// Settings of minimap rendering.
+#[derive(Clone, Debug)]
+pub struct MinimapSettings {
+    /// Root nodes of the hierarchies, that will be rendered on the minimap (terrain, buildings, roads,
+    /// etc.). If empty, the entire scene is rendered.
+    pub layers: Vec<Handle<Node>>,
+    /// World-space region of the minimap. If not set, the bounds of the layers are used. Only
+    /// horizontal (XZ) extents of the region affect the size of the map, vertical extents define the
+    /// range of heights that will be captured.
+    pub region: Option<AxisAlignedBoundingBox>,
+    /// Size of the longest side of the minimap in pixels, the other side is calculated from the aspect
+    /// ratio of the region.
+    pub resolution: u32,
+    /// Color of the areas, that have no objects.
+    pub background: Color,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            layers: Default::default(),
+            region: None,
+            resolution: 512,
+            background: Color::TRANSPARENT,
+        }
+    }
+}
+
+/// An icon on the minimap (a player, an objective, an enemy, etc.).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinimapIcon {
+    /// World-space position of the icon, the height is ignored.
+    pub position: Vector3<f32>,
+    /// Rotation of the icon around world Y axis in radians. Top side of the icon texture points towards
+    /// the direction of local Z axis of an object with such rotation.
+    pub yaw: f32,
+    /// Size of the icon in pixels of the minimap.
+    pub size: f32,
+    /// Color of the icon.
+    pub color: Color,
+    /// Optional texture of the icon. Icons without textures are drawn as circles.
+    pub texture: Option<TextureResource>,
+}
+
+impl Default for MinimapIcon {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            yaw: 0.0,
+            size: 8.0,
+            color: Color::WHITE,
+            texture: None,
+        }
+    }
+}
+
+/// Minimap is an orthographic top-down capture of a scene. It contains two textures - the map itself
+/// and the overlay with icons, both have the same size and should be drawn one over another (for
+/// example, using two image widgets).
+///
+/// The map is oriented so that world X axis points to the right and world Z axis points down (the
+/// first row of the texture corresponds to the minimal Z coordinate). Map coordinates are the same as
+/// texture coordinates of the textures, use [`Self::world_to_map`] and [`Self::map_to_world`] to
+/// convert between world and map coordinates.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     core::{algebra::Vector3, pool::Handle},
+///     renderer::Renderer,
+///     scene::{node::Node, Scene},
+///     utils::minimap::{Minimap, MinimapIcon, MinimapSettings},
+/// };
+///
+/// fn make_minimap(renderer: &mut Renderer, scene: &Scene, terrain: Handle<Node>) -> Minimap {
+///     let settings = MinimapSettings {
+///         layers: vec![terrain],
+///         ..Default::default()
+///     };
+///     Minimap::render(renderer, scene, &settings).unwrap()
+/// }
+///
+/// // Must be called every frame to keep the icons in sync with the scene.
+/// fn update_icons(renderer: &mut Renderer, minimap: &Minimap, player_position: Vector3<f32>) {
+///     let icons = [MinimapIcon {
+///         position: player_position,
+///         ..Default::default()
+///     }];
+///     minimap.draw_icons(renderer, &icons).unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Minimap {
+    /// Top-down capture of the scene.
+    pub texture: TextureResource,
+    /// Transparent texture with icons, see [`Self::draw_icons`].
+    pub overlay: TextureResource,
+    /// Minimal world-space X and Z coordinates of the region of the minimap.
+    pub min: Vector2<f32>,
+    /// Maximal world-space X and Z coordinates of the region of the minimap.
+    pub max: Vector2<f32>,
+    /// Size of the textures in pixels.
+    pub size: Vector2<u32>,
+}
+
+fn make_map_texture(size: Vector2<u32>, pixels: Vec<u8>) -> TextureResource {
+    let texture = TextureResource::from_bytes(
+        TextureKind::Rectangle {
+            width: size.x,
+            height: size.y,
+        },
+        TexturePixelKind::RGBA8,
+        pixels,
+        false,
+    )
+    .unwrap();
+
+    let mut data = texture.data_ref();
+    data.set_minification_filter(TextureMinificationFilter::Linear);
+    data.set_magnification_filter(TextureMagnificationFilter::Linear);
+    data.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+    data.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+    drop(data);
+
+    texture
+}
+
+// Puts the albedo over the background color, the albedo has zero alpha where there are no objects.
+fn compose(albedo: &mut [u8], background: Color) {
+    for pixel in albedo.chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32 / 255.0;
+        let mix = |a: u8, b: u8| (a as f32 * alpha + b as f32 * (1.0 - alpha)).round() as u8;
+        pixel[0] = mix(pixel[0], background.r);
+        pixel[1] = mix(pixel[1], background.g);
+        pixel[2] = mix(pixel[2], background.b);
+        pixel[3] = pixel[3].max(background.a);
+    }
+}
+
+impl Minimap {
+    /// Renders a minimap of the given scene. The scene is rendered off-screen by the given renderer
+    /// without lighting, so this method should not be called during rendering, and it stalls the
+    /// GPU pipeline until the map is rendered.
+    pub fn render(
+        renderer: &mut Renderer,
+        scene: &Scene,
+        settings: &MinimapSettings,
+    ) -> Result<Self, MinimapError> {
+        let graph = &scene.graph;
+
+        let roots = if settings.layers.is_empty() {
+            vec![graph.get_root()]
+        } else {
+            settings.layers.clone()
+        };
+
+        let region = match settings.region {
+            Some(region) => region,
+            None => roots
+                .iter()
+                .filter_map(|root| graph.aabb_of_descendants(*root))
+                .fold(None, |acc: Option<AxisAlignedBoundingBox>, aabb| {
+                    Some(match acc {
+                        Some(mut acc) => {
+                            acc.add_box(aabb);
+                            acc
+                        }
+                        None => aabb,
+                    })
+                })
+                .ok_or(MinimapError::EmptyRegion)?,
+        };
+
+        let extents = region.max - region.min;
+        if extents.x <= f32::EPSILON || extents.z <= f32::EPSILON {
+            return Err(MinimapError::EmptyRegion);
+        }
+
+        let resolution = settings.resolution.max(1) as f32;
+        let size = if extents.x >= extents.z {
+            Vector2::new(
+                resolution,
+                (resolution * extents.z / extents.x).round().max(1.0),
+            )
+        } else {
+            Vector2::new(
+                (resolution * extents.x / extents.z).round().max(1.0),
+                resolution,
+            )
+        };
+
+        // Adjust the horizontal size of the region, so the pixels are square.
+        let center = region.center();
+        let half_depth = extents.z * 0.5;
+        let half_width = half_depth * size.x / size.y;
+        let min = Vector2::new(center.x - half_width, center.z - half_depth);
+        let max = Vector2::new(center.x + half_width, center.z + half_depth);
+
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_projection(Projection::Orthographic(OrthographicProjection {
+                z_near: 0.0,
+                z_far: extents.y + 2.0,
+                vertical_size: half_depth,
+            }))
+            .build_camera();
+        // The camera looks down, its up vector points to -Z, so +X is on the right side of the map.
+        let rotation = UnitQuaternion::face_towards(&-Vector3::y(), &-Vector3::z());
+        camera.global_transform.set(
+            Matrix4::new_translation(&Vector3::new(center.x, region.max.y + 1.0, center.z))
+                * rotation.to_homogeneous(),
+        );
+        camera.calculate_matrices(size);
+
+        let mut layers = FxHashSet::default();
+        for root in roots.iter() {
+            layers.extend(graph.traverse_handle_iter(*root));
+        }
+
+        let size = Vector2::new(size.x as u32, size.y as u32);
+        let capture = renderer.render_scene_gbuffer_filtered(scene, &camera, size, |handle| {
+            layers.contains(&handle)
+        })?;
+
+        let mut pixels = capture.diffuse.pixels;
+        compose(&mut pixels, settings.background);
+
+        Ok(Self {
+            texture: make_map_texture(size, pixels),
+            overlay: make_map_texture(size, vec![0; (size.x * size.y * 4) as usize]),
+            min,
+            max,
+            size,
+        })
+    }
+
+    /// Converts the given world-space position to map coordinates, which are the same as texture
+    /// coordinates of the map. Height of the position is ignored.
+    pub fn world_to_map(&self, position: Vector3<f32>) -> Vector2<f32> {
+        Vector2::new(
+            (position.x - self.min.x) / (self.max.x - self.min.x),
+            (position.z - self.min.y) / (self.max.y - self.min.y),
+        )
+    }
+
+    /// Converts the given map coordinates to world-space position with the given height.
+    pub fn map_to_world(&self, map_position: Vector2<f32>, height: f32) -> Vector3<f32> {
+        Vector3::new(
+            self.min.x + map_position.x * (self.max.x - self.min.x),
+            height,
+            self.min.y + map_position.y * (self.max.y - self.min.y),
+        )
+    }
+
+    /// Converts the given world-space position to pixel coordinates of the map.
+    pub fn world_to_pixel(&self, position: Vector3<f32>) -> Vector2<f32> {
+        self.world_to_map(position)
+            .component_mul(&Vector2::new(self.size.x as f32, self.size.y as f32))
+    }
+
+    /// Returns `true` if the given world-space position is inside the region of the minimap, `false` -
+    /// otherwise.
+    pub fn contains(&self, position: Vector3<f32>) -> bool {
+        let map_position = self.world_to_map(position);
+        (0.0..=1.0).contains(&map_position.x) && (0.0..=1.0).contains(&map_position.y)
+    }
+
+    /// Redraws the overlay texture with the given set of icons, previous icons are removed. Icons are
+    /// drawn on GPU (see [`Renderer::paint_texture`]), so this method is cheap enough to be called
+    /// every frame.
+    pub fn draw_icons(
+        &self,
+        renderer: &mut Renderer,
+        icons: &[MinimapIcon],
+    ) -> Result<(), FrameworkError> {
+        let mut commands = PaintCommandList::new();
+        commands.clear(Color::TRANSPARENT);
+        for icon in icons {
+            let has_texture = icon.texture.is_some();
+            commands.stamp(
+                self.world_to_pixel(icon.position),
+                PaintBrush {
+                    shape: if has_texture {
+                        PaintBrushShape::Square
+                    } else {
+                        PaintBrushShape::Circle
+                    },
+                    radius: icon.size * 0.5,
+                    hardness: if has_texture { 1.0 } else { 0.8 },
+                    color: icon.color,
+                    texture: icon.texture.clone(),
+                    // Z axis of the map points down, so the rotation is inverted.
+                    rotation: -icon.yaw,
+                    spacing: 1.0,
+                    blend_mode: PaintBlendMode::Alpha,
+                },
+            );
+        }
+        renderer.paint_texture(&self.overlay, &commands)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            color::Color,
+        },
+        resource::texture::{
+            TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+        },
+        utils::minimap::{compose, Minimap},
+    };
+
+    fn dummy_texture() -> TextureResource {
+        TextureResource::from_bytes(
+            TextureKind::Rectangle {
+                width: 1,
+                height: 1,
+            },
+            TexturePixelKind::RGBA8,
+            vec![0; 4],
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_minimap_coordinates() {
+        let minimap = Minimap {
+            texture: dummy_texture(),
+            overlay: dummy_texture(),
+            min: Vector2::new(-10.0, 0.0),
+            max: Vector2::new(10.0, 40.0),
+            size: Vector2::new(100, 200),
+        };
+
+        let position = Vector3::new(5.0, 3.0, 10.0);
+        assert_eq!(minimap.world_to_map(position), Vector2::new(0.75, 0.25));
+        assert_eq!(minimap.world_to_pixel(position), Vector2::new(75.0, 50.0));
+        assert_eq!(
+            minimap.map_to_world(Vector2::new(0.75, 0.25), 3.0),
+            position
+        );
+        assert!(minimap.contains(position));
+        assert!(!minimap.contains(Vector3::new(11.0, 0.0, 10.0)));
+        assert!(!minimap.contains(Vector3::new(0.0, 0.0, -1.0)));
+    }
+
+    #[test]
+    fn test_minimap_compose() {
+        let mut pixels = vec![200, 100, 50, 255, 200, 100, 50, 0];
+        compose(&mut pixels, Color::opaque(0, 0, 100));
+        assert_eq!(pixels, [200, 100, 50, 255, 0, 0, 100, 255]);
+    }
+}
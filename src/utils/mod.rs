@@ -3,16 +3,27 @@
 //! Utilities module provides set of commonly used algorithms.
 
 pub mod astar;
+pub mod baking;
 pub mod behavior;
 pub mod component;
+pub mod coroutine;
+pub mod dialogue;
+pub mod impostor;
+pub mod inventory;
 pub mod lightmap;
+pub mod minimap;
 pub mod navmesh;
+pub mod pipeline;
+pub mod quest;
 pub mod raw_mesh;
+pub mod save;
+pub mod session;
+pub mod spawn_pool;
 pub mod uvgen;
 
 use crate::{
     core::algebra::{Vector2, Vector3},
-    event::{ElementState, MouseScrollDelta, WindowEvent},
+    event::{ElementState, MouseScrollDelta, TouchPhase, WindowEvent},
     gui::{
         draw, message,
         message::{ButtonState, KeyboardModifiers, OsEvent},
@@ -286,6 +297,16 @@ pub fn translate_state(state: ElementState) -> ButtonState {
     }
 }
 
+/// Translates touch phase into fyrox-ui touch phase.
+pub fn translate_touch_phase(phase: TouchPhase) -> message::TouchPhase {
+    match phase {
+        TouchPhase::Started => message::TouchPhase::Started,
+        TouchPhase::Moved => message::TouchPhase::Moved,
+        TouchPhase::Ended => message::TouchPhase::Ended,
+        TouchPhase::Cancelled => message::TouchPhase::Cancelled,
+    }
+}
+
 /// Translates window event to fyrox-ui event.
 pub fn translate_event(event: &WindowEvent) -> Option<OsEvent> {
     match event {
@@ -314,6 +335,11 @@ pub fn translate_event(event: &WindowEvent) -> Option<OsEvent> {
         &WindowEvent::ModifiersChanged(modifiers) => Some(OsEvent::KeyboardModifiers(
             translate_keyboard_modifiers(modifiers.state()),
         )),
+        WindowEvent::Touch(touch) => Some(OsEvent::Touch {
+            id: touch.id,
+            phase: translate_touch_phase(touch.phase),
+            location: Vector2::new(touch.location.x as f32, touch.location.y as f32),
+        }),
         _ => None,
     }
 }
@@ -0,0 +1,697 @@
+//! Crowd simulation with local avoidance. See [`Crowd`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        pool::{Handle, Pool},
+    },
+    utils::navmesh::{
+        link::{OffMeshLinkResponse, OffMeshLinkTraversal},
+        Navmesh, NavmeshAgent,
+    },
+};
+
+const EPSILON: f32 = 1.0e-5;
+
+/// Defines how well an agent avoids other agents. Higher quality makes an agent to react on other
+/// agents earlier and to handle dense crowds better, at the cost of performance.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AvoidanceQuality {
+    /// The agent does not avoid other agents, but other agents still avoid it.
+    None,
+    /// The agent reacts on collisions, that may happen in the next half of a second. In dense
+    /// crowds, when there's no way to avoid every collision, the agent may stop.
+    Low,
+    /// The agent reacts on collisions, that may happen in the next second.
+    #[default]
+    Medium,
+    /// The agent reacts on collisions, that may happen in the next two seconds.
+    High,
+}
+
+impl AvoidanceQuality {
+    fn time_horizon(self) -> f32 {
+        match self {
+            AvoidanceQuality::None => 0.0,
+            AvoidanceQuality::Low => 0.5,
+            AvoidanceQuality::Medium => 1.0,
+            AvoidanceQuality::High => 2.0,
+        }
+    }
+}
+
+/// An agent of a crowd. It wraps [`NavmeshAgent`] that follows its path, and adds properties,
+/// that are used for local avoidance.
+#[derive(Clone, Debug)]
+pub struct CrowdAgent {
+    agent: NavmeshAgent,
+    radius: f32,
+    priority: u32,
+    quality: AvoidanceQuality,
+    velocity: Vector3<f32>,
+}
+
+impl CrowdAgent {
+    /// Creates new crowd agent from the given navmesh agent.
+    pub fn new(agent: NavmeshAgent) -> Self {
+        Self {
+            agent,
+            radius: 0.5,
+            priority: 0,
+            quality: Default::default(),
+            velocity: Default::default(),
+        }
+    }
+
+    /// Sets desired radius of the agent.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets desired avoidance priority of the agent. Agents with lower priority give way to agents
+    /// with higher priority, while agents with the same priority avoid each other equally.
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets desired avoidance quality of the agent.
+    pub fn with_avoidance_quality(mut self, quality: AvoidanceQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Returns a reference to the inner navmesh agent.
+    pub fn agent(&self) -> &NavmeshAgent {
+        &self.agent
+    }
+
+    /// Returns a reference to the inner navmesh agent. It could be used to change the target of
+    /// the agent, for example.
+    pub fn agent_mut(&mut self) -> &mut NavmeshAgent {
+        &mut self.agent
+    }
+
+    /// Returns radius of the agent.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Sets new radius of the agent.
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius;
+    }
+
+    /// Returns avoidance priority of the agent.
+    pub fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    /// Sets new avoidance priority of the agent.
+    pub fn set_priority(&mut self, priority: u32) {
+        self.priority = priority;
+    }
+
+    /// Returns avoidance quality of the agent.
+    pub fn avoidance_quality(&self) -> AvoidanceQuality {
+        self.quality
+    }
+
+    /// Sets new avoidance quality of the agent.
+    pub fn set_avoidance_quality(&mut self, quality: AvoidanceQuality) {
+        self.quality = quality;
+    }
+
+    /// Returns actual velocity of the agent, it could be used to animate the agent.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+}
+
+// A half-plane of permitted velocities.
+#[derive(Copy, Clone, Debug)]
+struct Line {
+    point: Vector2<f32>,
+    direction: Vector2<f32>,
+}
+
+// State of an agent at the beginning of an update.
+#[derive(Copy, Clone, Debug)]
+struct Snapshot {
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    radius: f32,
+    priority: u32,
+}
+
+fn det(a: &Vector2<f32>, b: &Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn xz(v: &Vector3<f32>) -> Vector2<f32> {
+    Vector2::new(v.x, v.z)
+}
+
+/// Crowd manages a set of navmesh agents and prevents them from walking through each other, using
+/// Optimal Reciprocal Collision Avoidance (ORCA). Each agent follows its own path, but its velocity
+/// is adjusted every update, so it does not collide with agents nearby. Keep in mind, that local
+/// avoidance cannot solve every situation - agents may still get stuck in narrow corridors.
+///
+/// # Example
+///
+/// ```
+/// use fyrox::{
+///     core::algebra::Vector3,
+///     utils::navmesh::{
+///         crowd::{Crowd, CrowdAgent},
+///         Navmesh, NavmeshAgentBuilder,
+///     },
+/// };
+///
+/// fn walk(navmesh: &mut Navmesh, dt: f32) {
+///     let mut crowd = Crowd::new();
+///
+///     for i in 0..10 {
+///         let agent = NavmeshAgentBuilder::new()
+///             .with_position(Vector3::new(i as f32, 0.0, 0.0))
+///             .with_target(Vector3::new(i as f32, 0.0, 10.0))
+///             .build();
+///         crowd.add_agent(CrowdAgent::new(agent).with_radius(0.4));
+///     }
+///
+///     crowd.update(dt, navmesh);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Crowd {
+    agents: Pool<CrowdAgent>,
+    max_neighbours: usize,
+    neighbour_distance: f32,
+}
+
+impl Default for Crowd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crowd {
+    /// Creates new empty crowd.
+    pub fn new() -> Self {
+        Self {
+            agents: Default::default(),
+            max_neighbours: 10,
+            neighbour_distance: 5.0,
+        }
+    }
+
+    /// Adds new agent to the crowd.
+    pub fn add_agent(&mut self, agent: CrowdAgent) -> Handle<CrowdAgent> {
+        self.agents.spawn(agent)
+    }
+
+    /// Removes the agent from the crowd and returns it, if the handle is valid.
+    pub fn remove_agent(&mut self, handle: Handle<CrowdAgent>) -> Option<CrowdAgent> {
+        self.agents.try_free(handle)
+    }
+
+    /// Returns a reference to the agent, if the handle is valid.
+    pub fn agent(&self, handle: Handle<CrowdAgent>) -> Option<&CrowdAgent> {
+        self.agents.try_borrow(handle)
+    }
+
+    /// Returns a reference to the agent, if the handle is valid.
+    pub fn agent_mut(&mut self, handle: Handle<CrowdAgent>) -> Option<&mut CrowdAgent> {
+        self.agents.try_borrow_mut(handle)
+    }
+
+    /// Returns a reference to the pool of agents.
+    pub fn agents(&self) -> &Pool<CrowdAgent> {
+        &self.agents
+    }
+
+    /// Sets maximum amount of the closest agents, that are taken into account by each agent.
+    /// Default is 10.
+    pub fn set_max_neighbours(&mut self, max_neighbours: usize) {
+        self.max_neighbours = max_neighbours;
+    }
+
+    /// Returns maximum amount of the closest agents, that are taken into account by each agent.
+    pub fn max_neighbours(&self) -> usize {
+        self.max_neighbours
+    }
+
+    /// Sets maximum distance (in meters) between agents, at which agents start to avoid each
+    /// other. Default is 5 meters.
+    pub fn set_neighbour_distance(&mut self, distance: f32) {
+        self.neighbour_distance = distance;
+    }
+
+    /// Returns maximum distance between agents, at which agents start to avoid each other.
+    pub fn neighbour_distance(&self) -> f32 {
+        self.neighbour_distance
+    }
+
+    /// Moves every agent along its path, avoiding other agents. Off-mesh links are traversed the
+    /// same way as any other part of a path, see [`Self::update_with_link_handler`] to customize
+    /// the traversal. Agents, that failed to build a path, stay at their positions.
+    pub fn update(&mut self, dt: f32, navmesh: &mut Navmesh) {
+        self.update_with_link_handler(dt, navmesh, |_, _| OffMeshLinkResponse::Move)
+    }
+
+    /// The same as [`Self::update`], but calls the given handler when an agent reaches the start of
+    /// an off-mesh link. See [`NavmeshAgent::update_with_link_handler`] for more info. Agents do
+    /// not avoid other agents while traversing links.
+    pub fn update_with_link_handler<F>(&mut self, dt: f32, navmesh: &mut Navmesh, mut handler: F)
+    where
+        F: FnMut(Handle<CrowdAgent>, &OffMeshLinkTraversal) -> OffMeshLinkResponse,
+    {
+        if dt <= 0.0 {
+            return;
+        }
+
+        // Velocities, that agents want to have to follow their paths.
+        let mut preferred = Vec::with_capacity(self.agents.alive_count() as usize);
+        for (handle, crowd_agent) in self.agents.pair_iter_mut() {
+            let agent = &mut crowd_agent.agent;
+            let can_move = agent
+                .update_path(navmesh, &mut |traversal: &OffMeshLinkTraversal| {
+                    handler(handle, traversal)
+                })
+                .unwrap_or(false);
+
+            let velocity = if !can_move {
+                None
+            } else if agent.on_link {
+                // Links are traversed without avoidance.
+                agent.current_segment().map(|(source, destination)| {
+                    (destination - source)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_default()
+                        .scale(agent.speed)
+                })
+            } else {
+                agent.current_segment().map(|(_, destination)| {
+                    let offset = xz(&(destination - agent.position));
+                    let distance = offset.norm();
+                    let is_last = agent.current as usize + 2 >= agent.path.len();
+                    let speed = if is_last {
+                        // Slow down to stop exactly at the target.
+                        agent.speed.min(distance / dt)
+                    } else {
+                        agent.speed
+                    };
+                    let velocity = offset
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_default()
+                        .scale(speed);
+                    Vector3::new(velocity.x, 0.0, velocity.y)
+                })
+            };
+
+            preferred.push((handle, velocity));
+        }
+
+        let snapshots = self
+            .agents
+            .iter()
+            .map(|agent| Snapshot {
+                position: xz(&agent.agent.position),
+                velocity: xz(&agent.velocity),
+                radius: agent.radius,
+                priority: agent.priority,
+            })
+            .collect::<Vec<_>>();
+
+        let mut lines = Vec::new();
+        let mut neighbours = Vec::new();
+        for (index, (handle, velocity)) in preferred.into_iter().enumerate() {
+            let crowd_agent = &mut self.agents[handle];
+
+            let velocity = match velocity {
+                Some(velocity) => velocity,
+                None => {
+                    crowd_agent.velocity = Vector3::default();
+                    continue;
+                }
+            };
+
+            let velocity =
+                if crowd_agent.agent.on_link || crowd_agent.quality == AvoidanceQuality::None {
+                    velocity
+                } else {
+                    let snapshot = &snapshots[index];
+
+                    neighbours.clear();
+                    neighbours.extend(snapshots.iter().enumerate().filter_map(|(i, other)| {
+                        let distance = (other.position - snapshot.position).norm();
+                        if i != index && distance < self.neighbour_distance + other.radius {
+                            Some((distance, other))
+                        } else {
+                            None
+                        }
+                    }));
+                    neighbours
+                        .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+                    neighbours.truncate(self.max_neighbours);
+
+                    lines.clear();
+                    let time_horizon = crowd_agent.quality.time_horizon();
+                    for (_, other) in neighbours.iter() {
+                        lines.push(orca_line(snapshot, other, time_horizon, dt));
+                    }
+
+                    let max_speed = crowd_agent.agent.speed;
+                    let preferred = xz(&velocity);
+                    let mut result = Vector2::default();
+                    let failed = linear_program2(&lines, max_speed, &preferred, false, &mut result);
+                    if failed < lines.len() && crowd_agent.quality != AvoidanceQuality::Low {
+                        linear_program3(&lines, failed, max_speed, &mut result);
+                    } else if failed < lines.len() {
+                        result = Vector2::default();
+                    }
+
+                    Vector3::new(result.x, 0.0, result.y)
+                };
+
+            let agent = &mut crowd_agent.agent;
+            let old_position = agent.position;
+            let new_position = old_position + velocity.scale(dt);
+
+            let position = if agent.on_link {
+                Some(new_position)
+            } else {
+                // Keep the agent on the navmesh, let it slide along edges.
+                navmesh
+                    .project_on_surface(new_position)
+                    .or_else(|| {
+                        navmesh.project_on_surface(Vector3::new(
+                            new_position.x,
+                            old_position.y,
+                            old_position.z,
+                        ))
+                    })
+                    .or_else(|| {
+                        navmesh.project_on_surface(Vector3::new(
+                            old_position.x,
+                            old_position.y,
+                            new_position.z,
+                        ))
+                    })
+            };
+
+            if let Some(position) = position {
+                agent.advance(position);
+            }
+
+            crowd_agent.velocity = (agent.position - old_position).scale(1.0 / dt);
+        }
+    }
+}
+
+// Builds a half-plane of velocities, that do not lead to a collision with the other agent within
+// the time horizon.
+fn orca_line(agent: &Snapshot, other: &Snapshot, time_horizon: f32, dt: f32) -> Line {
+    // The agent with lower priority takes full responsibility for avoiding a collision.
+    let responsibility = match agent.priority.cmp(&other.priority) {
+        std::cmp::Ordering::Less => 1.0,
+        std::cmp::Ordering::Equal => 0.5,
+        std::cmp::Ordering::Greater => 0.0,
+    };
+
+    let relative_position = other.position - agent.position;
+    let relative_velocity = agent.velocity - other.velocity;
+    let distance_squared = relative_position.norm_squared();
+    let combined_radius = agent.radius + other.radius;
+    let combined_radius_squared = combined_radius * combined_radius;
+
+    let (direction, u) = if distance_squared > combined_radius_squared {
+        // No collision.
+        let inv_time_horizon = 1.0 / time_horizon;
+        let w = relative_velocity - relative_position.scale(inv_time_horizon);
+        let w_length_squared = w.norm_squared();
+        let dot_product = w.dot(&relative_position);
+
+        if dot_product < 0.0
+            && dot_product * dot_product > combined_radius_squared * w_length_squared
+        {
+            // Project on cut-off circle.
+            let w_length = w_length_squared.sqrt();
+            let unit_w = w / w_length;
+            (
+                Vector2::new(unit_w.y, -unit_w.x),
+                unit_w.scale(combined_radius * inv_time_horizon - w_length),
+            )
+        } else {
+            // Project on legs.
+            let leg = (distance_squared - combined_radius_squared).sqrt();
+            let direction = if det(&relative_position, &w) > 0.0 {
+                Vector2::new(
+                    relative_position.x * leg - relative_position.y * combined_radius,
+                    relative_position.x * combined_radius + relative_position.y * leg,
+                ) / distance_squared
+            } else {
+                -Vector2::new(
+                    relative_position.x * leg + relative_position.y * combined_radius,
+                    -relative_position.x * combined_radius + relative_position.y * leg,
+                ) / distance_squared
+            };
+            let projection = relative_velocity.dot(&direction);
+            (direction, direction.scale(projection) - relative_velocity)
+        }
+    } else {
+        // Collision, project on cut-off circle of the time step.
+        let inv_dt = 1.0 / dt;
+        let w = relative_velocity - relative_position.scale(inv_dt);
+        let w_length = w.norm();
+        let unit_w = w
+            .try_normalize(EPSILON)
+            .unwrap_or_else(|| Vector2::new(1.0, 0.0));
+        (
+            Vector2::new(unit_w.y, -unit_w.x),
+            unit_w.scale(combined_radius * inv_dt - w_length),
+        )
+    };
+
+    Line {
+        point: agent.velocity + u.scale(responsibility),
+        direction,
+    }
+}
+
+// Solves one-dimensional linear program on the given line, subject to the constraints of the
+// previous lines and the circular constraint of the maximum speed.
+fn linear_program1(
+    lines: &[Line],
+    line_no: usize,
+    radius: f32,
+    optimal: &Vector2<f32>,
+    optimize_direction: bool,
+    result: &mut Vector2<f32>,
+) -> bool {
+    let line = &lines[line_no];
+    let dot_product = line.point.dot(&line.direction);
+    let discriminant = dot_product * dot_product + radius * radius - line.point.norm_squared();
+    if discriminant < 0.0 {
+        // The maximum speed circle fully invalidates the line.
+        return false;
+    }
+
+    let discriminant = discriminant.sqrt();
+    let mut t_left = -dot_product - discriminant;
+    let mut t_right = -dot_product + discriminant;
+
+    for other in &lines[..line_no] {
+        let denominator = det(&line.direction, &other.direction);
+        let numerator = det(&other.direction, &(line.point - other.point));
+
+        if denominator.abs() <= EPSILON {
+            // The lines are (almost) parallel.
+            if numerator < 0.0 {
+                return false;
+            }
+            continue;
+        }
+
+        let t = numerator / denominator;
+        if denominator >= 0.0 {
+            t_right = t_right.min(t);
+        } else {
+            t_left = t_left.max(t);
+        }
+
+        if t_left > t_right {
+            return false;
+        }
+    }
+
+    let t = if optimize_direction {
+        if optimal.dot(&line.direction) > 0.0 {
+            t_right
+        } else {
+            t_left
+        }
+    } else {
+        line.direction
+            .dot(&(optimal - line.point))
+            .clamp(t_left, t_right)
+    };
+
+    *result = line.point + line.direction.scale(t);
+
+    true
+}
+
+// Solves two-dimensional linear program subject to the lines and the circular constraint of the
+// maximum speed. Returns the index of the line, that caused a failure, or the amount of lines on
+// success.
+fn linear_program2(
+    lines: &[Line],
+    radius: f32,
+    optimal: &Vector2<f32>,
+    optimize_direction: bool,
+    result: &mut Vector2<f32>,
+) -> usize {
+    *result = if optimize_direction {
+        optimal.scale(radius)
+    } else if optimal.norm_squared() > radius * radius {
+        optimal.normalize().scale(radius)
+    } else {
+        *optimal
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        if det(&line.direction, &(line.point - *result)) > 0.0 {
+            // The result does not satisfy the constraint of the line.
+            let previous = *result;
+            if !linear_program1(lines, i, radius, optimal, optimize_direction, result) {
+                *result = previous;
+                return i;
+            }
+        }
+    }
+
+    lines.len()
+}
+
+// Finds a velocity, that minimizes the maximum penetration into the half-planes, when the linear
+// program is infeasible (in dense crowds).
+fn linear_program3(lines: &[Line], begin_line: usize, radius: f32, result: &mut Vector2<f32>) {
+    let mut distance = 0.0;
+    let mut projected = Vec::new();
+
+    for i in begin_line..lines.len() {
+        let line = &lines[i];
+        if det(&line.direction, &(line.point - *result)) <= distance {
+            continue;
+        }
+
+        projected.clear();
+        for other in &lines[..i] {
+            let determinant = det(&line.direction, &other.direction);
+            let point = if determinant.abs() <= EPSILON {
+                if line.direction.dot(&other.direction) > 0.0 {
+                    // The lines are in the same direction.
+                    continue;
+                }
+                (line.point + other.point).scale(0.5)
+            } else {
+                line.point
+                    + line
+                        .direction
+                        .scale(det(&other.direction, &(line.point - other.point)) / determinant)
+            };
+
+            projected.push(Line {
+                point,
+                direction: (other.direction - line.direction)
+                    .try_normalize(EPSILON)
+                    .unwrap_or_default(),
+            });
+        }
+
+        let previous = *result;
+        let optimal = Vector2::new(-line.direction.y, line.direction.x);
+        if linear_program2(&projected, radius, &optimal, true, result) < projected.len() {
+            // Should not happen in theory, the result is already in the feasible region of the
+            // linear program, but it may fail due to floating point errors.
+            *result = previous;
+        }
+
+        distance = det(&line.direction, &(line.point - *result));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, math::TriangleDefinition},
+        utils::navmesh::{
+            crowd::{Crowd, CrowdAgent},
+            Navmesh, NavmeshAgentBuilder,
+        },
+    };
+
+    fn make_floor() -> Navmesh {
+        Navmesh::new(
+            &[TriangleDefinition([0, 1, 2]), TriangleDefinition([0, 2, 3])],
+            &[
+                Vector3::new(-10.0, 0.0, -10.0),
+                Vector3::new(-10.0, 0.0, 10.0),
+                Vector3::new(10.0, 0.0, 10.0),
+                Vector3::new(10.0, 0.0, -10.0),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_avoidance() {
+        let mut navmesh = make_floor();
+        let mut crowd = Crowd::new();
+
+        // Two agents walk towards each other.
+        let a = crowd.add_agent(
+            CrowdAgent::new(
+                NavmeshAgentBuilder::new()
+                    .with_position(Vector3::new(-5.0, 0.0, 0.0))
+                    .with_target(Vector3::new(5.0, 0.0, 0.0))
+                    .build(),
+            )
+            .with_radius(0.5),
+        );
+        let b = crowd.add_agent(
+            CrowdAgent::new(
+                NavmeshAgentBuilder::new()
+                    .with_position(Vector3::new(5.0, 0.0, 0.01))
+                    .with_target(Vector3::new(-5.0, 0.0, 0.01))
+                    .build(),
+            )
+            .with_radius(0.5),
+        );
+
+        let dt = 1.0 / 60.0;
+        let mut min_distance = f32::MAX;
+        for _ in 0..(20.0 / dt) as usize {
+            crowd.update(dt, &mut navmesh);
+            let pa = crowd.agent(a).unwrap().agent().position();
+            let pb = crowd.agent(b).unwrap().agent().position();
+            min_distance = min_distance.min(pa.metric_distance(&pb));
+        }
+
+        // Agents must not walk through each other, but must reach their targets.
+        assert!(min_distance > 0.9, "{}", min_distance);
+        let pa = crowd.agent(a).unwrap().agent().position();
+        let pb = crowd.agent(b).unwrap().agent().position();
+        assert!(
+            pa.metric_distance(&Vector3::new(5.0, 0.0, 0.0)) < 0.1,
+            "{:?}",
+            pa
+        );
+        assert!(
+            pb.metric_distance(&Vector3::new(-5.0, 0.0, 0.01)) < 0.1,
+            "{:?}",
+            pb
+        );
+    }
+}
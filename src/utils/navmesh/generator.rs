@@ -0,0 +1,1000 @@
+//! Automatic generation of navigation meshes from arbitrary level geometry. See [`NavmeshGenerator`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector3},
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        pool::Handle,
+    },
+    scene::{
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
+        terrain::Terrain,
+    },
+    utils::navmesh::Navmesh,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::collections::VecDeque;
+
+/// A set of parameters, that defines how a navmesh will be generated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavmeshGenerationSettings {
+    /// Horizontal size of a voxel (in meters). Smaller values produce more precise navmesh, but
+    /// significantly increase generation time.
+    pub cell_size: f32,
+    /// Vertical size of a voxel (in meters).
+    pub cell_height: f32,
+    /// Radius of agents. Walkable area is shrunk by this value, so agents won't intersect walls.
+    pub agent_radius: f32,
+    /// Height of agents. Areas with lower ceiling are not walkable.
+    pub agent_height: f32,
+    /// Maximum height of a ledge (for example a stair step), that agents can climb.
+    pub agent_max_climb: f32,
+    /// Maximum slope angle (in degrees) of a walkable surface.
+    pub agent_max_slope: f32,
+    /// Minimum area (in cells) of an isolated walkable region. Smaller regions (for example tops of
+    /// tables or crates) are removed.
+    pub min_region_area: usize,
+}
+
+impl Default for NavmeshGenerationSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: 0.25,
+            cell_height: 0.1,
+            agent_radius: 0.5,
+            agent_height: 2.0,
+            agent_max_climb: 0.4,
+            agent_max_slope: 45.0,
+            min_region_area: 8,
+        }
+    }
+}
+
+/// Navmesh generator builds a navigation mesh from arbitrary geometry (meshes and terrains of a
+/// scene), so there's no need to author navmeshes manually. Generation is performed in a few steps
+/// (similar to Recast):
+///
+/// 1) Voxelization - source triangles are rasterized into a height field. Each column of the
+///    height field contains a list of solid spans, spans with walkable top surface are marked.
+/// 2) Filtering - walkable spans, that have not enough free space above them, or that are on ledges,
+///    are marked as not walkable. Walkable area is then eroded by the radius of agents.
+/// 3) Region building - walkable spans are split into connected regions, each region has at most
+///    one span per column. Too small regions are removed.
+/// 4) Polygonization - regions are split into rectangles (which follow the surface within the
+///    vertical precision), rectangles are triangulated and joined into a navmesh.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::pool::Handle,
+///     scene::{node::Node, Scene},
+///     utils::navmesh::{
+///         generator::{NavmeshGenerationSettings, NavmeshGenerator},
+///         Navmesh,
+///     },
+/// };
+///
+/// fn generate_navmesh(scene: &Scene, level_geometry: &[Handle<Node>]) -> Navmesh {
+///     let mut generator = NavmeshGenerator::new(NavmeshGenerationSettings {
+///         agent_radius: 0.4,
+///         agent_height: 1.8,
+///         ..Default::default()
+///     });
+///     for &node in level_geometry {
+///         generator.add_node(&scene.graph, node);
+///     }
+///     generator.generate()
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NavmeshGenerator {
+    settings: NavmeshGenerationSettings,
+    triangles: Vec<[Vector3<f32>; 3]>,
+}
+
+impl NavmeshGenerator {
+    /// Creates new navmesh generator with the given settings.
+    pub fn new(settings: NavmeshGenerationSettings) -> Self {
+        Self {
+            settings,
+            triangles: Default::default(),
+        }
+    }
+
+    /// Returns a reference to the current generation settings.
+    pub fn settings(&self) -> &NavmeshGenerationSettings {
+        &self.settings
+    }
+
+    /// Sets new generation settings.
+    pub fn set_settings(&mut self, settings: NavmeshGenerationSettings) {
+        self.settings = settings;
+    }
+
+    /// Adds a set of triangles (in world coordinates) to the source geometry.
+    pub fn add_triangles<I: IntoIterator<Item = [Vector3<f32>; 3]>>(&mut self, triangles: I) {
+        self.triangles.extend(triangles)
+    }
+
+    /// Adds every surface of the given mesh to the source geometry.
+    pub fn add_mesh(&mut self, mesh: &Mesh) {
+        let global_transform = mesh.global_transform();
+        for surface in mesh.surfaces() {
+            let shared_data = surface.data();
+            let shared_data = shared_data.lock();
+
+            let vertex_buffer = &shared_data.vertex_buffer;
+            let position = |index: u32| -> Option<Vector3<f32>> {
+                let local = vertex_buffer
+                    .get(index as usize)?
+                    .read_3_f32(VertexAttributeUsage::Position)
+                    .ok()?;
+                Some(
+                    global_transform
+                        .transform_point(&Point3::from(local))
+                        .coords,
+                )
+            };
+
+            for triangle in shared_data.geometry_buffer.iter() {
+                if let (Some(a), Some(b), Some(c)) = (
+                    position(triangle[0]),
+                    position(triangle[1]),
+                    position(triangle[2]),
+                ) {
+                    self.triangles.push([a, b, c]);
+                }
+            }
+        }
+    }
+
+    /// Adds every chunk of the given terrain to the source geometry.
+    pub fn add_terrain(&mut self, terrain: &Terrain) {
+        let global_transform = terrain.global_transform();
+        let transform = |v: Vector3<f32>| global_transform.transform_point(&Point3::from(v)).coords;
+
+        for chunk in terrain.chunks_ref() {
+            let heightmap = chunk.heightmap_owned();
+            let size = chunk.height_map_size();
+            if size.x < 2 || size.y < 2 {
+                continue;
+            }
+
+            let cell_width = chunk.physical_size().x / (size.x - 1) as f32;
+            let cell_length = chunk.physical_size().y / (size.y - 1) as f32;
+            let origin = chunk.local_position();
+
+            // Remember Z -> Y mapping!
+            let vertex = |ix: u32, iy: u32| {
+                transform(Vector3::new(
+                    origin.x + ix as f32 * cell_width,
+                    heightmap[(iy * size.x + ix) as usize],
+                    origin.y + iy as f32 * cell_length,
+                ))
+            };
+
+            for iy in 0..size.y - 1 {
+                for ix in 0..size.x - 1 {
+                    let v0 = vertex(ix, iy);
+                    let v1 = vertex(ix, iy + 1);
+                    let v2 = vertex(ix + 1, iy + 1);
+                    let v3 = vertex(ix + 1, iy);
+                    self.triangles.push([v0, v1, v2]);
+                    self.triangles.push([v2, v3, v0]);
+                }
+            }
+        }
+    }
+
+    /// Adds the given scene node to the source geometry, if it is a mesh or a terrain. Returns
+    /// `false` if the node has any other type. Descendants of the node are not added.
+    pub fn add_node(&mut self, graph: &Graph, node: Handle<Node>) -> bool {
+        if let Some(node) = graph.try_get(node) {
+            if let Some(mesh) = node.cast::<Mesh>() {
+                self.add_mesh(mesh);
+                return true;
+            } else if let Some(terrain) = node.cast::<Terrain>() {
+                self.add_terrain(terrain);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Generates a navmesh from the source geometry. Returns empty navmesh if there's no walkable
+    /// area.
+    pub fn generate(&self) -> Navmesh {
+        let settings = &self.settings;
+        if self.triangles.is_empty() || settings.cell_size <= 0.0 || settings.cell_height <= 0.0 {
+            return Navmesh::default();
+        }
+
+        let mut heightfield = Heightfield::new(&self.triangles, settings);
+        for triangle in self.triangles.iter() {
+            heightfield.rasterize_triangle(triangle, settings);
+        }
+        heightfield.filter(settings);
+
+        let mut open = OpenHeightfield::new(&heightfield, settings);
+        open.erode(settings);
+        open.build_regions(settings);
+
+        open.polygonize(settings)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Span {
+    // Bottom and top of the span in cells.
+    min: i32,
+    max: i32,
+    walkable: bool,
+}
+
+struct Heightfield {
+    width: i32,
+    depth: i32,
+    origin: Vector3<f32>,
+    // Spans of each column sorted from bottom to top.
+    columns: Vec<Vec<Span>>,
+}
+
+fn climb_cells(settings: &NavmeshGenerationSettings) -> i32 {
+    (settings.agent_max_climb / settings.cell_height).floor() as i32
+}
+
+fn height_cells(settings: &NavmeshGenerationSettings) -> i32 {
+    (settings.agent_height / settings.cell_height).ceil() as i32
+}
+
+// Clips a convex polygon by an axis-aligned plane, keeps the part on the positive side if
+// `positive` is set.
+fn clip_polygon(
+    polygon: &[Vector3<f32>],
+    axis: usize,
+    value: f32,
+    positive: bool,
+) -> Vec<Vector3<f32>> {
+    let distance = |p: &Vector3<f32>| {
+        if positive {
+            p[axis] - value
+        } else {
+            value - p[axis]
+        }
+    };
+
+    let mut result = Vec::with_capacity(polygon.len() + 2);
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[(i + 1) % polygon.len()];
+        let da = distance(a);
+        let db = distance(b);
+        if da >= 0.0 {
+            result.push(*a);
+        }
+        if (da >= 0.0) != (db >= 0.0) {
+            let t = da / (da - db);
+            result.push(a.lerp(b, t));
+        }
+    }
+    result
+}
+
+impl Heightfield {
+    fn new(triangles: &[[Vector3<f32>; 3]], settings: &NavmeshGenerationSettings) -> Self {
+        let mut bounds = AxisAlignedBoundingBox::default();
+        for triangle in triangles {
+            for vertex in triangle {
+                bounds.add_point(*vertex);
+            }
+        }
+
+        let size = bounds.max - bounds.min;
+        let width = (size.x / settings.cell_size).ceil().max(1.0) as i32;
+        let depth = (size.z / settings.cell_size).ceil().max(1.0) as i32;
+
+        Self {
+            width,
+            depth,
+            origin: bounds.min,
+            columns: vec![Vec::new(); (width * depth) as usize],
+        }
+    }
+
+    fn column_index(&self, x: i32, z: i32) -> Option<usize> {
+        if x >= 0 && z >= 0 && x < self.width && z < self.depth {
+            Some((z * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn add_span(&mut self, column: usize, mut span: Span, climb: i32) {
+        let spans = &mut self.columns[column];
+        let mut i = 0;
+        while i < spans.len() {
+            let existing = spans[i];
+            if existing.max < span.min || existing.min > span.max {
+                i += 1;
+                continue;
+            }
+
+            // Merge overlapping spans, the top surface defines walkability.
+            if (existing.max - span.max).abs() <= climb {
+                span.walkable |= existing.walkable;
+            } else if existing.max > span.max {
+                span.walkable = existing.walkable;
+            }
+            span.min = span.min.min(existing.min);
+            span.max = span.max.max(existing.max);
+            spans.remove(i);
+        }
+
+        let position = spans
+            .iter()
+            .position(|s| s.min > span.min)
+            .unwrap_or(spans.len());
+        spans.insert(position, span);
+    }
+
+    fn rasterize_triangle(
+        &mut self,
+        triangle: &[Vector3<f32>; 3],
+        settings: &NavmeshGenerationSettings,
+    ) {
+        let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+        let normal = match normal.try_normalize(f32::EPSILON) {
+            Some(normal) => normal,
+            None => return,
+        };
+        // Source geometry could have any winding, so both sides are considered.
+        let walkable = normal.y.abs() >= settings.agent_max_slope.to_radians().cos();
+
+        let cs = settings.cell_size;
+        let ch = settings.cell_height;
+        let climb = climb_cells(settings);
+
+        let mut min = triangle[0];
+        let mut max = triangle[0];
+        for v in triangle.iter() {
+            min = min.inf(v);
+            max = max.sup(v);
+        }
+
+        let x0 = (((min.x - self.origin.x) / cs).floor() as i32).max(0);
+        let x1 = (((max.x - self.origin.x) / cs).floor() as i32).min(self.width - 1);
+        let z0 = (((min.z - self.origin.z) / cs).floor() as i32).max(0);
+        let z1 = (((max.z - self.origin.z) / cs).floor() as i32).min(self.depth - 1);
+
+        for z in z0..=z1 {
+            let row_min = self.origin.z + z as f32 * cs;
+            let row = clip_polygon(triangle, 2, row_min, true);
+            let row = clip_polygon(&row, 2, row_min + cs, false);
+            if row.len() < 3 {
+                continue;
+            }
+
+            for x in x0..=x1 {
+                let cell_min = self.origin.x + x as f32 * cs;
+                let cell = clip_polygon(&row, 0, cell_min, true);
+                let cell = clip_polygon(&cell, 0, cell_min + cs, false);
+                if cell.len() < 3 {
+                    continue;
+                }
+
+                let (y_min, y_max) = cell.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
+                    (lo.min(p.y), hi.max(p.y))
+                });
+
+                let span_min = ((y_min - self.origin.y) / ch).floor() as i32;
+                let span_max = (((y_max - self.origin.y) / ch).ceil() as i32).max(span_min + 1);
+
+                if let Some(column) = self.column_index(x, z) {
+                    self.add_span(
+                        column,
+                        Span {
+                            min: span_min,
+                            max: span_max,
+                            walkable,
+                        },
+                        climb,
+                    );
+                }
+            }
+        }
+    }
+
+    fn filter(&mut self, settings: &NavmeshGenerationSettings) {
+        let climb = climb_cells(settings);
+        let height = height_cells(settings);
+
+        // Low obstacles (for example stair steps or curbs) are walkable if agents can climb them.
+        for spans in self.columns.iter_mut() {
+            let mut previous_walkable = false;
+            let mut previous_max = 0;
+            for span in spans.iter_mut() {
+                let walkable = span.walkable;
+                if !walkable && previous_walkable && span.max - previous_max <= climb {
+                    span.walkable = true;
+                }
+                previous_walkable = walkable;
+                previous_max = span.max;
+            }
+        }
+
+        // Spans with not enough free space above them and spans on ledges are not walkable.
+        let mut not_walkable = Vec::new();
+        for z in 0..self.depth {
+            for x in 0..self.width {
+                let column = self.column_index(x, z).unwrap();
+                let spans = &self.columns[column];
+                for (i, span) in spans.iter().enumerate() {
+                    if !span.walkable {
+                        continue;
+                    }
+
+                    let floor = span.max;
+                    let ceiling = spans.get(i + 1).map_or(i32::MAX, |s| s.min);
+
+                    if ceiling.saturating_sub(floor) < height
+                        || self.is_ledge(x, z, floor, ceiling, climb, height)
+                    {
+                        not_walkable.push((column, i));
+                    }
+                }
+            }
+        }
+
+        for (column, i) in not_walkable {
+            self.columns[column][i].walkable = false;
+        }
+    }
+
+    fn is_ledge(&self, x: i32, z: i32, floor: i32, ceiling: i32, climb: i32, height: i32) -> bool {
+        for (dx, dz) in DIRECTIONS {
+            let column = match self.column_index(x + dx, z + dz) {
+                Some(column) => column,
+                // Edges of the height field are treated as ledges.
+                None => return true,
+            };
+
+            let spans = &self.columns[column];
+
+            // Free space below the first span of the neighbour column is bottomless.
+            let first = spans.first().map_or(i32::MAX, |s| s.min);
+            if ceiling.min(first).saturating_sub(floor) > height {
+                return true;
+            }
+
+            for (i, neighbour) in spans.iter().enumerate() {
+                let neighbour_floor = neighbour.max;
+                let neighbour_ceiling = spans.get(i + 1).map_or(i32::MAX, |s| s.min);
+                if ceiling
+                    .min(neighbour_ceiling)
+                    .saturating_sub(floor.max(neighbour_floor))
+                    > height
+                    && neighbour_floor - floor < -climb
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+const DIRECTIONS: [(i32, i32); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+#[derive(Clone, Debug)]
+struct OpenSpan {
+    x: i32,
+    z: i32,
+    // Floor of the span in cells.
+    y: i32,
+    ceiling: i32,
+    neighbours: [Option<usize>; 4],
+    removed: bool,
+    region: usize,
+}
+
+struct OpenHeightfield {
+    width: i32,
+    origin: Vector3<f32>,
+    spans: Vec<OpenSpan>,
+    region_count: usize,
+}
+
+impl OpenHeightfield {
+    fn new(heightfield: &Heightfield, settings: &NavmeshGenerationSettings) -> Self {
+        let climb = climb_cells(settings);
+        let height = height_cells(settings);
+
+        let mut spans = Vec::new();
+        let mut columns = vec![Vec::new(); heightfield.columns.len()];
+        for z in 0..heightfield.depth {
+            for x in 0..heightfield.width {
+                let column = heightfield.column_index(x, z).unwrap();
+                let solid = &heightfield.columns[column];
+                for (i, span) in solid.iter().enumerate() {
+                    if span.walkable {
+                        columns[column].push(spans.len());
+                        spans.push(OpenSpan {
+                            x,
+                            z,
+                            y: span.max,
+                            ceiling: solid.get(i + 1).map_or(i32::MAX, |s| s.min),
+                            neighbours: [None; 4],
+                            removed: false,
+                            region: 0,
+                        });
+                    }
+                }
+            }
+        }
+
+        for index in 0..spans.len() {
+            for (direction, (dx, dz)) in DIRECTIONS.iter().enumerate() {
+                let (x, z) = (spans[index].x + dx, spans[index].z + dz);
+                if let Some(column) = heightfield.column_index(x, z) {
+                    let span = &spans[index];
+                    let neighbour = columns[column].iter().cloned().find(|&other| {
+                        let other = &spans[other];
+                        span.ceiling
+                            .min(other.ceiling)
+                            .saturating_sub(span.y.max(other.y))
+                            >= height
+                            && (other.y - span.y).abs() <= climb
+                    });
+                    spans[index].neighbours[direction] = neighbour;
+                }
+            }
+        }
+
+        Self {
+            width: heightfield.width,
+            origin: heightfield.origin,
+            spans,
+            region_count: 0,
+        }
+    }
+
+    fn neighbours(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.spans[index]
+            .neighbours
+            .iter()
+            .flatten()
+            .cloned()
+            .filter(move |&n| !self.spans[n].removed)
+    }
+
+    // Removes every span, which is closer than the radius of agents to the border of walkable area.
+    fn erode(&mut self, settings: &NavmeshGenerationSettings) {
+        let radius = (settings.agent_radius / settings.cell_size).ceil() as u32;
+        if radius == 0 {
+            return;
+        }
+
+        let mut distances = vec![u32::MAX; self.spans.len()];
+        let mut queue = VecDeque::new();
+        for (index, span) in self.spans.iter().enumerate() {
+            if span.neighbours.iter().any(|n| n.is_none()) {
+                distances[index] = 0;
+                queue.push_back(index);
+            }
+        }
+
+        while let Some(index) = queue.pop_front() {
+            let distance = distances[index] + 1;
+            for neighbour in self.spans[index].neighbours.iter().flatten() {
+                if distances[*neighbour] > distance {
+                    distances[*neighbour] = distance;
+                    queue.push_back(*neighbour);
+                }
+            }
+        }
+
+        for (span, distance) in self.spans.iter_mut().zip(distances) {
+            if distance < radius {
+                span.removed = true;
+            }
+        }
+    }
+
+    fn build_regions(&mut self, settings: &NavmeshGenerationSettings) {
+        let mut region_count = 0;
+        for start in 0..self.spans.len() {
+            if self.spans[start].removed || self.spans[start].region != 0 {
+                continue;
+            }
+
+            region_count += 1;
+            let region = region_count;
+
+            // A region must not have more than one span per column, otherwise it can't be
+            // represented on a 2D grid.
+            let mut columns = FxHashSet::default();
+            let mut members = vec![start];
+            let mut queue = VecDeque::new();
+            columns.insert((self.spans[start].x, self.spans[start].z));
+            self.spans[start].region = region;
+            queue.push_back(start);
+
+            while let Some(index) = queue.pop_front() {
+                let neighbours = self.neighbours(index).collect::<Vec<_>>();
+                for neighbour in neighbours {
+                    let span = &self.spans[neighbour];
+                    if span.region == 0 && columns.insert((span.x, span.z)) {
+                        self.spans[neighbour].region = region;
+                        members.push(neighbour);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+
+            if members.len() < settings.min_region_area {
+                for member in members {
+                    self.spans[member].removed = true;
+                }
+            }
+        }
+        self.region_count = region_count;
+    }
+
+    fn polygonize(&self, settings: &NavmeshGenerationSettings) -> Navmesh {
+        let mut regions = vec![FxHashMap::default(); self.region_count + 1];
+        for span in self.spans.iter().filter(|s| !s.removed && s.region != 0) {
+            regions[span.region].insert((span.x, span.z), span.y);
+        }
+
+        let mut builder = PolygonBuilder {
+            origin: self.origin,
+            cell_size: settings.cell_size,
+            cell_height: settings.cell_height,
+            max_climb: settings.agent_max_climb.max(settings.cell_height),
+            vertices: Default::default(),
+            corners: Default::default(),
+            triangles: Default::default(),
+        };
+
+        let rects = regions
+            .iter()
+            .map(|cells| split_into_rects(cells, self.width))
+            .collect::<Vec<_>>();
+
+        // Corners of every rectangle must be created first, so rectangles could pick up corners of
+        // their neighbours lying on their borders (otherwise there would be T-junctions, that
+        // break connectivity of the navmesh).
+        for (cells, rects) in regions.iter().zip(rects.iter()) {
+            for rect in rects {
+                for (x, z) in rect.corners() {
+                    let y = builder.corner_height(cells, x, z);
+                    builder.corner_vertex(x, z, y, true);
+                }
+            }
+        }
+
+        for (cells, rects) in regions.iter().zip(rects.iter()) {
+            for rect in rects {
+                builder.triangulate(cells, rect);
+            }
+        }
+
+        Navmesh::new(&builder.triangles, &builder.vertices)
+    }
+}
+
+// Rectangle of cells, bounds are inclusive.
+#[derive(Copy, Clone, Debug)]
+struct Rect {
+    x0: i32,
+    z0: i32,
+    x1: i32,
+    z1: i32,
+}
+
+impl Rect {
+    fn corners(&self) -> [(i32, i32); 4] {
+        [
+            (self.x0, self.z0),
+            (self.x0, self.z1 + 1),
+            (self.x1 + 1, self.z1 + 1),
+            (self.x1 + 1, self.z0),
+        ]
+    }
+
+    // Returns grid points of the border of the rectangle in counter-clockwise order (when looking
+    // from above).
+    fn border(&self) -> Vec<(i32, i32)> {
+        let mut points = Vec::new();
+        for z in self.z0..=self.z1 {
+            points.push((self.x0, z));
+        }
+        for x in self.x0..=self.x1 {
+            points.push((x, self.z1 + 1));
+        }
+        for z in (self.z0 + 1..=self.z1 + 1).rev() {
+            points.push((self.x1 + 1, z));
+        }
+        for x in (self.x0 + 1..=self.x1 + 1).rev() {
+            points.push((x, self.z0));
+        }
+        points
+    }
+}
+
+// Checks whether floors of the cells of the rectangle lie close enough to a surface, defined by
+// the corner cells of the rectangle.
+fn is_flat(cells: &FxHashMap<(i32, i32), i32>, rect: &Rect) -> bool {
+    let h = |x, z| cells[&(x, z)] as f32;
+    let (h00, h10, h01, h11) = (
+        h(rect.x0, rect.z0),
+        h(rect.x1, rect.z0),
+        h(rect.x0, rect.z1),
+        h(rect.x1, rect.z1),
+    );
+    let w = (rect.x1 - rect.x0).max(1) as f32;
+    let d = (rect.z1 - rect.z0).max(1) as f32;
+
+    for z in rect.z0..=rect.z1 {
+        for x in rect.x0..=rect.x1 {
+            let u = (x - rect.x0) as f32 / w;
+            let v = (z - rect.z0) as f32 / d;
+            let expected = h00 * (1.0 - u) * (1.0 - v)
+                + h10 * u * (1.0 - v)
+                + h01 * (1.0 - u) * v
+                + h11 * u * v;
+            if (h(x, z) - expected).abs() > 1.0 {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn split_into_rects(cells: &FxHashMap<(i32, i32), i32>, width: i32) -> Vec<Rect> {
+    let mut sorted = cells.keys().cloned().collect::<Vec<_>>();
+    sorted.sort_by_key(|(x, z)| z * width + x);
+
+    let mut taken = FxHashSet::default();
+    let mut rects = Vec::new();
+    for (x, z) in sorted {
+        if taken.contains(&(x, z)) {
+            continue;
+        }
+
+        let free = |x: i32, z: i32, taken: &FxHashSet<(i32, i32)>| {
+            cells.contains_key(&(x, z)) && !taken.contains(&(x, z))
+        };
+
+        let mut rect = Rect {
+            x0: x,
+            z0: z,
+            x1: x,
+            z1: z,
+        };
+
+        while free(rect.x1 + 1, z, &taken)
+            && is_flat(
+                cells,
+                &Rect {
+                    x1: rect.x1 + 1,
+                    ..rect
+                },
+            )
+        {
+            rect.x1 += 1;
+        }
+
+        while (rect.x0..=rect.x1).all(|x| free(x, rect.z1 + 1, &taken))
+            && is_flat(
+                cells,
+                &Rect {
+                    z1: rect.z1 + 1,
+                    ..rect
+                },
+            )
+        {
+            rect.z1 += 1;
+        }
+
+        for z in rect.z0..=rect.z1 {
+            for x in rect.x0..=rect.x1 {
+                taken.insert((x, z));
+            }
+        }
+
+        rects.push(rect);
+    }
+    rects
+}
+
+struct PolygonBuilder {
+    origin: Vector3<f32>,
+    cell_size: f32,
+    cell_height: f32,
+    max_climb: f32,
+    vertices: Vec<Vector3<f32>>,
+    // Vertices at each grid point, there could be multiple vertices at different heights.
+    corners: FxHashMap<(i32, i32), Vec<u32>>,
+    triangles: Vec<TriangleDefinition>,
+}
+
+impl PolygonBuilder {
+    // Height of a grid point is the average height of adjacent cells of a region.
+    fn corner_height(&self, cells: &FxHashMap<(i32, i32), i32>, x: i32, z: i32) -> f32 {
+        let mut sum = 0.0;
+        let mut count = 0;
+        for cell in [(x - 1, z - 1), (x, z - 1), (x - 1, z), (x, z)] {
+            if let Some(y) = cells.get(&cell) {
+                sum += *y as f32;
+                count += 1;
+            }
+        }
+        self.origin.y + sum / count.max(1) as f32 * self.cell_height
+    }
+
+    fn corner_vertex(&mut self, x: i32, z: i32, y: f32, create: bool) -> Option<u32> {
+        let vertices = &self.vertices;
+        let max_climb = self.max_climb;
+        let existing = self.corners.get(&(x, z)).and_then(|indices| {
+            indices
+                .iter()
+                .cloned()
+                .find(|&i| (vertices[i as usize].y - y).abs() <= max_climb)
+        });
+
+        if existing.is_some() || !create {
+            return existing;
+        }
+
+        let index = self.vertices.len() as u32;
+        self.vertices.push(Vector3::new(
+            self.origin.x + x as f32 * self.cell_size,
+            y,
+            self.origin.z + z as f32 * self.cell_size,
+        ));
+        self.corners.entry((x, z)).or_default().push(index);
+        Some(index)
+    }
+
+    fn triangulate(&mut self, cells: &FxHashMap<(i32, i32), i32>, rect: &Rect) {
+        let mut polygon = Vec::new();
+        for (x, z) in rect.border() {
+            let y = self.corner_height(cells, x, z);
+            if let Some(vertex) = self.corner_vertex(x, z, y, false) {
+                if polygon.last() != Some(&vertex) && polygon.first() != Some(&vertex) {
+                    polygon.push(vertex);
+                }
+            }
+        }
+
+        if polygon.len() < 3 {
+            return;
+        }
+
+        if polygon.len() == 4 {
+            self.triangles
+                .push(TriangleDefinition([polygon[0], polygon[1], polygon[2]]));
+            self.triangles
+                .push(TriangleDefinition([polygon[0], polygon[2], polygon[3]]));
+        } else {
+            // Borders have extra vertices, fan triangulation from the center prevents degenerate
+            // triangles.
+            let center = polygon
+                .iter()
+                .map(|i| self.vertices[*i as usize])
+                .sum::<Vector3<f32>>()
+                / polygon.len() as f32;
+            let center_index = self.vertices.len() as u32;
+            self.vertices.push(center);
+            for i in 0..polygon.len() {
+                self.triangles.push(TriangleDefinition([
+                    center_index,
+                    polygon[i],
+                    polygon[(i + 1) % polygon.len()],
+                ]));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        utils::{
+            astar::PathKind,
+            navmesh::generator::{NavmeshGenerationSettings, NavmeshGenerator},
+        },
+    };
+
+    fn quad(min: Vector3<f32>, max: Vector3<f32>) -> [[Vector3<f32>; 3]; 2] {
+        let a = Vector3::new(min.x, min.y, min.z);
+        let b = Vector3::new(min.x, max.y, max.z);
+        let c = Vector3::new(max.x, max.y, max.z);
+        let d = Vector3::new(max.x, min.y, min.z);
+        [[a, b, c], [a, c, d]]
+    }
+
+    fn generator() -> NavmeshGenerator {
+        NavmeshGenerator::new(NavmeshGenerationSettings {
+            cell_size: 0.25,
+            cell_height: 0.1,
+            agent_radius: 0.5,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_flat_floor() {
+        let mut generator = generator();
+        generator.add_triangles(quad(
+            Vector3::new(-5.0, 0.0, -5.0),
+            Vector3::new(5.0, 0.0, 5.0),
+        ));
+
+        let navmesh = generator.generate();
+        assert!(!navmesh.triangles().is_empty());
+
+        for vertex in navmesh.vertices() {
+            // The floor is shrunk by the radius of agents.
+            assert!(vertex.position.x.abs() <= 4.5 + 0.001);
+            assert!(vertex.position.z.abs() <= 4.5 + 0.001);
+            assert!(vertex.position.y.abs() <= 0.1 + 0.001);
+        }
+    }
+
+    #[test]
+    fn test_obstacles_and_slopes() {
+        let mut generator = generator();
+        generator.add_triangles(quad(
+            Vector3::new(-5.0, 0.0, -5.0),
+            Vector3::new(5.0, 0.0, 5.0),
+        ));
+        // A low platform, it is too small to be walkable, and it is too low to walk under it.
+        generator.add_triangles(quad(
+            Vector3::new(-0.5, 1.5, -0.5),
+            Vector3::new(0.5, 1.5, 0.5),
+        ));
+        // Too steep slope is not walkable.
+        generator.add_triangles(quad(
+            Vector3::new(10.0, 0.0, -5.0),
+            Vector3::new(20.0, 20.0, 5.0),
+        ));
+
+        let mut navmesh = generator.generate();
+        assert!(!navmesh.triangles().is_empty());
+
+        for vertex in navmesh.vertices() {
+            let p = vertex.position;
+            assert!(p.x <= 4.5 + 0.001);
+            assert!(p.y.abs() <= 0.1 + 0.001);
+        }
+
+        // Area under the platform must be excluded.
+        for triangle in navmesh.triangles() {
+            let center = triangle
+                .0
+                .iter()
+                .map(|&i| navmesh.vertices()[i as usize].position)
+                .sum::<Vector3<f32>>()
+                / 3.0;
+            assert!(center.x.abs() > 0.5 || center.z.abs() > 0.5);
+        }
+
+        // Both sides of the platform must be connected.
+        let from = navmesh.query_closest(Vector3::new(-4.0, 0.0, 0.0)).unwrap();
+        let to = navmesh.query_closest(Vector3::new(4.0, 0.0, 0.0)).unwrap();
+        let mut path = Vec::new();
+        assert_eq!(
+            navmesh.build_path(from, to, &mut path).unwrap(),
+            PathKind::Full
+        );
+    }
+}
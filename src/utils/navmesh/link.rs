@@ -0,0 +1,126 @@
+//! Off-mesh links, that connect disjoint parts of a navmesh. See [`OffMeshLink`] docs for more info.
+
+use crate::core::{
+    algebra::Vector3,
+    pool::Handle,
+    visitor::{Visit, VisitResult, Visitor},
+};
+
+/// Kind of an off-mesh link. The kind does not affect path finding, it could be used to choose a
+/// way of traversal (an animation to play, for example).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Visit)]
+pub enum OffMeshLinkKind {
+    /// A jump over a gap or down from a ledge.
+    #[default]
+    Jump,
+    /// A ladder.
+    Ladder,
+    /// A teleport.
+    Teleport,
+    /// User-defined kind.
+    Custom(u32),
+}
+
+/// Off-mesh link is an authored connection between two points of a navmesh, that cannot be
+/// reached by walking - for example, a jump over a gap, a ladder or a teleport. Links are added to
+/// a navmesh using [`super::Navmesh::add_link`] and are taken into account by
+/// [`super::Navmesh::build_smooth_path`]. Navmesh agents call a user-defined handler when they need
+/// to traverse a link, see [`super::NavmeshAgent::update_with_link_handler`] for more info.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit)]
+pub struct OffMeshLink {
+    start: Vector3<f32>,
+    end: Vector3<f32>,
+    bidirectional: bool,
+    cost: f32,
+    kind: OffMeshLinkKind,
+}
+
+impl OffMeshLink {
+    /// Creates new one-way link between two points.
+    pub fn new(start: Vector3<f32>, end: Vector3<f32>) -> Self {
+        Self {
+            start,
+            end,
+            ..Default::default()
+        }
+    }
+
+    /// Defines whether the link could be traversed in both directions or not. Jumps down from
+    /// ledges are usually one-way, while ladders are bidirectional.
+    pub fn with_bidirectional(mut self, bidirectional: bool) -> Self {
+        self.bidirectional = bidirectional;
+        self
+    }
+
+    /// Sets additional cost of traversal of the link. The cost is added to the length of the link,
+    /// so the higher the cost, the more likely path finder will prefer walking around.
+    pub fn with_cost(mut self, cost: f32) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Sets kind of the link.
+    pub fn with_kind(mut self, kind: OffMeshLinkKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Returns start point of the link.
+    pub fn start(&self) -> Vector3<f32> {
+        self.start
+    }
+
+    /// Returns end point of the link.
+    pub fn end(&self) -> Vector3<f32> {
+        self.end
+    }
+
+    /// Returns `true` if the link could be traversed in both directions.
+    pub fn is_bidirectional(&self) -> bool {
+        self.bidirectional
+    }
+
+    /// Returns additional cost of traversal of the link.
+    pub fn cost(&self) -> f32 {
+        self.cost
+    }
+
+    /// Returns kind of the link.
+    pub fn kind(&self) -> OffMeshLinkKind {
+        self.kind
+    }
+
+    /// Returns full cost of traversal of the link.
+    pub(super) fn traversal_cost(&self) -> f32 {
+        self.start.metric_distance(&self.end) + self.cost
+    }
+}
+
+/// Traversal of an off-mesh link by a path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OffMeshLinkTraversal {
+    /// Handle of the link.
+    pub link: Handle<OffMeshLink>,
+    /// Kind of the link.
+    pub kind: OffMeshLinkKind,
+    /// Index of the path point, where the traversal begins. The next point of the path is the
+    /// point, where the traversal ends.
+    pub index: usize,
+    /// The point, where the traversal begins. It is the end point of the link, if the link is
+    /// traversed in reverse direction.
+    pub start: Vector3<f32>,
+    /// The point, where the traversal ends.
+    pub end: Vector3<f32>,
+}
+
+/// A way how a navmesh agent should traverse an off-mesh link.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OffMeshLinkResponse {
+    /// The agent moves along the link as usual, with its own speed.
+    Move,
+    /// The agent is moved to the end of the link immediately.
+    Teleport,
+    /// The agent waits at the start of the link, the handler will be called again on the next
+    /// update. It could be used to finish an animation (a jump, for example) before moving.
+    Wait,
+}
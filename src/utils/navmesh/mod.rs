@@ -0,0 +1,1745 @@
+//! Contains all structures and methods to create and manage navigation meshes (navmesh).
+//!
+//! Navigation mesh is a set of convex polygons which is used for path finding in complex
+//! environment.
+
+#![warn(missing_docs)]
+
+pub mod crowd;
+pub mod generator;
+pub mod link;
+pub mod obstacle;
+pub mod query;
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector3},
+        arrayvec::ArrayVec,
+        math::{self, aabb::AxisAlignedBoundingBox, ray::Ray, TriangleDefinition},
+        octree::{Octree, OctreeNode},
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::mesh::{
+        buffer::{VertexAttributeUsage, VertexReadTrait},
+        Mesh,
+    },
+    utils::{
+        astar::{search_graph, PathError, PathFinder, PathKind, PathVertex},
+        navmesh::{
+            link::{OffMeshLink, OffMeshLinkResponse, OffMeshLinkTraversal},
+            obstacle::{closest_point, is_point_inside, shared_edge, subtract, NavmeshObstacle},
+        },
+        raw_mesh::{RawMeshBuilder, RawVertex},
+    },
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{borrow::Cow, collections::VecDeque};
+
+/// Size of a tile (in meters), tiles are used to rebuild only the parts of a navmesh, that were
+/// affected by changed obstacles.
+const TILE_SIZE: f32 = 4.0;
+
+/// Maximum amount of changes, that are kept by a navmesh. See [`Navmesh::changes_since`].
+const MAX_CHANGES: usize = 64;
+
+// Index of a triangle and index of a walkable piece of the triangle.
+type PieceId = (usize, usize);
+
+// A common edge of two adjacent polygons.
+type Portal = (Vector3<f32>, Vector3<f32>);
+
+// A way to move from one walkable piece to another.
+#[derive(Copy, Clone, Debug)]
+enum Step {
+    Portal(Portal),
+    Link {
+        handle: Handle<OffMeshLink>,
+        reversed: bool,
+    },
+}
+
+/// See module docs.
+#[derive(Clone, Debug, Default, Reflect)]
+#[reflect(hide_all)]
+pub struct Navmesh {
+    octree: Octree,
+    triangles: Vec<TriangleDefinition>,
+    pathfinder: PathFinder,
+    query_buffer: Vec<u32>,
+    adjacency: Option<Vec<[Option<u32>; 3]>>,
+    links: Pool<OffMeshLink>,
+    obstacles: Pool<NavmeshObstacle>,
+    // Indices of triangles, that intersect each tile. Calculated on demand.
+    tiles: Option<FxHashMap<(i32, i32), Vec<u32>>>,
+    dirty_tiles: FxHashSet<(i32, i32)>,
+    // Walkable parts of triangles, that are intersected by obstacles.
+    carved: FxHashMap<u32, Vec<Vec<Vector3<f32>>>>,
+    changes: VecDeque<(u64, AxisAlignedBoundingBox)>,
+    revision: u64,
+}
+
+impl PartialEq for Navmesh {
+    fn eq(&self, other: &Self) -> bool {
+        self.triangles == other.triangles
+            && self.pathfinder == other.pathfinder
+            && self.links == other.links
+    }
+}
+
+impl Visit for Navmesh {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.pathfinder.visit("PathFinder", &mut region)?;
+        self.triangles.visit("Triangles", &mut region)?;
+        let _ = self.links.visit("OffMeshLinks", &mut region); // Backward compatibility.
+
+        drop(region);
+
+        // No need to save octree, we can restore it on load.
+        if visitor.is_reading() {
+            let vertices = self.pathfinder.vertices();
+            let raw_triangles = self
+                .triangles
+                .iter()
+                .map(|t| {
+                    [
+                        vertices[t[0] as usize].position,
+                        vertices[t[1] as usize].position,
+                        vertices[t[2] as usize].position,
+                    ]
+                })
+                .collect::<Vec<[Vector3<f32>; 3]>>();
+
+            self.octree = Octree::new(&raw_triangles, 32);
+        }
+
+        Ok(())
+    }
+}
+
+impl Navmesh {
+    /// Creates new navigation mesh from given set of triangles and vertices. This is
+    /// low level method that allows to specify triangles and vertices directly. In
+    /// most cases you should use `from_mesh` method.
+    pub fn new(triangles: &[TriangleDefinition], vertices: &[Vector3<f32>]) -> Self {
+        // Build triangles for octree.
+        let raw_triangles = triangles
+            .iter()
+            .map(|t| {
+                [
+                    vertices[t[0] as usize],
+                    vertices[t[1] as usize],
+                    vertices[t[2] as usize],
+                ]
+            })
+            .collect::<Vec<[Vector3<f32>; 3]>>();
+
+        // Fill in pathfinder.
+        let mut pathfinder = PathFinder::new();
+        pathfinder.set_vertices(vertices.iter().map(|v| PathVertex::new(*v)).collect());
+
+        let mut edges = FxHashSet::default();
+        for triangle in triangles {
+            for edge in triangle.edges() {
+                edges.insert(edge);
+            }
+        }
+
+        for edge in edges {
+            pathfinder.link_bidirect(edge.a as usize, edge.b as usize);
+        }
+
+        Self {
+            triangles: triangles.to_vec(),
+            octree: Octree::new(&raw_triangles, 32),
+            pathfinder,
+            query_buffer: Default::default(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates new navigation mesh (navmesh) from given mesh. It is most simple way to create complex
+    /// navigation mesh, it should be used in pair with model loading functionality - you can
+    /// load model from file and turn it into navigation mesh, or even build navigation mesh
+    /// from a model in existing scene. This method "eats" any kind of meshes with any amount
+    /// of surfaces - it joins all surfaces into single mesh and creates navmesh from it. The mesh
+    /// must be authored as a navmesh, use [`generator::NavmeshGenerator`] to generate a navmesh from
+    /// arbitrary level geometry.
+    ///
+    /// Example:
+    /// ```
+    /// use fyrox::scene::Scene;
+    /// use fyrox::utils::navmesh::Navmesh;
+    ///
+    /// fn make_navmesh(scene: &Scene, navmesh_name: &str) -> Navmesh {
+    ///     // Find mesh node in existing scene and create navigation mesh from it.
+    ///     let navmesh_node_handle = scene.graph.find_by_name_from_root(navmesh_name).unwrap().0;
+    ///     Navmesh::from_mesh(scene.graph[navmesh_node_handle].as_mesh())
+    /// }
+    /// ```
+    pub fn from_mesh(mesh: &Mesh) -> Self {
+        // Join surfaces into one simple mesh.
+        let mut builder = RawMeshBuilder::<RawVertex>::default();
+        let global_transform = mesh.global_transform();
+        for surface in mesh.surfaces() {
+            let shared_data = surface.data();
+            let shared_data = shared_data.lock();
+
+            let vertex_buffer = &shared_data.vertex_buffer;
+            for triangle in shared_data.geometry_buffer.iter() {
+                builder.insert(RawVertex::from(
+                    global_transform
+                        .transform_point(&Point3::from(
+                            vertex_buffer
+                                .get(triangle[0] as usize)
+                                .unwrap()
+                                .read_3_f32(VertexAttributeUsage::Position)
+                                .unwrap(),
+                        ))
+                        .coords,
+                ));
+                builder.insert(RawVertex::from(
+                    global_transform
+                        .transform_point(&Point3::from(
+                            vertex_buffer
+                                .get(triangle[1] as usize)
+                                .unwrap()
+                                .read_3_f32(VertexAttributeUsage::Position)
+                                .unwrap(),
+                        ))
+                        .coords,
+                ));
+                builder.insert(RawVertex::from(
+                    global_transform
+                        .transform_point(&Point3::from(
+                            vertex_buffer
+                                .get(triangle[2] as usize)
+                                .unwrap()
+                                .read_3_f32(VertexAttributeUsage::Position)
+                                .unwrap(),
+                        ))
+                        .coords,
+                ));
+            }
+        }
+
+        let mesh = builder.build();
+
+        Navmesh::new(
+            &mesh.triangles,
+            &mesh
+                .vertices
+                .into_iter()
+                .map(|v| Vector3::new(v.x, v.y, v.z))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Searches closest graph vertex to given point. Returns Some(index), or None
+    /// if navmesh was empty.
+    pub fn query_closest(&mut self, point: Vector3<f32>) -> Option<usize> {
+        self.octree.point_query(point, &mut self.query_buffer);
+        if self.query_buffer.is_empty() {
+            // TODO: This is not optimal. It is better to trace ray down from given point
+            //  and pick closest triangle.
+            math::get_closest_point(self.pathfinder.vertices(), point)
+        } else {
+            math::get_closest_point_triangles(
+                self.pathfinder.vertices(),
+                &self.triangles,
+                &self.query_buffer,
+                point,
+            )
+        }
+    }
+
+    /// Returns reference to array of triangles.
+    pub fn triangles(&self) -> &[TriangleDefinition] {
+        &self.triangles
+    }
+
+    /// Adds the triangle to the navigational mesh and returns its index in the internal array. Vertex indices in
+    /// the triangle must be valid!
+    pub fn add_triangle(&mut self, triangle: TriangleDefinition) -> u32 {
+        let index = self.triangles.len();
+        for edge in triangle.edges() {
+            self.pathfinder
+                .link_bidirect(edge.a as usize, edge.b as usize);
+        }
+        self.triangles.push(triangle);
+        self.adjacency = None;
+        self.tiles = None;
+        index as u32
+    }
+
+    /// Removes a triangle at the given index from the navigational mesh. Automatically fixes vertex links in the
+    /// internal navigational graph.
+    pub fn remove_triangle(&mut self, index: usize) -> TriangleDefinition {
+        let triangle = self.triangles.remove(index);
+        self.adjacency = None;
+        self.tiles = None;
+        for &vertex_index in triangle.indices() {
+            let mut isolated = true;
+            for other_triangle in self.triangles.iter() {
+                if other_triangle.indices().contains(&vertex_index) {
+                    isolated = false;
+                    break;
+                }
+            }
+
+            if isolated {
+                if let Some(vertex) = self.pathfinder.vertex_mut(vertex_index as usize) {
+                    let neighbour_indices = vertex.neighbours.clone();
+                    vertex.neighbours.clear();
+
+                    for neighbour_index in neighbour_indices {
+                        if let Some(neighbour_vertex) =
+                            self.pathfinder.vertex_mut(neighbour_index as usize)
+                        {
+                            if let Some(position) = neighbour_vertex
+                                .neighbours
+                                .iter()
+                                .position(|n| *n == vertex_index)
+                            {
+                                neighbour_vertex.neighbours.remove(position);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        triangle
+    }
+
+    /// Removes last triangle from the navigational mesh. Automatically fixes vertex links in the internal
+    /// navigational graph.
+    pub fn pop_triangle(&mut self) -> Option<TriangleDefinition> {
+        if self.triangles.is_empty() {
+            None
+        } else {
+            Some(self.remove_triangle(self.triangles.len() - 1))
+        }
+    }
+
+    /// Removes a vertex at the given index from the navigational mesh. All triangles that share the vertex will
+    /// be also removed.
+    pub fn remove_vertex(&mut self, index: usize) -> PathVertex {
+        // Remove triangles that sharing the vertex first.
+        let mut i = 0;
+        while i < self.triangles.len() {
+            if self.triangles[i].indices().contains(&(index as u32)) {
+                self.remove_triangle(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        // Shift vertex indices in triangles. Example:
+        //
+        // 0:A 1:B 2:C 3:D 4:E
+        // [A,B,C], [A,C,D], [A,D,E], [D,C,E]
+        // [0,1,2], [0,2,3], [0,3,4], [3,2,4]
+        //
+        // Remove B.
+        //
+        // 0:A 1:C 2:D 3:E
+        // [A,C,D], [A,D,E], [D,C,E]
+        // [0,1,2], [0,2,3], [2,1,3]
+        for triangle in self.triangles.iter_mut() {
+            for other_vertex_index in triangle.indices_mut() {
+                if *other_vertex_index > index as u32 {
+                    *other_vertex_index -= 1;
+                }
+            }
+        }
+
+        self.pathfinder.remove_vertex(index)
+    }
+
+    /// Returns reference to the internal array of vertices.
+    pub fn vertices(&self) -> &[PathVertex] {
+        self.pathfinder.vertices()
+    }
+
+    /// Returns reference to the graph of vertices of the navmesh. It could be used to build a
+    /// [`crate::utils::astar::hierarchy::HierarchicalPathFinder`] for huge navmeshes.
+    pub fn pathfinder(&self) -> &PathFinder {
+        &self.pathfinder
+    }
+
+    /// Returns a mutable reference to the internal array of vertices.
+    pub fn vertices_mut(&mut self) -> &mut [PathVertex] {
+        self.tiles = None;
+        self.pathfinder.vertices_mut()
+    }
+
+    /// Adds the vertex to the navigational mesh. The vertex will **not** be connected with any other vertex.
+    pub fn add_vertex(&mut self, vertex: PathVertex) -> u32 {
+        self.pathfinder.add_vertex(vertex)
+    }
+
+    /// Removes last vertex from the navigational mesh. All triangles that share the vertex will be also removed.
+    pub fn pop_vertex(&mut self) -> Option<PathVertex> {
+        if self.pathfinder.vertices().is_empty() {
+            None
+        } else {
+            Some(self.remove_vertex(self.pathfinder.vertices().len() - 1))
+        }
+    }
+
+    /// Inserts the vertex at the given index. Automatically shift indices in triangles to preserve mesh structure.
+    pub fn insert_vertex(&mut self, index: u32, vertex: PathVertex) {
+        self.pathfinder.insert_vertex(index, vertex);
+
+        // Shift vertex indices in triangles. Example:
+        //
+        // 0:A 1:C 2:D 3:E
+        // [A,C,D], [A,D,E], [D,C,E]
+        // [0,1,2], [0,2,3], [2,1,3]
+        //
+        // Insert B.
+        //
+        // 0:A 1:B 2:C 3:D 4:E
+        // [A,C,D], [A,D,E], [D,C,E]
+        // [0,2,3], [0,3,4], [3,2,4]
+        for triangle in self.triangles.iter_mut() {
+            for other_vertex_index in triangle.indices_mut() {
+                if *other_vertex_index >= index {
+                    *other_vertex_index += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns shared reference to inner octree.
+    pub fn octree(&self) -> &Octree {
+        &self.octree
+    }
+
+    /// Tries to build path using indices of begin and end points.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use fyrox::utils::navmesh::Navmesh;
+    /// use fyrox::core::algebra::Vector3;
+    /// use fyrox::utils::astar::{PathKind, PathError};
+    ///
+    /// fn find_path(navmesh: &mut Navmesh, begin: Vector3<f32>, end: Vector3<f32>, path: &mut Vec<Vector3<f32>>) -> Result<PathKind, PathError> {
+    ///     if let Some(begin_index) = navmesh.query_closest(begin) {
+    ///         if let Some(end_index) = navmesh.query_closest(end) {
+    ///             return navmesh.build_path(begin_index, end_index, path);
+    ///         }
+    ///     }
+    ///     Ok(PathKind::Empty)
+    /// }
+    /// ```
+    pub fn build_path(
+        &mut self,
+        from: usize,
+        to: usize,
+        path: &mut Vec<Vector3<f32>>,
+    ) -> Result<PathKind, PathError> {
+        self.pathfinder.build(from, to, path)
+    }
+
+    /// Tries to pick a triangle by given ray. Returns closest result.
+    pub fn ray_cast(&self, ray: Ray) -> Option<(Vector3<f32>, usize, TriangleDefinition)> {
+        let mut buffer = ArrayVec::<Handle<OctreeNode>, 128>::new();
+
+        self.octree.ray_query_static(&ray, &mut buffer);
+
+        let mut closest_distance = f32::MAX;
+        let mut result = None;
+        for node in buffer.into_iter() {
+            if let OctreeNode::Leaf { indices, .. } = self.octree.node(node) {
+                for &index in indices {
+                    let triangle = self.triangles[index as usize].clone();
+                    let a = self.pathfinder.vertices()[triangle[0] as usize].position;
+                    let b = self.pathfinder.vertices()[triangle[1] as usize].position;
+                    let c = self.pathfinder.vertices()[triangle[2] as usize].position;
+
+                    if let Some(intersection) = ray.triangle_intersection_point(&[a, b, c]) {
+                        let distance = intersection.metric_distance(&ray.origin);
+                        if distance < closest_distance {
+                            closest_distance = distance;
+                            result = Some((intersection, index as usize, triangle));
+                        }
+                    }
+                }
+            } else {
+                unreachable!()
+            }
+        }
+
+        result
+    }
+
+    fn triangle_points(&self, index: usize) -> [Vector3<f32>; 3] {
+        let vertices = self.pathfinder.vertices();
+        let triangle = &self.triangles[index];
+        [
+            vertices[triangle[0] as usize].position,
+            vertices[triangle[1] as usize].position,
+            vertices[triangle[2] as usize].position,
+        ]
+    }
+
+    fn triangle_center(&self, index: usize) -> Vector3<f32> {
+        self.triangle_points(index)
+            .iter()
+            .sum::<Vector3<f32>>()
+            .scale(1.0 / 3.0)
+    }
+
+    // Returns indices of the triangles, that share an edge with each triangle. Adjacency is
+    // calculated on demand and cached until the next change of triangles.
+    fn adjacency(&mut self) -> &[[Option<u32>; 3]] {
+        let triangles = &self.triangles;
+        self.adjacency.get_or_insert_with(|| {
+            let mut edges = FxHashMap::<(u32, u32), u32>::default();
+            let mut adjacency = vec![[None; 3]; triangles.len()];
+            for (index, triangle) in triangles.iter().enumerate() {
+                for (edge_index, edge) in triangle.edges().iter().enumerate() {
+                    let key = (edge.a.min(edge.b), edge.a.max(edge.b));
+                    if let Some(&other) = edges.get(&key) {
+                        adjacency[index][edge_index] = Some(other);
+                        if let Some(other_edge) = triangles[other as usize]
+                            .edges()
+                            .iter()
+                            .position(|e| *e == *edge)
+                        {
+                            adjacency[other as usize][other_edge] = Some(index as u32);
+                        }
+                    } else {
+                        edges.insert(key, index as u32);
+                    }
+                }
+            }
+            adjacency
+        })
+    }
+
+    /// Searches a sequence of adjacent triangles (a corridor) from one triangle to another, using
+    /// A* algorithm. If the destination is unreachable, the corridor leads to the reachable triangle,
+    /// that is closest to the destination, and [`PathKind::Partial`] is returned. This method does
+    /// not take obstacles into account.
+    pub fn build_corridor(
+        &mut self,
+        from_triangle: usize,
+        to_triangle: usize,
+        corridor: &mut Vec<usize>,
+    ) -> Result<PathKind, PathError> {
+        corridor.clear();
+
+        let count = self.triangles.len();
+        if from_triangle >= count {
+            return Err(PathError::InvalidIndex(from_triangle));
+        }
+        if to_triangle >= count {
+            return Err(PathError::InvalidIndex(to_triangle));
+        }
+
+        let centers = (0..count)
+            .map(|i| self.triangle_center(i))
+            .collect::<Vec<_>>();
+        let adjacency = self.adjacency();
+
+        let (triangles, reached) = search_graph(
+            from_triangle,
+            to_triangle,
+            |index| centers[index],
+            |index, neighbours| {
+                neighbours.extend(adjacency[index].iter().flatten().map(|n| {
+                    let n = *n as usize;
+                    (n, (), centers[index].metric_distance(&centers[n]))
+                }))
+            },
+        );
+        corridor.extend(triangles.into_iter().map(|(index, _)| index));
+
+        Ok(if reached {
+            PathKind::Full
+        } else {
+            PathKind::Partial
+        })
+    }
+
+    // Finds a triangle under the given point.
+    fn triangle_under(&self, point: Vector3<f32>) -> Option<(Vector3<f32>, usize)> {
+        self.ray_cast(Ray::new(
+            point + Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, -10.0, 0.0),
+        ))
+        .map(|(intersection, index, _)| (intersection, index))
+    }
+
+    // Finds a triangle under the given point, or the closest triangle if there's no such.
+    fn locate(&mut self, point: Vector3<f32>) -> Option<(Vector3<f32>, usize)> {
+        if let Some(location) = self.triangle_under(point) {
+            return Some(location);
+        }
+
+        let closest = self.query_closest(point)? as u32;
+        let position = self.pathfinder.vertices()[closest as usize].position;
+        self.triangles
+            .iter()
+            .position(|t| t.indices().contains(&closest))
+            .map(|index| (position, index))
+    }
+
+    // Returns amount of walkable pieces of a triangle.
+    fn piece_count(&self, triangle: usize) -> usize {
+        self.carved
+            .get(&(triangle as u32))
+            .map_or(1, |pieces| pieces.len())
+    }
+
+    // Returns a convex polygon of a walkable piece of a triangle. Triangles, that do not intersect
+    // any obstacle, consist of a single piece - the triangle itself.
+    fn piece(&self, (triangle, piece): PieceId) -> Cow<'_, [Vector3<f32>]> {
+        match self.carved.get(&(triangle as u32)) {
+            Some(pieces) => Cow::Borrowed(&pieces[piece]),
+            None => Cow::Owned(self.triangle_points(triangle).to_vec()),
+        }
+    }
+
+    fn piece_center(&self, piece: PieceId) -> Vector3<f32> {
+        let polygon = self.piece(piece);
+        polygon.iter().sum::<Vector3<f32>>() / polygon.len() as f32
+    }
+
+    fn piece_neighbours(
+        &self,
+        adjacency: &[[Option<u32>; 3]],
+        links: &[(Handle<OffMeshLink>, PieceId, PieceId)],
+        (triangle, piece): PieceId,
+        neighbours: &mut Vec<(PieceId, Step, f32)>,
+    ) {
+        let center = self.piece_center((triangle, piece));
+        let polygon = self.piece((triangle, piece));
+        let candidates = (0..self.piece_count(triangle))
+            .filter(|other| *other != piece)
+            .map(|other| (triangle, other))
+            .chain(adjacency[triangle].iter().flatten().flat_map(|other| {
+                let other = *other as usize;
+                (0..self.piece_count(other)).map(move |piece| (other, piece))
+            }));
+
+        for candidate in candidates {
+            if let Some(portal) = shared_edge(&polygon, &self.piece(candidate)) {
+                let cost = center.metric_distance(&self.piece_center(candidate));
+                neighbours.push((candidate, Step::Portal(portal), cost));
+            }
+        }
+
+        for (handle, start, end) in links {
+            let link = self.links.borrow(*handle);
+            let (other, reversed, start_point, end_point) = if *start == (triangle, piece) {
+                (*end, false, link.start(), link.end())
+            } else if *end == (triangle, piece) && link.is_bidirectional() {
+                (*start, true, link.end(), link.start())
+            } else {
+                continue;
+            };
+
+            let cost = center.metric_distance(&start_point)
+                + link.traversal_cost()
+                + end_point.metric_distance(&self.piece_center(other));
+            neighbours.push((
+                other,
+                Step::Link {
+                    handle: *handle,
+                    reversed,
+                },
+                cost,
+            ));
+        }
+    }
+
+    // Projects the point on the walkable surface of the navmesh. Returns `None` if there's no
+    // walkable surface under the point or if the surface is covered by an obstacle.
+    fn project_on_surface(&self, point: Vector3<f32>) -> Option<Vector3<f32>> {
+        let (position, triangle) = self.triangle_under(point)?;
+        if (0..self.piece_count(triangle))
+            .any(|piece| is_point_inside(&self.piece((triangle, piece)), &position))
+        {
+            Some(position)
+        } else {
+            None
+        }
+    }
+
+    // Finds walkable pieces at both ends of every off-mesh link. Links, that ends are not on the
+    // navmesh or are covered by obstacles, are ignored.
+    fn locate_links(&mut self) -> Vec<(Handle<OffMeshLink>, PieceId, PieceId)> {
+        let links = self
+            .links
+            .pair_iter()
+            .map(|(handle, link)| (handle, *link))
+            .collect::<Vec<_>>();
+
+        let mut result = Vec::with_capacity(links.len());
+        for (handle, link) in links {
+            if let (Some((start_point, start)), Some((end_point, end))) = (
+                self.locate_piece(link.start()),
+                self.locate_piece(link.end()),
+            ) {
+                if is_point_inside(&self.piece(start), &start_point)
+                    && is_point_inside(&self.piece(end), &end_point)
+                {
+                    result.push((handle, start, end));
+                }
+            }
+        }
+        result
+    }
+
+    // Finds a walkable piece under the given point, or the closest piece if there's no such.
+    fn locate_piece(&mut self, point: Vector3<f32>) -> Option<(Vector3<f32>, PieceId)> {
+        let (position, triangle) = self.locate(point)?;
+
+        if let Some(piece) = (0..self.piece_count(triangle))
+            .find(|piece| is_point_inside(&self.piece((triangle, *piece)), &position))
+        {
+            return Some((position, (triangle, piece)));
+        }
+
+        // The point is covered by an obstacle.
+        let mut closest = None;
+        let mut closest_distance = f32::MAX;
+        for triangle in 0..self.triangles.len() {
+            for piece in 0..self.piece_count(triangle) {
+                let distance = self
+                    .piece_center((triangle, piece))
+                    .metric_distance(&position);
+                if distance < closest_distance {
+                    closest_distance = distance;
+                    closest = Some((triangle, piece));
+                }
+            }
+        }
+        closest.map(|piece| (position, piece))
+    }
+
+    /// Builds the shortest path between two points on the navmesh. At first, it searches a
+    /// corridor of walkable polygons (see [`Self::build_corridor`]) and then straightens the path inside
+    /// the corridor using funnel algorithm (string pulling), so the path goes in straight lines
+    /// and bends only at corners of obstacles. Points, that are not on the navmesh, are replaced
+    /// with the closest vertices of the navmesh. Unlike other path finding methods, this one
+    /// takes [obstacles](Self::add_obstacle) into account. If the destination point is covered
+    /// by an obstacle, the path leads to the closest point near the obstacle and
+    /// [`PathKind::Partial`] is returned. The path could also go through
+    /// [off-mesh links](Self::add_link), use [`Self::build_smooth_path_with_links`] to get the
+    /// information about traversed links.
+    pub fn build_smooth_path(
+        &mut self,
+        from: Vector3<f32>,
+        to: Vector3<f32>,
+        path: &mut Vec<Vector3<f32>>,
+    ) -> Result<PathKind, PathError> {
+        self.build_smooth_path_with_links(from, to, path, &mut Vec::new())
+    }
+
+    /// The same as [`Self::build_smooth_path`], but also fills the given array with the off-mesh
+    /// links, that are traversed by the path, in the order of traversal.
+    pub fn build_smooth_path_with_links(
+        &mut self,
+        from: Vector3<f32>,
+        to: Vector3<f32>,
+        path: &mut Vec<Vector3<f32>>,
+        traversals: &mut Vec<OffMeshLinkTraversal>,
+    ) -> Result<PathKind, PathError> {
+        path.clear();
+        traversals.clear();
+
+        self.update_obstacles();
+
+        let (begin, from_piece) = match self.locate_piece(from) {
+            Some(location) => location,
+            None => return Ok(PathKind::Empty),
+        };
+        let (mut end, to_piece) = match self.locate_piece(to) {
+            Some(location) => location,
+            None => return Ok(PathKind::Empty),
+        };
+
+        let mut covered = false;
+        let target_polygon = self.piece(to_piece);
+        if !is_point_inside(&target_polygon, &end) {
+            end = closest_point(&target_polygon, &end);
+            covered = true;
+        }
+
+        let links = self.locate_links();
+
+        self.adjacency();
+        let adjacency = self.adjacency.as_deref().unwrap_or_default();
+
+        let (pieces, reached) = search_graph(
+            from_piece,
+            to_piece,
+            |piece| self.piece_center(piece),
+            |piece, neighbours| self.piece_neighbours(adjacency, &links, piece, neighbours),
+        );
+        if !reached {
+            if let Some((last, _)) = pieces.last() {
+                end = self.piece_center(*last);
+            }
+        }
+
+        let mut portals = vec![(begin, begin)];
+        for pair in pieces.windows(2) {
+            match pair[1].1 {
+                Some(Step::Portal((a, b))) => {
+                    let center = self.piece_center(pair[0].0);
+                    portals.push(if triarea2(&center, &a, &b) > 0.0 {
+                        (a, b)
+                    } else {
+                        (b, a)
+                    });
+                }
+                Some(Step::Link { handle, reversed }) => {
+                    let link = self.links.borrow(handle);
+                    let (start, end) = if reversed {
+                        (link.end(), link.start())
+                    } else {
+                        (link.start(), link.end())
+                    };
+
+                    // Straighten the path up to the start of the link and continue from its end.
+                    portals.push((start, start));
+                    string_pull(&portals, path);
+                    traversals.push(OffMeshLinkTraversal {
+                        link: handle,
+                        kind: link.kind(),
+                        index: path.len() - 1,
+                        start,
+                        end,
+                    });
+
+                    portals.clear();
+                    portals.push((end, end));
+                }
+                None => (),
+            }
+        }
+        portals.push((end, end));
+
+        string_pull(&portals, path);
+
+        Ok(if reached && !covered {
+            PathKind::Full
+        } else {
+            PathKind::Partial
+        })
+    }
+
+    /// Adds new off-mesh link to the navmesh.
+    pub fn add_link(&mut self, link: OffMeshLink) -> Handle<OffMeshLink> {
+        self.links.spawn(link)
+    }
+
+    /// Removes the off-mesh link from the navmesh and returns it, if the handle is valid.
+    pub fn remove_link(&mut self, handle: Handle<OffMeshLink>) -> Option<OffMeshLink> {
+        self.links.try_free(handle)
+    }
+
+    /// Returns a reference to the off-mesh link, if the handle is valid.
+    pub fn link(&self, handle: Handle<OffMeshLink>) -> Option<&OffMeshLink> {
+        self.links.try_borrow(handle)
+    }
+
+    /// Returns a reference to the pool of off-mesh links.
+    pub fn links(&self) -> &Pool<OffMeshLink> {
+        &self.links
+    }
+
+    /// Adds new obstacle to the navmesh. Triangles of the navmesh, that are intersected by the
+    /// obstacle, will be carved on the next [`Self::update_obstacles`] call.
+    pub fn add_obstacle(&mut self, obstacle: NavmeshObstacle) -> Handle<NavmeshObstacle> {
+        self.dirty_tiles.extend(tiles_of(&obstacle.bounds()));
+        self.obstacles.spawn(obstacle)
+    }
+
+    /// Removes the obstacle from the navmesh and returns it, if the handle is valid.
+    pub fn remove_obstacle(&mut self, handle: Handle<NavmeshObstacle>) -> Option<NavmeshObstacle> {
+        let obstacle = self.obstacles.try_free(handle)?;
+        self.dirty_tiles.extend(tiles_of(&obstacle.bounds()));
+        Some(obstacle)
+    }
+
+    /// Replaces the obstacle with a new one and returns the previous obstacle, if the handle is
+    /// valid. Only the tiles, that were covered by the previous obstacle or that are covered by
+    /// the new one, will be rebuilt.
+    pub fn set_obstacle(
+        &mut self,
+        handle: Handle<NavmeshObstacle>,
+        obstacle: NavmeshObstacle,
+    ) -> Option<NavmeshObstacle> {
+        let previous = std::mem::replace(self.obstacles.try_borrow_mut(handle)?, obstacle);
+        if previous != obstacle {
+            self.dirty_tiles.extend(tiles_of(&previous.bounds()));
+            self.dirty_tiles.extend(tiles_of(&obstacle.bounds()));
+        }
+        Some(previous)
+    }
+
+    /// Moves the obstacle to a new position. It is a shortcut for [`Self::set_obstacle`].
+    pub fn set_obstacle_position(
+        &mut self,
+        handle: Handle<NavmeshObstacle>,
+        position: Vector3<f32>,
+    ) {
+        if let Some(obstacle) = self.obstacles.try_borrow(handle).copied() {
+            self.set_obstacle(handle, obstacle.with_position(position));
+        }
+    }
+
+    /// Returns a reference to the obstacle, if the handle is valid.
+    pub fn obstacle(&self, handle: Handle<NavmeshObstacle>) -> Option<&NavmeshObstacle> {
+        self.obstacles.try_borrow(handle)
+    }
+
+    /// Returns a reference to the pool of obstacles.
+    pub fn obstacles(&self) -> &Pool<NavmeshObstacle> {
+        &self.obstacles
+    }
+
+    /// Rebuilds the tiles of the navmesh, that were affected by changed obstacles. Every rebuild
+    /// increases [revision](Self::revision) of the navmesh and adds the bounds of rebuilt triangles
+    /// to the list of changes (see [`Self::changes_since`]). There's no need to call this method
+    /// manually in most cases, [`Self::build_smooth_path`] calls it automatically.
+    pub fn update_obstacles(&mut self) {
+        if self.tiles.is_none() {
+            let mut tiles = FxHashMap::<(i32, i32), Vec<u32>>::default();
+            for index in 0..self.triangles.len() {
+                let bounds = AxisAlignedBoundingBox::from_points(&self.triangle_points(index));
+                for tile in tiles_of(&bounds) {
+                    tiles.entry(tile).or_default().push(index as u32);
+                }
+            }
+            self.tiles = Some(tiles);
+
+            // Triangles were changed, so every obstacle must be applied again.
+            self.carved.clear();
+            self.dirty_tiles
+                .extend(self.obstacles.iter().flat_map(|o| tiles_of(&o.bounds())));
+        }
+
+        if self.dirty_tiles.is_empty() {
+            return;
+        }
+
+        let tiles = self.tiles.as_ref().unwrap();
+        let triangles = self
+            .dirty_tiles
+            .drain()
+            .filter_map(|tile| tiles.get(&tile))
+            .flatten()
+            .copied()
+            .collect::<FxHashSet<_>>();
+
+        let mut changed_bounds: Option<AxisAlignedBoundingBox> = None;
+        for triangle in triangles {
+            let points = self.triangle_points(triangle as usize);
+            let bounds = AxisAlignedBoundingBox::from_points(&points);
+
+            let mut pieces = None;
+            for obstacle in self.obstacles.iter() {
+                if obstacle.bounds().is_intersects_aabb(&bounds) {
+                    let footprint = obstacle.footprint();
+                    let mut remaining = Vec::new();
+                    for piece in pieces.unwrap_or_else(|| vec![points.to_vec()]) {
+                        subtract(&piece, &footprint, &mut remaining);
+                    }
+                    pieces = Some(remaining);
+                }
+            }
+
+            let previous = match pieces.clone() {
+                Some(pieces) => self.carved.insert(triangle, pieces),
+                None => self.carved.remove(&triangle),
+            };
+
+            if previous != pieces {
+                match changed_bounds.as_mut() {
+                    Some(changed_bounds) => changed_bounds.add_box(bounds),
+                    None => changed_bounds = Some(bounds),
+                }
+            }
+        }
+
+        if let Some(changed_bounds) = changed_bounds {
+            self.revision += 1;
+            self.changes.push_back((self.revision, changed_bounds));
+            if self.changes.len() > MAX_CHANGES {
+                self.changes.pop_front();
+            }
+        }
+    }
+
+    /// Returns current revision of the navmesh. The revision is increased every time when
+    /// obstacles change the navmesh.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns bounds of the areas, that were changed by obstacles since the given revision.
+    /// Returns `None` if the changes are too old and are not tracked anymore, in this case
+    /// everything should be considered as changed.
+    pub fn changes_since(
+        &self,
+        revision: u64,
+    ) -> Option<impl Iterator<Item = &AxisAlignedBoundingBox>> {
+        let oldest = self
+            .changes
+            .front()
+            .map_or(self.revision + 1, |(revision, _)| *revision);
+        if revision < self.revision && revision + 1 < oldest {
+            return None;
+        }
+
+        Some(
+            self.changes
+                .iter()
+                .filter(move |(change, _)| *change > revision)
+                .map(|(_, bounds)| bounds),
+        )
+    }
+}
+
+fn tiles_of(bounds: &AxisAlignedBoundingBox) -> impl Iterator<Item = (i32, i32)> {
+    let min_x = (bounds.min.x / TILE_SIZE).floor() as i32;
+    let min_z = (bounds.min.z / TILE_SIZE).floor() as i32;
+    let max_x = (bounds.max.x / TILE_SIZE).floor() as i32;
+    let max_z = (bounds.max.z / TILE_SIZE).floor() as i32;
+    (min_x..=max_x).flat_map(move |x| (min_z..=max_z).map(move |z| (x, z)))
+}
+
+// Doubled signed area of a triangle projected on XZ plane. It is positive if `c` is on the right
+// side of `a -> b` line.
+fn triarea2(a: &Vector3<f32>, b: &Vector3<f32>, c: &Vector3<f32>) -> f32 {
+    (c.x - a.x) * (b.z - a.z) - (b.x - a.x) * (c.z - a.z)
+}
+
+fn is_same_point(a: &Vector3<f32>, b: &Vector3<f32>) -> bool {
+    (a - b).norm_squared() < 1.0e-6
+}
+
+// Funnel algorithm (string pulling), `portals` is a sequence of (left, right) pairs of portal
+// points, the first and the last portals must be degenerated into begin and end points.
+fn string_pull(portals: &[(Vector3<f32>, Vector3<f32>)], path: &mut Vec<Vector3<f32>>) {
+    let (mut apex, _) = portals[0];
+    let (mut left, mut right) = portals[0];
+    let (mut left_index, mut right_index) = (0, 0);
+
+    push_unique(path, apex);
+
+    let mut i = 1;
+    while i < portals.len() {
+        let (new_left, new_right) = portals[i];
+
+        // Try to narrow the funnel from the right side.
+        if triarea2(&apex, &right, &new_right) <= 0.0 {
+            if is_same_point(&apex, &right) || triarea2(&apex, &left, &new_right) > 0.0 {
+                right = new_right;
+                right_index = i;
+            } else {
+                // Right side crossed the left one, left point becomes the new apex.
+                push_unique(path, left);
+                apex = left;
+                right = apex;
+                right_index = left_index;
+                i = left_index + 1;
+                continue;
+            }
+        }
+
+        // Try to narrow the funnel from the left side.
+        if triarea2(&apex, &left, &new_left) >= 0.0 {
+            if is_same_point(&apex, &left) || triarea2(&apex, &right, &new_left) < 0.0 {
+                left = new_left;
+                left_index = i;
+            } else {
+                // Left side crossed the right one, right point becomes the new apex.
+                push_unique(path, right);
+                apex = right;
+                left = apex;
+                left_index = right_index;
+                i = right_index + 1;
+                continue;
+            }
+        }
+
+        i += 1;
+    }
+
+    if let Some((end, _)) = portals.last() {
+        push_unique(path, *end);
+    }
+}
+
+// Several consecutive portals may share a vertex, so the same corner could be reached twice.
+fn push_unique(path: &mut Vec<Vector3<f32>>, point: Vector3<f32>) {
+    if !matches!(path.last(), Some(last) if is_same_point(last, &point)) {
+        path.push(point);
+    }
+}
+
+/// Navmesh agent is a "pathfinding unit" that performs navigation on a mesh. It is designed to
+/// cover most of simple use cases when you need to build and follow some path from point A to point B.
+#[derive(Visit, Clone, Debug)]
+pub struct NavmeshAgent {
+    path: Vec<Vector3<f32>>,
+    current: u32,
+    position: Vector3<f32>,
+    last_warp_position: Vector3<f32>,
+    target: Vector3<f32>,
+    last_target_position: Vector3<f32>,
+    recalculation_threshold: f32,
+    speed: f32,
+    path_dirty: bool,
+    #[visit(skip)]
+    revision: u64,
+    #[visit(skip)]
+    partial_path: bool,
+    #[visit(skip)]
+    path_invalidated: bool,
+    #[visit(skip)]
+    traversals: Vec<OffMeshLinkTraversal>,
+    #[visit(skip)]
+    on_link: bool,
+}
+
+impl Default for NavmeshAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NavmeshAgent {
+    /// Creates new navigation mesh agent.
+    pub fn new() -> Self {
+        Self {
+            path: vec![],
+            current: 0,
+            position: Default::default(),
+            last_warp_position: Default::default(),
+            target: Default::default(),
+            last_target_position: Default::default(),
+            recalculation_threshold: 0.25,
+            speed: 1.5,
+            path_dirty: true,
+            revision: 0,
+            partial_path: false,
+            path_invalidated: false,
+            traversals: Default::default(),
+            on_link: false,
+        }
+    }
+
+    /// Returns agent's position.
+    pub fn position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    /// Returns agent's path that will be followed.
+    pub fn path(&self) -> &[Vector3<f32>] {
+        &self.path
+    }
+
+    /// Sets new speed of agent's movement.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Returns current agent's movement speed.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+}
+
+impl NavmeshAgent {
+    /// Calculates path from point A to point B. In most cases there is no need to use this method
+    /// directly, because `update` will call it anyway if target position has moved. See
+    /// [`Navmesh::build_smooth_path`] for more info.
+    pub fn calculate_path(
+        &mut self,
+        navmesh: &mut Navmesh,
+        from: Vector3<f32>,
+        to: Vector3<f32>,
+    ) -> Result<PathKind, PathError> {
+        self.current = 0;
+        self.on_link = false;
+
+        let kind =
+            navmesh.build_smooth_path_with_links(from, to, &mut self.path, &mut self.traversals)?;
+        self.revision = navmesh.revision();
+        self.partial_path = kind == PathKind::Partial;
+
+        match kind {
+            PathKind::Empty => Err(PathError::Custom("Empty navmesh!".to_owned())),
+            kind => Ok(kind),
+        }
+    }
+
+    // Checks whether the remaining part of the path was changed by obstacles since the path was
+    // calculated. Partial paths are always considered affected, because removed obstacles may
+    // open a way to the target.
+    fn is_path_affected(&mut self, navmesh: &Navmesh) -> bool {
+        if self.revision == navmesh.revision() {
+            return false;
+        }
+
+        let remaining = self.path.get(self.current as usize..).unwrap_or_default();
+        let affected = self.partial_path
+            || match navmesh.changes_since(self.revision) {
+                Some(mut changes) => changes.any(|bounds| {
+                    remaining.windows(2).any(|segment| {
+                        AxisAlignedBoundingBox::from_points(segment).is_intersects_aabb(bounds)
+                    })
+                }),
+                None => true,
+            };
+
+        self.revision = navmesh.revision();
+
+        affected
+    }
+
+    /// Returns `true` if the path of the agent was invalidated by navmesh obstacles during the last
+    /// [`Self::update`] call. Invalidated path is recalculated automatically, this method could be
+    /// used to react on such events - for example to play an animation of a confused character.
+    pub fn is_path_invalidated(&self) -> bool {
+        self.path_invalidated
+    }
+
+    /// Returns the off-mesh link, that the agent is about to traverse or is traversing right now.
+    pub fn current_link(&self) -> Option<&OffMeshLinkTraversal> {
+        self.traversals
+            .iter()
+            .find(|traversal| traversal.index == self.current as usize)
+    }
+
+    /// Performs single update tick that moves agent to the target along the path (which is automatically
+    /// recalculated if target's position has changed or if the path was blocked by obstacles).
+    /// Off-mesh links are traversed the same way as any other part of the path, use
+    /// [`Self::update_with_link_handler`] to customize the traversal.
+    pub fn update(&mut self, dt: f32, navmesh: &mut Navmesh) -> Result<PathKind, PathError> {
+        self.update_with_link_handler(dt, navmesh, |_| OffMeshLinkResponse::Move)
+    }
+
+    /// The same as [`Self::update`], but calls the given handler when the agent reaches the start
+    /// of an off-mesh link. The handler decides how the agent should traverse the link, see
+    /// [`OffMeshLinkResponse`] docs for more info. The path is not recalculated while the agent
+    /// is traversing a link.
+    pub fn update_with_link_handler<F>(
+        &mut self,
+        dt: f32,
+        navmesh: &mut Navmesh,
+        mut handler: F,
+    ) -> Result<PathKind, PathError>
+    where
+        F: FnMut(&OffMeshLinkTraversal) -> OffMeshLinkResponse,
+    {
+        if self.update_path(navmesh, &mut handler)? {
+            if let Some((source, destination)) = self.current_segment() {
+                let d = (destination - source)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_default();
+                self.advance(self.position + d.scale(self.speed * dt));
+            }
+        }
+
+        Ok(PathKind::Full)
+    }
+
+    // Recalculates the path if needed and asks the handler what to do with an off-mesh link, if
+    // the agent is at the start of one. Returns `false` if the agent must not move.
+    fn update_path<F>(&mut self, navmesh: &mut Navmesh, handler: &mut F) -> Result<bool, PathError>
+    where
+        F: FnMut(&OffMeshLinkTraversal) -> OffMeshLinkResponse,
+    {
+        navmesh.update_obstacles();
+
+        self.path_invalidated = !self.path_dirty && self.is_path_affected(navmesh);
+        if self.path_invalidated {
+            self.path_dirty = true;
+        }
+
+        if self.path_dirty && !self.on_link {
+            self.calculate_path(navmesh, self.position, self.target)?;
+            self.path_dirty = false;
+        }
+
+        if !self.on_link {
+            if let Some(traversal) = self.current_link().copied() {
+                match handler(&traversal) {
+                    OffMeshLinkResponse::Move => self.on_link = true,
+                    OffMeshLinkResponse::Teleport => {
+                        self.position = traversal.end;
+                        self.current += 1;
+                        return Ok(false);
+                    }
+                    OffMeshLinkResponse::Wait => return Ok(false),
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn current_segment(&self) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        let source = self.path.get(self.current as usize)?;
+        let destination = self.path.get(self.current as usize + 1)?;
+        Some((*source, *destination))
+    }
+
+    // Moves the agent to the given position and switches to the next segment of the path, if the
+    // agent has passed the current one.
+    fn advance(&mut self, position: Vector3<f32>) {
+        self.position = position;
+        if let Some((source, destination)) = self.current_segment() {
+            if Ray::from_two_points(source, destination).project_point(&position) >= 1.0 {
+                self.current += 1;
+                self.on_link = false;
+            }
+        }
+    }
+
+    /// Returns current steering target which in most cases next path point from which
+    /// agent is close to.
+    pub fn steering_target(&self) -> Option<Vector3<f32>> {
+        self.path
+            .get(self.current as usize + 1)
+            .or_else(|| self.path.last())
+            .cloned()
+    }
+
+    /// Sets new target for the agent.
+    pub fn set_target(&mut self, new_target: Vector3<f32>) {
+        if new_target.metric_distance(&self.last_target_position) >= self.recalculation_threshold {
+            self.path_dirty = true;
+            self.last_target_position = new_target;
+        }
+
+        self.target = new_target;
+    }
+
+    /// Returns current target of the agent.
+    pub fn target(&self) -> Vector3<f32> {
+        self.target
+    }
+
+    /// Sets new position of the agent.
+    pub fn set_position(&mut self, new_position: Vector3<f32>) {
+        if new_position.metric_distance(&self.last_warp_position) >= self.recalculation_threshold {
+            self.path_dirty = true;
+            self.last_warp_position = new_position;
+        }
+
+        self.position = new_position;
+    }
+}
+
+/// Allows you to build agent in declarative manner.
+pub struct NavmeshAgentBuilder {
+    position: Vector3<f32>,
+    target: Vector3<f32>,
+    recalculation_threshold: f32,
+    speed: f32,
+}
+
+impl Default for NavmeshAgentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NavmeshAgentBuilder {
+    /// Creates new builder instance.
+    pub fn new() -> Self {
+        Self {
+            position: Default::default(),
+            target: Default::default(),
+            recalculation_threshold: 0.25,
+            speed: 1.5,
+        }
+    }
+
+    /// Sets new desired position of the agent being built.
+    pub fn with_position(mut self, position: Vector3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets new desired target of the agent being built.
+    pub fn with_target(mut self, position: Vector3<f32>) -> Self {
+        self.target = position;
+        self
+    }
+
+    /// Sets new desired recalculation threshold (in meters) of the agent being built.
+    pub fn with_recalculation_threshold(mut self, threshold: f32) -> Self {
+        self.recalculation_threshold = threshold;
+        self
+    }
+
+    /// Sets new desired movement speed of the agent being built.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Build the agent.
+    pub fn build(self) -> NavmeshAgent {
+        NavmeshAgent {
+            position: self.position,
+            last_warp_position: self.position,
+            target: self.target,
+            last_target_position: self.target,
+            recalculation_threshold: self.recalculation_threshold,
+            speed: self.speed,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, math::TriangleDefinition},
+        utils::{
+            astar::PathKind,
+            navmesh::{
+                link::{OffMeshLink, OffMeshLinkKind, OffMeshLinkResponse},
+                obstacle::{NavmeshObstacle, NavmeshObstacleShape},
+                Navmesh, NavmeshAgentBuilder,
+            },
+        },
+    };
+
+    fn make_navmesh() -> Navmesh {
+        //             0                 1
+        //              *---------------*
+        //            / | \       A     |
+        //           /  |     \         |
+        //          /   |   B     \     |
+        //         /    |             \ |
+        //        /   3 *---------------* 2
+        //       / C  /                /
+        //      /   /    D      /
+        //     /  /      /
+        //    / /   /
+        //   //
+        //    4
+        Navmesh::new(
+            &[
+                TriangleDefinition([0, 1, 2]),
+                TriangleDefinition([0, 2, 3]),
+                TriangleDefinition([0, 3, 4]),
+                TriangleDefinition([3, 2, 4]),
+            ],
+            &[
+                Vector3::new(-1.0, 0.0, 1.0),
+                Vector3::new(1.0, 0.0, 1.0),
+                Vector3::new(1.0, 0.0, -1.0),
+                Vector3::new(-1.0, 0.0, -1.0),
+                Vector3::new(-2.0, 0.0, 2.0),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_remove_triangle() {
+        let mut navmesh = make_navmesh();
+
+        assert_eq!(navmesh.vertices()[0].neighbours, vec![4, 1, 2, 3]);
+        assert_eq!(navmesh.vertices()[1].neighbours, vec![2, 0]);
+        assert_eq!(navmesh.vertices()[2].neighbours, vec![1, 3, 0, 4]);
+        assert_eq!(navmesh.vertices()[3].neighbours, vec![4, 2, 0]);
+        assert_eq!(navmesh.vertices()[4].neighbours, vec![3, 0, 2]);
+
+        navmesh.remove_triangle(1); // B
+
+        assert_eq!(navmesh.vertices()[0].neighbours, vec![4, 1, 2, 3]);
+        assert_eq!(navmesh.vertices()[1].neighbours, vec![2, 0]);
+        assert_eq!(navmesh.vertices()[2].neighbours, vec![1, 3, 0, 4]);
+        assert_eq!(navmesh.vertices()[3].neighbours, vec![4, 2, 0]);
+        assert_eq!(navmesh.vertices()[4].neighbours, vec![3, 0, 2]);
+
+        navmesh.remove_triangle(0); // A
+
+        assert_eq!(navmesh.vertices()[0].neighbours, vec![4, 2, 3]);
+        assert_eq!(navmesh.vertices()[1].neighbours, Vec::<u32>::new());
+        assert_eq!(navmesh.vertices()[2].neighbours, vec![3, 0, 4]);
+        assert_eq!(navmesh.vertices()[3].neighbours, vec![4, 2, 0]);
+        assert_eq!(navmesh.vertices()[4].neighbours, vec![3, 0, 2]);
+
+        navmesh.remove_triangle(0); // C
+
+        assert_eq!(navmesh.vertices()[0].neighbours, Vec::<u32>::new());
+        assert_eq!(navmesh.vertices()[1].neighbours, Vec::<u32>::new());
+        assert_eq!(navmesh.vertices()[2].neighbours, vec![3, 4]);
+        assert_eq!(navmesh.vertices()[3].neighbours, vec![4, 2]);
+        assert_eq!(navmesh.vertices()[4].neighbours, vec![3, 2]);
+
+        navmesh.remove_triangle(0); // D
+
+        assert_eq!(navmesh.vertices()[0].neighbours, Vec::<u32>::new());
+        assert_eq!(navmesh.vertices()[1].neighbours, Vec::<u32>::new());
+        assert_eq!(navmesh.vertices()[2].neighbours, Vec::<u32>::new());
+        assert_eq!(navmesh.vertices()[3].neighbours, Vec::<u32>::new());
+        assert_eq!(navmesh.vertices()[4].neighbours, Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_remove_vertex() {
+        let mut navmesh = make_navmesh();
+
+        assert_eq!(navmesh.vertices()[0].neighbours, vec![4, 1, 2, 3]);
+        assert_eq!(navmesh.vertices()[1].neighbours, vec![2, 0]);
+        assert_eq!(navmesh.vertices()[2].neighbours, vec![1, 3, 0, 4]);
+        assert_eq!(navmesh.vertices()[3].neighbours, vec![4, 2, 0]);
+        assert_eq!(navmesh.vertices()[4].neighbours, vec![3, 0, 2]);
+
+        navmesh.remove_vertex(4);
+
+        assert_eq!(navmesh.triangles().len(), 2);
+
+        assert_eq!(navmesh.vertices()[0].neighbours, vec![1, 2, 3]);
+        assert_eq!(navmesh.vertices()[1].neighbours, vec![2, 0]);
+        assert_eq!(navmesh.vertices()[2].neighbours, vec![1, 3, 0]);
+        assert_eq!(navmesh.vertices()[3].neighbours, vec![2, 0]);
+
+        navmesh.remove_vertex(3);
+
+        assert_eq!(navmesh.triangles().len(), 1);
+
+        assert_eq!(navmesh.vertices()[0].neighbours, vec![1, 2]);
+        assert_eq!(navmesh.vertices()[1].neighbours, vec![2, 0]);
+        assert_eq!(navmesh.vertices()[2].neighbours, vec![1, 0]);
+
+        navmesh.remove_vertex(2);
+
+        assert_eq!(navmesh.triangles().len(), 0);
+
+        assert_eq!(navmesh.vertices()[0].neighbours, Vec::<u32>::new());
+        assert_eq!(navmesh.vertices()[1].neighbours, Vec::<u32>::new());
+
+        navmesh.remove_vertex(1);
+
+        assert_eq!(navmesh.triangles().len(), 0);
+
+        assert_eq!(navmesh.vertices()[0].neighbours, Vec::<u32>::new());
+
+        navmesh.remove_vertex(0);
+
+        assert_eq!(navmesh.triangles().len(), 0);
+        assert_eq!(navmesh.vertices().len(), 0);
+    }
+
+    // Creates a navmesh from a set of unit cells on XZ plane.
+    pub(super) fn make_grid_navmesh(cells: &[(u32, u32)]) -> Navmesh {
+        let size = 4;
+        let vertices = (0..size * size)
+            .map(|i| Vector3::new((i % size) as f32, 0.0, (i / size) as f32))
+            .collect::<Vec<_>>();
+        let mut triangles = Vec::new();
+        for &(x, z) in cells {
+            let a = z * size + x;
+            let b = (z + 1) * size + x;
+            let c = (z + 1) * size + x + 1;
+            let d = z * size + x + 1;
+            triangles.push(TriangleDefinition([a, b, c]));
+            triangles.push(TriangleDefinition([a, c, d]));
+        }
+        Navmesh::new(&triangles, &vertices)
+    }
+
+    #[test]
+    fn test_smooth_path() {
+        let mut path = Vec::new();
+
+        // Straight corridor, the path must be a straight line.
+        let mut navmesh = make_grid_navmesh(&[(0, 0), (1, 0), (2, 0)]);
+        let from = Vector3::new(0.5, 0.0, 0.5);
+        let to = Vector3::new(2.5, 0.0, 0.7);
+        assert_eq!(
+            navmesh.build_smooth_path(from, to, &mut path).unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(path, vec![from, to]);
+
+        // L-shaped corridor, the path must bend only at the inner corner.
+        let mut navmesh = make_grid_navmesh(&[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+        let to = Vector3::new(2.5, 0.0, 2.5);
+        assert_eq!(
+            navmesh.build_smooth_path(from, to, &mut path).unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(path, vec![from, Vector3::new(2.0, 0.0, 1.0), to]);
+
+        // Unreachable destination.
+        let mut navmesh = make_grid_navmesh(&[(0, 0), (2, 2)]);
+        assert_eq!(
+            navmesh.build_smooth_path(from, to, &mut path).unwrap(),
+            PathKind::Partial
+        );
+        assert_eq!(path.first(), Some(&from));
+    }
+
+    #[test]
+    fn test_obstacles() {
+        let mut navmesh = make_grid_navmesh(&[
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (1, 1),
+            (2, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+        ]);
+
+        let from = Vector3::new(0.5, 0.0, 0.5);
+        let to = Vector3::new(2.5, 0.0, 0.5);
+
+        let mut agent = NavmeshAgentBuilder::new()
+            .with_position(from)
+            .with_target(to)
+            .build();
+        agent.update(0.0, &mut navmesh).unwrap();
+        assert_eq!(agent.path(), &[from, to]);
+        assert!(!agent.is_path_invalidated());
+
+        // Block the straight way.
+        let obstacle = navmesh.add_obstacle(
+            NavmeshObstacle::new(NavmeshObstacleShape::Box {
+                half_extents: Vector3::new(0.5, 1.0, 0.5),
+            })
+            .with_position(Vector3::new(1.5, 0.0, 0.5)),
+        );
+        agent.update(0.0, &mut navmesh).unwrap();
+        assert!(agent.is_path_invalidated());
+        assert_eq!(navmesh.revision(), 1);
+        assert_eq!(
+            agent.path(),
+            &[
+                from,
+                Vector3::new(1.0, 0.0, 1.0),
+                Vector3::new(2.0, 0.0, 1.0),
+                to
+            ]
+        );
+
+        // Changes far from the path must not invalidate it.
+        let far = navmesh.add_obstacle(
+            NavmeshObstacle::new(NavmeshObstacleShape::Cylinder {
+                radius: 0.25,
+                height: 1.0,
+            })
+            .with_position(Vector3::new(0.5, 0.0, 2.5)),
+        );
+        agent.update(0.0, &mut navmesh).unwrap();
+        assert!(!agent.is_path_invalidated());
+        assert_eq!(navmesh.revision(), 2);
+        assert_eq!(navmesh.changes_since(1).unwrap().count(), 1);
+
+        // Block every way to the target.
+        navmesh.set_obstacle(
+            obstacle,
+            NavmeshObstacle::new(NavmeshObstacleShape::Box {
+                half_extents: Vector3::new(0.5, 1.0, 2.0),
+            })
+            .with_position(Vector3::new(1.5, 0.0, 1.5)),
+        );
+        let mut path = Vec::new();
+        assert_eq!(
+            navmesh.build_smooth_path(from, to, &mut path).unwrap(),
+            PathKind::Partial
+        );
+        assert!(path.iter().all(|p| p.x <= 1.0));
+
+        // Remove obstacles, the path must be straight again.
+        navmesh.remove_obstacle(obstacle);
+        navmesh.remove_obstacle(far);
+        agent.update(0.0, &mut navmesh).unwrap();
+        assert!(agent.is_path_invalidated());
+        assert_eq!(agent.path(), &[from, to]);
+    }
+
+    #[test]
+    fn test_off_mesh_links() {
+        // Two separate islands.
+        let mut navmesh = make_grid_navmesh(&[(0, 0), (2, 0)]);
+
+        let from = Vector3::new(0.5, 0.0, 0.5);
+        let to = Vector3::new(2.5, 0.0, 0.5);
+        let start = Vector3::new(0.9, 0.0, 0.5);
+        let end = Vector3::new(2.1, 0.0, 0.5);
+
+        let mut path = Vec::new();
+        assert_eq!(
+            navmesh.build_smooth_path(from, to, &mut path).unwrap(),
+            PathKind::Partial
+        );
+
+        let link =
+            navmesh.add_link(OffMeshLink::new(start, end).with_kind(OffMeshLinkKind::Ladder));
+
+        let mut traversals = Vec::new();
+        assert_eq!(
+            navmesh
+                .build_smooth_path_with_links(from, to, &mut path, &mut traversals)
+                .unwrap(),
+            PathKind::Full
+        );
+        assert_eq!(path, vec![from, start, end, to]);
+        assert_eq!(traversals.len(), 1);
+        assert_eq!(traversals[0].link, link);
+        assert_eq!(traversals[0].kind, OffMeshLinkKind::Ladder);
+        assert_eq!(traversals[0].index, 1);
+
+        // The link is one-way.
+        assert_eq!(
+            navmesh.build_smooth_path(to, from, &mut path).unwrap(),
+            PathKind::Partial
+        );
+
+        let mut agent = NavmeshAgentBuilder::new()
+            .with_position(from)
+            .with_target(to)
+            .with_speed(1.0)
+            .build();
+
+        let mut calls = 0;
+        let mut handler = |_: &_| {
+            calls += 1;
+            if calls < 3 {
+                OffMeshLinkResponse::Wait
+            } else {
+                OffMeshLinkResponse::Teleport
+            }
+        };
+
+        // Walk to the start of the link.
+        agent
+            .update_with_link_handler(0.5, &mut navmesh, &mut handler)
+            .unwrap();
+        assert!(agent.current_link().is_some());
+        for _ in 0..3 {
+            agent
+                .update_with_link_handler(0.5, &mut navmesh, &mut handler)
+                .unwrap();
+        }
+        assert_eq!(calls, 3);
+        assert_eq!(agent.position(), end);
+        assert!(agent.current_link().is_none());
+    }
+}
@@ -0,0 +1,330 @@
+//! Dynamic obstacles, that carve temporary holes in a navmesh. See [`NavmeshObstacle`] docs for
+//! more info.
+
+use crate::core::{
+    algebra::{Vector2, Vector3},
+    math::aabb::AxisAlignedBoundingBox,
+};
+
+/// Amount of sides of a polygon, that is used to approximate footprint of a cylinder.
+const CYLINDER_SIDES: usize = 12;
+
+/// Shape of a navmesh obstacle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NavmeshObstacleShape {
+    /// Oriented box, the box could be rotated only around vertical axis (see
+    /// [`NavmeshObstacle::with_rotation`]).
+    Box {
+        /// Half extents of the box.
+        half_extents: Vector3<f32>,
+    },
+    /// Vertical cylinder.
+    Cylinder {
+        /// Radius of the cylinder.
+        radius: f32,
+        /// Full height of the cylinder.
+        height: f32,
+    },
+}
+
+/// Navmesh obstacle is a temporary hole in a navmesh, it could be used to block some parts of a
+/// navmesh at runtime - for example around moved props or closed doors. Obstacles are added to a
+/// navmesh using [`super::Navmesh::add_obstacle`]. Every triangle of the navmesh, that intersects
+/// an obstacle, is split and the part, covered by the obstacle, is excluded from path finding.
+///
+/// Keep in mind, that obstacles do not take agent's size into account, so the size of an obstacle
+/// should be increased by the radius of agents.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NavmeshObstacle {
+    shape: NavmeshObstacleShape,
+    position: Vector3<f32>,
+    rotation: f32,
+}
+
+impl NavmeshObstacle {
+    /// Creates new obstacle of the given shape at the origin.
+    pub fn new(shape: NavmeshObstacleShape) -> Self {
+        Self {
+            shape,
+            position: Default::default(),
+            rotation: 0.0,
+        }
+    }
+
+    /// Sets desired position of the center of the obstacle.
+    pub fn with_position(mut self, position: Vector3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets desired rotation angle (in radians) of the obstacle around vertical axis.
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Returns shape of the obstacle.
+    pub fn shape(&self) -> NavmeshObstacleShape {
+        self.shape
+    }
+
+    /// Returns position of the center of the obstacle.
+    pub fn position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    /// Returns rotation angle (in radians) of the obstacle around vertical axis.
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    /// Returns world-space bounds of the obstacle.
+    pub fn bounds(&self) -> AxisAlignedBoundingBox {
+        let half_extents = match self.shape {
+            NavmeshObstacleShape::Box { half_extents } => {
+                let (sin, cos) = self.rotation.sin_cos();
+                Vector3::new(
+                    cos.abs() * half_extents.x + sin.abs() * half_extents.z,
+                    half_extents.y,
+                    sin.abs() * half_extents.x + cos.abs() * half_extents.z,
+                )
+            }
+            NavmeshObstacleShape::Cylinder { radius, height } => {
+                Vector3::new(radius, height * 0.5, radius)
+            }
+        };
+
+        AxisAlignedBoundingBox::from_min_max(
+            self.position - half_extents,
+            self.position + half_extents,
+        )
+    }
+
+    // Returns convex polygon on XZ plane, that covers the obstacle.
+    pub(super) fn footprint(&self) -> Vec<Vector2<f32>> {
+        let center = Vector2::new(self.position.x, self.position.z);
+        match self.shape {
+            NavmeshObstacleShape::Box { half_extents } => {
+                let (sin, cos) = self.rotation.sin_cos();
+                [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                    .iter()
+                    .map(|(x, z)| {
+                        let x = x * half_extents.x;
+                        let z = z * half_extents.z;
+                        center + Vector2::new(x * cos + z * sin, z * cos - x * sin)
+                    })
+                    .collect()
+            }
+            NavmeshObstacleShape::Cylinder { radius, .. } => {
+                // The polygon must enclose the circle.
+                let step = std::f32::consts::TAU / CYLINDER_SIDES as f32;
+                let radius = radius / (step * 0.5).cos();
+                (0..CYLINDER_SIDES)
+                    .map(|i| {
+                        let (sin, cos) = (i as f32 * step).sin_cos();
+                        center + Vector2::new(cos, sin).scale(radius)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+// Doubled signed area of a triangle on XZ plane.
+fn side(a: &Vector2<f32>, b: &Vector2<f32>, p: &Vector3<f32>) -> f32 {
+    (b.x - a.x) * (p.z - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+// Clips a convex polygon by a line, keeps the part on the given side of the line.
+fn clip(
+    polygon: &[Vector3<f32>],
+    a: &Vector2<f32>,
+    b: &Vector2<f32>,
+    sign: f32,
+) -> Vec<Vector3<f32>> {
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+    for (i, begin) in polygon.iter().enumerate() {
+        let end = &polygon[(i + 1) % polygon.len()];
+        let begin_distance = sign * side(a, b, begin);
+        let end_distance = sign * side(a, b, end);
+
+        if begin_distance >= 0.0 {
+            result.push(*begin);
+        }
+
+        if (begin_distance >= 0.0) != (end_distance >= 0.0) {
+            let t = begin_distance / (begin_distance - end_distance);
+            let point = begin.lerp(end, t);
+            if !matches!(result.last(), Some(last) if (last - point).norm_squared() < 1.0e-8) {
+                result.push(point);
+            }
+        }
+    }
+    result
+}
+
+fn area(polygon: &[Vector3<f32>]) -> f32 {
+    let mut area = 0.0;
+    for i in 1..polygon.len().saturating_sub(1) {
+        let (a, b, c) = (polygon[0], polygon[i], polygon[i + 1]);
+        area += (b.x - a.x) * (c.z - a.z) - (c.x - a.x) * (b.z - a.z);
+    }
+    area.abs() * 0.5
+}
+
+/// Subtracts a convex footprint from a convex polygon. The result is a set of convex polygons,
+/// that covers the part of the polygon outside the footprint.
+pub(super) fn subtract(
+    polygon: &[Vector3<f32>],
+    footprint: &[Vector2<f32>],
+    pieces: &mut Vec<Vec<Vector3<f32>>>,
+) {
+    let center = footprint.iter().sum::<Vector2<f32>>() / footprint.len() as f32;
+    let center = Vector3::new(center.x, 0.0, center.y);
+
+    let mut remaining = polygon.to_vec();
+    for (i, a) in footprint.iter().enumerate() {
+        let b = &footprint[(i + 1) % footprint.len()];
+        let inside = side(a, b, &center).signum();
+
+        let outside = clip(&remaining, a, b, -inside);
+        if area(&outside) > 1.0e-5 {
+            pieces.push(outside);
+        }
+
+        remaining = clip(&remaining, a, b, inside);
+        if area(&remaining) <= 1.0e-5 {
+            return;
+        }
+    }
+}
+
+/// Checks whether the point lies inside convex polygon on XZ plane.
+pub(super) fn is_point_inside(polygon: &[Vector3<f32>], point: &Vector3<f32>) -> bool {
+    let mut sign = 0.0;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[(i + 1) % polygon.len()];
+        let d = (b.x - a.x) * (point.z - a.z) - (b.z - a.z) * (point.x - a.x);
+        if d.abs() > f32::EPSILON {
+            if sign != 0.0 && d.signum() != sign {
+                return false;
+            }
+            sign = d.signum();
+        }
+    }
+    true
+}
+
+/// Returns the point of the boundary of a polygon, that is closest to the given point.
+pub(super) fn closest_point(polygon: &[Vector3<f32>], point: &Vector3<f32>) -> Vector3<f32> {
+    let mut closest = *point;
+    let mut closest_distance = f32::MAX;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[(i + 1) % polygon.len()];
+        let ab = b - a;
+        let t = ((point - a).dot(&ab) / ab.norm_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+        let candidate = a + ab.scale(t);
+        let distance = (candidate - point).norm_squared();
+        if distance < closest_distance {
+            closest_distance = distance;
+            closest = candidate;
+        }
+    }
+    closest
+}
+
+/// Returns a common part of edges of two convex polygons, if any.
+pub(super) fn shared_edge(
+    a: &[Vector3<f32>],
+    b: &[Vector3<f32>],
+) -> Option<(Vector3<f32>, Vector3<f32>)> {
+    for (i, begin) in a.iter().enumerate() {
+        let end = &a[(i + 1) % a.len()];
+        for (j, other_begin) in b.iter().enumerate() {
+            let other_end = &b[(j + 1) % b.len()];
+            if let Some(portal) = segment_overlap(begin, end, other_begin, other_end) {
+                return Some(portal);
+            }
+        }
+    }
+    None
+}
+
+// Returns a common part of two collinear segments, if any.
+fn segment_overlap(
+    a: &Vector3<f32>,
+    b: &Vector3<f32>,
+    c: &Vector3<f32>,
+    d: &Vector3<f32>,
+) -> Option<(Vector3<f32>, Vector3<f32>)> {
+    let ab = b - a;
+    let length_squared = ab.norm_squared();
+    if length_squared < 1.0e-8 {
+        return None;
+    }
+
+    let tc = (c - a).dot(&ab) / length_squared;
+    let td = (d - a).dot(&ab) / length_squared;
+    if (a + ab.scale(tc) - c).norm_squared() > 1.0e-6
+        || (a + ab.scale(td) - d).norm_squared() > 1.0e-6
+    {
+        return None;
+    }
+
+    let begin = tc.min(td).max(0.0);
+    let end = tc.max(td).min(1.0);
+    if (end - begin) * length_squared.sqrt() <= 1.0e-3 {
+        return None;
+    }
+
+    Some((a + ab.scale(begin), a + ab.scale(end)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        utils::navmesh::obstacle::{area, subtract, NavmeshObstacle, NavmeshObstacleShape},
+    };
+
+    #[test]
+    fn test_subtract() {
+        let square = [
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(4.0, 1.0, 0.0),
+            Vector3::new(4.0, 1.0, 4.0),
+            Vector3::new(0.0, 1.0, 4.0),
+        ];
+
+        let obstacle = NavmeshObstacle::new(NavmeshObstacleShape::Box {
+            half_extents: Vector3::new(1.0, 1.0, 1.0),
+        })
+        .with_position(Vector3::new(2.0, 1.0, 2.0));
+
+        let mut pieces = Vec::new();
+        subtract(&square, &obstacle.footprint(), &mut pieces);
+        assert_eq!(pieces.len(), 4);
+        let total = pieces.iter().map(|p| area(p)).sum::<f32>();
+        assert!((total - 12.0).abs() < 1.0e-4);
+        assert!(pieces.iter().flatten().all(|p| p.y == 1.0));
+
+        // Rotated obstacle, that does not intersect the polygon.
+        let obstacle = obstacle
+            .with_position(Vector3::new(10.0, 1.0, 10.0))
+            .with_rotation(0.5);
+        pieces.clear();
+        subtract(&square, &obstacle.footprint(), &mut pieces);
+        let total = pieces.iter().map(|p| area(p)).sum::<f32>();
+        assert!((total - 16.0).abs() < 1.0e-4);
+
+        // Obstacle, that covers the entire polygon.
+        let obstacle = NavmeshObstacle::new(NavmeshObstacleShape::Cylinder {
+            radius: 5.0,
+            height: 2.0,
+        })
+        .with_position(Vector3::new(2.0, 1.0, 2.0));
+        pieces.clear();
+        subtract(&square, &obstacle.footprint(), &mut pieces);
+        assert!(pieces.is_empty());
+    }
+}
@@ -0,0 +1,363 @@
+//! Asynchronous path queries with a per-frame budget. See [`PathQueryScheduler`] docs for more
+//! info.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::core::futures::executor::ThreadPool;
+use crate::{
+    core::{algebra::Vector3, parking_lot::Mutex},
+    utils::{
+        astar::{PathError, PathKind},
+        navmesh::{link::OffMeshLinkTraversal, Navmesh},
+    },
+};
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+/// Default amount of path queries, that could be started per frame.
+pub const DEFAULT_QUERY_BUDGET: usize = 8;
+
+/// Result of a successful path query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathQueryResult {
+    /// Points of the path, see [`Navmesh::build_smooth_path`] for more info.
+    pub path: Vec<Vector3<f32>>,
+    /// Off-mesh links, that are traversed by the path.
+    pub traversals: Vec<OffMeshLinkTraversal>,
+    /// Kind of the path.
+    pub kind: PathKind,
+}
+
+#[derive(Default)]
+struct QueryState {
+    result: Option<Result<PathQueryResult, PathError>>,
+    waker: Option<Waker>,
+    cancelled: bool,
+}
+
+type SharedQueryState = Arc<Mutex<QueryState>>;
+
+impl QueryState {
+    fn complete(state: &SharedQueryState, result: Result<PathQueryResult, PathError>) {
+        let mut state = state.lock();
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A handle to a path query, that was requested using [`PathQueryScheduler::request`]. The handle
+/// is a future, that resolves to the result of the query, it also could be polled manually every
+/// frame using [`Self::try_take`]. Dropping the handle cancels the query.
+pub struct PathQuery {
+    state: SharedQueryState,
+}
+
+impl Debug for PathQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PathQuery(ready: {})", self.is_ready())
+    }
+}
+
+impl PathQuery {
+    /// Returns `true` if the result of the query is ready.
+    pub fn is_ready(&self) -> bool {
+        self.state.lock().result.is_some()
+    }
+
+    /// Takes the result of the query, if it is ready. The result could be taken only once.
+    pub fn try_take(&self) -> Option<Result<PathQueryResult, PathError>> {
+        self.state.lock().result.take()
+    }
+}
+
+impl Future for PathQuery {
+    type Output = Result<PathQueryResult, PathError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for PathQuery {
+    fn drop(&mut self) {
+        self.state.lock().cancelled = true;
+    }
+}
+
+struct PendingQuery {
+    from: Vector3<f32>,
+    to: Vector3<f32>,
+    state: SharedQueryState,
+}
+
+type QueryCallback = Box<dyn FnOnce(Result<PathQueryResult, PathError>)>;
+
+/// Path query scheduler spreads path queries over multiple frames and runs them on a thread pool,
+/// so many simultaneous requests (for example, when a lot of agents repath at once) do not cause
+/// spikes of frame time. Every frame, [`Self::update`] starts at most [budget](Self::set_budget)
+/// queries, the rest of the queries wait in a queue in the order of their requests.
+///
+/// Queries are performed on a snapshot of a navmesh, the snapshot is updated automatically when
+/// [revision](Navmesh::revision) of the navmesh changes. Other changes of the navmesh (triangles,
+/// vertices or off-mesh links) must be reported using [`Self::invalidate`].
+///
+/// Results could be obtained either using [`PathQuery`] future (or by polling it manually), or
+/// using a callback, see [`Self::request_with_callback`]. Callbacks are called on the thread, that
+/// calls [`Self::update`].
+///
+/// On WebAssembly, there are no worker threads and queries are performed on the main thread, but
+/// the budget is still respected.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::algebra::Vector3,
+///     utils::navmesh::{query::PathQueryScheduler, Navmesh},
+/// };
+///
+/// fn request_paths(scheduler: &mut PathQueryScheduler, agents: &[Vector3<f32>]) {
+///     for position in agents {
+///         scheduler.request_with_callback(*position, Vector3::default(), |result| {
+///             if let Ok(result) = result {
+///                 println!("Path with {} points is ready!", result.path.len());
+///             }
+///         });
+///     }
+/// }
+///
+/// // Must be called once per frame.
+/// fn update(scheduler: &mut PathQueryScheduler, navmesh: &mut Navmesh) {
+///     scheduler.update(navmesh);
+/// }
+/// ```
+pub struct PathQueryScheduler {
+    #[cfg(not(target_arch = "wasm32"))]
+    thread_pool: ThreadPool,
+    budget: usize,
+    queue: VecDeque<PendingQuery>,
+    callbacks: Vec<(SharedQueryState, QueryCallback)>,
+    snapshot: Option<(u64, Arc<Navmesh>)>,
+}
+
+impl Debug for PathQueryScheduler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PathQueryScheduler")
+            .field("budget", &self.budget)
+            .field("queued", &self.queue.len())
+            .finish()
+    }
+}
+
+impl Default for PathQueryScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathQueryScheduler {
+    /// Creates new scheduler with default budget.
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            thread_pool: ThreadPool::new().unwrap(),
+            budget: DEFAULT_QUERY_BUDGET,
+            queue: Default::default(),
+            callbacks: Default::default(),
+            snapshot: None,
+        }
+    }
+
+    /// Sets amount of queries, that could be started per frame. Budget could not be less than one.
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget.max(1);
+    }
+
+    /// Returns amount of queries, that could be started per frame.
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Returns amount of queries, that are waiting in the queue.
+    pub fn queued_query_count(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Forces the scheduler to take a new snapshot of the navmesh on the next update. Must be
+    /// called when the navmesh was changed without changing its revision - for example, when
+    /// triangles or off-mesh links were added or removed.
+    pub fn invalidate(&mut self) {
+        self.snapshot = None;
+    }
+
+    fn enqueue(&mut self, from: Vector3<f32>, to: Vector3<f32>) -> SharedQueryState {
+        let state = SharedQueryState::default();
+        self.queue.push_back(PendingQuery {
+            from,
+            to,
+            state: state.clone(),
+        });
+        state
+    }
+
+    /// Requests a path between two points. Returns a handle to the query, that could be used to
+    /// get the result.
+    pub fn request(&mut self, from: Vector3<f32>, to: Vector3<f32>) -> PathQuery {
+        PathQuery {
+            state: self.enqueue(from, to),
+        }
+    }
+
+    /// Requests a path between two points. The callback will be called by [`Self::update`], when
+    /// the result is ready.
+    pub fn request_with_callback<F>(&mut self, from: Vector3<f32>, to: Vector3<f32>, callback: F)
+    where
+        F: FnOnce(Result<PathQueryResult, PathError>) + 'static,
+    {
+        let state = self.enqueue(from, to);
+        self.callbacks.push((state, Box::new(callback)));
+    }
+
+    /// Starts the next queries from the queue (at most [budget](Self::budget) of them) and calls
+    /// callbacks of the finished queries. Must be called once per frame.
+    pub fn update(&mut self, navmesh: &mut Navmesh) {
+        let mut batch = Vec::new();
+        while batch.len() < self.budget {
+            match self.queue.pop_front() {
+                Some(query) => {
+                    // Skip queries, whose handles were dropped.
+                    if !query.state.lock().cancelled {
+                        batch.push(query);
+                    }
+                }
+                None => break,
+            }
+        }
+
+        if !batch.is_empty() {
+            // Make sure, that the revision reflects the current state of the obstacles.
+            navmesh.update_obstacles();
+
+            let snapshot = match self.snapshot.as_ref() {
+                Some((revision, snapshot)) if *revision == navmesh.revision() => snapshot.clone(),
+                _ => {
+                    let snapshot = Arc::new(navmesh.clone());
+                    self.snapshot = Some((navmesh.revision(), snapshot.clone()));
+                    snapshot
+                }
+            };
+
+            let task = move || {
+                // Path finding requires mutable access to internal caches of the navmesh.
+                let mut navmesh = (*snapshot).clone();
+                for query in batch {
+                    if query.state.lock().cancelled {
+                        continue;
+                    }
+
+                    let mut path = Vec::new();
+                    let mut traversals = Vec::new();
+                    let result = navmesh
+                        .build_smooth_path_with_links(
+                            query.from,
+                            query.to,
+                            &mut path,
+                            &mut traversals,
+                        )
+                        .map(|kind| PathQueryResult {
+                            path,
+                            traversals,
+                            kind,
+                        });
+                    QueryState::complete(&query.state, result);
+                }
+            };
+
+            #[cfg(target_arch = "wasm32")]
+            task();
+
+            #[cfg(not(target_arch = "wasm32"))]
+            self.thread_pool.spawn_ok(async move { task() });
+        }
+
+        let mut i = 0;
+        while i < self.callbacks.len() {
+            let result = self.callbacks[i].0.lock().result.take();
+            match result {
+                Some(result) => {
+                    let (_, callback) = self.callbacks.swap_remove(i);
+                    callback(result);
+                }
+                None => i += 1,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, futures::executor::block_on},
+        utils::{
+            astar::PathKind,
+            navmesh::{query::PathQueryScheduler, test::make_grid_navmesh},
+        },
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn test_path_queries() {
+        let mut navmesh = make_grid_navmesh(&[(0, 0), (1, 0), (2, 0)]);
+        let mut scheduler = PathQueryScheduler::new();
+        scheduler.set_budget(2);
+
+        let from = Vector3::new(0.5, 0.0, 0.5);
+        let to = Vector3::new(2.5, 0.0, 0.7);
+
+        let queries = (0..3)
+            .map(|_| scheduler.request(from, to))
+            .collect::<Vec<_>>();
+        let dropped = scheduler.request(from, to);
+        drop(dropped);
+
+        let finished = Rc::new(RefCell::new(0));
+        let counter = finished.clone();
+        scheduler.request_with_callback(from, to, move |result| {
+            assert_eq!(result.unwrap().kind, PathKind::Full);
+            *counter.borrow_mut() += 1;
+        });
+        assert_eq!(scheduler.queued_query_count(), 5);
+
+        scheduler.update(&mut navmesh);
+        assert_eq!(scheduler.queued_query_count(), 3);
+
+        // The dropped query is skipped and does not consume the budget.
+        scheduler.update(&mut navmesh);
+        assert_eq!(scheduler.queued_query_count(), 0);
+
+        for query in queries {
+            let result = block_on(query).unwrap();
+            assert_eq!(result.kind, PathKind::Full);
+            assert_eq!(result.path, vec![from, to]);
+        }
+
+        while *finished.borrow() == 0 {
+            scheduler.update(&mut navmesh);
+            std::thread::yield_now();
+        }
+    }
+}
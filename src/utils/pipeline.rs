@@ -0,0 +1,498 @@
+//! Batch processing of game content without the editor and without a window. See [`ContentPipeline`]
+//! docs for more info.
+
+use crate::{
+    asset::manager::{ResourceManager, ResourceRegistrationError},
+    core::visitor::prelude::*,
+    engine::{initialize_resource_manager_loaders, SerializationContext},
+    scene::{
+        mesh::Mesh, navmesh::NavigationalMesh, terrain::Terrain, validation::SceneValidationReport,
+        Scene, SceneLoader,
+    },
+    utils::{
+        lightmap::{Lightmap, LightmapGenerationError},
+        navmesh::generator::{NavmeshGenerationSettings, NavmeshGenerator},
+    },
+};
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Default extension of scene files.
+pub const SCENE_EXTENSION: &str = "rgs";
+
+/// All possible errors that may occur during content processing.
+#[derive(Debug)]
+pub enum ContentPipelineError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// An error occurred during serialization or deserialization of a scene.
+    Visit(VisitError),
+    /// Lightmap generation has failed.
+    Lightmap(LightmapGenerationError),
+    /// Unable to save lightmap textures.
+    ResourceRegistration(ResourceRegistrationError),
+    /// Generated lightmap cannot be applied to the scene.
+    InvalidLightmap(&'static str),
+}
+
+impl Display for ContentPipelineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentPipelineError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            ContentPipelineError::Visit(v) => write!(f, "Unable to (de)serialize a scene: {v}"),
+            ContentPipelineError::Lightmap(v) => write!(f, "Unable to generate lightmap: {v}"),
+            ContentPipelineError::ResourceRegistration(v) => {
+                write!(f, "Unable to save lightmap textures: {v}")
+            }
+            ContentPipelineError::InvalidLightmap(v) => {
+                write!(f, "Unable to apply lightmap: {v}")
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for ContentPipelineError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for ContentPipelineError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+impl From<LightmapGenerationError> for ContentPipelineError {
+    fn from(e: LightmapGenerationError) -> Self {
+        Self::Lightmap(e)
+    }
+}
+
+impl From<ResourceRegistrationError> for ContentPipelineError {
+    fn from(e: ResourceRegistrationError) -> Self {
+        Self::ResourceRegistration(e)
+    }
+}
+
+/// Lightmap baking options.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightmapBakingOptions {
+    /// Resolution of the lightmap, see [`Lightmap::new`] for more info.
+    pub texels_per_unit: u32,
+    /// A directory, where lightmap textures will be saved. `None` - a directory with the name of the
+    /// scene (and `_lightmap` suffix) next to the scene file.
+    pub output_directory: Option<PathBuf>,
+}
+
+impl Default for LightmapBakingOptions {
+    fn default() -> Self {
+        Self {
+            texels_per_unit: 64,
+            output_directory: None,
+        }
+    }
+}
+
+/// A set of steps, that will be performed by [`ContentPipeline::process_scene`]. Steps are performed
+/// in the following order: lightmap baking, navmesh generation, validation, saving.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneProcessingOptions {
+    /// Bakes lightmap of the scene, if set.
+    pub lightmap: Option<LightmapBakingOptions>,
+    /// Re-generates navigational meshes of the scene, if set. See [`ContentPipeline::generate_navmeshes`]
+    /// for more info.
+    pub navmesh: Option<NavmeshGenerationSettings>,
+    /// Validates the scene, if set. See [`Scene::validate`] for more info.
+    pub validate: bool,
+    /// Saves the scene using the latest version of the format, if set. The scene is also saved if
+    /// it was modified by any other step.
+    pub resave: bool,
+    /// A path, where the processed scene will be saved. `None` - the source file will be overwritten.
+    pub output_path: Option<PathBuf>,
+}
+
+/// A result of scene processing.
+#[derive(Clone, Debug, Default)]
+pub struct SceneProcessingReport {
+    /// A path of the source scene.
+    pub path: PathBuf,
+    /// Amount of lightmap textures, that were baked.
+    pub lightmap_texture_count: usize,
+    /// Amount of navigational meshes, that were re-generated.
+    pub navmesh_count: usize,
+    /// Validation report, if the validation was requested.
+    pub validation: Option<SceneValidationReport>,
+    /// A path, where the processed scene was saved, if it was saved.
+    pub saved_to: Option<PathBuf>,
+}
+
+impl SceneProcessingReport {
+    /// Returns `true` if there's no validation problems in the scene (or the validation was not
+    /// requested).
+    pub fn is_ok(&self) -> bool {
+        self.validation.as_ref().map_or(true, |v| v.is_ok())
+    }
+}
+
+impl Display for SceneProcessingReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Scene: {}", self.path.display())?;
+        writeln!(f, "Lightmap textures: {}", self.lightmap_texture_count)?;
+        writeln!(f, "Navmeshes: {}", self.navmesh_count)?;
+        if let Some(validation) = self.validation.as_ref() {
+            writeln!(f, "{}", validation)?;
+        }
+        match self.saved_to.as_ref() {
+            Some(path) => write!(f, "Saved to: {}", path.display()),
+            None => write!(f, "Not saved"),
+        }
+    }
+}
+
+/// Content pipeline allows to process game content (scenes) without the editor and without any window
+/// or graphics context, so it could be used in build scripts, CI jobs or custom command-line tools to
+/// automate content processing. It can:
+///
+/// - Open scenes headlessly (every resource used by a scene is loaded too).
+/// - Re-save scenes using the latest version of the format.
+/// - Bake lightmaps.
+/// - Generate navigational meshes.
+/// - Validate scenes (see [`Scene::validate`]).
+///
+/// All methods that load content are `async`, use any executor (for example
+/// `fyrox::core::futures::executor::block_on`) to run them in a synchronous context.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     core::futures::executor::block_on,
+///     utils::{
+///         navmesh::generator::NavmeshGenerationSettings,
+///         pipeline::{ContentPipeline, SceneProcessingOptions},
+///     },
+/// };
+///
+/// fn process_levels() {
+///     let pipeline = ContentPipeline::new();
+///     let scenes = ContentPipeline::find_scenes("data/levels").unwrap();
+///     let options = SceneProcessingOptions {
+///         navmesh: Some(NavmeshGenerationSettings::default()),
+///         validate: true,
+///         resave: true,
+///         ..Default::default()
+///     };
+///     for (path, result) in block_on(pipeline.process_scenes(scenes, &options)) {
+///         match result {
+///             Ok(report) if report.is_ok() => println!("{report}"),
+///             Ok(report) => panic!("Scene {} is invalid:\n{report}", path.display()),
+///             Err(error) => panic!("Unable to process {}: {error}", path.display()),
+///         }
+///     }
+/// }
+/// ```
+pub struct ContentPipeline {
+    serialization_context: Arc<SerializationContext>,
+    resource_manager: ResourceManager,
+}
+
+impl Default for ContentPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentPipeline {
+    /// Creates new content pipeline with its own serialization context and resource manager. Use
+    /// [`Self::with_context`] if the content uses custom node types or custom resources.
+    pub fn new() -> Self {
+        let serialization_context = Arc::new(SerializationContext::new());
+        let resource_manager = ResourceManager::new();
+        initialize_resource_manager_loaders(&resource_manager, serialization_context.clone());
+        Self::with_context(serialization_context, resource_manager)
+    }
+
+    /// Creates new content pipeline, that uses the given serialization context and resource manager.
+    /// Resource manager must have all required resource loaders registered, for example it could be
+    /// the resource manager of a headless engine (see [`crate::engine::Engine::new_headless`]).
+    pub fn with_context(
+        serialization_context: Arc<SerializationContext>,
+        resource_manager: ResourceManager,
+    ) -> Self {
+        Self {
+            serialization_context,
+            resource_manager,
+        }
+    }
+
+    /// Returns a reference to the serialization context of the pipeline.
+    pub fn serialization_context(&self) -> &Arc<SerializationContext> {
+        &self.serialization_context
+    }
+
+    /// Returns a reference to the resource manager of the pipeline.
+    pub fn resource_manager(&self) -> &ResourceManager {
+        &self.resource_manager
+    }
+
+    /// Recursively searches for scene files (with [`SCENE_EXTENSION`]) in the given directory. Paths
+    /// are sorted, so the processing order is deterministic.
+    pub fn find_scenes<P: AsRef<Path>>(directory: P) -> Result<Vec<PathBuf>, ContentPipelineError> {
+        fn find_recursive(directory: &Path, scenes: &mut Vec<PathBuf>) -> std::io::Result<()> {
+            for entry in fs::read_dir(directory)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    find_recursive(&path, scenes)?;
+                } else if path
+                    .extension()
+                    .map_or(false, |extension| extension == SCENE_EXTENSION)
+                {
+                    scenes.push(path);
+                }
+            }
+            Ok(())
+        }
+
+        let mut scenes = Vec::new();
+        find_recursive(directory.as_ref(), &mut scenes)?;
+        scenes.sort();
+        Ok(scenes)
+    }
+
+    /// Loads a scene from the given file and waits until every resource used by the scene is loaded.
+    pub async fn open_scene<P: AsRef<Path>>(&self, path: P) -> Result<Scene, ContentPipelineError> {
+        let loader = SceneLoader::from_file(
+            path,
+            self.serialization_context.clone(),
+            self.resource_manager.clone(),
+        )
+        .await?;
+        Ok(loader.finish().await)
+    }
+
+    /// Saves the scene to the given file using the latest version of the format. Missing directories
+    /// are created.
+    pub fn save_scene<P: AsRef<Path>>(
+        &self,
+        scene: &mut Scene,
+        path: P,
+    ) -> Result<(), ContentPipelineError> {
+        if let Some(directory) = path.as_ref().parent() {
+            if !directory.as_os_str().is_empty() {
+                fs::create_dir_all(directory)?;
+            }
+        }
+
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    /// Bakes lightmap of the whole scene, saves lightmap textures to the given directory and applies
+    /// the lightmap to the scene. Returns the amount of baked textures.
+    pub fn bake_lightmap<P: AsRef<Path>>(
+        &self,
+        scene: &mut Scene,
+        texels_per_unit: u32,
+        output_directory: P,
+    ) -> Result<usize, ContentPipelineError> {
+        let lightmap = Lightmap::new(
+            scene,
+            texels_per_unit,
+            |_, _| true,
+            Default::default(),
+            Default::default(),
+        )?;
+        fs::create_dir_all(output_directory.as_ref())?;
+        lightmap.save(output_directory, self.resource_manager.clone())?;
+        let texture_count = lightmap.map.values().map(|entries| entries.len()).sum();
+        scene
+            .set_lightmap(lightmap)
+            .map_err(ContentPipelineError::InvalidLightmap)?;
+        Ok(texture_count)
+    }
+
+    /// Re-generates every navigational mesh of the scene using every enabled mesh and terrain of the
+    /// scene as the source geometry. Returns the amount of re-generated navigational meshes.
+    pub fn generate_navmeshes(
+        &self,
+        scene: &mut Scene,
+        settings: &NavmeshGenerationSettings,
+    ) -> usize {
+        scene.graph.update_hierarchical_data();
+
+        let navmeshes = scene
+            .graph
+            .pair_iter()
+            .filter(|(_, node)| node.cast::<NavigationalMesh>().is_some())
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+        if navmeshes.is_empty() {
+            return 0;
+        }
+
+        let mut generator = NavmeshGenerator::new(settings.clone());
+        for (handle, node) in scene.graph.pair_iter() {
+            if node.is_globally_enabled()
+                && (node.cast::<Mesh>().is_some() || node.cast::<Terrain>().is_some())
+            {
+                generator.add_node(&scene.graph, handle);
+            }
+        }
+        let navmesh = generator.generate();
+
+        for handle in navmeshes.iter() {
+            if let Some(navigational_mesh) = scene.graph[*handle].cast_mut::<NavigationalMesh>() {
+                *navigational_mesh.navmesh_mut() = navmesh.clone();
+            }
+        }
+
+        navmeshes.len()
+    }
+
+    /// Loads the scene from the given file and processes it using the given options.
+    pub async fn process_scene<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &SceneProcessingOptions,
+    ) -> Result<SceneProcessingReport, ContentPipelineError> {
+        let path = path.as_ref();
+        let mut scene = self.open_scene(path).await?;
+        let mut report = SceneProcessingReport {
+            path: path.to_owned(),
+            ..Default::default()
+        };
+
+        if let Some(lightmap) = options.lightmap.as_ref() {
+            let output_directory = lightmap.output_directory.clone().unwrap_or_else(|| {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                path.with_file_name(format!("{}_lightmap", stem))
+            });
+            report.lightmap_texture_count =
+                self.bake_lightmap(&mut scene, lightmap.texels_per_unit, output_directory)?;
+        }
+
+        if let Some(settings) = options.navmesh.as_ref() {
+            report.navmesh_count = self.generate_navmeshes(&mut scene, settings);
+        }
+
+        if options.validate {
+            report.validation = Some(scene.validate());
+        }
+
+        if options.resave || options.lightmap.is_some() || report.navmesh_count > 0 {
+            let output_path = options.output_path.as_deref().unwrap_or(path);
+            self.save_scene(&mut scene, output_path)?;
+            report.saved_to = Some(output_path.to_owned());
+        }
+
+        Ok(report)
+    }
+
+    /// Processes every scene from the given list using the same options. Scenes are processed one by
+    /// one, an error in one scene does not stop the processing of the rest of them. If
+    /// [`SceneProcessingOptions::output_path`] is set, it is treated as a directory, where processed
+    /// scenes will be saved (with their original file names).
+    pub async fn process_scenes<I, P>(
+        &self,
+        paths: I,
+        options: &SceneProcessingOptions,
+    ) -> Vec<(PathBuf, Result<SceneProcessingReport, ContentPipelineError>)>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut results = Vec::new();
+        for path in paths {
+            let path = path.as_ref().to_owned();
+            let options = SceneProcessingOptions {
+                output_path: options
+                    .output_path
+                    .as_ref()
+                    .map(|directory| directory.join(path.file_name().unwrap_or_default())),
+                ..options.clone()
+            };
+            let result = self.process_scene(&path, &options).await;
+            results.push((path, result));
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector3},
+            futures::executor::block_on,
+        },
+        scene::{
+            base::BaseBuilder,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+                MeshBuilder,
+            },
+            navmesh::NavigationalMeshBuilder,
+        },
+    };
+
+    #[test]
+    fn test_process_scenes() {
+        let directory = std::env::temp_dir().join("fyrox_test_content_pipeline");
+        let _ = fs::remove_dir_all(&directory);
+        let source = directory.join("source");
+        let output = directory.join("output");
+
+        let pipeline = ContentPipeline::new();
+
+        let mut scene = Scene::new();
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+                SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                    10.0, 1.0, 10.0,
+                ))),
+            ))
+            .build()])
+            .build(&mut scene.graph);
+        let navmesh = NavigationalMeshBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        pipeline
+            .save_scene(&mut scene, source.join("level.rgs"))
+            .unwrap();
+
+        let scenes = ContentPipeline::find_scenes(&directory).unwrap();
+        assert_eq!(scenes, vec![source.join("level.rgs")]);
+
+        let options = SceneProcessingOptions {
+            navmesh: Some(Default::default()),
+            validate: true,
+            output_path: Some(output.clone()),
+            ..Default::default()
+        };
+        let results = block_on(pipeline.process_scenes(scenes, &options));
+        assert_eq!(results.len(), 1);
+        let report = results[0].1.as_ref().unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.navmesh_count, 1);
+        assert_eq!(report.saved_to, Some(output.join("level.rgs")));
+
+        let processed = block_on(pipeline.open_scene(output.join("level.rgs"))).unwrap();
+        assert!(!processed.graph[navmesh]
+            .cast::<NavigationalMesh>()
+            .unwrap()
+            .navmesh_ref()
+            .triangles()
+            .is_empty());
+
+        assert!(matches!(
+            block_on(pipeline.open_scene(directory.join("missing.rgs"))),
+            Err(ContentPipelineError::Visit(_))
+        ));
+    }
+}
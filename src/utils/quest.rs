@@ -0,0 +1,540 @@
+//! Quest tracking. [`QuestLog`] stores the state of started quests and advances their objectives when
+//! it receives [`QuestMessage`]s. Every change of the log is reported as a [`QuestEvent`], so HUD and
+//! journal user interfaces could react to it. See [`crate::resource::quest`] for the description of quests.
+//!
+//! Quest messages are usually sent via the script message bus (see [`crate::script::ScriptMessageSender`]),
+//! so any script could report progress without knowing anything about quests. A script, that owns the
+//! log, should subscribe to [`QuestMessage`] and pass incoming messages to [`QuestLog::on_message`]. The
+//! log is serializable, so it is saved alongside other game entities.
+//!
+//! ```rust
+//! use fyrox::{
+//!     script::{ScriptMessagePayload, ScriptMessageSender},
+//!     utils::quest::{QuestEvent, QuestLog, QuestMessage},
+//! };
+//!
+//! fn on_wolf_killed(sender: &ScriptMessageSender) {
+//!     sender.send_global(QuestMessage::new("killed:wolf"));
+//! }
+//!
+//! fn on_message(log: &mut QuestLog, message: &mut dyn ScriptMessagePayload) {
+//!     if log.on_message(message) {
+//!         while let Some(event) = log.pop_event() {
+//!             if let QuestEvent::Completed { quest } = event {
+//!                 println!("{quest} is completed!");
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::{
+    asset::ResourceStateRef,
+    core::visitor::prelude::*,
+    resource::quest::{Quest, QuestResource},
+    script::ScriptMessagePayload,
+};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+};
+
+/// An error that may occur during quest log operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuestLogError {
+    /// The quest resource is not loaded (yet or at all).
+    NotLoaded,
+
+    /// The quest with the given name is in the log already.
+    AlreadyStarted(String),
+
+    /// The prerequisite quest with the given name is not completed.
+    PrerequisiteNotMet(String),
+
+    /// There's no quest with the given name in the log.
+    UnknownQuest(String),
+
+    /// The quest with the given name is not active.
+    NotActive(String),
+}
+
+impl Display for QuestLogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuestLogError::NotLoaded => write!(f, "The quest is not loaded!"),
+            QuestLogError::AlreadyStarted(name) => write!(f, "Quest {name} is already started!"),
+            QuestLogError::PrerequisiteNotMet(name) => {
+                write!(f, "Prerequisite quest {name} is not completed!")
+            }
+            QuestLogError::UnknownQuest(name) => write!(f, "There's no quest {name} in the log!"),
+            QuestLogError::NotActive(name) => write!(f, "Quest {name} is not active!"),
+        }
+    }
+}
+
+/// A message, that advances objectives of active quests. Names of messages are arbitrary, for example
+/// `killed:wolf` or `talked:elder`, they must match the names in quest objectives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestMessage {
+    /// Name of the message.
+    pub name: String,
+    /// Amount of the progress, for example amount of collected items.
+    pub amount: u32,
+}
+
+impl QuestMessage {
+    /// Creates new message with the given name and a single unit of progress.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            amount: 1,
+        }
+    }
+
+    /// Sets amount of the progress.
+    pub fn with_amount(mut self, amount: u32) -> Self {
+        self.amount = amount;
+        self
+    }
+}
+
+/// A change of a quest log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuestEvent {
+    /// A quest was started.
+    Started {
+        /// Name of the quest.
+        quest: String,
+    },
+    /// An objective of a quest received a message.
+    ObjectiveProgressed {
+        /// Name of the quest.
+        quest: String,
+        /// Name of the objective.
+        objective: String,
+        /// Current amount of received messages.
+        progress: u32,
+        /// Required amount of messages.
+        count: u32,
+    },
+    /// An objective of a quest was completed.
+    ObjectiveCompleted {
+        /// Name of the quest.
+        quest: String,
+        /// Name of the objective.
+        objective: String,
+    },
+    /// A quest was completed.
+    Completed {
+        /// Name of the quest.
+        quest: String,
+    },
+    /// A quest was failed.
+    Failed {
+        /// Name of the quest.
+        quest: String,
+    },
+    /// A quest was removed from the log.
+    Abandoned {
+        /// Name of the quest.
+        quest: String,
+    },
+}
+
+/// Status of a quest in a quest log.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Visit)]
+pub enum QuestStatus {
+    /// The quest is in progress.
+    Active,
+    /// All required objectives of the quest are completed.
+    Completed,
+    /// The quest received a fail message or was failed manually.
+    Failed,
+}
+
+impl Default for QuestStatus {
+    fn default() -> Self {
+        Self::Active
+    }
+}
+
+/// State of a started quest.
+#[derive(Debug, Clone, Default, PartialEq, Visit)]
+pub struct QuestState {
+    name: String,
+    quest: QuestResource,
+    status: QuestStatus,
+    progress: Vec<u32>,
+}
+
+impl QuestState {
+    /// Returns name of the quest.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the quest resource.
+    pub fn quest(&self) -> &QuestResource {
+        &self.quest
+    }
+
+    /// Returns status of the quest.
+    pub fn status(&self) -> QuestStatus {
+        self.status
+    }
+
+    /// Returns amounts of received messages for every objective of the quest, in the order of the
+    /// objectives.
+    pub fn progress(&self) -> &[u32] {
+        &self.progress
+    }
+
+    /// Returns amount of received messages for the objective with the given name.
+    pub fn objective_progress(&self, objective: &str) -> Option<u32> {
+        let index = with_quest(&self.quest, |quest| quest.find_objective(objective)).flatten()?;
+        Some(self.progress.get(index).cloned().unwrap_or_default())
+    }
+
+    /// Returns `true` if the objective with the given name is completed.
+    pub fn is_objective_completed(&self, objective: &str) -> bool {
+        with_quest(&self.quest, |quest| {
+            quest.find_objective(objective).map_or(false, |index| {
+                self.progress.get(index).cloned().unwrap_or_default()
+                    >= quest.objectives()[index].count
+            })
+        })
+        .unwrap_or_default()
+    }
+
+    fn handle_message(&mut self, message: &QuestMessage, events: &mut VecDeque<QuestEvent>) {
+        let quest = self.quest.clone();
+        let state = quest.state();
+        let quest = if let ResourceStateRef::Ok(quest) = state.get() {
+            quest
+        } else {
+            return;
+        };
+
+        // The quest could be changed after the log was saved.
+        self.progress.resize(quest.objectives().len(), 0);
+
+        if quest
+            .fail_messages()
+            .iter()
+            .any(|name| *name == message.name)
+        {
+            self.status = QuestStatus::Failed;
+            events.push_back(QuestEvent::Failed {
+                quest: self.name.clone(),
+            });
+            return;
+        }
+
+        for (objective, progress) in quest.objectives().iter().zip(self.progress.iter_mut()) {
+            if objective.message != message.name || *progress >= objective.count {
+                continue;
+            }
+
+            *progress = progress.saturating_add(message.amount).min(objective.count);
+            events.push_back(QuestEvent::ObjectiveProgressed {
+                quest: self.name.clone(),
+                objective: objective.name.clone(),
+                progress: *progress,
+                count: objective.count,
+            });
+            if *progress == objective.count {
+                events.push_back(QuestEvent::ObjectiveCompleted {
+                    quest: self.name.clone(),
+                    objective: objective.name.clone(),
+                });
+            }
+        }
+
+        let completed = quest
+            .objectives()
+            .iter()
+            .zip(self.progress.iter())
+            .all(|(objective, progress)| objective.optional || *progress >= objective.count);
+        if completed {
+            self.status = QuestStatus::Completed;
+            events.push_back(QuestEvent::Completed {
+                quest: self.name.clone(),
+            });
+        }
+    }
+}
+
+fn with_quest<R>(quest: &QuestResource, func: impl FnOnce(&Quest) -> R) -> Option<R> {
+    let state = quest.state();
+    match state.get() {
+        ResourceStateRef::Ok(quest) => Some(func(quest)),
+        _ => None,
+    }
+}
+
+/// See module docs.
+#[derive(Debug, Clone, Default, Visit)]
+pub struct QuestLog {
+    quests: Vec<QuestState>,
+    #[visit(skip)]
+    events: VecDeque<QuestEvent>,
+}
+
+impl QuestLog {
+    /// Creates new empty quest log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all quests of the log in the order of starting.
+    pub fn quests(&self) -> &[QuestState] {
+        &self.quests
+    }
+
+    /// Returns an iterator over active quests.
+    pub fn active(&self) -> impl Iterator<Item = &QuestState> {
+        self.quests
+            .iter()
+            .filter(|state| state.status == QuestStatus::Active)
+    }
+
+    /// Returns state of the quest with the given name.
+    pub fn quest(&self, name: &str) -> Option<&QuestState> {
+        self.quests.iter().find(|state| state.name == name)
+    }
+
+    /// Returns status of the quest with the given name, `None` means that the quest was not started.
+    pub fn status(&self, name: &str) -> Option<QuestStatus> {
+        self.quest(name).map(|state| state.status)
+    }
+
+    /// Checks whether the given quest could be started. The quest must be loaded, it must not be in the
+    /// log and all its prerequisites must be completed.
+    pub fn check_start(&self, quest: &QuestResource) -> Result<(), QuestLogError> {
+        with_quest(quest, |quest| {
+            if self.quest(quest.name()).is_some() {
+                return Err(QuestLogError::AlreadyStarted(quest.name().to_owned()));
+            }
+            for prerequisite in quest.prerequisites() {
+                if self.status(prerequisite) != Some(QuestStatus::Completed) {
+                    return Err(QuestLogError::PrerequisiteNotMet(prerequisite.clone()));
+                }
+            }
+            Ok(())
+        })
+        .unwrap_or(Err(QuestLogError::NotLoaded))
+    }
+
+    /// Starts the given quest. See [`Self::check_start`] for the requirements.
+    pub fn start(&mut self, quest: &QuestResource) -> Result<(), QuestLogError> {
+        self.check_start(quest)?;
+
+        let (name, objectives) = with_quest(quest, |quest| {
+            (quest.name().to_owned(), quest.objectives().len())
+        })
+        .ok_or(QuestLogError::NotLoaded)?;
+        self.quests.push(QuestState {
+            name: name.clone(),
+            quest: quest.clone(),
+            status: QuestStatus::Active,
+            progress: vec![0; objectives],
+        });
+        self.events.push_back(QuestEvent::Started { quest: name });
+        Ok(())
+    }
+
+    /// Advances objectives of all active quests, that wait for the given message. It also fails active
+    /// quests with the matching fail message.
+    pub fn handle_message(&mut self, message: &QuestMessage) {
+        for state in self.quests.iter_mut() {
+            if state.status == QuestStatus::Active {
+                state.handle_message(message, &mut self.events);
+            }
+        }
+    }
+
+    /// Shortcut for [`Self::handle_message`] with a single unit of progress. It is useful to pass custom
+    /// events of dialogues (see [`crate::utils::dialogue::DialoguePlayer`]) to the log.
+    pub fn notify(&mut self, message: &str) {
+        self.handle_message(&QuestMessage::new(message))
+    }
+
+    /// Handles a script message, if it is a [`QuestMessage`]. Returns `true` if the message was handled.
+    pub fn on_message(&mut self, message: &dyn ScriptMessagePayload) -> bool {
+        if let Some(message) = message.downcast_ref::<QuestMessage>() {
+            self.handle_message(message);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Fails the active quest with the given name.
+    pub fn fail(&mut self, name: &str) -> Result<(), QuestLogError> {
+        let state = self
+            .quests
+            .iter_mut()
+            .find(|state| state.name == name)
+            .ok_or_else(|| QuestLogError::UnknownQuest(name.to_owned()))?;
+        if state.status != QuestStatus::Active {
+            return Err(QuestLogError::NotActive(name.to_owned()));
+        }
+        state.status = QuestStatus::Failed;
+        self.events.push_back(QuestEvent::Failed {
+            quest: name.to_owned(),
+        });
+        Ok(())
+    }
+
+    /// Removes the quest with the given name from the log, so it could be started again.
+    pub fn abandon(&mut self, name: &str) -> Result<QuestState, QuestLogError> {
+        let index = self
+            .quests
+            .iter()
+            .position(|state| state.name == name)
+            .ok_or_else(|| QuestLogError::UnknownQuest(name.to_owned()))?;
+        self.events.push_back(QuestEvent::Abandoned {
+            quest: name.to_owned(),
+        });
+        Ok(self.quests.remove(index))
+    }
+
+    /// Pops a change of the log from the queue of changes.
+    pub fn pop_event(&mut self) -> Option<QuestEvent> {
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::Resource,
+        resource::quest::{Quest, QuestDefinition, QuestObjective, QuestResource},
+        script::ScriptMessagePayload,
+        utils::quest::{QuestEvent, QuestLog, QuestLogError, QuestMessage, QuestStatus},
+    };
+
+    fn objective(name: &str, message: &str, count: u32, optional: bool) -> QuestObjective {
+        QuestObjective {
+            name: name.to_string(),
+            message: message.to_string(),
+            count,
+            optional,
+            ..Default::default()
+        }
+    }
+
+    fn village() -> QuestResource {
+        Resource::new_ok(
+            Quest::new(QuestDefinition {
+                name: "village".to_string(),
+                objectives: vec![objective("talk", "talked:elder", 1, false)],
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    fn wolves() -> QuestResource {
+        Resource::new_ok(
+            Quest::new(QuestDefinition {
+                name: "wolves".to_string(),
+                prerequisites: vec!["village".to_string()],
+                objectives: vec![
+                    objective("hunt", "killed:wolf", 3, false),
+                    objective("pelts", "collected:pelt", 2, true),
+                    objective("report", "talked:elder", 1, false),
+                ],
+                fail_messages: vec!["killed:elder".to_string()],
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    fn events(log: &mut QuestLog) -> Vec<QuestEvent> {
+        std::iter::from_fn(|| log.pop_event()).collect()
+    }
+
+    #[test]
+    fn test_quest_log() {
+        let mut log = QuestLog::new();
+        let (village, wolves) = (village(), wolves());
+
+        assert_eq!(
+            log.start(&wolves),
+            Err(QuestLogError::PrerequisiteNotMet("village".to_string()))
+        );
+        log.start(&village).unwrap();
+        assert_eq!(
+            log.start(&village),
+            Err(QuestLogError::AlreadyStarted("village".to_string()))
+        );
+        log.notify("talked:elder");
+        assert_eq!(log.status("village"), Some(QuestStatus::Completed));
+        assert_eq!(
+            events(&mut log),
+            [
+                QuestEvent::Started {
+                    quest: "village".to_string()
+                },
+                QuestEvent::ObjectiveProgressed {
+                    quest: "village".to_string(),
+                    objective: "talk".to_string(),
+                    progress: 1,
+                    count: 1
+                },
+                QuestEvent::ObjectiveCompleted {
+                    quest: "village".to_string(),
+                    objective: "talk".to_string()
+                },
+                QuestEvent::Completed {
+                    quest: "village".to_string()
+                },
+            ]
+        );
+
+        log.start(&wolves).unwrap();
+        let message: Box<dyn ScriptMessagePayload> =
+            Box::new(QuestMessage::new("killed:wolf").with_amount(2));
+        assert!(log.on_message(&*message));
+        assert!(!log.on_message(&123u32));
+        let state = log.quest("wolves").unwrap();
+        assert_eq!(state.objective_progress("hunt"), Some(2));
+        assert!(!state.is_objective_completed("hunt"));
+
+        // Progress is clamped and completed objectives are not advanced anymore.
+        log.handle_message(&QuestMessage::new("killed:wolf").with_amount(5));
+        log.notify("killed:wolf");
+        assert_eq!(log.quest("wolves").unwrap().progress(), [3, 0, 0]);
+
+        // Optional objectives are not required.
+        log.notify("talked:elder");
+        assert_eq!(log.status("wolves"), Some(QuestStatus::Completed));
+        assert_eq!(log.active().count(), 0);
+
+        // Fail messages affect active quests only.
+        log.notify("killed:elder");
+        assert_eq!(log.status("wolves"), Some(QuestStatus::Completed));
+
+        log.abandon("wolves").unwrap();
+        log.start(&wolves).unwrap();
+        events(&mut log);
+        log.notify("killed:elder");
+        assert_eq!(log.status("wolves"), Some(QuestStatus::Failed));
+        assert_eq!(
+            events(&mut log),
+            [QuestEvent::Failed {
+                quest: "wolves".to_string()
+            }]
+        );
+        assert_eq!(
+            log.fail("wolves"),
+            Err(QuestLogError::NotActive("wolves".to_string()))
+        );
+        assert_eq!(
+            log.fail("dragons"),
+            Err(QuestLogError::UnknownQuest("dragons".to_string()))
+        );
+    }
+}
@@ -0,0 +1,576 @@
+//! High-level save game management. See [`SaveLoadManager`] docs for more info.
+
+use crate::{
+    asset::{manager::ResourceManager, Resource},
+    core::{
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::{prelude::*, PodVecView},
+    },
+    engine::SerializationContext,
+    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
+    scene::{node::Node, Scene, SceneLoader},
+    utils::session::GameSession,
+};
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const SAVE_EXTENSION: &str = "save";
+const METADATA_EXTENSION: &str = "meta";
+const SESSION_REGION: &str = "Session";
+
+/// All possible errors that may occur during saving or loading.
+#[derive(Debug)]
+pub enum SaveError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// An error occurred during serialization or deserialization.
+    Visit(VisitError),
+    /// Slot name is empty or contains characters that cannot be used in file names.
+    InvalidSlotName(String),
+    /// There is no save in the slot.
+    NoSuchSlot(String),
+    /// The save was made by newer version of the game and cannot be loaded.
+    NewerVersion {
+        /// Version of the save.
+        saved: u32,
+        /// Maximum version supported by the manager.
+        supported: u32,
+    },
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            SaveError::Visit(v) => write!(f, "Unable to serialize save data: {v}"),
+            SaveError::InvalidSlotName(v) => write!(f, "Invalid save slot name: {v}"),
+            SaveError::NoSuchSlot(v) => write!(f, "There is no save in slot {v}"),
+            SaveError::NewerVersion { saved, supported } => write!(
+                f,
+                "Save version {saved} is newer than the supported version {supported}"
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(err: std::io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<VisitError> for SaveError {
+    fn from(err: VisitError) -> Self {
+        SaveError::Visit(err)
+    }
+}
+
+/// A small image that is shown in save/load menus, pixels are stored in RGBA8 format.
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
+pub struct SaveThumbnail {
+    /// Width of the thumbnail in pixels.
+    pub width: u32,
+    /// Height of the thumbnail in pixels.
+    pub height: u32,
+    /// Pixels of the thumbnail in RGBA8 format, row by row.
+    #[reflect(hidden)]
+    pub pixels: Vec<u8>,
+}
+
+impl Visit for SaveThumbnail {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.width.visit("Width", &mut region)?;
+        self.height.visit("Height", &mut region)?;
+        PodVecView::from_pod_vec(&mut self.pixels).visit("Pixels", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl SaveThumbnail {
+    /// Creates new thumbnail from the given pixels in RGBA8 format. Returns `None` if the size of
+    /// pixels does not match the size of the thumbnail.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Option<Self> {
+        if pixels.len() == (width * height * 4) as usize {
+            Some(Self {
+                width,
+                height,
+                pixels,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Creates a texture from the thumbnail, so it could be shown in user interface.
+    pub fn to_texture(&self) -> Option<TextureResource> {
+        Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: self.width,
+                height: self.height,
+            },
+            TexturePixelKind::RGBA8,
+            self.pixels.clone(),
+            false,
+        )
+        .map(Resource::new_ok)
+    }
+}
+
+/// Metadata of a save, it is stored separately from the save itself, so save slots could be
+/// listed quickly.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct SaveMetadata {
+    /// Human-readable name of the save.
+    pub name: String,
+    /// Time when the save was made, in seconds since the UNIX epoch. It is set by the manager.
+    pub timestamp: u64,
+    /// Version of the game, that made the save. It is set by the manager.
+    pub version: u32,
+    /// Optional thumbnail of the save.
+    pub thumbnail: Option<SaveThumbnail>,
+}
+
+impl SaveMetadata {
+    /// Creates new metadata with the given name.
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets thumbnail of the save.
+    pub fn with_thumbnail(mut self, thumbnail: SaveThumbnail) -> Self {
+        self.thumbnail = Some(thumbnail);
+        self
+    }
+}
+
+/// Description of an occupied save slot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveSlot {
+    /// Name of the slot, use it to load or delete the save.
+    pub name: String,
+    /// Metadata of the save in the slot.
+    pub metadata: SaveMetadata,
+}
+
+/// Save/load manager takes care of serialization plumbing for save games. It stores saves in
+/// named slots in a directory, each slot is a pair of files: `<slot>.save` with the content and
+/// `<slot>.meta` with [`SaveMetadata`].
+///
+/// A save could contain either a whole scene or a set of nodes (with their descendants and
+/// scripts). When loading, every resource used by the saved content is re-requested from the
+/// resource manager and the content is re-synced with the resources (so meshes, textures, etc.
+/// are not stored in saves, only references to them).
+///
+/// A save could also include the persistent data of a game session (see [`GameSession`]), use
+/// [`Self::save_game`] and [`Self::load_game`] for that.
+///
+/// The manager stamps every save with its version, saves made with newer versions cannot be
+/// loaded. Increase the version when the save format of your game changes in a backward
+/// incompatible way.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     asset::manager::ResourceManager,
+///     engine::SerializationContext,
+///     scene::Scene,
+///     utils::save::{SaveError, SaveLoadManager, SaveMetadata},
+/// };
+/// use std::sync::Arc;
+///
+/// async fn quick_save_load(
+///     scene: &mut Scene,
+///     serialization_context: Arc<SerializationContext>,
+///     resource_manager: ResourceManager,
+/// ) -> Result<Scene, SaveError> {
+///     let manager = SaveLoadManager::new("saves", serialization_context, resource_manager);
+///
+///     manager.save_scene("quick", scene, SaveMetadata::new("Quick Save"))?;
+///
+///     for slot in manager.slots()? {
+///         println!("{}: {}", slot.name, slot.metadata.name);
+///     }
+///
+///     let (_metadata, scene) = manager.load_scene("quick").await?;
+///     Ok(scene)
+/// }
+/// ```
+pub struct SaveLoadManager {
+    directory: PathBuf,
+    version: u32,
+    serialization_context: Arc<SerializationContext>,
+    resource_manager: ResourceManager,
+}
+
+impl SaveLoadManager {
+    /// Creates new manager, that stores saves in the given directory. The directory will be
+    /// created on first save.
+    pub fn new<P: AsRef<Path>>(
+        directory: P,
+        serialization_context: Arc<SerializationContext>,
+        resource_manager: ResourceManager,
+    ) -> Self {
+        Self {
+            directory: directory.as_ref().to_owned(),
+            version: 0,
+            serialization_context,
+            resource_manager,
+        }
+    }
+
+    /// Sets current version of saves.
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Returns current version of saves.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns a directory with saves.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn slot_path(&self, slot: &str, extension: &str) -> Result<PathBuf, SaveError> {
+        let is_valid = !slot.is_empty()
+            && slot != "."
+            && slot != ".."
+            && !slot
+                .chars()
+                .any(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'));
+        if is_valid {
+            Ok(self.directory.join(format!("{}.{}", slot, extension)))
+        } else {
+            Err(SaveError::InvalidSlotName(slot.to_owned()))
+        }
+    }
+
+    fn write(
+        &self,
+        slot: &str,
+        mut metadata: SaveMetadata,
+        content: &mut Scene,
+        session: Option<&mut GameSession>,
+    ) -> Result<(), SaveError> {
+        let save_path = self.slot_path(slot, SAVE_EXTENSION)?;
+        let metadata_path = self.slot_path(slot, METADATA_EXTENSION)?;
+
+        metadata.timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        metadata.version = self.version;
+
+        fs::create_dir_all(&self.directory)?;
+
+        let mut visitor = Visitor::new();
+        content.save("Scene", &mut visitor)?;
+        if let Some(session) = session {
+            session.visit(SESSION_REGION, &mut visitor)?;
+        }
+        visitor.save_binary(save_path)?;
+
+        // Metadata is written last, so a slot is listed only if its content is written.
+        let mut visitor = Visitor::new();
+        metadata.visit("Metadata", &mut visitor)?;
+        visitor.save_binary(metadata_path)?;
+
+        Ok(())
+    }
+
+    /// Saves the whole scene in the given slot, previous content of the slot is overwritten.
+    pub fn save_scene(
+        &self,
+        slot: &str,
+        scene: &mut Scene,
+        metadata: SaveMetadata,
+    ) -> Result<(), SaveError> {
+        self.write(slot, metadata, scene, None)
+    }
+
+    /// Saves the whole scene together with the given game session in the given slot, previous content
+    /// of the slot is overwritten. Use [`Self::load_game`] to restore them.
+    pub fn save_game(
+        &self,
+        slot: &str,
+        scene: &mut Scene,
+        session: &mut GameSession,
+        metadata: SaveMetadata,
+    ) -> Result<(), SaveError> {
+        self.write(slot, metadata, scene, Some(session))
+    }
+
+    /// Saves the given nodes (with their descendants) of the scene in the given slot, previous
+    /// content of the slot is overwritten. Use [`Self::load_nodes`] to restore them.
+    pub fn save_nodes(
+        &self,
+        slot: &str,
+        scene: &Scene,
+        nodes: &[Handle<Node>],
+        metadata: SaveMetadata,
+    ) -> Result<(), SaveError> {
+        let mut content = Scene::new();
+        for node in nodes {
+            let (copy, _) = scene
+                .graph
+                .copy_node(*node, &mut content.graph, &mut |_, _| true);
+            let root = content.graph.get_root();
+            content.graph.link_nodes(copy, root);
+        }
+        self.write(slot, metadata, &mut content, None)
+    }
+
+    /// Returns `true` if there is a save in the given slot.
+    pub fn exists(&self, slot: &str) -> bool {
+        self.slot_path(slot, METADATA_EXTENSION)
+            .map_or(false, |path| path.exists())
+    }
+
+    /// Reads metadata of a save in the given slot.
+    pub fn metadata(&self, slot: &str) -> Result<SaveMetadata, SaveError> {
+        let path = self.slot_path(slot, METADATA_EXTENSION)?;
+        if !path.exists() {
+            return Err(SaveError::NoSuchSlot(slot.to_owned()));
+        }
+
+        let mut visitor = Visitor::load_from_memory(fs::read(path)?)?;
+        let mut metadata = SaveMetadata::default();
+        metadata.visit("Metadata", &mut visitor)?;
+        Ok(metadata)
+    }
+
+    /// Returns every occupied slot with metadata of its save. Slots are sorted by time, the most
+    /// recent save goes first.
+    pub fn slots(&self) -> Result<Vec<SaveSlot>, SaveError> {
+        if !self.directory.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut slots = Vec::new();
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(false, |ext| ext == METADATA_EXTENSION)
+            {
+                if let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy()) {
+                    slots.push(SaveSlot {
+                        metadata: self.metadata(&name)?,
+                        name: name.into_owned(),
+                    });
+                }
+            }
+        }
+
+        slots.sort_by_key(|slot| std::cmp::Reverse(slot.metadata.timestamp));
+
+        Ok(slots)
+    }
+
+    /// Deletes a save in the given slot.
+    pub fn delete(&self, slot: &str) -> Result<(), SaveError> {
+        if !self.exists(slot) {
+            return Err(SaveError::NoSuchSlot(slot.to_owned()));
+        }
+
+        fs::remove_file(self.slot_path(slot, METADATA_EXTENSION)?)?;
+        let save_path = self.slot_path(slot, SAVE_EXTENSION)?;
+        if save_path.exists() {
+            fs::remove_file(save_path)?;
+        }
+
+        Ok(())
+    }
+
+    async fn read(&self, slot: &str) -> Result<(SaveMetadata, Scene, GameSession), SaveError> {
+        let metadata = self.metadata(slot)?;
+        if metadata.version > self.version {
+            return Err(SaveError::NewerVersion {
+                saved: metadata.version,
+                supported: self.version,
+            });
+        }
+
+        let mut visitor = Visitor::load_binary(self.slot_path(slot, SAVE_EXTENSION)?).await?;
+        let loader = SceneLoader::load(
+            "Scene",
+            self.serialization_context.clone(),
+            self.resource_manager.clone(),
+            &mut visitor,
+            None,
+        )?;
+
+        // Saves without a session are loaded with an empty one.
+        let mut session = GameSession::default();
+        match session.visit(SESSION_REGION, &mut visitor) {
+            Err(VisitError::RegionDoesNotExist(region)) if region == SESSION_REGION => (),
+            result => result?,
+        }
+
+        // Waits until every used resource is loaded and re-syncs the content with them.
+        Ok((metadata, loader.finish().await, session))
+    }
+
+    /// Loads a scene from the given slot. Returned scene should be added to the scene container
+    /// of the engine.
+    pub async fn load_scene(&self, slot: &str) -> Result<(SaveMetadata, Scene), SaveError> {
+        let (metadata, scene, _) = self.read(slot).await?;
+        Ok((metadata, scene))
+    }
+
+    /// Loads a scene and a game session, saved by [`Self::save_game`], from the given slot. Returned
+    /// scene should be added to the scene container of the engine and the session should replace
+    /// [`crate::engine::Engine::session`]. If the save has no session, an empty session is returned.
+    pub async fn load_game(
+        &self,
+        slot: &str,
+    ) -> Result<(SaveMetadata, Scene, GameSession), SaveError> {
+        self.read(slot).await
+    }
+
+    /// Loads nodes, saved by [`Self::save_nodes`], from the given slot and adds them to the given
+    /// scene. Returns handles of the restored nodes in the same order as they were saved.
+    pub async fn load_nodes(
+        &self,
+        slot: &str,
+        scene: &mut Scene,
+    ) -> Result<(SaveMetadata, Vec<Handle<Node>>), SaveError> {
+        let (metadata, content, _) = self.read(slot).await?;
+
+        let root = content.graph.get_root();
+        let nodes = content.graph[root]
+            .children()
+            .iter()
+            .map(|child| {
+                content
+                    .graph
+                    .copy_node(*child, &mut scene.graph, &mut |_, _| true)
+                    .0
+            })
+            .collect();
+
+        Ok((metadata, nodes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::futures::executor::block_on;
+
+    fn manager(name: &str) -> SaveLoadManager {
+        let directory = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&directory);
+        SaveLoadManager::new(
+            directory,
+            Arc::new(SerializationContext::new()),
+            ResourceManager::new(),
+        )
+        .with_version(2)
+    }
+
+    #[test]
+    fn test_save_slots() {
+        let manager = manager("fyrox_test_save_slots");
+        assert!(manager.slots().unwrap().is_empty());
+
+        let thumbnail = SaveThumbnail::new(1, 1, vec![255, 0, 0, 255]).unwrap();
+        manager
+            .save_scene(
+                "slot1",
+                &mut Scene::new(),
+                SaveMetadata::new("First").with_thumbnail(thumbnail.clone()),
+            )
+            .unwrap();
+
+        assert!(manager.exists("slot1"));
+        let metadata = manager.metadata("slot1").unwrap();
+        assert_eq!(metadata.name, "First");
+        assert_eq!(metadata.version, 2);
+        assert_eq!(metadata.thumbnail, Some(thumbnail));
+
+        let slots = manager.slots().unwrap();
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].name, "slot1");
+
+        manager.delete("slot1").unwrap();
+        assert!(!manager.exists("slot1"));
+        assert!(matches!(
+            manager.delete("slot1"),
+            Err(SaveError::NoSuchSlot(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_slot_names() {
+        let manager = manager("fyrox_test_invalid_slot_names");
+        for name in ["", "..", "a/b", "a\\b"] {
+            assert!(matches!(
+                manager.save_scene(name, &mut Scene::new(), SaveMetadata::new("Test")),
+                Err(SaveError::InvalidSlotName(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_save_game() {
+        let manager = manager("fyrox_test_save_game");
+        let mut session = GameSession::default();
+        session.set("player.gold", 150i64);
+        session.set("village.saved", true);
+        manager
+            .save_game(
+                "slot",
+                &mut Scene::new(),
+                &mut session,
+                SaveMetadata::new("Test"),
+            )
+            .unwrap();
+
+        let (_, _, loaded) = block_on(manager.load_game("slot")).unwrap();
+        assert_eq!(loaded, session);
+
+        // Saves without a session have an empty one.
+        manager
+            .save_scene("slot", &mut Scene::new(), SaveMetadata::new("Test"))
+            .unwrap();
+        let (_, _, loaded) = block_on(manager.load_game("slot")).unwrap();
+        assert_eq!(loaded, GameSession::default());
+    }
+
+    #[test]
+    fn test_newer_version() {
+        let manager = manager("fyrox_test_newer_version");
+        manager
+            .save_scene("slot", &mut Scene::new(), SaveMetadata::new("Test"))
+            .unwrap();
+
+        let manager = SaveLoadManager {
+            version: 1,
+            ..manager
+        };
+        assert!(matches!(
+            block_on(manager.load_scene("slot")),
+            Err(SaveError::NewerVersion {
+                saved: 2,
+                supported: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+//! Persistent data of a game session. See [`GameSession`] docs for more info.
+
+use crate::{
+    core::visitor::prelude::*,
+    utils::behavior::blackboard::{Blackboard, BlackboardType, BlackboardValue},
+};
+
+/// Game session is a typed key-value storage for the data, that must outlive scenes - player stats,
+/// world flags, progress of a story, etc. The engine owns a single session (see
+/// [`crate::engine::Engine::session`]), it is available to plugins and scripts via their contexts and it
+/// is not affected by loading or unloading scenes. The session is stored in save games, made by
+/// [`crate::utils::save::SaveLoadManager::save_game`].
+///
+/// Values of the session are stored in a [`Blackboard`], so the session could be passed directly to
+/// the systems, that use blackboards (for example, to check conditions of dialogues).
+///
+/// ```rust
+/// use fyrox::utils::session::GameSession;
+///
+/// let mut session = GameSession::default();
+/// session.set("player.level", 3i64);
+/// session.set("village.saved", true);
+/// session.increment("player.gold", 150);
+///
+/// assert_eq!(session.get::<i64>("player.level"), Some(3));
+/// assert_eq!(session.get::<i64>("player.gold"), Some(150));
+/// assert!(session.flag("village.saved"));
+/// assert!(!session.flag("castle.visited"));
+/// ```
+#[derive(Debug, Default, PartialEq, Visit, Clone)]
+pub struct GameSession {
+    values: Blackboard,
+}
+
+impl GameSession {
+    /// Sets new value for the given key, returns previous value (if any).
+    pub fn set<K, T>(&mut self, key: K, value: T) -> Option<BlackboardValue>
+    where
+        K: AsRef<str>,
+        T: BlackboardType,
+    {
+        self.values.set(key, value)
+    }
+
+    /// Returns a value of the given key. `None` is returned if there's no such key or if the value has
+    /// different type.
+    pub fn get<T>(&self, key: impl AsRef<str>) -> Option<T>
+    where
+        T: BlackboardType,
+    {
+        self.values.get(key)
+    }
+
+    /// Returns a value of the given key or the given default value if there's no such key or if the
+    /// value has different type.
+    pub fn get_or<T>(&self, key: impl AsRef<str>, default: T) -> T
+    where
+        T: BlackboardType,
+    {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Returns boolean value of the given key, missing values are treated as `false`.
+    pub fn flag(&self, key: impl AsRef<str>) -> bool {
+        self.get_or(key, false)
+    }
+
+    /// Adds the given amount to an integer value of the given key and returns new value. Missing values
+    /// (or values of other types) are treated as zero.
+    pub fn increment(&mut self, key: impl AsRef<str>, amount: i64) -> i64 {
+        let value = self.get_or(key.as_ref(), 0i64).saturating_add(amount);
+        self.set(key, value);
+        value
+    }
+
+    /// Returns untyped value of the given key.
+    pub fn value(&self, key: impl AsRef<str>) -> Option<&BlackboardValue> {
+        self.values.value(key)
+    }
+
+    /// Returns `true` if there's a value for the given key, `false` - otherwise.
+    pub fn contains(&self, key: impl AsRef<str>) -> bool {
+        self.values.contains(key)
+    }
+
+    /// Removes a value of the given key and returns it.
+    pub fn remove(&mut self, key: impl AsRef<str>) -> Option<BlackboardValue> {
+        self.values.remove(key)
+    }
+
+    /// Removes every value from the session, it should be called when a new game is started.
+    pub fn clear(&mut self) {
+        self.values.clear()
+    }
+
+    /// Returns an iterator over every key-value pair.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &BlackboardValue)> {
+        self.values.iter()
+    }
+
+    /// Returns a reference to the blackboard with the values of the session.
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.values
+    }
+
+    /// Returns a reference to the blackboard with the values of the session.
+    pub fn blackboard_mut(&mut self) -> &mut Blackboard {
+        &mut self.values
+    }
+}
@@ -0,0 +1,286 @@
+//! Spawn pool allows to reuse instances of a prefab instead of creating and destroying them all the
+//! time. See [`SpawnPool`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        pool::Handle,
+    },
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{dim2, graph::Graph, node::Node, rigidbody::RigidBody, Scene},
+};
+use fxhash::FxHashSet;
+
+/// Spawn pool pre-instantiates a number of copies of a prefab and then hands them out on request.
+/// It is useful for objects that are spawned and despawned very frequently (bullets, shells,
+/// particle effects, etc.), because instantiation of a prefab involves copying of its whole
+/// hierarchy and could cause hitches when done many times per frame.
+///
+/// # How it works
+///
+/// Every instance that is not in use is _pooled_ - its root node is disabled (see
+/// [`crate::scene::base::Base::set_enabled`]). Disabled nodes are not rendered, their rigid bodies,
+/// colliders and joints are removed from the physics world and their scripts are not updated. When
+/// an instance is requested, the pool takes a pooled one, resets it to the state of the prefab,
+/// moves it to the requested location and enables it. When an instance is returned to the pool,
+/// it is disabled again and attached to the root of the scene graph.
+///
+/// Reset restores local transforms of every node of an instance and zeroes velocities of every
+/// rigid body. Scripts are not reset, they keep their state between spawns and their
+/// `on_init`/`on_start` methods are called only once, when the instance is created. Any other
+/// state that has to be reset must be reset manually after [`SpawnPool::spawn`].
+///
+/// # Important notes
+///
+/// The prefab must be fully loaded before creating a pool. Instances must not be deleted manually
+/// and must not have limited lifetime, otherwise the pool will lose track of them and create
+/// new instances instead.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox::{
+/// #     core::{algebra::{UnitQuaternion, Vector3}, pool::Handle},
+/// #     resource::model::ModelResource,
+/// #     scene::{node::Node, Scene},
+/// #     utils::spawn_pool::SpawnPool,
+/// # };
+/// struct Weapon {
+///     bullets: SpawnPool,
+///     fired: Vec<Handle<Node>>,
+/// }
+///
+/// impl Weapon {
+///     fn new(bullet_prefab: ModelResource, scene: &mut Scene) -> Self {
+///         Self {
+///             bullets: SpawnPool::new(bullet_prefab, scene, 64),
+///             fired: Default::default(),
+///         }
+///     }
+///
+///     fn shoot(&mut self, scene: &mut Scene, position: Vector3<f32>) {
+///         let bullet = self
+///             .bullets
+///             .spawn(scene, position, UnitQuaternion::identity());
+///         self.fired.push(bullet);
+///     }
+///
+///     fn on_bullet_hit(&mut self, scene: &mut Scene, bullet: Handle<Node>) {
+///         self.fired.retain(|b| *b != bullet);
+///         self.bullets.despawn(scene, bullet);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SpawnPool {
+    prefab: ModelResource,
+    free: Vec<Handle<Node>>,
+    active: FxHashSet<Handle<Node>>,
+}
+
+impl SpawnPool {
+    /// Creates new pool and pre-instantiates the given amount of instances of the prefab.
+    pub fn new(prefab: ModelResource, scene: &mut Scene, count: usize) -> Self {
+        let mut pool = Self {
+            prefab,
+            free: Vec::with_capacity(count),
+            active: Default::default(),
+        };
+        pool.reserve(scene, count);
+        pool
+    }
+
+    /// Returns the prefab of the pool.
+    pub fn prefab(&self) -> &ModelResource {
+        &self.prefab
+    }
+
+    /// Pre-instantiates additional instances of the prefab.
+    pub fn reserve(&mut self, scene: &mut Scene, additional: usize) {
+        for _ in 0..additional {
+            let instance = self.prefab.instantiate(scene);
+            scene.graph[instance].set_enabled(false);
+            self.free.push(instance);
+        }
+    }
+
+    /// Takes an instance from the pool (or creates a new one, if there's no free instances left),
+    /// resets it and places it at the specified position and orientation. Returns a handle to the
+    /// root node of the instance.
+    pub fn spawn(
+        &mut self,
+        scene: &mut Scene,
+        position: Vector3<f32>,
+        orientation: UnitQuaternion<f32>,
+    ) -> Handle<Node> {
+        let instance = loop {
+            match self.free.pop() {
+                // The instance could be deleted along with its parent.
+                Some(instance) if scene.graph.is_valid_handle(instance) => break instance,
+                Some(_) => continue,
+                None => break self.prefab.instantiate(scene),
+            }
+        };
+
+        self.reset(&mut scene.graph, instance);
+
+        let root = &mut scene.graph[instance];
+        root.local_transform_mut()
+            .set_position(position)
+            .set_rotation(orientation);
+        root.set_enabled(true);
+
+        scene
+            .graph
+            .update_hierarchical_data_for_descendants(instance);
+
+        self.active.insert(instance);
+
+        instance
+    }
+
+    /// Returns the instance back to the pool. Returns `false` if the instance does not belong to
+    /// the pool or it was already returned.
+    pub fn despawn(&mut self, scene: &mut Scene, instance: Handle<Node>) -> bool {
+        if !self.active.remove(&instance) {
+            return false;
+        }
+
+        if scene.graph.is_valid_handle(instance) {
+            let graph_root = scene.graph.get_root();
+            if scene.graph[instance].parent() != graph_root {
+                scene.graph.link_nodes(instance, graph_root);
+            }
+            scene.graph[instance].set_enabled(false);
+            self.free.push(instance);
+        }
+
+        true
+    }
+
+    /// Returns every active instance back to the pool.
+    pub fn despawn_all(&mut self, scene: &mut Scene) {
+        for instance in self.active.iter().cloned().collect::<Vec<_>>() {
+            self.despawn(scene, instance);
+        }
+    }
+
+    /// Returns `true` if the instance was spawned by the pool and not yet returned back.
+    pub fn is_active(&self, instance: Handle<Node>) -> bool {
+        self.active.contains(&instance)
+    }
+
+    /// Returns an iterator over the active instances.
+    pub fn active(&self) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.active.iter().cloned()
+    }
+
+    /// Returns the amount of active instances.
+    pub fn active_count(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Returns the amount of instances that are ready to be spawned without instantiation.
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Deletes every instance (both active and free) of the pool from the scene.
+    pub fn clear(&mut self, scene: &mut Scene) {
+        for instance in self.free.drain(..).chain(self.active.drain()) {
+            if scene.graph.is_valid_handle(instance) {
+                scene.graph.remove_node(instance);
+            }
+        }
+    }
+
+    fn reset(&self, graph: &mut Graph, instance: Handle<Node>) {
+        let model = self.prefab.data_ref();
+        let prefab_graph = &model.get_scene().graph;
+
+        let mut stack = vec![instance];
+        while let Some(handle) = stack.pop() {
+            let node = &mut graph[handle];
+
+            if let Some(original) = prefab_graph.try_get(node.original_handle_in_resource) {
+                let original = original.local_transform();
+                node.local_transform_mut()
+                    .set_position(**original.position())
+                    .set_rotation(**original.rotation())
+                    .set_scale(**original.scale());
+            }
+
+            if let Some(rigid_body) = node.cast_mut::<RigidBody>() {
+                rigid_body.set_lin_vel(Vector3::default());
+                rigid_body.set_ang_vel(Vector3::default());
+            } else if let Some(rigid_body) = node.cast_mut::<dim2::rigidbody::RigidBody>() {
+                rigid_body.set_lin_vel(Vector2::default());
+                rigid_body.set_ang_vel(0.0);
+            }
+
+            stack.extend_from_slice(node.children());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::Resource,
+        core::algebra::{UnitQuaternion, Vector3},
+        resource::model::{Model, NodeMapping},
+        scene::{base::BaseBuilder, pivot::PivotBuilder, rigidbody::RigidBodyBuilder, Scene},
+        utils::spawn_pool::SpawnPool,
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_spawn_pool() {
+        let mut prefab = Scene::new();
+        RigidBodyBuilder::new(BaseBuilder::new().with_name("Bullet")).build(&mut prefab.graph);
+        let resource = Resource::new_ok(Model::new(
+            PathBuf::from("bullet.rgs"),
+            NodeMapping::UseHandles,
+            prefab,
+        ));
+
+        let mut scene = Scene::new();
+        let mut pool = SpawnPool::new(resource, &mut scene, 2);
+        assert_eq!(pool.free_count(), 2);
+        // Root + 2 instances (root and bullet each).
+        assert_eq!(scene.graph.node_count(), 5);
+
+        let position = Vector3::new(1.0, 2.0, 3.0);
+        let a = pool.spawn(&mut scene, position, UnitQuaternion::identity());
+        assert!(scene.graph[a].is_globally_enabled());
+        assert_eq!(**scene.graph[a].local_transform().position(), position);
+        assert!(pool.is_active(a));
+
+        // Spawning more than reserved must create new instances.
+        let b = pool.spawn(&mut scene, position, UnitQuaternion::identity());
+        let c = pool.spawn(&mut scene, position, UnitQuaternion::identity());
+        assert_ne!(b, c);
+        assert_eq!(pool.active_count(), 3);
+        assert_eq!(pool.free_count(), 0);
+
+        // Returned instances must be disabled and reused.
+        let child = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        scene.graph.link_nodes(a, child);
+        assert!(pool.despawn(&mut scene, a));
+        assert!(!pool.despawn(&mut scene, a));
+        assert!(!scene.graph[a].is_enabled());
+        assert_eq!(scene.graph[a].parent(), scene.graph.get_root());
+        assert_eq!(
+            pool.spawn(&mut scene, position, UnitQuaternion::identity()),
+            a
+        );
+
+        pool.despawn_all(&mut scene);
+        assert_eq!(pool.active_count(), 0);
+        assert_eq!(pool.free_count(), 3);
+
+        pool.clear(&mut scene);
+        // Root + the pivot.
+        assert_eq!(scene.graph.node_count(), 2);
+    }
+}
@@ -433,7 +433,7 @@ pub fn generate_uvs_mesh(
         .map(|data| generate_uvs(&mut data.lock(), spacing))
         .collect::<Result<Vec<SurfaceDataPatch>, VertexFetchError>>()?;
 
-    println!("Generate UVs: {:?}", instant::Instant::now() - last);
+    crate::core::log_debug!("Generate UVs: {:?}", instant::Instant::now() - last);
 
     Ok(patches)
 }
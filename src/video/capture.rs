@@ -0,0 +1,643 @@
+//! Gameplay video capture. See [`VideoRecorder`] docs for more info.
+//!
+//! ## Encoders
+//!
+//! - [`GifEncoder`] - animated GIFs, it is written in pure Rust and always available.
+//! - `FfmpegEncoder` - MP4 (H.264), it is available with `ffmpeg_capture` feature. The engine does
+//!   not ship an H.264 encoder, the encoder pipes raw frames to an external `ffmpeg` process, so
+//!   `ffmpeg` must be installed on the machine that records the video.
+//!
+//! [`CaptureError::EncoderNotFound`] is returned if the executable could not be found.
+
+use crate::{
+    core::{algebra::Vector2, log::Log},
+    renderer::{CapturedFrame, Renderer},
+};
+use image::{
+    codecs::gif::{GifEncoder as ImageGifEncoder, Repeat},
+    imageops::{self, FilterType},
+    Delay, Frame, ImageError, RgbaImage,
+};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+#[cfg(feature = "ffmpeg_capture")]
+use std::{
+    io::{ErrorKind, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+};
+
+/// An error that may occur during video capture.
+#[derive(Debug)]
+pub enum CaptureError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// An encoder has failed to encode a frame.
+    Encoder(String),
+    /// An external encoder executable (for example, `ffmpeg`) could not be found.
+    EncoderNotFound(PathBuf),
+    /// Capture thread has stopped unexpectedly.
+    ThreadStopped,
+}
+
+impl Display for CaptureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            CaptureError::Encoder(v) => write!(f, "Unable to encode a frame: {v}"),
+            CaptureError::EncoderNotFound(v) => write!(
+                f,
+                "Encoder executable {} was not found, make sure it is installed and available in PATH",
+                v.display()
+            ),
+            CaptureError::ThreadStopped => write!(f, "Capture thread has stopped unexpectedly"),
+        }
+    }
+}
+
+impl From<std::io::Error> for CaptureError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ImageError> for CaptureError {
+    fn from(e: ImageError) -> Self {
+        Self::Encoder(e.to_string())
+    }
+}
+
+/// Encoder writes captured frames to a file or a stream. Encoders are used from the capture
+/// thread, so they must be [`Send`].
+pub trait VideoEncoder: Send {
+    /// Called once before the first frame with the size of the frames and the frame rate.
+    fn begin(&mut self, size: Vector2<u32>, fps: f32) -> Result<(), CaptureError>;
+
+    /// Encodes the next frame. Every frame has the size passed to [`Self::begin`].
+    fn encode_frame(&mut self, frame: &RgbaImage) -> Result<(), CaptureError>;
+
+    /// Finishes encoding and flushes every buffered data.
+    fn finish(&mut self) -> Result<(), CaptureError>;
+}
+
+/// Animated GIF encoder. GIF supports only 256 colors per frame and its files are quite large, so
+/// it is suitable only for short clips with small resolution.
+pub struct GifEncoder {
+    path: PathBuf,
+    encoder: Option<ImageGifEncoder<BufWriter<File>>>,
+    delay: Delay,
+}
+
+impl GifEncoder {
+    /// Creates a new encoder, that will write to a file with the given path.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+            encoder: None,
+            delay: Delay::from_numer_denom_ms(0, 1),
+        }
+    }
+}
+
+impl VideoEncoder for GifEncoder {
+    fn begin(&mut self, _size: Vector2<u32>, fps: f32) -> Result<(), CaptureError> {
+        // Speed 10 is the default of the quantizer, it is a good balance between speed and quality.
+        let mut encoder =
+            ImageGifEncoder::new_with_speed(BufWriter::new(File::create(&self.path)?), 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        self.encoder = Some(encoder);
+        self.delay = Delay::from_numer_denom_ms(1000, fps.round().max(1.0) as u32);
+        Ok(())
+    }
+
+    fn encode_frame(&mut self, frame: &RgbaImage) -> Result<(), CaptureError> {
+        if let Some(encoder) = self.encoder.as_mut() {
+            encoder.encode_frame(Frame::from_parts(frame.clone(), 0, 0, self.delay))?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), CaptureError> {
+        // The trailer of the file is written when the encoder is dropped.
+        self.encoder = None;
+        Ok(())
+    }
+}
+
+/// MP4 (H.264) encoder, that pipes raw frames to an external `ffmpeg` process. The engine does not
+/// ship an H.264 encoder, so `ffmpeg` must be installed and available in `PATH` (or passed
+/// explicitly using [`Self::with_executable`]), otherwise [`CaptureError::EncoderNotFound`] is
+/// returned when the encoding begins. The encoder is available with `ffmpeg_capture` feature.
+#[cfg(feature = "ffmpeg_capture")]
+pub struct FfmpegEncoder {
+    path: PathBuf,
+    executable: PathBuf,
+    process: Option<(Child, ChildStdin)>,
+}
+
+#[cfg(feature = "ffmpeg_capture")]
+impl FfmpegEncoder {
+    /// Creates a new encoder, that will write to a file with the given path.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+            executable: "ffmpeg".into(),
+            process: None,
+        }
+    }
+
+    /// Sets a path to `ffmpeg` executable.
+    pub fn with_executable<P: AsRef<Path>>(mut self, executable: P) -> Self {
+        self.executable = executable.as_ref().to_owned();
+        self
+    }
+}
+
+#[cfg(feature = "ffmpeg_capture")]
+impl VideoEncoder for FfmpegEncoder {
+    fn begin(&mut self, size: Vector2<u32>, fps: f32) -> Result<(), CaptureError> {
+        let mut child = Command::new(&self.executable)
+            .args([
+                "-y",
+                "-loglevel",
+                "error",
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgba",
+            ])
+            .args(["-s", &format!("{}x{}", size.x, size.y)])
+            .args(["-r", &fps.to_string(), "-i", "-"])
+            // H.264 with 4:2:0 chroma subsampling requires even dimensions.
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+            .args([
+                "-c:v",
+                "libx264",
+                "-pix_fmt",
+                "yuv420p",
+                "-movflags",
+                "+faststart",
+            ])
+            .arg(&self.path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => CaptureError::EncoderNotFound(self.executable.clone()),
+                _ => CaptureError::Io(e),
+            })?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| CaptureError::Encoder("Unable to open ffmpeg input".to_string()))?;
+        self.process = Some((child, stdin));
+        Ok(())
+    }
+
+    fn encode_frame(&mut self, frame: &RgbaImage) -> Result<(), CaptureError> {
+        if let Some((_, stdin)) = self.process.as_mut() {
+            stdin.write_all(frame.as_raw())?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), CaptureError> {
+        if let Some((mut child, stdin)) = self.process.take() {
+            // Closing the input tells ffmpeg that the stream has ended.
+            drop(stdin);
+            let status = child.wait()?;
+            if !status.success() {
+                return Err(CaptureError::Encoder(format!(
+                    "ffmpeg has failed: {status}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parameters of video capture.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaptureSettings {
+    /// Maximum size of the video in pixels. Frames are scaled down (preserving aspect ratio) to
+    /// fit this size. `None` - use the size of the window.
+    pub max_size: Option<Vector2<u32>>,
+    /// Frame rate of the video.
+    pub fps: f32,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            max_size: Some(Vector2::new(1280, 720)),
+            fps: 30.0,
+        }
+    }
+}
+
+impl CaptureSettings {
+    /// Settings suitable for GIFs: small resolution and low frame rate.
+    pub fn gif() -> Self {
+        Self {
+            max_size: Some(Vector2::new(480, 270)),
+            fps: 15.0,
+        }
+    }
+
+    fn frame_size(&self, source: Vector2<u32>) -> Vector2<u32> {
+        match self.max_size {
+            Some(max_size) if source.x > max_size.x || source.y > max_size.y => {
+                let scale = f32::min(
+                    max_size.x as f32 / source.x as f32,
+                    max_size.y as f32 / source.y as f32,
+                );
+                Vector2::new(
+                    ((source.x as f32 * scale).round() as u32).max(1),
+                    ((source.y as f32 * scale).round() as u32).max(1),
+                )
+            }
+            _ => source,
+        }
+    }
+}
+
+enum CaptureCommand {
+    Frame { frame: CapturedFrame, count: u32 },
+    SaveReplay(Box<dyn VideoEncoder>, Sender<Result<(), CaptureError>>),
+}
+
+enum CaptureMode {
+    Continuous(Box<dyn VideoEncoder>),
+    Replay(usize),
+}
+
+struct CaptureWorker {
+    settings: CaptureSettings,
+    mode: CaptureMode,
+    size: Option<Vector2<u32>>,
+    frames: VecDeque<Arc<RgbaImage>>,
+    started: bool,
+}
+
+impl CaptureWorker {
+    fn prepare_frame(&mut self, frame: CapturedFrame) -> Option<Arc<RgbaImage>> {
+        let image = RgbaImage::from_raw(frame.size.x, frame.size.y, frame.pixels)?;
+        // Size of the video is defined by the first frame, every next frame is scaled to it, so
+        // the window could be resized during recording.
+        let size = *self
+            .size
+            .get_or_insert_with(|| self.settings.frame_size(frame.size));
+        if size == frame.size {
+            Some(Arc::new(image))
+        } else {
+            Some(Arc::new(imageops::resize(
+                &image,
+                size.x,
+                size.y,
+                FilterType::Triangle,
+            )))
+        }
+    }
+
+    fn run(mut self, receiver: Receiver<CaptureCommand>) -> Result<(), CaptureError> {
+        for command in receiver {
+            match command {
+                CaptureCommand::Frame { frame, count } => {
+                    let image = match self.prepare_frame(frame) {
+                        Some(image) => image,
+                        None => continue,
+                    };
+                    let size = Vector2::new(image.width(), image.height());
+                    let fps = self.settings.fps;
+
+                    match self.mode {
+                        CaptureMode::Continuous(ref mut encoder) => {
+                            if !self.started {
+                                encoder.begin(size, fps)?;
+                                self.started = true;
+                            }
+                            for _ in 0..count {
+                                encoder.encode_frame(&image)?;
+                            }
+                        }
+                        CaptureMode::Replay(capacity) => {
+                            for _ in 0..count {
+                                if self.frames.len() == capacity {
+                                    self.frames.pop_front();
+                                }
+                                self.frames.push_back(image.clone());
+                            }
+                        }
+                    }
+                }
+                CaptureCommand::SaveReplay(mut encoder, result_sender) => {
+                    let frames = self.frames.iter().cloned().collect::<Vec<_>>();
+                    let fps = self.settings.fps;
+                    // Encoding may take a while, so it is done in a separate thread to not stop
+                    // collection of new frames.
+                    std::thread::spawn(move || {
+                        let _ = result_sender.send(encode_frames(encoder.as_mut(), &frames, fps));
+                    });
+                }
+            }
+        }
+
+        if let CaptureMode::Continuous(ref mut encoder) = self.mode {
+            if self.started {
+                encoder.finish()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn encode_frames(
+    encoder: &mut dyn VideoEncoder,
+    frames: &[Arc<RgbaImage>],
+    fps: f32,
+) -> Result<(), CaptureError> {
+    if let Some(first) = frames.first() {
+        encoder.begin(Vector2::new(first.width(), first.height()), fps)?;
+        for frame in frames {
+            encoder.encode_frame(frame)?;
+        }
+        encoder.finish()?;
+    }
+    Ok(())
+}
+
+/// Video recorder captures rendered frames of the main window (including the UI) and encodes them
+/// in a background thread. It works in two modes:
+///
+/// - [`Self::start`] - continuous mode, every frame is encoded right away until the recording is
+///   stopped.
+/// - [`Self::start_replay`] - replay mode, the last N seconds of gameplay are kept in memory, and
+///   could be saved at any moment using [`Self::save_replay`] (for example, when a player presses
+///   a hotkey or when a bug report is created). Frames are stored uncompressed, so keep the
+///   resolution and the frame rate small: 30 seconds at 480x270 and 15 FPS take ~230 Mb.
+///
+/// The engine provides [`GifEncoder`] and `FfmpegEncoder` (MP4, requires `ffmpeg_capture` feature
+/// and `ffmpeg` executable, see the module docs), custom formats could be supported by implementing
+/// [`VideoEncoder`] trait.
+///
+/// The recorder must be fed with frames by calling [`Self::capture`] once per frame after
+/// rendering. Reading pixels from the GPU stalls the pipeline, so capturing with high resolution or
+/// frame rate affects performance of the game.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     renderer::Renderer,
+///     video::capture::{CaptureSettings, GifEncoder, VideoRecorder},
+/// };
+/// use std::time::Duration;
+///
+/// fn start() -> VideoRecorder {
+///     VideoRecorder::start_replay(CaptureSettings::gif(), Duration::from_secs(30))
+/// }
+///
+/// // Call it after the frame was rendered.
+/// fn on_frame_rendered(recorder: &mut VideoRecorder, renderer: &mut Renderer, dt: f32) {
+///     recorder.capture(renderer, dt);
+/// }
+///
+/// fn on_save_replay_pressed(recorder: &VideoRecorder) {
+///     let _result_receiver = recorder.save_replay(Box::new(GifEncoder::new("replay.gif")));
+/// }
+/// ```
+pub struct VideoRecorder {
+    settings: CaptureSettings,
+    sender: Option<Sender<CaptureCommand>>,
+    thread: Option<JoinHandle<Result<(), CaptureError>>>,
+    accumulator: f32,
+    pending_count: u32,
+}
+
+impl VideoRecorder {
+    fn new(settings: CaptureSettings, mode: CaptureMode) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let worker = CaptureWorker {
+            settings: settings.clone(),
+            mode,
+            size: None,
+            frames: Default::default(),
+            started: false,
+        };
+        let thread = std::thread::Builder::new()
+            .name("VideoCapture".to_string())
+            .spawn(move || worker.run(receiver))
+            .ok();
+
+        Self {
+            settings,
+            sender: Some(sender),
+            thread,
+            accumulator: 0.0,
+            pending_count: 0,
+        }
+    }
+
+    /// Starts recording in continuous mode, every captured frame will be passed to the encoder.
+    pub fn start(settings: CaptureSettings, encoder: Box<dyn VideoEncoder>) -> Self {
+        Self::new(settings, CaptureMode::Continuous(encoder))
+    }
+
+    /// Starts recording in replay mode, that keeps the frames of the given duration in memory.
+    pub fn start_replay(settings: CaptureSettings, duration: Duration) -> Self {
+        let capacity = (duration.as_secs_f32() * settings.fps).ceil().max(1.0) as usize;
+        Self::new(settings, CaptureMode::Replay(capacity))
+    }
+
+    /// Returns current capture settings.
+    pub fn settings(&self) -> &CaptureSettings {
+        &self.settings
+    }
+
+    /// Collects the frame, that was rendered after the previous call, and requests capture of the
+    /// next frame when it is time for it. `dt` is the time passed since the previous frame in
+    /// seconds. If the game runs slower than the capture frame rate, frames are duplicated, so the
+    /// video has correct speed.
+    pub fn capture(&mut self, renderer: &mut Renderer, dt: f32) {
+        let sender = match self.sender.as_ref() {
+            Some(sender) => sender,
+            None => return,
+        };
+
+        if let Some(frame) = renderer.take_captured_frame() {
+            if self.pending_count > 0 {
+                let count = std::mem::take(&mut self.pending_count);
+                if sender.send(CaptureCommand::Frame { frame, count }).is_err() {
+                    Log::err("Video capture thread has stopped unexpectedly!");
+                    self.sender = None;
+                    return;
+                }
+            }
+        }
+
+        let interval = 1.0 / self.settings.fps.max(1.0);
+        self.accumulator += dt;
+        if self.accumulator >= interval {
+            // Do not duplicate frames for more than a second after long hitches (for example, a
+            // scene loading).
+            let count = ((self.accumulator / interval) as u32).min(self.settings.fps.ceil() as u32);
+            self.accumulator = (self.accumulator - count as f32 * interval).min(interval);
+            self.pending_count += count.max(1);
+            renderer.request_frame_capture();
+        }
+    }
+
+    /// Encodes the frames kept in memory in replay mode using the given encoder. Encoding is done
+    /// in a background thread, its result could be received from the returned receiver. In
+    /// continuous mode the encoder will not receive any frames.
+    pub fn save_replay(
+        &self,
+        encoder: Box<dyn VideoEncoder>,
+    ) -> Receiver<Result<(), CaptureError>> {
+        let (result_sender, result_receiver) = mpsc::channel();
+        match self.sender.as_ref() {
+            Some(sender) => {
+                if let Err(mpsc::SendError(CaptureCommand::SaveReplay(_, result_sender))) =
+                    sender.send(CaptureCommand::SaveReplay(encoder, result_sender))
+                {
+                    let _ = result_sender.send(Err(CaptureError::ThreadStopped));
+                }
+            }
+            None => {
+                let _ = result_sender.send(Err(CaptureError::ThreadStopped));
+            }
+        }
+        result_receiver
+    }
+
+    /// Stops recording, waits until every captured frame is encoded and finishes the encoding in
+    /// continuous mode.
+    pub fn stop(mut self) -> Result<(), CaptureError> {
+        self.sender = None;
+        match self.thread.take() {
+            Some(thread) => thread.join().map_err(|_| CaptureError::ThreadStopped)?,
+            None => Err(CaptureError::ThreadStopped),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct TestEncoder {
+        frames: Arc<Mutex<Vec<(u32, u32, u8)>>>,
+        finished: Arc<Mutex<bool>>,
+    }
+
+    impl VideoEncoder for TestEncoder {
+        fn begin(&mut self, _size: Vector2<u32>, _fps: f32) -> Result<(), CaptureError> {
+            Ok(())
+        }
+
+        fn encode_frame(&mut self, frame: &RgbaImage) -> Result<(), CaptureError> {
+            self.frames.lock().unwrap().push((
+                frame.width(),
+                frame.height(),
+                frame.get_pixel(0, 0)[0],
+            ));
+            Ok(())
+        }
+
+        fn finish(&mut self) -> Result<(), CaptureError> {
+            *self.finished.lock().unwrap() = true;
+            Ok(())
+        }
+    }
+
+    fn frame(value: u8) -> CaptureCommand {
+        CaptureCommand::Frame {
+            frame: CapturedFrame {
+                size: Vector2::new(40, 20),
+                pixels: vec![value; 40 * 20 * 4],
+            },
+            count: 1,
+        }
+    }
+
+    #[test]
+    fn test_capture_settings_frame_size() {
+        let settings = CaptureSettings {
+            max_size: Some(Vector2::new(480, 270)),
+            fps: 15.0,
+        };
+        assert_eq!(
+            settings.frame_size(Vector2::new(1920, 1080)),
+            Vector2::new(480, 270)
+        );
+        assert_eq!(
+            settings.frame_size(Vector2::new(1080, 1080)),
+            Vector2::new(270, 270)
+        );
+        assert_eq!(
+            settings.frame_size(Vector2::new(320, 200)),
+            Vector2::new(320, 200)
+        );
+    }
+
+    #[test]
+    fn test_replay_keeps_last_frames() {
+        let settings = CaptureSettings {
+            max_size: Some(Vector2::new(20, 20)),
+            fps: 2.0,
+        };
+        let worker = CaptureWorker {
+            settings,
+            // Two seconds at 2 FPS.
+            mode: CaptureMode::Replay(4),
+            size: None,
+            frames: Default::default(),
+            started: false,
+        };
+        let (sender, receiver) = mpsc::channel();
+        for value in 0..6 {
+            sender.send(frame(value)).unwrap();
+        }
+        let encoder = TestEncoder::default();
+        let (result_sender, result_receiver) = mpsc::channel();
+        sender
+            .send(CaptureCommand::SaveReplay(
+                Box::new(encoder.clone()),
+                result_sender,
+            ))
+            .unwrap();
+        drop(sender);
+
+        worker.run(receiver).unwrap();
+        result_receiver.recv().unwrap().unwrap();
+
+        assert_eq!(
+            *encoder.frames.lock().unwrap(),
+            vec![(20, 10, 2), (20, 10, 3), (20, 10, 4), (20, 10, 5)]
+        );
+        assert!(*encoder.finished.lock().unwrap());
+    }
+
+    #[cfg(feature = "ffmpeg_capture")]
+    #[test]
+    fn test_missing_ffmpeg() {
+        let mut encoder =
+            FfmpegEncoder::new("video.mp4").with_executable("missing-ffmpeg-executable");
+        assert!(matches!(
+            encoder.begin(Vector2::new(2, 2), 30.0),
+            Err(CaptureError::EncoderNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//! Codec backend, that uses FFmpeg libraries to decode VP8, VP9 and AV1 streams. See [`FfmpegBackend`]
+//! docs for more info.
+
+use crate::video::{
+    ivf::{Codec, CodecBackend},
+    ChromaSubsampling, ColorMatrix, ColorRange, VideoError, YuvImage,
+};
+use ffmpeg_next::{
+    codec::{decoder, Context, Id},
+    color,
+    error::EAGAIN,
+    frame,
+    util::format::Pixel,
+    Error, Packet,
+};
+
+impl From<Error> for VideoError {
+    fn from(e: Error) -> Self {
+        Self::Codec(e.to_string())
+    }
+}
+
+/// A [`CodecBackend`] for [`crate::video::ivf::IvfDecoder`], that decodes VP8, VP9 and AV1 streams
+/// using libavcodec. The backend is available with `ffmpeg_video` feature, which links the engine to
+/// FFmpeg libraries, so they must be installed on the target platform (or shipped together with
+/// the game). AV1 decoding requires libavcodec to be built with libdav1d or libaom (it is the case
+/// for most of the distributions).
+///
+/// ```rust,no_run
+/// use fyrox::video::{ffmpeg::FfmpegBackend, ivf::IvfDecoder, VideoPlayer};
+///
+/// async fn load_intro() -> VideoPlayer {
+///     let decoder = IvfDecoder::from_file("intro.ivf", Box::new(FfmpegBackend::new()))
+///         .await
+///         .unwrap();
+///     VideoPlayer::new(Box::new(decoder))
+/// }
+/// ```
+#[derive(Default)]
+pub struct FfmpegBackend {
+    decoder: Option<decoder::Video>,
+    frame: Option<frame::Video>,
+}
+
+impl FfmpegBackend {
+    /// Creates a new backend. The decoder itself is created when the codec of a stream is known.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn copy_plane(frame: &frame::Video, index: usize, width: u32, height: u32) -> Vec<u8> {
+    let stride = frame.stride(index);
+    let data = frame.data(index);
+    let mut plane = Vec::with_capacity((width * height) as usize);
+    for row in 0..height as usize {
+        let start = row * stride;
+        plane.extend_from_slice(&data[start..start + width as usize]);
+    }
+    plane
+}
+
+fn convert(frame: &frame::Video) -> Result<YuvImage, VideoError> {
+    let (subsampling, full_range) = match frame.format() {
+        Pixel::YUV420P => (ChromaSubsampling::Yuv420, false),
+        Pixel::YUVJ420P => (ChromaSubsampling::Yuv420, true),
+        Pixel::YUV422P => (ChromaSubsampling::Yuv422, false),
+        Pixel::YUVJ422P => (ChromaSubsampling::Yuv422, true),
+        Pixel::YUV444P => (ChromaSubsampling::Yuv444, false),
+        Pixel::YUVJ444P => (ChromaSubsampling::Yuv444, true),
+        Pixel::GRAY8 => (ChromaSubsampling::Monochrome, false),
+        format => {
+            return Err(VideoError::UnsupportedFormat(format!(
+                "Pixel format {format:?} is not supported, only 8-bit planar YUV is supported"
+            )))
+        }
+    };
+
+    let (width, height) = (frame.width(), frame.height());
+    let chroma_size = subsampling.chroma_size(width, height);
+    let (u, v) = if subsampling == ChromaSubsampling::Monochrome {
+        (Vec::new(), Vec::new())
+    } else {
+        (
+            copy_plane(frame, 1, chroma_size.x, chroma_size.y),
+            copy_plane(frame, 2, chroma_size.x, chroma_size.y),
+        )
+    };
+
+    Ok(YuvImage {
+        width,
+        height,
+        subsampling,
+        matrix: match frame.color_space() {
+            color::Space::BT709 => ColorMatrix::Bt709,
+            _ => ColorMatrix::Bt601,
+        },
+        range: if full_range || frame.color_range() == color::Range::JPEG {
+            ColorRange::Full
+        } else {
+            ColorRange::Limited
+        },
+        y: copy_plane(frame, 0, width, height),
+        u,
+        v,
+    })
+}
+
+impl CodecBackend for FfmpegBackend {
+    fn supports(&self, codec: Codec) -> bool {
+        matches!(codec, Codec::Vp8 | Codec::Vp9 | Codec::Av1)
+    }
+
+    fn init(&mut self, codec: Codec) -> Result<(), VideoError> {
+        let id = match codec {
+            Codec::Vp8 => Id::VP8,
+            Codec::Vp9 => Id::VP9,
+            Codec::Av1 => Id::AV1,
+            Codec::Other(_) => {
+                return Err(VideoError::UnsupportedFormat(format!(
+                    "Codec {codec} is not supported by FFmpeg backend"
+                )))
+            }
+        };
+        ffmpeg_next::init()?;
+        let decoder = decoder::find(id).ok_or_else(|| {
+            VideoError::UnsupportedFormat(format!("FFmpeg was built without {codec} decoder"))
+        })?;
+        self.decoder = Some(Context::new_with_codec(decoder).decoder().video()?);
+        Ok(())
+    }
+
+    fn decode(&mut self, packet: &[u8]) -> Result<Option<YuvImage>, VideoError> {
+        let decoder = self
+            .decoder
+            .as_mut()
+            .ok_or_else(|| VideoError::Codec("The decoder is not initialized".to_string()))?;
+        decoder.send_packet(&Packet::copy(packet))?;
+
+        let frame = self.frame.get_or_insert_with(frame::Video::empty);
+        match decoder.receive_frame(frame) {
+            Ok(()) => convert(frame).map(Some),
+            // Hidden frames do not produce a picture.
+            Err(Error::Other { errno: EAGAIN }) | Err(Error::Eof) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn reset(&mut self) {
+        if let Some(decoder) = self.decoder.as_mut() {
+            decoder.flush();
+        }
+    }
+}
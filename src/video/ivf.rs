@@ -0,0 +1,372 @@
+//! Demuxer of IVF container. See [`IvfDecoder`] docs for more info.
+
+use crate::{
+    core::io,
+    video::{VideoDecoder, VideoError, VideoFrame, VideoInfo, YuvImage},
+};
+use std::{
+    fmt::{Debug, Display, Formatter},
+    io::{Cursor, ErrorKind, Read, Seek, SeekFrom},
+    path::Path,
+    time::Duration,
+};
+
+const SIGNATURE: &[u8; 4] = b"DKIF";
+const HEADER_SIZE: usize = 32;
+const FRAME_HEADER_SIZE: usize = 12;
+
+/// A codec of a compressed video stream.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// VP8 codec, `VP80` FourCC.
+    Vp8,
+    /// VP9 codec, `VP90` FourCC.
+    Vp9,
+    /// AV1 codec, `AV01` FourCC.
+    Av1,
+    /// Any other codec with its FourCC.
+    Other([u8; 4]),
+}
+
+impl Codec {
+    /// Creates codec from its FourCC code.
+    pub fn from_fourcc(fourcc: [u8; 4]) -> Self {
+        match &fourcc {
+            b"VP80" => Self::Vp8,
+            b"VP90" => Self::Vp9,
+            b"AV01" => Self::Av1,
+            _ => Self::Other(fourcc),
+        }
+    }
+}
+
+impl Display for Codec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Codec::Vp8 => write!(f, "VP8"),
+            Codec::Vp9 => write!(f, "VP9"),
+            Codec::Av1 => write!(f, "AV1"),
+            Codec::Other(fourcc) => write!(f, "{}", String::from_utf8_lossy(fourcc)),
+        }
+    }
+}
+
+/// A decoder of compressed video frames. It is used by [`IvfDecoder`] to turn packets of the
+/// container into images. Implement the trait using a codec library of your choice, for example:
+///
+/// ```rust,no_run
+/// use fyrox::video::{
+///     ivf::{Codec, CodecBackend},
+///     VideoError, YuvImage,
+/// };
+///
+/// struct Av1Backend {
+///     // Some decoder, for example from dav1d bindings.
+/// }
+///
+/// impl CodecBackend for Av1Backend {
+///     fn supports(&self, codec: Codec) -> bool {
+///         codec == Codec::Av1
+///     }
+///
+///     fn decode(&mut self, packet: &[u8]) -> Result<Option<YuvImage>, VideoError> {
+///         // Send the packet to the decoder and copy the planes of the decoded picture
+///         // (if any) to a YuvImage.
+///         Ok(None)
+///     }
+/// }
+/// ```
+pub trait CodecBackend: Send {
+    /// Returns `true` if the backend is able to decode streams of the given codec.
+    fn supports(&self, codec: Codec) -> bool;
+
+    /// Prepares the backend to decode a stream of the given codec. It is called once, before the
+    /// first packet of the stream is decoded.
+    fn init(&mut self, _codec: Codec) -> Result<(), VideoError> {
+        Ok(())
+    }
+
+    /// Decodes a single packet. Some packets do not produce a picture (for example, hidden frames
+    /// of VP9 and AV1 streams), in this case the method should return `Ok(None)`.
+    fn decode(&mut self, packet: &[u8]) -> Result<Option<YuvImage>, VideoError>;
+
+    /// Resets internal state of the backend. It is called when the stream is rewound.
+    fn reset(&mut self) {}
+}
+
+/// Demuxer of IVF container - a simple container, that is used to store VP8, VP9 and AV1 streams.
+/// It could be produced by ffmpeg like so: `ffmpeg -i intro.mp4 -c:v libaom-av1 intro.ivf` (use
+/// `-c:v libvpx-vp9` for VP9). The container does not support audio, so audio track should be
+/// encoded separately (for example, as an ogg file) and played by a sound source, see
+/// [`crate::video::VideoPlayer::sync_sound`] for more info.
+///
+/// Decoding of the frames is delegated to a [`CodecBackend`].
+pub struct IvfDecoder<R> {
+    reader: R,
+    backend: Box<dyn CodecBackend>,
+    codec: Codec,
+    info: VideoInfo,
+    time_base_numerator: u32,
+    time_base_denominator: u32,
+    data_start: u64,
+    frame_index: usize,
+}
+
+impl<R> Debug for IvfDecoder<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IvfDecoder")
+            .field("codec", &self.codec)
+            .field("info", &self.info)
+            .field("frame_index", &self.frame_index)
+            .finish()
+    }
+}
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[0], bytes[1]])
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+impl IvfDecoder<Cursor<Vec<u8>>> {
+    /// Loads the entire file in memory and creates a decoder for it.
+    pub async fn from_file<P: AsRef<Path>>(
+        path: P,
+        backend: Box<dyn CodecBackend>,
+    ) -> Result<Self, VideoError> {
+        Self::new(Cursor::new(io::load_file(path).await?), backend)
+    }
+}
+
+impl<R: Read + Seek> IvfDecoder<R> {
+    /// Reads the header of the stream and creates a new decoder. Fails if the backend does not
+    /// support the codec of the stream.
+    pub fn new(mut reader: R, mut backend: Box<dyn CodecBackend>) -> Result<Self, VideoError> {
+        let mut header = [0u8; HEADER_SIZE];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| VideoError::InvalidHeader("Header is truncated".to_string()))?;
+
+        if &header[0..4] != SIGNATURE {
+            return Err(VideoError::InvalidHeader(
+                "Stream is not an IVF stream".to_string(),
+            ));
+        }
+
+        let header_size = read_u16(&header[6..8]) as u64;
+        let codec = Codec::from_fourcc([header[8], header[9], header[10], header[11]]);
+        if !backend.supports(codec) {
+            return Err(VideoError::UnsupportedFormat(format!(
+                "Codec {codec} is not supported by the backend"
+            )));
+        }
+        backend.init(codec)?;
+
+        let width = read_u16(&header[12..14]) as u32;
+        let height = read_u16(&header[14..16]) as u32;
+        let time_base_denominator = read_u32(&header[16..20]);
+        let time_base_numerator = read_u32(&header[20..24]);
+        let frame_count = read_u32(&header[24..28]);
+        if width == 0 || height == 0 || time_base_denominator == 0 || time_base_numerator == 0 {
+            return Err(VideoError::InvalidHeader(
+                "Frame size or time base is zero".to_string(),
+            ));
+        }
+
+        let data_start = header_size.max(HEADER_SIZE as u64);
+        reader.seek(SeekFrom::Start(data_start))?;
+
+        let frame_rate = time_base_denominator as f64 / time_base_numerator as f64;
+
+        Ok(Self {
+            reader,
+            backend,
+            codec,
+            info: VideoInfo {
+                width,
+                height,
+                frame_rate,
+                duration: if frame_count > 0 {
+                    Some(Duration::from_secs_f64(frame_count as f64 / frame_rate))
+                } else {
+                    None
+                },
+            },
+            time_base_numerator,
+            time_base_denominator,
+            data_start,
+            frame_index: 0,
+        })
+    }
+
+    /// Returns the codec of the stream.
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    fn read_packet(&mut self) -> Result<Option<(u64, Vec<u8>)>, VideoError> {
+        let mut header = [0u8; FRAME_HEADER_SIZE];
+        match self.reader.read_exact(&mut header) {
+            Ok(()) => (),
+            // A truncated frame header at the end of the stream is treated as the end.
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        let size = read_u32(&header[0..4]) as usize;
+        let timestamp = u64::from_le_bytes([
+            header[4], header[5], header[6], header[7], header[8], header[9], header[10],
+            header[11],
+        ]);
+        let mut packet = vec![0; size];
+        self.reader
+            .read_exact(&mut packet)
+            .map_err(|_| VideoError::InvalidFrame(self.frame_index))?;
+
+        Ok(Some((timestamp, packet)))
+    }
+}
+
+impl<R: Read + Seek + Send> VideoDecoder for IvfDecoder<R> {
+    fn info(&self) -> &VideoInfo {
+        &self.info
+    }
+
+    fn next_frame(&mut self) -> Result<Option<VideoFrame>, VideoError> {
+        while let Some((timestamp, packet)) = self.read_packet()? {
+            let index = self.frame_index;
+            self.frame_index += 1;
+
+            if let Some(image) = self.backend.decode(&packet)? {
+                if image.width != self.info.width
+                    || image.height != self.info.height
+                    || !image.is_valid()
+                {
+                    return Err(VideoError::InvalidFrame(index));
+                }
+
+                let seconds = timestamp as f64 * self.time_base_numerator as f64
+                    / self.time_base_denominator as f64;
+
+                return Ok(Some(VideoFrame {
+                    timestamp: Duration::from_secs_f64(seconds),
+                    image,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn rewind(&mut self) -> Result<(), VideoError> {
+        self.reader.seek(SeekFrom::Start(self.data_start))?;
+        self.backend.reset();
+        self.frame_index = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::video::{
+        ivf::{Codec, CodecBackend, IvfDecoder},
+        ChromaSubsampling, VideoDecoder, VideoError, YuvImage,
+    };
+    use std::{io::Cursor, time::Duration};
+
+    // Treats each packet as a gray 2x2 image with the brightness equal to the first byte. Empty
+    // packets do not produce a picture.
+    struct TestBackend;
+
+    impl CodecBackend for TestBackend {
+        fn supports(&self, codec: Codec) -> bool {
+            codec == Codec::Vp9
+        }
+
+        fn decode(&mut self, packet: &[u8]) -> Result<Option<YuvImage>, VideoError> {
+            Ok(packet.first().map(|brightness| YuvImage {
+                width: 2,
+                height: 2,
+                subsampling: ChromaSubsampling::Monochrome,
+                y: vec![*brightness; 4],
+                ..Default::default()
+            }))
+        }
+    }
+
+    // Accepts every codec, but fails to create a decoder for it.
+    struct BrokenBackend;
+
+    impl CodecBackend for BrokenBackend {
+        fn supports(&self, _codec: Codec) -> bool {
+            true
+        }
+
+        fn init(&mut self, codec: Codec) -> Result<(), VideoError> {
+            Err(VideoError::Codec(format!("No {codec} decoder")))
+        }
+
+        fn decode(&mut self, _packet: &[u8]) -> Result<Option<YuvImage>, VideoError> {
+            Ok(None)
+        }
+    }
+
+    fn make_stream(fourcc: &[u8; 4], packets: &[&[u8]]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"DKIF");
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&32u16.to_le_bytes());
+        data.extend_from_slice(fourcc);
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&30u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&(packets.len() as u32).to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for (pts, packet) in packets.iter().enumerate() {
+            data.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(pts as u64).to_le_bytes());
+            data.extend_from_slice(packet);
+        }
+        data
+    }
+
+    #[test]
+    fn test_ivf_demuxing() {
+        assert!(matches!(
+            IvfDecoder::new(
+                Cursor::new(make_stream(b"AV01", &[])),
+                Box::new(TestBackend)
+            ),
+            Err(VideoError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            IvfDecoder::new(
+                Cursor::new(make_stream(b"AV01", &[])),
+                Box::new(BrokenBackend)
+            ),
+            Err(VideoError::Codec(_))
+        ));
+
+        let stream = make_stream(b"VP90", &[&[10], &[], &[30]]);
+        let mut decoder = IvfDecoder::new(Cursor::new(stream), Box::new(TestBackend)).unwrap();
+        assert_eq!(decoder.codec(), Codec::Vp9);
+        assert_eq!(decoder.info().frame_rate, 30.0);
+        assert_eq!(decoder.info().duration, Some(Duration::from_millis(100)));
+
+        let first = decoder.next_frame().unwrap().unwrap();
+        assert_eq!(first.timestamp, Duration::ZERO);
+        assert_eq!(first.image.y, vec![10; 4]);
+
+        // The second packet is hidden, so the third one is returned with its own timestamp.
+        let second = decoder.next_frame().unwrap().unwrap();
+        assert_eq!(second.timestamp, Duration::from_secs_f64(2.0 / 30.0));
+        assert_eq!(second.image.y, vec![30; 4]);
+        assert!(decoder.next_frame().unwrap().is_none());
+
+        decoder.rewind().unwrap();
+        assert_eq!(decoder.next_frame().unwrap().unwrap().image.y, vec![10; 4]);
+    }
+}
@@ -0,0 +1,332 @@
+//! Video playback. It is mostly used for intro movies, cut scenes and in-world screens. See
+//! [`VideoPlayer`] docs for more info.
+//!
+//! ## Decoders
+//!
+//! Videos are decoded by an implementation of [`VideoDecoder`] trait. The engine provides two
+//! decoders out-of-the-box:
+//!
+//! - [`y4m::Y4mDecoder`] - a decoder of uncompressed YUV4MPEG2 streams. It is written in pure
+//!   Rust and works on every platform, but the files are huge, so it is suitable only for very short
+//!   clips.
+//! - [`ivf::IvfDecoder`] - a demuxer for IVF container which is used to store VP8, VP9 and AV1
+//!   streams. Bitstream decoding is delegated to a [`ivf::CodecBackend`], which is usually a thin
+//!   wrapper over a native library (dav1d, libvpx, etc.) or a pure-Rust decoder. The engine ships
+//!   [`ffmpeg::FfmpegBackend`] (VP8, VP9 and AV1) behind `ffmpeg_video` feature, it is not enabled by
+//!   default, because it links the engine to FFmpeg libraries. Implement the trait to use a
+//!   different library, that suits the target platform of your game.
+//!
+//! Custom containers and codecs could be supported by implementing [`VideoDecoder`] trait.
+//!
+//! ## Audio
+//!
+//! A decoder may provide an audio track via [`VideoDecoder::audio_stream`]. It is played by a
+//! regular [`crate::scene::sound::Sound`] node, and the video is synchronized with its playback
+//! position. See [`VideoPlayer::sync_sound`] for more info.
+
+use crate::core::{algebra::Vector2, io::FileLoadError};
+use fyrox_sound::buffer::RawStreamingDataSource;
+use std::{
+    fmt::{Debug, Display, Formatter},
+    time::Duration,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod capture;
+#[cfg(feature = "ffmpeg_video")]
+pub mod ffmpeg;
+pub mod ivf;
+pub mod player;
+pub mod y4m;
+
+pub use player::VideoPlayer;
+
+/// An error that may occur during video decoding.
+#[derive(Debug)]
+pub enum VideoError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// Header of the stream is malformed.
+    InvalidHeader(String),
+
+    /// The stream uses unsupported codec or pixel format.
+    UnsupportedFormat(String),
+
+    /// Frame data is corrupted or truncated.
+    InvalidFrame(usize),
+
+    /// A codec backend has failed to decode a frame.
+    Codec(String),
+}
+
+impl Display for VideoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoError::Io(v) => {
+                write!(f, "An i/o error has occurred {v:?}")
+            }
+            VideoError::InvalidHeader(v) => {
+                write!(f, "Invalid video stream header: {v}")
+            }
+            VideoError::UnsupportedFormat(v) => {
+                write!(f, "Unsupported video format: {v}")
+            }
+            VideoError::InvalidFrame(index) => {
+                write!(f, "Frame {index} is corrupted or truncated!")
+            }
+            VideoError::Codec(v) => {
+                write!(f, "Codec error: {v}")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for VideoError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<std::io::Error> for VideoError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(FileLoadError::Io(e))
+    }
+}
+
+/// Defines how chroma planes of a [`YuvImage`] are subsampled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum ChromaSubsampling {
+    /// Chroma planes have half width and half height of the luma plane.
+    #[default]
+    Yuv420,
+    /// Chroma planes have half width and full height of the luma plane.
+    Yuv422,
+    /// Chroma planes have the same size as the luma plane.
+    Yuv444,
+    /// There are no chroma planes, the image is grayscale.
+    Monochrome,
+}
+
+impl ChromaSubsampling {
+    /// Returns size of a chroma plane of an image with the given size.
+    pub fn chroma_size(self, width: u32, height: u32) -> Vector2<u32> {
+        let half = |size: u32| size / 2 + size % 2;
+        match self {
+            ChromaSubsampling::Yuv420 => Vector2::new(half(width), half(height)),
+            ChromaSubsampling::Yuv422 => Vector2::new(half(width), height),
+            ChromaSubsampling::Yuv444 => Vector2::new(width, height),
+            ChromaSubsampling::Monochrome => Vector2::new(0, 0),
+        }
+    }
+}
+
+/// Defines coefficients that are used to convert YUV colors to RGB.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum ColorMatrix {
+    /// ITU-R BT.601, standard definition video.
+    #[default]
+    Bt601,
+    /// ITU-R BT.709, high definition video.
+    Bt709,
+}
+
+/// Defines the range of YUV values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum ColorRange {
+    /// Luma is in `16..=235` range, chroma is in `16..=240` range. It is the most common range.
+    #[default]
+    Limited,
+    /// All components use full `0..=255` range.
+    Full,
+}
+
+/// Planar 8-bit YUV image, the common output format of video codecs. Planes are tightly packed,
+/// which means that their stride is equal to their width.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct YuvImage {
+    /// Width of the image (and the luma plane) in pixels.
+    pub width: u32,
+    /// Height of the image (and the luma plane) in pixels.
+    pub height: u32,
+    /// Chroma subsampling of the image, it defines the size of `u` and `v` planes.
+    pub subsampling: ChromaSubsampling,
+    /// Conversion coefficients.
+    pub matrix: ColorMatrix,
+    /// Range of the values.
+    pub range: ColorRange,
+    /// Luma plane.
+    pub y: Vec<u8>,
+    /// Blue-difference chroma plane.
+    pub u: Vec<u8>,
+    /// Red-difference chroma plane.
+    pub v: Vec<u8>,
+}
+
+impl YuvImage {
+    /// Returns `true` if the size of each plane matches the size of the image.
+    pub fn is_valid(&self) -> bool {
+        let luma = (self.width * self.height) as usize;
+        let chroma_size = self.subsampling.chroma_size(self.width, self.height);
+        let chroma = (chroma_size.x * chroma_size.y) as usize;
+        self.y.len() == luma && self.u.len() == chroma && self.v.len() == chroma
+    }
+
+    /// Converts the image to RGBA8 format and writes it to the given buffer. The buffer must be
+    /// able to hold `width * height * 4` bytes, otherwise the method does nothing.
+    pub fn write_rgba(&self, rgba: &mut [u8]) {
+        if self.width == 0
+            || !self.is_valid()
+            || rgba.len() < (self.width * self.height * 4) as usize
+        {
+            return;
+        }
+
+        let subsampling = self.subsampling;
+        let (shift_x, shift_y) = match subsampling {
+            ChromaSubsampling::Yuv420 => (1, 1),
+            ChromaSubsampling::Yuv422 => (1, 0),
+            ChromaSubsampling::Yuv444 | ChromaSubsampling::Monochrome => (0, 0),
+        };
+        let chroma_width = subsampling.chroma_size(self.width, self.height).x as usize;
+
+        // Coefficients of the inverse transform, see ITU-R BT.601 and BT.709.
+        let (cr_r, cb_g, cr_g, cb_b) = match self.matrix {
+            ColorMatrix::Bt601 => (1.402, 0.344_136, 0.714_136, 1.772),
+            ColorMatrix::Bt709 => (1.5748, 0.187_324, 0.468_124, 1.8556),
+        };
+        let (y_offset, y_scale, c_scale) = match self.range {
+            ColorRange::Limited => (16.0, 255.0 / 219.0, 255.0 / 224.0),
+            ColorRange::Full => (0.0, 1.0, 1.0),
+        };
+
+        let width = self.width as usize;
+        for (row, (luma_row, rgba_row)) in self
+            .y
+            .chunks_exact(width)
+            .zip(rgba.chunks_exact_mut(width * 4))
+            .enumerate()
+        {
+            let chroma_row = (row >> shift_y) * chroma_width;
+            for (column, (luma, pixel)) in luma_row
+                .iter()
+                .zip(rgba_row.chunks_exact_mut(4))
+                .enumerate()
+            {
+                let luma = (*luma as f32 - y_offset) * y_scale;
+                let (cb, cr) = if subsampling == ChromaSubsampling::Monochrome {
+                    (0.0, 0.0)
+                } else {
+                    let index = chroma_row + (column >> shift_x);
+                    (
+                        (self.u[index] as f32 - 128.0) * c_scale,
+                        (self.v[index] as f32 - 128.0) * c_scale,
+                    )
+                };
+
+                pixel[0] = (luma + cr_r * cr).clamp(0.0, 255.0) as u8;
+                pixel[1] = (luma - cb_g * cb - cr_g * cr).clamp(0.0, 255.0) as u8;
+                pixel[2] = (luma + cb_b * cb).clamp(0.0, 255.0) as u8;
+                pixel[3] = 255;
+            }
+        }
+    }
+}
+
+/// A single decoded frame of a video.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VideoFrame {
+    /// Presentation time of the frame, relative to the beginning of the video.
+    pub timestamp: Duration,
+    /// Pixels of the frame.
+    pub image: YuvImage,
+}
+
+/// Basic information about a video stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoInfo {
+    /// Width of each frame in pixels.
+    pub width: u32,
+    /// Height of each frame in pixels.
+    pub height: u32,
+    /// Amount of frames per second.
+    pub frame_rate: f64,
+    /// Total duration of the video, if known.
+    pub duration: Option<Duration>,
+}
+
+impl VideoInfo {
+    /// Returns the duration of a single frame.
+    pub fn frame_duration(&self) -> Duration {
+        if self.frame_rate > 0.0 {
+            Duration::from_secs_f64(1.0 / self.frame_rate)
+        } else {
+            Duration::default()
+        }
+    }
+}
+
+/// A source of decoded video frames. The trait is implemented by the built-in decoders, see
+/// [module docs](self) for more info. Implement it to add a support for a custom container or
+/// codec.
+pub trait VideoDecoder: Send + Debug {
+    /// Returns basic information about the video stream.
+    fn info(&self) -> &VideoInfo;
+
+    /// Decodes the next frame. Frames must be returned in presentation order. `Ok(None)` means that
+    /// the end of the stream is reached.
+    fn next_frame(&mut self) -> Result<Option<VideoFrame>, VideoError>;
+
+    /// Moves the decoder to the beginning of the stream.
+    fn rewind(&mut self) -> Result<(), VideoError>;
+
+    /// Creates an audio track of the video, if there is any. The stream is played by a sound
+    /// source, which means that it will be read from the sound thread independently of video
+    /// decoding. The method may be called more than once, every call should return a new stream
+    /// that starts from the beginning.
+    fn audio_stream(&self) -> Option<Box<dyn RawStreamingDataSource>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::video::{ChromaSubsampling, ColorMatrix, ColorRange, YuvImage};
+
+    #[test]
+    fn test_yuv_to_rgba() {
+        // 2x2 image with a single chroma sample: white, black, and two gray pixels.
+        let image = YuvImage {
+            width: 2,
+            height: 2,
+            subsampling: ChromaSubsampling::Yuv420,
+            matrix: ColorMatrix::Bt601,
+            range: ColorRange::Limited,
+            y: vec![235, 16, 126, 126],
+            u: vec![128],
+            v: vec![128],
+        };
+        assert!(image.is_valid());
+
+        let mut rgba = vec![0; 16];
+        image.write_rgba(&mut rgba);
+        assert_eq!(&rgba[0..4], &[255, 255, 255, 255]);
+        assert_eq!(&rgba[4..8], &[0, 0, 0, 255]);
+        assert_eq!(&rgba[8..12], &[128, 128, 128, 255]);
+
+        // Pure red in full range.
+        let image = YuvImage {
+            width: 1,
+            height: 1,
+            subsampling: ChromaSubsampling::Yuv444,
+            matrix: ColorMatrix::Bt601,
+            range: ColorRange::Full,
+            y: vec![76],
+            u: vec![85],
+            v: vec![255],
+        };
+        let mut rgba = vec![0; 4];
+        image.write_rgba(&mut rgba);
+        assert!(rgba[0] >= 253 && rgba[1] <= 2 && rgba[2] <= 2);
+    }
+}
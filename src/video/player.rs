@@ -0,0 +1,367 @@
+//! Video player, that decodes frames in time and puts them in a texture. See [`VideoPlayer`] docs
+//! for more info.
+
+use crate::{
+    core::pool::Handle,
+    resource::texture::{TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension},
+    scene::{
+        graph::Graph,
+        node::Node,
+        sound::{DataSource, Sound, SoundBufferResource, Status},
+    },
+    video::{VideoDecoder, VideoError, VideoFrame, VideoInfo},
+};
+use fyrox_sound::buffer::SoundBufferResourceExtension;
+use std::time::Duration;
+
+/// Video player decodes frames of a video in time and writes them to a texture. The texture could
+/// be used anywhere as any other texture: in an image widget for intro movies and cut scenes, or
+/// in a material of a mesh for in-world screens.
+///
+/// Frames are converted to RGBA8 format on CPU and uploaded to GPU once per [`Self::update`] call
+/// (only if there is a new frame). If the decoder is slower than the video, late frames are still
+/// decoded (inter-frame codecs need them), but only the most recent one is uploaded.
+///
+/// ## Audio
+///
+/// The player does not play any sounds by itself, instead it synchronizes itself with a
+/// [`Sound`] node, that plays the audio track of the video. The audio track could be provided by
+/// the decoder ([`Self::create_audio_buffer`]) or could be any other sound buffer (for example,
+/// a separate ogg file). When a sound is attached, its playback position is used as the master
+/// clock, and the playback status of the sound follows the status of the player.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     core::{futures::executor::block_on, pool::Handle},
+///     gui::{image::ImageBuilder, widget::WidgetBuilder, UiNode, UserInterface},
+///     scene::Scene,
+///     utils::into_gui_texture,
+///     video::{y4m::Y4mDecoder, VideoPlayer},
+/// };
+///
+/// fn play_intro(ui: &mut UserInterface) -> (VideoPlayer, Handle<UiNode>) {
+///     let decoder = block_on(Y4mDecoder::from_file("data/intro.y4m")).unwrap();
+///     let mut player = VideoPlayer::new(Box::new(decoder));
+///     player.play();
+///
+///     let image = ImageBuilder::new(WidgetBuilder::new())
+///         .with_texture(into_gui_texture(player.texture()))
+///         .build(&mut ui.build_ctx());
+///
+///     (player, image)
+/// }
+///
+/// fn update(player: &mut VideoPlayer, scene: &mut Scene, dt: f32) {
+///     player.sync_sound(&mut scene.graph);
+///     player.update(dt).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct VideoPlayer {
+    decoder: Box<dyn VideoDecoder>,
+    texture: TextureResource,
+    status: Status,
+    looping: bool,
+    time: Duration,
+    current: Option<Duration>,
+    pending: Option<VideoFrame>,
+    end_of_stream: bool,
+    sound: Handle<Node>,
+}
+
+impl VideoPlayer {
+    /// Creates a new stopped player. It immediately creates a black texture of the size of the
+    /// video.
+    pub fn new(decoder: Box<dyn VideoDecoder>) -> Self {
+        let info = decoder.info();
+        let texture = TextureResource::from_bytes(
+            TextureKind::Rectangle {
+                width: info.width,
+                height: info.height,
+            },
+            TexturePixelKind::RGBA8,
+            [0, 0, 0, 255].repeat((info.width * info.height) as usize),
+            false,
+        )
+        .unwrap();
+
+        Self {
+            decoder,
+            texture,
+            status: Status::Stopped,
+            looping: false,
+            time: Duration::default(),
+            current: None,
+            pending: None,
+            end_of_stream: false,
+            sound: Default::default(),
+        }
+    }
+
+    /// Sets whether the video should start over when it ends or not.
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Returns basic information about the video.
+    pub fn info(&self) -> &VideoInfo {
+        self.decoder.info()
+    }
+
+    /// Returns the texture, that contains current frame of the video.
+    pub fn texture(&self) -> TextureResource {
+        self.texture.clone()
+    }
+
+    /// Returns current playback status.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Starts or resumes playback.
+    pub fn play(&mut self) {
+        self.status = Status::Playing;
+    }
+
+    /// Pauses playback, current frame stays in the texture.
+    pub fn pause(&mut self) {
+        if self.status == Status::Playing {
+            self.status = Status::Paused;
+        }
+    }
+
+    /// Stops playback and rewinds the video. Current frame stays in the texture until the next
+    /// [`Self::play`] call.
+    pub fn stop(&mut self) -> Result<(), VideoError> {
+        self.status = Status::Stopped;
+        self.rewind()
+    }
+
+    /// Sets whether the video should start over when it ends or not.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Returns `true` if the video starts over when it ends.
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Returns current playback position.
+    pub fn playback_time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns `true` if the last frame of a non-looping video was shown for its whole duration.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.end_of_stream && self.pending.is_none() && self.time >= self.length()
+    }
+
+    /// Creates a streaming sound buffer for the audio track of the video, if the decoder provides
+    /// any. Use it to create a [`Sound`] node and attach it to the player using
+    /// [`Self::set_sound`].
+    pub fn create_audio_buffer(&self) -> Option<SoundBufferResource> {
+        let stream = self.decoder.audio_stream()?;
+        SoundBufferResource::new_streaming(DataSource::RawStreaming(stream)).ok()
+    }
+
+    /// Attaches a sound node, that plays the audio track of the video. Pass [`Handle::NONE`] to
+    /// detach current sound. See [`Self::sync_sound`] for more info.
+    pub fn set_sound(&mut self, sound: Handle<Node>) {
+        self.sound = sound;
+    }
+
+    /// Returns a handle of the attached sound node.
+    pub fn sound(&self) -> Handle<Node> {
+        self.sound
+    }
+
+    /// Synchronizes the player with the attached sound node. The sound follows the playback status
+    /// and looping of the player, and the playback position of the sound becomes the master clock
+    /// of the video. Call this method right before [`Self::update`].
+    pub fn sync_sound(&mut self, graph: &mut Graph) {
+        let sound = match graph.try_get_mut_of_type::<Sound>(self.sound) {
+            Some(sound) => sound,
+            None => return,
+        };
+
+        if sound.is_looping() != self.looping {
+            sound.set_looping(self.looping);
+        }
+
+        let status = if self.is_finished() {
+            Status::Stopped
+        } else {
+            self.status
+        };
+        if sound.status() != status {
+            sound.set_status(status);
+        }
+
+        if status == Status::Playing && sound.status() == Status::Playing {
+            self.time = sound.playback_time();
+        }
+    }
+
+    /// Advances internal clock of the player (if there is no attached sound) and uploads the most
+    /// recent frame to the texture. Decoding errors stop the playback.
+    pub fn update(&mut self, dt: f32) -> Result<(), VideoError> {
+        if self.status != Status::Playing {
+            return Ok(());
+        }
+
+        if self.sound.is_none() {
+            self.time += Duration::from_secs_f32(dt.max(0.0));
+        }
+
+        let result = self.present();
+        if result.is_err() {
+            self.status = Status::Stopped;
+        }
+        result
+    }
+
+    // Time at which the last decoded frame ends.
+    fn length(&self) -> Duration {
+        self.current.unwrap_or_default() + self.decoder.info().frame_duration()
+    }
+
+    fn rewind(&mut self) -> Result<(), VideoError> {
+        self.time = Duration::default();
+        self.current = None;
+        self.pending = None;
+        self.end_of_stream = false;
+        self.decoder.rewind()
+    }
+
+    fn present(&mut self) -> Result<(), VideoError> {
+        // The clock went backwards, this happens when the attached sound starts over.
+        if self.current.map_or(false, |current| {
+            self.time + self.decoder.info().frame_duration() < current
+        }) {
+            let time = self.time;
+            self.rewind()?;
+            self.time = time;
+        }
+
+        let mut latest = None;
+        loop {
+            if self.pending.is_none() && !self.end_of_stream {
+                self.pending = self.decoder.next_frame()?;
+                self.end_of_stream = self.pending.is_none();
+            }
+
+            match self.pending.take() {
+                Some(frame) if frame.timestamp <= self.time => {
+                    self.current = Some(frame.timestamp);
+                    latest = Some(frame);
+                }
+                Some(frame) => {
+                    self.pending = Some(frame);
+                    break;
+                }
+                None => {
+                    // An attached sound restarts the video when it starts over by itself.
+                    if self.looping && self.sound.is_none() && self.time >= self.length() {
+                        let time = self.time - self.length();
+                        self.rewind()?;
+                        self.time = time;
+                        continue;
+                    }
+                    break;
+                }
+            }
+        }
+
+        if let Some(frame) = latest {
+            let mut texture = self.texture.data_ref();
+            let mut modifier = texture.modify();
+            frame.image.write_rgba(modifier.data_mut());
+        }
+
+        if self.is_finished() {
+            self.status = Status::Stopped;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        scene::sound::Status,
+        video::{y4m::Y4mDecoder, VideoDecoder, VideoError, VideoPlayer},
+    };
+    use std::io::Cursor;
+
+    // 2x2 grayscale stream at 10 fps, brightness of each frame is equal to its index * 10 + 16.
+    fn make_stream(frames: u8) -> Vec<u8> {
+        let mut data = b"YUV4MPEG2 W2 H2 F10:1 Ip A1:1 Cmono\n".to_vec();
+        for i in 0..frames {
+            data.extend_from_slice(b"FRAME\n");
+            data.extend_from_slice(&[i * 10 + 16; 4]);
+        }
+        data
+    }
+
+    fn red(player: &VideoPlayer) -> u8 {
+        player.texture().data_ref().data()[0]
+    }
+
+    #[test]
+    fn test_video_playback() {
+        assert!(matches!(
+            Y4mDecoder::new(Cursor::new(b"YUV4MPEG2 W2 Cmono\n".to_vec())),
+            Err(VideoError::InvalidHeader(_))
+        ));
+
+        let decoder = Y4mDecoder::new(Cursor::new(make_stream(3))).unwrap();
+        assert_eq!(decoder.info().width, 2);
+        assert_eq!(decoder.info().frame_rate, 10.0);
+
+        let mut player = VideoPlayer::new(Box::new(decoder));
+        assert_eq!(player.texture().data_ref().data(), [0, 0, 0, 255].repeat(4));
+
+        // Nothing happens until the player is started.
+        player.update(1.0).unwrap();
+        assert_eq!(red(&player), 0);
+
+        player.play();
+        player.update(0.0).unwrap();
+        assert_eq!(red(&player), 0);
+        player.update(0.15).unwrap();
+        assert_eq!(red(&player), 11);
+        player.stop().unwrap();
+        player.play();
+
+        // Late frames are skipped.
+        player.update(0.25).unwrap();
+        assert_eq!(red(&player), 23);
+        assert!(!player.is_finished());
+        player.update(1.0).unwrap();
+        assert!(player.is_finished());
+        assert_eq!(player.status(), Status::Stopped);
+
+        player.set_looping(true);
+        player.stop().unwrap();
+        player.play();
+        player.update(0.35).unwrap();
+        assert_eq!(red(&player), 0);
+        assert!(!player.is_finished());
+
+        // A truncated frame stops the playback.
+        let mut stream = make_stream(2);
+        stream.pop();
+        let mut player = VideoPlayer::new(Box::new(Y4mDecoder::new(Cursor::new(stream)).unwrap()));
+        player.play();
+        assert!(matches!(
+            player.update(1.0),
+            Err(VideoError::InvalidFrame(1))
+        ));
+        assert_eq!(player.status(), Status::Stopped);
+    }
+}
@@ -0,0 +1,206 @@
+//! Decoder of YUV4MPEG2 (`.y4m`) streams. See [`Y4mDecoder`] docs for more info.
+
+use crate::{
+    core::io,
+    video::{
+        ChromaSubsampling, ColorMatrix, ColorRange, VideoDecoder, VideoError, VideoFrame,
+        VideoInfo, YuvImage,
+    },
+};
+use std::{
+    fmt::{Debug, Formatter},
+    io::{Cursor, ErrorKind, Read, Seek, SeekFrom},
+    path::Path,
+    time::Duration,
+};
+
+const SIGNATURE: &str = "YUV4MPEG2";
+const FRAME_SIGNATURE: &str = "FRAME";
+// Headers are short, anything longer is most likely not a header at all.
+const MAX_HEADER_LENGTH: usize = 1024;
+
+/// Decoder of YUV4MPEG2 streams. It is a very simple format, that stores uncompressed 8-bit planar
+/// YUV frames one after another, it is supported by pretty much every video tool. For example, a
+/// video could be converted using ffmpeg like so: `ffmpeg -i intro.mp4 -pix_fmt yuv420p intro.y4m`.
+///
+/// Since frames are stored uncompressed, the files are huge and it is advised to use this format
+/// only for short clips. Audio is not supported by the format.
+pub struct Y4mDecoder<R> {
+    reader: R,
+    info: VideoInfo,
+    subsampling: ChromaSubsampling,
+    range: ColorRange,
+    data_start: u64,
+    frame_index: usize,
+}
+
+impl<R> Debug for Y4mDecoder<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Y4mDecoder")
+            .field("info", &self.info)
+            .field("subsampling", &self.subsampling)
+            .field("frame_index", &self.frame_index)
+            .finish()
+    }
+}
+
+fn read_line<R: Read>(reader: &mut R) -> Result<Option<String>, VideoError> {
+    let mut line = Vec::new();
+    let mut byte = [0u8];
+    loop {
+        match reader.read_exact(&mut byte) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof && line.is_empty() => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        if byte[0] == b'\n' {
+            break;
+        }
+        line.push(byte[0]);
+        if line.len() > MAX_HEADER_LENGTH {
+            return Err(VideoError::InvalidHeader("Header is too long".to_string()));
+        }
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| VideoError::InvalidHeader("Header is not a valid string".to_string()))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, VideoError> {
+    value
+        .parse()
+        .map_err(|_| VideoError::InvalidHeader(format!("Invalid {name} value {value}")))
+}
+
+impl Y4mDecoder<Cursor<Vec<u8>>> {
+    /// Loads the entire file in memory and creates a decoder for it.
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, VideoError> {
+        Self::new(Cursor::new(io::load_file(path).await?))
+    }
+}
+
+impl<R: Read + Seek> Y4mDecoder<R> {
+    /// Reads the header of the stream and creates a new decoder.
+    pub fn new(mut reader: R) -> Result<Self, VideoError> {
+        let header = read_line(&mut reader)?
+            .ok_or_else(|| VideoError::InvalidHeader("Stream is empty".to_string()))?;
+
+        let mut params = header.split(' ');
+        if params.next() != Some(SIGNATURE) {
+            return Err(VideoError::InvalidHeader(
+                "Stream is not a YUV4MPEG2 stream".to_string(),
+            ));
+        }
+
+        let mut width = None;
+        let mut height = None;
+        let mut frame_rate = 25.0;
+        let mut subsampling = ChromaSubsampling::Yuv420;
+        let mut range = ColorRange::Limited;
+        for param in params.filter(|p| p.is_char_boundary(1)) {
+            let (tag, value) = param.split_at(1);
+            match tag {
+                "W" => width = Some(parse_number::<u32>(value, "width")?),
+                "H" => height = Some(parse_number::<u32>(value, "height")?),
+                "F" => {
+                    let (numerator, denominator) = value.split_once(':').ok_or_else(|| {
+                        VideoError::InvalidHeader(format!("Invalid frame rate {value}"))
+                    })?;
+                    let numerator = parse_number::<f64>(numerator, "frame rate")?;
+                    let denominator = parse_number::<f64>(denominator, "frame rate")?;
+                    if numerator > 0.0 && denominator > 0.0 {
+                        frame_rate = numerator / denominator;
+                    }
+                }
+                "C" => {
+                    subsampling = match value {
+                        "420" | "420jpeg" | "420paldv" | "420mpeg2" => ChromaSubsampling::Yuv420,
+                        "422" => ChromaSubsampling::Yuv422,
+                        "444" => ChromaSubsampling::Yuv444,
+                        "mono" => ChromaSubsampling::Monochrome,
+                        _ => {
+                            return Err(VideoError::UnsupportedFormat(format!(
+                            "Color space {value} is not supported, only 8-bit formats are allowed"
+                        )))
+                        }
+                    }
+                }
+                "X" if value == "COLORRANGE=FULL" => range = ColorRange::Full,
+                // Interlacing, pixel aspect ratio and other parameters are ignored.
+                _ => (),
+            }
+        }
+
+        let (width, height) = match (width, height) {
+            (Some(width), Some(height)) if width > 0 && height > 0 => (width, height),
+            _ => {
+                return Err(VideoError::InvalidHeader(
+                    "Frame size is not specified".to_string(),
+                ))
+            }
+        };
+
+        let data_start = reader.stream_position()?;
+
+        Ok(Self {
+            reader,
+            info: VideoInfo {
+                width,
+                height,
+                frame_rate,
+                duration: None,
+            },
+            subsampling,
+            range,
+            data_start,
+            frame_index: 0,
+        })
+    }
+}
+
+impl<R: Read + Seek + Send> VideoDecoder for Y4mDecoder<R> {
+    fn info(&self) -> &VideoInfo {
+        &self.info
+    }
+
+    fn next_frame(&mut self) -> Result<Option<VideoFrame>, VideoError> {
+        let frame_header = match read_line(&mut self.reader)? {
+            Some(frame_header) => frame_header,
+            None => return Ok(None),
+        };
+        if !frame_header.starts_with(FRAME_SIGNATURE) {
+            return Err(VideoError::InvalidFrame(self.frame_index));
+        }
+
+        let chroma_size = self
+            .subsampling
+            .chroma_size(self.info.width, self.info.height);
+        let mut image = YuvImage {
+            width: self.info.width,
+            height: self.info.height,
+            subsampling: self.subsampling,
+            matrix: ColorMatrix::Bt601,
+            range: self.range,
+            y: vec![0; (self.info.width * self.info.height) as usize],
+            u: vec![0; (chroma_size.x * chroma_size.y) as usize],
+            v: vec![0; (chroma_size.x * chroma_size.y) as usize],
+        };
+
+        for plane in [&mut image.y, &mut image.u, &mut image.v] {
+            self.reader
+                .read_exact(plane)
+                .map_err(|_| VideoError::InvalidFrame(self.frame_index))?;
+        }
+
+        let timestamp = Duration::from_secs_f64(self.frame_index as f64 / self.info.frame_rate);
+        self.frame_index += 1;
+
+        Ok(Some(VideoFrame { timestamp, image }))
+    }
+
+    fn rewind(&mut self) -> Result<(), VideoError> {
+        self.reader.seek(SeekFrom::Start(self.data_start))?;
+        self.frame_index = 0;
+        Ok(())
+    }
+}
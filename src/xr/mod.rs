@@ -0,0 +1,230 @@
+//! Virtual reality support. It allows to render scenes on head-mounted displays, track the headset
+//! and motion controllers and use the controllers as input devices. See [`XrSession`] docs for
+//! more info.
+//!
+//! ## Runtimes
+//!
+//! The engine talks to a VR runtime through [`XrRuntime`] trait, which mirrors the frame loop of
+//! OpenXR: the trait methods map to `xrPollEvent`, `xrSyncActions`, `xrWaitFrame`/`xrBeginFrame`
+//! (plus `xrLocateViews` and `xrLocateSpace`) and `xrEndFrame` respectively. The engine provides
+//! `openxr::OpenXrRuntime` (available with `openxr` feature), that works with any OpenXR
+//! runtime (SteamVR, Monado, Oculus, Windows Mixed Reality, etc.) using OpenGL graphics binding
+//! and copies the rendered image to swapchain images of the runtime on every frame. Custom
+//! runtimes could be supported by implementing the trait.
+//!
+//! ## Coordinate system
+//!
+//! Poses are given in OpenXR convention: right-handed coordinate system, where `+Y` is up and `-Z`
+//! is forward. The coordinate system matches the one used by the engine, except that the engine
+//! treats `+Z` as forward direction of nodes. [`XrPose::node_rotation`] takes care of it.
+
+use crate::{
+    core::algebra::{UnitQuaternion, Vector2, Vector3},
+    input::XrHand,
+    renderer::framework::state::PipelineState,
+    scene::camera::AsymmetricProjection,
+};
+use std::fmt::{Display, Formatter};
+
+#[cfg(all(feature = "openxr", not(target_arch = "wasm32")))]
+pub mod openxr;
+pub mod rig;
+pub mod session;
+
+pub use rig::XrRig;
+pub use session::XrSession;
+
+/// An error that may occur during interaction with a VR runtime.
+#[derive(Debug)]
+pub enum XrError {
+    /// A runtime-specific error has occurred.
+    Runtime(String),
+
+    /// The runtime was lost (for example, the headset was disconnected or VR software was closed).
+    /// The session must be destroyed.
+    InstanceLost,
+}
+
+impl Display for XrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XrError::Runtime(v) => {
+                write!(f, "VR runtime error: {v}")
+            }
+            XrError::InstanceLost => {
+                write!(f, "VR runtime was lost!")
+            }
+        }
+    }
+}
+
+/// Lifecycle state of an XR session, it mirrors `XrSessionState` of OpenXR.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum XrSessionState {
+    /// The session is created, but the runtime is not ready to show anything yet.
+    #[default]
+    Idle,
+    /// The runtime is ready, the session is about to begin.
+    Ready,
+    /// Frames are synchronized with the headset, but they are not visible to the user.
+    Synchronized,
+    /// Frames are visible to the user, but the application does not receive input (for example,
+    /// the system menu of the headset is opened).
+    Visible,
+    /// Frames are visible and the application receives input.
+    Focused,
+    /// The session is about to end.
+    Stopping,
+    /// The user or the runtime requested to close the application.
+    Exiting,
+}
+
+impl XrSessionState {
+    /// Returns `true` if the runtime expects new frames in this state.
+    pub fn is_running(self) -> bool {
+        matches!(
+            self,
+            XrSessionState::Synchronized | XrSessionState::Visible | XrSessionState::Focused
+        )
+    }
+}
+
+/// Position and orientation of a tracked object, relative to the origin of the tracking space.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct XrPose {
+    /// Position in meters.
+    pub position: Vector3<f32>,
+    /// Orientation in OpenXR convention (`-Z` is forward).
+    pub orientation: UnitQuaternion<f32>,
+}
+
+impl XrPose {
+    /// Returns rotation of a scene node, that makes its look vector (`+Z`) to point in the forward
+    /// direction of the pose.
+    pub fn node_rotation(&self) -> UnitQuaternion<f32> {
+        self.orientation * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), std::f32::consts::PI)
+    }
+}
+
+/// Angles of the sides of the field of view of an eye, in radians. Left and down angles are
+/// negative for typical headsets.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct XrFov {
+    /// Angle of the left side.
+    pub angle_left: f32,
+    /// Angle of the right side.
+    pub angle_right: f32,
+    /// Angle of the top side.
+    pub angle_up: f32,
+    /// Angle of the bottom side.
+    pub angle_down: f32,
+}
+
+impl XrFov {
+    /// Creates a projection with this field of view and the given clipping planes.
+    pub fn projection(&self, z_near: f32, z_far: f32) -> AsymmetricProjection {
+        AsymmetricProjection {
+            angle_left: self.angle_left,
+            angle_right: self.angle_right,
+            angle_up: self.angle_up,
+            angle_down: self.angle_down,
+            z_near,
+            z_far,
+        }
+    }
+}
+
+/// A view of an eye.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct XrView {
+    /// Pose of the eye.
+    pub pose: XrPose,
+    /// Field of view of the eye.
+    pub fov: XrFov,
+}
+
+/// Information about a frame, that is about to be rendered.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct XrFrame {
+    /// Predicted time (in nanoseconds, runtime clock), at which the frame will be displayed. Poses
+    /// are predicted for this time.
+    pub display_time: i64,
+    /// `false` if the runtime does not need an image for the frame (for example, when the headset
+    /// is taken off). The frame must be ended anyway.
+    pub should_render: bool,
+    /// Views of the left and the right eye.
+    pub views: [XrView; 2],
+    /// Pose of the headset, `None` if tracking is lost.
+    pub head: Option<XrPose>,
+    /// Grip poses of the left and the right controller, `None` if a controller is not tracked.
+    pub hands: [Option<XrPose>; 2],
+}
+
+/// State of the inputs of a motion controller.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct XrControllerState {
+    /// `true` if the controller is connected and its inputs are valid.
+    pub active: bool,
+    /// Value of the trigger in `[0; 1]` range.
+    pub trigger: f32,
+    /// Value of the grip (squeeze) in `[0; 1]` range.
+    pub grip: f32,
+    /// Position of the thumbstick (or the touchpad) in `[-1; 1]` range.
+    pub thumbstick: Vector2<f32>,
+    /// State of the primary button (`A` or `X`).
+    pub primary: bool,
+    /// State of the secondary button (`B` or `Y`).
+    pub secondary: bool,
+    /// `true` if the thumbstick is pressed.
+    pub thumbstick_click: bool,
+    /// State of the menu button.
+    pub menu: bool,
+}
+
+/// An image, that must be submitted to the headset. Both eyes are rendered in a single texture:
+/// the left eye is in the left half, the right eye is in the right half.
+pub struct XrRenderTarget<'a> {
+    /// Pipeline state of the renderer. It could be used to copy the image to swapchain images.
+    pub state: &'a mut PipelineState,
+    /// Texture with the rendered image.
+    pub texture: glow::Texture,
+    /// Size of the image of each eye, in pixels.
+    pub eye_size: Vector2<u32>,
+}
+
+/// A connection to a VR runtime, see [module docs](self) for more info.
+pub trait XrRuntime {
+    /// Returns the name of the runtime.
+    fn name(&self) -> &str;
+
+    /// Returns the recommended size of the image of each eye, in pixels.
+    fn recommended_eye_size(&self) -> Vector2<u32>;
+
+    /// Returns new state of the session, if it was changed since the last call. The runtime must
+    /// begin and end the session by itself, when the session becomes [`XrSessionState::Ready`] and
+    /// [`XrSessionState::Stopping`] respectively.
+    fn poll_state(&mut self) -> Result<Option<XrSessionState>, XrError>;
+
+    /// Returns current state of the left and the right controller.
+    fn sync_controllers(&mut self) -> Result<[XrControllerState; 2], XrError>;
+
+    /// Waits until the headset is ready for the next frame, begins the frame and locates every
+    /// tracked object. It is called only when the session is running.
+    fn begin_frame(&mut self) -> Result<XrFrame, XrError>;
+
+    /// Ends the frame that was started by [`Self::begin_frame`]. `target` is `None` if the frame
+    /// was not rendered.
+    fn end_frame(
+        &mut self,
+        frame: &XrFrame,
+        target: Option<XrRenderTarget<'_>>,
+    ) -> Result<(), XrError>;
+}
+
+/// Returns an index of the hand in the arrays of [`XrFrame::hands`] and [`XrRig::hands`].
+pub fn hand_index(hand: XrHand) -> usize {
+    match hand {
+        XrHand::Left => 0,
+        XrHand::Right => 1,
+    }
+}
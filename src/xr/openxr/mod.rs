@@ -0,0 +1,1092 @@
+//! [`XrRuntime`] implementation, that works with any OpenXR runtime. See [`OpenXrRuntime`] docs for
+//! more info.
+
+mod sys;
+
+use crate::{
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector2, Vector3},
+        log::Log,
+    },
+    engine::InitializedGraphicsContext,
+    renderer::framework::state::ColorMask,
+    xr::{
+        XrControllerState, XrError, XrFov, XrFrame, XrPose, XrRenderTarget, XrRuntime,
+        XrSessionState, XrView,
+    },
+};
+use glow::HasContext;
+use std::{ffi::CStr, num::NonZeroU32, os::raw::c_void, ptr};
+use sys::*;
+
+const EXTENSIONS: &[&[u8]] = &[b"XR_KHR_opengl_enable\0"];
+
+// Maps an OpenXR result to an error, success codes (non-negative values) are not errors.
+fn check(result: XrResult, function: &str) -> Result<(), XrError> {
+    match result {
+        XR_ERROR_INSTANCE_LOST | XR_ERROR_SESSION_LOST => Err(XrError::InstanceLost),
+        result if result < 0 => Err(XrError::Runtime(format!(
+            "{function} has failed with error {result}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn pose_from_sys(pose: &XrPosef) -> XrPose {
+    let o = &pose.orientation;
+    XrPose {
+        position: Vector3::new(pose.position.x, pose.position.y, pose.position.z),
+        orientation: UnitQuaternion::new_normalize(Quaternion::new(o.w, o.x, o.y, o.z)),
+    }
+}
+
+fn pose_to_sys(pose: &XrPose) -> XrPosef {
+    let o = pose.orientation.coords;
+    XrPosef {
+        orientation: XrQuaternionf {
+            x: o.x,
+            y: o.y,
+            z: o.z,
+            w: o.w,
+        },
+        position: XrVector3f {
+            x: pose.position.x,
+            y: pose.position.y,
+            z: pose.position.z,
+        },
+    }
+}
+
+enum GraphicsBinding {
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+    ))]
+    Xlib(XrGraphicsBindingOpenGLXlibKHR),
+    #[cfg(windows)]
+    Win32(XrGraphicsBindingOpenGLWin32KHR),
+}
+
+impl GraphicsBinding {
+    fn as_ptr(&self) -> *const c_void {
+        match self {
+            #[cfg(all(
+                unix,
+                not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+            ))]
+            GraphicsBinding::Xlib(binding) => binding as *const _ as *const c_void,
+            #[cfg(windows)]
+            GraphicsBinding::Win32(binding) => binding as *const _ as *const c_void,
+        }
+    }
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+))]
+fn graphics_binding(context: &InitializedGraphicsContext) -> Result<GraphicsBinding, XrError> {
+    use glutin::{
+        config::{AsRawConfig, RawConfig},
+        context::{AsRawContext, RawContext},
+        display::{AsRawDisplay, GetGlDisplay, RawDisplay},
+        platform::x11::X11GlConfigExt,
+        surface::{AsRawSurface, RawSurface},
+    };
+
+    let (config, gl_context, surface) = context.gl_objects();
+    match (
+        config.display().raw_display(),
+        config.raw_config(),
+        gl_context.raw_context(),
+        surface.raw_surface(),
+    ) {
+        (
+            RawDisplay::Glx(display),
+            RawConfig::Glx(fb_config),
+            RawContext::Glx(glx_context),
+            RawSurface::Glx(drawable),
+        ) => Ok(GraphicsBinding::Xlib(XrGraphicsBindingOpenGLXlibKHR {
+            ty: XR_TYPE_GRAPHICS_BINDING_OPENGL_XLIB_KHR,
+            next: ptr::null(),
+            x_display: display as *mut c_void,
+            visualid: config
+                .x11_visual()
+                .map(|visual| visual.visual_id() as u32)
+                .unwrap_or_default(),
+            glx_fb_config: fb_config as *mut c_void,
+            glx_drawable: drawable as _,
+            glx_context: glx_context as *mut c_void,
+        })),
+        _ => Err(XrError::Runtime(
+            "OpenXR runtime requires GLX context, EGL (Wayland) is not supported".to_string(),
+        )),
+    }
+}
+
+#[cfg(windows)]
+fn graphics_binding(context: &InitializedGraphicsContext) -> Result<GraphicsBinding, XrError> {
+    use glutin::{
+        context::{AsRawContext, RawContext},
+        surface::{AsRawSurface, RawSurface},
+    };
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetDC(hwnd: *mut c_void) -> *mut c_void;
+    }
+
+    let (_, gl_context, surface) = context.gl_objects();
+    match (gl_context.raw_context(), surface.raw_surface()) {
+        (RawContext::Wgl(glrc), RawSurface::Wgl(hwnd)) => {
+            Ok(GraphicsBinding::Win32(XrGraphicsBindingOpenGLWin32KHR {
+                ty: XR_TYPE_GRAPHICS_BINDING_OPENGL_WIN32_KHR,
+                next: ptr::null(),
+                h_dc: unsafe { GetDC(hwnd as *mut c_void) },
+                h_glrc: glrc as *mut c_void,
+            }))
+        }
+        _ => Err(XrError::Runtime(
+            "OpenXR runtime requires WGL context, EGL is not supported".to_string(),
+        )),
+    }
+}
+
+#[cfg(not(any(
+    windows,
+    all(
+        unix,
+        not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+    )
+)))]
+fn graphics_binding(_context: &InitializedGraphicsContext) -> Result<GraphicsBinding, XrError> {
+    Err(XrError::Runtime(
+        "OpenXR runtime is not supported on this platform".to_string(),
+    ))
+}
+
+#[derive(Default)]
+struct Actions {
+    set: XrActionSet,
+    trigger: XrAction,
+    grip: XrAction,
+    thumbstick: XrAction,
+    primary: XrAction,
+    secondary: XrAction,
+    thumbstick_click: XrAction,
+    menu: XrAction,
+    grip_pose: XrAction,
+}
+
+/// An [`XrRuntime`], that works with any OpenXR runtime (SteamVR, Monado, Oculus, Windows Mixed
+/// Reality, etc.). It is available with `openxr` feature. OpenXR loader library
+/// (`openxr_loader.dll` or `libopenxr_loader.so.1`) is loaded at runtime, so it must be shipped
+/// together with the game (or installed in the system).
+///
+/// The runtime shares the OpenGL context of the renderer with OpenXR (using `XR_KHR_opengl_enable`
+/// extension), the image rendered by [`crate::xr::XrRig`] cameras is copied to the swapchain of
+/// the runtime on every frame. Only GLX (X11) and WGL (Windows) contexts are supported.
+///
+/// Tracking space is the stage space (the origin is on the floor in the center of the play area)
+/// if the runtime supports it, otherwise it is the local space (the origin is at the initial
+/// position of the headset). Controllers are bound for Oculus Touch, Valve Index and simple KHR
+/// controllers, other controllers are usually remapped by runtimes.
+///
+/// ```rust,no_run
+/// use fyrox::{
+///     core::pool::Handle,
+///     engine::Engine,
+///     scene::Scene,
+///     xr::{openxr::OpenXrRuntime, XrSession},
+/// };
+///
+/// fn start_vr(engine: &mut Engine, scene: Handle<Scene>) {
+///     let runtime =
+///         OpenXrRuntime::new("My Game", engine.graphics_context.as_initialized_ref()).unwrap();
+///     let session = XrSession::new(Box::new(runtime), scene, &mut engine.scenes[scene]);
+///     engine.set_xr_session(Some(session));
+/// }
+/// ```
+pub struct OpenXrRuntime {
+    functions: InstanceFunctions,
+    instance: XrInstance,
+    system_name: String,
+    session: XrSession,
+    session_running: bool,
+    base_space: XrSpace,
+    view_space: XrSpace,
+    swapchain: XrSwapchain,
+    swapchain_images: Vec<u32>,
+    eye_size: Vector2<u32>,
+    actions: Actions,
+    hand_paths: [XrPath; 2],
+    hand_spaces: [XrSpace; 2],
+    // Must be dropped last, it keeps the functions loaded.
+    _loader: Loader,
+}
+
+impl OpenXrRuntime {
+    /// Connects to the active OpenXR runtime and creates a session, that uses OpenGL context of the
+    /// renderer of the given graphics context.
+    pub fn new(
+        application_name: &str,
+        graphics_context: &InitializedGraphicsContext,
+    ) -> Result<Self, XrError> {
+        let binding = graphics_binding(graphics_context)?;
+        let loader = Loader::new().map_err(XrError::Runtime)?;
+        let extensions = EXTENSIONS
+            .iter()
+            .filter_map(|name| CStr::from_bytes_with_nul(name).ok())
+            .collect::<Vec<_>>();
+        let (instance, functions) = loader
+            .create_instance(application_name, &extensions)
+            .map_err(XrError::Runtime)?;
+
+        let mut runtime = Self {
+            functions,
+            instance,
+            system_name: String::new(),
+            session: 0,
+            session_running: false,
+            base_space: 0,
+            view_space: 0,
+            swapchain: 0,
+            swapchain_images: Vec::new(),
+            eye_size: Vector2::new(1, 1),
+            actions: Default::default(),
+            hand_paths: [0; 2],
+            hand_spaces: [0; 2],
+            _loader: loader,
+        };
+        // The instance (with everything created so far) is destroyed by the runtime on failure.
+        runtime.init(&binding)?;
+        Ok(runtime)
+    }
+
+    fn init(&mut self, binding: &GraphicsBinding) -> Result<(), XrError> {
+        let f = &self.functions;
+        unsafe {
+            let info = XrSystemGetInfo {
+                ty: XR_TYPE_SYSTEM_GET_INFO,
+                next: ptr::null(),
+                form_factor: XR_FORM_FACTOR_HEAD_MOUNTED_DISPLAY,
+            };
+            let mut system = 0;
+            check(
+                (f.xrGetSystem)(self.instance, &info, &mut system),
+                "xrGetSystem",
+            )?;
+
+            let mut properties: XrSystemProperties = std::mem::zeroed();
+            properties.ty = XR_TYPE_SYSTEM_PROPERTIES;
+            check(
+                (f.xrGetSystemProperties)(self.instance, system, &mut properties),
+                "xrGetSystemProperties",
+            )?;
+            self.system_name = read_fixed_string(&properties.system_name);
+
+            // The requirements must be queried before the session is created.
+            let mut requirements: XrGraphicsRequirementsOpenGLKHR = std::mem::zeroed();
+            requirements.ty = XR_TYPE_GRAPHICS_REQUIREMENTS_OPENGL_KHR;
+            check(
+                (f.xrGetOpenGLGraphicsRequirementsKHR)(self.instance, system, &mut requirements),
+                "xrGetOpenGLGraphicsRequirementsKHR",
+            )?;
+
+            let mut view_count = 0;
+            check(
+                (f.xrEnumerateViewConfigurationViews)(
+                    self.instance,
+                    system,
+                    XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO,
+                    0,
+                    &mut view_count,
+                    ptr::null_mut(),
+                ),
+                "xrEnumerateViewConfigurationViews",
+            )?;
+            let mut views = (0..view_count)
+                .map(|_| {
+                    let mut view: XrViewConfigurationView = std::mem::zeroed();
+                    view.ty = XR_TYPE_VIEW_CONFIGURATION_VIEW;
+                    view
+                })
+                .collect::<Vec<_>>();
+            check(
+                (f.xrEnumerateViewConfigurationViews)(
+                    self.instance,
+                    system,
+                    XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO,
+                    view_count,
+                    &mut view_count,
+                    views.as_mut_ptr(),
+                ),
+                "xrEnumerateViewConfigurationViews",
+            )?;
+            // Both eyes share a single image, so they must have the same size.
+            self.eye_size = views.iter().fold(Vector2::new(1, 1), |size, view| {
+                size.sup(&Vector2::new(
+                    view.recommended_image_rect_width,
+                    view.recommended_image_rect_height,
+                ))
+            });
+
+            let info = XrSessionCreateInfo {
+                ty: XR_TYPE_SESSION_CREATE_INFO,
+                next: binding.as_ptr(),
+                create_flags: 0,
+                system_id: system,
+            };
+            check(
+                (f.xrCreateSession)(self.instance, &info, &mut self.session),
+                "xrCreateSession",
+            )?;
+
+            self.base_space = match self.create_reference_space(XR_REFERENCE_SPACE_TYPE_STAGE) {
+                Ok(space) => space,
+                Err(_) => self.create_reference_space(XR_REFERENCE_SPACE_TYPE_LOCAL)?,
+            };
+            self.view_space = self.create_reference_space(XR_REFERENCE_SPACE_TYPE_VIEW)?;
+        }
+
+        self.create_swapchain()?;
+        self.create_actions()
+    }
+
+    fn create_reference_space(&self, space_type: i32) -> Result<XrSpace, XrError> {
+        let info = XrReferenceSpaceCreateInfo {
+            ty: XR_TYPE_REFERENCE_SPACE_CREATE_INFO,
+            next: ptr::null(),
+            reference_space_type: space_type,
+            pose_in_reference_space: Default::default(),
+        };
+        let mut space = 0;
+        check(
+            unsafe { (self.functions.xrCreateReferenceSpace)(self.session, &info, &mut space) },
+            "xrCreateReferenceSpace",
+        )?;
+        Ok(space)
+    }
+
+    fn create_swapchain(&mut self) -> Result<(), XrError> {
+        let f = &self.functions;
+        unsafe {
+            let mut count = 0;
+            check(
+                (f.xrEnumerateSwapchainFormats)(self.session, 0, &mut count, ptr::null_mut()),
+                "xrEnumerateSwapchainFormats",
+            )?;
+            let mut formats = vec![0; count as usize];
+            check(
+                (f.xrEnumerateSwapchainFormats)(
+                    self.session,
+                    count,
+                    &mut count,
+                    formats.as_mut_ptr(),
+                ),
+                "xrEnumerateSwapchainFormats",
+            )?;
+            // The rendered image is already gamma-corrected, so it is copied as is to sRGB image.
+            let format = [GL_SRGB8_ALPHA8, GL_RGBA8]
+                .into_iter()
+                .find(|format| formats.contains(format))
+                .or_else(|| formats.first().cloned())
+                .ok_or_else(|| XrError::Runtime("No swapchain formats".to_string()))?;
+
+            // Both eyes are stored side-by-side, the same way as in the render target of the rig.
+            let info = XrSwapchainCreateInfo {
+                ty: XR_TYPE_SWAPCHAIN_CREATE_INFO,
+                next: ptr::null(),
+                create_flags: 0,
+                usage_flags: XR_SWAPCHAIN_USAGE_COLOR_ATTACHMENT_BIT
+                    | XR_SWAPCHAIN_USAGE_TRANSFER_DST_BIT,
+                format,
+                sample_count: 1,
+                width: self.eye_size.x * 2,
+                height: self.eye_size.y,
+                face_count: 1,
+                array_size: 1,
+                mip_count: 1,
+            };
+            check(
+                (f.xrCreateSwapchain)(self.session, &info, &mut self.swapchain),
+                "xrCreateSwapchain",
+            )?;
+
+            check(
+                (f.xrEnumerateSwapchainImages)(self.swapchain, 0, &mut count, ptr::null_mut()),
+                "xrEnumerateSwapchainImages",
+            )?;
+            let mut images = vec![
+                XrSwapchainImageOpenGLKHR {
+                    ty: XR_TYPE_SWAPCHAIN_IMAGE_OPENGL_KHR,
+                    next: ptr::null_mut(),
+                    image: 0,
+                };
+                count as usize
+            ];
+            check(
+                (f.xrEnumerateSwapchainImages)(
+                    self.swapchain,
+                    count,
+                    &mut count,
+                    images.as_mut_ptr(),
+                ),
+                "xrEnumerateSwapchainImages",
+            )?;
+            self.swapchain_images = images.iter().map(|image| image.image).collect();
+        }
+        Ok(())
+    }
+
+    fn path(&self, path: &str) -> Result<XrPath, XrError> {
+        let string = format!("{path}\0");
+        let mut result = 0;
+        check(
+            unsafe {
+                (self.functions.xrStringToPath)(
+                    self.instance,
+                    string.as_ptr() as *const _,
+                    &mut result,
+                )
+            },
+            "xrStringToPath",
+        )?;
+        Ok(result)
+    }
+
+    fn create_action(&self, name: &str, action_type: i32) -> Result<XrAction, XrError> {
+        let info = XrActionCreateInfo {
+            ty: XR_TYPE_ACTION_CREATE_INFO,
+            next: ptr::null(),
+            action_name: fixed_string(name),
+            action_type,
+            count_subaction_paths: self.hand_paths.len() as u32,
+            subaction_paths: self.hand_paths.as_ptr(),
+            localized_action_name: fixed_string(name),
+        };
+        let mut action = 0;
+        check(
+            unsafe { (self.functions.xrCreateAction)(self.actions.set, &info, &mut action) },
+            "xrCreateAction",
+        )?;
+        Ok(action)
+    }
+
+    fn suggest_bindings(
+        &self,
+        profile: &str,
+        bindings: &[(XrAction, &str)],
+    ) -> Result<(), XrError> {
+        let bindings = bindings
+            .iter()
+            .map(|(action, path)| {
+                Ok(XrActionSuggestedBinding {
+                    action: *action,
+                    binding: self.path(path)?,
+                })
+            })
+            .collect::<Result<Vec<_>, XrError>>()?;
+        let info = XrInteractionProfileSuggestedBinding {
+            ty: XR_TYPE_INTERACTION_PROFILE_SUGGESTED_BINDING,
+            next: ptr::null(),
+            interaction_profile: self.path(profile)?,
+            count_suggested_bindings: bindings.len() as u32,
+            suggested_bindings: bindings.as_ptr(),
+        };
+        check(
+            unsafe { (self.functions.xrSuggestInteractionProfileBindings)(self.instance, &info) },
+            "xrSuggestInteractionProfileBindings",
+        )
+    }
+
+    fn create_actions(&mut self) -> Result<(), XrError> {
+        self.hand_paths = [
+            self.path("/user/hand/left")?,
+            self.path("/user/hand/right")?,
+        ];
+
+        let info = XrActionSetCreateInfo {
+            ty: XR_TYPE_ACTION_SET_CREATE_INFO,
+            next: ptr::null(),
+            action_set_name: fixed_string("gameplay"),
+            localized_action_set_name: fixed_string("Gameplay"),
+            priority: 0,
+        };
+        check(
+            unsafe {
+                (self.functions.xrCreateActionSet)(self.instance, &info, &mut self.actions.set)
+            },
+            "xrCreateActionSet",
+        )?;
+
+        self.actions.trigger = self.create_action("trigger", XR_ACTION_TYPE_FLOAT_INPUT)?;
+        self.actions.grip = self.create_action("grip", XR_ACTION_TYPE_FLOAT_INPUT)?;
+        self.actions.thumbstick =
+            self.create_action("thumbstick", XR_ACTION_TYPE_VECTOR2F_INPUT)?;
+        self.actions.primary = self.create_action("primary", XR_ACTION_TYPE_BOOLEAN_INPUT)?;
+        self.actions.secondary = self.create_action("secondary", XR_ACTION_TYPE_BOOLEAN_INPUT)?;
+        self.actions.thumbstick_click =
+            self.create_action("thumbstick_click", XR_ACTION_TYPE_BOOLEAN_INPUT)?;
+        self.actions.menu = self.create_action("menu", XR_ACTION_TYPE_BOOLEAN_INPUT)?;
+        self.actions.grip_pose = self.create_action("grip_pose", XR_ACTION_TYPE_POSE_INPUT)?;
+
+        let a = &self.actions;
+        let profiles: [(&str, Vec<(XrAction, &str)>); 3] = [
+            (
+                "/interaction_profiles/oculus/touch_controller",
+                vec![
+                    (a.trigger, "/user/hand/left/input/trigger/value"),
+                    (a.trigger, "/user/hand/right/input/trigger/value"),
+                    (a.grip, "/user/hand/left/input/squeeze/value"),
+                    (a.grip, "/user/hand/right/input/squeeze/value"),
+                    (a.thumbstick, "/user/hand/left/input/thumbstick"),
+                    (a.thumbstick, "/user/hand/right/input/thumbstick"),
+                    (a.primary, "/user/hand/left/input/x/click"),
+                    (a.primary, "/user/hand/right/input/a/click"),
+                    (a.secondary, "/user/hand/left/input/y/click"),
+                    (a.secondary, "/user/hand/right/input/b/click"),
+                    (a.thumbstick_click, "/user/hand/left/input/thumbstick/click"),
+                    (
+                        a.thumbstick_click,
+                        "/user/hand/right/input/thumbstick/click",
+                    ),
+                    (a.menu, "/user/hand/left/input/menu/click"),
+                    (a.grip_pose, "/user/hand/left/input/grip/pose"),
+                    (a.grip_pose, "/user/hand/right/input/grip/pose"),
+                ],
+            ),
+            (
+                "/interaction_profiles/valve/index_controller",
+                vec![
+                    (a.trigger, "/user/hand/left/input/trigger/value"),
+                    (a.trigger, "/user/hand/right/input/trigger/value"),
+                    (a.grip, "/user/hand/left/input/squeeze/value"),
+                    (a.grip, "/user/hand/right/input/squeeze/value"),
+                    (a.thumbstick, "/user/hand/left/input/thumbstick"),
+                    (a.thumbstick, "/user/hand/right/input/thumbstick"),
+                    (a.primary, "/user/hand/left/input/a/click"),
+                    (a.primary, "/user/hand/right/input/a/click"),
+                    (a.secondary, "/user/hand/left/input/b/click"),
+                    (a.secondary, "/user/hand/right/input/b/click"),
+                    (a.thumbstick_click, "/user/hand/left/input/thumbstick/click"),
+                    (
+                        a.thumbstick_click,
+                        "/user/hand/right/input/thumbstick/click",
+                    ),
+                    (a.grip_pose, "/user/hand/left/input/grip/pose"),
+                    (a.grip_pose, "/user/hand/right/input/grip/pose"),
+                ],
+            ),
+            (
+                "/interaction_profiles/khr/simple_controller",
+                vec![
+                    (a.trigger, "/user/hand/left/input/select/click"),
+                    (a.trigger, "/user/hand/right/input/select/click"),
+                    (a.menu, "/user/hand/left/input/menu/click"),
+                    (a.menu, "/user/hand/right/input/menu/click"),
+                    (a.grip_pose, "/user/hand/left/input/grip/pose"),
+                    (a.grip_pose, "/user/hand/right/input/grip/pose"),
+                ],
+            ),
+        ];
+        for (profile, bindings) in profiles.iter() {
+            // A runtime may not know some profiles, it is not an error.
+            if let Err(err) = self.suggest_bindings(profile, bindings) {
+                Log::warn(format!("Unable to bind {profile} controllers: {err}"));
+            }
+        }
+
+        let info = XrSessionActionSetsAttachInfo {
+            ty: XR_TYPE_SESSION_ACTION_SETS_ATTACH_INFO,
+            next: ptr::null(),
+            count_action_sets: 1,
+            action_sets: &self.actions.set,
+        };
+        check(
+            unsafe { (self.functions.xrAttachSessionActionSets)(self.session, &info) },
+            "xrAttachSessionActionSets",
+        )?;
+
+        for (space, path) in self.hand_spaces.iter_mut().zip(self.hand_paths.iter()) {
+            let info = XrActionSpaceCreateInfo {
+                ty: XR_TYPE_ACTION_SPACE_CREATE_INFO,
+                next: ptr::null(),
+                action: self.actions.grip_pose,
+                subaction_path: *path,
+                pose_in_action_space: Default::default(),
+            };
+            check(
+                unsafe { (self.functions.xrCreateActionSpace)(self.session, &info, space) },
+                "xrCreateActionSpace",
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn locate(&self, space: XrSpace, time: XrTime) -> Result<Option<XrPose>, XrError> {
+        let mut location: XrSpaceLocation = unsafe { std::mem::zeroed() };
+        location.ty = XR_TYPE_SPACE_LOCATION;
+        check(
+            unsafe { (self.functions.xrLocateSpace)(space, self.base_space, time, &mut location) },
+            "xrLocateSpace",
+        )?;
+        let valid = XR_SPACE_LOCATION_ORIENTATION_VALID_BIT | XR_SPACE_LOCATION_POSITION_VALID_BIT;
+        Ok(if location.location_flags & valid == valid {
+            Some(pose_from_sys(&location.pose))
+        } else {
+            None
+        })
+    }
+
+    fn action_info(&self, action: XrAction, hand: usize) -> XrActionStateGetInfo {
+        XrActionStateGetInfo {
+            ty: XR_TYPE_ACTION_STATE_GET_INFO,
+            next: ptr::null(),
+            action,
+            subaction_path: self.hand_paths[hand],
+        }
+    }
+
+    fn bool_state(&self, action: XrAction, hand: usize) -> Result<bool, XrError> {
+        let mut state: XrActionStateBoolean = unsafe { std::mem::zeroed() };
+        state.ty = XR_TYPE_ACTION_STATE_BOOLEAN;
+        check(
+            unsafe {
+                (self.functions.xrGetActionStateBoolean)(
+                    self.session,
+                    &self.action_info(action, hand),
+                    &mut state,
+                )
+            },
+            "xrGetActionStateBoolean",
+        )?;
+        Ok(state.is_active != 0 && state.current_state != 0)
+    }
+
+    fn float_state(&self, action: XrAction, hand: usize) -> Result<f32, XrError> {
+        let mut state: XrActionStateFloat = unsafe { std::mem::zeroed() };
+        state.ty = XR_TYPE_ACTION_STATE_FLOAT;
+        check(
+            unsafe {
+                (self.functions.xrGetActionStateFloat)(
+                    self.session,
+                    &self.action_info(action, hand),
+                    &mut state,
+                )
+            },
+            "xrGetActionStateFloat",
+        )?;
+        Ok(if state.is_active != 0 {
+            state.current_state
+        } else {
+            0.0
+        })
+    }
+
+    fn vector_state(&self, action: XrAction, hand: usize) -> Result<Vector2<f32>, XrError> {
+        let mut state: XrActionStateVector2f = unsafe { std::mem::zeroed() };
+        state.ty = XR_TYPE_ACTION_STATE_VECTOR2F;
+        check(
+            unsafe {
+                (self.functions.xrGetActionStateVector2f)(
+                    self.session,
+                    &self.action_info(action, hand),
+                    &mut state,
+                )
+            },
+            "xrGetActionStateVector2f",
+        )?;
+        Ok(if state.is_active != 0 {
+            Vector2::new(state.current_state.x, state.current_state.y)
+        } else {
+            Vector2::default()
+        })
+    }
+
+    fn pose_active(&self, action: XrAction, hand: usize) -> Result<bool, XrError> {
+        let mut state: XrActionStatePose = unsafe { std::mem::zeroed() };
+        state.ty = XR_TYPE_ACTION_STATE_POSE;
+        check(
+            unsafe {
+                (self.functions.xrGetActionStatePose)(
+                    self.session,
+                    &self.action_info(action, hand),
+                    &mut state,
+                )
+            },
+            "xrGetActionStatePose",
+        )?;
+        Ok(state.is_active != 0)
+    }
+
+    // Copies the image of both eyes to the next image of the swapchain.
+    fn copy_to_swapchain(&self, target: XrRenderTarget<'_>) -> Result<(), XrError> {
+        let f = &self.functions;
+        let mut index = 0;
+        unsafe {
+            let info = XrSwapchainImageAcquireInfo {
+                ty: XR_TYPE_SWAPCHAIN_IMAGE_ACQUIRE_INFO,
+                next: ptr::null(),
+            };
+            check(
+                (f.xrAcquireSwapchainImage)(self.swapchain, &info, &mut index),
+                "xrAcquireSwapchainImage",
+            )?;
+            let info = XrSwapchainImageWaitInfo {
+                ty: XR_TYPE_SWAPCHAIN_IMAGE_WAIT_INFO,
+                next: ptr::null(),
+                timeout: XR_INFINITE_DURATION,
+            };
+            check(
+                (f.xrWaitSwapchainImage)(self.swapchain, &info),
+                "xrWaitSwapchainImage",
+            )?;
+        }
+
+        let image = self
+            .swapchain_images
+            .get(index as usize)
+            .and_then(|image| NonZeroU32::new(*image));
+        if let Some(image) = image {
+            let state = target.state;
+            // Blitting is affected by the scissor test and the color mask.
+            state.set_scissor_test(false);
+            state.set_color_write(ColorMask::default());
+            state.set_framebuffer(None);
+
+            let gl = &state.gl;
+            unsafe {
+                if let (Ok(read), Ok(draw)) = (gl.create_framebuffer(), gl.create_framebuffer()) {
+                    gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(read));
+                    gl.framebuffer_texture_2d(
+                        glow::READ_FRAMEBUFFER,
+                        glow::COLOR_ATTACHMENT0,
+                        glow::TEXTURE_2D,
+                        Some(target.texture),
+                        0,
+                    );
+                    gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(draw));
+                    gl.framebuffer_texture_2d(
+                        glow::DRAW_FRAMEBUFFER,
+                        glow::COLOR_ATTACHMENT0,
+                        glow::TEXTURE_2D,
+                        Some(glow::NativeTexture(image)),
+                        0,
+                    );
+                    gl.blit_framebuffer(
+                        0,
+                        0,
+                        (target.eye_size.x * 2) as i32,
+                        target.eye_size.y as i32,
+                        0,
+                        0,
+                        (self.eye_size.x * 2) as i32,
+                        self.eye_size.y as i32,
+                        glow::COLOR_BUFFER_BIT,
+                        glow::LINEAR,
+                    );
+                    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+                    gl.delete_framebuffer(read);
+                    gl.delete_framebuffer(draw);
+                }
+            }
+        }
+
+        let info = XrSwapchainImageReleaseInfo {
+            ty: XR_TYPE_SWAPCHAIN_IMAGE_RELEASE_INFO,
+            next: ptr::null(),
+        };
+        check(
+            unsafe { (f.xrReleaseSwapchainImage)(self.swapchain, &info) },
+            "xrReleaseSwapchainImage",
+        )
+    }
+}
+
+impl XrRuntime for OpenXrRuntime {
+    fn name(&self) -> &str {
+        &self.system_name
+    }
+
+    fn recommended_eye_size(&self) -> Vector2<u32> {
+        self.eye_size
+    }
+
+    fn poll_state(&mut self) -> Result<Option<XrSessionState>, XrError> {
+        loop {
+            let mut event = XrEventDataBuffer {
+                ty: XR_TYPE_EVENT_DATA_BUFFER,
+                next: ptr::null(),
+                varying: [0; 4000],
+            };
+            let result = unsafe { (self.functions.xrPollEvent)(self.instance, &mut event) };
+            if result == XR_EVENT_UNAVAILABLE {
+                return Ok(None);
+            }
+            check(result, "xrPollEvent")?;
+
+            match event.ty {
+                XR_TYPE_EVENT_DATA_INSTANCE_LOSS_PENDING => return Err(XrError::InstanceLost),
+                XR_TYPE_EVENT_DATA_SESSION_STATE_CHANGED => {
+                    let event = unsafe {
+                        &*(&event as *const XrEventDataBuffer
+                            as *const XrEventDataSessionStateChanged)
+                    };
+                    let state = match event.state {
+                        XR_SESSION_STATE_IDLE => XrSessionState::Idle,
+                        XR_SESSION_STATE_READY => {
+                            let info = XrSessionBeginInfo {
+                                ty: XR_TYPE_SESSION_BEGIN_INFO,
+                                next: ptr::null(),
+                                primary_view_configuration_type:
+                                    XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO,
+                            };
+                            check(
+                                unsafe { (self.functions.xrBeginSession)(self.session, &info) },
+                                "xrBeginSession",
+                            )?;
+                            self.session_running = true;
+                            XrSessionState::Ready
+                        }
+                        XR_SESSION_STATE_SYNCHRONIZED => XrSessionState::Synchronized,
+                        XR_SESSION_STATE_VISIBLE => XrSessionState::Visible,
+                        XR_SESSION_STATE_FOCUSED => XrSessionState::Focused,
+                        XR_SESSION_STATE_STOPPING => {
+                            if self.session_running {
+                                self.session_running = false;
+                                check(
+                                    unsafe { (self.functions.xrEndSession)(self.session) },
+                                    "xrEndSession",
+                                )?;
+                            }
+                            XrSessionState::Stopping
+                        }
+                        XR_SESSION_STATE_EXITING => XrSessionState::Exiting,
+                        XR_SESSION_STATE_LOSS_PENDING => return Err(XrError::InstanceLost),
+                        _ => continue,
+                    };
+                    return Ok(Some(state));
+                }
+                // Other events are not used.
+                _ => (),
+            }
+        }
+    }
+
+    fn sync_controllers(&mut self) -> Result<[XrControllerState; 2], XrError> {
+        let active_set = XrActiveActionSet {
+            action_set: self.actions.set,
+            subaction_path: 0,
+        };
+        let info = XrActionsSyncInfo {
+            ty: XR_TYPE_ACTIONS_SYNC_INFO,
+            next: ptr::null(),
+            count_active_action_sets: 1,
+            active_action_sets: &active_set,
+        };
+        check(
+            unsafe { (self.functions.xrSyncActions)(self.session, &info) },
+            "xrSyncActions",
+        )?;
+
+        let a = &self.actions;
+        let mut controllers = [XrControllerState::default(); 2];
+        for (hand, controller) in controllers.iter_mut().enumerate() {
+            *controller = XrControllerState {
+                active: self.pose_active(a.grip_pose, hand)?,
+                trigger: self.float_state(a.trigger, hand)?,
+                grip: self.float_state(a.grip, hand)?,
+                thumbstick: self.vector_state(a.thumbstick, hand)?,
+                primary: self.bool_state(a.primary, hand)?,
+                secondary: self.bool_state(a.secondary, hand)?,
+                thumbstick_click: self.bool_state(a.thumbstick_click, hand)?,
+                menu: self.bool_state(a.menu, hand)?,
+            };
+        }
+        Ok(controllers)
+    }
+
+    fn begin_frame(&mut self) -> Result<XrFrame, XrError> {
+        let f = &self.functions;
+        let mut frame_state: XrFrameState = unsafe { std::mem::zeroed() };
+        frame_state.ty = XR_TYPE_FRAME_STATE;
+        unsafe {
+            let info = XrFrameWaitInfo {
+                ty: XR_TYPE_FRAME_WAIT_INFO,
+                next: ptr::null(),
+            };
+            check(
+                (f.xrWaitFrame)(self.session, &info, &mut frame_state),
+                "xrWaitFrame",
+            )?;
+            let info = XrFrameBeginInfo {
+                ty: XR_TYPE_FRAME_BEGIN_INFO,
+                next: ptr::null(),
+            };
+            check((f.xrBeginFrame)(self.session, &info), "xrBeginFrame")?;
+        }
+
+        let time = frame_state.predicted_display_time;
+        let mut frame = XrFrame {
+            display_time: time,
+            should_render: frame_state.should_render != 0,
+            ..Default::default()
+        };
+
+        let info = XrViewLocateInfo {
+            ty: XR_TYPE_VIEW_LOCATE_INFO,
+            next: ptr::null(),
+            view_configuration_type: XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO,
+            display_time: time,
+            space: self.base_space,
+        };
+        let mut view_state: XrViewState = unsafe { std::mem::zeroed() };
+        view_state.ty = XR_TYPE_VIEW_STATE;
+        let mut views = [sys::XrView {
+            ty: XR_TYPE_VIEW,
+            next: ptr::null_mut(),
+            pose: Default::default(),
+            fov: Default::default(),
+        }; 2];
+        let mut count = 0;
+        check(
+            unsafe {
+                (f.xrLocateViews)(
+                    self.session,
+                    &info,
+                    &mut view_state,
+                    views.len() as u32,
+                    &mut count,
+                    views.as_mut_ptr(),
+                )
+            },
+            "xrLocateViews",
+        )?;
+        for (view, sys_view) in frame.views.iter_mut().zip(views.iter()) {
+            *view = XrView {
+                pose: pose_from_sys(&sys_view.pose),
+                fov: XrFov {
+                    angle_left: sys_view.fov.angle_left,
+                    angle_right: sys_view.fov.angle_right,
+                    angle_up: sys_view.fov.angle_up,
+                    angle_down: sys_view.fov.angle_down,
+                },
+            };
+        }
+
+        frame.head = self.locate(self.view_space, time)?;
+        for (pose, space) in frame.hands.iter_mut().zip(self.hand_spaces.iter()) {
+            *pose = self.locate(*space, time)?;
+        }
+
+        Ok(frame)
+    }
+
+    fn end_frame(
+        &mut self,
+        frame: &XrFrame,
+        target: Option<XrRenderTarget<'_>>,
+    ) -> Result<(), XrError> {
+        let rendered = match target {
+            Some(target) => {
+                self.copy_to_swapchain(target)?;
+                true
+            }
+            None => false,
+        };
+
+        let views = [0, 1].map(|eye| {
+            let view = &frame.views[eye];
+            XrCompositionLayerProjectionView {
+                ty: XR_TYPE_COMPOSITION_LAYER_PROJECTION_VIEW,
+                next: ptr::null(),
+                pose: pose_to_sys(&view.pose),
+                fov: XrFovf {
+                    angle_left: view.fov.angle_left,
+                    angle_right: view.fov.angle_right,
+                    angle_up: view.fov.angle_up,
+                    angle_down: view.fov.angle_down,
+                },
+                sub_image: XrSwapchainSubImage {
+                    swapchain: self.swapchain,
+                    image_rect: XrRect2Di {
+                        offset: XrOffset2Di {
+                            x: (eye as u32 * self.eye_size.x) as i32,
+                            y: 0,
+                        },
+                        extent: XrExtent2Di {
+                            width: self.eye_size.x as i32,
+                            height: self.eye_size.y as i32,
+                        },
+                    },
+                    image_array_index: 0,
+                },
+            }
+        });
+        let layer = XrCompositionLayerProjection {
+            ty: XR_TYPE_COMPOSITION_LAYER_PROJECTION,
+            next: ptr::null(),
+            layer_flags: 0,
+            space: self.base_space,
+            view_count: views.len() as u32,
+            views: views.as_ptr(),
+        };
+        let layers = [&layer as *const XrCompositionLayerProjection];
+
+        let info = XrFrameEndInfo {
+            ty: XR_TYPE_FRAME_END_INFO,
+            next: ptr::null(),
+            display_time: frame.display_time,
+            environment_blend_mode: XR_ENVIRONMENT_BLEND_MODE_OPAQUE,
+            layer_count: if rendered { 1 } else { 0 },
+            layers: layers.as_ptr(),
+        };
+        check(
+            unsafe { (self.functions.xrEndFrame)(self.session, &info) },
+            "xrEndFrame",
+        )
+    }
+}
+
+impl Drop for OpenXrRuntime {
+    fn drop(&mut self) {
+        // Destruction of the instance destroys every object created from it.
+        unsafe {
+            (self.functions.xrDestroyInstance)(self.instance);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector3},
+        xr::{
+            openxr::{pose_from_sys, pose_to_sys, sys::fixed_string, sys::read_fixed_string},
+            XrPose,
+        },
+    };
+    use std::os::raw::c_char;
+
+    #[test]
+    fn test_pose_conversion() {
+        let pose = XrPose {
+            position: Vector3::new(1.0, 2.0, 3.0),
+            orientation: UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3),
+        };
+        let converted = pose_from_sys(&pose_to_sys(&pose));
+        assert_eq!(converted.position, pose.position);
+        assert!(converted.orientation.angle_to(&pose.orientation) < 1.0e-5);
+    }
+
+    #[test]
+    fn test_fixed_string() {
+        let string: [c_char; 8] = fixed_string("gameplay_actions");
+        assert_eq!(string[7], 0);
+        assert_eq!(read_fixed_string(&string), "gamepla");
+        let string: [c_char; 8] = fixed_string("grip");
+        assert_eq!(read_fixed_string(&string), "grip");
+    }
+}
@@ -0,0 +1,766 @@
+//! Minimal subset of OpenXR 1.0 C API, that is used by [`super::OpenXrRuntime`]. Functions are
+//! loaded from OpenXR loader at runtime, so the engine does not link to it.
+
+#![allow(non_camel_case_types, clippy::upper_case_acronyms)]
+
+use libloading::Library;
+use std::{
+    ffi::{c_void, CStr},
+    os::raw::{c_char, c_ulong},
+    ptr,
+};
+
+pub type XrResult = i32;
+pub type XrStructureType = u32;
+pub type XrInstance = u64;
+pub type XrSession = u64;
+pub type XrSpace = u64;
+pub type XrSwapchain = u64;
+pub type XrActionSet = u64;
+pub type XrAction = u64;
+pub type XrSystemId = u64;
+pub type XrPath = u64;
+pub type XrTime = i64;
+pub type XrDuration = i64;
+pub type XrBool32 = u32;
+
+pub const XR_SUCCESS: XrResult = 0;
+pub const XR_EVENT_UNAVAILABLE: XrResult = 4;
+pub const XR_ERROR_INSTANCE_LOST: XrResult = -13;
+pub const XR_ERROR_SESSION_LOST: XrResult = -17;
+
+pub const XR_TYPE_INSTANCE_CREATE_INFO: XrStructureType = 3;
+pub const XR_TYPE_SYSTEM_GET_INFO: XrStructureType = 4;
+pub const XR_TYPE_SYSTEM_PROPERTIES: XrStructureType = 5;
+pub const XR_TYPE_VIEW_LOCATE_INFO: XrStructureType = 6;
+pub const XR_TYPE_VIEW: XrStructureType = 7;
+pub const XR_TYPE_SESSION_CREATE_INFO: XrStructureType = 8;
+pub const XR_TYPE_SWAPCHAIN_CREATE_INFO: XrStructureType = 9;
+pub const XR_TYPE_SESSION_BEGIN_INFO: XrStructureType = 10;
+pub const XR_TYPE_VIEW_STATE: XrStructureType = 11;
+pub const XR_TYPE_FRAME_END_INFO: XrStructureType = 12;
+pub const XR_TYPE_EVENT_DATA_BUFFER: XrStructureType = 16;
+pub const XR_TYPE_EVENT_DATA_INSTANCE_LOSS_PENDING: XrStructureType = 17;
+pub const XR_TYPE_EVENT_DATA_SESSION_STATE_CHANGED: XrStructureType = 18;
+pub const XR_TYPE_ACTION_STATE_BOOLEAN: XrStructureType = 23;
+pub const XR_TYPE_ACTION_STATE_FLOAT: XrStructureType = 24;
+pub const XR_TYPE_ACTION_STATE_VECTOR2F: XrStructureType = 25;
+pub const XR_TYPE_ACTION_STATE_POSE: XrStructureType = 27;
+pub const XR_TYPE_ACTION_SET_CREATE_INFO: XrStructureType = 28;
+pub const XR_TYPE_ACTION_CREATE_INFO: XrStructureType = 29;
+pub const XR_TYPE_FRAME_WAIT_INFO: XrStructureType = 33;
+pub const XR_TYPE_COMPOSITION_LAYER_PROJECTION: XrStructureType = 35;
+pub const XR_TYPE_REFERENCE_SPACE_CREATE_INFO: XrStructureType = 37;
+pub const XR_TYPE_ACTION_SPACE_CREATE_INFO: XrStructureType = 38;
+pub const XR_TYPE_VIEW_CONFIGURATION_VIEW: XrStructureType = 41;
+pub const XR_TYPE_SPACE_LOCATION: XrStructureType = 42;
+pub const XR_TYPE_FRAME_STATE: XrStructureType = 44;
+pub const XR_TYPE_FRAME_BEGIN_INFO: XrStructureType = 46;
+pub const XR_TYPE_COMPOSITION_LAYER_PROJECTION_VIEW: XrStructureType = 48;
+pub const XR_TYPE_INTERACTION_PROFILE_SUGGESTED_BINDING: XrStructureType = 51;
+pub const XR_TYPE_SWAPCHAIN_IMAGE_ACQUIRE_INFO: XrStructureType = 55;
+pub const XR_TYPE_SWAPCHAIN_IMAGE_WAIT_INFO: XrStructureType = 56;
+pub const XR_TYPE_SWAPCHAIN_IMAGE_RELEASE_INFO: XrStructureType = 57;
+pub const XR_TYPE_ACTION_STATE_GET_INFO: XrStructureType = 58;
+pub const XR_TYPE_SESSION_ACTION_SETS_ATTACH_INFO: XrStructureType = 60;
+pub const XR_TYPE_ACTIONS_SYNC_INFO: XrStructureType = 61;
+#[cfg(windows)]
+pub const XR_TYPE_GRAPHICS_BINDING_OPENGL_WIN32_KHR: XrStructureType = 1000023000;
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+))]
+pub const XR_TYPE_GRAPHICS_BINDING_OPENGL_XLIB_KHR: XrStructureType = 1000023001;
+pub const XR_TYPE_SWAPCHAIN_IMAGE_OPENGL_KHR: XrStructureType = 1000023004;
+pub const XR_TYPE_GRAPHICS_REQUIREMENTS_OPENGL_KHR: XrStructureType = 1000023005;
+
+pub const XR_FORM_FACTOR_HEAD_MOUNTED_DISPLAY: i32 = 1;
+pub const XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO: i32 = 2;
+pub const XR_REFERENCE_SPACE_TYPE_VIEW: i32 = 1;
+pub const XR_REFERENCE_SPACE_TYPE_LOCAL: i32 = 2;
+pub const XR_REFERENCE_SPACE_TYPE_STAGE: i32 = 3;
+pub const XR_ENVIRONMENT_BLEND_MODE_OPAQUE: i32 = 1;
+
+pub const XR_ACTION_TYPE_BOOLEAN_INPUT: i32 = 1;
+pub const XR_ACTION_TYPE_FLOAT_INPUT: i32 = 2;
+pub const XR_ACTION_TYPE_VECTOR2F_INPUT: i32 = 3;
+pub const XR_ACTION_TYPE_POSE_INPUT: i32 = 4;
+
+pub const XR_SESSION_STATE_IDLE: i32 = 1;
+pub const XR_SESSION_STATE_READY: i32 = 2;
+pub const XR_SESSION_STATE_SYNCHRONIZED: i32 = 3;
+pub const XR_SESSION_STATE_VISIBLE: i32 = 4;
+pub const XR_SESSION_STATE_FOCUSED: i32 = 5;
+pub const XR_SESSION_STATE_STOPPING: i32 = 6;
+pub const XR_SESSION_STATE_LOSS_PENDING: i32 = 7;
+pub const XR_SESSION_STATE_EXITING: i32 = 8;
+
+pub const XR_SWAPCHAIN_USAGE_COLOR_ATTACHMENT_BIT: u64 = 0x1;
+pub const XR_SWAPCHAIN_USAGE_TRANSFER_DST_BIT: u64 = 0x10;
+pub const XR_SPACE_LOCATION_ORIENTATION_VALID_BIT: u64 = 0x1;
+pub const XR_SPACE_LOCATION_POSITION_VALID_BIT: u64 = 0x2;
+pub const XR_INFINITE_DURATION: XrDuration = i64::MAX;
+pub const XR_CURRENT_API_VERSION: u64 = 1 << 48;
+
+pub const XR_MAX_APPLICATION_NAME_SIZE: usize = 128;
+pub const XR_MAX_ENGINE_NAME_SIZE: usize = 128;
+pub const XR_MAX_SYSTEM_NAME_SIZE: usize = 256;
+pub const XR_MAX_ACTION_SET_NAME_SIZE: usize = 64;
+pub const XR_MAX_ACTION_NAME_SIZE: usize = 64;
+pub const XR_MAX_LOCALIZED_NAME_SIZE: usize = 128;
+
+pub const GL_RGBA8: i64 = 0x8058;
+pub const GL_SRGB8_ALPHA8: i64 = 0x8C43;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct XrVector2f {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct XrVector3f {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct XrQuaternionf {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for XrQuaternionf {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct XrPosef {
+    pub orientation: XrQuaternionf,
+    pub position: XrVector3f,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct XrFovf {
+    pub angle_left: f32,
+    pub angle_right: f32,
+    pub angle_up: f32,
+    pub angle_down: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct XrOffset2Di {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct XrExtent2Di {
+    pub width: i32,
+    pub height: i32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct XrRect2Di {
+    pub offset: XrOffset2Di,
+    pub extent: XrExtent2Di,
+}
+
+#[repr(C)]
+pub struct XrApplicationInfo {
+    pub application_name: [c_char; XR_MAX_APPLICATION_NAME_SIZE],
+    pub application_version: u32,
+    pub engine_name: [c_char; XR_MAX_ENGINE_NAME_SIZE],
+    pub engine_version: u32,
+    pub api_version: u64,
+}
+
+#[repr(C)]
+pub struct XrInstanceCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub create_flags: u64,
+    pub application_info: XrApplicationInfo,
+    pub enabled_api_layer_count: u32,
+    pub enabled_api_layer_names: *const *const c_char,
+    pub enabled_extension_count: u32,
+    pub enabled_extension_names: *const *const c_char,
+}
+
+#[repr(C)]
+pub struct XrSystemGetInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub form_factor: i32,
+}
+
+#[repr(C)]
+pub struct XrSystemGraphicsProperties {
+    pub max_swapchain_image_height: u32,
+    pub max_swapchain_image_width: u32,
+    pub max_layer_count: u32,
+}
+
+#[repr(C)]
+pub struct XrSystemTrackingProperties {
+    pub orientation_tracking: XrBool32,
+    pub position_tracking: XrBool32,
+}
+
+#[repr(C)]
+pub struct XrSystemProperties {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub system_id: XrSystemId,
+    pub vendor_id: u32,
+    pub system_name: [c_char; XR_MAX_SYSTEM_NAME_SIZE],
+    pub graphics_properties: XrSystemGraphicsProperties,
+    pub tracking_properties: XrSystemTrackingProperties,
+}
+
+#[repr(C)]
+pub struct XrGraphicsRequirementsOpenGLKHR {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub min_api_version_supported: u64,
+    pub max_api_version_supported: u64,
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+))]
+#[repr(C)]
+pub struct XrGraphicsBindingOpenGLXlibKHR {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub x_display: *mut c_void,
+    pub visualid: u32,
+    pub glx_fb_config: *mut c_void,
+    pub glx_drawable: c_ulong,
+    pub glx_context: *mut c_void,
+}
+
+#[cfg(windows)]
+#[repr(C)]
+pub struct XrGraphicsBindingOpenGLWin32KHR {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub h_dc: *mut c_void,
+    pub h_glrc: *mut c_void,
+}
+
+#[repr(C)]
+pub struct XrSessionCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub create_flags: u64,
+    pub system_id: XrSystemId,
+}
+
+#[repr(C)]
+pub struct XrSessionBeginInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub primary_view_configuration_type: i32,
+}
+
+#[repr(C)]
+pub struct XrViewConfigurationView {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub recommended_image_rect_width: u32,
+    pub max_image_rect_width: u32,
+    pub recommended_image_rect_height: u32,
+    pub max_image_rect_height: u32,
+    pub recommended_swapchain_sample_count: u32,
+    pub max_swapchain_sample_count: u32,
+}
+
+#[repr(C)]
+pub struct XrEventDataBuffer {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub varying: [u8; 4000],
+}
+
+#[repr(C)]
+pub struct XrEventDataSessionStateChanged {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub session: XrSession,
+    pub state: i32,
+    pub time: XrTime,
+}
+
+#[repr(C)]
+pub struct XrReferenceSpaceCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub reference_space_type: i32,
+    pub pose_in_reference_space: XrPosef,
+}
+
+#[repr(C)]
+pub struct XrActionSpaceCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub action: XrAction,
+    pub subaction_path: XrPath,
+    pub pose_in_action_space: XrPosef,
+}
+
+#[repr(C)]
+pub struct XrSpaceLocation {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub location_flags: u64,
+    pub pose: XrPosef,
+}
+
+#[repr(C)]
+pub struct XrSwapchainCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub create_flags: u64,
+    pub usage_flags: u64,
+    pub format: i64,
+    pub sample_count: u32,
+    pub width: u32,
+    pub height: u32,
+    pub face_count: u32,
+    pub array_size: u32,
+    pub mip_count: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct XrSwapchainImageOpenGLKHR {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub image: u32,
+}
+
+#[repr(C)]
+pub struct XrSwapchainImageAcquireInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrSwapchainImageWaitInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub timeout: XrDuration,
+}
+
+#[repr(C)]
+pub struct XrSwapchainImageReleaseInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrFrameWaitInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrFrameState {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub predicted_display_time: XrTime,
+    pub predicted_display_period: XrDuration,
+    pub should_render: XrBool32,
+}
+
+#[repr(C)]
+pub struct XrFrameBeginInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrViewLocateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub view_configuration_type: i32,
+    pub display_time: XrTime,
+    pub space: XrSpace,
+}
+
+#[repr(C)]
+pub struct XrViewState {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub view_state_flags: u64,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct XrView {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub pose: XrPosef,
+    pub fov: XrFovf,
+}
+
+#[repr(C)]
+pub struct XrSwapchainSubImage {
+    pub swapchain: XrSwapchain,
+    pub image_rect: XrRect2Di,
+    pub image_array_index: u32,
+}
+
+#[repr(C)]
+pub struct XrCompositionLayerProjectionView {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub pose: XrPosef,
+    pub fov: XrFovf,
+    pub sub_image: XrSwapchainSubImage,
+}
+
+#[repr(C)]
+pub struct XrCompositionLayerProjection {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub layer_flags: u64,
+    pub space: XrSpace,
+    pub view_count: u32,
+    pub views: *const XrCompositionLayerProjectionView,
+}
+
+#[repr(C)]
+pub struct XrFrameEndInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub display_time: XrTime,
+    pub environment_blend_mode: i32,
+    pub layer_count: u32,
+    pub layers: *const *const XrCompositionLayerProjection,
+}
+
+#[repr(C)]
+pub struct XrActionSetCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub action_set_name: [c_char; XR_MAX_ACTION_SET_NAME_SIZE],
+    pub localized_action_set_name: [c_char; XR_MAX_LOCALIZED_NAME_SIZE],
+    pub priority: u32,
+}
+
+#[repr(C)]
+pub struct XrActionCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub action_name: [c_char; XR_MAX_ACTION_NAME_SIZE],
+    pub action_type: i32,
+    pub count_subaction_paths: u32,
+    pub subaction_paths: *const XrPath,
+    pub localized_action_name: [c_char; XR_MAX_LOCALIZED_NAME_SIZE],
+}
+
+#[repr(C)]
+pub struct XrActionSuggestedBinding {
+    pub action: XrAction,
+    pub binding: XrPath,
+}
+
+#[repr(C)]
+pub struct XrInteractionProfileSuggestedBinding {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub interaction_profile: XrPath,
+    pub count_suggested_bindings: u32,
+    pub suggested_bindings: *const XrActionSuggestedBinding,
+}
+
+#[repr(C)]
+pub struct XrSessionActionSetsAttachInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub count_action_sets: u32,
+    pub action_sets: *const XrActionSet,
+}
+
+#[repr(C)]
+pub struct XrActiveActionSet {
+    pub action_set: XrActionSet,
+    pub subaction_path: XrPath,
+}
+
+#[repr(C)]
+pub struct XrActionsSyncInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub count_active_action_sets: u32,
+    pub active_action_sets: *const XrActiveActionSet,
+}
+
+#[repr(C)]
+pub struct XrActionStateGetInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub action: XrAction,
+    pub subaction_path: XrPath,
+}
+
+#[repr(C)]
+pub struct XrActionStateBoolean {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub current_state: XrBool32,
+    pub changed_since_last_sync: XrBool32,
+    pub last_change_time: XrTime,
+    pub is_active: XrBool32,
+}
+
+#[repr(C)]
+pub struct XrActionStateFloat {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub current_state: f32,
+    pub changed_since_last_sync: XrBool32,
+    pub last_change_time: XrTime,
+    pub is_active: XrBool32,
+}
+
+#[repr(C)]
+pub struct XrActionStateVector2f {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub current_state: XrVector2f,
+    pub changed_since_last_sync: XrBool32,
+    pub last_change_time: XrTime,
+    pub is_active: XrBool32,
+}
+
+#[repr(C)]
+pub struct XrActionStatePose {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub is_active: XrBool32,
+}
+
+type PFN_xrVoidFunction = unsafe extern "system" fn();
+type PFN_xrGetInstanceProcAddr = unsafe extern "system" fn(
+    XrInstance,
+    *const c_char,
+    *mut Option<PFN_xrVoidFunction>,
+) -> XrResult;
+
+// Declares a table of functions, that are loaded using `xrGetInstanceProcAddr`.
+macro_rules! define_functions {
+    ($($name:ident: fn($($arg:ty),*);)*) => {
+        #[allow(non_snake_case)]
+        pub struct InstanceFunctions {
+            $(pub $name: unsafe extern "system" fn($($arg),*) -> XrResult,)*
+        }
+
+        impl InstanceFunctions {
+            unsafe fn load(
+                get_proc_address: PFN_xrGetInstanceProcAddr,
+                instance: XrInstance,
+            ) -> Result<Self, String> {
+                Ok(Self {
+                    $($name: std::mem::transmute::<
+                        PFN_xrVoidFunction,
+                        unsafe extern "system" fn($($arg),*) -> XrResult,
+                    >(load_function(
+                        get_proc_address,
+                        instance,
+                        concat!(stringify!($name), "\0"),
+                    )?),)*
+                })
+            }
+        }
+    };
+}
+
+define_functions! {
+    xrDestroyInstance: fn(XrInstance);
+    xrGetSystem: fn(XrInstance, *const XrSystemGetInfo, *mut XrSystemId);
+    xrGetSystemProperties: fn(XrInstance, XrSystemId, *mut XrSystemProperties);
+    xrGetOpenGLGraphicsRequirementsKHR:
+        fn(XrInstance, XrSystemId, *mut XrGraphicsRequirementsOpenGLKHR);
+    xrEnumerateViewConfigurationViews:
+        fn(XrInstance, XrSystemId, i32, u32, *mut u32, *mut XrViewConfigurationView);
+    xrCreateSession: fn(XrInstance, *const XrSessionCreateInfo, *mut XrSession);
+    xrBeginSession: fn(XrSession, *const XrSessionBeginInfo);
+    xrEndSession: fn(XrSession);
+    xrPollEvent: fn(XrInstance, *mut XrEventDataBuffer);
+    xrStringToPath: fn(XrInstance, *const c_char, *mut XrPath);
+    xrCreateReferenceSpace: fn(XrSession, *const XrReferenceSpaceCreateInfo, *mut XrSpace);
+    xrCreateActionSpace: fn(XrSession, *const XrActionSpaceCreateInfo, *mut XrSpace);
+    xrLocateSpace: fn(XrSpace, XrSpace, XrTime, *mut XrSpaceLocation);
+    xrEnumerateSwapchainFormats: fn(XrSession, u32, *mut u32, *mut i64);
+    xrCreateSwapchain: fn(XrSession, *const XrSwapchainCreateInfo, *mut XrSwapchain);
+    xrEnumerateSwapchainImages: fn(XrSwapchain, u32, *mut u32, *mut XrSwapchainImageOpenGLKHR);
+    xrAcquireSwapchainImage: fn(XrSwapchain, *const XrSwapchainImageAcquireInfo, *mut u32);
+    xrWaitSwapchainImage: fn(XrSwapchain, *const XrSwapchainImageWaitInfo);
+    xrReleaseSwapchainImage: fn(XrSwapchain, *const XrSwapchainImageReleaseInfo);
+    xrWaitFrame: fn(XrSession, *const XrFrameWaitInfo, *mut XrFrameState);
+    xrBeginFrame: fn(XrSession, *const XrFrameBeginInfo);
+    xrEndFrame: fn(XrSession, *const XrFrameEndInfo);
+    xrLocateViews:
+        fn(XrSession, *const XrViewLocateInfo, *mut XrViewState, u32, *mut u32, *mut XrView);
+    xrCreateActionSet: fn(XrInstance, *const XrActionSetCreateInfo, *mut XrActionSet);
+    xrCreateAction: fn(XrActionSet, *const XrActionCreateInfo, *mut XrAction);
+    xrSuggestInteractionProfileBindings:
+        fn(XrInstance, *const XrInteractionProfileSuggestedBinding);
+    xrAttachSessionActionSets: fn(XrSession, *const XrSessionActionSetsAttachInfo);
+    xrSyncActions: fn(XrSession, *const XrActionsSyncInfo);
+    xrGetActionStateBoolean:
+        fn(XrSession, *const XrActionStateGetInfo, *mut XrActionStateBoolean);
+    xrGetActionStateFloat: fn(XrSession, *const XrActionStateGetInfo, *mut XrActionStateFloat);
+    xrGetActionStateVector2f:
+        fn(XrSession, *const XrActionStateGetInfo, *mut XrActionStateVector2f);
+    xrGetActionStatePose: fn(XrSession, *const XrActionStateGetInfo, *mut XrActionStatePose);
+}
+
+unsafe fn load_function(
+    get_proc_address: PFN_xrGetInstanceProcAddr,
+    instance: XrInstance,
+    name: &str,
+) -> Result<PFN_xrVoidFunction, String> {
+    let mut function = None;
+    let result = get_proc_address(instance, name.as_ptr() as *const c_char, &mut function);
+    match function {
+        Some(function) if result == XR_SUCCESS => Ok(function),
+        _ => Err(format!(
+            "Unable to load {} function, error {}",
+            name.trim_end_matches('\0'),
+            result
+        )),
+    }
+}
+
+/// OpenXR loader library and the functions of an instance.
+pub struct Loader {
+    // Keeps the library loaded while the functions are in use.
+    _library: Library,
+    get_proc_address: PFN_xrGetInstanceProcAddr,
+}
+
+impl Loader {
+    pub fn new() -> Result<Self, String> {
+        #[cfg(windows)]
+        const NAMES: &[&str] = &["openxr_loader.dll"];
+        #[cfg(target_os = "macos")]
+        const NAMES: &[&str] = &["libopenxr_loader.dylib"];
+        #[cfg(not(any(windows, target_os = "macos")))]
+        const NAMES: &[&str] = &["libopenxr_loader.so.1", "libopenxr_loader.so"];
+
+        let mut last_error = String::new();
+        for name in NAMES {
+            match unsafe { Library::new(name) } {
+                Ok(library) => {
+                    let get_proc_address = unsafe {
+                        *library
+                            .get::<PFN_xrGetInstanceProcAddr>(b"xrGetInstanceProcAddr\0")
+                            .map_err(|e| e.to_string())?
+                    };
+                    return Ok(Self {
+                        _library: library,
+                        get_proc_address,
+                    });
+                }
+                Err(e) => last_error = format!("Unable to load {name}: {e}"),
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Creates a new instance with the given application name and extensions.
+    pub fn create_instance(
+        &self,
+        application_name: &str,
+        extensions: &[&CStr],
+    ) -> Result<(XrInstance, InstanceFunctions), String> {
+        unsafe {
+            type PFN_xrCreateInstance =
+                unsafe extern "system" fn(*const XrInstanceCreateInfo, *mut XrInstance) -> XrResult;
+            let create_instance: PFN_xrCreateInstance = std::mem::transmute(load_function(
+                self.get_proc_address,
+                0,
+                "xrCreateInstance\0",
+            )?);
+
+            let extensions = extensions.iter().map(|e| e.as_ptr()).collect::<Vec<_>>();
+            let info = XrInstanceCreateInfo {
+                ty: XR_TYPE_INSTANCE_CREATE_INFO,
+                next: ptr::null(),
+                create_flags: 0,
+                application_info: XrApplicationInfo {
+                    application_name: fixed_string(application_name),
+                    application_version: 0,
+                    engine_name: fixed_string("Fyrox"),
+                    engine_version: 0,
+                    api_version: XR_CURRENT_API_VERSION,
+                },
+                enabled_api_layer_count: 0,
+                enabled_api_layer_names: ptr::null(),
+                enabled_extension_count: extensions.len() as u32,
+                enabled_extension_names: extensions.as_ptr(),
+            };
+            let mut instance = 0;
+            let result = create_instance(&info, &mut instance);
+            if result != XR_SUCCESS {
+                return Err(format!("xrCreateInstance has failed with error {result}"));
+            }
+
+            match InstanceFunctions::load(self.get_proc_address, instance) {
+                Ok(functions) => Ok((instance, functions)),
+                Err(e) => {
+                    if let Ok(destroy) =
+                        load_function(self.get_proc_address, instance, "xrDestroyInstance\0")
+                    {
+                        let destroy: unsafe extern "system" fn(XrInstance) -> XrResult =
+                            std::mem::transmute(destroy);
+                        destroy(instance);
+                    }
+                    Err(e)
+                }
+            }
+        }
+    }
+}
+
+/// Copies a string to a fixed-size, null-terminated array. Long strings are truncated.
+pub fn fixed_string<const N: usize>(string: &str) -> [c_char; N] {
+    let mut array = [0; N];
+    for (dest, src) in array.iter_mut().zip(string.bytes().take(N - 1)) {
+        *dest = src as c_char;
+    }
+    array
+}
+
+/// Reads a null-terminated string from a fixed-size array.
+pub fn read_fixed_string(array: &[c_char]) -> String {
+    let bytes = array
+        .iter()
+        .take_while(|c| **c != 0)
+        .map(|c| *c as u8)
+        .collect::<Vec<_>>();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -0,0 +1,152 @@
+//! A set of scene nodes, that represents tracked objects of a VR headset. See [`XrRig`] docs for
+//! more info.
+
+use crate::{
+    core::{algebra::Vector2, math::Rect, pool::Handle},
+    resource::texture::{TextureResource, TextureResourceExtension},
+    scene::{
+        base::BaseBuilder,
+        camera::{CameraBuilder, Projection},
+        graph::Graph,
+        node::Node,
+        pivot::PivotBuilder,
+        Scene,
+    },
+    xr::XrFrame,
+};
+
+/// A set of scene nodes, that represents tracked objects of a VR headset:
+///
+/// ```text
+/// XrOrigin (Pivot)
+/// ├── XrHead (Pivot)
+/// ├── XrLeftEye (Camera)
+/// ├── XrRightEye (Camera)
+/// ├── XrLeftHand (Pivot)
+/// └── XrRightHand (Pivot)
+/// ```
+///
+/// Local transforms of the children are set to the poses of the tracked objects on every frame, so
+/// the origin defines where the tracking space is placed in the scene. Move and rotate the origin
+/// to move the player (for example, for teleportation or smooth locomotion), but never change the
+/// transforms of the children. Attach models of controllers, hands or tools to the hand nodes. Hand
+/// nodes are hidden when their controllers are not tracked.
+///
+/// Both eyes are rendered into a single render target of the scene (side-by-side), each camera
+/// occupies a half of it. The engine renders each camera separately (double-pass stereo
+/// rendering).
+#[derive(Clone, Debug)]
+pub struct XrRig {
+    /// A node, that defines the origin of the tracking space.
+    pub origin: Handle<Node>,
+    /// A node, that follows the headset.
+    pub head: Handle<Node>,
+    /// Cameras of the left and the right eye.
+    pub eyes: [Handle<Node>; 2],
+    /// Nodes, that follow the left and the right controller.
+    pub hands: [Handle<Node>; 2],
+    render_target: TextureResource,
+    eye_size: Vector2<u32>,
+}
+
+impl XrRig {
+    /// Creates the nodes in the scene and sets a new render target for the scene. Previous render
+    /// target (if any) is replaced, so the scene is not rendered in the window anymore.
+    pub fn new(scene: &mut Scene, eye_size: Vector2<u32>) -> Self {
+        let eye_size = eye_size.sup(&Vector2::new(1, 1));
+        let graph = &mut scene.graph;
+
+        let head = PivotBuilder::new(BaseBuilder::new().with_name("XrHead")).build(graph);
+        let eyes = [
+            ("XrLeftEye", Rect::new(0.0, 0.0, 0.5, 1.0)),
+            ("XrRightEye", Rect::new(0.5, 0.0, 0.5, 1.0)),
+        ]
+        .map(|(name, viewport)| {
+            CameraBuilder::new(BaseBuilder::new().with_name(name))
+                .with_projection(Projection::Asymmetric(Default::default()))
+                .with_viewport(viewport)
+                .build(graph)
+        });
+        let hands = ["XrLeftHand", "XrRightHand"].map(|name| {
+            PivotBuilder::new(BaseBuilder::new().with_name(name).with_visibility(false))
+                .build(graph)
+        });
+
+        let origin = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("XrOrigin")
+                .with_children(&[head, eyes[0], eyes[1], hands[0], hands[1]]),
+        )
+        .build(graph);
+
+        let render_target = TextureResource::new_render_target(eye_size.x * 2, eye_size.y);
+        scene.render_target = Some(render_target.clone());
+
+        Self {
+            origin,
+            head,
+            eyes,
+            hands,
+            render_target,
+            eye_size,
+        }
+    }
+
+    /// Returns the render target with images of both eyes. It could be shown in the window using
+    /// an image widget, so other people could see what the player sees.
+    pub fn render_target(&self) -> TextureResource {
+        self.render_target.clone()
+    }
+
+    /// Returns size of the image of each eye in pixels.
+    pub fn eye_size(&self) -> Vector2<u32> {
+        self.eye_size
+    }
+
+    /// Sets poses and projections of the nodes from the given frame, and updates global transforms
+    /// and matrices of the cameras, so the frame could be rendered right away. Clipping planes of
+    /// the cameras are preserved.
+    pub fn apply_frame(&self, graph: &mut Graph, frame: &XrFrame) {
+        if let Some(head) = frame.head.as_ref() {
+            graph[self.head]
+                .local_transform_mut()
+                .set_position(head.position)
+                .set_rotation(head.node_rotation());
+        }
+
+        for (eye, view) in self.eyes.iter().zip(frame.views.iter()) {
+            let camera = graph[*eye].as_camera_mut();
+            let projection = view
+                .fov
+                .projection(camera.projection().z_near(), camera.projection().z_far());
+            camera.set_projection(Projection::Asymmetric(projection));
+            camera
+                .local_transform_mut()
+                .set_position(view.pose.position)
+                .set_rotation(view.pose.node_rotation());
+        }
+
+        for (hand, pose) in self.hands.iter().zip(frame.hands.iter()) {
+            let node = &mut graph[*hand];
+            node.set_visibility(pose.is_some());
+            if let Some(pose) = pose {
+                node.local_transform_mut()
+                    .set_position(pose.position)
+                    .set_rotation(pose.node_rotation());
+            }
+        }
+
+        graph.update_hierarchical_data_for_descendants(self.origin);
+
+        let frame_size = Vector2::new(self.eye_size.x as f32 * 2.0, self.eye_size.y as f32);
+        for eye in self.eyes.iter() {
+            graph[*eye].as_camera_mut().calculate_matrices(frame_size);
+        }
+    }
+
+    /// Removes the nodes from the scene and resets its render target.
+    pub fn destroy(self, scene: &mut Scene) {
+        scene.graph.remove_node(self.origin);
+        scene.render_target = None;
+    }
+}
@@ -0,0 +1,163 @@
+//! A session of a VR runtime, that connects it with the engine. See [`XrSession`] docs for more
+//! info.
+
+use crate::{
+    core::pool::Handle,
+    input::{Input, XrAxis, XrButton, XrHand},
+    renderer::Renderer,
+    resource::texture::TextureResource,
+    scene::{Scene, SceneContainer},
+    xr::{hand_index, XrError, XrFrame, XrRenderTarget, XrRig, XrRuntime, XrSessionState},
+};
+
+/// A session of a VR runtime. It drives the frame loop of the runtime, moves nodes of an
+/// [`XrRig`] according to tracked poses, submits rendered images to the headset and feeds the
+/// state of motion controllers to [`Input`], so they could be bound to actions using
+/// [`crate::input::InputSource::XrButton`] and [`crate::input::InputSource::XrAxis`].
+///
+/// Once a session is passed to [`crate::engine::Engine::set_xr_session`], the engine calls its
+/// methods automatically:
+///
+/// 1) [`Self::poll`] - before the update of the scenes, so controllers are available for scripts
+///    and plugins.
+/// 2) [`Self::begin_frame`] - right before rendering, so the poses are predicted as precise as
+///    possible.
+/// 3) [`Self::end_frame`] - right after rendering.
+///
+/// The session renders a single scene, which is specified on creation. The scene will be rendered
+/// into its own render target, so the window could show something else (for example, a spectator
+/// view of the rig's render target).
+pub struct XrSession {
+    runtime: Box<dyn XrRuntime>,
+    scene: Handle<Scene>,
+    rig: XrRig,
+    state: XrSessionState,
+    frame: Option<XrFrame>,
+}
+
+impl XrSession {
+    /// Creates a new session for the given scene. It creates a new [`XrRig`] in the scene using the
+    /// eye size recommended by the runtime.
+    pub fn new(
+        runtime: Box<dyn XrRuntime>,
+        scene_handle: Handle<Scene>,
+        scene: &mut Scene,
+    ) -> Self {
+        let rig = XrRig::new(scene, runtime.recommended_eye_size());
+        Self {
+            runtime,
+            scene: scene_handle,
+            rig,
+            state: Default::default(),
+            frame: None,
+        }
+    }
+
+    /// Returns current state of the session.
+    pub fn state(&self) -> XrSessionState {
+        self.state
+    }
+
+    /// Returns the name of the runtime.
+    pub fn runtime_name(&self) -> &str {
+        self.runtime.name()
+    }
+
+    /// Returns a handle of the scene, that is rendered on the headset.
+    pub fn scene(&self) -> Handle<Scene> {
+        self.scene
+    }
+
+    /// Returns the rig, that is moved according to tracked poses.
+    pub fn rig(&self) -> &XrRig {
+        &self.rig
+    }
+
+    /// Returns the render target with images of both eyes.
+    pub fn render_target(&self) -> TextureResource {
+        self.rig.render_target()
+    }
+
+    /// Returns current frame, `None` if the session is not running.
+    pub fn frame(&self) -> Option<&XrFrame> {
+        self.frame.as_ref()
+    }
+
+    /// Handles state changes of the session and updates the state of the controllers in the input.
+    /// Controllers are reported as released when the application does not have input focus.
+    pub fn poll(&mut self, input: &mut Input) -> Result<(), XrError> {
+        while let Some(state) = self.runtime.poll_state()? {
+            self.state = state;
+        }
+
+        if self.state != XrSessionState::Focused {
+            input.reset_xr_controllers();
+            return Ok(());
+        }
+
+        let controllers = self.runtime.sync_controllers()?;
+        for hand in [XrHand::Left, XrHand::Right] {
+            let controller = &controllers[hand_index(hand)];
+            if !controller.active {
+                input.reset_xr_controller(hand);
+                continue;
+            }
+
+            input.set_xr_axis(hand, XrAxis::Trigger, controller.trigger);
+            input.set_xr_axis(hand, XrAxis::Grip, controller.grip);
+            input.set_xr_axis(hand, XrAxis::ThumbstickX, controller.thumbstick.x);
+            input.set_xr_axis(hand, XrAxis::ThumbstickY, controller.thumbstick.y);
+            input.set_xr_button(hand, XrButton::Primary, controller.primary);
+            input.set_xr_button(hand, XrButton::Secondary, controller.secondary);
+            input.set_xr_button(hand, XrButton::Thumbstick, controller.thumbstick_click);
+            input.set_xr_button(hand, XrButton::Menu, controller.menu);
+        }
+
+        Ok(())
+    }
+
+    /// Begins a new frame (if the session is running) and moves the rig according to the predicted
+    /// poses. It may block until the headset is ready for a new frame.
+    pub fn begin_frame(&mut self, scenes: &mut SceneContainer) -> Result<(), XrError> {
+        self.frame = None;
+
+        if !self.state.is_running() {
+            return Ok(());
+        }
+
+        let frame = self.runtime.begin_frame()?;
+        if frame.should_render {
+            if let Some(scene) = scenes.try_get_mut(self.scene) {
+                self.rig.apply_frame(&mut scene.graph, &frame);
+            }
+        }
+        self.frame = Some(frame);
+
+        Ok(())
+    }
+
+    /// Ends current frame (if any) and submits the rendered image to the runtime.
+    pub fn end_frame(&mut self, renderer: &mut Renderer) -> Result<(), XrError> {
+        let frame = match self.frame.as_ref() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+
+        let texture = if frame.should_render {
+            renderer
+                .scene_data_map
+                .get(&self.scene)
+                .map(|data| data.ldr_scene_frame_texture().borrow().id())
+        } else {
+            None
+        };
+
+        let target = texture.map(|texture| XrRenderTarget {
+            state: renderer.pipeline_state(),
+            texture,
+            eye_size: self.rig.eye_size(),
+        });
+
+        self.runtime.end_frame(frame, target)
+    }
+}